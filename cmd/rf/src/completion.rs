@@ -0,0 +1,176 @@
+//! # completion
+//!
+//! completion 模块 - Shell 补全与 man 手册生成
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Shell 补全与 man 手册
+//!
+//! 补全脚本由 clap 命令定义生成，补全时 shell 回调 `rf` 本身（`COMPLETE=<shell> rf -- ...`），
+//! 因此可以动态补全：
+//! - `rf migrate rollback <TAB>`：`--dir` 目录下的迁移版本（附迁移名称）
+//! - `rf gen model --tables <TAB>` / `--exclude`：`--db` 可连接时补全表名（逗号分隔列表）
+
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::engine::CompletionCandidate;
+use clap_complete::env::{Bash, EnvCompleter, Fish, Powershell, Zsh};
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::Duration;
+
+/// 补全时调用的环境变量
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// 连接数据库补全表名的超时时间
+const TABLE_COMPLETION_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 支持的 Shell
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl Shell {
+    fn completer(self) -> &'static dyn EnvCompleter {
+        match self {
+            Shell::Bash => &Bash,
+            Shell::Zsh => &Zsh,
+            Shell::Fish => &Fish,
+            Shell::Powershell => &Powershell,
+        }
+    }
+}
+
+/// 处理补全请求
+///
+/// 由 shell 通过 `COMPLETE=<shell> rf -- <words>` 调用时输出候选项并退出进程，
+/// 其他情况直接返回。必须在向 stdout 输出任何内容之前调用。
+pub fn complete() {
+    clap_complete::CompleteEnv::with_factory(crate::Cli::command)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// 输出补全注册脚本
+///
+/// # 示例
+///
+/// ```bash
+/// echo 'source <(rf completion bash)' >> ~/.bashrc
+/// echo 'source <(rf completion zsh)' >> ~/.zshrc
+/// echo 'rf completion fish | source' >> ~/.config/fish/config.fish
+/// ```
+pub fn print_registration(shell: Shell) -> std::io::Result<()> {
+    // 使用当前可执行文件的绝对路径，未安装到 PATH 时补全同样可用
+    let completer = std::env::current_exe()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "rf".to_string());
+    let mut stdout = std::io::stdout();
+    shell.completer().write_registration(COMPLETE_VAR, "rf", "rf", &completer, &mut stdout)
+}
+
+/// 生成 man 手册
+///
+/// 指定 `out_dir` 时为每个子命令写出一个页面（`rf.1`、`rf-migrate-up.1` ...），
+/// 否则将 `rf.1` 输出到 stdout。
+pub fn generate_man(out_dir: Option<&Path>) -> std::io::Result<()> {
+    let cmd = crate::Cli::command();
+    match out_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)?;
+            clap_mangen::generate_to(cmd, dir)?;
+            println!("Generated man pages in {}", dir.display());
+            Ok(())
+        }
+        None => clap_mangen::Man::new(cmd).render(&mut std::io::stdout()),
+    }
+}
+
+/// 补全迁移版本
+///
+/// 读取命令行中的 `--dir`（默认 `migrations`）下的迁移文件。
+pub fn migration_versions(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    let dir = completing_arg("--dir", Some('d')).unwrap_or_else(|| "migrations".to_string());
+    let Ok(migrations) = crate::migration_rs::MigrationManager::new(dir).list_migrations() else {
+        return Vec::new();
+    };
+    migrations
+        .into_iter()
+        .rev()
+        .filter(|m| m.version.starts_with(current.as_ref()))
+        .map(|m| CompletionCandidate::new(m.version).help(Some(m.name.into())))
+        .collect()
+}
+
+/// 补全表名
+///
+/// 仅在命令行中给出 `--db` 且数据库可连接时有候选项。值为逗号分隔的列表，
+/// 只补全最后一项，已列出的表不再出现。
+pub fn table_names(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    let Some(db_url) = completing_arg("--db", Some('d')) else {
+        return Vec::new();
+    };
+    let schema = completing_arg("--schema", Some('s'));
+    let Some(tables) = fetch_tables(&db_url, schema.as_deref()) else {
+        return Vec::new();
+    };
+
+    let (listed, partial) = match current.rfind(',') {
+        Some(idx) => (&current[..=idx], &current[idx + 1..]),
+        None => ("", current.as_ref()),
+    };
+    let listed_tables: Vec<&str> = listed.split(',').map(str::trim).collect();
+    tables
+        .into_iter()
+        .filter(|table| table.starts_with(partial) && !listed_tables.contains(&table.as_str()))
+        .map(|table| CompletionCandidate::new(format!("{}{}", listed, table)))
+        .collect()
+}
+
+/// 查询表名，连接失败或超时返回 None
+fn fetch_tables(db_url: &str, schema: Option<&str>) -> Option<Vec<String>> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().ok()?;
+    runtime.block_on(async {
+        let query = async {
+            let database = rf_database::db::Database::new_postgres(db_url).await.ok()?;
+            let inspector = crate::gen::database::SchemaInspector::new(database);
+            inspector.get_tables(schema).await.ok()
+        };
+        tokio::time::timeout(TABLE_COMPLETION_TIMEOUT, query).await.ok().flatten()
+    })
+}
+
+/// 从正在补全的命令行中读取参数值
+///
+/// 补全时进程参数为 `rf -- rf <words>`，支持 `--name value`、`--name=value` 和 `-x value`。
+fn completing_arg(long: &str, short: Option<char>) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let words = match args.iter().position(|arg| arg == "--") {
+        Some(idx) => &args[idx + 1..],
+        None => &args[..],
+    };
+    find_arg(words, long, short)
+}
+
+fn find_arg(words: &[String], long: &str, short: Option<char>) -> Option<String> {
+    let short = short.map(|c| format!("-{}", c));
+    let prefix = format!("{}=", long);
+    let mut value = None;
+    let mut iter = words.iter();
+    while let Some(word) = iter.next() {
+        if let Some(inline) = word.strip_prefix(&prefix) {
+            value = Some(inline.to_string());
+        } else if word == long || short.as_deref() == Some(word.as_str()) {
+            if let Some(next) = iter.next() {
+                value = Some(next.clone());
+            }
+        }
+    }
+    value.filter(|v| !v.is_empty())
+}
@@ -0,0 +1,867 @@
+//! # console
+//!
+//! console 模块 - 交互式 ORM / Redis 控制台
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! 交互式控制台（rf console）
+//!
+//! 从项目配置（`database.<name>.url`、`redis.<name>.url`）连接数据库和 Redis，
+//! 逐行执行输入并格式化输出结果：
+//! - 查询构造器表达式：`users.where_eq("status", 1).order_by("id", "desc").limit(5)`
+//! - 原始 SQL：`SELECT ...` / `UPDATE ...`，或以 `sql ` 开头的任意语句
+//! - Redis 命令：`redis HGETALL user:1`
+//!
+//! 查询构造器表达式默认不附加软删除条件（控制台常用于没有 `deleted_at` 列的表），
+//! 需要时调用 `.soft_delete_field("deleted_at")`。
+
+use rf_database::db::{Database, JsonRow, Model, ParamValue};
+use rf_database::redis::RedisClient;
+use serde_json::Value;
+use std::io::Write;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+type ConsoleResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// 未指定 limit 时最多显示的行数
+const DEFAULT_ROW_LIMIT: usize = 100;
+
+/// 表格单元格最大显示宽度
+const MAX_CELL_WIDTH: usize = 48;
+
+const HELP: &str = r#"Commands:
+  <table>.<method>(...)...      query builder expression, e.g.
+                                users.where_eq("status", 1).order_by("id", "desc").limit(5)
+                                ends with .all() (default), .one(), .count(), .sql(),
+                                .update("name = 'x'") or .delete()
+  SELECT ... / INSERT ... / ... raw SQL (prefix with `sql ` for other statements)
+  redis <COMMAND> [args...]     raw Redis command, e.g. redis HGETALL user:1
+  \x                            toggle expanded (one column per line) output
+  \json                         toggle JSON output
+  \dt                           list tables
+  \h, help                      show this help
+  \q, exit                      quit
+A line ending with `\` continues on the next line."#;
+
+/// 控制台启动参数
+pub struct ConsoleOptions {
+    /// 配置文件路径
+    pub config: String,
+    /// 数据库与 Redis 实例名
+    pub instance: String,
+    /// 覆盖配置中的数据库 URL
+    pub db: Option<String>,
+    /// 覆盖配置中的 Redis URL
+    pub redis: Option<String>,
+}
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputMode {
+    Table,
+    Expanded,
+    Json,
+}
+
+/// 控制台会话
+struct Console {
+    database: Option<Database>,
+    redis: Option<RedisClient>,
+    mode: OutputMode,
+}
+
+/// 启动控制台
+///
+/// 未配置或连接失败的后端会给出提示，其余功能照常可用。
+pub async fn run(options: ConsoleOptions) -> ConsoleResult<()> {
+    let config = load_config(&options.config)?;
+    let db_url = options.db.or_else(|| config.get(&format!("database.{}.url", options.instance)).ok().flatten());
+    let redis_url = options.redis.or_else(|| config.get(&format!("redis.{}.url", options.instance)).ok().flatten());
+
+    let database = match db_url {
+        Some(url) => match connect_database(&url).await {
+            Ok(database) => {
+                println!("Connected to database {}", redact(&url));
+                Some(database)
+            }
+            Err(e) => {
+                eprintln!("Database unavailable: {}", e);
+                None
+            }
+        },
+        None => {
+            println!("No database configured (database.{}.url)", options.instance);
+            None
+        }
+    };
+    let redis = match redis_url {
+        Some(url) => match RedisClient::new(&url).await {
+            Ok(client) => {
+                println!("Connected to redis {}", redact(&url));
+                Some(client)
+            }
+            Err(e) => {
+                eprintln!("Redis unavailable: {}", e);
+                None
+            }
+        },
+        None => {
+            println!("No redis configured (redis.{}.url)", options.instance);
+            None
+        }
+    };
+    println!("Type \\h for help, \\q to quit.");
+
+    let mut console = Console {
+        database,
+        redis,
+        mode: OutputMode::Table,
+    };
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut buffer = String::new();
+    loop {
+        print!("{}", if buffer.is_empty() { "rf> " } else { "..> " });
+        std::io::stdout().flush()?;
+        let Some(line) = lines.next_line().await? else {
+            println!();
+            break;
+        };
+        if let Some(partial) = line.strip_suffix('\\') {
+            buffer.push_str(partial);
+            buffer.push('\n');
+            continue;
+        }
+        buffer.push_str(&line);
+        let input = std::mem::take(&mut buffer);
+        let input = input.trim().trim_end_matches(';').trim();
+        if input.is_empty() {
+            continue;
+        }
+        if matches!(input, "\\q" | "exit" | "quit") {
+            break;
+        }
+        let started = std::time::Instant::now();
+        match console.eval(input).await {
+            Ok(true) => println!("({:.1} ms)", started.elapsed().as_secs_f64() * 1000.0),
+            Ok(false) => {}
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// 读取配置文件，文件不存在时为空配置
+fn load_config(path: &str) -> ConsoleResult<rf_os::cfg::Config> {
+    let adapter = rf_os::cfg::FileConfigAdapter::new(path)?;
+    Ok(rf_os::cfg::Config::new().adapter(std::sync::Arc::new(adapter)))
+}
+
+/// 按 URL 协议连接数据库
+async fn connect_database(url: &str) -> ConsoleResult<Database> {
+    let database = if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        Database::new_postgres(url).await?
+    } else if url.starts_with("mysql://") {
+        Database::new_mysql(url).await?
+    } else if let Some(path) = url.strip_prefix("sqlite://") {
+        Database::new_sqlite(path).await?
+    } else {
+        return Err(format!("Unsupported database URL: {}", redact(url)).into());
+    };
+    Ok(database)
+}
+
+/// 隐藏 URL 中的密码
+fn redact(url: &str) -> String {
+    match (url.find("://"), url.rfind('@')) {
+        (Some(scheme), Some(at)) if at > scheme => {
+            let credentials = &url[scheme + 3..at];
+            match credentials.find(':') {
+                Some(colon) => format!("{}:***{}", &url[..scheme + 3 + colon], &url[at..]),
+                None => url.to_string(),
+            }
+        }
+        _ => url.to_string(),
+    }
+}
+
+impl Console {
+    /// 执行一条输入，返回是否执行了查询（用于显示耗时）
+    async fn eval(&mut self, input: &str) -> ConsoleResult<bool> {
+        match input {
+            "\\h" | "help" => {
+                println!("{}", HELP);
+                return Ok(false);
+            }
+            "\\x" => {
+                self.mode = if self.mode == OutputMode::Expanded { OutputMode::Table } else { OutputMode::Expanded };
+                println!("Expanded display is {}", if self.mode == OutputMode::Expanded { "on" } else { "off" });
+                return Ok(false);
+            }
+            "\\json" => {
+                self.mode = if self.mode == OutputMode::Json { OutputMode::Table } else { OutputMode::Json };
+                println!("JSON output is {}", if self.mode == OutputMode::Json { "on" } else { "off" });
+                return Ok(false);
+            }
+            "\\dt" => {
+                self.list_tables().await?;
+                return Ok(true);
+            }
+            _ => {}
+        }
+
+        let (head, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        let keyword = head.to_ascii_lowercase();
+        if keyword == "redis" {
+            self.redis_command(rest.trim()).await?;
+        } else if keyword == "sql" {
+            self.sql(rest.trim()).await?;
+        } else if is_sql_keyword(&keyword) {
+            self.sql(input).await?;
+        } else {
+            self.expression(input).await?;
+        }
+        Ok(true)
+    }
+
+    fn database(&self) -> ConsoleResult<&Database> {
+        self.database.as_ref().ok_or_else(|| "No database connection".into())
+    }
+
+    async fn list_tables(&self) -> ConsoleResult<()> {
+        let database = self.database()?;
+        let sql = if database.as_postgres().is_some() {
+            "SELECT table_schema, table_name FROM information_schema.tables \
+             WHERE table_schema NOT IN ('pg_catalog', 'information_schema') ORDER BY 1, 2"
+        } else if database.as_mysql().is_some() {
+            "SELECT table_name FROM information_schema.tables WHERE table_schema = DATABASE() ORDER BY 1"
+        } else {
+            "SELECT name AS table_name FROM sqlite_master WHERE type = 'table' ORDER BY 1"
+        };
+        self.query(sql, None).await
+    }
+
+    /// 执行原始 SQL，返回结果集的语句显示结果，其余显示影响行数
+    async fn sql(&self, sql: &str) -> ConsoleResult<()> {
+        if sql.is_empty() {
+            return Err("Empty SQL statement".into());
+        }
+        let first = sql.split_whitespace().next().unwrap_or_default().to_ascii_lowercase();
+        if returns_rows(&first) || sql.to_ascii_lowercase().contains(" returning ") {
+            self.query(sql, None).await
+        } else {
+            let affected = self.database()?.raw_execute(sql).await?;
+            println!("{} row(s) affected", affected);
+            Ok(())
+        }
+    }
+
+    /// 执行查询并打印，`limit` 为空时最多显示 `DEFAULT_ROW_LIMIT` 行
+    async fn query(&self, sql: &str, limit: Option<usize>) -> ConsoleResult<()> {
+        let stream = self.database()?.raw_stream(sql, 64);
+        self.print_stream(stream, limit).await
+    }
+
+    async fn print_stream(&self, mut stream: rf_database::db::RowStream, limit: Option<usize>) -> ConsoleResult<()> {
+        use futures_util::StreamExt;
+
+        let cap = limit.unwrap_or(DEFAULT_ROW_LIMIT);
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        while let Some(row) = stream.next().await {
+            if rows.len() == cap {
+                truncated = true;
+                break;
+            }
+            rows.push(row?);
+        }
+        print_rows(&rows, self.mode);
+        if truncated {
+            println!("(showing first {} rows, add .limit(n) or LIMIT to see more)", cap);
+        } else {
+            println!("({} row{})", rows.len(), if rows.len() == 1 { "" } else { "s" });
+        }
+        Ok(())
+    }
+
+    /// 执行查询构造器表达式
+    async fn expression(&self, input: &str) -> ConsoleResult<()> {
+        let expr = parse_expression(input)?;
+        let database = self.database()?;
+        let mut model = database.model(&expr.table).unscoped();
+        let mut limit = None;
+        let mut terminal = Terminal::All;
+        let last = expr.calls.len().saturating_sub(1);
+        for (i, call) in expr.calls.into_iter().enumerate() {
+            if let Some(t) = Terminal::parse(&call)? {
+                if i != last {
+                    return Err(format!("{}() must be the last call", call.name).into());
+                }
+                terminal = t;
+                continue;
+            }
+            if call.name == "limit" {
+                limit = Some(call.usize_arg(0)?);
+            }
+            model = apply(model, &call)?;
+        }
+
+        match terminal {
+            Terminal::All => self.print_stream(model.stream(64), limit).await,
+            Terminal::One => self.print_stream(model.limit(1).stream(1), Some(1)).await,
+            Terminal::Count => {
+                println!("{}", model.count().await?);
+                Ok(())
+            }
+            Terminal::Sql => {
+                println!("{}", model.to_sql());
+                Ok(())
+            }
+            Terminal::Update(set) => {
+                println!("{} row(s) affected", model.update(&set).await?);
+                Ok(())
+            }
+            Terminal::Delete => {
+                println!("{} row(s) affected", model.delete().await?);
+                Ok(())
+            }
+        }
+    }
+
+    /// 执行 Redis 命令
+    async fn redis_command(&self, input: &str) -> ConsoleResult<()> {
+        let redis = self.redis.as_ref().ok_or("No redis connection")?;
+        let words = split_words(input)?;
+        let Some((name, args)) = words.split_first() else {
+            return Err("Usage: redis <COMMAND> [args...]".into());
+        };
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let value = redis.command(name, &args).await?;
+        if self.mode == OutputMode::Json {
+            println!("{}", serde_json::to_string_pretty(&redis_to_json(&value))?);
+        } else {
+            print!("{}", format_redis(&value, 0));
+        }
+        Ok(())
+    }
+}
+
+fn is_sql_keyword(word: &str) -> bool {
+    matches!(
+        word,
+        "select" | "with" | "insert" | "update" | "delete" | "create" | "alter" | "drop" | "truncate"
+            | "explain" | "show" | "describe" | "desc" | "pragma" | "values" | "begin" | "commit" | "rollback"
+            | "grant" | "revoke" | "analyze" | "vacuum"
+    )
+}
+
+fn returns_rows(keyword: &str) -> bool {
+    matches!(keyword, "select" | "with" | "explain" | "show" | "describe" | "desc" | "pragma" | "values" | "table")
+}
+
+/// 查询构造器的结束调用
+enum Terminal {
+    All,
+    One,
+    Count,
+    Sql,
+    Update(String),
+    Delete,
+}
+
+impl Terminal {
+    fn parse(call: &Call) -> ConsoleResult<Option<Self>> {
+        Ok(Some(match call.name.as_str() {
+            "all" | "get" => Terminal::All,
+            "one" | "first" => Terminal::One,
+            "count" => Terminal::Count,
+            "sql" | "to_sql" => Terminal::Sql,
+            "update" => Terminal::Update(call.str_arg(0)?),
+            "delete" => Terminal::Delete,
+            _ => return Ok(None),
+        }))
+    }
+}
+
+/// 将一个构造器调用应用到模型
+fn apply(model: Model, call: &Call) -> ConsoleResult<Model> {
+    let model = match call.name.as_str() {
+        "fields" | "select" => {
+            let fields = call.str_list()?;
+            model.fields(&fields.iter().map(String::as_str).collect::<Vec<_>>())
+        }
+        "fields_exclude" => {
+            let fields = call.str_list()?;
+            model.fields_exclude(&fields.iter().map(String::as_str).collect::<Vec<_>>())
+        }
+        "where" | "where_raw" => model.r#where(&call.str_arg(0)?),
+        "and_where" => model.and_where(&call.str_arg(0)?),
+        "or_where" => model.or_where(&call.str_arg(0)?),
+        // 带值的条件以转义后的字面量内联：Model 的命名参数在执行时不会绑定，
+        // 内联后 `.sql()` 也能显示实际执行的语句
+        "where_eq" => model.r#where(&format!("{} = {}", call.str_arg(0)?, call.param_arg(1)?.to_sql_string())),
+        "where_ne" => model.r#where(&format!("{} != {}", call.str_arg(0)?, call.param_arg(1)?.to_sql_string())),
+        "where_like" => model.r#where(&format!(
+            "{} LIKE {}",
+            call.str_arg(0)?,
+            ParamValue::String(call.str_arg(1)?).to_sql_string()
+        )),
+        "where_in" => model.r#where(&format!("{} IN ({})", call.str_arg(0)?, sql_list(&call.param_list(1)?)?)),
+        "where_not_in" => model.r#where(&format!("{} NOT IN ({})", call.str_arg(0)?, sql_list(&call.param_list(1)?)?)),
+        "where_between" => model.r#where(&format!(
+            "{} BETWEEN {} AND {}",
+            call.str_arg(0)?,
+            call.param_arg(1)?.to_sql_string(),
+            call.param_arg(2)?.to_sql_string()
+        )),
+        "where_null" => model.where_null(&call.str_arg(0)?),
+        "where_not_null" => model.where_not_null(&call.str_arg(0)?),
+        "where_cmp" => {
+            let op = call.str_arg(1)?;
+            if !matches!(op.as_str(), "=" | "!=" | "<>" | "<" | "<=" | ">" | ">=") {
+                return Err(format!("Unsupported operator: {}", op).into());
+            }
+            model.r#where(&format!("{} {} {}", call.str_arg(0)?, op, call.param_arg(2)?.to_sql_string()))
+        }
+        "order_by" => {
+            let order = if call.args.len() > 1 { call.str_arg(1)? } else { "ASC".to_string() };
+            model.order_by(&call.str_arg(0)?, &order)
+        }
+        "group_by" => model.group_by(&call.str_arg(0)?),
+        "having" => model.having(&call.str_arg(0)?),
+        "limit" => model.limit(call.usize_arg(0)?),
+        "offset" => model.offset(call.usize_arg(0)?),
+        "join" => model.join(&call.str_arg(0)?, &call.str_arg(1)?),
+        "inner_join" => model.inner_join(&call.str_arg(0)?, &call.str_arg(1)?),
+        "left_join" => model.left_join(&call.str_arg(0)?, &call.str_arg(1)?),
+        "right_join" => model.right_join(&call.str_arg(0)?, &call.str_arg(1)?),
+        "full_outer_join" => model.full_outer_join(&call.str_arg(0)?, &call.str_arg(1)?),
+        "schema" => model.schema(&call.str_arg(0)?),
+        "soft_delete_field" => model.soft_delete_field(&call.str_arg(0)?),
+        "unscoped" => model.unscoped(),
+        "with_deleted" => model.with_deleted(),
+        "only_deleted" => model.only_deleted(),
+        other => return Err(format!("Unknown method: {}()", other).into()),
+    };
+    Ok(model)
+}
+
+/// `IN` 列表的 SQL 文本
+fn sql_list(values: &[ParamValue]) -> ConsoleResult<String> {
+    if values.is_empty() {
+        return Err("IN list must not be empty".into());
+    }
+    Ok(values.iter().map(ParamValue::to_sql_string).collect::<Vec<_>>().join(", "))
+}
+
+/// 查询构造器表达式：`table.method(args)...`
+#[derive(Debug, PartialEq)]
+struct Expression {
+    table: String,
+    calls: Vec<Call>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Call {
+    name: String,
+    args: Vec<Arg>,
+}
+
+/// 字面量参数
+#[derive(Debug, Clone, PartialEq)]
+enum Arg {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Null,
+    List(Vec<Arg>),
+}
+
+impl Arg {
+    fn to_param(&self) -> ConsoleResult<ParamValue> {
+        Ok(match self {
+            Arg::Str(s) => ParamValue::String(s.clone()),
+            Arg::Int(i) => ParamValue::Int(*i),
+            Arg::Float(f) => ParamValue::Float(*f),
+            Arg::Bool(b) => ParamValue::Bool(*b),
+            Arg::Null => ParamValue::Null,
+            Arg::List(_) => return Err("Expected a value, found a list".into()),
+        })
+    }
+}
+
+impl Call {
+    fn arg(&self, index: usize) -> ConsoleResult<&Arg> {
+        self.args
+            .get(index)
+            .ok_or_else(|| format!("{}() expects at least {} argument(s)", self.name, index + 1).into())
+    }
+
+    fn str_arg(&self, index: usize) -> ConsoleResult<String> {
+        match self.arg(index)? {
+            Arg::Str(s) => Ok(s.clone()),
+            other => Err(format!("{}() argument {} must be a string, found {:?}", self.name, index + 1, other).into()),
+        }
+    }
+
+    fn usize_arg(&self, index: usize) -> ConsoleResult<usize> {
+        match self.arg(index)? {
+            Arg::Int(i) if *i >= 0 => Ok(*i as usize),
+            other => Err(format!("{}() argument {} must be a non-negative integer, found {:?}", self.name, index + 1, other).into()),
+        }
+    }
+
+    fn param_arg(&self, index: usize) -> ConsoleResult<ParamValue> {
+        self.arg(index)?.to_param()
+    }
+
+    fn param_list(&self, index: usize) -> ConsoleResult<Vec<ParamValue>> {
+        match self.arg(index)? {
+            Arg::List(items) => items.iter().map(Arg::to_param).collect(),
+            other => Ok(vec![other.to_param()?]),
+        }
+    }
+
+    /// 字符串列表，接受 `("a", "b")` 或 `(["a", "b"])`
+    fn str_list(&self) -> ConsoleResult<Vec<String>> {
+        let items = match self.args.as_slice() {
+            [Arg::List(items)] => items.as_slice(),
+            args => args,
+        };
+        items
+            .iter()
+            .map(|item| match item {
+                Arg::Str(s) => Ok(s.clone()),
+                other => Err(format!("{}() expects strings, found {:?}", self.name, other).into()),
+            })
+            .collect()
+    }
+}
+
+fn parse_expression(input: &str) -> ConsoleResult<Expression> {
+    let mut parser = Parser { chars: input.chars().collect(), pos: 0 };
+    let mut table = parser.ident()?;
+    let mut calls = Vec::new();
+    loop {
+        parser.skip_ws();
+        if parser.eof() {
+            break;
+        }
+        parser.expect('.')?;
+        let name = parser.ident()?;
+        parser.skip_ws();
+        if parser.peek() == Some('(') {
+            parser.pos += 1;
+            let args = parser.args(')')?;
+            calls.push(Call { name, args });
+        } else if calls.is_empty() {
+            // schema.table：调用之前不带括号的段属于表名
+            table = format!("{}.{}", table, name);
+        } else {
+            return Err(format!("Expected `(` after {}", name).into());
+        }
+    }
+    Ok(Expression { table, calls })
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn eof(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> ConsoleResult<()> {
+        self.skip_ws();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(c) => Err(format!("Expected `{}` at column {}, found `{}`", expected, self.pos + 1, c).into()),
+            None => Err(format!("Expected `{}` at end of input", expected).into()),
+        }
+    }
+
+    fn ident(&mut self) -> ConsoleResult<String> {
+        self.skip_ws();
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(format!("Expected a name at column {}", start + 1).into());
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// 逗号分隔的参数，读到 `close` 为止
+    fn args(&mut self, close: char) -> ConsoleResult<Vec<Arg>> {
+        let mut args = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(args);
+        }
+        loop {
+            args.push(self.value()?);
+            self.skip_ws();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    return Ok(args);
+                }
+                _ => return Err(format!("Expected `,` or `{}` at column {}", close, self.pos + 1).into()),
+            }
+        }
+    }
+
+    fn value(&mut self) -> ConsoleResult<Arg> {
+        self.skip_ws();
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let mut text = String::new();
+                loop {
+                    match self.peek() {
+                        None => return Err("Unterminated string".into()),
+                        Some('\\') => {
+                            self.pos += 1;
+                            let escaped = self.peek().ok_or("Unterminated string")?;
+                            text.push(match escaped {
+                                'n' => '\n',
+                                't' => '\t',
+                                c => c,
+                            });
+                            self.pos += 1;
+                        }
+                        Some(c) if c == quote => {
+                            self.pos += 1;
+                            return Ok(Arg::Str(text));
+                        }
+                        Some(c) => {
+                            text.push(c);
+                            self.pos += 1;
+                        }
+                    }
+                }
+            }
+            Some('[') => {
+                self.pos += 1;
+                Ok(Arg::List(self.args(']')?))
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let start = self.pos;
+                self.pos += 1;
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.' || c == '_' || c == 'e' || c == 'E') {
+                    self.pos += 1;
+                }
+                let text: String = self.chars[start..self.pos].iter().filter(|c| **c != '_').collect();
+                if let Ok(int) = text.parse::<i64>() {
+                    Ok(Arg::Int(int))
+                } else {
+                    text.parse::<f64>().map(Arg::Float).map_err(|_| format!("Invalid number: {}", text).into())
+                }
+            }
+            Some(_) => match self.ident()?.as_str() {
+                "true" => Ok(Arg::Bool(true)),
+                "false" => Ok(Arg::Bool(false)),
+                "null" | "None" => Ok(Arg::Null),
+                other => Err(format!("Unexpected `{}`, strings must be quoted", other).into()),
+            },
+            None => Err("Unexpected end of input".into()),
+        }
+    }
+}
+
+/// 按空白拆分 Redis 命令，支持单双引号
+fn split_words(input: &str) -> ConsoleResult<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => current.push(chars.next().ok_or("Unterminated quote")?),
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("Unterminated quote".into());
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+fn print_rows(rows: &[JsonRow], mode: OutputMode) {
+    match mode {
+        OutputMode::Json => {
+            let rows: Vec<&JsonRow> = rows.iter().collect();
+            println!("{}", serde_json::to_string_pretty(&rows).unwrap_or_default());
+        }
+        OutputMode::Expanded => {
+            let width = rows.iter().flat_map(|row| row.keys()).map(|k| k.chars().count()).max().unwrap_or(0);
+            for (i, row) in rows.iter().enumerate() {
+                println!("-[ RECORD {} ]{}", i + 1, "-".repeat(width.saturating_sub(8)));
+                for (key, value) in row {
+                    println!("{:<width$} | {}", key, cell(value, usize::MAX), width = width);
+                }
+            }
+        }
+        OutputMode::Table => print_table(rows),
+    }
+}
+
+fn print_table(rows: &[JsonRow]) {
+    let mut columns: Vec<&String> = Vec::new();
+    for key in rows.iter().flat_map(|row| row.keys()) {
+        if !columns.contains(&key) {
+            columns.push(key);
+        }
+    }
+    if columns.is_empty() {
+        return;
+    }
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| columns.iter().map(|c| cell(row.get(*c).unwrap_or(&Value::Null), MAX_CELL_WIDTH)).collect())
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| cells.iter().map(|r| r[i].chars().count()).chain([c.chars().count()]).max().unwrap_or(0))
+        .collect();
+    let border = format!("+{}+", widths.iter().map(|w| "-".repeat(w + 2)).collect::<Vec<_>>().join("+"));
+    let line = |values: Vec<String>| {
+        let padded: Vec<String> = values
+            .iter()
+            .zip(&widths)
+            .map(|(v, w)| format!(" {}{} ", v, " ".repeat(w - v.chars().count())))
+            .collect();
+        format!("|{}|", padded.join("|"))
+    };
+    println!("{}", border);
+    println!("{}", line(columns.iter().map(|c| c.to_string()).collect()));
+    println!("{}", border);
+    for row in cells {
+        println!("{}", line(row));
+    }
+    println!("{}", border);
+}
+
+/// 单元格文本，超过 `max` 个字符时截断
+fn cell(value: &Value, max: usize) -> String {
+    let text = match value {
+        Value::Null => "NULL".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let text = text.replace('\n', "\\n");
+    if text.chars().count() > max {
+        format!("{}…", text.chars().take(max - 1).collect::<String>())
+    } else {
+        text
+    }
+}
+
+/// redis-cli 风格的回复格式
+fn format_redis(value: &redis::Value, indent: usize) -> String {
+    use redis::Value as R;
+
+    let items = |items: &[R]| -> String {
+        if items.is_empty() {
+            return "(empty array)\n".to_string();
+        }
+        let width = items.len().to_string().len();
+        let mut out = String::new();
+        for (i, item) in items.iter().enumerate() {
+            let prefix = format!("{:>width$}) ", i + 1, width = width);
+            let nested = format_redis(item, indent + prefix.len());
+            if i > 0 {
+                out.push_str(&" ".repeat(indent));
+            }
+            out.push_str(&prefix);
+            out.push_str(&nested);
+        }
+        out
+    };
+    match value {
+        R::Nil => "(nil)\n".to_string(),
+        R::Int(i) => format!("(integer) {}\n", i),
+        R::Double(f) => format!("(double) {}\n", f),
+        R::Boolean(b) => format!("({})\n", b),
+        R::Okay => "OK\n".to_string(),
+        R::SimpleString(s) => format!("{}\n", s),
+        R::BulkString(bytes) => format!("{:?}\n", String::from_utf8_lossy(bytes)),
+        R::VerbatimString { text, .. } => format!("{}\n", text),
+        R::Array(values) | R::Set(values) => items(values),
+        R::Map(pairs) => {
+            let flat: Vec<R> = pairs.iter().flat_map(|(k, v)| [k.clone(), v.clone()]).collect();
+            items(&flat)
+        }
+        R::ServerError(e) => format!("(error) {:?}\n", e),
+        other => format!("{:?}\n", other),
+    }
+}
+
+fn redis_to_json(value: &redis::Value) -> Value {
+    use redis::Value as R;
+
+    match value {
+        R::Nil => Value::Null,
+        R::Int(i) => Value::from(*i),
+        R::Double(f) => Value::from(*f),
+        R::Boolean(b) => Value::Bool(*b),
+        R::Okay => Value::from("OK"),
+        R::SimpleString(s) => Value::from(s.as_str()),
+        R::BulkString(bytes) => Value::from(String::from_utf8_lossy(bytes).into_owned()),
+        R::VerbatimString { text, .. } => Value::from(text.as_str()),
+        R::Array(values) | R::Set(values) => Value::Array(values.iter().map(redis_to_json).collect()),
+        R::Map(pairs) => Value::Object(
+            pairs
+                .iter()
+                .map(|(k, v)| {
+                    let key = match redis_to_json(k) {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (key, redis_to_json(v))
+                })
+                .collect(),
+        ),
+        other => Value::from(format!("{:?}", other)),
+    }
+}
@@ -0,0 +1,376 @@
+//! # proto
+//!
+//! proto 模块 - Protobuf 代码生成
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Protobuf 代码生成
+//!
+//! 用 tonic-build（prost）编译目录下的所有 `.proto` 文件，输出：
+//! - 每个 package 一个 `<package>.rs`，包含消息、客户端和服务端代码
+//! - `descriptor.bin` 文件描述符集，供服务反射和 HTTP 网关使用
+//! - `mod.rs` 按 package 嵌套声明模块，导出 `FILE_DESCRIPTOR_SET`
+//! - `services/` 每个服务一个实现骨架，以及把它们注册到 `GrpcServer` 的 `register`
+//!
+//! 骨架文件只在不存在时生成（`force` 时覆盖），其余文件每次重新生成。
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use prost::Message;
+use prost_types::{FileDescriptorProto, FileDescriptorSet, MethodDescriptorProto, ServiceDescriptorProto};
+use rf_errors::{Result, RfError};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 文件描述符集文件名
+const DESCRIPTOR_FILE: &str = "descriptor.bin";
+
+/// 生成文件的头部注释
+const HEADER: &str = "// Generated by `rf gen proto`. Do not edit.\n";
+
+/// Protobuf 生成选项
+#[derive(Debug, Clone)]
+pub struct ProtoOptions {
+    /// `.proto` 文件目录（递归查找）
+    pub proto_dir: PathBuf,
+    /// 输出目录
+    pub output: PathBuf,
+    /// 额外的 import 搜索目录，`proto_dir` 总是第一个
+    pub includes: Vec<PathBuf>,
+    /// 生成客户端代码
+    pub client: bool,
+    /// 生成服务端代码
+    pub server: bool,
+    /// 生成服务实现骨架（需要服务端代码）
+    pub skeletons: bool,
+    /// 覆盖已存在的骨架文件
+    pub force: bool,
+    /// protoc 路径，默认读取 `PROTOC` 环境变量或 PATH
+    pub protoc: Option<PathBuf>,
+}
+
+/// 生成代码
+pub fn generate(options: ProtoOptions) -> Result<()> {
+    let files = find_protos(&options.proto_dir)?;
+    if files.is_empty() {
+        return Err(RfError::NotFound(format!("No .proto files in {}", options.proto_dir.display())));
+    }
+    if let Some(protoc) = &options.protoc {
+        std::env::set_var("PROTOC", protoc);
+    }
+
+    let mut includes = vec![options.proto_dir.clone()];
+    includes.extend(options.includes.iter().cloned());
+    fs::create_dir_all(&options.output)?;
+    tonic_build::configure()
+        .build_client(options.client)
+        .build_server(options.server)
+        .out_dir(&options.output)
+        .file_descriptor_set_path(options.output.join(DESCRIPTOR_FILE))
+        .include_file("mod.rs")
+        .emit_rerun_if_changed(false)
+        .compile_protos(&files, &includes)
+        .map_err(|e| RfError::Internal(format!("Failed to compile protos: {}", e)))?;
+
+    let bytes = fs::read(options.output.join(DESCRIPTOR_FILE))?;
+    let set = FileDescriptorSet::decode(bytes.as_slice())
+        .map_err(|e| RfError::Serialization(format!("Invalid file descriptor set: {}", e)))?;
+    // Imported files are part of the set too, only services of the compiled files get skeletons
+    let compiled: HashSet<String> = files
+        .iter()
+        .filter_map(|file| file.strip_prefix(&options.proto_dir).ok())
+        .map(|file| file.to_string_lossy().replace('\\', "/"))
+        .collect();
+    write_wiring(&options, &compiled, &set)?;
+    println!("Generated {} proto file(s) into {}", files.len(), options.output.display());
+    Ok(())
+}
+
+/// 补全 `mod.rs` 并生成服务骨架
+fn write_wiring(options: &ProtoOptions, compiled: &HashSet<String>, set: &FileDescriptorSet) -> Result<()> {
+    let services: Vec<(&FileDescriptorProto, &ServiceDescriptorProto)> = set
+        .file
+        .iter()
+        .filter(|file| compiled.contains(file.name()))
+        .flat_map(|file| file.service.iter().map(move |service| (file, service)))
+        .collect();
+    let skeletons = options.server && options.skeletons && !services.is_empty();
+
+    let mod_path = options.output.join("mod.rs");
+    let mut wiring = format!("{}\n{}", HEADER, fs::read_to_string(&mod_path)?);
+    wiring.push_str(&format!(
+        "\n/// Encoded `FileDescriptorSet` of the compiled protos, for `GrpcServer::with_reflection`\n\
+         /// and `GrpcGateway::with_descriptors`\n\
+         pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!(\"{}\");\n",
+        DESCRIPTOR_FILE
+    ));
+    if skeletons {
+        wiring.push_str("\npub mod services;\n");
+    }
+    fs::write(&mod_path, wiring)?;
+
+    if skeletons {
+        let resolver = TypeResolver::new(set);
+        write_services(options, &services, &resolver)?;
+    }
+    Ok(())
+}
+
+/// 递归查找 `.proto` 文件，按路径排序
+fn find_protos(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Err(RfError::NotFound(format!("Proto directory {} not found", dir.display())));
+    }
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "proto") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// 写出 `services/` 目录：骨架文件和注册函数
+fn write_services(
+    options: &ProtoOptions,
+    services: &[(&FileDescriptorProto, &ServiceDescriptorProto)],
+    resolver: &TypeResolver,
+) -> Result<()> {
+    let dir = options.output.join("services");
+    fs::create_dir_all(&dir)?;
+
+    // Services of different packages may share a name
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, service) in services {
+        *counts.entry(naive_snake_case(&to_upper_camel(service.name()))).or_default() += 1;
+    }
+
+    let mut modules = Vec::new();
+    for (file, service) in services {
+        let mut module = naive_snake_case(&to_upper_camel(service.name()));
+        if counts[&module] > 1 && !file.package().is_empty() {
+            module = format!("{}_{}", file.package().replace('.', "_"), module);
+        }
+        let path = dir.join(format!("{}.rs", module));
+        if options.force || !path.exists() {
+            fs::write(&path, skeleton(file, service, resolver))?;
+            println!("Generated service skeleton {}", path.display());
+        }
+        modules.push(module);
+    }
+
+    let mut code = String::from(
+        "// Generated by `rf gen proto`. The service files next to this one are created once\n\
+         // and then edited by hand; this file is rewritten on every run.\n\n",
+    );
+    for module in &modules {
+        code.push_str(&format!("pub mod {};\n", module));
+    }
+    code.push_str("\nuse rf_contrib_grpc::GrpcServer;\n\n");
+    code.push_str("/// Adds every service to the server\n");
+    code.push_str("pub fn register(server: GrpcServer) -> GrpcServer {\n    server");
+    for module in &modules {
+        code.push_str(&format!("\n        .add_service({}::server())", module));
+    }
+    code.push_str("\n}\n");
+    fs::write(dir.join("mod.rs"), code)?;
+    Ok(())
+}
+
+/// 生成一个服务的实现骨架，方法都返回 `UNIMPLEMENTED`
+fn skeleton(file: &FileDescriptorProto, service: &ServiceDescriptorProto, resolver: &TypeResolver) -> String {
+    let trait_name = to_upper_camel(service.name());
+    let impl_name = format!("{}Impl", trait_name);
+    let server_mod = format!("{}_server", naive_snake_case(&trait_name));
+    let package = resolver.module_path(file.package());
+    let prefix = if package.is_empty() { "super::super".to_string() } else { format!("super::super::{}", package) };
+    let full_name = if file.package().is_empty() {
+        service.name().to_string()
+    } else {
+        format!("{}.{}", file.package(), service.name())
+    };
+
+    // Import message types by name unless two of them clash
+    let mut types = BTreeSet::new();
+    for method in &service.method {
+        types.extend(resolver.rust_type(method.input_type()));
+        types.extend(resolver.rust_type(method.output_type()));
+    }
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    for path in &types {
+        *names.entry(last_segment(path)).or_default() += 1;
+    }
+    let type_name = |proto: &str| match resolver.rust_type(proto) {
+        None => "()".to_string(),
+        Some(path) if path.starts_with("::") || names[last_segment(&path)] > 1 => qualify(&path),
+        Some(path) => last_segment(&path).to_string(),
+    };
+
+    let mut code = format!("//! `{}` service\n\n", full_name);
+    code.push_str(&format!("use {}::{}::{{{}, {}Server}};\n", prefix, server_mod, trait_name, trait_name));
+    for path in &types {
+        if !path.starts_with("::") && names[last_segment(path)] == 1 {
+            code.push_str(&format!("use {};\n", qualify(path)));
+        }
+    }
+    if service.method.iter().any(|m| m.server_streaming()) {
+        code.push_str("use tonic::codegen::BoxStream;\n");
+    }
+    code.push_str(if service.method.iter().any(|m| m.client_streaming()) {
+        "use tonic::{Request, Response, Status, Streaming};\n"
+    } else {
+        "use tonic::{Request, Response, Status};\n"
+    });
+
+    code.push_str(&format!(
+        "\n/// `{}` implementation\n#[derive(Debug, Default)]\npub struct {};\n\n#[tonic::async_trait]\nimpl {} for {} {{\n",
+        full_name, impl_name, trait_name, impl_name
+    ));
+    for (index, method) in service.method.iter().enumerate() {
+        if index > 0 {
+            code.push('\n');
+        }
+        code.push_str(&method_skeleton(&full_name, method, &type_name));
+    }
+    code.push_str(&format!(
+        "}}\n\n/// Server for `GrpcServer::add_service`\npub fn server() -> {}Server<{}> {{\n    {}Server::new({}::default())\n}}\n",
+        trait_name, impl_name, trait_name, impl_name
+    ));
+    code
+}
+
+/// 一个方法的骨架
+fn method_skeleton(service: &str, method: &MethodDescriptorProto, type_name: &impl Fn(&str) -> String) -> String {
+    let input = type_name(method.input_type());
+    let output = type_name(method.output_type());
+    let request = if method.client_streaming() { format!("Streaming<{}>", input) } else { input };
+    let mut code = String::new();
+    let response = if method.server_streaming() {
+        let stream = format!("{}Stream", method.name());
+        code.push_str(&format!("    type {} = BoxStream<{}>;\n\n", stream, output));
+        format!("Self::{}", stream)
+    } else {
+        output
+    };
+    code.push_str(&format!(
+        "    async fn {}(&self, _request: Request<{}>) -> Result<Response<{}>, Status> {{\n",
+        to_snake(method.name()),
+        request,
+        response
+    ));
+    code.push_str(&format!(
+        "        Err(Status::unimplemented(\"{}/{} is not implemented\"))\n    }}\n",
+        service,
+        method.name()
+    ));
+    code
+}
+
+/// 把 protobuf 类型名映射为相对生成根模块的 Rust 路径
+struct TypeResolver {
+    packages: HashSet<String>,
+}
+
+impl TypeResolver {
+    fn new(set: &FileDescriptorSet) -> Self {
+        Self {
+            packages: set.file.iter().map(|file| file.package().to_string()).collect(),
+        }
+    }
+
+    /// `.pkg.Outer.Inner` → `pkg::outer::Inner`，`google.protobuf.Empty` 为 `None`（即 `()`），
+    /// 其他知名类型映射到 `::prost_types`
+    fn rust_type(&self, proto: &str) -> Option<String> {
+        let name = proto.trim_start_matches('.');
+        if name == "google.protobuf.Empty" {
+            return None;
+        }
+        // prost maps the well-known types to prost-types instead of generating them
+        if let Some(name) = name.strip_prefix("google.protobuf.") {
+            return Some(format!("::prost_types::{}", to_upper_camel(name)));
+        }
+        // The longest known package is the module, the rest are (nested) messages
+        let package = self
+            .packages
+            .iter()
+            .filter(|package| package.is_empty() || name.starts_with(&format!("{}.", package)))
+            .max_by_key(|package| package.len())
+            .cloned()
+            .unwrap_or_default();
+        let messages: Vec<&str> = name[if package.is_empty() { 0 } else { package.len() + 1 }..].split('.').collect();
+        let mut path: Vec<String> = Vec::new();
+        if !package.is_empty() {
+            path.push(self.module_path(&package));
+        }
+        if let Some((last, outer)) = messages.split_last() {
+            path.extend(outer.iter().map(|message| to_snake(message)));
+            path.push(to_upper_camel(last));
+        }
+        Some(path.join("::"))
+    }
+
+    /// package 对应的模块路径
+    fn module_path(&self, package: &str) -> String {
+        if package.is_empty() {
+            return String::new();
+        }
+        package.split('.').map(to_snake).collect::<Vec<_>>().join("::")
+    }
+}
+
+/// 在骨架文件（`services::<name>`）中引用生成根模块下的路径
+fn qualify(path: &str) -> String {
+    if path.starts_with("::") {
+        path.to_string()
+    } else {
+        format!("super::super::{}", path)
+    }
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// 与 prost 一致的标识符处理：关键字使用原始标识符或加下划线
+fn sanitize_identifier(ident: String) -> String {
+    match ident.as_str() {
+        "as" | "break" | "const" | "continue" | "else" | "enum" | "false" | "fn" | "for" | "if" | "impl" | "in"
+        | "let" | "loop" | "match" | "mod" | "move" | "mut" | "pub" | "ref" | "return" | "static" | "struct"
+        | "trait" | "true" | "type" | "unsafe" | "use" | "where" | "while" | "dyn" | "abstract" | "become"
+        | "box" | "do" | "final" | "macro" | "override" | "priv" | "typeof" | "unsized" | "virtual" | "yield"
+        | "async" | "await" | "try" => format!("r#{}", ident),
+        "_" | "super" | "self" | "Self" | "extern" | "crate" => format!("{}_", ident),
+        s if s.starts_with(|c: char| c.is_numeric()) => format!("_{}", ident),
+        _ => ident,
+    }
+}
+
+fn to_snake(name: &str) -> String {
+    sanitize_identifier(name.to_snake_case())
+}
+
+fn to_upper_camel(name: &str) -> String {
+    sanitize_identifier(name.to_upper_camel_case())
+}
+
+/// tonic-build 生成 `<service>_server` 模块名的方式
+fn naive_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        snake.push(c.to_ascii_lowercase());
+        if chars.peek().is_some_and(|next| next.is_uppercase()) {
+            snake.push('_');
+        }
+    }
+    snake
+}
+
@@ -0,0 +1,77 @@
+//! # sdk
+//!
+//! sdk 模块 - 客户端 SDK 生成
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! 客户端 SDK 生成
+//!
+//! 读取服务导出的 OpenAPI 文档（文件或 `/openapi.json` 地址），通过
+//! `rf_net::oai::SdkGenerator` 生成 Rust 客户端 crate 和可选的 TypeScript 客户端。
+
+use rf_errors::{Result, RfError};
+use rf_net::oai::SdkGenerator;
+use std::path::PathBuf;
+
+/// SDK 生成选项
+#[derive(Debug, Clone)]
+pub struct SdkOptions {
+    /// OpenAPI 文档路径或 URL
+    pub spec: String,
+    /// Rust crate 输出目录，`None` 表示不生成 Rust 客户端
+    pub rust_dir: Option<PathBuf>,
+    /// TypeScript 输出文件
+    pub typescript: Option<PathBuf>,
+    /// crate 名称
+    pub crate_name: Option<String>,
+    /// 客户端类型名称
+    pub client_name: Option<String>,
+    /// 本地 RF 源码目录，写入生成的 Cargo.toml
+    pub rf_path: Option<String>,
+}
+
+/// 生成 SDK
+pub async fn generate(options: SdkOptions) -> Result<()> {
+    let document = load_spec(&options.spec).await?;
+    let mut generator = SdkGenerator::new(document);
+    if let Some(name) = &options.crate_name {
+        generator = generator.crate_name(name);
+    }
+    if let Some(name) = &options.client_name {
+        generator = generator.client_name(name);
+    }
+    if let Some(path) = &options.rf_path {
+        generator = generator.rf_path(path);
+    }
+
+    if let Some(dir) = &options.rust_dir {
+        generator.write_rust(dir)?;
+        println!("Generated Rust client in {}", dir.display());
+    }
+    if let Some(path) = &options.typescript {
+        generator.write_typescript(path)?;
+        println!("Generated TypeScript client {}", path.display());
+    }
+    Ok(())
+}
+
+/// 读取 OpenAPI 文档，`http://` / `https://` 开头时从服务下载
+async fn load_spec(spec: &str) -> Result<serde_json::Value> {
+    let text = if spec.starts_with("http://") || spec.starts_with("https://") {
+        let response = rf_net::client::HttpClient::new().get(spec).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(RfError::Network(format!("Failed to fetch {}: HTTP {}", spec, status)));
+        }
+        response
+            .text()
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to fetch {}: {}", spec, e)))?
+    } else {
+        std::fs::read_to_string(spec)
+            .map_err(|e| RfError::InvalidParameter(format!("Failed to read {}: {}", spec, e)))?
+    };
+    serde_json::from_str(&text)
+        .map_err(|e| RfError::Serialization(format!("Invalid OpenAPI document {}: {}", spec, e)))
+}
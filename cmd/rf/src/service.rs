@@ -0,0 +1,153 @@
+//! # service
+//!
+//! service 模块 - 后台运行 RF 应用
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! 服务管理（rf service）
+//!
+//! `start` 以守护进程方式运行当前项目构建出的可执行文件（Unix 上通过 `setsid` 脱离终端，
+//! Windows 上作为独立进程组运行），把 PID 写入 PID 文件，并把标准输出和标准错误追加到日志文件；
+//! `stop`、`restart`、`status` 读取 PID 文件定位进程。
+//!
+//! 默认路径按服务名推导，服务名默认是 `Cargo.toml` 中的包名：
+//! - 可执行文件：`target/debug/<name>`，加 `--release` 时为 `target/release/<name>`
+//! - PID 文件：`run/<name>.pid`
+//! - 日志文件：`logs/<name>.log`
+//!
+//! PID 文件指向的进程已经不存在时视为过期：`status` 会提示，`start` 会覆盖，`stop` 会删除。
+
+use rf_os::proc::{stop_pid, Daemon, PidFile, PidStatus};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+type ServiceResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// 定位服务的参数
+#[derive(Debug, Clone, Default)]
+pub struct ServiceTarget {
+    /// 服务名，默认是 Cargo.toml 中的包名
+    pub name: Option<String>,
+    /// PID 文件路径，默认 `run/<name>.pid`
+    pub pid_file: Option<String>,
+}
+
+/// 启动参数
+#[derive(Debug, Clone, Default)]
+pub struct StartOptions {
+    pub target: ServiceTarget,
+    /// 可执行文件，默认 `target/{debug,release}/<name>`
+    pub bin: Option<String>,
+    /// 使用 release 构建
+    pub release: bool,
+    /// 日志文件，默认 `logs/<name>.log`
+    pub log_file: Option<String>,
+    /// 传给服务的参数
+    pub args: Vec<String>,
+}
+
+impl ServiceTarget {
+    /// 服务名：显式指定的名称，否则是 Cargo.toml 中的包名
+    fn name(&self) -> ServiceResult<String> {
+        if let Some(name) = &self.name {
+            return Ok(name.clone());
+        }
+        let manifest = std::fs::read_to_string("Cargo.toml")
+            .map_err(|e| format!("Cannot read Cargo.toml ({}), pass --name", e))?;
+        let manifest: toml::Value = toml::from_str(&manifest)?;
+        manifest
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(|name| name.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| "Cargo.toml has no [package] name, pass --name".into())
+    }
+
+    fn pid_file(&self, name: &str) -> PidFile {
+        match &self.pid_file {
+            Some(path) => PidFile::new(path),
+            None => PidFile::new(Path::new("run").join(format!("{}.pid", name))),
+        }
+    }
+}
+
+/// 启动服务
+pub fn start(options: &StartOptions) -> ServiceResult<()> {
+    let name = options.target.name()?;
+    let bin = match &options.bin {
+        Some(bin) => PathBuf::from(bin),
+        None => {
+            let profile = if options.release { "release" } else { "debug" };
+            Path::new("target").join(profile).join(format!("{}{}", name, std::env::consts::EXE_SUFFIX))
+        }
+    };
+    if !bin.exists() {
+        let build = if options.release { "cargo build --release" } else { "cargo build" };
+        return Err(format!("{} not found, run `{}` or pass --bin", bin.display(), build).into());
+    }
+    let log_file = options.log_file.clone().map(PathBuf::from).unwrap_or_else(|| Path::new("logs").join(format!("{}.log", name)));
+    let pid_file = options.target.pid_file(&name);
+
+    let pid = Daemon::new(std::fs::canonicalize(&bin)?)
+        .args(options.args.iter().cloned())
+        .log_file(&log_file)
+        .pid_file(pid_file.path())
+        .spawn()?;
+    println!("Started {} (PID {})", name, pid);
+    println!("  PID file: {}", pid_file.path().display());
+    println!("  Log file: {}", log_file.display());
+    Ok(())
+}
+
+/// 停止服务：先 SIGTERM，超时后 SIGKILL
+///
+/// 服务未运行时返回 `Ok(false)`
+pub async fn stop(target: &ServiceTarget, timeout: Duration) -> ServiceResult<bool> {
+    let name = target.name()?;
+    let pid_file = target.pid_file(&name);
+    match pid_file.status()? {
+        PidStatus::Running(pid) => {
+            println!("Stopping {} (PID {})...", name, pid);
+            if stop_pid(pid, timeout).await? {
+                println!("Stopped {}", name);
+            } else {
+                println!("Killed {} after {}s", name, timeout.as_secs());
+            }
+            pid_file.remove()?;
+            Ok(true)
+        }
+        PidStatus::Stale(pid) => {
+            println!("{} is not running, removing stale PID file {} (PID {})", name, pid_file.path().display(), pid);
+            pid_file.remove()?;
+            Ok(false)
+        }
+        PidStatus::Stopped => {
+            println!("{} is not running", name);
+            Ok(false)
+        }
+    }
+}
+
+/// 重启服务：停止（如果正在运行）后重新启动
+pub async fn restart(options: &StartOptions, timeout: Duration) -> ServiceResult<()> {
+    stop(&options.target, timeout).await?;
+    start(options)
+}
+
+/// 显示服务状态
+pub fn status(target: &ServiceTarget) -> ServiceResult<()> {
+    let name = target.name()?;
+    let pid_file = target.pid_file(&name);
+    match pid_file.status()? {
+        PidStatus::Running(pid) => println!("{} is running (PID {})", name, pid),
+        PidStatus::Stale(pid) => println!(
+            "{} is not running (stale PID file {} names PID {})",
+            name,
+            pid_file.path().display(),
+            pid
+        ),
+        PidStatus::Stopped => println!("{} is not running", name),
+    }
+    Ok(())
+}
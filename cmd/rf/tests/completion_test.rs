@@ -0,0 +1,88 @@
+//! # completion_test
+//!
+//! completion_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Shell completion and man page tests
+
+#[cfg(test)]
+mod tests {
+    use std::process::{Command, Output};
+    use tempfile::TempDir;
+
+    fn rf(args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_rf")).args(args).output().unwrap()
+    }
+
+    fn stdout(output: &Output) -> String {
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout.clone()).unwrap()
+    }
+
+    /// Ask for candidates the way the fish registration script does
+    fn complete(words: &[&str]) -> Vec<String> {
+        let output = Command::new(env!("CARGO_BIN_EXE_rf"))
+            .env("COMPLETE", "fish")
+            .arg("--")
+            .args(words)
+            .output()
+            .unwrap();
+        stdout(&output).lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_completion_registration() {
+        let exe = env!("CARGO_BIN_EXE_rf");
+        for shell in ["bash", "zsh", "fish", "powershell"] {
+            let script = stdout(&rf(&["completion", shell]));
+            assert!(script.contains("COMPLETE"), "{}: {}", shell, script);
+            assert!(script.contains(exe), "{}: {}", shell, script);
+        }
+        assert!(!rf(&["completion", "tcsh"]).status.success());
+    }
+
+    #[test]
+    fn test_static_completion() {
+        let candidates = complete(&["rf", "mig"]);
+        assert!(candidates.iter().any(|c| c.starts_with("migrate")), "{:?}", candidates);
+        let candidates = complete(&["rf", "migrate", ""]);
+        for subcommand in ["up", "down", "rollback"] {
+            assert!(candidates.iter().any(|c| c.starts_with(subcommand)), "{:?}", candidates);
+        }
+    }
+
+    #[test]
+    fn test_migration_version_completion() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("20260101000000_create_users.rs"), "").unwrap();
+        std::fs::write(dir.path().join("20260201000000_add_email.rs"), "").unwrap();
+        let dir = dir.path().to_str().unwrap();
+
+        let candidates = complete(&["rf", "migrate", "rollback", "--dir", dir, ""]);
+        // Newest first, with the migration name as help, before the flags
+        assert_eq!(candidates[..2], ["20260201000000\tadd_email", "20260101000000\tcreate_users"]);
+        let candidates = complete(&["rf", "migrate", "rollback", "-d", dir, "202601"]);
+        assert_eq!(candidates, ["20260101000000\tcreate_users"]);
+    }
+
+    #[test]
+    fn test_table_completion_without_db() {
+        assert!(complete(&["rf", "gen", "model", "--tables", ""]).is_empty());
+    }
+
+    #[test]
+    fn test_man_page() {
+        let page = stdout(&rf(&["man"]));
+        assert!(page.contains(".TH rf"), "{}", page);
+        assert!(page.contains("migrate"));
+
+        let dir = TempDir::new().unwrap();
+        let out = dir.path().join("man1");
+        stdout(&rf(&["man", "--out", out.to_str().unwrap()]));
+        assert!(out.join("rf.1").exists());
+        assert!(out.join("rf-migrate.1").exists());
+        assert!(out.join("rf-gen-proto.1").exists());
+    }
+}
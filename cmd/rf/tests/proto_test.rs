@@ -0,0 +1,177 @@
+//! # proto_test
+//!
+//! proto_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! `rf gen proto` tests
+//!
+//! protoc is replaced by a script that writes a prepared descriptor set, so
+//! everything after protoc (prost codegen, mod.rs wiring, service skeletons)
+//! runs for real without protoc installed. The fake protoc is a shell script.
+
+#[cfg(all(test, unix))]
+mod tests {
+    use prost::Message;
+    use prost_types::field_descriptor_proto::{Label, Type};
+    use prost_types::{
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet, MethodDescriptorProto,
+        ServiceDescriptorProto,
+    };
+    use std::fs;
+    use std::path::Path;
+    use std::process::{Command, Output};
+    use tempfile::TempDir;
+
+    fn message(name: &str, field: &str) -> DescriptorProto {
+        DescriptorProto {
+            name: Some(name.to_string()),
+            field: vec![FieldDescriptorProto {
+                name: Some(field.to_string()),
+                number: Some(1),
+                label: Some(Label::Optional as i32),
+                r#type: Some(Type::String as i32),
+                json_name: Some(field.to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn method(name: &str, server_streaming: bool) -> MethodDescriptorProto {
+        MethodDescriptorProto {
+            name: Some(name.to_string()),
+            input_type: Some(".helloworld.HelloRequest".to_string()),
+            output_type: Some(".helloworld.HelloReply".to_string()),
+            server_streaming: Some(server_streaming),
+            ..Default::default()
+        }
+    }
+
+    /// A project with `proto/greeter.proto` and a fake protoc that outputs its descriptor set
+    fn project() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let set = FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("greeter.proto".to_string()),
+                package: Some("helloworld".to_string()),
+                syntax: Some("proto3".to_string()),
+                message_type: vec![message("HelloRequest", "name"), message("HelloReply", "message")],
+                service: vec![ServiceDescriptorProto {
+                    name: Some("Greeter".to_string()),
+                    method: vec![method("SayHello", false), method("StreamHellos", true)],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+        fs::write(dir.path().join("greeter.desc"), set.encode_to_vec()).unwrap();
+        fs::create_dir(dir.path().join("proto")).unwrap();
+        fs::write(dir.path().join("proto").join("greeter.proto"), "syntax = \"proto3\";\n").unwrap();
+
+        let protoc = dir.path().join("protoc");
+        fs::write(
+            &protoc,
+            "#!/bin/sh\nwhile [ $# -gt 0 ]; do\n  if [ \"$1\" = \"-o\" ]; then cp \"$RF_TEST_DESCRIPTOR\" \"$2\"; exit 0; fi\n  shift\ndone\nexit 1\n",
+        )
+        .unwrap();
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&protoc, fs::Permissions::from_mode(0o755)).unwrap();
+        dir
+    }
+
+    fn gen_proto(dir: &Path, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_rf"))
+            .current_dir(dir)
+            .env("RF_TEST_DESCRIPTOR", dir.join("greeter.desc"))
+            .args(["gen", "proto", "--protoc"])
+            .arg(dir.join("protoc"))
+            .args(args)
+            .output()
+            .unwrap()
+    }
+
+    fn read(path: impl AsRef<Path>) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_generates_code_wiring_and_skeletons() {
+        let dir = project();
+        let output = gen_proto(dir.path(), &[]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let out = dir.path().join("src").join("proto");
+
+        let code = read(out.join("helloworld.rs"));
+        assert!(code.contains("pub struct HelloRequest"));
+        assert!(code.contains("pub mod greeter_client"));
+        assert!(code.contains("pub mod greeter_server"));
+        assert!(out.join("descriptor.bin").exists());
+
+        let wiring = read(out.join("mod.rs"));
+        assert!(wiring.starts_with("// Generated by `rf gen proto`"));
+        assert!(wiring.contains("pub mod helloworld"));
+        assert!(wiring.contains("pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!(\"descriptor.bin\");"));
+        assert!(wiring.contains("pub mod services;"));
+
+        let skeleton = read(out.join("services").join("greeter.rs"));
+        assert!(skeleton.contains("use super::super::helloworld::greeter_server::{Greeter, GreeterServer};"));
+        assert!(skeleton.contains("use super::super::helloworld::HelloReply;\nuse super::super::helloworld::HelloRequest;\n"));
+        assert!(skeleton.contains("impl Greeter for GreeterImpl"));
+        assert!(skeleton.contains(
+            "async fn say_hello(&self, _request: Request<HelloRequest>) -> Result<Response<HelloReply>, Status>"
+        ));
+        assert!(skeleton.contains("type StreamHellosStream = BoxStream<HelloReply>;"));
+        assert!(skeleton.contains("Status::unimplemented(\"helloworld.Greeter/SayHello is not implemented\")"));
+        assert!(skeleton.contains("pub fn server() -> GreeterServer<GreeterImpl>"));
+
+        let services = read(out.join("services").join("mod.rs"));
+        assert!(services.contains("pub mod greeter;"));
+        assert!(services.contains("use rf_contrib_grpc::GrpcServer;"));
+        assert!(services.contains(".add_service(greeter::server())"));
+    }
+
+    #[test]
+    fn test_keeps_edited_skeletons_unless_forced() {
+        let dir = project();
+        assert!(gen_proto(dir.path(), &[]).status.success());
+        let skeleton = dir.path().join("src/proto/services/greeter.rs");
+        fs::write(&skeleton, "// edited\n").unwrap();
+
+        assert!(gen_proto(dir.path(), &[]).status.success());
+        assert_eq!(read(&skeleton), "// edited\n");
+        assert!(read(dir.path().join("src/proto/mod.rs")).contains("FILE_DESCRIPTOR_SET"));
+
+        assert!(gen_proto(dir.path(), &["--force"]).status.success());
+        assert!(read(&skeleton).contains("impl Greeter for GreeterImpl"));
+    }
+
+    #[test]
+    fn test_client_only() {
+        let dir = project();
+        let output = gen_proto(dir.path(), &["--no-server", "--output", "src/api"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let out = dir.path().join("src").join("api");
+
+        let code = read(out.join("helloworld.rs"));
+        assert!(code.contains("pub mod greeter_client"));
+        assert!(!code.contains("pub mod greeter_server"));
+        assert!(!read(out.join("mod.rs")).contains("pub mod services;"));
+        assert!(!out.join("services").exists());
+    }
+
+    #[test]
+    fn test_missing_protos() {
+        let dir = TempDir::new().unwrap();
+        let output = gen_proto(dir.path(), &[]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Proto directory proto not found"));
+
+        fs::create_dir(dir.path().join("proto")).unwrap();
+        let output = gen_proto(dir.path(), &[]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("No .proto files in proto"));
+        assert!(!dir.path().join("src").exists());
+    }
+}
@@ -0,0 +1,66 @@
+//! # adapter
+//!
+//! adapter 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Policy storage adapters
+
+use super::model::Policy;
+use async_trait::async_trait;
+use rf_errors::{Result, RfError};
+use std::path::{Path, PathBuf};
+
+/// Policy storage adapter trait
+#[async_trait]
+pub trait PolicyAdapter: Send + Sync {
+    /// Load the whole policy from storage
+    async fn load_policy(&self) -> Result<Policy>;
+
+    /// Replace the stored policy
+    async fn save_policy(&self, policy: &Policy) -> Result<()>;
+}
+
+/// File-based policy adapter using Casbin-style CSV lines
+pub struct FileAdapter {
+    path: PathBuf,
+}
+
+impl FileAdapter {
+    /// Create a new file adapter
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Get the policy file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl PolicyAdapter for FileAdapter {
+    async fn load_policy(&self) -> Result<Policy> {
+        if !self.path.exists() {
+            return Ok(Policy::new());
+        }
+        let content = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(RfError::Io)?;
+        Policy::parse(&content)
+    }
+
+    async fn save_policy(&self, policy: &Policy) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                tokio::fs::create_dir_all(parent).await.map_err(RfError::Io)?;
+            }
+        }
+        tokio::fs::write(&self.path, policy.to_csv())
+            .await
+            .map_err(RfError::Io)
+    }
+}
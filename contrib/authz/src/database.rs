@@ -0,0 +1,186 @@
+//! # database
+//!
+//! database 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Database policy adapter
+//!
+//! Stores rules in a Casbin-compatible table:
+//!
+//! ```sql
+//! CREATE TABLE casbin_rule (
+//!     ptype VARCHAR(8) NOT NULL,
+//!     v0 VARCHAR(255) NOT NULL DEFAULT '',
+//!     v1 VARCHAR(255) NOT NULL DEFAULT '',
+//!     v2 VARCHAR(255) NOT NULL DEFAULT '',
+//!     v3 VARCHAR(255) NOT NULL DEFAULT '',
+//!     v4 VARCHAR(255) NOT NULL DEFAULT ''
+//! );
+//! ```
+
+use super::adapter::PolicyAdapter;
+use super::model::{Effect, Policy, PolicyRule, RoleRule};
+use async_trait::async_trait;
+use rf_database::db::Database;
+use rf_errors::{Result, RfError};
+use std::sync::Arc;
+
+/// Raw rule row: ptype, v0..v4
+type RuleRow = (String, String, String, String, String, String);
+
+/// Database-backed policy adapter
+pub struct DatabaseAdapter {
+    database: Arc<Database>,
+    table: String,
+}
+
+impl DatabaseAdapter {
+    /// Create a new database adapter using the default `casbin_rule` table
+    pub fn new(database: Arc<Database>) -> Self {
+        Self {
+            database,
+            table: "casbin_rule".to_string(),
+        }
+    }
+
+    /// Use a custom table name
+    pub fn table(mut self, table: &str) -> Self {
+        self.table = table.to_string();
+        self
+    }
+
+    fn select_sql(&self) -> String {
+        format!("SELECT ptype, v0, v1, v2, v3, v4 FROM {}", self.table)
+    }
+
+    fn insert_sql(&self, postgres: bool) -> String {
+        let placeholders = if postgres {
+            "$1, $2, $3, $4, $5, $6"
+        } else {
+            "?, ?, ?, ?, ?, ?"
+        };
+        format!(
+            "INSERT INTO {} (ptype, v0, v1, v2, v3, v4) VALUES ({})",
+            self.table, placeholders
+        )
+    }
+}
+
+/// Convert stored rows into a policy
+fn rows_to_policy(rows: Vec<RuleRow>) -> Result<Policy> {
+    let mut policy = Policy::new();
+    for (ptype, v0, v1, v2, v3, v4) in rows {
+        let domain = |v: String| if v.is_empty() { None } else { Some(v) };
+        match ptype.as_str() {
+            "p" => policy.rules.push(PolicyRule {
+                subject: v0,
+                resource: v1,
+                action: v2,
+                effect: Effect::parse(&v3)?,
+                domain: domain(v4),
+            }),
+            "g" => policy.roles.push(RoleRule {
+                user: v0,
+                role: v1,
+                domain: domain(v2),
+            }),
+            other => {
+                return Err(RfError::Database(format!("Unknown policy type: {}", other)));
+            }
+        }
+    }
+    Ok(policy)
+}
+
+/// Convert a policy into rows for storage
+fn policy_to_rows(policy: &Policy) -> Vec<RuleRow> {
+    let rules = policy.rules.iter().map(|r| {
+        (
+            "p".to_string(),
+            r.subject.clone(),
+            r.resource.clone(),
+            r.action.clone(),
+            r.effect.as_str().to_string(),
+            r.domain.clone().unwrap_or_default(),
+        )
+    });
+    let roles = policy.roles.iter().map(|g| {
+        (
+            "g".to_string(),
+            g.user.clone(),
+            g.role.clone(),
+            g.domain.clone().unwrap_or_default(),
+            String::new(),
+            String::new(),
+        )
+    });
+    rules.chain(roles).collect()
+}
+
+fn db_err(e: sqlx::Error) -> RfError {
+    RfError::Database(format!("Policy storage failed: {}", e))
+}
+
+#[async_trait]
+impl PolicyAdapter for DatabaseAdapter {
+    async fn load_policy(&self) -> Result<Policy> {
+        let sql = self.select_sql();
+        let rows: Vec<RuleRow> = if let Some(pool) = self.database.as_postgres() {
+            sqlx::query_as(&sql).fetch_all(pool).await.map_err(db_err)?
+        } else if let Some(pool) = self.database.as_mysql() {
+            sqlx::query_as(&sql).fetch_all(pool).await.map_err(db_err)?
+        } else if let Some(pool) = self.database.as_sqlite() {
+            sqlx::query_as(&sql).fetch_all(pool).await.map_err(db_err)?
+        } else {
+            return Err(RfError::Database("Unsupported database type".to_string()));
+        };
+        rows_to_policy(rows)
+    }
+
+    async fn save_policy(&self, policy: &Policy) -> Result<()> {
+        let delete_sql = format!("DELETE FROM {}", self.table);
+        let rows = policy_to_rows(policy);
+
+        if let Some(pool) = self.database.as_postgres() {
+            let insert_sql = self.insert_sql(true);
+            let mut tx = pool.begin().await.map_err(db_err)?;
+            sqlx::query(&delete_sql).execute(&mut *tx).await.map_err(db_err)?;
+            for (ptype, v0, v1, v2, v3, v4) in rows {
+                sqlx::query(&insert_sql)
+                    .bind(ptype).bind(v0).bind(v1).bind(v2).bind(v3).bind(v4)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_err)?;
+            }
+            tx.commit().await.map_err(db_err)
+        } else if let Some(pool) = self.database.as_mysql() {
+            let insert_sql = self.insert_sql(false);
+            let mut tx = pool.begin().await.map_err(db_err)?;
+            sqlx::query(&delete_sql).execute(&mut *tx).await.map_err(db_err)?;
+            for (ptype, v0, v1, v2, v3, v4) in rows {
+                sqlx::query(&insert_sql)
+                    .bind(ptype).bind(v0).bind(v1).bind(v2).bind(v3).bind(v4)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_err)?;
+            }
+            tx.commit().await.map_err(db_err)
+        } else if let Some(pool) = self.database.as_sqlite() {
+            let insert_sql = self.insert_sql(false);
+            let mut tx = pool.begin().await.map_err(db_err)?;
+            sqlx::query(&delete_sql).execute(&mut *tx).await.map_err(db_err)?;
+            for (ptype, v0, v1, v2, v3, v4) in rows {
+                sqlx::query(&insert_sql)
+                    .bind(ptype).bind(v0).bind(v1).bind(v2).bind(v3).bind(v4)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_err)?;
+            }
+            tx.commit().await.map_err(db_err)
+        } else {
+            Err(RfError::Database("Unsupported database type".to_string()))
+        }
+    }
+}
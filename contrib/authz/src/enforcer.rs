@@ -0,0 +1,197 @@
+//! # enforcer
+//!
+//! enforcer 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Policy enforcer
+
+use super::adapter::PolicyAdapter;
+use super::model::{domain_match, Effect, Policy, PolicyRule, RoleRule, WILDCARD};
+use super::AuthzContext;
+use rf_errors::{Result, RfError};
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, RwLock};
+
+/// Policy enforcer
+///
+/// Decides whether a subject may perform an action on a resource.
+/// Deny rules take precedence over allow rules; a request that matches
+/// no rule is denied.
+pub struct Enforcer {
+    policy: RwLock<Policy>,
+    adapter: Option<Arc<dyn PolicyAdapter>>,
+}
+
+impl Enforcer {
+    /// Create an enforcer with an empty in-memory policy
+    pub fn new() -> Self {
+        Self {
+            policy: RwLock::new(Policy::new()),
+            adapter: None,
+        }
+    }
+
+    /// Create an enforcer from an existing policy
+    pub fn with_policy(policy: Policy) -> Self {
+        Self {
+            policy: RwLock::new(policy),
+            adapter: None,
+        }
+    }
+
+    /// Create an enforcer backed by a storage adapter and load its policy
+    pub async fn with_adapter(adapter: Arc<dyn PolicyAdapter>) -> Result<Self> {
+        let policy = adapter.load_policy().await?;
+        Ok(Self {
+            policy: RwLock::new(policy),
+            adapter: Some(adapter),
+        })
+    }
+
+    /// Reload the policy from the adapter
+    pub async fn load_policy(&self) -> Result<()> {
+        let adapter = self.adapter.as_ref()
+            .ok_or_else(|| RfError::Config("No policy adapter configured".to_string()))?;
+        let policy = adapter.load_policy().await?;
+        *self.write()? = policy;
+        Ok(())
+    }
+
+    /// Persist the current policy through the adapter
+    pub async fn save_policy(&self) -> Result<()> {
+        let adapter = self.adapter.as_ref()
+            .ok_or_else(|| RfError::Config("No policy adapter configured".to_string()))?;
+        let policy = self.policy()?;
+        adapter.save_policy(&policy).await
+    }
+
+    /// Get a snapshot of the current policy
+    pub fn policy(&self) -> Result<Policy> {
+        Ok(self.read()?.clone())
+    }
+
+    /// Replace the current policy
+    pub fn set_policy(&self, policy: Policy) -> Result<()> {
+        *self.write()? = policy;
+        Ok(())
+    }
+
+    /// Add a permission rule, returns false if it already exists
+    pub fn add_policy(&self, rule: PolicyRule) -> Result<bool> {
+        let mut policy = self.write()?;
+        if policy.rules.contains(&rule) {
+            return Ok(false);
+        }
+        policy.rules.push(rule);
+        Ok(true)
+    }
+
+    /// Remove a permission rule, returns false if it did not exist
+    pub fn remove_policy(&self, rule: &PolicyRule) -> Result<bool> {
+        let mut policy = self.write()?;
+        let before = policy.rules.len();
+        policy.rules.retain(|r| r != rule);
+        Ok(policy.rules.len() != before)
+    }
+
+    /// Assign a role to a user (or a parent role to a role)
+    pub fn add_role_for_user(&self, user: &str, role: &str, domain: Option<&str>) -> Result<bool> {
+        let mut assignment = RoleRule::new(user, role);
+        assignment.domain = domain.map(|d| d.to_string());
+        let mut policy = self.write()?;
+        if policy.roles.contains(&assignment) {
+            return Ok(false);
+        }
+        policy.roles.push(assignment);
+        Ok(true)
+    }
+
+    /// Remove a role from a user
+    pub fn delete_role_for_user(&self, user: &str, role: &str, domain: Option<&str>) -> Result<bool> {
+        let mut policy = self.write()?;
+        let before = policy.roles.len();
+        policy.roles.retain(|g| !(g.user == user && g.role == role && g.domain.as_deref() == domain));
+        Ok(policy.roles.len() != before)
+    }
+
+    /// Get all roles of a user in a domain, including inherited ones
+    pub fn roles_for_user(&self, user: &str, domain: Option<&str>) -> Result<Vec<String>> {
+        let policy = self.read()?;
+        let mut roles = expand_roles(&policy, user, domain);
+        roles.retain(|r| r != user);
+        roles.sort();
+        Ok(roles)
+    }
+
+    /// Check whether the caller may perform the action on the resource
+    pub fn enforce(&self, ctx: &AuthzContext, resource: &str, action: &str) -> Result<bool> {
+        let policy = self.read()?;
+        let domain = ctx.domain.as_deref();
+        let subjects = expand_roles(&policy, &ctx.subject, domain);
+
+        let mut allowed = false;
+        for rule in &policy.rules {
+            let subject_match = rule.subject == WILDCARD || subjects.contains(&rule.subject);
+            if !subject_match || !rule.matches(resource, action, domain) {
+                continue;
+            }
+            match rule.effect {
+                Effect::Deny => return Ok(false),
+                Effect::Allow => allowed = true,
+            }
+        }
+        Ok(allowed)
+    }
+
+    /// Like `enforce`, but returns `RfError::Forbidden` when access is denied
+    pub fn check(&self, ctx: &AuthzContext, resource: &str, action: &str) -> Result<()> {
+        if self.enforce(ctx, resource, action)? {
+            Ok(())
+        } else {
+            Err(RfError::Forbidden(format!(
+                "{} is not allowed to {} {}",
+                ctx.subject, action, resource
+            )))
+        }
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, Policy>> {
+        self.policy.read()
+            .map_err(|_| RfError::Internal("Failed to acquire read lock".to_string()))
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, Policy>> {
+        self.policy.write()
+            .map_err(|_| RfError::Internal("Failed to acquire write lock".to_string()))
+    }
+}
+
+impl Default for Enforcer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Collect the subject and every role it inherits (breadth-first, cycle safe)
+fn expand_roles(policy: &Policy, subject: &str, domain: Option<&str>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    let mut result = Vec::new();
+    seen.insert(subject.to_string());
+    queue.push_back(subject.to_string());
+
+    while let Some(current) = queue.pop_front() {
+        for assignment in &policy.roles {
+            if assignment.user == current
+                && domain_match(assignment.domain.as_deref(), domain)
+                && seen.insert(assignment.role.clone())
+            {
+                queue.push_back(assignment.role.clone());
+            }
+        }
+        result.push(current);
+    }
+    result
+}
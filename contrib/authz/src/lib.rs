@@ -0,0 +1,53 @@
+//! # lib
+//!
+//! lib 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Authorization (RBAC) module
+//!
+//! Provides a Casbin-style access control model:
+//! - Policy model: subjects, roles, resources, actions and allow/deny effects
+//! - Role inheritance with optional domains (tenants)
+//! - Policy storage adapters: file (CSV) and database
+//! - `Enforcer::enforce(ctx, resource, action)` API
+//! - HTTP middleware for route-level permissions
+
+pub mod model;
+pub mod adapter;
+pub mod database;
+pub mod enforcer;
+pub mod middleware;
+
+pub use model::*;
+pub use adapter::*;
+pub use database::*;
+pub use enforcer::*;
+pub use middleware::*;
+
+/// Authorization context of the current caller
+///
+/// Usually created by an authentication middleware and stored in the
+/// request extensions, where the authz middleware picks it up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthzContext {
+    pub subject: String,
+    pub domain: Option<String>,
+}
+
+impl AuthzContext {
+    /// Create a context for a subject
+    pub fn new(subject: &str) -> Self {
+        Self {
+            subject: subject.to_string(),
+            domain: None,
+        }
+    }
+
+    /// Set the domain (tenant) of the context
+    pub fn with_domain(mut self, domain: &str) -> Self {
+        self.domain = Some(domain.to_string());
+        self
+    }
+}
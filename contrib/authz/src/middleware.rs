@@ -0,0 +1,80 @@
+//! # middleware
+//!
+//! middleware 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! HTTP authorization middleware
+//!
+//! The caller identity is read from an `AuthzContext` placed into the
+//! request extensions by an upstream authentication middleware.
+//!
+//! ```ignore
+//! let enforcer = Arc::new(Enforcer::with_adapter(Arc::new(FileAdapter::new("policy.csv"))).await?);
+//! let router = Router::new()
+//!     .route("/api/users/{id}", get(get_user))
+//!     .route_layer(axum::middleware::from_fn(move |req, next| {
+//!         authz_middleware(enforcer.clone(), req, next)
+//!     }));
+//! ```
+
+use super::enforcer::Enforcer;
+use super::AuthzContext;
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::response::Response;
+use std::sync::Arc;
+
+fn status_response(status: StatusCode) -> Response {
+    let mut response = Response::new(axum::body::Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+/// Authorize the request using its path as resource and method as action
+pub async fn authz_middleware(
+    enforcer: Arc<Enforcer>,
+    request: Request,
+    next: axum::middleware::Next,
+) -> Result<Response, axum::Error> {
+    let resource = request.uri().path().to_string();
+    let action = request.method().as_str().to_string();
+    authorize(&enforcer, &resource, &action, request, next).await
+}
+
+/// Require a fixed permission for the route, independent of the request path
+pub async fn require_permission(
+    enforcer: Arc<Enforcer>,
+    resource: String,
+    action: String,
+    request: Request,
+    next: axum::middleware::Next,
+) -> Result<Response, axum::Error> {
+    authorize(&enforcer, &resource, &action, request, next).await
+}
+
+async fn authorize(
+    enforcer: &Enforcer,
+    resource: &str,
+    action: &str,
+    request: Request,
+    next: axum::middleware::Next,
+) -> Result<Response, axum::Error> {
+    let ctx = match request.extensions().get::<AuthzContext>() {
+        Some(ctx) => ctx.clone(),
+        None => return Ok(status_response(StatusCode::UNAUTHORIZED)),
+    };
+
+    match enforcer.enforce(&ctx, resource, action) {
+        Ok(true) => Ok(next.run(request).await),
+        Ok(false) => {
+            tracing::debug!("Access denied: {} {} {}", ctx.subject, action, resource);
+            Ok(status_response(StatusCode::FORBIDDEN))
+        }
+        Err(e) => {
+            tracing::error!("Authorization failed: {}", e);
+            Ok(status_response(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! # model
+//!
+//! model 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Policy model: rules, roles and matching
+
+use rf_errors::{Result, RfError};
+use serde::{Deserialize, Serialize};
+
+/// Wildcard matching any subject, domain or action
+pub const WILDCARD: &str = "*";
+
+/// Effect of a policy rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Effect {
+    Allow,
+    Deny,
+}
+
+impl Effect {
+    /// Parse an effect from its textual form (`allow` / `deny`)
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "" | "allow" => Ok(Effect::Allow),
+            "deny" => Ok(Effect::Deny),
+            other => Err(RfError::InvalidParameter(format!("Unknown policy effect: {}", other))),
+        }
+    }
+
+    /// Textual form of the effect
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Effect::Allow => "allow",
+            Effect::Deny => "deny",
+        }
+    }
+}
+
+/// Permission rule (`p` line): subject may (not) perform action on resource
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub subject: String,
+    pub resource: String,
+    pub action: String,
+    pub effect: Effect,
+    pub domain: Option<String>,
+}
+
+impl PolicyRule {
+    /// Create an allow rule
+    pub fn allow(subject: &str, resource: &str, action: &str) -> Self {
+        Self {
+            subject: subject.to_string(),
+            resource: resource.to_string(),
+            action: action.to_string(),
+            effect: Effect::Allow,
+            domain: None,
+        }
+    }
+
+    /// Create a deny rule
+    pub fn deny(subject: &str, resource: &str, action: &str) -> Self {
+        Self {
+            effect: Effect::Deny,
+            ..Self::allow(subject, resource, action)
+        }
+    }
+
+    /// Restrict the rule to a domain
+    pub fn in_domain(mut self, domain: &str) -> Self {
+        self.domain = Some(domain.to_string());
+        self
+    }
+
+    /// Check whether the rule covers the resource, action and domain
+    pub fn matches(&self, resource: &str, action: &str, domain: Option<&str>) -> bool {
+        domain_match(self.domain.as_deref(), domain)
+            && resource_match(&self.resource, resource)
+            && action_match(&self.action, action)
+    }
+}
+
+/// Role assignment (`g` line): user (or role) inherits role
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleRule {
+    pub user: String,
+    pub role: String,
+    pub domain: Option<String>,
+}
+
+impl RoleRule {
+    /// Create a role assignment
+    pub fn new(user: &str, role: &str) -> Self {
+        Self {
+            user: user.to_string(),
+            role: role.to_string(),
+            domain: None,
+        }
+    }
+
+    /// Restrict the assignment to a domain
+    pub fn in_domain(mut self, domain: &str) -> Self {
+        self.domain = Some(domain.to_string());
+        self
+    }
+}
+
+/// Complete policy: permission rules plus role assignments
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Policy {
+    pub rules: Vec<PolicyRule>,
+    pub roles: Vec<RoleRule>,
+}
+
+impl Policy {
+    /// Create an empty policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a policy from Casbin-style CSV lines
+    ///
+    /// Supported lines (blank lines and `#` comments are skipped):
+    /// - `p, subject, resource, action[, allow|deny[, domain]]`
+    /// - `g, user, role[, domain]`
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut policy = Policy::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            match fields[0] {
+                "p" if fields.len() >= 4 => {
+                    let mut rule = PolicyRule::allow(fields[1], fields[2], fields[3]);
+                    if let Some(effect) = fields.get(4) {
+                        rule.effect = Effect::parse(effect)?;
+                    }
+                    rule.domain = fields.get(5).filter(|d| !d.is_empty()).map(|d| d.to_string());
+                    policy.rules.push(rule);
+                }
+                "g" if fields.len() >= 3 => {
+                    let mut role = RoleRule::new(fields[1], fields[2]);
+                    role.domain = fields.get(3).filter(|d| !d.is_empty()).map(|d| d.to_string());
+                    policy.roles.push(role);
+                }
+                _ => {
+                    return Err(RfError::Config(format!(
+                        "Invalid policy line {}: {}",
+                        index + 1,
+                        line
+                    )))
+                }
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Render the policy as Casbin-style CSV lines
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        for rule in &self.rules {
+            out.push_str(&format!(
+                "p, {}, {}, {}, {}",
+                rule.subject,
+                rule.resource,
+                rule.action,
+                rule.effect.as_str()
+            ));
+            if let Some(ref domain) = rule.domain {
+                out.push_str(&format!(", {}", domain));
+            }
+            out.push('\n');
+        }
+        for role in &self.roles {
+            out.push_str(&format!("g, {}, {}", role.user, role.role));
+            if let Some(ref domain) = role.domain {
+                out.push_str(&format!(", {}", domain));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Check whether a rule domain covers the requested domain
+///
+/// Rules without a domain (or with `*`) apply to every domain.
+pub fn domain_match(rule_domain: Option<&str>, domain: Option<&str>) -> bool {
+    match rule_domain {
+        None | Some(WILDCARD) => true,
+        Some(rule_domain) => domain == Some(rule_domain),
+    }
+}
+
+/// Check whether a rule action covers the requested action
+///
+/// Supports `*` and `|`-separated alternatives such as `GET|HEAD`.
+/// Comparison is case-insensitive so HTTP methods match naturally.
+pub fn action_match(pattern: &str, action: &str) -> bool {
+    pattern
+        .split('|')
+        .map(|p| p.trim())
+        .any(|p| p == WILDCARD || p.eq_ignore_ascii_case(action))
+}
+
+/// Check whether a resource pattern covers the requested resource
+///
+/// Supported patterns:
+/// - `*` matches everything
+/// - `/api/users/:id` matches exactly one segment per parameter
+/// - `/files/*` matches any (possibly empty) suffix
+pub fn resource_match(pattern: &str, resource: &str) -> bool {
+    if pattern == WILDCARD || pattern == resource {
+        return true;
+    }
+    if let Some(prefix) = pattern.strip_suffix('*') {
+        if !prefix.contains(':') {
+            return resource.starts_with(prefix);
+        }
+    }
+
+    let mut pattern_parts = pattern.split('/');
+    let mut resource_parts = resource.split('/');
+    loop {
+        match (pattern_parts.next(), resource_parts.next()) {
+            (None, None) => return true,
+            (Some(WILDCARD), _) => return true,
+            (Some(p), Some(r)) => {
+                if p.starts_with(':') {
+                    if r.is_empty() {
+                        return false;
+                    }
+                } else if p != r {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+    }
+}
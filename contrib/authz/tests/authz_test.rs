@@ -0,0 +1,49 @@
+//! Authz module tests
+
+use rf_contrib_authz::{resource_match, AuthzContext, Enforcer, Policy, PolicyRule};
+
+#[test]
+fn test_resource_match() {
+    assert!(resource_match("*", "/anything"));
+    assert!(resource_match("/api/users/:id", "/api/users/42"));
+    assert!(!resource_match("/api/users/:id", "/api/users/42/posts"));
+    assert!(resource_match("/files/*", "/files/a/b/c"));
+    assert!(!resource_match("/files/*", "/other"));
+}
+
+#[test]
+fn test_enforce_with_roles() {
+    let policy = Policy::parse(
+        "p, admin, /api/*, *\n\
+         p, reader, /api/users/:id, GET|HEAD\n\
+         p, reader, /api/users/secret, GET, deny\n\
+         g, alice, admin\n\
+         g, bob, reader\n",
+    )
+    .unwrap();
+    let enforcer = Enforcer::with_policy(policy);
+
+    let alice = AuthzContext::new("alice");
+    let bob = AuthzContext::new("bob");
+    assert!(enforcer.enforce(&alice, "/api/users/1", "DELETE").unwrap());
+    assert!(enforcer.enforce(&bob, "/api/users/1", "get").unwrap());
+    assert!(!enforcer.enforce(&bob, "/api/users/1", "DELETE").unwrap());
+    assert!(!enforcer.enforce(&bob, "/api/users/secret", "GET").unwrap());
+    assert!(enforcer.check(&AuthzContext::new("carol"), "/api/users/1", "GET").is_err());
+}
+
+#[test]
+fn test_domains_and_policy_roundtrip() {
+    let enforcer = Enforcer::new();
+    enforcer.add_policy(PolicyRule::allow("editor", "/docs/*", "POST").in_domain("tenant-a")).unwrap();
+    enforcer.add_role_for_user("dave", "editor", Some("tenant-a")).unwrap();
+
+    let in_a = AuthzContext::new("dave").with_domain("tenant-a");
+    let in_b = AuthzContext::new("dave").with_domain("tenant-b");
+    assert!(enforcer.enforce(&in_a, "/docs/1", "POST").unwrap());
+    assert!(!enforcer.enforce(&in_b, "/docs/1", "POST").unwrap());
+    assert_eq!(enforcer.roles_for_user("dave", Some("tenant-a")).unwrap(), vec!["editor"]);
+
+    let policy = enforcer.policy().unwrap();
+    assert_eq!(Policy::parse(&policy.to_csv()).unwrap(), policy);
+}
@@ -0,0 +1,184 @@
+//! # bridge
+//!
+//! bridge 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Configuration centers as `rf_os::cfg::Config` sources
+//!
+//! `CenterConfigAdapter` mounts any `ConfigCenterAdapter` in a `Config`, so
+//! `config.get("database.default.url")` reads from Apollo, Nacos, Consul,
+//! Kubernetes or Polaris like it reads from a file:
+//!
+//! ```rust,ignore
+//! use rf_contrib_config::{CenterConfigAdapter, ConsulAdapter};
+//!
+//! let center = Arc::new(CenterConfigAdapter::new(ConsulAdapter::new("http://consul:8500", "myapp"))
+//!     .ttl(Duration::from_secs(60)));
+//! center.watch()?;
+//! let config = Config::layered("config.toml")?.adapter(center.clone());
+//! let url = config.get("database.default.url")?;
+//! ```
+//!
+//! The whole namespace is fetched at once and cached. Reads inside the TTL
+//! never touch the network; once it expires the next read refetches, and if
+//! the center is unreachable the stale values keep being served. `watch`
+//! applies pushed changes to the cache right away, and `Config::reload`
+//! forces a refetch. Keys written with `/` (Consul, etcd style) are read as
+//! dotted keys, so `database/default/url` is `database.default.url`.
+//!
+//! The adapter is read-only: `Config::set` skips it, so runtime writes never
+//! reach the shared config center.
+
+use super::ConfigCenterAdapter;
+use rf_errors::{Result, RfError};
+use rf_os::cfg::ConfigAdapter;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Default time cached values are used without refetching
+const DEFAULT_TTL: Duration = Duration::from_secs(30);
+
+/// Callback run after a watched change was applied, with the dotted key
+type ChangeHook = Arc<dyn Fn(&str) + Send + Sync>;
+
+#[derive(Default)]
+struct Cache {
+    values: HashMap<String, String>,
+    /// When the snapshot was fetched; `None` until the first fetch
+    loaded_at: Option<Instant>,
+}
+
+/// Config center mounted as a `Config` source, see the module documentation
+pub struct CenterConfigAdapter<A> {
+    center: Arc<A>,
+    cache: Arc<RwLock<Cache>>,
+    ttl: Duration,
+}
+
+impl<A: ConfigCenterAdapter + 'static> CenterConfigAdapter<A> {
+    /// Mount a config center with the default 30 second TTL
+    pub fn new(center: A) -> Self {
+        Self {
+            center: Arc::new(center),
+            cache: Arc::new(RwLock::new(Cache::default())),
+            ttl: DEFAULT_TTL,
+        }
+    }
+
+    /// How long fetched values are used before refetching
+    ///
+    /// With `watch` running the TTL only bounds how stale values get if a
+    /// push is missed, so it can be long.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The wrapped config center
+    pub fn center(&self) -> &A {
+        &self.center
+    }
+
+    /// Fetch the whole namespace now, replacing the cache
+    pub fn refresh(&self) -> Result<()> {
+        let values = self
+            .center
+            .all()?
+            .into_iter()
+            .map(|(key, value)| (normalize(&key), value))
+            .collect();
+        let mut cache = self.cache.write().unwrap_or_else(|e| e.into_inner());
+        cache.values = values;
+        cache.loaded_at = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Apply changes pushed by the config center to the cache
+    ///
+    /// Needs a Tokio runtime, which the centers' watch loops run on. An empty
+    /// value removes the key, as the centers report deletions that way.
+    pub fn watch(&self) -> Result<()> {
+        self.watch_with(|_| {})
+    }
+
+    /// Like `watch`, calling `hook` with each changed key before it is applied
+    ///
+    /// The cache still holds the previous value while `hook` runs, so running
+    /// `Config::reload` from it refetches and `on_change` callbacks see the change:
+    ///
+    /// ```rust,ignore
+    /// let weak = Arc::downgrade(&config);
+    /// center.watch_with(move |_| {
+    ///     if let Some(config) = weak.upgrade() {
+    ///         let _ = config.reload();
+    ///     }
+    /// })?;
+    /// ```
+    pub fn watch_with<F>(&self, hook: F) -> Result<()>
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        let cache = Arc::clone(&self.cache);
+        let hook: ChangeHook = Arc::new(hook);
+        self.center.watch(move |key, value| {
+            let key = normalize(key);
+            hook(&key);
+            let mut cache = cache.write().unwrap_or_else(|e| e.into_inner());
+            if value.is_empty() {
+                cache.values.remove(&key);
+            } else {
+                cache.values.insert(key, value.to_string());
+            }
+            Ok(())
+        })
+    }
+
+    /// Cached values, refetched first when missing or older than the TTL
+    fn values<T>(&self, read: impl FnOnce(&HashMap<String, String>) -> T) -> Result<T> {
+        let fresh = {
+            let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+            cache.loaded_at.map(|at| at.elapsed() < self.ttl)
+        };
+        match fresh {
+            Some(true) => {}
+            loaded => {
+                if let Err(e) = self.refresh() {
+                    if loaded.is_none() {
+                        return Err(e);
+                    }
+                    tracing::warn!("Config center unreachable, using cached values: {}", e);
+                    // Serve the stale values for another TTL instead of retrying on every read
+                    self.cache.write().unwrap_or_else(|e| e.into_inner()).loaded_at = Some(Instant::now());
+                }
+            }
+        }
+        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        Ok(read(&cache.values))
+    }
+}
+
+impl<A: ConfigCenterAdapter + 'static> ConfigAdapter for CenterConfigAdapter<A> {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        self.values(|values| values.get(key).cloned())
+    }
+
+    fn set(&self, _key: &str, _value: &str) -> Result<()> {
+        Err(RfError::Config("Config center sources are read-only".to_string()))
+    }
+
+    fn all(&self) -> Result<HashMap<String, String>> {
+        self.values(|values| values.clone())
+    }
+
+    fn reload(&self) -> Result<()> {
+        self.refresh()
+    }
+}
+
+/// `database/default/url` and `/database/default/url` as `database.default.url`
+pub(crate) fn normalize(key: &str) -> String {
+    key.trim_matches('/').replace('/', ".")
+}
@@ -12,18 +12,31 @@
 //! - Consul
 //! - Nacos
 //! - Kubernetes ConfigMap
+//! - Polaris
+//!
+//! `CenterConfigAdapter` mounts any of them as an `rf_os::cfg::Config` source,
+//! and `VaultSecrets` resolves `vault:` secret references in config values.
+//! `Rollout` gray-releases changes of a key to a share of the instances.
+//! `PolarisAdapter` also discovers instances through Polaris routing rules
+//! and exposes its rate-limit rules to the HTTP `rate_limit` middleware.
 
 pub mod apollo;
 pub mod consul;
 pub mod nacos;
 pub mod k8s;
 pub mod polaris;
+pub mod bridge;
+pub mod vault;
+pub mod rollout;
 
 pub use apollo::*;
 pub use consul::*;
 pub use nacos::*;
 pub use k8s::*;
 pub use polaris::*;
+pub use bridge::*;
+pub use vault::*;
+pub use rollout::*;
 
 use rf_errors::Result;
 use std::collections::HashMap;
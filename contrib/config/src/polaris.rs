@@ -6,14 +6,44 @@
 //! @date 2026-01-06
 
 //! Polaris configuration center adapter
+//!
+//! Besides configuration files, `PolarisAdapter` reads the naming side of
+//! Polaris: service instances, routing rules and rate-limit rules.
+//!
+//! ```rust,ignore
+//! use rf_contrib_config::{CenterConfigAdapter, PolarisAdapter};
+//!
+//! let polaris = PolarisAdapter::new("http://polaris:8090", "Production", "app", "app.properties");
+//!
+//! // Healthy instances of `orders` picked by the routing rules for a caller tagged env=gray
+//! let caller = HashMap::from([("env".to_string(), "gray".to_string())]);
+//! let instances = polaris.route("orders", &caller).await?;
+//!
+//! // Rate-limit rules of `orders` as `rate_limit.{rule}.*` keys, read by `gins::rate_limiter`
+//! let limits = Arc::new(CenterConfigAdapter::new(polaris.rate_limits_source("orders")));
+//! let config = Config::layered("config.toml")?.adapter(limits.clone());
+//! let limiter = gins::rate_limiter(Some("orders-api"));
+//! ```
 
 use super::ConfigCenterAdapter;
-use rf_errors::Result;
+use regex::Regex;
+use rf_errors::{Result, RfError};
+use serde::Deserialize;
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use reqwest::Client;
 
+/// Interval between polls of `watch`
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Polaris discover request types
+const DISCOVER_INSTANCE: u32 = 1;
+const DISCOVER_ROUTING: u32 = 3;
+const DISCOVER_RATE_LIMIT: u32 = 4;
+
 /// Polaris configuration center adapter
 pub struct PolarisAdapter {
     client: Client,
@@ -272,3 +302,397 @@ impl Clone for PolarisAdapter {
     }
 }
 
+/// Service instance registered in Polaris
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolarisInstance {
+    #[serde(default)]
+    pub id: String,
+    pub host: String,
+    pub port: u16,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    #[serde(default = "default_healthy")]
+    pub healthy: bool,
+    /// Isolated instances receive no traffic
+    #[serde(default)]
+    pub isolate: bool,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+fn default_weight() -> u32 {
+    100
+}
+
+fn default_healthy() -> bool {
+    true
+}
+
+impl PolarisInstance {
+    /// `host:port`
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+/// How a metadata value is matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MatchType {
+    #[default]
+    Exact,
+    Regex,
+    NotEquals,
+    /// One of the comma-separated values
+    In,
+    /// None of the comma-separated values
+    NotIn,
+}
+
+/// A metadata condition of a routing rule
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct MatchString {
+    #[serde(default, rename = "type")]
+    pub kind: MatchType,
+    #[serde(default)]
+    pub value: String,
+}
+
+impl MatchString {
+    /// Whether `actual` (`None` when the label is missing) satisfies the condition
+    ///
+    /// A value of `*` matches any present label.
+    pub fn matches(&self, actual: Option<&str>) -> bool {
+        let in_list = |actual: &str| self.value.split(',').any(|value| value.trim() == actual);
+        match (self.kind, actual) {
+            (MatchType::NotEquals, actual) => actual != Some(self.value.as_str()),
+            (MatchType::NotIn, actual) => !actual.is_some_and(in_list),
+            (_, None) => false,
+            (_, Some(_)) if self.value == "*" => true,
+            (MatchType::Exact, Some(actual)) => actual == self.value,
+            (MatchType::In, Some(actual)) => in_list(actual),
+            (MatchType::Regex, Some(actual)) => Regex::new(&format!("^(?:{})$", self.value))
+                .map(|regex| regex.is_match(actual))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// All conditions of `metadata` hold for `labels`
+fn metadata_matches(conditions: &HashMap<String, MatchString>, labels: &HashMap<String, String>) -> bool {
+    conditions
+        .iter()
+        .all(|(key, condition)| condition.matches(labels.get(key).map(String::as_str)))
+}
+
+/// Callers a route applies to
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct RouteSource {
+    #[serde(default)]
+    pub metadata: HashMap<String, MatchString>,
+}
+
+/// Instances a route sends traffic to
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct RouteDestination {
+    #[serde(default)]
+    pub metadata: HashMap<String, MatchString>,
+    /// Lower values are tried first
+    #[serde(default)]
+    pub priority: u32,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// Isolated destinations receive no traffic
+    #[serde(default)]
+    pub isolate: bool,
+}
+
+/// One inbound routing rule
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct Route {
+    #[serde(default)]
+    pub sources: Vec<RouteSource>,
+    #[serde(default)]
+    pub destinations: Vec<RouteDestination>,
+}
+
+/// Inbound routing rules of a service
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct RoutingRules {
+    #[serde(default)]
+    pub inbounds: Vec<Route>,
+}
+
+impl RoutingRules {
+    /// Instances a caller with `caller` metadata is routed to
+    ///
+    /// The first route with a source matching the caller applies (a route
+    /// without sources matches everyone). Its destinations are tried by
+    /// priority and the first with matching healthy instances wins. Without a
+    /// matching route, or when no destination has instances, all healthy
+    /// instances are returned. Unhealthy and isolated instances are never returned.
+    pub fn route(&self, caller: &HashMap<String, String>, instances: &[PolarisInstance]) -> Vec<PolarisInstance> {
+        let available: Vec<&PolarisInstance> = instances.iter().filter(|i| i.healthy && !i.isolate).collect();
+        let route = self.inbounds.iter().find(|route| {
+            route.sources.is_empty() || route.sources.iter().any(|source| metadata_matches(&source.metadata, caller))
+        });
+        if let Some(route) = route {
+            let mut destinations: Vec<&RouteDestination> =
+                route.destinations.iter().filter(|d| !d.isolate && d.weight > 0).collect();
+            destinations.sort_by_key(|d| d.priority);
+            for destination in destinations {
+                let matched: Vec<PolarisInstance> = available
+                    .iter()
+                    .filter(|instance| metadata_matches(&destination.metadata, &instance.metadata))
+                    .map(|instance| (*instance).clone())
+                    .collect();
+                if !matched.is_empty() {
+                    return matched;
+                }
+            }
+        }
+        available.into_iter().cloned().collect()
+    }
+}
+
+/// One amount of a rate-limit rule: `max_amount` requests per `window`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitAmount {
+    pub max_amount: u64,
+    pub window: Duration,
+}
+
+impl RateLimitAmount {
+    /// Tokens per second allowing the amount, rounded up and at least 1
+    fn refill_rate(&self) -> u64 {
+        let secs = self.window.as_secs_f64();
+        if secs <= 0.0 {
+            return self.max_amount.max(1);
+        }
+        ((self.max_amount as f64 / secs).ceil() as u64).max(1)
+    }
+}
+
+/// An enabled rate-limit rule of a service
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitRule {
+    /// Rule name, or its id when unnamed
+    pub name: String,
+    /// Never empty
+    pub amounts: Vec<RateLimitAmount>,
+}
+
+impl RateLimitRule {
+    /// Token bucket burst: the smallest amount of the rule
+    pub fn capacity(&self) -> u64 {
+        self.amounts.iter().map(|amount| amount.max_amount).min().unwrap_or(0)
+    }
+
+    /// Tokens added per second: the slowest amount of the rule
+    pub fn refill_rate(&self) -> u64 {
+        self.amounts.iter().map(RateLimitAmount::refill_rate).min().unwrap_or(1)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawRateLimit {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    disable: bool,
+    #[serde(default)]
+    amounts: Vec<RawAmount>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAmount {
+    max_amount: u64,
+    valid_duration: String,
+}
+
+impl RawRateLimit {
+    /// `None` for disabled rules and rules without a valid amount
+    fn into_rule(self) -> Option<RateLimitRule> {
+        if self.disable {
+            return None;
+        }
+        let amounts: Vec<RateLimitAmount> = self
+            .amounts
+            .into_iter()
+            .filter_map(|amount| {
+                let window = amount.valid_duration.parse::<rf_os::cfg::RfDuration>().ok()?.into();
+                Some(RateLimitAmount { max_amount: amount.max_amount, window })
+            })
+            .collect();
+        if amounts.is_empty() {
+            return None;
+        }
+        let name = if self.name.is_empty() { self.id } else { self.name };
+        Some(RateLimitRule { name, amounts })
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DiscoverResponse {
+    #[serde(default)]
+    instances: Vec<PolarisInstance>,
+    #[serde(default)]
+    routing: Option<RoutingRules>,
+    #[serde(default)]
+    rate_limit: Option<RawRateLimits>,
+}
+
+#[derive(Default, Deserialize)]
+struct RawRateLimits {
+    #[serde(default)]
+    rules: Vec<RawRateLimit>,
+}
+
+impl PolarisAdapter {
+    /// Ask the naming service for `kind` data of `service`
+    async fn discover_request(&self, kind: u32, service: &str) -> Result<DiscoverResponse> {
+        let url = format!("{}/v1/Discover", self.server_url);
+        let response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({
+                "type": kind,
+                "service": { "name": service, "namespace": self.namespace },
+            }))
+            .send()
+            .await
+            .map_err(|e| RfError::Config(format!("Polaris discover request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(RfError::Config(format!("Polaris discover returned {}", response.status())));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| RfError::Config(format!("Failed to parse Polaris discover response: {}", e)))
+    }
+
+    /// All instances of `service`, including unhealthy and isolated ones
+    pub async fn instances(&self, service: &str) -> Result<Vec<PolarisInstance>> {
+        Ok(self.discover_request(DISCOVER_INSTANCE, service).await?.instances)
+    }
+
+    /// Inbound routing rules of `service`
+    pub async fn routing(&self, service: &str) -> Result<RoutingRules> {
+        Ok(self.discover_request(DISCOVER_ROUTING, service).await?.routing.unwrap_or_default())
+    }
+
+    /// Healthy instances of `service` for a caller with `caller` metadata, see `RoutingRules::route`
+    pub async fn route(&self, service: &str, caller: &HashMap<String, String>) -> Result<Vec<PolarisInstance>> {
+        let (instances, routing) = tokio::try_join!(self.instances(service), self.routing(service))?;
+        Ok(routing.route(caller, &instances))
+    }
+
+    /// Enabled rate-limit rules of `service`
+    pub async fn rate_limits(&self, service: &str) -> Result<Vec<RateLimitRule>> {
+        let rules = self.discover_request(DISCOVER_RATE_LIMIT, service).await?.rate_limit.unwrap_or_default().rules;
+        Ok(rules.into_iter().filter_map(RawRateLimit::into_rule).collect())
+    }
+
+    /// Rate-limit rules of `service` as a config source, see `PolarisRateLimits`
+    pub fn rate_limits_source(&self, service: &str) -> PolarisRateLimits {
+        PolarisRateLimits {
+            adapter: self.clone(),
+            service: service.to_string(),
+        }
+    }
+}
+
+/// Rate-limit rules of a Polaris service as `rate_limit.{rule}.capacity` and
+/// `rate_limit.{rule}.refill_rate` keys
+///
+/// Mounted in a `Config` through `CenterConfigAdapter`, the rules feed the
+/// limiters of `gins::rate_limiter(Some(rule))`, which follow changes once
+/// the config reloads. Dots in rule names become `_`. Read-only.
+pub struct PolarisRateLimits {
+    adapter: PolarisAdapter,
+    service: String,
+}
+
+impl PolarisRateLimits {
+    async fn fetch(&self) -> Result<HashMap<String, String>> {
+        let rules = self.adapter.rate_limits(&self.service).await?;
+        let mut values = HashMap::new();
+        for rule in rules {
+            let name = rule.name.replace('.', "_");
+            values.insert(format!("rate_limit.{}.capacity", name), rule.capacity().to_string());
+            values.insert(format!("rate_limit.{}.refill_rate", name), rule.refill_rate().to_string());
+        }
+        Ok(values)
+    }
+}
+
+impl ConfigCenterAdapter for PolarisRateLimits {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.all()?.remove(key))
+    }
+
+    fn set(&self, _key: &str, _value: &str) -> Result<()> {
+        Err(RfError::Config("Polaris rate-limit rules are read-only".to_string()))
+    }
+
+    fn all(&self) -> Result<HashMap<String, String>> {
+        block_on(self.fetch())
+    }
+
+    fn watch<F>(&self, callback: F) -> Result<()>
+    where
+        F: Fn(&str, &str) -> Result<()> + Send + Sync + 'static,
+    {
+        let source = PolarisRateLimits {
+            adapter: self.adapter.clone(),
+            service: self.service.clone(),
+        };
+        tokio::spawn(async move {
+            let mut last: Option<HashMap<String, String>> = None;
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                let values = match source.fetch().await {
+                    Ok(values) => values,
+                    Err(e) => {
+                        tracing::warn!("Failed to fetch Polaris rate limits of {}: {}", source.service, e);
+                        continue;
+                    }
+                };
+                if let Some(last) = &last {
+                    let changed = values.iter().filter(|(key, value)| last.get(*key) != Some(*value));
+                    let removed = last.keys().filter(|key| !values.contains_key(*key)).map(|key| (key, ""));
+                    for (key, value) in changed.map(|(key, value)| (key, value.as_str())).chain(removed) {
+                        if let Err(e) = callback(key, value) {
+                            tracing::warn!("Polaris rate limit watch callback failed for key {}: {}", key, e);
+                        }
+                    }
+                }
+                last = Some(values);
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Run `future` to completion from sync code, inside or outside a Tokio runtime
+fn block_on<T: Send>(future: impl Future<Output = Result<T>> + Send) -> Result<T> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| RfError::Config(format!("Failed to create runtime: {}", e)))?
+                    .block_on(future)
+            })
+            .join()
+            .map_err(|_| RfError::Config("Polaris request thread panicked".to_string()))?
+    })
+}
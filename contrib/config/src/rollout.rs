@@ -0,0 +1,306 @@
+//! # rollout
+//!
+//! rollout 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Gray release of config center changes
+//!
+//! `Rollout` watches one key of a config center and applies new values on a
+//! share of the instances only, like Apollo's gray release:
+//!
+//! ```rust,ignore
+//! use rf_contrib_config::{ApolloAdapter, Rollout};
+//!
+//! let rollout = Rollout::new(ApolloAdapter::new(url, "myapp", "default", "application"), "pool.size", &hostname)
+//!     .percentage(10)
+//!     .validate(|value| value.parse::<u32>().map(|_| ()).map_err(|e| RfError::Config(e.to_string())))
+//!     .health(move || error_rate.load(Ordering::Relaxed) < 5)
+//!     .observe(Duration::from_secs(300));
+//! rollout.start(move |value| pool.resize(value.parse().unwrap()))?;
+//! ```
+//!
+//! Each change goes through these steps:
+//!
+//! 1. Instances outside the rollout skip it. An instance is in the rollout
+//!    when the hash of its id and the key, taken modulo 100, is below the
+//!    percentage, so the same instances get every change of a key and
+//!    raising the percentage only adds instances.
+//! 2. The validator rejects malformed values before they are applied.
+//! 3. The value is applied, then the health signal is sampled for the
+//!    observation window. If it reports unhealthy the previous value is
+//!    applied again and the new value is blocked until the center publishes
+//!    a different one.
+//!
+//! Rollbacks are local: the config center keeps the new value, so operators
+//! see it in the center and fix or revert it there.
+
+use super::bridge::normalize;
+use super::ConfigCenterAdapter;
+use rf_errors::Result;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Applies a value to the running instance
+type Apply = Arc<dyn Fn(&str) -> Result<()> + Send + Sync>;
+/// Checks a value before it is applied
+type Validator = Arc<dyn Fn(&str) -> Result<()> + Send + Sync>;
+/// Reports whether the instance is healthy
+type HealthSignal = Arc<dyn Fn() -> bool + Send + Sync>;
+
+/// Default time the health signal is watched after a change
+const DEFAULT_OBSERVE: Duration = Duration::from_secs(60);
+/// Default time between health samples
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What happened to the latest change of the key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RolloutEvent {
+    /// The instance is outside the rollout percentage
+    Skipped { value: String },
+    /// The validator or the apply callback refused the value
+    Rejected { value: String, error: String },
+    /// The value was applied and is being observed
+    Applied { value: String },
+    /// The value passed the observation window
+    Confirmed { value: String },
+    /// The health signal degraded and the previous value was restored
+    RolledBack { value: String, restored: Option<String> },
+}
+
+/// Rollout state of the key on this instance
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RolloutStatus {
+    /// Value currently applied, `None` before the first value
+    pub applied: Option<String>,
+    /// Value blocked after a failed validation or a rollback
+    pub blocked: Option<String>,
+    /// Latest event
+    pub last_event: Option<RolloutEvent>,
+}
+
+#[derive(Default)]
+struct State {
+    status: RolloutStatus,
+    /// Bumped on every change so an older observation stops when a newer value arrives
+    generation: u64,
+}
+
+/// Gray release of one config center key, see the module documentation
+pub struct Rollout<A> {
+    center: Arc<A>,
+    key: String,
+    instance_id: String,
+    percentage: u8,
+    validator: Option<Validator>,
+    health: Option<HealthSignal>,
+    observe: Duration,
+    interval: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+impl<A: ConfigCenterAdapter + 'static> Rollout<A> {
+    /// Roll out changes of `key` on the instance `instance_id` (100% by default)
+    ///
+    /// Keys written with `/` are matched as dotted keys, as in `CenterConfigAdapter`.
+    pub fn new(center: A, key: &str, instance_id: &str) -> Self {
+        Self {
+            center: Arc::new(center),
+            key: normalize(key),
+            instance_id: instance_id.to_string(),
+            percentage: 100,
+            validator: None,
+            health: None,
+            observe: DEFAULT_OBSERVE,
+            interval: DEFAULT_INTERVAL,
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Share of instances, 0 to 100, that apply changes
+    pub fn percentage(mut self, percentage: u8) -> Self {
+        self.percentage = percentage.min(100);
+        self
+    }
+
+    /// Check values before they are applied; an error rejects the value
+    pub fn validate<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&str) -> Result<()> + Send + Sync + 'static,
+    {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Health signal sampled after each change; `false` rolls the change back
+    pub fn health<F>(mut self, health: F) -> Self
+    where
+        F: Fn() -> bool + Send + Sync + 'static,
+    {
+        self.health = Some(Arc::new(health));
+        self
+    }
+
+    /// How long the health signal is watched after a change (default 60 seconds)
+    pub fn observe(mut self, window: Duration) -> Self {
+        self.observe = window;
+        self
+    }
+
+    /// Time between health samples (default 1 second)
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The wrapped config center
+    pub fn center(&self) -> &A {
+        &self.center
+    }
+
+    /// Whether this instance applies changes of the key
+    pub fn in_rollout(&self) -> bool {
+        bucket(&self.instance_id, &self.key) < self.percentage
+    }
+
+    /// Current rollout state
+    pub fn status(&self) -> RolloutStatus {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).status.clone()
+    }
+
+    /// Apply the current value, then watch the key and roll out its changes
+    ///
+    /// The current value is applied without observation: it is already live
+    /// on the other instances. Needs a Tokio runtime for the health checks.
+    pub fn start<F>(&self, apply: F) -> Result<()>
+    where
+        F: Fn(&str) -> Result<()> + Send + Sync + 'static,
+    {
+        let apply: Apply = Arc::new(apply);
+        let current = self
+            .center
+            .all()?
+            .into_iter()
+            .find(|(key, _)| normalize(key) == self.key)
+            .map(|(_, value)| value);
+        if let Some(value) = current {
+            apply(&value)?;
+            self.state.lock().unwrap_or_else(|e| e.into_inner()).status.applied = Some(value);
+        }
+
+        let rollout = Change {
+            key: self.key.clone(),
+            in_rollout: self.in_rollout(),
+            validator: self.validator.clone(),
+            health: self.health.clone(),
+            observe: self.observe,
+            interval: self.interval,
+            state: Arc::clone(&self.state),
+            apply,
+        };
+        let key = self.key.clone();
+        self.center.watch(move |changed, value| {
+            if normalize(changed) == key {
+                rollout.handle(value);
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Everything the watch callback needs to roll out one change
+struct Change {
+    key: String,
+    in_rollout: bool,
+    validator: Option<Validator>,
+    health: Option<HealthSignal>,
+    observe: Duration,
+    interval: Duration,
+    state: Arc<Mutex<State>>,
+    apply: Apply,
+}
+
+impl Change {
+    fn handle(&self, value: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let status = &state.status;
+        if status.applied.as_deref() == Some(value) || status.blocked.as_deref() == Some(value) {
+            return;
+        }
+        state.generation += 1;
+        let generation = state.generation;
+
+        if !self.in_rollout {
+            tracing::debug!("Config rollout of {} skipped: instance outside the rollout", self.key);
+            state.status.last_event = Some(RolloutEvent::Skipped { value: value.to_string() });
+            return;
+        }
+        let checked = match &self.validator {
+            Some(validator) => validator(value),
+            None => Ok(()),
+        };
+        if let Err(e) = checked.and_then(|()| (self.apply)(value)) {
+            tracing::warn!("Config rollout of {} rejected '{}': {}", self.key, value, e);
+            state.status.blocked = Some(value.to_string());
+            state.status.last_event = Some(RolloutEvent::Rejected {
+                value: value.to_string(),
+                error: e.to_string(),
+            });
+            return;
+        }
+
+        tracing::info!("Config rollout of {} applied '{}'", self.key, value);
+        let previous = state.status.applied.replace(value.to_string());
+        state.status.blocked = None;
+        state.status.last_event = Some(RolloutEvent::Applied { value: value.to_string() });
+        drop(state);
+
+        let health = self.health.clone();
+        let (state, apply, key) = (Arc::clone(&self.state), Arc::clone(&self.apply), self.key.clone());
+        let (observe, interval, value) = (self.observe, self.interval, value.to_string());
+        tokio::spawn(async move {
+            let deadline = tokio::time::Instant::now() + observe;
+            let healthy = loop {
+                if health.as_ref().is_some_and(|health| !health()) {
+                    break false;
+                }
+                if tokio::time::Instant::now() >= deadline {
+                    break true;
+                }
+                tokio::time::sleep(interval.min(deadline.saturating_duration_since(tokio::time::Instant::now()))).await;
+                if state.lock().unwrap_or_else(|e| e.into_inner()).generation != generation {
+                    return;
+                }
+            };
+
+            let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+            if state.generation != generation {
+                return;
+            }
+            if healthy {
+                state.status.last_event = Some(RolloutEvent::Confirmed { value });
+                return;
+            }
+            // An empty value is how the centers report a deleted key
+            let restore = previous.clone().unwrap_or_default();
+            if let Err(e) = apply(&restore) {
+                tracing::error!("Config rollout of {} failed to restore the previous value: {}", key, e);
+            }
+            tracing::warn!("Config rollout of {} rolled back '{}': health signal degraded", key, value);
+            state.status.applied = previous.clone();
+            state.status.blocked = Some(value.clone());
+            state.status.last_event = Some(RolloutEvent::RolledBack { value, restored: previous });
+        });
+    }
+}
+
+/// Stable bucket, 0 to 99, of an instance for a key (FNV-1a)
+pub fn bucket(instance_id: &str, key: &str) -> u8 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in instance_id.bytes().chain(std::iter::once(b'/')).chain(key.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash % 100) as u8
+}
@@ -0,0 +1,207 @@
+//! # vault
+//!
+//! vault 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! HashiCorp Vault secret provider
+//!
+//! Resolves `vault:<path>#<key>` configuration values through the Vault HTTP
+//! API. Both KV v2 (`vault:secret/data/myapp/db#password`) and KV v1
+//! (`vault:secret/myapp/db#password`) mounts are supported. Secrets are cached
+//! per path for a configurable TTL, so reading a config section does not hit
+//! Vault once per key.
+//!
+//! ```rust,no_run
+//! use rf_contrib_config::VaultSecrets;
+//! use rf_os::cfg::Config;
+//! use std::sync::Arc;
+//!
+//! # fn example() -> rf_errors::Result<()> {
+//! // VAULT_ADDR, VAULT_TOKEN and optionally VAULT_NAMESPACE
+//! let vault = VaultSecrets::from_env()?;
+//! let config = Config::layered("config.toml")?.with_secrets(Arc::new(vault));
+//! // database.default.password = "vault:secret/data/myapp/db#password"
+//! let password = config.get("database.default.password")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use rf_errors::{Result, RfError};
+use rf_os::cfg::SecretProvider;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Prefix of Vault secret references
+pub const VAULT_PREFIX: &str = "vault:";
+
+/// Key/value pairs of a secret and when they were fetched
+type CachedSecret = (Instant, HashMap<String, String>);
+
+/// Secret provider backed by HashiCorp Vault
+pub struct VaultSecrets {
+    addr: String,
+    token: String,
+    namespace: Option<String>,
+    ttl: Duration,
+    timeout: Duration,
+    cache: Mutex<HashMap<String, CachedSecret>>,
+}
+
+impl VaultSecrets {
+    /// Create a provider for the Vault server at `addr` (e.g. `https://vault:8200`)
+    pub fn new(addr: &str, token: &str) -> Self {
+        Self {
+            addr: addr.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            namespace: None,
+            ttl: Duration::from_secs(300),
+            timeout: Duration::from_secs(10),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create a provider from `VAULT_ADDR`, `VAULT_TOKEN` and `VAULT_NAMESPACE`
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let addr = var("VAULT_ADDR").ok_or_else(|| RfError::Config("VAULT_ADDR is not set".to_string()))?;
+        let token = var("VAULT_TOKEN").ok_or_else(|| RfError::Config("VAULT_TOKEN is not set".to_string()))?;
+        let mut secrets = Self::new(&addr, &token);
+        secrets.namespace = var("VAULT_NAMESPACE");
+        Ok(secrets)
+    }
+
+    /// Set the Vault Enterprise namespace
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// Set how long fetched secrets are cached (default 5 minutes, zero disables caching)
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the request timeout (default 10 seconds)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Drop cached secrets, e.g. after a rotation
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Read one key of the secret at `path`
+    pub fn read(&self, path: &str, key: &str) -> Result<String> {
+        let path = path.trim_matches('/');
+        let cached = {
+            let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            cache
+                .get(path)
+                .filter(|(fetched, _)| fetched.elapsed() < self.ttl)
+                .map(|(_, data)| data.clone())
+        };
+        let data = match cached {
+            Some(data) => data,
+            None => {
+                let data = self.fetch(path)?;
+                self.cache
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(path.to_string(), (Instant::now(), data.clone()));
+                data
+            }
+        };
+        data.get(key)
+            .cloned()
+            .ok_or_else(|| RfError::Config(format!("Vault secret {} has no key {}", path, key)))
+    }
+
+    /// Resolve a `vault:<path>#<key>` reference
+    fn resolve_reference(&self, reference: &str) -> Result<String> {
+        let (path, key) = reference
+            .trim_start_matches(VAULT_PREFIX)
+            .rsplit_once('#')
+            .filter(|(path, key)| !path.is_empty() && !key.is_empty())
+            .ok_or_else(|| RfError::Config(format!("Invalid Vault reference {}, expected vault:<path>#<key>", reference)))?;
+        self.read(path, key)
+    }
+
+    /// Fetch the key/value pairs of the secret at `path`
+    ///
+    /// Runs on its own thread and runtime: config reads are synchronous and
+    /// may happen both inside and outside of a tokio runtime.
+    fn fetch(&self, path: &str) -> Result<HashMap<String, String>> {
+        let url = format!("{}/v1/{}", self.addr, path);
+        let token = self.token.clone();
+        let namespace = self.namespace.clone();
+        let timeout = self.timeout;
+        let request = async move {
+            let client = reqwest::Client::builder()
+                .timeout(timeout)
+                .pool_max_idle_per_host(0)
+                .build()
+                .map_err(|e| RfError::Config(format!("Failed to create HTTP client: {}", e)))?;
+            let mut request = client.get(&url).header("X-Vault-Token", token);
+            if let Some(namespace) = namespace {
+                request = request.header("X-Vault-Namespace", namespace);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| RfError::Config(format!("Vault request failed: {}", e)))?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(RfError::Config(format!("Vault returned {} for {}", status, url)));
+            }
+            response
+                .json::<serde_json::Value>()
+                .await
+                .map_err(|e| RfError::Config(format!("Failed to parse Vault response: {}", e)))
+        };
+        let json = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .map_err(|e| RfError::Config(format!("Failed to create runtime: {}", e)))?
+                        .block_on(request)
+                })
+                .join()
+                .map_err(|_| RfError::Config("Vault request thread panicked".to_string()))?
+        })?;
+
+        // KV v2 nests the secret under data.data, KV v1 returns it as data
+        let data = json
+            .get("data")
+            .map(|data| match data.get("data") {
+                Some(inner) if data.get("metadata").is_some() => inner,
+                _ => data,
+            })
+            .and_then(|data| data.as_object())
+            .ok_or_else(|| RfError::Config(format!("Vault secret {} has no data", path)))?;
+        Ok(data
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (key.clone(), value)
+            })
+            .collect())
+    }
+}
+
+impl SecretProvider for VaultSecrets {
+    fn resolve(&self, value: &str) -> Option<Result<String>> {
+        let reference = value.trim();
+        reference.starts_with(VAULT_PREFIX).then(|| self.resolve_reference(reference))
+    }
+}
@@ -0,0 +1,143 @@
+//! Config center bridge tests
+
+use rf_contrib_config::{CenterConfigAdapter, ConfigCenterAdapter};
+use rf_errors::{Result, RfError};
+use rf_os::cfg::{Config, ConfigAdapter, MemoryConfigAdapter};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Callback = Box<dyn Fn(&str, &str) -> Result<()> + Send + Sync>;
+
+/// In-memory config center counting fetches; pushes go through `push`
+#[derive(Default)]
+struct FakeCenter {
+    values: Mutex<HashMap<String, String>>,
+    fetches: AtomicUsize,
+    down: AtomicBool,
+    watcher: Mutex<Option<Callback>>,
+}
+
+impl FakeCenter {
+    fn with(values: &[(&str, &str)]) -> Self {
+        let center = Self::default();
+        for (key, value) in values {
+            center.values.lock().unwrap().insert(key.to_string(), value.to_string());
+        }
+        center
+    }
+
+    fn push(&self, key: &str, value: &str) {
+        if let Some(callback) = self.watcher.lock().unwrap().as_ref() {
+            callback(key, value).unwrap();
+        }
+    }
+}
+
+impl ConfigCenterAdapter for FakeCenter {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.values.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<()> {
+        self.values.lock().unwrap().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn all(&self) -> Result<HashMap<String, String>> {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        if self.down.load(Ordering::SeqCst) {
+            return Err(RfError::Config("connection refused".to_string()));
+        }
+        Ok(self.values.lock().unwrap().clone())
+    }
+
+    fn watch<F>(&self, callback: F) -> Result<()>
+    where
+        F: Fn(&str, &str) -> Result<()> + Send + Sync + 'static,
+    {
+        *self.watcher.lock().unwrap() = Some(Box::new(callback));
+        Ok(())
+    }
+}
+
+#[test]
+fn test_reads_through_cache() {
+    let center = Arc::new(CenterConfigAdapter::new(FakeCenter::with(&[
+        ("database/default/url", "postgresql://center/app"),
+        ("log.level", "warn"),
+    ])));
+    let config = Config::new()
+        .adapter(Arc::new(MemoryConfigAdapter::from_values(HashMap::from([
+            ("database.default.url".to_string(), "sqlite://local.db".to_string()),
+            ("server.port".to_string(), "8080".to_string()),
+        ]))))
+        .adapter(center.clone());
+
+    assert_eq!(config.get("database.default.url").unwrap().as_deref(), Some("postgresql://center/app"));
+    assert_eq!(config.get("server.port").unwrap().as_deref(), Some("8080"));
+    assert_eq!(config.get("log.level").unwrap().as_deref(), Some("warn"));
+    assert_eq!(config.all().unwrap().len(), 3);
+    assert_eq!(center.center().fetches.load(Ordering::SeqCst), 1);
+
+    // Writes stay local
+    config.set("log.level", "debug").unwrap();
+    assert!(center.set("log.level", "debug").is_err());
+    assert_eq!(center.center().values.lock().unwrap()["log.level"], "warn");
+}
+
+#[test]
+fn test_ttl_and_stale_values() {
+    let center = CenterConfigAdapter::new(FakeCenter::with(&[("feature.beta", "off")])).ttl(Duration::from_millis(50));
+    assert_eq!(center.get("feature.beta").unwrap().as_deref(), Some("off"));
+
+    center.center().values.lock().unwrap().insert("feature.beta".into(), "on".into());
+    assert_eq!(center.get("feature.beta").unwrap().as_deref(), Some("off"));
+    std::thread::sleep(Duration::from_millis(60));
+    assert_eq!(center.get("feature.beta").unwrap().as_deref(), Some("on"));
+    assert_eq!(center.center().fetches.load(Ordering::SeqCst), 2);
+
+    // An unreachable center keeps serving what was fetched, without retrying on every read
+    center.center().down.store(true, Ordering::SeqCst);
+    std::thread::sleep(Duration::from_millis(60));
+    assert_eq!(center.get("feature.beta").unwrap().as_deref(), Some("on"));
+    assert_eq!(center.get("feature.beta").unwrap().as_deref(), Some("on"));
+    assert_eq!(center.center().fetches.load(Ordering::SeqCst), 3);
+    assert!(center.reload().is_err());
+
+    // Nothing to fall back on before the first fetch
+    let empty = CenterConfigAdapter::new(FakeCenter::default());
+    empty.center().down.store(true, Ordering::SeqCst);
+    assert!(empty.get("feature.beta").is_err());
+}
+
+#[test]
+fn test_watch_updates_and_reload() {
+    let center = Arc::new(CenterConfigAdapter::new(FakeCenter::with(&[("cache/ttl", "30s")])).ttl(Duration::from_secs(3600)));
+    let config = Arc::new(Config::new().adapter(center.clone()));
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let sink = changes.clone();
+    config.on_change("cache", move |c| sink.lock().unwrap().extend_from_slice(c));
+
+    let weak = Arc::downgrade(&config);
+    center
+        .watch_with(move |_| {
+            if let Some(config) = weak.upgrade() {
+                config.reload().unwrap();
+            }
+        })
+        .unwrap();
+    assert_eq!(config.get_duration("cache.ttl").unwrap(), Some(Duration::from_secs(30)));
+
+    // Pushed values apply immediately, ahead of the TTL
+    center.center().values.lock().unwrap().insert("cache/ttl".into(), "5m".into());
+    center.center().push("cache/ttl", "5m");
+    assert_eq!(config.get_duration("cache.ttl").unwrap(), Some(Duration::from_secs(300)));
+    assert_eq!(changes.lock().unwrap()[0].new.as_deref(), Some("5m"));
+
+    center.center().values.lock().unwrap().remove("cache/ttl");
+    center.center().push("cache/ttl", "");
+    assert_eq!(config.get("cache.ttl").unwrap(), None);
+    assert_eq!(changes.lock().unwrap().len(), 2);
+}
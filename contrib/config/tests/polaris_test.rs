@@ -0,0 +1,142 @@
+//! Polaris discovery, routing and rate-limit tests
+
+use rf_contrib_config::{ConfigCenterAdapter, MatchString, MatchType, PolarisAdapter, PolarisInstance, RoutingRules};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+
+const INSTANCES: &str = r#"{"instances":[
+    {"id":"a","host":"10.0.0.1","port":8080,"metadata":{"env":"gray","version":"2.0"}},
+    {"id":"b","host":"10.0.0.2","port":8080,"metadata":{"env":"prod","version":"1.0"}},
+    {"id":"c","host":"10.0.0.3","port":8080,"healthy":false,"metadata":{"env":"prod","version":"1.0"}},
+    {"id":"d","host":"10.0.0.4","port":8080,"isolate":true,"metadata":{"env":"gray","version":"2.0"}}
+]}"#;
+
+const ROUTING: &str = r#"{"routing":{"inbounds":[
+    {"sources":[{"metadata":{"env":{"type":"EXACT","value":"gray"}}}],
+     "destinations":[
+        {"metadata":{"version":{"type":"REGEX","value":"3\\..*"}},"priority":0},
+        {"metadata":{"env":{"value":"gray"}},"priority":1},
+        {"metadata":{"env":{"value":"prod"}},"priority":2}]}
+]}}"#;
+
+const RATE_LIMITS: &str = r#"{"rateLimit":{"rules":[
+    {"id":"r1","name":"orders.api","amounts":[{"maxAmount":100,"validDuration":"10s"},{"maxAmount":30,"validDuration":"1s"}]},
+    {"id":"r2","amounts":[{"maxAmount":5,"validDuration":"1s"}]},
+    {"id":"r3","name":"off","disable":true,"amounts":[{"maxAmount":1,"validDuration":"1s"}]}
+]}}"#;
+
+/// Minimal Polaris naming server: answers `POST /v1/Discover` by request type
+fn fake_polaris() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let request: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+            let known = request_line.contains("/v1/Discover") && request["service"]["name"] == "orders";
+            let (status, body) = match (known, request["type"].as_u64()) {
+                (true, Some(1)) => ("200 OK", INSTANCES),
+                (true, Some(3)) => ("200 OK", ROUTING),
+                (true, Some(4)) => ("200 OK", RATE_LIMITS),
+                _ => ("404 Not Found", "{}"),
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    addr
+}
+
+fn labels(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+}
+
+fn ids(instances: &[PolarisInstance]) -> Vec<&str> {
+    instances.iter().map(|instance| instance.id.as_str()).collect()
+}
+
+#[test]
+fn test_match_string() {
+    let condition = |kind, value: &str| MatchString { kind, value: value.to_string() };
+    assert!(condition(MatchType::Exact, "gray").matches(Some("gray")));
+    assert!(!condition(MatchType::Exact, "gray").matches(None));
+    assert!(condition(MatchType::Exact, "*").matches(Some("anything")));
+    assert!(condition(MatchType::Regex, "v2\\..*").matches(Some("v2.1")));
+    assert!(!condition(MatchType::Regex, "v2").matches(Some("v2.1")));
+    assert!(condition(MatchType::NotEquals, "prod").matches(None));
+    assert!(condition(MatchType::In, "a, b").matches(Some("b")));
+    assert!(condition(MatchType::NotIn, "a,b").matches(Some("c")));
+    assert!(!condition(MatchType::NotIn, "a,b").matches(Some("a")));
+}
+
+#[test]
+fn test_routing_rules() {
+    let instances: Vec<PolarisInstance> =
+        serde_json::from_value(serde_json::from_str::<serde_json::Value>(INSTANCES).unwrap()["instances"].clone()).unwrap();
+    let rules: RoutingRules =
+        serde_json::from_value(serde_json::from_str::<serde_json::Value>(ROUTING).unwrap()["routing"].clone()).unwrap();
+
+    // Priority 0 has no instances, priority 1 picks the healthy gray one
+    assert_eq!(ids(&rules.route(&labels(&[("env", "gray")]), &instances)), ["a"]);
+    // No matching route: every healthy, non-isolated instance
+    assert_eq!(ids(&rules.route(&labels(&[("env", "prod")]), &instances)), ["a", "b"]);
+    assert_eq!(ids(&RoutingRules::default().route(&HashMap::new(), &instances)), ["a", "b"]);
+    // Falls back to the next group when a group has no healthy instance
+    let prod_only: Vec<_> = instances.iter().filter(|i| i.id != "a").cloned().collect();
+    assert_eq!(ids(&rules.route(&labels(&[("env", "gray")]), &prod_only)), ["b"]);
+}
+
+#[tokio::test]
+async fn test_discover_and_route() {
+    let polaris = PolarisAdapter::new(&fake_polaris(), "Production", "app", "app.properties");
+    let instances = polaris.instances("orders").await.unwrap();
+    assert_eq!(instances.len(), 4);
+    assert_eq!(instances[0].address(), "10.0.0.1:8080");
+    assert_eq!(instances[0].weight, 100);
+
+    let routed = polaris.route("orders", &labels(&[("env", "gray")])).await.unwrap();
+    assert_eq!(ids(&routed), ["a"]);
+    assert!(polaris.instances("unknown").await.is_err());
+}
+
+#[tokio::test]
+async fn test_rate_limits_as_config() {
+    let polaris = PolarisAdapter::new(&fake_polaris(), "Production", "app", "app.properties");
+    let rules = polaris.rate_limits("orders").await.unwrap();
+    assert_eq!(rules.len(), 2);
+    // Both amounts hold: bursts of 30, 10 per second on average
+    assert_eq!((rules[0].name.as_str(), rules[0].capacity(), rules[0].refill_rate()), ("orders.api", 30, 10));
+
+    let source = polaris.rate_limits_source("orders");
+    let values = source.all().unwrap();
+    assert_eq!(values.len(), 4);
+    assert_eq!(values["rate_limit.orders_api.capacity"], "30");
+    assert_eq!(values["rate_limit.orders_api.refill_rate"], "10");
+    assert_eq!(values["rate_limit.r2.capacity"], "5");
+    assert_eq!(source.get("rate_limit.r2.refill_rate").unwrap().as_deref(), Some("5"));
+    assert!(source.set("rate_limit.r2.capacity", "1").is_err());
+}
@@ -0,0 +1,158 @@
+//! Config rollout tests
+
+use rf_contrib_config::{bucket, ConfigCenterAdapter, Rollout, RolloutEvent};
+use rf_errors::{Result, RfError};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Callback = Box<dyn Fn(&str, &str) -> Result<()> + Send + Sync>;
+type Applied = Arc<Mutex<Vec<String>>>;
+
+/// In-memory config center; pushes go through `push`
+#[derive(Default)]
+struct FakeCenter {
+    values: Mutex<HashMap<String, String>>,
+    watcher: Mutex<Option<Callback>>,
+}
+
+impl FakeCenter {
+    fn with(values: &[(&str, &str)]) -> Self {
+        let center = Self::default();
+        for (key, value) in values {
+            center.values.lock().unwrap().insert(key.to_string(), value.to_string());
+        }
+        center
+    }
+
+    fn push(&self, key: &str, value: &str) {
+        self.values.lock().unwrap().insert(key.to_string(), value.to_string());
+        if let Some(callback) = self.watcher.lock().unwrap().as_ref() {
+            callback(key, value).unwrap();
+        }
+    }
+}
+
+impl ConfigCenterAdapter for FakeCenter {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.values.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<()> {
+        self.values.lock().unwrap().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn all(&self) -> Result<HashMap<String, String>> {
+        Ok(self.values.lock().unwrap().clone())
+    }
+
+    fn watch<F>(&self, callback: F) -> Result<()>
+    where
+        F: Fn(&str, &str) -> Result<()> + Send + Sync + 'static,
+    {
+        *self.watcher.lock().unwrap() = Some(Box::new(callback));
+        Ok(())
+    }
+}
+
+/// Apply callback recording every applied value
+fn recorder() -> (Applied, impl Fn(&str) -> Result<()> + Send + Sync + 'static) {
+    let applied = Arc::new(Mutex::new(Vec::new()));
+    let log = applied.clone();
+    (applied, move |value: &str| {
+        log.lock().unwrap().push(value.to_string());
+        Ok(())
+    })
+}
+
+#[test]
+fn test_bucket_is_stable_and_spread() {
+    assert_eq!(bucket("host-1", "pool.size"), bucket("host-1", "pool.size"));
+    let instances: Vec<String> = (0..1000).map(|i| format!("host-{}", i)).collect();
+    let in_ten = instances.iter().filter(|id| bucket(id, "pool.size") < 10).count();
+    assert!((50..150).contains(&in_ten), "{}", in_ten);
+
+    // Raising the percentage only adds instances
+    let center = || FakeCenter::default();
+    for id in &instances {
+        let small = Rollout::new(center(), "pool.size", id).percentage(10).in_rollout();
+        let large = Rollout::new(center(), "pool/size", id).percentage(30).in_rollout();
+        assert!(!small || large);
+    }
+    assert!(!Rollout::new(center(), "pool.size", "host-1").percentage(0).in_rollout());
+}
+
+#[tokio::test]
+async fn test_validates_and_confirms() {
+    let rollout = Rollout::new(FakeCenter::with(&[("pool/size", "10")]), "pool.size", "host-1")
+        .validate(|value| {
+            value.parse::<u32>().map(|_| ()).map_err(|e| RfError::Config(e.to_string()))
+        })
+        .observe(Duration::from_millis(50))
+        .interval(Duration::from_millis(10));
+    let (applied, apply) = recorder();
+    rollout.start(apply).unwrap();
+    assert_eq!(*applied.lock().unwrap(), ["10"]);
+
+    rollout.center().push("pool/size", "many");
+    let status = rollout.status();
+    assert_eq!(status.applied.as_deref(), Some("10"));
+    assert_eq!(status.blocked.as_deref(), Some("many"));
+    assert!(matches!(status.last_event, Some(RolloutEvent::Rejected { .. })));
+
+    rollout.center().push("pool/size", "20");
+    rollout.center().push("other", "1");
+    assert_eq!(rollout.status().last_event, Some(RolloutEvent::Applied { value: "20".to_string() }));
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let status = rollout.status();
+    assert_eq!(status.last_event, Some(RolloutEvent::Confirmed { value: "20".to_string() }));
+    assert_eq!((status.applied.as_deref(), status.blocked), (Some("20"), None));
+    assert_eq!(*applied.lock().unwrap(), ["10", "20"]);
+}
+
+#[tokio::test]
+async fn test_rolls_back_when_health_degrades() {
+    let healthy = Arc::new(AtomicBool::new(true));
+    let signal = healthy.clone();
+    let rollout = Rollout::new(FakeCenter::with(&[("pool.size", "10")]), "pool.size", "host-1")
+        .health(move || signal.load(Ordering::SeqCst))
+        .observe(Duration::from_secs(5))
+        .interval(Duration::from_millis(10));
+    let (applied, apply) = recorder();
+    rollout.start(apply).unwrap();
+
+    rollout.center().push("pool.size", "500");
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    healthy.store(false, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let status = rollout.status();
+    assert_eq!(
+        status.last_event,
+        Some(RolloutEvent::RolledBack { value: "500".to_string(), restored: Some("10".to_string()) })
+    );
+    assert_eq!((status.applied.as_deref(), status.blocked.as_deref()), (Some("10"), Some("500")));
+    assert_eq!(*applied.lock().unwrap(), ["10", "500", "10"]);
+
+    // The blocked value is not retried, a different one is
+    healthy.store(true, Ordering::SeqCst);
+    rollout.center().push("pool.size", "500");
+    assert_eq!(applied.lock().unwrap().len(), 3);
+    rollout.center().push("pool.size", "20");
+    assert_eq!(rollout.status().applied.as_deref(), Some("20"));
+}
+
+#[tokio::test]
+async fn test_instances_outside_rollout_skip_changes() {
+    let rollout = Rollout::new(FakeCenter::with(&[("pool.size", "10")]), "pool.size", "host-1").percentage(0);
+    let (applied, apply) = recorder();
+    rollout.start(apply).unwrap();
+    rollout.center().push("pool.size", "20");
+
+    assert_eq!(*applied.lock().unwrap(), ["10"]);
+    let status = rollout.status();
+    assert_eq!(status.applied.as_deref(), Some("10"));
+    assert_eq!(status.last_event, Some(RolloutEvent::Skipped { value: "20".to_string() }));
+}
@@ -0,0 +1,96 @@
+//! Vault secret provider tests
+
+use rf_contrib_config::VaultSecrets;
+use rf_os::cfg::{Config, MemoryConfigAdapter, SecretProvider};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Minimal Vault: answers `GET /v1/<path>` from a fixed table, recording requests
+struct FakeVault {
+    addr: String,
+    requests: Arc<AtomicUsize>,
+    headers: Arc<Mutex<Vec<String>>>,
+}
+
+fn fake_vault() -> FakeVault {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let headers = Arc::new(Mutex::new(Vec::new()));
+    let (count, seen) = (requests.clone(), headers.clone());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                seen.lock().unwrap().push(line.trim().to_lowercase());
+            }
+            count.fetch_add(1, Ordering::SeqCst);
+            let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+            let (status, body) = match path {
+                "/v1/secret/data/myapp/db" => (
+                    "200 OK",
+                    r#"{"data":{"data":{"password":"s3cr3t","port":5432},"metadata":{"version":3}}}"#,
+                ),
+                "/v1/kv/myapp/api" => ("200 OK", r#"{"data":{"key":"abc123"}}"#),
+                _ => ("404 Not Found", r#"{"errors":[]}"#),
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    FakeVault { addr, requests, headers }
+}
+
+#[test]
+fn test_resolve_kv_v1_and_v2() {
+    let vault = fake_vault();
+    let secrets = VaultSecrets::new(&vault.addr, "root-token").namespace("team-a");
+
+    assert_eq!(secrets.resolve("vault:secret/data/myapp/db#password").unwrap().unwrap(), "s3cr3t");
+    assert_eq!(secrets.resolve("vault:secret/data/myapp/db#port").unwrap().unwrap(), "5432");
+    assert_eq!(secrets.resolve("vault:kv/myapp/api#key").unwrap().unwrap(), "abc123");
+    assert!(secrets.resolve("plain value").is_none());
+    assert!(secrets.resolve("vault:secret/data/myapp/db").unwrap().is_err());
+    assert!(secrets.resolve("vault:secret/data/myapp/db#missing").unwrap().is_err());
+    assert!(secrets.resolve("vault:secret/data/unknown#password").unwrap().is_err());
+
+    // One request per path while cached
+    assert_eq!(vault.requests.load(Ordering::SeqCst), 3);
+    let headers = vault.headers.lock().unwrap().clone();
+    assert!(headers.contains(&"x-vault-token: root-token".to_string()));
+    assert!(headers.contains(&"x-vault-namespace: team-a".to_string()));
+
+    secrets.clear_cache();
+    secrets.read("secret/data/myapp/db", "password").unwrap();
+    assert_eq!(vault.requests.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_config_integration_inside_runtime() {
+    let vault = fake_vault();
+    let adapter = MemoryConfigAdapter::new();
+    rf_os::cfg::ConfigAdapter::set(&adapter, "database.default.password", "vault:secret/data/myapp/db#password").unwrap();
+    let config = Config::new()
+        .adapter(Arc::new(adapter))
+        .with_secrets(Arc::new(VaultSecrets::new(&vault.addr, "root-token").ttl(Duration::ZERO)));
+
+    assert_eq!(config.get("database.default.password").unwrap().as_deref(), Some("s3cr3t"));
+    assert_eq!(config.all().unwrap()["database.default.password"], "s3cr3t");
+    assert_eq!(vault.requests.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,14 @@
+//! # backend
+//!
+//! backend 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Lock backend abstraction
+//!
+//! `LockBackend` and the in-process `MemoryBackend` are defined in
+//! `rf_os::lock`, so the cron scheduler can take its locks from the same
+//! backends without depending on this crate.
+
+pub use rf_os::lock::{LockBackend, MemoryBackend};
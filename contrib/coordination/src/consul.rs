@@ -0,0 +1,191 @@
+//! # consul
+//!
+//! consul 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Consul lock backend
+//!
+//! Each hold is a Consul session with a TTL; the lock key is acquired with
+//! that session and deleted when the session is invalidated. Consul accepts
+//! session TTLs between 10s and 24h and may keep an expired session alive for
+//! up to twice its TTL.
+
+use super::backend::LockBackend;
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use rf_errors::{Result, RfError};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Consul lock backend
+pub struct ConsulBackend {
+    client: Client,
+    base_url: String,
+    prefix: String,
+    token: Option<String>,
+    /// Session of each `(key, owner)` hold
+    sessions: Mutex<HashMap<(String, String), String>>,
+}
+
+impl ConsulBackend {
+    /// Create a backend for a Consul agent, e.g. `http://127.0.0.1:8500`
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            prefix: "rf/locks".to_string(),
+            token: None,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Key prefix (default `rf/locks`)
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.trim_matches('/').to_string();
+        self
+    }
+
+    /// ACL token
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    fn session(&self, key: &str, owner: &str) -> Option<String> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+            .get(&(key.to_string(), owner.to_string()))
+            .cloned()
+    }
+
+    fn set_session(&self, key: &str, owner: &str, session: Option<String>) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = (key.to_string(), owner.to_string());
+        match session {
+            Some(session) => sessions.insert(entry, session),
+            None => sessions.remove(&entry),
+        };
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        let builder = self.client.request(method, format!("{}{}", self.base_url, path));
+        match &self.token {
+            Some(token) => builder.header("X-Consul-Token", token),
+            None => builder,
+        }
+    }
+
+    async fn send(&self, builder: RequestBuilder) -> Result<(StatusCode, String)> {
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| RfError::Network(format!("Consul request failed: {}", e)))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to read Consul response: {}", e)))?;
+        if !status.is_success() && status != StatusCode::NOT_FOUND {
+            return Err(RfError::Internal(format!("Consul request failed ({}): {}", status.as_u16(), body.trim())));
+        }
+        Ok((status, body))
+    }
+
+    fn kv_path(&self, key: &str) -> String {
+        format!("/v1/kv/{}/{}", self.prefix, key)
+    }
+
+    async fn create_session(&self, key: &str, ttl: Duration) -> Result<String> {
+        let ttl = ttl.as_secs().clamp(10, 86400);
+        let builder = self.request(reqwest::Method::PUT, "/v1/session/create").json(&json!({
+            "Name": format!("{}/{}", self.prefix, key),
+            "TTL": format!("{}s", ttl),
+            "Behavior": "delete",
+            "LockDelay": "0s",
+        }));
+        let (_, body) = self.send(builder).await?;
+        let value: Value = serde_json::from_str(&body)
+            .map_err(|e| RfError::Internal(format!("Invalid Consul session response: {}", e)))?;
+        value.get("ID")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| RfError::Internal("Consul session create returned no ID".to_string()))
+    }
+
+    async fn destroy_session(&self, session: &str) -> Result<()> {
+        let path = format!("/v1/session/destroy/{}", session);
+        self.send(self.request(reqwest::Method::PUT, &path)).await.map(|_| ())
+    }
+}
+
+#[async_trait]
+impl LockBackend for ConsulBackend {
+    fn name(&self) -> &str {
+        "consul"
+    }
+
+    async fn try_acquire(&self, key: &str, owner: &str, ttl: Duration) -> Result<bool> {
+        let existing = self.session(key, owner);
+        let session = match &existing {
+            Some(session) => session.clone(),
+            None => self.create_session(key, ttl).await?,
+        };
+        let path = format!("{}?acquire={}", self.kv_path(key), session);
+        let (_, body) = self.send(self.request(reqwest::Method::PUT, &path).body(owner.to_string())).await?;
+        if body.trim() == "true" {
+            self.set_session(key, owner, Some(session));
+            return Ok(true);
+        }
+        self.set_session(key, owner, None);
+        self.destroy_session(&session).await?;
+        Ok(false)
+    }
+
+    async fn renew(&self, key: &str, owner: &str, _ttl: Duration) -> Result<bool> {
+        let Some(session) = self.session(key, owner) else {
+            return Ok(false);
+        };
+        let path = format!("/v1/session/renew/{}", session);
+        let (status, _) = self.send(self.request(reqwest::Method::PUT, &path)).await?;
+        if status == StatusCode::NOT_FOUND {
+            self.set_session(key, owner, None);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    async fn release(&self, key: &str, owner: &str) -> Result<bool> {
+        let Some(session) = self.session(key, owner) else {
+            return Ok(false);
+        };
+        self.set_session(key, owner, None);
+        let path = format!("{}?release={}", self.kv_path(key), session);
+        let (_, body) = self.send(self.request(reqwest::Method::PUT, &path)).await?;
+        self.destroy_session(&session).await?;
+        Ok(body.trim() == "true")
+    }
+
+    async fn holder(&self, key: &str) -> Result<Option<String>> {
+        let (status, body) = self.send(self.request(reqwest::Method::GET, &self.kv_path(key))).await?;
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let entries: Value = serde_json::from_str(&body)
+            .map_err(|e| RfError::Internal(format!("Invalid Consul KV response: {}", e)))?;
+        let Some(entry) = entries.as_array().and_then(|entries| entries.first()) else {
+            return Ok(None);
+        };
+        // A key without a session is left over from a released lock
+        if entry.get("Session").and_then(Value::as_str).is_none() {
+            return Ok(None);
+        }
+        Ok(entry.get("Value")
+            .and_then(Value::as_str)
+            .and_then(|value| general_purpose::STANDARD.decode(value).ok())
+            .and_then(|bytes| String::from_utf8(bytes).ok()))
+    }
+}
@@ -0,0 +1,171 @@
+//! # election
+//!
+//! election 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Leader election
+//!
+//! Candidates compete for a lock on the election key; the holder is the
+//! leader until it resigns, stops renewing or loses the lock.
+//!
+//! ```ignore
+//! let elector = LeaderElector::new(backend, "workers/reindex");
+//!
+//! // Runs on exactly one replica; if that replica dies another one
+//! // takes over after the TTL.
+//! elector.run(|| async {
+//!     loop {
+//!         reindex_pending().await;
+//!         tokio::time::sleep(Duration::from_secs(10)).await;
+//!     }
+//! }).await?;
+//! ```
+
+use super::backend::LockBackend;
+use super::mutex::{DistributedMutex, LockGuard};
+use rf_errors::Result;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Leader election over a lock backend
+pub struct LeaderElector {
+    mutex: DistributedMutex,
+    leader: watch::Sender<bool>,
+}
+
+impl LeaderElector {
+    /// Create a candidate for the election `key`
+    pub fn new(backend: Arc<dyn LockBackend>, key: &str) -> Self {
+        Self {
+            mutex: DistributedMutex::new(backend, key).retry_interval(Duration::from_secs(1)),
+            leader: watch::channel(false).0,
+        }
+    }
+
+    /// Leadership TTL (default 30s)
+    ///
+    /// Bounds how long the election stays without a leader after the leader
+    /// dies.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.mutex = self.mutex.ttl(ttl);
+        self
+    }
+
+    /// Delay between campaign attempts (default 1s)
+    pub fn retry_interval(mut self, interval: Duration) -> Self {
+        self.mutex = self.mutex.retry_interval(interval);
+        self
+    }
+
+    /// Election key
+    pub fn key(&self) -> &str {
+        self.mutex.key()
+    }
+
+    /// Whether this candidate is currently the leader
+    pub fn is_leader(&self) -> bool {
+        *self.leader.borrow()
+    }
+
+    /// Watch leadership changes of this candidate
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.leader.subscribe()
+    }
+
+    /// Current leader id, if any
+    pub async fn leader(&self) -> Result<Option<String>> {
+        self.mutex.holder().await
+    }
+
+    /// Wait until this candidate becomes leader
+    ///
+    /// Leadership lasts until the returned `Leadership` is resigned, dropped
+    /// or lost.
+    pub async fn campaign(&self) -> Result<Leadership> {
+        let guard = self.mutex.lock().await?;
+        tracing::info!("Became leader of {}", self.key());
+        self.leader.send_replace(true);
+        Ok(Leadership {
+            guard: Some(guard),
+            leader: self.leader.clone(),
+        })
+    }
+
+    /// Become leader if nobody else is
+    pub async fn try_campaign(&self) -> Result<Option<Leadership>> {
+        let Some(guard) = self.mutex.try_lock().await? else {
+            return Ok(None);
+        };
+        self.leader.send_replace(true);
+        Ok(Some(Leadership {
+            guard: Some(guard),
+            leader: self.leader.clone(),
+        }))
+    }
+
+    /// Run `task` while leader
+    ///
+    /// Campaigns, runs the task once elected and returns when it completes.
+    /// If leadership is lost the task is cancelled and the candidate
+    /// campaigns again. Dropping the returned future resigns.
+    pub async fn run<F, Fut>(&self, task: F) -> Result<()>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            let leadership = self.campaign().await?;
+            tokio::select! {
+                _ = task() => return leadership.resign().await,
+                _ = leadership.lost() => {
+                    tracing::warn!("Lost leadership of {}, campaigning again", self.key());
+                }
+            }
+        }
+    }
+}
+
+/// Held leadership
+pub struct Leadership {
+    guard: Option<LockGuard>,
+    leader: watch::Sender<bool>,
+}
+
+impl Leadership {
+    /// Leader id stored in the backend
+    pub fn id(&self) -> &str {
+        self.guard.as_ref().map(LockGuard::owner).unwrap_or_default()
+    }
+
+    /// Whether leadership is still held
+    pub fn is_held(&self) -> bool {
+        self.guard.as_ref().is_some_and(LockGuard::is_held)
+    }
+
+    /// Resolve once leadership has been lost
+    pub async fn lost(&self) {
+        match &self.guard {
+            Some(guard) => guard.lost().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Step down
+    pub async fn resign(mut self) -> Result<()> {
+        self.leader.send_replace(false);
+        match self.guard.take() {
+            Some(guard) => guard.unlock().await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Leadership {
+    fn drop(&mut self) {
+        self.leader.send_replace(false);
+    }
+}
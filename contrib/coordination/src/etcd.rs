@@ -0,0 +1,176 @@
+//! # etcd
+//!
+//! etcd 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! etcd lock backend
+//!
+//! Uses the v3 JSON gateway. Each hold is a key attached to a lease: the key
+//! is created in a transaction that only succeeds if it does not exist, and
+//! renewing the lock keeps the lease alive. When the holder stops renewing,
+//! the lease expires and etcd deletes the key.
+
+use super::backend::LockBackend;
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::Client;
+use rf_errors::{Result, RfError};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// etcd lock backend
+pub struct EtcdBackend {
+    client: Client,
+    base_url: String,
+    prefix: String,
+    /// Lease of each `(key, owner)` hold
+    leases: Mutex<HashMap<(String, String), String>>,
+}
+
+impl EtcdBackend {
+    /// Create a backend for an etcd endpoint, e.g. `http://127.0.0.1:2379`
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            prefix: "/rf/locks".to_string(),
+            leases: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Key prefix (default `/rf/locks`)
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        encode(&format!("{}/{}", self.prefix, key))
+    }
+
+    fn lease(&self, key: &str, owner: &str) -> Option<String> {
+        self.leases.lock().unwrap_or_else(|e| e.into_inner())
+            .get(&(key.to_string(), owner.to_string()))
+            .cloned()
+    }
+
+    fn set_lease(&self, key: &str, owner: &str, lease: Option<String>) {
+        let mut leases = self.leases.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = (key.to_string(), owner.to_string());
+        match lease {
+            Some(lease) => leases.insert(entry, lease),
+            None => leases.remove(&entry),
+        };
+    }
+
+    async fn call(&self, path: &str, body: Value) -> Result<Value> {
+        let response = self.client
+            .post(format!("{}{}", self.base_url, path))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| RfError::Network(format!("etcd request failed: {}", e)))?;
+        let status = response.status();
+        let value: Value = response
+            .json()
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to parse etcd response: {}", e)))?;
+        if !status.is_success() {
+            let message = value.get("message").and_then(Value::as_str).unwrap_or("");
+            return Err(RfError::Internal(format!("etcd request failed ({}): {}", status.as_u16(), message)));
+        }
+        Ok(value)
+    }
+
+    async fn revoke(&self, lease: &str) -> Result<()> {
+        self.call("/v3/lease/revoke", json!({ "ID": lease })).await.map(|_| ())
+    }
+}
+
+#[async_trait]
+impl LockBackend for EtcdBackend {
+    fn name(&self) -> &str {
+        "etcd"
+    }
+
+    async fn try_acquire(&self, key: &str, owner: &str, ttl: Duration) -> Result<bool> {
+        if self.lease(key, owner).is_some() && self.holder(key).await?.as_deref() == Some(owner) {
+            return self.renew(key, owner, ttl).await;
+        }
+
+        let grant = self.call("/v3/lease/grant", json!({ "TTL": ttl.as_secs().max(1) })).await?;
+        let lease = grant.get("ID").map(int64).ok_or_else(|| {
+            RfError::Internal("etcd lease grant returned no ID".to_string())
+        })?;
+        let full_key = self.full_key(key);
+        let txn = self.call("/v3/kv/txn", json!({
+            "compare": [{ "key": full_key, "target": "CREATE", "result": "EQUAL", "create_revision": "0" }],
+            "success": [{ "request_put": { "key": full_key, "value": encode(owner), "lease": lease } }],
+        })).await?;
+
+        if txn.get("succeeded").and_then(Value::as_bool) == Some(true) {
+            self.set_lease(key, owner, Some(lease));
+            Ok(true)
+        } else {
+            self.revoke(&lease).await?;
+            Ok(false)
+        }
+    }
+
+    async fn renew(&self, key: &str, owner: &str, _ttl: Duration) -> Result<bool> {
+        let Some(lease) = self.lease(key, owner) else {
+            return Ok(false);
+        };
+        let response = self.call("/v3/lease/keepalive", json!({ "ID": lease })).await?;
+        // An expired lease comes back without a TTL (or with TTL 0)
+        let ttl = response.get("result").and_then(|r| r.get("TTL")).map(int64);
+        if ttl.as_deref().is_none_or(|ttl| ttl == "0") {
+            self.set_lease(key, owner, None);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    async fn release(&self, key: &str, owner: &str) -> Result<bool> {
+        let full_key = self.full_key(key);
+        let txn = self.call("/v3/kv/txn", json!({
+            "compare": [{ "key": full_key, "target": "VALUE", "result": "EQUAL", "value": encode(owner) }],
+            "success": [{ "request_delete_range": { "key": full_key } }],
+        })).await?;
+        if let Some(lease) = self.lease(key, owner) {
+            self.set_lease(key, owner, None);
+            self.revoke(&lease).await?;
+        }
+        Ok(txn.get("succeeded").and_then(Value::as_bool) == Some(true))
+    }
+
+    async fn holder(&self, key: &str) -> Result<Option<String>> {
+        let range = self.call("/v3/kv/range", json!({ "key": self.full_key(key) })).await?;
+        let value = range.get("kvs")
+            .and_then(Value::as_array)
+            .and_then(|kvs| kvs.first())
+            .and_then(|kv| kv.get("value"))
+            .and_then(Value::as_str);
+        Ok(value.and_then(decode))
+    }
+}
+
+fn encode(text: &str) -> String {
+    general_purpose::STANDARD.encode(text.as_bytes())
+}
+
+fn decode(text: &str) -> Option<String> {
+    general_purpose::STANDARD.decode(text).ok().and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+/// The gateway encodes int64 fields as strings
+fn int64(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,30 @@
+//! # lib
+//!
+//! lib 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Coordination module
+//!
+//! Distributed locks and leader election:
+//! - `LockBackend` trait with etcd (leases), Consul (sessions), Redis
+//!   (feature `redis`) and in-memory backends
+//! - `DistributedMutex`: TTL lock renewed in the background while held
+//! - `LeaderElector`: leader election and singleton background workers
+
+pub mod backend;
+pub mod mutex;
+pub mod election;
+pub mod etcd;
+pub mod consul;
+#[cfg(feature = "redis")]
+pub mod redis;
+
+pub use backend::*;
+pub use mutex::*;
+pub use election::*;
+pub use etcd::*;
+pub use consul::*;
+#[cfg(feature = "redis")]
+pub use self::redis::*;
@@ -0,0 +1,247 @@
+//! # mutex
+//!
+//! mutex 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Distributed mutex
+//!
+//! ```ignore
+//! let mutex = DistributedMutex::new(Arc::new(EtcdBackend::new("http://127.0.0.1:2379")), "report")
+//!     .ttl(Duration::from_secs(15));
+//!
+//! if let Some(guard) = mutex.try_lock().await? {
+//!     build_report().await;
+//!     guard.unlock().await?;
+//! }
+//! ```
+//!
+//! While a guard is alive its lock is renewed every third of the TTL. If a
+//! renewal fails (network partition, backend restart) the guard is marked as
+//! lost; long-running holders should check `is_held()` or race their work
+//! against `lost()`.
+
+use super::backend::LockBackend;
+use rf_errors::{Result, RfError};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Lock on a key shared by several processes
+#[derive(Clone)]
+pub struct DistributedMutex {
+    backend: Arc<dyn LockBackend>,
+    key: String,
+    ttl: Duration,
+    retry_interval: Duration,
+}
+
+impl DistributedMutex {
+    /// Create a mutex for `key`
+    pub fn new(backend: Arc<dyn LockBackend>, key: &str) -> Self {
+        Self {
+            backend,
+            key: key.to_string(),
+            ttl: Duration::from_secs(30),
+            retry_interval: Duration::from_millis(200),
+        }
+    }
+
+    /// Lock TTL (default 30s)
+    ///
+    /// A holder that crashes keeps the lock until the TTL runs out.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl.max(Duration::from_millis(100));
+        self
+    }
+
+    /// Delay between attempts while waiting for the lock (default 200ms)
+    pub fn retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Locked key
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Backend of the mutex
+    pub fn backend(&self) -> &Arc<dyn LockBackend> {
+        &self.backend
+    }
+
+    /// Take the lock if it is free
+    pub async fn try_lock(&self) -> Result<Option<LockGuard>> {
+        let owner = new_owner();
+        if !self.backend.try_acquire(&self.key, &owner, self.ttl).await? {
+            return Ok(None);
+        }
+        Ok(Some(LockGuard::new(self.backend.clone(), self.key.clone(), owner, self.ttl)))
+    }
+
+    /// Wait until the lock is taken
+    pub async fn lock(&self) -> Result<LockGuard> {
+        loop {
+            if let Some(guard) = self.try_lock().await? {
+                return Ok(guard);
+            }
+            tokio::time::sleep(self.retry_interval).await;
+        }
+    }
+
+    /// Wait up to `timeout` for the lock
+    pub async fn lock_timeout(&self, timeout: Duration) -> Result<LockGuard> {
+        tokio::time::timeout(timeout, self.lock())
+            .await
+            .map_err(|_| RfError::Timeout(format!("Timed out waiting for lock {}", self.key)))?
+    }
+
+    /// Run `task` while holding the lock, waiting for it if needed
+    pub async fn with_lock<F, Fut, T>(&self, task: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let guard = self.lock().await?;
+        let result = task().await;
+        guard.unlock().await?;
+        Ok(result)
+    }
+
+    /// Run `task` only if the lock is free, returns `None` otherwise
+    pub async fn try_with_lock<F, Fut, T>(&self, task: F) -> Result<Option<T>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let Some(guard) = self.try_lock().await? else {
+            return Ok(None);
+        };
+        let result = task().await;
+        guard.unlock().await?;
+        Ok(Some(result))
+    }
+
+    /// Current holder of the lock
+    pub async fn holder(&self) -> Result<Option<String>> {
+        self.backend.holder(&self.key).await
+    }
+}
+
+/// Unique owner id: host name and a random suffix
+fn new_owner() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string());
+    format!("{}-{}-{}", host, std::process::id(), uuid::Uuid::new_v4().simple())
+}
+
+/// Held lock, released on `unlock` or drop
+pub struct LockGuard {
+    backend: Arc<dyn LockBackend>,
+    key: String,
+    owner: String,
+    lost: watch::Receiver<bool>,
+    keepalive: JoinHandle<()>,
+    released: bool,
+}
+
+impl LockGuard {
+    fn new(backend: Arc<dyn LockBackend>, key: String, owner: String, ttl: Duration) -> Self {
+        let (lost_tx, lost) = watch::channel(false);
+        let keepalive = tokio::spawn(keepalive(backend.clone(), key.clone(), owner.clone(), ttl, lost_tx));
+        Self {
+            backend,
+            key,
+            owner,
+            lost,
+            keepalive,
+            released: false,
+        }
+    }
+
+    /// Locked key
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Owner id stored in the backend
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Whether the lock is still held
+    pub fn is_held(&self) -> bool {
+        !*self.lost.borrow()
+    }
+
+    /// Resolve once the lock has been lost
+    pub async fn lost(&self) {
+        let mut lost = self.lost.clone();
+        // An error means the keepalive task ended without reporting a loss
+        let _ = lost.wait_for(|lost| *lost).await;
+        if !*lost.borrow() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Release the lock
+    pub async fn unlock(mut self) -> Result<()> {
+        self.released = true;
+        self.keepalive.abort();
+        self.backend.release(&self.key, &self.owner).await?;
+        Ok(())
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        self.keepalive.abort();
+        if self.released {
+            return;
+        }
+        // Release in the background; the TTL covers the case without a runtime
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let backend = self.backend.clone();
+            let key = std::mem::take(&mut self.key);
+            let owner = std::mem::take(&mut self.owner);
+            handle.spawn(async move {
+                if let Err(e) = backend.release(&key, &owner).await {
+                    tracing::warn!("Failed to release lock {}: {}", key, e);
+                }
+            });
+        }
+    }
+}
+
+/// Renew the lock every third of the TTL until it is lost
+async fn keepalive(
+    backend: Arc<dyn LockBackend>,
+    key: String,
+    owner: String,
+    ttl: Duration,
+    lost: watch::Sender<bool>,
+) {
+    let interval = ttl / 3;
+    let mut renewed_at = Instant::now();
+    loop {
+        tokio::time::sleep(interval).await;
+        match backend.renew(&key, &owner, ttl).await {
+            Ok(true) => renewed_at = Instant::now(),
+            Ok(false) => {
+                tracing::warn!("Lock {} was taken over", key);
+                break;
+            }
+            Err(e) if renewed_at.elapsed() + interval < ttl => {
+                tracing::warn!("Failed to renew lock {}: {}", key, e);
+            }
+            Err(e) => {
+                tracing::warn!("Lock {} expired after renewal failures: {}", key, e);
+                break;
+            }
+        }
+    }
+    let _ = lost.send(true);
+}
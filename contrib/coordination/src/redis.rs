@@ -0,0 +1,99 @@
+//! # redis
+//!
+//! redis 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Redis lock backend (feature `redis`)
+//!
+//! Keys are set with `SET NX PX`; renew and release are Lua scripts that
+//! check the owner first. This is the single-instance Redis lock, not
+//! Redlock: with a failover to a replica that missed the write, two holders
+//! are possible. Prefer etcd or Consul when that matters.
+
+use super::backend::LockBackend;
+use async_trait::async_trait;
+use rf_database::redis::RedisClient;
+use rf_errors::{Result, RfError};
+use std::sync::Arc;
+use std::time::Duration;
+
+const ACQUIRE: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    return 1
+end
+if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then
+    return 1
+end
+return 0
+"#;
+
+const RENEW: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+"#;
+
+const RELEASE: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Redis lock backend
+pub struct RedisBackend {
+    client: Arc<RedisClient>,
+    prefix: String,
+}
+
+impl RedisBackend {
+    pub fn new(client: Arc<RedisClient>) -> Self {
+        Self {
+            client,
+            prefix: "lock:".to_string(),
+        }
+    }
+
+    /// Key prefix (default `lock:`)
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    async fn eval(&self, script: &str, key: &str, args: &[&str]) -> Result<bool> {
+        let key = format!("{}{}", self.prefix, key);
+        let value = self.client.script().eval(script, &[&key], args).await?;
+        let result: i64 = redis::from_redis_value(value)
+            .map_err(|e| RfError::Database(format!("Unexpected Redis reply: {}", e)))?;
+        Ok(result == 1)
+    }
+}
+
+#[async_trait]
+impl LockBackend for RedisBackend {
+    fn name(&self) -> &str {
+        "redis"
+    }
+
+    async fn try_acquire(&self, key: &str, owner: &str, ttl: Duration) -> Result<bool> {
+        self.eval(ACQUIRE, key, &[owner, &ttl.as_millis().to_string()]).await
+    }
+
+    async fn renew(&self, key: &str, owner: &str, ttl: Duration) -> Result<bool> {
+        self.eval(RENEW, key, &[owner, &ttl.as_millis().to_string()]).await
+    }
+
+    async fn release(&self, key: &str, owner: &str) -> Result<bool> {
+        self.eval(RELEASE, key, &[owner]).await
+    }
+
+    async fn holder(&self, key: &str) -> Result<Option<String>> {
+        let key = format!("{}{}", self.prefix, key);
+        let value = self.client.script().eval("return redis.call('GET', KEYS[1])", &[&key], &[]).await?;
+        redis::from_redis_value(value).map_err(|e| RfError::Database(format!("Unexpected Redis reply: {}", e)))
+    }
+}
@@ -0,0 +1,193 @@
+//! Coordination module tests
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use rf_contrib_coordination::{ConsulBackend, DistributedMutex, EtcdBackend, LeaderElector, LockBackend, MemoryBackend};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_mutex_exclusive_and_lost() {
+    let backend = Arc::new(MemoryBackend::new());
+    let mutex = DistributedMutex::new(backend.clone(), "jobs").ttl(Duration::from_millis(300));
+
+    let guard = mutex.try_lock().await.unwrap().unwrap();
+    assert!(mutex.try_lock().await.unwrap().is_none());
+    assert_eq!(mutex.holder().await.unwrap().as_deref(), Some(guard.owner()));
+
+    // Renewal keeps the lock past its TTL
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(guard.is_held());
+    assert!(mutex.try_lock().await.unwrap().is_none());
+
+    backend.expire("jobs");
+    let other = mutex.try_lock().await.unwrap().unwrap();
+    tokio::time::timeout(Duration::from_secs(1), guard.lost()).await.unwrap();
+    assert!(!guard.is_held());
+
+    // The stale guard must not release the new holder's lock
+    guard.unlock().await.unwrap();
+    assert_eq!(mutex.holder().await.unwrap().as_deref(), Some(other.owner()));
+    other.unlock().await.unwrap();
+    assert_eq!(mutex.try_with_lock(|| async { 7 }).await.unwrap(), Some(7));
+}
+
+#[tokio::test]
+async fn test_leader_failover() {
+    let backend: Arc<dyn LockBackend> = Arc::new(MemoryBackend::new());
+    let runs = Arc::new(AtomicUsize::new(0));
+    let worker = |elector: LeaderElector| {
+        let runs = runs.clone();
+        tokio::spawn(async move {
+            elector.run(|| async {
+                runs.fetch_add(1, Ordering::SeqCst);
+                std::future::pending::<()>().await
+            }).await
+        })
+    };
+
+    let first = worker(LeaderElector::new(backend.clone(), "reindex").retry_interval(Duration::from_millis(20)));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let second = worker(LeaderElector::new(backend.clone(), "reindex").retry_interval(Duration::from_millis(20)));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+    // Cancelling the leader resigns; the standby takes over
+    first.abort();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+    second.abort();
+
+    let elector = LeaderElector::new(backend.clone(), "reindex");
+    let mut changes = elector.subscribe();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let leadership = elector.try_campaign().await.unwrap().unwrap();
+    assert!(elector.is_leader() && *changes.borrow_and_update());
+    assert_eq!(elector.leader().await.unwrap().as_deref(), Some(leadership.id()));
+    leadership.resign().await.unwrap();
+    assert!(!elector.is_leader());
+}
+
+type Kv = Arc<Mutex<HashMap<String, (String, String)>>>;
+
+#[tokio::test]
+async fn test_consul_backend() {
+    // key -> (value, session)
+    let kv: Kv = Arc::default();
+    let sessions = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/v1/session/create", put(|State((_, sessions)): State<(Kv, Arc<AtomicUsize>)>| async move {
+            Json(json!({ "ID": format!("s{}", sessions.fetch_add(1, Ordering::SeqCst)) }))
+        }))
+        .route("/v1/session/renew/{id}", put(|| async { Json(json!([{}])) }))
+        .route("/v1/session/destroy/{id}", put(|| async { "true" }))
+        .route("/v1/kv/{*key}", get(|State((kv, _)): State<(Kv, Arc<AtomicUsize>)>, Path(key): Path<String>| async move {
+            match kv.lock().unwrap().get(&key) {
+                Some((value, session)) => {
+                    use base64::Engine;
+                    let value = base64::engine::general_purpose::STANDARD.encode(value);
+                    Ok(Json(json!([{ "Key": key, "Value": value, "Session": session }])))
+                }
+                None => Err(axum::http::StatusCode::NOT_FOUND),
+            }
+        }).put(|State((kv, _)): State<(Kv, Arc<AtomicUsize>)>, Path(key): Path<String>,
+                Query(query): Query<HashMap<String, String>>, body: Bytes| async move {
+            let mut kv = kv.lock().unwrap();
+            if let Some(session) = query.get("acquire") {
+                if kv.get(&key).is_some_and(|(_, held)| held != session) {
+                    return "false";
+                }
+                kv.insert(key, (String::from_utf8(body.to_vec()).unwrap(), session.clone()));
+            } else if let Some(session) = query.get("release") {
+                if kv.get(&key).is_none_or(|(_, held)| held != session) {
+                    return "false";
+                }
+                kv.remove(&key);
+            }
+            "true"
+        }))
+        .with_state((kv.clone(), sessions.clone()));
+    let backend = ConsulBackend::new(&serve(app).await);
+
+    let ttl = Duration::from_secs(15);
+    assert!(backend.try_acquire("jobs", "a", ttl).await.unwrap());
+    assert!(!backend.try_acquire("jobs", "b", ttl).await.unwrap());
+    assert!(backend.try_acquire("jobs", "a", ttl).await.unwrap());
+    assert_eq!(backend.holder("jobs").await.unwrap().as_deref(), Some("a"));
+    assert!(kv.lock().unwrap().contains_key("rf/locks/jobs"));
+    assert!(backend.renew("jobs", "a", ttl).await.unwrap());
+    assert!(!backend.renew("jobs", "b", ttl).await.unwrap());
+    assert!(!backend.release("jobs", "b").await.unwrap());
+    assert!(backend.release("jobs", "a").await.unwrap());
+    assert_eq!(backend.holder("jobs").await.unwrap(), None);
+    assert_eq!(sessions.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_etcd_backend() {
+    // key -> (value, lease)
+    let kv: Kv = Arc::default();
+    let leases = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/v3/lease/grant", post(|State((_, leases)): State<(Kv, Arc<AtomicUsize>)>| async move {
+            Json(json!({ "ID": (leases.fetch_add(1, Ordering::SeqCst) + 1).to_string(), "TTL": "30" }))
+        }))
+        .route("/v3/lease/keepalive", post(|State((kv, _)): State<(Kv, Arc<AtomicUsize>)>, Json(body): Json<Value>| async move {
+            let id = body["ID"].as_str().unwrap().to_string();
+            let alive = kv.lock().unwrap().values().any(|(_, lease)| *lease == id);
+            Json(if alive { json!({ "result": { "ID": id, "TTL": "30" } }) } else { json!({ "result": { "ID": id } }) })
+        }))
+        .route("/v3/lease/revoke", post(|| async { Json(json!({})) }))
+        .route("/v3/kv/range", post(|State((kv, _)): State<(Kv, Arc<AtomicUsize>)>, Json(body): Json<Value>| async move {
+            let key = body["key"].as_str().unwrap();
+            let kvs: Vec<Value> = kv.lock().unwrap().get(key).map(|(value, _)| json!({ "key": key, "value": value })).into_iter().collect();
+            Json(json!({ "kvs": kvs }))
+        }))
+        .route("/v3/kv/txn", post(|State((kv, _)): State<(Kv, Arc<AtomicUsize>)>, Json(body): Json<Value>| async move {
+            let compare = &body["compare"][0];
+            let key = compare["key"].as_str().unwrap().to_string();
+            let mut kv = kv.lock().unwrap();
+            let succeeded = match compare["target"].as_str().unwrap() {
+                "CREATE" => !kv.contains_key(&key),
+                _ => kv.get(&key).is_some_and(|(value, _)| compare["value"] == *value),
+            };
+            if succeeded {
+                let op = &body["success"][0];
+                if let Some(put) = op.get("request_put") {
+                    kv.insert(key, (put["value"].as_str().unwrap().to_string(), put["lease"].as_str().unwrap().to_string()));
+                } else {
+                    kv.remove(&key);
+                }
+            }
+            Json(json!({ "succeeded": succeeded }))
+        }))
+        .with_state((kv.clone(), leases.clone()));
+    let backend = EtcdBackend::new(&serve(app).await).prefix("/locks");
+
+    let ttl = Duration::from_secs(30);
+    assert!(backend.try_acquire("jobs", "a", ttl).await.unwrap());
+    assert!(!backend.try_acquire("jobs", "b", ttl).await.unwrap());
+    assert_eq!(backend.holder("jobs").await.unwrap().as_deref(), Some("a"));
+    assert!(backend.renew("jobs", "a", ttl).await.unwrap());
+    assert!(!backend.release("jobs", "b").await.unwrap());
+    assert!(backend.release("jobs", "a").await.unwrap());
+    assert_eq!(backend.holder("jobs").await.unwrap(), None);
+
+    // A lease that expired server-side reports the lock as lost
+    assert!(backend.try_acquire("jobs", "c", ttl).await.unwrap());
+    kv.lock().unwrap().clear();
+    assert!(!backend.renew("jobs", "c", ttl).await.unwrap());
+    assert_eq!(leases.load(Ordering::SeqCst), 3);
+}
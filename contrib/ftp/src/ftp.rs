@@ -0,0 +1,434 @@
+//! # ftp
+//!
+//! ftp 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Async FTP/FTPS client
+//!
+//! Passive mode only (EPSV, falling back to PASV); data connections go to
+//! the control host, ignoring the address in PASV replies so that servers
+//! behind NAT work. With the `ftps` feature, `explicit_tls` upgrades the
+//! control connection with `AUTH TLS` and protects data with `PROT P`.
+//!
+//! ```ignore
+//! let client = FtpClient::connect(
+//!     FtpConfig::new("ftp.partner.com").login("acme", "secret").explicit_tls(),
+//! ).await?;
+//! client.upload_file(Path::new("report.csv"), "/inbox/report.csv").await?;
+//! client.quit().await?;
+//! ```
+
+use crate::transfer::{join, FileTransfer, RemoteEntry};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rf_errors::{Result, RfError};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+/// A control or data connection, plain or TLS
+trait Io: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Io for T {}
+
+/// FTP connection settings
+#[derive(Debug, Clone)]
+pub struct FtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    /// Connect and reply timeout
+    pub timeout: Duration,
+    /// Use explicit FTPS (`AUTH TLS`); requires the `ftps` feature
+    pub tls: bool,
+    /// Accept self-signed or otherwise invalid server certificates
+    pub accept_invalid_certs: bool,
+}
+
+impl FtpConfig {
+    /// Settings for `host:21` with anonymous login
+    pub fn new(host: &str) -> Self {
+        Self {
+            host: host.to_string(),
+            port: 21,
+            username: "anonymous".to_string(),
+            password: "anonymous@".to_string(),
+            timeout: Duration::from_secs(30),
+            tls: false,
+            accept_invalid_certs: false,
+        }
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn login(mut self, username: &str, password: &str) -> Self {
+        self.username = username.to_string();
+        self.password = password.to_string();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Upgrade to TLS with `AUTH TLS` before logging in
+    #[cfg(feature = "ftps")]
+    pub fn explicit_tls(mut self) -> Self {
+        self.tls = true;
+        self
+    }
+
+    /// Accept invalid server certificates (testing only)
+    #[cfg(feature = "ftps")]
+    pub fn accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+}
+
+struct Reply {
+    code: u16,
+    text: String,
+}
+
+struct Control {
+    stream: BufReader<Box<dyn Io>>,
+    timeout: Duration,
+}
+
+impl Control {
+    async fn read_reply(&mut self) -> Result<Reply> {
+        let read = async {
+            let mut lines: Vec<String> = Vec::new();
+            loop {
+                let mut line = String::new();
+                if self.stream.read_line(&mut line).await.map_err(RfError::Io)? == 0 {
+                    return Err(RfError::Network("FTP server closed the connection".to_string()));
+                }
+                let line = line.trim_end_matches(['\r', '\n']).to_string();
+                // "ddd-" starts a multi-line reply that ends with "ddd "
+                let done = match lines.first() {
+                    None => line.as_bytes().get(3) != Some(&b'-'),
+                    Some(first) => line.len() >= 4 && line.starts_with(&first[..3]) && line.as_bytes()[3] == b' ',
+                };
+                lines.push(line);
+                if done {
+                    break;
+                }
+            }
+            let code = lines[0].get(..3).and_then(|code| code.parse().ok())
+                .ok_or_else(|| RfError::Network(format!("Invalid FTP reply: {}", lines[0])))?;
+            Ok(Reply { code, text: lines.join("\n") })
+        };
+        tokio::time::timeout(self.timeout, read).await
+            .map_err(|_| RfError::Timeout("FTP server did not reply".to_string()))?
+    }
+
+    async fn command(&mut self, command: &str) -> Result<Reply> {
+        if command.starts_with("PASS ") {
+            tracing::trace!("FTP > PASS ****");
+        } else {
+            tracing::trace!("FTP > {}", command);
+        }
+        let stream = self.stream.get_mut();
+        stream.write_all(format!("{}\r\n", command).as_bytes()).await.map_err(RfError::Io)?;
+        stream.flush().await.map_err(RfError::Io)?;
+        self.read_reply().await
+    }
+
+    /// Send a command and require one of the `expected` reply codes
+    async fn expect(&mut self, command: &str, expected: &[u16]) -> Result<Reply> {
+        let reply = self.command(command).await?;
+        check(command, reply, expected)
+    }
+}
+
+fn check(command: &str, reply: Reply, expected: &[u16]) -> Result<Reply> {
+    if expected.contains(&reply.code) {
+        return Ok(reply);
+    }
+    let verb = command.split(' ').next().unwrap_or(command);
+    let message = format!("FTP {} failed: {}", verb, reply.text);
+    Err(match reply.code {
+        530 => RfError::Unauthorized(message),
+        550 => RfError::NotFound(message),
+        _ => RfError::Network(message),
+    })
+}
+
+/// Async FTP client with optional explicit TLS
+pub struct FtpClient {
+    config: Arc<FtpConfig>,
+    control: Mutex<Control>,
+    #[cfg(feature = "ftps")]
+    tls: Option<tokio_native_tls::TlsConnector>,
+}
+
+impl FtpClient {
+    /// Connect and log in
+    pub async fn connect(config: FtpConfig) -> Result<Self> {
+        #[cfg(not(feature = "ftps"))]
+        if config.tls {
+            return Err(RfError::Config("FTPS requires the `ftps` feature".to_string()));
+        }
+        let tcp = Self::dial(&config, config.port).await?;
+        let mut control = Control { stream: BufReader::new(Box::new(tcp)), timeout: config.timeout };
+        check("connect", control.read_reply().await?, &[220])?;
+
+        #[cfg(feature = "ftps")]
+        let tls = if config.tls {
+            let connector = native_tls::TlsConnector::builder()
+                .danger_accept_invalid_certs(config.accept_invalid_certs)
+                .build()
+                .map_err(|e| RfError::Network(format!("TLS setup failed: {}", e)))?;
+            let connector = tokio_native_tls::TlsConnector::from(connector);
+            control.expect("AUTH TLS", &[234]).await?;
+            let plain = control.stream.into_inner();
+            let secure = connector.connect(&config.host, plain).await
+                .map_err(|e| RfError::Network(format!("FTPS handshake failed: {}", e)))?;
+            control.stream = BufReader::new(Box::new(secure));
+            Some(connector)
+        } else {
+            None
+        };
+
+        let reply = control.expect(&format!("USER {}", config.username), &[230, 331]).await?;
+        if reply.code == 331 {
+            control.expect(&format!("PASS {}", config.password), &[230, 202]).await?;
+        }
+        if config.tls {
+            control.expect("PBSZ 0", &[200]).await?;
+            control.expect("PROT P", &[200]).await?;
+        }
+        control.expect("TYPE I", &[200]).await?;
+
+        Ok(Self {
+            config: Arc::new(config),
+            control: Mutex::new(control),
+            #[cfg(feature = "ftps")]
+            tls,
+        })
+    }
+
+    async fn dial(config: &FtpConfig, port: u16) -> Result<TcpStream> {
+        let addr = format!("{}:{}", config.host, port);
+        tokio::time::timeout(config.timeout, TcpStream::connect(&addr)).await
+            .map_err(|_| RfError::Timeout(format!("FTP connect to {} timed out", addr)))?
+            .map_err(|e| RfError::Network(format!("FTP connect to {} failed: {}", addr, e)))
+    }
+
+    /// Log out and close the connection
+    pub async fn quit(&self) -> Result<()> {
+        self.control.lock().await.expect("QUIT", &[221]).await.map(|_| ())
+    }
+
+    /// Send a raw command, returning the reply code and text
+    pub async fn raw(&self, command: &str) -> Result<(u16, String)> {
+        let reply = self.control.lock().await.command(command).await?;
+        Ok((reply.code, reply.text))
+    }
+
+    /// Enter passive mode and return the data port
+    async fn passive(&self, control: &mut Control) -> Result<u16> {
+        let reply = control.command("EPSV").await?;
+        if reply.code == 229 {
+            // 229 Entering Extended Passive Mode (|||6446|)
+            let port = reply.text.split('|').nth(3).and_then(|port| port.parse().ok());
+            return port.ok_or_else(|| RfError::Network(format!("Invalid EPSV reply: {}", reply.text)));
+        }
+        let reply = control.expect("PASV", &[227]).await?;
+        // 227 Entering Passive Mode (h1,h2,h3,h4,p1,p2)
+        let numbers: Vec<u16> = reply.text
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .skip(1)
+            .filter_map(|part| part.parse().ok())
+            .collect();
+        match numbers[..] {
+            [_, _, _, _, high, low] if high < 256 && low < 256 => Ok(high * 256 + low),
+            _ => Err(RfError::Network(format!("Invalid PASV reply: {}", reply.text))),
+        }
+    }
+
+    /// Open a data connection for `command`; the caller must read the final reply
+    async fn data(&self, control: &mut Control, command: &str) -> Result<Box<dyn Io>> {
+        let port = self.passive(control).await?;
+        let tcp = Self::dial(&self.config, port).await?;
+        control.expect(command, &[125, 150]).await?;
+        #[cfg(feature = "ftps")]
+        if let Some(connector) = &self.tls {
+            let secure = connector.connect(&self.config.host, tcp).await
+                .map_err(|e| RfError::Network(format!("FTPS data handshake failed: {}", e)))?;
+            return Ok(Box::new(secure));
+        }
+        Ok(Box::new(tcp))
+    }
+
+    /// Read a listing command's data connection to the end
+    async fn read_listing(&self, control: &mut Control, command: &str) -> Result<String> {
+        let mut data = self.data(control, command).await?;
+        let mut listing = Vec::new();
+        tokio::io::copy(&mut data, &mut listing).await.map_err(RfError::Io)?;
+        drop(data);
+        check(command, control.read_reply().await?, &[226, 250])?;
+        Ok(String::from_utf8_lossy(&listing).into_owned())
+    }
+}
+
+/// Parse an MLSD/MLST fact line: `type=file;size=12;modify=20240101120000; name`
+fn parse_facts(line: &str, dir: &str) -> Option<RemoteEntry> {
+    let (facts, name) = line.trim_start().split_once(' ')?;
+    let mut entry = RemoteEntry {
+        name: name.rsplit('/').next().unwrap_or(name).to_string(),
+        path: if name.starts_with('/') { name.to_string() } else { join(dir, name) },
+        is_dir: false,
+        size: 0,
+        modified: None,
+        permissions: None,
+    };
+    for fact in facts.split(';') {
+        let Some((key, value)) = fact.split_once('=') else { continue };
+        match key.to_ascii_lowercase().as_str() {
+            "type" => match value.to_ascii_lowercase().as_str() {
+                "dir" => entry.is_dir = true,
+                "cdir" | "pdir" => return None,
+                _ => {}
+            },
+            "size" => entry.size = value.parse().unwrap_or(0),
+            "modify" => {
+                entry.modified = NaiveDateTime::parse_from_str(value.split('.').next().unwrap_or(value), "%Y%m%d%H%M%S")
+                    .ok()
+                    .map(|time| DateTime::<Utc>::from_naive_utc_and_offset(time, Utc));
+            }
+            "unix.mode" => entry.permissions = u32::from_str_radix(value, 8).ok(),
+            _ => {}
+        }
+    }
+    Some(entry)
+}
+
+/// Parse a Unix-style LIST line: `-rw-r--r-- 1 owner group 1024 Jan 01 12:00 name`
+fn parse_list_line(line: &str, dir: &str) -> Option<RemoteEntry> {
+    let mut rest = line.trim_start();
+    let mut fields = Vec::new();
+    for _ in 0..8 {
+        let (field, tail) = rest.split_once(char::is_whitespace)?;
+        fields.push(field);
+        rest = tail.trim_start();
+    }
+    let mode = fields[0];
+    let name = rest.split(" -> ").next().unwrap_or(rest);
+    if name == "." || name == ".." || mode.len() < 10 {
+        return None;
+    }
+    let permissions = mode[1..10].chars().fold(0u32, |bits, c| (bits << 1) | u32::from(c != '-'));
+    Some(RemoteEntry {
+        name: name.to_string(),
+        path: join(dir, name),
+        is_dir: mode.starts_with('d'),
+        size: fields[4].parse().unwrap_or(0),
+        modified: None,
+        permissions: Some(permissions),
+    })
+}
+
+fn unsupported(e: &RfError) -> bool {
+    matches!(e, RfError::Network(message) if message.contains(": 500") || message.contains(": 502"))
+}
+
+#[async_trait]
+impl FileTransfer for FtpClient {
+    async fn list(&self, dir: &str) -> Result<Vec<RemoteEntry>> {
+        let mut control = self.control.lock().await;
+        match self.read_listing(&mut control, &format!("MLSD {}", dir)).await {
+            Ok(listing) => Ok(listing.lines().filter_map(|line| parse_facts(line, dir)).collect()),
+            Err(e) if unsupported(&e) => {
+                let listing = self.read_listing(&mut control, &format!("LIST {}", dir)).await?;
+                Ok(listing.lines().filter_map(|line| parse_list_line(line, dir)).collect())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn stat(&self, path: &str) -> Result<Option<RemoteEntry>> {
+        let mut control = self.control.lock().await;
+        let reply = control.command(&format!("MLST {}", path)).await?;
+        match reply.code {
+            250 => {
+                let dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+                let facts = reply.text.lines().find(|line| line.starts_with(' '))
+                    .and_then(|line| parse_facts(line, dir));
+                Ok(facts.map(|mut entry| {
+                    entry.path = path.to_string();
+                    entry
+                }))
+            }
+            550 => Ok(None),
+            500 | 502 => {
+                // Without MLST only files can be detected
+                let reply = control.command(&format!("SIZE {}", path)).await?;
+                match reply.code {
+                    213 => Ok(Some(RemoteEntry {
+                        name: path.rsplit('/').next().unwrap_or(path).to_string(),
+                        path: path.to_string(),
+                        is_dir: false,
+                        size: reply.text[4..].trim().parse().unwrap_or(0),
+                        modified: None,
+                        permissions: None,
+                    })),
+                    550 => Ok(None),
+                    _ => check("SIZE", reply, &[213]).map(|_| None),
+                }
+            }
+            _ => check("MLST", reply, &[250]).map(|_| None),
+        }
+    }
+
+    async fn mkdir(&self, path: &str) -> Result<()> {
+        self.control.lock().await.expect(&format!("MKD {}", path), &[257]).await.map(|_| ())
+    }
+
+    async fn remove(&self, path: &str) -> Result<()> {
+        self.control.lock().await.expect(&format!("DELE {}", path), &[250]).await.map(|_| ())
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<()> {
+        let mut control = self.control.lock().await;
+        control.expect(&format!("RNFR {}", from), &[350]).await?;
+        control.expect(&format!("RNTO {}", to), &[250]).await.map(|_| ())
+    }
+
+    async fn upload_from(&self, reader: &mut (dyn AsyncRead + Send + Unpin), path: &str, offset: u64) -> Result<u64> {
+        let mut control = self.control.lock().await;
+        // APPE continues at the current remote size
+        let command = if offset == 0 { format!("STOR {}", path) } else { format!("APPE {}", path) };
+        let mut data = self.data(&mut control, &command).await?;
+        let sent = tokio::io::copy(reader, &mut data).await.map_err(RfError::Io)?;
+        data.shutdown().await.map_err(RfError::Io)?;
+        drop(data);
+        check(&command, control.read_reply().await?, &[226, 250])?;
+        Ok(sent)
+    }
+
+    async fn download_to(&self, path: &str, writer: &mut (dyn AsyncWrite + Send + Unpin), offset: u64) -> Result<u64> {
+        let mut control = self.control.lock().await;
+        if offset > 0 {
+            control.expect(&format!("REST {}", offset), &[350]).await?;
+        }
+        let command = format!("RETR {}", path);
+        let mut data = self.data(&mut control, &command).await?;
+        let received = tokio::io::copy(&mut data, writer).await.map_err(RfError::Io)?;
+        drop(data);
+        check(&command, control.read_reply().await?, &[226, 250])?;
+        Ok(received)
+    }
+}
@@ -0,0 +1,24 @@
+//! # lib
+//!
+//! lib 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! FTP module
+//!
+//! File exchange with partner servers:
+//! - `FileTransfer` trait: list, stat, streamed upload/download and resume
+//! - `SftpClient` (feature `sftp`, default): SFTP with password, key file,
+//!   in-memory key or ssh-agent auth and host key verification
+//! - `FtpClient`: passive-mode FTP, with explicit TLS under feature `ftps`
+
+pub mod transfer;
+pub mod ftp;
+#[cfg(feature = "sftp")]
+pub mod sftp;
+
+pub use transfer::*;
+pub use ftp::*;
+#[cfg(feature = "sftp")]
+pub use sftp::*;
@@ -0,0 +1,352 @@
+//! # sftp
+//!
+//! sftp 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Async SFTP client
+//!
+//! Built on libssh2. Blocking libssh2 calls run on the Tokio blocking pool;
+//! transfers stream through a bounded channel, so files are never held in
+//! memory as a whole. Operations on one client are serialized.
+//!
+//! ```ignore
+//! let client = SftpClient::connect(
+//!     SftpConfig::new("sftp.partner.com", "acme")
+//!         .private_key_file("/etc/rf/keys/partner_ed25519", None)
+//!         .host_key(HostKeyCheck::Fingerprint("SHA256:nThbg6kXUpJWGl7E1IGOCspRomTxdCARLviKw6E5SY8".into())),
+//! ).await?;
+//!
+//! for entry in client.list("/outbox").await? {
+//!     client.resume_download(&entry.path, Path::new("/data/inbox").join(&entry.name).as_path()).await?;
+//! }
+//! ```
+
+use crate::transfer::{join, FileTransfer, RemoteEntry, CHUNK_SIZE};
+use async_trait::async_trait;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use rf_errors::{Result, RfError};
+use ssh2::{CheckResult, ErrorCode, FileStat, HashType, KnownHostFileKind, OpenFlags, OpenType, Session, Sftp};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+/// libssh2 status for a missing file
+const SFTP_NO_SUCH_FILE: i32 = 2;
+
+/// How the client authenticates
+#[derive(Debug, Clone)]
+pub enum SftpAuth {
+    Password(String),
+    /// Private key file, with optional passphrase
+    KeyFile { private_key: PathBuf, passphrase: Option<String> },
+    /// PEM/OpenSSH private key held in memory (e.g. from a secret store)
+    KeyMemory { private_key: String, passphrase: Option<String> },
+    /// Keys offered by a running ssh-agent
+    Agent,
+}
+
+/// How the server's host key is verified
+#[derive(Debug, Clone)]
+pub enum HostKeyCheck {
+    /// Look the host up in an OpenSSH known_hosts file
+    KnownHosts(PathBuf),
+    /// Expect this SHA-256 fingerprint, as printed by `ssh-keygen -lf` (`SHA256:...`)
+    Fingerprint(String),
+    /// Accept any host key (testing only)
+    AcceptAny,
+}
+
+/// SFTP connection settings
+#[derive(Debug, Clone)]
+pub struct SftpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub auth: SftpAuth,
+    pub host_key: HostKeyCheck,
+    /// Connect and operation timeout
+    pub timeout: Duration,
+}
+
+impl SftpConfig {
+    /// Settings for `username@host:22` using ssh-agent and `~/.ssh/known_hosts`
+    pub fn new(host: &str, username: &str) -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        Self {
+            host: host.to_string(),
+            port: 22,
+            username: username.to_string(),
+            auth: SftpAuth::Agent,
+            host_key: HostKeyCheck::KnownHosts(home.join(".ssh").join("known_hosts")),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn password(mut self, password: &str) -> Self {
+        self.auth = SftpAuth::Password(password.to_string());
+        self
+    }
+
+    /// Authenticate with a private key file
+    pub fn private_key_file(mut self, path: impl Into<PathBuf>, passphrase: Option<&str>) -> Self {
+        self.auth = SftpAuth::KeyFile { private_key: path.into(), passphrase: passphrase.map(str::to_string) };
+        self
+    }
+
+    /// Authenticate with an in-memory private key
+    pub fn private_key(mut self, key: &str, passphrase: Option<&str>) -> Self {
+        self.auth = SftpAuth::KeyMemory { private_key: key.to_string(), passphrase: passphrase.map(str::to_string) };
+        self
+    }
+
+    pub fn host_key(mut self, check: HostKeyCheck) -> Self {
+        self.host_key = check;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+struct Connection {
+    // Kept alive for the SFTP channel
+    _session: Session,
+    sftp: Sftp,
+}
+
+/// Async SFTP client
+#[derive(Clone)]
+pub struct SftpClient {
+    connection: Arc<Mutex<Connection>>,
+}
+
+fn ssh_error(context: &str, e: ssh2::Error) -> RfError {
+    RfError::Network(format!("SFTP {}: {}", context, e))
+}
+
+fn is_missing(e: &ssh2::Error) -> bool {
+    e.code() == ErrorCode::SFTP(SFTP_NO_SUCH_FILE)
+}
+
+fn entry(path: String, stat: &FileStat) -> RemoteEntry {
+    let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+    RemoteEntry {
+        name,
+        is_dir: stat.is_dir(),
+        size: stat.size.unwrap_or(0),
+        modified: stat.mtime.and_then(|mtime| DateTime::<Utc>::from_timestamp(mtime as i64, 0)),
+        permissions: stat.perm.map(|perm| perm & 0o7777),
+        path,
+    }
+}
+
+impl SftpClient {
+    /// Connect, verify the host key and authenticate
+    pub async fn connect(config: SftpConfig) -> Result<Self> {
+        let connection = tokio::task::spawn_blocking(move || Self::open(&config))
+            .await
+            .map_err(|e| RfError::Internal(format!("SFTP connect task failed: {}", e)))??;
+        Ok(Self { connection: Arc::new(Mutex::new(connection)) })
+    }
+
+    fn open(config: &SftpConfig) -> Result<Connection> {
+        let addr = (config.host.as_str(), config.port).to_socket_addrs()
+            .map_err(|e| RfError::Network(format!("Cannot resolve {}: {}", config.host, e)))?
+            .next()
+            .ok_or_else(|| RfError::Network(format!("Cannot resolve {}", config.host)))?;
+        let tcp = TcpStream::connect_timeout(&addr, config.timeout)
+            .map_err(|e| RfError::Network(format!("SFTP connect to {} failed: {}", addr, e)))?;
+        let mut session = Session::new().map_err(|e| ssh_error("session", e))?;
+        session.set_timeout(config.timeout.as_millis().min(u32::MAX as u128) as u32);
+        session.set_tcp_stream(tcp);
+        session.handshake().map_err(|e| ssh_error("handshake", e))?;
+        Self::verify_host_key(&session, config)?;
+
+        let user = config.username.as_str();
+        let result = match &config.auth {
+            SftpAuth::Password(password) => session.userauth_password(user, password),
+            SftpAuth::KeyFile { private_key, passphrase } =>
+                session.userauth_pubkey_file(user, None, private_key, passphrase.as_deref()),
+            SftpAuth::KeyMemory { private_key, passphrase } =>
+                session.userauth_pubkey_memory(user, None, private_key, passphrase.as_deref()),
+            SftpAuth::Agent => session.userauth_agent(user),
+        };
+        if result.is_err() || !session.authenticated() {
+            return Err(RfError::Unauthorized(format!("SFTP authentication failed for {}@{}", user, config.host)));
+        }
+        let sftp = session.sftp().map_err(|e| ssh_error("subsystem", e))?;
+        Ok(Connection { _session: session, sftp })
+    }
+
+    fn verify_host_key(session: &Session, config: &SftpConfig) -> Result<()> {
+        match &config.host_key {
+            HostKeyCheck::AcceptAny => Ok(()),
+            HostKeyCheck::Fingerprint(expected) => {
+                let hash = session.host_key_hash(HashType::Sha256)
+                    .ok_or_else(|| RfError::Network("SFTP server sent no host key".to_string()))?;
+                let actual = format!("SHA256:{}", base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash));
+                if actual == expected.trim_end_matches('=') {
+                    Ok(())
+                } else {
+                    Err(RfError::Forbidden(format!("Host key {} of {} does not match {}", actual, config.host, expected)))
+                }
+            }
+            HostKeyCheck::KnownHosts(path) => {
+                let (key, _) = session.host_key()
+                    .ok_or_else(|| RfError::Network("SFTP server sent no host key".to_string()))?;
+                let mut known = session.known_hosts().map_err(|e| ssh_error("known_hosts", e))?;
+                known.read_file(path, KnownHostFileKind::OpenSSH)
+                    .map_err(|e| RfError::Config(format!("Cannot read known_hosts {}: {}", path.display(), e)))?;
+                match known.check_port(&config.host, config.port, key) {
+                    CheckResult::Match => Ok(()),
+                    CheckResult::Mismatch => Err(RfError::Forbidden(format!("Host key of {} has changed", config.host))),
+                    CheckResult::NotFound => Err(RfError::Forbidden(format!("{} is not in {}", config.host, path.display()))),
+                    CheckResult::Failure => Err(RfError::Network("Host key check failed".to_string())),
+                }
+            }
+        }
+    }
+
+    /// Run a blocking libssh2 operation on the shared connection
+    async fn run<T, F>(&self, operation: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Sftp) -> Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let connection = connection.lock().map_err(|_| RfError::Internal("SFTP connection poisoned".to_string()))?;
+            operation(&connection.sftp)
+        })
+        .await
+        .map_err(|e| RfError::Internal(format!("SFTP task failed: {}", e)))?
+    }
+}
+
+#[async_trait]
+impl FileTransfer for SftpClient {
+    async fn list(&self, dir: &str) -> Result<Vec<RemoteEntry>> {
+        let dir = dir.to_string();
+        self.run(move |sftp| {
+            let entries = sftp.readdir(Path::new(&dir)).map_err(|e| ssh_error("readdir", e))?;
+            Ok(entries.iter()
+                .filter_map(|(path, stat)| {
+                    let name = path.file_name()?.to_string_lossy().into_owned();
+                    (name != "." && name != "..").then(|| entry(join(&dir, &name), stat))
+                })
+                .collect())
+        }).await
+    }
+
+    async fn stat(&self, path: &str) -> Result<Option<RemoteEntry>> {
+        let path = path.to_string();
+        self.run(move |sftp| match sftp.stat(Path::new(&path)) {
+            Ok(stat) => Ok(Some(entry(path, &stat))),
+            Err(e) if is_missing(&e) => Ok(None),
+            Err(e) => Err(ssh_error("stat", e)),
+        }).await
+    }
+
+    async fn mkdir(&self, path: &str) -> Result<()> {
+        let path = path.to_string();
+        self.run(move |sftp| sftp.mkdir(Path::new(&path), 0o755).map_err(|e| ssh_error("mkdir", e))).await
+    }
+
+    async fn remove(&self, path: &str) -> Result<()> {
+        let path = path.to_string();
+        self.run(move |sftp| match sftp.unlink(Path::new(&path)) {
+            Err(e) if is_missing(&e) => Err(RfError::NotFound(format!("Remote file {} not found", path))),
+            result => result.map_err(|e| ssh_error("remove", e)),
+        }).await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<()> {
+        let (from, to) = (from.to_string(), to.to_string());
+        self.run(move |sftp| sftp.rename(Path::new(&from), Path::new(&to), None).map_err(|e| ssh_error("rename", e))).await
+    }
+
+    async fn upload_from(&self, reader: &mut (dyn AsyncRead + Send + Unpin), path: &str, offset: u64) -> Result<u64> {
+        let (sender, mut chunks) = mpsc::channel::<Vec<u8>>(4);
+        let path = path.to_string();
+        let writer = self.run(move |sftp| {
+            let mut flags = OpenFlags::WRITE | OpenFlags::CREATE;
+            if offset == 0 {
+                flags |= OpenFlags::TRUNCATE;
+            }
+            let mut file = sftp.open_mode(Path::new(&path), flags, 0o644, OpenType::File)
+                .map_err(|e| ssh_error("open", e))?;
+            file.seek(SeekFrom::Start(offset)).map_err(RfError::Io)?;
+            while let Some(chunk) = chunks.blocking_recv() {
+                file.write_all(&chunk).map_err(RfError::Io)?;
+            }
+            file.flush().map_err(RfError::Io)
+        });
+
+        let feed = async {
+            let mut total = 0u64;
+            let mut buffer = vec![0u8; CHUNK_SIZE];
+            loop {
+                let read = reader.read(&mut buffer).await.map_err(RfError::Io)?;
+                if read == 0 {
+                    break;
+                }
+                total += read as u64;
+                // A closed channel means the writer failed; its error is reported below
+                if sender.send(buffer[..read].to_vec()).await.is_err() {
+                    break;
+                }
+            }
+            drop(sender);
+            Ok::<u64, RfError>(total)
+        };
+        let (total, written) = tokio::join!(feed, writer);
+        written?;
+        total
+    }
+
+    async fn download_to(&self, path: &str, writer: &mut (dyn AsyncWrite + Send + Unpin), offset: u64) -> Result<u64> {
+        let (sender, mut chunks) = mpsc::channel::<Vec<u8>>(4);
+        let path = path.to_string();
+        let reader = self.run(move |sftp| {
+            let mut file = match sftp.open(Path::new(&path)) {
+                Ok(file) => file,
+                Err(e) if is_missing(&e) => return Err(RfError::NotFound(format!("Remote file {} not found", path))),
+                Err(e) => return Err(ssh_error("open", e)),
+            };
+            file.seek(SeekFrom::Start(offset)).map_err(RfError::Io)?;
+            let mut buffer = vec![0u8; CHUNK_SIZE];
+            loop {
+                let read = file.read(&mut buffer).map_err(RfError::Io)?;
+                if read == 0 || sender.blocking_send(buffer[..read].to_vec()).is_err() {
+                    return Ok(());
+                }
+            }
+        });
+
+        let drain = async {
+            let mut total = 0u64;
+            while let Some(chunk) = chunks.recv().await {
+                writer.write_all(&chunk).await.map_err(RfError::Io)?;
+                total += chunk.len() as u64;
+            }
+            Ok::<u64, RfError>(total)
+        };
+        let (total, read) = tokio::join!(drain, reader);
+        read?;
+        total
+    }
+}
@@ -0,0 +1,129 @@
+//! # transfer
+//!
+//! transfer 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Protocol-independent file transfer API
+//!
+//! `SftpClient` and `FtpClient` both implement `FileTransfer`, so partner
+//! integrations can switch protocols by configuration.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rf_errors::{Result, RfError};
+use std::path::Path;
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+/// Chunk size for streamed transfers
+pub(crate) const CHUNK_SIZE: usize = 32 * 1024;
+
+/// A file or directory on the remote server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEntry {
+    /// File name without directory
+    pub name: String,
+    /// Full remote path
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+    /// Unix permission bits, when the server reports them
+    pub permissions: Option<u32>,
+}
+
+/// Join a remote directory and a name with `/`
+pub(crate) fn join(dir: &str, name: &str) -> String {
+    match dir.trim_end_matches('/') {
+        "" if dir.starts_with('/') => format!("/{}", name),
+        "" => name.to_string(),
+        dir => format!("{}/{}", dir, name),
+    }
+}
+
+/// Remote file operations shared by the SFTP and FTP clients
+#[async_trait]
+pub trait FileTransfer: Send + Sync {
+    /// List a directory (without `.` and `..`)
+    async fn list(&self, dir: &str) -> Result<Vec<RemoteEntry>>;
+
+    /// Describe a path; `None` if it does not exist
+    async fn stat(&self, path: &str) -> Result<Option<RemoteEntry>>;
+
+    /// Create a directory
+    async fn mkdir(&self, path: &str) -> Result<()>;
+
+    /// Delete a file
+    async fn remove(&self, path: &str) -> Result<()>;
+
+    /// Rename or move a file
+    async fn rename(&self, from: &str, to: &str) -> Result<()>;
+
+    /// Stream `reader` into `path`
+    ///
+    /// An `offset` of 0 replaces the file; otherwise the data is written
+    /// from `offset` on, which must be the current remote size.
+    /// Returns the number of bytes written.
+    async fn upload_from(&self, reader: &mut (dyn AsyncRead + Send + Unpin), path: &str, offset: u64) -> Result<u64>;
+
+    /// Stream `path` from `offset` on into `writer`
+    ///
+    /// Returns the number of bytes read.
+    async fn download_to(&self, path: &str, writer: &mut (dyn AsyncWrite + Send + Unpin), offset: u64) -> Result<u64>;
+
+    /// Upload a local file, replacing the remote one
+    async fn upload_file(&self, local: &Path, remote: &str) -> Result<u64> {
+        let mut file = fs::File::open(local).await.map_err(RfError::Io)?;
+        self.upload_from(&mut file, remote, 0).await
+    }
+
+    /// Download a remote file, replacing the local one
+    async fn download_file(&self, remote: &str, local: &Path) -> Result<u64> {
+        let mut file = fs::File::create(local).await.map_err(RfError::Io)?;
+        let size = self.download_to(remote, &mut file, 0).await?;
+        file.flush().await.map_err(RfError::Io)?;
+        Ok(size)
+    }
+
+    /// Continue an interrupted upload from the remote file's current size
+    ///
+    /// Returns the number of bytes sent by this call.
+    async fn resume_upload(&self, local: &Path, remote: &str) -> Result<u64> {
+        let mut file = fs::File::open(local).await.map_err(RfError::Io)?;
+        let total = file.metadata().await.map_err(RfError::Io)?.len();
+        let done = self.stat(remote).await?.map(|entry| entry.size).unwrap_or(0);
+        if done > total {
+            return Err(RfError::Validation(format!(
+                "Remote file {} ({} bytes) is larger than {} ({} bytes)", remote, done, local.display(), total
+            )));
+        }
+        if done == total && done > 0 {
+            return Ok(0);
+        }
+        file.seek(std::io::SeekFrom::Start(done)).await.map_err(RfError::Io)?;
+        self.upload_from(&mut file, remote, done).await
+    }
+
+    /// Continue an interrupted download from the local file's current size
+    ///
+    /// Returns the number of bytes received by this call.
+    async fn resume_download(&self, remote: &str, local: &Path) -> Result<u64> {
+        let entry = self.stat(remote).await?
+            .ok_or_else(|| RfError::NotFound(format!("Remote file {} not found", remote)))?;
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(local).await.map_err(RfError::Io)?;
+        let done = file.metadata().await.map_err(RfError::Io)?.len();
+        if done > entry.size {
+            return Err(RfError::Validation(format!(
+                "Local file {} ({} bytes) is larger than {} ({} bytes)", local.display(), done, remote, entry.size
+            )));
+        }
+        if done == entry.size {
+            return Ok(0);
+        }
+        let received = self.download_to(remote, &mut file, done).await?;
+        file.flush().await.map_err(RfError::Io)?;
+        Ok(received)
+    }
+}
@@ -0,0 +1,238 @@
+//! FTP module tests
+
+use rf_contrib_ftp::{FileTransfer, FtpClient, FtpConfig};
+use rf_errors::RfError;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// In-memory FTP server: path -> contents, `None` for directories
+type Files = Arc<Mutex<BTreeMap<String, Option<Vec<u8>>>>>;
+
+/// Serve one session; `modern` enables EPSV/MLSD/MLST
+async fn session(stream: TcpStream, files: Files, modern: bool) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut data: Option<TcpListener> = None;
+    let mut rest = 0usize;
+    let mut rename_from = None;
+    let mut user = String::new();
+    writer.write_all(b"220-Fake FTP\r\n220 Ready\r\n").await.unwrap();
+
+    async fn accept(data: &mut Option<TcpListener>) -> TcpStream {
+        data.take().unwrap().accept().await.unwrap().0
+    }
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let (command, arg) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+        let arg = arg.to_string();
+        let reply = match command {
+            "USER" => {
+                user = arg;
+                "331 Password required".to_string()
+            }
+            "PASS" if user == "acme" && arg == "secret" => "230 Logged in".to_string(),
+            "PASS" => "530 Login incorrect".to_string(),
+            "TYPE" => "200 Type set".to_string(),
+            "EPSV" if modern => {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let port = listener.local_addr().unwrap().port();
+                data = Some(listener);
+                format!("229 Entering Extended Passive Mode (|||{}|)", port)
+            }
+            "PASV" => {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let port = listener.local_addr().unwrap().port();
+                data = Some(listener);
+                // Report an unroutable address: clients must use the control host
+                format!("227 Entering Passive Mode (10,9,8,7,{},{})", port / 256, port % 256)
+            }
+            "REST" => {
+                rest = arg.parse().unwrap();
+                format!("350 Restarting at {}", rest)
+            }
+            "RETR" => {
+                let content = files.lock().unwrap().get(&arg).cloned().flatten();
+                match content {
+                    Some(content) => {
+                        writer.write_all(b"150 Opening data connection\r\n").await.unwrap();
+                        let mut socket = accept(&mut data).await;
+                        socket.write_all(&content[rest..]).await.unwrap();
+                        rest = 0;
+                        drop(socket);
+                        "226 Transfer complete".to_string()
+                    }
+                    None => "550 No such file".to_string(),
+                }
+            }
+            "STOR" | "APPE" => {
+                writer.write_all(b"150 Ok to send data\r\n").await.unwrap();
+                let mut socket = accept(&mut data).await;
+                let mut received = Vec::new();
+                socket.read_to_end(&mut received).await.unwrap();
+                let mut files = files.lock().unwrap();
+                let content = files.entry(arg).or_insert(Some(Vec::new())).get_or_insert_with(Vec::new);
+                if command == "STOR" {
+                    content.clear();
+                }
+                content.extend_from_slice(&received);
+                "226 Transfer complete".to_string()
+            }
+            "MLSD" | "LIST" if command == "LIST" || modern => {
+                let listing: String = files.lock().unwrap().iter()
+                    .filter(|(path, _)| path.rsplit_once('/').map(|(dir, _)| dir) == Some(arg.as_str()))
+                    .map(|(path, content)| {
+                        let name = path.rsplit('/').next().unwrap();
+                        match (command, content) {
+                            ("MLSD", Some(content)) => format!("type=file;size={};modify=20240102030405;unix.mode=0644; {}\r\n", content.len(), name),
+                            ("MLSD", None) => format!("type=dir;modify=20240102030405; {}\r\n", name),
+                            (_, Some(content)) => format!("-rw-r--r--   1 ftp ftp {:>8} Jan 02 03:04 {}\r\n", content.len(), name),
+                            (_, None) => format!("drwxr-xr-x   2 ftp ftp     4096 Jan 02 03:04 {}\r\n", name),
+                        }
+                    })
+                    .collect();
+                writer.write_all(b"150 Here comes the listing\r\n").await.unwrap();
+                let mut socket = accept(&mut data).await;
+                let header = if command == "MLSD" { "type=cdir; .\r\n" } else { "" };
+                socket.write_all(format!("{}{}", header, listing).as_bytes()).await.unwrap();
+                drop(socket);
+                "226 Directory send OK".to_string()
+            }
+            "MLST" if modern => match files.lock().unwrap().get(&arg) {
+                Some(Some(content)) => format!("250-Listing {}\r\n type=file;size={}; {}\r\n250 End", arg, content.len(), arg),
+                Some(None) => format!("250-Listing {}\r\n type=dir; {}\r\n250 End", arg, arg),
+                None => "550 No such file".to_string(),
+            },
+            "SIZE" => match files.lock().unwrap().get(&arg) {
+                Some(Some(content)) => format!("213 {}", content.len()),
+                _ => "550 Could not get file size".to_string(),
+            },
+            "MKD" => {
+                files.lock().unwrap().insert(arg.clone(), None);
+                format!("257 \"{}\" created", arg)
+            }
+            "DELE" => match files.lock().unwrap().remove(&arg) {
+                Some(_) => "250 Deleted".to_string(),
+                None => "550 No such file".to_string(),
+            },
+            "RNFR" => {
+                rename_from = Some(arg);
+                "350 Ready for RNTO".to_string()
+            }
+            "RNTO" => {
+                let mut files = files.lock().unwrap();
+                let content = files.remove(&rename_from.take().unwrap()).unwrap();
+                files.insert(arg, content);
+                "250 Rename successful".to_string()
+            }
+            "QUIT" => {
+                writer.write_all(b"221 Goodbye\r\n").await.unwrap();
+                return;
+            }
+            _ => "502 Command not implemented".to_string(),
+        };
+        writer.write_all(format!("{}\r\n", reply).as_bytes()).await.unwrap();
+    }
+}
+
+async fn server(modern: bool) -> (u16, Files) {
+    let files: Files = Arc::default();
+    files.lock().unwrap().insert("/outbox".to_string(), None);
+    files.lock().unwrap().insert("/outbox/orders.csv".to_string(), Some(b"id,qty\n1,5\n2,7\n".to_vec()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let shared = files.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(session(stream, shared.clone(), modern));
+        }
+    });
+    (port, files)
+}
+
+async fn connect(port: u16) -> FtpClient {
+    FtpClient::connect(FtpConfig::new("127.0.0.1").port(port).login("acme", "secret")).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_ftp_transfer_and_resume() {
+    let (port, files) = server(true).await;
+    let bad = FtpClient::connect(FtpConfig::new("127.0.0.1").port(port).login("acme", "wrong")).await;
+    assert!(matches!(bad, Err(RfError::Unauthorized(_))));
+    let client = connect(port).await;
+
+    let entries = client.list("/outbox").await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!((entries[0].path.as_str(), entries[0].size, entries[0].permissions), ("/outbox/orders.csv", 15, Some(0o644)));
+    assert_eq!(entries[0].modified.unwrap().to_rfc3339(), "2024-01-02T03:04:05+00:00");
+    assert!(client.stat("/outbox").await.unwrap().unwrap().is_dir);
+    assert_eq!(client.stat("/missing").await.unwrap(), None);
+
+    // Resume a partial download
+    let dir = tempfile::tempdir().unwrap();
+    let local = dir.path().join("orders.csv");
+    std::fs::write(&local, b"id,qty\n").unwrap();
+    assert_eq!(client.resume_download("/outbox/orders.csv", &local).await.unwrap(), 8);
+    assert_eq!(std::fs::read(&local).unwrap(), b"id,qty\n1,5\n2,7\n");
+    assert_eq!(client.resume_download("/outbox/orders.csv", &local).await.unwrap(), 0);
+
+    // Resume a partial upload
+    let report = dir.path().join("report.txt");
+    std::fs::write(&report, b"0123456789").unwrap();
+    files.lock().unwrap().insert("/inbox/report.txt".to_string(), Some(b"0123".to_vec()));
+    assert_eq!(client.resume_upload(&report, "/inbox/report.txt").await.unwrap(), 6);
+    assert_eq!(files.lock().unwrap()["/inbox/report.txt"].as_deref(), Some(&b"0123456789"[..]));
+    assert_eq!(client.upload_file(&report, "/inbox/report.txt").await.unwrap(), 10);
+
+    client.mkdir("/archive").await.unwrap();
+    client.rename("/outbox/orders.csv", "/archive/orders.csv").await.unwrap();
+    client.remove("/inbox/report.txt").await.unwrap();
+    assert!(matches!(client.remove("/inbox/report.txt").await, Err(RfError::NotFound(_))));
+    let names: Vec<String> = files.lock().unwrap().keys().cloned().collect();
+    assert_eq!(names, ["/archive", "/archive/orders.csv", "/outbox"]);
+    client.quit().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ftp_legacy_server_fallbacks() {
+    // PASV with a bogus address, LIST instead of MLSD and SIZE instead of MLST
+    let (port, _files) = server(false).await;
+    let client = connect(port).await;
+
+    let entries: HashMap<String, (bool, u64, Option<u32>)> = client.list("/outbox").await.unwrap().into_iter()
+        .map(|entry| (entry.name, (entry.is_dir, entry.size, entry.permissions)))
+        .collect();
+    assert_eq!(entries["orders.csv"], (false, 15, Some(0o644)));
+    assert_eq!(client.stat("/outbox/orders.csv").await.unwrap().unwrap().size, 15);
+    assert_eq!(client.stat("/outbox/missing.csv").await.unwrap(), None);
+
+    let mut content = Vec::new();
+    assert_eq!(client.download_to("/outbox/orders.csv", &mut content, 7).await.unwrap(), 8);
+    assert_eq!(content, b"1,5\n2,7\n");
+}
+
+#[cfg(feature = "sftp")]
+#[tokio::test]
+async fn test_sftp_connect_errors() {
+    use rf_contrib_ftp::{HostKeyCheck, SftpClient, SftpConfig};
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+    let refused = SftpClient::connect(SftpConfig::new("127.0.0.1", "acme").port(port).password("secret")).await;
+    assert!(matches!(refused, Err(RfError::Network(_))));
+
+    // A server that is not speaking SSH fails the handshake
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        stream.write_all(b"220 this is FTP\r\n").await.unwrap();
+    });
+    let config = SftpConfig::new("127.0.0.1", "acme").port(port)
+        .host_key(HostKeyCheck::AcceptAny)
+        .timeout(std::time::Duration::from_secs(2));
+    assert!(matches!(SftpClient::connect(config).await, Err(RfError::Network(_))));
+}
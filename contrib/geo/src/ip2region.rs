@@ -0,0 +1,136 @@
+//! # ip2region
+//!
+//! ip2region 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! ip2region xdb reader
+//!
+//! Reads the IPv4 xdb format (2.x): a 256-byte header, a 256x256 vector
+//! index keyed by the first two octets, and 14-byte segment index entries.
+//! Regions are stored as `国家|区域|省份|城市|ISP`, with `0` for unknown.
+
+use super::location::{GeoLookup, Location};
+use super::mmap::Mmap;
+use rf_errors::{Result, RfError};
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::Path;
+
+/// Header size in bytes
+pub const HEADER_SIZE: usize = 256;
+/// Vector index size: 256 x 256 entries of 8 bytes
+pub const VECTOR_INDEX_SIZE: usize = 256 * 256 * 8;
+/// Segment index entry size in bytes
+pub const SEGMENT_INDEX_SIZE: usize = 14;
+
+fn invalid(msg: impl Into<String>) -> RfError {
+    RfError::Config(format!("Invalid ip2region xdb: {}", msg.into()))
+}
+
+fn le32(data: &[u8], pos: usize) -> Result<u32> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid("index out of range"))
+}
+
+pub(crate) fn looks_like_xdb(data: &[u8]) -> bool {
+    data.len() >= HEADER_SIZE + VECTOR_INDEX_SIZE
+        && matches!(u16::from_le_bytes([data[0], data[1]]), 2 | 3)
+}
+
+/// ip2region xdb reader
+pub struct Ip2RegionReader {
+    data: Mmap,
+}
+
+impl Ip2RegionReader {
+    /// Open and memory-map an `.xdb` file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_source(Mmap::open(path)?)
+    }
+
+    /// Read a database from memory
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        Self::from_source(Mmap::from_vec(data))
+    }
+
+    /// Read a database from a mapped file
+    pub fn from_source(data: Mmap) -> Result<Self> {
+        if data.len() < HEADER_SIZE + VECTOR_INDEX_SIZE {
+            return Err(invalid("file too small"));
+        }
+        Ok(Self { data })
+    }
+
+    /// Look up the raw region string of an IPv4 address
+    pub fn search(&self, ip: Ipv4Addr) -> Result<Option<String>> {
+        let data: &[u8] = &self.data;
+        let octets = ip.octets();
+        let ip = u32::from(ip);
+
+        let index = HEADER_SIZE + (octets[0] as usize * 256 + octets[1] as usize) * 8;
+        let start = le32(data, index)? as usize;
+        let end = le32(data, index + 4)? as usize;
+        if end < start {
+            return Err(invalid("corrupt vector index"));
+        }
+
+        let mut low = 0i64;
+        let mut high = ((end - start) / SEGMENT_INDEX_SIZE) as i64;
+        while low <= high {
+            let middle = (low + high) / 2;
+            let pos = start + middle as usize * SEGMENT_INDEX_SIZE;
+            let first = le32(data, pos)?;
+            let last = le32(data, pos + 4)?;
+            if ip < first {
+                high = middle - 1;
+            } else if ip > last {
+                low = middle + 1;
+            } else {
+                let len = data
+                    .get(pos + 8..pos + 10)
+                    .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+                    .ok_or_else(|| invalid("index out of range"))?;
+                let ptr = le32(data, pos + 10)? as usize;
+                let region = data.get(ptr..ptr + len).ok_or_else(|| invalid("region out of range"))?;
+                return String::from_utf8(region.to_vec())
+                    .map(Some)
+                    .map_err(|_| invalid("region is not UTF-8"));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Parse a `国家|区域|省份|城市|ISP` region string
+pub fn parse_region(region: &str) -> Location {
+    let field = |i: usize| {
+        region
+            .split('|')
+            .nth(i)
+            .map(str::trim)
+            .filter(|v| !v.is_empty() && *v != "0")
+            .map(String::from)
+    };
+    Location {
+        country: field(0),
+        region: field(2),
+        city: field(3),
+        isp: field(4),
+        ..Default::default()
+    }
+}
+
+impl GeoLookup for Ip2RegionReader {
+    fn lookup(&self, ip: IpAddr) -> Result<Option<Location>> {
+        let v4 = match ip {
+            IpAddr::V4(v4) => v4,
+            IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+                Some(v4) => v4,
+                None => return Ok(None),
+            },
+        };
+        Ok(self.search(v4)?.map(|region| parse_region(&region)))
+    }
+}
@@ -0,0 +1,47 @@
+//! # lib
+//!
+//! lib 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Geo/IP location module
+//!
+//! Resolves IP addresses to country, region, city and ISP:
+//! - MaxMind DB format (GeoLite2/GeoIP2 City, Country, ASN, ISP)
+//! - ip2region xdb format (IPv4)
+//! - Database files are memory-mapped, lookups never copy the file
+//! - HTTP middleware that annotates requests with their location and
+//!   optionally blocks countries
+
+pub mod mmap;
+pub mod location;
+pub mod maxmind;
+pub mod ip2region;
+pub mod middleware;
+
+pub use mmap::*;
+pub use location::*;
+pub use maxmind::*;
+pub use ip2region::*;
+pub use middleware::*;
+
+use rf_errors::{Result, RfError};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Open a geo database, detecting MaxMind DB or ip2region xdb format
+pub fn open_database(path: impl AsRef<Path>) -> Result<Arc<dyn GeoLookup>> {
+    let path = path.as_ref();
+    let data = Mmap::open(path)?;
+    if maxmind::find_metadata(&data).is_some() {
+        return Ok(Arc::new(MaxMindReader::from_source(data)?));
+    }
+    if ip2region::looks_like_xdb(&data) {
+        return Ok(Arc::new(Ip2RegionReader::from_source(data)?));
+    }
+    Err(RfError::Config(format!(
+        "Unrecognized geo database format: {}",
+        path.display()
+    )))
+}
@@ -0,0 +1,56 @@
+//! # location
+//!
+//! location 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Location result and lookup trait
+
+use rf_errors::Result;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// Location of an IP address
+///
+/// Fields are `None` when the database has no data for them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    /// Country name
+    pub country: Option<String>,
+    /// ISO 3166-1 alpha-2 country code
+    pub country_code: Option<String>,
+    /// Region, state or province
+    pub region: Option<String>,
+    pub city: Option<String>,
+    /// ISP or autonomous system organization
+    pub isp: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+impl Location {
+    /// Whether no field is known
+    pub fn is_empty(&self) -> bool {
+        self.country.is_none()
+            && self.country_code.is_none()
+            && self.region.is_none()
+            && self.city.is_none()
+            && self.isp.is_none()
+            && self.latitude.is_none()
+            && self.longitude.is_none()
+    }
+
+    /// Whether the location is in the given country (code or name, case-insensitive)
+    pub fn in_country(&self, country: &str) -> bool {
+        [&self.country_code, &self.country]
+            .iter()
+            .any(|value| value.as_deref().is_some_and(|v| v.eq_ignore_ascii_case(country)))
+    }
+}
+
+/// IP geolocation database
+pub trait GeoLookup: Send + Sync {
+    /// Look up an address; `Ok(None)` when the address is not in the database
+    fn lookup(&self, ip: IpAddr) -> Result<Option<Location>>;
+}
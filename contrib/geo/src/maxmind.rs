@@ -0,0 +1,391 @@
+//! # maxmind
+//!
+//! maxmind 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! MaxMind DB (GeoLite2/GeoIP2) reader
+
+use super::location::{GeoLookup, Location};
+use super::mmap::Mmap;
+use rf_errors::{Result, RfError};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// Marker that precedes the metadata section
+const METADATA_MARKER: &[u8] = b"\xAB\xCD\xEFMaxMind.com";
+/// Size of the zero separator between the search tree and the data section
+const DATA_SEPARATOR: usize = 16;
+
+fn invalid(msg: impl Into<String>) -> RfError {
+    RfError::Config(format!("Invalid MaxMind DB: {}", msg.into()))
+}
+
+/// A decoded MaxMind DB data value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Double(f64),
+    Bytes(Vec<u8>),
+    Uint(u64),
+    Uint128(u128),
+    Int(i32),
+    Map(BTreeMap<String, Value>),
+    Array(Vec<Value>),
+    Bool(bool),
+    Float(f32),
+}
+
+impl Value {
+    /// Get a map entry
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Map(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    /// Follow a path of map keys; numeric segments index into arrays
+    pub fn path(&self, path: &[&str]) -> Option<&Value> {
+        path.iter().try_fold(self, |value, key| match value {
+            Value::Map(map) => map.get(*key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Uint(v) => Some(*v),
+            Value::Int(v) if *v >= 0 => Some(*v as u64),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Double(v) => Some(*v),
+            Value::Float(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Database metadata
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    pub node_count: u32,
+    pub record_size: u16,
+    pub ip_version: u16,
+    pub database_type: String,
+    pub languages: Vec<String>,
+    pub build_epoch: u64,
+}
+
+/// Locate the start of the metadata map
+pub(crate) fn find_metadata(data: &[u8]) -> Option<usize> {
+    // The metadata is at most 128KiB from the end of the file
+    let window = data.len().saturating_sub(128 * 1024);
+    data[window..]
+        .windows(METADATA_MARKER.len())
+        .rposition(|w| w == METADATA_MARKER)
+        .map(|pos| window + pos + METADATA_MARKER.len())
+}
+
+/// MaxMind DB reader
+pub struct MaxMindReader {
+    data: Mmap,
+    metadata: Metadata,
+    node_bytes: usize,
+    tree_size: usize,
+    ipv4_start: u32,
+    languages: Vec<String>,
+}
+
+impl MaxMindReader {
+    /// Open and memory-map a `.mmdb` file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_source(Mmap::open(path)?)
+    }
+
+    /// Read a database from memory
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        Self::from_source(Mmap::from_vec(data))
+    }
+
+    /// Read a database from a mapped file
+    pub fn from_source(data: Mmap) -> Result<Self> {
+        let start = find_metadata(&data).ok_or_else(|| invalid("metadata marker not found"))?;
+        let meta = Decoder { data: &data[start..] }.decode(&mut 0)?;
+        let field = |key: &str| meta.get(key).and_then(Value::as_u64);
+
+        let metadata = Metadata {
+            node_count: field("node_count").ok_or_else(|| invalid("missing node_count"))? as u32,
+            record_size: field("record_size").ok_or_else(|| invalid("missing record_size"))? as u16,
+            ip_version: field("ip_version").unwrap_or(6) as u16,
+            database_type: meta.get("database_type").and_then(Value::as_str).unwrap_or_default().to_string(),
+            languages: match meta.get("languages") {
+                Some(Value::Array(items)) => items.iter().filter_map(|v| v.as_str().map(String::from)).collect(),
+                _ => Vec::new(),
+            },
+            build_epoch: field("build_epoch").unwrap_or(0),
+        };
+        if !matches!(metadata.record_size, 24 | 28 | 32) {
+            return Err(invalid(format!("unsupported record size {}", metadata.record_size)));
+        }
+
+        let node_bytes = metadata.record_size as usize * 2 / 8;
+        let tree_size = metadata.node_count as usize * node_bytes;
+        if tree_size + DATA_SEPARATOR > start {
+            return Err(invalid("search tree exceeds file size"));
+        }
+
+        let mut reader = Self {
+            data,
+            metadata,
+            node_bytes,
+            tree_size,
+            ipv4_start: 0,
+            languages: vec!["en".to_string()],
+        };
+        // IPv4 addresses live under ::/96 in IPv6 databases
+        if reader.metadata.ip_version == 6 {
+            let mut node = 0;
+            for _ in 0..96 {
+                if node >= reader.metadata.node_count {
+                    break;
+                }
+                node = reader.read_record(node, 0)?;
+            }
+            reader.ipv4_start = node;
+        }
+        Ok(reader)
+    }
+
+    /// Preferred languages for names, most preferred first (default `en`)
+    ///
+    /// For example `["zh-CN", "en"]`.
+    pub fn with_languages(mut self, languages: &[&str]) -> Self {
+        self.languages = languages.iter().map(|l| l.to_string()).collect();
+        self
+    }
+
+    /// Database metadata
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn read_record(&self, node: u32, bit: u8) -> Result<u32> {
+        let offset = node as usize * self.node_bytes;
+        let b = self
+            .data
+            .get(offset..offset + self.node_bytes)
+            .ok_or_else(|| invalid("node out of range"))?;
+        let be = |bytes: &[u8]| bytes.iter().fold(0u32, |acc, &x| (acc << 8) | x as u32);
+        Ok(match (self.metadata.record_size, bit) {
+            (24, 0) => be(&b[0..3]),
+            (24, _) => be(&b[3..6]),
+            (28, 0) => ((b[3] as u32 & 0xF0) << 20) | be(&b[0..3]),
+            (28, _) => ((b[3] as u32 & 0x0F) << 24) | be(&b[4..7]),
+            (_, 0) => be(&b[0..4]),
+            (_, _) => be(&b[4..8]),
+        })
+    }
+
+    /// Find the data section offset and prefix length for an address
+    fn find(&self, ip: IpAddr) -> Result<Option<(usize, u8)>> {
+        let (bytes, mut node): (Vec<u8>, u32) = match ip {
+            IpAddr::V4(v4) => (v4.octets().to_vec(), self.ipv4_start),
+            IpAddr::V6(v6) => {
+                if self.metadata.ip_version == 4 {
+                    return match v6.to_ipv4_mapped() {
+                        Some(v4) => self.find(IpAddr::V4(v4)),
+                        None => Ok(None),
+                    };
+                }
+                (v6.octets().to_vec(), 0)
+            }
+        };
+
+        let node_count = self.metadata.node_count;
+        let bits = bytes.len() * 8;
+        let mut depth = 0;
+        while depth < bits && node < node_count {
+            let bit = (bytes[depth / 8] >> (7 - depth % 8)) & 1;
+            node = self.read_record(node, bit)?;
+            depth += 1;
+        }
+        if node == node_count {
+            return Ok(None);
+        }
+        if node < node_count {
+            return Err(invalid("search tree is deeper than the address"));
+        }
+        let offset = ((node - node_count) as usize)
+            .checked_sub(DATA_SEPARATOR)
+            .ok_or_else(|| invalid("record points into the separator"))?;
+        Ok(Some((offset, depth as u8)))
+    }
+
+    /// Look up the raw record for an address
+    pub fn lookup_value(&self, ip: IpAddr) -> Result<Option<Value>> {
+        match self.find(ip)? {
+            Some((offset, _)) => {
+                let section = &self.data[self.tree_size + DATA_SEPARATOR..];
+                let mut pos = offset;
+                Decoder { data: section }.decode(&mut pos).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn name(&self, value: Option<&Value>) -> Option<String> {
+        let names = value?.get("names")?;
+        self.languages
+            .iter()
+            .find_map(|lang| names.get(lang).and_then(Value::as_str))
+            .map(String::from)
+    }
+}
+
+impl GeoLookup for MaxMindReader {
+    fn lookup(&self, ip: IpAddr) -> Result<Option<Location>> {
+        let record = match self.lookup_value(ip)? {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        let country = record.get("country").or_else(|| record.get("registered_country"));
+        let location = Location {
+            country: self.name(country),
+            country_code: country
+                .and_then(|c| c.get("iso_code"))
+                .and_then(Value::as_str)
+                .map(String::from),
+            region: self.name(record.path(&["subdivisions", "0"])),
+            city: self.name(record.get("city")),
+            isp: ["isp", "organization", "autonomous_system_organization"]
+                .iter()
+                .find_map(|key| record.get(key).and_then(Value::as_str))
+                .map(String::from),
+            latitude: record.path(&["location", "latitude"]).and_then(Value::as_f64),
+            longitude: record.path(&["location", "longitude"]).and_then(Value::as_f64),
+        };
+        Ok(Some(location))
+    }
+}
+
+/// Data section decoder
+struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl Decoder<'_> {
+    fn byte(&self, pos: &mut usize) -> Result<u8> {
+        let b = *self.data.get(*pos).ok_or_else(|| invalid("unexpected end of data"))?;
+        *pos += 1;
+        Ok(b)
+    }
+
+    fn bytes(&self, pos: &mut usize, len: usize) -> Result<&[u8]> {
+        let b = self
+            .data
+            .get(*pos..*pos + len)
+            .ok_or_else(|| invalid("unexpected end of data"))?;
+        *pos += len;
+        Ok(b)
+    }
+
+    fn uint(&self, pos: &mut usize, len: usize) -> Result<u128> {
+        Ok(self.bytes(pos, len)?.iter().fold(0u128, |acc, &b| (acc << 8) | b as u128))
+    }
+
+    fn decode(&self, pos: &mut usize) -> Result<Value> {
+        self.decode_depth(pos, 0)
+    }
+
+    fn decode_depth(&self, pos: &mut usize, depth: usize) -> Result<Value> {
+        if depth > 64 {
+            return Err(invalid("data nested too deeply"));
+        }
+        let ctrl = self.byte(pos)?;
+        let mut kind = ctrl >> 5;
+
+        if kind == 1 {
+            // Pointer: decode the target, continue after the pointer
+            let size = ((ctrl >> 3) & 0x3) as usize;
+            let high = (ctrl & 0x7) as usize;
+            let target = match size {
+                0 => (high << 8) | self.uint(pos, 1)? as usize,
+                1 => ((high << 16) | self.uint(pos, 2)? as usize) + 2048,
+                2 => ((high << 24) | self.uint(pos, 3)? as usize) + 526_336,
+                _ => self.uint(pos, 4)? as usize,
+            };
+            let mut target_pos = target;
+            return self.decode_depth(&mut target_pos, depth + 1);
+        }
+
+        if kind == 0 {
+            kind = 7 + self.byte(pos)?;
+        }
+
+        let mut size = (ctrl & 0x1F) as usize;
+        if size >= 29 {
+            size = match size {
+                29 => 29 + self.uint(pos, 1)? as usize,
+                30 => 285 + self.uint(pos, 2)? as usize,
+                _ => 65_821 + self.uint(pos, 3)? as usize,
+            };
+        }
+
+        Ok(match kind {
+            2 => Value::String(
+                String::from_utf8(self.bytes(pos, size)?.to_vec()).map_err(|_| invalid("invalid UTF-8 string"))?,
+            ),
+            3 => {
+                let b = self.bytes(pos, 8)?;
+                Value::Double(f64::from_be_bytes(b.try_into().unwrap_or_default()))
+            }
+            4 => Value::Bytes(self.bytes(pos, size)?.to_vec()),
+            5 | 6 | 9 => Value::Uint(self.uint(pos, size)? as u64),
+            7 => {
+                let mut map = BTreeMap::new();
+                for _ in 0..size {
+                    let key = match self.decode_depth(pos, depth + 1)? {
+                        Value::String(key) => key,
+                        _ => return Err(invalid("map key is not a string")),
+                    };
+                    let value = self.decode_depth(pos, depth + 1)?;
+                    map.insert(key, value);
+                }
+                Value::Map(map)
+            }
+            8 => {
+                let raw = self.uint(pos, size)? as u32;
+                Value::Int(raw as i32)
+            }
+            10 => Value::Uint128(self.uint(pos, size)?),
+            11 => {
+                let mut items = Vec::with_capacity(size.min(1024));
+                for _ in 0..size {
+                    items.push(self.decode_depth(pos, depth + 1)?);
+                }
+                Value::Array(items)
+            }
+            14 => Value::Bool(size != 0),
+            15 => {
+                let b = self.bytes(pos, 4)?;
+                Value::Float(f32::from_be_bytes(b.try_into().unwrap_or_default()))
+            }
+            other => return Err(invalid(format!("unsupported data type {}", other))),
+        })
+    }
+}
@@ -0,0 +1,162 @@
+//! # middleware
+//!
+//! middleware 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! HTTP geolocation middleware
+//!
+//! Resolves the client address, stores a `GeoContext` in the request
+//! extensions for handlers and analytics, and rejects requests from
+//! blocked countries with `451 Unavailable For Legal Reasons`.
+//!
+//! ```ignore
+//! let geo = open_database("GeoLite2-City.mmdb")?;
+//! let filter = Arc::new(GeoFilter::new().block(&["KP", "IR"]).trust_proxy_headers(true));
+//! let router = Router::new()
+//!     .route("/", get(index))
+//!     .layer(axum::middleware::from_fn(move |req: Request, next: Next| {
+//!         let (geo, filter) = (geo.clone(), filter.clone());
+//!         async move { geo_middleware(geo, filter, req, next).await.map_err(|e| e.to_string()) }
+//!     }));
+//! // serve with `into_make_service_with_connect_info::<SocketAddr>()`
+//! ```
+
+use super::location::{GeoLookup, Location};
+use axum::extract::{ConnectInfo, Request};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// Client address and location, stored in the request extensions
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoContext {
+    pub ip: IpAddr,
+    pub location: Option<Location>,
+}
+
+/// Country allow/block rules
+#[derive(Debug, Clone, Default)]
+pub struct GeoFilter {
+    blocked: HashSet<String>,
+    allowed: Option<HashSet<String>>,
+    block_unknown: bool,
+    trust_proxy_headers: bool,
+}
+
+impl GeoFilter {
+    /// Create a filter that allows everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Block countries (ISO codes or names)
+    pub fn block(mut self, countries: &[&str]) -> Self {
+        self.blocked.extend(countries.iter().map(|c| c.to_uppercase()));
+        self
+    }
+
+    /// Only allow these countries (ISO codes or names)
+    pub fn allow_only(mut self, countries: &[&str]) -> Self {
+        self.allowed
+            .get_or_insert_with(HashSet::new)
+            .extend(countries.iter().map(|c| c.to_uppercase()));
+        self
+    }
+
+    /// Block requests whose country cannot be resolved (default: allow)
+    pub fn block_unknown(mut self, block: bool) -> Self {
+        self.block_unknown = block;
+        self
+    }
+
+    /// Read the client address from `X-Forwarded-For`/`X-Real-IP`
+    ///
+    /// Only enable this behind a proxy that sets these headers.
+    pub fn trust_proxy_headers(mut self, trust: bool) -> Self {
+        self.trust_proxy_headers = trust;
+        self
+    }
+
+    /// Whether a request from this location is allowed
+    pub fn is_allowed(&self, location: Option<&Location>) -> bool {
+        let countries: Vec<String> = location
+            .map(|l| {
+                [&l.country_code, &l.country]
+                    .iter()
+                    .filter_map(|v| v.as_ref().map(|v| v.to_uppercase()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if countries.is_empty() {
+            return !self.block_unknown;
+        }
+        if countries.iter().any(|c| self.blocked.contains(c)) {
+            return false;
+        }
+        match &self.allowed {
+            Some(allowed) => countries.iter().any(|c| allowed.contains(c)),
+            None => true,
+        }
+    }
+
+    /// Resolve the client address of a request
+    pub fn client_ip(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Option<IpAddr> {
+        if self.trust_proxy_headers {
+            let forwarded = headers
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(',').next())
+                .and_then(|v| v.trim().parse().ok());
+            let real_ip = || {
+                headers
+                    .get("x-real-ip")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse().ok())
+            };
+            if let Some(ip) = forwarded.or_else(real_ip) {
+                return Some(ip);
+            }
+        }
+        peer
+    }
+}
+
+/// Annotate requests with their location and enforce the filter
+pub async fn geo_middleware(
+    lookup: Arc<dyn GeoLookup>,
+    filter: Arc<GeoFilter>,
+    mut request: Request,
+    next: axum::middleware::Next,
+) -> Result<Response, axum::Error> {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip());
+    let ip = match filter.client_ip(request.headers(), peer) {
+        Some(ip) => ip,
+        None => return Ok(next.run(request).await),
+    };
+
+    let location = match lookup.lookup(ip) {
+        Ok(location) => location,
+        Err(e) => {
+            tracing::warn!("Geo lookup failed for {}: {}", ip, e);
+            None
+        }
+    };
+
+    if !filter.is_allowed(location.as_ref()) {
+        tracing::debug!("Request from {} blocked by geo filter", ip);
+        let mut response = Response::new(axum::body::Body::empty());
+        *response.status_mut() = StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS;
+        return Ok(response);
+    }
+
+    request.extensions_mut().insert(GeoContext { ip, location });
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,124 @@
+//! # mmap
+//!
+//! mmap 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Read-only memory-mapped files
+
+use rf_errors::{Result, RfError};
+use std::ops::Deref;
+use std::path::Path;
+
+/// Read-only view of a file's contents
+///
+/// On Unix the file is mapped with `mmap(2)` so pages are loaded lazily
+/// and shared between processes. Elsewhere, and for empty files, the file
+/// is read into memory.
+pub struct Mmap {
+    inner: Inner,
+}
+
+enum Inner {
+    #[cfg(unix)]
+    Mapped { ptr: *mut libc::c_void, len: usize },
+    Owned(Vec<u8>),
+}
+
+// The mapping is read-only and never mutated after creation
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    /// Map a file into memory
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = std::fs::File::open(path.as_ref()).map_err(RfError::Io)?;
+        let len = file.metadata().map_err(RfError::Io)?.len() as usize;
+        if len == 0 {
+            return Ok(Self::from_vec(Vec::new()));
+        }
+        Self::map(file, len)
+    }
+
+    #[cfg(unix)]
+    fn map(file: std::fs::File, len: usize) -> Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        // SAFETY: a read-only private mapping of an open file; the length
+        // comes from the file metadata and the pointer is checked below.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(RfError::Io(std::io::Error::last_os_error()));
+        }
+        Ok(Self { inner: Inner::Mapped { ptr, len } })
+    }
+
+    #[cfg(not(unix))]
+    fn map(mut file: std::fs::File, len: usize) -> Result<Self> {
+        use std::io::Read;
+
+        let mut data = Vec::with_capacity(len);
+        file.read_to_end(&mut data).map_err(RfError::Io)?;
+        Ok(Self::from_vec(data))
+    }
+
+    /// Wrap an in-memory buffer
+    pub fn from_vec(data: Vec<u8>) -> Self {
+        Self { inner: Inner::Owned(data) }
+    }
+
+    /// Whether the data is backed by a memory mapping
+    pub fn is_mapped(&self) -> bool {
+        !matches!(self.inner, Inner::Owned(_))
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.inner {
+            // SAFETY: ptr/len describe a live mapping owned by self
+            #[cfg(unix)]
+            Inner::Mapped { ptr, len } => unsafe { std::slice::from_raw_parts(*ptr as *const u8, *len) },
+            Inner::Owned(data) => data,
+        }
+    }
+}
+
+impl AsRef<[u8]> for Mmap {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Inner::Mapped { ptr, len } = self.inner {
+            // SAFETY: unmapping the region created in `map`
+            unsafe {
+                libc::munmap(ptr, len);
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Mmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mmap")
+            .field("len", &self.len())
+            .field("mapped", &self.is_mapped())
+            .finish()
+    }
+}
@@ -0,0 +1,216 @@
+//! Geo module tests
+
+use axum::body::Body;
+use axum::routing::get;
+use axum::{Extension, Router};
+use rf_contrib_geo::*;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+// --- MaxMind DB writer (just enough of the format for tests) ---
+
+fn ctrl(out: &mut Vec<u8>, kind: u8, size: usize) {
+    assert!(size < 29);
+    if kind <= 7 {
+        out.push((kind << 5) | size as u8);
+    } else {
+        out.push(size as u8);
+        out.push(kind - 7);
+    }
+}
+
+fn string(out: &mut Vec<u8>, s: &str) {
+    ctrl(out, 2, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn uint(out: &mut Vec<u8>, v: u32) {
+    ctrl(out, 6, 4);
+    out.extend_from_slice(&v.to_be_bytes());
+}
+
+fn double(out: &mut Vec<u8>, v: f64) {
+    ctrl(out, 3, 8);
+    out.extend_from_slice(&v.to_be_bytes());
+}
+
+fn names(out: &mut Vec<u8>, en: &str, zh: &str) {
+    ctrl(out, 7, 1);
+    string(out, "names");
+    ctrl(out, 7, 2);
+    string(out, "en");
+    string(out, en);
+    string(out, "zh-CN");
+    string(out, zh);
+}
+
+/// IPv4 database (record size 24) mapping 1.0.0.0/8 to a city record
+fn mmdb() -> Vec<u8> {
+    let node_count = 8u32;
+    let mut data = Vec::new();
+    ctrl(&mut data, 7, 4);
+    string(&mut data, "country");
+    ctrl(&mut data, 7, 2);
+    string(&mut data, "iso_code");
+    string(&mut data, "AU");
+    string(&mut data, "names");
+    ctrl(&mut data, 7, 1);
+    string(&mut data, "en");
+    string(&mut data, "Australia");
+    string(&mut data, "city");
+    names(&mut data, "Brisbane", "布里斯班");
+    string(&mut data, "subdivisions");
+    ctrl(&mut data, 11, 1);
+    names(&mut data, "Queensland", "昆士兰");
+    string(&mut data, "location");
+    ctrl(&mut data, 7, 2);
+    string(&mut data, "latitude");
+    double(&mut data, -27.5);
+    string(&mut data, "longitude");
+    double(&mut data, 153.0);
+
+    // Path for the first octet 00000001
+    let mut tree = Vec::new();
+    for node in 0..node_count {
+        let (left, right) = if node < 7 {
+            (node + 1, node_count)
+        } else {
+            (node_count, node_count + 16)
+        };
+        tree.extend_from_slice(&left.to_be_bytes()[1..]);
+        tree.extend_from_slice(&right.to_be_bytes()[1..]);
+    }
+
+    let mut file = tree;
+    file.extend_from_slice(&[0u8; 16]);
+    file.extend_from_slice(&data);
+    file.extend_from_slice(b"\xAB\xCD\xEFMaxMind.com");
+    ctrl(&mut file, 7, 4);
+    string(&mut file, "node_count");
+    uint(&mut file, node_count);
+    string(&mut file, "record_size");
+    uint(&mut file, 24);
+    string(&mut file, "ip_version");
+    uint(&mut file, 4);
+    string(&mut file, "database_type");
+    string(&mut file, "GeoLite2-City");
+    file
+}
+
+/// xdb with a single segment 1.2.3.0 - 1.2.3.255
+fn xdb() -> Vec<u8> {
+    let mut file = vec![0u8; HEADER_SIZE + VECTOR_INDEX_SIZE];
+    file[0] = 2;
+    let region = "中国|0|广东省|深圳市|电信".as_bytes();
+    let region_ptr = file.len();
+    file.extend_from_slice(region);
+    let segment = file.len() as u32;
+    file.extend_from_slice(&u32::from(Ipv4Addr::new(1, 2, 3, 0)).to_le_bytes());
+    file.extend_from_slice(&u32::from(Ipv4Addr::new(1, 2, 3, 255)).to_le_bytes());
+    file.extend_from_slice(&(region.len() as u16).to_le_bytes());
+    file.extend_from_slice(&(region_ptr as u32).to_le_bytes());
+    let index = HEADER_SIZE + (256 + 2) * 8;
+    file[index..index + 4].copy_from_slice(&segment.to_le_bytes());
+    file[index + 4..index + 8].copy_from_slice(&segment.to_le_bytes());
+    file
+}
+
+#[test]
+fn test_maxmind_lookup() {
+    let reader = MaxMindReader::from_bytes(mmdb()).unwrap();
+    assert_eq!(reader.metadata().database_type, "GeoLite2-City");
+
+    let location = reader.lookup("1.2.3.4".parse().unwrap()).unwrap().unwrap();
+    assert_eq!(location.country_code.as_deref(), Some("AU"));
+    assert_eq!(location.country.as_deref(), Some("Australia"));
+    assert_eq!(location.region.as_deref(), Some("Queensland"));
+    assert_eq!(location.city.as_deref(), Some("Brisbane"));
+    assert_eq!(location.latitude, Some(-27.5));
+
+    assert!(reader.lookup("2.0.0.1".parse().unwrap()).unwrap().is_none());
+    assert!(reader.lookup("2001:db8::1".parse().unwrap()).unwrap().is_none());
+
+    let zh = MaxMindReader::from_bytes(mmdb()).unwrap().with_languages(&["zh-CN", "en"]);
+    let location = zh.lookup("1.9.9.9".parse().unwrap()).unwrap().unwrap();
+    assert_eq!(location.city.as_deref(), Some("布里斯班"));
+    assert_eq!(location.country.as_deref(), Some("Australia"));
+}
+
+#[test]
+fn test_ip2region_lookup() {
+    let reader = Ip2RegionReader::from_bytes(xdb()).unwrap();
+    let location = reader.lookup("1.2.3.100".parse().unwrap()).unwrap().unwrap();
+    assert_eq!(location.country.as_deref(), Some("中国"));
+    assert_eq!(location.region.as_deref(), Some("广东省"));
+    assert_eq!(location.city.as_deref(), Some("深圳市"));
+    assert_eq!(location.isp.as_deref(), Some("电信"));
+    assert!(reader.lookup("1.2.4.1".parse().unwrap()).unwrap().is_none());
+    assert!(reader.lookup("::1".parse().unwrap()).unwrap().is_none());
+}
+
+#[test]
+fn test_open_database_detects_format() {
+    let dir = std::env::temp_dir().join(format!("rf-geo-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mmdb_path = dir.join("city.mmdb");
+    let xdb_path = dir.join("ip2region.xdb");
+    std::fs::write(&mmdb_path, mmdb()).unwrap();
+    std::fs::write(&xdb_path, xdb()).unwrap();
+
+    let ip: IpAddr = "1.2.3.4".parse().unwrap();
+    let maxmind = open_database(&mmdb_path).unwrap();
+    assert_eq!(maxmind.lookup(ip).unwrap().unwrap().country_code.as_deref(), Some("AU"));
+    let ip2region = open_database(&xdb_path).unwrap();
+    assert_eq!(ip2region.lookup(ip).unwrap().unwrap().city.as_deref(), Some("深圳市"));
+    assert!(Mmap::open(&xdb_path).unwrap().len() > VECTOR_INDEX_SIZE);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_geo_filter() {
+    let au = Location { country_code: Some("AU".into()), ..Default::default() };
+    let cn = Location { country: Some("中国".into()), ..Default::default() };
+
+    assert!(GeoFilter::new().is_allowed(None));
+    assert!(!GeoFilter::new().block_unknown(true).is_allowed(None));
+    assert!(!GeoFilter::new().block(&["au"]).is_allowed(Some(&au)));
+    assert!(GeoFilter::new().allow_only(&["中国"]).is_allowed(Some(&cn)));
+    assert!(!GeoFilter::new().allow_only(&["中国"]).is_allowed(Some(&au)));
+}
+
+#[tokio::test]
+async fn test_geo_middleware() {
+    let lookup: Arc<dyn GeoLookup> = Arc::new(MaxMindReader::from_bytes(mmdb()).unwrap());
+    let router = |filter: GeoFilter| {
+        let lookup = lookup.clone();
+        let filter = Arc::new(filter.trust_proxy_headers(true));
+        Router::new()
+            .route(
+                "/",
+                get(|Extension(ctx): Extension<GeoContext>| async move {
+                    ctx.location.and_then(|l| l.city).unwrap_or_default()
+                }),
+            )
+            .layer(axum::middleware::from_fn(move |req: axum::extract::Request, next: axum::middleware::Next| {
+                let (lookup, filter) = (lookup.clone(), filter.clone());
+                async move { geo_middleware(lookup, filter, req, next).await.unwrap() }
+            }))
+    };
+    let request = || {
+        axum::extract::Request::builder()
+            .uri("/")
+            .header("x-forwarded-for", "1.2.3.4, 10.0.0.1")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = router(GeoFilter::new()).oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+    assert_eq!(&body[..], b"Brisbane");
+
+    let response = router(GeoFilter::new().block(&["AU"])).oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), 451);
+}
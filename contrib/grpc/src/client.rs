@@ -7,46 +7,136 @@
 
 //! gRPC client encapsulation
 
+use super::pool::{registry_discover, Discover, LoadBalancer, PoolOptions, PooledChannel, RetryPolicy};
+use rf_contrib_registry::ServiceRegistry;
 use rf_errors::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// gRPC client builder
+///
+/// `connect` opens a plain tonic `Channel` to the first endpoint;
+/// `connect_pool` builds a `PooledChannel` balancing over all endpoints
+/// with retries.
 pub struct GrpcClient {
-    endpoint: String,
+    endpoints: Vec<String>,
+    discover: Option<Discover>,
     timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
     retry_count: usize,
+    policies: HashMap<String, RetryPolicy>,
+    default_policy: Option<RetryPolicy>,
+    balancer: LoadBalancer,
+    refresh_interval: Duration,
+    trace_propagation: bool,
 }
 
 impl GrpcClient {
     /// Create a new gRPC client
     pub fn new(endpoint: impl Into<String>) -> Self {
+        Self::balanced([endpoint])
+    }
+
+    /// Create a client spreading calls over several endpoints
+    pub fn balanced<E: Into<String>>(endpoints: impl IntoIterator<Item = E>) -> Self {
         Self {
-            endpoint: endpoint.into(),
+            endpoints: endpoints.into_iter().map(Into::into).collect(),
+            discover: None,
             timeout: Some(Duration::from_secs(30)),
+            connect_timeout: None,
             retry_count: 3,
+            policies: HashMap::new(),
+            default_policy: None,
+            balancer: LoadBalancer::default(),
+            refresh_interval: Duration::from_secs(30),
+            trace_propagation: true,
         }
     }
 
+    /// Create a client for the instances of `service` in a registry
+    ///
+    /// Instances not marked unhealthy are used, looked up again every
+    /// refresh interval. Only works with `connect_pool`.
+    pub fn discover<R>(registry: Arc<R>, service: impl Into<String>) -> Self
+    where
+        R: ServiceRegistry + 'static,
+    {
+        let mut client = Self::balanced(Vec::<String>::new());
+        client.discover = Some(registry_discover(registry, service.into()));
+        client
+    }
+
     /// Set timeout
+    ///
+    /// For pooled channels this is the default deadline of a call including
+    /// its retries.
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = Some(timeout);
         self
     }
 
+    /// Set the timeout for establishing connections
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
     /// Set retry count
+    ///
+    /// Retries of methods without their own policy, on `UNAVAILABLE`.
     pub fn with_retry(mut self, count: usize) -> Self {
         self.retry_count = count;
         self
     }
 
+    /// Policy for methods without their own, replacing `with_retry`
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.default_policy = Some(policy);
+        self
+    }
+
+    /// Policy for a method (`/package.Service/Method`) or a whole service
+    /// (`package.Service`)
+    pub fn method_policy(mut self, method: impl Into<String>, policy: RetryPolicy) -> Self {
+        self.policies.insert(method.into(), policy);
+        self
+    }
+
+    /// How calls are spread over the endpoints (default round robin)
+    pub fn load_balancer(mut self, balancer: LoadBalancer) -> Self {
+        self.balancer = balancer;
+        self
+    }
+
+    /// How often registry endpoints are looked up again (default 30 seconds)
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Inject the current trace context into call metadata (default enabled)
+    pub fn with_trace_propagation(mut self, enabled: bool) -> Self {
+        self.trace_propagation = enabled;
+        self
+    }
+
     /// Connect to the gRPC server and return a channel
     pub async fn connect(self) -> Result<tonic::transport::Channel> {
-        let mut endpoint = tonic::transport::Endpoint::from_shared(self.endpoint)
+        let endpoint = self
+            .endpoints
+            .into_iter()
+            .next()
+            .ok_or_else(|| rf_errors::RfError::Network("No endpoint configured".to_string()))?;
+        let mut endpoint = tonic::transport::Endpoint::from_shared(endpoint)
             .map_err(|e| rf_errors::RfError::Network(format!("Invalid endpoint: {}", e)))?;
 
         if let Some(timeout) = self.timeout {
             endpoint = endpoint.timeout(timeout);
         }
+        if let Some(timeout) = self.connect_timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
 
         let channel = endpoint
             .connect()
@@ -55,5 +145,20 @@ impl GrpcClient {
 
         Ok(channel)
     }
-}
 
+    /// Build a load-balanced channel pool over all endpoints
+    ///
+    /// Connections are opened lazily. With a registry the endpoints are
+    /// looked up before returning, failing if the registry does.
+    pub async fn connect_pool(self) -> Result<PooledChannel> {
+        let options = PoolOptions {
+            balancer: self.balancer,
+            default_policy: self.default_policy.unwrap_or_else(|| RetryPolicy::new(self.retry_count)),
+            policies: self.policies,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            trace_propagation: self.trace_propagation,
+        };
+        PooledChannel::new(options, self.endpoints, self.discover, self.refresh_interval).await
+    }
+}
@@ -0,0 +1,823 @@
+//! # gateway
+//!
+//! gateway 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! HTTP/JSON and gRPC-Web gateway
+//!
+//! `GrpcGateway` serves gRPC services from an `HttpServer`, so one port
+//! carries REST clients, browsers and native gRPC clients:
+//!
+//! - HTTP/JSON requests are transcoded to gRPC calls, routed by the
+//!   `google.api.http` annotations in the services' descriptors or by rules
+//!   added with `route`
+//! - gRPC-Web calls (`application/grpc-web`, `application/grpc-web-text`)
+//!   are translated to gRPC, with the trailers sent at the end of the body
+//! - native gRPC calls over HTTP/2 are passed through
+//!
+//! ```ignore
+//! const DESCRIPTOR: &[u8] = tonic::include_file_descriptor_set!("shop_descriptor");
+//!
+//! let server = GrpcGateway::new()
+//!     .add_service(ShopServer::new(shop))
+//!     .with_descriptors(DESCRIPTOR)?
+//!     .route(HttpRule::get("/v1/orders/:id", "shop.Shop/GetOrder"))
+//!     .route(HttpRule::post("/v1/orders", "shop.Shop/CreateOrder").body("*"))
+//!     .mount(HttpServer::new(addr).with_cors())?;
+//! server.serve().await?;
+//! ```
+//!
+//! Path templates use the `google.api.http` syntax (`/v1/{name=shelves/*}`,
+//! `{path=**}`, `:verb` suffixes) or the router's `:id` and `*path`.
+//! Request fields not bound by the path or body are read from the query
+//! string. Failed calls answer with the HTTP status matching the gRPC code
+//! and a `{"code", "message", "details"}` JSON body.
+
+use super::reflection::{decode_file_descriptor_set, HttpRuleProto};
+use super::transcode::TypeRegistry;
+use axum::body::{Body, Bytes};
+use axum::extract::Request;
+use axum::http::header::{CONTENT_LENGTH, CONTENT_TYPE, TE};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router};
+use base64::Engine;
+use http_body::Frame;
+use http_body_util::{BodyExt, Full};
+use percent_encoding::percent_decode_str;
+use rf_errors::{Result, RfError};
+use rf_net::http::HttpServer;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tonic::body::{boxed, BoxBody};
+use tonic::codegen::{http, Service};
+use tonic::server::NamedService;
+use tonic::service::{Routes, RoutesBuilder};
+use tonic::{Code, Status};
+use tower::ServiceExt;
+
+const GRPC_WEB: &str = "application/grpc-web+proto";
+const GRPC_WEB_TEXT: &str = "application/grpc-web-text+proto";
+
+/// Request headers not passed on as gRPC metadata
+const HOP_HEADERS: &[&str] = &[
+    "host",
+    "content-type",
+    "content-length",
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "te",
+    "transfer-encoding",
+    "upgrade",
+    "accept-encoding",
+];
+
+/// An HTTP route bound to a gRPC method
+///
+/// The method is named `pkg.Service/Method` (a leading `/` or the dotted
+/// `pkg.Service.Method` form work too). Without a body binding the request
+/// message is built from the path and query string.
+#[derive(Debug, Clone)]
+pub struct HttpRule {
+    method: Method,
+    pattern: String,
+    rpc: String,
+    body: Option<String>,
+    response_body: Option<String>,
+}
+
+impl HttpRule {
+    pub fn new(method: Method, pattern: &str, rpc: &str) -> Self {
+        Self {
+            method,
+            pattern: pattern.to_string(),
+            rpc: rpc.to_string(),
+            body: None,
+            response_body: None,
+        }
+    }
+
+    pub fn get(pattern: &str, rpc: &str) -> Self {
+        Self::new(Method::GET, pattern, rpc)
+    }
+
+    pub fn post(pattern: &str, rpc: &str) -> Self {
+        Self::new(Method::POST, pattern, rpc)
+    }
+
+    pub fn put(pattern: &str, rpc: &str) -> Self {
+        Self::new(Method::PUT, pattern, rpc)
+    }
+
+    pub fn patch(pattern: &str, rpc: &str) -> Self {
+        Self::new(Method::PATCH, pattern, rpc)
+    }
+
+    pub fn delete(pattern: &str, rpc: &str) -> Self {
+        Self::new(Method::DELETE, pattern, rpc)
+    }
+
+    /// Read this request field from the JSON body, `*` for the whole message
+    pub fn body(mut self, field: &str) -> Self {
+        self.body = Some(field.to_string());
+        self
+    }
+
+    /// Answer with this response field instead of the whole message
+    pub fn response_body(mut self, field: &str) -> Self {
+        self.response_body = Some(field.to_string());
+        self
+    }
+
+    /// Rules of a `google.api.http` annotation, additional bindings included
+    fn from_annotation(http: &HttpRuleProto, rpc: &str) -> Vec<Self> {
+        let pattern = [
+            (Method::GET, &http.get),
+            (Method::PUT, &http.put),
+            (Method::POST, &http.post),
+            (Method::DELETE, &http.delete),
+            (Method::PATCH, &http.patch),
+        ]
+        .into_iter()
+        .find(|(_, pattern)| !pattern.is_empty())
+        .map(|(method, pattern)| (method, pattern.clone()))
+        .or_else(|| {
+            let custom = http.custom.as_ref()?;
+            match Method::from_bytes(custom.kind.as_bytes()) {
+                Ok(method) => Some((method, custom.path.clone())),
+                Err(_) => {
+                    tracing::warn!("Ignoring HTTP rule of {} with invalid method {}", rpc, custom.kind);
+                    None
+                }
+            }
+        });
+        let mut rules = Vec::new();
+        if let Some((method, pattern)) = pattern {
+            let mut rule = Self::new(method, &pattern, rpc);
+            if !http.body.is_empty() {
+                rule = rule.body(&http.body);
+            }
+            if !http.response_body.is_empty() {
+                rule = rule.response_body(&http.response_body);
+            }
+            rules.push(rule);
+        }
+        for binding in &http.additional_bindings {
+            rules.extend(Self::from_annotation(binding, rpc));
+        }
+        rules
+    }
+}
+
+/// `pkg.Service/Method` from the accepted method name forms
+fn method_name(rpc: &str) -> String {
+    let rpc = rpc.trim_start_matches('/');
+    match rpc.contains('/') {
+        true => rpc.to_string(),
+        false => match rpc.rsplit_once('.') {
+            Some((service, method)) => format!("{}/{}", service, method),
+            None => rpc.to_string(),
+        },
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    /// `*`, one path segment
+    Single,
+    /// `**`, the rest of the path
+    Multi,
+}
+
+/// A parsed HTTP rule path template
+#[derive(Debug)]
+struct PathTemplate {
+    segments: Vec<Segment>,
+    /// Bound field paths and the range of segments they capture
+    variables: Vec<(String, usize, usize)>,
+    verb: Option<String>,
+}
+
+impl PathTemplate {
+    fn parse(pattern: &str) -> Result<Self> {
+        let invalid = || RfError::InvalidParameter(format!("Invalid path template '{}'", pattern));
+        let rest = pattern.strip_prefix('/').ok_or_else(invalid)?;
+        let mut tokens = Vec::new();
+        let (mut depth, mut start) = (0, 0);
+        for (i, c) in rest.char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                '/' if depth == 0 => {
+                    tokens.push(&rest[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        tokens.push(&rest[start..]);
+
+        let mut verb = None;
+        if let Some(last) = tokens.last_mut() {
+            if let Some(pos) = last.rfind(':').filter(|&pos| pos > 0 && !last[pos..].contains('}')) {
+                verb = Some(last[pos + 1..].to_string());
+                *last = &last[..pos];
+            }
+        }
+
+        let mut template = Self {
+            segments: Vec::new(),
+            variables: Vec::new(),
+            verb,
+        };
+        for token in tokens {
+            let (field, pattern) = if let Some(inner) = token.strip_prefix('{').and_then(|t| t.strip_suffix('}')) {
+                let (field, pattern) = inner.split_once('=').unwrap_or((inner, "*"));
+                (Some(field), pattern)
+            } else if let Some(field) = token.strip_prefix(':') {
+                (Some(field), "*")
+            } else if let Some(field) = token.strip_prefix('*').filter(|f| !f.is_empty() && *f != "*") {
+                (Some(field), "**")
+            } else {
+                (None, token)
+            };
+            if field.is_some_and(|f| f.is_empty() || !f.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.')) {
+                return Err(invalid());
+            }
+            let start = template.segments.len();
+            for part in pattern.split('/') {
+                template.segments.push(match part {
+                    "*" => Segment::Single,
+                    "**" => Segment::Multi,
+                    part if part.contains(['{', '}', '*']) => return Err(invalid()),
+                    part => Segment::Literal(part.to_string()),
+                });
+            }
+            if let Some(field) = field {
+                template.variables.push((field.to_string(), start, template.segments.len()));
+            }
+        }
+        let multi = template.segments.iter().position(|s| *s == Segment::Multi);
+        if multi.is_some_and(|i| i + 1 != template.segments.len()) {
+            return Err(invalid());
+        }
+        Ok(template)
+    }
+
+    /// Bound field values when `path` matches
+    fn matches(&self, path: &str) -> Option<Vec<(String, String)>> {
+        let path = path.strip_prefix('/')?;
+        let path = match &self.verb {
+            Some(verb) => path.strip_suffix(verb.as_str())?.strip_suffix(':')?,
+            None => path,
+        };
+        let parts: Vec<&str> = path.split('/').collect();
+        let mut bounds = Vec::with_capacity(self.segments.len() + 1);
+        let mut index = 0;
+        for segment in &self.segments {
+            bounds.push(index);
+            match segment {
+                Segment::Literal(literal) => {
+                    if parts.get(index)? != literal {
+                        return None;
+                    }
+                    index += 1;
+                }
+                Segment::Single => {
+                    if parts.get(index)?.is_empty() {
+                        return None;
+                    }
+                    index += 1;
+                }
+                Segment::Multi => index = parts.len(),
+            }
+        }
+        bounds.push(index);
+        if index != parts.len() {
+            return None;
+        }
+        self.variables
+            .iter()
+            .map(|(field, start, end)| {
+                let raw = parts[bounds[*start]..bounds[*end]].join("/");
+                let value = percent_decode_str(&raw).decode_utf8().ok()?.into_owned();
+                Some((field.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Route matching at least the paths the template matches
+    fn axum_path(&self) -> String {
+        let last = self.segments.len().saturating_sub(1);
+        let mut path = String::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            path.push('/');
+            match segment {
+                Segment::Multi => path.push_str("{*_rest}"),
+                // The verb suffix needs a capture, axum has no partial segments
+                Segment::Literal(_) | Segment::Single if i == last && self.verb.is_some() => {
+                    path.push_str(&format!("{{_g{}}}", i))
+                }
+                Segment::Literal(literal) => path.push_str(literal),
+                Segment::Single => path.push_str(&format!("{{_g{}}}", i)),
+            }
+        }
+        path
+    }
+}
+
+/// A rule resolved against the method descriptors
+struct Binding {
+    method: Method,
+    template: PathTemplate,
+    /// `/pkg.Service/Method`
+    path: String,
+    input: String,
+    output: String,
+    body: Option<String>,
+    /// JSON name of the response field to answer with
+    response_body: Option<String>,
+    server_streaming: bool,
+}
+
+/// gRPC services exposed over HTTP/JSON, gRPC-Web and gRPC
+///
+/// JSON transcoding needs the services' file descriptor set (see
+/// `with_descriptors`); gRPC-Web and native gRPC work without it.
+pub struct GrpcGateway {
+    routes: RoutesBuilder,
+    services: Vec<&'static str>,
+    types: TypeRegistry,
+    rules: Vec<HttpRule>,
+    grpc_web: bool,
+    grpc: bool,
+}
+
+impl Default for GrpcGateway {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GrpcGateway {
+    pub fn new() -> Self {
+        Self {
+            routes: RoutesBuilder::default(),
+            services: Vec::new(),
+            types: TypeRegistry::default(),
+            rules: Vec::new(),
+            grpc_web: true,
+            grpc: true,
+        }
+    }
+
+    /// Add a service, e.g. a `tonic-build` generated `XxxServer`
+    pub fn add_service<S>(mut self, service: S) -> Self
+    where
+        S: Service<http::Request<BoxBody>, Response = http::Response<BoxBody>, Error = Infallible>
+            + NamedService
+            + Clone
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        self.routes.add_service(service);
+        self.services.push(S::NAME);
+        self
+    }
+
+    /// Describe messages and methods with an encoded `FileDescriptorSet`
+    ///
+    /// `google.api.http` annotations of the added services become routes.
+    /// Call once per descriptor set.
+    pub fn with_descriptors(mut self, file_descriptor_set: &[u8]) -> Result<Self> {
+        for (_, file) in decode_file_descriptor_set(file_descriptor_set)? {
+            self.types.add_file(&file);
+        }
+        Ok(self)
+    }
+
+    /// Add an HTTP route to a gRPC method, besides the annotated ones
+    pub fn route(mut self, rule: HttpRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Accept gRPC-Web calls on the services' paths (default enabled)
+    pub fn with_grpc_web(mut self, enabled: bool) -> Self {
+        self.grpc_web = enabled;
+        self
+    }
+
+    /// Accept native gRPC calls on the services' paths (default enabled)
+    pub fn with_grpc(mut self, enabled: bool) -> Self {
+        self.grpc = enabled;
+        self
+    }
+
+    /// Build the router serving the gateway
+    ///
+    /// Fails when a rule names an unknown method or field, a client
+    /// streaming method, or a service that was not added.
+    pub fn into_router(mut self) -> Result<Router> {
+        let mut rules = Vec::new();
+        for service in &self.services {
+            let mut methods: Vec<_> = self.types.service_methods(service).collect();
+            methods.sort_by_key(|(name, _)| *name);
+            for (name, method) in methods {
+                if let Some(http) = &method.http {
+                    rules.extend(HttpRule::from_annotation(http, &format!("{}/{}", service, name)));
+                }
+            }
+        }
+        rules.append(&mut self.rules);
+        let bindings = rules
+            .into_iter()
+            .map(|rule| self.bind(rule))
+            .collect::<Result<Vec<_>>>()?;
+
+        let gateway = Arc::new(Gateway {
+            routes: self.routes.routes().prepare(),
+            types: self.types,
+            bindings,
+            grpc_web: self.grpc_web,
+            grpc: self.grpc,
+        });
+        let mut router = Router::new();
+        if self.grpc || self.grpc_web {
+            for service in &self.services {
+                let gateway = gateway.clone();
+                let handler = tower::service_fn(move |request: Request| {
+                    let gateway = gateway.clone();
+                    async move { Ok::<_, Infallible>(gateway.grpc(request).await) }
+                });
+                router = router.route_service(&format!("/{}/{{_method}}", service), handler);
+            }
+        }
+        let mut paths = HashSet::new();
+        for binding in &gateway.bindings {
+            let path = binding.template.axum_path();
+            if !paths.insert(path.clone()) {
+                continue;
+            }
+            let gateway = gateway.clone();
+            let handler = tower::service_fn(move |request: Request| {
+                let gateway = gateway.clone();
+                async move { Ok::<_, Infallible>(gateway.json(request).await) }
+            });
+            router = router.route_service(&path, handler);
+        }
+        Ok(router)
+    }
+
+    /// Serve the gateway from an `HttpServer`, next to its other routes
+    pub fn mount(self, mut server: HttpServer) -> Result<HttpServer> {
+        let router = std::mem::take(server.router()).merge(self.into_router()?);
+        *server.router() = router;
+        Ok(server)
+    }
+
+    fn bind(&self, rule: HttpRule) -> Result<Binding> {
+        let name = method_name(&rule.rpc);
+        let invalid = |message: String| RfError::InvalidParameter(format!("HTTP rule {} {}: {}", rule.method, rule.pattern, message));
+        if !self.services.iter().any(|service| name.strip_prefix(service).is_some_and(|m| m.starts_with('/'))) {
+            return Err(invalid(format!("service of {} is not added to the gateway", name)));
+        }
+        let method = self
+            .types
+            .method(&name)
+            .ok_or_else(|| invalid(format!("no descriptor for {}, see with_descriptors", name)))?;
+        if method.client_streaming {
+            return Err(invalid(format!("client streaming method {} cannot be transcoded", name)));
+        }
+        let template = PathTemplate::parse(&rule.pattern)?;
+        let fields = template.variables.iter().map(|(field, ..)| field).chain(rule.body.iter().filter(|f| *f != "*"));
+        for field in fields {
+            if !self.types.has_field_path(&method.input, field) {
+                return Err(invalid(format!("unknown field '{}' in {}", field, method.input)));
+            }
+        }
+        let response_body = match &rule.response_body {
+            Some(field) => Some(
+                self.types
+                    .json_name(&method.output, field)
+                    .ok_or_else(|| invalid(format!("unknown field '{}' in {}", field, method.output)))?,
+            ),
+            None => None,
+        };
+        Ok(Binding {
+            method: rule.method,
+            template,
+            path: format!("/{}", name),
+            input: method.input.clone(),
+            output: method.output.clone(),
+            body: rule.body,
+            response_body,
+            server_streaming: method.server_streaming,
+        })
+    }
+}
+
+struct Gateway {
+    routes: Routes,
+    types: TypeRegistry,
+    bindings: Vec<Binding>,
+    grpc_web: bool,
+    grpc: bool,
+}
+
+impl Gateway {
+    async fn call(&self, request: http::Request<BoxBody>) -> http::Response<BoxBody> {
+        match self.routes.clone().oneshot(request).await {
+            Ok(response) => response,
+            Err(e) => Status::internal(e.to_string()).into_http(),
+        }
+    }
+
+    /// gRPC and gRPC-Web calls on a service path
+    async fn grpc(&self, request: Request) -> Response {
+        let content_type = request.headers().get(CONTENT_TYPE).map(HeaderValue::as_bytes).unwrap_or_default();
+        if content_type.starts_with(b"application/grpc-web") {
+            if self.grpc_web {
+                return self.grpc_web(request).await;
+            }
+        } else if content_type.starts_with(b"application/grpc") && self.grpc {
+            return self.call(request.map(boxed)).await.map(Body::new);
+        }
+        StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response()
+    }
+
+    async fn grpc_web(&self, request: Request) -> Response {
+        let (mut parts, body) = request.into_parts();
+        let text = parts
+            .headers
+            .get(CONTENT_TYPE)
+            .is_some_and(|v| v.as_bytes().starts_with(b"application/grpc-web-text"));
+        let body = if text {
+            let decoded = match body.collect().await {
+                Ok(collected) => decode_text(&collected.to_bytes()),
+                Err(_) => None,
+            };
+            match decoded {
+                Some(decoded) => boxed(Full::new(Bytes::from(decoded))),
+                None => return web_response(Status::invalid_argument("Invalid grpc-web-text body").into_http(), text),
+            }
+        } else {
+            boxed(body)
+        };
+        parts.headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+        parts.headers.insert(TE, HeaderValue::from_static("trailers"));
+        parts.headers.remove(CONTENT_LENGTH);
+        let response = self.call(http::Request::from_parts(parts, body)).await;
+        web_response(response, text)
+    }
+
+    /// HTTP/JSON requests on a rule path
+    async fn json(&self, request: Request) -> Response {
+        let path = request.uri().path().to_string();
+        let mut other_method = false;
+        for binding in &self.bindings {
+            let Some(variables) = binding.template.matches(&path) else {
+                continue;
+            };
+            if binding.method != request.method() {
+                other_method = true;
+                continue;
+            }
+            return match self.transcode(binding, variables, request).await {
+                Ok(response) => response,
+                Err(status) => error_response(http_status(status.code()), &status),
+            };
+        }
+        match other_method {
+            true => error_response(StatusCode::METHOD_NOT_ALLOWED, &Status::unimplemented("Method not allowed")),
+            false => error_response(StatusCode::NOT_FOUND, &Status::not_found("Not found")),
+        }
+    }
+
+    async fn transcode(&self, binding: &Binding, variables: Vec<(String, String)>, request: Request) -> std::result::Result<Response, Status> {
+        let (parts, body) = request.into_parts();
+        let mut message = Map::new();
+        match binding.body.as_deref() {
+            Some(field) => {
+                let bytes = body
+                    .collect()
+                    .await
+                    .map_err(|e| Status::invalid_argument(format!("Failed to read request body: {}", e)))?
+                    .to_bytes();
+                let value = match bytes.iter().all(u8::is_ascii_whitespace) {
+                    true => Value::Null,
+                    false => serde_json::from_slice(&bytes)
+                        .map_err(|e| Status::invalid_argument(format!("Invalid JSON body: {}", e)))?,
+                };
+                match (field, value) {
+                    (_, Value::Null) => {}
+                    ("*", Value::Object(object)) => message = object,
+                    ("*", _) => return Err(Status::invalid_argument("Request body must be a JSON object")),
+                    (field, value) => {
+                        self.types.set_field(&binding.input, &mut message, field, value, false);
+                    }
+                }
+            }
+            None => {
+                let query = parts.uri.query().unwrap_or_default();
+                for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+                    // Unknown parameters, e.g. cache busters, are ignored
+                    self.types.set_field(&binding.input, &mut message, &key, Value::String(value.into_owned()), true);
+                }
+            }
+        }
+        for (field, value) in variables {
+            self.types.set_field(&binding.input, &mut message, &field, Value::String(value), false);
+        }
+
+        let payload = self.types.encode(&binding.input, &Value::Object(message))?;
+        let mut frame = Vec::with_capacity(payload.len() + 5);
+        frame.push(0);
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&payload);
+        let mut request = http::Request::post(binding.path.as_str())
+            .body(boxed(Full::new(Bytes::from(frame))))
+            .map_err(|e| Status::internal(e.to_string()))?;
+        for (name, value) in &parts.headers {
+            if !HOP_HEADERS.contains(&name.as_str()) {
+                request.headers_mut().append(name.clone(), value.clone());
+            }
+        }
+        request.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+        request.headers_mut().insert(TE, HeaderValue::from_static("trailers"));
+        request.extensions_mut().extend(parts.extensions);
+
+        let (parts, body) = self.call(request).await.into_parts();
+        check_status(&parts.headers)?;
+        let collected = body.collect().await?;
+        if let Some(trailers) = collected.trailers() {
+            check_status(trailers)?;
+        }
+        let bytes = collected.to_bytes();
+        let mut rest = &bytes[..];
+        let mut messages = Vec::new();
+        while !rest.is_empty() {
+            let (header, body) = rest.split_at_checked(5).ok_or_else(|| Status::internal("Truncated gRPC frame"))?;
+            if header[0] & 1 != 0 {
+                return Err(Status::internal("Compressed gRPC response"));
+            }
+            let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+            let (message, next) = body.split_at_checked(len).ok_or_else(|| Status::internal("Truncated gRPC frame"))?;
+            let mut value = self.types.decode(&binding.output, message)?;
+            if let Some(field) = &binding.response_body {
+                value = value.get(field).cloned().unwrap_or(Value::Null);
+            }
+            messages.push(value);
+            rest = next;
+        }
+        let value = match binding.server_streaming {
+            true => Value::Array(messages),
+            false => messages.into_iter().next().ok_or_else(|| Status::internal("Missing response message"))?,
+        };
+
+        let mut response = Json(value).into_response();
+        for (name, value) in &parts.headers {
+            if name != CONTENT_TYPE && !name.as_str().starts_with("grpc-") {
+                response.headers_mut().append(name.clone(), value.clone());
+            }
+        }
+        Ok(response)
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn check_status(headers: &HeaderMap) -> std::result::Result<(), Status> {
+    match Status::from_header_map(headers) {
+        Some(status) if status.code() != Code::Ok => Err(status),
+        _ => Ok(()),
+    }
+}
+
+/// HTTP status for a gRPC code, as grpc-gateway maps them
+fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::Cancelled => StatusCode::from_u16(499).unwrap_or(StatusCode::BAD_REQUEST),
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => StatusCode::BAD_REQUEST,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn error_response(http_status: StatusCode, status: &Status) -> Response {
+    let body = serde_json::json!({
+        "code": status.code() as i32,
+        "message": status.message(),
+        "details": [],
+    });
+    (http_status, Json(body)).into_response()
+}
+
+/// Decode a `grpc-web-text` body, which may be several padded base64 chunks
+fn decode_text(bytes: &[u8]) -> Option<Vec<u8>> {
+    let text: Vec<u8> = bytes.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !text.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(text.len() / 4 * 3);
+    for group in text.chunks(4) {
+        decoded.extend(base64::engine::general_purpose::STANDARD.decode(group).ok()?);
+    }
+    Some(decoded)
+}
+
+fn web_response(response: http::Response<BoxBody>, text: bool) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let content_type = if text { GRPC_WEB_TEXT } else { GRPC_WEB };
+    parts.headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    let body = GrpcWebBody {
+        inner: body,
+        text,
+        done: false,
+    };
+    Response::from_parts(parts, Body::new(body))
+}
+
+/// gRPC response body with the trailers moved into a final frame
+struct GrpcWebBody {
+    inner: BoxBody,
+    text: bool,
+    done: bool,
+}
+
+impl GrpcWebBody {
+    fn encode(&self, data: Bytes) -> Bytes {
+        match self.text {
+            true => Bytes::from(base64::engine::general_purpose::STANDARD.encode(&data)),
+            false => data,
+        }
+    }
+
+    fn trailers(&mut self, trailers: &HeaderMap) -> Frame<Bytes> {
+        self.done = true;
+        let mut block = Vec::new();
+        for (name, value) in trailers {
+            block.extend_from_slice(name.as_str().as_bytes());
+            block.push(b':');
+            block.extend_from_slice(value.as_bytes());
+            block.extend_from_slice(b"\r\n");
+        }
+        let mut frame = Vec::with_capacity(block.len() + 5);
+        frame.push(0x80);
+        frame.extend_from_slice(&(block.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&block);
+        Frame::data(self.encode(Bytes::from(frame)))
+    }
+}
+
+impl http_body::Body for GrpcWebBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<std::result::Result<Frame<Bytes>, Status>>> {
+        while !self.done {
+            let frame = match ready!(Pin::new(&mut self.inner).poll_frame(cx)) {
+                Some(Ok(frame)) => frame,
+                // Report a failure mid-stream the way trailers would
+                Some(Err(status)) => {
+                    let mut trailers = HeaderMap::new();
+                    let _ = status.add_header(&mut trailers);
+                    return Poll::Ready(Some(Ok(self.trailers(&trailers))));
+                }
+                None => {
+                    self.done = true;
+                    return Poll::Ready(None);
+                }
+            };
+            match frame.into_data() {
+                Ok(data) => return Poll::Ready(Some(Ok(Frame::data(self.encode(data))))),
+                Err(frame) => {
+                    if let Ok(trailers) = frame.into_trailers() {
+                        return Poll::Ready(Some(Ok(self.trailers(&trailers))));
+                    }
+                }
+            }
+        }
+        Poll::Ready(None)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+}
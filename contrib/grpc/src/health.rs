@@ -0,0 +1,224 @@
+//! # health
+//!
+//! health 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! gRPC health checking protocol
+//!
+//! Implements `grpc.health.v1.Health` so load balancers, Kubernetes gRPC
+//! probes and `grpc_health_probe` can check the server. `GrpcServer`
+//! registers it by default, marks every added service as serving and flips
+//! all of them to not serving when shutdown starts.
+//!
+//! ```ignore
+//! let server = GrpcServer::new(addr).add_service(GreeterServer::new(greeter));
+//! let health = server.health_reporter();
+//!
+//! // Take the service out of rotation while its database is down
+//! health.set_not_serving("helloworld.Greeter");
+//! ```
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use tokio::sync::{mpsc, watch};
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::tokio_stream::wrappers::ReceiverStream;
+use tonic::codegen::{http, BoxFuture, Service};
+use tonic::server::{Grpc, NamedService};
+use tonic::{Request, Response, Status};
+
+/// `grpc.health.v1.HealthCheckRequest`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct HealthCheckRequest {
+    /// Service name, empty for the whole server
+    #[prost(string, tag = "1")]
+    pub service: String,
+}
+
+/// `grpc.health.v1.HealthCheckResponse`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct HealthCheckResponse {
+    #[prost(enumeration = "ServingStatus", tag = "1")]
+    pub status: i32,
+}
+
+/// Serving status of a service
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum ServingStatus {
+    Unknown = 0,
+    Serving = 1,
+    NotServing = 2,
+    /// Only sent by `Watch` for services the server does not know
+    ServiceUnknown = 3,
+}
+
+/// Handle for updating the statuses reported by the health service
+#[derive(Clone)]
+pub struct HealthReporter {
+    statuses: Arc<RwLock<HashMap<String, watch::Sender<ServingStatus>>>>,
+    /// Set when the server shuts down, ending `Watch` streams
+    shutdown: Arc<watch::Sender<bool>>,
+}
+
+impl HealthReporter {
+    /// Reporter with the whole server (`""`) serving
+    pub fn new() -> Self {
+        let reporter = Self {
+            statuses: Arc::new(RwLock::new(HashMap::new())),
+            shutdown: Arc::new(watch::channel(false).0),
+        };
+        reporter.set_serving("");
+        reporter
+    }
+
+    /// Mark a service as serving
+    pub fn set_serving(&self, service: &str) {
+        self.set_status(service, ServingStatus::Serving);
+    }
+
+    /// Mark a service as not serving
+    pub fn set_not_serving(&self, service: &str) {
+        self.set_status(service, ServingStatus::NotServing);
+    }
+
+    /// Set the status of a service, notifying `Watch` callers
+    pub fn set_status(&self, service: &str, status: ServingStatus) {
+        let mut statuses = self.statuses.write().unwrap_or_else(|e| e.into_inner());
+        match statuses.get(service) {
+            Some(sender) => {
+                sender.send_if_modified(|current| std::mem::replace(current, status) != status);
+            }
+            None => {
+                statuses.insert(service.to_string(), watch::channel(status).0);
+            }
+        }
+    }
+
+    /// Current status of a service, `None` if it was never reported
+    pub fn status(&self, service: &str) -> Option<ServingStatus> {
+        let statuses = self.statuses.read().unwrap_or_else(|e| e.into_inner());
+        statuses
+            .get(service)
+            .map(|sender| *sender.borrow())
+            .filter(|status| *status != ServingStatus::ServiceUnknown)
+    }
+
+    /// Mark every service, including the server itself, as not serving
+    pub fn set_all_not_serving(&self) {
+        let services: Vec<String> = {
+            let statuses = self.statuses.read().unwrap_or_else(|e| e.into_inner());
+            statuses.keys().cloned().collect()
+        };
+        for service in services {
+            if self.status(&service).is_some() {
+                self.set_not_serving(&service);
+            }
+        }
+    }
+
+    /// Report everything as not serving and end the `Watch` streams, which
+    /// would otherwise hold the server's graceful shutdown open
+    pub(crate) fn shutdown(&self) {
+        self.set_all_not_serving();
+        self.shutdown.send_replace(true);
+    }
+
+    /// The `grpc.health.v1.Health` service reporting these statuses
+    pub fn service(&self) -> HealthService {
+        HealthService {
+            reporter: self.clone(),
+        }
+    }
+
+    fn subscribe(&self, service: &str) -> watch::Receiver<ServingStatus> {
+        let mut statuses = self.statuses.write().unwrap_or_else(|e| e.into_inner());
+        statuses
+            .entry(service.to_string())
+            .or_insert_with(|| watch::channel(ServingStatus::ServiceUnknown).0)
+            .subscribe()
+    }
+
+    fn watch(&self, service: &str) -> ReceiverStream<Result<HealthCheckResponse, Status>> {
+        let mut statuses = self.subscribe(service);
+        let mut shutdown = self.shutdown.subscribe();
+        let (tx, rx) = mpsc::channel(4);
+        tokio::spawn(async move {
+            let send = |status: ServingStatus| tx.send(Ok(HealthCheckResponse { status: status as i32 }));
+            loop {
+                let status = *statuses.borrow_and_update();
+                if send(status).await.is_err() {
+                    break;
+                }
+                tokio::select! {
+                    changed = statuses.changed() => if changed.is_err() { break },
+                    _ = async { shutdown.wait_for(|down| *down).await.is_ok() } => {
+                        if statuses.has_changed().unwrap_or(false) {
+                            let status = *statuses.borrow_and_update();
+                            let _ = send(status).await;
+                        }
+                        break;
+                    }
+                    _ = tx.closed() => break,
+                }
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+}
+
+impl Default for HealthReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `grpc.health.v1.Health` service
+#[derive(Clone)]
+pub struct HealthService {
+    reporter: HealthReporter,
+}
+
+impl NamedService for HealthService {
+    const NAME: &'static str = "grpc.health.v1.Health";
+}
+
+impl Service<http::Request<BoxBody>> for HealthService {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        let reporter = self.reporter.clone();
+        match req.uri().path() {
+            "/grpc.health.v1.Health/Check" => Box::pin(async move {
+                let method = tower::service_fn(move |request: Request<HealthCheckRequest>| {
+                    let service = request.into_inner().service;
+                    let response = match reporter.status(&service) {
+                        Some(status) => Ok(Response::new(HealthCheckResponse { status: status as i32 })),
+                        None => Err(Status::not_found(format!("Unknown service {}", service))),
+                    };
+                    async move { response }
+                });
+                Ok(Grpc::new(ProstCodec::default()).unary(method, req).await)
+            }),
+            "/grpc.health.v1.Health/Watch" => Box::pin(async move {
+                let method = tower::service_fn(move |request: Request<HealthCheckRequest>| {
+                    let stream = reporter.watch(&request.into_inner().service);
+                    async move { Ok::<_, Status>(Response::new(stream)) }
+                });
+                Ok(Grpc::new(ProstCodec::default()).server_streaming(method, req).await)
+            }),
+            _ => Box::pin(async { Ok(Status::unimplemented("").into_http()) }),
+        }
+    }
+}
@@ -7,13 +7,28 @@
 
 //! RF gRPC framework
 //!
-//! Provides gRPC server and client encapsulation with middleware support
+//! Provides gRPC server and client encapsulation with middleware support,
+//! the gRPC health checking protocol, server reflection, a load-balanced
+//! client channel pool, an HTTP/JSON and gRPC-Web gateway and trace context
+//! propagation over call metadata
 
 pub mod server;
 pub mod client;
 pub mod middleware;
+pub mod health;
+pub mod reflection;
+pub mod pool;
+pub mod gateway;
+pub mod trace;
+mod limits;
+mod transcode;
 
 pub use server::GrpcServer;
 pub use client::GrpcClient;
 pub use middleware::*;
+pub use health::{HealthReporter, HealthService, ServingStatus};
+pub use pool::{LoadBalancer, PooledChannel, RetryPolicy};
+pub use gateway::{GrpcGateway, HttpRule};
+pub use reflection::{DescriptorIndex, ReflectionService, ReflectionServiceV1};
+pub use trace::{extract_context, inject_context, MetadataExtractor, MetadataInjector, TraceInterceptor};
 
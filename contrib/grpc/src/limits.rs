@@ -0,0 +1,227 @@
+//! # limits
+//!
+//! limits 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Server-wide concurrency and message size limits
+//!
+//! Applied by `GrpcServer` in front of every service. Message sizes are
+//! checked on the gRPC length prefix of each message, so oversized messages
+//! fail with `RESOURCE_EXHAUSTED` before they are buffered.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tonic::body::BoxBody;
+use tonic::codegen::{http, BoxFuture, Bytes, Service};
+use tonic::Status;
+use tower::Layer;
+
+/// Limits enforced by `LimitsLayer`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GrpcLimits {
+    pub max_concurrent_requests: Option<usize>,
+    pub max_decoding_message_size: Option<usize>,
+    pub max_encoding_message_size: Option<usize>,
+}
+
+impl GrpcLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_concurrent_requests.is_none()
+            && self.max_decoding_message_size.is_none()
+            && self.max_encoding_message_size.is_none()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct LimitsLayer {
+    permits: Option<Arc<Semaphore>>,
+    max_decoding: Option<usize>,
+    max_encoding: Option<usize>,
+}
+
+impl LimitsLayer {
+    pub fn new(limits: &GrpcLimits) -> Self {
+        Self {
+            permits: limits.max_concurrent_requests.map(|n| Arc::new(Semaphore::new(n.max(1)))),
+            max_decoding: limits.max_decoding_message_size,
+            max_encoding: limits.max_encoding_message_size,
+        }
+    }
+}
+
+impl<S> Layer<S> for LimitsLayer {
+    type Service = Limits<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Limits {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Limits<S> {
+    inner: S,
+    layer: LimitsLayer,
+}
+
+impl<S> Service<http::Request<BoxBody>> for Limits<S>
+where
+    S: Service<http::Request<BoxBody>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        // The clone may not be ready; call the instance poll_ready was called on
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+        Box::pin(async move {
+            // Held until the response body ends, covering streaming responses
+            let permit = match layer.permits {
+                Some(permits) => permits.acquire_owned().await.ok(),
+                None => None,
+            };
+            let req = match layer.max_decoding {
+                Some(limit) => req.map(|body| tonic::body::boxed(LimitedBody::new(body, Some(limit), Direction::Decode, None))),
+                None => req,
+            };
+            let response = inner.call(req).await?;
+            if layer.max_encoding.is_none() && permit.is_none() {
+                return Ok(response);
+            }
+            Ok(response.map(|body| tonic::body::boxed(LimitedBody::new(body, layer.max_encoding, Direction::Encode, permit))))
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Decode,
+    Encode,
+}
+
+/// Finds the length prefixes of gRPC messages in a body
+#[derive(Default)]
+struct FrameScanner {
+    header: [u8; 5],
+    filled: usize,
+    /// Payload bytes of the current message still to skip
+    remaining: usize,
+}
+
+impl FrameScanner {
+    /// Length of the first message longer than `limit`
+    fn scan(&mut self, mut data: &[u8], limit: usize) -> Option<usize> {
+        while !data.is_empty() {
+            if self.remaining > 0 {
+                let n = self.remaining.min(data.len());
+                self.remaining -= n;
+                data = &data[n..];
+                continue;
+            }
+            let n = (5 - self.filled).min(data.len());
+            self.header[self.filled..self.filled + n].copy_from_slice(&data[..n]);
+            self.filled += n;
+            data = &data[n..];
+            if self.filled == 5 {
+                self.filled = 0;
+                let length = u32::from_be_bytes([self.header[1], self.header[2], self.header[3], self.header[4]]) as usize;
+                if length > limit {
+                    return Some(length);
+                }
+                self.remaining = length;
+            }
+        }
+        None
+    }
+}
+
+struct LimitedBody {
+    inner: BoxBody,
+    limit: Option<usize>,
+    direction: Direction,
+    scanner: FrameScanner,
+    done: bool,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl LimitedBody {
+    fn new(inner: BoxBody, limit: Option<usize>, direction: Direction, permit: Option<OwnedSemaphorePermit>) -> Self {
+        Self {
+            inner,
+            limit,
+            direction,
+            scanner: FrameScanner::default(),
+            done: false,
+            _permit: permit,
+        }
+    }
+}
+
+impl http_body::Body for LimitedBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        let frame = match ready!(Pin::new(&mut self.inner).poll_frame(cx)) {
+            Some(Ok(frame)) => frame,
+            other => return Poll::Ready(other),
+        };
+        let this = &mut *self;
+        let oversized = match (this.limit, frame.data_ref()) {
+            (Some(limit), Some(data)) => this.scanner.scan(data, limit).map(|length| (length, limit)),
+            _ => None,
+        };
+        let Some((length, limit)) = oversized else {
+            return Poll::Ready(Some(Ok(frame)));
+        };
+        this.done = true;
+        let status = match this.direction {
+            Direction::Decode => Status::resource_exhausted(format!(
+                "Received message larger than max ({} vs. {})",
+                length, limit
+            )),
+            Direction::Encode => Status::resource_exhausted(format!(
+                "Attempted to send message larger than max ({} vs. {})",
+                length, limit
+            )),
+        };
+        match this.direction {
+            Direction::Decode => Poll::Ready(Some(Err(status))),
+            // Response headers are already out, so end with error trailers
+            Direction::Encode => {
+                tracing::warn!("{}", status.message());
+                let mut trailers = http::HeaderMap::new();
+                let _ = status.add_header(&mut trailers);
+                Poll::Ready(Some(Ok(http_body::Frame::trailers(trailers))))
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done || self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,563 @@
+//! # pool
+//!
+//! pool 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Load-balanced gRPC channel pool
+//!
+//! `PooledChannel` spreads calls over several endpoints, either a fixed list
+//! or the instances of a service in a `ServiceRegistry`, retries failed calls
+//! on another endpoint according to a per-method `RetryPolicy`, enforces a
+//! deadline across all attempts and injects the current trace context into
+//! the call metadata. It is used like a tonic `Channel`:
+//!
+//! ```ignore
+//! let channel = GrpcClient::discover(registry, "greeter")
+//!     .load_balancer(LoadBalancer::LeastRequests)
+//!     .method_policy("/helloworld.Greeter/SayHello", RetryPolicy::new(2).timeout(Duration::from_secs(1)))
+//!     .connect_pool()
+//!     .await?;
+//! let mut client = GreeterClient::new(channel);
+//! ```
+//!
+//! Only calls whose request body is complete when the call starts (unary and
+//! server streaming) are retried; client and bidirectional streams get a
+//! single attempt.
+
+use rand::Rng;
+use rf_contrib_registry::{ServiceHealth, ServiceRegistry};
+use rf_errors::{Result, RfError};
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::Instant;
+use tonic::body::BoxBody;
+use tonic::codegen::{http, BoxFuture, Bytes, Service};
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Status};
+use tower::ServiceExt;
+
+/// Request bodies larger than this are not buffered for retries
+const RETRY_BUFFER_LIMIT: usize = 4 * 1024 * 1024;
+
+/// How long an endpoint that failed to connect is skipped
+const EJECT_DURATION: Duration = Duration::from_secs(5);
+
+/// How calls are spread over the endpoints
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoadBalancer {
+    /// Each endpoint in turn
+    #[default]
+    RoundRobin,
+    /// A random endpoint
+    Random,
+    /// The endpoint with the fewest calls in flight
+    LeastRequests,
+}
+
+/// Retry and deadline policy for a method or service
+///
+/// A failed attempt is retried on another endpoint when the transport fails
+/// or the server answers with one of the retryable codes before sending a
+/// response, waiting an exponential backoff with jitter in between.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_retries: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    retryable: Vec<Code>,
+    timeout: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// Retry up to `max_retries` times on `UNAVAILABLE`
+    pub fn new(max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(2),
+            multiplier: 2.0,
+            retryable: vec![Code::Unavailable],
+            timeout: None,
+        }
+    }
+
+    /// Never retry
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    /// Backoff before the first retry and the cap it doubles up to
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Factor the backoff grows by after each retry (default 2)
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Status codes worth retrying (default `UNAVAILABLE`)
+    ///
+    /// Only list codes for which a retry is safe, e.g. `RESOURCE_EXHAUSTED`
+    /// for idempotent methods.
+    pub fn retry_on(mut self, codes: impl IntoIterator<Item = Code>) -> Self {
+        self.retryable = codes.into_iter().collect();
+        self
+    }
+
+    /// Deadline for the call including all retries
+    ///
+    /// Failing calls end with `DEADLINE_EXCEEDED`. A shorter deadline set by
+    /// the caller with `Request::set_timeout` still applies.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn backoff_for(&self, retry: usize) -> Duration {
+        let factor = self.multiplier.powi(retry.min(32) as i32);
+        let backoff = self.initial_backoff.mul_f64(factor).min(self.max_backoff);
+        // Jitter between half and the whole backoff
+        backoff.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+/// Options `GrpcClient` builds a pool from
+pub(crate) struct PoolOptions {
+    pub balancer: LoadBalancer,
+    pub default_policy: RetryPolicy,
+    pub policies: HashMap<String, RetryPolicy>,
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub trace_propagation: bool,
+}
+
+/// Where the pool's endpoints come from
+pub(crate) type Discover = Arc<dyn Fn() -> Result<Vec<String>> + Send + Sync>;
+
+/// Wrap a registry lookup of `service`, keeping instances not marked unhealthy
+pub(crate) fn registry_discover<R>(registry: Arc<R>, service: String) -> Discover
+where
+    R: ServiceRegistry + 'static,
+{
+    Arc::new(move || {
+        Ok(registry
+            .discover(&service)?
+            .into_iter()
+            .filter(|instance| instance.health != ServiceHealth::Unhealthy)
+            .map(|instance| format!("http://{}", instance.address))
+            .collect())
+    })
+}
+
+struct PoolEndpoint {
+    uri: String,
+    channel: Channel,
+    pending: AtomicUsize,
+    ejected_until: Mutex<Option<Instant>>,
+}
+
+impl PoolEndpoint {
+    fn is_ejected(&self, now: Instant) -> bool {
+        let ejected = self.ejected_until.lock().unwrap_or_else(|e| e.into_inner());
+        ejected.is_some_and(|until| until > now)
+    }
+
+    fn eject(&self) {
+        *self.ejected_until.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now() + EJECT_DURATION);
+    }
+
+    fn restore(&self) {
+        *self.ejected_until.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+struct PoolInner {
+    options: PoolOptions,
+    endpoints: RwLock<Vec<Arc<PoolEndpoint>>>,
+    next: AtomicUsize,
+    discover: Option<Discover>,
+}
+
+impl PoolInner {
+    fn policy(&self, path: &str) -> &RetryPolicy {
+        let service = path.trim_start_matches('/').split('/').next().unwrap_or_default();
+        self.options
+            .policies
+            .get(path)
+            .or_else(|| self.options.policies.get(service))
+            .unwrap_or(&self.options.default_policy)
+    }
+
+    fn connect(&self, uri: &str) -> Result<Channel> {
+        let mut endpoint = Endpoint::from_shared(uri.to_string())
+            .map_err(|e| RfError::Network(format!("Invalid endpoint {}: {}", uri, e)))?;
+        if let Some(timeout) = self.options.connect_timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
+        Ok(endpoint.connect_lazy())
+    }
+
+    /// Replace the endpoint list, keeping the channels of unchanged endpoints
+    fn update(&self, uris: Vec<String>) -> Result<()> {
+        let current: HashMap<String, Arc<PoolEndpoint>> = self
+            .endpoints
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|endpoint| (endpoint.uri.clone(), endpoint.clone()))
+            .collect();
+        let mut endpoints = Vec::with_capacity(uris.len());
+        for uri in uris {
+            if endpoints.iter().any(|endpoint: &Arc<PoolEndpoint>| endpoint.uri == uri) {
+                continue;
+            }
+            let endpoint = match current.get(&uri) {
+                Some(endpoint) => endpoint.clone(),
+                None => Arc::new(PoolEndpoint {
+                    channel: self.connect(&uri)?,
+                    uri,
+                    pending: AtomicUsize::new(0),
+                    ejected_until: Mutex::new(None),
+                }),
+            };
+            endpoints.push(endpoint);
+        }
+        *self.endpoints.write().unwrap_or_else(|e| e.into_inner()) = endpoints;
+        Ok(())
+    }
+
+    async fn refresh(&self) -> Result<()> {
+        let Some(discover) = self.discover.clone() else {
+            return Ok(());
+        };
+        let uris = tokio::task::spawn_blocking(move || discover())
+            .await
+            .map_err(|e| RfError::Internal(format!("Endpoint discovery failed: {}", e)))??;
+        self.update(uris)
+    }
+
+    /// Pick an endpoint, avoiding `avoid` and ejected endpoints when possible
+    fn pick(&self, avoid: Option<&str>) -> Option<Arc<PoolEndpoint>> {
+        let endpoints = self.endpoints.read().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let healthy: Vec<&Arc<PoolEndpoint>> = endpoints.iter().filter(|e| !e.is_ejected(now)).collect();
+        let preferred: Vec<&Arc<PoolEndpoint>> = healthy.iter().copied().filter(|e| Some(e.uri.as_str()) != avoid).collect();
+        let candidates = match (preferred.is_empty(), healthy.is_empty()) {
+            (false, _) => preferred,
+            (true, false) => healthy,
+            (true, true) => endpoints.iter().collect(),
+        };
+        if candidates.is_empty() {
+            return None;
+        }
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let index = match self.options.balancer {
+            LoadBalancer::RoundRobin => start % candidates.len(),
+            LoadBalancer::Random => rand::thread_rng().gen_range(0..candidates.len()),
+            LoadBalancer::LeastRequests => (0..candidates.len())
+                .map(|offset| (start + offset) % candidates.len())
+                .min_by_key(|&i| candidates[i].pending.load(Ordering::Relaxed))
+                .unwrap_or(0),
+        };
+        Some(candidates[index].clone())
+    }
+}
+
+/// Load-balanced, retrying gRPC channel
+///
+/// Cheap to clone; clones share the endpoints. Pass it to a generated
+/// client in place of a `Channel`.
+#[derive(Clone)]
+pub struct PooledChannel {
+    inner: Arc<PoolInner>,
+}
+
+impl PooledChannel {
+    pub(crate) async fn new(
+        options: PoolOptions,
+        uris: Vec<String>,
+        discover: Option<Discover>,
+        refresh_interval: Duration,
+    ) -> Result<Self> {
+        let inner = Arc::new(PoolInner {
+            options,
+            endpoints: RwLock::new(Vec::new()),
+            next: AtomicUsize::new(0),
+            discover,
+        });
+        inner.update(uris)?;
+        if inner.discover.is_some() {
+            inner.refresh().await?;
+            tokio::spawn(refresh_loop(Arc::downgrade(&inner), refresh_interval));
+        }
+        Ok(Self { inner })
+    }
+
+    /// URIs of the endpoints calls are currently spread over
+    pub fn endpoints(&self) -> Vec<String> {
+        let endpoints = self.inner.endpoints.read().unwrap_or_else(|e| e.into_inner());
+        endpoints.iter().map(|endpoint| endpoint.uri.clone()).collect()
+    }
+
+    /// Look the endpoints up in the registry now instead of waiting for the
+    /// next refresh
+    pub async fn refresh(&self) -> Result<()> {
+        self.inner.refresh().await
+    }
+}
+
+async fn refresh_loop(inner: Weak<PoolInner>, interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let Some(inner) = inner.upgrade() else {
+            break;
+        };
+        if let Err(e) = inner.refresh().await {
+            tracing::warn!("Failed to refresh gRPC endpoints: {}", e);
+        }
+    }
+}
+
+impl Service<http::Request<BoxBody>> for PooledChannel {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut req: http::Request<BoxBody>) -> Self::Future {
+        // The trace context is task-local state, so read it before the call moves
+        if self.inner.options.trace_propagation {
+            rf_contrib_trace::inject_context_to_headers(&opentelemetry::Context::current(), req.headers_mut());
+        }
+        let inner = self.inner.clone();
+        Box::pin(async move { Ok(call(inner, req).await) })
+    }
+}
+
+async fn call(inner: Arc<PoolInner>, req: http::Request<BoxBody>) -> http::Response<BoxBody> {
+    let (parts, body) = req.into_parts();
+    let path = parts.uri.path().to_string();
+    let policy = inner.policy(&path).clone();
+    let timeout = [policy.timeout.or(inner.options.timeout), grpc_timeout(&parts.headers)]
+        .into_iter()
+        .flatten()
+        .min();
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+    let mut body = match RequestBody::buffer(body).await {
+        Ok(body) => body,
+        Err(status) => return status.into_http(),
+    };
+    let mut last: Option<String> = None;
+    let mut retry = 0;
+    loop {
+        let Some(endpoint) = inner.pick(last.as_deref()) else {
+            return Status::unavailable(format!("No endpoints available for {}", path)).into_http();
+        };
+        let Some(attempt_body) = body.attempt() else {
+            return Status::internal("Request body already consumed").into_http();
+        };
+        let mut request = http::Request::new(tonic::body::boxed(attempt_body));
+        *request.method_mut() = parts.method.clone();
+        *request.uri_mut() = parts.uri.clone();
+        *request.version_mut() = parts.version;
+        *request.headers_mut() = parts.headers.clone();
+        if let Some(deadline) = deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if let Ok(value) = http::HeaderValue::from_str(&format!("{}m", remaining.as_millis().min(99_999_999))) {
+                request.headers_mut().insert("grpc-timeout", value);
+            }
+        }
+
+        endpoint.pending.fetch_add(1, Ordering::Relaxed);
+        let attempt = endpoint.channel.clone().oneshot(request);
+        let result = match deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, attempt).await {
+                Ok(result) => result,
+                Err(_) => {
+                    endpoint.pending.fetch_sub(1, Ordering::Relaxed);
+                    return deadline_exceeded(&path);
+                }
+            },
+            None => attempt.await,
+        };
+        endpoint.pending.fetch_sub(1, Ordering::Relaxed);
+
+        let code = match &result {
+            Ok(response) => {
+                endpoint.restore();
+                response_code(response)
+            }
+            Err(_) => {
+                endpoint.eject();
+                Some(Code::Unavailable)
+            }
+        };
+        let retryable = code.is_some_and(|code| policy.retryable.contains(&code));
+        if !retryable || retry >= policy.max_retries || !body.is_replayable() {
+            return match result {
+                Ok(response) => response,
+                Err(e) => Status::unavailable(format!("Failed to call {}: {}", endpoint.uri, e)).into_http(),
+            };
+        }
+
+        let backoff = policy.backoff_for(retry);
+        retry += 1;
+        tracing::debug!("Retrying {} on another endpoint after {:?} (attempt {})", path, backoff, retry + 1);
+        if deadline.is_some_and(|deadline| Instant::now() + backoff >= deadline) {
+            return deadline_exceeded(&path);
+        }
+        tokio::time::sleep(backoff).await;
+        last = Some(endpoint.uri.clone());
+    }
+}
+
+fn deadline_exceeded(path: &str) -> http::Response<BoxBody> {
+    Status::deadline_exceeded(format!("Deadline exceeded calling {}", path)).into_http()
+}
+
+/// Status of a trailers-only response, which carries no messages
+fn response_code(response: &http::Response<BoxBody>) -> Option<Code> {
+    let status = response.headers().get("grpc-status")?.to_str().ok()?.parse::<i32>().ok()?;
+    Some(Code::from_i32(status))
+}
+
+/// Parse the `grpc-timeout` header set by the caller
+fn grpc_timeout(headers: &http::HeaderMap) -> Option<Duration> {
+    let value = headers.get("grpc-timeout")?.to_str().ok()?;
+    let (amount, unit) = value.split_at(value.len().checked_sub(1)?);
+    let amount: u64 = amount.parse().ok()?;
+    Some(match unit {
+        "H" => Duration::from_secs(amount * 3600),
+        "M" => Duration::from_secs(amount * 60),
+        "S" => Duration::from_secs(amount),
+        "m" => Duration::from_millis(amount),
+        "u" => Duration::from_micros(amount),
+        "n" => Duration::from_nanos(amount),
+        _ => return None,
+    })
+}
+
+#[derive(Clone)]
+enum BufferedFrame {
+    Data(Bytes),
+    Trailers(http::HeaderMap),
+}
+
+/// A request body read ahead as far as it is available
+struct RequestBody {
+    frames: Vec<BufferedFrame>,
+    /// Rest of a body that was still streaming; such calls are not retried
+    rest: Option<BoxBody>,
+    streaming: bool,
+}
+
+impl RequestBody {
+    async fn buffer(mut body: BoxBody) -> std::result::Result<Self, Status> {
+        use http_body::Body;
+
+        let mut frames = Vec::new();
+        let mut size = 0;
+        loop {
+            let polled = poll_fn(|cx| Poll::Ready(Pin::new(&mut body).poll_frame(cx))).await;
+            let frame = match polled {
+                Poll::Ready(Some(Ok(frame))) => frame,
+                Poll::Ready(Some(Err(status))) => return Err(status),
+                Poll::Ready(None) => {
+                    return Ok(Self {
+                        frames,
+                        rest: None,
+                        streaming: false,
+                    })
+                }
+                Poll::Pending => break,
+            };
+            match frame.into_data() {
+                Ok(data) => {
+                    size += data.len();
+                    frames.push(BufferedFrame::Data(data));
+                }
+                Err(frame) => {
+                    if let Ok(trailers) = frame.into_trailers() {
+                        frames.push(BufferedFrame::Trailers(trailers));
+                    }
+                }
+            }
+            if size > RETRY_BUFFER_LIMIT {
+                break;
+            }
+        }
+        Ok(Self {
+            frames,
+            rest: Some(body),
+            streaming: true,
+        })
+    }
+
+    fn is_replayable(&self) -> bool {
+        !self.streaming
+    }
+
+    /// Body for the next attempt, `None` once a streaming body was used
+    fn attempt(&mut self) -> Option<ReplayBody> {
+        if self.streaming && self.rest.is_none() {
+            return None;
+        }
+        Some(ReplayBody {
+            frames: self.frames.iter().cloned().collect(),
+            rest: self.rest.take(),
+        })
+    }
+}
+
+struct ReplayBody {
+    frames: VecDeque<BufferedFrame>,
+    rest: Option<BoxBody>,
+}
+
+impl http_body::Body for ReplayBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<std::result::Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        if let Some(frame) = self.frames.pop_front() {
+            return Poll::Ready(Some(Ok(match frame {
+                BufferedFrame::Data(data) => http_body::Frame::data(data),
+                BufferedFrame::Trailers(trailers) => http_body::Frame::trailers(trailers),
+            })));
+        }
+        match self.rest.as_mut() {
+            Some(rest) => Pin::new(rest).poll_frame(cx),
+            None => Poll::Ready(None),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.frames.is_empty() && self.rest.as_ref().is_none_or(http_body::Body::is_end_stream)
+    }
+}
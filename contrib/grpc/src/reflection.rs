@@ -0,0 +1,745 @@
+//! # reflection
+//!
+//! reflection 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! gRPC server reflection
+//!
+//! Implements `grpc.reflection.v1` and `grpc.reflection.v1alpha` so tools
+//! such as `grpcurl` and `grpcui` can list and call services without the
+//! `.proto` files. Services are described by the file descriptor sets
+//! `tonic-build` writes with `file_descriptor_set_path`:
+//!
+//! ```ignore
+//! // build.rs
+//! tonic_build::configure()
+//!     .file_descriptor_set_path(out_dir.join("helloworld_descriptor.bin"))
+//!     .compile_protos(&["proto/helloworld.proto"], &["proto"])?;
+//!
+//! // main.rs
+//! const DESCRIPTOR: &[u8] = tonic::include_file_descriptor_set!("helloworld_descriptor");
+//!
+//! GrpcServer::new(addr)
+//!     .add_service(GreeterServer::new(greeter))
+//!     .with_reflection(DESCRIPTOR)?
+//!     .serve()
+//!     .await?;
+//! // grpcurl -plaintext localhost:50051 list
+//! ```
+//!
+//! The health and reflection services are described too.
+
+use prost::Message;
+use rf_errors::{Result, RfError};
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::tokio_stream::wrappers::ReceiverStream;
+use tonic::codegen::{http, BoxFuture, Service};
+use tonic::server::{Grpc, NamedService};
+use tonic::{Request, Response, Status, Streaming};
+
+/// `ServerReflectionRequest`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ServerReflectionRequest {
+    #[prost(string, tag = "1")]
+    pub host: String,
+    #[prost(oneof = "MessageRequest", tags = "3, 4, 5, 6, 7")]
+    pub message_request: Option<MessageRequest>,
+}
+
+/// Request kinds of `ServerReflectionRequest`
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum MessageRequest {
+    #[prost(string, tag = "3")]
+    FileByFilename(String),
+    #[prost(string, tag = "4")]
+    FileContainingSymbol(String),
+    #[prost(message, tag = "5")]
+    FileContainingExtension(ExtensionRequest),
+    #[prost(string, tag = "6")]
+    AllExtensionNumbersOfType(String),
+    #[prost(string, tag = "7")]
+    ListServices(String),
+}
+
+/// `ExtensionRequest`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ExtensionRequest {
+    #[prost(string, tag = "1")]
+    pub containing_type: String,
+    #[prost(int32, tag = "2")]
+    pub extension_number: i32,
+}
+
+/// `ServerReflectionResponse`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ServerReflectionResponse {
+    #[prost(string, tag = "1")]
+    pub valid_host: String,
+    #[prost(message, optional, tag = "2")]
+    pub original_request: Option<ServerReflectionRequest>,
+    #[prost(oneof = "MessageResponse", tags = "4, 5, 6, 7")]
+    pub message_response: Option<MessageResponse>,
+}
+
+/// Response kinds of `ServerReflectionResponse`
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum MessageResponse {
+    #[prost(message, tag = "4")]
+    FileDescriptorResponse(FileDescriptorResponse),
+    #[prost(message, tag = "5")]
+    AllExtensionNumbersResponse(ExtensionNumberResponse),
+    #[prost(message, tag = "6")]
+    ListServicesResponse(ListServiceResponse),
+    #[prost(message, tag = "7")]
+    ErrorResponse(ErrorResponse),
+}
+
+/// Serialized `FileDescriptorProto`s, the requested file first
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FileDescriptorResponse {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub file_descriptor_proto: Vec<Vec<u8>>,
+}
+
+/// `ExtensionNumberResponse`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ExtensionNumberResponse {
+    #[prost(string, tag = "1")]
+    pub base_type_name: String,
+    #[prost(int32, repeated, tag = "2")]
+    pub extension_number: Vec<i32>,
+}
+
+/// `ListServiceResponse`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListServiceResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub service: Vec<ServiceResponse>,
+}
+
+/// `ServiceResponse`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ServiceResponse {
+    #[prost(string, tag = "1")]
+    pub name: String,
+}
+
+/// `ErrorResponse`, `error_code` is a gRPC status code
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ErrorResponse {
+    #[prost(int32, tag = "1")]
+    pub error_code: i32,
+    #[prost(string, tag = "2")]
+    pub error_message: String,
+}
+
+// The parts of descriptor.proto needed to index files, describe the
+// built-in services and transcode JSON in the gateway. Files are served as
+// registered, so fields left out here are not lost.
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct FileDescriptorSet {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub(crate) file: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct FileDescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub(crate) name: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub(crate) package: Option<String>,
+    #[prost(string, repeated, tag = "3")]
+    pub(crate) dependency: Vec<String>,
+    #[prost(message, repeated, tag = "4")]
+    pub(crate) message_type: Vec<DescriptorProto>,
+    #[prost(message, repeated, tag = "5")]
+    pub(crate) enum_type: Vec<EnumDescriptorProto>,
+    #[prost(message, repeated, tag = "6")]
+    pub(crate) service: Vec<ServiceDescriptorProto>,
+    #[prost(message, repeated, tag = "7")]
+    pub(crate) extension: Vec<FieldDescriptorProto>,
+    #[prost(string, optional, tag = "12")]
+    pub(crate) syntax: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct DescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub(crate) name: Option<String>,
+    #[prost(message, repeated, tag = "2")]
+    pub(crate) field: Vec<FieldDescriptorProto>,
+    #[prost(message, repeated, tag = "3")]
+    pub(crate) nested_type: Vec<DescriptorProto>,
+    #[prost(message, repeated, tag = "4")]
+    pub(crate) enum_type: Vec<EnumDescriptorProto>,
+    #[prost(message, repeated, tag = "6")]
+    pub(crate) extension: Vec<FieldDescriptorProto>,
+    #[prost(message, optional, tag = "7")]
+    pub(crate) options: Option<MessageOptions>,
+    #[prost(message, repeated, tag = "8")]
+    pub(crate) oneof_decl: Vec<OneofDescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct MessageOptions {
+    #[prost(bool, optional, tag = "7")]
+    pub(crate) map_entry: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct FieldDescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub(crate) name: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub(crate) extendee: Option<String>,
+    #[prost(int32, optional, tag = "3")]
+    pub(crate) number: Option<i32>,
+    #[prost(int32, optional, tag = "4")]
+    pub(crate) label: Option<i32>,
+    #[prost(int32, optional, tag = "5")]
+    pub(crate) r#type: Option<i32>,
+    #[prost(string, optional, tag = "6")]
+    pub(crate) type_name: Option<String>,
+    #[prost(int32, optional, tag = "9")]
+    pub(crate) oneof_index: Option<i32>,
+    #[prost(string, optional, tag = "10")]
+    pub(crate) json_name: Option<String>,
+    #[prost(bool, optional, tag = "17")]
+    pub(crate) proto3_optional: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct OneofDescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub(crate) name: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct EnumDescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub(crate) name: Option<String>,
+    #[prost(message, repeated, tag = "2")]
+    pub(crate) value: Vec<EnumValueDescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct EnumValueDescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub(crate) name: Option<String>,
+    #[prost(int32, optional, tag = "2")]
+    pub(crate) number: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ServiceDescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub(crate) name: Option<String>,
+    #[prost(message, repeated, tag = "2")]
+    pub(crate) method: Vec<MethodDescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct MethodDescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    pub(crate) name: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub(crate) input_type: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub(crate) output_type: Option<String>,
+    #[prost(message, optional, tag = "4")]
+    pub(crate) options: Option<MethodOptions>,
+    #[prost(bool, optional, tag = "5")]
+    pub(crate) client_streaming: Option<bool>,
+    #[prost(bool, optional, tag = "6")]
+    pub(crate) server_streaming: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct MethodOptions {
+    /// `google.api.http` annotation
+    #[prost(message, optional, tag = "72295728")]
+    pub(crate) http: Option<HttpRuleProto>,
+}
+
+/// `google.api.HttpRule`
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct HttpRuleProto {
+    #[prost(string, tag = "2")]
+    pub(crate) get: String,
+    #[prost(string, tag = "3")]
+    pub(crate) put: String,
+    #[prost(string, tag = "4")]
+    pub(crate) post: String,
+    #[prost(string, tag = "5")]
+    pub(crate) delete: String,
+    #[prost(string, tag = "6")]
+    pub(crate) patch: String,
+    #[prost(string, tag = "7")]
+    pub(crate) body: String,
+    #[prost(message, optional, tag = "8")]
+    pub(crate) custom: Option<CustomHttpPattern>,
+    #[prost(message, repeated, tag = "11")]
+    pub(crate) additional_bindings: Vec<HttpRuleProto>,
+    #[prost(string, tag = "12")]
+    pub(crate) response_body: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct CustomHttpPattern {
+    #[prost(string, tag = "1")]
+    pub(crate) kind: String,
+    #[prost(string, tag = "2")]
+    pub(crate) path: String,
+}
+
+/// The files of an encoded `FileDescriptorSet`, raw and decoded
+pub(crate) fn decode_file_descriptor_set(bytes: &[u8]) -> Result<Vec<(Vec<u8>, FileDescriptorProto)>> {
+    let set = FileDescriptorSet::decode(bytes)
+        .map_err(|e| RfError::InvalidParameter(format!("Invalid file descriptor set: {}", e)))?;
+    set.file
+        .into_iter()
+        .map(|raw| {
+            let file = FileDescriptorProto::decode(raw.as_slice())
+                .map_err(|e| RfError::InvalidParameter(format!("Invalid file descriptor: {}", e)))?;
+            Ok((raw, file))
+        })
+        .collect()
+}
+
+/// Registered file descriptors indexed by file name and symbol
+#[derive(Clone, Default)]
+pub struct DescriptorIndex {
+    /// Serialized descriptor and dependencies by file name
+    files: HashMap<String, (Vec<u8>, Vec<String>)>,
+    /// File defining each fully qualified symbol
+    symbols: HashMap<String, String>,
+    /// Extension numbers by extended message
+    extensions: HashMap<String, Vec<(i32, String)>>,
+    services: Vec<String>,
+}
+
+impl DescriptorIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Describe the `grpc.health.v1.Health` service
+    pub fn add_health(&mut self) {
+        let file = health_descriptor();
+        self.add_file(file.encode_to_vec(), file);
+    }
+
+    /// Describe the reflection services themselves
+    pub fn add_reflection(&mut self) {
+        for file in [reflection_descriptor("v1"), reflection_descriptor("v1alpha")] {
+            self.add_file(file.encode_to_vec(), file);
+        }
+    }
+
+    /// Add the files of an encoded `FileDescriptorSet`
+    pub fn add_file_descriptor_set(&mut self, bytes: &[u8]) -> Result<()> {
+        for (raw, file) in decode_file_descriptor_set(bytes)? {
+            self.add_file(raw, file);
+        }
+        Ok(())
+    }
+
+    /// Fully qualified names of the described services
+    pub fn services(&self) -> &[String] {
+        &self.services
+    }
+
+    fn add_file(&mut self, raw: Vec<u8>, file: FileDescriptorProto) {
+        let name = file.name.clone().unwrap_or_default();
+        if self.files.contains_key(&name) {
+            return;
+        }
+        let package = file.package.clone().unwrap_or_default();
+        let qualify = |name: &Option<String>| match package.as_str() {
+            "" => name.clone().unwrap_or_default(),
+            package => format!("{}.{}", package, name.as_deref().unwrap_or_default()),
+        };
+        for service in &file.service {
+            let service_name = qualify(&service.name);
+            for method in &service.method {
+                self.symbols
+                    .insert(format!("{}.{}", service_name, method.name.as_deref().unwrap_or_default()), name.clone());
+            }
+            self.symbols.insert(service_name.clone(), name.clone());
+            self.services.push(service_name);
+        }
+        for message in &file.message_type {
+            self.add_message(&qualify(&message.name), message, &name);
+        }
+        for enumeration in &file.enum_type {
+            self.symbols.insert(qualify(&enumeration.name), name.clone());
+        }
+        for extension in &file.extension {
+            self.add_extension(extension, &qualify(&extension.name), &name);
+        }
+        self.files.insert(name, (raw, file.dependency));
+    }
+
+    fn add_message(&mut self, full_name: &str, message: &DescriptorProto, file: &str) {
+        self.symbols.insert(full_name.to_string(), file.to_string());
+        for nested in &message.nested_type {
+            self.add_message(&format!("{}.{}", full_name, nested.name.as_deref().unwrap_or_default()), nested, file);
+        }
+        for enumeration in &message.enum_type {
+            let enum_name = format!("{}.{}", full_name, enumeration.name.as_deref().unwrap_or_default());
+            self.symbols.insert(enum_name, file.to_string());
+        }
+        for extension in &message.extension {
+            let extension_name = format!("{}.{}", full_name, extension.name.as_deref().unwrap_or_default());
+            self.add_extension(extension, &extension_name, file);
+        }
+    }
+
+    fn add_extension(&mut self, extension: &FieldDescriptorProto, full_name: &str, file: &str) {
+        self.symbols.insert(full_name.to_string(), file.to_string());
+        let extendee = extension.extendee.as_deref().unwrap_or_default().trim_start_matches('.').to_string();
+        self.extensions
+            .entry(extendee)
+            .or_default()
+            .push((extension.number.unwrap_or_default(), file.to_string()));
+    }
+
+    /// A file followed by its transitive dependencies
+    fn file_with_dependencies(&self, name: &str) -> Option<Vec<Vec<u8>>> {
+        self.files.get(name)?;
+        let mut seen = HashSet::new();
+        let mut pending = vec![name.to_string()];
+        let mut files = Vec::new();
+        while let Some(name) = pending.pop() {
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            // Well-known imports the index does not hold are left to the client
+            if let Some((raw, dependencies)) = self.files.get(&name) {
+                files.push(raw.clone());
+                pending.extend(dependencies.iter().rev().cloned());
+            }
+        }
+        Some(files)
+    }
+
+    fn respond(&self, request: ServerReflectionRequest) -> ServerReflectionResponse {
+        let not_found = |message: String| {
+            MessageResponse::ErrorResponse(ErrorResponse {
+                error_code: tonic::Code::NotFound as i32,
+                error_message: message,
+            })
+        };
+        let files = |name: &str| match self.file_with_dependencies(name) {
+            Some(files) => MessageResponse::FileDescriptorResponse(FileDescriptorResponse {
+                file_descriptor_proto: files,
+            }),
+            None => not_found(format!("File {} not found", name)),
+        };
+        let response = match &request.message_request {
+            Some(MessageRequest::ListServices(_)) => MessageResponse::ListServicesResponse(ListServiceResponse {
+                service: self.services.iter().map(|name| ServiceResponse { name: name.clone() }).collect(),
+            }),
+            Some(MessageRequest::FileByFilename(name)) => files(name),
+            Some(MessageRequest::FileContainingSymbol(symbol)) => {
+                match self.symbols.get(symbol.trim_start_matches('.')) {
+                    Some(file) => files(file),
+                    None => not_found(format!("Symbol {} not found", symbol)),
+                }
+            }
+            Some(MessageRequest::FileContainingExtension(extension)) => {
+                let file = self
+                    .extensions
+                    .get(extension.containing_type.trim_start_matches('.'))
+                    .and_then(|numbers| numbers.iter().find(|(number, _)| *number == extension.extension_number));
+                match file {
+                    Some((_, file)) => files(file),
+                    None => not_found(format!(
+                        "Extension {} of {} not found",
+                        extension.extension_number, extension.containing_type
+                    )),
+                }
+            }
+            Some(MessageRequest::AllExtensionNumbersOfType(name)) => {
+                let name = name.trim_start_matches('.');
+                if self.symbols.contains_key(name) {
+                    MessageResponse::AllExtensionNumbersResponse(ExtensionNumberResponse {
+                        base_type_name: name.to_string(),
+                        extension_number: self.extensions.get(name).into_iter().flatten().map(|(n, _)| *n).collect(),
+                    })
+                } else {
+                    not_found(format!("Type {} not found", name))
+                }
+            }
+            None => MessageResponse::ErrorResponse(ErrorResponse {
+                error_code: tonic::Code::InvalidArgument as i32,
+                error_message: "Empty reflection request".to_string(),
+            }),
+        };
+        ServerReflectionResponse {
+            valid_host: request.host.clone(),
+            original_request: Some(request),
+            message_response: Some(response),
+        }
+    }
+}
+
+/// `grpc.reflection.v1alpha.ServerReflection` service
+///
+/// Served together with `ReflectionServiceV1`; clients pick the version
+/// they support.
+#[derive(Clone)]
+pub struct ReflectionService {
+    index: Arc<DescriptorIndex>,
+}
+
+/// `grpc.reflection.v1.ServerReflection` service
+#[derive(Clone)]
+pub struct ReflectionServiceV1 {
+    index: Arc<DescriptorIndex>,
+}
+
+impl ReflectionService {
+    pub fn new(index: DescriptorIndex) -> Self {
+        Self { index: Arc::new(index) }
+    }
+
+    /// The same descriptors served under `grpc.reflection.v1`
+    pub fn v1(&self) -> ReflectionServiceV1 {
+        ReflectionServiceV1 {
+            index: self.index.clone(),
+        }
+    }
+}
+
+impl NamedService for ReflectionService {
+    const NAME: &'static str = "grpc.reflection.v1alpha.ServerReflection";
+}
+
+impl NamedService for ReflectionServiceV1 {
+    const NAME: &'static str = "grpc.reflection.v1.ServerReflection";
+}
+
+impl Service<http::Request<BoxBody>> for ReflectionService {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        serve_reflection::<Self>(self.index.clone(), req)
+    }
+}
+
+impl Service<http::Request<BoxBody>> for ReflectionServiceV1 {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        serve_reflection::<Self>(self.index.clone(), req)
+    }
+}
+
+fn serve_reflection<S: NamedService>(
+    index: Arc<DescriptorIndex>,
+    req: http::Request<BoxBody>,
+) -> BoxFuture<http::Response<BoxBody>, Infallible> {
+    if req.uri().path() != format!("/{}/ServerReflectionInfo", S::NAME) {
+        return Box::pin(async { Ok(Status::unimplemented("").into_http()) });
+    }
+    Box::pin(async move {
+        let method = tower::service_fn(move |request: Request<Streaming<ServerReflectionRequest>>| {
+            let index = index.clone();
+            let mut inbound = request.into_inner();
+            let (tx, rx) = mpsc::channel(4);
+            tokio::spawn(async move {
+                loop {
+                    let response = match inbound.message().await {
+                        Ok(Some(request)) => Ok(index.respond(request)),
+                        Ok(None) => break,
+                        Err(status) => Err(status),
+                    };
+                    let failed = response.is_err();
+                    if tx.send(response).await.is_err() || failed {
+                        break;
+                    }
+                }
+            });
+            async move { Ok::<_, Status>(Response::new(ReceiverStream::new(rx))) }
+        });
+        Ok(Grpc::new(ProstCodec::default()).streaming(method, req).await)
+    })
+}
+
+const TYPE_INT32: i32 = 5;
+const TYPE_STRING: i32 = 9;
+const TYPE_MESSAGE: i32 = 11;
+const TYPE_BYTES: i32 = 12;
+const TYPE_ENUM: i32 = 14;
+const LABEL_OPTIONAL: i32 = 1;
+const LABEL_REPEATED: i32 = 3;
+
+fn message(name: &str, fields: Vec<FieldDescriptorProto>) -> DescriptorProto {
+    DescriptorProto {
+        name: Some(name.to_string()),
+        field: fields,
+        ..Default::default()
+    }
+}
+
+fn field(name: &str, number: i32, ty: i32, type_name: Option<&str>) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: Some(name.to_string()),
+        number: Some(number),
+        label: Some(LABEL_OPTIONAL),
+        r#type: Some(ty),
+        type_name: type_name.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+fn repeated(mut field: FieldDescriptorProto) -> FieldDescriptorProto {
+    field.label = Some(LABEL_REPEATED);
+    field
+}
+
+fn in_oneof(mut field: FieldDescriptorProto, index: i32) -> FieldDescriptorProto {
+    field.oneof_index = Some(index);
+    field
+}
+
+fn method(name: &str, input: &str, output: &str, client_streaming: bool, server_streaming: bool) -> MethodDescriptorProto {
+    MethodDescriptorProto {
+        name: Some(name.to_string()),
+        input_type: Some(input.to_string()),
+        output_type: Some(output.to_string()),
+        options: None,
+        client_streaming: client_streaming.then_some(true),
+        server_streaming: server_streaming.then_some(true),
+    }
+}
+
+/// `grpc/health/v1/health.proto`
+fn health_descriptor() -> FileDescriptorProto {
+    let mut response = message(
+        "HealthCheckResponse",
+        vec![field("status", 1, TYPE_ENUM, Some(".grpc.health.v1.HealthCheckResponse.ServingStatus"))],
+    );
+    response.enum_type.push(EnumDescriptorProto {
+        name: Some("ServingStatus".to_string()),
+        value: ["UNKNOWN", "SERVING", "NOT_SERVING", "SERVICE_UNKNOWN"]
+            .iter()
+            .zip(0..)
+            .map(|(name, number)| EnumValueDescriptorProto {
+                name: Some(name.to_string()),
+                number: Some(number),
+            })
+            .collect(),
+    });
+    FileDescriptorProto {
+        name: Some("grpc/health/v1/health.proto".to_string()),
+        package: Some("grpc.health.v1".to_string()),
+        message_type: vec![message("HealthCheckRequest", vec![field("service", 1, TYPE_STRING, None)]), response],
+        service: vec![ServiceDescriptorProto {
+            name: Some("Health".to_string()),
+            method: vec![
+                method("Check", ".grpc.health.v1.HealthCheckRequest", ".grpc.health.v1.HealthCheckResponse", false, false),
+                method("Watch", ".grpc.health.v1.HealthCheckRequest", ".grpc.health.v1.HealthCheckResponse", false, true),
+            ],
+        }],
+        syntax: Some("proto3".to_string()),
+        ..Default::default()
+    }
+}
+
+/// `grpc/reflection/<version>/reflection.proto`
+fn reflection_descriptor(version: &str) -> FileDescriptorProto {
+    let package = format!("grpc.reflection.{}", version);
+    let ty = |name: &str| format!(".{}.{}", package, name);
+    let mut request = message(
+        "ServerReflectionRequest",
+        vec![
+            field("host", 1, TYPE_STRING, None),
+            in_oneof(field("file_by_filename", 3, TYPE_STRING, None), 0),
+            in_oneof(field("file_containing_symbol", 4, TYPE_STRING, None), 0),
+            in_oneof(field("file_containing_extension", 5, TYPE_MESSAGE, Some(&ty("ExtensionRequest"))), 0),
+            in_oneof(field("all_extension_numbers_of_type", 6, TYPE_STRING, None), 0),
+            in_oneof(field("list_services", 7, TYPE_STRING, None), 0),
+        ],
+    );
+    request.oneof_decl.push(OneofDescriptorProto {
+        name: Some("message_request".to_string()),
+    });
+    let mut response = message(
+        "ServerReflectionResponse",
+        vec![
+            field("valid_host", 1, TYPE_STRING, None),
+            field("original_request", 2, TYPE_MESSAGE, Some(&ty("ServerReflectionRequest"))),
+            in_oneof(field("file_descriptor_response", 4, TYPE_MESSAGE, Some(&ty("FileDescriptorResponse"))), 0),
+            in_oneof(field("all_extension_numbers_response", 5, TYPE_MESSAGE, Some(&ty("ExtensionNumberResponse"))), 0),
+            in_oneof(field("list_services_response", 6, TYPE_MESSAGE, Some(&ty("ListServiceResponse"))), 0),
+            in_oneof(field("error_response", 7, TYPE_MESSAGE, Some(&ty("ErrorResponse"))), 0),
+        ],
+    );
+    response.oneof_decl.push(OneofDescriptorProto {
+        name: Some("message_response".to_string()),
+    });
+    FileDescriptorProto {
+        name: Some(format!("grpc/reflection/{}/reflection.proto", version)),
+        package: Some(package.clone()),
+        message_type: vec![
+            request,
+            message(
+                "ExtensionRequest",
+                vec![field("containing_type", 1, TYPE_STRING, None), field("extension_number", 2, TYPE_INT32, None)],
+            ),
+            response,
+            message(
+                "FileDescriptorResponse",
+                vec![repeated(field("file_descriptor_proto", 1, TYPE_BYTES, None))],
+            ),
+            message(
+                "ExtensionNumberResponse",
+                vec![field("base_type_name", 1, TYPE_STRING, None), repeated(field("extension_number", 2, TYPE_INT32, None))],
+            ),
+            message(
+                "ListServiceResponse",
+                vec![repeated(field("service", 1, TYPE_MESSAGE, Some(&ty("ServiceResponse"))))],
+            ),
+            message("ServiceResponse", vec![field("name", 1, TYPE_STRING, None)]),
+            message(
+                "ErrorResponse",
+                vec![field("error_code", 1, TYPE_INT32, None), field("error_message", 2, TYPE_STRING, None)],
+            ),
+        ],
+        service: vec![ServiceDescriptorProto {
+            name: Some("ServerReflection".to_string()),
+            method: vec![method(
+                "ServerReflectionInfo",
+                &ty("ServerReflectionRequest"),
+                &ty("ServerReflectionResponse"),
+                true,
+                true,
+            )],
+        }],
+        syntax: Some("proto3".to_string()),
+        ..Default::default()
+    }
+}
@@ -7,70 +7,273 @@
 
 //! gRPC server encapsulation
 
-use rf_errors::Result;
+use super::health::HealthReporter;
+use super::limits::{GrpcLimits, LimitsLayer};
+use super::reflection::{DescriptorIndex, ReflectionService};
+use super::trace::TraceLayer;
+use rf_contrib_trace::TraceMiddleware;
+use rf_errors::{Result, RfError};
+use std::convert::Infallible;
+use std::future::Future;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
 use tokio::signal;
+use tonic::body::BoxBody;
+use tonic::codegen::{http, Service};
+use tonic::server::NamedService;
+use tonic::service::RoutesBuilder;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
 
 /// gRPC server builder
+///
+/// ```ignore
+/// let server = GrpcServer::new("0.0.0.0:50051".parse()?)
+///     .add_service(GreeterServer::new(greeter))
+///     .with_reflection(DESCRIPTOR)?
+///     .max_concurrent_requests(1000)
+///     .max_decoding_message_size(16 * 1024 * 1024);
+/// server.serve().await?;
+/// ```
 pub struct GrpcServer {
     addr: SocketAddr,
     shutdown_timeout: Option<std::time::Duration>,
+    routes: RoutesBuilder,
+    services: Vec<&'static str>,
+    health: Option<HealthReporter>,
+    reflection: Option<DescriptorIndex>,
+    limits: GrpcLimits,
+    request_timeout: Option<Duration>,
+    concurrency_limit_per_connection: Option<usize>,
+    max_concurrent_streams: Option<u32>,
+    tracing: Option<Arc<TraceMiddleware>>,
 }
 
 impl GrpcServer {
     /// Create a new gRPC server
+    ///
+    /// The `grpc.health.v1.Health` service is registered by default.
     pub fn new(addr: SocketAddr) -> Self {
         Self {
             addr,
             shutdown_timeout: Some(std::time::Duration::from_secs(30)),
+            routes: RoutesBuilder::default(),
+            services: Vec::new(),
+            health: Some(HealthReporter::new()),
+            reflection: None,
+            limits: GrpcLimits::default(),
+            request_timeout: None,
+            concurrency_limit_per_connection: None,
+            max_concurrent_streams: None,
+            tracing: None,
         }
     }
 
-    /// Add a service to the server
-    /// Note: This is a simplified implementation. For full gRPC support,
-    /// use tonic's service registration directly with Server::builder().
-    pub fn add_service<S>(self, _service: S) -> Self
+    /// Add a service to the server, e.g. a `tonic-build` generated `XxxServer`
+    ///
+    /// The service is reported as serving by the health service.
+    pub fn add_service<S>(mut self, service: S) -> Self
     where
-        S: Send + Sync + 'static,
+        S: Service<http::Request<BoxBody>, Response = http::Response<BoxBody>, Error = Infallible>
+            + NamedService
+            + Clone
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
     {
-        // Simplified: store service registration logic
-        // In a full implementation, this would properly register the service
+        self.routes.add_service(service);
+        self.services.push(S::NAME);
+        if let Some(health) = &self.health {
+            health.set_serving(S::NAME);
+        }
+        self
+    }
+
+    /// Enable or disable the built-in health service (default enabled)
+    pub fn with_health(mut self, enabled: bool) -> Self {
+        if !enabled {
+            self.health = None;
+        } else if self.health.is_none() {
+            let health = HealthReporter::new();
+            for service in &self.services {
+                health.set_serving(service);
+            }
+            self.health = Some(health);
+        }
+        self
+    }
+
+    /// Handle for changing the statuses reported by the health service
+    ///
+    /// Reporting is a no-op when health checking is disabled.
+    pub fn health_reporter(&self) -> HealthReporter {
+        self.health.clone().unwrap_or_default()
+    }
+
+    /// Serve reflection for the services in an encoded `FileDescriptorSet`
+    ///
+    /// Call once per descriptor set; see the `reflection` module.
+    pub fn with_reflection(mut self, file_descriptor_set: &[u8]) -> Result<Self> {
+        self.reflection
+            .get_or_insert_with(DescriptorIndex::new)
+            .add_file_descriptor_set(file_descriptor_set)?;
+        Ok(self)
+    }
+
+    /// Start a server span for every call under the caller's trace context
+    ///
+    /// On by default while an `init_tracing_otlp` pipeline runs, using its
+    /// service name as tracer name.
+    pub fn with_tracing(mut self, tracing: TraceMiddleware) -> Self {
+        self.tracing = Some(Arc::new(tracing));
         self
     }
 
     /// Set shutdown timeout
+    ///
+    /// In-flight calls get this long to finish after the shutdown signal
+    /// before the server stops anyway.
     pub fn with_shutdown_timeout(mut self, timeout: std::time::Duration) -> Self {
         self.shutdown_timeout = Some(timeout);
         self
     }
 
-    /// Start the server
-    /// Note: This is a placeholder implementation. In a full implementation,
-    /// you would register services using Server::builder().add_service().
+    /// Fail calls still running after `timeout` with `CANCELLED`
+    ///
+    /// Clients can ask for less with the `grpc-timeout` header.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Calls handled at once across all connections; further calls wait
+    pub fn max_concurrent_requests(mut self, limit: usize) -> Self {
+        self.limits.max_concurrent_requests = Some(limit);
+        self
+    }
+
+    /// Calls handled at once on each connection; further calls wait
+    pub fn concurrency_limit_per_connection(mut self, limit: usize) -> Self {
+        self.concurrency_limit_per_connection = Some(limit);
+        self
+    }
+
+    /// HTTP/2 streams a client may open on one connection
+    pub fn max_concurrent_streams(mut self, limit: u32) -> Self {
+        self.max_concurrent_streams = Some(limit);
+        self
+    }
+
+    /// Largest request message accepted, answered with `RESOURCE_EXHAUSTED`
+    ///
+    /// Generated services also apply their own limit (4 MiB unless set with
+    /// their `max_decoding_message_size`), so raising it beyond that needs
+    /// both.
+    pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+        self.limits.max_decoding_message_size = Some(limit);
+        self
+    }
+
+    /// Largest response message sent; larger ones end the call with `RESOURCE_EXHAUSTED`
+    pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+        self.limits.max_encoding_message_size = Some(limit);
+        self
+    }
+
+    /// Start the server, stopping on Ctrl+C
     pub async fn serve(self) -> Result<()> {
-        // Create a TcpListener for the address
-        let _listener = tokio::net::TcpListener::bind(self.addr).await
-            .map_err(|e| rf_errors::RfError::Network(format!("Failed to bind to {}: {}", self.addr, e)))?;
-        
-        // Build server (simplified - no services registered)
-        // Note: In a full implementation, you would use Server::builder().add_service() to register services
-        // and then call server.serve() or server.serve_with_incoming()
-        
-        // Start server with graceful shutdown
-        // This is a placeholder - in full implementation, use server.serve() or server.serve_with_incoming()
+        self.serve_with_shutdown(async {
+            let _ = signal::ctrl_c().await;
+            tracing::info!("Received shutdown signal, shutting down gracefully");
+        })
+        .await
+    }
+
+    /// Start the server, stopping when `signal` completes
+    pub async fn serve_with_shutdown<F>(self, signal: F) -> Result<()>
+    where
+        F: Future<Output = ()> + Send,
+    {
+        let listener = TcpListener::bind(self.addr)
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to bind to {}: {}", self.addr, e)))?;
+        self.serve_with_listener(listener, signal).await
+    }
+
+    /// Serve on an already bound listener, stopping when `signal` completes
+    ///
+    /// On shutdown every service is reported as not serving, new calls are
+    /// refused and in-flight calls drain for up to the shutdown timeout.
+    pub async fn serve_with_listener<F>(mut self, listener: TcpListener, signal: F) -> Result<()>
+    where
+        F: Future<Output = ()> + Send,
+    {
+        let addr = listener.local_addr().unwrap_or(self.addr);
+        let incoming = TcpIncoming::from_listener(listener, true, None)
+            .map_err(|e| RfError::Network(format!("Failed to listen on {}: {}", addr, e)))?;
+
+        if let Some(health) = &self.health {
+            self.routes.add_service(health.service());
+        }
+        if let Some(mut index) = self.reflection.take() {
+            if self.health.is_some() {
+                index.add_health();
+            }
+            index.add_reflection();
+            let reflection = ReflectionService::new(index);
+            self.routes.add_service(reflection.v1());
+            self.routes.add_service(reflection);
+        }
+
+        let mut builder = Server::builder().max_concurrent_streams(self.max_concurrent_streams);
+        if let Some(limit) = self.concurrency_limit_per_connection {
+            builder = builder.concurrency_limit_per_connection(limit);
+        }
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        // Outermost, so the span covers waiting for a concurrency permit
+        let tracing = self.tracing.take().or_else(TraceMiddleware::global);
+        let mut builder = builder
+            .layer(tower::util::option_layer(tracing.map(TraceLayer::new)))
+            .layer(tower::util::option_layer(
+                (!self.limits.is_empty()).then(|| LimitsLayer::new(&self.limits)),
+            ));
+        let router = builder.add_routes(self.routes.routes());
+
+        let health = self.health.clone();
+        let (stopping_tx, mut stopping_rx) = tokio::sync::watch::channel(false);
+        let shutdown = async move {
+            signal.await;
+            // Let load balancers and watchers see the server leave first
+            if let Some(health) = health {
+                health.shutdown();
+            }
+            let _ = stopping_tx.send(true);
+        };
+        tracing::info!("gRPC server listening on {}", addr);
+        let server = router.serve_with_incoming_shutdown(incoming, shutdown);
+        tokio::pin!(server);
+
+        let shutdown_timeout = self.shutdown_timeout;
+        let drain_deadline = async move {
+            let _ = stopping_rx.wait_for(|stopping| *stopping).await;
+            match shutdown_timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
         tokio::select! {
-            _ = async {
-                // Placeholder: wait for shutdown signal
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-            } => {
-                tracing::info!("gRPC server placeholder - use Server::builder() directly for full functionality");
+            result = &mut server => {
+                result.map_err(|e| RfError::Network(format!("gRPC server error: {}", e)))?;
             }
-            _ = signal::ctrl_c() => {
-                tracing::info!("Received shutdown signal, shutting down gracefully");
+            _ = drain_deadline => {
+                tracing::warn!("gRPC calls still running after the shutdown timeout, stopping anyway");
             }
         }
-
+        tracing::info!("gRPC server stopped");
         Ok(())
     }
 }
-
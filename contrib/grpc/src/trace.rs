@@ -0,0 +1,209 @@
+//! # trace
+//!
+//! trace 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Trace context propagation over gRPC metadata
+//!
+//! `inject_context` and `extract_context` carry the trace context in call
+//! metadata through the global text map propagator (`traceparent` with the
+//! W3C propagator). Extraction falls back to the binary `grpc-trace-bin`
+//! entry sent by OpenCensus-based clients.
+//!
+//! `PooledChannel` injects the context on its own; for clients generated by
+//! `tonic-build` on a plain channel, add `TraceInterceptor`. `GrpcServer`
+//! starts a server span per call under the extracted parent when tracing
+//! is on (see `GrpcServer::with_tracing`).
+//!
+//! ```rust,ignore
+//! use rf_contrib_grpc::TraceInterceptor;
+//!
+//! let channel = GrpcClient::new("http://orders:50051").connect().await?;
+//! let mut client = OrdersClient::with_interceptor(channel, TraceInterceptor);
+//! ```
+
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::{FutureExt, SpanKind, Status as SpanStatus, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use rf_contrib_trace::TraceMiddleware;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use tonic::body::BoxBody;
+use tonic::codegen::{http, BoxFuture, Service};
+use tonic::metadata::{KeyRef, MetadataKey, MetadataMap, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::transport::server::TcpConnectInfo;
+use tonic::{Code, Request, Status};
+use tower::Layer;
+
+/// RPC system attribute, always `grpc`
+pub const RPC_SYSTEM: &str = "rpc.system";
+
+/// Full service name attribute, such as `shop.Orders`
+pub const RPC_SERVICE: &str = "rpc.service";
+
+/// Method name attribute, such as `Get`
+pub const RPC_METHOD: &str = "rpc.method";
+
+/// gRPC status code attribute
+pub const RPC_GRPC_STATUS_CODE: &str = "rpc.grpc.status_code";
+
+/// Binary trace context entry of OpenCensus-based clients
+const GRPC_TRACE_BIN: &str = "grpc-trace-bin";
+
+/// Reads propagation fields from the ASCII entries of call metadata
+pub struct MetadataExtractor<'a>(pub &'a MetadataMap);
+
+impl Extractor for MetadataExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key)?.to_str().ok()
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0
+            .keys()
+            .filter_map(|key| match key {
+                KeyRef::Ascii(key) => Some(key.as_str()),
+                KeyRef::Binary(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Writes propagation fields into call metadata, skipping invalid ones
+pub struct MetadataInjector<'a>(pub &'a mut MetadataMap);
+
+impl Injector for MetadataInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(key), Ok(value)) = (MetadataKey::from_bytes(key.as_bytes()), MetadataValue::try_from(value)) {
+            self.0.insert(key, value);
+        }
+    }
+}
+
+/// Inject `context` into call metadata
+pub fn inject_context(context: &Context, metadata: &mut MetadataMap) {
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(context, &mut MetadataInjector(metadata));
+    });
+}
+
+/// Extract the caller's trace context from call metadata
+pub fn extract_context(metadata: &MetadataMap) -> Context {
+    let context = opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(&MetadataExtractor(metadata)));
+    if context.span().span_context().is_valid() {
+        return context;
+    }
+    match metadata.get_bin(GRPC_TRACE_BIN).and_then(|value| value.to_bytes().ok()) {
+        Some(bytes) => rf_contrib_trace::extract_context_from_binary(&bytes),
+        None => context,
+    }
+}
+
+/// Client interceptor injecting the current trace context into every call
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraceInterceptor;
+
+impl Interceptor for TraceInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        inject_context(&Context::current(), request.metadata_mut());
+        Ok(request)
+    }
+}
+
+/// Status codes marking a server span as failed; the rest are caller errors
+fn is_server_error(code: Code) -> bool {
+    matches!(
+        code,
+        Code::Unknown | Code::DeadlineExceeded | Code::Unimplemented | Code::Internal | Code::Unavailable | Code::DataLoss
+    )
+}
+
+/// Server span per call, applied by `GrpcServer`
+#[derive(Clone)]
+pub(crate) struct TraceLayer {
+    config: Arc<TraceMiddleware>,
+}
+
+impl TraceLayer {
+    pub fn new(config: Arc<TraceMiddleware>) -> Self {
+        Self { config }
+    }
+}
+
+impl<S> Layer<S> for TraceLayer {
+    type Service = Traced<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Traced {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Traced<S> {
+    inner: S,
+    config: Arc<TraceMiddleware>,
+}
+
+impl<S> Service<http::Request<BoxBody>> for Traced<S>
+where
+    S: Service<http::Request<BoxBody>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<BoxBody>) -> Self::Future {
+        let metadata = MetadataMap::from_headers(std::mem::take(req.headers_mut()));
+        let parent = extract_context(&metadata);
+        *req.headers_mut() = metadata.into_headers();
+
+        let path = req.uri().path().trim_start_matches('/').to_string();
+        let mut attributes = vec![KeyValue::new(RPC_SYSTEM, "grpc")];
+        if let Some((service, method)) = path.split_once('/') {
+            attributes.push(KeyValue::new(RPC_SERVICE, service.to_string()));
+            attributes.push(KeyValue::new(RPC_METHOD, method.to_string()));
+        }
+        if let Some(peer) = req.extensions().get::<TcpConnectInfo>().and_then(|info| info.remote_addr()) {
+            attributes.push(KeyValue::new(rf_contrib_trace::http::NETWORK_PEER_ADDRESS, peer.ip().to_string()));
+        }
+        let tracer = opentelemetry::global::tracer(self.config.tracer().to_string());
+        let span = tracer
+            .span_builder(path)
+            .with_kind(SpanKind::Server)
+            .with_attributes(attributes)
+            .start_with_context(&tracer, &parent);
+        let cx = parent.with_span(span);
+
+        // The clone may not be ready; call the instance poll_ready was called on
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let future = inner.call(req).with_context(cx.clone());
+        Box::pin(async move {
+            let result = future.await;
+            let span = cx.span();
+            // Handler errors come back trailers-only, with the status in the headers;
+            // a status sent in the trailers of a streamed body is not seen here
+            let code = match &result {
+                Ok(response) => Status::from_header_map(response.headers()).map_or(Code::Ok, |status| status.code()),
+                Err(_) => Code::Internal,
+            };
+            span.set_attribute(KeyValue::new(RPC_GRPC_STATUS_CODE, code as i64));
+            if is_server_error(code) {
+                span.set_status(SpanStatus::error(code.description()));
+            }
+            span.end();
+            result
+        })
+    }
+}
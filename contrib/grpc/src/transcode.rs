@@ -0,0 +1,771 @@
+//! # transcode
+//!
+//! transcode 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! JSON transcoding of protobuf messages
+//!
+//! Messages are converted using the types of registered file descriptors,
+//! following the proto3 JSON mapping: fields by JSON name (the proto name
+//! is accepted too), 64-bit integers as strings, bytes as base64, enums by
+//! name, maps as objects. `Timestamp`, `Duration` and the wrapper types use
+//! their JSON forms; other well-known types are plain messages.
+
+// Errors are `Status` values handed straight back to callers
+#![allow(clippy::result_large_err)]
+
+use crate::reflection::{DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto, HttpRuleProto};
+use base64::Engine;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use tonic::Status;
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_FIXED64: u64 = 1;
+const WIRE_LEN: u64 = 2;
+const WIRE_FIXED32: u64 = 5;
+
+/// Field type, `FieldDescriptorProto.Type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Double,
+    Float,
+    Int64,
+    Uint64,
+    Int32,
+    Fixed64,
+    Fixed32,
+    Bool,
+    String,
+    Message,
+    Bytes,
+    Uint32,
+    Enum,
+    Sfixed32,
+    Sfixed64,
+    Sint32,
+    Sint64,
+}
+
+impl Kind {
+    fn from_proto(ty: i32) -> Option<Self> {
+        Some(match ty {
+            1 => Self::Double,
+            2 => Self::Float,
+            3 => Self::Int64,
+            4 => Self::Uint64,
+            5 => Self::Int32,
+            6 => Self::Fixed64,
+            7 => Self::Fixed32,
+            8 => Self::Bool,
+            9 => Self::String,
+            11 => Self::Message,
+            12 => Self::Bytes,
+            13 => Self::Uint32,
+            14 => Self::Enum,
+            15 => Self::Sfixed32,
+            16 => Self::Sfixed64,
+            17 => Self::Sint32,
+            18 => Self::Sint64,
+            // Groups are not supported
+            _ => return None,
+        })
+    }
+
+    fn wire_type(self) -> u64 {
+        match self {
+            Self::Double | Self::Fixed64 | Self::Sfixed64 => WIRE_FIXED64,
+            Self::Float | Self::Fixed32 | Self::Sfixed32 => WIRE_FIXED32,
+            Self::String | Self::Bytes | Self::Message => WIRE_LEN,
+            _ => WIRE_VARINT,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    name: String,
+    json_name: String,
+    number: u32,
+    kind: Kind,
+    /// Fully qualified message or enum type, without the leading dot
+    type_name: String,
+    repeated: bool,
+}
+
+impl Field {
+    fn scalar(number: u32, kind: Kind) -> Self {
+        Self {
+            name: "value".to_string(),
+            json_name: "value".to_string(),
+            number,
+            kind,
+            type_name: String::new(),
+            repeated: false,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MessageType {
+    fields: Vec<Field>,
+    map_entry: bool,
+}
+
+impl MessageType {
+    /// Field by JSON or proto name
+    fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.json_name == name).or_else(|| self.fields.iter().find(|f| f.name == name))
+    }
+
+    fn field_by_number(&self, number: u32) -> Option<&Field> {
+        self.fields.iter().find(|f| f.number == number)
+    }
+}
+
+/// A method of a described service
+#[derive(Debug, Clone)]
+pub(crate) struct MethodType {
+    pub(crate) input: String,
+    pub(crate) output: String,
+    pub(crate) client_streaming: bool,
+    pub(crate) server_streaming: bool,
+    /// `google.api.http` annotation
+    pub(crate) http: Option<HttpRuleProto>,
+}
+
+enum WellKnown {
+    Timestamp,
+    Duration,
+    Wrapper(Kind),
+}
+
+fn well_known(type_name: &str) -> Option<WellKnown> {
+    Some(match type_name.strip_prefix("google.protobuf.")? {
+        "Timestamp" => WellKnown::Timestamp,
+        "Duration" => WellKnown::Duration,
+        "DoubleValue" => WellKnown::Wrapper(Kind::Double),
+        "FloatValue" => WellKnown::Wrapper(Kind::Float),
+        "Int64Value" => WellKnown::Wrapper(Kind::Int64),
+        "UInt64Value" => WellKnown::Wrapper(Kind::Uint64),
+        "Int32Value" => WellKnown::Wrapper(Kind::Int32),
+        "UInt32Value" => WellKnown::Wrapper(Kind::Uint32),
+        "BoolValue" => WellKnown::Wrapper(Kind::Bool),
+        "StringValue" => WellKnown::Wrapper(Kind::String),
+        "BytesValue" => WellKnown::Wrapper(Kind::Bytes),
+        _ => return None,
+    })
+}
+
+/// A field value as read from the wire
+#[derive(Clone, Copy)]
+enum Raw<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Fixed32(u32),
+    Bytes(&'a [u8]),
+}
+
+/// Message, enum and method types of registered file descriptors
+#[derive(Debug, Default)]
+pub(crate) struct TypeRegistry {
+    messages: HashMap<String, MessageType>,
+    /// Value names and numbers by enum type
+    enums: HashMap<String, Vec<(String, i32)>>,
+    /// Methods by `pkg.Service/Method`
+    methods: HashMap<String, MethodType>,
+}
+
+impl TypeRegistry {
+    pub(crate) fn add_file(&mut self, file: &FileDescriptorProto) {
+        let package = file.package.as_deref().unwrap_or_default();
+        let qualify = |name: &Option<String>| match package {
+            "" => name.clone().unwrap_or_default(),
+            package => format!("{}.{}", package, name.as_deref().unwrap_or_default()),
+        };
+        for message in &file.message_type {
+            self.add_message(&qualify(&message.name), message);
+        }
+        for enumeration in &file.enum_type {
+            self.add_enum(qualify(&enumeration.name), enumeration);
+        }
+        for service in &file.service {
+            let service_name = qualify(&service.name);
+            for method in &service.method {
+                let name = format!("{}/{}", service_name, method.name.as_deref().unwrap_or_default());
+                self.methods.insert(
+                    name,
+                    MethodType {
+                        input: type_name(&method.input_type),
+                        output: type_name(&method.output_type),
+                        client_streaming: method.client_streaming.unwrap_or_default(),
+                        server_streaming: method.server_streaming.unwrap_or_default(),
+                        http: method.options.as_ref().and_then(|options| options.http.clone()),
+                    },
+                );
+            }
+        }
+    }
+
+    fn add_message(&mut self, full_name: &str, message: &DescriptorProto) {
+        for nested in &message.nested_type {
+            self.add_message(&format!("{}.{}", full_name, nested.name.as_deref().unwrap_or_default()), nested);
+        }
+        for enumeration in &message.enum_type {
+            let name = format!("{}.{}", full_name, enumeration.name.as_deref().unwrap_or_default());
+            self.add_enum(name, enumeration);
+        }
+        let message_type = MessageType {
+            fields: message.field.iter().filter_map(field).collect(),
+            map_entry: message.options.as_ref().and_then(|o| o.map_entry).unwrap_or_default(),
+        };
+        self.messages.insert(full_name.to_string(), message_type);
+    }
+
+    fn add_enum(&mut self, full_name: String, enumeration: &EnumDescriptorProto) {
+        let values = enumeration
+            .value
+            .iter()
+            .map(|v| (v.name.clone().unwrap_or_default(), v.number.unwrap_or_default()))
+            .collect();
+        self.enums.insert(full_name, values);
+    }
+
+    /// Method by `pkg.Service/Method`
+    pub(crate) fn method(&self, name: &str) -> Option<&MethodType> {
+        self.methods.get(name)
+    }
+
+    /// Methods of a service with their names
+    pub(crate) fn service_methods<'a>(&'a self, service: &'a str) -> impl Iterator<Item = (&'a str, &'a MethodType)> {
+        self.methods.iter().filter_map(move |(name, method)| {
+            let (service_name, method_name) = name.split_once('/')?;
+            (service_name == service).then_some((method_name, method))
+        })
+    }
+
+    /// Whether a dotted field path exists in a message type
+    pub(crate) fn has_field_path(&self, ty: &str, path: &str) -> bool {
+        self.resolve(ty, path).is_some()
+    }
+
+    /// JSON name of a top-level field
+    pub(crate) fn json_name(&self, ty: &str, name: &str) -> Option<String> {
+        Some(self.messages.get(ty)?.field(name)?.json_name.clone())
+    }
+
+    fn resolve(&self, ty: &str, path: &str) -> Option<Vec<&Field>> {
+        let mut fields: Vec<&Field> = Vec::new();
+        let mut ty = ty;
+        for (i, name) in path.split('.').enumerate() {
+            if i > 0 {
+                let parent = *fields.last()?;
+                if parent.kind != Kind::Message || parent.repeated {
+                    return None;
+                }
+                ty = &parent.type_name;
+            }
+            fields.push(self.messages.get(ty)?.field(name)?);
+        }
+        Some(fields)
+    }
+
+    /// Set a dotted field path in a request object
+    ///
+    /// Repeated fields get `value` appended when `append` is set. Returns
+    /// `false` for an unknown field.
+    pub(crate) fn set_field(&self, ty: &str, object: &mut Map<String, Value>, path: &str, value: Value, append: bool) -> bool {
+        let Some(fields) = self.resolve(ty, path) else {
+            return false;
+        };
+        let mut object = object;
+        for (i, field) in fields.iter().enumerate() {
+            // Values given under the proto name move to the JSON name
+            if field.name != field.json_name {
+                if let Some(existing) = object.remove(&field.name) {
+                    object.entry(field.json_name.clone()).or_insert(existing);
+                }
+            }
+            if i + 1 == fields.len() {
+                if field.repeated && append {
+                    let entry = object.entry(field.json_name.clone()).or_insert_with(|| Value::Array(Vec::new()));
+                    if !entry.is_array() {
+                        *entry = Value::Array(Vec::new());
+                    }
+                    if let Value::Array(values) = entry {
+                        values.push(value);
+                    }
+                } else {
+                    object.insert(field.json_name.clone(), value);
+                }
+                break;
+            }
+            let entry = object.entry(field.json_name.clone()).or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            let Value::Object(nested) = entry else { unreachable!() };
+            object = nested;
+        }
+        true
+    }
+
+    /// Encode a JSON value as a message of type `ty`
+    pub(crate) fn encode(&self, ty: &str, value: &Value) -> Result<Vec<u8>, Status> {
+        let mut buf = Vec::new();
+        self.encode_message(ty, value, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn encode_message(&self, ty: &str, value: &Value, buf: &mut Vec<u8>) -> Result<(), Status> {
+        if let Some(known) = well_known(ty) {
+            return self.encode_well_known(known, ty, value, buf);
+        }
+        let message = self.message(ty)?;
+        let object = match value {
+            Value::Object(object) => object,
+            Value::Null => return Ok(()),
+            _ => return Err(invalid(format!("expected an object for {}", ty))),
+        };
+        for (key, value) in object {
+            let field = message
+                .field(key)
+                .ok_or_else(|| invalid(format!("unknown field '{}' in {}", key, ty)))?;
+            match value {
+                Value::Null => {}
+                _ if self.is_map(field) => {
+                    let entry = self.message(&field.type_name)?;
+                    let (Some(key_field), Some(value_field)) = (entry.field_by_number(1), entry.field_by_number(2)) else {
+                        return Err(Status::internal(format!("invalid map entry {}", field.type_name)));
+                    };
+                    let Value::Object(map) = value else {
+                        return Err(invalid(format!("expected an object for '{}'", key)));
+                    };
+                    for (map_key, map_value) in map {
+                        let mut entry = Vec::new();
+                        self.encode_field(key_field, &Value::String(map_key.clone()), &mut entry)?;
+                        self.encode_field(value_field, map_value, &mut entry)?;
+                        put_key(field.number, WIRE_LEN, buf);
+                        put_bytes(&entry, buf);
+                    }
+                }
+                Value::Array(values) if field.repeated => {
+                    for value in values {
+                        self.encode_field(field, value, buf)?;
+                    }
+                }
+                _ if field.repeated => return Err(invalid(format!("expected an array for '{}'", key))),
+                _ => self.encode_field(field, value, buf)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_field(&self, field: &Field, value: &Value, buf: &mut Vec<u8>) -> Result<(), Status> {
+        let bad = || invalid(format!("invalid value {} for '{}'", value, field.json_name));
+        put_key(field.number, field.kind.wire_type(), buf);
+        match field.kind {
+            Kind::Message => {
+                let mut nested = Vec::new();
+                self.encode_message(&field.type_name, value, &mut nested)?;
+                put_bytes(&nested, buf);
+            }
+            Kind::String => put_bytes(value.as_str().ok_or_else(bad)?.as_bytes(), buf),
+            Kind::Bytes => {
+                let text = value.as_str().ok_or_else(bad)?;
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(text)
+                    .or_else(|_| base64::engine::general_purpose::URL_SAFE.decode(text))
+                    .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(text))
+                    .or_else(|_| base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(text))
+                    .map_err(|_| bad())?;
+                put_bytes(&bytes, buf);
+            }
+            Kind::Enum => {
+                let number = match value {
+                    Value::String(name) => self
+                        .enums
+                        .get(&field.type_name)
+                        .and_then(|values| values.iter().find(|(n, _)| n == name))
+                        .map(|(_, number)| *number)
+                        .or_else(|| name.parse().ok()),
+                    _ => int(value, i32::MIN.into(), i32::MAX.into()).map(|n| n as i32),
+                };
+                put_varint(number.ok_or_else(bad)? as i64 as u64, buf);
+            }
+            Kind::Bool => {
+                let flag = match value {
+                    Value::Bool(flag) => Some(*flag),
+                    Value::String(text) => text.parse().ok(),
+                    _ => None,
+                };
+                put_varint(flag.ok_or_else(bad)? as u64, buf);
+            }
+            Kind::Int32 => put_varint(int(value, i32::MIN.into(), i32::MAX.into()).ok_or_else(bad)? as u64, buf),
+            Kind::Int64 => put_varint(int(value, i64::MIN, i64::MAX).ok_or_else(bad)? as u64, buf),
+            Kind::Uint32 => put_varint(uint(value, u32::MAX.into()).ok_or_else(bad)?, buf),
+            Kind::Uint64 => put_varint(uint(value, u64::MAX).ok_or_else(bad)?, buf),
+            Kind::Sint32 => {
+                let n = int(value, i32::MIN.into(), i32::MAX.into()).ok_or_else(bad)? as i32;
+                put_varint(((n << 1) ^ (n >> 31)) as u32 as u64, buf);
+            }
+            Kind::Sint64 => {
+                let n = int(value, i64::MIN, i64::MAX).ok_or_else(bad)?;
+                put_varint(((n << 1) ^ (n >> 63)) as u64, buf);
+            }
+            Kind::Fixed32 => buf.extend_from_slice(&(uint(value, u32::MAX.into()).ok_or_else(bad)? as u32).to_le_bytes()),
+            Kind::Sfixed32 => {
+                let n = int(value, i32::MIN.into(), i32::MAX.into()).ok_or_else(bad)? as i32;
+                buf.extend_from_slice(&n.to_le_bytes());
+            }
+            Kind::Fixed64 => buf.extend_from_slice(&uint(value, u64::MAX).ok_or_else(bad)?.to_le_bytes()),
+            Kind::Sfixed64 => buf.extend_from_slice(&int(value, i64::MIN, i64::MAX).ok_or_else(bad)?.to_le_bytes()),
+            Kind::Float => buf.extend_from_slice(&(float(value).ok_or_else(bad)? as f32).to_le_bytes()),
+            Kind::Double => buf.extend_from_slice(&float(value).ok_or_else(bad)?.to_le_bytes()),
+        }
+        Ok(())
+    }
+
+    fn encode_well_known(&self, known: WellKnown, ty: &str, value: &Value, buf: &mut Vec<u8>) -> Result<(), Status> {
+        let bad = || invalid(format!("invalid value {} for {}", value, ty));
+        let (seconds, nanos) = match known {
+            WellKnown::Wrapper(kind) => return self.encode_field(&Field::scalar(1, kind), value, buf),
+            WellKnown::Timestamp => {
+                let time = chrono::DateTime::parse_from_rfc3339(value.as_str().ok_or_else(bad)?).map_err(|_| bad())?;
+                (time.timestamp(), time.timestamp_subsec_nanos() as i32)
+            }
+            WellKnown::Duration => parse_duration(value.as_str().ok_or_else(bad)?).ok_or_else(bad)?,
+        };
+        if seconds != 0 {
+            put_key(1, WIRE_VARINT, buf);
+            put_varint(seconds as u64, buf);
+        }
+        if nanos != 0 {
+            put_key(2, WIRE_VARINT, buf);
+            put_varint(nanos as i64 as u64, buf);
+        }
+        Ok(())
+    }
+
+    /// Decode a message of type `ty` to JSON
+    pub(crate) fn decode(&self, ty: &str, bytes: &[u8]) -> Result<Value, Status> {
+        if let Some(known) = well_known(ty) {
+            return self.decode_well_known(known, bytes);
+        }
+        let message = self.message(ty)?;
+        let mut object = Map::new();
+        for (number, raw) in read_fields(bytes)? {
+            let Some(field) = message.field_by_number(number) else {
+                continue;
+            };
+            if self.is_map(field) {
+                let Raw::Bytes(entry) = raw else {
+                    return Err(malformed());
+                };
+                let (key, value) = self.decode_map_entry(&field.type_name, entry)?;
+                let map = object.entry(field.json_name.clone()).or_insert_with(|| Value::Object(Map::new()));
+                if let Value::Object(map) = map {
+                    map.insert(key, value);
+                }
+            } else if field.repeated {
+                let values = object.entry(field.json_name.clone()).or_insert_with(|| Value::Array(Vec::new()));
+                let Value::Array(values) = values else {
+                    return Err(malformed());
+                };
+                match raw {
+                    // Packed scalars
+                    Raw::Bytes(mut packed) if field.kind.wire_type() != WIRE_LEN => {
+                        while !packed.is_empty() {
+                            let raw = read_raw(&mut packed, field.kind.wire_type())?;
+                            values.push(self.decode_value(field, raw)?);
+                        }
+                    }
+                    raw => values.push(self.decode_value(field, raw)?),
+                }
+            } else {
+                object.insert(field.json_name.clone(), self.decode_value(field, raw)?);
+            }
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn decode_map_entry(&self, ty: &str, bytes: &[u8]) -> Result<(String, Value), Status> {
+        let entry = self.message(ty)?;
+        let (Some(key_field), Some(value_field)) = (entry.field_by_number(1), entry.field_by_number(2)) else {
+            return Err(Status::internal(format!("invalid map entry {}", ty)));
+        };
+        let (mut key, mut value) = (None, None);
+        for (number, raw) in read_fields(bytes)? {
+            match number {
+                1 => key = Some(self.decode_value(key_field, raw)?),
+                2 => value = Some(self.decode_value(value_field, raw)?),
+                _ => {}
+            }
+        }
+        let key = match key.unwrap_or_else(|| self.default_value(key_field)) {
+            Value::String(key) => key,
+            key => key.to_string(),
+        };
+        Ok((key, value.unwrap_or_else(|| self.default_value(value_field))))
+    }
+
+    fn decode_value(&self, field: &Field, raw: Raw<'_>) -> Result<Value, Status> {
+        Ok(match (field.kind, raw) {
+            (Kind::Int32, Raw::Varint(v)) => Value::from(v as i32),
+            (Kind::Int64, Raw::Varint(v)) => Value::String((v as i64).to_string()),
+            (Kind::Uint32, Raw::Varint(v)) => Value::from(v as u32),
+            (Kind::Uint64, Raw::Varint(v)) => Value::String(v.to_string()),
+            (Kind::Sint32, Raw::Varint(v)) => Value::from(((v >> 1) as i64 ^ -((v & 1) as i64)) as i32),
+            (Kind::Sint64, Raw::Varint(v)) => Value::String(((v >> 1) as i64 ^ -((v & 1) as i64)).to_string()),
+            (Kind::Bool, Raw::Varint(v)) => Value::Bool(v != 0),
+            (Kind::Enum, Raw::Varint(v)) => {
+                let number = v as i32;
+                self.enums
+                    .get(&field.type_name)
+                    .and_then(|values| values.iter().find(|(_, n)| *n == number))
+                    .map_or_else(|| Value::from(number), |(name, _)| Value::String(name.clone()))
+            }
+            (Kind::Fixed32, Raw::Fixed32(v)) => Value::from(v),
+            (Kind::Sfixed32, Raw::Fixed32(v)) => Value::from(v as i32),
+            // Shortest form of the f32, not of its f64 widening
+            (Kind::Float, Raw::Fixed32(v)) => float_value(f32::from_bits(v).to_string().parse().unwrap_or(f64::NAN)),
+            (Kind::Fixed64, Raw::Fixed64(v)) => Value::String(v.to_string()),
+            (Kind::Sfixed64, Raw::Fixed64(v)) => Value::String((v as i64).to_string()),
+            (Kind::Double, Raw::Fixed64(v)) => float_value(f64::from_bits(v)),
+            (Kind::String, Raw::Bytes(bytes)) => {
+                Value::String(String::from_utf8(bytes.to_vec()).map_err(|_| malformed())?)
+            }
+            (Kind::Bytes, Raw::Bytes(bytes)) => Value::String(base64::engine::general_purpose::STANDARD.encode(bytes)),
+            (Kind::Message, Raw::Bytes(bytes)) => self.decode(&field.type_name, bytes)?,
+            _ => return Err(malformed()),
+        })
+    }
+
+    fn decode_well_known(&self, known: WellKnown, bytes: &[u8]) -> Result<Value, Status> {
+        if let WellKnown::Wrapper(kind) = known {
+            let field = Field::scalar(1, kind);
+            let mut value = self.default_value(&field);
+            for (number, raw) in read_fields(bytes)? {
+                if number == 1 {
+                    value = self.decode_value(&field, raw)?;
+                }
+            }
+            return Ok(value);
+        }
+        let (mut seconds, mut nanos) = (0i64, 0i32);
+        for (number, raw) in read_fields(bytes)? {
+            match (number, raw) {
+                (1, Raw::Varint(v)) => seconds = v as i64,
+                (2, Raw::Varint(v)) => nanos = v as i32,
+                _ => {}
+            }
+        }
+        Ok(Value::String(match known {
+            WellKnown::Timestamp => chrono::DateTime::from_timestamp(seconds, nanos.max(0) as u32)
+                .ok_or_else(malformed)?
+                .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+            _ => format_duration(seconds, nanos),
+        }))
+    }
+
+    fn default_value(&self, field: &Field) -> Value {
+        match field.kind {
+            Kind::Message => Value::Object(Map::new()),
+            Kind::String | Kind::Bytes => Value::String(String::new()),
+            Kind::Bool => Value::Bool(false),
+            Kind::Int64 | Kind::Uint64 | Kind::Sint64 | Kind::Fixed64 | Kind::Sfixed64 => Value::String("0".to_string()),
+            Kind::Enum => self
+                .enums
+                .get(&field.type_name)
+                .and_then(|values| values.iter().find(|(_, n)| *n == 0))
+                .map_or_else(|| Value::from(0), |(name, _)| Value::String(name.clone())),
+            _ => Value::from(0),
+        }
+    }
+
+    fn message(&self, ty: &str) -> Result<&MessageType, Status> {
+        self.messages
+            .get(ty)
+            .ok_or_else(|| Status::internal(format!("no descriptor for message {}", ty)))
+    }
+
+    fn is_map(&self, field: &Field) -> bool {
+        field.repeated && field.kind == Kind::Message && self.messages.get(&field.type_name).is_some_and(|m| m.map_entry)
+    }
+}
+
+fn type_name(name: &Option<String>) -> String {
+    name.as_deref().unwrap_or_default().trim_start_matches('.').to_string()
+}
+
+fn field(field: &FieldDescriptorProto) -> Option<Field> {
+    let name = field.name.clone().unwrap_or_default();
+    Some(Field {
+        json_name: field.json_name.clone().unwrap_or_else(|| lower_camel(&name)),
+        name,
+        number: u32::try_from(field.number?).ok()?,
+        kind: Kind::from_proto(field.r#type?)?,
+        type_name: type_name(&field.type_name),
+        repeated: field.label == Some(3),
+    })
+}
+
+/// protoc's default JSON name, `user_id` becomes `userId`
+fn lower_camel(name: &str) -> String {
+    let mut json_name = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            json_name.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            json_name.push(c);
+        }
+    }
+    json_name
+}
+
+fn invalid(message: String) -> Status {
+    Status::invalid_argument(message)
+}
+
+fn malformed() -> Status {
+    Status::internal("malformed protobuf message")
+}
+
+fn put_varint(mut value: u64, buf: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_key(number: u32, wire_type: u64, buf: &mut Vec<u8>) {
+    put_varint(u64::from(number) << 3 | wire_type, buf);
+}
+
+fn put_bytes(bytes: &[u8], buf: &mut Vec<u8>) {
+    put_varint(bytes.len() as u64, buf);
+    buf.extend_from_slice(bytes);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, Status> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first().ok_or_else(malformed)?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return Ok(value);
+        }
+    }
+    Err(malformed())
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], Status> {
+    if bytes.len() < len {
+        return Err(malformed());
+    }
+    let (value, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(value)
+}
+
+fn read_raw<'a>(bytes: &mut &'a [u8], wire_type: u64) -> Result<Raw<'a>, Status> {
+    Ok(match wire_type {
+        WIRE_VARINT => Raw::Varint(read_varint(bytes)?),
+        WIRE_FIXED64 => Raw::Fixed64(u64::from_le_bytes(take(bytes, 8)?.try_into().map_err(|_| malformed())?)),
+        WIRE_FIXED32 => Raw::Fixed32(u32::from_le_bytes(take(bytes, 4)?.try_into().map_err(|_| malformed())?)),
+        WIRE_LEN => {
+            let len = usize::try_from(read_varint(bytes)?).map_err(|_| malformed())?;
+            Raw::Bytes(take(bytes, len)?)
+        }
+        _ => return Err(Status::internal("unsupported protobuf wire type")),
+    })
+}
+
+fn read_fields(mut bytes: &[u8]) -> Result<Vec<(u32, Raw<'_>)>, Status> {
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        let key = read_varint(&mut bytes)?;
+        let number = u32::try_from(key >> 3).map_err(|_| malformed())?;
+        fields.push((number, read_raw(&mut bytes, key & 7)?));
+    }
+    Ok(fields)
+}
+
+fn int(value: &Value, min: i64, max: i64) -> Option<i64> {
+    let n = match value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64))?,
+        Value::String(text) => text.trim().parse().ok()?,
+        _ => return None,
+    };
+    (min..=max).contains(&n).then_some(n)
+}
+
+fn uint(value: &Value, max: u64) -> Option<u64> {
+    let n = match value {
+        Value::Number(n) => n.as_u64().or_else(|| n.as_f64().filter(|f| f.fract() == 0.0 && *f >= 0.0).map(|f| f as u64))?,
+        Value::String(text) => text.trim().parse().ok()?,
+        _ => return None,
+    };
+    (n <= max).then_some(n)
+}
+
+fn float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(text) => match text.as_str() {
+            "NaN" => Some(f64::NAN),
+            "Infinity" => Some(f64::INFINITY),
+            "-Infinity" => Some(f64::NEG_INFINITY),
+            text => text.trim().parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+fn float_value(value: f64) -> Value {
+    match Number::from_f64(value) {
+        Some(n) => Value::Number(n),
+        None if value.is_nan() => Value::String("NaN".to_string()),
+        None if value > 0.0 => Value::String("Infinity".to_string()),
+        None => Value::String("-Infinity".to_string()),
+    }
+}
+
+/// `1.5s` to seconds and nanoseconds of the same sign
+fn parse_duration(text: &str) -> Option<(i64, i32)> {
+    let text = text.strip_suffix('s')?;
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+    let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+    if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let seconds: i64 = whole.parse().ok()?;
+    let nanos: i32 = format!("{:0<9}", fraction).parse().ok()?;
+    Some(if negative { (-seconds, -nanos) } else { (seconds, nanos) })
+}
+
+/// Seconds and nanoseconds as `1.500s`, with 0, 3, 6 or 9 fractional digits
+fn format_duration(seconds: i64, nanos: i32) -> String {
+    let sign = if seconds < 0 || nanos < 0 { "-" } else { "" };
+    let (seconds, nanos) = (seconds.unsigned_abs(), nanos.unsigned_abs());
+    if nanos == 0 {
+        format!("{}{}s", sign, seconds)
+    } else if nanos % 1_000_000 == 0 {
+        format!("{}{}.{:03}s", sign, seconds, nanos / 1_000_000)
+    } else if nanos % 1_000 == 0 {
+        format!("{}{}.{:06}s", sign, seconds, nanos / 1_000)
+    } else {
+        format!("{}{}.{:09}s", sign, seconds, nanos)
+    }
+}
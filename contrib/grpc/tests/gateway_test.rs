@@ -0,0 +1,609 @@
+//! gRPC gateway tests
+
+use axum::body::Body;
+use axum::http::{Method, Request as HttpRequest, StatusCode};
+use axum::Router;
+use base64::Engine;
+use http_body_util::BodyExt;
+use prost::Message;
+use rf_contrib_grpc::{GrpcClient, GrpcGateway, HttpRule};
+use rf_net::http::HttpServer;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::codegen::{http, BoxFuture, Service};
+use tonic::server::{Grpc, NamedService};
+use tonic::{Request, Response, Status};
+use tower::ServiceExt;
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct GetItemRequest {
+    #[prost(int64, tag = "1")]
+    id: i64,
+    #[prost(bool, tag = "2")]
+    verbose: bool,
+    #[prost(string, repeated, tag = "3")]
+    tags: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Timestamp {
+    #[prost(int64, tag = "1")]
+    seconds: i64,
+    #[prost(int32, tag = "2")]
+    nanos: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Item {
+    #[prost(int64, tag = "1")]
+    id: i64,
+    #[prost(string, tag = "2")]
+    name: String,
+    #[prost(double, tag = "3")]
+    price: f64,
+    #[prost(int32, tag = "4")]
+    kind: i32,
+    #[prost(map = "string, int32", tag = "5")]
+    labels: HashMap<String, i32>,
+    #[prost(message, optional, tag = "6")]
+    created_at: Option<Timestamp>,
+    #[prost(bytes = "vec", tag = "7")]
+    data: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct CreateItemRequest {
+    #[prost(string, tag = "1")]
+    parent: String,
+    #[prost(message, optional, tag = "2")]
+    item: Option<Item>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ListItemsRequest {
+    #[prost(uint32, tag = "1")]
+    count: u32,
+}
+
+/// `test.Shop` service
+#[derive(Clone)]
+struct ShopService;
+
+impl NamedService for ShopService {
+    const NAME: &'static str = "test.Shop";
+}
+
+impl Service<http::Request<BoxBody>> for ShopService {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        Box::pin(async move {
+            Ok(match req.uri().path() {
+                "/test.Shop/GetItem" => Grpc::new(ProstCodec::default()).unary(tower::service_fn(get_item), req).await,
+                "/test.Shop/CreateItem" => Grpc::new(ProstCodec::default()).unary(tower::service_fn(create_item), req).await,
+                "/test.Shop/ListItems" => {
+                    Grpc::new(ProstCodec::default()).server_streaming(tower::service_fn(list_items), req).await
+                }
+                _ => Status::unimplemented("").into_http(),
+            })
+        })
+    }
+}
+
+async fn get_item(request: Request<GetItemRequest>) -> Result<Response<Item>, Status> {
+    let user = request.metadata().get("x-user").and_then(|v| v.to_str().ok()).map(str::to_string);
+    let request = request.into_inner();
+    if request.id == 0 {
+        return Err(Status::not_found("item 0 not found"));
+    }
+    let mut name = format!("item {}", request.id);
+    if !request.tags.is_empty() {
+        name.push_str(&format!(" [{}]", request.tags.join(",")));
+    }
+    if request.verbose {
+        name.push_str(" (verbose)");
+    }
+    if let Some(user) = user {
+        name.push_str(&format!(" for {}", user));
+    }
+    Ok(Response::new(Item {
+        id: request.id,
+        name,
+        price: 9.5,
+        kind: 2,
+        labels: HashMap::from([("x".to_string(), 1)]),
+        created_at: Some(Timestamp {
+            seconds: 1_704_067_200,
+            nanos: 500_000_000,
+        }),
+        data: b"hi".to_vec(),
+    }))
+}
+
+async fn create_item(request: Request<CreateItemRequest>) -> Result<Response<Item>, Status> {
+    let request = request.into_inner();
+    let mut item = request.item.unwrap_or_default();
+    item.id = 7;
+    item.name = format!("{}/{}", request.parent, item.name);
+    Ok(Response::new(item))
+}
+
+type ItemStream = tonic::codegen::tokio_stream::Iter<std::vec::IntoIter<Result<Item, Status>>>;
+
+#[allow(clippy::result_large_err)]
+async fn list_items(request: Request<ListItemsRequest>) -> Result<Response<ItemStream>, Status> {
+    let items: Vec<_> = (1..=i64::from(request.into_inner().count))
+        .map(|id| {
+            Ok(Item {
+                id,
+                ..Default::default()
+            })
+        })
+        .collect();
+    Ok(Response::new(tonic::codegen::tokio_stream::iter(items)))
+}
+
+// The parts of descriptor.proto and google/api/http.proto the gateway reads
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct DescriptorSet {
+    #[prost(message, repeated, tag = "1")]
+    file: Vec<FileProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FileProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    package: String,
+    #[prost(message, repeated, tag = "4")]
+    message_type: Vec<MessageProto>,
+    #[prost(message, repeated, tag = "5")]
+    enum_type: Vec<EnumProto>,
+    #[prost(message, repeated, tag = "6")]
+    service: Vec<ServiceProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct MessageProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(message, repeated, tag = "2")]
+    field: Vec<FieldProto>,
+    #[prost(message, repeated, tag = "3")]
+    nested_type: Vec<MessageProto>,
+    #[prost(message, optional, tag = "7")]
+    options: Option<MessageOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct MessageOptions {
+    #[prost(bool, tag = "7")]
+    map_entry: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FieldProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int32, tag = "3")]
+    number: i32,
+    #[prost(int32, tag = "4")]
+    label: i32,
+    #[prost(int32, tag = "5")]
+    r#type: i32,
+    #[prost(string, tag = "6")]
+    type_name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct EnumProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(message, repeated, tag = "2")]
+    value: Vec<EnumValueProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct EnumValueProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int32, tag = "2")]
+    number: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ServiceProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(message, repeated, tag = "2")]
+    method: Vec<MethodProto>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct MethodProto {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    input_type: String,
+    #[prost(string, tag = "3")]
+    output_type: String,
+    #[prost(message, optional, tag = "4")]
+    options: Option<MethodOptions>,
+    #[prost(bool, tag = "6")]
+    server_streaming: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct MethodOptions {
+    #[prost(message, optional, tag = "72295728")]
+    http: Option<HttpAnnotation>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct HttpAnnotation {
+    #[prost(string, tag = "2")]
+    get: String,
+    #[prost(string, tag = "4")]
+    post: String,
+    #[prost(string, tag = "7")]
+    body: String,
+    #[prost(message, repeated, tag = "11")]
+    additional_bindings: Vec<HttpAnnotation>,
+}
+
+fn field(name: &str, number: i32, ty: i32, type_name: &str) -> FieldProto {
+    FieldProto {
+        name: name.to_string(),
+        number,
+        label: 1,
+        r#type: ty,
+        type_name: type_name.to_string(),
+    }
+}
+
+fn repeated(mut field: FieldProto) -> FieldProto {
+    field.label = 3;
+    field
+}
+
+fn message(name: &str, fields: Vec<FieldProto>) -> MessageProto {
+    MessageProto {
+        name: name.to_string(),
+        field: fields,
+        ..Default::default()
+    }
+}
+
+fn method(name: &str, input: &str, output: &str, http: HttpAnnotation) -> MethodProto {
+    MethodProto {
+        name: name.to_string(),
+        input_type: format!(".test.{}", input),
+        output_type: format!(".test.{}", output),
+        options: Some(MethodOptions { http: Some(http) }),
+        server_streaming: false,
+    }
+}
+
+/// `test/shop.proto` with `google.api.http` annotations
+fn descriptor_set() -> Vec<u8> {
+    let mut item = message(
+        "Item",
+        vec![
+            field("id", 1, 3, ""),
+            field("name", 2, 9, ""),
+            field("price", 3, 1, ""),
+            field("kind", 4, 14, ".test.Kind"),
+            repeated(field("labels", 5, 11, ".test.Item.LabelsEntry")),
+            field("created_at", 6, 11, ".google.protobuf.Timestamp"),
+            field("data", 7, 12, ""),
+        ],
+    );
+    let mut entry = message("LabelsEntry", vec![field("key", 1, 9, ""), field("value", 2, 5, "")]);
+    entry.options = Some(MessageOptions { map_entry: true });
+    item.nested_type.push(entry);
+
+    let mut list = method(
+        "ListItems",
+        "ListItemsRequest",
+        "Item",
+        HttpAnnotation {
+            get: "/v1/items".to_string(),
+            ..Default::default()
+        },
+    );
+    list.server_streaming = true;
+    DescriptorSet {
+        file: vec![FileProto {
+            name: "test/shop.proto".to_string(),
+            package: "test".to_string(),
+            message_type: vec![
+                message(
+                    "GetItemRequest",
+                    vec![field("id", 1, 3, ""), field("verbose", 2, 8, ""), repeated(field("tags", 3, 9, ""))],
+                ),
+                item,
+                message(
+                    "CreateItemRequest",
+                    vec![field("parent", 1, 9, ""), field("item", 2, 11, ".test.Item")],
+                ),
+                message("ListItemsRequest", vec![field("count", 1, 13, "")]),
+            ],
+            enum_type: vec![EnumProto {
+                name: "Kind".to_string(),
+                value: ["KIND_UNSPECIFIED", "KIND_FOOD", "KIND_BOOK"]
+                    .iter()
+                    .zip(0..)
+                    .map(|(name, number)| EnumValueProto {
+                        name: name.to_string(),
+                        number,
+                    })
+                    .collect(),
+            }],
+            service: vec![ServiceProto {
+                name: "Shop".to_string(),
+                method: vec![
+                    method(
+                        "GetItem",
+                        "GetItemRequest",
+                        "Item",
+                        HttpAnnotation {
+                            get: "/v1/items/{id}".to_string(),
+                            ..Default::default()
+                        },
+                    ),
+                    method(
+                        "CreateItem",
+                        "CreateItemRequest",
+                        "Item",
+                        HttpAnnotation {
+                            post: "/v1/{parent=shelves/*}/items".to_string(),
+                            body: "item".to_string(),
+                            additional_bindings: vec![HttpAnnotation {
+                                post: "/v1/items:create".to_string(),
+                                body: "*".to_string(),
+                                ..Default::default()
+                            }],
+                            ..Default::default()
+                        },
+                    ),
+                    list,
+                ],
+            }],
+        }],
+    }
+    .encode_to_vec()
+}
+
+fn gateway() -> GrpcGateway {
+    GrpcGateway::new()
+        .add_service(ShopService)
+        .with_descriptors(&descriptor_set())
+        .unwrap()
+        .route(HttpRule::get("/v1/items/:id/name", "test.Shop/GetItem").response_body("name"))
+}
+
+async fn call(app: &Router, request: HttpRequest<Body>) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn get(uri: &str) -> HttpRequest<Body> {
+    HttpRequest::get(uri).body(Body::empty()).unwrap()
+}
+
+fn post(uri: &str, body: Value) -> HttpRequest<Body> {
+    HttpRequest::post(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn grpc_frame(message: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0];
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend_from_slice(message);
+    frame
+}
+
+/// Data and trailer frames of a gRPC-Web response body
+fn web_frames(mut body: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    let mut frames = Vec::new();
+    while !body.is_empty() {
+        let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+        frames.push((body[0], body[5..5 + len].to_vec()));
+        body = &body[5 + len..];
+    }
+    frames
+}
+
+#[tokio::test]
+async fn test_json_transcoding() {
+    let app = gateway().into_router().unwrap();
+
+    let request = HttpRequest::get("/v1/items/42?verbose=true&tags=a&tags=b&_=123")
+        .header("x-user", "alice")
+        .body(Body::empty())
+        .unwrap();
+    let (status, body) = call(&app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({
+            "id": "42",
+            "name": "item 42 [a,b] (verbose) for alice",
+            "price": 9.5,
+            "kind": "KIND_BOOK",
+            "labels": {"x": 1},
+            "createdAt": "2024-01-01T00:00:00.500Z",
+            "data": "aGk=",
+        })
+    );
+
+    // Body bound to a field, parent from a path pattern
+    let item = json!({
+        "name": "pen",
+        "price": 1.25,
+        "kind": "KIND_FOOD",
+        "labels": {"a": 2},
+        "createdAt": "2024-01-01T00:00:00Z",
+        "data": "aGk=",
+    });
+    let (status, body) = call(&app, post("/v1/shelves/s1/items", item)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({
+            "id": "7",
+            "name": "shelves/s1/pen",
+            "price": 1.25,
+            "kind": "KIND_FOOD",
+            "labels": {"a": 2},
+            "createdAt": "2024-01-01T00:00:00Z",
+            "data": "aGk=",
+        })
+    );
+
+    // Additional binding with a verb, whole message in the body
+    let (_, body) = call(&app, post("/v1/items:create", json!({"parent": "shelves/s2", "item": {"name": "cup"}}))).await;
+    assert_eq!(body, json!({"id": "7", "name": "shelves/s2/cup"}));
+
+    // Server streaming answers with an array, explicit rules pick a response field
+    let (_, body) = call(&app, get("/v1/items?count=2")).await;
+    assert_eq!(body, json!([{"id": "1"}, {"id": "2"}]));
+    let (_, body) = call(&app, get("/v1/items/3/name")).await;
+    assert_eq!(body, json!("item 3"));
+}
+
+#[tokio::test]
+async fn test_json_errors() {
+    let app = gateway().into_router().unwrap();
+
+    let (status, body) = call(&app, get("/v1/items/0")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body, json!({"code": 5, "message": "item 0 not found", "details": []}));
+
+    let (status, body) = call(&app, post("/v1/shelves/s1/items", json!({"nope": 1}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], 3);
+    let (status, _) = call(&app, get("/v1/items/abc")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = call(&app, HttpRequest::delete("/v1/items/42").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+
+    // Rules are checked when the router is built
+    let unknown_method = gateway().route(HttpRule::get("/x/:id", "test.Shop/Missing"));
+    assert!(unknown_method.into_router().is_err());
+    let unknown_field = gateway().route(HttpRule::get("/x/{missing}", "test.Shop.GetItem"));
+    assert!(unknown_field.into_router().is_err());
+    let bad_template = gateway().route(HttpRule::get("/x/{id", "test.Shop/GetItem"));
+    assert!(bad_template.into_router().is_err());
+    let not_added = GrpcGateway::new()
+        .with_descriptors(&descriptor_set())
+        .unwrap()
+        .route(HttpRule::get("/x/:id", "test.Shop/GetItem"));
+    assert!(not_added.into_router().is_err());
+}
+
+#[tokio::test]
+async fn test_grpc_web() {
+    let app = gateway().into_router().unwrap();
+    let message = GetItemRequest {
+        id: 5,
+        ..Default::default()
+    }
+    .encode_to_vec();
+
+    let request = HttpRequest::post("/test.Shop/GetItem")
+        .header("content-type", "application/grpc-web+proto")
+        .body(Body::from(grpc_frame(&message)))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/grpc-web+proto");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let frames = web_frames(&body);
+    assert_eq!(frames.len(), 2);
+    assert_eq!(Item::decode(frames[0].1.as_slice()).unwrap().name, "item 5");
+    assert_eq!(frames[1].0, 0x80);
+    assert!(String::from_utf8_lossy(&frames[1].1).contains("grpc-status:0"));
+
+    // Text mode, base64 both ways
+    let engine = base64::engine::general_purpose::STANDARD;
+    let request = HttpRequest::post("/test.Shop/GetItem")
+        .header("content-type", "application/grpc-web-text")
+        .body(Body::from(engine.encode(grpc_frame(&message))))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.headers()["content-type"], "application/grpc-web-text+proto");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let decoded: Vec<u8> = body.chunks(4).flat_map(|group| engine.decode(group).unwrap()).collect();
+    let frames = web_frames(&decoded);
+    assert_eq!(Item::decode(frames[0].1.as_slice()).unwrap().name, "item 5");
+
+    // Errors come back in the headers of a trailers-only response
+    let request = HttpRequest::post("/test.Shop/GetItem")
+        .header("content-type", "application/grpc-web+proto")
+        .body(Body::from(grpc_frame(&GetItemRequest::default().encode_to_vec())))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.headers()["grpc-status"], "5");
+
+    let request = HttpRequest::post("/test.Shop/GetItem")
+        .header("content-type", "application/json")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn test_mount_serves_json_and_grpc_on_one_port() {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let server = HttpServer::new(addr)
+        .route(Method::GET, "/ping", || async { "pong" })
+        .unwrap();
+    let server = gateway().mount(server).unwrap();
+    let task = tokio::spawn(async move {
+        let _ = server.serve().await;
+    });
+    for _ in 0..100 {
+        if tokio::net::TcpStream::connect(addr).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let client = reqwest::Client::new();
+    let response = client.get(format!("http://{}/ping", addr)).send().await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "pong");
+    let response = client.get(format!("http://{}/v1/items/3", addr)).send().await.unwrap();
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["name"], "item 3");
+
+    let channel = GrpcClient::new(format!("http://{}", addr)).connect().await.unwrap();
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready().await.unwrap();
+    let request = Request::new(GetItemRequest {
+        id: 3,
+        ..Default::default()
+    });
+    let path = PathAndQuery::from_static("/test.Shop/GetItem");
+    let response: Response<Item> = grpc.unary(request, path, ProstCodec::default()).await.unwrap();
+    assert_eq!(response.into_inner().name, "item 3");
+    task.abort();
+}
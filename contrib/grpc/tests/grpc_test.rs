@@ -0,0 +1,515 @@
+//! gRPC server tests
+
+use prost::Message;
+use rf_contrib_grpc::health::{HealthCheckRequest, HealthCheckResponse};
+use rf_contrib_grpc::reflection::{MessageRequest, MessageResponse, ServerReflectionRequest, ServerReflectionResponse};
+use rf_contrib_grpc::{GrpcClient, GrpcServer, PooledChannel, RetryPolicy, ServingStatus};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::client::GrpcService;
+use tonic::codegen::{http, BoxFuture, Bytes, Service, StdError};
+use tonic::server::{Grpc, NamedService};
+use tonic::transport::Channel;
+use tonic::{Code, Request, Response, Status, Streaming};
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Echo {
+    #[prost(bytes = "vec", tag = "1")]
+    payload: Vec<u8>,
+    /// Milliseconds to wait before answering
+    #[prost(uint64, tag = "2")]
+    delay: u64,
+    /// Answer with a payload this many times as long
+    #[prost(uint32, tag = "3")]
+    repeat: u32,
+    /// Answer with the value of this request header instead
+    #[prost(string, tag = "4")]
+    header: String,
+}
+
+/// `test.Echo` service echoing the payload of `Say`
+#[derive(Clone)]
+struct EchoService;
+
+impl NamedService for EchoService {
+    const NAME: &'static str = "test.Echo";
+}
+
+impl Service<http::Request<BoxBody>> for EchoService {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        Box::pin(async move {
+            let method = tower::service_fn(|request: Request<Echo>| async move {
+                let header = request.metadata().get(request.get_ref().header.as_str()).cloned();
+                let mut echo = request.into_inner();
+                if let Some(value) = header {
+                    echo.payload = value.as_bytes().to_vec();
+                    return Ok(Response::new(echo));
+                }
+                tokio::time::sleep(Duration::from_millis(echo.delay)).await;
+                echo.payload = echo.payload.repeat(echo.repeat.max(1) as usize);
+                Ok::<_, Status>(Response::new(echo))
+            });
+            // Sizes are left to the server-wide limits
+            let mut grpc = Grpc::new(ProstCodec::default()).max_decoding_message_size(usize::MAX);
+            Ok(grpc.unary(method, req).await)
+        })
+    }
+}
+
+struct Running {
+    addr: SocketAddr,
+    stop: oneshot::Sender<()>,
+    task: tokio::task::JoinHandle<rf_errors::Result<()>>,
+}
+
+async fn start(server: GrpcServer) -> Running {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop, stopped) = oneshot::channel::<()>();
+    let task = tokio::spawn(server.add_service(EchoService).serve_with_listener(listener, async {
+        let _ = stopped.await;
+    }));
+    Running { addr, stop, task }
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    GrpcClient::new(format!("http://{}", addr)).connect().await.unwrap()
+}
+
+async fn unary<T, Req, Res>(channel: &T, path: &'static str, message: Req) -> Result<Res, Status>
+where
+    T: GrpcService<BoxBody> + Clone,
+    T::Error: Into<StdError>,
+    T::ResponseBody: http_body::Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as http_body::Body>::Error: Into<StdError>,
+    Req: Message + Send + Sync + 'static,
+    Res: Message + Default + Send + Sync + 'static,
+{
+    let mut grpc = tonic::client::Grpc::new(channel.clone());
+    grpc.ready().await.map_err(|e| Status::unavailable(e.into().to_string()))?;
+    let response = grpc
+        .unary(Request::new(message), PathAndQuery::from_static(path), ProstCodec::default())
+        .await?;
+    Ok(response.into_inner())
+}
+
+async fn say(channel: &Channel, echo: Echo) -> Result<Echo, Status> {
+    unary(channel, "/test.Echo/Say", echo).await
+}
+
+async fn check(channel: &Channel, service: &str) -> Result<i32, Status> {
+    let request = HealthCheckRequest {
+        service: service.to_string(),
+    };
+    let response: HealthCheckResponse = unary(channel, "/grpc.health.v1.Health/Check", request).await?;
+    Ok(response.status)
+}
+
+async fn next(responses: &mut Streaming<ServerReflectionResponse>) -> MessageResponse {
+    responses.message().await.unwrap().unwrap().message_response.unwrap()
+}
+
+fn echo(size: usize) -> Echo {
+    Echo {
+        payload: vec![b'x'; size],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_health_check_and_watch() {
+    let server = GrpcServer::new("127.0.0.1:0".parse().unwrap());
+    let reporter = server.health_reporter();
+    let running = start(server).await;
+    let channel = connect(running.addr).await;
+
+    assert_eq!(check(&channel, "").await.unwrap(), ServingStatus::Serving as i32);
+    assert_eq!(check(&channel, "test.Echo").await.unwrap(), ServingStatus::Serving as i32);
+    assert_eq!(check(&channel, "missing").await.unwrap_err().code(), Code::NotFound);
+
+    let mut grpc = tonic::client::Grpc::new(channel.clone());
+    grpc.ready().await.unwrap();
+    let request = Request::new(HealthCheckRequest {
+        service: "test.Echo".to_string(),
+    });
+    let path = PathAndQuery::from_static("/grpc.health.v1.Health/Watch");
+    let mut watch = grpc
+        .server_streaming::<_, HealthCheckResponse, _>(request, path, ProstCodec::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(watch.message().await.unwrap().unwrap().status, ServingStatus::Serving as i32);
+
+    reporter.set_not_serving("test.Echo");
+    assert_eq!(watch.message().await.unwrap().unwrap().status, ServingStatus::NotServing as i32);
+    assert_eq!(check(&channel, "test.Echo").await.unwrap(), ServingStatus::NotServing as i32);
+    reporter.set_serving("test.Echo");
+    assert_eq!(watch.message().await.unwrap().unwrap().status, ServingStatus::Serving as i32);
+
+    // Watchers learn about the shutdown before the server goes away
+    running.stop.send(()).unwrap();
+    assert_eq!(watch.message().await.unwrap().unwrap().status, ServingStatus::NotServing as i32);
+    running.task.await.unwrap().unwrap();
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct TestDescriptorSet {
+    #[prost(message, repeated, tag = "1")]
+    file: Vec<TestFile>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct TestFile {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    package: String,
+    #[prost(string, repeated, tag = "3")]
+    dependency: Vec<String>,
+    #[prost(message, repeated, tag = "4")]
+    message_type: Vec<TestNamed>,
+    #[prost(message, repeated, tag = "6")]
+    service: Vec<TestService>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct TestService {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(message, repeated, tag = "2")]
+    method: Vec<TestNamed>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct TestNamed {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+fn descriptor_set() -> Vec<u8> {
+    let named = |name: &str| TestNamed { name: name.to_string() };
+    TestDescriptorSet {
+        file: vec![
+            TestFile {
+                name: "test/echo.proto".to_string(),
+                package: "test".to_string(),
+                dependency: vec!["test/types.proto".to_string()],
+                service: vec![TestService {
+                    name: "Echo".to_string(),
+                    method: vec![named("Say")],
+                }],
+                ..Default::default()
+            },
+            TestFile {
+                name: "test/types.proto".to_string(),
+                package: "test".to_string(),
+                message_type: vec![named("Echo")],
+                ..Default::default()
+            },
+        ],
+    }
+    .encode_to_vec()
+}
+
+#[tokio::test]
+async fn test_reflection() {
+    assert!(GrpcServer::new("127.0.0.1:0".parse().unwrap()).with_reflection(b"\xff\xff").is_err());
+
+    let server = GrpcServer::new("127.0.0.1:0".parse().unwrap()).with_reflection(&descriptor_set()).unwrap();
+    let running = start(server).await;
+    let channel = connect(running.addr).await;
+
+    for version in ["v1", "v1alpha"] {
+        let requests = [
+            MessageRequest::ListServices(String::new()),
+            MessageRequest::FileContainingSymbol("test.Echo.Say".to_string()),
+            MessageRequest::FileContainingSymbol(".test.Missing".to_string()),
+            MessageRequest::FileByFilename("grpc/health/v1/health.proto".to_string()),
+        ];
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        for request in requests {
+            tx.send(ServerReflectionRequest {
+                host: String::new(),
+                message_request: Some(request),
+            })
+            .await
+            .unwrap();
+        }
+        drop(tx);
+
+        let mut grpc = tonic::client::Grpc::new(channel.clone());
+        grpc.ready().await.unwrap();
+        let path = PathAndQuery::try_from(format!("/grpc.reflection.{}.ServerReflection/ServerReflectionInfo", version)).unwrap();
+        let stream = tonic::codegen::tokio_stream::wrappers::ReceiverStream::new(rx);
+        let mut responses = grpc
+            .streaming::<_, _, ServerReflectionResponse, _>(Request::new(stream), path, ProstCodec::default())
+            .await
+            .unwrap()
+            .into_inner();
+
+        let MessageResponse::ListServicesResponse(list) = next(&mut responses).await else { panic!("expected services") };
+        let mut services: Vec<String> = list.service.into_iter().map(|s| s.name).collect();
+        services.sort();
+        assert_eq!(
+            services,
+            [
+                "grpc.health.v1.Health",
+                "grpc.reflection.v1.ServerReflection",
+                "grpc.reflection.v1alpha.ServerReflection",
+                "test.Echo"
+            ]
+        );
+
+        // The file and its dependencies
+        let MessageResponse::FileDescriptorResponse(files) = next(&mut responses).await else { panic!("expected files") };
+        let names: Vec<String> = files
+            .file_descriptor_proto
+            .iter()
+            .map(|raw| TestFile::decode(raw.as_slice()).unwrap().name)
+            .collect();
+        assert_eq!(names, ["test/echo.proto", "test/types.proto"]);
+
+        let MessageResponse::ErrorResponse(error) = next(&mut responses).await else { panic!("expected error") };
+        assert_eq!(error.error_code, Code::NotFound as i32);
+
+        let MessageResponse::FileDescriptorResponse(files) = next(&mut responses).await else { panic!("expected files") };
+        let health = TestFile::decode(files.file_descriptor_proto[0].as_slice()).unwrap();
+        assert_eq!(health.package, "grpc.health.v1");
+        assert_eq!(health.service[0].method.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), ["Check", "Watch"]);
+        assert!(responses.message().await.unwrap().is_none());
+    }
+    running.stop.send(()).unwrap();
+}
+
+#[tokio::test]
+async fn test_message_size_limits() {
+    let server = GrpcServer::new("127.0.0.1:0".parse().unwrap())
+        .max_decoding_message_size(1024)
+        .max_encoding_message_size(4096);
+    let running = start(server).await;
+    let channel = connect(running.addr).await;
+
+    assert_eq!(say(&channel, echo(1000)).await.unwrap().payload.len(), 1000);
+    let err = say(&channel, echo(2000)).await.unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted, "{}", err);
+
+    let err = say(&channel, Echo { repeat: 5, ..echo(1000) }).await.unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted, "{}", err);
+    // The connection is still usable
+    assert!(say(&channel, echo(10)).await.is_ok());
+    running.stop.send(()).unwrap();
+}
+
+#[tokio::test]
+async fn test_concurrency_limit() {
+    let server = GrpcServer::new("127.0.0.1:0".parse().unwrap()).max_concurrent_requests(1);
+    let running = start(server).await;
+    let channel = connect(running.addr).await;
+
+    let started = Instant::now();
+    let slow = || Echo { delay: 200, ..echo(1) };
+    let (a, b) = tokio::join!(say(&channel, slow()), say(&channel, slow()));
+    assert!(a.is_ok() && b.is_ok());
+    assert!(started.elapsed() >= Duration::from_millis(400));
+    running.stop.send(()).unwrap();
+}
+
+#[tokio::test]
+async fn test_graceful_shutdown() {
+    let server = GrpcServer::new("127.0.0.1:0".parse().unwrap()).with_shutdown_timeout(Duration::from_secs(5));
+    let running = start(server).await;
+    let channel = connect(running.addr).await;
+
+    // In-flight calls finish
+    let call = tokio::spawn({
+        let channel = channel.clone();
+        async move { say(&channel, Echo { delay: 300, ..echo(3) }).await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    running.stop.send(()).unwrap();
+    assert_eq!(call.await.unwrap().unwrap().payload, b"xxx");
+    running.task.await.unwrap().unwrap();
+
+    // Calls outliving the timeout do not hold the server
+    let server = GrpcServer::new("127.0.0.1:0".parse().unwrap()).with_shutdown_timeout(Duration::from_millis(200));
+    let running = start(server).await;
+    let channel = connect(running.addr).await;
+    let _call = tokio::spawn(async move { say(&channel, Echo { delay: 10_000, ..echo(1) }).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let started = Instant::now();
+    running.stop.send(()).unwrap();
+    running.task.await.unwrap().unwrap();
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+async fn say_pooled(channel: &PooledChannel, echo: Echo) -> Result<Echo, Status> {
+    unary(channel, "/test.Echo/Say", echo).await
+}
+
+async fn check_pooled(channel: &PooledChannel, service: &str) -> Result<i32, Status> {
+    let request = HealthCheckRequest {
+        service: service.to_string(),
+    };
+    let response: HealthCheckResponse = unary(channel, "/grpc.health.v1.Health/Check", request).await?;
+    Ok(response.status)
+}
+
+/// An address nothing listens on
+async fn dead_addr() -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap()
+}
+
+#[tokio::test]
+async fn test_pool_round_robin() {
+    let a = GrpcServer::new("127.0.0.1:0".parse().unwrap());
+    let reporter = a.health_reporter();
+    let (a, b) = (start(a).await, start(GrpcServer::new("127.0.0.1:0".parse().unwrap())).await);
+    reporter.set_not_serving("test.Echo");
+    let channel = GrpcClient::balanced([format!("http://{}", a.addr), format!("http://{}", b.addr)])
+        .connect_pool()
+        .await
+        .unwrap();
+    assert_eq!(channel.endpoints().len(), 2);
+
+    let mut statuses = Vec::new();
+    for _ in 0..4 {
+        statuses.push(check_pooled(&channel, "test.Echo").await.unwrap());
+    }
+    let serving = ServingStatus::Serving as i32;
+    assert_eq!(statuses.iter().filter(|s| **s == serving).count(), 2);
+    assert_ne!(statuses[0], statuses[1]);
+    assert_eq!(statuses[0], statuses[2]);
+    a.stop.send(()).unwrap();
+    b.stop.send(()).unwrap();
+}
+
+#[tokio::test]
+async fn test_pool_retries_on_another_endpoint() {
+    let running = start(GrpcServer::new("127.0.0.1:0".parse().unwrap())).await;
+    let endpoints = [format!("http://{}", dead_addr().await), format!("http://{}", running.addr)];
+
+    let channel = GrpcClient::balanced(endpoints.clone()).connect_pool().await.unwrap();
+    for _ in 0..6 {
+        assert_eq!(say_pooled(&channel, echo(3)).await.unwrap().payload.len(), 3);
+    }
+
+    let channel = GrpcClient::balanced(endpoints)
+        .retry_policy(RetryPolicy::none())
+        .connect_pool()
+        .await
+        .unwrap();
+    let first = say_pooled(&channel, echo(3)).await;
+    let second = say_pooled(&channel, echo(3)).await;
+    let err = first.and(second).unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable, "{}", err);
+    running.stop.send(()).unwrap();
+}
+
+#[tokio::test]
+async fn test_pool_method_deadline() {
+    let running = start(GrpcServer::new("127.0.0.1:0".parse().unwrap())).await;
+    let channel = GrpcClient::new(format!("http://{}", running.addr))
+        .method_policy("/test.Echo/Say", RetryPolicy::new(2).timeout(Duration::from_millis(100)))
+        .connect_pool()
+        .await
+        .unwrap();
+
+    let started = Instant::now();
+    let err = say_pooled(&channel, Echo { delay: 1000, ..echo(1) }).await.unwrap_err();
+    assert_eq!(err.code(), Code::DeadlineExceeded, "{}", err);
+    assert!(started.elapsed() < Duration::from_millis(800));
+    // Other methods keep the default policy
+    assert_eq!(check_pooled(&channel, "").await.unwrap(), ServingStatus::Serving as i32);
+    running.stop.send(()).unwrap();
+}
+
+#[tokio::test]
+async fn test_pool_registry_discovery() {
+    use rf_contrib_registry::{FileRegistry, ServiceHealth, ServiceInstance, ServiceRegistry};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    let (a, b) = (
+        start(GrpcServer::new("127.0.0.1:0".parse().unwrap())).await,
+        start(GrpcServer::new("127.0.0.1:0".parse().unwrap())).await,
+    );
+    let path = std::env::temp_dir().join(format!("rf-grpc-registry-{}.json", std::process::id()));
+    let registry = Arc::new(FileRegistry::new(path.to_str().unwrap()).unwrap());
+    let instance = |id: &str, address: SocketAddr, health: ServiceHealth| ServiceInstance {
+        id: id.to_string(),
+        name: "echo".to_string(),
+        address,
+        metadata: HashMap::new(),
+        health,
+    };
+    registry.register(&instance("a", a.addr, ServiceHealth::Healthy)).unwrap();
+    registry.register(&instance("dead", dead_addr().await, ServiceHealth::Unhealthy)).unwrap();
+
+    let channel = GrpcClient::discover(registry.clone(), "echo").connect_pool().await.unwrap();
+    assert_eq!(channel.endpoints(), vec![format!("http://{}", a.addr)]);
+    assert!(say_pooled(&channel, echo(1)).await.is_ok());
+
+    registry.register(&instance("b", b.addr, ServiceHealth::Healthy)).unwrap();
+    registry.deregister("a").unwrap();
+    channel.refresh().await.unwrap();
+    assert_eq!(channel.endpoints(), vec![format!("http://{}", b.addr)]);
+    assert!(say_pooled(&channel, echo(1)).await.is_ok());
+
+    let _ = std::fs::remove_file(path);
+    a.stop.send(()).unwrap();
+    b.stop.send(()).unwrap();
+}
+
+#[tokio::test]
+async fn test_pool_trace_propagation() {
+    use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+
+    opentelemetry::global::set_text_map_propagator(opentelemetry_sdk::propagation::TraceContextPropagator::new());
+    let running = start(GrpcServer::new("127.0.0.1:0".parse().unwrap())).await;
+    let channel = GrpcClient::new(format!("http://{}", running.addr)).connect_pool().await.unwrap();
+    let traced = Echo {
+        header: "traceparent".to_string(),
+        ..echo(1)
+    };
+
+    let span = SpanContext::new(
+        TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    let context = opentelemetry::Context::current().with_remote_span_context(span);
+    let response = {
+        let _guard = context.clone().attach();
+        say_pooled(&channel, traced.clone()).await.unwrap()
+    };
+    assert_eq!(
+        String::from_utf8(response.payload).unwrap(),
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+    );
+
+    let channel = GrpcClient::new(format!("http://{}", running.addr))
+        .with_trace_propagation(false)
+        .connect_pool()
+        .await
+        .unwrap();
+    let _guard = context.attach();
+    assert_eq!(say_pooled(&channel, traced).await.unwrap().payload, b"x");
+    running.stop.send(()).unwrap();
+}
@@ -0,0 +1,164 @@
+//! gRPC trace propagation tests
+
+use opentelemetry::trace::{FutureExt, SpanKind, Status as SpanStatus, TraceContextExt, Tracer, TracerProvider};
+use opentelemetry::{Context as OtelContext, Value};
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{SdkTracerProvider, SpanData, SpanProcessor};
+use rf_contrib_grpc::{extract_context, inject_context, GrpcClient, GrpcServer, TraceInterceptor};
+use rf_contrib_trace::TraceMiddleware;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::codegen::{http, BoxFuture, Service};
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::server::{Grpc, NamedService};
+use tonic::service::interceptor::InterceptedService;
+use tonic::{Code, Request, Response, Status};
+
+#[derive(Debug, Clone, Default)]
+struct Collector(Arc<Mutex<Vec<SpanData>>>);
+
+impl SpanProcessor for Collector {
+    fn on_start(&self, _span: &mut opentelemetry_sdk::trace::Span, _cx: &OtelContext) {}
+
+    fn on_end(&self, span: SpanData) {
+        self.0.lock().unwrap().push(span);
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+        Ok(())
+    }
+}
+
+fn attribute(span: &SpanData, key: &str) -> Option<Value> {
+    span.attributes.iter().find(|kv| kv.key.as_str() == key).map(|kv| kv.value.clone())
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Note {
+    #[prost(string, tag = "1")]
+    text: String,
+}
+
+/// `test.Notes` service answering `Get` with the trace id the handler runs under
+#[derive(Clone)]
+struct NoteService;
+
+impl NamedService for NoteService {
+    const NAME: &'static str = "test.Notes";
+}
+
+impl Service<http::Request<BoxBody>> for NoteService {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        Box::pin(async move {
+            let method = tower::service_fn(|request: Request<Note>| async move {
+                if request.get_ref().text == "fail" {
+                    return Err(Status::internal("boom"));
+                }
+                let text = OtelContext::current().span().span_context().trace_id().to_string();
+                Ok(Response::new(Note { text }))
+            });
+            Ok(Grpc::new(ProstCodec::default()).unary(method, req).await)
+        })
+    }
+}
+
+#[test]
+fn test_metadata_propagation() {
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    let provider = SdkTracerProvider::builder().build();
+    let span = provider.tracer("client").start("call");
+    let cx = OtelContext::new().with_span(span);
+
+    let mut metadata = MetadataMap::new();
+    inject_context(&cx, &mut metadata);
+    assert!(metadata.get("traceparent").is_some());
+    let extracted = extract_context(&metadata);
+    assert_eq!(extracted.span().span_context().trace_id(), cx.span().span_context().trace_id());
+    assert!(extracted.span().span_context().is_remote());
+
+    // OpenCensus-style clients send the binary form only
+    let bytes = rf_contrib_trace::inject_context_to_binary(&cx).unwrap();
+    let mut metadata = MetadataMap::new();
+    metadata.insert_bin("grpc-trace-bin", MetadataValue::from_bytes(&bytes));
+    assert_eq!(extract_context(&metadata).span().span_context().span_id(), cx.span().span_context().span_id());
+    assert!(!extract_context(&MetadataMap::new()).has_active_span());
+}
+
+#[tokio::test]
+async fn test_server_span_continues_client_trace() {
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    let collector = Collector::default();
+    let provider = SdkTracerProvider::builder().with_span_processor(collector.clone()).build();
+    opentelemetry::global::set_tracer_provider(provider.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = GrpcServer::new(addr).add_service(NoteService).with_tracing(TraceMiddleware::new("notes"));
+    tokio::spawn(server.serve_with_listener(listener, std::future::pending()));
+
+    let channel = GrpcClient::new(format!("http://{}", addr)).connect().await.unwrap();
+    let mut grpc = tonic::client::Grpc::new(InterceptedService::new(channel, TraceInterceptor));
+    let client_span = provider.tracer("client").start("client call");
+    let cx = OtelContext::new().with_span(client_span);
+    let path = PathAndQuery::from_static("/test.Notes/Get");
+
+    grpc.ready().await.unwrap();
+    let request = Request::new(Note { text: "hi".to_string() });
+    let response: Response<Note> = grpc
+        .unary(request, path.clone(), ProstCodec::default())
+        .with_context(cx.clone())
+        .await
+        .unwrap();
+    let trace_id = cx.span().span_context().trace_id();
+    assert_eq!(response.get_ref().text, trace_id.to_string());
+
+    grpc.ready().await.unwrap();
+    let request = Request::new(Note { text: "fail".to_string() });
+    let failed = grpc
+        .unary::<Note, Note, _>(request, path, ProstCodec::default())
+        .with_context(cx.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(failed.code(), Code::Internal);
+
+    let spans: Vec<SpanData> = collector
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|span| span.span_kind == SpanKind::Server)
+        .cloned()
+        .collect();
+    assert_eq!(spans.len(), 2);
+    for span in &spans {
+        assert_eq!(span.name, "test.Notes/Get");
+        assert_eq!(span.span_context.trace_id(), trace_id);
+        assert_eq!(span.parent_span_id, cx.span().span_context().span_id());
+        assert_eq!(attribute(span, "rpc.system"), Some(Value::from("grpc")));
+        assert_eq!(attribute(span, "rpc.service"), Some(Value::from("test.Notes")));
+        assert_eq!(attribute(span, "rpc.method"), Some(Value::from("Get")));
+        assert_eq!(attribute(span, "network.peer.address"), Some(Value::from("127.0.0.1")));
+    }
+    assert_eq!(attribute(&spans[0], "rpc.grpc.status_code"), Some(Value::I64(0)));
+    assert_eq!(spans[0].status, SpanStatus::Unset);
+    assert_eq!(attribute(&spans[1], "rpc.grpc.status_code"), Some(Value::I64(13)));
+    assert!(matches!(spans[1].status, SpanStatus::Error { .. }));
+}
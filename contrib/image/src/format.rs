@@ -0,0 +1,147 @@
+//! # format
+//!
+//! format 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Image format detection
+
+use rf_errors::{Result, RfError};
+
+/// Supported image formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    WebP,
+    Gif,
+}
+
+impl ImageFormat {
+    /// Detect the format from the leading bytes of a file
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageFormat::Jpeg)
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            Some(ImageFormat::WebP)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else {
+            None
+        }
+    }
+
+    /// Get the format from a file extension
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.trim_start_matches('.').to_lowercase().as_str() {
+            "jpg" | "jpeg" | "jpe" => Some(ImageFormat::Jpeg),
+            "png" => Some(ImageFormat::Png),
+            "webp" => Some(ImageFormat::WebP),
+            "gif" => Some(ImageFormat::Gif),
+            _ => None,
+        }
+    }
+
+    /// Get the format from a MIME type
+    pub fn from_mime_type(mime: &str) -> Option<Self> {
+        match mime.split(';').next().unwrap_or("").trim().to_lowercase().as_str() {
+            "image/jpeg" | "image/jpg" => Some(ImageFormat::Jpeg),
+            "image/png" => Some(ImageFormat::Png),
+            "image/webp" => Some(ImageFormat::WebP),
+            "image/gif" => Some(ImageFormat::Gif),
+            _ => None,
+        }
+    }
+
+    /// MIME type of the format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+            ImageFormat::WebP => "image/webp",
+            ImageFormat::Gif => "image/gif",
+        }
+    }
+
+    /// Preferred file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::WebP => "webp",
+            ImageFormat::Gif => "gif",
+        }
+    }
+
+    /// Whether pixel data of this format can be decoded
+    pub fn can_decode(&self) -> bool {
+        matches!(self, ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP)
+    }
+
+    /// Whether pixel data can be encoded to this format
+    pub fn can_encode(&self) -> bool {
+        matches!(self, ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP)
+    }
+}
+
+/// Read the image dimensions from the file header without decoding pixels
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| data.get(i..i + 2).map(|b| u16::from_be_bytes([b[0], b[1]]) as u32);
+    let le16 = |i: usize| data.get(i..i + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as u32);
+    let le24 = |i: usize| data.get(i..i + 3).map(|b| u32::from_le_bytes([b[0], b[1], b[2], 0]));
+
+    match ImageFormat::detect(data)? {
+        ImageFormat::Png => {
+            let ihdr = data.get(16..24)?;
+            Some((
+                u32::from_be_bytes([ihdr[0], ihdr[1], ihdr[2], ihdr[3]]),
+                u32::from_be_bytes([ihdr[4], ihdr[5], ihdr[6], ihdr[7]]),
+            ))
+        }
+        ImageFormat::Gif => Some((le16(6)?, le16(8)?)),
+        ImageFormat::Jpeg => {
+            let mut pos = 2;
+            while pos + 4 <= data.len() {
+                if data[pos] != 0xFF {
+                    return None;
+                }
+                let marker = data[pos + 1];
+                if marker == 0xFF {
+                    pos += 1;
+                    continue;
+                }
+                if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                    return Some((be16(pos + 7)?, be16(pos + 5)?));
+                }
+                pos += 2 + be16(pos + 2)? as usize;
+            }
+            None
+        }
+        ImageFormat::WebP => match data.get(12..16)? {
+            b"VP8X" => Some((le24(24)? + 1, le24(27)? + 1)),
+            b"VP8L" => {
+                let b = data.get(21..25)?;
+                let bits = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+                Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+            }
+            b"VP8 " => Some((le16(26)? & 0x3FFF, le16(28)? & 0x3FFF)),
+            _ => None,
+        },
+    }
+}
+
+pub(crate) fn unsupported(format: ImageFormat, operation: &str) -> RfError {
+    RfError::InvalidParameter(format!(
+        "{} {} is not supported; supported formats are JPEG, PNG and WebP",
+        format.mime_type(),
+        operation
+    ))
+}
+
+pub(crate) fn detect_or_err(data: &[u8]) -> Result<ImageFormat> {
+    ImageFormat::detect(data)
+        .ok_or_else(|| RfError::InvalidParameter("Unrecognized image format".to_string()))
+}
@@ -0,0 +1,467 @@
+//! # image
+//!
+//! image 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! RGBA image buffer and transformations
+//!
+//! Decoding and encoding go through the `image` crate: JPEG (baseline and
+//! progressive), PNG and WebP. WebP is encoded losslessly.
+
+use super::format::{detect_or_err, unsupported, ImageFormat};
+use ::image::codecs::jpeg::JpegEncoder;
+use ::image::codecs::png::PngEncoder;
+use ::image::codecs::webp::WebPEncoder;
+use ::image::{ExtendedColorType, ImageEncoder};
+use rf_errors::{Result, RfError};
+use std::io::{Read, Write};
+
+/// Resampling filter used by `Image::resize`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterType {
+    /// Nearest neighbour, fastest
+    Nearest,
+    /// Linear (triangle) filter, widened when downscaling
+    Triangle,
+}
+
+/// Watermark placement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+    /// Absolute offset of the watermark's top-left corner
+    At(u32, u32),
+}
+
+/// Encoding options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodeOptions {
+    /// JPEG quality (1-100); PNG and WebP are lossless
+    pub quality: u8,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self { quality: 85 }
+    }
+}
+
+/// 8-bit RGBA image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Create a transparent image
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Create an image from raw RGBA8 pixels
+    pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self> {
+        if pixels.len() != width as usize * height as usize * 4 {
+            return Err(RfError::InvalidParameter(format!(
+                "Expected {} bytes of RGBA data for {}x{}, got {}",
+                width as usize * height as usize * 4, width, height, pixels.len()
+            )));
+        }
+        Ok(Self { width, height, pixels })
+    }
+
+    /// Decode an image, detecting its format
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let format = detect_or_err(data)?;
+        let codec = codec_format(format).ok_or_else(|| unsupported(format, "decoding"))?;
+        let decoded = ::image::load_from_memory_with_format(data, codec)
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid {}: {}", format.mime_type(), e)))?
+            .into_rgba8();
+        Ok(Self {
+            width: decoded.width(),
+            height: decoded.height(),
+            pixels: decoded.into_raw(),
+        })
+    }
+
+    /// Decode an image from a reader
+    pub fn decode_from<R: Read>(mut reader: R) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(RfError::Io)?;
+        Self::decode(&data)
+    }
+
+    /// Encode the image with default options
+    pub fn encode(&self, format: ImageFormat) -> Result<Vec<u8>> {
+        self.encode_with(format, EncodeOptions::default())
+    }
+
+    /// Encode the image with options
+    pub fn encode_with(&self, format: ImageFormat, options: EncodeOptions) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.encode_to(&mut out, format, options)?;
+        Ok(out)
+    }
+
+    /// Encode the image into a writer
+    pub fn encode_to<W: Write>(&self, writer: W, format: ImageFormat, options: EncodeOptions) -> Result<()> {
+        let (width, height) = (self.width, self.height);
+        let result = match format {
+            ImageFormat::Png => PngEncoder::new(writer).write_image(&self.pixels, width, height, ExtendedColorType::Rgba8),
+            // JPEG has no alpha channel, composite onto white
+            ImageFormat::Jpeg => JpegEncoder::new_with_quality(writer, options.quality.clamp(1, 100))
+                .write_image(&self.to_rgb_on_white(), width, height, ExtendedColorType::Rgb8),
+            ImageFormat::WebP => WebPEncoder::new_lossless(writer).write_image(&self.pixels, width, height, ExtendedColorType::Rgba8),
+            other => return Err(unsupported(other, "encoding")),
+        };
+        result.map_err(|e| RfError::Internal(format!("Failed to encode {}: {}", format.mime_type(), e)))
+    }
+
+    fn to_rgb_on_white(&self) -> Vec<u8> {
+        let mut rgb = Vec::with_capacity(self.pixels.len() / 4 * 3);
+        for pixel in self.pixels.chunks_exact(4) {
+            let [r, g, b, _] = blend([255, 255, 255, 255], [pixel[0], pixel[1], pixel[2], pixel[3]], 1.0);
+            rgb.extend_from_slice(&[r, g, b]);
+        }
+        rgb
+    }
+
+    /// Width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Raw RGBA8 pixels, row-major
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Consume the image and return its pixels
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// Whether every pixel is fully opaque
+    pub fn is_opaque(&self) -> bool {
+        self.pixels.chunks_exact(4).all(|p| p[3] == 255)
+    }
+
+    /// Get a pixel
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = self.index(x, y);
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    /// Set a pixel
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        let i = self.index(x, y);
+        self.pixels[i..i + 4].copy_from_slice(&rgba);
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+
+    /// Crop a region; the region is clamped to the image bounds
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Self> {
+        if x >= self.width || y >= self.height {
+            return Err(RfError::InvalidParameter(format!(
+                "Crop origin ({}, {}) is outside the {}x{} image", x, y, self.width, self.height
+            )));
+        }
+        let width = width.min(self.width - x);
+        let height = height.min(self.height - y);
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for row in y..y + height {
+            let start = self.index(x, row);
+            pixels.extend_from_slice(&self.pixels[start..start + width as usize * 4]);
+        }
+        Ok(Self { width, height, pixels })
+    }
+
+    /// Resize to exact dimensions
+    pub fn resize(&self, width: u32, height: u32, filter: FilterType) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(RfError::InvalidParameter("Target size must be non-zero".to_string()));
+        }
+        if width == self.width && height == self.height {
+            return Ok(self.clone());
+        }
+        match filter {
+            FilterType::Nearest => Ok(self.resize_nearest(width, height)),
+            FilterType::Triangle => {
+                let horizontal = resample(self, width, true);
+                Ok(resample(&horizontal, height, false))
+            }
+        }
+    }
+
+    fn resize_nearest(&self, width: u32, height: u32) -> Self {
+        let mut out = Self::new(width, height);
+        for y in 0..height {
+            let sy = ((y as u64 * self.height as u64) / height as u64) as u32;
+            for x in 0..width {
+                let sx = ((x as u64 * self.width as u64) / width as u64) as u32;
+                out.set_pixel(x, y, self.pixel(sx, sy));
+            }
+        }
+        out
+    }
+
+    /// Resize to fit within the box, preserving the aspect ratio
+    pub fn resize_to_fit(&self, max_width: u32, max_height: u32, filter: FilterType) -> Result<Self> {
+        let (width, height) = fit_dimensions(self.width, self.height, max_width, max_height);
+        self.resize(width, height, filter)
+    }
+
+    /// Generate a thumbnail that fits within the box; never upscales
+    pub fn thumbnail(&self, max_width: u32, max_height: u32) -> Result<Self> {
+        if self.width <= max_width && self.height <= max_height {
+            return Ok(self.clone());
+        }
+        self.resize_to_fit(max_width, max_height, FilterType::Triangle)
+    }
+
+    /// Scale and crop to fill exactly `width` x `height` (center crop)
+    pub fn thumbnail_exact(&self, width: u32, height: u32) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(RfError::InvalidParameter("Target size must be non-zero".to_string()));
+        }
+        let scale = f64::max(width as f64 / self.width as f64, height as f64 / self.height as f64);
+        let scaled_w = ((self.width as f64 * scale).round() as u32).max(width);
+        let scaled_h = ((self.height as f64 * scale).round() as u32).max(height);
+        let scaled = self.resize(scaled_w, scaled_h, FilterType::Triangle)?;
+        scaled.crop((scaled_w - width) / 2, (scaled_h - height) / 2, width, height)
+    }
+
+    /// Rotate 90 degrees clockwise
+    pub fn rotate90(&self) -> Self {
+        let mut out = Self::new(self.height, self.width);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                out.set_pixel(self.height - 1 - y, x, self.pixel(x, y));
+            }
+        }
+        out
+    }
+
+    /// Rotate 180 degrees
+    pub fn rotate180(&self) -> Self {
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for pixel in self.pixels.chunks_exact(4).rev() {
+            pixels.extend_from_slice(pixel);
+        }
+        Self { width: self.width, height: self.height, pixels }
+    }
+
+    /// Rotate 270 degrees clockwise (90 counter-clockwise)
+    pub fn rotate270(&self) -> Self {
+        let mut out = Self::new(self.height, self.width);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                out.set_pixel(y, self.width - 1 - x, self.pixel(x, y));
+            }
+        }
+        out
+    }
+
+    /// Rotate by a multiple of 90 degrees (clockwise)
+    pub fn rotate(&self, degrees: i32) -> Result<Self> {
+        match degrees.rem_euclid(360) {
+            0 => Ok(self.clone()),
+            90 => Ok(self.rotate90()),
+            180 => Ok(self.rotate180()),
+            270 => Ok(self.rotate270()),
+            other => Err(RfError::InvalidParameter(format!(
+                "Rotation must be a multiple of 90 degrees, got {}", other
+            ))),
+        }
+    }
+
+    /// Mirror horizontally
+    pub fn flip_horizontal(&self) -> Self {
+        let mut out = self.clone();
+        let row_len = self.width as usize * 4;
+        for row in out.pixels.chunks_exact_mut(row_len) {
+            let pixels: Vec<[u8; 4]> = row.chunks_exact(4).rev().map(|p| [p[0], p[1], p[2], p[3]]).collect();
+            for (dst, src) in row.chunks_exact_mut(4).zip(pixels) {
+                dst.copy_from_slice(&src);
+            }
+        }
+        out
+    }
+
+    /// Mirror vertically
+    pub fn flip_vertical(&self) -> Self {
+        let row_len = self.width as usize * 4;
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for row in self.pixels.chunks_exact(row_len).rev() {
+            pixels.extend_from_slice(row);
+        }
+        Self { width: self.width, height: self.height, pixels }
+    }
+
+    /// Draw another image on top of this one
+    ///
+    /// `opacity` (0.0-1.0) scales the watermark's own alpha; `margin` is
+    /// applied for the corner positions.
+    pub fn watermark(&mut self, mark: &Image, position: Position, margin: u32, opacity: f32) {
+        let opacity = opacity.clamp(0.0, 1.0);
+        let (ox, oy) = match position {
+            Position::TopLeft => (margin as i64, margin as i64),
+            Position::TopRight => (self.width as i64 - mark.width as i64 - margin as i64, margin as i64),
+            Position::BottomLeft => (margin as i64, self.height as i64 - mark.height as i64 - margin as i64),
+            Position::BottomRight => (
+                self.width as i64 - mark.width as i64 - margin as i64,
+                self.height as i64 - mark.height as i64 - margin as i64,
+            ),
+            Position::Center => (
+                (self.width as i64 - mark.width as i64) / 2,
+                (self.height as i64 - mark.height as i64) / 2,
+            ),
+            Position::At(x, y) => (x as i64, y as i64),
+        };
+
+        for my in 0..mark.height {
+            let y = oy + my as i64;
+            if y < 0 || y >= self.height as i64 {
+                continue;
+            }
+            for mx in 0..mark.width {
+                let x = ox + mx as i64;
+                if x < 0 || x >= self.width as i64 {
+                    continue;
+                }
+                let src = mark.pixel(mx, my);
+                let dst = self.pixel(x as u32, y as u32);
+                self.set_pixel(x as u32, y as u32, blend(dst, src, opacity));
+            }
+        }
+    }
+}
+
+/// Codec of the `image` crate for a format it can read and write
+fn codec_format(format: ImageFormat) -> Option<::image::ImageFormat> {
+    match format {
+        ImageFormat::Jpeg => Some(::image::ImageFormat::Jpeg),
+        ImageFormat::Png => Some(::image::ImageFormat::Png),
+        ImageFormat::WebP => Some(::image::ImageFormat::WebP),
+        ImageFormat::Gif => None,
+    }
+}
+
+/// Source-over alpha compositing
+fn blend(dst: [u8; 4], src: [u8; 4], opacity: f32) -> [u8; 4] {
+    let sa = src[3] as f32 / 255.0 * opacity;
+    let da = dst[3] as f32 / 255.0;
+    let out_a = sa + da * (1.0 - sa);
+    if out_a <= 0.0 {
+        return [0, 0, 0, 0];
+    }
+    let mut out = [0u8; 4];
+    for c in 0..3 {
+        let value = (src[c] as f32 * sa + dst[c] as f32 * da * (1.0 - sa)) / out_a;
+        out[c] = value.round().clamp(0.0, 255.0) as u8;
+    }
+    out[3] = (out_a * 255.0).round().clamp(0.0, 255.0) as u8;
+    out
+}
+
+/// Compute dimensions that fit within a box while keeping the aspect ratio
+pub fn fit_dimensions(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    if width == 0 || height == 0 {
+        return (width, height);
+    }
+    let scale = f64::min(max_width as f64 / width as f64, max_height as f64 / height as f64);
+    let w = ((width as f64 * scale).round() as u32).max(1);
+    let h = ((height as f64 * scale).round() as u32).max(1);
+    (w, h)
+}
+
+/// Resample along one axis with a triangle filter (alpha-premultiplied)
+fn resample(image: &Image, new_len: u32, horizontal: bool) -> Image {
+    let (src_len, other_len) = if horizontal {
+        (image.width, image.height)
+    } else {
+        (image.height, image.width)
+    };
+    let (out_w, out_h) = if horizontal {
+        (new_len, image.height)
+    } else {
+        (image.width, new_len)
+    };
+    let mut out = Image::new(out_w, out_h);
+    if src_len == new_len {
+        return image.clone();
+    }
+
+    let ratio = src_len as f32 / new_len as f32;
+    let support = if ratio > 1.0 { ratio } else { 1.0 };
+
+    // Precompute the weights for every output position
+    let weights: Vec<(usize, Vec<f32>)> = (0..new_len)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * ratio;
+            let start = ((center - support).floor().max(0.0)) as usize;
+            let end = ((center + support).ceil() as usize).min(src_len as usize);
+            let mut w: Vec<f32> = (start..end)
+                .map(|j| {
+                    let distance = ((j as f32 + 0.5) - center).abs() / support;
+                    (1.0 - distance).max(0.0)
+                })
+                .collect();
+            let sum: f32 = w.iter().sum();
+            if sum > 0.0 {
+                w.iter_mut().for_each(|v| *v /= sum);
+            }
+            (start, w)
+        })
+        .collect();
+
+    for o in 0..other_len {
+        for (i, (start, w)) in weights.iter().enumerate() {
+            let mut acc = [0f32; 4];
+            for (k, weight) in w.iter().enumerate() {
+                let j = (start + k) as u32;
+                let p = if horizontal { image.pixel(j, o) } else { image.pixel(o, j) };
+                let a = p[3] as f32 * weight;
+                acc[0] += p[0] as f32 * a;
+                acc[1] += p[1] as f32 * a;
+                acc[2] += p[2] as f32 * a;
+                acc[3] += a;
+            }
+            let pixel = if acc[3] > 0.0 {
+                [
+                    (acc[0] / acc[3]).round().clamp(0.0, 255.0) as u8,
+                    (acc[1] / acc[3]).round().clamp(0.0, 255.0) as u8,
+                    (acc[2] / acc[3]).round().clamp(0.0, 255.0) as u8,
+                    acc[3].round().clamp(0.0, 255.0) as u8,
+                ]
+            } else {
+                [0, 0, 0, 0]
+            };
+            if horizontal {
+                out.set_pixel(i as u32, o, pixel);
+            } else {
+                out.set_pixel(o, i as u32, pixel);
+            }
+        }
+    }
+    out
+}
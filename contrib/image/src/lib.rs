@@ -0,0 +1,25 @@
+//! # lib
+//!
+//! lib 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Image processing module
+//!
+//! Image utilities:
+//! - RGBA8 `Image` with resize, crop, rotate, flip, thumbnail and watermark
+//! - Codecs from the `image` crate: JPEG, PNG and WebP (decode/encode)
+//! - Format detection for JPEG, PNG, WebP and GIF
+//! - EXIF/metadata stripping for JPEG, PNG and WebP without re-encoding
+//! - `ImagePipeline` for processing uploads
+
+pub mod format;
+pub mod image;
+pub mod metadata;
+pub mod pipeline;
+
+pub use format::*;
+pub use image::*;
+pub use metadata::*;
+pub use pipeline::*;
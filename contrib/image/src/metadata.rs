@@ -0,0 +1,169 @@
+//! # metadata
+//!
+//! metadata 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! EXIF and metadata stripping
+//!
+//! Removes EXIF, XMP, IPTC and text metadata without re-encoding pixels.
+//! Color profiles (ICC) are kept so colors render the same.
+
+use super::format::{detect_or_err, ImageFormat};
+use rf_errors::{Result, RfError};
+use std::io::{Cursor, Read, Write};
+
+/// Strip metadata from an in-memory image
+pub fn strip_metadata(data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    strip_metadata_to(data, &mut out)?;
+    Ok(out)
+}
+
+/// Strip metadata while copying from a reader to a writer
+///
+/// JPEG and PNG are processed chunk by chunk without buffering the whole
+/// file; WebP is buffered because the RIFF header carries the total size.
+/// GIF has no EXIF and is copied unchanged.
+pub fn strip_metadata_to<R: Read, W: Write>(mut reader: R, mut writer: W) -> Result<ImageFormat> {
+    let mut head = [0u8; 12];
+    let read = read_up_to(&mut reader, &mut head)?;
+    let format = detect_or_err(&head[..read])?;
+    let mut reader = Cursor::new(&head[..read]).chain(reader);
+
+    match format {
+        ImageFormat::Jpeg => strip_jpeg(&mut reader, &mut writer)?,
+        ImageFormat::Png => strip_png(&mut reader, &mut writer)?,
+        ImageFormat::WebP => {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            writer.write_all(&strip_webp(&data)?)?;
+        }
+        ImageFormat::Gif => {
+            std::io::copy(&mut reader, &mut writer)?;
+        }
+    }
+    Ok(format)
+}
+
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// JPEG markers that carry metadata: APP1 (EXIF/XMP), APP13 (IPTC), COM
+fn is_jpeg_metadata(marker: u8) -> bool {
+    matches!(marker, 0xE1 | 0xED | 0xFE)
+}
+
+fn strip_jpeg<R: Read, W: Write>(reader: &mut R, writer: &mut W) -> Result<()> {
+    let mut soi = [0u8; 2];
+    reader.read_exact(&mut soi)?;
+    writer.write_all(&soi)?;
+
+    let mut byte = [0u8; 1];
+    loop {
+        reader.read_exact(&mut byte)?;
+        if byte[0] != 0xFF {
+            return Err(RfError::InvalidParameter("Invalid JPEG: expected marker".to_string()));
+        }
+        // Skip fill bytes
+        let marker = loop {
+            reader.read_exact(&mut byte)?;
+            if byte[0] != 0xFF {
+                break byte[0];
+            }
+        };
+        match marker {
+            // Start of scan: everything after is entropy-coded data
+            0xDA | 0xD9 => {
+                writer.write_all(&[0xFF, marker])?;
+                std::io::copy(reader, writer)?;
+                return Ok(());
+            }
+            0x01 | 0xD0..=0xD7 => writer.write_all(&[0xFF, marker])?,
+            _ => {
+                let mut len = [0u8; 2];
+                reader.read_exact(&mut len)?;
+                let size = (u16::from_be_bytes(len) as usize).saturating_sub(2);
+                let mut payload = vec![0u8; size];
+                reader.read_exact(&mut payload)?;
+                if !is_jpeg_metadata(marker) {
+                    writer.write_all(&[0xFF, marker])?;
+                    writer.write_all(&len)?;
+                    writer.write_all(&payload)?;
+                }
+            }
+        }
+    }
+}
+
+/// PNG chunks that carry metadata
+fn is_png_metadata(kind: &[u8]) -> bool {
+    matches!(kind, b"tEXt" | b"zTXt" | b"iTXt" | b"eXIf" | b"tIME")
+}
+
+fn strip_png<R: Read, W: Write>(reader: &mut R, writer: &mut W) -> Result<()> {
+    let mut signature = [0u8; 8];
+    reader.read_exact(&mut signature)?;
+    writer.write_all(&signature)?;
+
+    let mut header = [0u8; 8];
+    loop {
+        if read_up_to(reader, &mut header)? < header.len() {
+            return Ok(());
+        }
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = &header[4..8];
+        let mut payload = vec![0u8; len + 4];
+        reader.read_exact(&mut payload)?;
+        if !is_png_metadata(kind) {
+            writer.write_all(&header)?;
+            writer.write_all(&payload)?;
+        }
+        if kind == b"IEND" {
+            return Ok(());
+        }
+    }
+}
+
+fn strip_webp(data: &[u8]) -> Result<Vec<u8>> {
+    let invalid = || RfError::InvalidParameter("Invalid WebP: truncated chunk".to_string());
+    let mut chunks = Vec::new();
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let kind = &data[pos..pos + 4];
+        let size = u32::from_le_bytes([data[pos + 4], data[pos + 5], data[pos + 6], data[pos + 7]]) as usize;
+        let padded = size + (size & 1);
+        let end = (pos + 8 + padded).min(data.len());
+        if pos + 8 + size > data.len() {
+            return Err(invalid());
+        }
+        if kind != b"EXIF" && kind != b"XMP " {
+            let mut chunk = data[pos..end].to_vec();
+            if kind == b"VP8X" && chunk.len() > 8 {
+                // Clear the EXIF (0x08) and XMP (0x04) flags
+                chunk[8] &= !0x0C;
+            }
+            chunks.push(chunk);
+        }
+        pos += 8 + padded;
+    }
+
+    let body: usize = chunks.iter().map(Vec::len).sum();
+    let mut out = Vec::with_capacity(12 + body);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&((4 + body) as u32).to_le_bytes());
+    out.extend_from_slice(b"WEBP");
+    for chunk in chunks {
+        out.extend_from_slice(&chunk);
+    }
+    Ok(out)
+}
@@ -0,0 +1,244 @@
+//! # pipeline
+//!
+//! pipeline 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Image processing pipeline for uploads
+
+use super::format::{detect_or_err, dimensions, unsupported, ImageFormat};
+use super::image::{EncodeOptions, FilterType, Image, Position};
+use super::metadata::strip_metadata_to;
+use rf_errors::{Result, RfError};
+use rf_net::http::UploadFile;
+use std::io::Write;
+use std::path::Path;
+
+/// A single pipeline step
+#[derive(Debug, Clone)]
+pub enum Operation {
+    /// Resize to exact dimensions
+    Resize(u32, u32),
+    /// Resize to fit within a box, preserving the aspect ratio
+    Fit(u32, u32),
+    /// Shrink to fit within a box; never upscales
+    Thumbnail(u32, u32),
+    /// Crop a region (x, y, width, height)
+    Crop(u32, u32, u32, u32),
+    /// Rotate clockwise by a multiple of 90 degrees
+    Rotate(i32),
+    FlipHorizontal,
+    FlipVertical,
+    /// Draw a watermark
+    Watermark {
+        image: Image,
+        position: Position,
+        margin: u32,
+        opacity: f32,
+    },
+}
+
+/// Dimensions and format of a processed image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageInfo {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Output of `ImagePipeline::process`
+#[derive(Debug, Clone)]
+pub struct ProcessedImage {
+    pub data: Vec<u8>,
+    pub info: ImageInfo,
+}
+
+/// Image processing pipeline
+///
+/// Steps run in the order they are added. A pipeline with no pixel steps
+/// and no format change only strips metadata, which works for every
+/// detected format (including WebP) and never re-encodes.
+#[derive(Debug, Clone)]
+pub struct ImagePipeline {
+    operations: Vec<Operation>,
+    strip_metadata: bool,
+    output: Option<ImageFormat>,
+    quality: u8,
+    max_pixels: Option<u64>,
+}
+
+impl ImagePipeline {
+    /// Create a pipeline that strips metadata by default
+    pub fn new() -> Self {
+        Self {
+            operations: Vec::new(),
+            strip_metadata: true,
+            output: None,
+            quality: EncodeOptions::default().quality,
+            max_pixels: None,
+        }
+    }
+
+    /// Enable or disable metadata stripping
+    pub fn strip_metadata(mut self, strip: bool) -> Self {
+        self.strip_metadata = strip;
+        self
+    }
+
+    /// Resize to exact dimensions
+    pub fn resize(mut self, width: u32, height: u32) -> Self {
+        self.operations.push(Operation::Resize(width, height));
+        self
+    }
+
+    /// Resize to fit within a box
+    pub fn fit(mut self, max_width: u32, max_height: u32) -> Self {
+        self.operations.push(Operation::Fit(max_width, max_height));
+        self
+    }
+
+    /// Generate a thumbnail that fits within a box
+    pub fn thumbnail(mut self, max_width: u32, max_height: u32) -> Self {
+        self.operations.push(Operation::Thumbnail(max_width, max_height));
+        self
+    }
+
+    /// Crop a region
+    pub fn crop(mut self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        self.operations.push(Operation::Crop(x, y, width, height));
+        self
+    }
+
+    /// Rotate clockwise by a multiple of 90 degrees
+    pub fn rotate(mut self, degrees: i32) -> Self {
+        self.operations.push(Operation::Rotate(degrees));
+        self
+    }
+
+    /// Mirror horizontally
+    pub fn flip_horizontal(mut self) -> Self {
+        self.operations.push(Operation::FlipHorizontal);
+        self
+    }
+
+    /// Mirror vertically
+    pub fn flip_vertical(mut self) -> Self {
+        self.operations.push(Operation::FlipVertical);
+        self
+    }
+
+    /// Draw a watermark with a 10px margin at full opacity
+    pub fn watermark(self, image: Image, position: Position) -> Self {
+        self.watermark_with(image, position, 10, 1.0)
+    }
+
+    /// Draw a watermark with a custom margin and opacity
+    pub fn watermark_with(mut self, image: Image, position: Position, margin: u32, opacity: f32) -> Self {
+        self.operations.push(Operation::Watermark { image, position, margin, opacity });
+        self
+    }
+
+    /// Convert to another format
+    pub fn convert(mut self, format: ImageFormat) -> Self {
+        self.output = Some(format);
+        self
+    }
+
+    /// Set the JPEG quality (1-100)
+    pub fn quality(mut self, quality: u8) -> Self {
+        self.quality = quality.clamp(1, 100);
+        self
+    }
+
+    /// Reject images larger than this many pixels before decoding
+    pub fn max_pixels(mut self, max_pixels: u64) -> Self {
+        self.max_pixels = Some(max_pixels);
+        self
+    }
+
+    /// Process an image in memory
+    pub fn process(&self, data: &[u8]) -> Result<ProcessedImage> {
+        let mut out = Vec::new();
+        let info = self.process_to(data, &mut out)?;
+        Ok(ProcessedImage { data: out, info })
+    }
+
+    /// Process an image and write the result to a writer
+    pub fn process_to<W: Write>(&self, data: &[u8], mut writer: W) -> Result<ImageInfo> {
+        let input = detect_or_err(data)?;
+        let output = self.output.unwrap_or(input);
+        let (width, height) = dimensions(data)
+            .ok_or_else(|| RfError::InvalidParameter("Unable to read image dimensions".to_string()))?;
+        if let Some(max) = self.max_pixels {
+            if width as u64 * height as u64 > max {
+                return Err(RfError::InvalidParameter(format!(
+                    "Image {}x{} exceeds the limit of {} pixels", width, height, max
+                )));
+            }
+        }
+
+        if self.operations.is_empty() && output == input {
+            if self.strip_metadata {
+                strip_metadata_to(data, &mut writer)?;
+            } else {
+                writer.write_all(data)?;
+            }
+            return Ok(ImageInfo { format: output, width, height });
+        }
+
+        if !output.can_encode() {
+            return Err(unsupported(output, "encoding"));
+        }
+        let mut image = Image::decode(data)?;
+        for operation in &self.operations {
+            image = apply(image, operation)?;
+        }
+        // Re-encoding never carries metadata over
+        image.encode_to(&mut writer, output, EncodeOptions { quality: self.quality })?;
+        Ok(ImageInfo { format: output, width: image.width(), height: image.height() })
+    }
+
+    /// Process an uploaded file in place
+    ///
+    /// Updates the data, size, content type and file extension.
+    pub fn process_upload(&self, file: &mut UploadFile) -> Result<ImageInfo> {
+        let processed = self.process(&file.data)?;
+        let info = processed.info;
+        file.size = processed.data.len() as u64;
+        file.data = processed.data;
+        file.content_type = Some(info.format.mime_type().to_string());
+
+        let current = file.extension().and_then(ImageFormat::from_extension);
+        if current != Some(info.format) && !file.filename.is_empty() {
+            file.filename = Path::new(&file.filename)
+                .with_extension(info.format.extension())
+                .to_string_lossy()
+                .into_owned();
+        }
+        Ok(info)
+    }
+}
+
+impl Default for ImagePipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn apply(image: Image, operation: &Operation) -> Result<Image> {
+    match operation {
+        Operation::Resize(width, height) => image.resize(*width, *height, FilterType::Triangle),
+        Operation::Fit(width, height) => image.resize_to_fit(*width, *height, FilterType::Triangle),
+        Operation::Thumbnail(width, height) => image.thumbnail(*width, *height),
+        Operation::Crop(x, y, width, height) => image.crop(*x, *y, *width, *height),
+        Operation::Rotate(degrees) => image.rotate(*degrees),
+        Operation::FlipHorizontal => Ok(image.flip_horizontal()),
+        Operation::FlipVertical => Ok(image.flip_vertical()),
+        Operation::Watermark { image: mark, position, margin, opacity } => {
+            let mut image = image;
+            image.watermark(mark, *position, *margin, *opacity);
+            Ok(image)
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Image module tests
+
+use rf_contrib_image::*;
+use rf_net::http::UploadFile;
+
+fn gradient(width: u32, height: u32) -> Image {
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            pixels.extend_from_slice(&[(x * 255 / width.max(2)) as u8, (y * 255 / height.max(2)) as u8, 128, 255]);
+        }
+    }
+    Image::from_rgba(width, height, pixels).unwrap()
+}
+
+fn max_diff(a: &Image, b: &Image) -> u8 {
+    a.pixels().iter().zip(b.pixels()).map(|(x, y)| x.abs_diff(*y)).max().unwrap_or(0)
+}
+
+/// JPEG with an EXIF APP1 segment and a comment inserted after SOI
+fn jpeg_with_exif(image: &Image) -> Vec<u8> {
+    let jpeg = image.encode(ImageFormat::Jpeg).unwrap();
+    let mut out = vec![0xFF, 0xD8];
+    out.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x0C]);
+    out.extend_from_slice(b"Exif\0\0GPS!");
+    out.extend_from_slice(&[0xFF, 0xFE, 0x00, 0x07]);
+    out.extend_from_slice(b"hello");
+    out.extend_from_slice(&jpeg[2..]);
+    out
+}
+
+#[test]
+fn test_format_detection() {
+    let png = gradient(4, 4).encode(ImageFormat::Png).unwrap();
+    assert_eq!(ImageFormat::detect(&png), Some(ImageFormat::Png));
+    assert_eq!(ImageFormat::detect(b"GIF89a...."), Some(ImageFormat::Gif));
+    assert_eq!(ImageFormat::from_extension(".JPG"), Some(ImageFormat::Jpeg));
+    assert_eq!(ImageFormat::from_mime_type("image/webp"), Some(ImageFormat::WebP));
+    assert_eq!(dimensions(&png), Some((4, 4)));
+    assert!(ImageFormat::detect(b"plain text").is_none());
+}
+
+#[test]
+fn test_png_round_trip() {
+    let mut image = gradient(13, 7);
+    image.set_pixel(3, 3, [10, 20, 30, 40]);
+    let data = image.encode(ImageFormat::Png).unwrap();
+    let decoded = Image::decode(&data).unwrap();
+    assert_eq!(decoded, image);
+}
+
+#[test]
+fn test_jpeg_round_trip() {
+    let image = gradient(37, 21);
+    let data = image.encode_with(ImageFormat::Jpeg, EncodeOptions { quality: 95 }).unwrap();
+    assert_eq!(dimensions(&data), Some((37, 21)));
+    let decoded = Image::decode(&data).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (37, 21));
+    assert!(max_diff(&decoded, &image) < 24);
+}
+
+#[test]
+fn test_progressive_jpeg_decode() {
+    let image = gradient(40, 24);
+    let mut data = Vec::new();
+    let mut encoder = jpeg_encoder::Encoder::new(&mut data, 95);
+    encoder.set_progressive(true);
+    encoder.encode(image.pixels(), 40, 24, jpeg_encoder::ColorType::Rgba).unwrap();
+    // SOF2 marks a progressive frame
+    assert!(data.windows(2).any(|w| w == [0xFF, 0xC2]));
+
+    assert_eq!(dimensions(&data), Some((40, 24)));
+    let decoded = Image::decode(&data).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (40, 24));
+    assert!(max_diff(&decoded, &image) < 24);
+}
+
+#[test]
+fn test_webp_round_trip() {
+    let mut image = gradient(19, 11);
+    image.set_pixel(2, 5, [10, 20, 30, 40]);
+    let data = image.encode(ImageFormat::WebP).unwrap();
+    assert_eq!(ImageFormat::detect(&data), Some(ImageFormat::WebP));
+    assert_eq!(dimensions(&data), Some((19, 11)));
+    // Lossless, alpha included
+    assert_eq!(Image::decode(&data).unwrap(), image);
+    assert!(ImageFormat::WebP.can_decode() && ImageFormat::WebP.can_encode());
+}
+
+#[test]
+fn test_transforms() {
+    let image = gradient(6, 4);
+    let rotated = image.rotate90();
+    assert_eq!((rotated.width(), rotated.height()), (4, 6));
+    assert_eq!(rotated.pixel(3, 0), image.pixel(0, 0));
+    assert_eq!(image.rotate(-90).unwrap(), image.rotate270());
+    assert_eq!(image.rotate180().rotate180(), image);
+    assert_eq!(image.flip_horizontal().pixel(0, 0), image.pixel(5, 0));
+    assert!(image.rotate(45).is_err());
+
+    let cropped = image.crop(2, 1, 10, 2).unwrap();
+    assert_eq!((cropped.width(), cropped.height()), (4, 2));
+    assert_eq!(cropped.pixel(0, 0), image.pixel(2, 1));
+
+    let resized = image.resize(3, 2, FilterType::Triangle).unwrap();
+    assert_eq!((resized.width(), resized.height()), (3, 2));
+
+    let thumb = gradient(400, 200).thumbnail(100, 100).unwrap();
+    assert_eq!((thumb.width(), thumb.height()), (100, 50));
+    let exact = gradient(400, 200).thumbnail_exact(64, 64).unwrap();
+    assert_eq!((exact.width(), exact.height()), (64, 64));
+}
+
+#[test]
+fn test_watermark() {
+    let mut image = Image::from_rgba(10, 10, vec![255; 400]).unwrap();
+    let mark = Image::from_rgba(2, 2, [0, 0, 0, 255].repeat(4)).unwrap();
+    image.watermark(&mark, Position::BottomRight, 1, 0.5);
+    assert_eq!(image.pixel(8, 8)[0], 128);
+    assert_eq!(image.pixel(9, 9), [255, 255, 255, 255]);
+    assert_eq!(image.pixel(0, 0), [255, 255, 255, 255]);
+}
+
+#[test]
+fn test_strip_jpeg_metadata() {
+    let data = jpeg_with_exif(&gradient(16, 16));
+    let stripped = strip_metadata(&data).unwrap();
+    assert!(stripped.len() < data.len());
+    assert!(!stripped.windows(4).any(|w| w == b"Exif"));
+    assert!(!stripped.windows(5).any(|w| w == b"hello"));
+    // Pixel data is preserved bit-for-bit
+    assert_eq!(Image::decode(&stripped).unwrap(), Image::decode(&data).unwrap());
+}
+
+#[test]
+fn test_strip_webp_metadata() {
+    let mut body = Vec::new();
+    body.extend_from_slice(b"VP8X");
+    body.extend_from_slice(&10u32.to_le_bytes());
+    body.extend_from_slice(&[0x08, 0, 0, 0, 9, 0, 0, 4, 0, 0]);
+    body.extend_from_slice(b"EXIF");
+    body.extend_from_slice(&3u32.to_le_bytes());
+    body.extend_from_slice(&[1, 2, 3, 0]);
+    let mut data = b"RIFF".to_vec();
+    data.extend_from_slice(&((4 + body.len()) as u32).to_le_bytes());
+    data.extend_from_slice(b"WEBP");
+    data.extend_from_slice(&body);
+
+    let stripped = strip_metadata(&data).unwrap();
+    assert_eq!(stripped.len(), 12 + 18);
+    assert_eq!(stripped[20], 0);
+    assert_eq!(u32::from_le_bytes([stripped[4], stripped[5], stripped[6], stripped[7]]), 4 + 18);
+    assert_eq!(dimensions(&stripped), Some((10, 5)));
+}
+
+#[test]
+fn test_pipeline_upload() {
+    let png = gradient(300, 150).encode(ImageFormat::Png).unwrap();
+    let mut file = UploadFile::new("avatar".to_string(), "me.png".to_string(), Some("image/png".to_string()), png);
+
+    let info = ImagePipeline::new()
+        .thumbnail(100, 100)
+        .convert(ImageFormat::Jpeg)
+        .quality(80)
+        .process_upload(&mut file)
+        .unwrap();
+
+    assert_eq!(info, ImageInfo { format: ImageFormat::Jpeg, width: 100, height: 50 });
+    assert_eq!(file.filename, "me.jpg");
+    assert_eq!(file.content_type.as_deref(), Some("image/jpeg"));
+    assert_eq!(file.size, file.data.len() as u64);
+    assert_eq!(ImageFormat::detect(&file.data), Some(ImageFormat::Jpeg));
+}
+
+#[test]
+fn test_pipeline_strip_only_and_limits() {
+    let data = jpeg_with_exif(&gradient(16, 16));
+    let processed = ImagePipeline::new().process(&data).unwrap();
+    assert_eq!(processed.data, strip_metadata(&data).unwrap());
+    assert_eq!((processed.info.width, processed.info.height), (16, 16));
+
+    assert!(ImagePipeline::new().max_pixels(100).process(&data).is_err());
+    assert!(ImagePipeline::new().convert(ImageFormat::Gif).process(&data).is_err());
+}
+
+#[test]
+fn test_pipeline_converts_to_and_from_webp() {
+    let jpeg = jpeg_with_exif(&gradient(64, 32));
+    let webp = ImagePipeline::new().thumbnail(32, 32).convert(ImageFormat::WebP).process(&jpeg).unwrap();
+    assert_eq!(webp.info, ImageInfo { format: ImageFormat::WebP, width: 32, height: 16 });
+    assert_eq!(ImageFormat::detect(&webp.data), Some(ImageFormat::WebP));
+
+    let png = ImagePipeline::new().rotate(90).convert(ImageFormat::Png).process(&webp.data).unwrap();
+    assert_eq!(png.info, ImageInfo { format: ImageFormat::Png, width: 16, height: 32 });
+    let decoded = Image::decode(&png.data).unwrap();
+    assert_eq!(decoded, Image::decode(&webp.data).unwrap().rotate90());
+}
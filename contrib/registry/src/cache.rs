@@ -0,0 +1,160 @@
+//! # cache
+//!
+//! cache 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Local cache of discovered instances
+//!
+//! `CachedRegistry` wraps a registry and answers `discover` from memory. The
+//! first lookup of a service asks the backend and subscribes to its `watch`,
+//! which keeps the entry current from then on. While the backend is
+//! unreachable the last known instances are served and the watch retries in
+//! the background with backoff.
+//!
+//! Its own `watch` shares that subscription, so a load balancer and the
+//! handlers discovering the same service do not each poll the backend.
+//! Registration calls go straight to the wrapped registry.
+//!
+//! ```ignore
+//! let registry = CachedRegistry::new(NacosRegistry::new("http://127.0.0.1:8848", "public"));
+//! balancer.watch(&registry, "orders")?;
+//! let instances = registry.discover("orders")?; // from memory
+//! ```
+
+use super::{ServiceInstance, ServiceRegistry};
+use rf_errors::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+type Callback = Arc<dyn Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync>;
+
+struct Entry {
+    instances: Vec<ServiceInstance>,
+    subscribers: Vec<Callback>,
+}
+
+type Entries = Arc<RwLock<HashMap<String, Entry>>>;
+
+/// Registry answering discovery from a local cache kept current by `watch`
+pub struct CachedRegistry<R> {
+    inner: Arc<R>,
+    entries: Entries,
+}
+
+impl<R: ServiceRegistry + 'static> CachedRegistry<R> {
+    /// Cache lookups of `registry`
+    pub fn new(registry: R) -> Self {
+        Self::shared(Arc::new(registry))
+    }
+
+    /// Cache lookups of a registry used elsewhere too
+    pub fn shared(registry: Arc<R>) -> Self {
+        Self {
+            inner: registry,
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// The wrapped registry
+    pub fn inner(&self) -> &Arc<R> {
+        &self.inner
+    }
+
+    /// Cached instances of `service_name`, without asking the backend
+    pub fn cached(&self, service_name: &str) -> Option<Vec<ServiceInstance>> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries.get(service_name).map(|entry| entry.instances.clone())
+    }
+
+    /// Cached instances, looking the service up and watching it on first use
+    fn subscribe(&self, service_name: &str) -> Result<Vec<ServiceInstance>> {
+        if let Some(instances) = self.cached(service_name) {
+            return Ok(instances);
+        }
+        let instances = self.inner.discover(service_name)?;
+        {
+            let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+            if let Some(entry) = entries.get(service_name) {
+                // Another caller subscribed meanwhile
+                return Ok(entry.instances.clone());
+            }
+            entries.insert(
+                service_name.to_string(),
+                Entry {
+                    instances: instances.clone(),
+                    subscribers: Vec::new(),
+                },
+            );
+        }
+
+        // The watch outlives the cache, so it only holds a weak reference
+        let weak = Arc::downgrade(&self.entries);
+        let name = service_name.to_string();
+        let watched = self.inner.watch(service_name, move |instances| {
+            if let Some(entries) = weak.upgrade() {
+                update(&entries, &name, instances);
+            }
+            Ok(())
+        });
+        if let Err(e) = watched {
+            // Without updates the entry would go stale, look it up again next time
+            tracing::warn!("Not caching {}, watching it failed: {}", service_name, e);
+            self.entries.write().unwrap_or_else(|e| e.into_inner()).remove(service_name);
+        }
+        Ok(instances)
+    }
+}
+
+/// Store `instances` and pass them to the subscribers of `service_name`
+fn update(entries: &RwLock<HashMap<String, Entry>>, service_name: &str, instances: Vec<ServiceInstance>) {
+    let subscribers = {
+        let mut entries = entries.write().unwrap_or_else(|e| e.into_inner());
+        let Some(entry) = entries.get_mut(service_name) else {
+            return;
+        };
+        entry.instances = instances.clone();
+        entry.subscribers.clone()
+    };
+    for subscriber in subscribers {
+        if let Err(e) = subscriber(instances.clone()) {
+            tracing::warn!("Watch callback for {} failed: {}", service_name, e);
+        }
+    }
+}
+
+impl<R: ServiceRegistry + 'static> ServiceRegistry for CachedRegistry<R> {
+    fn register(&self, instance: &ServiceInstance) -> Result<()> {
+        self.inner.register(instance)
+    }
+
+    fn deregister(&self, service_id: &str) -> Result<()> {
+        self.inner.deregister(service_id)
+    }
+
+    fn heartbeat(&self, instance: &ServiceInstance) -> Result<()> {
+        self.inner.heartbeat(instance)
+    }
+
+    fn discover(&self, service_name: &str) -> Result<Vec<ServiceInstance>> {
+        self.subscribe(service_name)
+    }
+
+    fn list_services(&self) -> Result<Vec<String>> {
+        self.inner.list_services()
+    }
+
+    /// `callback` gets the cached instances right away, then every update
+    fn watch<F>(&self, service_name: &str, callback: F) -> Result<()>
+    where
+        F: Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync + 'static,
+    {
+        let instances = self.subscribe(service_name)?;
+        let callback: Callback = Arc::new(callback);
+        if let Some(entry) = self.entries.write().unwrap_or_else(|e| e.into_inner()).get_mut(service_name) {
+            entry.subscribers.push(callback.clone());
+        }
+        callback(instances)
+    }
+}
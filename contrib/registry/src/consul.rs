@@ -6,17 +6,25 @@
 //! @date 2026-01-06
 
 //! Consul service registry adapter
+//!
+//! Instances are checked by Consul over HTTP at `/health`, or with `ttl` by
+//! a TTL check that heartbeats pass. `watch` polls the passing instances.
 
+use super::poll::{watch_by_polling, DEFAULT_WATCH_INTERVAL};
 use super::{ServiceInstance, ServiceRegistry, ServiceHealth};
 use rf_errors::{Result, RfError};
-use reqwest::Client;
+use reqwest::{Client, Response, StatusCode};
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::time::Duration;
 
 /// Consul service registry
+#[derive(Clone)]
 pub struct ConsulRegistry {
     client: Client,
     base_url: String,
+    ttl: Option<Duration>,
+    watch_interval: Duration,
 }
 
 impl ConsulRegistry {
@@ -25,8 +33,34 @@ impl ConsulRegistry {
         Self {
             client: Client::new(),
             base_url: base_url.to_string(),
+            ttl: None,
+            watch_interval: DEFAULT_WATCH_INTERVAL,
         }
     }
+
+    /// Check instances with a TTL check instead of HTTP
+    ///
+    /// An instance turns critical when no heartbeat passes the check within
+    /// `ttl`, and Consul removes it after four TTLs, at least a minute.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Time between two lookups of `watch`
+    pub fn watch_interval(mut self, interval: Duration) -> Self {
+        self.watch_interval = interval;
+        self
+    }
+}
+
+/// Fail on a non-2xx answer
+fn check_status(response: Response, action: &str) -> Result<Response> {
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        Err(RfError::Internal(format!("Consul {} failed: {}", action, response.status())))
+    }
 }
 
 impl ServiceRegistry for ConsulRegistry {
@@ -40,19 +74,49 @@ impl ServiceRegistry for ConsulRegistry {
             "Port": instance.address.port(),
             "Tags": instance.metadata.keys().collect::<Vec<_>>(),
             "Meta": instance.metadata,
-            "Check": {
-                "HTTP": format!("http://{}:{}/health", instance.address.ip(), instance.address.port()),
-                "Interval": "10s"
+            "Check": match self.ttl {
+                Some(ttl) => serde_json::json!({
+                    "CheckID": format!("service:{}", instance.id),
+                    "TTL": format!("{}s", ttl.as_secs().max(1)),
+                    // Discoverable right away, not only after the first heartbeat
+                    "Status": "passing",
+                    "DeregisterCriticalServiceAfter": format!("{}s", (ttl.as_secs() * 4).max(60)),
+                }),
+                None => serde_json::json!({
+                    "HTTP": format!("http://{}:{}/health", instance.address.ip(), instance.address.port()),
+                    "Interval": "10s"
+                }),
             }
         });
         
-        let _response = futures::executor::block_on(
+        let response = futures::executor::block_on(
             self.client.put(&url).json(&payload).send()
         )
         .map_err(|e| RfError::Internal(format!("Consul register failed: {}", e)))?;
+        check_status(response, "register")?;
         
         Ok(())
     }
+
+    fn heartbeat(&self, instance: &ServiceInstance) -> Result<()> {
+        let response = match self.ttl {
+            Some(_) => {
+                let url = format!("{}/v1/agent/check/pass/service:{}", self.base_url, instance.id);
+                futures::executor::block_on(self.client.put(&url).send())
+            }
+            // Consul checks the instance itself, only make sure it is still known
+            None => {
+                let url = format!("{}/v1/agent/service/{}", self.base_url, instance.id);
+                futures::executor::block_on(self.client.get(&url).send())
+            }
+        }
+        .map_err(|e| RfError::Internal(format!("Consul heartbeat failed: {}", e)))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(RfError::NotFound(format!("Consul no longer knows instance {}", instance.id)));
+        }
+        check_status(response, "heartbeat")?;
+        Ok(())
+    }
     
     fn deregister(&self, service_id: &str) -> Result<()> {
         let url = format!("{}/v1/agent/service/deregister/{}", self.base_url, service_id);
@@ -146,13 +210,12 @@ impl ServiceRegistry for ConsulRegistry {
         Ok(Vec::new())
     }
     
-    fn watch<F>(&self, _service_name: &str, _callback: F) -> Result<()>
+    fn watch<F>(&self, service_name: &str, callback: F) -> Result<()>
     where
         F: Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync + 'static,
     {
-        // Consul watch implementation would use blocking query with index
-        // This is a placeholder
-        Ok(())
+        let (registry, name) = (self.clone(), service_name.to_string());
+        watch_by_polling(service_name, self.watch_interval, move || registry.discover(&name), callback)
     }
 }
 
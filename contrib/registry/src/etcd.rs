@@ -6,16 +6,38 @@
 //! @date 2026-01-06
 
 //! etcd service registry adapter
+//!
+//! Instances are stored as JSON under `{prefix}/{name}/{id}` through the v3
+//! JSON gateway. With `ttl` each key is bound to a lease that heartbeats
+//! keep alive, so instances of crashed processes disappear once the lease
+//! expires. `watch` polls the keys of the service.
 
+use super::poll::{watch_by_polling, DEFAULT_WATCH_INTERVAL};
 use super::{ServiceInstance, ServiceRegistry};
+use base64::{engine::general_purpose, Engine as _};
 use rf_errors::{Result, RfError};
 use reqwest::Client;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Key and lease of an instance registered by this process
+#[derive(Debug, Clone)]
+struct Registered {
+    key: String,
+    lease: Option<String>,
+}
 
 /// etcd service registry
+#[derive(Clone)]
 pub struct EtcdRegistry {
     client: Client,
     base_url: String,
     prefix: String,
+    ttl: Option<Duration>,
+    watch_interval: Duration,
+    registered: Arc<Mutex<HashMap<String, Registered>>>,
 }
 
 impl EtcdRegistry {
@@ -24,9 +46,90 @@ impl EtcdRegistry {
         Self {
             client: Client::new(),
             base_url: base_url.to_string(),
-            prefix: prefix.to_string(),
+            prefix: prefix.trim_end_matches('/').to_string(),
+            ttl: None,
+            watch_interval: DEFAULT_WATCH_INTERVAL,
+            registered: Arc::new(Mutex::new(HashMap::new())),
         }
     }
+
+    /// Bind instances to a lease of `ttl`, renewed by heartbeats
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Time between two lookups of `watch`
+    pub fn watch_interval(mut self, interval: Duration) -> Self {
+        self.watch_interval = interval;
+        self
+    }
+
+    /// POST `payload` to a gateway endpoint, failing on a non-2xx answer
+    fn post(&self, path: &str, payload: Value, action: &str) -> Result<Value> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = futures::executor::block_on(self.client.post(&url).json(&payload).send())
+            .map_err(|e| RfError::Internal(format!("etcd {} failed: {}", action, e)))?;
+        if !response.status().is_success() {
+            return Err(RfError::Internal(format!("etcd {} failed: {}", action, response.status())));
+        }
+        futures::executor::block_on(response.json())
+            .map_err(|e| RfError::Internal(format!("Failed to parse etcd response: {}", e)))
+    }
+
+    /// Keys and values under `prefix`
+    fn range(&self, prefix: &str, action: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let json = self.post(
+            "/v3/kv/range",
+            serde_json::json!({
+                "key": general_purpose::STANDARD.encode(prefix.as_bytes()),
+                "range_end": general_purpose::STANDARD.encode(range_end(prefix.as_bytes())),
+            }),
+            action,
+        )?;
+        let decode = |field: &Value| field.as_str().and_then(|s| general_purpose::STANDARD.decode(s).ok());
+        Ok(json
+            .get("kvs")
+            .and_then(|kvs| kvs.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|kv| {
+                let key = String::from_utf8(decode(&kv["key"])?).ok()?;
+                Some((key, decode(&kv["value"]).unwrap_or_default()))
+            })
+            .collect())
+    }
+
+    fn delete(&self, registered: &Registered) -> Result<()> {
+        match &registered.lease {
+            // Revoking the lease deletes its key too
+            Some(lease) => self.post("/v3/lease/revoke", serde_json::json!({ "ID": lease }), "deregister")?,
+            None => self.post(
+                "/v3/kv/deleterange",
+                serde_json::json!({ "key": general_purpose::STANDARD.encode(registered.key.as_bytes()) }),
+                "deregister",
+            )?,
+        };
+        Ok(())
+    }
+}
+
+/// End of the key range starting with `prefix`: the prefix with its last byte incremented
+fn range_end(prefix: &[u8]) -> Vec<u8> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < 0xff {
+            end.push(last + 1);
+            return end;
+        }
+    }
+    // All bytes 0xff, range to the end of the keyspace
+    vec![0]
+}
+
+/// An int64 of the JSON gateway, which encodes them as strings
+fn int64(value: &Value) -> Option<i64> {
+    value.as_i64().or_else(|| value.as_str()?.parse().ok())
 }
 
 impl ServiceRegistry for EtcdRegistry {
@@ -34,102 +137,90 @@ impl ServiceRegistry for EtcdRegistry {
         let key = format!("{}/{}/{}", self.prefix, instance.name, instance.id);
         let value = serde_json::to_string(instance)
             .map_err(|e| RfError::Internal(format!("Failed to serialize instance: {}", e)))?;
-        
-        let url = format!("{}/v3/kv/put", self.base_url);
-        
-        use base64::{Engine as _, engine::general_purpose};
-        let payload = serde_json::json!({
+
+        let lease = match self.ttl {
+            Some(ttl) => {
+                let grant = self.post("/v3/lease/grant", serde_json::json!({ "TTL": ttl.as_secs().max(1) }), "lease grant")?;
+                let id = int64(&grant["ID"])
+                    .ok_or_else(|| RfError::Internal("etcd did not return a lease id".to_string()))?;
+                Some(id.to_string())
+            }
+            None => None,
+        };
+        let mut payload = serde_json::json!({
             "key": general_purpose::STANDARD.encode(key.as_bytes()),
             "value": general_purpose::STANDARD.encode(value.as_bytes()),
         });
-        
-        let _response = futures::executor::block_on(
-            self.client.post(&url).json(&payload).send()
-        )
-        .map_err(|e| RfError::Internal(format!("etcd register failed: {}", e)))?;
-        
+        if let Some(lease) = &lease {
+            payload["lease"] = Value::String(lease.clone());
+        }
+        self.post("/v3/kv/put", payload, "register")?;
+
+        self.registered
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(instance.id.clone(), Registered { key, lease });
         Ok(())
     }
-    
+
+    fn heartbeat(&self, instance: &ServiceInstance) -> Result<()> {
+        let registered = self.registered.lock().unwrap_or_else(|e| e.into_inner()).get(&instance.id).cloned();
+        let Some(lease) = registered.and_then(|registered| registered.lease) else {
+            return self.register(instance);
+        };
+        let json = self.post("/v3/lease/keepalive", serde_json::json!({ "ID": lease }), "heartbeat")?;
+        // An expired lease comes back without a TTL
+        match int64(&json["result"]["TTL"]) {
+            Some(ttl) if ttl > 0 => Ok(()),
+            _ => Err(RfError::NotFound(format!("etcd lease of instance {} expired", instance.id))),
+        }
+    }
+
     fn deregister(&self, service_id: &str) -> Result<()> {
-        // In etcd, we need to know the service name to construct the key
-        // This is a simplified implementation
-        let key = format!("{}/{}", self.prefix, service_id);
-        
-        let url = format!("{}/v3/kv/deleterange", self.base_url);
-        
-        use base64::{Engine as _, engine::general_purpose};
-        let payload = serde_json::json!({
-            "key": general_purpose::STANDARD.encode(key.as_bytes()),
-        });
-        
-        let _response = futures::executor::block_on(
-            self.client.post(&url).json(&payload).send()
-        )
-        .map_err(|e| RfError::Internal(format!("etcd deregister failed: {}", e)))?;
-        
+        let registered = self.registered.lock().unwrap_or_else(|e| e.into_inner()).remove(service_id);
+        if let Some(registered) = registered {
+            return self.delete(&registered);
+        }
+        // Registered by another process, find its key
+        let suffix = format!("/{}", service_id);
+        for (key, _) in self.range(&format!("{}/", self.prefix), "deregister")? {
+            if key.ends_with(&suffix) {
+                self.delete(&Registered { key, lease: None })?;
+            }
+        }
         Ok(())
     }
-    
+
     fn discover(&self, service_name: &str) -> Result<Vec<ServiceInstance>> {
-        let key = format!("{}/{}", self.prefix, service_name);
-        
-        let url = format!("{}/v3/kv/range", self.base_url);
-        
-        use base64::{Engine as _, engine::general_purpose};
-        let payload = serde_json::json!({
-            "key": general_purpose::STANDARD.encode(key.as_bytes()),
-            "range_end": general_purpose::STANDARD.encode(format!("{}/{}", key, "\0").as_bytes()),
-        });
-        
-        let response = futures::executor::block_on(
-            self.client.post(&url).json(&payload).send()
-        )
-        .map_err(|e| RfError::Internal(format!("etcd discover failed: {}", e)))?;
-        
-        let instances = Vec::new();
-        
-        if response.status().is_success() {
-            let _json: serde_json::Value = futures::executor::block_on(response.json())
-                .map_err(|e| RfError::Internal(format!("Failed to parse etcd response: {}", e)))?;
-            // Simplified - in full implementation would parse kvs and extract instances
-        }
-        
-        Ok(instances)
+        let prefix = format!("{}/{}/", self.prefix, service_name);
+        Ok(self
+            .range(&prefix, "discover")?
+            .into_iter()
+            .filter_map(|(key, value)| match serde_json::from_slice(&value) {
+                Ok(instance) => Some(instance),
+                Err(e) => {
+                    tracing::debug!("Skipping etcd key {}: {}", key, e);
+                    None
+                }
+            })
+            .collect())
     }
-    
+
     fn list_services(&self) -> Result<Vec<String>> {
-        let url = format!("{}/v3/kv/range", self.base_url);
-        
-        use base64::{Engine as _, engine::general_purpose};
-        let payload = serde_json::json!({
-            "key": general_purpose::STANDARD.encode(self.prefix.as_bytes()),
-            "range_end": general_purpose::STANDARD.encode(format!("{}/{}", self.prefix, "\0").as_bytes()),
-        });
-        
-        let response = futures::executor::block_on(
-            self.client.post(&url).json(&payload).send()
-        )
-        .map_err(|e| RfError::Internal(format!("etcd list services failed: {}", e)))?;
-        
-        let services = std::collections::HashSet::new();
-        
-        if response.status().is_success() {
-            let _json: serde_json::Value = futures::executor::block_on(response.json())
-                .map_err(|e| RfError::Internal(format!("Failed to parse etcd response: {}", e)))?;
-            // Simplified - in full implementation would parse kvs and extract service names
-        }
-        
+        let prefix = format!("{}/", self.prefix);
+        let services: HashSet<String> = self
+            .range(&prefix, "list services")?
+            .into_iter()
+            .filter_map(|(key, _)| Some(key.strip_prefix(&prefix)?.split('/').next()?.to_string()))
+            .collect();
         Ok(services.into_iter().collect())
     }
-    
-    fn watch<F>(&self, _service_name: &str, _callback: F) -> Result<()>
+
+    fn watch<F>(&self, service_name: &str, callback: F) -> Result<()>
     where
         F: Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync + 'static,
     {
-        // etcd watch implementation would use watch API
-        // This is a placeholder
-        Ok(())
+        let (registry, name) = (self.clone(), service_name.to_string());
+        watch_by_polling(service_name, self.watch_interval, move || registry.discover(&name), callback)
     }
 }
-
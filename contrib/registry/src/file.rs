@@ -6,18 +6,25 @@
 //! @date 2026-01-06
 
 //! File-based service registry adapter
+//!
+//! `watch` re-reads the file every `watch_interval`, so instances written by
+//! other processes sharing it show up.
 
+use super::poll::{watch_by_polling, DEFAULT_WATCH_INTERVAL};
 use super::{ServiceInstance, ServiceRegistry};
 use rf_errors::{Result, RfError};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 /// File-based service registry
+#[derive(Clone)]
 pub struct FileRegistry {
     path: String,
     services: Arc<RwLock<HashMap<String, Vec<ServiceInstance>>>>,
+    watch_interval: Duration,
 }
 
 impl FileRegistry {
@@ -41,8 +48,29 @@ impl FileRegistry {
         Ok(Self {
             path: path.to_string(),
             services,
+            watch_interval: DEFAULT_WATCH_INTERVAL,
         })
     }
+
+    /// Time between two reads of the file by `watch`
+    pub fn watch_interval(mut self, interval: Duration) -> Self {
+        self.watch_interval = interval;
+        self
+    }
+
+    /// Replace the services with the content of the file, if it exists
+    fn reload(&self) -> Result<()> {
+        if !Path::new(&self.path).exists() {
+            return Ok(());
+        }
+        let content = fs::read_to_string(&self.path)
+            .map_err(RfError::Io)?;
+        let data: HashMap<String, Vec<ServiceInstance>> = serde_json::from_str(&content)
+            .map_err(|e| RfError::Internal(format!("Failed to parse {}: {}", self.path, e)))?;
+        *self.services.write()
+            .map_err(|_| RfError::Internal("Failed to acquire write lock".to_string()))? = data;
+        Ok(())
+    }
     
     fn save(&self) -> Result<()> {
         let services = self.services.read()
@@ -102,13 +130,15 @@ impl ServiceRegistry for FileRegistry {
         Ok(services.keys().cloned().collect())
     }
     
-    fn watch<F>(&self, _service_name: &str, _callback: F) -> Result<()>
+    fn watch<F>(&self, service_name: &str, callback: F) -> Result<()>
     where
         F: Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync + 'static,
     {
-        // File-based watch would use file system watcher
-        // This is a placeholder
-        Ok(())
+        let (registry, name) = (self.clone(), service_name.to_string());
+        watch_by_polling(service_name, self.watch_interval, move || {
+            registry.reload()?;
+            registry.discover(&name)
+        }, callback)
     }
 }
 
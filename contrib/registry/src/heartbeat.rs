@@ -0,0 +1,216 @@
+//! # heartbeat
+//!
+//! heartbeat 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Heartbeats and TTL renewal for registered instances
+//!
+//! `Registration::start` registers an instance, then renews it every
+//! `interval` with `ServiceRegistry::heartbeat`: a TTL check pass in Consul,
+//! a lease keep-alive in etcd, a client beat in Nacos. Keep the interval
+//! well below the backend's TTL; a third of it is common.
+//!
+//! A failed heartbeat means the backend is unreachable or has dropped the
+//! instance. The task then registers the instance again, waiting a backoff
+//! that doubles from `initial` up to `max` between attempts, and returns to
+//! heartbeats once it is back.
+//!
+//! `Registration::deregister` stops the task and removes the instance; call
+//! it on shutdown (`HttpServer::with_registry` does). Dropping a
+//! `Registration` only stops the heartbeats, the backend then expires the
+//! instance after its TTL.
+//!
+//! ```ignore
+//! let registry = Arc::new(ConsulRegistry::new("http://127.0.0.1:8500").ttl(Duration::from_secs(15)));
+//! let options = HeartbeatOptions::new().interval(Duration::from_secs(5));
+//! let registration = Registration::start(registry, instance, options).await?;
+//! shutdown_signal().await;
+//! registration.deregister().await?;
+//! ```
+
+use super::poll::{backoff_delay, blocking};
+use super::{ServiceInstance, ServiceRegistry};
+use rf_errors::Result;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// When and how a `Registration` renews its instance
+#[derive(Debug, Clone)]
+pub struct HeartbeatOptions {
+    interval: Duration,
+    backoff_initial: Duration,
+    backoff_max: Duration,
+}
+
+impl HeartbeatOptions {
+    /// Heartbeat every 10 seconds, re-register with a backoff from 1 to 30 seconds
+    pub fn new() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            backoff_initial: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(30),
+        }
+    }
+
+    /// Time between heartbeats
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Delay before the first re-registration attempt and its maximum
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff_initial = initial;
+        self.backoff_max = max.max(initial);
+        self
+    }
+}
+
+impl Default for HeartbeatOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The calls a `Registration` makes, without `watch` so it can be boxed
+trait Renew: Send + Sync {
+    fn register(&self, instance: &ServiceInstance) -> Result<()>;
+    fn heartbeat(&self, instance: &ServiceInstance) -> Result<()>;
+    fn deregister(&self, service_id: &str) -> Result<()>;
+}
+
+impl<R: ServiceRegistry> Renew for R {
+    fn register(&self, instance: &ServiceInstance) -> Result<()> {
+        ServiceRegistry::register(self, instance)
+    }
+
+    fn heartbeat(&self, instance: &ServiceInstance) -> Result<()> {
+        ServiceRegistry::heartbeat(self, instance)
+    }
+
+    fn deregister(&self, service_id: &str) -> Result<()> {
+        ServiceRegistry::deregister(self, service_id)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    registered: AtomicBool,
+    renewals: AtomicU64,
+    failures: AtomicU64,
+}
+
+/// A registered instance kept alive by a heartbeat task
+pub struct Registration {
+    registry: Arc<dyn Renew>,
+    instance: ServiceInstance,
+    state: Arc<State>,
+    task: JoinHandle<()>,
+}
+
+impl Registration {
+    /// Register `instance` and start renewing it
+    ///
+    /// Fails if the first registration fails.
+    pub async fn start<R: ServiceRegistry + 'static>(
+        registry: Arc<R>,
+        instance: ServiceInstance,
+        options: HeartbeatOptions,
+    ) -> Result<Self> {
+        let registry: Arc<dyn Renew> = registry;
+        let (backend, registered) = (registry.clone(), instance.clone());
+        blocking(move || backend.register(&registered)).await?;
+
+        let state = Arc::new(State::default());
+        state.registered.store(true, Ordering::SeqCst);
+        let task = tokio::spawn(keep_alive(registry.clone(), instance.clone(), options, state.clone()));
+        Ok(Self {
+            registry,
+            instance,
+            state,
+            task,
+        })
+    }
+
+    /// The registered instance
+    pub fn instance(&self) -> &ServiceInstance {
+        &self.instance
+    }
+
+    /// Whether the last heartbeat or registration succeeded
+    pub fn is_registered(&self) -> bool {
+        self.state.registered.load(Ordering::SeqCst)
+    }
+
+    /// Successful heartbeats and re-registrations
+    pub fn renewals(&self) -> u64 {
+        self.state.renewals.load(Ordering::SeqCst)
+    }
+
+    /// Failed heartbeats and re-registrations
+    pub fn failures(&self) -> u64 {
+        self.state.failures.load(Ordering::SeqCst)
+    }
+
+    /// Stop the heartbeats and remove the instance from the registry
+    pub async fn deregister(self) -> Result<()> {
+        self.task.abort();
+        let (registry, id) = (self.registry.clone(), self.instance.id.clone());
+        blocking(move || registry.deregister(&id)).await
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn keep_alive(registry: Arc<dyn Renew>, instance: ServiceInstance, options: HeartbeatOptions, state: Arc<State>) {
+    // Consecutive failures, the backoff grows with them
+    let mut failures = 0u32;
+    loop {
+        let delay = match failures {
+            0 => options.interval,
+            n => backoff_delay(options.backoff_initial, options.backoff_max, n - 1),
+        };
+        tokio::time::sleep(delay).await;
+
+        let registered = state.registered.load(Ordering::SeqCst);
+        let (backend, renewed) = (registry.clone(), instance.clone());
+        let result = blocking(move || {
+            if registered {
+                backend.heartbeat(&renewed)
+            } else {
+                backend.register(&renewed)
+            }
+        })
+        .await;
+        match result {
+            Ok(()) => {
+                if !registered {
+                    tracing::info!("Registered {} ({}) again", instance.name, instance.id);
+                }
+                failures = 0;
+                state.registered.store(true, Ordering::SeqCst);
+                state.renewals.fetch_add(1, Ordering::SeqCst);
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "{} of {} ({}) failed: {}",
+                    if registered { "Heartbeat" } else { "Registration" },
+                    instance.name,
+                    instance.id,
+                    e
+                );
+                failures = failures.saturating_add(1);
+                state.registered.store(false, Ordering::SeqCst);
+                state.failures.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+}
@@ -12,25 +12,35 @@
 //! - etcd
 //! - Nacos
 //! - File-based registry
+//!
+//! `Registration` keeps a registered instance alive with periodic
+//! heartbeats and re-registers it after the backend was lost.
+//! `CachedRegistry` answers discovery from a local cache kept current by
+//! `watch`.
 
 pub mod consul;
 pub mod etcd;
 pub mod nacos;
 pub mod file;
 pub mod zookeeper;
+pub mod heartbeat;
+pub mod cache;
+mod poll;
 
 pub use consul::*;
 pub use etcd::*;
 pub use nacos::*;
 pub use file::*;
 pub use zookeeper::*;
+pub use heartbeat::*;
+pub use cache::*;
 
 use rf_errors::Result;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 
 /// Service instance information
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ServiceInstance {
     pub id: String,
     pub name: String,
@@ -54,6 +64,14 @@ pub trait ServiceRegistry: Send + Sync {
     
     /// Deregister a service instance
     fn deregister(&self, service_id: &str) -> Result<()>;
+
+    /// Renew the registration of `instance`, e.g. pass a TTL check or keep a lease alive
+    ///
+    /// Fails when the backend no longer knows the instance. Registries
+    /// without expiring registrations register it again.
+    fn heartbeat(&self, instance: &ServiceInstance) -> Result<()> {
+        self.register(instance)
+    }
     
     /// Discover service instances by name
     fn discover(&self, service_name: &str) -> Result<Vec<ServiceInstance>>;
@@ -6,18 +6,32 @@
 //! @date 2026-01-06
 
 //! Nacos service registry adapter
+//!
+//! Instances are ephemeral: Nacos marks them unhealthy and then removes
+//! them when client beats stop, so keep them alive with a `Registration`
+//! beating every 5 seconds. `watch` polls the instance list.
 
+use super::poll::{watch_by_polling, DEFAULT_WATCH_INTERVAL};
 use super::{ServiceInstance, ServiceRegistry, ServiceHealth};
 use rf_errors::{Result, RfError};
-use reqwest::Client;
+use reqwest::{Client, Response};
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Code of a beat for an instance Nacos does not know
+const RESOURCE_NOT_FOUND: i64 = 20404;
 
 /// Nacos service registry
+#[derive(Clone)]
 pub struct NacosRegistry {
     client: Client,
     base_url: String,
     namespace: String,
+    watch_interval: Duration,
+    /// Instances registered by this process, by ID; deregistering needs their address
+    registered: Arc<Mutex<HashMap<String, ServiceInstance>>>,
 }
 
 impl NacosRegistry {
@@ -27,8 +41,25 @@ impl NacosRegistry {
             client: Client::new(),
             base_url: base_url.to_string(),
             namespace: namespace.to_string(),
+            watch_interval: DEFAULT_WATCH_INTERVAL,
+            registered: Arc::new(Mutex::new(HashMap::new())),
         }
     }
+
+    /// Time between two lookups of `watch`
+    pub fn watch_interval(mut self, interval: Duration) -> Self {
+        self.watch_interval = interval;
+        self
+    }
+}
+
+/// Fail on a non-2xx answer
+fn check_status(response: Response, action: &str) -> Result<Response> {
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        Err(RfError::Internal(format!("Nacos {} failed: {}", action, response.status())))
+    }
 }
 
 impl ServiceRegistry for NacosRegistry {
@@ -41,27 +72,66 @@ impl ServiceRegistry for NacosRegistry {
             ("port", &instance.address.port().to_string()),
             ("namespaceId", &self.namespace),
             ("metadata", &serde_json::to_string(&instance.metadata).unwrap_or_default()),
+            ("ephemeral", "true"),
         ];
         
-        let _response = futures::executor::block_on(
+        let response = futures::executor::block_on(
             self.client.post(&url).form(&params).send()
         )
         .map_err(|e| RfError::Internal(format!("Nacos register failed: {}", e)))?;
+        check_status(response, "register")?;
         
+        self.registered.lock().unwrap_or_else(|e| e.into_inner()).insert(instance.id.clone(), instance.clone());
+        Ok(())
+    }
+
+    fn heartbeat(&self, instance: &ServiceInstance) -> Result<()> {
+        let url = format!("{}/nacos/v1/ns/instance/beat", self.base_url);
+        let beat = serde_json::json!({
+            "serviceName": instance.name,
+            "ip": instance.address.ip().to_string(),
+            "port": instance.address.port(),
+            "metadata": instance.metadata,
+            "scheduled": true,
+        });
+        let params = [
+            ("serviceName", instance.name.as_str()),
+            ("ip", &instance.address.ip().to_string()),
+            ("port", &instance.address.port().to_string()),
+            ("namespaceId", &self.namespace),
+            ("beat", &beat.to_string()),
+        ];
+        
+        let response = futures::executor::block_on(
+            self.client.put(&url).query(&params).send()
+        )
+        .map_err(|e| RfError::Internal(format!("Nacos heartbeat failed: {}", e)))?;
+        let json: serde_json::Value = futures::executor::block_on(check_status(response, "heartbeat")?.json())
+            .map_err(|e| RfError::Internal(format!("Failed to parse Nacos response: {}", e)))?;
+        if json.get("code").and_then(|v| v.as_i64()) == Some(RESOURCE_NOT_FOUND) {
+            return Err(RfError::NotFound(format!("Nacos no longer knows instance {}", instance.id)));
+        }
         Ok(())
     }
     
-    fn deregister(&self, _service_id: &str) -> Result<()> {
-        // Nacos deregister requires service name and IP:port
-        // This is a simplified implementation
+    fn deregister(&self, service_id: &str) -> Result<()> {
+        let instance = self.registered.lock().unwrap_or_else(|e| e.into_inner()).remove(service_id)
+            .ok_or_else(|| RfError::NotFound(format!("Nacos instance {} was not registered here", service_id)))?;
         let url = format!("{}/nacos/v1/ns/instance", self.base_url);
         
-        // Parse service_id to extract name and address
-        // In real implementation, would need to store this mapping
-        let _response = futures::executor::block_on(
-            self.client.delete(&url).send()
+        let params = [
+            ("serviceName", instance.name.as_str()),
+            ("ip", &instance.address.ip().to_string()),
+            ("port", &instance.address.port().to_string()),
+            ("namespaceId", &self.namespace),
+            ("ephemeral", "true"),
+        ];
+        
+        let response = futures::executor::block_on(
+            self.client.delete(&url).query(&params).send()
         )
         .map_err(|e| RfError::Internal(format!("Nacos deregister failed: {}", e)))?;
+        check_status(response, "deregister")?;
         
         Ok(())
     }
@@ -103,6 +173,10 @@ impl ServiceRegistry for NacosRegistry {
                                 }
                             }
                         }
+                        // Instance weight, used by weighted load balancing
+                        if let Some(weight) = host.get("weight").and_then(|v| v.as_f64()) {
+                            metadata.entry("weight".to_string()).or_insert_with(|| weight.to_string());
+                        }
                         
                         let health = if let Some(healthy) = host.get("healthy").and_then(|v| v.as_bool()) {
                             if healthy {
@@ -153,13 +227,12 @@ impl ServiceRegistry for NacosRegistry {
         Ok(Vec::new())
     }
     
-    fn watch<F>(&self, _service_name: &str, _callback: F) -> Result<()>
+    fn watch<F>(&self, service_name: &str, callback: F) -> Result<()>
     where
         F: Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync + 'static,
     {
-        // Nacos watch implementation would use long polling
-        // This is a placeholder
-        Ok(())
+        let (registry, name) = (self.clone(), service_name.to_string());
+        watch_by_polling(service_name, self.watch_interval, move || registry.discover(&name), callback)
     }
 }
 
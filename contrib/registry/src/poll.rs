@@ -0,0 +1,83 @@
+//! # poll
+//!
+//! poll 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Blocking calls, backoff and polling watches shared by the registries
+
+use super::ServiceInstance;
+use rand::Rng;
+use rf_errors::{Result, RfError};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Delay before the first retry after the backend failed
+pub(crate) const DEFAULT_BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+/// Largest delay between retries
+pub(crate) const DEFAULT_BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// Time between two lookups of a polling watch
+pub(crate) const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Run a blocking registry call off the async workers
+pub(crate) async fn blocking<T, F>(call: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(call)
+        .await
+        .map_err(|e| RfError::Internal(format!("Registry call panicked: {}", e)))?
+}
+
+/// Delay before retry `attempt` (from 0), doubling from `initial` up to `max`
+pub(crate) fn backoff_delay(initial: Duration, max: Duration, attempt: u32) -> Duration {
+    let backoff = initial.saturating_mul(2u32.saturating_pow(attempt.min(31))).min(max);
+    // Jitter between half and the whole backoff, so instances do not retry in step
+    backoff.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+}
+
+/// Watch a service by calling `discover` every `interval`
+///
+/// `callback` gets the first result and every result that differs from the
+/// previous one. While the backend fails, lookups are retried with backoff.
+pub(crate) fn watch_by_polling<D, F>(service_name: &str, interval: Duration, discover: D, callback: F) -> Result<()>
+where
+    D: Fn() -> Result<Vec<ServiceInstance>> + Send + Sync + 'static,
+    F: Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync + 'static,
+{
+    let handle = tokio::runtime::Handle::try_current()
+        .map_err(|_| RfError::Internal("Watching a service requires a tokio runtime".to_string()))?;
+    let discover = Arc::new(discover);
+    let service_name = service_name.to_string();
+    handle.spawn(async move {
+        let mut last: Option<Vec<ServiceInstance>> = None;
+        let mut failures = 0u32;
+        loop {
+            let lookup = discover.clone();
+            match blocking(move || lookup()).await {
+                Ok(mut instances) => {
+                    if failures > 0 {
+                        tracing::info!("Registry reachable again, watching {}", service_name);
+                    }
+                    failures = 0;
+                    instances.sort_by(|a, b| a.id.cmp(&b.id));
+                    if last.as_ref() != Some(&instances) {
+                        if let Err(e) = callback(instances.clone()) {
+                            tracing::warn!("Watch callback for {} failed: {}", service_name, e);
+                        }
+                        last = Some(instances);
+                    }
+                    tokio::time::sleep(interval).await;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to watch {}: {}", service_name, e);
+                    tokio::time::sleep(backoff_delay(DEFAULT_BACKOFF_INITIAL, DEFAULT_BACKOFF_MAX, failures)).await;
+                    failures = failures.saturating_add(1);
+                }
+            }
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,212 @@
+//! Registry heartbeat and cache tests
+
+use rf_contrib_registry::{
+    CachedRegistry, HeartbeatOptions, Registration, ServiceHealth, ServiceInstance, ServiceRegistry,
+};
+use rf_errors::{Result, RfError};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Callback = Box<dyn Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync>;
+
+/// In-memory registry counting calls; `down` makes every call fail
+#[derive(Default)]
+struct FakeRegistry {
+    instances: Mutex<HashMap<String, ServiceInstance>>,
+    watchers: Mutex<Vec<(String, Callback)>>,
+    down: AtomicBool,
+    registers: AtomicUsize,
+    heartbeats: AtomicUsize,
+    discovers: AtomicUsize,
+}
+
+impl FakeRegistry {
+    fn check(&self) -> Result<()> {
+        if self.down.load(Ordering::SeqCst) {
+            Err(RfError::Network("registry down".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn instances_of(&self, service_name: &str) -> Vec<ServiceInstance> {
+        let instances = self.instances.lock().unwrap();
+        let mut found: Vec<_> = instances.values().filter(|i| i.name == service_name).cloned().collect();
+        found.sort_by(|a, b| a.id.cmp(&b.id));
+        found
+    }
+
+    /// Push the current instances to the watchers of `service_name`
+    fn notify(&self, service_name: &str) {
+        let instances = self.instances_of(service_name);
+        for (name, callback) in self.watchers.lock().unwrap().iter() {
+            if name == service_name {
+                callback(instances.clone()).unwrap();
+            }
+        }
+    }
+}
+
+impl ServiceRegistry for FakeRegistry {
+    fn register(&self, instance: &ServiceInstance) -> Result<()> {
+        self.check()?;
+        self.registers.fetch_add(1, Ordering::SeqCst);
+        self.instances.lock().unwrap().insert(instance.id.clone(), instance.clone());
+        Ok(())
+    }
+
+    fn deregister(&self, service_id: &str) -> Result<()> {
+        self.check()?;
+        self.instances.lock().unwrap().remove(service_id);
+        Ok(())
+    }
+
+    fn heartbeat(&self, instance: &ServiceInstance) -> Result<()> {
+        self.check()?;
+        self.heartbeats.fetch_add(1, Ordering::SeqCst);
+        if self.instances.lock().unwrap().contains_key(&instance.id) {
+            Ok(())
+        } else {
+            Err(RfError::NotFound(instance.id.clone()))
+        }
+    }
+
+    fn discover(&self, service_name: &str) -> Result<Vec<ServiceInstance>> {
+        self.check()?;
+        self.discovers.fetch_add(1, Ordering::SeqCst);
+        Ok(self.instances_of(service_name))
+    }
+
+    fn list_services(&self) -> Result<Vec<String>> {
+        self.check()?;
+        Ok(self.instances.lock().unwrap().values().map(|i| i.name.clone()).collect())
+    }
+
+    fn watch<F>(&self, service_name: &str, callback: F) -> Result<()>
+    where
+        F: Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync + 'static,
+    {
+        self.watchers.lock().unwrap().push((service_name.to_string(), Box::new(callback)));
+        Ok(())
+    }
+}
+
+fn instance(id: &str, port: u16) -> ServiceInstance {
+    ServiceInstance {
+        id: id.to_string(),
+        name: "orders".to_string(),
+        address: format!("127.0.0.1:{}", port).parse().unwrap(),
+        metadata: HashMap::new(),
+        health: ServiceHealth::Healthy,
+    }
+}
+
+fn fast() -> HeartbeatOptions {
+    HeartbeatOptions::new()
+        .interval(Duration::from_millis(20))
+        .backoff(Duration::from_millis(10), Duration::from_millis(40))
+}
+
+async fn eventually(condition: impl Fn() -> bool) {
+    for _ in 0..200 {
+        if condition() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("condition not met in time");
+}
+
+#[tokio::test]
+async fn test_heartbeats_renew_registration() {
+    let registry = Arc::new(FakeRegistry::default());
+    let registration = Registration::start(registry.clone(), instance("a", 8001), fast()).await.unwrap();
+    assert_eq!(registry.registers.load(Ordering::SeqCst), 1);
+    assert!(registration.is_registered());
+
+    eventually(|| registration.renewals() >= 3).await;
+    assert!(registry.heartbeats.load(Ordering::SeqCst) >= 3);
+    assert_eq!(registration.failures(), 0);
+}
+
+#[tokio::test]
+async fn test_start_fails_when_backend_is_down() {
+    let registry = Arc::new(FakeRegistry::default());
+    registry.down.store(true, Ordering::SeqCst);
+    assert!(Registration::start(registry, instance("a", 8001), fast()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_reregisters_after_outage() {
+    let registry = Arc::new(FakeRegistry::default());
+    let registration = Registration::start(registry.clone(), instance("a", 8001), fast()).await.unwrap();
+
+    // The backend loses the instance and is unreachable for a while
+    registry.down.store(true, Ordering::SeqCst);
+    registry.instances.lock().unwrap().clear();
+    eventually(|| registration.failures() >= 2).await;
+    assert!(!registration.is_registered());
+
+    registry.down.store(false, Ordering::SeqCst);
+    eventually(|| registration.is_registered()).await;
+    assert!(registry.registers.load(Ordering::SeqCst) >= 2);
+    assert_eq!(registry.instances_of("orders"), vec![instance("a", 8001)]);
+}
+
+#[tokio::test]
+async fn test_deregister_stops_heartbeats() {
+    let registry = Arc::new(FakeRegistry::default());
+    let registration = Registration::start(registry.clone(), instance("a", 8001), fast()).await.unwrap();
+    eventually(|| registration.renewals() >= 1).await;
+
+    registration.deregister().await.unwrap();
+    assert!(registry.instances_of("orders").is_empty());
+    let heartbeats = registry.heartbeats.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    assert_eq!(registry.heartbeats.load(Ordering::SeqCst), heartbeats);
+}
+
+#[test]
+fn test_cached_discovery() {
+    let registry = Arc::new(FakeRegistry::default());
+    registry.register(&instance("a", 8001)).unwrap();
+    let cached = CachedRegistry::shared(registry.clone());
+    assert_eq!(cached.cached("orders"), None);
+
+    assert_eq!(cached.discover("orders").unwrap(), vec![instance("a", 8001)]);
+    assert_eq!(cached.discover("orders").unwrap(), vec![instance("a", 8001)]);
+    assert_eq!(registry.discovers.load(Ordering::SeqCst), 1);
+
+    // Served from memory while the backend is down
+    registry.down.store(true, Ordering::SeqCst);
+    assert_eq!(cached.discover("orders").unwrap(), vec![instance("a", 8001)]);
+}
+
+#[test]
+fn test_cache_follows_watch() {
+    let registry = Arc::new(FakeRegistry::default());
+    registry.register(&instance("a", 8001)).unwrap();
+    let cached = CachedRegistry::shared(registry.clone());
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    for _ in 0..2 {
+        let seen = seen.clone();
+        cached
+            .watch("orders", move |instances| {
+                seen.lock().unwrap().push(instances.len());
+                Ok(())
+            })
+            .unwrap();
+    }
+    // One backend watch shared by both subscribers, each called right away
+    assert_eq!(registry.watchers.lock().unwrap().len(), 1);
+    assert_eq!(*seen.lock().unwrap(), vec![1, 1]);
+
+    registry.register(&instance("b", 8002)).unwrap();
+    registry.notify("orders");
+    assert_eq!(*seen.lock().unwrap(), vec![1, 1, 2, 2]);
+    assert_eq!(cached.discover("orders").unwrap().len(), 2);
+    assert_eq!(registry.discovers.load(Ordering::SeqCst), 1);
+}
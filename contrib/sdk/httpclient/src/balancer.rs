@@ -0,0 +1,178 @@
+//! # balancer
+//!
+//! balancer 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Client-side load balancing
+//!
+//! A `LoadBalancer` is a shared handle: clones see the same endpoints, so the
+//! list can be replaced at runtime (see `DiscoveryLoadBalancer`) while clients
+//! keep using it. Requests hold an `EndpointGuard` while in flight, which is
+//! what `LeastConnections` balances on.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Load balancing strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadBalanceStrategy {
+    RoundRobin,
+    Random,
+    /// Fewest in-flight requests relative to weight, ties rotate
+    LeastConnections,
+    /// Smooth weighted round-robin
+    Weighted,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: String,
+    active: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct Slot {
+    endpoint: Arc<Endpoint>,
+    weight: u32,
+    current_weight: i64,
+}
+
+#[derive(Debug)]
+struct BalancerInner {
+    slots: Mutex<Vec<Slot>>,
+    current_index: AtomicUsize,
+    strategy: LoadBalanceStrategy,
+}
+
+/// Load balancer for multiple endpoints
+#[derive(Debug, Clone)]
+pub struct LoadBalancer {
+    inner: Arc<BalancerInner>,
+}
+
+/// An endpoint picked for one request, counted as in flight until dropped
+#[derive(Debug)]
+pub struct EndpointGuard {
+    endpoint: Arc<Endpoint>,
+}
+
+impl EndpointGuard {
+    /// Base URL of the endpoint
+    pub fn url(&self) -> &str {
+        &self.endpoint.url
+    }
+}
+
+impl Drop for EndpointGuard {
+    fn drop(&mut self) {
+        self.endpoint.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl LoadBalancer {
+    pub fn new(endpoints: Vec<String>, strategy: LoadBalanceStrategy) -> Self {
+        Self::weighted(endpoints.into_iter().map(|url| (url, 1)).collect(), strategy)
+    }
+
+    /// Balancer over `(url, weight)` pairs
+    pub fn weighted(endpoints: Vec<(String, u32)>, strategy: LoadBalanceStrategy) -> Self {
+        let balancer = Self {
+            inner: Arc::new(BalancerInner {
+                slots: Mutex::new(Vec::new()),
+                current_index: AtomicUsize::new(0),
+                strategy,
+            }),
+        };
+        balancer.set_endpoints(endpoints);
+        balancer
+    }
+
+    pub fn strategy(&self) -> LoadBalanceStrategy {
+        self.inner.strategy
+    }
+
+    /// Replace the endpoints, keeping in-flight counts of those that remain
+    pub fn set_endpoints(&self, endpoints: Vec<(String, u32)>) {
+        let mut slots = self.inner.slots.lock().unwrap();
+        let previous: Vec<Slot> = std::mem::take(&mut *slots);
+        for (url, weight) in endpoints {
+            if slots.iter().any(|slot| slot.endpoint.url == url) {
+                continue;
+            }
+            let endpoint = match previous.iter().find(|slot| slot.endpoint.url == url) {
+                Some(slot) => slot.endpoint.clone(),
+                None => Arc::new(Endpoint { url, active: AtomicUsize::new(0) }),
+            };
+            slots.push(Slot { endpoint, weight: weight.max(1), current_weight: 0 });
+        }
+    }
+
+    /// Current endpoint URLs
+    pub fn endpoints(&self) -> Vec<String> {
+        self.inner.slots.lock().unwrap().iter().map(|slot| slot.endpoint.url.clone()).collect()
+    }
+
+    /// In-flight requests to `url`
+    pub fn connections(&self, url: &str) -> usize {
+        self.inner
+            .slots
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|slot| slot.endpoint.url == url)
+            .map_or(0, |slot| slot.endpoint.active.load(Ordering::Relaxed))
+    }
+
+    /// Pick an endpoint without tracking the request
+    pub fn next(&self) -> Option<String> {
+        self.pick().map(|guard| guard.url().to_string())
+    }
+
+    /// Pick an endpoint and count a request against it until the guard drops
+    pub fn pick(&self) -> Option<EndpointGuard> {
+        let mut slots = self.inner.slots.lock().unwrap();
+        if slots.is_empty() {
+            return None;
+        }
+        let len = slots.len();
+        let index = match self.inner.strategy {
+            LoadBalanceStrategy::RoundRobin => self.inner.current_index.fetch_add(1, Ordering::Relaxed) % len,
+            LoadBalanceStrategy::Random => {
+                use rand::Rng;
+                rand::thread_rng().gen_range(0..len)
+            }
+            LoadBalanceStrategy::LeastConnections => {
+                // Start from a rotating offset so ties spread across endpoints
+                let start = self.inner.current_index.fetch_add(1, Ordering::Relaxed);
+                (0..len)
+                    .map(|i| (start + i) % len)
+                    .min_by_key(|&i| {
+                        // Compare active / weight without floats
+                        let active = slots[i].endpoint.active.load(Ordering::Relaxed) as u128;
+                        active * 1_000_000 / u128::from(slots[i].weight)
+                    })
+                    .unwrap_or(0)
+            }
+            LoadBalanceStrategy::Weighted => {
+                let total: i64 = slots.iter().map(|slot| i64::from(slot.weight)).sum();
+                let (mut best, mut best_weight) = (0, i64::MIN);
+                for (i, slot) in slots.iter_mut().enumerate() {
+                    slot.current_weight += i64::from(slot.weight);
+                    if i > 0 && slot.current_weight > best_weight {
+                        best = i;
+                    }
+                    if i == best {
+                        best_weight = slot.current_weight;
+                    }
+                }
+                slots[best].current_weight -= total;
+                best
+            }
+        };
+        let endpoint = slots[index].endpoint.clone();
+        endpoint.active.fetch_add(1, Ordering::Relaxed);
+        Some(EndpointGuard { endpoint })
+    }
+}
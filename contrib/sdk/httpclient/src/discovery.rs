@@ -0,0 +1,195 @@
+//! # discovery
+//!
+//! discovery 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Load balancing over service registry instances
+//!
+//! `DiscoveryLoadBalancer` resolves a service name through any
+//! `ServiceRegistry` (Consul, etcd, Nacos, ...) and keeps a `LoadBalancer`
+//! in sync: registry watch notifications are applied as they arrive and the
+//! service is re-resolved every `refresh_interval` for registries that do not
+//! push. Unhealthy instances are never balanced to.
+//!
+//! ```rust,ignore
+//! use rf_contrib_sdk_httpclient::{DiscoveryLoadBalancer, HttpClient, LoadBalanceStrategy};
+//!
+//! let registry = Arc::new(ConsulRegistry::new("http://127.0.0.1:8500"));
+//! let discovery = DiscoveryLoadBalancer::new(registry, "orders", LoadBalanceStrategy::LeastConnections)
+//!     .refresh_interval(Duration::from_secs(10))
+//!     .start()
+//!     .await?;
+//! let client = HttpClient::new().with_load_balancer(discovery.balancer());
+//! let response = client.get("/orders/42").await?;
+//! ```
+
+use crate::{LoadBalanceStrategy, LoadBalancer};
+use rf_contrib_registry::{ServiceHealth, ServiceInstance, ServiceRegistry};
+use rf_errors::{Result, RfError};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Instance metadata key holding the instance's weight
+pub const WEIGHT_METADATA: &str = "weight";
+
+/// Instance metadata key holding the URL scheme, overriding the default
+pub const SCHEME_METADATA: &str = "scheme";
+
+type Discover = Arc<dyn Fn(&str) -> Result<Vec<ServiceInstance>> + Send + Sync>;
+type WatchCallback = Box<dyn Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync>;
+type Watch = Box<dyn FnOnce(&str, WatchCallback) -> Result<()> + Send>;
+
+/// How instances become endpoints
+#[derive(Debug, Clone)]
+struct EndpointPolicy {
+    scheme: String,
+    include_unknown: bool,
+}
+
+impl EndpointPolicy {
+    fn apply(&self, balancer: &LoadBalancer, instances: &[ServiceInstance]) -> usize {
+        let endpoints: Vec<(String, u32)> = instances
+            .iter()
+            .filter(|instance| match instance.health {
+                ServiceHealth::Healthy => true,
+                ServiceHealth::Unknown => self.include_unknown,
+                ServiceHealth::Unhealthy => false,
+            })
+            .map(|instance| {
+                let scheme = instance.metadata.get(SCHEME_METADATA).unwrap_or(&self.scheme);
+                let weight = instance
+                    .metadata
+                    .get(WEIGHT_METADATA)
+                    .and_then(|weight| weight.parse::<f64>().ok())
+                    .map_or(1, |weight| weight.round().max(1.0) as u32);
+                (format!("{}://{}", scheme, instance.address), weight)
+            })
+            .collect();
+        let count = endpoints.len();
+        balancer.set_endpoints(endpoints);
+        count
+    }
+}
+
+/// Load balancer fed by a service registry
+pub struct DiscoveryLoadBalancer {
+    service: String,
+    balancer: LoadBalancer,
+    policy: EndpointPolicy,
+    discover: Discover,
+    watch: Option<Watch>,
+    refresh_interval: Duration,
+    task: Option<JoinHandle<()>>,
+}
+
+impl DiscoveryLoadBalancer {
+    /// Balance requests over the instances of `service` in `registry`
+    pub fn new<R: ServiceRegistry + 'static>(registry: Arc<R>, service: &str, strategy: LoadBalanceStrategy) -> Self {
+        let discover_registry = registry.clone();
+        Self {
+            service: service.to_string(),
+            balancer: LoadBalancer::new(Vec::new(), strategy),
+            policy: EndpointPolicy {
+                scheme: "http".to_string(),
+                include_unknown: true,
+            },
+            discover: Arc::new(move |service| discover_registry.discover(service)),
+            watch: Some(Box::new(move |service, callback| registry.watch(service, callback))),
+            refresh_interval: Duration::from_secs(30),
+            task: None,
+        }
+    }
+
+    /// URL scheme for instances without a `scheme` metadata entry (default `http`)
+    pub fn scheme(mut self, scheme: &str) -> Self {
+        self.policy.scheme = scheme.to_string();
+        self
+    }
+
+    /// Balance to instances whose health is unknown (default true)
+    pub fn include_unknown(mut self, include: bool) -> Self {
+        self.policy.include_unknown = include;
+        self
+    }
+
+    /// Interval between re-resolving the service (default 30 seconds)
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Resolve the service, subscribe to changes and start periodic refreshes
+    ///
+    /// Fails if the first resolution fails; later failures keep the last
+    /// known endpoints.
+    pub async fn start(mut self) -> Result<Self> {
+        self.refresh().await?;
+
+        if let Some(watch) = self.watch.take() {
+            let balancer = self.balancer.clone();
+            let policy = self.policy.clone();
+            let service = self.service.clone();
+            let callback: WatchCallback = Box::new(move |instances| {
+                policy.apply(&balancer, &instances);
+                Ok(())
+            });
+            tokio::task::spawn_blocking(move || watch(&service, callback))
+                .await
+                .map_err(|e| RfError::Internal(format!("Registry watch panicked: {}", e)))??;
+        }
+
+        let balancer = self.balancer.clone();
+        let policy = self.policy.clone();
+        let discover = self.discover.clone();
+        let service = self.service.clone();
+        let interval = self.refresh_interval;
+        self.task = Some(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                match resolve(&discover, &service).await {
+                    Ok(instances) => {
+                        policy.apply(&balancer, &instances);
+                    }
+                    Err(e) => tracing::warn!(service = %service, "Service discovery refresh failed: {}", e),
+                }
+            }
+        }));
+        Ok(self)
+    }
+
+    /// Resolve the service now, returns the number of usable endpoints
+    pub async fn refresh(&self) -> Result<usize> {
+        let instances = resolve(&self.discover, &self.service).await?;
+        Ok(self.policy.apply(&self.balancer, &instances))
+    }
+
+    /// Shared balancer to pass to `HttpClient::with_load_balancer`
+    pub fn balancer(&self) -> LoadBalancer {
+        self.balancer.clone()
+    }
+
+    /// Name of the discovered service
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+}
+
+impl Drop for DiscoveryLoadBalancer {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Registry clients block, so resolve off the async workers
+async fn resolve(discover: &Discover, service: &str) -> Result<Vec<ServiceInstance>> {
+    let discover = discover.clone();
+    let service = service.to_string();
+    tokio::task::spawn_blocking(move || discover(&service))
+        .await
+        .map_err(|e| RfError::Internal(format!("Service discovery panicked: {}", e)))?
+}
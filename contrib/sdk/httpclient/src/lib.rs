@@ -6,13 +6,38 @@
 //! @date 2026-01-06
 
 //! Enhanced HTTP client SDK
+//!
+//! ```rust,ignore
+//! use rf_contrib_sdk_httpclient::{HttpClient, Method, MultipartForm};
+//!
+//! let client = HttpClient::new().with_base_url("https://api.example.com".to_string());
+//! let order: Order = client.request(Method::PUT, "/orders/42")
+//!     .bearer_auth(&token)
+//!     .query(&[("notify", "true")])
+//!     .json(&update)
+//!     .timeout(Duration::from_secs(5))
+//!     .send_json()
+//!     .await?;
+//! ```
+
+mod balancer;
+#[cfg(feature = "discovery")]
+mod discovery;
+mod request;
+
+pub use balancer::{EndpointGuard, LoadBalanceStrategy, LoadBalancer};
+#[cfg(feature = "discovery")]
+pub use discovery::{DiscoveryLoadBalancer, SCHEME_METADATA, WEIGHT_METADATA};
+pub use request::{ClientRequest, MultipartForm};
+pub use reqwest::Method;
+pub use rf_net::breaker::{CircuitBreaker, CircuitBreakerMetrics, CircuitState, StateChange};
+pub use rf_net::retry::RetryConfig;
 
 use reqwest::Client;
 use reqwest::RequestBuilder;
-use rf_errors::Result;
-use std::time::Duration;
+use rf_errors::{Result, RfError};
+use rf_net::retry::send_error;
 use std::sync::Arc;
-use tokio::sync::RwLock;
 
 /// HTTP client with retry, load balancing, and circuit breaker
 pub struct HttpClient {
@@ -21,167 +46,8 @@ pub struct HttpClient {
     retry_config: RetryConfig,
     load_balancer: Option<LoadBalancer>,
     circuit_breaker: Option<Arc<CircuitBreaker>>,
-}
-
-/// Retry configuration
-#[derive(Debug, Clone)]
-pub struct RetryConfig {
-    pub max_retries: u32,
-    pub retry_delay: Duration,
-    pub retry_on_status: Vec<u16>, // HTTP status codes to retry on
-}
-
-impl Default for RetryConfig {
-    fn default() -> Self {
-        Self {
-            max_retries: 3,
-            retry_delay: Duration::from_millis(100),
-            retry_on_status: vec![500, 502, 503, 504],
-        }
-    }
-}
-
-/// Load balancer for multiple endpoints
-pub enum LoadBalanceStrategy {
-    RoundRobin,
-    Random,
-    LeastConnections,
-}
-
-/// Load balancer
-pub struct LoadBalancer {
-    endpoints: Vec<String>,
-    current_index: Arc<std::sync::atomic::AtomicUsize>,
-    strategy: LoadBalanceStrategy,
-}
-
-impl LoadBalancer {
-    pub fn new(endpoints: Vec<String>, strategy: LoadBalanceStrategy) -> Self {
-        Self {
-            endpoints,
-            current_index: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
-            strategy,
-        }
-    }
-
-    pub fn next(&self) -> Option<&String> {
-        if self.endpoints.is_empty() {
-            return None;
-        }
-
-        match self.strategy {
-            LoadBalanceStrategy::RoundRobin => {
-                let index = self.current_index.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                self.endpoints.get(index % self.endpoints.len())
-            }
-            LoadBalanceStrategy::Random => {
-                use rand::Rng;
-                let mut rng = rand::thread_rng();
-                let index = rng.gen_range(0..self.endpoints.len());
-                self.endpoints.get(index)
-            }
-            LoadBalanceStrategy::LeastConnections => {
-                // For now, use round-robin as fallback
-                let index = self.current_index.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                self.endpoints.get(index % self.endpoints.len())
-            }
-        }
-    }
-}
-
-/// Circuit breaker state
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum CircuitState {
-    Closed,   // Normal operation
-    Open,     // Failing, reject requests
-    HalfOpen, // Testing if service recovered
-}
-
-/// Circuit breaker
-pub struct CircuitBreaker {
-    state: Arc<RwLock<CircuitState>>,
-    failure_threshold: u32,
-    failure_count: Arc<std::sync::atomic::AtomicU32>,
-    success_threshold: u32,
-    success_count: Arc<std::sync::atomic::AtomicU32>,
-    timeout: Duration,
-    last_failure_time: Arc<RwLock<Option<std::time::Instant>>>,
-}
-
-impl CircuitBreaker {
-    pub fn new(failure_threshold: u32, timeout: Duration) -> Self {
-        Self {
-            state: Arc::new(RwLock::new(CircuitState::Closed)),
-            failure_threshold,
-            failure_count: Arc::new(std::sync::atomic::AtomicU32::new(0)),
-            success_threshold: 3,
-            success_count: Arc::new(std::sync::atomic::AtomicU32::new(0)),
-            timeout,
-            last_failure_time: Arc::new(RwLock::new(None)),
-        }
-    }
-
-    pub async fn call<F, Fut, T>(&self, f: F) -> Result<T>
-    where
-        F: FnOnce() -> Fut,
-        Fut: std::future::Future<Output = Result<T>>,
-    {
-        let state = *self.state.read().await;
-        
-        match state {
-            CircuitState::Open => {
-                // Check if timeout has passed
-                let last_failure = *self.last_failure_time.read().await;
-                if let Some(last) = last_failure {
-                    if last.elapsed() >= self.timeout {
-                        // Move to half-open
-                        *self.state.write().await = CircuitState::HalfOpen;
-                        self.success_count.store(0, std::sync::atomic::Ordering::Relaxed);
-                    } else {
-                        return Err(rf_errors::RfError::Network("Circuit breaker is open".to_string()));
-                    }
-                } else {
-                    return Err(rf_errors::RfError::Network("Circuit breaker is open".to_string()));
-                }
-            }
-            CircuitState::HalfOpen => {
-                // Allow request to test recovery
-            }
-            CircuitState::Closed => {
-                // Normal operation
-            }
-        }
-
-        match f().await {
-            Ok(result) => {
-                // Success
-                if state == CircuitState::HalfOpen {
-                    let success = self.success_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
-                    if success >= self.success_threshold {
-                        *self.state.write().await = CircuitState::Closed;
-                        self.failure_count.store(0, std::sync::atomic::Ordering::Relaxed);
-                    }
-                } else {
-                    self.failure_count.store(0, std::sync::atomic::Ordering::Relaxed);
-                }
-                Ok(result)
-            }
-            Err(e) => {
-                // Failure
-                if state == CircuitState::HalfOpen {
-                    *self.state.write().await = CircuitState::Open;
-                    *self.last_failure_time.write().await = Some(std::time::Instant::now());
-                } else {
-                    let failures = self.failure_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
-                    if failures >= self.failure_threshold {
-                        *self.state.write().await = CircuitState::Open;
-                        *self.last_failure_time.write().await = Some(std::time::Instant::now());
-                    }
-                }
-                Err(e)
-            }
-        }
-    }
+    #[cfg(feature = "fault-injection")]
+    faults: Option<rf_core::fault::FaultInjector>,
 }
 
 impl HttpClient {
@@ -193,6 +59,8 @@ impl HttpClient {
             retry_config: RetryConfig::default(),
             load_balancer: None,
             circuit_breaker: None,
+            #[cfg(feature = "fault-injection")]
+            faults: None,
         }
     }
 
@@ -220,83 +88,122 @@ impl HttpClient {
         self
     }
 
+    /// Inject latency, errors and connection resets into requests
+    ///
+    /// Rules match the full request URL; see `rf_core::fault`. Faults are
+    /// injected inside the retry loop and the circuit breaker: an injected
+    /// error arrives as a response with that status and a reset as a
+    /// network error, so both are retried and counted like real ones.
+    #[cfg(feature = "fault-injection")]
+    pub fn with_faults(mut self, faults: rf_core::fault::FaultInjector) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Build a request to `path`, resolved against the base URL or load balancer
+    ///
+    /// The request is sent with the client's retry and circuit breaker settings.
+    pub fn request(&self, method: Method, path: &str) -> ClientRequest<'_> {
+        ClientRequest::new(self, method, path)
+    }
+
     /// Make a GET request with retry
     pub async fn get(&self, url: &str) -> Result<reqwest::Response> {
-        self.request_with_retry(|client| {
-            let url = self.resolve_url(url);
-            client.get(&url)
-        }).await
+        self.request_path_with_retry(url, |client, url| client.get(url)).await
     }
 
     /// Make a POST request with retry
     pub async fn post(&self, url: &str, body: &str) -> Result<reqwest::Response> {
-        self.request_with_retry(|client| {
-            let url = self.resolve_url(url);
-            client.post(&url).body(body.to_string())
-        }).await
+        self.request_path_with_retry(url, |client, url| client.post(url).body(body.to_string())).await
     }
 
     /// Resolve URL (with load balancing if configured)
-    fn resolve_url(&self, url: &str) -> String {
+    ///
+    /// A balanced endpoint comes with a guard that counts the request as in
+    /// flight until dropped.
+    pub(crate) fn resolve_url(&self, url: &str) -> Result<(String, Option<EndpointGuard>)> {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            return Ok((url.to_string(), None));
+        }
+
         if let Some(ref balancer) = self.load_balancer {
-            if let Some(base) = balancer.next() {
-                if url.starts_with("http://") || url.starts_with("https://") {
-                    return url.to_string();
-                }
-                return format!("{}{}", base, url);
+            if let Some(endpoint) = balancer.pick() {
+                return Ok((format!("{}{}", endpoint.url(), url), Some(endpoint)));
+            }
+            if self.base_url.is_none() {
+                return Err(RfError::Network("No endpoint available from the load balancer".to_string()));
             }
         }
         
         if let Some(ref base) = self.base_url {
-            if url.starts_with("http://") || url.starts_with("https://") {
-                return url.to_string();
-            }
-            return format!("{}{}", base, url);
+            return Ok((format!("{}{}", base, url), None));
         }
         
-        url.to_string()
+        Ok((url.to_string(), None))
     }
 
-    /// Make request with retry and circuit breaker
-    async fn request_with_retry<F>(&self, builder: F) -> Result<reqwest::Response>
+    /// Make a request to `path` with retry and circuit breaker
+    ///
+    /// `path` is resolved on every attempt, so a retry may go to another endpoint.
+    pub(crate) async fn request_path_with_retry<F>(&self, path: &str, builder: F) -> Result<reqwest::Response>
     where
-        F: Fn(&Client) -> RequestBuilder,
+        F: Fn(&Client, &str) -> RequestBuilder,
     {
-        let mut last_error = None;
-        
-        for attempt in 0..=self.retry_config.max_retries {
-            let result = if let Some(ref breaker) = self.circuit_breaker {
-                breaker.call(|| async {
-                    let request = builder(&self.client);
-                    request.send().await
-                        .map_err(|e| rf_errors::RfError::Network(format!("Request failed: {}", e)))
-                }).await
-            } else {
-                let request = builder(&self.client);
-                request.send().await
-                    .map_err(|e| rf_errors::RfError::Network(format!("Request failed: {}", e)))
-            };
+        self.retry(|| {
+            let (url, endpoint) = self.resolve_url(path)?;
+            Ok((builder(&self.client, &url), endpoint))
+        })
+        .await
+    }
+
+    async fn retry<F>(&self, attempt_request: F) -> Result<reqwest::Response>
+    where
+        F: Fn() -> Result<(RequestBuilder, Option<EndpointGuard>)>,
+    {
+        rf_net::retry::send_with_retry(
+            &self.retry_config,
+            self.circuit_breaker.as_deref(),
+            attempt_request,
+            |(request, endpoint)| async move {
+                let result = self.send(request).await;
+                drop(endpoint);
+                result
+            },
+        )
+        .await
+    }
+
+    /// Send one attempt
+    #[cfg(not(feature = "fault-injection"))]
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        request.send().await.map_err(send_error)
+    }
 
-            match result {
-                Ok(response) => {
-                    let status = response.status();
-                    if self.retry_config.retry_on_status.contains(&status.as_u16()) && attempt < self.retry_config.max_retries {
-                        tokio::time::sleep(self.retry_config.retry_delay).await;
-                        last_error = Some(rf_errors::RfError::Network(format!("HTTP {}", status)));
-                        continue;
-                    }
-                    return Ok(response);
-                }
-                Err(e) => {
-                    last_error = Some(e);
-                    if attempt < self.retry_config.max_retries {
-                        tokio::time::sleep(self.retry_config.retry_delay).await;
-                    }
-                }
+    /// Send one attempt, after injecting faults for its URL
+    #[cfg(feature = "fault-injection")]
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        use rf_core::fault::Fault;
+
+        let Some(ref faults) = self.faults else {
+            return request.send().await.map_err(send_error);
+        };
+        let (client, request) = request.build_split();
+        let request = request.map_err(send_error)?;
+        match faults.inject(request.url().as_str()).await {
+            Ok(()) => client.execute(request).await.map_err(send_error),
+            Err(Fault::Error { status, message }) => {
+                let response = http::Response::builder()
+                    .status(status)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(serde_json::json!({"code": status, "message": message}).to_string())
+                    .map_err(|e| RfError::Internal(format!("Invalid injected response: {}", e)))?;
+                Ok(reqwest::Response::from(response))
             }
+            Err(Fault::Reset) => Err(RfError::Network(format!(
+                "Request failed: connection reset (injected) for {}",
+                request.url()
+            ))),
         }
-        
-        Err(last_error.unwrap_or_else(|| rf_errors::RfError::Network("Request failed after retries".to_string())))
     }
 }
 
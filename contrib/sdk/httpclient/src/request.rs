@@ -0,0 +1,299 @@
+//! # request
+//!
+//! request 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Fluent request builder
+//!
+//! A `ClientRequest` keeps everything needed to rebuild the request, so each
+//! retry attempt sends the same method, headers, query and body, and goes
+//! through the client's circuit breaker.
+
+use crate::HttpClient;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Method, StatusCode};
+use rf_errors::{Result, RfError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Longest error response body quoted in error messages
+const MAX_ERROR_BODY: usize = 512;
+
+/// Request body, kept in a form that can be sent more than once
+#[derive(Debug, Clone)]
+enum RequestBody {
+    None,
+    Bytes { data: Vec<u8>, content_type: Option<&'static str> },
+    Multipart(MultipartForm),
+}
+
+/// A multipart/form-data body
+///
+/// Unlike `reqwest::multipart::Form` it can be cloned, which retries need.
+#[derive(Debug, Clone, Default)]
+pub struct MultipartForm {
+    parts: Vec<MultipartPart>,
+}
+
+#[derive(Debug, Clone)]
+struct MultipartPart {
+    name: String,
+    data: Vec<u8>,
+    file_name: Option<String>,
+    mime: Option<String>,
+}
+
+impl MultipartForm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a text field
+    pub fn text(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.parts.push(MultipartPart {
+            name: name.to_string(),
+            data: value.into().into_bytes(),
+            file_name: None,
+            mime: None,
+        });
+        self
+    }
+
+    /// Add a file field
+    pub fn file(mut self, name: &str, file_name: &str, data: impl Into<Vec<u8>>) -> Self {
+        self.parts.push(MultipartPart {
+            name: name.to_string(),
+            data: data.into(),
+            file_name: Some(file_name.to_string()),
+            mime: None,
+        });
+        self
+    }
+
+    /// Add a file field with an explicit content type
+    pub fn file_with_mime(mut self, name: &str, file_name: &str, mime: &str, data: impl Into<Vec<u8>>) -> Self {
+        self.parts.push(MultipartPart {
+            name: name.to_string(),
+            data: data.into(),
+            file_name: Some(file_name.to_string()),
+            mime: Some(mime.to_string()),
+        });
+        self
+    }
+
+    /// Add a file read from disk, named after the file
+    pub async fn file_path(self, name: &str, path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = tokio::fs::read(path).await?;
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or(name).to_string();
+        Ok(self.file(name, &file_name, data))
+    }
+
+    fn to_form(&self) -> Result<Form> {
+        let mut form = Form::new();
+        for part in &self.parts {
+            let mut body = Part::bytes(part.data.clone());
+            if let Some(file_name) = &part.file_name {
+                body = body.file_name(file_name.clone());
+            }
+            if let Some(mime) = &part.mime {
+                body = body.mime_str(mime)
+                    .map_err(|e| RfError::InvalidParameter(format!("Invalid content type '{}': {}", mime, e)))?;
+            }
+            form = form.part(part.name.clone(), body);
+        }
+        Ok(form)
+    }
+}
+
+/// Request being built by `HttpClient::request`
+pub struct ClientRequest<'a> {
+    client: &'a HttpClient,
+    method: Method,
+    path: String,
+    query: Vec<String>,
+    headers: HeaderMap,
+    body: RequestBody,
+    timeout: Option<Duration>,
+    error: Option<RfError>,
+}
+
+impl<'a> ClientRequest<'a> {
+    pub(crate) fn new(client: &'a HttpClient, method: Method, path: &str) -> Self {
+        Self {
+            client,
+            method,
+            path: path.to_string(),
+            query: Vec::new(),
+            headers: HeaderMap::new(),
+            body: RequestBody::None,
+            timeout: None,
+            error: None,
+        }
+    }
+
+    /// Set a header; invalid names or values fail the request when sent
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = HeaderName::try_from(name)
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid header name '{}': {}", name, e)));
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid header value: {}", e)));
+        match name.and_then(|name| Ok((name, value?))) {
+            Ok((name, value)) => {
+                self.headers.insert(name, value);
+            }
+            Err(e) => self.fail(e),
+        }
+        self
+    }
+
+    /// Set several headers
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Send `Authorization: Bearer <token>`
+    pub fn bearer_auth(self, token: &str) -> Self {
+        self.header(AUTHORIZATION.as_str(), &format!("Bearer {}", token))
+    }
+
+    /// Append query parameters, e.g. `&[("page", "2")]` or a `Serialize` struct
+    pub fn query<T: Serialize + ?Sized>(mut self, params: &T) -> Self {
+        match serde_urlencoded::to_string(params) {
+            Ok(query) if query.is_empty() => {}
+            Ok(query) => self.query.push(query),
+            Err(e) => self.fail(RfError::Serialization(format!("Failed to encode query: {}", e))),
+        }
+        self
+    }
+
+    /// Send `body` as JSON
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Self {
+        match serde_json::to_vec(body) {
+            Ok(data) => self.body = RequestBody::Bytes { data, content_type: Some("application/json") },
+            Err(e) => self.fail(RfError::Serialization(format!("Failed to encode JSON body: {}", e))),
+        }
+        self
+    }
+
+    /// Send `body` as `application/x-www-form-urlencoded`
+    pub fn form<T: Serialize + ?Sized>(mut self, body: &T) -> Self {
+        match serde_urlencoded::to_string(body) {
+            Ok(data) => {
+                self.body = RequestBody::Bytes {
+                    data: data.into_bytes(),
+                    content_type: Some("application/x-www-form-urlencoded"),
+                }
+            }
+            Err(e) => self.fail(RfError::Serialization(format!("Failed to encode form body: {}", e))),
+        }
+        self
+    }
+
+    /// Send `form` as `multipart/form-data`
+    pub fn multipart(mut self, form: MultipartForm) -> Self {
+        self.body = RequestBody::Multipart(form);
+        self
+    }
+
+    /// Send a raw body; set the content type with `header`
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = RequestBody::Bytes { data: body.into(), content_type: None };
+        self
+    }
+
+    /// Time limit for each attempt, from connecting until the body is read
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send the request with retry and circuit breaker
+    pub async fn send(self) -> Result<reqwest::Response> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        if let RequestBody::Multipart(form) = &self.body {
+            // Surface invalid parts once rather than on every attempt
+            form.to_form()?;
+        }
+        self.client.request_path_with_retry(&self.path, |client, url| {
+            let mut url = url.to_string();
+            for query in &self.query {
+                url.push(if url.contains('?') { '&' } else { '?' });
+                url.push_str(query);
+            }
+            let mut request = client.request(self.method.clone(), url).headers(self.headers.clone());
+            if let Some(timeout) = self.timeout {
+                request = request.timeout(timeout);
+            }
+            match &self.body {
+                RequestBody::None => request,
+                RequestBody::Bytes { data, content_type } => {
+                    if let Some(content_type) = content_type.filter(|_| !self.headers.contains_key(CONTENT_TYPE)) {
+                        request = request.header(CONTENT_TYPE, content_type);
+                    }
+                    request.body(data.clone())
+                }
+                RequestBody::Multipart(form) => match form.to_form() {
+                    Ok(form) => request.multipart(form),
+                    Err(_) => request,
+                },
+            }
+        }).await
+    }
+
+    /// Send the request and decode a JSON response
+    ///
+    /// Non-2xx responses become errors: 400/422 `InvalidParameter`, 401
+    /// `Unauthorized`, 403 `Forbidden`, 404 `NotFound`, 408/504 `Timeout`,
+    /// anything else `Network`, each quoting the start of the response body.
+    pub async fn send_json<T: DeserializeOwned>(self) -> Result<T> {
+        let response = check_status(self.send().await?).await?;
+        let body = response.bytes().await.map_err(rf_net::retry::send_error)?;
+        serde_json::from_slice(&body)
+            .map_err(|e| RfError::Serialization(format!("Failed to parse JSON response: {}", e)))
+    }
+
+    /// Send the request and read a text response, failing on non-2xx like `send_json`
+    pub async fn send_text(self) -> Result<String> {
+        let response = check_status(self.send().await?).await?;
+        response.text().await.map_err(rf_net::retry::send_error)
+    }
+
+    /// Keep the first builder error
+    fn fail(&mut self, error: RfError) {
+        self.error.get_or_insert(error);
+    }
+}
+
+/// Turn a non-2xx response into an error
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let url = response.url().to_string();
+    let mut body = response.text().await.unwrap_or_default();
+    if body.len() > MAX_ERROR_BODY {
+        let mut end = MAX_ERROR_BODY;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body.truncate(end);
+    }
+    let message = format!("HTTP {} from {}: {}", status, url, body.trim());
+    Err(match status {
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => RfError::InvalidParameter(message),
+        StatusCode::UNAUTHORIZED => RfError::Unauthorized(message),
+        StatusCode::FORBIDDEN => RfError::Forbidden(message),
+        StatusCode::NOT_FOUND => RfError::NotFound(message),
+        StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => RfError::Timeout(message),
+        _ => RfError::Network(message),
+    })
+}
@@ -0,0 +1,355 @@
+//! HTTP client request builder tests
+
+use axum::extract::{Multipart, Path, Query};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post, put};
+use axum::{Form, Json, Router};
+use rf_contrib_sdk_httpclient::{
+    CircuitBreaker, CircuitState, HttpClient, LoadBalanceStrategy, LoadBalancer, Method, MultipartForm, RetryConfig,
+};
+use rf_errors::RfError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Order {
+    id: u64,
+    qty: u32,
+}
+
+async fn server() -> String {
+    let flaky = Arc::new(AtomicU32::new(0));
+    let app = Router::new()
+        .route("/orders/{id}", put(|Path(id): Path<u64>, Query(query): Query<HashMap<String, String>>, headers: HeaderMap, Json(order): Json<Order>| async move {
+            Json(json!({
+                "id": id,
+                "qty": order.qty,
+                "notify": query.get("notify"),
+                "page": query.get("page"),
+                "auth": headers.get("authorization").map(|v| v.to_str().unwrap().to_string()),
+            }))
+        }))
+        .route("/flaky", post(move |body: String| {
+            let flaky = flaky.clone();
+            async move {
+                // Fails twice, then echoes the body sent on the third attempt
+                match flaky.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => (StatusCode::SERVICE_UNAVAILABLE, String::new()),
+                    _ => (StatusCode::OK, body),
+                }
+            }
+        }))
+        .route("/login", post(|Form(form): Form<HashMap<String, String>>| async move { form["user"].clone() }))
+        .route("/upload", post(|mut multipart: Multipart| async move {
+            let mut fields = Vec::new();
+            while let Some(field) = multipart.next_field().await.unwrap() {
+                let name = field.name().unwrap().to_string();
+                let file = field.file_name().map(str::to_string);
+                let mime = field.content_type().map(str::to_string);
+                let text = field.text().await.unwrap();
+                fields.push(json!({"name": name, "file": file, "mime": mime, "text": text}));
+            }
+            Json(fields)
+        }))
+        .route("/missing", get(|| async { (StatusCode::NOT_FOUND, "no such order") }))
+        .route("/slow", get(|| async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            "late"
+        }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+fn client(base: &str) -> HttpClient {
+    HttpClient::new()
+        .with_base_url(base.to_string())
+        .with_retry(RetryConfig { max_retries: 2, retry_delay: Duration::from_millis(1), ..RetryConfig::default() })
+}
+
+#[tokio::test]
+async fn test_request_builder_json_query_and_headers() {
+    let base = server().await;
+    let client = client(&base);
+
+    let reply: Value = client.request(Method::PUT, "/orders/42?notify=true")
+        .bearer_auth("t0ken")
+        .query(&[("page", 2)])
+        .json(&Order { id: 0, qty: 5 })
+        .send_json()
+        .await
+        .unwrap();
+    assert_eq!(reply, json!({"id": 42, "qty": 5, "notify": "true", "page": "2", "auth": "Bearer t0ken"}));
+
+    // Bodies are resent on retries
+    let echoed = client.request(Method::POST, "/flaky").body("payload").send_text().await.unwrap();
+    assert_eq!(echoed, "payload");
+
+    let user = client.request(Method::POST, "/login").form(&[("user", "ann"), ("pass", "x y")]).send_text().await.unwrap();
+    assert_eq!(user, "ann");
+
+    let fields: Value = client.request(Method::POST, "/upload")
+        .multipart(MultipartForm::new()
+            .text("title", "report")
+            .file_with_mime("file", "a.csv", "text/csv", b"id\n1\n".to_vec()))
+        .send_json()
+        .await
+        .unwrap();
+    assert_eq!(fields, json!([
+        {"name": "title", "file": null, "mime": null, "text": "report"},
+        {"name": "file", "file": "a.csv", "mime": "text/csv", "text": "id\n1\n"},
+    ]));
+}
+
+#[tokio::test]
+async fn test_request_builder_errors() {
+    let base = server().await;
+    let client = client(&base);
+
+    match client.request(Method::GET, "/missing").send_json::<Value>().await {
+        Err(RfError::NotFound(message)) => assert!(message.contains("no such order"), "{}", message),
+        other => panic!("unexpected {:?}", other),
+    }
+    // `send` leaves status handling to the caller
+    assert_eq!(client.request(Method::GET, "/missing").send().await.unwrap().status(), 404);
+
+    let slow = client.request(Method::GET, "/slow").timeout(Duration::from_millis(50)).send_text().await;
+    assert!(matches!(slow, Err(RfError::Timeout(_))), "{:?}", slow);
+    assert_eq!(client.request(Method::GET, "/slow").send_text().await.unwrap(), "late");
+
+    let bad = client.request(Method::GET, "/missing").header("bad header", "x").send().await;
+    assert!(matches!(bad, Err(RfError::InvalidParameter(_))));
+    let bad = client.request(Method::POST, "/login").form(&[("user", "ann")]).send_json::<Order>().await;
+    assert!(matches!(bad, Err(RfError::Serialization(_))));
+
+    // Failed attempts count towards the circuit breaker
+    let breaker = Arc::new(CircuitBreaker::new(2, Duration::from_secs(60)));
+    let client = HttpClient::new()
+        .with_base_url("http://127.0.0.1:1".to_string())
+        .with_retry(RetryConfig { max_retries: 1, retry_delay: Duration::from_millis(1), ..RetryConfig::default() })
+        .with_circuit_breaker(breaker);
+    assert!(matches!(client.request(Method::GET, "/").send().await, Err(RfError::Network(m)) if m.starts_with("Request failed")));
+    assert!(matches!(client.request(Method::GET, "/").send().await, Err(RfError::Network(m)) if m == "Circuit breaker is open"));
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_failure_rate_and_probes() {
+    let breaker = Arc::new(
+        CircuitBreaker::new(0, Duration::from_millis(50))
+            .with_name("orders")
+            .rolling_window(4)
+            .failure_rate_threshold(0.5, 4)
+            .success_threshold(2)
+            .half_open_max_calls(1),
+    );
+    let changes = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = changes.clone();
+    breaker.on_state_change(move |change| sink.lock().unwrap().push((change.from, change.to)));
+
+    let fail = || async { Err::<(), _>(RfError::Network("down".to_string())) };
+    // Alternating failures never trip a consecutive count, the window's 50% rate does
+    for round in 0..2 {
+        breaker.call(|| async { Ok(()) }).await.unwrap();
+        assert!(breaker.call(fail).await.is_err());
+        assert_eq!(breaker.state(), if round == 0 { CircuitState::Closed } else { CircuitState::Open });
+    }
+    assert!(matches!(breaker.call(|| async { Ok(()) }).await, Err(RfError::Network(m)) if m == "Circuit breaker is open"));
+
+    // Half-open admits one probe at a time
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let (release, released) = tokio::sync::oneshot::channel::<()>();
+    let probe = tokio::spawn({
+        let breaker = breaker.clone();
+        async move { breaker.call(|| async { released.await.map_err(|e| RfError::Internal(e.to_string())) }).await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    assert!(breaker.call(|| async { Ok(()) }).await.is_err());
+    release.send(()).unwrap();
+    probe.await.unwrap().unwrap();
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    breaker.call(|| async { Ok(()) }).await.unwrap();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    assert_eq!(*changes.lock().unwrap(), [
+        (CircuitState::Closed, CircuitState::Open),
+        (CircuitState::Open, CircuitState::HalfOpen),
+        (CircuitState::HalfOpen, CircuitState::Closed),
+    ]);
+    let metrics = breaker.metrics();
+    assert_eq!((metrics.successes, metrics.failures, metrics.rejected), (4, 2, 2));
+    assert_eq!(metrics.failure_rate, None);
+}
+
+#[test]
+fn test_load_balancer_strategies() {
+    let weighted = LoadBalancer::weighted(
+        vec![("http://a".to_string(), 5), ("http://b".to_string(), 1), ("http://c".to_string(), 1)],
+        LoadBalanceStrategy::Weighted,
+    );
+    let picks: Vec<String> = (0..7).map(|_| weighted.next().unwrap().replace("http://", "")).collect();
+    assert_eq!(picks, ["a", "a", "b", "a", "c", "a", "a"]);
+
+    let least = LoadBalancer::new(vec!["http://a".to_string(), "http://b".to_string()], LoadBalanceStrategy::LeastConnections);
+    let first = least.pick().unwrap();
+    let second = least.pick().unwrap();
+    assert_ne!(first.url(), second.url());
+    let busy = first.url().to_string();
+    drop(second);
+    // The endpoint still holding a request is avoided until it finishes
+    for _ in 0..3 {
+        assert_ne!(least.next().unwrap(), busy);
+    }
+    let other = least.pick().unwrap();
+    assert_eq!((least.connections(&busy), least.connections(other.url())), (1, 1));
+
+    // Replacing endpoints keeps in-flight counts of the ones that remain
+    least.set_endpoints(vec![(busy.clone(), 1), ("http://c".to_string(), 1)]);
+    assert_eq!(least.connections(&busy), 1);
+    assert_eq!(least.next().as_deref(), Some("http://c"));
+    drop((first, other));
+    assert_eq!(least.connections(&busy), 0);
+
+    let empty = LoadBalancer::new(Vec::new(), LoadBalanceStrategy::RoundRobin);
+    assert!(empty.next().is_none());
+}
+
+#[tokio::test]
+async fn test_client_without_endpoints() {
+    let client = HttpClient::new().with_load_balancer(LoadBalancer::new(Vec::new(), LoadBalanceStrategy::RoundRobin));
+    assert!(matches!(client.get("/orders").await, Err(RfError::Network(m)) if m.contains("No endpoint available")));
+}
+
+#[cfg(feature = "fault-injection")]
+mod faults {
+    use super::*;
+    use rf_core::fault::{FaultInjector, FaultRule};
+
+    #[tokio::test]
+    async fn test_injected_faults() {
+        let base = server().await;
+        let faults = FaultInjector::new()
+            .rule(FaultRule::new("*/orders/1*").error_rate(1.0).error_status(502).times(2))
+            .rule(FaultRule::new("*/orders/2*").reset_rate(1.0));
+        let client = client(&base).with_faults(faults.clone());
+
+        // Injected 502s are retried like real ones
+        let order: Value = client.request(Method::PUT, "/orders/1").json(&Order { id: 1, qty: 2 }).send_json().await.unwrap();
+        assert_eq!(order["qty"], 2);
+        assert_eq!(faults.injected(), 2);
+
+        // Resets fail every attempt and count against the breaker
+        let breaker = Arc::new(CircuitBreaker::new(3, Duration::from_secs(60)));
+        let client = client.with_circuit_breaker(breaker.clone());
+        let result = client.request(Method::PUT, "/orders/2").json(&Order { id: 2, qty: 1 }).send().await;
+        assert!(matches!(result, Err(RfError::Network(m)) if m.contains("connection reset")));
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(faults.injected(), 5);
+
+        faults.clear();
+        assert!(matches!(client.get("/missing").await, Err(RfError::Network(m)) if m == "Circuit breaker is open"));
+        assert_eq!(faults.injected(), 5);
+    }
+}
+
+#[cfg(feature = "discovery")]
+mod discovery {
+    use super::*;
+    use rf_contrib_registry::{ServiceHealth, ServiceInstance, ServiceRegistry};
+    use rf_contrib_sdk_httpclient::DiscoveryLoadBalancer;
+    use std::net::SocketAddr;
+    use std::sync::Mutex;
+
+    type Callback = Box<dyn Fn(Vec<ServiceInstance>) -> rf_errors::Result<()> + Send + Sync>;
+
+    #[derive(Default)]
+    struct FakeRegistry {
+        instances: Mutex<Vec<ServiceInstance>>,
+        watcher: Mutex<Option<Callback>>,
+    }
+
+    impl ServiceRegistry for FakeRegistry {
+        fn register(&self, instance: &ServiceInstance) -> rf_errors::Result<()> {
+            self.instances.lock().unwrap().push(instance.clone());
+            Ok(())
+        }
+
+        fn deregister(&self, service_id: &str) -> rf_errors::Result<()> {
+            self.instances.lock().unwrap().retain(|instance| instance.id != service_id);
+            Ok(())
+        }
+
+        fn discover(&self, service_name: &str) -> rf_errors::Result<Vec<ServiceInstance>> {
+            Ok(self.instances.lock().unwrap().iter().filter(|i| i.name == service_name).cloned().collect())
+        }
+
+        fn list_services(&self) -> rf_errors::Result<Vec<String>> {
+            Ok(self.instances.lock().unwrap().iter().map(|i| i.name.clone()).collect())
+        }
+
+        fn watch<F>(&self, _service_name: &str, callback: F) -> rf_errors::Result<()>
+        where
+            F: Fn(Vec<ServiceInstance>) -> rf_errors::Result<()> + Send + Sync + 'static,
+        {
+            *self.watcher.lock().unwrap() = Some(Box::new(callback));
+            Ok(())
+        }
+    }
+
+    async fn named_server(name: &'static str) -> SocketAddr {
+        let app = Router::new().route("/name", get(move || async move { name }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    fn instance(id: &str, address: SocketAddr, health: ServiceHealth, weight: &str) -> ServiceInstance {
+        ServiceInstance {
+            id: id.to_string(),
+            name: "names".to_string(),
+            address,
+            metadata: HashMap::from([("weight".to_string(), weight.to_string())]),
+            health,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_discovery_load_balancer() {
+        let (a, b) = (named_server("a").await, named_server("b").await);
+        let registry = Arc::new(FakeRegistry::default());
+        registry.register(&instance("a", a, ServiceHealth::Healthy, "3")).unwrap();
+        registry.register(&instance("b", b, ServiceHealth::Healthy, "1")).unwrap();
+        registry.register(&instance("down", "127.0.0.1:1".parse().unwrap(), ServiceHealth::Unhealthy, "10")).unwrap();
+
+        let discovery = DiscoveryLoadBalancer::new(registry.clone(), "names", LoadBalanceStrategy::Weighted)
+            .refresh_interval(Duration::from_millis(20))
+            .start()
+            .await
+            .unwrap();
+        assert_eq!(discovery.balancer().endpoints(), [format!("http://{}", a), format!("http://{}", b)]);
+
+        let client = HttpClient::new().with_load_balancer(discovery.balancer());
+        let mut names = Vec::new();
+        for _ in 0..4 {
+            names.push(client.request(Method::GET, "/name").send_text().await.unwrap());
+        }
+        assert_eq!(names, ["a", "a", "b", "a"]);
+
+        // Pushed by the registry watch
+        let watcher = registry.watcher.lock().unwrap().take().unwrap();
+        watcher(vec![instance("b", b, ServiceHealth::Healthy, "1")]).unwrap();
+        assert_eq!(client.request(Method::GET, "/name").send_text().await.unwrap(), "b");
+
+        // Picked up by the periodic refresh
+        registry.deregister("b").unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(discovery.balancer().endpoints(), [format!("http://{}", a)]);
+        assert_eq!(discovery.refresh().await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,185 @@
+//! # bulk
+//!
+//! bulk 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Bulk API request builder and response
+
+use rf_errors::{Result, RfError};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// One bulk operation
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkOperation {
+    /// Create or replace a document
+    Index { index: Option<String>, id: Option<String>, document: Value },
+    /// Create a document, failing if it exists
+    Create { index: Option<String>, id: String, document: Value },
+    /// Partial update
+    Update { index: Option<String>, id: String, document: Value },
+    /// Delete a document
+    Delete { index: Option<String>, id: String },
+}
+
+/// Bulk request
+///
+/// Operations without an explicit index use the index passed to
+/// `SearchClient::bulk`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BulkRequest {
+    operations: Vec<BulkOperation>,
+}
+
+impl BulkRequest {
+    /// Create an empty bulk request
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index a document
+    pub fn index<T: Serialize>(mut self, id: Option<&str>, document: &T) -> Result<Self> {
+        self.operations.push(BulkOperation::Index {
+            index: None,
+            id: id.map(str::to_string),
+            document: to_value(document)?,
+        });
+        Ok(self)
+    }
+
+    /// Create a document
+    pub fn create<T: Serialize>(mut self, id: &str, document: &T) -> Result<Self> {
+        self.operations.push(BulkOperation::Create {
+            index: None,
+            id: id.to_string(),
+            document: to_value(document)?,
+        });
+        Ok(self)
+    }
+
+    /// Partially update a document
+    pub fn update<T: Serialize>(mut self, id: &str, document: &T) -> Result<Self> {
+        self.operations.push(BulkOperation::Update {
+            index: None,
+            id: id.to_string(),
+            document: to_value(document)?,
+        });
+        Ok(self)
+    }
+
+    /// Delete a document
+    pub fn delete(mut self, id: &str) -> Self {
+        self.operations.push(BulkOperation::Delete { index: None, id: id.to_string() });
+        self
+    }
+
+    /// Add a prepared operation
+    pub fn operation(mut self, operation: BulkOperation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    /// Number of operations
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Whether the request has no operations
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Operations of the request
+    pub fn operations(&self) -> &[BulkOperation] {
+        &self.operations
+    }
+
+    /// NDJSON body, `prefix` is applied to explicit index names
+    pub fn to_ndjson(&self, prefix: &str) -> String {
+        let mut body = String::new();
+        for operation in &self.operations {
+            let (action, index, id, source) = match operation {
+                BulkOperation::Index { index, id, document } => ("index", index, id.as_deref(), Some(document.clone())),
+                BulkOperation::Create { index, id, document } => ("create", index, Some(id.as_str()), Some(document.clone())),
+                BulkOperation::Update { index, id, document } => ("update", index, Some(id.as_str()), Some(json!({ "doc": document }))),
+                BulkOperation::Delete { index, id } => ("delete", index, Some(id.as_str()), None),
+            };
+            let mut meta = serde_json::Map::new();
+            if let Some(index) = index {
+                meta.insert("_index".to_string(), Value::String(format!("{}{}", prefix, index)));
+            }
+            if let Some(id) = id {
+                meta.insert("_id".to_string(), Value::String(id.to_string()));
+            }
+            body.push_str(&json!({ action: meta }).to_string());
+            body.push('\n');
+            if let Some(source) = source {
+                body.push_str(&source.to_string());
+                body.push('\n');
+            }
+        }
+        body
+    }
+}
+
+fn to_value<T: Serialize>(document: &T) -> Result<Value> {
+    serde_json::to_value(document)
+        .map_err(|e| RfError::Serialization(format!("Failed to serialize document: {}", e)))
+}
+
+/// Result of one bulk operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkItem {
+    pub action: String,
+    #[serde(rename = "_index")]
+    pub index: String,
+    #[serde(rename = "_id")]
+    pub id: Option<String>,
+    pub status: u16,
+    pub result: Option<String>,
+    pub error: Option<Value>,
+}
+
+impl BulkItem {
+    /// Whether the operation succeeded
+    pub fn is_success(&self) -> bool {
+        self.error.is_none() && (200..300).contains(&self.status)
+    }
+}
+
+/// Bulk response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkResponse {
+    pub took: u64,
+    pub errors: bool,
+    pub items: Vec<BulkItem>,
+}
+
+impl BulkResponse {
+    /// Parse a raw `_bulk` response, flattening `{"index": {...}}` items
+    pub(crate) fn from_value(value: Value) -> Result<Self> {
+        let took = value.get("took").and_then(Value::as_u64).unwrap_or(0);
+        let errors = value.get("errors").and_then(Value::as_bool).unwrap_or(false);
+        let mut items = Vec::new();
+        for item in value.get("items").and_then(Value::as_array).into_iter().flatten() {
+            let Some((action, result)) = item.as_object().and_then(|item| item.iter().next()) else {
+                continue;
+            };
+            let mut result = result.clone();
+            if let Some(result) = result.as_object_mut() {
+                result.insert("action".to_string(), Value::String(action.clone()));
+            }
+            items.push(serde_json::from_value(result).map_err(|e| {
+                RfError::Serialization(format!("Invalid bulk response item: {}", e))
+            })?);
+        }
+        Ok(Self { took, errors, items })
+    }
+
+    /// Failed operations
+    pub fn failed(&self) -> impl Iterator<Item = &BulkItem> {
+        self.items.iter().filter(|item| !item.is_success())
+    }
+}
@@ -0,0 +1,382 @@
+//! # client
+//!
+//! client 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Elasticsearch/OpenSearch client
+//!
+//! Uses the REST API shared by Elasticsearch 7+/8 and OpenSearch 1+/2.
+//! Requests are spread over the configured nodes round-robin; a node that
+//! cannot be reached is skipped and the next one is tried.
+//!
+//! ```ignore
+//! let client = SearchClient::new(SearchConfig::new("http://127.0.0.1:9200"))?;
+//!
+//! client.index("articles", Some("1"), &article).await?;
+//! let page = client
+//!     .search::<Article>("articles", Query::match_text("title", "rust"))
+//!     .await?;
+//! for hit in page.hits {
+//!     println!("{} {:?}", hit.id, hit.source);
+//! }
+//! ```
+
+use super::bulk::{BulkRequest, BulkResponse};
+use super::config::SearchConfig;
+use super::query::{Query, SearchRequest};
+use base64::Engine;
+use reqwest::{Method, StatusCode};
+use rf_errors::{Result, RfError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Result of a single document write
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteResponse {
+    #[serde(rename = "_index")]
+    pub index: String,
+    #[serde(rename = "_id")]
+    pub id: String,
+    #[serde(rename = "_version", default)]
+    pub version: u64,
+    /// `created`, `updated`, `deleted`, `noop` or `not_found`
+    pub result: String,
+}
+
+/// One search hit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit<T> {
+    pub index: String,
+    pub id: String,
+    pub score: Option<f64>,
+    pub source: T,
+    pub highlight: HashMap<String, Vec<String>>,
+}
+
+/// Search result page
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResponse<T> {
+    pub took: u64,
+    pub timed_out: bool,
+    /// Total matching documents (may be a lower bound, see `track_total_hits`)
+    pub total: u64,
+    pub max_score: Option<f64>,
+    pub hits: Vec<Hit<T>>,
+    pub aggregations: Option<Value>,
+}
+
+impl<T: DeserializeOwned> SearchResponse<T> {
+    fn from_value(mut value: Value) -> Result<Self> {
+        let hits = value.get_mut("hits").map(Value::take).unwrap_or(Value::Null);
+        // ES 7+ reports `{"value": n, "relation": "eq"}`, older versions a number
+        let total = match hits.get("total") {
+            Some(Value::Object(total)) => total.get("value").and_then(Value::as_u64).unwrap_or(0),
+            Some(total) => total.as_u64().unwrap_or(0),
+            None => 0,
+        };
+        let mut parsed = Vec::new();
+        for hit in hits.get("hits").and_then(Value::as_array).into_iter().flatten() {
+            let source = hit.get("_source").cloned().unwrap_or(Value::Null);
+            parsed.push(Hit {
+                index: hit.get("_index").and_then(Value::as_str).unwrap_or_default().to_string(),
+                id: hit.get("_id").and_then(Value::as_str).unwrap_or_default().to_string(),
+                score: hit.get("_score").and_then(Value::as_f64),
+                source: serde_json::from_value(source).map_err(|e| {
+                    RfError::Serialization(format!("Failed to decode search hit: {}", e))
+                })?,
+                highlight: hit.get("highlight")
+                    .and_then(|h| serde_json::from_value(h.clone()).ok())
+                    .unwrap_or_default(),
+            });
+        }
+        Ok(Self {
+            took: value.get("took").and_then(Value::as_u64).unwrap_or(0),
+            timed_out: value.get("timed_out").and_then(Value::as_bool).unwrap_or(false),
+            total,
+            max_score: hits.get("max_score").and_then(Value::as_f64),
+            hits: parsed,
+            aggregations: value.get_mut("aggregations").map(Value::take),
+        })
+    }
+
+    /// Sources of the hits
+    pub fn documents(self) -> Vec<T> {
+        self.hits.into_iter().map(|hit| hit.source).collect()
+    }
+}
+
+struct Inner {
+    config: SearchConfig,
+    http: reqwest::Client,
+    next: AtomicUsize,
+}
+
+/// Elasticsearch/OpenSearch client
+///
+/// Cloning is cheap; clones share the HTTP connection pool.
+#[derive(Clone)]
+pub struct SearchClient {
+    inner: Arc<Inner>,
+}
+
+impl SearchClient {
+    /// Create a client
+    pub fn new(config: SearchConfig) -> Result<Self> {
+        config.validate()?;
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout_duration())
+            .build()
+            .map_err(|e| RfError::Network(format!("Failed to create search client: {}", e)))?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                config,
+                http,
+                next: AtomicUsize::new(0),
+            }),
+        })
+    }
+
+    /// Client configuration
+    pub fn config(&self) -> &SearchConfig {
+        &self.inner.config
+    }
+
+    /// Index name with the configured prefix
+    pub fn index_name(&self, index: &str) -> String {
+        match &self.inner.config.index_prefix {
+            Some(prefix) => format!("{}{}", prefix, index),
+            None => index.to_string(),
+        }
+    }
+
+    /// Create an index with optional settings and mappings
+    pub async fn create_index(&self, index: &str, body: Option<Value>) -> Result<()> {
+        let path = format!("/{}", self.index_name(index));
+        self.request(Method::PUT, &path, Some(body.unwrap_or_else(|| json!({})))).await?;
+        Ok(())
+    }
+
+    /// Delete an index, returns `false` if it did not exist
+    pub async fn delete_index(&self, index: &str) -> Result<bool> {
+        let path = format!("/{}", self.index_name(index));
+        ok_unless_not_found(self.request(Method::DELETE, &path, None).await)
+    }
+
+    /// Whether an index exists
+    pub async fn index_exists(&self, index: &str) -> Result<bool> {
+        let path = format!("/{}", self.index_name(index));
+        ok_unless_not_found(self.request(Method::HEAD, &path, None).await)
+    }
+
+    /// Make recent writes visible to search
+    pub async fn refresh(&self, index: &str) -> Result<()> {
+        let path = format!("/{}/_refresh", self.index_name(index));
+        self.request(Method::POST, &path, None).await?;
+        Ok(())
+    }
+
+    /// Create or replace a document, the id is generated if `None`
+    pub async fn index<T: Serialize>(&self, index: &str, id: Option<&str>, document: &T) -> Result<WriteResponse> {
+        let body = to_value(document)?;
+        let value = match id {
+            Some(id) => {
+                let path = format!("/{}/_doc/{}", self.index_name(index), encode(id));
+                self.request(Method::PUT, &path, Some(body)).await?
+            }
+            None => {
+                let path = format!("/{}/_doc", self.index_name(index));
+                self.request(Method::POST, &path, Some(body)).await?
+            }
+        };
+        from_value(value)
+    }
+
+    /// Get a document by id
+    pub async fn get<T: DeserializeOwned>(&self, index: &str, id: &str) -> Result<Option<T>> {
+        let path = format!("/{}/_doc/{}", self.index_name(index), encode(id));
+        let value = match self.request(Method::GET, &path, None).await {
+            Ok(value) => value,
+            Err(RfError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if value.get("found").and_then(Value::as_bool) != Some(true) {
+            return Ok(None);
+        }
+        from_value(value.get("_source").cloned().unwrap_or(Value::Null)).map(Some)
+    }
+
+    /// Partially update a document
+    pub async fn update<T: Serialize>(&self, index: &str, id: &str, partial: &T) -> Result<WriteResponse> {
+        let path = format!("/{}/_update/{}", self.index_name(index), encode(id));
+        let value = self.request(Method::POST, &path, Some(json!({ "doc": to_value(partial)? }))).await?;
+        from_value(value)
+    }
+
+    /// Delete a document, returns `false` if it did not exist
+    pub async fn delete(&self, index: &str, id: &str) -> Result<bool> {
+        let path = format!("/{}/_doc/{}", self.index_name(index), encode(id));
+        ok_unless_not_found(self.request(Method::DELETE, &path, None).await)
+    }
+
+    /// Search an index
+    pub async fn search<T: DeserializeOwned>(
+        &self,
+        index: &str,
+        request: impl Into<SearchRequest>,
+    ) -> Result<SearchResponse<T>> {
+        let path = format!("/{}/_search", self.index_name(index));
+        let value = self.request(Method::POST, &path, Some(request.into().to_value())).await?;
+        SearchResponse::from_value(value)
+    }
+
+    /// Count documents matching a query
+    pub async fn count(&self, index: &str, query: Query) -> Result<u64> {
+        let path = format!("/{}/_count", self.index_name(index));
+        let value = self.request(Method::POST, &path, Some(json!({ "query": query.to_value() }))).await?;
+        Ok(value.get("count").and_then(Value::as_u64).unwrap_or(0))
+    }
+
+    /// Run a bulk request against `index`
+    ///
+    /// Per-item failures are reported in the response, not as an error.
+    pub async fn bulk(&self, index: &str, request: &BulkRequest) -> Result<BulkResponse> {
+        if request.is_empty() {
+            return Ok(BulkResponse { took: 0, errors: false, items: Vec::new() });
+        }
+        let prefix = self.inner.config.index_prefix.as_deref().unwrap_or("");
+        let path = format!("/{}/_bulk", self.index_name(index));
+        let value = self.send(Method::POST, &path, Some(Body::NdJson(request.to_ndjson(prefix)))).await?;
+        BulkResponse::from_value(value)
+    }
+
+    /// Send a raw JSON request, `path` starts with `/`
+    pub async fn request(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        self.send(method, path, body.map(Body::Json)).await
+    }
+
+    async fn send(&self, method: Method, path: &str, body: Option<Body>) -> Result<Value> {
+        let urls = &self.inner.config.urls;
+        let start = self.inner.next.fetch_add(1, Ordering::Relaxed);
+        let mut last_error = None;
+        for attempt in 0..urls.len() {
+            let url = format!("{}{}", urls[(start + attempt) % urls.len()], path);
+            let mut builder = self.inner.http.request(method.clone(), &url);
+            builder = self.authorize(builder);
+            builder = match &body {
+                Some(Body::Json(json)) => builder.json(json),
+                Some(Body::NdJson(text)) => builder
+                    .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+                    .body(text.clone()),
+                None => builder,
+            };
+            let response = match builder.send().await {
+                Ok(response) => response,
+                Err(e) if e.is_connect() => {
+                    tracing::warn!("Search node {} unreachable: {}", url, e);
+                    last_error = Some(RfError::Network(format!("Search request failed: {}", e)));
+                    continue;
+                }
+                Err(e) if e.is_timeout() => {
+                    return Err(RfError::Timeout(format!("Search request timed out: {}", e)));
+                }
+                Err(e) => return Err(RfError::Network(format!("Search request failed: {}", e))),
+            };
+            return read_response(response).await;
+        }
+        Err(last_error.unwrap_or_else(|| RfError::Network("No search node available".to_string())))
+    }
+
+    fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let config = &self.inner.config;
+        if let Some(api_key) = &config.api_key {
+            let key = if api_key.contains(':') {
+                base64::engine::general_purpose::STANDARD.encode(api_key)
+            } else {
+                api_key.clone()
+            };
+            builder.header(reqwest::header::AUTHORIZATION, format!("ApiKey {}", key))
+        } else if let Some(username) = &config.username {
+            builder.basic_auth(username, config.password.as_ref())
+        } else {
+            builder
+        }
+    }
+}
+
+enum Body {
+    Json(Value),
+    NdJson(String),
+}
+
+async fn read_response(response: reqwest::Response) -> Result<Value> {
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| RfError::Network(format!("Failed to read search response: {}", e)))?;
+    let value = if text.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_str(&text).unwrap_or(Value::String(text))
+    };
+    if status.is_success() {
+        return Ok(value);
+    }
+
+    let reason = error_reason(&value).unwrap_or_else(|| status.to_string());
+    Err(match status {
+        StatusCode::NOT_FOUND => RfError::NotFound(reason),
+        StatusCode::UNAUTHORIZED => RfError::Unauthorized(reason),
+        StatusCode::FORBIDDEN => RfError::Forbidden(reason),
+        StatusCode::BAD_REQUEST => RfError::InvalidParameter(reason),
+        StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => RfError::Timeout(reason),
+        _ => RfError::Internal(format!("Search request failed ({}): {}", status.as_u16(), reason)),
+    })
+}
+
+/// `type: reason` of an error response
+fn error_reason(value: &Value) -> Option<String> {
+    let error = value.get("error")?;
+    if let Some(error) = error.as_str() {
+        return Some(error.to_string());
+    }
+    let kind = error.get("type").and_then(Value::as_str).unwrap_or("error");
+    let reason = error.get("reason").and_then(Value::as_str).unwrap_or("");
+    Some(format!("{}: {}", kind, reason))
+}
+
+fn ok_unless_not_found(result: Result<Value>) -> Result<bool> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(RfError::NotFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+fn to_value<T: Serialize>(document: &T) -> Result<Value> {
+    serde_json::to_value(document)
+        .map_err(|e| RfError::Serialization(format!("Failed to serialize document: {}", e)))
+}
+
+fn from_value<T: DeserializeOwned>(value: Value) -> Result<T> {
+    serde_json::from_value(value)
+        .map_err(|e| RfError::Serialization(format!("Failed to decode search response: {}", e)))
+}
+
+/// Percent-encode a document id for use in a path segment
+fn encode(id: &str) -> String {
+    let mut encoded = String::with_capacity(id.len());
+    for byte in id.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
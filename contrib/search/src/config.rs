@@ -0,0 +1,139 @@
+//! # config
+//!
+//! config 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Search client configuration
+//!
+//! Named instances are configured under `search.{name}`:
+//!
+//! ```toml
+//! [search.default]
+//! urls = "http://es1:9200,http://es2:9200"
+//! username = "elastic"
+//! password = "changeme"
+//! timeout = 30
+//! index_prefix = "prod_"
+//! ```
+
+use rf_errors::{Result, RfError};
+use rf_os::cfg::RfDuration;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Default node URL
+pub const DEFAULT_URL: &str = "http://127.0.0.1:9200";
+
+/// Connection settings for an Elasticsearch/OpenSearch cluster
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Node URLs, used round-robin
+    pub urls: Vec<String>,
+    /// Basic auth user name
+    pub username: Option<String>,
+    /// Basic auth password
+    pub password: Option<String>,
+    /// API key, sent as `Authorization: ApiKey <key>`
+    pub api_key: Option<String>,
+    /// Request timeout, e.g. `"30s"`; plain numbers are seconds
+    pub timeout: RfDuration,
+    /// Prefix prepended to every index name
+    pub index_prefix: Option<String>,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            urls: vec![DEFAULT_URL.to_string()],
+            username: None,
+            password: None,
+            api_key: None,
+            timeout: RfDuration::from_secs(30),
+            index_prefix: None,
+        }
+    }
+}
+
+impl SearchConfig {
+    /// Create a configuration for a single node
+    pub fn new(url: &str) -> Self {
+        Self {
+            urls: vec![url.trim_end_matches('/').to_string()],
+            ..Self::default()
+        }
+    }
+
+    /// Add another node
+    pub fn url(mut self, url: &str) -> Self {
+        self.urls.push(url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Use basic authentication
+    pub fn basic_auth(mut self, username: &str, password: &str) -> Self {
+        self.username = Some(username.to_string());
+        self.password = Some(password.to_string());
+        self
+    }
+
+    /// Use API key authentication
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Set the request timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout.into();
+        self
+    }
+
+    /// Set the index name prefix
+    pub fn index_prefix(mut self, prefix: &str) -> Self {
+        self.index_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Request timeout as a `Duration`
+    pub fn timeout_duration(&self) -> Duration {
+        self.timeout.into()
+    }
+
+    /// Read the `search.{name}` section of a configuration
+    ///
+    /// `urls` is a comma separated list; missing keys keep their defaults.
+    pub fn from_config(config: &rf_os::cfg::Config, name: &str) -> Result<Self> {
+        let get = |key: &str| config.get(&format!("search.{}.{}", name, key));
+        let mut search = Self::default();
+        if let Some(urls) = get("urls")? {
+            search.urls = urls
+                .split(',')
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty())
+                .collect();
+        }
+        search.username = get("username")?;
+        search.password = get("password")?;
+        search.api_key = get("api_key")?;
+        if let Some(timeout) = config.get_duration(&format!("search.{}.timeout", name))? {
+            search.timeout = timeout.into();
+        }
+        search.index_prefix = get("index_prefix")?;
+        search.validate()?;
+        Ok(search)
+    }
+
+    /// Check that the configuration is usable
+    pub fn validate(&self) -> Result<()> {
+        if self.urls.is_empty() {
+            return Err(RfError::Config("Search config has no urls".to_string()));
+        }
+        if let Some(url) = self.urls.iter().find(|url| !url.starts_with("http://") && !url.starts_with("https://")) {
+            return Err(RfError::Config(format!("Invalid search url: {}", url)));
+        }
+        Ok(())
+    }
+}
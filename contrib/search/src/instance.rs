@@ -0,0 +1,75 @@
+//! # instance
+//!
+//! instance 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Named client instances
+//!
+//! Like `gins::database(name)`, `instance(name)` returns a shared client for
+//! a name, creating it from the `search.{name}` configuration section on
+//! first use. `None` means `"default"`.
+//!
+//! ```ignore
+//! search::set_config(Arc::new(config));
+//!
+//! let default = search::instance(None)?;
+//! let logs = search::instance(Some("logs"))?;
+//! ```
+
+use super::client::SearchClient;
+use super::config::SearchConfig;
+use once_cell::sync::Lazy;
+use rf_errors::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+static INSTANCES: Lazy<Mutex<HashMap<String, SearchClient>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+static CONFIG: Lazy<RwLock<Option<Arc<rf_os::cfg::Config>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Set the configuration instances are created from
+///
+/// Instances that were already created are kept.
+pub fn set_config(config: Arc<rf_os::cfg::Config>) {
+    *CONFIG.write().expect("search config lock poisoned") = Some(config);
+}
+
+/// Get or create a named client
+///
+/// Without a configuration (or without a `search.{name}` section) the
+/// client connects to `http://127.0.0.1:9200`.
+pub fn instance(name: Option<&str>) -> Result<SearchClient> {
+    let name = name.unwrap_or("default");
+    let mut instances = INSTANCES.lock().expect("search instances lock poisoned");
+    if let Some(client) = instances.get(name) {
+        return Ok(client.clone());
+    }
+
+    let config = CONFIG.read().expect("search config lock poisoned").clone();
+    let search = match config {
+        Some(config) => SearchConfig::from_config(&config, name)?,
+        None => SearchConfig::default(),
+    };
+    let client = SearchClient::new(search)?;
+    instances.insert(name.to_string(), client.clone());
+    Ok(client)
+}
+
+/// Register a client under a name, replacing any existing instance
+pub fn register(name: &str, client: SearchClient) {
+    INSTANCES.lock().expect("search instances lock poisoned").insert(name.to_string(), client);
+}
+
+/// Remove a named client
+pub fn remove(name: &str) -> Option<SearchClient> {
+    INSTANCES.lock().expect("search instances lock poisoned").remove(name)
+}
+
+/// Names of the created clients
+pub fn names() -> Vec<String> {
+    let mut names: Vec<String> = INSTANCES.lock().expect("search instances lock poisoned").keys().cloned().collect();
+    names.sort();
+    names
+}
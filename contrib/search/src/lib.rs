@@ -0,0 +1,30 @@
+//! # lib
+//!
+//! lib 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Search module
+//!
+//! Elasticsearch/OpenSearch integration:
+//! - `SearchClient`: async client with typed index/get/search/bulk APIs
+//! - `Query`/`SearchRequest`: query DSL builder
+//! - `instance(name)`: named clients configured under `search.{name}`
+//! - `ModelSync` (feature `model-sync`): index ORM writes
+
+pub mod config;
+pub mod query;
+pub mod bulk;
+pub mod client;
+pub mod instance;
+#[cfg(feature = "model-sync")]
+pub mod sync;
+
+pub use config::*;
+pub use query::*;
+pub use bulk::*;
+pub use client::*;
+pub use instance::*;
+#[cfg(feature = "model-sync")]
+pub use sync::*;
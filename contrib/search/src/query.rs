@@ -0,0 +1,401 @@
+//! # query
+//!
+//! query 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Query DSL builder
+//!
+//! ```ignore
+//! let request = SearchRequest::new(
+//!     Query::bool()
+//!         .must(Query::match_text("title", "rust web"))
+//!         .filter(Query::term("status", "published"))
+//!         .filter(Query::range("views").gte(100)),
+//! )
+//! .sort("created_at", SortOrder::Desc)
+//! .from(0)
+//! .size(20);
+//! ```
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// A query clause
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    MatchAll,
+    Match { field: String, query: Value, operator: Option<String> },
+    MultiMatch { fields: Vec<String>, query: String },
+    Term { field: String, value: Value },
+    Terms { field: String, values: Vec<Value> },
+    Range(RangeQuery),
+    Exists { field: String },
+    Prefix { field: String, value: String },
+    Wildcard { field: String, value: String },
+    Ids { values: Vec<String> },
+    Bool(BoolQuery),
+    /// Raw DSL for clauses the builder does not cover
+    Raw(Value),
+}
+
+impl Query {
+    /// `match_all`
+    pub fn match_all() -> Self {
+        Query::MatchAll
+    }
+
+    /// Full-text `match`
+    pub fn match_text(field: &str, query: impl Into<Value>) -> Self {
+        Query::Match { field: field.to_string(), query: query.into(), operator: None }
+    }
+
+    /// Full-text `match` requiring every term (`operator: and`)
+    pub fn match_all_terms(field: &str, query: &str) -> Self {
+        Query::Match { field: field.to_string(), query: query.into(), operator: Some("and".to_string()) }
+    }
+
+    /// `multi_match` over several fields
+    pub fn multi_match(fields: &[&str], query: &str) -> Self {
+        Query::MultiMatch {
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+            query: query.to_string(),
+        }
+    }
+
+    /// Exact `term`
+    pub fn term(field: &str, value: impl Into<Value>) -> Self {
+        Query::Term { field: field.to_string(), value: value.into() }
+    }
+
+    /// Exact `terms`
+    pub fn terms<V: Into<Value>>(field: &str, values: impl IntoIterator<Item = V>) -> Self {
+        Query::Terms {
+            field: field.to_string(),
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// `range`, bounds are set on the returned builder
+    pub fn range(field: &str) -> RangeQuery {
+        RangeQuery { field: field.to_string(), bounds: Map::new() }
+    }
+
+    /// `exists`
+    pub fn exists(field: &str) -> Self {
+        Query::Exists { field: field.to_string() }
+    }
+
+    /// `prefix`
+    pub fn prefix(field: &str, value: &str) -> Self {
+        Query::Prefix { field: field.to_string(), value: value.to_string() }
+    }
+
+    /// `wildcard`
+    pub fn wildcard(field: &str, value: &str) -> Self {
+        Query::Wildcard { field: field.to_string(), value: value.to_string() }
+    }
+
+    /// `ids`
+    pub fn ids<S: ToString>(ids: impl IntoIterator<Item = S>) -> Self {
+        Query::Ids { values: ids.into_iter().map(|id| id.to_string()).collect() }
+    }
+
+    /// Empty `bool` query, clauses are added on the returned builder
+    pub fn bool() -> BoolQuery {
+        BoolQuery::default()
+    }
+
+    /// Raw query DSL
+    pub fn raw(value: Value) -> Self {
+        Query::Raw(value)
+    }
+
+    /// Query DSL JSON
+    pub fn to_value(&self) -> Value {
+        match self {
+            Query::MatchAll => json!({ "match_all": {} }),
+            Query::Match { field, query, operator } => {
+                let mut body = Map::new();
+                body.insert("query".to_string(), query.clone());
+                if let Some(operator) = operator {
+                    body.insert("operator".to_string(), Value::String(operator.clone()));
+                }
+                json!({ "match": { field: body } })
+            }
+            Query::MultiMatch { fields, query } => {
+                json!({ "multi_match": { "query": query, "fields": fields } })
+            }
+            Query::Term { field, value } => json!({ "term": { field: { "value": value } } }),
+            Query::Terms { field, values } => json!({ "terms": { field: values } }),
+            Query::Range(range) => json!({ "range": { (range.field.clone()): range.bounds } }),
+            Query::Exists { field } => json!({ "exists": { "field": field } }),
+            Query::Prefix { field, value } => json!({ "prefix": { field: { "value": value } } }),
+            Query::Wildcard { field, value } => json!({ "wildcard": { field: { "value": value } } }),
+            Query::Ids { values } => json!({ "ids": { "values": values } }),
+            Query::Bool(query) => query.to_value(),
+            Query::Raw(value) => value.clone(),
+        }
+    }
+}
+
+impl Serialize for Query {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+/// `range` query builder
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeQuery {
+    field: String,
+    bounds: Map<String, Value>,
+}
+
+impl RangeQuery {
+    /// Greater than
+    pub fn gt(mut self, value: impl Into<Value>) -> Self {
+        self.bounds.insert("gt".to_string(), value.into());
+        self
+    }
+
+    /// Greater than or equal
+    pub fn gte(mut self, value: impl Into<Value>) -> Self {
+        self.bounds.insert("gte".to_string(), value.into());
+        self
+    }
+
+    /// Less than
+    pub fn lt(mut self, value: impl Into<Value>) -> Self {
+        self.bounds.insert("lt".to_string(), value.into());
+        self
+    }
+
+    /// Less than or equal
+    pub fn lte(mut self, value: impl Into<Value>) -> Self {
+        self.bounds.insert("lte".to_string(), value.into());
+        self
+    }
+
+    /// Date format of the bounds
+    pub fn format(mut self, format: &str) -> Self {
+        self.bounds.insert("format".to_string(), format.into());
+        self
+    }
+}
+
+impl From<RangeQuery> for Query {
+    fn from(range: RangeQuery) -> Self {
+        Query::Range(range)
+    }
+}
+
+/// `bool` query builder
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BoolQuery {
+    must: Vec<Query>,
+    should: Vec<Query>,
+    filter: Vec<Query>,
+    must_not: Vec<Query>,
+    minimum_should_match: Option<u32>,
+}
+
+impl BoolQuery {
+    /// Clause that must match and contributes to the score
+    pub fn must(mut self, query: impl Into<Query>) -> Self {
+        self.must.push(query.into());
+        self
+    }
+
+    /// Clause that should match
+    pub fn should(mut self, query: impl Into<Query>) -> Self {
+        self.should.push(query.into());
+        self
+    }
+
+    /// Clause that must match, without scoring
+    pub fn filter(mut self, query: impl Into<Query>) -> Self {
+        self.filter.push(query.into());
+        self
+    }
+
+    /// Clause that must not match
+    pub fn must_not(mut self, query: impl Into<Query>) -> Self {
+        self.must_not.push(query.into());
+        self
+    }
+
+    /// Number of `should` clauses that must match
+    pub fn minimum_should_match(mut self, count: u32) -> Self {
+        self.minimum_should_match = Some(count);
+        self
+    }
+
+    fn to_value(&self) -> Value {
+        let mut body = Map::new();
+        for (name, clauses) in [
+            ("must", &self.must),
+            ("should", &self.should),
+            ("filter", &self.filter),
+            ("must_not", &self.must_not),
+        ] {
+            if !clauses.is_empty() {
+                body.insert(name.to_string(), clauses.iter().map(Query::to_value).collect());
+            }
+        }
+        if let Some(count) = self.minimum_should_match {
+            body.insert("minimum_should_match".to_string(), count.into());
+        }
+        json!({ "bool": body })
+    }
+}
+
+impl From<BoolQuery> for Query {
+    fn from(query: BoolQuery) -> Self {
+        Query::Bool(query)
+    }
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+/// Body of a `_search` request
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchRequest {
+    query: Query,
+    from: Option<usize>,
+    size: Option<usize>,
+    sort: Vec<(String, SortOrder)>,
+    source: Option<Vec<String>>,
+    highlight: Vec<String>,
+    aggregations: Map<String, Value>,
+    track_total_hits: Option<bool>,
+}
+
+impl SearchRequest {
+    /// Create a request for a query
+    pub fn new(query: impl Into<Query>) -> Self {
+        Self {
+            query: query.into(),
+            from: None,
+            size: None,
+            sort: Vec::new(),
+            source: None,
+            highlight: Vec::new(),
+            aggregations: Map::new(),
+            track_total_hits: None,
+        }
+    }
+
+    /// Offset of the first hit
+    pub fn from(mut self, from: usize) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Maximum number of hits
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Page of hits, `page` starts at 1
+    pub fn page(self, page: usize, per_page: usize) -> Self {
+        self.from(page.saturating_sub(1) * per_page).size(per_page)
+    }
+
+    /// Add a sort field
+    pub fn sort(mut self, field: &str, order: SortOrder) -> Self {
+        self.sort.push((field.to_string(), order));
+        self
+    }
+
+    /// Only return these `_source` fields
+    pub fn source(mut self, fields: &[&str]) -> Self {
+        self.source = Some(fields.iter().map(|f| f.to_string()).collect());
+        self
+    }
+
+    /// Highlight matches in a field
+    pub fn highlight(mut self, field: &str) -> Self {
+        self.highlight.push(field.to_string());
+        self
+    }
+
+    /// Add a raw aggregation
+    pub fn aggregation(mut self, name: &str, aggregation: Value) -> Self {
+        self.aggregations.insert(name.to_string(), aggregation);
+        self
+    }
+
+    /// Count all matching hits instead of stopping at 10 000
+    pub fn track_total_hits(mut self, enabled: bool) -> Self {
+        self.track_total_hits = Some(enabled);
+        self
+    }
+
+    /// Query of the request
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// Request body JSON
+    pub fn to_value(&self) -> Value {
+        let mut body = Map::new();
+        body.insert("query".to_string(), self.query.to_value());
+        if let Some(from) = self.from {
+            body.insert("from".to_string(), from.into());
+        }
+        if let Some(size) = self.size {
+            body.insert("size".to_string(), size.into());
+        }
+        if !self.sort.is_empty() {
+            let sort = self.sort.iter()
+                .map(|(field, order)| json!({ field: { "order": order.as_str() } }))
+                .collect();
+            body.insert("sort".to_string(), Value::Array(sort));
+        }
+        if let Some(source) = &self.source {
+            body.insert("_source".to_string(), json!(source));
+        }
+        if !self.highlight.is_empty() {
+            let fields: Map<String, Value> = self.highlight.iter()
+                .map(|field| (field.clone(), json!({})))
+                .collect();
+            body.insert("highlight".to_string(), json!({ "fields": fields }));
+        }
+        if !self.aggregations.is_empty() {
+            body.insert("aggs".to_string(), Value::Object(self.aggregations.clone()));
+        }
+        if let Some(track) = self.track_total_hits {
+            body.insert("track_total_hits".to_string(), track.into());
+        }
+        Value::Object(body)
+    }
+}
+
+impl From<Query> for SearchRequest {
+    fn from(query: Query) -> Self {
+        SearchRequest::new(query)
+    }
+}
+
+impl Serialize for SearchRequest {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
@@ -0,0 +1,195 @@
+//! # sync
+//!
+//! sync 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Model sync (feature `model-sync`)
+//!
+//! `ModelSync` keeps a search index in step with a database table: writes
+//! made through it go to the database first and are then indexed, using the
+//! row's `id_field` as document id. Writes made elsewhere can call the
+//! `after_*` hooks directly, and `reindex` rebuilds the index from the table.
+//!
+//! The database write is not rolled back when indexing fails. By default the
+//! failure is logged and the write result returned; in strict mode the
+//! indexing error is returned instead.
+//!
+//! ```ignore
+//! let sync = ModelSync::new(search::instance(None)?, "users");
+//!
+//! sync.insert(&db.model("users"), &user).await?;
+//! sync.delete(db.model("users"), user.id).await?;
+//! sync.reindex(&db.model("users")).await?;
+//! ```
+
+use super::bulk::BulkRequest;
+use super::client::SearchClient;
+use futures_util::StreamExt;
+use rf_database::db::{Model, ParamValue};
+use rf_errors::{Result, RfError};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Indexes ORM writes into a search index
+#[derive(Clone)]
+pub struct ModelSync {
+    client: SearchClient,
+    index: String,
+    id_field: String,
+    strict: bool,
+    batch_size: usize,
+}
+
+impl ModelSync {
+    /// Sync writes into `index`
+    pub fn new(client: SearchClient, index: &str) -> Self {
+        Self {
+            client,
+            index: index.to_string(),
+            id_field: "id".to_string(),
+            strict: false,
+            batch_size: 500,
+        }
+    }
+
+    /// Field holding the document id (default `id`)
+    pub fn id_field(mut self, field: &str) -> Self {
+        self.id_field = field.to_string();
+        self
+    }
+
+    /// Return indexing errors instead of logging them
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Documents per bulk request (default 500)
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Insert a row and index it
+    pub async fn insert<T: Serialize>(&self, model: &Model, data: &T) -> Result<u64> {
+        let affected = model.insert(data).await?;
+        self.after_insert(data).await?;
+        Ok(affected)
+    }
+
+    /// Insert rows and index them
+    pub async fn batch_insert<T: Serialize>(&self, model: &Model, data: &[T]) -> Result<u64> {
+        let affected = model.batch_insert(data).await?;
+        self.after_batch_insert(data).await?;
+        Ok(affected)
+    }
+
+    /// Delete the row with `id` and remove its document
+    pub async fn delete<I>(&self, model: Model, id: I) -> Result<u64>
+    where
+        I: Into<ParamValue> + ToString,
+    {
+        let id_string = id.to_string();
+        // Inlined as a literal: named `where_eq` parameters are not bound on delete
+        let condition = format!("{} = {}", self.id_field, id.into().to_sql_string());
+        let affected = model.r#where(&condition).delete().await?;
+        self.after_delete(&id_string).await?;
+        Ok(affected)
+    }
+
+    /// Index a row written elsewhere
+    pub async fn after_insert<T: Serialize>(&self, data: &T) -> Result<()> {
+        let result = async {
+            let document = to_value(data)?;
+            let id = self.document_id(&document)?;
+            self.client.index(&self.index, Some(&id), &document).await
+        }
+        .await;
+        self.settle(result.map(|_| ()))
+    }
+
+    /// Index rows written elsewhere
+    pub async fn after_batch_insert<T: Serialize>(&self, data: &[T]) -> Result<()> {
+        for chunk in data.chunks(self.batch_size) {
+            let result = self.index_batch(chunk.iter().map(to_value)).await;
+            self.settle(result.map(|_| ()))?;
+        }
+        Ok(())
+    }
+
+    /// Re-index a row updated elsewhere
+    pub async fn after_update<T: Serialize>(&self, data: &T) -> Result<()> {
+        self.after_insert(data).await
+    }
+
+    /// Remove the document of a row deleted elsewhere
+    pub async fn after_delete(&self, id: &str) -> Result<()> {
+        let result = self.client.delete(&self.index, id).await;
+        self.settle(result.map(|_| ()))
+    }
+
+    /// Index every row selected by `model`, returns the number indexed
+    ///
+    /// Rows are streamed, so large tables are not loaded into memory.
+    pub async fn reindex(&self, model: &Model) -> Result<usize> {
+        let mut rows = model.stream(self.batch_size);
+        let mut batch = Vec::with_capacity(self.batch_size);
+        let mut indexed = 0;
+        while let Some(row) = rows.next().await {
+            batch.push(Value::Object(row?));
+            if batch.len() >= self.batch_size {
+                indexed += self.index_batch(batch.drain(..).map(Ok)).await?;
+            }
+        }
+        if !batch.is_empty() {
+            indexed += self.index_batch(batch.into_iter().map(Ok)).await?;
+        }
+        Ok(indexed)
+    }
+
+    /// Bulk-index documents, returns the number indexed successfully
+    async fn index_batch(&self, documents: impl Iterator<Item = Result<Value>>) -> Result<usize> {
+        let mut request = BulkRequest::new();
+        for document in documents {
+            let document = document?;
+            let id = self.document_id(&document)?;
+            request = request.index(Some(&id), &document)?;
+        }
+        let response = self.client.bulk(&self.index, &request).await?;
+        for item in response.failed() {
+            tracing::warn!(
+                "Failed to index {:?} into {}: {:?}",
+                item.id, self.index, item.error
+            );
+        }
+        Ok(response.items.iter().filter(|item| item.is_success()).count())
+    }
+
+    fn document_id(&self, document: &Value) -> Result<String> {
+        match document.get(&self.id_field) {
+            Some(Value::String(id)) => Ok(id.clone()),
+            Some(Value::Number(id)) => Ok(id.to_string()),
+            _ => Err(RfError::InvalidParameter(format!(
+                "Document has no {} field to use as search id",
+                self.id_field
+            ))),
+        }
+    }
+
+    fn settle(&self, result: Result<()>) -> Result<()> {
+        match result {
+            Err(e) if !self.strict => {
+                tracing::warn!("Search sync for {} failed: {}", self.index, e);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+fn to_value<T: Serialize>(data: &T) -> Result<Value> {
+    serde_json::to_value(data)
+        .map_err(|e| RfError::Serialization(format!("Failed to serialize document: {}", e)))
+}
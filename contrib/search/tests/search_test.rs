@@ -0,0 +1,219 @@
+//! Search module tests
+
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rf_contrib_search::{BulkRequest, Query, SearchClient, SearchConfig, SearchRequest, SortOrder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use rf_os::cfg::RfDuration;
+use std::sync::{Arc, Mutex};
+
+/// In-memory stand-in for an Elasticsearch node
+#[derive(Clone, Default)]
+struct Node {
+    docs: Arc<Mutex<BTreeMap<(String, String), Value>>>,
+    last_search: Arc<Mutex<Option<Value>>>,
+    last_auth: Arc<Mutex<Option<String>>>,
+}
+
+async fn put_doc(
+    State(node): State<Node>,
+    Path((index, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(doc): Json<Value>,
+) -> Json<Value> {
+    *node.last_auth.lock().unwrap() = headers.get("authorization").map(|v| v.to_str().unwrap().to_string());
+    let created = node.docs.lock().unwrap().insert((index.clone(), id.clone()), doc).is_none();
+    Json(json!({ "_index": index, "_id": id, "_version": 1, "result": if created { "created" } else { "updated" } }))
+}
+
+async fn get_doc(State(node): State<Node>, Path((index, id)): Path<(String, String)>) -> (StatusCode, Json<Value>) {
+    match node.docs.lock().unwrap().get(&(index.clone(), id.clone())) {
+        Some(doc) => (StatusCode::OK, Json(json!({ "_index": index, "_id": id, "found": true, "_source": doc }))),
+        None => (StatusCode::NOT_FOUND, Json(json!({ "_index": index, "_id": id, "found": false }))),
+    }
+}
+
+async fn delete_doc(State(node): State<Node>, Path((index, id)): Path<(String, String)>) -> (StatusCode, Json<Value>) {
+    match node.docs.lock().unwrap().remove(&(index.clone(), id.clone())) {
+        Some(_) => (StatusCode::OK, Json(json!({ "_index": index, "_id": id, "result": "deleted" }))),
+        None => (StatusCode::NOT_FOUND, Json(json!({ "_index": index, "_id": id, "result": "not_found" }))),
+    }
+}
+
+async fn search(State(node): State<Node>, Path(index): Path<String>, Json(body): Json<Value>) -> Json<Value> {
+    *node.last_search.lock().unwrap() = Some(body);
+    let hits: Vec<Value> = node.docs.lock().unwrap().iter()
+        .filter(|((i, _), _)| *i == index)
+        .map(|((i, id), doc)| json!({ "_index": i, "_id": id, "_score": 1.0, "_source": doc,
+            "highlight": { "title": ["<em>rust</em>"] } }))
+        .collect();
+    Json(json!({ "took": 3, "timed_out": false,
+        "hits": { "total": { "value": hits.len(), "relation": "eq" }, "max_score": 1.0, "hits": hits } }))
+}
+
+async fn bulk(State(node): State<Node>, Path(index): Path<String>, body: Bytes) -> Json<Value> {
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    let mut lines = text.lines();
+    let mut items = Vec::new();
+    while let Some(meta) = lines.next() {
+        let meta: Value = serde_json::from_str(meta).unwrap();
+        let (action, meta) = meta.as_object().unwrap().iter().next().unwrap();
+        let target = meta.get("_index").and_then(Value::as_str).unwrap_or(&index).to_string();
+        let id = meta["_id"].as_str().unwrap().to_string();
+        let status = if action == "delete" {
+            node.docs.lock().unwrap().remove(&(target.clone(), id.clone()));
+            200
+        } else {
+            let doc: Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+            node.docs.lock().unwrap().insert((target.clone(), id.clone()), doc);
+            201
+        };
+        items.push(json!({ action: { "_index": target, "_id": id, "status": status, "result": "created" } }));
+    }
+    Json(json!({ "took": 1, "errors": false, "items": items }))
+}
+
+async fn start_node() -> (Node, String) {
+    let node = Node::default();
+    let app = Router::new()
+        .route("/{index}/_doc/{id}", get(get_doc).put(put_doc).delete(delete_doc))
+        .route("/{index}/_search", post(search))
+        .route("/{index}/_bulk", post(bulk))
+        .with_state(node.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (node, format!("http://{}", addr))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Article {
+    id: i64,
+    title: String,
+}
+
+#[test]
+fn test_query_dsl() {
+    let request = SearchRequest::new(
+        Query::bool()
+            .must(Query::match_text("title", "rust web"))
+            .filter(Query::term("status", "published"))
+            .filter(Query::range("views").gte(100).lt(1000))
+            .must_not(Query::exists("deleted_at")),
+    )
+    .page(3, 20)
+    .sort("created_at", SortOrder::Desc)
+    .source(&["id", "title"]);
+
+    assert_eq!(request.to_value(), json!({
+        "query": { "bool": {
+            "must": [{ "match": { "title": { "query": "rust web" } } }],
+            "filter": [
+                { "term": { "status": { "value": "published" } } },
+                { "range": { "views": { "gte": 100, "lt": 1000 } } }
+            ],
+            "must_not": [{ "exists": { "field": "deleted_at" } }]
+        } },
+        "from": 40,
+        "size": 20,
+        "sort": [{ "created_at": { "order": "desc" } }],
+        "_source": ["id", "title"]
+    }));
+}
+
+#[test]
+fn test_bulk_ndjson_and_config() {
+    let request = BulkRequest::new()
+        .index(Some("1"), &json!({ "a": 1 })).unwrap()
+        .update("2", &json!({ "b": 2 })).unwrap()
+        .delete("3");
+    assert_eq!(
+        request.to_ndjson(""),
+        "{\"index\":{\"_id\":\"1\"}}\n{\"a\":1}\n{\"update\":{\"_id\":\"2\"}}\n{\"doc\":{\"b\":2}}\n{\"delete\":{\"_id\":\"3\"}}\n"
+    );
+
+    let adapter = Arc::new(rf_os::cfg::MemoryConfigAdapter::new());
+    rf_os::cfg::ConfigAdapter::set(&*adapter, "search.logs.urls", "http://es1:9200/, http://es2:9200").unwrap();
+    rf_os::cfg::ConfigAdapter::set(&*adapter, "search.logs.timeout", "5").unwrap();
+    let config = rf_os::cfg::Config::new().adapter(adapter);
+    let search = SearchConfig::from_config(&config, "logs").unwrap();
+    assert_eq!(search.urls, vec!["http://es1:9200", "http://es2:9200"]);
+    assert_eq!(search.timeout, RfDuration::from_secs(5));
+    assert_eq!(SearchConfig::from_config(&config, "other").unwrap(), SearchConfig::default());
+
+    rf_contrib_search::set_config(Arc::new(config));
+    let client = rf_contrib_search::instance(Some("logs")).unwrap();
+    assert_eq!(client.config().timeout, RfDuration::from_secs(5));
+    assert!(rf_contrib_search::names().contains(&"logs".to_string()));
+}
+
+#[tokio::test]
+async fn test_client_roundtrip() {
+    let (node, url) = start_node().await;
+    // The first node is unreachable, requests fail over to the second
+    let client = SearchClient::new(
+        SearchConfig::new("http://127.0.0.1:1").url(&url).basic_auth("elastic", "secret").index_prefix("test_"),
+    )
+    .unwrap();
+
+    let article = Article { id: 1, title: "rust search".to_string() };
+    let written = client.index("articles", Some("1"), &article).await.unwrap();
+    assert_eq!((written.index.as_str(), written.result.as_str()), ("test_articles", "created"));
+    assert_eq!(node.last_auth.lock().unwrap().as_deref(), Some("Basic ZWxhc3RpYzpzZWNyZXQ="));
+
+    assert_eq!(client.get::<Article>("articles", "1").await.unwrap(), Some(article.clone()));
+    assert_eq!(client.get::<Article>("articles", "2").await.unwrap(), None);
+
+    let bulk = BulkRequest::new()
+        .index(Some("2"), &Article { id: 2, title: "web".to_string() }).unwrap()
+        .index(Some("3"), &Article { id: 3, title: "db".to_string() }).unwrap()
+        .delete("3");
+    let response = client.bulk("articles", &bulk).await.unwrap();
+    assert_eq!(response.items.len(), 3);
+    assert_eq!(response.failed().count(), 0);
+
+    let page = client
+        .search::<Article>("articles", Query::match_text("title", "rust"))
+        .await
+        .unwrap();
+    assert_eq!(page.total, 2);
+    assert_eq!(page.hits[0].highlight["title"], vec!["<em>rust</em>"]);
+    assert_eq!(page.documents()[1].title, "web");
+    assert_eq!(node.last_search.lock().unwrap().clone().unwrap()["query"], json!({ "match": { "title": { "query": "rust" } } }));
+
+    assert!(client.delete("articles", "1").await.unwrap());
+    assert!(!client.delete("articles", "1").await.unwrap());
+}
+
+#[cfg(feature = "model-sync")]
+#[tokio::test]
+async fn test_model_sync() {
+    use rf_contrib_search::ModelSync;
+
+    let (node, url) = start_node().await;
+    let dir = std::env::temp_dir().join(format!("rf_search_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let db_url = format!("sqlite://{}?mode=rwc", dir.join("search.db").display());
+    let db = rf_database::Database::new_sqlite(&db_url).await.unwrap();
+    db.raw_execute("CREATE TABLE articles (id INTEGER PRIMARY KEY, title TEXT NOT NULL)").await.unwrap();
+
+    let sync = ModelSync::new(SearchClient::new(SearchConfig::new(&url)).unwrap(), "articles").strict(true);
+    let article = Article { id: 1, title: "rust".to_string() };
+    sync.insert(&db.model("articles").unscoped(), &article).await.unwrap();
+    sync.batch_insert(&db.model("articles").unscoped(), &[Article { id: 2, title: "web".to_string() }]).await.unwrap();
+    assert_eq!(node.docs.lock().unwrap().len(), 2);
+
+    sync.delete(db.model("articles").unscoped(), 1).await.unwrap();
+    assert_eq!(db.model("articles").unscoped().count().await.unwrap(), 1);
+    assert_eq!(node.docs.lock().unwrap().len(), 1);
+
+    node.docs.lock().unwrap().clear();
+    assert_eq!(sync.reindex(&db.model("articles").unscoped()).await.unwrap(), 1);
+    assert_eq!(node.docs.lock().unwrap()[&("articles".to_string(), "2".to_string())]["title"], "web");
+    let _ = std::fs::remove_dir_all(dir);
+}
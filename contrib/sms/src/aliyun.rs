@@ -0,0 +1,177 @@
+//! # aliyun
+//!
+//! aliyun 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Aliyun SMS (Dysmsapi) provider
+
+use super::provider::{DeliveryReport, DeliveryStatus, SendReceipt, SendResult, SmsMessage, SmsProvider};
+use async_trait::async_trait;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use rf_errors::{Result, RfError};
+use serde::Deserialize;
+use sha1::Sha1;
+
+const DEFAULT_ENDPOINT: &str = "https://dysmsapi.aliyuncs.com";
+
+/// Aliyun SMS provider
+pub struct AliyunSms {
+    client: Client,
+    endpoint: String,
+    access_key_id: String,
+    access_key_secret: String,
+    region_id: String,
+}
+
+impl AliyunSms {
+    /// Create a new Aliyun SMS provider
+    pub fn new(access_key_id: &str, access_key_secret: &str) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            access_key_id: access_key_id.to_string(),
+            access_key_secret: access_key_secret.to_string(),
+            region_id: "cn-hangzhou".to_string(),
+        }
+    }
+
+    /// Use a custom API endpoint
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set the region id
+    pub fn region(mut self, region_id: &str) -> Self {
+        self.region_id = region_id.to_string();
+        self
+    }
+
+    /// Build the signed query string for a SendSms request
+    fn signed_query(&self, message: &SmsMessage, timestamp: &str, nonce: &str) -> Result<String> {
+        let template_param: serde_json::Map<String, serde_json::Value> = message.params.iter()
+            .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+            .collect();
+        let template_param = serde_json::to_string(&template_param)
+            .map_err(|e| RfError::Serialization(format!("Failed to encode template params: {}", e)))?;
+
+        let mut params = vec![
+            ("AccessKeyId", self.access_key_id.clone()),
+            ("Action", "SendSms".to_string()),
+            ("Format", "JSON".to_string()),
+            ("PhoneNumbers", message.phone_numbers.join(",")),
+            ("RegionId", self.region_id.clone()),
+            ("SignName", message.sign_name.clone()),
+            ("SignatureMethod", "HMAC-SHA1".to_string()),
+            ("SignatureNonce", nonce.to_string()),
+            ("SignatureVersion", "1.0".to_string()),
+            ("TemplateCode", message.template_id.clone()),
+            ("Timestamp", timestamp.to_string()),
+            ("Version", "2017-05-25".to_string()),
+        ];
+        if !message.params.is_empty() {
+            params.push(("TemplateParam", template_param));
+        }
+        params.sort_by(|a, b| a.0.cmp(b.0));
+
+        let canonical = params.iter()
+            .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let signature = sign(&self.access_key_secret, &canonical)?;
+        Ok(format!("Signature={}&{}", percent_encode(&signature), canonical))
+    }
+}
+
+/// Compute the RPC-style request signature for a canonical query string
+pub fn sign(access_key_secret: &str, canonical_query: &str) -> Result<String> {
+    let string_to_sign = format!("GET&{}&{}", percent_encode("/"), percent_encode(canonical_query));
+    let mut mac = Hmac::<Sha1>::new_from_slice(format!("{}&", access_key_secret).as_bytes())
+        .map_err(|e| RfError::Internal(format!("Invalid signing key: {}", e)))?;
+    mac.update(string_to_sign.as_bytes());
+    Ok(base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes()))
+}
+
+/// RFC 3986 percent-encoding as required by the Aliyun signature
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SendSmsResponse {
+    code: String,
+    message: Option<String>,
+    biz_id: Option<String>,
+    request_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SmsReport {
+    phone_number: String,
+    success: bool,
+    biz_id: String,
+    err_code: Option<String>,
+    err_msg: Option<String>,
+    report_time: Option<String>,
+}
+
+#[async_trait]
+impl SmsProvider for AliyunSms {
+    fn name(&self) -> &str {
+        "aliyun"
+    }
+
+    async fn send(&self, message: &SmsMessage) -> Result<SendResult> {
+        let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let nonce = uuid::Uuid::new_v4().to_string();
+        let url = format!("{}/?{}", self.endpoint, self.signed_query(message, &timestamp, &nonce)?);
+
+        let response: SendSmsResponse = self.client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| RfError::Network(format!("Aliyun SMS request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| RfError::Serialization(format!("Failed to parse Aliyun SMS response: {}", e)))?;
+
+        let success = response.code == "OK";
+        let text = response.message.unwrap_or_default();
+        Ok(SendResult {
+            provider: self.name().to_string(),
+            request_id: response.request_id,
+            receipts: message.phone_numbers.iter().map(|phone| SendReceipt {
+                phone_number: phone.clone(),
+                message_id: response.biz_id.clone(),
+                success,
+                code: response.code.clone(),
+                message: text.clone(),
+            }).collect(),
+        })
+    }
+
+    fn parse_delivery_reports(&self, body: &str) -> Result<Vec<DeliveryReport>> {
+        let reports: Vec<SmsReport> = serde_json::from_str(body)
+            .map_err(|e| RfError::Serialization(format!("Invalid Aliyun SMS report: {}", e)))?;
+        Ok(reports.into_iter().map(|r| DeliveryReport {
+            message_id: r.biz_id,
+            phone_number: r.phone_number,
+            status: if r.success { DeliveryStatus::Delivered } else { DeliveryStatus::Failed },
+            error_code: r.err_code,
+            description: r.err_msg,
+            reported_at: r.report_time,
+        }).collect())
+    }
+}
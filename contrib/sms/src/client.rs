@@ -0,0 +1,123 @@
+//! # client
+//!
+//! client 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! SMS client
+//!
+//! ```ignore
+//! let client = SmsClient::new(Arc::new(AliyunSms::new("key-id", "key-secret")))
+//!     .with_rate_limiter(RateLimiter::new()
+//!         .limit(1, Duration::from_secs(60))
+//!         .limit(10, Duration::from_secs(86400)))
+//!     .sandbox(cfg!(test));
+//!
+//! let message = SmsMessage::new("13800000000", "RF", "SMS_123456").param("code", "1234");
+//! client.send(&message).await?;
+//! ```
+
+use super::limiter::RateLimiter;
+use super::provider::{DeliveryReport, SendReceipt, SendResult, SmsMessage, SmsProvider};
+use super::sandbox::SandboxProvider;
+use rf_errors::{Result, RfError};
+use std::sync::Arc;
+
+/// Receipt code for recipients rejected by the rate limiter
+pub const RATE_LIMITED: &str = "RATE_LIMITED";
+
+/// SMS client with rate limiting and sandbox mode
+pub struct SmsClient {
+    provider: Arc<dyn SmsProvider>,
+    limiter: Option<RateLimiter>,
+    sandbox: Option<Arc<SandboxProvider>>,
+}
+
+impl SmsClient {
+    /// Create a new SMS client for a provider
+    pub fn new(provider: Arc<dyn SmsProvider>) -> Self {
+        Self {
+            provider,
+            limiter: None,
+            sandbox: None,
+        }
+    }
+
+    /// Apply per-number rate limiting
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Enable or disable sandbox mode
+    ///
+    /// In sandbox mode messages are recorded instead of being sent
+    /// through the provider; rate limiting still applies.
+    pub fn sandbox(mut self, enabled: bool) -> Self {
+        self.sandbox = if enabled {
+            Some(Arc::new(SandboxProvider::new()))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Sandbox recorder, if sandbox mode is enabled
+    pub fn sandbox_provider(&self) -> Option<Arc<SandboxProvider>> {
+        self.sandbox.clone()
+    }
+
+    /// Whether sandbox mode is enabled
+    pub fn is_sandbox(&self) -> bool {
+        self.sandbox.is_some()
+    }
+
+    /// Send a template message
+    ///
+    /// Recipients rejected by the rate limiter get a failed receipt with
+    /// code `RATE_LIMITED`; if every recipient is limited nothing is sent
+    /// and `RfError::Forbidden` is returned.
+    pub async fn send(&self, message: &SmsMessage) -> Result<SendResult> {
+        if message.phone_numbers.is_empty() {
+            return Err(RfError::InvalidParameter("SMS message has no recipients".to_string()));
+        }
+
+        let (allowed, limited): (Vec<String>, Vec<String>) = match &self.limiter {
+            Some(limiter) => message.phone_numbers.iter()
+                .cloned()
+                .partition(|phone| limiter.try_acquire(phone)),
+            None => (message.phone_numbers.clone(), Vec::new()),
+        };
+        if allowed.is_empty() {
+            return Err(RfError::Forbidden(format!(
+                "SMS rate limit exceeded for {}",
+                limited.join(",")
+            )));
+        }
+
+        let mut outgoing = message.clone();
+        outgoing.phone_numbers = allowed;
+        let mut result = match &self.sandbox {
+            Some(sandbox) => sandbox.send(&outgoing).await?,
+            None => self.provider.send(&outgoing).await?,
+        };
+
+        for phone in limited {
+            tracing::warn!("SMS to {} rejected by rate limiter", phone);
+            result.receipts.push(SendReceipt {
+                phone_number: phone,
+                message_id: None,
+                success: false,
+                code: RATE_LIMITED.to_string(),
+                message: "rate limit exceeded".to_string(),
+            });
+        }
+        Ok(result)
+    }
+
+    /// Parse a delivery status callback body with the configured provider
+    pub fn parse_delivery_reports(&self, body: &str) -> Result<Vec<DeliveryReport>> {
+        self.provider.parse_delivery_reports(body)
+    }
+}
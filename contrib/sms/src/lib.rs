@@ -0,0 +1,28 @@
+//! # lib
+//!
+//! lib 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! SMS module
+//!
+//! Provides a provider-agnostic SMS sending API:
+//! - `SmsProvider` trait: template messages and delivery report parsing
+//! - Aliyun (Dysmsapi) and Tencent Cloud SMS providers
+//! - Per-number rate limiting
+//! - Sandbox mode that records messages instead of sending them
+
+pub mod provider;
+pub mod aliyun;
+pub mod tencent;
+pub mod limiter;
+pub mod sandbox;
+pub mod client;
+
+pub use provider::*;
+pub use aliyun::*;
+pub use tencent::*;
+pub use limiter::*;
+pub use sandbox::*;
+pub use client::*;
@@ -0,0 +1,102 @@
+//! # limiter
+//!
+//! limiter 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Per-number rate limiting
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Sliding-window rate limiter keyed by phone number
+///
+/// Several windows can be combined, e.g. 1 per minute and 10 per day.
+/// A send is only counted when every window allows it.
+pub struct RateLimiter {
+    limits: Vec<(u32, Duration)>,
+    history: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter without limits
+    pub fn new() -> Self {
+        Self {
+            limits: Vec::new(),
+            history: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Allow at most `max` messages per number within `window`
+    pub fn limit(mut self, max: u32, window: Duration) -> Self {
+        self.limits.push((max, window));
+        self
+    }
+
+    /// Try to record a send for the number, returns false if it is limited
+    pub fn try_acquire(&self, phone_number: &str) -> bool {
+        self.try_acquire_at(phone_number, Instant::now())
+    }
+
+    fn try_acquire_at(&self, phone_number: &str, now: Instant) -> bool {
+        let longest = match self.limits.iter().map(|(_, w)| *w).max() {
+            Some(window) => window,
+            None => return true,
+        };
+        let mut history = match self.history.lock() {
+            Ok(history) => history,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let sent = history.entry(phone_number.to_string()).or_default();
+        while sent.front().is_some_and(|t| now.duration_since(*t) >= longest) {
+            sent.pop_front();
+        }
+
+        let allowed = self.limits.iter().all(|(max, window)| {
+            let count = sent.iter().filter(|t| now.duration_since(**t) < *window).count();
+            count < *max as usize
+        });
+        if allowed {
+            sent.push_back(now);
+        }
+        allowed
+    }
+
+    /// Time until the number may send again, `None` if it is not limited
+    pub fn retry_after(&self, phone_number: &str) -> Option<Duration> {
+        let now = Instant::now();
+        let history = match self.history.lock() {
+            Ok(history) => history,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let sent = history.get(phone_number)?;
+        self.limits.iter()
+            .filter_map(|(max, window)| {
+                let recent: Vec<&Instant> = sent.iter()
+                    .filter(|t| now.duration_since(**t) < *window)
+                    .collect();
+                if recent.len() < *max as usize {
+                    return None;
+                }
+                // The window frees up once the oldest counted send expires
+                let oldest = recent[recent.len() - *max as usize];
+                Some(*window - now.duration_since(*oldest))
+            })
+            .max()
+    }
+
+    /// Forget the history of a number
+    pub fn reset(&self, phone_number: &str) {
+        if let Ok(mut history) = self.history.lock() {
+            history.remove(phone_number);
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
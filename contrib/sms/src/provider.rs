@@ -0,0 +1,106 @@
+//! # provider
+//!
+//! provider 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! SMS provider abstraction
+
+use async_trait::async_trait;
+use rf_errors::Result;
+use serde::{Deserialize, Serialize};
+
+/// Template SMS message
+///
+/// Template parameters keep their insertion order: providers that use
+/// named parameters (Aliyun) send them as an object, providers that use
+/// positional parameters (Tencent) send the values in order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmsMessage {
+    pub phone_numbers: Vec<String>,
+    pub sign_name: String,
+    pub template_id: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl SmsMessage {
+    /// Create a new template message for one phone number
+    pub fn new(phone_number: &str, sign_name: &str, template_id: &str) -> Self {
+        Self {
+            phone_numbers: vec![phone_number.to_string()],
+            sign_name: sign_name.to_string(),
+            template_id: template_id.to_string(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Add another recipient
+    pub fn to(mut self, phone_number: &str) -> Self {
+        self.phone_numbers.push(phone_number.to_string());
+        self
+    }
+
+    /// Add a template parameter
+    pub fn param(mut self, name: &str, value: &str) -> Self {
+        self.params.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Send result for a single phone number
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendReceipt {
+    pub phone_number: String,
+    pub message_id: Option<String>,
+    pub success: bool,
+    pub code: String,
+    pub message: String,
+}
+
+/// Result of a send request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendResult {
+    pub provider: String,
+    pub request_id: Option<String>,
+    pub receipts: Vec<SendReceipt>,
+}
+
+impl SendResult {
+    /// Whether every recipient was accepted by the provider
+    pub fn is_success(&self) -> bool {
+        !self.receipts.is_empty() && self.receipts.iter().all(|r| r.success)
+    }
+}
+
+/// Final delivery status reported by the carrier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    Delivered,
+    Failed,
+    Unknown,
+}
+
+/// Delivery report received through a provider callback
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryReport {
+    pub message_id: String,
+    pub phone_number: String,
+    pub status: DeliveryStatus,
+    pub error_code: Option<String>,
+    pub description: Option<String>,
+    pub reported_at: Option<String>,
+}
+
+/// SMS provider trait
+#[async_trait]
+pub trait SmsProvider: Send + Sync {
+    /// Provider name
+    fn name(&self) -> &str;
+
+    /// Send a template message
+    async fn send(&self, message: &SmsMessage) -> Result<SendResult>;
+
+    /// Parse the body of a delivery status callback
+    fn parse_delivery_reports(&self, body: &str) -> Result<Vec<DeliveryReport>>;
+}
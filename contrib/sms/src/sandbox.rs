@@ -0,0 +1,88 @@
+//! # sandbox
+//!
+//! sandbox 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Sandbox provider
+//!
+//! Records messages in memory instead of sending them, for tests and
+//! local development.
+
+use super::provider::{DeliveryReport, SendReceipt, SendResult, SmsMessage, SmsProvider};
+use async_trait::async_trait;
+use rf_errors::{Result, RfError};
+use std::sync::Mutex;
+
+/// In-memory SMS provider that never contacts a carrier
+pub struct SandboxProvider {
+    sent: Mutex<Vec<SmsMessage>>,
+}
+
+impl SandboxProvider {
+    /// Create a new sandbox provider
+    pub fn new() -> Self {
+        Self {
+            sent: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Messages sent so far
+    pub fn sent(&self) -> Vec<SmsMessage> {
+        self.sent.lock().map(|sent| sent.clone()).unwrap_or_default()
+    }
+
+    /// Messages sent to a phone number
+    pub fn sent_to(&self, phone_number: &str) -> Vec<SmsMessage> {
+        self.sent()
+            .into_iter()
+            .filter(|m| m.phone_numbers.iter().any(|p| p == phone_number))
+            .collect()
+    }
+
+    /// Clear the recorded messages
+    pub fn clear(&self) {
+        if let Ok(mut sent) = self.sent.lock() {
+            sent.clear();
+        }
+    }
+}
+
+impl Default for SandboxProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SmsProvider for SandboxProvider {
+    fn name(&self) -> &str {
+        "sandbox"
+    }
+
+    async fn send(&self, message: &SmsMessage) -> Result<SendResult> {
+        let message_id = uuid::Uuid::new_v4().to_string();
+        self.sent.lock()
+            .map_err(|_| RfError::Internal("Failed to acquire sandbox lock".to_string()))?
+            .push(message.clone());
+        tracing::debug!("Sandbox SMS to {:?} with template {}", message.phone_numbers, message.template_id);
+
+        Ok(SendResult {
+            provider: self.name().to_string(),
+            request_id: Some(message_id.clone()),
+            receipts: message.phone_numbers.iter().map(|phone| SendReceipt {
+                phone_number: phone.clone(),
+                message_id: Some(message_id.clone()),
+                success: true,
+                code: "OK".to_string(),
+                message: "sandbox".to_string(),
+            }).collect(),
+        })
+    }
+
+    fn parse_delivery_reports(&self, body: &str) -> Result<Vec<DeliveryReport>> {
+        serde_json::from_str(body)
+            .map_err(|e| RfError::Serialization(format!("Invalid sandbox SMS report: {}", e)))
+    }
+}
@@ -0,0 +1,214 @@
+//! # tencent
+//!
+//! tencent 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Tencent Cloud SMS provider
+
+use super::provider::{DeliveryReport, DeliveryStatus, SendReceipt, SendResult, SmsMessage, SmsProvider};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use rf_errors::{Result, RfError};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+const DEFAULT_HOST: &str = "sms.tencentcloudapi.com";
+const SERVICE: &str = "sms";
+const CONTENT_TYPE: &str = "application/json; charset=utf-8";
+
+/// Tencent Cloud SMS provider
+pub struct TencentSms {
+    client: Client,
+    host: String,
+    secret_id: String,
+    secret_key: String,
+    sdk_app_id: String,
+    region: String,
+}
+
+impl TencentSms {
+    /// Create a new Tencent Cloud SMS provider
+    pub fn new(secret_id: &str, secret_key: &str, sdk_app_id: &str) -> Self {
+        Self {
+            client: Client::new(),
+            host: DEFAULT_HOST.to_string(),
+            secret_id: secret_id.to_string(),
+            secret_key: secret_key.to_string(),
+            sdk_app_id: sdk_app_id.to_string(),
+            region: "ap-guangzhou".to_string(),
+        }
+    }
+
+    /// Use a custom API host
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = host.to_string();
+        self
+    }
+
+    /// Set the region
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = region.to_string();
+        self
+    }
+
+    /// Build the TC3-HMAC-SHA256 Authorization header for a payload
+    pub fn authorization(&self, payload: &str, timestamp: i64) -> Result<String> {
+        let date = chrono::DateTime::from_timestamp(timestamp, 0)
+            .ok_or_else(|| RfError::InvalidParameter(format!("Invalid timestamp: {}", timestamp)))?
+            .format("%Y-%m-%d")
+            .to_string();
+
+        let canonical_request = format!(
+            "POST\n/\n\ncontent-type:{}\nhost:{}\n\ncontent-type;host\n{}",
+            CONTENT_TYPE,
+            self.host,
+            to_hex(&Sha256::digest(payload.as_bytes()))
+        );
+        let credential_scope = format!("{}/{}/tc3_request", date, SERVICE);
+        let string_to_sign = format!(
+            "TC3-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            credential_scope,
+            to_hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let secret_date = hmac_sha256(format!("TC3{}", self.secret_key).as_bytes(), &date)?;
+        let secret_service = hmac_sha256(&secret_date, SERVICE)?;
+        let secret_signing = hmac_sha256(&secret_service, "tc3_request")?;
+        let signature = to_hex(&hmac_sha256(&secret_signing, &string_to_sign)?);
+
+        Ok(format!(
+            "TC3-HMAC-SHA256 Credential={}/{}, SignedHeaders=content-type;host, Signature={}",
+            self.secret_id, credential_scope, signature
+        ))
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Result<Vec<u8>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .map_err(|e| RfError::Internal(format!("Invalid signing key: {}", e)))?;
+    mac.update(data.as_bytes());
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SendSmsEnvelope {
+    response: SendSmsResponse,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SendSmsResponse {
+    #[serde(default)]
+    send_status_set: Vec<SendStatus>,
+    error: Option<ApiError>,
+    request_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SendStatus {
+    serial_no: String,
+    phone_number: String,
+    code: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ApiError {
+    code: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct StatusReport {
+    sid: String,
+    mobile: String,
+    nationcode: Option<String>,
+    report_status: String,
+    errmsg: Option<String>,
+    description: Option<String>,
+    user_receive_time: Option<String>,
+}
+
+#[async_trait]
+impl SmsProvider for TencentSms {
+    fn name(&self) -> &str {
+        "tencent"
+    }
+
+    async fn send(&self, message: &SmsMessage) -> Result<SendResult> {
+        let payload = serde_json::json!({
+            "PhoneNumberSet": message.phone_numbers,
+            "SmsSdkAppId": self.sdk_app_id,
+            "SignName": message.sign_name,
+            "TemplateId": message.template_id,
+            "TemplateParamSet": message.params.iter().map(|(_, v)| v.as_str()).collect::<Vec<_>>(),
+        })
+        .to_string();
+        let timestamp = chrono::Utc::now().timestamp();
+        let authorization = self.authorization(&payload, timestamp)?;
+
+        let envelope: SendSmsEnvelope = self.client
+            .post(format!("https://{}", self.host))
+            .header("Authorization", authorization)
+            .header("Content-Type", CONTENT_TYPE)
+            .header("Host", &self.host)
+            .header("X-TC-Action", "SendSms")
+            .header("X-TC-Timestamp", timestamp.to_string())
+            .header("X-TC-Version", "2021-01-11")
+            .header("X-TC-Region", &self.region)
+            .body(payload)
+            .send()
+            .await
+            .map_err(|e| RfError::Network(format!("Tencent SMS request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| RfError::Serialization(format!("Failed to parse Tencent SMS response: {}", e)))?;
+
+        let response = envelope.response;
+        if let Some(error) = response.error {
+            return Err(RfError::Network(format!("Tencent SMS error {}: {}", error.code, error.message)));
+        }
+        Ok(SendResult {
+            provider: self.name().to_string(),
+            request_id: response.request_id,
+            receipts: response.send_status_set.into_iter().map(|s| SendReceipt {
+                phone_number: s.phone_number,
+                message_id: Some(s.serial_no).filter(|id| !id.is_empty()),
+                success: s.code.eq_ignore_ascii_case("Ok"),
+                code: s.code,
+                message: s.message,
+            }).collect(),
+        })
+    }
+
+    fn parse_delivery_reports(&self, body: &str) -> Result<Vec<DeliveryReport>> {
+        let reports: Vec<StatusReport> = serde_json::from_str(body)
+            .map_err(|e| RfError::Serialization(format!("Invalid Tencent SMS report: {}", e)))?;
+        Ok(reports.into_iter().map(|r| DeliveryReport {
+            message_id: r.sid,
+            phone_number: match r.nationcode {
+                Some(code) if !code.is_empty() => format!("+{}{}", code, r.mobile),
+                _ => r.mobile,
+            },
+            status: match r.report_status.as_str() {
+                "SUCCESS" => DeliveryStatus::Delivered,
+                "FAIL" => DeliveryStatus::Failed,
+                _ => DeliveryStatus::Unknown,
+            },
+            error_code: r.errmsg,
+            description: r.description,
+            reported_at: r.user_receive_time,
+        }).collect())
+    }
+}
@@ -0,0 +1,56 @@
+//! SMS module tests
+
+use rf_contrib_sms::{
+    AliyunSms, DeliveryStatus, RateLimiter, SmsClient, SmsMessage, SmsProvider, TencentSms, RATE_LIMITED,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_aliyun_signature() {
+    // Example from the Aliyun signature documentation
+    let canonical = "AccessKeyId=testId&Action=SendSms&Format=XML&OutId=123&PhoneNumbers=15300000001\
+        &RegionId=cn-hangzhou&SignName=%E9%98%BF%E9%87%8C%E4%BA%91%E7%9F%AD%E4%BF%A1%E6%B5%8B%E8%AF%95%E4%B8%93%E7%94%A8\
+        &SignatureMethod=HMAC-SHA1&SignatureNonce=45e25e9b-0a6f-4070-8c85-2956eda1b466&SignatureVersion=1.0\
+        &TemplateCode=SMS_71390007&TemplateParam=%7B%22customer%22%3A%22test%22%7D\
+        &Timestamp=2017-07-12T02%3A42%3A19Z&Version=2017-05-25";
+    assert_eq!(rf_contrib_sms::sign("testSecret", canonical).unwrap(), "zJDF+Lrzhj/ThnlvIToysFRq6t4=");
+}
+
+#[test]
+fn test_parse_delivery_reports() {
+    let aliyun = AliyunSms::new("id", "secret");
+    let reports = aliyun.parse_delivery_reports(
+        r#"[{"phone_number":"13800000000","send_time":"2026-01-01 10:00:00","report_time":"2026-01-01 10:00:05",
+            "success":false,"err_code":"MK:0001","err_msg":"blocked","sms_size":"1","biz_id":"123^0","out_id":""}]"#,
+    ).unwrap();
+    assert_eq!(reports[0].status, DeliveryStatus::Failed);
+    assert_eq!(reports[0].message_id, "123^0");
+
+    let tencent = TencentSms::new("id", "key", "1400000000");
+    let reports = tencent.parse_delivery_reports(
+        r#"[{"user_receive_time":"2026-01-01 10:00:05","nationcode":"86","mobile":"13800000000",
+            "report_status":"SUCCESS","errmsg":"DELIVRD","description":"ok","sid":"sid-1"}]"#,
+    ).unwrap();
+    assert_eq!(reports[0].status, DeliveryStatus::Delivered);
+    assert_eq!(reports[0].phone_number, "+8613800000000");
+}
+
+#[tokio::test]
+async fn test_sandbox_and_rate_limit() {
+    let client = SmsClient::new(Arc::new(AliyunSms::new("id", "secret")))
+        .with_rate_limiter(RateLimiter::new().limit(1, Duration::from_secs(60)))
+        .sandbox(true);
+
+    let message = SmsMessage::new("13800000000", "RF", "SMS_1").param("code", "1234");
+    assert!(client.send(&message).await.unwrap().is_success());
+    assert!(client.send(&message).await.is_err());
+
+    let result = client.send(&message.clone().to("13900000000")).await.unwrap();
+    assert_eq!(result.receipts.len(), 2);
+    assert_eq!(result.receipts[1].code, RATE_LIMITED);
+
+    let sandbox = client.sandbox_provider().unwrap();
+    assert_eq!(sandbox.sent_to("13800000000").len(), 1);
+    assert_eq!(sandbox.sent_to("13900000000").len(), 1);
+}
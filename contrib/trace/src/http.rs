@@ -0,0 +1,138 @@
+//! # http
+//!
+//! http 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! HTTP server spans
+//!
+//! `trace_middleware` starts a server span for every request, continuing
+//! the trace from the inbound propagation headers. The span carries the
+//! attributes the sampling policy reads: `http.route` (the matched route
+//! template) at start for per-route rates, and `http.response.status_code`
+//! plus an error status for 5xx responses at the end for tail sampling.
+//! `client.address` is the first `X-Forwarded-For` hop or the peer address,
+//! which is also recorded as `network.peer.address`.
+//!
+//! `TraceMiddleware` implements `rf_net::trace::HttpTracing`. An
+//! `init_tracing_*` pipeline registers it with `rf_net::trace::set_http_tracing`,
+//! so the `rf_net` HTTP server adds it on its own and database and Redis
+//! spans of handlers nest under the request span without extra code.
+//!
+//! ```rust,ignore
+//! use rf_contrib_trace::http::{trace_middleware, TraceMiddleware};
+//!
+//! let router = router.layer(axum::middleware::from_fn_with_state(
+//!     Arc::new(TraceMiddleware::new("shop")),
+//!     trace_middleware,
+//! ));
+//! ```
+
+use crate::sampling::{HTTP_RESPONSE_STATUS_CODE, HTTP_ROUTE, URL_PATH};
+use axum::extract::{ConnectInfo, MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use opentelemetry::trace::{FutureExt, SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::KeyValue;
+use std::borrow::Cow;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Request method attribute
+pub const HTTP_REQUEST_METHOD: &str = "http.request.method";
+
+/// Originating client address attribute
+pub const CLIENT_ADDRESS: &str = "client.address";
+
+/// Socket peer address attribute
+pub const NETWORK_PEER_ADDRESS: &str = "network.peer.address";
+
+/// Tracing middleware configuration
+#[derive(Debug, Clone)]
+pub struct TraceMiddleware {
+    tracer: Cow<'static, str>,
+}
+
+impl TraceMiddleware {
+    /// Spans come from the global tracer provider under this tracer name
+    pub fn new(tracer: impl Into<Cow<'static, str>>) -> Self {
+        Self { tracer: tracer.into() }
+    }
+
+    /// Tracer name spans are created under
+    pub fn tracer(&self) -> &str {
+        &self.tracer
+    }
+
+    /// Middleware for the pipeline started by `init_tracing_otlp`, if one runs
+    ///
+    /// Spans use the service name of the pipeline as tracer name.
+    pub fn global() -> Option<Arc<Self>> {
+        crate::otlp::global_service_name().map(|name| Arc::new(Self::new(name)))
+    }
+}
+
+impl rf_net::trace::HttpTracing for TraceMiddleware {
+    fn trace(&self, request: Request, next: Next) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        Box::pin(trace_middleware(State(Arc::new(self.clone())), request, next))
+    }
+}
+
+/// First `X-Forwarded-For` hop, or the peer address
+fn client_address(request: &Request, peer: Option<SocketAddr>) -> Option<String> {
+    request
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(|hop| hop.trim().to_string())
+        .filter(|hop| !hop.is_empty())
+        .or_else(|| peer.map(|peer| peer.ip().to_string()))
+}
+
+/// Wrap every request in a server span
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc::new(config), trace_middleware)`
+/// as a router layer, so the matched route is known.
+pub async fn trace_middleware(State(config): State<Arc<TraceMiddleware>>, request: Request, next: Next) -> Response {
+    let parent = crate::extract_context_from_headers(request.headers());
+    let method = request.method().as_str().to_string();
+    let path = request.uri().path().to_string();
+    let route = request.extensions().get::<MatchedPath>().map(|matched| matched.as_str().to_string());
+
+    let name = match &route {
+        Some(route) => format!("{} {}", method, route),
+        None => method.clone(),
+    };
+    let mut attributes = vec![KeyValue::new(HTTP_REQUEST_METHOD, method), KeyValue::new(URL_PATH, path)];
+    if let Some(route) = route {
+        attributes.push(KeyValue::new(HTTP_ROUTE, route));
+    }
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+    if let Some(client) = client_address(&request, peer) {
+        attributes.push(KeyValue::new(CLIENT_ADDRESS, client));
+    }
+    if let Some(peer) = peer {
+        attributes.push(KeyValue::new(NETWORK_PEER_ADDRESS, peer.ip().to_string()));
+    }
+    let tracer = opentelemetry::global::tracer(config.tracer.clone());
+    let span = tracer
+        .span_builder(name)
+        .with_kind(SpanKind::Server)
+        .with_attributes(attributes)
+        .start_with_context(&tracer, &parent);
+    let cx = parent.with_span(span);
+
+    let response = next.run(request).with_context(cx.clone()).await;
+    let span = cx.span();
+    let status = response.status();
+    span.set_attribute(KeyValue::new(HTTP_RESPONSE_STATUS_CODE, i64::from(status.as_u16())));
+    if status.is_server_error() {
+        span.set_status(Status::error(status.to_string()));
+    }
+    span.end();
+    response
+}
@@ -7,22 +7,54 @@
 
 //! RF Distributed Tracing Module
 //!
-//! Provides distributed tracing support using OpenTelemetry.
+//! Provides distributed tracing support using OpenTelemetry: an OTLP export
+//! pipeline, per-route and error-biased sampling, an HTTP server span
+//! middleware and trace context propagation over HTTP headers and binary
+//! frames.
 
+pub mod http;
 pub mod otlp;
+pub mod sampling;
 
+pub use http::{trace_middleware, TraceMiddleware};
 pub use otlp::*;
+pub use rf_net::trace::{extract_context_from_binary, inject_context_to_binary, span_context_from_binary, BINARY_CONTEXT_LEN};
+pub use sampling::{RouteSampler, SamplingConfig, TailSampler};
 
 /// Initialize tracing with OpenTelemetry
+///
+/// Exports spans over OTLP when `OTEL_EXPORTER_OTLP_ENDPOINT` (or
+/// `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`) is set, using
+/// `OTEL_EXPORTER_OTLP_PROTOCOL` (`grpc` by default, or `http/protobuf`);
+/// otherwise only logs are printed. See `init_tracing_otlp` for explicit settings.
+///
+/// When the exporter cannot be built, logs are still set up and the error
+/// is logged as a warning through them.
 pub fn init_tracing(service_name: &str) -> rf_errors::Result<()> {
-    // Initialize OpenTelemetry tracer
-    tracing::info!("Initializing OpenTelemetry tracing for service: {}", service_name);
-    
+    let endpoint = ["OTEL_EXPORTER_OTLP_TRACES_ENDPOINT", "OTEL_EXPORTER_OTLP_ENDPOINT"]
+        .iter()
+        .any(|name| std::env::var_os(name).is_some_and(|value| !value.is_empty()));
+    let mut otlp_error = None;
+    if endpoint {
+        let protocol = match std::env::var("OTEL_EXPORTER_OTLP_PROTOCOL") {
+            Ok(protocol) if protocol.starts_with("http") => rf_os::metric_otel::OtlpProtocol::Http,
+            _ => rf_os::metric_otel::OtlpProtocol::Grpc,
+        };
+        let config = OtlpTracingConfig { protocol, ..OtlpTracingConfig::default() }.service_name(service_name);
+        match init_tracing_otlp(&config) {
+            Ok(()) => return Ok(()),
+            Err(e) => otlp_error = Some(e),
+        }
+    }
+
     // Set up basic tracing subscriber if not already initialized
-    let _ = tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .try_init();
-    
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+    let _ = tracing_subscriber::fmt().with_env_filter(filter).try_init();
+    if let Some(e) = otlp_error {
+        tracing::warn!("OTLP tracing for {} is off: {}", service_name, e);
+    }
+
     Ok(())
 }
 
@@ -6,40 +6,363 @@
 //! @date 2026-01-06
 
 //! OpenTelemetry Protocol (OTLP) exporters
+//!
+//! `OtlpTracing::start` builds the tracing pipeline: an OTLP span exporter
+//! (gRPC or HTTP protobuf) behind a batch span processor, a parent-based
+//! ratio sampler and a resource with `service.name` and the configured
+//! attributes. It installs the provider and the W3C trace context
+//! propagator globally; `init_tracing_otlp` also routes `tracing` spans into
+//! it. Shutting down exports the spans still queued.
+//!
+//! Settings come from the `tracing.otlp` configuration section:
+//!
+//! ```toml
+//! [tracing.otlp]
+//! protocol = "grpc"                 # or "http"
+//! endpoint = "http://otel-collector:4317"
+//! service_name = "orders"
+//! sample_rate = 0.1                 # root spans; children follow their parent
+//! scheduled_delay = "5s"
+//!
+//! [tracing.otlp.resource]
+//! "deployment.environment" = "prod"
+//! ```
+//!
+//! ```rust,ignore
+//! init_tracing_otlp(&OtlpTracingConfig::from_config(&config)?)?;
+//! // ...
+//! shutdown_tracing()?; // before exit, or the last spans are lost
+//! ```
+//!
+//! For per-route rates and keeping failed traces, pass a `SamplingConfig`
+//! with `OtlpTracingConfig::sampling` instead of a plain rate. gRPC export
+//! runs on the tokio runtime, so start it inside one.
+
+use crate::sampling::SamplingConfig;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig, WithHttpConfig, WithTonicConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{BatchConfigBuilder, BatchSpanProcessor, Sampler, SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use parking_lot::Mutex;
+use rf_errors::{Result, RfError};
+use rf_os::metric_otel::OtlpProtocol;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+fn default_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn default_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_scheduled_delay() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_max_queue_size() -> usize {
+    2048
+}
+
+fn default_max_export_batch_size() -> usize {
+    512
+}
+
+/// OTLP tracing settings, usually the `tracing.otlp` configuration section
+#[derive(Debug, Clone, Deserialize)]
+pub struct OtlpTracingConfig {
+    #[serde(default)]
+    pub protocol: OtlpProtocol,
+    /// Collector endpoint; the exporter's default (or `OTEL_EXPORTER_OTLP_ENDPOINT`) when unset.
+    /// For HTTP the full URL of the traces path, e.g. `http://collector:4318/v1/traces`
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Export timeout (default 10s)
+    #[serde(default = "default_timeout", with = "rf_os::cfg::serde_duration")]
+    pub timeout: Duration,
+    /// `service.name` resource attribute
+    #[serde(default)]
+    pub service_name: Option<String>,
+    /// Further resource attributes, e.g. `deployment.environment`
+    #[serde(default)]
+    pub resource: BTreeMap<String, String>,
+    /// Headers (HTTP) or metadata (gRPC) sent with every export, e.g. credentials
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Share of root spans sampled, 0.0 to 1.0 (default 1.0); child spans follow their parent
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: f64,
+    /// Delay between batch exports (default 5s)
+    #[serde(default = "default_scheduled_delay", with = "rf_os::cfg::serde_duration")]
+    pub scheduled_delay: Duration,
+    /// Spans queued for export before new ones are dropped (default 2048)
+    #[serde(default = "default_max_queue_size")]
+    pub max_queue_size: usize,
+    /// Spans per export request (default 512)
+    #[serde(default = "default_max_export_batch_size")]
+    pub max_export_batch_size: usize,
+    /// Route and tail sampling replacing `sample_rate`
+    #[serde(skip)]
+    pub sampling: Option<SamplingConfig>,
+}
+
+impl Default for OtlpTracingConfig {
+    fn default() -> Self {
+        Self {
+            protocol: OtlpProtocol::default(),
+            endpoint: None,
+            timeout: default_timeout(),
+            service_name: None,
+            resource: BTreeMap::new(),
+            headers: BTreeMap::new(),
+            sample_rate: default_sample_rate(),
+            scheduled_delay: default_scheduled_delay(),
+            max_queue_size: default_max_queue_size(),
+            max_export_batch_size: default_max_export_batch_size(),
+            sampling: None,
+        }
+    }
+}
+
+impl OtlpTracingConfig {
+    /// Export to `endpoint` over `protocol`
+    pub fn new(protocol: OtlpProtocol, endpoint: &str) -> Self {
+        Self { protocol, endpoint: Some(endpoint.to_string()), ..Self::default() }
+    }
+
+    /// Read the `tracing.otlp` section; unset keys keep their defaults
+    pub fn from_config(config: &rf_os::cfg::Config) -> Result<Self> {
+        config.get_struct("tracing.otlp")
+    }
+
+    pub fn service_name(mut self, name: &str) -> Self {
+        self.service_name = Some(name.to_string());
+        self
+    }
+
+    /// Add a resource attribute
+    pub fn resource(mut self, key: &str, value: &str) -> Self {
+        self.resource.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Add a header sent with every export
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = rate;
+        self
+    }
 
-use rf_errors::Result;
+    pub fn scheduled_delay(mut self, delay: Duration) -> Self {
+        self.scheduled_delay = delay;
+        self
+    }
+
+    /// Sample with `sampling` instead of `sample_rate`
+    pub fn sampling(mut self, sampling: SamplingConfig) -> Self {
+        self.sampling = Some(sampling);
+        self
+    }
+
+    /// Parent-based sampler of `sample_rate`
+    pub fn sampler(&self) -> Sampler {
+        let root = match self.sample_rate.clamp(0.0, 1.0) {
+            rate if rate >= 1.0 => Sampler::AlwaysOn,
+            rate if rate <= 0.0 => Sampler::AlwaysOff,
+            rate => Sampler::TraceIdRatioBased(rate),
+        };
+        Sampler::ParentBased(Box::new(root))
+    }
+
+    fn build_resource(&self) -> Resource {
+        let mut builder = Resource::builder();
+        if let Some(name) = &self.service_name {
+            builder = builder.with_service_name(name.clone());
+        }
+        builder
+            .with_attributes(self.resource.iter().map(|(key, value)| KeyValue::new(key.clone(), value.clone())))
+            .build()
+    }
+
+    fn build_exporter(&self) -> Result<SpanExporter> {
+        let exporter = match self.protocol {
+            OtlpProtocol::Grpc => {
+                if tokio::runtime::Handle::try_current().is_err() {
+                    return Err(RfError::Config("OTLP gRPC export must be started inside a tokio runtime".to_string()));
+                }
+                let mut builder = SpanExporter::builder().with_tonic().with_timeout(self.timeout);
+                if let Some(endpoint) = &self.endpoint {
+                    builder = builder.with_endpoint(endpoint.clone());
+                }
+                if !self.headers.is_empty() {
+                    let mut headers = http::HeaderMap::new();
+                    for (name, value) in &self.headers {
+                        let name = http::HeaderName::from_bytes(name.as_bytes())
+                            .map_err(|e| RfError::Config(format!("Invalid OTLP header name '{}': {}", name, e)))?;
+                        let value = http::HeaderValue::from_str(value)
+                            .map_err(|e| RfError::Config(format!("Invalid OTLP header value for '{}': {}", name, e)))?;
+                        headers.insert(name, value);
+                    }
+                    builder = builder.with_metadata(opentelemetry_otlp::tonic_types::metadata::MetadataMap::from_headers(headers));
+                }
+                builder.build()
+            }
+            OtlpProtocol::Http => {
+                let mut builder = SpanExporter::builder()
+                    .with_http()
+                    .with_protocol(Protocol::HttpBinary)
+                    .with_timeout(self.timeout)
+                    .with_headers(self.headers.clone().into_iter().collect());
+                if let Some(endpoint) = &self.endpoint {
+                    builder = builder.with_endpoint(endpoint.clone());
+                }
+                builder.build()
+            }
+        };
+        exporter.map_err(|e| RfError::Config(format!("Failed to build OTLP span exporter: {}", e)))
+    }
+}
+
+/// Running OTLP tracing pipeline, see the module documentation
+#[derive(Debug)]
+pub struct OtlpTracing {
+    provider: SdkTracerProvider,
+    tracer: SdkTracer,
+    service_name: String,
+}
+
+impl OtlpTracing {
+    /// Build the pipeline and install it as the global tracer provider
+    ///
+    /// # Errors
+    ///
+    /// `RfError::Config` when the exporter cannot be built from `config`
+    pub fn start(config: &OtlpTracingConfig) -> Result<Self> {
+        let batch = BatchConfigBuilder::default()
+            .with_scheduled_delay(config.scheduled_delay)
+            .with_max_queue_size(config.max_queue_size.max(1))
+            .with_max_export_batch_size(config.max_export_batch_size.clamp(1, config.max_queue_size.max(1)))
+            .build();
+        let processor = BatchSpanProcessor::builder(config.build_exporter()?).with_batch_config(batch).build();
+        let builder = SdkTracerProvider::builder().with_resource(config.build_resource());
+        let provider = match &config.sampling {
+            Some(sampling) => sampling.configure(builder, processor),
+            None => builder.with_sampler(config.sampler()).with_span_processor(processor),
+        }
+        .build();
+        opentelemetry::global::set_tracer_provider(provider.clone());
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let service_name = config.service_name.clone().unwrap_or_else(|| "rf".to_string());
+        let tracer = provider.tracer(service_name.clone());
+        tracing::info!(
+            "Exporting OTLP traces over {:?} to {}",
+            config.protocol,
+            config.endpoint.as_deref().unwrap_or("the default endpoint")
+        );
+        Ok(Self { provider, tracer, service_name })
+    }
+
+    pub fn provider(&self) -> &SdkTracerProvider {
+        &self.provider
+    }
+
+    pub fn service_name(&self) -> &str {
+        &self.service_name
+    }
+
+    /// Tracer of the service
+    pub fn tracer(&self) -> &SdkTracer {
+        &self.tracer
+    }
+
+    /// `tracing` layer recording spans into this pipeline
+    pub fn layer<S>(&self) -> tracing_opentelemetry::OpenTelemetryLayer<S, SdkTracer>
+    where
+        S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(self.tracer.clone())
+    }
+
+    /// Export the queued spans now
+    pub fn flush(&self) -> Result<()> {
+        self.provider
+            .force_flush()
+            .map_err(|e| RfError::Network(format!("Failed to export OTLP spans: {}", e)))
+    }
+
+    /// Export the queued spans and stop
+    pub fn shutdown(self) -> Result<()> {
+        self.provider
+            .shutdown()
+            .map_err(|e| RfError::Network(format!("Failed to shut down OTLP tracing: {}", e)))
+    }
+}
+
+static GLOBAL: Mutex<Option<OtlpTracing>> = Mutex::new(None);
+
+/// Start the pipeline and route `tracing` spans and logs through it
+///
+/// Logs are printed as with `init_tracing` (`RUST_LOG`, default `info`);
+/// spans passing the same filter are exported. The pipeline runs until
+/// `shutdown_tracing`. It is registered with `rf_net::trace`, so `rf_net`
+/// HTTP servers trace their requests and `rf_net::trace::shutdown_tracing`
+/// (run by the `rf-frame` shutdown coordinator) stops it.
+pub fn init_tracing_otlp(config: &OtlpTracingConfig) -> Result<()> {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let otlp = OtlpTracing::start(config)?;
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+    if tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(otlp.layer())
+        .try_init()
+        .is_err()
+    {
+        tracing::warn!("A tracing subscriber is already set, only OpenTelemetry API spans are exported");
+    }
+    let tracing = crate::http::TraceMiddleware::new(otlp.service_name.clone());
+    if let Some(previous) = GLOBAL.lock().replace(otlp) {
+        let _ = previous.shutdown();
+    }
+    rf_net::trace::set_http_tracing(Some(std::sync::Arc::new(tracing)));
+    rf_net::trace::set_tracing_shutdown(Some(shutdown_tracing));
+    Ok(())
+}
 
 /// Initialize tracing with OTLP exporter (gRPC)
-/// 
-/// This is a simplified implementation. A full implementation would:
-/// 1. Create OTLP gRPC exporter using opentelemetry_otlp
-/// 2. Create tracer provider with opentelemetry_sdk
-/// 3. Set it as global tracer provider
-/// 4. Initialize tracing subscriber with tracing-opentelemetry
 pub fn init_tracing_otlp_grpc(service_name: &str, endpoint: &str) -> Result<()> {
-    tracing::info!("Initialized OTLP gRPC tracing for service: {} at endpoint: {}", service_name, endpoint);
-    
-    // Note: Full implementation would use opentelemetry_sdk and opentelemetry_otlp
-    // This is a placeholder that logs the initialization
-    // In production, you would configure the OTLP gRPC exporter here
-    
-    Ok(())
+    init_tracing_otlp(&OtlpTracingConfig::new(OtlpProtocol::Grpc, endpoint).service_name(service_name))
 }
 
 /// Initialize tracing with OTLP exporter (HTTP)
-/// 
-/// This is a simplified implementation. A full implementation would:
-/// 1. Create OTLP HTTP exporter using opentelemetry_otlp
-/// 2. Create tracer provider with opentelemetry_sdk
-/// 3. Set it as global tracer provider
-/// 4. Initialize tracing subscriber with tracing-opentelemetry
+///
+/// `endpoint` is the full traces URL, e.g. `http://collector:4318/v1/traces`.
 pub fn init_tracing_otlp_http(service_name: &str, endpoint: &str) -> Result<()> {
-    tracing::info!("Initialized OTLP HTTP tracing for service: {} at endpoint: {}", service_name, endpoint);
-    
-    // Note: Full implementation would use opentelemetry_sdk and opentelemetry_otlp
-    // This is a placeholder that logs the initialization
-    // In production, you would configure the OTLP HTTP exporter here
-    
-    Ok(())
+    init_tracing_otlp(&OtlpTracingConfig::new(OtlpProtocol::Http, endpoint).service_name(service_name))
 }
 
+/// Service name of the running `init_tracing_*` pipeline
+pub(crate) fn global_service_name() -> Option<String> {
+    GLOBAL.lock().as_ref().map(|otlp| otlp.service_name.clone())
+}
+
+/// Export the spans still queued by the `init_tracing_*` pipeline and stop it
+pub fn shutdown_tracing() -> Result<()> {
+    rf_net::trace::set_http_tracing(None);
+    match GLOBAL.lock().take() {
+        Some(otlp) => otlp.shutdown(),
+        None => Ok(()),
+    }
+}
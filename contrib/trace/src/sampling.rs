@@ -0,0 +1,337 @@
+//! # sampling
+//!
+//! sampling 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Route-based and error-biased sampling
+//!
+//! `SamplingConfig` builds both halves of the sampling policy:
+//! - `RouteSampler` is the head sampler. Root spans are sampled at the rate
+//!   of their route (the `http.route` attribute, else `url.path`, else the
+//!   span name); children follow their parent, as with `ParentBased`.
+//! - `TailSampler` wraps the exporting span processor. Spans of traces the
+//!   head sampler dropped are still recorded and held back until the local
+//!   root span ends; if it failed (error status or a 5xx) or was slow, the
+//!   whole trace is exported anyway, otherwise it is discarded.
+//!
+//! The HTTP middleware in [`crate::http`] sets the route, status code and
+//! error status these decisions read.
+//!
+//! ```rust,ignore
+//! use opentelemetry_sdk::trace::{BatchSpanProcessor, SdkTracerProvider};
+//! use rf_contrib_trace::SamplingConfig;
+//!
+//! let provider = SamplingConfig::new(0.05)
+//!     .route("/health", 0.0)
+//!     .route("/api/payments/*", 1.0)
+//!     .slow_threshold(Duration::from_millis(500))
+//!     .configure(SdkTracerProvider::builder(), BatchSpanProcessor::builder(exporter).build())
+//!     .build();
+//! opentelemetry::global::set_tracer_provider(provider);
+//! ```
+
+use opentelemetry::trace::{
+    Link, SamplingDecision, SamplingResult, SpanContext, SpanId, SpanKind, Status, TraceContextExt, TraceId,
+};
+use opentelemetry::{Context, KeyValue, Value};
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{ShouldSample, Span, SpanData, SpanProcessor, TracerProviderBuilder};
+use opentelemetry_sdk::Resource;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Route template attribute
+pub const HTTP_ROUTE: &str = "http.route";
+/// Request path attribute, used when there is no route
+pub const URL_PATH: &str = "url.path";
+/// Response status code attribute
+pub const HTTP_RESPONSE_STATUS_CODE: &str = "http.response.status_code";
+
+/// Sampling policy
+#[derive(Debug, Clone)]
+pub struct SamplingConfig {
+    default_rate: f64,
+    routes: Vec<(String, f64)>,
+    keep_errors: bool,
+    slow_threshold: Option<Duration>,
+    max_pending_traces: usize,
+    max_pending_age: Duration,
+}
+
+impl SamplingConfig {
+    /// Sample root spans at `rate` (0.0 to 1.0) unless a route says otherwise
+    ///
+    /// Traces with errors are kept regardless of the rate; see `keep_errors`.
+    pub fn new(rate: f64) -> Self {
+        Self {
+            default_rate: rate.clamp(0.0, 1.0),
+            routes: Vec::new(),
+            keep_errors: true,
+            slow_threshold: None,
+            max_pending_traces: 10_000,
+            max_pending_age: Duration::from_secs(60),
+        }
+    }
+
+    /// Sampling rate for a route
+    ///
+    /// `pattern` is a route template or path; a trailing `*` matches any
+    /// suffix. The first matching route wins.
+    pub fn route(mut self, pattern: impl Into<String>, rate: f64) -> Self {
+        self.routes.push((pattern.into(), rate.clamp(0.0, 1.0)));
+        self
+    }
+
+    /// Keep traces whose local root failed or any span errored (default: true)
+    pub fn keep_errors(mut self, enabled: bool) -> Self {
+        self.keep_errors = enabled;
+        self
+    }
+
+    /// Keep traces whose local root took at least `threshold`
+    pub fn slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = Some(threshold);
+        self
+    }
+
+    /// Most traces held back at once waiting for their root (default: 10000)
+    ///
+    /// Spans of further traces are dropped until room frees up.
+    pub fn max_pending_traces(mut self, limit: usize) -> Self {
+        self.max_pending_traces = limit;
+        self
+    }
+
+    /// How long spans wait for their root before they are dropped (default: 60s)
+    pub fn max_pending_age(mut self, age: Duration) -> Self {
+        self.max_pending_age = age;
+        self
+    }
+
+    /// Whether unsampled traces are recorded for a tail decision
+    pub fn tail_sampling(&self) -> bool {
+        self.keep_errors || self.slow_threshold.is_some()
+    }
+
+    /// Sampling rate for a route or path
+    pub fn rate_for(&self, route: &str) -> f64 {
+        self.routes
+            .iter()
+            .find(|(pattern, _)| match pattern.strip_suffix('*') {
+                Some(prefix) => route.starts_with(prefix),
+                None => route == pattern,
+            })
+            .map_or(self.default_rate, |(_, rate)| *rate)
+    }
+
+    /// Head sampler for `TracerProviderBuilder::with_sampler`
+    pub fn sampler(&self) -> RouteSampler {
+        RouteSampler {
+            config: Arc::new(self.clone()),
+        }
+    }
+
+    /// Tail sampling processor in front of the exporting `inner` processor
+    pub fn processor<P: SpanProcessor>(&self, inner: P) -> TailSampler<P> {
+        TailSampler {
+            inner,
+            config: Arc::new(self.clone()),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Install the sampler and the tail sampling processor on a provider builder
+    pub fn configure<P: SpanProcessor + 'static>(&self, builder: TracerProviderBuilder, inner: P) -> TracerProviderBuilder {
+        builder.with_sampler(self.sampler()).with_span_processor(self.processor(inner))
+    }
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Head sampler with per-route rates
+#[derive(Debug, Clone)]
+pub struct RouteSampler {
+    config: Arc<SamplingConfig>,
+}
+
+impl ShouldSample for RouteSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        _span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        _links: &[Link],
+    ) -> SamplingResult {
+        let held_back = if self.config.tail_sampling() {
+            SamplingDecision::RecordOnly
+        } else {
+            SamplingDecision::Drop
+        };
+        let parent = parent_context.filter(|cx| cx.has_active_span()).map(|cx| cx.span());
+        let decision = match &parent {
+            Some(span) if span.span_context().is_sampled() => SamplingDecision::RecordAndSample,
+            // A local parent that is not recording was dropped outright
+            Some(span) if !span.span_context().is_remote() && !span.is_recording() => SamplingDecision::Drop,
+            Some(_) => held_back,
+            None => {
+                let route = attribute(attributes, HTTP_ROUTE)
+                    .or_else(|| attribute(attributes, URL_PATH))
+                    .map(|value| value.as_str().into_owned())
+                    .unwrap_or_else(|| name.to_string());
+                if sample_ratio(trace_id, self.config.rate_for(&route)) {
+                    SamplingDecision::RecordAndSample
+                } else {
+                    held_back
+                }
+            }
+        };
+        SamplingResult {
+            decision,
+            attributes: Vec::new(),
+            trace_state: parent
+                .map(|span| span.span_context().trace_state().clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Same decision for the same trace ID on every service, like `TraceIdRatioBased`
+fn sample_ratio(trace_id: TraceId, rate: f64) -> bool {
+    if rate >= 1.0 {
+        return true;
+    }
+    if rate <= 0.0 {
+        return false;
+    }
+    let bytes = trace_id.to_bytes();
+    let low = u64::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11], bytes[12], bytes[13], bytes[14], bytes[15]]);
+    (low >> 1) < (rate * (1u64 << 63) as f64) as u64
+}
+
+fn attribute<'a>(attributes: &'a [KeyValue], key: &str) -> Option<&'a Value> {
+    attributes.iter().find(|kv| kv.key.as_str() == key).map(|kv| &kv.value)
+}
+
+/// Tail sampling span processor
+///
+/// Sampled spans pass straight through to the inner processor. Recorded but
+/// unsampled spans are buffered per trace until the local root ends, then
+/// forwarded marked as sampled if the trace is worth keeping.
+#[derive(Debug)]
+pub struct TailSampler<P> {
+    inner: P,
+    config: Arc<SamplingConfig>,
+    pending: Mutex<HashMap<TraceId, Pending>>,
+}
+
+#[derive(Debug)]
+struct Pending {
+    spans: Vec<SpanData>,
+    since: Instant,
+    error: bool,
+}
+
+impl<P> TailSampler<P> {
+    /// Traces currently waiting for their root span
+    pub fn pending_traces(&self) -> usize {
+        self.pending.lock().len()
+    }
+
+    fn is_error(&self, span: &SpanData) -> bool {
+        self.config.keep_errors
+            && (matches!(span.status, Status::Error { .. })
+                || attribute(&span.attributes, HTTP_RESPONSE_STATUS_CODE)
+                    .and_then(|value| match value {
+                        Value::I64(code) => Some(*code),
+                        Value::String(code) => code.as_str().parse().ok(),
+                        _ => None,
+                    })
+                    .is_some_and(|code| code >= 500))
+    }
+
+    fn is_slow(&self, span: &SpanData) -> bool {
+        self.config.slow_threshold.is_some_and(|threshold| {
+            span.end_time.duration_since(span.start_time).is_ok_and(|elapsed| elapsed >= threshold)
+        })
+    }
+}
+
+impl<P: SpanProcessor> SpanProcessor for TailSampler<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx);
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if span.span_context.is_sampled() {
+            self.inner.on_end(span);
+            return;
+        }
+
+        let trace_id = span.span_context.trace_id();
+        let error = self.is_error(&span);
+        let local_root = span.parent_span_id == SpanId::INVALID || span.parent_span_is_remote;
+        let mut pending = self.pending.lock();
+        if !local_root {
+            if !pending.contains_key(&trace_id) && pending.len() >= self.config.max_pending_traces {
+                let max_age = self.config.max_pending_age;
+                pending.retain(|_, trace| trace.since.elapsed() < max_age);
+                if pending.len() >= self.config.max_pending_traces {
+                    return;
+                }
+            }
+            let trace = pending.entry(trace_id).or_insert_with(|| Pending {
+                spans: Vec::new(),
+                since: Instant::now(),
+                error: false,
+            });
+            trace.error |= error;
+            trace.spans.push(span);
+            return;
+        }
+
+        let buffered = pending.remove(&trace_id);
+        drop(pending);
+        let keep = error || self.is_slow(&span) || buffered.as_ref().is_some_and(|trace| trace.error);
+        if !keep {
+            return;
+        }
+        for span in buffered.into_iter().flat_map(|trace| trace.spans).chain(std::iter::once(span)) {
+            self.inner.on_end(mark_sampled(span));
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
+
+/// Exporting processors skip unsampled spans
+fn mark_sampled(mut span: SpanData) -> SpanData {
+    let context = &span.span_context;
+    span.span_context = SpanContext::new(
+        context.trace_id(),
+        context.span_id(),
+        context.trace_flags().with_sampled(true),
+        context.is_remote(),
+        context.trace_state().clone(),
+    );
+    span
+}
@@ -0,0 +1,100 @@
+//! HTTP server span tests
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::Request;
+use axum::routing::get;
+use axum::Router;
+use opentelemetry::trace::{SpanKind, TracerProvider};
+use opentelemetry::Value;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{SdkTracerProvider, SpanData, SpanProcessor};
+use rf_contrib_trace::{trace_middleware, TraceMiddleware};
+use rf_net::trace::HttpTracing;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tower::ServiceExt;
+use tracing::Instrument;
+use tracing_subscriber::layer::SubscriberExt;
+
+#[derive(Debug, Clone, Default)]
+struct Collector(Arc<Mutex<Vec<SpanData>>>);
+
+impl SpanProcessor for Collector {
+    fn on_start(&self, _span: &mut opentelemetry_sdk::trace::Span, _cx: &opentelemetry::Context) {}
+
+    fn on_end(&self, span: SpanData) {
+        self.0.lock().unwrap().push(span);
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+        Ok(())
+    }
+}
+
+fn attribute(span: &SpanData, key: &str) -> Option<Value> {
+    span.attributes.iter().find(|kv| kv.key.as_str() == key).map(|kv| kv.value.clone())
+}
+
+#[tokio::test]
+async fn test_client_spans_nest_under_request() {
+    assert!(TraceMiddleware::global().is_none());
+    assert!(rf_net::trace::http_tracing().is_none());
+
+    let collector = Collector::default();
+    let provider = SdkTracerProvider::builder().with_span_processor(collector.clone()).build();
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("shop")));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    // Stands in for a database call made by the handler
+    let app = Router::new()
+        .route(
+            "/orders/{id}",
+            get(|| async {
+                async {}.instrument(tracing::info_span!("db.query", otel.name = "SELECT orders", otel.kind = "client")).await;
+                "order"
+            }),
+        )
+        .layer(axum::middleware::from_fn_with_state(Arc::new(TraceMiddleware::new("shop")), trace_middleware));
+    let peer: SocketAddr = "10.0.0.7:5123".parse().unwrap();
+    let mut request = Request::get("/orders/1").body(Body::empty()).unwrap();
+    request.extensions_mut().insert(ConnectInfo(peer));
+    app.clone().oneshot(request).await.unwrap();
+    let request = Request::get("/orders/2").header("x-forwarded-for", "203.0.113.9, 10.0.0.1").body(Body::empty()).unwrap();
+    app.oneshot(request).await.unwrap();
+
+    let spans = std::mem::take(&mut *collector.0.lock().unwrap());
+    let servers: Vec<&SpanData> = spans.iter().filter(|span| span.span_kind == SpanKind::Server).collect();
+    let clients: Vec<&SpanData> = spans.iter().filter(|span| span.span_kind == SpanKind::Client).collect();
+    assert_eq!((servers.len(), clients.len()), (2, 2));
+    for (server, client) in servers.iter().zip(&clients) {
+        assert_eq!(client.name, "SELECT orders");
+        assert_eq!(client.parent_span_id, server.span_context.span_id());
+        assert_eq!(client.span_context.trace_id(), server.span_context.trace_id());
+    }
+    assert_eq!(attribute(servers[0], "client.address"), Some(Value::from("10.0.0.7")));
+    assert_eq!(attribute(servers[0], "network.peer.address"), Some(Value::from("10.0.0.7")));
+    assert_eq!(attribute(servers[1], "client.address"), Some(Value::from("203.0.113.9")));
+    assert_eq!(attribute(servers[1], "network.peer.address"), None);
+
+    // The same spans through the hook the rf_net HTTP server uses
+    let tracing: Arc<dyn HttpTracing> = Arc::new(TraceMiddleware::new("shop"));
+    let app = Router::new().route("/orders/{id}", get(|| async { "order" })).layer(axum::middleware::from_fn(
+        move |request, next| {
+            let tracing = tracing.clone();
+            async move { tracing.trace(request, next).await }
+        },
+    ));
+    app.oneshot(Request::get("/orders/3").body(Body::empty()).unwrap()).await.unwrap();
+    let spans = std::mem::take(&mut *collector.0.lock().unwrap());
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].name, "GET /orders/{id}");
+    assert_eq!(spans[0].span_kind, SpanKind::Server);
+}
@@ -0,0 +1,15 @@
+//! init_tracing tests
+
+use rf_contrib_trace::{init_tracing, TraceMiddleware};
+
+#[test]
+fn test_failed_exporter_falls_back_to_logs() {
+    // gRPC export needs a tokio runtime, so the pipeline cannot start here
+    std::env::set_var("OTEL_EXPORTER_OTLP_ENDPOINT", "http://127.0.0.1:4317");
+    std::env::remove_var("OTEL_EXPORTER_OTLP_PROTOCOL");
+
+    assert!(init_tracing("orders").is_ok());
+    assert!(tracing::dispatcher::has_been_set());
+    assert!(TraceMiddleware::global().is_none());
+    assert!(rf_net::trace::http_tracing().is_none());
+}
@@ -0,0 +1,147 @@
+//! OTLP pipeline tests
+
+use opentelemetry::trace::{
+    Span, SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState, Tracer,
+};
+use opentelemetry::Context;
+use rf_contrib_trace::{OtlpTracing, OtlpTracingConfig};
+use rf_os::cfg::{Config, ConfigAdapter, MemoryConfigAdapter};
+use rf_os::metric_otel::OtlpProtocol;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Collector stub answering every OTLP/HTTP export, forwarding head and body
+fn collector() -> (String, mpsc::Receiver<(String, Vec<u8>)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}/v1/traces", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { return };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut head = String::new();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                    head.push_str(&line.to_ascii_lowercase());
+                    line.clear();
+                }
+                if head.is_empty() {
+                    break;
+                }
+                let length = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length:"))
+                    .and_then(|l| l.trim().parse().ok())
+                    .unwrap_or(0);
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let _ = stream.write_all(b"HTTP/1.1 200 OK\r\ncontent-type: application/x-protobuf\r\ncontent-length: 0\r\n\r\n");
+                let _ = tx.send((head, body));
+            }
+        }
+    });
+    (endpoint, rx)
+}
+
+fn contains(body: &[u8], needle: &str) -> bool {
+    body.windows(needle.len()).any(|w| w == needle.as_bytes())
+}
+
+/// Every export body received within `wait`
+fn exported(exports: &mpsc::Receiver<(String, Vec<u8>)>, wait: Duration) -> Vec<(String, Vec<u8>)> {
+    let mut all = Vec::new();
+    while let Ok(export) = exports.recv_timeout(wait) {
+        all.push(export);
+    }
+    all
+}
+
+#[test]
+fn test_config_section() {
+    let adapter = Arc::new(MemoryConfigAdapter::new());
+    adapter.set("tracing.otlp.protocol", "http").unwrap();
+    adapter.set("tracing.otlp.endpoint", "http://collector:4318/v1/traces").unwrap();
+    adapter.set("tracing.otlp.sample_rate", "0.25").unwrap();
+    adapter.set("tracing.otlp.scheduled_delay", "2s").unwrap();
+    adapter.set("tracing.otlp.resource.region", "eu").unwrap();
+    let config = OtlpTracingConfig::from_config(&Config::new().adapter(adapter)).unwrap();
+    assert_eq!(config.protocol, OtlpProtocol::Http);
+    assert_eq!(config.sample_rate, 0.25);
+    assert_eq!(config.scheduled_delay, Duration::from_secs(2));
+    assert_eq!(config.max_queue_size, 2048);
+    assert_eq!(config.resource.get("region").map(String::as_str), Some("eu"));
+    assert_eq!(format!("{:?}", config.sampler()), "ParentBased(TraceIdRatioBased(0.25))");
+
+    let err = OtlpTracing::start(&OtlpTracingConfig::new(OtlpProtocol::Grpc, "http://127.0.0.1:4317")).unwrap_err();
+    assert!(err.to_string().contains("tokio runtime"), "{}", err);
+}
+
+#[test]
+fn test_shutdown_exports_queued_spans() {
+    let (endpoint, exports) = collector();
+    let otlp = OtlpTracing::start(
+        &OtlpTracingConfig::new(OtlpProtocol::Http, &endpoint)
+            .service_name("orders")
+            .resource("deployment.environment", "staging")
+            .header("x-api-key", "secret")
+            .scheduled_delay(Duration::from_secs(3600)),
+    )
+    .unwrap();
+
+    let mut span = otlp.tracer().start("api_span");
+    span.end();
+    let subscriber = tracing_subscriber::registry().with(otlp.layer());
+    tracing::subscriber::with_default(subscriber, || {
+        let _span = tracing::info_span!("tracing_span").entered();
+    });
+    // Nothing is sent before the batch delay
+    assert!(exports.recv_timeout(Duration::from_millis(200)).is_err());
+
+    otlp.shutdown().unwrap();
+    let all = exported(&exports, Duration::from_secs(2));
+    assert!(!all.is_empty());
+    let (head, _) = &all[0];
+    assert!(head.starts_with("post /v1/traces"), "{}", head);
+    assert!(head.contains("x-api-key: secret"), "{}", head);
+    let body: Vec<u8> = all.into_iter().flat_map(|(_, body)| body).collect();
+    for expected in ["api_span", "tracing_span", "orders", "deployment.environment", "staging"] {
+        assert!(contains(&body, expected), "{} missing from export", expected);
+    }
+}
+
+#[test]
+fn test_parent_based_ratio_sampling() {
+    let (endpoint, exports) = collector();
+    let otlp = OtlpTracing::start(
+        &OtlpTracingConfig::new(OtlpProtocol::Http, &endpoint)
+            .service_name("orders")
+            .sample_rate(0.0)
+            .scheduled_delay(Duration::from_secs(3600)),
+    )
+    .unwrap();
+
+    // Roots follow the rate, children of a sampled remote parent are kept
+    let mut root = otlp.tracer().start("dropped_root");
+    root.end();
+    let parent = SpanContext::new(
+        TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    let cx = Context::new().with_remote_span_context(parent);
+    let mut child = otlp.tracer().start_with_context("kept_child", &cx);
+    child.end();
+
+    otlp.flush().unwrap();
+    let body: Vec<u8> = exported(&exports, Duration::from_secs(2)).into_iter().flat_map(|(_, body)| body).collect();
+    assert!(contains(&body, "kept_child"));
+    assert!(!contains(&body, "dropped_root"));
+    otlp.shutdown().unwrap();
+}
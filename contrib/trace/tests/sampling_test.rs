@@ -0,0 +1,195 @@
+//! Sampling tests
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use opentelemetry::trace::{Span, SpanKind, Status, TraceContextExt, TraceId, Tracer, TracerProvider};
+use opentelemetry::{Context, KeyValue, Value};
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{SdkTracerProvider, SpanData, SpanProcessor};
+use rf_contrib_trace::{trace_middleware, SamplingConfig, TraceMiddleware};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tower::ServiceExt;
+
+/// Collects spans like an exporting processor: unsampled spans are skipped
+#[derive(Debug, Clone, Default)]
+struct Collector(Arc<Mutex<Vec<SpanData>>>);
+
+impl Collector {
+    fn names(&self) -> Vec<String> {
+        self.0.lock().unwrap().iter().map(|span| span.name.to_string()).collect()
+    }
+
+    fn take(&self) -> Vec<SpanData> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl SpanProcessor for Collector {
+    fn on_start(&self, _span: &mut opentelemetry_sdk::trace::Span, _cx: &Context) {}
+
+    fn on_end(&self, span: SpanData) {
+        if span.span_context.is_sampled() {
+            self.0.lock().unwrap().push(span);
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+        Ok(())
+    }
+}
+
+fn provider(config: &SamplingConfig, collector: &Collector) -> SdkTracerProvider {
+    config.configure(SdkTracerProvider::builder(), collector.clone()).build()
+}
+
+#[test]
+fn test_route_rates() {
+    let config = SamplingConfig::new(0.0)
+        .route("/health", 0.0)
+        .route("/api/*", 1.0)
+        .keep_errors(false);
+    assert_eq!(config.rate_for("/api/orders/{id}"), 1.0);
+    assert_eq!(config.rate_for("/health"), 0.0);
+    assert_eq!(config.rate_for("/healthz"), 0.0);
+    assert!(!config.tail_sampling());
+
+    let collector = Collector::default();
+    let tracer = provider(&config, &collector).tracer("test");
+    let root = tracer
+        .span_builder("GET /api/orders/{id}")
+        .with_attributes([KeyValue::new("http.route", "/api/orders/{id}")])
+        .start(&tracer);
+    assert!(root.span_context().is_sampled());
+    let cx = Context::current_with_span(root);
+    tracer.start_with_context("load order", &cx).end();
+    cx.span().end();
+
+    // Without tail sampling dropped spans are not recorded at all
+    let health = tracer
+        .span_builder("GET /health")
+        .with_attributes([KeyValue::new("url.path", "/health")])
+        .start(&tracer);
+    assert!(!health.is_recording());
+    let cx = Context::current_with_span(health);
+    assert!(!tracer.start_with_context("child", &cx).is_recording());
+    assert_eq!(collector.names(), ["load order", "GET /api/orders/{id}"]);
+
+    // Rates hold roughly and agree for the same trace ID
+    let half = SamplingConfig::new(0.5).keep_errors(false).sampler();
+    let sampled = (1..=2000u128)
+        .filter(|i| {
+            let trace_id = TraceId::from(i.wrapping_mul(0x9e37_79b9_7f4a_7c15_f39c_c060_5ced_c834));
+            let result = opentelemetry_sdk::trace::ShouldSample::should_sample(
+                &half,
+                None,
+                trace_id,
+                "op",
+                &SpanKind::Internal,
+                &[],
+                &[],
+            );
+            matches!(result.decision, opentelemetry::trace::SamplingDecision::RecordAndSample)
+        })
+        .count();
+    assert!((800..1200).contains(&sampled), "{}", sampled);
+}
+
+#[test]
+fn test_tail_sampling_keeps_errors_and_slow_traces() {
+    let config = SamplingConfig::new(0.0).slow_threshold(Duration::from_millis(100));
+    let collector = Collector::default();
+    let tracer = provider(&config, &collector).tracer("test");
+
+    let trace = |name: &'static str, configure: &dyn Fn(&Context)| {
+        let root = tracer.start(name);
+        assert!(root.is_recording() && !root.span_context().is_sampled());
+        let cx = Context::current_with_span(root);
+        tracer.start_with_context(format!("{} child", name), &cx).end();
+        configure(&cx);
+        cx.span().end();
+    };
+    trace("fine", &|cx| cx.span().set_attribute(KeyValue::new("http.response.status_code", 200)));
+    assert!(collector.names().is_empty());
+
+    trace("server error", &|cx| cx.span().set_attribute(KeyValue::new("http.response.status_code", 503)));
+    let spans = collector.take();
+    assert_eq!(spans.len(), 2);
+    assert!(spans.iter().all(|span| span.span_context.is_sampled()));
+    assert_eq!(spans[0].span_context.trace_id(), spans[1].span_context.trace_id());
+
+    // An error anywhere in the trace keeps all of it
+    let root = tracer.start("child error");
+    let cx = Context::current_with_span(root);
+    let mut child = tracer.start_with_context("query", &cx);
+    child.set_status(Status::error("timeout"));
+    child.end();
+    cx.span().end();
+    assert_eq!(collector.take().len(), 2);
+
+    let slow = tracer
+        .span_builder("slow")
+        .with_start_time(SystemTime::now() - Duration::from_millis(200))
+        .start(&tracer);
+    drop(slow);
+    assert_eq!(collector.names(), ["slow"]);
+
+    // Errors can be ignored
+    let config = SamplingConfig::new(0.0).keep_errors(false).slow_threshold(Duration::from_secs(1));
+    let collector = Collector::default();
+    let tracer = provider(&config, &collector).tracer("test");
+    let mut span = tracer.start("ignored");
+    span.set_status(Status::error("boom"));
+    span.end();
+    assert!(collector.names().is_empty());
+}
+
+#[tokio::test]
+async fn test_http_middleware() {
+    let collector = Collector::default();
+    let config = SamplingConfig::new(0.0).route("/api/*", 1.0);
+    opentelemetry::global::set_tracer_provider(provider(&config, &collector));
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let app = Router::new()
+        .route("/api/items/{id}", get(|| async { "item" }))
+        .route("/ok", get(|| async { "ok" }))
+        .route("/fail", get(|| async { StatusCode::SERVICE_UNAVAILABLE }))
+        .layer(axum::middleware::from_fn_with_state(
+            Arc::new(TraceMiddleware::new("http")),
+            trace_middleware,
+        ));
+    let call = |uri: &str, traceparent: Option<&str>| {
+        let mut request = Request::get(uri);
+        if let Some(traceparent) = traceparent {
+            request = request.header("traceparent", traceparent);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+
+    assert_eq!(call("/api/items/1", None).await.unwrap().status(), StatusCode::OK);
+    call("/ok", None).await.unwrap();
+    assert_eq!(call("/fail", None).await.unwrap().status(), StatusCode::SERVICE_UNAVAILABLE);
+    let spans = collector.take();
+    assert_eq!(spans.len(), 2);
+    assert_eq!(spans[0].name, "GET /api/items/{id}");
+    assert_eq!(spans[0].span_kind, SpanKind::Server);
+    let route = spans[0].attributes.iter().find(|kv| kv.key.as_str() == "http.route").unwrap();
+    assert_eq!(route.value, Value::from("/api/items/{id}"));
+    assert_eq!(spans[1].name, "GET /fail");
+    assert!(matches!(spans[1].status, Status::Error { .. }));
+
+    // A sampled upstream trace is continued
+    call("/ok", Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")).await.unwrap();
+    let spans = collector.take();
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].span_context.trace_id().to_string(), "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert!(spans[0].parent_span_is_remote);
+}
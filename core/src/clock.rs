@@ -0,0 +1,178 @@
+//! # clock
+//!
+//! clock 模块 - 可替换的时钟
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! 可替换的时钟
+//!
+//! 缓存 TTL、定时任务、计时器和重试退避通过 [`Clock`] 读取当前时间和等待，
+//! 而不是直接调用 `Instant::now()` 和 `tokio::time::sleep`。默认的
+//! [`Clock::system`] 就是系统时钟；测试时换成 `rf_test::MockClock` 提供的时钟，
+//! 由测试推进时间，过期和调度逻辑不再依赖真实的等待。
+//!
+//! 其他时间来源实现 [`TimeSource`] 后用 [`Clock::new`] 包装即可。
+//!
+//! # 使用示例
+//!
+//! ```rust
+//! use rf_core::clock::Clock;
+//! use std::time::Duration;
+//!
+//! # async fn example() {
+//! let clock = Clock::system();
+//! let started = clock.now();
+//! clock.sleep(Duration::from_millis(1)).await;
+//! assert!(clock.now() > started);
+//!
+//! let slow = clock.sleep(Duration::from_secs(10));
+//! assert!(clock.timeout(Duration::from_millis(1), slow).await.is_err());
+//! # }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// 过长的等待按约 30 年计算，避免时间溢出
+const FAR_FUTURE: Duration = Duration::from_secs(86400 * 365 * 30);
+
+/// 等待到某个时刻的 future
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// 时间来源
+pub trait TimeSource: Send + Sync + 'static {
+    /// 单调时间
+    fn now(&self) -> Instant;
+
+    /// 墙上时间
+    fn system_time(&self) -> SystemTime;
+
+    /// 等待到单调时间 `deadline`
+    fn sleep_until(&self, deadline: Instant) -> SleepFuture;
+}
+
+/// 时钟
+///
+/// 克隆共享同一个时间来源。
+#[derive(Clone, Default)]
+pub struct Clock {
+    /// `None` 为系统时钟
+    source: Option<Arc<dyn TimeSource>>,
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.source.is_some() { "Clock(custom)" } else { "Clock(system)" })
+    }
+}
+
+/// [`Clock::timeout`] 超时
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+impl Clock {
+    /// 系统时钟
+    pub fn system() -> Self {
+        Self { source: None }
+    }
+
+    /// 使用自定义时间来源的时钟
+    pub fn new(source: impl TimeSource) -> Self {
+        Self { source: Some(Arc::new(source)) }
+    }
+
+    /// 是否为系统时钟
+    pub fn is_system(&self) -> bool {
+        self.source.is_none()
+    }
+
+    /// 当前单调时间
+    pub fn now(&self) -> Instant {
+        match &self.source {
+            Some(source) => source.now(),
+            None => Instant::now(),
+        }
+    }
+
+    /// 当前墙上时间
+    pub fn system_time(&self) -> SystemTime {
+        match &self.source {
+            Some(source) => source.system_time(),
+            None => SystemTime::now(),
+        }
+    }
+
+    /// 自 `earlier` 以来经过的时间
+    pub fn elapsed(&self, earlier: Instant) -> Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+
+    /// 等待 `duration`
+    pub fn sleep(&self, duration: Duration) -> SleepFuture {
+        self.sleep_until(self.now() + duration.min(FAR_FUTURE))
+    }
+
+    /// 等待到单调时间 `deadline`
+    pub fn sleep_until(&self, deadline: Instant) -> SleepFuture {
+        match &self.source {
+            Some(source) => source.sleep_until(deadline),
+            None => Box::pin(tokio::time::sleep_until(deadline.into())),
+        }
+    }
+
+    /// 在 `duration` 内等待 `future` 完成，超时返回 [`Elapsed`]
+    pub async fn timeout<F: Future>(&self, duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+        tokio::select! {
+            output = future => Ok(output),
+            _ = self.sleep(duration) => Err(Elapsed),
+        }
+    }
+
+    /// 每隔 `period` 触发一次的计时器，第一次立即触发
+    pub fn interval(&self, period: Duration) -> Interval {
+        assert!(!period.is_zero(), "interval period must be non-zero");
+        Interval {
+            clock: self.clone(),
+            next: self.now(),
+            period,
+        }
+    }
+}
+
+/// [`Clock::interval`] 创建的计时器
+///
+/// 错过的触发不补，下一次从当前时间起算。
+#[derive(Debug)]
+pub struct Interval {
+    clock: Clock,
+    next: Instant,
+    period: Duration,
+}
+
+impl Interval {
+    /// 等待下一次触发，返回计划的触发时间
+    pub async fn tick(&mut self) -> Instant {
+        let scheduled = self.next;
+        self.clock.sleep_until(scheduled).await;
+        let now = self.clock.now();
+        self.next = if now > scheduled + self.period { now + self.period } else { scheduled + self.period };
+        scheduled
+    }
+
+    /// 触发周期
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
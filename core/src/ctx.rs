@@ -0,0 +1,127 @@
+//! # ctx
+//!
+//! ctx 模块 - 请求上下文截止时间、请求 ID 和语言环境
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! 请求上下文截止时间、请求 ID 和语言环境
+//!
+//! 对应 GoFrame 中 `context.WithDeadline` 的用法：在一个异步作用域内设置截止时间，
+//! 作用域内的下游调用（例如数据库查询）通过 [`remaining`] 读取剩余预算，
+//! 从而不会让请求超出其时间预算。
+//!
+//! 请求 ID 以同样的方式通过 [`with_request_id`] 设置，日志、错误响应和出站
+//! HTTP 请求通过 [`request_id`] 读取，把一次请求的所有输出关联起来。
+//!
+//! 请求方协商出的语言和时区通过 [`with_locale`] 设置，`rf_os::time::Time`
+//! 的序列化和模板中的日期过滤器通过 [`locale`] 读取，按请求方的时区和语言渲染时间。
+//!
+//! 截止时间和请求 ID 保存在 tokio 的 task-local 中，只对当前任务可见；
+//! 通过 `tokio::spawn` 启动的新任务不会继承，需要时可以用 [`deadline`] 取出后重新设置。
+//!
+//! # 使用示例
+//!
+//! ```rust
+//! use rf_core::ctx;
+//! use std::time::Duration;
+//!
+//! # async fn example() {
+//! ctx::with_timeout(Duration::from_secs(3), async {
+//!     let remaining = ctx::remaining().unwrap();
+//!     assert!(remaining <= Duration::from_secs(3));
+//! }).await;
+//! assert!(ctx::deadline().is_none());
+//!
+//! ctx::with_request_id("req-1", async {
+//!     assert_eq!(ctx::request_id().as_deref(), Some("req-1"));
+//! }).await;
+//!
+//! ctx::with_locale(ctx::Locale::new("zh-CN", "Asia/Shanghai"), async {
+//!     assert_eq!(ctx::locale().unwrap().timezone, "Asia/Shanghai");
+//! }).await;
+//! # }
+//! ```
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+    static REQUEST_ID: Arc<str>;
+    static LOCALE: Arc<Locale>;
+}
+
+/// 请求方的语言和时区
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    /// BCP 47 语言标签，例如 `zh-CN`
+    pub language: String,
+    /// IANA 时区名，例如 `Asia/Shanghai`
+    pub timezone: String,
+}
+
+impl Locale {
+    /// 创建语言环境
+    pub fn new(language: impl Into<String>, timezone: impl Into<String>) -> Self {
+        Self { language: language.into(), timezone: timezone.into() }
+    }
+}
+
+/// 在截止时间作用域内执行 future
+///
+/// 嵌套设置时取更早的截止时间，内层作用域无法延长外层的预算。
+pub async fn with_deadline<F: Future>(deadline: Instant, future: F) -> F::Output {
+    let deadline = match self::deadline() {
+        Some(outer) => outer.min(deadline),
+        None => deadline,
+    };
+    DEADLINE.scope(deadline, future).await
+}
+
+/// 在 `timeout` 之后到期的截止时间作用域内执行 future
+pub async fn with_timeout<F: Future>(timeout: Duration, future: F) -> F::Output {
+    with_deadline(Instant::now() + timeout, future).await
+}
+
+/// 当前作用域的截止时间，没有设置时返回 `None`
+pub fn deadline() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// 距离截止时间的剩余时长
+///
+/// 已经到期时返回 `Some(Duration::ZERO)`，没有设置截止时间时返回 `None`。
+pub fn remaining() -> Option<Duration> {
+    deadline().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// 截止时间是否已经到期
+pub fn is_expired() -> bool {
+    remaining().is_some_and(|remaining| remaining.is_zero())
+}
+
+/// 在请求 ID 作用域内执行 future
+///
+/// 嵌套设置时内层的请求 ID 生效。
+pub async fn with_request_id<F: Future>(request_id: impl Into<Arc<str>>, future: F) -> F::Output {
+    REQUEST_ID.scope(request_id.into(), future).await
+}
+
+/// 当前作用域的请求 ID，没有设置时返回 `None`
+pub fn request_id() -> Option<Arc<str>> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// 在语言环境作用域内执行 future
+///
+/// 嵌套设置时内层的语言环境生效。
+pub async fn with_locale<F: Future>(locale: Locale, future: F) -> F::Output {
+    LOCALE.scope(Arc::new(locale), future).await
+}
+
+/// 当前作用域的语言环境，没有设置时返回 `None`
+pub fn locale() -> Option<Arc<Locale>> {
+    LOCALE.try_with(|locale| locale.clone()).ok()
+}
@@ -0,0 +1,325 @@
+//! # fault
+//!
+//! fault 模块 - 故障注入
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! 故障注入
+//!
+//! 测试时用 [`FaultInjector`] 向调用路径注入延迟、错误和连接重置，从而端到端验证
+//! 重试、熔断和超时逻辑。注入器可以挂到 HTTP 服务器路由、`HttpClient`、Redis
+//! 客户端和数据库上（这些挂载点需要开启对应 crate 的 `fault-injection` feature），
+//! 每个挂载点在调用前以一个目标字符串调用 [`FaultInjector::inject`]：
+//!
+//! - HTTP 服务器：请求路径，例如 `/api/orders/42`
+//! - `HttpClient`：完整 URL
+//! - Redis：命令名和第一个参数，例如 `GET user:1`
+//! - 数据库：`Model` 操作为 `SELECT users` 这样的操作名和表名，原始 SQL 为 SQL 文本
+//!
+//! 规则按添加顺序匹配目标，使用第一条匹配且未用完次数的规则；`*` 匹配任意字符。
+//! 注入器可以克隆，克隆之间共享规则，测试中途可以增删规则或整体关闭。
+//!
+//! # 使用示例
+//!
+//! ```rust
+//! use rf_core::fault::{Fault, FaultInjector, FaultRule};
+//! use std::time::Duration;
+//!
+//! # async fn example() {
+//! let faults = FaultInjector::new()
+//!     .rule(FaultRule::new("/api/payments/*").error_rate(1.0).error_status(503).times(2))
+//!     .rule(FaultRule::new("*").latency(Duration::from_millis(5)));
+//!
+//! assert!(matches!(faults.inject("/api/payments/1").await, Err(Fault::Error { status: 503, .. })));
+//! assert!(faults.inject("/api/payments/1").await.is_err());
+//! // 前一条规则已用完，之后只有延迟
+//! assert!(faults.inject("/api/payments/1").await.is_ok());
+//! assert_eq!(faults.injected(), 3);
+//!
+//! faults.set_enabled(false);
+//! assert!(faults.inject("/api/payments/1").await.is_ok());
+//! # }
+//! ```
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// 注入的故障
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// 调用失败；HTTP 挂载点以 `status` 作为响应状态码
+    Error { status: u16, message: String },
+    /// 连接被重置
+    Reset,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fault::Error { status, message } => write!(f, "injected fault ({}): {}", status, message),
+            Fault::Reset => f.write_str("injected fault: connection reset"),
+        }
+    }
+}
+
+impl std::error::Error for Fault {}
+
+/// 故障规则
+///
+/// 每次匹配时先按 `latency_rate` 决定是否延迟，再依次按 `reset_rate` 和 `error_rate`
+/// 决定结果，因此可以模拟“慢且失败”的调用。
+#[derive(Debug, Clone)]
+pub struct FaultRule {
+    pattern: String,
+    latency: Duration,
+    jitter: Duration,
+    latency_rate: f64,
+    error_rate: f64,
+    error_status: u16,
+    error_message: String,
+    reset_rate: f64,
+    times: Option<u64>,
+}
+
+impl FaultRule {
+    /// 匹配 `pattern` 的规则，`*` 匹配任意字符，`"*"` 匹配所有目标
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            latency: Duration::ZERO,
+            jitter: Duration::ZERO,
+            latency_rate: 1.0,
+            error_rate: 0.0,
+            error_status: 503,
+            error_message: "service unavailable".to_string(),
+            reset_rate: 0.0,
+            times: None,
+        }
+    }
+
+    /// 固定延迟
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// 在固定延迟上随机增加 0 到 `jitter`
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// 延迟生效的概率（默认 1.0）
+    pub fn latency_rate(mut self, rate: f64) -> Self {
+        self.latency_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 返回错误的概率
+    pub fn error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 错误的状态码（默认 503）
+    pub fn error_status(mut self, status: u16) -> Self {
+        self.error_status = status;
+        self
+    }
+
+    /// 错误消息
+    pub fn error_message(mut self, message: impl Into<String>) -> Self {
+        self.error_message = message.into();
+        self
+    }
+
+    /// 重置连接的概率
+    pub fn reset_rate(mut self, rate: f64) -> Self {
+        self.reset_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 只生效前 `times` 次匹配，之后跳过该规则
+    pub fn times(mut self, times: u64) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    /// 目标是否匹配
+    pub fn matches(&self, target: &str) -> bool {
+        wildcard_match(self.pattern.as_bytes(), target.as_bytes())
+    }
+}
+
+/// 规则和已生效次数
+#[derive(Debug)]
+struct Entry {
+    rule: FaultRule,
+    hits: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Inner {
+    rules: RwLock<Vec<Arc<Entry>>>,
+    enabled: AtomicBool,
+    injected: AtomicU64,
+}
+
+/// 故障注入器
+#[derive(Debug, Clone)]
+pub struct FaultInjector {
+    inner: Arc<Inner>,
+}
+
+impl FaultInjector {
+    /// 创建没有规则的注入器
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                rules: RwLock::new(Vec::new()),
+                enabled: AtomicBool::new(true),
+                injected: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// 添加规则（构建时使用）
+    pub fn rule(self, rule: FaultRule) -> Self {
+        self.add(rule);
+        self
+    }
+
+    /// 添加规则，所有克隆立即生效
+    pub fn add(&self, rule: FaultRule) {
+        let entry = Arc::new(Entry { rule, hits: AtomicU64::new(0) });
+        self.inner.rules.write().unwrap_or_else(|e| e.into_inner()).push(entry);
+    }
+
+    /// 移除所有规则
+    pub fn clear(&self) {
+        self.inner.rules.write().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// 启用或关闭注入，关闭时保留规则
+    pub fn set_enabled(&self, enabled: bool) {
+        self.inner.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// 是否启用
+    pub fn is_enabled(&self) -> bool {
+        self.inner.enabled.load(Ordering::Relaxed)
+    }
+
+    /// 已注入的故障次数（延迟、错误和重置都计入）
+    pub fn injected(&self) -> u64 {
+        self.inner.injected.load(Ordering::Relaxed)
+    }
+
+    /// 对目标执行注入：按规则等待延迟，然后返回注入的故障或 `Ok(())`
+    pub async fn inject(&self, target: &str) -> Result<(), Fault> {
+        let Some((delay, fault)) = self.decide(target) else {
+            return Ok(());
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        fault.map_or(Ok(()), Err)
+    }
+
+    /// 选择规则并掷骰子，没有故障时返回 `None`
+    fn decide(&self, target: &str) -> Option<(Duration, Option<Fault>)> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let entry = {
+            let rules = self.inner.rules.read().unwrap_or_else(|e| e.into_inner());
+            rules
+                .iter()
+                .find(|entry| {
+                    entry.rule.matches(target)
+                        && entry.rule.times.is_none_or(|times| {
+                            entry
+                                .hits
+                                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |hits| (hits < times).then_some(hits + 1))
+                                .is_ok()
+                        })
+                })
+                .cloned()?
+        };
+        let rule = &entry.rule;
+
+        let mut delay = Duration::ZERO;
+        if (!rule.latency.is_zero() || !rule.jitter.is_zero()) && chance(rule.latency_rate) {
+            delay = rule.latency + rule.jitter.mul_f64(random());
+        }
+        let fault = if chance(rule.reset_rate) {
+            Some(Fault::Reset)
+        } else if chance(rule.error_rate) {
+            Some(Fault::Error {
+                status: rule.error_status,
+                message: rule.error_message.clone(),
+            })
+        } else {
+            None
+        };
+        if delay.is_zero() && fault.is_none() {
+            return None;
+        }
+        self.inner.injected.fetch_add(1, Ordering::Relaxed);
+        Some((delay, fault))
+    }
+}
+
+impl Default for FaultInjector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn chance(rate: f64) -> bool {
+    rate >= 1.0 || (rate > 0.0 && random() < rate)
+}
+
+/// `[0, 1)` 上的伪随机数（xorshift，每个线程独立的种子）
+fn random() -> f64 {
+    use std::cell::Cell;
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    thread_local! {
+        static STATE: Cell<u64> = Cell::new(RandomState::new().build_hasher().finish() | 1);
+    }
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    })
+}
+
+/// `*` 通配符匹配
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&b| b == b'*')
+}
@@ -13,6 +13,9 @@
 //!
 //! - `types`: 定义了各种常用的类型别名，包括 Map、List 和 Var 等容器类型
 //! - `traits`: 定义了框架的核心 trait，包括 ToString、Clone、Compare 和 Hash
+//! - `ctx`: 请求上下文截止时间，供下游调用读取剩余的时间预算
+//! - `fault`: 测试用的故障注入（延迟、错误率和连接重置）
+//! - `clock`: 可替换的时钟，测试中可以由 `rf_test::MockClock` 推进时间
 //!
 //! # 使用示例
 //!
@@ -35,6 +38,9 @@
 
 pub mod types;
 pub mod traits;
+pub mod ctx;
+pub mod fault;
+pub mod clock;
 
 pub use types::*;
 pub use traits::*;
@@ -16,6 +16,29 @@
 //! // 输出类似: CRC32: e7b45194
 //! ```
 //!
+//! # 其它算法与流式计算
+//!
+//! 除 CRC32（IEEE）外，还提供 CRC32C（Castagnoli）、CRC16 和 CRC64 的常用变体，
+//! 通过 [`Algorithm`] 选择；[`Hasher`] 支持分块增量计算，适合大文件和网络流：
+//!
+//! ```ignore
+//! use rf_crypto::crc32::{self, Algorithm, Hasher};
+//!
+//! // CRC32C，支持的 CPU 上使用 SSE4.2 / ARMv8 CRC 指令
+//! let crc = crc32::crc32c(b"123456789");
+//! assert_eq!(crc, 0xe306_9283);
+//!
+//! // CRC16/MODBUS 一次性计算
+//! let crc = Algorithm::Crc16Modbus.checksum(frame) as u16;
+//!
+//! // 流式计算 CRC64/XZ，结果与一次性计算相同
+//! let mut hasher = Hasher::new(Algorithm::Crc64Xz);
+//! for chunk in chunks {
+//!     hasher.update(chunk);
+//! }
+//! let crc = hasher.finalize();
+//! ```
+//!
 //! # 特点
 //!
 //! - 高速计算：CRC32 计算速度非常快，适合实时处理
@@ -33,7 +56,8 @@
 //! // @author TimonQWQ
 //! // @date 2026-01-06
 
-use crc32fast::Hasher;
+use crc::{Crc, Digest, Table};
+use std::io;
 
 /// 计算数据的 CRC32 校验和
 ///
@@ -88,8 +112,259 @@ use crc32fast::Hasher;
 /// - CRC32 计算速度远快于 SHA-256 等加密哈希
 /// - 如果需要安全性，应该使用 SHA-256 等加密哈希算法
 pub fn checksum(data: &[u8]) -> u32 {
-    let mut hasher = Hasher::new();
+    let mut hasher = crc32fast::Hasher::new();
     hasher.update(data);
     hasher.finalize()
 }
 
+
+/// CRC 算法
+///
+/// 括号内为 CRC RevEng 目录中的标准名称，`"123456789"` 的校验值见各变体说明。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// CRC-32（ISO-HDLC），ZIP、PNG、以太网使用，校验值 `0xcbf43926`
+    Crc32,
+    /// CRC-32C（ISCSI，Castagnoli），iSCSI、ext4、RocksDB、gRPC 使用，校验值 `0xe3069283`
+    Crc32c,
+    /// CRC-16/CCITT-FALSE（IBM-3740），校验值 `0x29b1`
+    Crc16Ccitt,
+    /// CRC-16/XMODEM，Redis Cluster 哈希槽使用，校验值 `0x31c3`
+    Crc16Xmodem,
+    /// CRC-16/MODBUS，校验值 `0x4b37`
+    Crc16Modbus,
+    /// CRC-16/KERMIT，校验值 `0x2189`
+    Crc16Kermit,
+    /// CRC-64/XZ（Go 的 `crc64.ECMA` 表），xz 压缩格式使用，校验值 `0x995dc9bbdf1939fa`
+    Crc64Xz,
+    /// CRC-64/ECMA-182，校验值 `0x6c40df5f0b497347`
+    Crc64Ecma,
+    /// CRC-64/REDIS（Jones），Redis RDB 文件使用，校验值 `0xe9c6d914c4b8d9ca`
+    Crc64Redis,
+}
+
+static CRC16_CCITT: Crc<u16, Table<16>> = Crc::<u16, Table<16>>::new(&crc::CRC_16_IBM_3740);
+static CRC16_XMODEM: Crc<u16, Table<16>> = Crc::<u16, Table<16>>::new(&crc::CRC_16_XMODEM);
+static CRC16_MODBUS: Crc<u16, Table<16>> = Crc::<u16, Table<16>>::new(&crc::CRC_16_MODBUS);
+static CRC16_KERMIT: Crc<u16, Table<16>> = Crc::<u16, Table<16>>::new(&crc::CRC_16_KERMIT);
+static CRC64_XZ: Crc<u64, Table<16>> = Crc::<u64, Table<16>>::new(&crc::CRC_64_XZ);
+static CRC64_ECMA: Crc<u64, Table<16>> = Crc::<u64, Table<16>>::new(&crc::CRC_64_ECMA_182);
+static CRC64_REDIS: Crc<u64, Table<16>> = Crc::<u64, Table<16>>::new(&crc::CRC_64_REDIS);
+
+impl Algorithm {
+    /// 校验值的位数：16、32 或 64
+    pub fn width(self) -> u32 {
+        match self {
+            Algorithm::Crc32 | Algorithm::Crc32c => 32,
+            Algorithm::Crc16Ccitt | Algorithm::Crc16Xmodem | Algorithm::Crc16Modbus | Algorithm::Crc16Kermit => 16,
+            Algorithm::Crc64Xz | Algorithm::Crc64Ecma | Algorithm::Crc64Redis => 64,
+        }
+    }
+
+    /// 一次性计算校验和，结果按 [`width`](Self::width) 位存放在 `u64` 的低位
+    pub fn checksum(self, data: &[u8]) -> u64 {
+        let mut hasher = Hasher::new(self);
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    /// 创建该算法的增量计算器
+    pub fn hasher(self) -> Hasher {
+        Hasher::new(self)
+    }
+}
+
+/// 计算数据的 CRC32C（Castagnoli）校验和
+///
+/// 在支持的 CPU 上（x86_64 SSE4.2、aarch64 CRC 扩展）使用硬件指令，
+/// 否则使用查表实现，两者结果相同。
+///
+/// # 使用示例
+///
+/// ```ignore
+/// use rf_crypto::crc32;
+///
+/// assert_eq!(crc32::crc32c(b"123456789"), 0xe306_9283);
+/// ```
+pub fn crc32c(data: &[u8]) -> u32 {
+    !crc32c_update(!0, data)
+}
+
+/// CRC32C 是否使用硬件指令计算
+pub fn hardware_accelerated() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        std::is_x86_feature_detected!("sse4.2")
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        std::arch::is_aarch64_feature_detected!("crc")
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        false
+    }
+}
+
+/// CRC32C 反射多项式
+const CRC32C_POLY: u32 = 0x82f6_3b78;
+
+/// CRC32C 查表实现的 256 项表
+static CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ CRC32C_POLY } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// 以未取反的寄存器值 `crc` 继续计算 CRC32C
+fn crc32c_update(crc: u32, data: &[u8]) -> u32 {
+    #[cfg(target_arch = "x86_64")]
+    if std::is_x86_feature_detected!("sse4.2") {
+        // SAFETY: 已检测 CPU 支持 SSE4.2
+        return unsafe { crc32c_sse42(crc, data) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("crc") {
+        // SAFETY: 已检测 CPU 支持 CRC 扩展
+        return unsafe { crc32c_armv8(crc, data) };
+    }
+    crc32c_table(crc, data)
+}
+
+fn crc32c_table(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc = CRC32C_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2")]
+unsafe fn crc32c_sse42(crc: u32, data: &[u8]) -> u32 {
+    use std::arch::x86_64::{_mm_crc32_u64, _mm_crc32_u8};
+
+    let mut chunks = data.chunks_exact(8);
+    let mut crc = crc as u64;
+    for chunk in &mut chunks {
+        crc = _mm_crc32_u64(crc, u64::from_le_bytes(chunk.try_into().unwrap()));
+    }
+    let mut crc = crc as u32;
+    for &byte in chunks.remainder() {
+        crc = _mm_crc32_u8(crc, byte);
+    }
+    crc
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "crc")]
+unsafe fn crc32c_armv8(crc: u32, data: &[u8]) -> u32 {
+    use std::arch::aarch64::{__crc32cb, __crc32cd};
+
+    let mut chunks = data.chunks_exact(8);
+    let mut crc = crc;
+    for chunk in &mut chunks {
+        crc = __crc32cd(crc, u64::from_le_bytes(chunk.try_into().unwrap()));
+    }
+    for &byte in chunks.remainder() {
+        crc = __crc32cb(crc, byte);
+    }
+    crc
+}
+
+#[derive(Clone)]
+enum State {
+    Crc32(crc32fast::Hasher),
+    /// 未取反的寄存器值
+    Crc32c(u32),
+    Crc16(Digest<'static, u16, Table<16>>),
+    Crc64(Digest<'static, u64, Table<16>>),
+}
+
+/// 增量 CRC 计算器
+///
+/// 数据可以分多次传入 [`update`](Self::update)，结果与一次性计算整段数据相同。
+/// 实现了 `std::io::Write`，可以配合 `std::io::copy` 计算文件或流的校验和。
+///
+/// # 使用示例
+///
+/// ```ignore
+/// use rf_crypto::crc32::{Algorithm, Hasher};
+///
+/// let mut hasher = Hasher::new(Algorithm::Crc32c);
+/// std::io::copy(&mut std::fs::File::open("data.bin")?, &mut hasher)?;
+/// println!("CRC32C: {:08x}", hasher.finalize());
+/// ```
+#[derive(Clone)]
+pub struct Hasher {
+    algorithm: Algorithm,
+    state: State,
+}
+
+impl Hasher {
+    /// 创建指定算法的计算器
+    pub fn new(algorithm: Algorithm) -> Self {
+        let state = match algorithm {
+            Algorithm::Crc32 => State::Crc32(crc32fast::Hasher::new()),
+            Algorithm::Crc32c => State::Crc32c(!0),
+            Algorithm::Crc16Ccitt => State::Crc16(CRC16_CCITT.digest()),
+            Algorithm::Crc16Xmodem => State::Crc16(CRC16_XMODEM.digest()),
+            Algorithm::Crc16Modbus => State::Crc16(CRC16_MODBUS.digest()),
+            Algorithm::Crc16Kermit => State::Crc16(CRC16_KERMIT.digest()),
+            Algorithm::Crc64Xz => State::Crc64(CRC64_XZ.digest()),
+            Algorithm::Crc64Ecma => State::Crc64(CRC64_ECMA.digest()),
+            Algorithm::Crc64Redis => State::Crc64(CRC64_REDIS.digest()),
+        };
+        Self { algorithm, state }
+    }
+
+    /// 计算器使用的算法
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// 追加数据
+    pub fn update(&mut self, data: &[u8]) {
+        match &mut self.state {
+            State::Crc32(hasher) => hasher.update(data),
+            State::Crc32c(crc) => *crc = crc32c_update(*crc, data),
+            State::Crc16(digest) => digest.update(data),
+            State::Crc64(digest) => digest.update(data),
+        }
+    }
+
+    /// 当前已传入数据的校验和，不影响后续追加
+    pub fn finalize(&self) -> u64 {
+        match &self.state {
+            State::Crc32(hasher) => hasher.clone().finalize() as u64,
+            State::Crc32c(crc) => !*crc as u64,
+            State::Crc16(digest) => digest.clone().finalize() as u64,
+            State::Crc64(digest) => digest.clone().finalize(),
+        }
+    }
+
+    /// 清空已传入的数据
+    pub fn reset(&mut self) {
+        *self = Self::new(self.algorithm);
+    }
+}
+
+impl io::Write for Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
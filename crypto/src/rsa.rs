@@ -47,8 +47,11 @@
 //! @author TimonQWQ
 //! @date 2026-01-06
 
-use rsa::{RsaPrivateKey, RsaPublicKey};
-use sha2::Sha256;
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::Pkcs1v15Sign;
+pub use rsa::{RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha256};
 use rf_errors::{Result, RfError};
 
 /// RSA 密钥对
@@ -127,6 +130,71 @@ impl RsaKeyPair {
         })
     }
 
+    /// 从 PEM 格式的私钥创建密钥对
+    ///
+    /// 支持 PKCS#8（`BEGIN PRIVATE KEY`）和 PKCS#1（`BEGIN RSA PRIVATE KEY`）格式，公钥由私钥导出。
+    ///
+    /// # 参数
+    ///
+    /// * `pem` - PEM 格式的私钥
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `RsaKeyPair`
+    ///
+    /// # 错误
+    ///
+    /// 如果 PEM 内容无效，返回 `RfError::InvalidParameter`
+    ///
+    /// # 使用示例
+    ///
+    /// ```ignore
+    /// use crypto::rsa::RsaKeyPair;
+    ///
+    /// let key_pair = RsaKeyPair::from_pem(&std::fs::read_to_string("private.pem")?)?;
+    /// ```
+    pub fn from_pem(pem: &str) -> Result<Self> {
+        let private_key = RsaPrivateKey::from_pkcs8_pem(pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem))
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid RSA private key: {}", e)))?;
+        let public_key = RsaPublicKey::from(&private_key);
+        Ok(Self {
+            private_key,
+            public_key,
+        })
+    }
+
+    /// 将私钥导出为 PKCS#8 PEM 格式
+    ///
+    /// # 返回值
+    ///
+    /// 返回 PEM 字符串
+    ///
+    /// # 错误
+    ///
+    /// 如果编码失败，返回内部错误
+    pub fn private_key_pem(&self) -> Result<String> {
+        self.private_key
+            .to_pkcs8_pem(LineEnding::LF)
+            .map(|pem| pem.to_string())
+            .map_err(|e| RfError::Internal(format!("Failed to encode RSA private key: {}", e)))
+    }
+
+    /// 将公钥导出为 PEM 格式（`BEGIN PUBLIC KEY`）
+    ///
+    /// # 返回值
+    ///
+    /// 返回 PEM 字符串
+    ///
+    /// # 错误
+    ///
+    /// 如果编码失败，返回内部错误
+    pub fn public_key_pem(&self) -> Result<String> {
+        self.public_key
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|e| RfError::Internal(format!("Failed to encode RSA public key: {}", e)))
+    }
+
     /// 获取私钥
     ///
     /// 返回 RSA 密钥对中的私钥引用，用于解密操作。
@@ -287,3 +355,73 @@ pub fn decrypt(private_key: &RsaPrivateKey, data: &[u8]) -> Result<Vec<u8>> {
     private_key.decrypt(rsa::Oaep::new::<Sha256>(), data)
         .map_err(|e| RfError::Internal(format!("RSA decryption failed: {}", e)))
 }
+
+/// 从 PEM 格式解析 RSA 公钥
+///
+/// 支持 `BEGIN PUBLIC KEY`（SPKI）和 `BEGIN RSA PUBLIC KEY`（PKCS#1）格式。
+///
+/// # 参数
+///
+/// * `pem` - PEM 格式的公钥
+///
+/// # 返回值
+///
+/// 返回 `RsaPublicKey`
+///
+/// # 错误
+///
+/// 如果 PEM 内容无效，返回 `RfError::InvalidParameter`
+pub fn public_key_from_pem(pem: &str) -> Result<RsaPublicKey> {
+    RsaPublicKey::from_public_key_pem(pem)
+        .or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))
+        .map_err(|e| RfError::InvalidParameter(format!("Invalid RSA public key: {}", e)))
+}
+
+/// 使用 RSA 私钥签名数据（PKCS#1 v1.5 + SHA-256，即 JWT 的 RS256）
+///
+/// # 参数
+///
+/// * `private_key` - RSA 私钥
+/// * `data` - 要签名的数据（内部先计算 SHA-256）
+///
+/// # 返回值
+///
+/// 返回签名，长度等于密钥长度（字节）
+///
+/// # 错误
+///
+/// 如果签名失败，返回内部错误
+///
+/// # 使用示例
+///
+/// ```ignore
+/// use crypto::rsa::{RsaKeyPair, sign, verify};
+///
+/// let key_pair = RsaKeyPair::new(2048)?;
+/// let signature = sign(key_pair.private_key(), b"message")?;
+/// assert!(verify(key_pair.public_key(), b"message", &signature));
+/// ```
+pub fn sign(private_key: &RsaPrivateKey, data: &[u8]) -> Result<Vec<u8>> {
+    let digest = Sha256::digest(data);
+    private_key
+        .sign(Pkcs1v15Sign::new::<Sha256>(), &digest)
+        .map_err(|e| RfError::Internal(format!("RSA signing failed: {}", e)))
+}
+
+/// 使用 RSA 公钥校验签名（PKCS#1 v1.5 + SHA-256）
+///
+/// # 参数
+///
+/// * `public_key` - RSA 公钥
+/// * `data` - 被签名的数据
+/// * `signature` - 签名
+///
+/// # 返回值
+///
+/// 签名有效时返回 `true`
+pub fn verify(public_key: &RsaPublicKey, data: &[u8], signature: &[u8]) -> bool {
+    let digest = Sha256::digest(data);
+    public_key
+        .verify(Pkcs1v15Sign::new::<Sha256>(), &digest, signature)
+        .is_ok()
+}
@@ -26,6 +26,7 @@
 //! @author TimonQWQ
 //! @date 2026-01-06
 
+use hmac::{Hmac, Mac};
 use sha2::{Sha256, Digest};
 
 /// 计算数据的 SHA-256 哈希值
@@ -86,3 +87,61 @@ pub fn hash(data: &[u8]) -> String {
     format!("{:x}", hasher.finalize())
 }
 
+/// 计算 HMAC-SHA256
+///
+/// 使用密钥对数据计算 HMAC-SHA256 消息认证码，可用于 JWT HS256 签名、Webhook 签名等场景。
+///
+/// # 参数
+///
+/// * `key` - 密钥，任意长度
+/// * `data` - 要认证的数据
+///
+/// # 返回值
+///
+/// 返回 32 字节的认证码
+///
+/// # 使用示例
+///
+/// ```rust
+/// use rf_crypto::sha256::hmac;
+///
+/// let mac = hmac(b"key", b"The quick brown fox jumps over the lazy dog");
+/// assert_eq!(mac.len(), 32);
+/// ```
+///
+/// # 安全注意事项
+///
+/// 校验认证码时应使用常量时间比较（如 `verify_hmac`），避免时序攻击。
+pub fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC 接受任意长度的密钥，new_from_slice 不会失败
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// 以常量时间校验 HMAC-SHA256 认证码
+///
+/// # 参数
+///
+/// * `key` - 密钥
+/// * `data` - 被认证的数据
+/// * `tag` - 待校验的认证码
+///
+/// # 返回值
+///
+/// 认证码正确时返回 `true`
+///
+/// # 使用示例
+///
+/// ```rust
+/// use rf_crypto::sha256::{hmac, verify_hmac};
+///
+/// let mac = hmac(b"key", b"data");
+/// assert!(verify_hmac(b"key", b"data", &mac));
+/// assert!(!verify_hmac(b"other", b"data", &mac));
+/// ```
+pub fn verify_hmac(key: &[u8], data: &[u8], tag: &[u8]) -> bool {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.verify_slice(tag).is_ok()
+}
@@ -41,6 +41,59 @@ fn test_crc32_consistency() {
     assert_eq!(checksum1, checksum2); // Same input should produce same checksum
 }
 
+#[test]
+fn test_crc_check_values() {
+    use crc32::Algorithm;
+
+    let data = b"123456789";
+    assert_eq!(crc32::crc32c(data), 0xe306_9283);
+    let cases = [
+        (Algorithm::Crc32, 0xcbf4_3926),
+        (Algorithm::Crc32c, 0xe306_9283),
+        (Algorithm::Crc16Ccitt, 0x29b1),
+        (Algorithm::Crc16Xmodem, 0x31c3),
+        (Algorithm::Crc16Modbus, 0x4b37),
+        (Algorithm::Crc16Kermit, 0x2189),
+        (Algorithm::Crc64Xz, 0x995d_c9bb_df19_39fa),
+        (Algorithm::Crc64Ecma, 0x6c40_df5f_0b49_7347),
+        (Algorithm::Crc64Redis, 0xe9c6_d914_c4b8_d9ca),
+    ];
+    for (algorithm, check) in cases {
+        assert_eq!(algorithm.checksum(data), check, "{:?}", algorithm);
+    }
+}
+
+#[test]
+fn test_crc_streaming() {
+    use crc32::{Algorithm, Hasher};
+    use std::io::Write;
+
+    // Lengths around the 8-byte blocks of the hardware CRC32C path
+    let data: Vec<u8> = (0..1031u32).map(|i| (i * 31 % 251) as u8).collect();
+    for algorithm in [Algorithm::Crc32, Algorithm::Crc32c, Algorithm::Crc16Modbus, Algorithm::Crc64Xz] {
+        let mut hasher = Hasher::new(algorithm);
+        for chunk in data.chunks(13) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.finalize(), algorithm.checksum(&data), "{:?}", algorithm);
+        // Finalizing does not end the stream
+        hasher.write_all(b"tail").unwrap();
+        let mut whole = data.clone();
+        whole.extend_from_slice(b"tail");
+        assert_eq!(hasher.finalize(), algorithm.checksum(&whole));
+
+        hasher.reset();
+        hasher.update(b"123456789");
+        assert_eq!(hasher.algorithm(), algorithm);
+        assert_eq!(hasher.finalize(), algorithm.checksum(b"123456789"));
+    }
+    assert_eq!(Algorithm::Crc32.checksum(&data), crc32::checksum(&data) as u64);
+    // RFC 3720 (iSCSI) test vectors
+    assert_eq!(crc32::crc32c(&[0u8; 32]), 0x8a91_36aa);
+    assert_eq!(crc32::crc32c(&[0xffu8; 32]), 0x62a8_ab43);
+    assert_eq!(crc32::crc32c(&(0..32u8).collect::<Vec<_>>()), 0x46dd_794e);
+}
+
 #[test]
 fn test_aes_encrypt_decrypt() {
     let key = [0u8; 32]; // 256-bit key
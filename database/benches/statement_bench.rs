@@ -0,0 +1,67 @@
+//! # Statement Benchmark Tests
+//!
+//! Benchmark prepared statements against the per-call parse path for a hot query
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rf_database::db::Database;
+use tokio::runtime::Runtime;
+
+const SQL: &str = "SELECT id, name FROM users WHERE id = ?";
+
+fn setup(rt: &Runtime) -> Database {
+    rt.block_on(async {
+        // A file database: each pooled connection to `sqlite::memory:` would see its own empty database
+        let path = std::env::temp_dir().join(format!("rf_statement_bench_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let db = Database::new_sqlite(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        db.raw_execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").await.unwrap();
+        for i in 0..1000 {
+            db.raw_execute(&format!("INSERT INTO users (id, name) VALUES ({}, 'user{}')", i, i)).await.unwrap();
+        }
+        db
+    })
+}
+
+fn bench_hot_query(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let db = setup(&rt);
+    let pool = db.as_sqlite().unwrap().clone();
+
+    c.bench_function("query_parse_per_call", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                for i in 0..100 {
+                    let row = sqlx::query(SQL).bind(black_box(i as i64)).persistent(false).fetch_optional(&pool).await;
+                    black_box(row.unwrap());
+                }
+            });
+        });
+    });
+
+    c.bench_function("query_prepared", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                for i in 0..100 {
+                    let stmt = db.prepare(SQL).await.unwrap();
+                    black_box(stmt.fetch_one(&[black_box(i).into()]).await.unwrap());
+                }
+            });
+        });
+    });
+}
+
+fn bench_prepare_cached(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let db = setup(&rt);
+    rt.block_on(db.prepare(SQL)).unwrap();
+    c.bench_function("prepare_cache_hit", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                black_box(db.prepare(black_box(SQL)).await.unwrap());
+            });
+        });
+    });
+}
+
+criterion_group!(benches, bench_hot_query, bench_prepare_cached);
+criterion_main!(benches);
@@ -15,6 +15,7 @@
 //! - 支持设置缓存容量和过期时间
 //! - 自动管理缓存失效
 //! - 支持表级别的缓存清理
+//! - `CacheStore` trait 允许 `Model` 和事务使用其它缓存实现（如 `rf_os::cache::TieredCache`）
 //!
 //! ## 使用场景
 //!
@@ -47,6 +48,7 @@
 //! # }
 //! ```
 
+use futures_util::future::BoxFuture;
 use moka::future::Cache;
 use std::hash::{Hash, Hasher};
 use std::time::Duration;
@@ -77,6 +79,27 @@ impl PartialEq for CacheKey {
 
 impl Eq for CacheKey {}
 
+/// 查询结果缓存的存储
+///
+/// `Model::cache` 和 `TransactionWrapper::invalidate_on_commit` 通过该 trait 访问缓存，
+/// 值为序列化后的查询结果。
+pub trait CacheStore: Send + Sync + 'static {
+    /// 获取缓存，不存在时返回 `None`
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<Vec<u8>>>;
+
+    /// 设置缓存
+    fn set<'a>(&'a self, key: &'a str, value: Vec<u8>) -> BoxFuture<'a, ()>;
+
+    /// 使单个缓存条目失效
+    fn invalidate<'a>(&'a self, key: &'a str) -> BoxFuture<'a, ()>;
+
+    /// 使与表相关的缓存失效
+    fn invalidate_table<'a>(&'a self, table: &'a str) -> BoxFuture<'a, ()>;
+
+    /// 清空缓存
+    fn clear(&self) -> BoxFuture<'_, ()>;
+}
+
 /// 查询缓存管理器
 ///
 /// 用于管理查询结果缓存的核心结构。
@@ -162,3 +185,24 @@ impl QueryCache {
     }
 }
 
+impl CacheStore for QueryCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<Vec<u8>>> {
+        Box::pin(QueryCache::get(self, key))
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: Vec<u8>) -> BoxFuture<'a, ()> {
+        Box::pin(QueryCache::set(self, key, value))
+    }
+
+    fn invalidate<'a>(&'a self, key: &'a str) -> BoxFuture<'a, ()> {
+        Box::pin(QueryCache::invalidate(self, key))
+    }
+
+    fn invalidate_table<'a>(&'a self, table: &'a str) -> BoxFuture<'a, ()> {
+        Box::pin(QueryCache::invalidate_table(self, table))
+    }
+
+    fn clear(&self) -> BoxFuture<'_, ()> {
+        Box::pin(QueryCache::clear(self))
+    }
+}
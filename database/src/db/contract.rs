@@ -0,0 +1,244 @@
+//! # contract
+//!
+//! contract 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Message contracts
+//!
+//! A `ContractRegistry` holds the versioned schema of each topic: a JSON
+//! Schema, or a protobuf message type for binary payloads. Producers validate
+//! before publishing (`Outbox::contracts` does it on enqueue and again in the
+//! relay) and consumers validate while decoding, so a malformed event is
+//! stopped at the first hop instead of propagating.
+//!
+//! Registering a new version of a topic's JSON Schema is checked against the
+//! latest version with the registry's `Compatibility` mode (backward by
+//! default: new consumers must accept old messages).
+//!
+//! ```rust,ignore
+//! use rf_database::db::ContractRegistry;
+//!
+//! let contracts = Arc::new(ContractRegistry::new());
+//! contracts.register_json_schema("order.created", json!({
+//!     "type": "object",
+//!     "properties": {"id": {"type": "integer"}, "total": {"type": "number", "minimum": 0}},
+//!     "required": ["id", "total"],
+//! }))?;
+//! let outbox = Outbox::new(&db).contracts(contracts.clone());
+//!
+//! // Consumer side
+//! let order: OrderCreated = contracts.decode("order.created", entry_payload.as_bytes())?;
+//! ```
+
+use parking_lot::RwLock;
+use rf_encoding::json_schema::JsonSchema;
+use rf_errors::{Result, RfError};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub use rf_encoding::json_schema::Compatibility;
+
+/// Validates protobuf payloads
+type ProtobufCheck = Arc<dyn Fn(&[u8]) -> std::result::Result<(), String> + Send + Sync>;
+
+/// Schema of one topic version
+#[derive(Clone)]
+pub enum Contract {
+    JsonSchema(Arc<JsonSchema>),
+    /// Payloads must decode as the named protobuf message
+    Protobuf { message: String, check: ProtobufCheck },
+}
+
+impl std::fmt::Debug for Contract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Contract::JsonSchema(schema) => f.debug_tuple("JsonSchema").field(schema.as_value()).finish(),
+            Contract::Protobuf { message, .. } => f.debug_struct("Protobuf").field("message", message).finish(),
+        }
+    }
+}
+
+/// A registered contract version
+#[derive(Debug, Clone)]
+pub struct ContractVersion {
+    /// Starts at 1
+    pub version: u32,
+    pub contract: Contract,
+}
+
+/// Versioned message contracts per topic
+pub struct ContractRegistry {
+    topics: RwLock<HashMap<String, Vec<ContractVersion>>>,
+    compatibility: Compatibility,
+    strict: bool,
+}
+
+impl Default for ContractRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContractRegistry {
+    /// Registry with backward compatibility checks that lets unregistered topics through
+    pub fn new() -> Self {
+        Self {
+            topics: RwLock::new(HashMap::new()),
+            compatibility: Compatibility::Backward,
+            strict: false,
+        }
+    }
+
+    /// Compatibility required between consecutive JSON Schema versions
+    pub fn with_compatibility(mut self, compatibility: Compatibility) -> Self {
+        self.compatibility = compatibility;
+        self
+    }
+
+    /// Reject messages for topics without a contract
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Register a JSON Schema for `topic`, returns its version
+    ///
+    /// Registering the latest schema again returns the existing version; an
+    /// incompatible schema is rejected with `RfError::Validation`.
+    pub fn register_json_schema(&self, topic: &str, schema: Value) -> Result<u32> {
+        let schema = JsonSchema::new(schema)?;
+        let mut topics = self.topics.write();
+        let versions = topics.entry(topic.to_string()).or_default();
+        if let Some(latest) = versions.last() {
+            match &latest.contract {
+                Contract::JsonSchema(previous) if previous.as_value() == schema.as_value() => return Ok(latest.version),
+                Contract::JsonSchema(previous) => {
+                    let issues = previous.compatibility_issues(&schema, self.compatibility);
+                    if !issues.is_empty() {
+                        return Err(RfError::Validation(format!(
+                            "Schema for topic '{}' is not {:?} compatible with v{}: {}",
+                            topic,
+                            self.compatibility,
+                            latest.version,
+                            issues.join("; ")
+                        )));
+                    }
+                }
+                Contract::Protobuf { message, .. } if self.compatibility != Compatibility::None => {
+                    return Err(RfError::Validation(format!(
+                        "Topic '{}' carries protobuf {} and cannot switch to JSON Schema",
+                        topic, message
+                    )));
+                }
+                Contract::Protobuf { .. } => {}
+            }
+        }
+        let version = versions.len() as u32 + 1;
+        versions.push(ContractVersion { version, contract: Contract::JsonSchema(Arc::new(schema)) });
+        Ok(version)
+    }
+
+    /// Register protobuf message `M` for `topic`, returns its version
+    ///
+    /// Payloads are checked by decoding them as `M`. Unless compatibility
+    /// checks are off, a topic keeps one message type: field-level evolution
+    /// follows protobuf's own rules (never reuse or retype field numbers).
+    pub fn register_protobuf<M: prost::Message + Default + 'static>(&self, topic: &str) -> Result<u32> {
+        let message = std::any::type_name::<M>().to_string();
+        let mut topics = self.topics.write();
+        let versions = topics.entry(topic.to_string()).or_default();
+        if let Some(latest) = versions.last() {
+            match &latest.contract {
+                Contract::Protobuf { message: previous, .. } if *previous == message => return Ok(latest.version),
+                _ if self.compatibility == Compatibility::None => {}
+                contract => {
+                    return Err(RfError::Validation(format!(
+                        "Topic '{}' carries {:?} and cannot switch to protobuf {}",
+                        topic, contract, message
+                    )));
+                }
+            }
+        }
+        let check: ProtobufCheck = Arc::new(|payload: &[u8]| M::decode(payload).map(|_| ()).map_err(|e| e.to_string()));
+        let version = versions.len() as u32 + 1;
+        versions.push(ContractVersion { version, contract: Contract::Protobuf { message, check } });
+        Ok(version)
+    }
+
+    /// Latest contract of `topic`
+    pub fn latest(&self, topic: &str) -> Option<ContractVersion> {
+        self.topics.read().get(topic).and_then(|versions| versions.last().cloned())
+    }
+
+    /// All versions of `topic`, oldest first
+    pub fn versions(&self, topic: &str) -> Vec<ContractVersion> {
+        self.topics.read().get(topic).cloned().unwrap_or_default()
+    }
+
+    /// Topics with a contract
+    pub fn topics(&self) -> Vec<String> {
+        let mut topics: Vec<String> = self.topics.read().keys().cloned().collect();
+        topics.sort();
+        topics
+    }
+
+    /// Check a JSON value against the latest contract of `topic`
+    pub fn validate_json(&self, topic: &str, payload: &Value) -> Result<()> {
+        let Some(latest) = self.contract_for(topic)? else {
+            return Ok(());
+        };
+        match &latest.contract {
+            Contract::JsonSchema(schema) => schema.validate(payload).map_err(|errors| {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                RfError::Validation(format!(
+                    "Message for topic '{}' violates schema v{}: {}",
+                    topic,
+                    latest.version,
+                    errors.join("; ")
+                ))
+            }),
+            Contract::Protobuf { message, .. } => Err(RfError::Validation(format!(
+                "Topic '{}' expects protobuf {}, got JSON",
+                topic, message
+            ))),
+        }
+    }
+
+    /// Check a raw payload: JSON text or an encoded protobuf message
+    pub fn validate(&self, topic: &str, payload: &[u8]) -> Result<()> {
+        let Some(latest) = self.contract_for(topic)? else {
+            return Ok(());
+        };
+        match &latest.contract {
+            Contract::JsonSchema(_) => self.validate_json(topic, &parse(topic, payload)?),
+            Contract::Protobuf { message, check } => check(payload).map_err(|e| {
+                RfError::Validation(format!("Message for topic '{}' is not a valid {}: {}", topic, message, e))
+            }),
+        }
+    }
+
+    /// Validate a JSON payload and deserialize it, for consumers
+    pub fn decode<T: DeserializeOwned>(&self, topic: &str, payload: &[u8]) -> Result<T> {
+        let value = parse(topic, payload)?;
+        self.validate_json(topic, &value)?;
+        serde_json::from_value(value)
+            .map_err(|e| RfError::Serialization(format!("Failed to decode message for topic '{}': {}", topic, e)))
+    }
+
+    fn contract_for(&self, topic: &str) -> Result<Option<ContractVersion>> {
+        match self.latest(topic) {
+            Some(latest) => Ok(Some(latest)),
+            None if self.strict => Err(RfError::Validation(format!("No contract registered for topic '{}'", topic))),
+            None => Ok(None),
+        }
+    }
+}
+
+fn parse(topic: &str, payload: &[u8]) -> Result<Value> {
+    serde_json::from_slice(payload)
+        .map_err(|e| RfError::Validation(format!("Message for topic '{}' is not valid JSON: {}", topic, e)))
+}
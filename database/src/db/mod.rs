@@ -20,6 +20,12 @@
 //! - `pool_monitor`: 连接池状态监控和健康检查
 //! - `replication`: 主从复制和读写分离
 //! - `query_plan_cache`: 查询计划缓存优化
+//! - `stream`: 查询结果流式读取，用于导出等大结果集场景
+//! - `timeout`: 查询超时，结合请求上下文截止时间限制查询耗时
+//! - `statement`: 预编译语句和按连接池共享的 LRU 语句缓存
+//! - `outbox`: 事务性发件箱，与业务变更同事务写入事件并由后台中继发布
+//! - `contract`: 按主题注册的消息契约（JSON Schema、protobuf）与版本兼容性检查
+//! - `scope`: 按表注册的命名作用域和全局作用域（如租户过滤）
 
 pub mod model;
 pub mod query;
@@ -30,6 +36,12 @@ pub mod logger;
 pub mod pool_monitor;
 pub mod replication;
 pub mod query_plan_cache;
+pub mod stream;
+pub mod timeout;
+pub mod statement;
+pub mod outbox;
+pub mod contract;
+pub mod scope;
 
 pub use model::*;
 pub use query::*;
@@ -40,4 +52,9 @@ pub use logger::*;
 pub use pool_monitor::*;
 pub use replication::*;
 pub use query_plan_cache::*;
+pub use stream::*;
+pub use statement::*;
+pub use outbox::*;
+pub use contract::*;
+pub use scope::*;
 
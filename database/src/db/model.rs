@@ -9,11 +9,14 @@
 
 use super::database::Database;
 use super::query::QueryBuilder;
-use super::cache::QueryCache;
+use super::cache::CacheStore;
+use super::timeout;
+use super::trace;
 use rf_errors::Result;
 use serde::Serialize;
 use sqlx::Row;
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -36,29 +39,22 @@ pub struct Model {
     soft_delete_field: Option<String>,
     with_deleted: bool,
     only_deleted: bool,
-    cache: Option<Arc<QueryCache>>,
+    cache: Option<Arc<dyn CacheStore>>,
     cache_ttl: Option<Duration>,
     schema: Option<String>, // Database schema
+    timeout: Option<Duration>,
+    /// Global scopes bypassed with `without_scope`
+    skipped_scopes: Vec<String>,
+    /// All global scopes bypassed with `unscoped`
+    skip_global_scopes: bool,
 }
 
 impl Model {
     /// Create a new model for a table
-    /// 
-    /// Note: This method takes a reference to Database and wraps it in Arc.
-    /// The Database should outlive the Model. For better safety when Database
-    /// is already in an Arc, consider using `new_with_arc`.
+    ///
+    /// The model holds a clone of `database`, sharing its connection pool.
     pub fn new(database: &Database, table: String) -> Self {
-        // Wrap database reference in Arc for safe sharing
-        // This creates a new Arc that owns a copy of Database
-        // Safety: Database contains connection pools which are designed to be shared,
-        // so creating an Arc from a reference is acceptable here.
-        // The original Database must outlive this Model.
-        let database_arc = Arc::new(unsafe {
-            // Safety: Database is designed to be shared and long-lived.
-            // Creating an Arc from a reference is safe as long as the original
-            // Database outlives this Model, which is the typical use case.
-            std::ptr::read(database as *const Database)
-        });
+        let database_arc = Arc::new(database.clone());
         Self {
             database: database_arc,
             table,
@@ -72,9 +68,43 @@ impl Model {
             cache: None,
             cache_ttl: None,
             schema: None,
+            timeout: None,
+            skipped_scopes: Vec::new(),
+            skip_global_scopes: false,
         }
     }
 
+    /// Set the query timeout, overriding the database default
+    ///
+    /// The effective budget is the smaller of this and the time left before
+    /// the request deadline (`rf_core::ctx`). Exceeding it returns
+    /// `RfError::Timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn budget(&self) -> Option<Duration> {
+        timeout::budget(self.timeout.or(self.database.query_timeout()))
+    }
+
+    /// Inject faults for an operation on this table, such as `SELECT users`
+    ///
+    /// See `Database::with_faults`; a no-op without the `fault-injection` feature.
+    async fn inject_fault(&self, operation: &str, budget: Option<Duration>) -> Result<()> {
+        if cfg!(feature = "fault-injection") {
+            self.database.inject_fault(&format!("{} {}", operation, self.table), budget).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Run `query` in a client span for `operation` on this table, see `super::trace`
+    async fn traced<T>(&self, operation: &str, sql: Option<&str>, query: impl Future<Output = Result<T>>) -> Result<T> {
+        let span = trace::query_span(self.database.db_type(), operation, &self.full_table_name(), sql);
+        trace::traced(span, query).await
+    }
+
     /// Set database schema
     pub fn schema(mut self, schema: &str) -> Self {
         self.schema = Some(schema.to_string());
@@ -91,7 +121,9 @@ impl Model {
     }
 
     /// Enable query caching with TTL
-    pub fn cache(mut self, cache: Arc<QueryCache>, ttl: Duration) -> Self {
+    ///
+    /// Any `CacheStore` works, such as `QueryCache` or a two-tier memory and Redis cache.
+    pub fn cache(mut self, cache: Arc<dyn CacheStore>, ttl: Duration) -> Self {
         self.cache = Some(cache);
         self.cache_ttl = Some(ttl);
         self
@@ -304,12 +336,48 @@ impl Model {
         self
     }
 
-    /// Disable soft delete (unscoped)
+    /// Apply a named scope registered for this table with `Database::register_scope`
+    ///
+    /// An unknown scope matches no rows, so a misspelled filter never widens
+    /// a query or an update; the mistake is logged as an error.
+    pub fn scope(mut self, name: &str) -> Self {
+        match self.database.scopes().named(&self.table, name) {
+            Some(scope) => self.query = scope(self.query),
+            None => {
+                tracing::error!("Unknown scope '{}' for table {}, matching no rows", name, self.table);
+                self.query = self.query.and_where("1 = 0");
+            }
+        }
+        self
+    }
+
+    /// Bypass one global scope registered with `Database::register_global_scope`
+    pub fn without_scope(mut self, name: &str) -> Self {
+        self.skipped_scopes.push(name.to_string());
+        self
+    }
+
+    /// Bypass all global scopes and disable soft delete
     pub fn unscoped(mut self) -> Self {
         self.soft_delete_field = None;
+        self.skip_global_scopes = true;
         self
     }
 
+    /// The query with the table's global scopes applied
+    fn scoped_query(&self) -> QueryBuilder {
+        let mut query = self.query.clone();
+        if self.skip_global_scopes {
+            return query;
+        }
+        for (name, scope) in self.database.scopes().global(&self.table) {
+            if !self.skipped_scopes.contains(&name) {
+                query = scope(query);
+            }
+        }
+        query
+    }
+
     /// Include deleted records in query
     pub fn with_deleted(mut self) -> Self {
         self.with_deleted = true;
@@ -324,6 +392,11 @@ impl Model {
         self
     }
 
+    /// SELECT statement the model would run, including global scopes and the soft delete condition
+    pub fn to_sql(&self) -> String {
+        self.build_select_sql()
+    }
+
     /// Build SELECT SQL
     fn build_select_sql(&self) -> String {
         let fields = if self.fields.is_empty() {
@@ -332,32 +405,19 @@ impl Model {
             self.fields.join(", ")
         };
         let table_name = self.full_table_name();
-        let mut sql = format!("SELECT {} FROM {}", fields, table_name);
-        sql = self.query.build_select(&sql);
-        
-        // Add soft delete condition if enabled
-        if let Some(ref soft_field) = self.soft_delete_field {
-            if !self.with_deleted && !self.only_deleted {
-                // Normal query: exclude deleted records
-                let condition = format!("{} IS NULL", soft_field);
-                if sql.contains(" WHERE ") {
-                    sql = format!("{} AND {}", sql, condition);
-                } else {
-                    sql = format!("{} WHERE {}", sql, condition);
-                }
-            } else if self.only_deleted {
-                // Only deleted: include only deleted records
-                let condition = format!("{} IS NOT NULL", soft_field);
-                if sql.contains(" WHERE ") {
-                    sql = format!("{} AND {}", sql, condition);
-                } else {
-                    sql = format!("{} WHERE {}", sql, condition);
-                }
-            }
-            // with_deleted = true: no additional condition, include all
+        let sql = format!("SELECT {} FROM {}", fields, table_name);
+        self.read_query().build_select(&sql)
+    }
+
+    /// The scoped query with the soft delete condition, for reads
+    fn read_query(&self) -> QueryBuilder {
+        let query = self.scoped_query();
+        match self.soft_delete_field {
+            // with_deleted: no additional condition, include all
+            Some(ref soft_field) if self.only_deleted => query.and_where(&format!("{} IS NOT NULL", soft_field)),
+            Some(ref soft_field) if !self.with_deleted => query.and_where(&format!("{} IS NULL", soft_field)),
+            _ => query,
         }
-        
-        sql
     }
 
     /// Select all records (supports PostgreSQL, MySQL, SQLite)
@@ -378,10 +438,9 @@ impl Model {
         }
         
         let rows = if let Some(pool) = database.as_postgres() {
-            sqlx::query_as::<_, T>(&sql)
-                .fetch_all(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Query failed: {}", e)))?
+            let budget = self.budget();
+            self.inject_fault("SELECT", budget).await?;
+            self.traced("SELECT", Some(&sql), timeout::guard(budget, "Query failed", timeout::pg_fetch_all(pool, budget, sqlx::query_as::<_, T>(&sql)))).await?
         } else {
             // For MySQL and SQLite, we need different trait bounds
             // This is a limitation - full implementation would use macros or separate methods
@@ -400,6 +459,24 @@ impl Model {
         Ok(rows)
     }
 
+    /// Stream the selected rows as JSON objects
+    ///
+    /// At most `buffer` rows are read ahead of the consumer; dropping the
+    /// stream stops the query. Query caching does not apply.
+    pub fn stream(&self, buffer: usize) -> super::stream::RowStream {
+        let database = &*self.database;
+        let sql = self.build_select_sql();
+        if let Some(pool) = database.as_postgres() {
+            super::stream::RowStream::spawn(pool.clone(), sql, buffer)
+        } else if let Some(pool) = database.as_mysql() {
+            super::stream::RowStream::spawn(pool.clone(), sql, buffer)
+        } else if let Some(pool) = database.as_sqlite() {
+            super::stream::RowStream::spawn(pool.clone(), sql, buffer)
+        } else {
+            unreachable!("Database always holds one of the supported pools")
+        }
+    }
+
     /// Select all records for MySQL
     pub async fn all_mysql<T>(&self) -> Result<Vec<T>>
     where
@@ -409,11 +486,10 @@ impl Model {
         let sql = self.build_select_sql();
         
         if let Some(pool) = database.as_mysql() {
-            let rows = sqlx::query_as::<_, T>(&sql)
-                .fetch_all(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Query failed: {}", e)))?;
-            Ok(rows)
+            let budget = self.budget();
+            self.inject_fault("SELECT", budget).await?;
+            let sql = timeout::mysql_hint(&sql, budget);
+            self.traced("SELECT", Some(&sql), timeout::guard(budget, "Query failed", sqlx::query_as::<_, T>(&sql).fetch_all(pool))).await
         } else {
             Err(rf_errors::RfError::Database("Not a MySQL database".to_string()))
         }
@@ -428,11 +504,9 @@ impl Model {
         let sql = self.build_select_sql();
         
         if let Some(pool) = database.as_sqlite() {
-            let rows = sqlx::query_as::<_, T>(&sql)
-                .fetch_all(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Query failed: {}", e)))?;
-            Ok(rows)
+            let budget = self.budget();
+            self.inject_fault("SELECT", budget).await?;
+            self.traced("SELECT", Some(&sql), timeout::guard(budget, "Query failed", sqlx::query_as::<_, T>(&sql).fetch_all(pool))).await
         } else {
             Err(rf_errors::RfError::Database("Not a SQLite database".to_string()))
         }
@@ -456,10 +530,9 @@ impl Model {
         }
         
         let row = if let Some(pool) = database.as_postgres() {
-            sqlx::query_as::<_, T>(&sql)
-                .fetch_optional(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Query failed: {}", e)))?
+            let budget = self.budget();
+            self.inject_fault("SELECT", budget).await?;
+            self.traced("SELECT", Some(&sql), timeout::guard(budget, "Query failed", timeout::pg_fetch_optional(pool, budget, sqlx::query_as::<_, T>(&sql)))).await?
         } else {
             return Err(rf_errors::RfError::Database(
                 "one() method currently only supports PostgreSQL. Use raw_query_one() for other databases.".to_string()
@@ -485,11 +558,10 @@ impl Model {
         let sql = self.build_select_sql();
         
         if let Some(pool) = database.as_mysql() {
-            let row = sqlx::query_as::<_, T>(&sql)
-                .fetch_optional(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Query failed: {}", e)))?;
-            Ok(row)
+            let budget = self.budget();
+            self.inject_fault("SELECT", budget).await?;
+            let sql = timeout::mysql_hint(&sql, budget);
+            self.traced("SELECT", Some(&sql), timeout::guard(budget, "Query failed", sqlx::query_as::<_, T>(&sql).fetch_optional(pool))).await
         } else {
             Err(rf_errors::RfError::Database("Not a MySQL database".to_string()))
         }
@@ -504,11 +576,9 @@ impl Model {
         let sql = self.build_select_sql();
         
         if let Some(pool) = database.as_sqlite() {
-            let row = sqlx::query_as::<_, T>(&sql)
-                .fetch_optional(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Query failed: {}", e)))?;
-            Ok(row)
+            let budget = self.budget();
+            self.inject_fault("SELECT", budget).await?;
+            self.traced("SELECT", Some(&sql), timeout::guard(budget, "Query failed", sqlx::query_as::<_, T>(&sql).fetch_optional(pool))).await
         } else {
             Err(rf_errors::RfError::Database("Not a SQLite database".to_string()))
         }
@@ -529,6 +599,7 @@ impl Model {
         if obj.is_empty() {
             return Err(rf_errors::RfError::Internal("Cannot insert empty object".to_string()));
         }
+        self.inject_fault("INSERT", self.budget()).await?;
         
         // Build INSERT statement with placeholders
         let fields: Vec<String> = obj.keys().cloned().collect();
@@ -567,11 +638,9 @@ impl Model {
             }
         }
         
+        let budget = self.budget();
         let rows_affected = if let Some(pool) = database.as_postgres() {
-            query
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Insert failed: {}", e)))?
+            self.traced("INSERT", Some(&sql), timeout::guard(budget, "Insert failed", timeout::pg_execute(pool, budget, query))).await?
                 .rows_affected()
         } else if let Some(pool) = database.as_mysql() {
             // MySQL uses ? instead of $1, $2, etc.
@@ -596,10 +665,7 @@ impl Model {
                     };
                 }
             }
-            mysql_query
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Insert failed: {}", e)))?
+            self.traced("INSERT", Some(&mysql_sql), timeout::guard(budget, "Insert failed", mysql_query.execute(pool))).await?
                 .rows_affected()
         } else if let Some(pool) = database.as_sqlite() {
             // SQLite uses ? instead of $1, $2, etc.
@@ -624,10 +690,7 @@ impl Model {
                     };
                 }
             }
-            sqlite_query
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Insert failed: {}", e)))?
+            self.traced("INSERT", Some(&sqlite_sql), timeout::guard(budget, "Insert failed", sqlite_query.execute(pool))).await?
                 .rows_affected()
         } else {
             return Err(rf_errors::RfError::Database("Unsupported database type".to_string()));
@@ -641,11 +704,61 @@ impl Model {
         Ok(rows_affected)
     }
 
+    /// Insert a record inside a transaction
+    ///
+    /// The row becomes visible when `tx` commits, and the query cache for
+    /// the table is invalidated then; a rollback leaves the cache untouched.
+    pub async fn insert_tx<T: Serialize>(&self, tx: &mut super::transaction::TransactionWrapper, data: &T) -> Result<u64> {
+        let json_value = serde_json::to_value(data)
+            .map_err(|e| rf_errors::RfError::Internal(format!("Failed to serialize data: {}", e)))?;
+        let obj = json_value.as_object()
+            .ok_or_else(|| rf_errors::RfError::Internal("Data must be a JSON object".to_string()))?;
+        if obj.is_empty() {
+            return Err(rf_errors::RfError::Internal("Cannot insert empty object".to_string()));
+        }
+
+        let fields: Vec<&str> = obj.keys().map(String::as_str).collect();
+        let placeholders: Vec<String> = (1..=fields.len()).map(|i| format!("${}", i)).collect();
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            self.full_table_name(),
+            fields.join(", "),
+            placeholders.join(", ")
+        );
+        let params: Vec<super::query::ParamValue> = obj.values().map(|value| {
+            use super::query::ParamValue;
+            match value {
+                serde_json::Value::Null => ParamValue::Null,
+                serde_json::Value::Bool(b) => ParamValue::Bool(*b),
+                serde_json::Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+                    (Some(i), _) => ParamValue::Int(i),
+                    (None, Some(u)) => ParamValue::Int(u as i64),
+                    _ => ParamValue::Float(n.as_f64().unwrap_or_default()),
+                },
+                serde_json::Value::String(s) => ParamValue::String(s.clone()),
+                _ => ParamValue::String(value.to_string()),
+            }
+        }).collect();
+
+        let budget = self.budget();
+        self.inject_fault("INSERT", budget).await?;
+        let rows_affected = self.traced("INSERT", Some(&sql), timeout::run(budget, tx.execute_with(&sql, &params))).await?;
+        if let Some(ref cache) = self.cache {
+            tx.invalidate_on_commit(cache.clone(), &self.table);
+        }
+        Ok(rows_affected)
+    }
+
     /// Batch insert records (optimized with transaction)
     pub async fn batch_insert<T: Serialize>(&self, data: &[T]) -> Result<u64> {
+        self.traced("INSERT", None, timeout::run(self.budget(), self.batch_insert_inner(data))).await
+    }
+
+    async fn batch_insert_inner<T: Serialize>(&self, data: &[T]) -> Result<u64> {
         if data.is_empty() {
             return Ok(0);
         }
+        self.inject_fault("INSERT", None).await?;
         
         let database = &*self.database;
         let table_name = self.full_table_name();
@@ -854,9 +967,14 @@ impl Model {
 
     /// Batch update records
     pub async fn batch_update(&self, updates: &[(&str, &str)]) -> Result<u64> {
+        self.traced("UPDATE", None, timeout::run(self.budget(), self.batch_update_inner(updates))).await
+    }
+
+    async fn batch_update_inner(&self, updates: &[(&str, &str)]) -> Result<u64> {
         if updates.is_empty() {
             return Ok(0);
         }
+        self.inject_fault("UPDATE", None).await?;
         
         let database = &*self.database;
         
@@ -891,9 +1009,14 @@ impl Model {
 
     /// Batch delete records
     pub async fn batch_delete(&self, conditions: &[&str]) -> Result<u64> {
+        self.traced("DELETE", None, timeout::run(self.budget(), self.batch_delete_inner(conditions))).await
+    }
+
+    async fn batch_delete_inner(&self, conditions: &[&str]) -> Result<u64> {
         if conditions.is_empty() {
             return Ok(0);
         }
+        self.inject_fault("DELETE", None).await?;
         
         let database = &*self.database;
         
@@ -952,10 +1075,9 @@ impl Model {
         );
         
         if let Some(pool) = database.as_postgres() {
-            let result = sqlx::query(&sql)
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Upsert failed: {}", e)))?;
+            let budget = self.budget();
+            self.inject_fault("INSERT", budget).await?;
+            let result = self.traced("INSERT", Some(&sql), timeout::guard(budget, "Upsert failed", timeout::pg_execute(pool, budget, sqlx::query(&sql)))).await?;
             
             // Invalidate cache
             if let Some(ref cache) = self.cache {
@@ -970,32 +1092,11 @@ impl Model {
 
     /// Update records
     pub async fn update(&self, set: &str) -> Result<u64> {
-        let database = &*self.database;
         let table_name = self.full_table_name();
         let sql = format!("UPDATE {} SET {}", table_name, set);
-        let query = self.query.build_update(&sql);
+        let query = self.scoped_query().build_update(&sql);
         
-        let rows_affected = if let Some(pool) = database.as_postgres() {
-            sqlx::query(&query)
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Update failed: {}", e)))?
-                .rows_affected()
-        } else if let Some(pool) = database.as_mysql() {
-            sqlx::query(&query)
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Update failed: {}", e)))?
-                .rows_affected()
-        } else if let Some(pool) = database.as_sqlite() {
-            sqlx::query(&query)
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Update failed: {}", e)))?
-                .rows_affected()
-        } else {
-            return Err(rf_errors::RfError::Database("Unsupported database type".to_string()));
-        };
+        let rows_affected = self.execute(&query, "Update failed").await?;
         
         // Invalidate cache for this table
         if let Some(ref cache) = self.cache {
@@ -1008,64 +1109,22 @@ impl Model {
     /// Soft delete records (UPDATE deleted_at instead of DELETE)
     pub async fn soft_delete(&self) -> Result<u64> {
         let soft_field = self.soft_delete_field.as_deref().unwrap_or("deleted_at");
-        let database = &*self.database;
         let table_name = self.full_table_name();
         let sql = format!("UPDATE {} SET {} = NOW()", table_name, soft_field);
-        let query = self.query.build_update(&sql);
+        let query = self.scoped_query().build_update(&sql);
         
-        let rows_affected = if let Some(pool) = database.as_postgres() {
-            sqlx::query(&query)
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Soft delete failed: {}", e)))?
-                .rows_affected()
-        } else if let Some(pool) = database.as_mysql() {
-            sqlx::query(&query)
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Soft delete failed: {}", e)))?
-                .rows_affected()
-        } else if let Some(pool) = database.as_sqlite() {
-            sqlx::query(&query)
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Soft delete failed: {}", e)))?
-                .rows_affected()
-        } else {
-            return Err(rf_errors::RfError::Database("Unsupported database type".to_string()));
-        };
+        let rows_affected = self.execute(&query, "Soft delete failed").await?;
         Ok(rows_affected)
     }
 
     /// Restore soft deleted records
     pub async fn restore(&self) -> Result<u64> {
         let soft_field = self.soft_delete_field.as_deref().unwrap_or("deleted_at");
-        let database = &*self.database;
         let table_name = self.full_table_name();
         let sql = format!("UPDATE {} SET {} = NULL", table_name, soft_field);
-        let query = self.query.build_update(&sql);
+        let query = self.scoped_query().build_update(&sql);
         
-        let rows_affected = if let Some(pool) = database.as_postgres() {
-            sqlx::query(&query)
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Restore failed: {}", e)))?
-                .rows_affected()
-        } else if let Some(pool) = database.as_mysql() {
-            sqlx::query(&query)
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Restore failed: {}", e)))?
-                .rows_affected()
-        } else if let Some(pool) = database.as_sqlite() {
-            sqlx::query(&query)
-                .execute(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Restore failed: {}", e)))?
-                .rows_affected()
-        } else {
-            return Err(rf_errors::RfError::Database("Unsupported database type".to_string()));
-        };
+        let rows_affected = self.execute(&query, "Restore failed").await?;
         Ok(rows_affected)
     }
 
@@ -1076,32 +1135,11 @@ impl Model {
             self.soft_delete().await
         } else {
             // Hard delete
-            let database = &*self.database;
             let table_name = self.full_table_name();
             let sql = format!("DELETE FROM {}", table_name);
-            let query = self.query.build_delete(&sql);
+            let query = self.scoped_query().build_delete(&sql);
             
-            let rows_affected = if let Some(pool) = database.as_postgres() {
-                sqlx::query(&query)
-                    .execute(pool)
-                    .await
-                    .map_err(|e| rf_errors::RfError::Database(format!("Delete failed: {}", e)))?
-                    .rows_affected()
-            } else if let Some(pool) = database.as_mysql() {
-                sqlx::query(&query)
-                    .execute(pool)
-                    .await
-                    .map_err(|e| rf_errors::RfError::Database(format!("Delete failed: {}", e)))?
-                    .rows_affected()
-            } else if let Some(pool) = database.as_sqlite() {
-                sqlx::query(&query)
-                    .execute(pool)
-                    .await
-                    .map_err(|e| rf_errors::RfError::Database(format!("Delete failed: {}", e)))?
-                    .rows_affected()
-            } else {
-                return Err(rf_errors::RfError::Database("Unsupported database type".to_string()));
-            };
+            let rows_affected = self.execute(&query, "Delete failed").await?;
             Ok(rows_affected)
         }?;
         
@@ -1113,30 +1151,24 @@ impl Model {
         Ok(result)
     }
 
-    /// Count records
+    /// Count records, excluding soft deleted ones like `all`
     pub async fn count(&self) -> Result<i64> {
         let database = &*self.database;
         let table_name = self.full_table_name();
         let sql = format!("SELECT COUNT(*) FROM {}", table_name);
-        let query = self.query.build_select(&sql);
+        let query = self.read_query().build_select(&sql);
         
+        let budget = self.budget();
+        self.inject_fault("SELECT", budget).await?;
         let count: i64 = if let Some(pool) = database.as_postgres() {
-            let row = sqlx::query(&query)
-                .fetch_one(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Count failed: {}", e)))?;
+            let row = self.traced("SELECT", Some(&query), timeout::guard(budget, "Count failed", timeout::pg_fetch_one(pool, budget, sqlx::query(&query)))).await?;
             row.get(0)
         } else if let Some(pool) = database.as_mysql() {
-            let row = sqlx::query(&query)
-                .fetch_one(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Count failed: {}", e)))?;
+            let query = timeout::mysql_hint(&query, budget);
+            let row = self.traced("SELECT", Some(&query), timeout::guard(budget, "Count failed", sqlx::query(&query).fetch_one(pool))).await?;
             row.get(0)
         } else if let Some(pool) = database.as_sqlite() {
-            let row = sqlx::query(&query)
-                .fetch_one(pool)
-                .await
-                .map_err(|e| rf_errors::RfError::Database(format!("Count failed: {}", e)))?;
+            let row = self.traced("SELECT", Some(&query), timeout::guard(budget, "Count failed", sqlx::query(&query).fetch_one(pool))).await?;
             row.get(0)
         } else {
             return Err(rf_errors::RfError::Database("Unsupported database type".to_string()));
@@ -1144,13 +1176,34 @@ impl Model {
         Ok(count)
     }
 
+    /// Execute a statement without bind parameters within the query budget
+    async fn execute(&self, sql: &str, context: &str) -> Result<u64> {
+        let database = &*self.database;
+        let budget = self.budget();
+        let operation = trace::operation_of(sql);
+        self.inject_fault(&operation, budget).await?;
+        let result = if let Some(pool) = database.as_postgres() {
+            self.traced(&operation, Some(sql), timeout::guard(budget, context, timeout::pg_execute(pool, budget, sqlx::query(sql)))).await?
+                .rows_affected()
+        } else if let Some(pool) = database.as_mysql() {
+            self.traced(&operation, Some(sql), timeout::guard(budget, context, sqlx::query(sql).execute(pool))).await?
+                .rows_affected()
+        } else if let Some(pool) = database.as_sqlite() {
+            self.traced(&operation, Some(sql), timeout::guard(budget, context, sqlx::query(sql).execute(pool))).await?
+                .rows_affected()
+        } else {
+            return Err(rf_errors::RfError::Database("Unsupported database type".to_string()));
+        };
+        Ok(result)
+    }
+
     /// Execute raw SQL query
     pub async fn raw<T>(&self, sql: &str) -> Result<Vec<T>>
     where
         T: for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Send + Unpin,
     {
         let database = &*self.database;
-        database.raw_query(sql).await
+        self.traced(&trace::operation_of(sql), Some(sql), database.raw_query(sql)).await
     }
 
     /// Execute raw SQL query and return one result
@@ -1159,13 +1212,13 @@ impl Model {
         T: for<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> + Send + Unpin,
     {
         let database = &*self.database;
-        database.raw_query_one(sql).await
+        self.traced(&trace::operation_of(sql), Some(sql), database.raw_query_one(sql)).await
     }
 
     /// Execute raw SQL (INSERT/UPDATE/DELETE)
     pub async fn raw_execute(&self, sql: &str) -> Result<u64> {
         let database = &*self.database;
-        let result = database.raw_execute(sql).await?;
+        let result = self.traced(&trace::operation_of(sql), Some(sql), database.raw_execute(sql)).await?;
         
         // Invalidate cache
         if let Some(ref cache) = self.cache {
@@ -0,0 +1,512 @@
+//! # outbox
+//!
+//! outbox 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Transactional outbox
+//!
+//! Events are inserted into an outbox table with `Model::insert_tx`, in the
+//! same transaction as the business change, so an event exists exactly when
+//! the change commits. `OutboxRelay` polls the table in the background, hands
+//! pending events to an `OutboxPublisher` (a Redis stream, a message broker, a
+//! webhook, ...), marks them published and deletes them after a retention
+//! period.
+//!
+//! Delivery is at-least-once: an event is published again if the relay stops
+//! between publishing and marking it, so consumers should deduplicate on
+//! `OutboxEvent::event_id`. Events sharing a key are published in insert
+//! order; a failed event holds back later events with the same key until it
+//! is published or runs out of attempts. Several relays may run against one
+//! table on PostgreSQL and MySQL 8 (`FOR UPDATE SKIP LOCKED`), although
+//! per-key ordering is then only guaranteed between events of one batch.
+//!
+//! With `Outbox::contracts`, payloads are checked against the topic's
+//! contract when enqueued and again before publishing (see
+//! [`crate::db::contract`]); an event failing the check is never published.
+//!
+//! ```rust,ignore
+//! use rf_database::db::{Outbox, RedisStreamPublisher};
+//!
+//! let outbox = Outbox::new(&db);
+//! outbox.migrate().await?;
+//! let relay = outbox.relay(RedisStreamPublisher::new(redis.stream())).spawn();
+//!
+//! let mut tx = db.begin().await?;
+//! db.model("orders").insert_tx(&mut tx, &order).await?;
+//! outbox.enqueue_keyed(&mut tx, "order.created", &order.id.to_string(), &order).await?;
+//! tx.commit().await?;
+//! relay.wake();
+//! ```
+
+use super::contract::ContractRegistry;
+use super::database::{Database, DatabaseType};
+use super::query::ParamValue;
+use super::stream::JsonRow;
+use super::transaction::TransactionWrapper;
+use crate::redis::StreamGroup;
+use futures_util::future::BoxFuture;
+use rf_errors::{Result, RfError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{watch, Notify};
+use tokio::task::JoinHandle;
+
+/// Default outbox table
+pub const DEFAULT_OUTBOX_TABLE: &str = "rf_outbox";
+
+/// Longest failure message stored with an event
+const MAX_ERROR_LEN: usize = 1000;
+
+/// Longest delay between attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(3600);
+
+/// An event read from the outbox
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboxEvent {
+    /// Row id, increasing in insert order
+    pub id: i64,
+    /// Unique event id for consumer deduplication
+    pub event_id: String,
+    pub topic: String,
+    /// Ordering key, e.g. the aggregate id
+    pub event_key: Option<String>,
+    /// JSON payload
+    pub payload: String,
+    /// Failed publish attempts so far
+    pub attempts: i64,
+    /// Enqueue time, Unix milliseconds
+    pub created_at: i64,
+}
+
+impl OutboxEvent {
+    /// Decode the payload
+    pub fn payload<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_str(&self.payload)
+            .map_err(|e| RfError::Serialization(format!("Invalid outbox payload for {}: {}", self.event_id, e)))
+    }
+}
+
+/// Outbox table bound to a database
+#[derive(Clone)]
+pub struct Outbox {
+    database: Database,
+    table: String,
+    contracts: Option<Arc<ContractRegistry>>,
+}
+
+impl Outbox {
+    /// Outbox stored in `rf_outbox`
+    pub fn new(database: &Database) -> Self {
+        Self {
+            database: database.clone(),
+            table: DEFAULT_OUTBOX_TABLE.to_string(),
+            contracts: None,
+        }
+    }
+
+    /// Validate payloads against topic contracts
+    pub fn contracts(mut self, contracts: Arc<ContractRegistry>) -> Self {
+        self.contracts = Some(contracts);
+        self
+    }
+
+    /// Use another table name
+    pub fn table(mut self, table: &str) -> Self {
+        self.table = table.to_string();
+        self
+    }
+
+    /// Table name
+    pub fn table_name(&self) -> &str {
+        &self.table
+    }
+
+    /// Create the outbox table and index if missing
+    pub async fn migrate(&self) -> Result<()> {
+        let table = &self.table;
+        let statements = match self.database.db_type() {
+            DatabaseType::Postgres => vec![
+                format!(
+                    "CREATE TABLE IF NOT EXISTS {table} (id BIGSERIAL PRIMARY KEY, event_id VARCHAR(64) NOT NULL UNIQUE, \
+                     topic VARCHAR(255) NOT NULL, event_key VARCHAR(255), payload TEXT NOT NULL, \
+                     attempts BIGINT NOT NULL DEFAULT 0, next_attempt_at BIGINT NOT NULL DEFAULT 0, \
+                     created_at BIGINT NOT NULL, published_at BIGINT, last_error TEXT)"
+                ),
+                format!("CREATE INDEX IF NOT EXISTS {table}_pending ON {table} (published_at, next_attempt_at, id)"),
+            ],
+            DatabaseType::MySql => vec![format!(
+                "CREATE TABLE IF NOT EXISTS {table} (id BIGINT AUTO_INCREMENT PRIMARY KEY, event_id VARCHAR(64) NOT NULL UNIQUE, \
+                 topic VARCHAR(255) NOT NULL, event_key VARCHAR(255), payload LONGTEXT NOT NULL, \
+                 attempts BIGINT NOT NULL DEFAULT 0, next_attempt_at BIGINT NOT NULL DEFAULT 0, \
+                 created_at BIGINT NOT NULL, published_at BIGINT, last_error TEXT, \
+                 INDEX {table}_pending (published_at, next_attempt_at, id))"
+            )],
+            DatabaseType::Sqlite => vec![
+                format!(
+                    "CREATE TABLE IF NOT EXISTS {table} (id INTEGER PRIMARY KEY AUTOINCREMENT, event_id TEXT NOT NULL UNIQUE, \
+                     topic TEXT NOT NULL, event_key TEXT, payload TEXT NOT NULL, \
+                     attempts INTEGER NOT NULL DEFAULT 0, next_attempt_at INTEGER NOT NULL DEFAULT 0, \
+                     created_at INTEGER NOT NULL, published_at INTEGER, last_error TEXT)"
+                ),
+                format!("CREATE INDEX IF NOT EXISTS {table}_pending ON {table} (published_at, next_attempt_at, id)"),
+            ],
+        };
+        let mut tx = self.database.begin().await?;
+        for sql in statements {
+            tx.execute(&sql).await?;
+        }
+        tx.commit().await
+    }
+
+    /// Add an event to `tx`, returns its event id
+    pub async fn enqueue<T: Serialize>(&self, tx: &mut TransactionWrapper, topic: &str, payload: &T) -> Result<String> {
+        self.insert(tx, topic, None, payload).await
+    }
+
+    /// Add an event with an ordering key to `tx`, returns its event id
+    pub async fn enqueue_keyed<T: Serialize>(
+        &self,
+        tx: &mut TransactionWrapper,
+        topic: &str,
+        key: &str,
+        payload: &T,
+    ) -> Result<String> {
+        self.insert(tx, topic, Some(key), payload).await
+    }
+
+    async fn insert<T: Serialize>(
+        &self,
+        tx: &mut TransactionWrapper,
+        topic: &str,
+        key: Option<&str>,
+        payload: &T,
+    ) -> Result<String> {
+        let payload = serde_json::to_value(payload)
+            .map_err(|e| RfError::Serialization(format!("Failed to serialize outbox payload: {}", e)))?;
+        if let Some(contracts) = &self.contracts {
+            contracts.validate_json(topic, &payload)?;
+        }
+        let payload = payload.to_string();
+        let event_id = uuid::Uuid::new_v4().simple().to_string();
+        let row = serde_json::json!({
+            "event_id": event_id,
+            "topic": topic,
+            "event_key": key,
+            "payload": payload,
+            "created_at": now_millis(),
+        });
+        self.database.model(&self.table).insert_tx(tx, &row).await?;
+        Ok(event_id)
+    }
+
+    /// Number of events not yet published, including ones out of attempts
+    pub async fn pending(&self) -> Result<i64> {
+        let mut tx = self.database.begin().await?;
+        let rows = tx
+            .fetch_json(&format!("SELECT COUNT(*) AS pending FROM {} WHERE published_at IS NULL", self.table), &[])
+            .await?;
+        tx.commit().await?;
+        Ok(rows.first().and_then(|row| row.get("pending")).and_then(|v| v.as_i64()).unwrap_or(0))
+    }
+
+    /// Relay publishing this outbox through `publisher`
+    pub fn relay(&self, publisher: impl OutboxPublisher) -> OutboxRelay {
+        OutboxRelay {
+            outbox: self.clone(),
+            publisher: Arc::new(publisher),
+            batch_size: 100,
+            poll_interval: Duration::from_secs(1),
+            max_attempts: 10,
+            retry_backoff: Duration::from_secs(1),
+            retention: Some(Duration::from_secs(7 * 24 * 3600)),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+}
+
+/// Destination of outbox events
+pub trait OutboxPublisher: Send + Sync + 'static {
+    /// Publish one event; an error schedules a retry
+    fn publish<'a>(&'a self, event: &'a OutboxEvent) -> BoxFuture<'a, Result<()>>;
+}
+
+impl<F, Fut> OutboxPublisher for F
+where
+    F: Fn(OutboxEvent) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    fn publish<'a>(&'a self, event: &'a OutboxEvent) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self(event.clone()))
+    }
+}
+
+/// Publishes events to Redis streams named `{prefix}{topic}`
+///
+/// Entries carry the `event_id`, `key` and `payload` fields.
+pub struct RedisStreamPublisher {
+    stream: StreamGroup,
+    prefix: String,
+    max_len: Option<usize>,
+}
+
+impl RedisStreamPublisher {
+    pub fn new(stream: StreamGroup) -> Self {
+        Self {
+            stream,
+            prefix: String::new(),
+            max_len: None,
+        }
+    }
+
+    /// Prefix stream names, e.g. `events:`
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Trim streams to about `max_len` entries
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+}
+
+impl OutboxPublisher for RedisStreamPublisher {
+    fn publish<'a>(&'a self, event: &'a OutboxEvent) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let stream = format!("{}{}", self.prefix, event.topic);
+            let fields = [
+                ("event_id", event.event_id.as_str()),
+                ("key", event.event_key.as_deref().unwrap_or("")),
+                ("payload", event.payload.as_str()),
+            ];
+            match self.max_len {
+                Some(max_len) => self.stream.xadd_maxlen(&stream, max_len, &fields).await?,
+                None => self.stream.xadd(&stream, "*", &fields).await?,
+            };
+            Ok(())
+        })
+    }
+}
+
+/// Result of one relay pass
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RelayStats {
+    pub published: usize,
+    pub failed: usize,
+    /// Published events removed after the retention period
+    pub deleted: u64,
+}
+
+/// Background publisher of outbox events
+pub struct OutboxRelay {
+    outbox: Outbox,
+    publisher: Arc<dyn OutboxPublisher>,
+    batch_size: usize,
+    poll_interval: Duration,
+    max_attempts: u32,
+    retry_backoff: Duration,
+    retention: Option<Duration>,
+    wake: Arc<Notify>,
+}
+
+impl OutboxRelay {
+    /// Events claimed per pass (default 100)
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Wait between passes when the outbox is drained (default 1s)
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Attempts before an event is left unpublished for inspection (default 10)
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Delay after the first failure, doubled per attempt up to an hour (default 1s)
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Keep published events for `retention` (default 7 days), `None` keeps them forever
+    pub fn retention(mut self, retention: Option<Duration>) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Publish one batch of due events and clean up old ones
+    pub async fn run_once(&self) -> Result<RelayStats> {
+        let table = &self.outbox.table;
+        let now = now_millis();
+        let max_attempts = ParamValue::Int(self.max_attempts as i64);
+        let mut stats = RelayStats::default();
+
+        let mut tx = self.outbox.database.begin().await?;
+        let lock = match tx.db_type() {
+            DatabaseType::Postgres | DatabaseType::MySql => " FOR UPDATE SKIP LOCKED",
+            DatabaseType::Sqlite => "",
+        };
+        // Events waiting behind an earlier failed event with the same key are held back
+        let sql = format!(
+            "SELECT id, event_id, topic, event_key, payload, attempts, created_at FROM {table} e \
+             WHERE published_at IS NULL AND attempts < $1 AND next_attempt_at <= $2 \
+             AND (event_key IS NULL OR NOT EXISTS (SELECT 1 FROM {table} prior WHERE prior.event_key = e.event_key \
+             AND prior.published_at IS NULL AND prior.attempts < $3 AND prior.next_attempt_at > $4 AND prior.id < e.id)) \
+             ORDER BY id LIMIT $5{lock}"
+        );
+        let rows = tx
+            .fetch_json(&sql, &[
+                max_attempts.clone(),
+                ParamValue::Int(now),
+                max_attempts,
+                ParamValue::Int(now),
+                ParamValue::Int(self.batch_size as i64),
+            ])
+            .await?;
+
+        let mut held_keys = HashSet::new();
+        for row in rows {
+            let event = decode_event(row)?;
+            if event.event_key.as_ref().is_some_and(|key| held_keys.contains(key)) {
+                continue;
+            }
+            let result = match &self.outbox.contracts {
+                Some(contracts) => match contracts.validate(&event.topic, event.payload.as_bytes()) {
+                    Ok(()) => self.publisher.publish(&event).await,
+                    Err(e) => Err(e),
+                },
+                None => self.publisher.publish(&event).await,
+            };
+            match result {
+                Ok(()) => {
+                    tx.execute_with(
+                        &format!("UPDATE {table} SET published_at = $1 WHERE id = $2"),
+                        &[ParamValue::Int(now_millis()), ParamValue::Int(event.id)],
+                    )
+                    .await?;
+                    stats.published += 1;
+                }
+                Err(e) => {
+                    let attempts = event.attempts + 1;
+                    if attempts >= self.max_attempts as i64 {
+                        tracing::error!(event_id = %event.event_id, topic = %event.topic, "Giving up on outbox event after {} attempts: {}", attempts, e);
+                    } else {
+                        tracing::warn!(event_id = %event.event_id, topic = %event.topic, "Failed to publish outbox event: {}", e);
+                    }
+                    let mut message = e.to_string();
+                    if message.len() > MAX_ERROR_LEN {
+                        let mut end = MAX_ERROR_LEN;
+                        while !message.is_char_boundary(end) {
+                            end -= 1;
+                        }
+                        message.truncate(end);
+                    }
+                    tx.execute_with(
+                        &format!("UPDATE {table} SET attempts = $1, next_attempt_at = $2, last_error = $3 WHERE id = $4"),
+                        &[
+                            ParamValue::Int(attempts),
+                            ParamValue::Int(now_millis() + self.backoff(attempts).as_millis() as i64),
+                            ParamValue::String(message),
+                            ParamValue::Int(event.id),
+                        ],
+                    )
+                    .await?;
+                    if let Some(key) = event.event_key {
+                        held_keys.insert(key);
+                    }
+                    stats.failed += 1;
+                }
+            }
+        }
+        tx.commit().await?;
+
+        if let Some(retention) = self.retention {
+            let mut tx = self.outbox.database.begin().await?;
+            stats.deleted = tx
+                .execute_with(
+                    &format!("DELETE FROM {table} WHERE published_at IS NOT NULL AND published_at < $1"),
+                    &[ParamValue::Int(now - retention.as_millis() as i64)],
+                )
+                .await?;
+            tx.commit().await?;
+        }
+        Ok(stats)
+    }
+
+    /// Run passes in the background until the handle is shut down
+    pub fn spawn(self) -> OutboxRelayHandle {
+        let (stop, mut stopped) = watch::channel(false);
+        let wake = self.wake.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                // A full batch suggests more events are due right away
+                let busy = match self.run_once().await {
+                    Ok(stats) => stats.published + stats.failed >= self.batch_size,
+                    Err(e) => {
+                        tracing::warn!("Outbox relay pass failed: {}", e);
+                        false
+                    }
+                };
+                if *stopped.borrow() {
+                    break;
+                }
+                if busy {
+                    continue;
+                }
+                tokio::select! {
+                    _ = stopped.changed() => break,
+                    _ = self.wake.notified() => {}
+                    _ = tokio::time::sleep(self.poll_interval) => {}
+                }
+            }
+        });
+        OutboxRelayHandle { stop, wake, task }
+    }
+
+    fn backoff(&self, attempts: i64) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1).clamp(0, 31) as u32);
+        self.retry_backoff.saturating_mul(factor).min(MAX_BACKOFF)
+    }
+}
+
+/// Handle of a spawned relay
+pub struct OutboxRelayHandle {
+    stop: watch::Sender<bool>,
+    wake: Arc<Notify>,
+    task: JoinHandle<()>,
+}
+
+impl OutboxRelayHandle {
+    /// Start the next pass now instead of after the poll interval
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Stop after the current pass
+    pub async fn shutdown(self) {
+        let _ = self.stop.send(true);
+        let _ = self.task.await;
+    }
+}
+
+fn decode_event(row: JsonRow) -> Result<OutboxEvent> {
+    serde_json::from_value(serde_json::Value::Object(row))
+        .map_err(|e| RfError::Database(format!("Invalid outbox row: {}", e)))
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or_default()
+}
@@ -54,7 +54,6 @@
 //! # }
 //! ```
 
-use rf_errors::Result;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
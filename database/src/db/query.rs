@@ -86,6 +86,7 @@ pub struct ParameterizedQuery {
 }
 
 /// Query builder for constructing SQL queries
+#[derive(Clone)]
 pub struct QueryBuilder {
     where_clauses: Vec<String>,
     where_groups: Vec<WhereGroup>,
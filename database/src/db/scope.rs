@@ -0,0 +1,101 @@
+//! # scope
+//!
+//! scope 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Query scopes
+//!
+//! Scopes are reusable query constraints registered per table on a
+//! `Database`. Named scopes are applied on demand with `Model::scope`;
+//! global scopes are added to every query of the table (SELECT, COUNT,
+//! UPDATE and DELETE) until bypassed with `Model::without_scope` or
+//! `Model::unscoped`.
+//!
+//! ```rust,no_run
+//! use rf_database::db::Database;
+//!
+//! # async fn example(db: Database, tenant_id: i64) -> rf_errors::Result<()> {
+//! db.register_scope("posts", "published", |q| q.and_where("status = 'published'"));
+//! db.register_global_scope("posts", "tenant", move |q| q.and_where(&format!("tenant_id = {}", tenant_id)));
+//!
+//! // ... WHERE status = 'published' AND tenant_id = 7 AND deleted_at IS NULL
+//! let count = db.model("posts").scope("published").count().await?;
+//! // Cross-tenant report: no global scopes, no soft delete filter
+//! let total = db.model("posts").unscoped().count().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::query::QueryBuilder;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// A scope: adds constraints to a query
+pub type ScopeFn = Arc<dyn Fn(QueryBuilder) -> QueryBuilder + Send + Sync>;
+
+#[derive(Default)]
+struct TableScopes {
+    named: HashMap<String, ScopeFn>,
+    /// In registration order
+    global: Vec<(String, ScopeFn)>,
+}
+
+/// Scopes of all tables of a database, shared by its clones
+#[derive(Default)]
+pub struct ScopeRegistry {
+    tables: RwLock<HashMap<String, TableScopes>>,
+}
+
+impl ScopeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a named scope, replacing one of the same name
+    pub fn register<F>(&self, table: &str, name: &str, scope: F)
+    where
+        F: Fn(QueryBuilder) -> QueryBuilder + Send + Sync + 'static,
+    {
+        let mut tables = self.tables.write().unwrap_or_else(|e| e.into_inner());
+        tables.entry(table.to_string()).or_default().named.insert(name.to_string(), Arc::new(scope));
+    }
+
+    /// Register a global scope, replacing one of the same name
+    pub fn register_global<F>(&self, table: &str, name: &str, scope: F)
+    where
+        F: Fn(QueryBuilder) -> QueryBuilder + Send + Sync + 'static,
+    {
+        let mut tables = self.tables.write().unwrap_or_else(|e| e.into_inner());
+        let global = &mut tables.entry(table.to_string()).or_default().global;
+        let scope: ScopeFn = Arc::new(scope);
+        match global.iter_mut().find(|(existing, _)| existing == name) {
+            Some(entry) => entry.1 = scope,
+            None => global.push((name.to_string(), scope)),
+        }
+    }
+
+    /// Remove a named or global scope; true if one was registered
+    pub fn remove(&self, table: &str, name: &str) -> bool {
+        let mut tables = self.tables.write().unwrap_or_else(|e| e.into_inner());
+        let Some(scopes) = tables.get_mut(table) else {
+            return false;
+        };
+        let before = scopes.global.len();
+        scopes.global.retain(|(existing, _)| existing != name);
+        scopes.named.remove(name).is_some() || scopes.global.len() != before
+    }
+
+    /// A named scope of a table
+    pub fn named(&self, table: &str, name: &str) -> Option<ScopeFn> {
+        let tables = self.tables.read().unwrap_or_else(|e| e.into_inner());
+        tables.get(table)?.named.get(name).cloned()
+    }
+
+    /// Global scopes of a table, in registration order
+    pub fn global(&self, table: &str) -> Vec<(String, ScopeFn)> {
+        let tables = self.tables.read().unwrap_or_else(|e| e.into_inner());
+        tables.get(table).map(|scopes| scopes.global.clone()).unwrap_or_default()
+    }
+}
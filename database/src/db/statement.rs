@@ -0,0 +1,297 @@
+//! # statement
+//!
+//! statement 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Prepared statements
+//!
+//! `Database::prepare` parses and describes a statement once and keeps it in
+//! an LRU cache shared by every clone of the `Database` (one cache per pool).
+//! Later `prepare` calls for the same SQL are served from the cache without a
+//! round trip; `statement_cache_stats` reports the hit rate.
+//!
+//! The server-side statement handles live on each pooled connection in sqlx's
+//! own per-connection cache (`statement_cache_capacity`, 100 by default).
+//! Executing a `PreparedStatement` reuses that handle, so keep this cache no
+//! larger than the per-connection one.
+//!
+//! Placeholders use the database's native syntax: `$1, $2` for PostgreSQL,
+//! `?` for MySQL and SQLite.
+
+use super::database::Database;
+use super::query::ParamValue;
+use super::stream::{JsonRow, JsonRowExt};
+use super::timeout;
+use rf_errors::{Result, RfError};
+use sqlx::query::Query;
+use sqlx::{Column, Either, Encode, Executor, Pool, Statement, Type};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Default number of cached statements per pool
+pub const DEFAULT_STATEMENT_CACHE_CAPACITY: usize = 100;
+
+/// Statement metadata returned by the database when preparing
+#[derive(Debug, Clone)]
+pub struct StatementInfo {
+    pub sql: String,
+    /// Number of bind parameters, `None` when the driver does not report it
+    pub params: Option<usize>,
+    pub columns: Vec<String>,
+}
+
+/// Statement cache counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatementCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub size: usize,
+    pub capacity: usize,
+}
+
+impl StatementCacheStats {
+    /// Fraction of `prepare` calls served from the cache
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+#[derive(Default)]
+struct Entries {
+    // sql -> (statement, last use)
+    map: HashMap<String, (Arc<StatementInfo>, u64)>,
+    // last use -> sql, oldest first
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+/// LRU cache of prepared statements
+pub struct StatementCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl StatementCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(Entries::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Look up a statement, marking it as most recently used
+    pub fn get(&self, sql: &str) -> Option<Arc<StatementInfo>> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.tick += 1;
+        let tick = entries.tick;
+        let Some((info, last)) = entries.map.get_mut(sql) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        let (info, previous) = (info.clone(), std::mem::replace(last, tick));
+        entries.order.remove(&previous);
+        entries.order.insert(tick, sql.to_string());
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(info)
+    }
+
+    /// Insert a statement, evicting the least recently used one when full
+    pub fn insert(&self, info: Arc<StatementInfo>) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.tick += 1;
+        let tick = entries.tick;
+        if let Some((_, previous)) = entries.map.insert(info.sql.clone(), (info.clone(), tick)) {
+            entries.order.remove(&previous);
+        }
+        entries.order.insert(tick, info.sql.clone());
+        while entries.map.len() > self.capacity {
+            let Some((_, sql)) = entries.order.pop_first() else { break };
+            entries.map.remove(&sql);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Drop every cached statement; counters are kept
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.map.clear();
+        entries.order.clear();
+    }
+
+    pub fn stats(&self) -> StatementCacheStats {
+        StatementCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            size: self.entries.lock().unwrap().map.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// A statement prepared with `Database::prepare`
+///
+/// Cheap to clone; execute it any number of times with different parameters.
+#[derive(Clone)]
+pub struct PreparedStatement {
+    database: Database,
+    info: Arc<StatementInfo>,
+}
+
+impl PreparedStatement {
+    pub(crate) async fn prepare(database: &Database, sql: &str) -> Result<Self> {
+        let cache = database.statement_cache();
+        let info = match cache.get(sql) {
+            Some(info) => info,
+            None => {
+                let budget = timeout::budget(database.query_timeout());
+                let info = if let Some(pool) = database.as_postgres() {
+                    timeout::guard(budget, "Prepare failed", describe(pool, sql)).await?
+                } else if let Some(pool) = database.as_mysql() {
+                    timeout::guard(budget, "Prepare failed", describe(pool, sql)).await?
+                } else if let Some(pool) = database.as_sqlite() {
+                    timeout::guard(budget, "Prepare failed", describe(pool, sql)).await?
+                } else {
+                    return Err(RfError::Database("Unsupported database type".to_string()));
+                };
+                let info = Arc::new(info);
+                cache.insert(info.clone());
+                info
+            }
+        };
+        Ok(Self {
+            database: database.clone(),
+            info,
+        })
+    }
+
+    pub fn sql(&self) -> &str {
+        &self.info.sql
+    }
+
+    /// Number of bind parameters, when reported by the driver
+    pub fn params(&self) -> Option<usize> {
+        self.info.params
+    }
+
+    /// Result column names
+    pub fn columns(&self) -> &[String] {
+        &self.info.columns
+    }
+
+    /// Execute the statement, returning the number of affected rows
+    pub async fn execute(&self, params: &[ParamValue]) -> Result<u64> {
+        self.check_params(params)?;
+        let database = &self.database;
+        let budget = timeout::budget(database.query_timeout());
+        database.inject_fault(&self.info.sql, budget).await?;
+        let result = if let Some(pool) = database.as_postgres() {
+            timeout::guard(budget, "Execute failed", timeout::pg_execute(pool, budget, bind(&self.info.sql, params))).await?
+                .rows_affected()
+        } else if let Some(pool) = database.as_mysql() {
+            timeout::guard(budget, "Execute failed", bind(&self.info.sql, params).execute(pool)).await?
+                .rows_affected()
+        } else if let Some(pool) = database.as_sqlite() {
+            timeout::guard(budget, "Execute failed", bind(&self.info.sql, params).execute(pool)).await?
+                .rows_affected()
+        } else {
+            return Err(RfError::Database("Unsupported database type".to_string()));
+        };
+        Ok(result)
+    }
+
+    /// Fetch all rows as JSON objects
+    pub async fn fetch_all(&self, params: &[ParamValue]) -> Result<Vec<JsonRow>> {
+        self.check_params(params)?;
+        let database = &self.database;
+        let budget = timeout::budget(database.query_timeout());
+        database.inject_fault(&self.info.sql, budget).await?;
+        let rows = if let Some(pool) = database.as_postgres() {
+            timeout::guard(budget, "Query failed", timeout::pg_fetch_rows(pool, budget, bind(&self.info.sql, params))).await?
+                .iter()
+                .map(|row| row.to_json())
+                .collect()
+        } else if let Some(pool) = database.as_mysql() {
+            let sql = timeout::mysql_hint(&self.info.sql, budget);
+            timeout::guard(budget, "Query failed", bind(&sql, params).fetch_all(pool)).await?
+                .iter()
+                .map(|row| row.to_json())
+                .collect()
+        } else if let Some(pool) = database.as_sqlite() {
+            timeout::guard(budget, "Query failed", bind(&self.info.sql, params).fetch_all(pool)).await?
+                .iter()
+                .map(|row| row.to_json())
+                .collect()
+        } else {
+            return Err(RfError::Database("Unsupported database type".to_string()));
+        };
+        Ok(rows)
+    }
+
+    /// Fetch the first row, if any
+    ///
+    /// The whole result is read; add `LIMIT 1` to statements that may match many rows.
+    pub async fn fetch_one(&self, params: &[ParamValue]) -> Result<Option<JsonRow>> {
+        Ok(self.fetch_all(params).await?.into_iter().next())
+    }
+
+    fn check_params(&self, params: &[ParamValue]) -> Result<()> {
+        match self.info.params {
+            Some(expected) if expected != params.len() => Err(RfError::InvalidParameter(format!(
+                "Statement expects {} parameters, got {}",
+                expected,
+                params.len()
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+async fn describe<DB>(pool: &Pool<DB>, sql: &str) -> std::result::Result<StatementInfo, sqlx::Error>
+where
+    DB: sqlx::Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+{
+    let statement = pool.prepare(sql).await?;
+    Ok(StatementInfo {
+        sql: sql.to_string(),
+        params: statement.parameters().map(|params| match params {
+            Either::Left(types) => types.len(),
+            Either::Right(count) => count,
+        }),
+        columns: statement.columns().iter().map(|column| column.name().to_string()).collect(),
+    })
+}
+
+fn bind<'q, DB>(sql: &'q str, params: &'q [ParamValue]) -> Query<'q, DB, DB::Arguments<'q>>
+where
+    DB: sqlx::Database,
+    i64: Encode<'q, DB> + Type<DB>,
+    f64: Encode<'q, DB> + Type<DB>,
+    bool: Encode<'q, DB> + Type<DB>,
+    &'q str: Encode<'q, DB> + Type<DB>,
+    Option<String>: Encode<'q, DB> + Type<DB>,
+{
+    params.iter().fold(sqlx::query(sql), |query, param| match param {
+        ParamValue::String(s) => query.bind(s.as_str()),
+        ParamValue::Int(i) => query.bind(*i),
+        ParamValue::Float(f) => query.bind(*f),
+        ParamValue::Bool(b) => query.bind(*b),
+        ParamValue::Null => query.bind(None::<String>),
+    })
+}
@@ -0,0 +1,144 @@
+//! # stream
+//!
+//! stream 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Row streaming
+//!
+//! `Model::stream` reads query results row by row into JSON objects without
+//! loading the whole result set. Rows pass through a bounded channel, so the
+//! query only advances as fast as the consumer reads: exports of large tables
+//! hold at most `buffer` rows in memory.
+//!
+//! Columns are decoded as integers, floats, booleans or strings; other types
+//! (timestamps, JSON, UUID, ...) are `null` unless cast in the select list,
+//! e.g. `fields(&["id", "CAST(created_at AS TEXT) AS created_at"])`.
+
+use futures_util::{Stream, StreamExt};
+use rf_errors::{Result, RfError};
+use serde_json::{Map, Value};
+use sqlx::{Column, ColumnIndex, Database as SqlxDatabase, Decode, Row, Type};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+
+/// Row as a JSON object keyed by column name
+pub type JsonRow = Map<String, Value>;
+
+/// Stream of query rows, see `Model::stream`
+pub struct RowStream {
+    receiver: mpsc::Receiver<Result<JsonRow>>,
+}
+
+impl RowStream {
+    /// Run `sql` on `pool` in the background, buffering up to `buffer` rows
+    pub(crate) fn spawn<DB>(pool: sqlx::Pool<DB>, sql: String, buffer: usize) -> Self
+    where
+        DB: SqlxDatabase,
+        for<'c> &'c mut DB::Connection: sqlx::Executor<'c, Database = DB>,
+        for<'q> DB::Arguments<'q>: sqlx::IntoArguments<'q, DB>,
+        DB::Row: JsonRowExt,
+    {
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        tokio::spawn(async move {
+            let mut conn = match pool.acquire().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    let _ = sender.send(Err(RfError::Database(format!("Query failed: {}", e)))).await;
+                    return;
+                }
+            };
+            let mut rows = sqlx::query(&sql).fetch(&mut *conn);
+            while let Some(row) = rows.next().await {
+                let row = row
+                    .map(|row| row.to_json())
+                    .map_err(|e| RfError::Database(format!("Query failed: {}", e)));
+                let failed = row.is_err();
+                // A closed channel means the consumer went away
+                if sender.send(row).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Self { receiver }
+    }
+
+    /// Collect the remaining rows
+    pub async fn collect_all(mut self) -> Result<Vec<JsonRow>> {
+        let mut rows = Vec::new();
+        while let Some(row) = self.next().await {
+            rows.push(row?);
+        }
+        Ok(rows)
+    }
+}
+
+impl Stream for RowStream {
+    type Item = Result<JsonRow>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Conversion of a database row into a JSON object
+pub trait JsonRowExt {
+    fn to_json(&self) -> JsonRow;
+}
+
+impl<R> JsonRowExt for R
+where
+    R: Row,
+    usize: ColumnIndex<R>,
+    for<'r> Option<i64>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<i32>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<i16>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<f64>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<f32>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<bool>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<String>: Decode<'r, R::Database> + Type<R::Database>,
+{
+    fn to_json(&self) -> JsonRow {
+        let mut map = Map::new();
+        for (index, column) in self.columns().iter().enumerate() {
+            map.insert(column.name().to_string(), decode(self, index));
+        }
+        map
+    }
+}
+
+/// Decode a column by trying the supported types in turn
+fn decode<R>(row: &R, index: usize) -> Value
+where
+    R: Row,
+    usize: ColumnIndex<R>,
+    for<'r> Option<i64>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<i32>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<i16>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<f64>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<f32>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<bool>: Decode<'r, R::Database> + Type<R::Database>,
+    for<'r> Option<String>: Decode<'r, R::Database> + Type<R::Database>,
+{
+    macro_rules! try_decode {
+        ($($ty:ty => $into:expr),* $(,)?) => {
+            $(
+                if let Ok(value) = row.try_get::<Option<$ty>, _>(index) {
+                    return value.map($into).unwrap_or(Value::Null);
+                }
+            )*
+        };
+    }
+    try_decode! {
+        i64 => Value::from,
+        i32 => Value::from,
+        i16 => Value::from,
+        f64 => Value::from,
+        f32 => |v: f32| Value::from(v as f64),
+        bool => Value::Bool,
+        String => Value::String,
+    }
+    Value::Null
+}
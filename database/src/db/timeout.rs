@@ -0,0 +1,200 @@
+//! # timeout
+//!
+//! timeout 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! # Query Timeouts
+//!
+//! 查询超时控制。每条查询的时间预算取以下两者中较小的一个：
+//!
+//! - 显式超时：`Model::timeout`，或 `Database::with_query_timeout` 设置的默认值
+//! - 请求上下文的剩余时间：`rf_core::ctx` 中的截止时间（HTTP 超时中间件会设置）
+//!
+//! 预算同时在两个层面生效：
+//!
+//! - 服务端：PostgreSQL 在事务内执行 `SET LOCAL statement_timeout`，
+//!   MySQL 为 SELECT 语句加上 `MAX_EXECUTION_TIME` 优化器提示
+//! - 客户端：用 `tokio::time::timeout` 包裹整个调用（包括等待连接），
+//!   超时后丢弃 future，未提交的事务随之回滚
+//!
+//! 超时统一返回 `RfError::Timeout`。
+
+use rf_errors::{Result, RfError};
+use sqlx::postgres::{PgArguments, PgQueryResult, PgRow};
+use sqlx::query::{Query, QueryAs};
+use sqlx::{Pool, Postgres};
+use std::future::Future;
+use std::time::Duration;
+
+/// PostgreSQL `query_canceled`（statement_timeout 触发）
+const PG_QUERY_CANCELED: &str = "57014";
+/// MySQL `ER_QUERY_TIMEOUT`（MAX_EXECUTION_TIME 触发）
+const MYSQL_QUERY_TIMEOUT: &str = "3024";
+
+/// 计算查询的时间预算
+///
+/// 返回显式超时与请求上下文剩余时间中较小的一个，两者都没有时返回 `None`。
+pub fn budget(explicit: Option<Duration>) -> Option<Duration> {
+    match (explicit, rf_core::ctx::remaining()) {
+        (Some(explicit), Some(remaining)) => Some(explicit.min(remaining)),
+        (explicit, remaining) => explicit.or(remaining),
+    }
+}
+
+/// 为 MySQL SELECT 语句加上 `MAX_EXECUTION_TIME` 提示
+///
+/// 非 SELECT 语句（MySQL 不支持该提示）或没有预算时原样返回。
+pub fn mysql_hint(sql: &str, budget: Option<Duration>) -> String {
+    match budget {
+        Some(budget) if sql.len() >= 6 && sql[..6].eq_ignore_ascii_case("SELECT") => {
+            format!("SELECT /*+ MAX_EXECUTION_TIME({}) */{}", millis(budget), &sql[6..])
+        }
+        _ => sql.to_string(),
+    }
+}
+
+/// 在预算内执行 sqlx 调用
+///
+/// `context` 作为数据库错误消息的前缀，例如 `"Query failed"`。
+/// 服务端因超时取消的语句同样映射为 `RfError::Timeout`。
+pub(crate) async fn guard<T, F>(budget: Option<Duration>, context: &str, future: F) -> Result<T>
+where
+    F: Future<Output = std::result::Result<T, sqlx::Error>>,
+{
+    let result = match budget {
+        Some(budget) => run_until(budget, future).await?,
+        None => future.await,
+    };
+    result.map_err(|e| {
+        let code = e.as_database_error().and_then(|e| e.code()).map(|code| code.into_owned());
+        match code.as_deref() {
+            Some(PG_QUERY_CANCELED) | Some(MYSQL_QUERY_TIMEOUT) => {
+                RfError::Timeout(format!("{}: statement timeout: {}", context, e))
+            }
+            _ => RfError::Database(format!("{}: {}", context, e)),
+        }
+    })
+}
+
+/// 在预算内执行返回 `rf_errors::Result` 的调用
+pub(crate) async fn run<T, F>(budget: Option<Duration>, future: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match budget {
+        Some(budget) => run_until(budget, future).await?,
+        None => future.await,
+    }
+}
+
+async fn run_until<F: Future>(budget: Duration, future: F) -> Result<F::Output> {
+    if budget.is_zero() {
+        return Err(RfError::Timeout("Query deadline exceeded before execution".to_string()));
+    }
+    tokio::time::timeout(budget, future)
+        .await
+        .map_err(|_| RfError::Timeout(format!("Query exceeded timeout of {}ms", millis(budget))))
+}
+
+/// 执行 PostgreSQL 语句，有预算时在带 `statement_timeout` 的事务内执行
+pub(crate) async fn pg_execute(
+    pool: &Pool<Postgres>,
+    budget: Option<Duration>,
+    query: Query<'_, Postgres, PgArguments>,
+) -> std::result::Result<PgQueryResult, sqlx::Error> {
+    let Some(budget) = budget else {
+        return query.execute(pool).await;
+    };
+    let mut tx = pool.begin().await?;
+    set_statement_timeout(&mut tx, budget).await?;
+    let result = query.execute(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(result)
+}
+
+/// 查询 PostgreSQL 的单行结果（必须存在）
+pub(crate) async fn pg_fetch_one(
+    pool: &Pool<Postgres>,
+    budget: Option<Duration>,
+    query: Query<'_, Postgres, PgArguments>,
+) -> std::result::Result<PgRow, sqlx::Error> {
+    let Some(budget) = budget else {
+        return query.fetch_one(pool).await;
+    };
+    let mut tx = pool.begin().await?;
+    set_statement_timeout(&mut tx, budget).await?;
+    let row = query.fetch_one(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// 查询 PostgreSQL 的全部原始行
+pub(crate) async fn pg_fetch_rows(
+    pool: &Pool<Postgres>,
+    budget: Option<Duration>,
+    query: Query<'_, Postgres, PgArguments>,
+) -> std::result::Result<Vec<PgRow>, sqlx::Error> {
+    let Some(budget) = budget else {
+        return query.fetch_all(pool).await;
+    };
+    let mut tx = pool.begin().await?;
+    set_statement_timeout(&mut tx, budget).await?;
+    let rows = query.fetch_all(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(rows)
+}
+
+/// 查询 PostgreSQL 的全部结果
+pub(crate) async fn pg_fetch_all<T>(
+    pool: &Pool<Postgres>,
+    budget: Option<Duration>,
+    query: QueryAs<'_, Postgres, T, PgArguments>,
+) -> std::result::Result<Vec<T>, sqlx::Error>
+where
+    T: for<'r> sqlx::FromRow<'r, PgRow> + Send + Unpin,
+{
+    let Some(budget) = budget else {
+        return query.fetch_all(pool).await;
+    };
+    let mut tx = pool.begin().await?;
+    set_statement_timeout(&mut tx, budget).await?;
+    let rows = query.fetch_all(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(rows)
+}
+
+/// 查询 PostgreSQL 的单个可选结果
+pub(crate) async fn pg_fetch_optional<T>(
+    pool: &Pool<Postgres>,
+    budget: Option<Duration>,
+    query: QueryAs<'_, Postgres, T, PgArguments>,
+) -> std::result::Result<Option<T>, sqlx::Error>
+where
+    T: for<'r> sqlx::FromRow<'r, PgRow> + Send + Unpin,
+{
+    let Some(budget) = budget else {
+        return query.fetch_optional(pool).await;
+    };
+    let mut tx = pool.begin().await?;
+    set_statement_timeout(&mut tx, budget).await?;
+    let row = query.fetch_optional(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+async fn set_statement_timeout(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    budget: Duration,
+) -> std::result::Result<(), sqlx::Error> {
+    // SET does not accept bind parameters; the value is a plain integer
+    sqlx::query(&format!("SET LOCAL statement_timeout = {}", millis(budget)))
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+fn millis(budget: Duration) -> u128 {
+    budget.as_millis().max(1)
+}
@@ -0,0 +1,199 @@
+//! # trace
+//!
+//! trace 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! # Query Tracing
+//!
+//! 为 `Model` 查询和 `RedisClient` 命令创建 `tracing` 客户端 span。
+//!
+//! span 使用 INFO 级别和 OpenTelemetry 语义约定的字段名：
+//!
+//! - `db.system.name`: `postgresql`、`mysql`、`sqlite` 或 `redis`
+//! - `db.operation.name`: `SELECT`、`INSERT`、`GET` 等
+//! - `db.collection.name`: 表名（仅 SQL）
+//! - `db.query.text`: 脱敏后的语句，字符串和数字字面量替换为 `?`，Redis 命令只保留命令名和键
+//!
+//! 无需在调用处编写代码：安装了 `tracing-opentelemetry` 层（如
+//! `rf_contrib_trace::init_tracing_otlp`）后，这些 span 会作为当前请求
+//! span 的子 span 导出；没有订阅者时开销可以忽略，语句也不会被格式化。
+
+use super::database::DatabaseType;
+use std::future::Future;
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
+
+/// 记录的语句最大长度（字节），超出部分截断
+pub const MAX_STATEMENT_LEN: usize = 2048;
+
+/// 脱敏 SQL 语句
+///
+/// 字符串和数字字面量替换为 `?`，连续空白合并为一个空格；
+/// 标识符、`$1` 等占位符和带引号的标识符保持不变。
+///
+/// ## 使用示例
+///
+/// ```rust
+/// use rf_database::db::trace::sanitize_sql;
+///
+/// assert_eq!(
+///     sanitize_sql("SELECT * FROM users\n WHERE email = 'a@b.c' AND age > 30 AND id = $1"),
+///     "SELECT * FROM users WHERE email = ? AND age > ? AND id = $1"
+/// );
+/// ```
+pub fn sanitize_sql(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len().min(MAX_STATEMENT_LEN));
+    let mut chars = sql.chars().peekable();
+    // Whether the previous character continues an identifier or placeholder
+    let mut word = false;
+    while let Some(c) = chars.next() {
+        if out.len() >= MAX_STATEMENT_LEN {
+            out.push_str("...");
+            break;
+        }
+        match c {
+            '\'' => {
+                // '' inside a literal is an escaped quote
+                while let Some(c) = chars.next() {
+                    if c == '\'' && chars.next_if_eq(&'\'').is_none() {
+                        break;
+                    }
+                }
+                out.push('?');
+                word = false;
+            }
+            '"' | '`' => {
+                out.push(c);
+                for inner in chars.by_ref() {
+                    out.push(inner);
+                    if inner == c {
+                        break;
+                    }
+                }
+                word = false;
+            }
+            c if c.is_ascii_digit() && !word => {
+                while chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '.').is_some() {}
+                out.push('?');
+            }
+            c if c.is_whitespace() => {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if !out.is_empty() && chars.peek().is_some() {
+                    out.push(' ');
+                }
+                word = false;
+            }
+            c => {
+                out.push(c);
+                word = c.is_alphanumeric() || c == '_' || c == '$';
+            }
+        }
+    }
+    out
+}
+
+/// 语句的操作名：第一个关键字的大写形式
+pub fn operation_of(sql: &str) -> String {
+    sql.split_whitespace().next().unwrap_or_default().to_ascii_uppercase()
+}
+
+fn system_name(db_type: &DatabaseType) -> &'static str {
+    match db_type {
+        DatabaseType::Postgres => "postgresql",
+        DatabaseType::MySql => "mysql",
+        DatabaseType::Sqlite => "sqlite",
+    }
+}
+
+/// 表上一次 SQL 操作的 span，名称如 `SELECT users`
+pub(crate) fn query_span(db_type: &DatabaseType, operation: &str, table: &str, sql: Option<&str>) -> Span {
+    let span = tracing::info_span!(
+        "db.query",
+        otel.name = %format!("{} {}", operation, table),
+        otel.kind = "client",
+        otel.status_code = Empty,
+        otel.status_description = Empty,
+        db.system.name = system_name(db_type),
+        db.operation.name = operation,
+        db.collection.name = table,
+        db.query.text = Empty,
+    );
+    if let Some(sql) = sql {
+        if !span.is_disabled() {
+            span.record("db.query.text", sanitize_sql(sql));
+        }
+    }
+    span
+}
+
+/// 一条 Redis 命令的 span，语句只保留命令名和键，如 `SET user:1 ?`
+pub(crate) fn redis_span(cmd: &redis::Cmd) -> Span {
+    let mut args = cmd.args_iter().map(|arg| match arg {
+        redis::Arg::Simple(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+        _ => "0".to_string(),
+    });
+    let name = args.next().unwrap_or_default().to_ascii_uppercase();
+    let span = tracing::info_span!(
+        "redis.command",
+        otel.name = %name,
+        otel.kind = "client",
+        otel.status_code = Empty,
+        otel.status_description = Empty,
+        db.system.name = "redis",
+        db.operation.name = %name,
+        db.query.text = Empty,
+    );
+    if !span.is_disabled() {
+        let mut text = name;
+        if let Some(key) = args.next() {
+            text.push(' ');
+            text.push_str(&key);
+        }
+        if args.next().is_some() {
+            text.push_str(" ?");
+        }
+        span.record("db.query.text", text);
+    }
+    span
+}
+
+/// 一个 Redis 管道的 span，名称为 `PIPELINE`，语句为各命令名
+pub(crate) fn redis_pipeline_span(pipeline: &redis::Pipeline) -> Span {
+    let span = tracing::info_span!(
+        "redis.command",
+        otel.name = "PIPELINE",
+        otel.kind = "client",
+        otel.status_code = Empty,
+        otel.status_description = Empty,
+        db.system.name = "redis",
+        db.operation.name = "PIPELINE",
+        db.query.text = Empty,
+    );
+    if !span.is_disabled() {
+        let names: Vec<String> = pipeline
+            .cmd_iter()
+            .filter_map(|cmd| match cmd.args_iter().next() {
+                Some(redis::Arg::Simple(name)) => Some(String::from_utf8_lossy(name).to_ascii_uppercase()),
+                _ => None,
+            })
+            .collect();
+        span.record("db.query.text", names.join("; "));
+    }
+    span
+}
+
+/// 在 `span` 内执行 `future`，失败时把 span 标记为错误
+pub(crate) async fn traced<T, E, F>(span: Span, future: F) -> std::result::Result<T, E>
+where
+    E: std::fmt::Display,
+    F: Future<Output = std::result::Result<T, E>>,
+{
+    let result = future.instrument(span.clone()).await;
+    if let Err(e) = &result {
+        span.record("otel.status_code", "error");
+        span.record("otel.status_description", e.to_string());
+    }
+    result
+}
@@ -25,34 +25,78 @@
 //! ## 使用示例
 //!
 //! ```rust,no_run
-//! use rf_database::db::transaction::TransactionWrapper;
+//! use rf_database::db::Database;
 //!
-//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! # async fn example(database: Database) -> Result<(), Box<dyn std::error::Error>> {
 //! // 开始事务
-//! // let mut tx = database.begin().await?;
+//! let mut tx = database.begin().await?;
 //!
 //! // 执行多个操作
-//! // tx.execute("UPDATE accounts SET balance = balance - 100 WHERE id = 1").await?;
-//! // tx.execute("UPDATE accounts SET balance = balance + 100 WHERE id = 2").await?;
+//! tx.execute("UPDATE accounts SET balance = balance - 100 WHERE id = 1").await?;
+//! tx.execute("UPDATE accounts SET balance = balance + 100 WHERE id = 2").await?;
 //!
 //! // 提交事务
-//! // tx.commit().await?;
+//! tx.commit().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## 提交后执行
+//!
+//! 事务中的写操作产生的副作用（查询缓存失效、事件发布）缓存在事务内，
+//! 提交成功后才执行，回滚或未提交即丢弃时直接丢弃，避免为最终回滚的写入清理缓存或发出事件：
+//!
+//! ```rust,no_run
+//! use rf_database::db::Database;
+//!
+//! # async fn example(database: Database, order_id: i64) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut tx = database.begin().await?;
+//! database.model("orders").insert_tx(&mut tx, &serde_json::json!({"id": order_id})).await?;
+//! tx.after_commit(async move {
+//!     println!("order {} created", order_id);   // 例如发布到事件总线
+//! });
+//! tx.commit().await?;   // 先提交，再清理 orders 的查询缓存，再执行回调
 //! # Ok(())
 //! # }
 //! ```
 
+use super::cache::CacheStore;
+use super::database::DatabaseType;
+use super::query::ParamValue;
+use super::stream::{JsonRow, JsonRowExt};
+use futures_util::TryStreamExt;
 use rf_errors::{Result, RfError};
-use sqlx::{Transaction, Postgres};
+use sqlx::{Database as SqlxDatabase, Encode, MySql, Postgres, Sqlite, Transaction, Type};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// 提交或回滚后执行的任务
+type Deferred = Pin<Box<dyn Future<Output = ()> + Send>>;
 
 /// 事务包装器
 ///
-/// 封装 PostgreSQL 事务，提供安全的交易操作接口。
+/// 封装 PostgreSQL、MySQL 或 SQLite 事务，提供安全的交易操作接口。
+/// 通过 `Database::begin()` 创建。
 ///
 /// ## 字段说明
 ///
-/// - `transaction`: 底层 PostgreSQL 事务实例
+/// - `transaction`: 底层事务实例
+/// - `invalidations`: 提交后失效的查询缓存（缓存、表名）
+/// - `after_commit`: 提交后执行的任务
+/// - `after_rollback`: 显式回滚后执行的任务
 pub struct TransactionWrapper {
-    transaction: Transaction<'static, Postgres>,
+    transaction: Tx,
+    invalidations: Vec<(Arc<dyn CacheStore>, String)>,
+    after_commit: Vec<Deferred>,
+    after_rollback: Vec<Deferred>,
+}
+
+/// 各数据库的底层事务
+enum Tx {
+    Postgres(Transaction<'static, Postgres>),
+    MySql(Transaction<'static, MySql>),
+    Sqlite(Transaction<'static, Sqlite>),
 }
 
 impl TransactionWrapper {
@@ -66,7 +110,78 @@ impl TransactionWrapper {
     ///
     /// 返回 `TransactionWrapper` 实例。
     pub fn new(transaction: Transaction<'static, Postgres>) -> Self {
-        Self { transaction }
+        Self::wrap(Tx::Postgres(transaction))
+    }
+
+    pub(crate) fn mysql(transaction: Transaction<'static, MySql>) -> Self {
+        Self::wrap(Tx::MySql(transaction))
+    }
+
+    pub(crate) fn sqlite(transaction: Transaction<'static, Sqlite>) -> Self {
+        Self::wrap(Tx::Sqlite(transaction))
+    }
+
+    fn wrap(transaction: Tx) -> Self {
+        Self {
+            transaction,
+            invalidations: Vec::new(),
+            after_commit: Vec::new(),
+            after_rollback: Vec::new(),
+        }
+    }
+
+    /// 事务所属的数据库类型
+    pub fn db_type(&self) -> DatabaseType {
+        match self.transaction {
+            Tx::Postgres(_) => DatabaseType::Postgres,
+            Tx::MySql(_) => DatabaseType::MySql,
+            Tx::Sqlite(_) => DatabaseType::Sqlite,
+        }
+    }
+
+    /// 提交成功后使表的查询缓存失效
+    ///
+    /// 事务中的 ORM 写操作（如 `Model::insert_tx`）自动调用；同一缓存的同一张表只记录一次。
+    /// 回滚或未提交即丢弃时不失效，其他连接继续读取提交前的缓存结果。
+    pub fn invalidate_on_commit(&mut self, cache: Arc<dyn CacheStore>, table: &str) {
+        let recorded = self
+            .invalidations
+            .iter()
+            .any(|(existing, existing_table)| Arc::ptr_eq(existing, &cache) && existing_table == table);
+        if !recorded {
+            self.invalidations.push((cache, table.to_string()));
+        }
+    }
+
+    /// 提交成功后执行任务
+    ///
+    /// 用于发布事件、发送通知等只应在数据真正写入后发生的操作。任务按注册顺序在
+    /// `commit()` 中依次执行（在缓存失效之后）；回滚或未提交即丢弃时任务被丢弃，不会执行。
+    ///
+    /// ## 参数
+    ///
+    /// - `task`: 提交后执行的异步任务，注册时不会开始执行
+    pub fn after_commit<F>(&mut self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.after_commit.push(Box::pin(task));
+    }
+
+    /// 显式回滚后执行任务
+    ///
+    /// 任务按注册顺序在 `rollback()` 中执行，例如释放为事务预留的外部资源。
+    /// 未提交即丢弃的事务同样会回滚，但不会执行这些任务。
+    pub fn after_rollback<F>(&mut self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.after_rollback.push(Box::pin(task));
+    }
+
+    /// 提交后等待执行的任务数量（不含缓存失效）
+    pub fn pending_tasks(&self) -> usize {
+        self.after_commit.len()
     }
 
     /// 在事务中执行查询
@@ -97,11 +212,51 @@ impl TransactionWrapper {
     /// # }
     /// ```
     pub async fn execute(&mut self, query: &str) -> Result<u64> {
-        sqlx::query(query)
-            .execute(&mut *self.transaction)
-            .await
-            .map(|r| r.rows_affected())
-            .map_err(|e| RfError::Database(format!("Transaction query failed: {}", e)))
+        self.execute_with(query, &[]).await
+    }
+
+    /// 在事务中执行带参数的语句
+    ///
+    /// ## 参数
+    ///
+    /// - `sql`: 使用 `$1`、`$2` 占位符的 SQL 语句，MySQL 和 SQLite 下自动改写为 `?`，
+    ///   因此每个占位符只能按顺序出现一次
+    /// - `params`: 参数值
+    ///
+    /// ## 返回值
+    ///
+    /// 返回 `Result<u64>`，成功时返回受影响的行数。
+    pub async fn execute_with(&mut self, sql: &str, params: &[ParamValue]) -> Result<u64> {
+        let result = match &mut self.transaction {
+            Tx::Postgres(tx) => bind_all(sqlx::query(sql), params).execute(&mut **tx).await.map(|r| r.rows_affected()),
+            Tx::MySql(tx) => {
+                let sql = positional(sql);
+                bind_all(sqlx::query(&sql), params).execute(&mut **tx).await.map(|r| r.rows_affected())
+            }
+            Tx::Sqlite(tx) => {
+                let sql = positional(sql);
+                bind_all(sqlx::query(&sql), params).execute(&mut **tx).await.map(|r| r.rows_affected())
+            }
+        };
+        result.map_err(|e| RfError::Database(format!("Transaction query failed: {}", e)))
+    }
+
+    /// 在事务中查询，每行转换为 JSON 对象
+    ///
+    /// 占位符规则与 `execute_with` 相同，列的解码规则见 [`crate::db::stream`]。
+    pub async fn fetch_json(&mut self, sql: &str, params: &[ParamValue]) -> Result<Vec<JsonRow>> {
+        let result = match &mut self.transaction {
+            Tx::Postgres(tx) => bind_all(sqlx::query(sql), params).fetch(&mut **tx).map_ok(|r| r.to_json()).try_collect().await,
+            Tx::MySql(tx) => {
+                let sql = positional(sql);
+                bind_all(sqlx::query(&sql), params).fetch(&mut **tx).map_ok(|r| r.to_json()).try_collect().await
+            }
+            Tx::Sqlite(tx) => {
+                let sql = positional(sql);
+                bind_all(sqlx::query(&sql), params).fetch(&mut **tx).map_ok(|r| r.to_json()).try_collect().await
+            }
+        };
+        result.map_err(|e| RfError::Database(format!("Transaction query failed: {}", e)))
     }
 
     /// 提交事务
@@ -114,6 +269,7 @@ impl TransactionWrapper {
     ///
     /// - 提交后，事务中的所有操作将永久生效
     /// - 提交后事务实例不能再使用
+    /// - 提交成功后依次执行缓存失效和 `after_commit` 任务；提交失败时丢弃这些任务
     ///
     /// ## 使用示例
     ///
@@ -128,10 +284,20 @@ impl TransactionWrapper {
     /// # }
     /// ```
     pub async fn commit(self) -> Result<()> {
-        self.transaction
-            .commit()
-            .await
-            .map_err(|e| RfError::Database(format!("Transaction commit failed: {}", e)))
+        match self.transaction {
+            Tx::Postgres(tx) => tx.commit().await,
+            Tx::MySql(tx) => tx.commit().await,
+            Tx::Sqlite(tx) => tx.commit().await,
+        }
+        .map_err(|e| RfError::Database(format!("Transaction commit failed: {}", e)))?;
+
+        for (cache, table) in self.invalidations {
+            cache.invalidate_table(&table).await;
+        }
+        for task in self.after_commit {
+            task.await;
+        }
+        Ok(())
     }
 
     /// 回滚事务
@@ -144,6 +310,7 @@ impl TransactionWrapper {
     ///
     /// - 回滚后，事务中的所有操作都将被撤销
     /// - 回滚后事务实例不能再使用
+    /// - 缓存失效和 `after_commit` 任务被丢弃，回滚成功后执行 `after_rollback` 任务
     /// - 如果事务中的任何操作失败，应调用此方法
     ///
     /// ## 使用示例
@@ -159,9 +326,58 @@ impl TransactionWrapper {
     /// # }
     /// ```
     pub async fn rollback(self) -> Result<()> {
-        self.transaction
-            .rollback()
-            .await
-            .map_err(|e| RfError::Database(format!("Transaction rollback failed: {}", e)))
+        match self.transaction {
+            Tx::Postgres(tx) => tx.rollback().await,
+            Tx::MySql(tx) => tx.rollback().await,
+            Tx::Sqlite(tx) => tx.rollback().await,
+        }
+        .map_err(|e| RfError::Database(format!("Transaction rollback failed: {}", e)))?;
+
+        for task in self.after_rollback {
+            task.await;
+        }
+        Ok(())
+    }
+}
+
+/// 绑定参数
+fn bind_all<'q, DB>(
+    mut query: sqlx::query::Query<'q, DB, DB::Arguments<'q>>,
+    params: &'q [ParamValue],
+) -> sqlx::query::Query<'q, DB, DB::Arguments<'q>>
+where
+    DB: SqlxDatabase,
+    &'q str: Encode<'q, DB> + Type<DB>,
+    i64: Encode<'q, DB> + Type<DB>,
+    f64: Encode<'q, DB> + Type<DB>,
+    bool: Encode<'q, DB> + Type<DB>,
+    Option<String>: Encode<'q, DB> + Type<DB>,
+{
+    for param in params {
+        query = match param {
+            ParamValue::String(s) => query.bind(s.as_str()),
+            ParamValue::Int(i) => query.bind(*i),
+            ParamValue::Float(f) => query.bind(*f),
+            ParamValue::Bool(b) => query.bind(*b),
+            ParamValue::Null => query.bind(None::<String>),
+        };
+    }
+    query
+}
+
+/// 将 `$1`、`$2` 占位符改写为 `?`
+fn positional(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '$' && chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+            while chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+                chars.next();
+            }
+            out.push('?');
+        } else {
+            out.push(c);
+        }
     }
+    out
 }
@@ -28,8 +28,14 @@
 //!   - `cache`: 查询结果缓存
 //!   - `logger`: 查询日志记录
 //!   - `pool_monitor`: 连接池监控
+//!   - `stream`: 查询结果流式读取
+//!   - `timeout`: 查询超时控制
+//!   - `trace`: 查询和 Redis 命令的追踪 span，语句脱敏
+//!   - `statement`: 预编译语句和语句缓存
 //!   - `replication`: 主从复制管理
 //!   - `query_plan_cache`: 查询计划缓存
+//!   - `outbox`: 事务性发件箱，与业务变更同事务写入事件并由后台中继发布
+//!   - `contract`: 按主题注册的消息契约（JSON Schema、protobuf）与版本兼容性检查
 //! - `redis`: Redis 客户端和操作封装
 
 pub mod db {
@@ -38,12 +44,26 @@ pub mod db {
     pub mod transaction;
     pub mod database;
     pub mod cache;
+    pub mod pool_monitor;
+    pub mod stream;
+    pub mod timeout;
+    pub mod trace;
+    pub mod statement;
+    pub mod outbox;
+    pub mod contract;
+    pub mod scope;
 
     pub use model::*;
     pub use query::*;
     pub use transaction::*;
     pub use database::*;
     pub use cache::*;
+    pub use pool_monitor::*;
+    pub use stream::*;
+    pub use statement::*;
+    pub use outbox::*;
+    pub use contract::*;
+    pub use scope::*;
 }
 
 pub mod redis;
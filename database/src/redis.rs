@@ -15,6 +15,7 @@
 //! - 提供多种 Redis 数据结构的操作接口
 //! - 支持多种数据类型：String、Hash、List、Set、SortedSet 等
 //! - 支持发布订阅、Lua 脚本等高级功能
+//! - 支持 Redis Cluster（MOVED/ASK 重定向、节点拓扑刷新）和 Sentinel 主从切换
 //!
 //! ## 操作分组
 //!
@@ -26,8 +27,9 @@
 //! - `set_ops()`: Set 操作（SADD、SREM、SMEMBERS 等）
 //! - `sorted_set()`: Sorted Set 操作（ZADD、ZRANGE、ZREM 等）
 //! - `generic()`: 通用操作（EXISTS、DEL、EXPIRE 等）
-//! - `pubsub()`: 发布订阅操作（PUBLISH）
+//! - `pubsub()`: 发布订阅操作（PUBLISH、SUBSCRIBE）
 //! - `script()`: Lua 脚本操作（EVAL、SCRIPT LOAD）
+//! - `stream()`: Stream 操作（XADD、XREADGROUP、XACK、消费者组管理）
 //!
 //! ## 使用示例
 //!
@@ -54,23 +56,32 @@
 //! # }
 //! ```
 
+mod cluster;
+mod connection;
 mod groups;
+mod sentinel;
 
-use redis::aio::MultiplexedConnection;
 use redis::{Client, AsyncCommands};
 use rf_errors::{Result, RfError};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+pub use cluster::{key_slot, ClusterConnection, CLUSTER_SLOTS};
+pub use connection::RedisConnection;
 pub use groups::*;
+pub use sentinel::SentinelConnection;
 
 /// Redis 客户端包装器，提供连接池和操作接口
 ///
 /// ## 字段说明
 ///
-/// - `connection`: 多路复用连接的共享引用
+/// - `connection`: 连接的共享引用（单机、集群或 Sentinel）
+/// - `compress_threshold`: JSON 值压缩阈值（字节），`None` 表示不压缩
+/// - `subscribe_url`: 订阅使用的独立连接地址（单机为连接地址，集群为第一个种子节点，Sentinel 时动态解析）
 pub struct RedisClient {
-    connection: Arc<Mutex<MultiplexedConnection>>,
+    connection: Arc<Mutex<RedisConnection>>,
+    compress_threshold: Option<usize>,
+    subscribe_url: Option<String>,
 }
 
 impl RedisClient {
@@ -108,10 +119,175 @@ impl RedisClient {
             .map_err(|e| RfError::Database(format!("Failed to create connection: {}", e)))?;
 
         Ok(Self {
-            connection: Arc::new(Mutex::new(connection)),
+            connection: Arc::new(Mutex::new(RedisConnection::Single(connection))),
+            compress_threshold: None,
+            subscribe_url: Some(url.to_string()),
         })
     }
 
+    /// 创建一个 Redis Cluster 客户端
+    ///
+    /// ## 参数
+    ///
+    /// - `urls`: 种子节点连接字符串，至少一个；其它节点通过 `CLUSTER SLOTS` 自动发现，
+    ///   并沿用第一个种子节点的协议和认证信息
+    ///
+    /// ## 说明
+    ///
+    /// 命令按键所在的哈希槽路由到对应节点，自动处理 `MOVED`/`ASK` 重定向，
+    /// 节点不可用时刷新拓扑后重试。多键命令和管道按第一个键路由，
+    /// 涉及的键需使用 `{hash tag}` 保证落在同一个槽。
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,no_run
+    /// use rf_database::redis::RedisClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = RedisClient::new_cluster(&[
+    ///     "redis://10.0.0.1:7000/",
+    ///     "redis://10.0.0.2:7000/",
+    /// ]).await?;
+    /// client.hash().hset("user:{1}", "name", "Alice").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new_cluster(urls: &[&str]) -> Result<Self> {
+        let connection = ClusterConnection::connect(urls)
+            .await
+            .map_err(|e| RfError::Database(format!("Failed to connect to Redis cluster: {}", e)))?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(RedisConnection::Cluster(connection))),
+            compress_threshold: None,
+            // PUBLISH is broadcast to the whole cluster, so any node can serve subscriptions
+            subscribe_url: urls.first().map(|url| url.to_string()),
+        })
+    }
+
+    /// 通过 Sentinel 发现主节点并创建 Redis 客户端
+    ///
+    /// ## 参数
+    ///
+    /// - `sentinels`: Sentinel 节点连接字符串
+    /// - `master_name`: Sentinel 中配置的主节点名称
+    /// - `master_password`: 主节点密码（可选）
+    ///
+    /// ## 说明
+    ///
+    /// 发生故障转移（连接断开或旧主节点返回 `READONLY`）时，
+    /// 自动重新向 Sentinel 查询主节点并重试一次。
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,no_run
+    /// use rf_database::redis::RedisClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = RedisClient::new_sentinel(
+    ///     &["redis://10.0.0.1:26379/", "redis://10.0.0.2:26379/"],
+    ///     "mymaster",
+    ///     None,
+    /// ).await?;
+    /// client.set("key", "value").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new_sentinel(sentinels: &[&str], master_name: &str, master_password: Option<&str>) -> Result<Self> {
+        let connection = SentinelConnection::connect(sentinels, master_name, master_password)
+            .await
+            .map_err(|e| RfError::Database(format!("Failed to connect via Redis sentinel: {}", e)))?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(RedisConnection::Sentinel(connection))),
+            compress_threshold: None,
+            subscribe_url: None,
+        })
+    }
+
+    /// 刷新集群拓扑或重新解析 Sentinel 主节点
+    ///
+    /// 单机模式下无操作。
+    pub async fn refresh_topology(&self) -> Result<()> {
+        let mut guard = self.connection.lock().await;
+        let conn = &mut *guard;
+        #[cfg(feature = "fault-injection")]
+        let conn = match conn {
+            RedisConnection::Faulty(inner, _) => &mut **inner,
+            conn => conn,
+        };
+        match conn {
+            RedisConnection::Single(_) => Ok(()),
+            RedisConnection::Cluster(cluster) => cluster.refresh_slots().await
+                .map_err(|e| RfError::Database(format!("Redis cluster refresh failed: {}", e))),
+            RedisConnection::Sentinel(sentinel) => sentinel.refresh_master().await
+                .map_err(|e| RfError::Database(format!("Redis sentinel refresh failed: {}", e))),
+            #[cfg(feature = "fault-injection")]
+            RedisConnection::Faulty(..) => Ok(()),
+        }
+    }
+
+    /// 注入延迟、错误和连接重置（需要 `fault-injection` feature）
+    ///
+    /// ## 说明
+    ///
+    /// 规则匹配命令名和第一个参数，例如 `GET user:1`、`HSET user:*`；管道使用其第一条命令。
+    /// 注入的错误以 Redis 服务端错误返回，连接重置以 I/O 错误返回，参见 `rf_core::fault`。
+    /// 应在创建客户端后立即调用，此时连接尚未被操作分组共享。
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,ignore
+    /// use rf_core::fault::{FaultInjector, FaultRule};
+    ///
+    /// let faults = FaultInjector::new().rule(FaultRule::new("GET session:*").reset_rate(0.2));
+    /// let client = RedisClient::new("redis://127.0.0.1/").await?.with_faults(faults);
+    /// ```
+    #[cfg(feature = "fault-injection")]
+    pub fn with_faults(mut self, faults: rf_core::fault::FaultInjector) -> Self {
+        match Arc::try_unwrap(self.connection) {
+            Ok(connection) => {
+                let connection = match connection.into_inner() {
+                    RedisConnection::Faulty(inner, _) => *inner,
+                    connection => connection,
+                };
+                self.connection = Arc::new(Mutex::new(RedisConnection::Faulty(Box::new(connection), faults)));
+            }
+            Err(shared) => {
+                tracing::warn!("Redis fault injection not enabled: the connection is already shared");
+                self.connection = shared;
+            }
+        }
+        self
+    }
+
+    /// 启用 JSON 值压缩
+    ///
+    /// ## 参数
+    ///
+    /// - `threshold`: 序列化后达到该字节数的 JSON 值使用 Gzip 压缩后存储
+    ///
+    /// ## 说明
+    ///
+    /// 影响 `set_json`、`hset_json` 等写入方法。压缩后的值带有 `\0rfgz:` 前缀，
+    /// 读取时按前缀区分压缩和未压缩的数据，因此可以随时开启或调整阈值。
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,no_run
+    /// use rf_database::redis::RedisClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = RedisClient::new("redis://127.0.0.1/").await?
+    ///     .with_json_compression(4096);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_json_compression(mut self, threshold: usize) -> Self {
+        self.compress_threshold = Some(threshold);
+        self
+    }
+
     /// 设置键值对
     ///
     /// ## 参数
@@ -170,6 +346,158 @@ impl RedisClient {
             .map_err(|e| RfError::Database(format!("Redis GET failed: {}", e)))
     }
 
+    /// 将值序列化为 JSON 后存储
+    ///
+    /// ## 参数
+    ///
+    /// - `key`: 键名
+    /// - `value`: 实现 `Serialize` 的值
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,no_run
+    /// # use rf_database::redis::RedisClient;
+    /// # use std::collections::HashMap;
+    /// #
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = RedisClient::new("redis://127.0.0.1/").await?;
+    /// let mut user = HashMap::new();
+    /// user.insert("name", "Alice");
+    /// client.set_json("user:1", &user).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_json<T: serde::Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        self.string().set_json(key, value).await
+    }
+
+    /// 读取 JSON 值并反序列化
+    ///
+    /// ## 返回值
+    ///
+    /// 键不存在时返回 `Ok(None)`。
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,no_run
+    /// # use rf_database::redis::RedisClient;
+    /// # use std::collections::HashMap;
+    /// #
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = RedisClient::new("redis://127.0.0.1/").await?;
+    /// let user: Option<HashMap<String, String>> = client.get_json("user:1").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_json<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.string().get_json(key).await
+    }
+
+    /// 存储二进制值
+    ///
+    /// 值按原样写入，无需 base64 等编码。
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,no_run
+    /// # use rf_database::redis::RedisClient;
+    /// #
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = RedisClient::new("redis://127.0.0.1/").await?;
+    /// client.set_bytes("avatar:1", &[0x89, b'P', b'N', b'G']).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_bytes(&self, key: &str, value: &[u8]) -> Result<()> {
+        self.string().set_bytes(key, value).await
+    }
+
+    /// 读取二进制值
+    ///
+    /// ## 返回值
+    ///
+    /// 键不存在时返回 `Ok(None)`。
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,no_run
+    /// # use rf_database::redis::RedisClient;
+    /// #
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = RedisClient::new("redis://127.0.0.1/").await?;
+    /// let avatar: Option<Vec<u8>> = client.get_bytes("avatar:1").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bytes(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.string().get_bytes(key).await
+    }
+
+    /// 执行自行构造的 Redis 命令，并将回复转换为指定类型
+    ///
+    /// 参数可以是任何实现 `ToRedisArgs` 的值（字符串、整数、`&[u8]`、`Vec<u8>` 等），
+    /// 回复可以是任何实现 `FromRedisValue` 的类型。
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,no_run
+    /// # use rf_database::redis::RedisClient;
+    /// #
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = RedisClient::new("redis://127.0.0.1/").await?;
+    /// let mut cmd = redis::cmd("SET");
+    /// cmd.arg("blob").arg(&[0u8, 1, 2][..]).arg("EX").arg(60);
+    /// client.query::<()>(&cmd).await?;
+    /// let blob: Option<Vec<u8>> = client.query(redis::cmd("GET").arg("blob")).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query<T: redis::FromRedisValue>(&self, cmd: &redis::Cmd) -> Result<T> {
+        let name = cmd
+            .args_iter()
+            .next()
+            .and_then(|arg| match arg {
+                redis::Arg::Simple(name) => Some(String::from_utf8_lossy(name).to_uppercase()),
+                _ => None,
+            })
+            .unwrap_or_else(|| "command".to_string());
+        let mut conn = self.connection.lock().await;
+        cmd.query_async::<T>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis {} failed: {}", name, e)))
+    }
+
+    /// 执行任意 Redis 命令
+    ///
+    /// ## 参数
+    ///
+    /// - `name`: 命令名，如 `GET`、`HGETALL`、`INFO`
+    /// - `args`: 命令参数
+    ///
+    /// ## 返回值
+    ///
+    /// 返回 `Result<redis::Value>`，即服务器的原始回复。
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,no_run
+    /// # use rf_database::redis::RedisClient;
+    /// #
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = RedisClient::new("redis://127.0.0.1/").await?;
+    /// let info = client.command("INFO", &["memory"]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn command(&self, name: &str, args: &[&str]) -> Result<redis::Value> {
+        let mut cmd = redis::cmd(name);
+        for arg in args {
+            cmd.arg(*arg);
+        }
+        let mut conn = self.connection.lock().await;
+        cmd.query_async::<redis::Value>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis {} failed: {}", name.to_uppercase(), e)))
+    }
+
     /// 获取 String 操作分组
     ///
     /// ## 返回值
@@ -192,6 +520,7 @@ impl RedisClient {
     pub fn string(&self) -> StringGroup {
         StringGroup {
             connection: self.connection.clone(),
+            compress_threshold: self.compress_threshold,
         }
     }
 
@@ -217,6 +546,7 @@ impl RedisClient {
     pub fn hash(&self) -> HashGroup {
         HashGroup {
             connection: self.connection.clone(),
+            compress_threshold: self.compress_threshold,
         }
     }
 
@@ -338,6 +668,7 @@ impl RedisClient {
     pub fn pubsub(&self) -> PubSubGroup {
         PubSubGroup {
             connection: self.connection.clone(),
+            subscribe_url: self.subscribe_url.clone(),
         }
     }
 
@@ -367,4 +698,37 @@ impl RedisClient {
             connection: self.connection.clone(),
         }
     }
+
+    /// 获取 Stream 操作分组
+    ///
+    /// ## 返回值
+    ///
+    /// 返回 `StreamGroup`，提供 Stream 及消费者组操作，可用作轻量级消息队列。
+    ///
+    /// ## 使用示例
+    ///
+    /// ```rust,no_run
+    /// # use rf_database::redis::RedisClient;
+    /// #
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = RedisClient::new("redis://127.0.0.1/").await?;
+    /// let stream = client.stream();
+    /// stream.xgroup_create("orders", "workers", "$", true).await?;
+    /// stream.xadd("orders", "*", &[("id", "1001")]).await?;
+    ///
+    /// let reads = stream.xreadgroup("workers", "worker-1", &["orders"], &[">"], Some(10), Some(5000)).await?;
+    /// for read in reads {
+    ///     for entry in read.entries {
+    ///         // 处理消息后确认
+    ///         stream.xack(&read.key, "workers", &[entry.id.as_str()]).await?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stream(&self) -> StreamGroup {
+        StreamGroup {
+            connection: self.connection.clone(),
+        }
+    }
 }
@@ -0,0 +1,398 @@
+//! # cluster
+//!
+//! cluster 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Redis Cluster connection
+//!
+//! Routes every command to the node owning its key slot, follows
+//! `MOVED`/`ASK` redirections and refreshes the slot map from
+//! `CLUSTER SLOTS` whenever the topology changes.
+//!
+//! Multi-key commands and pipelines are routed by their first key, so all
+//! keys involved must hash to the same slot (use `{hash tags}`).
+
+use redis::aio::{ConnectionLike, MultiplexedConnection};
+use redis::{Client, Cmd, ErrorKind, Pipeline, RedisError, RedisResult, ServerErrorKind, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Number of hash slots in a Redis Cluster
+pub const CLUSTER_SLOTS: u16 = 16384;
+
+/// Maximum number of redirections/retries for one command
+const MAX_REDIRECTS: usize = 5;
+
+/// Compute the cluster hash slot of a key (CRC16/XMODEM with hash tags)
+pub fn key_slot(key: &[u8]) -> u16 {
+    let key = match key.iter().position(|&b| b == b'{') {
+        Some(open) => match key[open + 1..].iter().position(|&b| b == b'}') {
+            Some(len) if len > 0 => &key[open + 1..open + 1 + len],
+            _ => key,
+        },
+        None => key,
+    };
+    crc16(key) % CLUSTER_SLOTS
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Extract the key used for routing from a command's arguments
+///
+/// Returns `None` for commands that are not bound to a key; those are sent
+/// to any node.
+pub(crate) fn routing_key<'a>(args: &[&'a [u8]]) -> Option<&'a [u8]> {
+    let name = String::from_utf8_lossy(args.first()?).to_ascii_uppercase();
+    match name.as_str() {
+        "EVAL" | "EVALSHA" | "EVAL_RO" | "EVALSHA_RO" | "FCALL" | "FCALL_RO" => {
+            let numkeys: usize = std::str::from_utf8(args.get(2)?).ok()?.parse().ok()?;
+            if numkeys > 0 { args.get(3).copied() } else { None }
+        }
+        "XREAD" | "XREADGROUP" => {
+            let pos = args.iter().position(|a| a.eq_ignore_ascii_case(b"STREAMS"))?;
+            args.get(pos + 1).copied()
+        }
+        "XGROUP" | "XINFO" | "OBJECT" | "MEMORY" => args.get(2).copied(),
+        "PING" | "ECHO" | "INFO" | "TIME" | "DBSIZE" | "KEYS" | "SCAN" | "PUBLISH" | "SCRIPT"
+        | "FUNCTION" | "CLUSTER" | "CONFIG" | "CLIENT" | "FLUSHDB" | "FLUSHALL" | "ASKING"
+        | "MULTI" | "EXEC" | "DISCARD" => None,
+        _ => args.get(1).copied(),
+    }
+}
+
+fn cmd_args(cmd: &Cmd) -> Vec<&[u8]> {
+    cmd.args_iter()
+        .filter_map(|arg| match arg {
+            redis::Arg::Simple(data) => Some(data),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Scheme and credentials shared by all cluster nodes, taken from the seed URL
+#[derive(Debug, Clone)]
+struct NodeUrl {
+    scheme: String,
+    auth: String,
+}
+
+impl NodeUrl {
+    fn parse(url: &str) -> Self {
+        let (scheme, rest) = url.split_once("://").unwrap_or(("redis", url));
+        let authority = rest.split('/').next().unwrap_or(rest);
+        let auth = match authority.rfind('@') {
+            Some(at) => authority[..=at].to_string(),
+            None => String::new(),
+        };
+        Self {
+            scheme: scheme.to_string(),
+            auth,
+        }
+    }
+
+    fn address(url: &str) -> String {
+        let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+        let authority = rest.split('/').next().unwrap_or(rest);
+        match authority.rfind('@') {
+            Some(at) => authority[at + 1..].to_string(),
+            None => authority.to_string(),
+        }
+    }
+
+    fn url_for(&self, addr: &str) -> String {
+        format!("{}://{}{}/", self.scheme, self.auth, addr)
+    }
+}
+
+/// Slot range served by a master node
+#[derive(Debug, Clone)]
+struct SlotRange {
+    start: u16,
+    end: u16,
+    node: String,
+}
+
+/// Connection to a Redis Cluster
+pub struct ClusterConnection {
+    seeds: Vec<String>,
+    node_url: NodeUrl,
+    slots: Vec<SlotRange>,
+    nodes: HashMap<String, MultiplexedConnection>,
+}
+
+impl ClusterConnection {
+    /// Connect to a cluster using one or more seed node URLs
+    pub async fn connect(urls: &[&str]) -> RedisResult<Self> {
+        let first = urls.first().ok_or_else(|| {
+            RedisError::from((ErrorKind::InvalidClientConfig, "No cluster seed nodes configured"))
+        })?;
+        let mut connection = Self {
+            seeds: urls.iter().map(|u| NodeUrl::address(u)).collect(),
+            node_url: NodeUrl::parse(first),
+            slots: Vec::new(),
+            nodes: HashMap::new(),
+        };
+        connection.refresh_slots().await?;
+        Ok(connection)
+    }
+
+    /// Reload the slot map from any reachable node
+    pub async fn refresh_slots(&mut self) -> RedisResult<()> {
+        let mut candidates: Vec<String> = self.nodes.keys().cloned().collect();
+        for seed in &self.seeds {
+            if !candidates.contains(seed) {
+                candidates.push(seed.clone());
+            }
+        }
+
+        let mut last_error = None;
+        for addr in candidates {
+            let mut conn = match self.node_connection(&addr).await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+            match redis::cmd("CLUSTER").arg("SLOTS").query_async::<Value>(&mut conn).await {
+                Ok(value) => {
+                    let mut slots = parse_slots(&value, &addr);
+                    if slots.is_empty() {
+                        continue;
+                    }
+                    slots.sort_by_key(|s| s.start);
+                    self.nodes.retain(|node, _| slots.iter().any(|s| &s.node == node));
+                    self.slots = slots;
+                    return Ok(());
+                }
+                Err(e) => {
+                    self.nodes.remove(&addr);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            RedisError::from((ErrorKind::ClusterConnectionNotFound, "No cluster node returned a slot map"))
+        }))
+    }
+
+    /// Addresses of all known master nodes
+    pub fn nodes(&self) -> Vec<String> {
+        let mut nodes: Vec<String> = self.slots.iter().map(|s| s.node.clone()).collect();
+        nodes.sort();
+        nodes.dedup();
+        nodes
+    }
+
+    fn node_for_slot(&self, slot: u16) -> Option<String> {
+        let index = self.slots.partition_point(|s| s.end < slot);
+        self.slots.get(index)
+            .filter(|s| s.start <= slot)
+            .map(|s| s.node.clone())
+    }
+
+    fn any_node(&self) -> Option<String> {
+        self.slots.first().map(|s| s.node.clone())
+            .or_else(|| self.seeds.first().cloned())
+    }
+
+    fn target_for(&self, key: Option<&[u8]>) -> RedisResult<String> {
+        key.and_then(|k| self.node_for_slot(key_slot(k)))
+            .or_else(|| self.any_node())
+            .ok_or_else(|| RedisError::from((ErrorKind::ClusterConnectionNotFound, "No node serves the slot")))
+    }
+
+    fn assign_slot(&mut self, slot: u16, node: &str) {
+        if let Some(range) = self.slots.iter_mut().find(|s| s.start <= slot && slot <= s.end) {
+            if range.start == range.end {
+                range.node = node.to_string();
+                return;
+            }
+        }
+        // Split the owning range so that only the moved slot changes owner
+        let mut updated = Vec::with_capacity(self.slots.len() + 2);
+        for range in self.slots.drain(..) {
+            if range.start <= slot && slot <= range.end {
+                if range.start < slot {
+                    updated.push(SlotRange { start: range.start, end: slot - 1, node: range.node.clone() });
+                }
+                if slot < range.end {
+                    updated.push(SlotRange { start: slot + 1, end: range.end, node: range.node.clone() });
+                }
+            } else {
+                updated.push(range);
+            }
+        }
+        updated.push(SlotRange { start: slot, end: slot, node: node.to_string() });
+        updated.sort_by_key(|s| s.start);
+        self.slots = updated;
+    }
+
+    async fn node_connection(&mut self, addr: &str) -> RedisResult<MultiplexedConnection> {
+        if let Some(conn) = self.nodes.get(addr) {
+            return Ok(conn.clone());
+        }
+        let client = Client::open(self.node_url.url_for(addr))?;
+        let conn = client.get_multiplexed_async_connection().await?;
+        self.nodes.insert(addr.to_string(), conn.clone());
+        Ok(conn)
+    }
+
+    /// Handle a failed request, returning the node to retry on and whether ASKING is required
+    async fn handle_error(&mut self, error: RedisError, addr: &str, attempt: usize) -> RedisResult<(Option<String>, bool)> {
+        match error.kind() {
+            ErrorKind::Server(ServerErrorKind::Moved) => {
+                let (node, slot) = error.redirect_node()
+                    .map(|(node, slot)| (node.to_string(), slot))
+                    .ok_or(error)?;
+                self.assign_slot(slot, &node);
+                Ok((Some(node), false))
+            }
+            ErrorKind::Server(ServerErrorKind::Ask) => {
+                let node = error.redirect_node()
+                    .map(|(node, _)| node.to_string())
+                    .ok_or(error)?;
+                Ok((Some(node), true))
+            }
+            ErrorKind::Server(ServerErrorKind::TryAgain) | ErrorKind::Server(ServerErrorKind::ClusterDown) => {
+                tokio::time::sleep(Duration::from_millis(50 * (attempt as u64 + 1))).await;
+                Ok((None, false))
+            }
+            _ if error.is_io_error() || error.is_connection_dropped() || error.is_unrecoverable_error() => {
+                self.nodes.remove(addr);
+                let _ = self.refresh_slots().await;
+                Ok((None, false))
+            }
+            _ => Err(error),
+        }
+    }
+
+    async fn execute(&mut self, cmd: &Cmd) -> RedisResult<Value> {
+        let args = cmd_args(cmd);
+        let key = routing_key(&args);
+        let mut redirect: Option<String> = None;
+        let mut asking = false;
+
+        for attempt in 0..=MAX_REDIRECTS {
+            let addr = match redirect.take() {
+                Some(addr) => addr,
+                None => self.target_for(key)?,
+            };
+            let mut conn = self.node_connection(&addr).await?;
+            if asking {
+                redis::cmd("ASKING").query_async::<()>(&mut conn).await?;
+            }
+            match conn.req_packed_command(cmd).await {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    if attempt == MAX_REDIRECTS {
+                        return Err(e);
+                    }
+                    let (next, ask) = self.handle_error(e, &addr, attempt).await?;
+                    redirect = next;
+                    asking = ask;
+                }
+            }
+        }
+        Err(RedisError::from((ErrorKind::Client, "Too many cluster redirections")))
+    }
+
+    async fn execute_pipeline(&mut self, pipeline: &Pipeline, offset: usize, count: usize) -> RedisResult<Vec<Value>> {
+        let key = pipeline.cmd_iter()
+            .find_map(|cmd| routing_key(&cmd_args(cmd)).map(|k| k.to_vec()));
+        let mut redirect: Option<String> = None;
+        let mut asking = false;
+
+        for attempt in 0..=MAX_REDIRECTS {
+            let addr = match redirect.take() {
+                Some(addr) => addr,
+                None => self.target_for(key.as_deref())?,
+            };
+            let mut conn = self.node_connection(&addr).await?;
+            if asking {
+                redis::cmd("ASKING").query_async::<()>(&mut conn).await?;
+            }
+            match conn.req_packed_commands(pipeline, offset, count).await {
+                Ok(values) => return Ok(values),
+                Err(e) => {
+                    if attempt == MAX_REDIRECTS {
+                        return Err(e);
+                    }
+                    let (next, ask) = self.handle_error(e, &addr, attempt).await?;
+                    redirect = next;
+                    asking = ask;
+                }
+            }
+        }
+        Err(RedisError::from((ErrorKind::Client, "Too many cluster redirections")))
+    }
+}
+
+impl ConnectionLike for ClusterConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> redis::RedisFuture<'a, Value> {
+        Box::pin(self.execute(cmd))
+    }
+
+    fn req_packed_commands<'a>(&'a mut self, cmd: &'a Pipeline, offset: usize, count: usize) -> redis::RedisFuture<'a, Vec<Value>> {
+        Box::pin(self.execute_pipeline(cmd, offset, count))
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+}
+
+/// Parse a `CLUSTER SLOTS` reply into master slot ranges
+fn parse_slots(value: &Value, queried: &str) -> Vec<SlotRange> {
+    let entries = match value {
+        Value::Array(entries) => entries,
+        _ => return Vec::new(),
+    };
+    let mut slots = Vec::new();
+    for entry in entries {
+        let items = match entry {
+            Value::Array(items) if items.len() >= 3 => items,
+            _ => continue,
+        };
+        let (start, end) = match (&items[0], &items[1]) {
+            (Value::Int(start), Value::Int(end)) => (*start as u16, *end as u16),
+            _ => continue,
+        };
+        let master = match &items[2] {
+            Value::Array(master) if master.len() >= 2 => master,
+            _ => continue,
+        };
+        let host = match &master[0] {
+            Value::BulkString(host) => String::from_utf8_lossy(host).to_string(),
+            Value::SimpleString(host) => host.clone(),
+            _ => continue,
+        };
+        let port = match &master[1] {
+            Value::Int(port) => *port,
+            _ => continue,
+        };
+        // An empty host means "the node you asked"
+        let host = if host.is_empty() || host == "?" {
+            queried.rsplit_once(':').map(|(h, _)| h.to_string()).unwrap_or(host)
+        } else {
+            host
+        };
+        slots.push(SlotRange { start, end, node: format!("{}:{}", host, port) });
+    }
+    slots
+}
@@ -0,0 +1,122 @@
+//! # connection
+//!
+//! connection 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Deployment-agnostic Redis connection
+//!
+//! All operation groups talk to Redis through `RedisConnection`, so the same
+//! API works against a standalone server, a cluster or a Sentinel setup,
+//! and every command gets a client span (see `crate::db::trace`).
+
+use super::cluster::ClusterConnection;
+use super::sentinel::SentinelConnection;
+use redis::aio::{ConnectionLike, MultiplexedConnection};
+use redis::{Cmd, Pipeline, RedisFuture, Value};
+use crate::db::trace;
+
+/// Connection used by `RedisClient` and its operation groups
+pub enum RedisConnection {
+    /// Standalone server
+    Single(MultiplexedConnection),
+    /// Redis Cluster with slot-based routing
+    Cluster(ClusterConnection),
+    /// Master discovered through Redis Sentinel
+    Sentinel(SentinelConnection),
+    /// Another connection with injected faults, see `RedisClient::with_faults`
+    #[cfg(feature = "fault-injection")]
+    Faulty(Box<RedisConnection>, rf_core::fault::FaultInjector),
+}
+
+impl ConnectionLike for RedisConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        Box::pin(trace::traced(trace::redis_span(cmd), self.send_command(cmd)))
+    }
+
+    fn req_packed_commands<'a>(&'a mut self, cmd: &'a Pipeline, offset: usize, count: usize) -> RedisFuture<'a, Vec<Value>> {
+        Box::pin(trace::traced(trace::redis_pipeline_span(cmd), self.send_commands(cmd, offset, count)))
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            RedisConnection::Single(conn) => conn.get_db(),
+            RedisConnection::Cluster(conn) => conn.get_db(),
+            RedisConnection::Sentinel(conn) => conn.get_db(),
+            #[cfg(feature = "fault-injection")]
+            RedisConnection::Faulty(conn, _) => conn.get_db(),
+        }
+    }
+}
+
+impl RedisConnection {
+    /// Send a command without a span; the fault wrapper sits inside the traced call
+    fn send_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            RedisConnection::Single(conn) => conn.req_packed_command(cmd),
+            RedisConnection::Cluster(conn) => conn.req_packed_command(cmd),
+            RedisConnection::Sentinel(conn) => conn.req_packed_command(cmd),
+            #[cfg(feature = "fault-injection")]
+            RedisConnection::Faulty(conn, faults) => Box::pin(async move {
+                faults.inject(&command_target(cmd)).await.map_err(fault_error)?;
+                conn.send_command(cmd).await
+            }),
+        }
+    }
+
+    /// Pipeline counterpart of `send_command`
+    fn send_commands<'a>(&'a mut self, cmd: &'a Pipeline, offset: usize, count: usize) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            RedisConnection::Single(conn) => conn.req_packed_commands(cmd, offset, count),
+            RedisConnection::Cluster(conn) => conn.req_packed_commands(cmd, offset, count),
+            RedisConnection::Sentinel(conn) => conn.req_packed_commands(cmd, offset, count),
+            #[cfg(feature = "fault-injection")]
+            RedisConnection::Faulty(conn, faults) => Box::pin(async move {
+                let target = cmd.cmd_iter().next().map(command_target).unwrap_or_default();
+                faults.inject(&target).await.map_err(fault_error)?;
+                conn.send_commands(cmd, offset, count).await
+            }),
+        }
+    }
+
+    /// Master URL of a Sentinel deployment, which moves on failover
+    pub(crate) fn sentinel_url(&self) -> Option<String> {
+        match self {
+            RedisConnection::Sentinel(conn) => Some(conn.master_url()),
+            #[cfg(feature = "fault-injection")]
+            RedisConnection::Faulty(conn, _) => conn.sentinel_url(),
+            _ => None,
+        }
+    }
+}
+
+/// Fault target of a command: its name and first argument, such as `GET user:1`
+#[cfg(feature = "fault-injection")]
+fn command_target(cmd: &Cmd) -> String {
+    cmd.args_iter()
+        .take(2)
+        .filter_map(|arg| match arg {
+            redis::Arg::Simple(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Injected errors come back as server errors, resets as I/O errors
+#[cfg(feature = "fault-injection")]
+fn fault_error(fault: rf_core::fault::Fault) -> redis::RedisError {
+    use redis::{ErrorKind, RedisError, ServerErrorKind};
+    use rf_core::fault::Fault;
+
+    match fault {
+        Fault::Error { message, .. } => {
+            RedisError::from((ErrorKind::Server(ServerErrorKind::ResponseError), "Injected fault", message))
+        }
+        Fault::Reset => RedisError::from(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "injected connection reset",
+        )),
+    }
+}
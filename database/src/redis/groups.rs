@@ -7,15 +7,53 @@
 
 //! Redis operation groups
 
-use redis::aio::MultiplexedConnection;
-use redis::AsyncCommands;
+use super::connection::RedisConnection;
+use redis::streams::{
+    StreamAutoClaimReply, StreamClaimReply, StreamId, StreamInfoConsumersReply, StreamInfoGroupsReply, StreamPendingCountReply,
+    StreamPendingReply, StreamRangeReply, StreamReadReply,
+};
+use redis::{AsyncCommands, FromRedisValue, ToRedisArgs};
 use rf_errors::{Result, RfError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+/// Prefix marking a gzip-compressed JSON payload
+///
+/// A JSON text never starts with a NUL byte, so plain JSON is never mistaken
+/// for a compressed payload, whatever its first bytes.
+const COMPRESSED_PREFIX: &[u8] = b"\0rfgz:";
+
+/// Serialize a value to JSON, gzip-compressing it when it reaches the threshold
+fn encode_json<T: Serialize>(value: &T, compress_threshold: Option<usize>) -> Result<Vec<u8>> {
+    let json = serde_json::to_vec(value)
+        .map_err(|e| RfError::Serialization(format!("Failed to serialize JSON: {}", e)))?;
+    match compress_threshold {
+        Some(threshold) if json.len() >= threshold => {
+            let mut data = COMPRESSED_PREFIX.to_vec();
+            data.extend(rf_encoding::gzip_compress(&json)?);
+            Ok(data)
+        }
+        _ => Ok(json),
+    }
+}
+
+/// Deserialize a JSON payload written by `encode_json` (plain or compressed)
+fn decode_json<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    let json = match data.strip_prefix(COMPRESSED_PREFIX) {
+        Some(compressed) => std::borrow::Cow::Owned(rf_encoding::gzip_decompress(compressed)?),
+        None => std::borrow::Cow::Borrowed(data),
+    };
+    serde_json::from_slice(&json)
+        .map_err(|e| RfError::Serialization(format!("Failed to deserialize JSON: {}", e)))
+}
+
 /// String operations group
 pub struct StringGroup {
-    pub(crate) connection: Arc<Mutex<MultiplexedConnection>>,
+    pub(crate) connection: Arc<Mutex<RedisConnection>>,
+    pub(crate) compress_threshold: Option<usize>,
 }
 
 impl StringGroup {
@@ -133,11 +171,84 @@ impl StringGroup {
         cmd.query_async::<Vec<String>>(&mut *conn).await
             .map_err(|e| RfError::Database(format!("Redis MGET failed: {}", e)))
     }
+
+    /// Set a binary value
+    pub async fn set_bytes(&self, key: &str, value: &[u8]) -> Result<()> {
+        let mut conn = self.connection.lock().await;
+        conn.set::<_, _, ()>(key, value).await
+            .map_err(|e| RfError::Database(format!("Redis SET failed: {}", e)))
+    }
+
+    /// Set a binary value with expiration (seconds)
+    pub async fn set_bytes_ex(&self, key: &str, value: &[u8], seconds: u64) -> Result<()> {
+        let mut conn = self.connection.lock().await;
+        conn.set_ex::<_, _, ()>(key, value, seconds).await
+            .map_err(|e| RfError::Database(format!("Redis SETEX failed: {}", e)))
+    }
+
+    /// Get a binary value, `None` if the key does not exist
+    pub async fn get_bytes(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut conn = self.connection.lock().await;
+        conn.get::<_, Option<Vec<u8>>>(key).await
+            .map_err(|e| RfError::Database(format!("Redis GET failed: {}", e)))
+    }
+
+    /// Multiple get of binary values, `None` for missing keys
+    pub async fn mget_bytes(&self, keys: &[&str]) -> Result<Vec<Option<Vec<u8>>>> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("MGET");
+        for key in keys {
+            cmd.arg(key);
+        }
+        cmd.query_async::<Vec<Option<Vec<u8>>>>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis MGET failed: {}", e)))
+    }
+
+    /// Set any value the redis crate can encode (`Vec<u8>`, integers, `bytes::Bytes`, ...)
+    pub async fn set_value<V: ToRedisArgs>(&self, key: &str, value: V) -> Result<()> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("SET").arg(key).arg(value).query_async::<()>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis SET failed: {}", e)))
+    }
+
+    /// Get a value as any type the redis crate can decode, use `Option<T>` for missing keys
+    pub async fn get_value<T: FromRedisValue>(&self, key: &str) -> Result<T> {
+        let mut conn = self.connection.lock().await;
+        conn.get::<_, T>(key).await
+            .map_err(|e| RfError::Database(format!("Redis GET failed: {}", e)))
+    }
+
+    /// Set a value serialized as JSON
+    pub async fn set_json<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let data = encode_json(value, self.compress_threshold)?;
+        let mut conn = self.connection.lock().await;
+        conn.set::<_, _, ()>(key, data).await
+            .map_err(|e| RfError::Database(format!("Redis SET failed: {}", e)))
+    }
+
+    /// Set a value serialized as JSON with expiration (seconds)
+    pub async fn set_json_ex<T: Serialize>(&self, key: &str, value: &T, seconds: u64) -> Result<()> {
+        let data = encode_json(value, self.compress_threshold)?;
+        let mut conn = self.connection.lock().await;
+        conn.set_ex::<_, _, ()>(key, data, seconds).await
+            .map_err(|e| RfError::Database(format!("Redis SETEX failed: {}", e)))
+    }
+
+    /// Get a JSON value, `None` if the key does not exist
+    pub async fn get_json<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let data = {
+            let mut conn = self.connection.lock().await;
+            conn.get::<_, Option<Vec<u8>>>(key).await
+                .map_err(|e| RfError::Database(format!("Redis GET failed: {}", e)))?
+        };
+        data.map(|data| decode_json(&data)).transpose()
+    }
 }
 
 /// Hash operations group
 pub struct HashGroup {
-    pub(crate) connection: Arc<Mutex<MultiplexedConnection>>,
+    pub(crate) connection: Arc<Mutex<RedisConnection>>,
+    pub(crate) compress_threshold: Option<usize>,
 }
 
 impl HashGroup {
@@ -233,11 +344,88 @@ impl HashGroup {
         cmd.query_async::<Vec<String>>(&mut *conn).await
             .map_err(|e| RfError::Database(format!("Redis HMGET failed: {}", e)))
     }
+
+    /// Set a binary hash field
+    pub async fn hset_bytes(&self, key: &str, field: &str, value: &[u8]) -> Result<()> {
+        let mut conn = self.connection.lock().await;
+        conn.hset::<_, _, _, ()>(key, field, value).await
+            .map_err(|e| RfError::Database(format!("Redis HSET failed: {}", e)))
+    }
+
+    /// Get a binary hash field, `None` if the field does not exist
+    pub async fn hget_bytes(&self, key: &str, field: &str) -> Result<Option<Vec<u8>>> {
+        let mut conn = self.connection.lock().await;
+        conn.hget::<_, _, Option<Vec<u8>>>(key, field).await
+            .map_err(|e| RfError::Database(format!("Redis HGET failed: {}", e)))
+    }
+
+    /// Get all hash fields with binary values
+    pub async fn hgetall_bytes(&self, key: &str) -> Result<HashMap<String, Vec<u8>>> {
+        let mut conn = self.connection.lock().await;
+        conn.hgetall::<_, HashMap<String, Vec<u8>>>(key).await
+            .map_err(|e| RfError::Database(format!("Redis HGETALL failed: {}", e)))
+    }
+
+    /// Set multiple binary hash fields
+    pub async fn hmset_bytes(&self, key: &str, pairs: &[(&str, &[u8])]) -> Result<()> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("HSET");
+        cmd.arg(key);
+        for (field, value) in pairs {
+            cmd.arg(field).arg(value);
+        }
+        cmd.query_async::<()>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis HSET failed: {}", e)))
+    }
+
+    /// Set a hash field to any value the redis crate can encode
+    pub async fn hset_value<V: ToRedisArgs>(&self, key: &str, field: &str, value: V) -> Result<()> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("HSET").arg(key).arg(field).arg(value).query_async::<()>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis HSET failed: {}", e)))
+    }
+
+    /// Get a hash field as any type the redis crate can decode
+    pub async fn hget_value<T: FromRedisValue>(&self, key: &str, field: &str) -> Result<T> {
+        let mut conn = self.connection.lock().await;
+        conn.hget::<_, _, T>(key, field).await
+            .map_err(|e| RfError::Database(format!("Redis HGET failed: {}", e)))
+    }
+
+    /// Set a hash field serialized as JSON
+    pub async fn hset_json<T: Serialize>(&self, key: &str, field: &str, value: &T) -> Result<()> {
+        let data = encode_json(value, self.compress_threshold)?;
+        let mut conn = self.connection.lock().await;
+        conn.hset::<_, _, _, ()>(key, field, data).await
+            .map_err(|e| RfError::Database(format!("Redis HSET failed: {}", e)))
+    }
+
+    /// Get a JSON hash field, `None` if the field does not exist
+    pub async fn hget_json<T: DeserializeOwned>(&self, key: &str, field: &str) -> Result<Option<T>> {
+        let data = {
+            let mut conn = self.connection.lock().await;
+            conn.hget::<_, _, Option<Vec<u8>>>(key, field).await
+                .map_err(|e| RfError::Database(format!("Redis HGET failed: {}", e)))?
+        };
+        data.map(|data| decode_json(&data)).transpose()
+    }
+
+    /// Get all fields of a hash as JSON values
+    pub async fn hgetall_json<T: DeserializeOwned>(&self, key: &str) -> Result<HashMap<String, T>> {
+        let data = {
+            let mut conn = self.connection.lock().await;
+            conn.hgetall::<_, HashMap<String, Vec<u8>>>(key).await
+                .map_err(|e| RfError::Database(format!("Redis HGETALL failed: {}", e)))?
+        };
+        data.into_iter()
+            .map(|(field, value)| decode_json(&value).map(|value| (field, value)))
+            .collect()
+    }
 }
 
 /// List operations group
 pub struct ListGroup {
-    pub(crate) connection: Arc<Mutex<MultiplexedConnection>>,
+    pub(crate) connection: Arc<Mutex<RedisConnection>>,
 }
 
 impl ListGroup {
@@ -300,6 +488,51 @@ impl ListGroup {
             .map_err(|e| RfError::Database(format!("Redis LRANGE failed: {}", e)))
     }
 
+    /// Left push binary values
+    pub async fn lpush_bytes(&self, key: &str, values: &[&[u8]]) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("LPUSH");
+        cmd.arg(key);
+        for value in values {
+            cmd.arg(*value);
+        }
+        cmd.query_async::<usize>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis LPUSH failed: {}", e)))
+    }
+
+    /// Right push binary values
+    pub async fn rpush_bytes(&self, key: &str, values: &[&[u8]]) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("RPUSH");
+        cmd.arg(key);
+        for value in values {
+            cmd.arg(*value);
+        }
+        cmd.query_async::<usize>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis RPUSH failed: {}", e)))
+    }
+
+    /// Left pop a binary value
+    pub async fn lpop_bytes(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut conn = self.connection.lock().await;
+        conn.lpop::<_, Option<Vec<u8>>>(key, None).await
+            .map_err(|e| RfError::Database(format!("Redis LPOP failed: {}", e)))
+    }
+
+    /// Right pop a binary value
+    pub async fn rpop_bytes(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut conn = self.connection.lock().await;
+        conn.rpop::<_, Option<Vec<u8>>>(key, None).await
+            .map_err(|e| RfError::Database(format!("Redis RPOP failed: {}", e)))
+    }
+
+    /// Get a list range of binary values
+    pub async fn lrange_bytes(&self, key: &str, start: i64, stop: i64) -> Result<Vec<Vec<u8>>> {
+        let mut conn = self.connection.lock().await;
+        conn.lrange::<_, Vec<Vec<u8>>>(key, start as isize, stop as isize).await
+            .map_err(|e| RfError::Database(format!("Redis LRANGE failed: {}", e)))
+    }
+
     /// Trim list
     pub async fn ltrim(&self, key: &str, start: i64, stop: i64) -> Result<()> {
         let mut conn = self.connection.lock().await;
@@ -311,7 +544,7 @@ impl ListGroup {
 
 /// Set operations group
 pub struct SetGroup {
-    pub(crate) connection: Arc<Mutex<MultiplexedConnection>>,
+    pub(crate) connection: Arc<Mutex<RedisConnection>>,
 }
 
 impl SetGroup {
@@ -363,7 +596,7 @@ impl SetGroup {
 
 /// SortedSet operations group
 pub struct SortedSetGroup {
-    pub(crate) connection: Arc<Mutex<MultiplexedConnection>>,
+    pub(crate) connection: Arc<Mutex<RedisConnection>>,
 }
 
 impl SortedSetGroup {
@@ -410,7 +643,7 @@ impl SortedSetGroup {
 
 /// Generic operations group
 pub struct GenericGroup {
-    pub(crate) connection: Arc<Mutex<MultiplexedConnection>>,
+    pub(crate) connection: Arc<Mutex<RedisConnection>>,
 }
 
 impl GenericGroup {
@@ -473,9 +706,13 @@ impl GenericGroup {
     }
 }
 
+/// Messages received on a subscription, as UTF-8 payloads
+pub type Subscription = futures_util::stream::BoxStream<'static, String>;
+
 /// PubSub operations group
 pub struct PubSubGroup {
-    pub(crate) connection: Arc<Mutex<MultiplexedConnection>>,
+    pub(crate) connection: Arc<Mutex<RedisConnection>>,
+    pub(crate) subscribe_url: Option<String>,
 }
 
 impl PubSubGroup {
@@ -485,11 +722,38 @@ impl PubSubGroup {
         conn.publish::<_, _, usize>(channel, message).await
             .map_err(|e| RfError::Database(format!("Redis PUBLISH failed: {}", e)))
     }
+
+    /// Subscribe to channels on a dedicated connection
+    ///
+    /// The stream ends when the connection drops; subscribe again to resume.
+    /// Messages published while no subscription is open are lost.
+    pub async fn subscribe(&self, channels: &[&str]) -> Result<Subscription> {
+        use futures_util::StreamExt;
+
+        let url = match self.connection.lock().await.sentinel_url() {
+            Some(url) => url,
+            None => self.subscribe_url.clone()
+                .ok_or_else(|| RfError::Database("Redis SUBSCRIBE failed: no server to subscribe on".to_string()))?,
+        };
+        let subscribe_error = |e: redis::RedisError| RfError::Database(format!("Redis SUBSCRIBE failed: {}", e));
+        let mut pubsub = redis::Client::open(url.as_str())
+            .map_err(subscribe_error)?
+            .get_async_pubsub()
+            .await
+            .map_err(subscribe_error)?;
+        for channel in channels {
+            pubsub.subscribe(*channel).await.map_err(subscribe_error)?;
+        }
+        Ok(pubsub
+            .into_on_message()
+            .filter_map(|message| async move { message.get_payload::<String>().ok() })
+            .boxed())
+    }
 }
 
 /// Script operations group
 pub struct ScriptGroup {
-    pub(crate) connection: Arc<Mutex<MultiplexedConnection>>,
+    pub(crate) connection: Arc<Mutex<RedisConnection>>,
 }
 
 impl ScriptGroup {
@@ -531,3 +795,350 @@ impl ScriptGroup {
     }
 }
 
+
+/// Stream entry: id and field-value pairs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamEntry {
+    pub id: String,
+    pub fields: HashMap<String, String>,
+}
+
+impl StreamEntry {
+    /// Get a field value
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.fields.get(field).map(|v| v.as_str())
+    }
+}
+
+/// Entries read from one stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamRead {
+    pub key: String,
+    pub entries: Vec<StreamEntry>,
+}
+
+/// Summary of a consumer group's pending entries list
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamPendingSummary {
+    pub count: usize,
+    pub min_id: Option<String>,
+    pub max_id: Option<String>,
+    pub consumers: Vec<(String, usize)>,
+}
+
+/// Pending entry waiting for acknowledgement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamPendingEntry {
+    pub id: String,
+    pub consumer: String,
+    pub idle_ms: usize,
+    pub delivery_count: usize,
+}
+
+/// Consumer group information
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamGroupInfo {
+    pub name: String,
+    pub consumers: usize,
+    pub pending: usize,
+    pub last_delivered_id: String,
+}
+
+/// Consumer information
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamConsumerInfo {
+    pub name: String,
+    pub pending: usize,
+    pub idle_ms: usize,
+}
+
+/// Result of an XAUTOCLAIM scan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamAutoClaim {
+    /// Id to resume the scan from, `0-0` once the whole list was scanned
+    pub next_id: String,
+    pub entries: Vec<StreamEntry>,
+    /// Pending ids whose entries were deleted from the stream
+    pub deleted_ids: Vec<String>,
+}
+
+/// Convert an entry, failing on a field value that is not UTF-8
+fn stream_entry(id: StreamId) -> Result<StreamEntry> {
+    let mut fields = HashMap::with_capacity(id.map.len());
+    for (field, value) in id.map {
+        let value = redis::from_redis_value::<String>(value).map_err(|e| {
+            RfError::Serialization(format!("Stream entry {} field {} is not UTF-8: {}", id.id, field, e))
+        })?;
+        fields.insert(field, value);
+    }
+    Ok(StreamEntry { id: id.id, fields })
+}
+
+fn stream_entries(ids: Vec<StreamId>) -> Result<Vec<StreamEntry>> {
+    ids.into_iter().map(stream_entry).collect()
+}
+
+fn stream_reads(reply: Option<StreamReadReply>) -> Result<Vec<StreamRead>> {
+    reply.map(|reply| reply.keys.into_iter().map(|k| Ok(StreamRead {
+        key: k.key,
+        entries: stream_entries(k.ids)?,
+    })).collect()).unwrap_or_else(|| Ok(Vec::new()))
+}
+
+/// Stream operations group
+///
+/// Blocking reads (`block_ms`) hold the client's shared connection until
+/// they return, so long-polling consumers should use a dedicated client.
+pub struct StreamGroup {
+    pub(crate) connection: Arc<Mutex<RedisConnection>>,
+}
+
+impl StreamGroup {
+    /// Append an entry, returns its id (`id` is usually `*`)
+    pub async fn xadd(&self, key: &str, id: &str, fields: &[(&str, &str)]) -> Result<String> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("XADD");
+        cmd.arg(key).arg(id);
+        for (field, value) in fields {
+            cmd.arg(field).arg(value);
+        }
+        cmd.query_async::<String>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XADD failed: {}", e)))
+    }
+
+    /// Append an entry with an auto-generated id, trimming the stream to about `maxlen` entries
+    pub async fn xadd_maxlen(&self, key: &str, maxlen: usize, fields: &[(&str, &str)]) -> Result<String> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("XADD");
+        cmd.arg(key).arg("MAXLEN").arg("~").arg(maxlen).arg("*");
+        for (field, value) in fields {
+            cmd.arg(field).arg(value);
+        }
+        cmd.query_async::<String>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XADD failed: {}", e)))
+    }
+
+    /// Get stream length
+    pub async fn xlen(&self, key: &str) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("XLEN").arg(key).query_async::<usize>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XLEN failed: {}", e)))
+    }
+
+    /// Get entries in an id range (`-` and `+` for the whole stream)
+    pub async fn xrange(&self, key: &str, start: &str, end: &str, count: Option<usize>) -> Result<Vec<StreamEntry>> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("XRANGE");
+        cmd.arg(key).arg(start).arg(end);
+        if let Some(count) = count {
+            cmd.arg("COUNT").arg(count);
+        }
+        let reply = cmd.query_async::<StreamRangeReply>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XRANGE failed: {}", e)))?;
+        stream_entries(reply.ids)
+    }
+
+    /// Delete entries
+    pub async fn xdel(&self, key: &str, ids: &[&str]) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("XDEL").arg(key).arg(ids).query_async::<usize>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XDEL failed: {}", e)))
+    }
+
+    /// Trim the stream to `maxlen` entries, returns the number of evicted entries
+    pub async fn xtrim(&self, key: &str, maxlen: usize) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("XTRIM").arg(key).arg("MAXLEN").arg(maxlen).query_async::<usize>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XTRIM failed: {}", e)))
+    }
+
+    /// Read entries after the given ids (`$` for new entries only)
+    pub async fn xread(&self, keys: &[&str], ids: &[&str], count: Option<usize>, block_ms: Option<usize>) -> Result<Vec<StreamRead>> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("XREAD");
+        if let Some(count) = count {
+            cmd.arg("COUNT").arg(count);
+        }
+        if let Some(block) = block_ms {
+            cmd.arg("BLOCK").arg(block);
+        }
+        cmd.arg("STREAMS").arg(keys).arg(ids);
+        let reply = cmd.query_async::<Option<StreamReadReply>>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XREAD failed: {}", e)))?;
+        stream_reads(reply)
+    }
+
+    /// Read entries as a group consumer (`>` for never-delivered entries)
+    pub async fn xreadgroup(
+        &self,
+        group: &str,
+        consumer: &str,
+        keys: &[&str],
+        ids: &[&str],
+        count: Option<usize>,
+        block_ms: Option<usize>,
+    ) -> Result<Vec<StreamRead>> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("XREADGROUP");
+        cmd.arg("GROUP").arg(group).arg(consumer);
+        if let Some(count) = count {
+            cmd.arg("COUNT").arg(count);
+        }
+        if let Some(block) = block_ms {
+            cmd.arg("BLOCK").arg(block);
+        }
+        cmd.arg("STREAMS").arg(keys).arg(ids);
+        let reply = cmd.query_async::<Option<StreamReadReply>>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XREADGROUP failed: {}", e)))?;
+        stream_reads(reply)
+    }
+
+    /// Acknowledge processed entries
+    pub async fn xack(&self, key: &str, group: &str, ids: &[&str]) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("XACK").arg(key).arg(group).arg(ids).query_async::<usize>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XACK failed: {}", e)))
+    }
+
+    /// Get the pending entries summary of a group
+    pub async fn xpending(&self, key: &str, group: &str) -> Result<StreamPendingSummary> {
+        let mut conn = self.connection.lock().await;
+        let reply = redis::cmd("XPENDING").arg(key).arg(group).query_async::<StreamPendingReply>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XPENDING failed: {}", e)))?;
+        Ok(match reply {
+            StreamPendingReply::Data(data) => StreamPendingSummary {
+                count: data.count,
+                min_id: Some(data.start_id),
+                max_id: Some(data.end_id),
+                consumers: data.consumers.into_iter().map(|c| (c.name, c.pending)).collect(),
+            },
+            _ => StreamPendingSummary::default(),
+        })
+    }
+
+    /// List pending entries in an id range, optionally for one consumer
+    pub async fn xpending_range(
+        &self,
+        key: &str,
+        group: &str,
+        start: &str,
+        end: &str,
+        count: usize,
+        consumer: Option<&str>,
+    ) -> Result<Vec<StreamPendingEntry>> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("XPENDING");
+        cmd.arg(key).arg(group).arg(start).arg(end).arg(count);
+        if let Some(consumer) = consumer {
+            cmd.arg(consumer);
+        }
+        let reply = cmd.query_async::<StreamPendingCountReply>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XPENDING failed: {}", e)))?;
+        Ok(reply.ids.into_iter().map(|p| StreamPendingEntry {
+            id: p.id,
+            consumer: p.consumer,
+            idle_ms: p.last_delivered_ms,
+            delivery_count: p.times_delivered,
+        }).collect())
+    }
+
+    /// Transfer pending entries idle for at least `min_idle_ms` to another consumer
+    pub async fn xclaim(&self, key: &str, group: &str, consumer: &str, min_idle_ms: usize, ids: &[&str]) -> Result<Vec<StreamEntry>> {
+        let mut conn = self.connection.lock().await;
+        let reply = redis::cmd("XCLAIM").arg(key).arg(group).arg(consumer).arg(min_idle_ms).arg(ids)
+            .query_async::<StreamClaimReply>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XCLAIM failed: {}", e)))?;
+        stream_entries(reply.ids)
+    }
+
+    /// Scan the pending entries from `start` (`0-0` for the beginning) and transfer
+    /// up to `count` of those idle for at least `min_idle_ms` to `consumer`
+    pub async fn xautoclaim(
+        &self,
+        key: &str,
+        group: &str,
+        consumer: &str,
+        min_idle_ms: usize,
+        start: &str,
+        count: usize,
+    ) -> Result<StreamAutoClaim> {
+        let mut conn = self.connection.lock().await;
+        let reply = redis::cmd("XAUTOCLAIM").arg(key).arg(group).arg(consumer).arg(min_idle_ms).arg(start)
+            .arg("COUNT").arg(count)
+            .query_async::<StreamAutoClaimReply>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XAUTOCLAIM failed: {}", e)))?;
+        Ok(StreamAutoClaim {
+            next_id: reply.next_stream_id,
+            entries: stream_entries(reply.claimed)?,
+            deleted_ids: reply.deleted_ids,
+        })
+    }
+
+    /// Create a consumer group starting at `id` (`$` for new entries, `0` for all)
+    pub async fn xgroup_create(&self, key: &str, group: &str, id: &str, mkstream: bool) -> Result<()> {
+        let mut conn = self.connection.lock().await;
+        let mut cmd = redis::cmd("XGROUP");
+        cmd.arg("CREATE").arg(key).arg(group).arg(id);
+        if mkstream {
+            cmd.arg("MKSTREAM");
+        }
+        cmd.query_async::<()>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XGROUP CREATE failed: {}", e)))
+    }
+
+    /// Destroy a consumer group
+    pub async fn xgroup_destroy(&self, key: &str, group: &str) -> Result<bool> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("XGROUP").arg("DESTROY").arg(key).arg(group).query_async::<bool>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XGROUP DESTROY failed: {}", e)))
+    }
+
+    /// Create a consumer in a group
+    pub async fn xgroup_create_consumer(&self, key: &str, group: &str, consumer: &str) -> Result<bool> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("XGROUP").arg("CREATECONSUMER").arg(key).arg(group).arg(consumer)
+            .query_async::<bool>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XGROUP CREATECONSUMER failed: {}", e)))
+    }
+
+    /// Delete a consumer, returns the number of pending entries it owned
+    pub async fn xgroup_del_consumer(&self, key: &str, group: &str, consumer: &str) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("XGROUP").arg("DELCONSUMER").arg(key).arg(group).arg(consumer)
+            .query_async::<usize>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XGROUP DELCONSUMER failed: {}", e)))
+    }
+
+    /// Set the last delivered id of a group
+    pub async fn xgroup_set_id(&self, key: &str, group: &str, id: &str) -> Result<()> {
+        let mut conn = self.connection.lock().await;
+        redis::cmd("XGROUP").arg("SETID").arg(key).arg(group).arg(id).query_async::<()>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XGROUP SETID failed: {}", e)))
+    }
+
+    /// List the consumer groups of a stream
+    pub async fn xinfo_groups(&self, key: &str) -> Result<Vec<StreamGroupInfo>> {
+        let mut conn = self.connection.lock().await;
+        let reply = redis::cmd("XINFO").arg("GROUPS").arg(key).query_async::<StreamInfoGroupsReply>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XINFO GROUPS failed: {}", e)))?;
+        Ok(reply.groups.into_iter().map(|g| StreamGroupInfo {
+            name: g.name,
+            consumers: g.consumers,
+            pending: g.pending,
+            last_delivered_id: g.last_delivered_id,
+        }).collect())
+    }
+
+    /// List the consumers of a group
+    pub async fn xinfo_consumers(&self, key: &str, group: &str) -> Result<Vec<StreamConsumerInfo>> {
+        let mut conn = self.connection.lock().await;
+        let reply = redis::cmd("XINFO").arg("CONSUMERS").arg(key).arg(group)
+            .query_async::<StreamInfoConsumersReply>(&mut *conn).await
+            .map_err(|e| RfError::Database(format!("Redis XINFO CONSUMERS failed: {}", e)))?;
+        Ok(reply.consumers.into_iter().map(|c| StreamConsumerInfo {
+            name: c.name,
+            pending: c.pending,
+            idle_ms: c.idle,
+        }).collect())
+    }
+}
@@ -0,0 +1,143 @@
+//! # sentinel
+//!
+//! sentinel 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Redis Sentinel connection
+//!
+//! Resolves the current master through the configured sentinels and
+//! transparently reconnects after a failover (dropped connection or
+//! `READONLY` reply from a demoted master).
+
+use redis::aio::{ConnectionLike, MultiplexedConnection};
+use redis::{Client, Cmd, ErrorKind, Pipeline, RedisError, RedisResult, Value};
+
+/// Connection to the master of a Sentinel-managed deployment
+pub struct SentinelConnection {
+    sentinels: Vec<String>,
+    master_name: String,
+    master_password: Option<String>,
+    master_addr: String,
+    connection: MultiplexedConnection,
+}
+
+impl SentinelConnection {
+    /// Resolve the master through the sentinels and connect to it
+    pub async fn connect(sentinels: &[&str], master_name: &str, master_password: Option<&str>) -> RedisResult<Self> {
+        if sentinels.is_empty() {
+            return Err(RedisError::from((ErrorKind::InvalidClientConfig, "No sentinel nodes configured")));
+        }
+        let sentinels: Vec<String> = sentinels.iter().map(|s| s.to_string()).collect();
+        let master_addr = resolve_master(&sentinels, master_name).await?;
+        let connection = open(&master_addr, master_password).await?;
+        Ok(Self {
+            sentinels,
+            master_name: master_name.to_string(),
+            master_password: master_password.map(|p| p.to_string()),
+            master_addr,
+            connection,
+        })
+    }
+
+    /// Address of the master currently in use
+    pub fn master_addr(&self) -> &str {
+        &self.master_addr
+    }
+
+    /// Connection URL of the master currently in use, with the master password
+    pub fn master_url(&self) -> String {
+        master_url(&self.master_addr, self.master_password.as_deref())
+    }
+
+    /// Ask the sentinels for the master again and reconnect if it changed
+    pub async fn refresh_master(&mut self) -> RedisResult<()> {
+        let addr = resolve_master(&self.sentinels, &self.master_name).await?;
+        self.connection = open(&addr, self.master_password.as_deref()).await?;
+        self.master_addr = addr;
+        Ok(())
+    }
+
+    async fn execute(&mut self, cmd: &Cmd) -> RedisResult<Value> {
+        match self.connection.req_packed_command(cmd).await {
+            Err(e) if is_failover(&e) => {
+                self.refresh_master().await?;
+                self.connection.req_packed_command(cmd).await
+            }
+            result => result,
+        }
+    }
+
+    async fn execute_pipeline(&mut self, pipeline: &Pipeline, offset: usize, count: usize) -> RedisResult<Vec<Value>> {
+        match self.connection.req_packed_commands(pipeline, offset, count).await {
+            Err(e) if is_failover(&e) => {
+                self.refresh_master().await?;
+                self.connection.req_packed_commands(pipeline, offset, count).await
+            }
+            result => result,
+        }
+    }
+}
+
+impl ConnectionLike for SentinelConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> redis::RedisFuture<'a, Value> {
+        Box::pin(self.execute(cmd))
+    }
+
+    fn req_packed_commands<'a>(&'a mut self, cmd: &'a Pipeline, offset: usize, count: usize) -> redis::RedisFuture<'a, Vec<Value>> {
+        Box::pin(self.execute_pipeline(cmd, offset, count))
+    }
+
+    fn get_db(&self) -> i64 {
+        self.connection.get_db()
+    }
+}
+
+fn is_failover(error: &RedisError) -> bool {
+    error.is_io_error()
+        || error.is_connection_dropped()
+        || error.is_unrecoverable_error()
+        || error.code() == Some("READONLY")
+}
+
+async fn resolve_master(sentinels: &[String], master_name: &str) -> RedisResult<String> {
+    let mut last_error = None;
+    for sentinel in sentinels {
+        let result = async {
+            let client = Client::open(sentinel.as_str())?;
+            let mut conn = client.get_multiplexed_async_connection().await?;
+            redis::cmd("SENTINEL")
+                .arg("get-master-addr-by-name")
+                .arg(master_name)
+                .query_async::<Option<(String, u16)>>(&mut conn)
+                .await
+        }
+        .await;
+        match result {
+            Ok(Some((host, port))) => return Ok(format!("{}:{}", host, port)),
+            Ok(None) => {
+                last_error = Some(RedisError::from((
+                    ErrorKind::MasterNameNotFoundBySentinel,
+                    "Master name not known by sentinel",
+                    format!("{} ({})", master_name, sentinel),
+                )));
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        RedisError::from((ErrorKind::MasterNameNotFoundBySentinel, "No sentinel reachable"))
+    }))
+}
+
+fn master_url(addr: &str, password: Option<&str>) -> String {
+    match password {
+        Some(password) => format!("redis://:{}@{}/", password, addr),
+        None => format!("redis://{}/", addr),
+    }
+}
+
+async fn open(addr: &str, password: Option<&str>) -> RedisResult<MultiplexedConnection> {
+    Client::open(master_url(addr, password))?.get_multiplexed_async_connection().await
+}
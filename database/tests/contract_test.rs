@@ -0,0 +1,124 @@
+//! # contract_test
+//!
+//! contract_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Message contract tests
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+    use rf_database::db::{Compatibility, ContractRegistry, Database, Outbox, OutboxEvent, RelayStats};
+    use rf_errors::RfError;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct UserCreated {
+        #[prost(int64, tag = "1")]
+        id: i64,
+        #[prost(string, tag = "2")]
+        name: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct OrderCreated {
+        id: i64,
+        total: f64,
+    }
+
+    fn order_schema() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {"id": {"type": "integer"}, "total": {"type": "number", "minimum": 0}},
+            "required": ["id", "total"],
+        })
+    }
+
+    #[test]
+    fn test_contract_versions_and_evolution() {
+        let contracts = ContractRegistry::new();
+        assert_eq!(contracts.register_json_schema("order.created", order_schema()).unwrap(), 1);
+        assert_eq!(contracts.register_json_schema("order.created", order_schema()).unwrap(), 1);
+
+        // Optional field: compatible
+        let mut v2 = order_schema();
+        v2["properties"]["coupon"] = json!({"type": "string"});
+        assert_eq!(contracts.register_json_schema("order.created", v2.clone()).unwrap(), 2);
+
+        // New required field: old messages would be rejected
+        let mut v3 = v2.clone();
+        v3["required"] = json!(["id", "total", "currency"]);
+        let err = contracts.register_json_schema("order.created", v3.clone()).unwrap_err();
+        assert!(matches!(&err, RfError::Validation(msg) if msg.contains("property 'currency' became required")), "{}", err);
+        assert_eq!(contracts.versions("order.created").len(), 2);
+
+        let relaxed = ContractRegistry::new().with_compatibility(Compatibility::None);
+        relaxed.register_json_schema("order.created", v2).unwrap();
+        assert_eq!(relaxed.register_json_schema("order.created", v3).unwrap(), 2);
+
+        assert!(contracts.register_protobuf::<UserCreated>("order.created").is_err());
+        assert!(contracts.register_json_schema("bad", json!({"type": "string", "pattern": "("})).is_err());
+        assert_eq!(contracts.topics(), ["order.created"]);
+    }
+
+    #[test]
+    fn test_contract_validate_and_decode() {
+        let contracts = ContractRegistry::new();
+        contracts.register_json_schema("order.created", order_schema()).unwrap();
+
+        let order: OrderCreated = contracts.decode("order.created", br#"{"id": 1, "total": 9.5}"#).unwrap();
+        assert_eq!(order, OrderCreated { id: 1, total: 9.5 });
+        let err = contracts.decode::<OrderCreated>("order.created", br#"{"id": 1, "total": -1}"#).unwrap_err();
+        assert_eq!(err.to_string(), "Validation error: Message for topic 'order.created' violates schema v1: /total: value below minimum 0");
+        assert!(contracts.validate("order.created", b"not json").is_err());
+
+        // Unregistered topics pass unless the registry is strict
+        assert!(contracts.validate("other", b"anything").is_ok());
+        let strict = ContractRegistry::new().strict(true);
+        assert!(strict.validate_json("other", &json!({})).is_err());
+
+        assert_eq!(contracts.register_protobuf::<UserCreated>("user.created").unwrap(), 1);
+        let encoded = UserCreated { id: 7, name: "ann".into() }.encode_to_vec();
+        assert!(contracts.validate("user.created", &encoded).is_ok());
+        assert!(contracts.validate("user.created", &[0x0a, 0xff]).is_err());
+        assert!(contracts.validate_json("user.created", &json!({"id": 7})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_contract_guards_outbox() {
+        let dir = TempDir::new().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("app.db").display());
+        let db = Database::new_sqlite(&url).await.unwrap();
+        let contracts = Arc::new(ContractRegistry::new());
+        contracts.register_json_schema("order.created", order_schema()).unwrap();
+        let outbox = Outbox::new(&db).contracts(contracts.clone());
+        outbox.migrate().await.unwrap();
+
+        let mut tx = db.begin().await.unwrap();
+        outbox.enqueue(&mut tx, "order.created", &json!({"id": 1, "total": 2.0})).await.unwrap();
+        let err = outbox.enqueue(&mut tx, "order.created", &json!({"id": "1"})).await.unwrap_err();
+        assert!(matches!(err, RfError::Validation(_)));
+        // Written without the registry, stopped by the relay
+        Outbox::new(&db).enqueue(&mut tx, "order.created", &json!({"id": 2})).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let published = Arc::new(Mutex::new(Vec::new()));
+        let sink = published.clone();
+        let relay = outbox
+            .relay(move |event: OutboxEvent| {
+                let sink = sink.clone();
+                async move {
+                    sink.lock().unwrap().push(event.payload::<serde_json::Value>()?);
+                    Ok(())
+                }
+            })
+            .max_attempts(1);
+        assert_eq!(relay.run_once().await.unwrap(), RelayStats { published: 1, failed: 1, deleted: 0 });
+        assert_eq!(*published.lock().unwrap(), [json!({"id": 1, "total": 2.0})]);
+    }
+}
@@ -0,0 +1,78 @@
+//! # fault_test
+//!
+//! fault_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Fault injection tests
+#![cfg(feature = "fault-injection")]
+
+#[cfg(test)]
+mod tests {
+    use rf_core::fault::{FaultInjector, FaultRule};
+    use rf_database::db::Database;
+    use rf_database::redis::RedisClient;
+    use rf_errors::RfError;
+    use rf_test::redis::FakeRedis;
+    use std::time::Duration;
+
+    async fn database(name: &str) -> (Database, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("rf_fault_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = Database::new_sqlite(&format!("sqlite://{}?mode=rwc", dir.join("fault.db").display())).await.unwrap();
+        db.raw_execute("CREATE TABLE IF NOT EXISTS jobs (id INTEGER PRIMARY KEY, name TEXT)").await.unwrap();
+        db.raw_execute("DELETE FROM jobs").await.unwrap();
+        db.raw_execute("INSERT INTO jobs (name) VALUES ('a'), ('b')").await.unwrap();
+        (db, dir)
+    }
+
+    #[tokio::test]
+    async fn test_database_faults() {
+        let (db, dir) = database("db").await;
+        let faults = FaultInjector::new()
+            .rule(FaultRule::new("SELECT jobs").error_rate(1.0).error_message("too many connections").times(1))
+            .rule(FaultRule::new("UPDATE jobs").reset_rate(1.0))
+            .rule(FaultRule::new("DELETE FROM jobs*").latency(Duration::from_millis(300)));
+        let db = db.with_query_timeout(Duration::from_millis(100)).with_faults(faults.clone());
+
+        let result = db.model("jobs").unscoped().count().await;
+        assert!(matches!(result, Err(RfError::Database(m)) if m == "Injected fault: too many connections"));
+        assert_eq!(db.model("jobs").unscoped().count().await.unwrap(), 2);
+
+        let result = db.model("jobs").unscoped().update("name = 'x'").await;
+        assert!(matches!(result, Err(RfError::Database(m)) if m.contains("connection reset")));
+
+        // Injected latency counts against the query timeout
+        let result = db.raw_execute("DELETE FROM jobs WHERE id = 1").await;
+        assert!(matches!(result, Err(RfError::Timeout(_))), "{:?}", result);
+        assert_eq!(faults.injected(), 3);
+
+        faults.clear();
+        assert_eq!(db.model("jobs").unscoped().update("name = 'x'").await.unwrap(), 2);
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_redis_faults() {
+        let redis = FakeRedis::start().await;
+        redis.set("session:1", "alice");
+        let faults = FaultInjector::new()
+            .rule(FaultRule::new("GET session:*").reset_rate(1.0).times(1))
+            .rule(FaultRule::new("SET *").error_rate(1.0).error_message("OOM command not allowed"));
+        let client = RedisClient::new(redis.url()).await.unwrap().with_faults(faults.clone());
+
+        let result = client.get("session:1").await;
+        assert!(matches!(result, Err(RfError::Database(m)) if m.starts_with("Redis GET failed")));
+        assert_eq!(client.get("session:1").await.unwrap(), "alice");
+
+        let result = client.set("user:1", "bob").await;
+        assert!(matches!(result, Err(RfError::Database(m)) if m.contains("OOM command not allowed")));
+        assert_eq!(faults.injected(), 2);
+
+        // Failed commands never reached the server
+        let sent = redis.commands();
+        assert_eq!(sent.iter().filter(|c| c.starts_with("GET") || c.starts_with("SET")).collect::<Vec<_>>(), ["GET session:1"]);
+        client.refresh_topology().await.unwrap();
+    }
+}
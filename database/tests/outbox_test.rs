@@ -0,0 +1,140 @@
+//! # outbox_test
+//!
+//! outbox_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Transactional outbox tests
+
+#[cfg(test)]
+mod tests {
+    use rf_database::db::{Database, Outbox, OutboxEvent, RelayStats};
+    use rf_errors::RfError;
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, Database, Outbox) {
+        let dir = TempDir::new().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("app.db").display());
+        let db = Database::new_sqlite(&url).await.unwrap();
+        let mut tx = db.begin().await.unwrap();
+        tx.execute("CREATE TABLE orders (id INTEGER PRIMARY KEY, total REAL)").await.unwrap();
+        tx.commit().await.unwrap();
+        let outbox = Outbox::new(&db);
+        outbox.migrate().await.unwrap();
+        outbox.migrate().await.unwrap();
+        (dir, db, outbox)
+    }
+
+    #[tokio::test]
+    async fn test_outbox_follows_transaction() {
+        let (_dir, db, outbox) = setup().await;
+
+        let mut tx = db.begin().await.unwrap();
+        db.model("orders").insert_tx(&mut tx, &json!({"id": 1, "total": 9.5})).await.unwrap();
+        let event_id = outbox.enqueue_keyed(&mut tx, "order.created", "1", &json!({"id": 1})).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let mut tx = db.begin().await.unwrap();
+        db.model("orders").insert_tx(&mut tx, &json!({"id": 2, "total": 3.0})).await.unwrap();
+        outbox.enqueue(&mut tx, "order.created", &json!({"id": 2})).await.unwrap();
+        tx.rollback().await.unwrap();
+
+        assert_eq!(outbox.pending().await.unwrap(), 1);
+        let mut tx = db.begin().await.unwrap();
+        let orders = tx.fetch_json("SELECT id FROM orders WHERE total > $1", &[9.0.into()]).await.unwrap();
+        tx.commit().await.unwrap();
+        assert_eq!(orders.len(), 1);
+
+        let published = Arc::new(Mutex::new(Vec::<OutboxEvent>::new()));
+        let sink = published.clone();
+        let relay = outbox.relay(move |event: OutboxEvent| {
+            let sink = sink.clone();
+            async move {
+                sink.lock().unwrap().push(event);
+                Ok(())
+            }
+        });
+        assert_eq!(relay.run_once().await.unwrap(), RelayStats { published: 1, failed: 0, deleted: 0 });
+        assert_eq!(relay.run_once().await.unwrap().published, 0);
+
+        let event = published.lock().unwrap()[0].clone();
+        assert_eq!((event.event_id.as_str(), event.topic.as_str(), event.event_key.as_deref()), (event_id.as_str(), "order.created", Some("1")));
+        assert_eq!(event.payload::<Value>().unwrap(), json!({"id": 1}));
+        assert_eq!(outbox.pending().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_outbox_relay_retries_in_key_order() {
+        let (_dir, db, outbox) = setup().await;
+        let mut tx = db.begin().await.unwrap();
+        for (key, step) in [("a", "a1"), ("a", "a2"), ("b", "b1"), ("poison", "p1")] {
+            outbox.enqueue_keyed(&mut tx, "steps", key, &step).await.unwrap();
+        }
+        tx.commit().await.unwrap();
+
+        // `a1` fails once, `p1` always fails
+        let published = Arc::new(Mutex::new(Vec::<String>::new()));
+        let failed_once = Arc::new(Mutex::new(false));
+        let sink = published.clone();
+        let relay = outbox
+            .relay(move |event: OutboxEvent| {
+                let sink = sink.clone();
+                let failed_once = failed_once.clone();
+                async move {
+                    let step: String = event.payload()?;
+                    if step == "p1" || (step == "a1" && !std::mem::replace(&mut *failed_once.lock().unwrap(), true)) {
+                        return Err(RfError::Network(format!("broker rejected {}", step)));
+                    }
+                    sink.lock().unwrap().push(step);
+                    Ok(())
+                }
+            })
+            .retry_backoff(Duration::ZERO)
+            .max_attempts(2)
+            .retention(Some(Duration::ZERO));
+
+        assert_eq!(relay.run_once().await.unwrap(), RelayStats { published: 1, failed: 2, deleted: 0 });
+        assert_eq!(*published.lock().unwrap(), ["b1"]);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let stats = relay.run_once().await.unwrap();
+        assert_eq!((stats.published, stats.failed, stats.deleted), (2, 1, 1));
+        assert_eq!(*published.lock().unwrap(), ["b1", "a1", "a2"]);
+
+        // Out of attempts: kept for inspection, no longer retried
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let stats = relay.run_once().await.unwrap();
+        assert_eq!((stats.published, stats.failed, stats.deleted), (0, 0, 2));
+        assert_eq!(outbox.pending().await.unwrap(), 1);
+        let mut tx = db.begin().await.unwrap();
+        let rows = tx.fetch_json("SELECT attempts, last_error FROM rf_outbox", &[]).await.unwrap();
+        tx.commit().await.unwrap();
+        assert_eq!(Value::Object(rows[0].clone()), json!({"attempts": 2, "last_error": "Network error: broker rejected p1"}));
+    }
+
+    #[tokio::test]
+    async fn test_outbox_spawned_relay() {
+        let (_dir, db, outbox) = setup().await;
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let handle = outbox
+            .relay(move |event: OutboxEvent| {
+                let sender = sender.clone();
+                async move {
+                    sender.send(event.topic).map_err(|e| RfError::Internal(e.to_string()))
+                }
+            })
+            .poll_interval(Duration::from_secs(3600))
+            .spawn();
+
+        let mut tx = db.begin().await.unwrap();
+        outbox.enqueue(&mut tx, "user.signed_up", &json!({"id": 7})).await.unwrap();
+        tx.commit().await.unwrap();
+        handle.wake();
+        let topic = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await.unwrap();
+        assert_eq!(topic.as_deref(), Some("user.signed_up"));
+        handle.shutdown().await;
+    }
+}
@@ -0,0 +1,60 @@
+//! # redis_bytes_test
+//!
+//! redis_bytes_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Binary-safe Redis value tests
+
+#[cfg(test)]
+mod tests {
+    use rf_database::redis::RedisClient;
+    use rf_test::redis::FakeRedis;
+
+    /// A value that breaks anything expecting UTF-8 or line-based framing
+    const PAYLOAD: &[u8] = &[0x00, 0xff, b'\r', b'\n', 0xc3, 0x28, b'$', b'*'];
+
+    #[tokio::test]
+    async fn test_string_bytes() {
+        let client = RedisClient::new(FakeRedis::start().await.url()).await.unwrap();
+        client.set_bytes("blob", PAYLOAD).await.unwrap();
+        assert_eq!(client.get_bytes("blob").await.unwrap().as_deref(), Some(PAYLOAD));
+        assert_eq!(client.get_bytes("missing").await.unwrap(), None);
+
+        let strings = client.string();
+        strings.set_value("counter", 42u64).await.unwrap();
+        assert_eq!(strings.get_value::<u64>("counter").await.unwrap(), 42);
+        strings.set_value("owned", PAYLOAD.to_vec()).await.unwrap();
+        assert_eq!(strings.get_value::<Option<Vec<u8>>>("owned").await.unwrap().as_deref(), Some(PAYLOAD));
+        assert_eq!(
+            strings.mget_bytes(&["blob", "missing"]).await.unwrap(),
+            vec![Some(PAYLOAD.to_vec()), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hash_and_list_bytes() {
+        let client = RedisClient::new(FakeRedis::start().await.url()).await.unwrap();
+        let hash = client.hash();
+        hash.hset_bytes("h", "raw", PAYLOAD).await.unwrap();
+        hash.hmset_bytes("h", &[("a", b"\x01"), ("b", b"\x02")]).await.unwrap();
+        assert_eq!(hash.hget_bytes("h", "raw").await.unwrap().as_deref(), Some(PAYLOAD));
+        assert_eq!(hash.hget_bytes("h", "missing").await.unwrap(), None);
+        let all = hash.hgetall_bytes("h").await.unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all["b"], b"\x02");
+
+        let list = client.list();
+        assert_eq!(list.rpush_bytes("l", &[PAYLOAD, b"\xfe"]).await.unwrap(), 2);
+        assert_eq!(list.lrange_bytes("l", 0, -1).await.unwrap(), vec![PAYLOAD.to_vec(), b"\xfe".to_vec()]);
+        assert_eq!(list.lpop_bytes("l").await.unwrap().as_deref(), Some(PAYLOAD));
+
+        // Hand-built commands take any `ToRedisArgs` argument
+        let mut cmd = redis::cmd("SET");
+        cmd.arg("built").arg(PAYLOAD);
+        client.query::<()>(&cmd).await.unwrap();
+        let value: Option<Vec<u8>> = client.query(redis::cmd("GET").arg("built")).await.unwrap();
+        assert_eq!(value.as_deref(), Some(PAYLOAD));
+    }
+}
@@ -0,0 +1,25 @@
+//! # redis_cluster_test
+//!
+//! redis_cluster_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Redis cluster slot tests
+
+use rf_database::redis::{key_slot, CLUSTER_SLOTS};
+
+#[test]
+fn test_key_slot() {
+    // CRC16/XMODEM("123456789") = 0x31C3
+    assert_eq!(key_slot(b"123456789"), 0x31C3 % CLUSTER_SLOTS);
+    assert_eq!(key_slot(b"foo"), 12182);
+}
+
+#[test]
+fn test_key_slot_hash_tags() {
+    assert_eq!(key_slot(b"{user:1}:profile"), key_slot(b"user:1"));
+    assert_eq!(key_slot(b"{user:1}:profile"), key_slot(b"{user:1}:orders"));
+    // Empty tag hashes the whole key
+    assert_ne!(key_slot(b"{}foo"), key_slot(b"foo"));
+}
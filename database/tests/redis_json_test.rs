@@ -0,0 +1,69 @@
+//! # redis_json_test
+//!
+//! redis_json_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Redis JSON helper and compression tests
+
+#[cfg(test)]
+mod tests {
+    use rf_database::redis::RedisClient;
+    use rf_errors::RfError;
+    use rf_test::redis::FakeRedis;
+    use serde_json::{json, Value};
+
+    const PREFIX: &[u8] = b"\0rfgz:";
+
+    async fn client(redis: &FakeRedis) -> RedisClient {
+        RedisClient::new(redis.url()).await.unwrap().with_json_compression(64)
+    }
+
+    #[tokio::test]
+    async fn test_below_threshold_is_plain_json() {
+        let redis = FakeRedis::start().await;
+        let client = client(&redis).await;
+        let value = json!({"id": 1, "name": "alice"});
+        client.string().set_json("user:1", &value).await.unwrap();
+
+        assert_eq!(redis.get("user:1").unwrap(), serde_json::to_vec(&value).unwrap());
+        assert_eq!(client.string().get_json::<Value>("user:1").await.unwrap(), Some(value));
+    }
+
+    #[tokio::test]
+    async fn test_above_threshold_is_compressed() {
+        let redis = FakeRedis::start().await;
+        let client = client(&redis).await;
+        let value = json!({"tags": vec!["repeated"; 100]});
+        client.string().set_json("user:1", &value).await.unwrap();
+
+        let stored = redis.get("user:1").unwrap();
+        assert!(stored.starts_with(PREFIX));
+        assert!(stored.len() < serde_json::to_vec(&value).unwrap().len());
+        assert_eq!(client.string().get_json::<Value>("user:1").await.unwrap(), Some(value.clone()));
+
+        client.hash().hset_json("users", "1", &value).await.unwrap();
+        assert_eq!(client.hash().hget_json::<Value>("users", "1").await.unwrap(), Some(value));
+    }
+
+    #[tokio::test]
+    async fn test_plain_value_starting_with_gzip_magic() {
+        let redis = FakeRedis::start().await;
+        let client = client(&redis).await;
+        // A value from another writer that starts with the gzip magic bytes is read as JSON, not sniffed as gzip
+        let mut gzip_like = vec![0x1f, 0x8b];
+        gzip_like.extend_from_slice(b"[1]");
+        redis.set("foreign", &gzip_like);
+        let result = client.string().get_json::<Value>("foreign").await;
+        assert!(matches!(&result, Err(RfError::Serialization(m)) if m.starts_with("Failed to deserialize JSON")), "{:?}", result);
+
+        // Strings holding those bytes round-trip whether compressed or not
+        let small = json!("\u{1f}\u{8b}");
+        let large = json!("\u{1f}\u{8b}".repeat(100));
+        for value in [small, large] {
+            client.string().set_json("magic", &value).await.unwrap();
+            assert_eq!(client.string().get_json::<Value>("magic").await.unwrap(), Some(value));
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! # redis_stream_test
+//!
+//! redis_stream_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Redis stream consumer group tests
+
+#[cfg(test)]
+mod tests {
+    use rf_database::redis::RedisClient;
+    use rf_errors::RfError;
+    use rf_test::redis::FakeRedis;
+    use std::time::Duration;
+
+    async fn client() -> RedisClient {
+        RedisClient::new(FakeRedis::start().await.url()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_consumer_group_read_and_ack() {
+        let client = client().await;
+        let stream = client.stream();
+        stream.xgroup_create("orders", "workers", "$", true).await.unwrap();
+        let first = stream.xadd("orders", "*", &[("id", "1001"), ("amount", "99")]).await.unwrap();
+        let second = stream.xadd("orders", "*", &[("id", "1002")]).await.unwrap();
+        assert_eq!(stream.xlen("orders").await.unwrap(), 2);
+
+        let reads = stream.xreadgroup("workers", "worker-1", &["orders"], &[">"], Some(1), None).await.unwrap();
+        assert_eq!(reads.len(), 1);
+        assert_eq!(reads[0].key, "orders");
+        assert_eq!(reads[0].entries.len(), 1);
+        assert_eq!(reads[0].entries[0].id, first);
+        assert_eq!(reads[0].entries[0].get("amount"), Some("99"));
+
+        let reads = stream.xreadgroup("workers", "worker-2", &["orders"], &[">"], None, None).await.unwrap();
+        assert_eq!(reads[0].entries[0].id, second);
+        // Everything was delivered once
+        assert!(stream.xreadgroup("workers", "worker-1", &["orders"], &[">"], None, None).await.unwrap().is_empty());
+
+        // `0` re-reads the consumer's own unacknowledged entries
+        let reads = stream.xreadgroup("workers", "worker-1", &["orders"], &["0"], None, None).await.unwrap();
+        assert_eq!(reads[0].entries.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), [first.as_str()]);
+
+        assert_eq!(stream.xack("orders", "workers", &[first.as_str()]).await.unwrap(), 1);
+        assert_eq!(stream.xack("orders", "workers", &[first.as_str()]).await.unwrap(), 0);
+        let reads = stream.xreadgroup("workers", "worker-1", &["orders"], &["0"], None, None).await.unwrap();
+        assert!(reads[0].entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pending_list() {
+        let client = client().await;
+        let stream = client.stream();
+        stream.xgroup_create("orders", "workers", "0", true).await.unwrap();
+        assert_eq!(stream.xpending("orders", "workers").await.unwrap(), Default::default());
+
+        let mut added = Vec::new();
+        for id in ["1", "2", "3"] {
+            added.push(stream.xadd("orders", "*", &[("id", id)]).await.unwrap());
+        }
+        stream.xreadgroup("workers", "worker-1", &["orders"], &[">"], Some(2), None).await.unwrap();
+        stream.xreadgroup("workers", "worker-2", &["orders"], &[">"], None, None).await.unwrap();
+
+        let summary = stream.xpending("orders", "workers").await.unwrap();
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.min_id.as_deref(), Some(added[0].as_str()));
+        assert_eq!(summary.max_id.as_deref(), Some(added[2].as_str()));
+        assert_eq!(summary.consumers, vec![("worker-1".to_string(), 2), ("worker-2".to_string(), 1)]);
+
+        let pending = stream.xpending_range("orders", "workers", "-", "+", 10, Some("worker-1")).await.unwrap();
+        assert_eq!(pending.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(), [added[0].as_str(), added[1].as_str()]);
+        assert!(pending.iter().all(|p| p.consumer == "worker-1" && p.delivery_count == 1));
+        assert_eq!(stream.xpending_range("orders", "workers", "-", "+", 1, None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_claim_idle_entries() {
+        let client = client().await;
+        let stream = client.stream();
+        stream.xgroup_create("orders", "workers", "0", true).await.unwrap();
+        for id in ["1", "2", "3"] {
+            stream.xadd("orders", "*", &[("id", id)]).await.unwrap();
+        }
+        stream.xreadgroup("workers", "worker-1", &["orders"], &[">"], None, None).await.unwrap();
+
+        // Nothing has been idle long enough yet
+        let claimed = stream.xautoclaim("orders", "workers", "worker-2", 60_000, "0-0", 10).await.unwrap();
+        assert!(claimed.entries.is_empty());
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let claimed = stream.xautoclaim("orders", "workers", "worker-2", 20, "0-0", 2).await.unwrap();
+        assert_eq!(claimed.entries.iter().map(|e| e.get("id")).collect::<Vec<_>>(), [Some("1"), Some("2")]);
+        assert_ne!(claimed.next_id, "0-0");
+        let rest = stream.xautoclaim("orders", "workers", "worker-2", 20, &claimed.next_id, 2).await.unwrap();
+        assert_eq!(rest.entries.len(), 1);
+        assert_eq!(rest.next_id, "0-0");
+
+        let pending = stream.xpending_range("orders", "workers", "-", "+", 10, None).await.unwrap();
+        assert!(pending.iter().all(|p| p.consumer == "worker-2" && p.delivery_count == 2));
+
+        // XCLAIM moves one back by id
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let ids: Vec<&str> = pending.iter().map(|p| p.id.as_str()).take(1).collect();
+        let claimed = stream.xclaim("orders", "workers", "worker-1", 20, &ids).await.unwrap();
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(stream.xpending("orders", "workers").await.unwrap().consumers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_non_utf8_field_is_an_error() {
+        let client = client().await;
+        let mut cmd = redis::cmd("XADD");
+        cmd.arg("raw").arg("*").arg("text").arg("ok").arg("blob").arg(&[0xffu8, 0xfe][..]);
+        client.query::<String>(&cmd).await.unwrap();
+
+        let result = client.stream().xrange("raw", "-", "+", None).await;
+        assert!(matches!(&result, Err(RfError::Serialization(m)) if m.contains("field blob")), "{:?}", result);
+    }
+}
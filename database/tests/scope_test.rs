@@ -0,0 +1,86 @@
+//! # scope_test
+//!
+//! scope_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Query scope tests
+
+#[cfg(test)]
+mod tests {
+    use rf_database::db::Database;
+    use tempfile::TempDir;
+
+    async fn posts_db(dir: &TempDir) -> Database {
+        let db = Database::new_sqlite(&format!("sqlite://{}?mode=rwc", dir.path().join("scope.db").display()))
+            .await
+            .unwrap();
+        db.raw_execute(
+            "CREATE TABLE posts (id INTEGER PRIMARY KEY, tenant_id INTEGER, status TEXT, deleted_at TEXT)",
+        )
+        .await
+        .unwrap();
+        db.raw_execute(
+            "INSERT INTO posts (tenant_id, status, deleted_at) VALUES \
+             (1, 'published', NULL), (1, 'draft', NULL), (1, 'published', '2026-01-01'), \
+             (2, 'published', NULL), (2, 'draft', NULL)",
+        )
+        .await
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = rf_database::db::ScopeRegistry::new();
+        registry.register_global("posts", "tenant", |q| q.and_where("tenant_id = 1"));
+        registry.register_global("posts", "visible", |q| q.and_where("hidden = 0"));
+        registry.register_global("posts", "tenant", |q| q.and_where("tenant_id = 2"));
+        let names: Vec<String> = registry.global("posts").into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["tenant", "visible"]);
+        assert!(registry.global("users").is_empty());
+        assert!(registry.remove("posts", "visible"));
+        assert!(!registry.remove("posts", "visible"));
+        assert!(registry.named("posts", "tenant").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_named_and_global_scopes() {
+        let dir = TempDir::new().unwrap();
+        let db = posts_db(&dir).await;
+        db.register_scope("posts", "published", |q| q.and_where("status = 'published'"));
+        db.register_global_scope("posts", "tenant", |q| q.and_where("tenant_id = 1"));
+
+        assert_eq!(
+            db.model("posts").scope("published").to_sql(),
+            "SELECT * FROM posts WHERE status = 'published' AND tenant_id = 1 AND deleted_at IS NULL"
+        );
+        assert_eq!(db.model("posts").count().await.unwrap(), 2);
+        assert_eq!(db.model("posts").scope("published").count().await.unwrap(), 1);
+        assert_eq!(db.model("posts").scope("published").with_deleted().count().await.unwrap(), 2);
+        assert_eq!(db.model("posts").without_scope("tenant").count().await.unwrap(), 4);
+        assert_eq!(db.model("posts").unscoped().count().await.unwrap(), 5);
+
+        // OR conditions stay inside the tenant
+        let either = db.model("posts").r#where("status = 'draft'").or_where("status = 'published'");
+        assert_eq!(either.count().await.unwrap(), 2);
+
+        // Writes are scoped too (soft deleted rows included, as before)
+        assert_eq!(db.model("posts").update("status = 'archived'").await.unwrap(), 3);
+        assert_eq!(db.model("posts").unscoped().r#where("status = 'archived'").count().await.unwrap(), 3);
+
+        // Scopes are shared by clones of the database and can be removed
+        let clone = db.clone();
+        assert!(clone.remove_scope("posts", "tenant"));
+        assert_eq!(db.model("posts").count().await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_scope_matches_nothing() {
+        let dir = TempDir::new().unwrap();
+        let db = posts_db(&dir).await;
+        assert_eq!(db.model("posts").scope("pubished").count().await.unwrap(), 0);
+        assert_eq!(db.model("posts").scope("pubished").unscoped().update("status = 'x'").await.unwrap(), 0);
+    }
+}
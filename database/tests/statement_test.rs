@@ -0,0 +1,74 @@
+//! # statement_test
+//!
+//! statement_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Prepared statement tests
+
+#[cfg(test)]
+mod tests {
+    use rf_database::db::{Database, StatementCache, StatementInfo};
+    use rf_errors::RfError;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn info(sql: &str) -> Arc<StatementInfo> {
+        Arc::new(StatementInfo { sql: sql.to_string(), params: None, columns: Vec::new() })
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = StatementCache::new(2);
+        cache.insert(info("a"));
+        cache.insert(info("b"));
+        assert!(cache.get("a").is_some());
+        // "b" is now the least recently used
+        cache.insert(info("c"));
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some() && cache.get("c").is_some());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions, stats.size), (3, 1, 1, 2));
+        assert_eq!(stats.hit_rate(), 0.75);
+        cache.clear();
+        assert_eq!(cache.stats().size, 0);
+    }
+
+    #[tokio::test]
+    async fn test_prepare_and_execute() {
+        let dir = std::env::temp_dir().join(format!("rf_statement_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = Database::new_sqlite(&format!("sqlite://{}?mode=rwc", dir.join("statement.db").display()))
+            .await
+            .unwrap()
+            .with_statement_cache_capacity(8);
+        db.raw_execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, active BOOLEAN)").await.unwrap();
+
+        let insert = db.prepare("INSERT INTO users (id, name, active) VALUES (?, ?, ?)").await.unwrap();
+        assert_eq!(insert.params(), Some(3));
+        for (id, name) in [(1, "ann"), (2, "bob")] {
+            assert_eq!(insert.execute(&[id.into(), name.into(), true.into()]).await.unwrap(), 1);
+        }
+        let result = insert.execute(&[3.into()]).await;
+        assert!(matches!(result, Err(RfError::InvalidParameter(_))));
+
+        let select = db.prepare("SELECT id, name FROM users WHERE id >= ? ORDER BY id").await.unwrap();
+        assert_eq!(select.columns(), ["id", "name"]);
+        let rows = select.fetch_all(&[1.into()]).await.unwrap();
+        assert_eq!(serde_json::Value::from(rows[1].clone()), json!({ "id": 2, "name": "bob" }));
+        assert_eq!(select.fetch_one(&[5.into()]).await.unwrap(), None);
+
+        // The same SQL is served from the cache shared by clones
+        let again = db.clone().prepare("SELECT id, name FROM users WHERE id >= ? ORDER BY id").await.unwrap();
+        assert_eq!(again.fetch_all(&[2.into()]).await.unwrap().len(), 1);
+        let stats = db.statement_cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.size, stats.capacity), (1, 2, 2, 8));
+
+        // Syntax errors surface at prepare time and are not cached
+        assert!(matches!(db.prepare("SELEC id FROM users").await, Err(RfError::Database(_))));
+        assert_eq!(db.statement_cache_stats().size, 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
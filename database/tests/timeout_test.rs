@@ -0,0 +1,87 @@
+//! # timeout_test
+//!
+//! timeout_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Query timeout tests
+
+#[cfg(test)]
+mod tests {
+    use rf_core::ctx;
+    use rf_database::db::{timeout, Database};
+    use rf_errors::RfError;
+    use std::time::{Duration, Instant};
+
+    // Counts to ten million through a recursive CTE; takes well over a second
+    const SLOW: &str = "(WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 10000000) SELECT COUNT(*) FROM c) > 0";
+
+    async fn database(name: &str) -> (Database, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("rf_timeout_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = Database::new_sqlite(&format!("sqlite://{}?mode=rwc", dir.join("timeout.db").display())).await.unwrap();
+        db.raw_execute("CREATE TABLE jobs (id INTEGER PRIMARY KEY, name TEXT)").await.unwrap();
+        db.raw_execute("INSERT INTO jobs (name) VALUES ('a'), ('b')").await.unwrap();
+        (db, dir)
+    }
+
+    #[test]
+    fn test_budget_and_mysql_hint() {
+        assert_eq!(timeout::budget(None), None);
+        assert_eq!(timeout::budget(Some(Duration::from_secs(2))), Some(Duration::from_secs(2)));
+        assert_eq!(
+            timeout::mysql_hint("select * FROM jobs", Some(Duration::from_millis(1500))),
+            "SELECT /*+ MAX_EXECUTION_TIME(1500) */ * FROM jobs"
+        );
+        assert_eq!(timeout::mysql_hint("UPDATE jobs SET name = 'x'", Some(Duration::from_secs(1))), "UPDATE jobs SET name = 'x'");
+        assert_eq!(timeout::mysql_hint("SELECT 1", None), "SELECT 1");
+    }
+
+    #[tokio::test]
+    async fn test_deadline_limits_queries() {
+        let (db, dir) = database("deadline").await;
+
+        // Fast queries fit in the budget
+        let count = ctx::with_timeout(Duration::from_secs(5), async {
+            assert!(timeout::budget(Some(Duration::from_secs(30))).unwrap() <= Duration::from_secs(5));
+            db.model("jobs").unscoped().count().await
+        }).await.unwrap();
+        assert_eq!(count, 2);
+
+        // The request deadline cuts a slow query short
+        let started = Instant::now();
+        let result = ctx::with_timeout(Duration::from_millis(100), db.model("jobs").unscoped().r#where(SLOW).count()).await;
+        assert!(matches!(result, Err(RfError::Timeout(_))), "{:?}", result);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // An expired deadline fails before touching the database
+        let result = ctx::with_timeout(Duration::ZERO, db.model("jobs").unscoped().update("name = 'x'")).await;
+        assert!(matches!(result, Err(RfError::Timeout(_))));
+        assert_eq!(db.model("jobs").unscoped().r#where("name = 'x'").count().await.unwrap(), 0);
+
+        // Nested scopes cannot extend the outer deadline
+        ctx::with_timeout(Duration::from_millis(50), ctx::with_timeout(Duration::from_secs(60), async {
+            assert!(ctx::remaining().unwrap() <= Duration::from_millis(50));
+        })).await;
+        assert!(ctx::deadline().is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_model_and_database_timeouts() {
+        let (db, dir) = database("explicit").await;
+
+        let result = db.model("jobs").unscoped().r#where(SLOW).timeout(Duration::from_millis(100)).count().await;
+        assert!(matches!(result, Err(RfError::Timeout(_))));
+
+        let db = db.with_query_timeout(Duration::from_millis(100));
+        assert_eq!(db.query_timeout(), Some(Duration::from_millis(100)));
+        let result = db.model("jobs").unscoped().r#where(SLOW).count().await;
+        assert!(matches!(result, Err(RfError::Timeout(_))));
+
+        // A per-model timeout overrides the database default
+        assert_eq!(db.model("jobs").unscoped().timeout(Duration::from_secs(5)).count().await.unwrap(), 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
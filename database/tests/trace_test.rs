@@ -0,0 +1,131 @@
+//! # trace_test
+//!
+//! trace_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Query and Redis command span tests
+
+#[cfg(test)]
+mod tests {
+    use rf_database::db::trace::{operation_of, sanitize_sql};
+    use rf_database::db::Database;
+    use rf_database::redis::RedisClient;
+    use std::collections::HashMap;
+    use rf_test::redis::FakeRedis;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::Layer;
+
+    type Spans = Arc<Mutex<Vec<(Id, HashMap<String, String>)>>>;
+
+    /// Records the fields of every span, including those recorded later
+    #[derive(Clone, Default)]
+    struct Capture(Spans);
+
+    struct Fields<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for Capture {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _: Context<'_, S>) {
+            let mut fields = HashMap::new();
+            attrs.record(&mut Fields(&mut fields));
+            self.0.lock().unwrap().push((id.clone(), fields));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, _: Context<'_, S>) {
+            if let Some((_, fields)) = self.0.lock().unwrap().iter_mut().find(|(span, _)| span == id) {
+                values.record(&mut Fields(fields));
+            }
+        }
+    }
+
+    impl Capture {
+        fn named(&self, name: &str) -> Vec<HashMap<String, String>> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(_, fields)| fields.clone())
+                .filter(|fields| fields.get("otel.name").map(String::as_str) == Some(name))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_sanitize_sql() {
+        assert_eq!(
+            sanitize_sql("SELECT * FROM users WHERE name = 'O''Brien' AND  age >= 42.5 LIMIT 10"),
+            "SELECT * FROM users WHERE name = ? AND age >= ? LIMIT ?"
+        );
+        assert_eq!(
+            sanitize_sql("UPDATE t1 SET \"col 2\" = $1, note = 'x' WHERE id IN (1, 2)"),
+            "UPDATE t1 SET \"col 2\" = $1, note = ? WHERE id IN (?, ?)"
+        );
+        assert_eq!(operation_of("  select 1"), "SELECT");
+        let long = format!("SELECT {}", "a, ".repeat(2000));
+        assert!(sanitize_sql(&long).ends_with("..."));
+    }
+
+    #[tokio::test]
+    async fn test_model_spans() {
+        let capture = Capture::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new_sqlite(&format!("sqlite://{}?mode=rwc", dir.path().join("trace.db").display()))
+            .await
+            .unwrap();
+        db.raw_execute("CREATE TABLE jobs (id INTEGER PRIMARY KEY, name TEXT)").await.unwrap();
+        let jobs = db.model("jobs").unscoped();
+        jobs.raw_execute("INSERT INTO jobs (name) VALUES ('secret')").await.unwrap();
+        assert_eq!(jobs.count().await.unwrap(), 1);
+        db.model("jobs").unscoped().and_where("name = 'secret'").update("name = 'public'").await.unwrap();
+        assert!(db.model("missing").unscoped().count().await.is_err());
+
+        let insert = &capture.named("INSERT jobs")[0];
+        assert_eq!(insert["db.system.name"], "sqlite");
+        assert_eq!(insert["db.collection.name"], "jobs");
+        assert_eq!(insert["db.query.text"], "INSERT INTO jobs (name) VALUES (?)");
+        assert_eq!(insert["otel.kind"], "client");
+        let count = &capture.named("SELECT jobs")[0];
+        assert_eq!(count["db.operation.name"], "SELECT");
+        assert_eq!(count["db.query.text"], "SELECT COUNT(*) FROM jobs");
+        let update = &capture.named("UPDATE jobs")[0];
+        assert_eq!(update["db.query.text"], "UPDATE jobs SET name = ? WHERE name = ?");
+        assert!(!update.contains_key("otel.status_code"));
+
+        let failed = &capture.named("SELECT missing")[0];
+        assert_eq!(failed["otel.status_code"], "error");
+        assert!(failed["otel.status_description"].contains("no such table"), "{:?}", failed);
+    }
+
+    #[tokio::test]
+    async fn test_redis_spans() {
+        let redis = FakeRedis::start().await;
+        let client = RedisClient::new(redis.url()).await.unwrap();
+        let capture = Capture::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+        client.set("user:1", "secret").await.unwrap();
+        assert_eq!(client.get("user:1").await.unwrap(), "secret");
+
+        let set = &capture.named("SET")[0];
+        assert_eq!(set["db.system.name"], "redis");
+        assert_eq!(set["db.operation.name"], "SET");
+        assert_eq!(set["db.query.text"], "SET user:1 ?");
+        assert_eq!(capture.named("GET")[0]["db.query.text"], "GET user:1");
+    }
+}
@@ -0,0 +1,81 @@
+//! # transaction_test
+//!
+//! transaction_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Transaction deferred side effect tests
+
+#[cfg(test)]
+mod tests {
+    use rf_database::db::{Database, QueryCache};
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    async fn orders_db(dir: &TempDir) -> Database {
+        let db = Database::new_sqlite(&format!("sqlite://{}?mode=rwc", dir.path().join("tx.db").display()))
+            .await
+            .unwrap();
+        db.raw_execute("CREATE TABLE orders (id INTEGER PRIMARY KEY, total REAL, deleted_at TEXT)").await.unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_effects_run_after_commit() {
+        let dir = TempDir::new().unwrap();
+        let db = orders_db(&dir).await;
+        let cache = Arc::new(QueryCache::with_defaults());
+        cache.set("SELECT * FROM orders", b"[]".to_vec()).await;
+        let events = Arc::new(Mutex::new(Vec::new()));
+
+        let mut tx = db.begin().await.unwrap();
+        let orders = db.model("orders").cache(cache.clone(), Duration::from_secs(60));
+        orders.insert_tx(&mut tx, &json!({"id": 1, "total": 9.5})).await.unwrap();
+        orders.insert_tx(&mut tx, &json!({"id": 2, "total": 3.0})).await.unwrap();
+        let published = events.clone();
+        tx.after_commit(async move { published.lock().unwrap().push("order.created") });
+        assert_eq!(tx.pending_tasks(), 1);
+
+        // Nothing happens before the commit
+        assert!(cache.get("SELECT * FROM orders").await.is_some());
+        assert!(events.lock().unwrap().is_empty());
+
+        tx.commit().await.unwrap();
+        assert!(cache.get("SELECT * FROM orders").await.is_none());
+        assert_eq!(*events.lock().unwrap(), ["order.created"]);
+    }
+
+    #[tokio::test]
+    async fn test_effects_dropped_on_rollback() {
+        let dir = TempDir::new().unwrap();
+        let db = orders_db(&dir).await;
+        let cache = Arc::new(QueryCache::with_defaults());
+        cache.set("SELECT * FROM orders", b"[]".to_vec()).await;
+        let events = Arc::new(Mutex::new(Vec::new()));
+
+        let mut tx = db.begin().await.unwrap();
+        let orders = db.model("orders").cache(cache.clone(), Duration::from_secs(60));
+        orders.insert_tx(&mut tx, &json!({"id": 1, "total": 9.5})).await.unwrap();
+        let published = events.clone();
+        tx.after_commit(async move { published.lock().unwrap().push("order.created") });
+        let compensated = events.clone();
+        tx.after_rollback(async move { compensated.lock().unwrap().push("order.released") });
+        tx.rollback().await.unwrap();
+
+        assert!(cache.get("SELECT * FROM orders").await.is_some());
+        assert_eq!(*events.lock().unwrap(), ["order.released"]);
+        assert_eq!(db.model("orders").count().await.unwrap(), 0);
+
+        // Dropped without commit: rolled back, no task runs
+        let mut tx = db.begin().await.unwrap();
+        orders.insert_tx(&mut tx, &json!({"id": 2, "total": 3.0})).await.unwrap();
+        let published = events.clone();
+        tx.after_commit(async move { published.lock().unwrap().push("order.created") });
+        drop(tx);
+        assert!(cache.get("SELECT * FROM orders").await.is_some());
+        assert_eq!(events.lock().unwrap().len(), 1);
+    }
+}
@@ -76,3 +76,52 @@ pub fn decode(s: &str) -> Result<Vec<u8>> {
         .map_err(|e| RfError::Serialization(format!("Base64 decode error: {}", e)))
 }
 
+/// 将字节数据编码为 URL 安全的 Base64 字符串（无填充）
+///
+/// 使用 `-` 和 `_` 代替 `+` 和 `/`，并省略末尾的 `=`，适用于 URL 和 JWT。
+///
+/// # 参数
+///
+/// * `data` - 要编码的字节数据
+///
+/// # 返回值
+///
+/// 返回 URL 安全的 Base64 字符串
+///
+/// # 示例
+///
+/// ```rust
+/// use rf_encoding::base64_url_encode;
+///
+/// assert_eq!(base64_url_encode(&[0xfb, 0xff]), "-_8");
+/// ```
+pub fn encode_url(data: &[u8]) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(data)
+}
+
+/// 解码 URL 安全的 Base64 字符串（无填充）
+///
+/// # 参数
+///
+/// * `s` - 要解码的字符串
+///
+/// # 返回值
+///
+/// 返回解码后的字节数据
+///
+/// # 错误
+///
+/// 如果输入不是有效的 URL 安全 Base64 字符串，返回 `RfError::Serialization`
+///
+/// # 示例
+///
+/// ```rust
+/// use rf_encoding::base64_url_decode;
+///
+/// assert_eq!(base64_url_decode("-_8").unwrap(), vec![0xfb, 0xff]);
+/// ```
+pub fn decode_url(s: &str) -> Result<Vec<u8>> {
+    general_purpose::URL_SAFE_NO_PAD
+        .decode(s)
+        .map_err(|e| RfError::Serialization(format!("Base64 decode error: {}", e)))
+}
@@ -0,0 +1,579 @@
+//! # json_schema
+//!
+//! json_schema 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! # JSON Schema 校验
+//!
+//! 按 JSON Schema（2020-12 的常用子集）校验 JSON 值，并检查两个版本的 schema 是否兼容。
+//!
+//! 支持的关键字：`type`、`enum`、`const`、`properties`、`required`、`additionalProperties`、
+//! `minProperties`/`maxProperties`、`items`、`prefixItems`、`minItems`/`maxItems`、`uniqueItems`、
+//! `minLength`/`maxLength`、`pattern`、`format`（`date-time`、`date`、`email`、`uuid`）、
+//! `minimum`/`maximum`、`exclusiveMinimum`/`exclusiveMaximum`、`multipleOf`、
+//! `allOf`/`anyOf`/`oneOf`/`not`，以及指向同一文档的 `$ref`（如 `#/$defs/Item`）。
+//! 其他关键字被忽略。
+//!
+//! ## 使用示例
+//!
+//! ```rust
+//! use rf_encoding::json_schema::{Compatibility, JsonSchema};
+//! use serde_json::json;
+//!
+//! let v1 = JsonSchema::new(json!({
+//!     "type": "object",
+//!     "properties": {"id": {"type": "integer"}, "email": {"type": "string", "format": "email"}},
+//!     "required": ["id"],
+//! })).unwrap();
+//! assert!(v1.validate(&json!({"id": 1, "email": "a@b.co"})).is_ok());
+//! let errors = v1.validate(&json!({"email": 3})).unwrap_err();
+//! assert_eq!(errors[0].to_string(), "/: missing required property 'id'");
+//!
+//! // 新版本新增必填字段，旧消息无法通过校验
+//! let v2 = JsonSchema::new(json!({
+//!     "type": "object",
+//!     "properties": {"id": {"type": "integer"}, "tenant": {"type": "string"}},
+//!     "required": ["id", "tenant"],
+//! })).unwrap();
+//! assert!(!v1.compatibility_issues(&v2, Compatibility::Backward).is_empty());
+//! ```
+
+use regex::Regex;
+use rf_errors::{Result, RfError};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// 校验失败的位置与原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// JSON Pointer，根为 `/`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Schema 演进的兼容性要求
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compatibility {
+    /// 不检查
+    None,
+    /// 新 schema 能接受按旧 schema 生成的数据（消费者先升级）
+    #[default]
+    Backward,
+    /// 旧 schema 能接受按新 schema 生成的数据（生产者先升级）
+    Forward,
+    /// 同时满足 Backward 和 Forward
+    Full,
+}
+
+/// 编译后的 JSON Schema
+#[derive(Debug, Clone)]
+pub struct JsonSchema {
+    root: Value,
+    patterns: HashMap<String, Regex>,
+}
+
+impl JsonSchema {
+    /// 编译 schema，`pattern` 不是合法正则或 schema 不是对象/布尔值时返回错误
+    pub fn new(root: Value) -> Result<Self> {
+        let mut patterns = HashMap::new();
+        collect_patterns(&root, &mut patterns)?;
+        if !root.is_object() && !root.is_boolean() {
+            return Err(RfError::InvalidParameter("JSON Schema must be an object or a boolean".to_string()));
+        }
+        Ok(Self { root, patterns })
+    }
+
+    /// 原始 schema
+    pub fn as_value(&self) -> &Value {
+        &self.root
+    }
+
+    /// 校验 `instance`，返回全部错误
+    pub fn validate(&self, instance: &Value) -> std::result::Result<(), Vec<SchemaError>> {
+        let mut errors = Vec::new();
+        self.check(&self.root, instance, &mut String::new(), &mut errors, 0);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// 是否通过校验
+    pub fn is_valid(&self, instance: &Value) -> bool {
+        let mut errors = Vec::new();
+        self.check(&self.root, instance, &mut String::new(), &mut errors, 0);
+        errors.is_empty()
+    }
+
+    /// 以 `self` 为旧版本、`next` 为新版本检查兼容性，返回不兼容之处
+    pub fn compatibility_issues(&self, next: &JsonSchema, mode: Compatibility) -> Vec<String> {
+        let mut issues = Vec::new();
+        if matches!(mode, Compatibility::Backward | Compatibility::Full) {
+            // 新 schema 读取旧数据
+            readable(self, &self.root, next, &next.root, "", &mut issues, 0);
+        }
+        if matches!(mode, Compatibility::Forward | Compatibility::Full) {
+            // 旧 schema 读取新数据
+            readable(next, &next.root, self, &self.root, "", &mut issues, 0);
+        }
+        issues.dedup();
+        issues
+    }
+
+    /// 解析 `$ref`
+    fn resolve<'a>(&'a self, schema: &'a Value) -> &'a Value {
+        let mut schema = schema;
+        // 限制跳转次数，避免循环引用
+        for _ in 0..32 {
+            match schema.get("$ref").and_then(Value::as_str).and_then(|r| r.strip_prefix('#')) {
+                Some(pointer) => match self.root.pointer(pointer) {
+                    Some(target) => schema = target,
+                    None => return &Value::Bool(false),
+                },
+                None => return schema,
+            }
+        }
+        schema
+    }
+
+    fn check(&self, schema: &Value, instance: &Value, path: &mut String, errors: &mut Vec<SchemaError>, depth: usize) {
+        let mut fail = |path: &str, message: String| {
+            errors.push(SchemaError {
+                path: if path.is_empty() { "/".to_string() } else { path.to_string() },
+                message,
+            })
+        };
+        if depth > 64 {
+            fail(path, "schema nesting too deep".to_string());
+            return;
+        }
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            if reference.strip_prefix('#').and_then(|p| self.root.pointer(p)).is_none() {
+                fail(path, format!("unresolvable $ref '{}'", reference));
+                return;
+            }
+        }
+        let schema = self.resolve(schema);
+        let object = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return fail(path, "no value is allowed here".to_string()),
+            Value::Object(object) => object,
+            _ => return,
+        };
+
+        if let Some(expected) = object.get("type") {
+            let types: Vec<&str> = match expected {
+                Value::String(ty) => vec![ty.as_str()],
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !types.is_empty() && !types.iter().any(|ty| type_matches(ty, instance)) {
+                return fail(path, format!("expected {}, got {}", types.join(" or "), type_name(instance)));
+            }
+        }
+        if let Some(values) = object.get("enum").and_then(Value::as_array) {
+            if !values.iter().any(|v| json_eq(v, instance)) {
+                fail(path, format!("value {} is not one of {}", instance, Value::Array(values.clone())));
+            }
+        }
+        if let Some(expected) = object.get("const") {
+            if !json_eq(expected, instance) {
+                fail(path, format!("value must be {}", expected));
+            }
+        }
+
+        match instance {
+            Value::String(s) => {
+                let len = s.chars().count() as u64;
+                if let Some(min) = object.get("minLength").and_then(Value::as_u64).filter(|min| len < *min) {
+                    fail(path, format!("string shorter than {} characters", min));
+                }
+                if let Some(max) = object.get("maxLength").and_then(Value::as_u64).filter(|max| len > *max) {
+                    fail(path, format!("string longer than {} characters", max));
+                }
+                if let Some(pattern) = object.get("pattern").and_then(Value::as_str) {
+                    if self.patterns.get(pattern).is_some_and(|re| !re.is_match(s)) {
+                        fail(path, format!("string does not match pattern '{}'", pattern));
+                    }
+                }
+                if let Some(format) = object.get("format").and_then(Value::as_str) {
+                    if !format_matches(format, s) {
+                        fail(path, format!("string is not a valid {}", format));
+                    }
+                }
+            }
+            Value::Number(n) => {
+                let n = n.as_f64().unwrap_or_default();
+                let bound = |key: &str| object.get(key).and_then(Value::as_f64);
+                if let Some(min) = bound("minimum").filter(|min| n < *min) {
+                    fail(path, format!("value below minimum {}", min));
+                }
+                if let Some(max) = bound("maximum").filter(|max| n > *max) {
+                    fail(path, format!("value above maximum {}", max));
+                }
+                if let Some(min) = bound("exclusiveMinimum").filter(|min| n <= *min) {
+                    fail(path, format!("value must be greater than {}", min));
+                }
+                if let Some(max) = bound("exclusiveMaximum").filter(|max| n >= *max) {
+                    fail(path, format!("value must be less than {}", max));
+                }
+                if let Some(step) = bound("multipleOf").filter(|step| *step > 0.0) {
+                    let ratio = n / step;
+                    if (ratio - ratio.round()).abs() > 1e-9 {
+                        fail(path, format!("value is not a multiple of {}", step));
+                    }
+                }
+            }
+            Value::Array(items) => {
+                let len = items.len() as u64;
+                if let Some(min) = object.get("minItems").and_then(Value::as_u64).filter(|min| len < *min) {
+                    fail(path, format!("array has fewer than {} items", min));
+                }
+                if let Some(max) = object.get("maxItems").and_then(Value::as_u64).filter(|max| len > *max) {
+                    fail(path, format!("array has more than {} items", max));
+                }
+                if object.get("uniqueItems") == Some(&Value::Bool(true)) {
+                    let duplicate = items.iter().enumerate().any(|(i, a)| items[..i].iter().any(|b| json_eq(a, b)));
+                    if duplicate {
+                        fail(path, "array items are not unique".to_string());
+                    }
+                }
+                let prefix = object.get("prefixItems").and_then(Value::as_array);
+                let prefix_len = prefix.map_or(0, Vec::len);
+                for (index, item) in items.iter().enumerate() {
+                    let item_schema = match prefix.and_then(|p| p.get(index)) {
+                        Some(schema) => Some(schema),
+                        None if index >= prefix_len => object.get("items"),
+                        None => None,
+                    };
+                    if let Some(item_schema) = item_schema {
+                        let len = path.len();
+                        path.push_str(&format!("/{}", index));
+                        self.check(item_schema, item, path, errors, depth + 1);
+                        path.truncate(len);
+                    }
+                }
+            }
+            Value::Object(fields) => self.check_object(object, fields, path, errors, depth),
+            _ => {}
+        }
+
+        for (keyword, combine) in [("allOf", 0), ("anyOf", 1), ("oneOf", 2)] {
+            let Some(branches) = object.get(keyword).and_then(Value::as_array) else {
+                continue;
+            };
+            let mut matched = 0;
+            let mut branch_errors = Vec::new();
+            for branch in branches {
+                let mut errs = Vec::new();
+                self.check(branch, instance, path, &mut errs, depth + 1);
+                if errs.is_empty() {
+                    matched += 1;
+                }
+                branch_errors.extend(errs);
+            }
+            match combine {
+                0 => errors.extend(branch_errors),
+                1 if matched == 0 => errors.push(SchemaError {
+                    path: if path.is_empty() { "/".to_string() } else { path.clone() },
+                    message: "value matches none of anyOf".to_string(),
+                }),
+                2 if matched != 1 => errors.push(SchemaError {
+                    path: if path.is_empty() { "/".to_string() } else { path.clone() },
+                    message: format!("value matches {} of oneOf, expected exactly 1", matched),
+                }),
+                _ => {}
+            }
+        }
+        if let Some(not) = object.get("not") {
+            let mut errs = Vec::new();
+            self.check(not, instance, path, &mut errs, depth + 1);
+            if errs.is_empty() {
+                errors.push(SchemaError {
+                    path: if path.is_empty() { "/".to_string() } else { path.clone() },
+                    message: "value must not match 'not' schema".to_string(),
+                });
+            }
+        }
+    }
+
+    fn check_object(
+        &self,
+        schema: &Map<String, Value>,
+        fields: &Map<String, Value>,
+        path: &mut String,
+        errors: &mut Vec<SchemaError>,
+        depth: usize,
+    ) {
+        let here = if path.is_empty() { "/".to_string() } else { path.clone() };
+        for name in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+            if !fields.contains_key(name) {
+                errors.push(SchemaError { path: here.clone(), message: format!("missing required property '{}'", name) });
+            }
+        }
+        let len = fields.len() as u64;
+        if let Some(min) = schema.get("minProperties").and_then(Value::as_u64).filter(|min| len < *min) {
+            errors.push(SchemaError { path: here.clone(), message: format!("object has fewer than {} properties", min) });
+        }
+        if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64).filter(|max| len > *max) {
+            errors.push(SchemaError { path: here.clone(), message: format!("object has more than {} properties", max) });
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, value) in fields {
+            let property = match properties.and_then(|p| p.get(name)) {
+                Some(property) => property,
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        errors.push(SchemaError { path: here.clone(), message: format!("unexpected property '{}'", name) });
+                        continue;
+                    }
+                    Some(additional) => additional,
+                    None => continue,
+                },
+            };
+            let len = path.len();
+            path.push('/');
+            path.push_str(&name.replace('~', "~0").replace('/', "~1"));
+            self.check(property, value, path, errors, depth + 1);
+            path.truncate(len);
+        }
+    }
+}
+
+/// 检查 `reader` 能否接受所有符合 `writer` 的数据
+fn readable(
+    writer_doc: &JsonSchema,
+    writer: &Value,
+    reader_doc: &JsonSchema,
+    reader: &Value,
+    path: &str,
+    issues: &mut Vec<String>,
+    depth: usize,
+) {
+    if depth > 32 {
+        return;
+    }
+    let writer = writer_doc.resolve(writer);
+    let reader = reader_doc.resolve(reader);
+    let at = if path.is_empty() { "/" } else { path };
+    let (writer, reader) = match (writer, reader) {
+        (_, Value::Bool(true)) | (Value::Bool(false), _) => return,
+        (_, Value::Bool(false)) => {
+            issues.push(format!("{}: values are no longer accepted", at));
+            return;
+        }
+        (Value::Bool(true), Value::Object(reader)) => (&Map::new(), reader),
+        (Value::Object(writer), Value::Object(reader)) => (writer, reader),
+        _ => return,
+    };
+
+    // 类型只能放宽
+    if let Some(reader_types) = types_of(reader) {
+        match types_of(writer) {
+            Some(writer_types) => {
+                for ty in writer_types {
+                    let covered = reader_types.contains(&ty) || (ty == "integer" && reader_types.contains(&"number"));
+                    if !covered {
+                        issues.push(format!("{}: type {} is no longer accepted", at, ty));
+                    }
+                }
+            }
+            None => issues.push(format!("{}: type restricted to {}", at, reader_types.join(" or "))),
+        }
+    }
+    if let Some(reader_enum) = reader.get("enum").and_then(Value::as_array) {
+        match writer.get("enum").and_then(Value::as_array) {
+            Some(writer_enum) => {
+                for value in writer_enum.iter().filter(|v| !reader_enum.iter().any(|r| json_eq(r, v))) {
+                    issues.push(format!("{}: enum value {} was removed", at, value));
+                }
+            }
+            None => issues.push(format!("{}: values restricted to an enum", at)),
+        }
+    }
+
+    // 取值范围只能放宽
+    for (keyword, lower) in [
+        ("minimum", true),
+        ("exclusiveMinimum", true),
+        ("minLength", true),
+        ("minItems", true),
+        ("minProperties", true),
+        ("maximum", false),
+        ("exclusiveMaximum", false),
+        ("maxLength", false),
+        ("maxItems", false),
+        ("maxProperties", false),
+    ] {
+        let Some(new) = reader.get(keyword).and_then(Value::as_f64) else {
+            continue;
+        };
+        let tightened = match writer.get(keyword).and_then(Value::as_f64) {
+            Some(old) => if lower { new > old } else { new < old },
+            None => true,
+        };
+        if tightened {
+            issues.push(format!("{}: {} tightened to {}", at, keyword, new));
+        }
+    }
+    if let Some(pattern) = reader.get("pattern").and_then(Value::as_str) {
+        if writer.get("pattern").and_then(Value::as_str) != Some(pattern) {
+            issues.push(format!("{}: pattern changed to '{}'", at, pattern));
+        }
+    }
+
+    // 对象：新增必填字段、关闭额外字段、字段收紧
+    let writer_required: Vec<&str> = required_of(writer);
+    let writer_props = writer.get("properties").and_then(Value::as_object);
+    let reader_props = reader.get("properties").and_then(Value::as_object);
+    for name in required_of(reader) {
+        let has_default = reader_props.and_then(|p| p.get(name)).is_some_and(|p| p.get("default").is_some());
+        if !writer_required.contains(&name) && !has_default {
+            issues.push(format!("{}: property '{}' became required", at, name));
+        }
+    }
+    let writer_closed = writer.get("additionalProperties") == Some(&Value::Bool(false));
+    if reader.get("additionalProperties") == Some(&Value::Bool(false)) {
+        if !writer_closed {
+            issues.push(format!("{}: additional properties are no longer accepted", at));
+        } else {
+            for name in writer_props.into_iter().flat_map(|p| p.keys()) {
+                if !reader_props.is_some_and(|p| p.contains_key(name)) {
+                    issues.push(format!("{}: property '{}' was removed", at, name));
+                }
+            }
+        }
+    }
+    if let Some(reader_props) = reader_props {
+        for (name, reader_prop) in reader_props {
+            let child = format!("{}/{}", path, name);
+            match writer_props.and_then(|p| p.get(name)) {
+                Some(writer_prop) => readable(writer_doc, writer_prop, reader_doc, reader_prop, &child, issues, depth + 1),
+                // 新增字段：旧 schema 显式约束了额外字段时按其检查，
+                // 未声明的额外字段视为旧数据中不存在（常规的新增可选字段）
+                None if !writer_closed => {
+                    if let Some(additional) = writer.get("additionalProperties") {
+                        readable(writer_doc, additional, reader_doc, reader_prop, &child, issues, depth + 1);
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+    if let (Some(writer_items), Some(reader_items)) = (writer.get("items"), reader.get("items")) {
+        readable(writer_doc, writer_items, reader_doc, reader_items, &format!("{}/items", path), issues, depth + 1);
+    }
+}
+
+fn types_of(schema: &Map<String, Value>) -> Option<Vec<&str>> {
+    match schema.get("type")? {
+        Value::String(ty) => Some(vec![ty.as_str()]),
+        Value::Array(types) => Some(types.iter().filter_map(Value::as_str).collect()),
+        _ => None,
+    }
+}
+
+fn required_of(schema: &Map<String, Value>) -> Vec<&str> {
+    schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str).collect()
+}
+
+fn collect_patterns(schema: &Value, patterns: &mut HashMap<String, Regex>) -> Result<()> {
+    match schema {
+        Value::Object(object) => {
+            if let Some(pattern) = object.get("pattern").and_then(Value::as_str) {
+                if !patterns.contains_key(pattern) {
+                    let regex = Regex::new(pattern)
+                        .map_err(|e| RfError::InvalidParameter(format!("Invalid pattern '{}': {}", pattern, e)))?;
+                    patterns.insert(pattern.to_string(), regex);
+                }
+            }
+            object.values().try_for_each(|v| collect_patterns(v, patterns))
+        }
+        Value::Array(items) => items.iter().try_for_each(|v| collect_patterns(v, patterns)),
+        _ => Ok(()),
+    }
+}
+
+fn type_matches(ty: &str, instance: &Value) -> bool {
+    match ty {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => instance.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "array" => instance.is_array(),
+        "object" => instance.is_object(),
+        _ => true,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// JSON 相等，`1` 与 `1.0` 视为相等
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        (Value::Array(x), Value::Array(y)) => x.len() == y.len() && x.iter().zip(y).all(|(x, y)| json_eq(x, y)),
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| json_eq(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
+fn format_matches(format: &str, s: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let date = |s: &str| {
+        let parts: Vec<&str> = s.split('-').collect();
+        parts.len() == 3
+            && parts[0].len() == 4
+            && parts[1].len() == 2
+            && parts[2].len() == 2
+            && parts.iter().all(|p| digits(p))
+            && (1..=12).contains(&parts[1].parse::<u32>().unwrap_or(0))
+            && (1..=31).contains(&parts[2].parse::<u32>().unwrap_or(0))
+    };
+    match format {
+        "date" => date(s),
+        "date-time" => {
+            let Some((day, time)) = s.split_once(['T', 't', ' ']) else {
+                return false;
+            };
+            let time = time.trim_end_matches(['Z', 'z']);
+            let clock = time.split(['+', '-']).next().unwrap_or("");
+            let fields: Vec<&str> = clock.split(':').collect();
+            date(day)
+                && fields.len() == 3
+                && fields[..2].iter().all(|f| f.len() == 2 && digits(f))
+                && fields[2].split('.').all(digits)
+        }
+        "email" => s
+            .split_once('@')
+            .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.') && !s.contains(char::is_whitespace)),
+        "uuid" => {
+            let groups: Vec<&str> = s.split('-').collect();
+            groups.iter().map(|g| g.len()).eq([8, 4, 4, 4, 12]) && groups.iter().all(|g| g.bytes().all(|b| b.is_ascii_hexdigit()))
+        }
+        _ => true,
+    }
+}
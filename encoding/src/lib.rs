@@ -12,6 +12,7 @@
 //! ## 支持的格式
 //!
 //! - **数据序列化格式**: JSON, YAML, TOML, XML
+//! - **数据校验**: JSON Schema 校验与版本兼容性检查
 //! - **配置文件格式**: INI, Properties
 //! - **编码格式**: Base64, Binary, Charset
 //! - **压缩格式**: Gzip, Zlib
@@ -37,6 +38,7 @@
 //! @date 2026-01-06
 
 pub mod json;
+pub mod json_schema;
 pub mod yaml;
 pub mod toml;
 pub mod xml;
@@ -57,7 +59,7 @@ pub use toml::{encode as toml_encode, encode_pretty as toml_encode_pretty, decod
 pub use xml::{encode as xml_encode, decode as xml_decode};
 pub use ini::{parse as ini_parse, encode as ini_encode};
 pub use properties::{parse as properties_parse, encode as properties_encode};
-pub use base64::{encode as base64_encode, decode as base64_decode};
+pub use base64::{encode as base64_encode, decode as base64_decode, encode_url as base64_url_encode, decode_url as base64_url_decode};
 pub use binary::*;
 pub use charset::*;
 pub use compress::*;
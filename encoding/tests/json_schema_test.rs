@@ -0,0 +1,126 @@
+//! JSON Schema tests
+
+use rf_encoding::json_schema::{Compatibility, JsonSchema};
+use serde_json::json;
+
+fn errors(schema: &JsonSchema, value: serde_json::Value) -> Vec<String> {
+    schema.validate(&value).err().unwrap_or_default().iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn test_json_schema_validation() {
+    let schema = JsonSchema::new(json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "integer", "minimum": 1},
+            "sku": {"type": "string", "pattern": "^[A-Z]{3}-\\d+$"},
+            "status": {"$ref": "#/$defs/Status"},
+            "placed_at": {"type": "string", "format": "date-time"},
+            "lines": {
+                "type": "array",
+                "minItems": 1,
+                "items": {
+                    "type": "object",
+                    "properties": {"qty": {"type": "integer", "exclusiveMinimum": 0}, "price": {"type": "number", "multipleOf": 0.01}},
+                    "required": ["qty"],
+                    "additionalProperties": false,
+                },
+            },
+            "note": {"type": ["string", "null"], "maxLength": 5},
+            "contact": {"oneOf": [{"type": "string", "format": "email"}, {"type": "string", "format": "uuid"}]},
+        },
+        "required": ["id", "status"],
+        "$defs": {"Status": {"enum": ["new", "paid"]}},
+    }))
+    .unwrap();
+
+    assert!(schema.is_valid(&json!({
+        "id": 7,
+        "sku": "ABC-12",
+        "status": "paid",
+        "placed_at": "2024-05-01T10:20:30.5+08:00",
+        "lines": [{"qty": 2, "price": 9.99}],
+        "note": null,
+        "contact": "ann@example.com",
+        "extra": true,
+    })));
+    assert_eq!(errors(&schema, json!({"id": 0, "status": "lost"})), [
+        "/id: value below minimum 1",
+        "/status: value \"lost\" is not one of [\"new\",\"paid\"]",
+    ]);
+    assert_eq!(errors(&schema, json!({"status": "new", "sku": "abc", "note": "too long", "placed_at": "yesterday"})), [
+        "/: missing required property 'id'",
+        "/note: string longer than 5 characters",
+        "/placed_at: string is not a valid date-time",
+        "/sku: string does not match pattern '^[A-Z]{3}-\\d+$'",
+    ]);
+    assert_eq!(errors(&schema, json!({"id": 1.5, "status": "new", "lines": [{"qty": 0, "price": 1.005, "sku": 1}]})), [
+        "/id: expected integer, got number",
+        "/lines/0/price: value is not a multiple of 0.01",
+        "/lines/0/qty: value must be greater than 0",
+        "/lines/0: unexpected property 'sku'",
+    ]);
+    assert_eq!(errors(&schema, json!({"id": 1, "status": "new", "lines": [], "contact": "nobody"})), [
+        "/contact: value matches 0 of oneOf, expected exactly 1",
+        "/lines: array has fewer than 1 items",
+    ]);
+    assert_eq!(errors(&schema, json!([1])), ["/: expected object, got array"]);
+
+    assert!(JsonSchema::new(json!({"pattern": "("})).is_err());
+    assert!(JsonSchema::new(json!("string")).is_err());
+    assert!(!JsonSchema::new(json!(false)).unwrap().is_valid(&json!(1)));
+}
+
+#[test]
+fn test_json_schema_compatibility() {
+    let v1 = JsonSchema::new(json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "integer"},
+            "status": {"enum": ["new", "paid"]},
+            "note": {"type": "string", "maxLength": 100},
+        },
+        "required": ["id"],
+    }))
+    .unwrap();
+
+    // Optional field added, integer widened to number, enum extended
+    let widened = JsonSchema::new(json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "number"},
+            "status": {"enum": ["new", "paid", "refunded"]},
+            "note": {"type": "string", "maxLength": 200},
+            "channel": {"type": "string"},
+            "region": {"type": "string", "default": "eu"},
+        },
+        "required": ["id", "region"],
+    }))
+    .unwrap();
+    assert_eq!(v1.compatibility_issues(&widened, Compatibility::Backward), Vec::<String>::new());
+    assert_eq!(v1.compatibility_issues(&widened, Compatibility::Forward), [
+        "/id: type number is no longer accepted",
+        "/note: maxLength tightened to 100",
+        "/status: enum value \"refunded\" was removed",
+    ]);
+
+    let narrowed = JsonSchema::new(json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "string"},
+            "status": {"enum": ["new"]},
+            "note": {"type": "string", "maxLength": 100},
+        },
+        "required": ["id", "status"],
+        "additionalProperties": false,
+    }))
+    .unwrap();
+    assert_eq!(v1.compatibility_issues(&narrowed, Compatibility::Backward), [
+        "/: property 'status' became required",
+        "/: additional properties are no longer accepted",
+        "/id: type integer is no longer accepted",
+        "/status: enum value \"paid\" was removed",
+    ]);
+    assert!(v1.compatibility_issues(&narrowed, Compatibility::None).is_empty());
+    assert!(v1.compatibility_issues(&v1, Compatibility::Full).is_empty());
+}
@@ -0,0 +1,63 @@
+//! # admin
+//!
+//! admin 模块 - 管理面板
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! # Admin dashboard
+//!
+//! 在 `rf_net::http::AdminPlugin` 的基础上预置框架级信息：
+//!
+//! - `instances`: 通过 gins 创建的全部实例名称
+//! - `databases`: 各 gins 数据库实例的连接池状态
+//! - `status`: 各 gins 实例的健康状态（见 `gins::status`）
+//!
+//! 缓存、定时任务等由应用持有的对象通过 `section` / `async_section` 自行添加。
+//!
+//! ## 使用示例
+//!
+//! ```rust,ignore
+//! use rf_frame::admin;
+//!
+//! let cache = Arc::new(CacheContainer::<String, String>::new(10_000));
+//! let cron = Arc::new(Cron::new().await?);
+//!
+//! let plugin = admin::plugin("secret-token")
+//!     .async_section("cache", move || {
+//!         let cache = cache.clone();
+//!         async move { json!(cache.stats().await) }
+//!     })
+//!     .async_section("cron", move || {
+//!         let cron = cron.clone();
+//!         async move { json!(cron.jobs().await) }
+//!     });
+//!
+//! HttpServer::new(addr).with_plugin(plugin)?.serve().await?;
+//! ```
+
+use crate::gins::InstanceManager;
+use rf_net::http::AdminPlugin;
+use serde_json::{json, Map};
+
+/// 创建预置 gins 实例和数据库连接池信息的管理面板插件
+///
+/// # 参数
+///
+/// * `token` - 访问令牌
+///
+/// # 返回值
+///
+/// 返回 `AdminPlugin`，可继续添加自定义信息或修改挂载路径
+pub fn plugin(token: impl Into<String>) -> AdminPlugin {
+    AdminPlugin::new(token)
+        .section("instances", || json!(InstanceManager::names()))
+        .section("databases", || {
+            let databases: Map<_, _> = InstanceManager::databases()
+                .into_iter()
+                .map(|(name, db)| (name, json!(db.pool_stats())))
+                .collect();
+            json!(databases)
+        })
+        .async_section("status", || async { json!(crate::gins::status().await) })
+}
@@ -0,0 +1,195 @@
+//! # app
+//!
+//! app 模块 - 应用启动
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! # Application startup
+//!
+//! gins 默认在第一次使用时创建实例：第一个请求要承担建立连接的延迟，配置错误（如数据库地址
+//! 拼写错误）也要等到运行时才暴露。`App` 在启动时预热指定的 database / redis / view 实例，
+//! 逐个检查健康状态，任何一个失败都让启动返回错误。
+//!
+//! ## 配置示例
+//!
+//! ```toml
+//! [app]
+//! eager = true                                 # 预热所有已配置的 database / redis / view 实例
+//! warmup = ["database.default", "redis.cache"] # 或只预热列出的实例（eager = false 时）
+//! ```
+//!
+//! ## 使用示例
+//!
+//! ```rust,ignore
+//! use rf_frame::{gins, App};
+//!
+//! gins::set_config(None, rf_os::cfg::Config::layered("config.toml")?);
+//!
+//! // 读取 app.eager / app.warmup，实例创建或健康检查失败时返回错误
+//! let statuses = App::new().warm("view.email").start().await?;
+//! ```
+//!
+//! `run` 在预热后运行 HTTP 服务器直到收到 Ctrl+C / SIGTERM，再通过全局关闭协调器依次停止接收请求、
+//! 刷新日志和链路数据、关闭连接池：
+//!
+//! ```rust,ignore
+//! let report = App::new().run(g::server(addr).route(...)).await?;
+//! ```
+
+use crate::gins::{self, InstanceStatus};
+use crate::shutdown::{self, ShutdownReport};
+use rf_errors::{Result, RfError};
+use rf_net::http::HttpServer;
+use std::time::{Duration, Instant};
+
+/// 可预热的实例类型
+const WARMABLE: [&str; 3] = ["database", "redis", "view"];
+
+/// HTTP 服务器关闭钩子的超时时间，与服务器默认的 `shutdown_timeout` 一致
+const HTTP_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `[app]` 配置段
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct AppConfig {
+    eager: bool,
+    warmup: Vec<String>,
+}
+
+/// 应用启动器
+///
+/// 默认按 `app.eager` / `app.warmup` 配置预热实例，未配置时不预热（保持延迟创建）。
+pub struct App {
+    eager: Option<bool>,
+    warmup: Vec<String>,
+    health_timeout: Duration,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl App {
+    /// 创建应用启动器
+    pub fn new() -> Self {
+        Self {
+            eager: None,
+            warmup: Vec::new(),
+            health_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// 是否预热所有已配置的实例，覆盖 `app.eager`
+    pub fn eager(mut self, eager: bool) -> Self {
+        self.eager = Some(eager);
+        self
+    }
+
+    /// 预热指定实例，格式为 `类型.名称`（如 `database.default`），追加到 `app.warmup` 之后
+    pub fn warm(mut self, instance: &str) -> Self {
+        self.warmup.push(instance.to_string());
+        self
+    }
+
+    /// 设置每个实例健康检查的超时时间（默认 5 秒）
+    pub fn health_timeout(mut self, timeout: Duration) -> Self {
+        self.health_timeout = timeout;
+        self
+    }
+
+    /// 需要预热的实例，按配置顺序去重
+    fn targets(&self, config: &rf_os::cfg::Config) -> Result<Vec<String>> {
+        let settings: AppConfig = config.get_struct("app")?;
+        let mut targets = settings.warmup;
+        targets.extend(self.warmup.iter().cloned());
+        if self.eager.unwrap_or(settings.eager) {
+            let mut configured: Vec<String> = config
+                .all()?
+                .keys()
+                .filter_map(|key| {
+                    let mut parts = key.splitn(3, '.');
+                    let (kind, name) = (parts.next()?, parts.next()?);
+                    (WARMABLE.contains(&kind) && parts.next().is_some()).then(|| format!("{}.{}", kind, name))
+                })
+                .collect();
+            configured.sort();
+            targets.extend(configured);
+        }
+        let mut seen = std::collections::HashSet::new();
+        targets.retain(|target| seen.insert(target.clone()));
+        Ok(targets)
+    }
+
+    /// 创建一个实例
+    async fn create(target: &str) -> Result<()> {
+        let (kind, name) = target
+            .split_once('.')
+            .filter(|(_, name)| !name.is_empty())
+            .ok_or_else(|| RfError::Config(format!("Invalid instance '{}', expected <type>.<name>", target)))?;
+        match kind {
+            "database" => gins::database(Some(name)).await.map(|_| ()),
+            "redis" => gins::redis(Some(name)).await.map(|_| ()),
+            "view" => gins::view(Some(name)).map(|_| ()),
+            _ => Err(RfError::Config(format!(
+                "Cannot warm up '{}': instance type must be one of {}",
+                target,
+                WARMABLE.join(", ")
+            ))),
+        }
+    }
+
+    /// 预热实例并检查健康状态
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回预热实例的状态；任何实例创建失败或不健康时返回 `RfError::Config`，
+    /// 错误信息列出全部失败的实例
+    pub async fn start(self) -> Result<Vec<InstanceStatus>> {
+        let config = gins::config(None);
+        let targets = self.targets(&config)?;
+        let mut failures = Vec::new();
+        for target in &targets {
+            let started = Instant::now();
+            match Self::create(target).await {
+                Ok(()) => tracing::info!("Initialized {} in {:?}", target, started.elapsed()),
+                Err(e) => failures.push(format!("{}: {}", target, e)),
+            }
+        }
+
+        let statuses: Vec<InstanceStatus> = gins::status_with_timeout(self.health_timeout)
+            .await
+            .into_iter()
+            .filter(|status| targets.contains(&status.name))
+            .collect();
+        for status in statuses.iter().filter(|status| !status.healthy) {
+            failures.push(format!("{}: {}", status.name, status.error.as_deref().unwrap_or("unhealthy")));
+        }
+        if !failures.is_empty() {
+            return Err(RfError::Config(format!(
+                "Failed to initialize {} instance(s): {}",
+                failures.len(),
+                failures.join("; ")
+            )));
+        }
+        Ok(statuses)
+    }
+
+    /// 启动应用并运行到收到 Ctrl+C / SIGTERM
+    ///
+    /// 预热实例（见 `start`）后在后台运行 `server`，并向全局关闭协调器注册服务器和内置钩子
+    /// （见 `Shutdown::serve`、`Shutdown::register_defaults`）；收到信号后执行所有已注册的钩子。
+    ///
+    /// # 返回值
+    ///
+    /// 返回关闭报告；预热失败或信号监听失败时返回错误
+    pub async fn run(self, server: HttpServer) -> Result<ShutdownReport> {
+        self.start().await?;
+        let coordinator = shutdown::global();
+        coordinator.serve("http", server, HTTP_SHUTDOWN_TIMEOUT);
+        coordinator.register_defaults();
+        coordinator.wait_and_run().await
+    }
+}
@@ -13,7 +13,8 @@
 //!
 //! - **单例模式**: 确保每种类型的实例在应用中只创建一次
 //! - **按名称管理**: 支持为同类型实例创建多个命名实例（如多个数据库连接）
-//! - **配置驱动**: 从配置文件自动加载实例参数
+//! - **配置驱动**: 从 `config(None)`（由 `set_config` 安装）自动加载实例参数
+//! - **预热与健康检查**: `App` 在启动时创建实例并快速失败，`status()` 列出实例及其健康状态
 //! - **线程安全**: 使用 Mutex 和 Arc 确保多线程环境下的安全访问
 //! - **异步友好**: 支持异步初始化的实例
 //!
@@ -23,7 +24,8 @@
 //! - `database()`: 数据库连接实例
 //! - `redis()`: Redis 客户端实例
 //! - `view()`: 视图引擎实例
-//! - `config()`: 配置管理实例
+//! - `config()`: 配置管理实例（`set_config` 安装，`watch_config` 热加载）
+//! - `rate_limiter()`: 请求限流器（随配置热更新）
 //! - `i18n()`: 国际化实例
 //! - `resource()`: 资源存储实例
 //!
@@ -247,6 +249,148 @@ impl InstanceManager {
             .expect("Mutex poisoned in InstanceManager - this should not happen in normal operation");
         instances.clear();
     }
+
+    /// 列出所有实例名称
+    ///
+    /// # 返回值
+    ///
+    /// 返回按字母排序的实例名称，格式为 `类型.名称`（如 `database.default`）
+    ///
+    /// # 使用示例
+    ///
+    /// ```rust
+    /// use rf_frame::gins::InstanceManager;
+    ///
+    /// for name in InstanceManager::names() {
+    ///     println!("{}", name);
+    /// }
+    /// ```
+    pub fn names() -> Vec<String> {
+        let instances = INSTANCE_MANAGER.instances.lock()
+            .expect("Mutex poisoned in InstanceManager - this should not happen in normal operation");
+        let mut names: Vec<String> = instances.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// 获取所有已创建的数据库实例
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `(实例名称, 数据库实例)` 列表，实例名称不含 `database.` 前缀
+    pub fn databases() -> Vec<(String, Arc<rf_database::db::Database>)> {
+        let instances = INSTANCE_MANAGER.instances.lock()
+            .expect("Mutex poisoned in InstanceManager - this should not happen in normal operation");
+        let mut databases: Vec<_> = instances
+            .iter()
+            .filter_map(|(key, instance)| {
+                let name = key.strip_prefix("database.")?;
+                let db = instance.downcast_ref::<Arc<rf_database::db::Database>>()?;
+                Some((name.to_string(), Arc::clone(db)))
+            })
+            .collect();
+        databases.sort_by(|a, b| a.0.cmp(&b.0));
+        databases
+    }
+
+    /// 关闭所有数据库连接池，并移除数据库和 Redis 实例
+    ///
+    /// 数据库连接池等待借出的连接归还后关闭；Redis 客户端从管理器中移除，
+    /// 其他地方持有的引用释放后连接随之关闭。之后再次获取实例会重新创建。
+    ///
+    /// # 返回值
+    ///
+    /// 返回移除的实例数量
+    pub async fn close_pools() -> usize {
+        for (name, db) in Self::databases() {
+            db.close().await;
+            tracing::info!("Closed database pool '{}'", name);
+        }
+        let mut instances = INSTANCE_MANAGER.instances.lock()
+            .expect("Mutex poisoned in InstanceManager - this should not happen in normal operation");
+        let before = instances.len();
+        instances.retain(|key, _| !key.starts_with("database.") && !key.starts_with("redis."));
+        before - instances.len()
+    }
+}
+
+/// 框架实例的配置结构
+///
+/// 声明 gins 读取的配置键、类型与默认值。注册后（见 `register_config_schema`），
+/// `Config::validate` 可在启动时报告拼写错误、类型错误和缺失的数据库地址，
+/// 而不是在运行时悄悄回退到默认值。
+///
+/// # 使用示例
+///
+/// ```rust,ignore
+/// use rf_frame::gins;
+///
+/// gins::register_config_schema();
+/// config.validate()?;
+/// ```
+pub fn config_schema() -> rf_os::cfg::ConfigSchema {
+    use rf_os::cfg::{KeySpec, TypeRule};
+    rf_os::cfg::ConfigSchema::new()
+        .key(KeySpec::new("server.*.address", TypeRule::SocketAddr)
+            .default("127.0.0.1:8080")
+            .description("HTTP 服务器监听地址"))
+        .key(KeySpec::new("server.*.request_timeout", TypeRule::Duration)
+            .description("请求处理超时，如 30s"))
+        .key(KeySpec::new("server.*.shutdown_timeout", TypeRule::Duration)
+            .default("30s")
+            .description("优雅关闭等待时间"))
+        .key(KeySpec::new("server.*.header_read_timeout", TypeRule::Duration)
+            .default("30s")
+            .description("接收请求头的超时"))
+        .key(KeySpec::new("server.*.body_read_timeout", TypeRule::Duration)
+            .description("接收请求体的超时"))
+        .key(KeySpec::new("server.*.write_timeout", TypeRule::Duration)
+            .description("响应写入停滞的超时"))
+        .key(KeySpec::new("server.*.socket.nodelay", TypeRule::Boolean)
+            .description("TCP_NODELAY"))
+        .key(KeySpec::new("server.*.socket.keepalive", TypeRule::Duration)
+            .description("开启 SO_KEEPALIVE，空闲多久后发送第一个探测"))
+        .key(KeySpec::new("server.*.socket.keepalive_interval", TypeRule::Duration)
+            .description("keepalive 探测间隔"))
+        .key(KeySpec::new("server.*.socket.keepalive_retries", TypeRule::Integer)
+            .description("keepalive 探测失败多少次后断开"))
+        .key(KeySpec::new("server.*.socket.reuse_address", TypeRule::Boolean)
+            .default("true")
+            .description("SO_REUSEADDR"))
+        .key(KeySpec::new("server.*.socket.reuse_port", TypeRule::Boolean)
+            .default("false")
+            .description("SO_REUSEPORT，多进程监听同一端口"))
+        .key(KeySpec::new("server.*.socket.recv_buffer_size", TypeRule::Integer)
+            .description("SO_RCVBUF 字节数"))
+        .key(KeySpec::new("server.*.socket.send_buffer_size", TypeRule::Integer)
+            .description("SO_SNDBUF 字节数"))
+        .key(KeySpec::new("server.*.socket.backlog", TypeRule::Integer)
+            .default("1024")
+            .description("等待接受的连接队列长度"))
+        .key(KeySpec::new("database.*.url", TypeRule::Url)
+            .required()
+            .description("数据库连接 URL，如 postgresql://host/db"))
+        .key(KeySpec::new("redis.*.url", TypeRule::Url)
+            .default("redis://127.0.0.1:6379/")
+            .description("Redis 连接 URL"))
+        .key(KeySpec::new("view.*.template_dir", TypeRule::String)
+            .default("templates")
+            .description("模板目录"))
+        .key(KeySpec::new("i18n.*.language", TypeRule::String)
+            .description("默认语言"))
+        .key(KeySpec::new("logger.level", TypeRule::String)
+            .description("日志级别或过滤指令，如 info,rf_database=debug；watch_config 时热更新"))
+        .key(KeySpec::new("rate_limit.*.capacity", TypeRule::Integer)
+            .default("100")
+            .description("限流令牌桶容量"))
+        .key(KeySpec::new("rate_limit.*.refill_rate", TypeRule::Integer)
+            .default("100")
+            .description("每秒补充的令牌数"))
+}
+
+/// 注册框架实例的配置结构（名称为 `frame`，重复调用会替换）
+pub fn register_config_schema() {
+    rf_os::cfg::schema::register("frame", config_schema());
 }
 
 /// 获取 HTTP 服务器实例（按名称，从配置加载）
@@ -268,6 +412,12 @@ impl InstanceManager {
 /// - 如果未配置，默认使用 "127.0.0.1:8080"
 /// - 地址格式示例：`"0.0.0.0:8080"` 或 `"127.0.0.1:3000"`
 ///
+/// `request_timeout`、`shutdown_timeout`、`header_read_timeout`、`body_read_timeout`、
+/// `write_timeout` 设置超时，写法如 `"30s"`、`"2m"`
+///
+/// `server.{name}.socket` 设置套接字选项（`nodelay`、`keepalive`、`reuse_port`、缓冲区大小等），
+/// 见 `rf_net::SocketOptions`
+///
 /// # 使用示例
 ///
 /// ```no_run
@@ -294,24 +444,61 @@ pub fn server(name: Option<&str>) -> Result<Arc<rf_net::http::HttpServer>> {
             return Ok(Arc::clone(typed));
         }
     }
+    drop(instances);
 
     // 尝试从配置加载
-    let config = rf_os::cfg::Config::new();
+    let config = config(None);
     let addr = if let Ok(Some(addr_str)) = config.get(&format!("server.{}.address", instance_name)) {
         addr_str.parse::<std::net::SocketAddr>().unwrap_or_else(|_| {
+            tracing::warn!(
+                "Invalid server.{}.address {:?}, falling back to 127.0.0.1:8080",
+                instance_name, addr_str
+            );
             "127.0.0.1:8080".parse().unwrap()
         })
     } else {
         "127.0.0.1:8080".parse().unwrap()
     };
 
-    let server = Arc::new(rf_net::http::HttpServer::new(addr));
-    drop(instances);
+    let server = Arc::new(server_from_config(&config, instance_name, addr)?);
     let mut instances = INSTANCE_MANAGER.instances.lock().unwrap();
     instances.insert(key, Box::new(Arc::clone(&server)));
     Ok(server)
 }
 
+/// 按 `server.{name}.*` 中的超时配置创建 HTTP 服务器
+///
+/// 超时支持 `30s`、`5m` 等写法，格式错误时返回 `RfError::Config`。
+fn server_from_config(
+    config: &rf_os::cfg::Config,
+    instance_name: &str,
+    addr: std::net::SocketAddr,
+) -> Result<rf_net::http::HttpServer> {
+    let duration = |field: &str| config.get_duration(&format!("server.{}.{}", instance_name, field));
+    let mut limits = rf_net::http::ServerLimits::default();
+    if let Some(timeout) = duration("header_read_timeout")? {
+        limits = limits.header_read_timeout(timeout);
+    }
+    if let Some(timeout) = duration("body_read_timeout")? {
+        limits = limits.body_read_timeout(timeout);
+    }
+    if let Some(timeout) = duration("write_timeout")? {
+        limits = limits.write_timeout(timeout);
+    }
+    let mut server = rf_net::http::HttpServer::new(addr).with_limits(limits);
+    if let Some(timeout) = duration("shutdown_timeout")? {
+        server = server.shutdown_timeout(timeout);
+    }
+    if let Some(timeout) = duration("request_timeout")? {
+        server = server.with_request_timeout(timeout);
+    }
+    let socket = format!("server.{}.socket", instance_name);
+    if config.all()?.keys().any(|key| key.starts_with(&format!("{}.", socket))) {
+        server = server.with_socket_options(config.get_struct(&socket)?);
+    }
+    Ok(server)
+}
+
 /// 获取数据库实例（按名称，从配置加载）
 ///
 /// 此方法获取或创建一个命名的数据库连接实例。
@@ -371,7 +558,7 @@ pub async fn database(name: Option<&str>) -> Result<Arc<rf_database::db::Databas
     } // 锁在此处释放
 
     // 尝试从配置加载
-    let config = rf_os::cfg::Config::new();
+    let config = config(None);
     let db = if let Ok(Some(url)) = config.get(&format!("database.{}.url", instance_name)) {
         // 优先尝试 PostgreSQL
         if url.starts_with("postgresql://") {
@@ -382,10 +569,18 @@ pub async fn database(name: Option<&str>) -> Result<Arc<rf_database::db::Databas
             let sqlite_url = url.trim_start_matches("sqlite://");
             rf_database::db::Database::new_sqlite(sqlite_url).await?
         } else {
+            tracing::warn!(
+                "Unsupported database.{}.url scheme, falling back to postgresql://localhost/test",
+                instance_name
+            );
             rf_database::db::Database::new_postgres("postgresql://localhost/test").await?
         }
     } else {
         // 默认：PostgreSQL
+        tracing::warn!(
+            "database.{}.url is not configured, falling back to postgresql://localhost/test",
+            instance_name
+        );
         rf_database::db::Database::new_postgres("postgresql://localhost/test").await?
     };
 
@@ -448,7 +643,7 @@ pub async fn redis(name: Option<&str>) -> Result<Arc<rf_database::redis::RedisCl
     } // 锁在此处释放
 
     // 尝试从配置加载
-    let config = rf_os::cfg::Config::new();
+    let config = config(None);
     let client = if let Ok(Some(url)) = config.get(&format!("redis.{}.url", instance_name)) {
         rf_database::redis::RedisClient::new(&url).await
             .map_err(|e| rf_errors::RfError::Database(format!("Failed to create Redis client: {}", e)))?
@@ -483,7 +678,7 @@ pub async fn redis(name: Option<&str>) -> Result<Arc<rf_database::redis::RedisCl
 ///
 /// 配置文件中的 `view.{name}.template_dir` 字段指定模板目录路径
 /// - 如果未配置，默认使用 `"templates"` 目录
-/// - 如果指定的目录无效，会回退到 `"templates"` 目录
+/// - 如果指定的目录无效，返回错误
 ///
 /// # 使用示例
 ///
@@ -514,19 +709,12 @@ pub fn view(name: Option<&str>) -> Result<Arc<rf_os::view::View>> {
     drop(instances);
 
     // 尝试从配置加载
-    let config = rf_os::cfg::Config::new();
-    let view = if let Ok(Some(template_dir)) = config.get(&format!("view.{}.template_dir", instance_name)) {
-        rf_os::view::View::new(&template_dir)
-            .unwrap_or_else(|_| {
-                rf_os::view::View::new("templates").unwrap()
-            })
-    } else {
-        // 默认模板目录
-        rf_os::view::View::new("templates")
-            .unwrap_or_else(|_| {
-                rf_os::view::View::new("templates").unwrap()
-            })
-    };
+    let config = config(None);
+    // 配置的模板目录加载失败时返回错误，而不是悄悄回退到默认目录
+    let template_dir = config
+        .get(&format!("view.{}.template_dir", instance_name))?
+        .unwrap_or_else(|| "templates".to_string());
+    let view = rf_os::view::View::new(&template_dir)?;
 
     let arc_view = Arc::new(view);
     {
@@ -585,6 +773,142 @@ pub fn config(name: Option<&str>) -> Arc<rf_os::cfg::Config> {
     config
 }
 
+/// 安装配置实例（按名称）
+///
+/// 替换 `config(name)` 返回的实例。已通过 `rate_limiter` 等订阅旧实例的组件不会迁移，
+/// 因此应在应用启动、创建其他实例之前调用。
+///
+/// # 参数
+///
+/// * `name` - 实例名称，None 表示使用默认名称 "default"
+/// * `config` - 配置实例
+///
+/// # 使用示例
+///
+/// ```rust,ignore
+/// use rf_frame::gins;
+///
+/// gins::set_config(None, rf_os::cfg::Config::layered("config.toml")?);
+/// gins::watch_config(None)?;
+/// ```
+pub fn set_config(name: Option<&str>, config: rf_os::cfg::Config) -> Arc<rf_os::cfg::Config> {
+    let key = format!("config.{}", name.unwrap_or("default"));
+    let config = Arc::new(config);
+    let mut instances = INSTANCE_MANAGER.instances.lock()
+        .expect("Mutex poisoned in InstanceManager - this should not happen in normal operation");
+    instances.insert(key, Box::new(Arc::clone(&config)));
+    config
+}
+
+/// 监控配置文件，变化时热加载（按名称）
+///
+/// 启动 `Config::watch`，并让 `logger.level` 的变化通过 `rf_os::log::set_level` 立即生效。
+/// 由 gins 创建的其他实例（如 `rate_limiter`）自行订阅各自的配置键。重复调用不会重复监控。
+///
+/// # 参数
+///
+/// * `name` - 实例名称，None 表示使用默认名称 "default"
+///
+/// # 返回值
+///
+/// 配置实例没有配置文件时返回 `RfError::Config`
+pub fn watch_config(name: Option<&str>) -> Result<()> {
+    let key = format!("config_watch.{}", name.unwrap_or("default"));
+    {
+        let instances = INSTANCE_MANAGER.instances.lock()
+            .expect("Mutex poisoned in InstanceManager - this should not happen in normal operation");
+        if instances.contains_key(&key) {
+            return Ok(());
+        }
+    }
+
+    let config = config(name);
+    let watch = config.watch()?;
+    config.on_change("logger.level", |changes| {
+        let Some(level) = changes.iter().find(|c| c.key == "logger.level").and_then(|c| c.new.as_deref()) else {
+            return;
+        };
+        match rf_os::log::set_level(level) {
+            Ok(()) => tracing::info!("Log level changed to {}", level),
+            Err(e) => tracing::warn!("Ignoring logger.level {:?}: {}", level, e),
+        }
+    });
+    let mut instances = INSTANCE_MANAGER.instances.lock()
+        .expect("Mutex poisoned in InstanceManager - this should not happen in normal operation");
+    instances.insert(key, Box::new(Arc::new(std::sync::Mutex::new(watch))));
+    Ok(())
+}
+
+/// 获取请求限流器实例（按名称，从配置加载）
+///
+/// 读取 `rate_limit.{name}.capacity` 和 `rate_limit.{name}.refill_rate`（默认均为 100），
+/// 并订阅这两个键：配置热加载后限流器立即使用新的限制。
+///
+/// # 参数
+///
+/// * `name` - 实例名称，None 表示使用默认名称 "default"
+///
+/// # 使用示例
+///
+/// ```rust,ignore
+/// use rf_frame::gins;
+///
+/// let limiter = gins::rate_limiter(Some("api"));
+/// router.layer(axum::middleware::from_fn(move |req, next| {
+///     rf_net::http::rate_limit_middleware(limiter.clone(), req, next)
+/// }));
+/// ```
+pub fn rate_limiter(name: Option<&str>) -> Arc<rf_net::http::RateLimiter> {
+    let instance_name = name.unwrap_or("default");
+    let key = format!("rate_limit.{}", instance_name);
+
+    {
+        let instances = INSTANCE_MANAGER.instances.lock()
+            .expect("Mutex poisoned in InstanceManager - this should not happen in normal operation");
+        if let Some(instance) = instances.get(&key) {
+            if let Some(typed) = instance.downcast_ref::<Arc<rf_net::http::RateLimiter>>() {
+                return Arc::clone(typed);
+            }
+        }
+    }
+
+    let config = config(None);
+    let instance_name = instance_name.to_string();
+    let limits = move |config: &rf_os::cfg::Config| {
+        let read = |field: &str| {
+            let key = format!("rate_limit.{}.{}", instance_name, field);
+            match config.get_int(&key) {
+                Ok(Some(value)) if value >= 0 => value as u64,
+                Ok(None) => 100,
+                other => {
+                    tracing::warn!("Invalid {}: {:?}, using 100", key, other);
+                    100
+                }
+            }
+        };
+        (read("capacity"), read("refill_rate"))
+    };
+    let (capacity, refill_rate) = limits(&config);
+    let limiter = Arc::new(rf_net::http::RateLimiter::new(capacity, refill_rate));
+
+    let weak_config = Arc::downgrade(&config);
+    let limiter_name = key.clone();
+    let weak_limiter = Arc::downgrade(&limiter);
+    config.on_change(&key, move |_| {
+        let (Some(config), Some(limiter)) = (weak_config.upgrade(), weak_limiter.upgrade()) else {
+            return;
+        };
+        let (capacity, refill_rate) = limits(&config);
+        limiter.set_limits(capacity, refill_rate);
+        tracing::info!("Rate limit {} set to {} burst, {}/s", limiter_name, capacity, refill_rate);
+    });
+
+    let mut instances = INSTANCE_MANAGER.instances.lock()
+        .expect("Mutex poisoned in InstanceManager - this should not happen in normal operation");
+    instances.insert(key, Box::new(Arc::clone(&limiter)));
+    limiter
+}
+
 /// 获取国际化实例（按名称，从配置加载）
 ///
 /// 此方法获取或创建一个命名的国际化 (i18n) 管理实例。
@@ -631,7 +955,7 @@ pub fn i18n(name: Option<&str>) -> Arc<rf_i18n::i18n::I18n> {
     drop(instances);
 
     // 尝试从配置加载
-    let config = rf_os::cfg::Config::new();
+    let config = config(None);
     let i18n_instance = if let Ok(Some(lang)) = config.get(&format!("i18n.{}.language", instance_name)) {
         rf_i18n::i18n::I18n::new(&lang)
     } else {
@@ -692,3 +1016,113 @@ pub fn resource(name: Option<&str>) -> Arc<rf_os::res::ResourceStorage> {
     instances.insert(key, Box::new(Arc::clone(&resource)));
     resource
 }
+
+/// 实例状态
+///
+/// `status()` 为每个已创建的实例返回一项。数据库实例执行 `SELECT 1`、Redis 实例执行
+/// `PING` 检查健康状态，其余实例创建成功即视为健康。
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InstanceStatus {
+    /// 实例名称，格式为 `类型.名称`（如 `database.default`）
+    pub name: String,
+    /// 实例类型，如 `database`、`redis`、`view`
+    pub kind: String,
+    /// 是否健康
+    pub healthy: bool,
+    /// 健康检查耗时（毫秒），未执行检查时为 None
+    pub latency_ms: Option<u64>,
+    /// 检查失败的原因
+    pub error: Option<String>,
+}
+
+/// 健康检查的目标
+enum Probe {
+    Database(Arc<rf_database::db::Database>),
+    Redis(Arc<rf_database::redis::RedisClient>),
+    None,
+}
+
+impl Probe {
+    async fn check(self, timeout: std::time::Duration) -> (Option<u64>, Option<String>) {
+        let started = std::time::Instant::now();
+        let result = match self {
+            Probe::Database(db) => {
+                tokio::time::timeout(timeout, async move { db.raw_execute("SELECT 1").await.map(|_| ()) }).await
+            }
+            Probe::Redis(client) => {
+                tokio::time::timeout(timeout, async move { client.command("PING", &[]).await.map(|_| ()) }).await
+            }
+            Probe::None => return (None, None),
+        };
+        let latency = Some(started.elapsed().as_millis() as u64);
+        match result {
+            Ok(Ok(())) => (latency, None),
+            Ok(Err(e)) => (latency, Some(e.to_string())),
+            Err(_) => (latency, Some(format!("health check timed out after {:?}", timeout))),
+        }
+    }
+}
+
+/// 列出所有已创建的实例及其健康状态
+///
+/// 健康检查并发执行，每项最多等待 5 秒；超时时间可通过 `status_with_timeout` 指定。
+///
+/// # 返回值
+///
+/// 返回按名称排序的 `InstanceStatus` 列表
+///
+/// # 使用示例
+///
+/// ```rust,ignore
+/// use rf_frame::gins;
+///
+/// for status in gins::status().await {
+///     println!("{} healthy={} {:?}", status.name, status.healthy, status.error);
+/// }
+/// ```
+pub async fn status() -> Vec<InstanceStatus> {
+    status_with_timeout(std::time::Duration::from_secs(5)).await
+}
+
+/// 列出所有已创建的实例及其健康状态，每项健康检查最多等待 `timeout`
+pub async fn status_with_timeout(timeout: std::time::Duration) -> Vec<InstanceStatus> {
+    // 在锁内只复制实例句柄，检查在释放锁后进行
+    let probes: Vec<(String, Probe)> = {
+        let instances = INSTANCE_MANAGER.instances.lock()
+            .expect("Mutex poisoned in InstanceManager - this should not happen in normal operation");
+        let mut probes: Vec<_> = instances
+            .iter()
+            .map(|(name, instance)| {
+                let probe = if let Some(db) = instance.downcast_ref::<Arc<rf_database::db::Database>>() {
+                    Probe::Database(Arc::clone(db))
+                } else if let Some(client) = instance.downcast_ref::<Arc<rf_database::redis::RedisClient>>() {
+                    Probe::Redis(Arc::clone(client))
+                } else {
+                    Probe::None
+                };
+                (name.clone(), probe)
+            })
+            .collect();
+        probes.sort_by(|a, b| a.0.cmp(&b.0));
+        probes
+    };
+
+    let checks = probes.into_iter().map(|(name, probe)| async move {
+        let (latency_ms, error) = probe.check(timeout).await;
+        InstanceStatus {
+            kind: name.split('.').next().unwrap_or_default().to_string(),
+            name,
+            healthy: error.is_none(),
+            latency_ms,
+            error,
+        }
+    });
+    let handles: Vec<_> = checks.map(tokio::spawn).collect();
+    let mut statuses = Vec::with_capacity(handles.len());
+    for handle in handles {
+        if let Ok(status) = handle.await {
+            statuses.push(status);
+        }
+    }
+    statuses
+}
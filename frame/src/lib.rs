@@ -13,6 +13,8 @@
 //!
 //! - **g 模块**: 提供全局便捷函数，用于快速创建各种服务实例（服务器、客户端、数据库等）
 //! - **gins 模块**: 提供全局实例管理器，用于管理和复用框架中的各种实例
+//! - **app 模块**: 应用启动时预热实例并检查健康状态
+//! - **shutdown 模块**: 按优先级和超时预算执行组件的关闭钩子
 //!
 //! ## 主要功能
 //!
@@ -50,6 +52,12 @@
 
 pub mod g;
 pub mod gins;
+pub mod admin;
+pub mod app;
+pub mod shutdown;
+
+pub use app::App;
+pub use shutdown::{Phase, Shutdown};
 
 // Re-export with specific names to avoid conflicts
 // 重新导出并使用特定名称以避免命名冲突
@@ -83,5 +91,6 @@ pub use gins::{
     config,
     i18n,
     resource,
+    status,
 };
 
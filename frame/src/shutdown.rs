@@ -0,0 +1,381 @@
+//! # shutdown
+//!
+//! shutdown 模块 - 优雅关闭
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! # Graceful shutdown
+//!
+//! 进程退出时各组件的清理顺序原本取决于 drop 顺序：连接池可能先于仍在处理请求的服务器关闭，
+//! 日志和链路数据可能在任务结束前就被丢弃。`Shutdown` 让组件按优先级注册关闭钩子，
+//! 关闭时按优先级从小到大依次执行，同一优先级的钩子并发执行。
+//!
+//! 每个钩子有自己的超时时间，所有钩子共享一个总预算：钩子实际可用的时间是自身超时与剩余预算中的
+//! 较小值，预算耗尽后剩余的钩子被跳过。每个钩子的开始、完成、失败和超时都会记录日志。
+//!
+//! ## 阶段
+//!
+//! | 阶段 | 优先级 | 用途 |
+//! |------|--------|------|
+//! | `Phase::StopTraffic` | 100 | 停止接收新请求（HTTP / WebSocket / 消息消费者） |
+//! | `Phase::DrainJobs` | 200 | 等待进行中的任务和定时任务结束 |
+//! | `Phase::Flush` | 300 | 刷新日志、链路追踪和指标 |
+//! | `Phase::ClosePools` | 400 | 关闭数据库 / Redis 连接池 |
+//!
+//! 也可以直接使用数字优先级在阶段之间插入钩子。
+//!
+//! ## 内置钩子
+//!
+//! - `serve` 在后台运行 HTTP 服务器，并注册为 `Phase::StopTraffic` 钩子：服务器不再自己监听信号，
+//!   钩子执行时停止接收新连接并等待进行中的请求结束
+//! - `register_defaults` 注册 `logs` 和 `trace`（`Phase::Flush`，刷新异步日志写入器、导出未发送的 span）
+//!   以及 `pools`（`Phase::ClosePools`，关闭 gins 创建的数据库连接池和 Redis 客户端）
+//!
+//! `App::run` 会完成这些注册并等待信号。
+//!
+//! ## 使用示例
+//!
+//! ```rust,ignore
+//! use rf_frame::shutdown::{self, Phase};
+//! use std::time::Duration;
+//!
+//! let coordinator = shutdown::global();
+//! coordinator.serve("http", server, Duration::from_secs(30));
+//! coordinator.register_defaults();
+//! coordinator.register("outbox", Phase::DrainJobs, Duration::from_secs(5), move || async move {
+//!     relay.shutdown().await;
+//!     Ok(())
+//! });
+//!
+//! // 等待 Ctrl+C / SIGTERM，然后按优先级执行所有钩子
+//! let report = coordinator.wait_and_run().await?;
+//! ```
+
+use crate::gins::InstanceManager;
+use once_cell::sync::Lazy;
+use rf_errors::{Result, RfError};
+use rf_net::http::HttpServer;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 关闭钩子
+type Hook = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send>;
+
+/// 默认总预算
+const DEFAULT_BUDGET: Duration = Duration::from_secs(30);
+
+/// 内置刷新钩子的超时时间
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 内置关闭连接池钩子的超时时间
+const CLOSE_POOLS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 关闭阶段，对应预设的优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    /// 停止接收新流量
+    StopTraffic,
+    /// 等待进行中的任务结束
+    DrainJobs,
+    /// 刷新日志、链路追踪和指标
+    Flush,
+    /// 关闭连接池
+    ClosePools,
+}
+
+impl Phase {
+    /// 阶段的优先级，数值越小越先执行
+    pub fn priority(self) -> u32 {
+        match self {
+            Phase::StopTraffic => 100,
+            Phase::DrainJobs => 200,
+            Phase::Flush => 300,
+            Phase::ClosePools => 400,
+        }
+    }
+}
+
+impl From<Phase> for u32 {
+    fn from(phase: Phase) -> Self {
+        phase.priority()
+    }
+}
+
+/// 钩子的执行结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookOutcome {
+    /// 正常完成
+    Completed,
+    /// 返回错误
+    Failed(String),
+    /// 超过超时时间或剩余预算
+    TimedOut,
+    /// 预算已耗尽，未执行
+    Skipped,
+}
+
+/// 单个钩子的执行报告
+#[derive(Debug, Clone)]
+pub struct HookReport {
+    /// 注册时的名称
+    pub name: String,
+    /// 优先级
+    pub priority: u32,
+    /// 执行耗时
+    pub elapsed: Duration,
+    /// 执行结果
+    pub outcome: HookOutcome,
+}
+
+/// 一次关闭的执行报告，按执行顺序排列
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownReport {
+    /// 各钩子的执行报告
+    pub hooks: Vec<HookReport>,
+    /// 总耗时
+    pub elapsed: Duration,
+}
+
+impl ShutdownReport {
+    /// 是否所有钩子都正常完成
+    pub fn is_clean(&self) -> bool {
+        self.hooks.iter().all(|hook| hook.outcome == HookOutcome::Completed)
+    }
+}
+
+/// 已注册的钩子
+struct Registration {
+    name: String,
+    priority: u32,
+    timeout: Duration,
+    hook: Hook,
+}
+
+/// 关闭协调器
+///
+/// 钩子只执行一次：`run` 取出所有已注册的钩子，之后再次调用 `run` 只会执行新注册的钩子。
+pub struct Shutdown {
+    hooks: Mutex<Vec<Registration>>,
+    budget: Mutex<Duration>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static GLOBAL: Lazy<Shutdown> = Lazy::new(Shutdown::new);
+
+/// 全局关闭协调器
+pub fn global() -> &'static Shutdown {
+    &GLOBAL
+}
+
+impl Shutdown {
+    /// 创建关闭协调器，总预算默认 30 秒
+    pub fn new() -> Self {
+        Self {
+            hooks: Mutex::new(Vec::new()),
+            budget: Mutex::new(DEFAULT_BUDGET),
+        }
+    }
+
+    /// 设置所有钩子共享的总预算
+    pub fn set_budget(&self, budget: Duration) {
+        *self.budget.lock().unwrap() = budget;
+    }
+
+    /// 注册关闭钩子
+    ///
+    /// # 参数
+    ///
+    /// * `name` - 名称，用于日志和报告
+    /// * `priority` - `Phase` 或数字优先级，数值越小越先执行
+    /// * `timeout` - 钩子的超时时间
+    /// * `hook` - 关闭时调用一次
+    pub fn register<F, Fut>(&self, name: &str, priority: impl Into<u32>, timeout: Duration, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.hooks.lock().unwrap().push(Registration {
+            name: name.to_string(),
+            priority: priority.into(),
+            timeout,
+            hook: Box::new(move || Box::pin(hook())),
+        });
+    }
+
+    /// 在后台运行 HTTP 服务器，并注册为 `Phase::StopTraffic` 钩子
+    ///
+    /// 服务器不再监听 Ctrl+C / SIGTERM，而是在钩子执行时停止接收新连接，并在服务器自身的
+    /// `shutdown_timeout` 内等待进行中的请求结束；`timeout` 应不小于该值。服务器启动失败时
+    /// 钩子返回该错误。协调器被丢弃而钩子未执行时，服务器同样会停止。
+    ///
+    /// 必须在 Tokio 运行时中调用。
+    pub fn serve(&self, name: &str, server: HttpServer, timeout: Duration) {
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let label = name.to_string();
+        let task = tokio::spawn(async move {
+            let result = server
+                .with_shutdown_signal(async move {
+                    let _ = stopped.await;
+                })
+                .serve()
+                .await;
+            if let Err(e) = &result {
+                tracing::error!("HTTP server '{}' stopped: {}", label, e);
+            }
+            result
+        });
+        self.register(name, Phase::StopTraffic, timeout, move || async move {
+            let _ = stop.send(());
+            task.await
+                .map_err(|e| RfError::Internal(format!("HTTP server task failed: {}", e)))?
+        });
+    }
+
+    /// 注册框架内置的关闭钩子
+    ///
+    /// - `logs`（`Phase::Flush`）：写出采样摘要并刷新所有异步日志写入器，见 `rf_os::log::flush`
+    /// - `trace`（`Phase::Flush`）：导出未发送的 span，见 `rf_net::trace::shutdown_tracing`
+    /// - `pools`（`Phase::ClosePools`）：关闭 gins 创建的连接池，见 `InstanceManager::close_pools`
+    ///
+    /// 每个协调器只应调用一次，重复调用会重复注册。
+    pub fn register_defaults(&self) {
+        self.register("logs", Phase::Flush, FLUSH_TIMEOUT, || async {
+            let flushed = tokio::task::spawn_blocking(rf_os::log::flush)
+                .await
+                .map_err(|e| RfError::Internal(format!("Log flush task failed: {}", e)))?;
+            tracing::debug!("Flushed {} log writer(s)", flushed);
+            Ok(())
+        });
+        self.register("trace", Phase::Flush, FLUSH_TIMEOUT, || async {
+            tokio::task::spawn_blocking(rf_net::trace::shutdown_tracing)
+                .await
+                .map_err(|e| RfError::Internal(format!("Trace shutdown task failed: {}", e)))?
+        });
+        self.register("pools", Phase::ClosePools, CLOSE_POOLS_TIMEOUT, || async {
+            let closed = InstanceManager::close_pools().await;
+            tracing::debug!("Closed {} pooled instance(s)", closed);
+            Ok(())
+        });
+    }
+
+    /// 已注册但尚未执行的钩子数量
+    pub fn len(&self) -> usize {
+        self.hooks.lock().unwrap().len()
+    }
+
+    /// 是否没有待执行的钩子
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 按优先级执行所有钩子
+    ///
+    /// 同一优先级的钩子并发执行，全部结束后才进入下一个优先级；钩子失败或超时不会中断关闭。
+    pub async fn run(&self) -> ShutdownReport {
+        let mut hooks = std::mem::take(&mut *self.hooks.lock().unwrap());
+        // 稳定排序：同一优先级保持注册顺序
+        hooks.sort_by_key(|registration| registration.priority);
+        let budget = *self.budget.lock().unwrap();
+        let started = Instant::now();
+        let deadline = started + budget;
+        tracing::info!("Shutting down: {} hook(s), budget {:?}", hooks.len(), budget);
+
+        let mut report = ShutdownReport::default();
+        let mut hooks = hooks.into_iter().peekable();
+        while let Some(first) = hooks.next() {
+            let priority = first.priority;
+            let mut group = vec![first];
+            while let Some(next) = hooks.next_if(|registration| registration.priority == priority) {
+                group.push(next);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                for registration in group {
+                    tracing::warn!("Skipped shutdown hook '{}': budget exhausted", registration.name);
+                    report.hooks.push(HookReport {
+                        name: registration.name,
+                        priority,
+                        elapsed: Duration::ZERO,
+                        outcome: HookOutcome::Skipped,
+                    });
+                }
+                continue;
+            }
+
+            let mut tasks = Vec::with_capacity(group.len());
+            for registration in group {
+                let limit = registration.timeout.min(remaining);
+                tracing::info!("Running shutdown hook '{}' (priority {}, timeout {:?})", registration.name, priority, limit);
+                let future = (registration.hook)();
+                let task = tokio::spawn(async move {
+                    let started = Instant::now();
+                    let result = tokio::time::timeout(limit, future).await;
+                    (started.elapsed(), result)
+                });
+                tasks.push((registration.name, task));
+            }
+            for (name, task) in tasks {
+                let (elapsed, outcome) = match task.await {
+                    Ok((elapsed, Ok(Ok(())))) => {
+                        tracing::info!("Shutdown hook '{}' completed in {:?}", name, elapsed);
+                        (elapsed, HookOutcome::Completed)
+                    }
+                    Ok((elapsed, Ok(Err(e)))) => {
+                        tracing::error!("Shutdown hook '{}' failed after {:?}: {}", name, elapsed, e);
+                        (elapsed, HookOutcome::Failed(e.to_string()))
+                    }
+                    Ok((elapsed, Err(_))) => {
+                        tracing::warn!("Shutdown hook '{}' timed out after {:?}", name, elapsed);
+                        (elapsed, HookOutcome::TimedOut)
+                    }
+                    Err(e) => {
+                        tracing::error!("Shutdown hook '{}' panicked: {}", name, e);
+                        (Duration::ZERO, HookOutcome::Failed(e.to_string()))
+                    }
+                };
+                report.hooks.push(HookReport { name, priority, elapsed, outcome });
+            }
+        }
+
+        report.elapsed = started.elapsed();
+        tracing::info!("Shutdown finished in {:?}", report.elapsed);
+        report
+    }
+
+    /// 等待 Ctrl+C（Unix 上还包括 SIGTERM），然后执行所有钩子
+    ///
+    /// # 错误
+    ///
+    /// 信号监听失败时返回 `RfError::Internal`
+    pub async fn wait_and_run(&self) -> Result<ShutdownReport> {
+        wait_for_signal().await?;
+        Ok(self.run().await)
+    }
+}
+
+/// 等待 Ctrl+C 或 SIGTERM
+async fn wait_for_signal() -> Result<()> {
+    let signal_error = |e: std::io::Error| RfError::Internal(format!("Failed to wait for shutdown: {}", e));
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .map_err(signal_error)?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result.map_err(signal_error),
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await.map_err(signal_error)
+    }
+}
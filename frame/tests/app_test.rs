@@ -0,0 +1,66 @@
+//! # app_test
+//!
+//! app_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Eager initialization and instance status tests
+
+#[cfg(test)]
+mod tests {
+    use rf_frame::{gins, App};
+    use std::sync::OnceLock;
+    use tempfile::TempDir;
+
+    /// Install one config for the whole test binary: gins instances are global
+    fn setup() {
+        static DIR: OnceLock<TempDir> = OnceLock::new();
+        let dir = DIR.get_or_init(|| {
+            let dir = TempDir::new().unwrap();
+            let url = format!("sqlite://{}?mode=rwc", dir.path().join("main.db").display());
+            let broken = format!("sqlite://{}?mode=rw", dir.path().join("missing").join("broken.db").display());
+            let config = rf_os::cfg::Config::new().with_defaults([
+                ("database.main.url".to_string(), url),
+                ("database.broken.url".to_string(), broken),
+            ]);
+            gins::set_config(None, config);
+            dir
+        });
+        assert!(dir.path().exists());
+    }
+
+    #[tokio::test]
+    async fn test_warm_named_instance() {
+        setup();
+        let statuses = App::new().warm("database.main").start().await.unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].name, "database.main");
+        assert_eq!(statuses[0].kind, "database");
+        assert!(statuses[0].healthy);
+        assert!(statuses[0].latency_ms.is_some());
+
+        let status = gins::status().await;
+        let main = status.iter().find(|s| s.name == "database.main").unwrap();
+        assert!(main.healthy, "{:?}", main.error);
+    }
+
+    #[tokio::test]
+    async fn test_eager_fails_fast_on_misconfiguration() {
+        setup();
+        let err = App::new().eager(true).start().await.unwrap_err().to_string();
+        assert!(err.contains("database.broken"), "{}", err);
+        assert!(!err.contains("database.main"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_by_default_and_invalid_targets() {
+        setup();
+        assert!(App::new().start().await.unwrap().is_empty());
+
+        let err = App::new().warm("cache.default").start().await.unwrap_err().to_string();
+        assert!(err.contains("instance type must be one of"), "{}", err);
+        let err = App::new().warm("database").start().await.unwrap_err().to_string();
+        assert!(err.contains("expected <type>.<name>"), "{}", err);
+    }
+}
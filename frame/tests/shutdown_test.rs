@@ -0,0 +1,137 @@
+//! # shutdown_test
+//!
+//! shutdown_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Shutdown ordering, timeout, budget and built-in hook tests
+
+#[cfg(test)]
+mod tests {
+    use rf_errors::RfError;
+    use rf_frame::shutdown::HookOutcome;
+    use rf_frame::gins::{self, InstanceManager};
+    use rf_frame::{g, Phase, Shutdown};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn record(log: &Arc<Mutex<Vec<String>>>, name: &str) -> impl FnOnce() -> std::future::Ready<rf_errors::Result<()>> {
+        let log = log.clone();
+        let name = name.to_string();
+        move || {
+            log.lock().unwrap().push(name);
+            std::future::ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runs_by_priority() {
+        let shutdown = Shutdown::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let timeout = Duration::from_secs(1);
+        shutdown.register("pools", Phase::ClosePools, timeout, record(&log, "pools"));
+        shutdown.register("http", Phase::StopTraffic, timeout, record(&log, "http"));
+        shutdown.register("logs", Phase::Flush, timeout, record(&log, "logs"));
+        shutdown.register("jobs", Phase::DrainJobs, timeout, record(&log, "jobs"));
+        shutdown.register("outbox", 250u32, timeout, record(&log, "outbox"));
+        assert_eq!(shutdown.len(), 5);
+
+        let report = shutdown.run().await;
+        assert!(report.is_clean());
+        assert_eq!(*log.lock().unwrap(), ["http", "jobs", "outbox", "logs", "pools"]);
+        let names: Vec<_> = report.hooks.iter().map(|hook| hook.name.as_str()).collect();
+        assert_eq!(names, ["http", "jobs", "outbox", "logs", "pools"]);
+
+        // Hooks run once
+        assert!(shutdown.is_empty());
+        assert!(shutdown.run().await.hooks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failures_and_timeouts_do_not_stop_shutdown() {
+        let shutdown = Shutdown::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        shutdown.register("slow", Phase::StopTraffic, Duration::from_millis(50), || async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        });
+        shutdown.register("broken", Phase::DrainJobs, Duration::from_secs(1), || async {
+            Err(RfError::Internal("queue unavailable".to_string()))
+        });
+        shutdown.register("pools", Phase::ClosePools, Duration::from_secs(1), record(&log, "pools"));
+
+        let report = shutdown.run().await;
+        assert!(!report.is_clean());
+        let outcomes: Vec<_> = report.hooks.iter().map(|hook| hook.outcome.clone()).collect();
+        assert_eq!(outcomes[0], HookOutcome::TimedOut);
+        assert!(matches!(&outcomes[1], HookOutcome::Failed(e) if e.contains("queue unavailable")));
+        assert_eq!(outcomes[2], HookOutcome::Completed);
+        assert_eq!(*log.lock().unwrap(), ["pools"]);
+        assert!(report.elapsed < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_budget_caps_hooks() {
+        let shutdown = Shutdown::new();
+        shutdown.set_budget(Duration::from_millis(100));
+        let log = Arc::new(Mutex::new(Vec::new()));
+        // Same priority: both run concurrently and share the remaining budget
+        for name in ["a", "b"] {
+            shutdown.register(name, Phase::DrainJobs, Duration::from_secs(10), || async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            });
+        }
+        shutdown.register("pools", Phase::ClosePools, Duration::from_secs(10), record(&log, "pools"));
+
+        let report = shutdown.run().await;
+        assert!(report.elapsed < Duration::from_secs(1));
+        let outcomes: Vec<_> = report.hooks.iter().map(|hook| hook.outcome.clone()).collect();
+        assert_eq!(outcomes, [HookOutcome::TimedOut, HookOutcome::TimedOut, HookOutcome::Skipped]);
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_serve_stops_server_in_stop_traffic() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let shutdown = Shutdown::new();
+        shutdown.serve("http", g::server(addr).shutdown_timeout(Duration::from_secs(1)), Duration::from_secs(5));
+
+        let mut listening = false;
+        for _ in 0..100 {
+            if tokio::net::TcpStream::connect(addr).await.is_ok() {
+                listening = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(listening);
+
+        let report = shutdown.run().await;
+        assert!(report.is_clean(), "{:?}", report);
+        assert_eq!(report.hooks[0].name, "http");
+        assert_eq!(report.hooks[0].priority, Phase::StopTraffic.priority());
+        assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_default_hooks_flush_and_close_pools() {
+        let dir = TempDir::new().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("app.db").display());
+        gins::set_config(None, rf_os::cfg::Config::new().with_defaults([("database.app.url".to_string(), url)]));
+        let db = gins::database(Some("app")).await.unwrap();
+        assert!(!db.is_closed());
+
+        let shutdown = Shutdown::new();
+        shutdown.register_defaults();
+        let report = shutdown.run().await;
+        assert!(report.is_clean(), "{:?}", report);
+        let names: Vec<_> = report.hooks.iter().map(|hook| hook.name.as_str()).collect();
+        assert_eq!(names, ["logs", "trace", "pools"]);
+
+        assert!(db.is_closed());
+        assert!(!InstanceManager::names().contains(&"database.app".to_string()));
+    }
+}
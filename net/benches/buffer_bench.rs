@@ -0,0 +1,65 @@
+//! # Buffer Pool Benchmark Tests
+//!
+//! Benchmark tests for pooled request bodies and TCP frames, each against a
+//! pool that keeps no buffers so every request allocates
+
+use axum::body::Body;
+use bytes::Bytes;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rf_net::{BufferPool, FrameCodec};
+use tokio::runtime::Runtime;
+
+/// Bodies arrive in four chunks
+fn chunked_body(size: usize) -> Body {
+    let chunk = Bytes::from(vec![b'x'; size / 4]);
+    let chunks: Vec<Result<Bytes, std::io::Error>> = (0..4).map(|_| Ok(chunk.clone())).collect();
+    Body::from_stream(futures_util::stream::iter(chunks))
+}
+
+fn pools() -> [(&'static str, BufferPool); 2] {
+    [("unpooled", BufferPool::new().max_buffers(0)), ("pooled", BufferPool::new())]
+}
+
+fn bench_read_body(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("read_body");
+    for size in [4 * 1024, 64 * 1024, 512 * 1024] {
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("to_bytes", size), &size, |b, &size| {
+            b.iter(|| rt.block_on(async { black_box(axum::body::to_bytes(chunked_body(size), usize::MAX).await.unwrap()) }));
+        });
+        for (name, pool) in pools() {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                b.iter(|| rt.block_on(async { black_box(pool.read_body(chunked_body(size), usize::MAX).await.unwrap()) }));
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_frames(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("frame_roundtrip");
+    for size in [1024, 64 * 1024] {
+        let payload = vec![b'x'; size];
+        group.throughput(Throughput::Bytes(size as u64 * 100));
+        for (name, pool) in pools() {
+            let codec = FrameCodec::new().buffer_pool(pool);
+            group.bench_with_input(BenchmarkId::new(name, size), &payload, |b, payload| {
+                b.iter(|| {
+                    rt.block_on(async {
+                        let (mut writer, mut reader) = tokio::io::duplex(256 * 1024);
+                        for _ in 0..100 {
+                            codec.write_frame(&mut writer, payload).await.unwrap();
+                            black_box(codec.read_frame(&mut reader).await.unwrap());
+                        }
+                    })
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_read_body, bench_frames);
+criterion_main!(benches);
@@ -0,0 +1,370 @@
+//! # breaker
+//!
+//! breaker 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Circuit breaker
+//!
+//! The breaker opens after `failure_threshold` consecutive failures or, with
+//! a rolling window, when the failure rate of the last calls reaches
+//! `failure_rate_threshold`. After `timeout` it lets probe calls through
+//! (half-open) and closes again after `success_threshold` successful probes.
+//!
+//! Calls and transitions are reported through `rf_os::metric`, labelled with
+//! the breaker's name:
+//!
+//! - `circuit_breaker_calls_total{breaker,outcome}` with outcome `success`, `failure` or `rejected`
+//! - `circuit_breaker_transitions_total{breaker,to}`
+//! - `circuit_breaker_state{breaker}`: 0 closed, 1 open, 2 half-open
+
+use rf_errors::{Result, RfError};
+use rf_os::metric::{self, MetricLabels};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Circuit breaker state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Normal operation
+    Closed,
+    /// Failing, reject requests
+    Open,
+    /// Testing if service recovered
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+
+    /// Value of the `circuit_breaker_state` gauge
+    pub fn as_gauge(&self) -> f64 {
+        match self {
+            CircuitState::Closed => 0.0,
+            CircuitState::Open => 1.0,
+            CircuitState::HalfOpen => 2.0,
+        }
+    }
+}
+
+impl std::fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A state transition, passed to `on_state_change` subscribers
+#[derive(Debug, Clone)]
+pub struct StateChange {
+    pub breaker: String,
+    pub from: CircuitState,
+    pub to: CircuitState,
+    pub at: SystemTime,
+    /// Failure rate of the rolling window when the transition happened
+    pub failure_rate: Option<f64>,
+}
+
+/// Counters of a circuit breaker
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitBreakerMetrics {
+    pub state: CircuitState,
+    pub successes: u64,
+    pub failures: u64,
+    /// Calls rejected while open or over the half-open probe limit
+    pub rejected: u64,
+    pub consecutive_failures: u32,
+    /// Failure rate of the rolling window, if one is configured and not empty
+    pub failure_rate: Option<f64>,
+}
+
+type StateChangeCallback = Arc<dyn Fn(&StateChange) + Send + Sync>;
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    half_open_successes: u32,
+    half_open_in_flight: u32,
+    opened_at: Option<Instant>,
+    window: VecDeque<bool>,
+    successes: u64,
+    failures: u64,
+    rejected: u64,
+}
+
+impl BreakerState {
+    fn failure_rate(&self) -> Option<f64> {
+        if self.window.is_empty() {
+            return None;
+        }
+        let failures = self.window.iter().filter(|ok| !**ok).count();
+        Some(failures as f64 / self.window.len() as f64)
+    }
+}
+
+/// Circuit breaker
+pub struct CircuitBreaker {
+    name: String,
+    failure_threshold: u32,
+    success_threshold: u32,
+    timeout: Duration,
+    half_open_max_calls: Option<u32>,
+    window_size: usize,
+    failure_rate_threshold: Option<(f64, usize)>,
+    state: Mutex<BreakerState>,
+    subscribers: Mutex<Vec<StateChangeCallback>>,
+}
+
+impl CircuitBreaker {
+    /// Open after `failure_threshold` consecutive failures, probe again after `timeout`
+    pub fn new(failure_threshold: u32, timeout: Duration) -> Self {
+        Self {
+            name: "default".to_string(),
+            failure_threshold,
+            success_threshold: 3,
+            timeout,
+            half_open_max_calls: None,
+            window_size: 0,
+            failure_rate_threshold: None,
+            state: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                half_open_successes: 0,
+                half_open_in_flight: 0,
+                opened_at: None,
+                window: VecDeque::new(),
+                successes: 0,
+                failures: 0,
+                rejected: 0,
+            }),
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Name used as the `breaker` metric label (default `default`)
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Successful probes needed to close from half-open (default 3)
+    pub fn success_threshold(mut self, threshold: u32) -> Self {
+        self.success_threshold = threshold.max(1);
+        self
+    }
+
+    /// Concurrent probe calls allowed while half-open, others are rejected (default unlimited)
+    pub fn half_open_max_calls(mut self, max: u32) -> Self {
+        self.half_open_max_calls = Some(max.max(1));
+        self
+    }
+
+    /// Track the outcome of the last `size` calls for failure-rate tripping
+    pub fn rolling_window(mut self, size: usize) -> Self {
+        self.window_size = size;
+        self
+    }
+
+    /// Open when the rolling window's failure rate reaches `rate` (0.0-1.0)
+    ///
+    /// Only applies once the window holds at least `minimum_calls` outcomes.
+    /// Without `rolling_window`, a window of 100 calls is used.
+    pub fn failure_rate_threshold(mut self, rate: f64, minimum_calls: usize) -> Self {
+        self.failure_rate_threshold = Some((rate.clamp(0.0, 1.0), minimum_calls.max(1)));
+        if self.window_size == 0 {
+            self.window_size = 100;
+        }
+        self
+    }
+
+    /// Subscribe to state transitions
+    ///
+    /// Callbacks run synchronously on the task that caused the transition.
+    pub fn on_state_change<F>(&self, callback: F)
+    where
+        F: Fn(&StateChange) + Send + Sync + 'static,
+    {
+        self.subscribers.lock().unwrap().push(Arc::new(callback));
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Current state, without moving an expired open breaker to half-open
+    pub fn state(&self) -> CircuitState {
+        self.state.lock().unwrap().state
+    }
+
+    /// Snapshot of the counters
+    pub fn metrics(&self) -> CircuitBreakerMetrics {
+        let state = self.state.lock().unwrap();
+        CircuitBreakerMetrics {
+            state: state.state,
+            successes: state.successes,
+            failures: state.failures,
+            rejected: state.rejected,
+            consecutive_failures: state.consecutive_failures,
+            failure_rate: state.failure_rate(),
+        }
+    }
+
+    pub async fn call<F, Fut, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let probe = self.acquire()?;
+        let result = f().await;
+        self.complete(probe, result.is_ok());
+        result
+    }
+
+    /// Admit a call, returns whether it is a half-open probe
+    fn acquire(&self) -> Result<bool> {
+        let mut change = None;
+        let admitted = {
+            let mut state = self.state.lock().unwrap();
+            if state.state == CircuitState::Open {
+                if state.opened_at.is_some_and(|at| at.elapsed() >= self.timeout) {
+                    change = self.transition(&mut state, CircuitState::HalfOpen);
+                } else {
+                    state.rejected += 1;
+                }
+            }
+            match state.state {
+                CircuitState::Closed => Some(false),
+                CircuitState::Open => None,
+                CircuitState::HalfOpen => {
+                    if self.half_open_max_calls.is_some_and(|max| state.half_open_in_flight >= max) {
+                        state.rejected += 1;
+                        None
+                    } else {
+                        state.half_open_in_flight += 1;
+                        Some(true)
+                    }
+                }
+            }
+        };
+        self.notify(change);
+        match admitted {
+            Some(probe) => Ok(probe),
+            None => {
+                self.record_call("rejected");
+                Err(RfError::Network("Circuit breaker is open".to_string()))
+            }
+        }
+    }
+
+    fn complete(&self, probe: bool, success: bool) {
+        let change = {
+            let mut state = self.state.lock().unwrap();
+            if probe {
+                state.half_open_in_flight = state.half_open_in_flight.saturating_sub(1);
+            }
+            if self.window_size > 0 {
+                if state.window.len() == self.window_size {
+                    state.window.pop_front();
+                }
+                state.window.push_back(success);
+            }
+            if success {
+                state.successes += 1;
+                state.consecutive_failures = 0;
+                if probe && state.state == CircuitState::HalfOpen {
+                    state.half_open_successes += 1;
+                    if state.half_open_successes >= self.success_threshold {
+                        self.transition(&mut state, CircuitState::Closed)
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                }
+            } else {
+                state.failures += 1;
+                state.consecutive_failures += 1;
+                match state.state {
+                    CircuitState::HalfOpen if probe => self.transition(&mut state, CircuitState::Open),
+                    CircuitState::Closed if self.should_trip(&state) => self.transition(&mut state, CircuitState::Open),
+                    _ => None,
+                }
+            }
+        };
+        self.record_call(if success { "success" } else { "failure" });
+        self.notify(change);
+    }
+
+    fn should_trip(&self, state: &BreakerState) -> bool {
+        if self.failure_threshold > 0 && state.consecutive_failures >= self.failure_threshold {
+            return true;
+        }
+        match (self.failure_rate_threshold, state.failure_rate()) {
+            (Some((threshold, minimum_calls)), Some(rate)) => state.window.len() >= minimum_calls && rate >= threshold,
+            _ => false,
+        }
+    }
+
+    fn transition(&self, state: &mut BreakerState, to: CircuitState) -> Option<StateChange> {
+        let from = state.state;
+        if from == to {
+            return None;
+        }
+        state.state = to;
+        match to {
+            CircuitState::Open => state.opened_at = Some(Instant::now()),
+            CircuitState::HalfOpen => {
+                state.half_open_successes = 0;
+                state.half_open_in_flight = 0;
+            }
+            CircuitState::Closed => {
+                state.consecutive_failures = 0;
+                state.opened_at = None;
+                // Start over so failures from before the outage do not trip it again
+                state.window.clear();
+            }
+        }
+        Some(StateChange {
+            breaker: self.name.clone(),
+            from,
+            to,
+            at: SystemTime::now(),
+            failure_rate: state.failure_rate(),
+        })
+    }
+
+    fn record_call(&self, outcome: &str) {
+        let labels = MetricLabels::new().with_label("breaker", &self.name).with_label("outcome", outcome);
+        metric::counter_inc_with_key(labels.to_key("circuit_breaker_calls_total"), 1);
+    }
+
+    fn notify(&self, change: Option<StateChange>) {
+        let Some(change) = change else {
+            return;
+        };
+        let labels = MetricLabels::new().with_label("breaker", &self.name);
+        metric::gauge_set_with_key(labels.to_key("circuit_breaker_state"), change.to.as_gauge());
+        let labels = labels.with_label("to", change.to.as_str());
+        metric::counter_inc_with_key(labels.to_key("circuit_breaker_transitions_total"), 1);
+        tracing::info!(breaker = %change.breaker, from = %change.from, to = %change.to, "Circuit breaker state changed");
+
+        let subscribers: Vec<StateChangeCallback> = self.subscribers.lock().unwrap().clone();
+        for subscriber in subscribers {
+            subscriber(&change);
+        }
+    }
+}
+
+impl std::fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CircuitBreaker").field("name", &self.name).field("state", &self.state()).finish()
+    }
+}
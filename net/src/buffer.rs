@@ -0,0 +1,307 @@
+//! # buffer
+//!
+//! buffer 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Reusable byte buffers for request bodies, uploads and TCP frames
+//!
+//! A `BufferPool` keeps released `BytesMut` buffers and hands them out
+//! again, so the hot paths do not allocate and grow a fresh buffer for every
+//! request or frame. Buffers return to the pool when the `PooledBuffer` is
+//! dropped. Bytes split off with `PooledBuffer::into_bytes` share the
+//! buffer's allocation; it is reused once they are dropped too.
+//!
+//! The pool bounds what it keeps: at most `max_buffers` idle buffers, none
+//! larger than `max_capacity`. Larger buffers are freed instead, so one big
+//! upload does not pin its memory.
+//!
+//! `BufferPool::global()` is used by `Request::parse`, the envelope and
+//! webhook middlewares, retried proxy requests, `StreamingUpload` and
+//! `FrameCodec`. `HttpServer::with_metrics` exports its statistics:
+//!
+//! - `buffer_pool_acquired_total`: buffers handed out, labeled `pool`
+//! - `buffer_pool_reused_total`: of those, taken from the idle list
+//! - `buffer_pool_discarded_total`: buffers freed instead of kept
+//! - `buffer_pool_idle_buffers`: buffers waiting to be reused
+//!
+//! ```ignore
+//! let pool = BufferPool::global();
+//! let body = pool.read_body(request.into_body(), 64 * 1024).await?;
+//!
+//! let mut buffer = pool.get();
+//! buffer.extend_from_slice(b"header");
+//! socket.write_all(&buffer).await?;
+//! ```
+
+use axum::body::Body;
+use bytes::{Bytes, BytesMut};
+use http_body::Body as _;
+use http_body_util::BodyExt;
+use rf_errors::{Result, RfError};
+use rf_os::metric::MetricRegistry;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Default capacity of a new buffer, 8 KiB
+const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+/// Default number of idle buffers kept
+const DEFAULT_MAX_BUFFERS: usize = 128;
+/// Default largest buffer kept, 1 MiB
+const DEFAULT_MAX_CAPACITY: usize = 1024 * 1024;
+
+#[derive(Debug, Default)]
+struct Shared {
+    idle: Mutex<Vec<BytesMut>>,
+    acquired: AtomicU64,
+    reused: AtomicU64,
+    discarded: AtomicU64,
+}
+
+/// Pool of reusable byte buffers
+///
+/// Clones share the idle buffers and statistics.
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+    buffer_size: usize,
+    max_buffers: usize,
+    max_capacity: usize,
+    shared: Arc<Shared>,
+}
+
+/// Statistics of a `BufferPool`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufferPoolStats {
+    /// Buffers handed out
+    pub acquired: u64,
+    /// Buffers handed out from the idle list instead of allocated
+    pub reused: u64,
+    /// Buffers freed on release because the pool was full or they were too large
+    pub discarded: u64,
+    /// Buffers waiting to be reused
+    pub idle: usize,
+}
+
+impl BufferPoolStats {
+    /// Share of buffers that were reused, 0 before the first one
+    pub fn hit_ratio(&self) -> f64 {
+        if self.acquired == 0 {
+            0.0
+        } else {
+            self.reused as f64 / self.acquired as f64
+        }
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self {
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            max_buffers: DEFAULT_MAX_BUFFERS,
+            max_capacity: DEFAULT_MAX_CAPACITY,
+            shared: Arc::new(Shared::default()),
+        }
+    }
+}
+
+impl BufferPool {
+    /// Pool of 8 KiB buffers keeping up to 128 idle buffers of at most 1 MiB
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pool shared by the HTTP server, uploads and TCP frames
+    pub fn global() -> &'static BufferPool {
+        static GLOBAL: OnceLock<BufferPool> = OnceLock::new();
+        GLOBAL.get_or_init(BufferPool::new)
+    }
+
+    /// Capacity every buffer starts with
+    pub fn buffer_size(mut self, bytes: usize) -> Self {
+        self.buffer_size = bytes;
+        self
+    }
+
+    /// Idle buffers kept; more are freed on release
+    pub fn max_buffers(mut self, count: usize) -> Self {
+        self.max_buffers = count;
+        self
+    }
+
+    /// Largest buffer kept; larger ones are freed on release
+    pub fn max_capacity(mut self, bytes: usize) -> Self {
+        self.max_capacity = bytes;
+        self
+    }
+
+    /// An empty buffer with at least `buffer_size` capacity
+    pub fn get(&self) -> PooledBuffer {
+        self.get_with_capacity(self.buffer_size)
+    }
+
+    /// An empty buffer with at least `capacity` bytes of room
+    pub fn get_with_capacity(&self, capacity: usize) -> PooledBuffer {
+        self.shared.acquired.fetch_add(1, Ordering::Relaxed);
+        let idle = self.shared.idle.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        let buffer = match idle {
+            Some(mut buffer) => {
+                self.shared.reused.fetch_add(1, Ordering::Relaxed);
+                // Reclaims the allocation when the bytes split off it are gone
+                buffer.reserve(capacity.max(self.buffer_size));
+                buffer
+            }
+            None => BytesMut::with_capacity(capacity.max(self.buffer_size)),
+        };
+        PooledBuffer {
+            buffer,
+            pool: self.clone(),
+            detached: false,
+        }
+    }
+
+    /// Current statistics
+    pub fn stats(&self) -> BufferPoolStats {
+        BufferPoolStats {
+            acquired: self.shared.acquired.load(Ordering::Relaxed),
+            reused: self.shared.reused.load(Ordering::Relaxed),
+            discarded: self.shared.discarded.load(Ordering::Relaxed),
+            idle: self.shared.idle.lock().unwrap_or_else(|e| e.into_inner()).len(),
+        }
+    }
+
+    /// Free all idle buffers
+    pub fn clear(&self) {
+        self.shared.idle.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Read a whole body of at most `limit` bytes
+    ///
+    /// A body arriving in one chunk is returned as is; chunked bodies are
+    /// gathered in a pooled buffer instead of a growing `Vec`.
+    pub async fn read_body(&self, mut body: Body, limit: usize) -> Result<Bytes> {
+        let mut first: Option<Bytes> = None;
+        let mut buffer: Option<PooledBuffer> = None;
+        let mut len = 0usize;
+        while let Some(frame) = body.frame().await {
+            let frame = frame.map_err(|e| RfError::Network(format!("Failed to read body: {}", e)))?;
+            let Ok(data) = frame.into_data() else {
+                continue;
+            };
+            len += data.len();
+            if len > limit {
+                return Err(RfError::Network(format!("Body exceeds the limit of {} bytes", limit)));
+            }
+            match (buffer.as_mut(), first.take()) {
+                (Some(buffer), _) => buffer.extend_from_slice(&data),
+                (None, None) => first = Some(data),
+                (None, Some(head)) => {
+                    let expected = len.saturating_add(body.size_hint().lower() as usize).min(limit);
+                    let mut pooled = self.get_with_capacity(expected);
+                    pooled.extend_from_slice(&head);
+                    pooled.extend_from_slice(&data);
+                    buffer = Some(pooled);
+                }
+            }
+        }
+        Ok(match (buffer, first) {
+            (Some(buffer), _) => buffer.into_bytes(),
+            (None, Some(first)) => first,
+            (None, None) => Bytes::new(),
+        })
+    }
+
+    fn release(&self, mut buffer: BytesMut) {
+        buffer.clear();
+        if buffer.capacity() <= self.max_capacity {
+            let mut idle = self.shared.idle.lock().unwrap_or_else(|e| e.into_inner());
+            if idle.len() < self.max_buffers {
+                idle.push(buffer);
+                return;
+            }
+        }
+        self.shared.discarded.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// A buffer from a `BufferPool`, returned to it on drop
+///
+/// Dereferences to `BytesMut`.
+#[derive(Debug)]
+pub struct PooledBuffer {
+    buffer: BytesMut,
+    pool: BufferPool,
+    /// The allocation left with split bytes and is not returned
+    detached: bool,
+}
+
+impl PooledBuffer {
+    /// Take the written bytes, leaving the buffer empty for reuse
+    ///
+    /// The bytes share the buffer's allocation, except when they are larger
+    /// than the pool keeps; then the whole buffer goes with them.
+    pub fn split_bytes(&mut self) -> Bytes {
+        if self.buffer.len() > self.pool.max_capacity {
+            self.detached = true;
+            return std::mem::take(&mut self.buffer).freeze();
+        }
+        self.buffer.split().freeze()
+    }
+
+    /// The written bytes, returning the rest of the buffer to the pool
+    pub fn into_bytes(mut self) -> Bytes {
+        self.split_bytes()
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = BytesMut;
+
+    fn deref(&self) -> &BytesMut {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut BytesMut {
+        &mut self.buffer
+    }
+}
+
+impl AsRef<[u8]> for PooledBuffer {
+    fn as_ref(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if self.detached {
+            self.pool.shared.discarded.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.pool.release(std::mem::take(&mut self.buffer));
+        }
+    }
+}
+
+/// Export the statistics of `pool` as metrics labeled `pool = name`
+pub fn register_buffer_pool_metrics(registry: &MetricRegistry, name: &str, pool: &BufferPool) {
+    let (name, pool) = (name.to_string(), pool.clone());
+    registry.collector(&format!("buffer_pool:{}", name), move |registry| {
+        let stats = pool.stats();
+        let totals = [
+            ("buffer_pool_acquired_total", "Buffers handed out by the pool", stats.acquired),
+            ("buffer_pool_reused_total", "Buffers reused from the idle list", stats.reused),
+            ("buffer_pool_discarded_total", "Buffers freed instead of kept", stats.discarded),
+        ];
+        for (metric, help, value) in totals {
+            if let Ok(counter) = registry.counter_vec(metric, help, &["pool"]) {
+                counter.with(&[&name]).set_total(value as f64);
+            }
+        }
+        if let Ok(idle) = registry.gauge_vec("buffer_pool_idle_buffers", "Buffers waiting to be reused", &["pool"]) {
+            idle.with(&[&name]).set(stats.idle as f64);
+        }
+    });
+}
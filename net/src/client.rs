@@ -40,9 +40,11 @@
 //! }
 //! ```
 
+use crate::proxy::{Socks5Connector, Socks5HttpBridge};
 use reqwest::{Client, RequestBuilder, Response};
 use rf_errors::{Result, RfError};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// HTTP 客户端封装
 ///
@@ -52,9 +54,11 @@ use serde::{Deserialize, Serialize};
 ///
 /// - `client`: 底层的 reqwest Client 实例
 /// - `builder`: 可选的请求构建器，用于链式调用
+/// - `bridge`: 经由 SOCKS5 访问时使用的本地代理桥
 pub struct HttpClient {
     client: Client,
     builder: Option<RequestBuilder>,
+    bridge: Option<Arc<Socks5HttpBridge>>,
 }
 
 impl HttpClient {
@@ -73,9 +77,44 @@ impl HttpClient {
         Self {
             client: Client::new(),
             builder: None,
+            bridge: None,
         }
     }
 
+    /// 创建经由 SOCKS5 代理发送请求的客户端
+    ///
+    /// 目标主机名由代理解析。必须在 Tokio 运行时中调用。
+    ///
+    /// # 参数
+    ///
+    /// - `connector`: SOCKS5 连接器
+    ///
+    /// # 错误
+    ///
+    /// 如果本地代理桥启动失败，返回 RfError::Network 错误
+    ///
+    /// # 示例
+    ///
+    /// ```ignore
+    /// let socks = Socks5Connector::new("bastion:1080").with_auth("user", "secret");
+    /// let client = HttpClient::with_socks5(socks).await?;
+    /// let text = client.get("http://admin.internal/health").text().await?;
+    /// ```
+    pub async fn with_socks5(connector: Socks5Connector) -> Result<Self> {
+        let bridge = connector.http_bridge().await?;
+        let proxy = reqwest::Proxy::all(bridge.url())
+            .map_err(|e| RfError::Network(format!("Invalid proxy: {}", e)))?;
+        let client = Client::builder()
+            .proxy(proxy)
+            .build()
+            .map_err(|e| RfError::Network(format!("Failed to build HTTP client: {}", e)))?;
+        Ok(Self {
+            client,
+            builder: None,
+            bridge: Some(Arc::new(bridge)),
+        })
+    }
+
     /// 创建一个 GET 请求
     ///
     /// # 参数
@@ -97,6 +136,7 @@ impl HttpClient {
         Self {
             client: self.client.clone(),
             builder: Some(self.client.get(url)),
+            bridge: self.bridge.clone(),
         }
     }
 
@@ -122,6 +162,7 @@ impl HttpClient {
         Self {
             client: self.client.clone(),
             builder: Some(self.client.post(url)),
+            bridge: self.bridge.clone(),
         }
     }
 
@@ -195,7 +236,15 @@ impl HttpClient {
     /// ```
     pub async fn send(self) -> Result<Response> {
         let builder = self.builder.ok_or_else(|| RfError::Internal("No request builder".to_string()))?;
-        builder.send().await
+        let (client, request) = builder.build_split();
+        let mut request = request.map_err(|e| RfError::Network(format!("Request failed: {}", e)))?;
+        // 在请求作用域内调用时传递请求 ID
+        if let Some(id) = rf_core::ctx::request_id() {
+            if let Ok(value) = reqwest::header::HeaderValue::from_str(&id) {
+                request.headers_mut().entry(crate::http::REQUEST_ID_HEADER).or_insert(value);
+            }
+        }
+        client.execute(request).await
             .map_err(|e| RfError::Network(format!("Request failed: {}", e)))
     }
 
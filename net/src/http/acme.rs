@@ -0,0 +1,497 @@
+//! # acme
+//!
+//! acme 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Automatic certificates with ACME (Let's Encrypt)
+//!
+//! `AcmeManager` issues certificates with HTTP-01 or TLS-ALPN-01
+//! validation, keeps them in a `CertStorage` (files or Redis) and renews
+//! them before they expire. Renewed certificates are swapped into the
+//! running TLS acceptor without a restart.
+//!
+//! Until the first certificate is issued the server presents a temporary
+//! self-signed certificate.
+//!
+//! ```ignore
+//! let config = AcmeConfig::new(["example.com", "www.example.com"])
+//!     .contact("admin@example.com")
+//!     .agree_to_terms(true)
+//!     .http_challenge_addr("0.0.0.0:80".parse()?);
+//! let acme = Arc::new(AcmeManager::new(config, Arc::new(FileCertStorage::new("certs"))));
+//!
+//! HttpServer::new("0.0.0.0:443".parse()?)
+//!     .with_acme(acme)
+//!     .serve()
+//!     .await?;
+//! ```
+
+mod challenge;
+mod client;
+mod storage;
+
+pub use challenge::{AcmeChallenges, ACME_TLS_ALPN_PROTOCOL, HTTP01_PATH};
+#[cfg(feature = "acme-redis")]
+pub use storage::RedisCertStorage;
+pub use storage::{CertStorage, FileCertStorage};
+
+use super::tls::TlsAcceptorHandle;
+use client::{AccountKey, AcmeClient};
+use openssl::asn1::Asn1Time;
+use openssl::hash::MessageDigest;
+use openssl::stack::Stack;
+use openssl::x509::extension::SubjectAlternativeName;
+use openssl::x509::{X509NameBuilder, X509ReqBuilder, X509};
+use rf_errors::{Result, RfError};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Let's Encrypt production directory
+pub const LETS_ENCRYPT_PRODUCTION: &str = "https://acme-v02.api.letsencrypt.org/directory";
+/// Let's Encrypt staging directory (untrusted certificates, higher rate limits)
+pub const LETS_ENCRYPT_STAGING: &str = "https://acme-staging-v02.api.letsencrypt.org/directory";
+
+/// ACME validation method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcmeChallengeType {
+    /// Token served over HTTP on port 80
+    Http01,
+    /// Special certificate served over TLS on port 443
+    TlsAlpn01,
+}
+
+impl AcmeChallengeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AcmeChallengeType::Http01 => "http-01",
+            AcmeChallengeType::TlsAlpn01 => "tls-alpn-01",
+        }
+    }
+}
+
+/// ACME configuration
+#[derive(Debug, Clone)]
+pub struct AcmeConfig {
+    pub domains: Vec<String>,
+    pub contact: Vec<String>,
+    pub directory_url: String,
+    pub challenge: AcmeChallengeType,
+    /// Renew when the certificate expires within this period (default: 30 days)
+    pub renew_before: Duration,
+    /// How often to check for renewal (default: 12 hours)
+    pub check_interval: Duration,
+    /// Retry delay after a failed issuance (default: 1 hour)
+    pub retry_interval: Duration,
+    /// Poll interval while waiting for validation (default: 2 seconds)
+    pub poll_interval: Duration,
+    /// Maximum time to wait for validation and issuance (default: 5 minutes)
+    pub timeout: Duration,
+    /// Address of a plain HTTP listener for HTTP-01 challenges
+    pub http_challenge_addr: Option<SocketAddr>,
+    pub terms_agreed: bool,
+}
+
+impl AcmeConfig {
+    /// Create a configuration for the given domains (the first one names the certificate)
+    pub fn new<I, S>(domains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            domains: domains.into_iter().map(|d| d.into().to_ascii_lowercase()).collect(),
+            contact: Vec::new(),
+            directory_url: LETS_ENCRYPT_PRODUCTION.to_string(),
+            challenge: AcmeChallengeType::Http01,
+            renew_before: Duration::from_secs(30 * 24 * 3600),
+            check_interval: Duration::from_secs(12 * 3600),
+            retry_interval: Duration::from_secs(3600),
+            poll_interval: Duration::from_secs(2),
+            timeout: Duration::from_secs(300),
+            http_challenge_addr: None,
+            terms_agreed: false,
+        }
+    }
+
+    /// Add a contact e-mail address (or `mailto:` URI)
+    pub fn contact(mut self, contact: impl Into<String>) -> Self {
+        self.contact.push(contact.into());
+        self
+    }
+
+    /// Use another ACME directory, e.g. `LETS_ENCRYPT_STAGING`
+    pub fn directory(mut self, url: impl Into<String>) -> Self {
+        self.directory_url = url.into();
+        self
+    }
+
+    /// Select the validation method (default: HTTP-01)
+    pub fn challenge(mut self, challenge: AcmeChallengeType) -> Self {
+        self.challenge = challenge;
+        self
+    }
+
+    pub fn renew_before(mut self, period: Duration) -> Self {
+        self.renew_before = period;
+        self
+    }
+
+    pub fn check_interval(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+
+    pub fn retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Serve HTTP-01 challenges on a separate plain HTTP listener (usually port 80)
+    pub fn http_challenge_addr(mut self, addr: SocketAddr) -> Self {
+        self.http_challenge_addr = Some(addr);
+        self
+    }
+
+    /// Agree to the terms of service of the ACME provider (required)
+    pub fn agree_to_terms(mut self, agreed: bool) -> Self {
+        self.terms_agreed = agreed;
+        self
+    }
+}
+
+/// Certificate chain and private key as PEM
+#[derive(Debug, Clone)]
+pub struct CertificateBundle {
+    pub cert_pem: Vec<u8>,
+    pub key_pem: Vec<u8>,
+}
+
+impl CertificateBundle {
+    /// Time until the leaf certificate expires (zero if already expired)
+    pub fn expires_in(&self) -> Result<Duration> {
+        let cert = X509::from_pem(&self.cert_pem)
+            .map_err(|e| RfError::Config(format!("Invalid certificate: {}", e)))?;
+        let now = Asn1Time::days_from_now(0).map_err(|e| RfError::Internal(e.to_string()))?;
+        let diff = now
+            .diff(cert.not_after())
+            .map_err(|e| RfError::Internal(e.to_string()))?;
+        let secs = diff.days as i64 * 86400 + diff.secs as i64;
+        Ok(Duration::from_secs(secs.max(0) as u64))
+    }
+}
+
+/// Issues, stores and renews certificates
+pub struct AcmeManager {
+    config: AcmeConfig,
+    storage: Arc<dyn CertStorage>,
+    challenges: Arc<AcmeChallenges>,
+    http: reqwest::Client,
+}
+
+impl AcmeManager {
+    /// Create a manager
+    pub fn new(config: AcmeConfig, storage: Arc<dyn CertStorage>) -> Self {
+        Self {
+            config,
+            storage,
+            challenges: Arc::new(AcmeChallenges::new()),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Use a custom HTTP client for talking to the ACME server
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.http = client;
+        self
+    }
+
+    pub fn config(&self) -> &AcmeConfig {
+        &self.config
+    }
+
+    /// Pending challenge responses; serve `challenges().router()` on port 80
+    /// for HTTP-01, or use it as `TlsInterceptor` for TLS-ALPN-01
+    pub fn challenges(&self) -> Arc<AcmeChallenges> {
+        self.challenges.clone()
+    }
+
+    fn primary_domain(&self) -> Result<&str> {
+        self.config
+            .domains
+            .first()
+            .map(String::as_str)
+            .ok_or_else(|| RfError::Config("ACME requires at least one domain".to_string()))
+    }
+
+    fn cert_keys(&self) -> Result<(String, String)> {
+        let name = self.primary_domain()?.replace('*', "_");
+        Ok((format!("{}.crt", name), format!("{}.key", name)))
+    }
+
+    fn account_key_name(&self) -> String {
+        let digest = openssl::sha::sha256(self.config.directory_url.as_bytes());
+        let id: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("account-{}.key", id)
+    }
+
+    /// The stored certificate, if any
+    pub async fn load_certificate(&self) -> Result<Option<CertificateBundle>> {
+        let (cert_key, key_key) = self.cert_keys()?;
+        match (self.storage.load(&cert_key).await?, self.storage.load(&key_key).await?) {
+            (Some(cert_pem), Some(key_pem)) => Ok(Some(CertificateBundle { cert_pem, key_pem })),
+            _ => Ok(None),
+        }
+    }
+
+    async fn account(&self) -> Result<AcmeClient> {
+        if !self.config.terms_agreed {
+            return Err(RfError::Config(
+                "ACME terms of service must be agreed to (AcmeConfig::agree_to_terms)".to_string(),
+            ));
+        }
+        let name = self.account_key_name();
+        let key = match self.storage.load(&name).await? {
+            Some(pem) => AccountKey::from_pem(&pem)?,
+            None => {
+                let key = AccountKey::generate()?;
+                self.storage.store(&name, &key.to_pem()?).await?;
+                key
+            }
+        };
+        let mut client =
+            AcmeClient::connect(self.http.clone(), &self.config.directory_url, key, self.config.poll_interval).await?;
+        client.register(&self.config.contact).await?;
+        Ok(client)
+    }
+
+    /// Issue a new certificate now and store it
+    pub async fn issue(&self) -> Result<CertificateBundle> {
+        self.primary_domain()?;
+        tracing::info!("Requesting ACME certificate for {}", self.config.domains.join(", "));
+        let client = self.account().await?;
+        let thumbprint = client.thumbprint()?;
+        let (order_url, order) = client.new_order(&self.config.domains).await?;
+
+        for authz_url in &order.authorizations {
+            let authz = client.authorization(authz_url).await?;
+            if authz.status == "valid" {
+                continue;
+            }
+            let domain = authz.identifier.value.clone();
+            let challenge = authz
+                .challenges
+                .iter()
+                .find(|c| c.kind == self.config.challenge.as_str())
+                .ok_or_else(|| {
+                    RfError::Network(format!(
+                        "ACME server offers no {} challenge for {}",
+                        self.config.challenge.as_str(),
+                        domain
+                    ))
+                })?;
+            let key_authorization = format!("{}.{}", challenge.token, thumbprint);
+
+            match self.config.challenge {
+                AcmeChallengeType::Http01 => self.challenges.add_http01(&challenge.token, &key_authorization),
+                AcmeChallengeType::TlsAlpn01 => self.challenges.add_tls_alpn01(&domain, &key_authorization)?,
+            }
+            let result = async {
+                client.respond(&challenge.url).await?;
+                client.wait_authorization(authz_url, self.config.timeout).await
+            }
+            .await;
+            match self.config.challenge {
+                AcmeChallengeType::Http01 => self.challenges.remove_http01(&challenge.token),
+                AcmeChallengeType::TlsAlpn01 => self.challenges.remove_tls_alpn01(&domain),
+            }
+            result?;
+        }
+
+        let key = client::generate_ec_key()?;
+        let csr = build_csr(&self.config.domains, &key)
+            .map_err(|e| RfError::Internal(format!("Failed to create CSR: {}", e)))?;
+        client.finalize(&order, &csr).await?;
+        let order = client.wait_order(&order_url, self.config.timeout).await?;
+        let certificate_url = order
+            .certificate
+            .ok_or_else(|| RfError::Network("ACME order has no certificate".to_string()))?;
+        let bundle = CertificateBundle {
+            cert_pem: client.download(&certificate_url).await?,
+            key_pem: key
+                .private_key_to_pem_pkcs8()
+                .map_err(|e| RfError::Internal(e.to_string()))?,
+        };
+
+        let (cert_key, key_key) = self.cert_keys()?;
+        self.storage.store(&key_key, &bundle.key_pem).await?;
+        self.storage.store(&cert_key, &bundle.cert_pem).await?;
+        tracing::info!("Issued ACME certificate for {}", self.config.domains.join(", "));
+        Ok(bundle)
+    }
+
+    /// Start serving: returns an acceptor with the stored (or a temporary)
+    /// certificate and spawns issuance/renewal in the background
+    pub async fn start(self: &Arc<Self>) -> Result<AcmeHandle> {
+        let stored = self.load_certificate().await?;
+        let acceptor = match &stored {
+            Some(bundle) => match TlsAcceptorHandle::from_pem(&bundle.cert_pem, &bundle.key_pem) {
+                Ok(acceptor) => Some(acceptor),
+                Err(e) => {
+                    tracing::warn!("Stored ACME certificate is unusable, requesting a new one: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let has_certificate = acceptor.is_some();
+        let acceptor = Arc::new(match acceptor {
+            Some(acceptor) => acceptor,
+            None => {
+                let placeholder = self_signed(self.primary_domain()?)?;
+                TlsAcceptorHandle::from_pem(&placeholder.cert_pem, &placeholder.key_pem)?
+            }
+        });
+
+        let mut tasks = Vec::new();
+        if let Some(addr) = self.config.http_challenge_addr {
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .map_err(|e| RfError::Network(format!("Failed to bind ACME challenge listener: {}", e)))?;
+            let router = self.challenges.clone().router();
+            tasks.push(tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, router).await {
+                    tracing::error!("ACME challenge listener failed: {}", e);
+                }
+            }));
+        }
+
+        let manager = self.clone();
+        let renew_acceptor = acceptor.clone();
+        let current = if has_certificate { stored } else { None };
+        tasks.push(tokio::spawn(async move { manager.renew_loop(renew_acceptor, current).await }));
+
+        Ok(AcmeHandle { acceptor, tasks })
+    }
+
+    async fn renew_loop(&self, acceptor: Arc<TlsAcceptorHandle>, mut current: Option<CertificateBundle>) {
+        loop {
+            let due = match &current {
+                Some(bundle) => bundle
+                    .expires_in()
+                    .map(|remaining| remaining <= self.config.renew_before)
+                    .unwrap_or(true),
+                None => true,
+            };
+            let delay = if due {
+                match self.issue().await {
+                    Ok(bundle) => match acceptor.swap(&bundle.cert_pem, &bundle.key_pem) {
+                        Ok(()) => {
+                            current = Some(bundle);
+                            self.config.check_interval
+                        }
+                        Err(e) => {
+                            tracing::error!("Issued ACME certificate could not be loaded: {}", e);
+                            self.config.retry_interval
+                        }
+                    },
+                    Err(e) => {
+                        tracing::error!("ACME certificate issuance failed: {}", e);
+                        self.config.retry_interval
+                    }
+                }
+            } else {
+                // Another instance may have renewed it in shared storage
+                if let Ok(Some(stored)) = self.load_certificate().await {
+                    if stored.cert_pem != current.as_ref().map(|c| c.cert_pem.clone()).unwrap_or_default()
+                        && acceptor.swap(&stored.cert_pem, &stored.key_pem).is_ok()
+                    {
+                        current = Some(stored);
+                        continue;
+                    }
+                }
+                self.config.check_interval
+            };
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Running ACME tasks; stops them when dropped
+pub struct AcmeHandle {
+    acceptor: Arc<TlsAcceptorHandle>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl AcmeHandle {
+    /// Acceptor that always holds the current certificate
+    pub fn acceptor(&self) -> Arc<TlsAcceptorHandle> {
+        self.acceptor.clone()
+    }
+}
+
+impl Drop for AcmeHandle {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+fn build_csr(
+    domains: &[String],
+    key: &openssl::pkey::PKey<openssl::pkey::Private>,
+) -> std::result::Result<Vec<u8>, openssl::error::ErrorStack> {
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_text("CN", &domains[0])?;
+    let mut req = X509ReqBuilder::new()?;
+    req.set_subject_name(&name.build())?;
+    req.set_pubkey(key)?;
+    let mut san = SubjectAlternativeName::new();
+    for domain in domains {
+        san.dns(domain);
+    }
+    let mut extensions = Stack::new()?;
+    extensions.push(san.build(&req.x509v3_context(None))?)?;
+    req.add_extensions(&extensions)?;
+    req.sign(key, MessageDigest::sha256())?;
+    req.build().to_der()
+}
+
+/// Temporary certificate used until the first issuance completes
+fn self_signed(domain: &str) -> Result<CertificateBundle> {
+    let key = client::generate_ec_key()?;
+    let build = || -> std::result::Result<X509, openssl::error::ErrorStack> {
+        let mut name = X509NameBuilder::new()?;
+        name.append_entry_by_text("CN", domain)?;
+        let name = name.build();
+        let mut builder = X509::builder()?;
+        builder.set_version(2)?;
+        builder.set_subject_name(&name)?;
+        builder.set_issuer_name(&name)?;
+        builder.set_pubkey(&key)?;
+        let (not_before, not_after) = (Asn1Time::days_from_now(0)?, Asn1Time::days_from_now(1)?);
+        builder.set_not_before(&not_before)?;
+        builder.set_not_after(&not_after)?;
+        builder.sign(&key, MessageDigest::sha256())?;
+        Ok(builder.build())
+    };
+    let error = |e: openssl::error::ErrorStack| RfError::Internal(format!("Failed to create certificate: {}", e));
+    Ok(CertificateBundle {
+        cert_pem: build().map_err(error)?.to_pem().map_err(error)?,
+        key_pem: key.private_key_to_pem_pkcs8().map_err(error)?,
+    })
+}
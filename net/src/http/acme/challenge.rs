@@ -0,0 +1,292 @@
+//! # challenge
+//!
+//! challenge 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! ACME challenge responders (HTTP-01 and TLS-ALPN-01)
+
+use super::client::generate_ec_key;
+use crate::http::tls::crypto_provider;
+use crate::http::TlsInterceptor;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Router;
+use futures_util::future::BoxFuture;
+use openssl::asn1::{Asn1Object, Asn1OctetString, Asn1Time};
+use openssl::bn::BigNum;
+use openssl::hash::MessageDigest;
+use openssl::x509::extension::SubjectAlternativeName;
+use openssl::x509::{X509Extension, X509NameBuilder, X509};
+use rf_errors::{Result, RfError};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::sign::{CertifiedKey, SingleCertAndKey};
+use rustls::ServerConfig;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsAcceptor;
+
+/// ALPN protocol negotiated by TLS-ALPN-01 validation
+pub const ACME_TLS_ALPN_PROTOCOL: &[u8] = b"acme-tls/1";
+
+/// id-pe-acmeIdentifier (RFC 8737)
+const ACME_IDENTIFIER_OID: &str = "1.3.6.1.5.5.7.1.31";
+
+/// Path prefix for HTTP-01 responses
+pub const HTTP01_PATH: &str = "/.well-known/acme-challenge";
+
+/// Pending challenge responses
+#[derive(Default)]
+pub struct AcmeChallenges {
+    http01: RwLock<HashMap<String, String>>,
+    /// Handshake configs serving the self-signed validation certificates
+    tls_alpn01: Arc<RwLock<HashMap<String, Arc<ServerConfig>>>>,
+}
+
+impl AcmeChallenges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `key_authorization` for an HTTP-01 token
+    pub fn add_http01(&self, token: &str, key_authorization: &str) {
+        self.http01
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(token.to_string(), key_authorization.to_string());
+    }
+
+    pub fn remove_http01(&self, token: &str) {
+        self.http01.write().unwrap_or_else(|e| e.into_inner()).remove(token);
+    }
+
+    /// Key authorization for an HTTP-01 token
+    pub fn http01(&self, token: &str) -> Option<String> {
+        self.http01.read().unwrap_or_else(|e| e.into_inner()).get(token).cloned()
+    }
+
+    /// Serve a validation certificate for `domain` on `acme-tls/1` handshakes
+    pub fn add_tls_alpn01(&self, domain: &str, key_authorization: &str) -> Result<()> {
+        let error = |e: &dyn std::fmt::Display| RfError::Internal(format!("Failed to create TLS-ALPN-01 certificate: {}", e));
+        let key = generate_ec_key()?;
+        let cert = alpn_certificate(domain, key_authorization, &key).map_err(|e| error(&e))?;
+        let cert = CertificateDer::from(cert.to_der().map_err(|e| error(&e))?);
+        let key = PrivateKeyDer::from(PrivatePkcs8KeyDer::from(key.private_key_to_pkcs8().map_err(|e| error(&e))?));
+
+        // Not `with_single_cert`: webpki refuses the critical acmeIdentifier extension
+        let provider = crypto_provider();
+        let signing_key = provider.key_provider.load_private_key(key).map_err(|e| error(&e))?;
+        let certified = CertifiedKey::new(vec![cert], signing_key);
+        let mut config = ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| error(&e))?
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(SingleCertAndKey::from(certified)));
+        config.alpn_protocols = vec![ACME_TLS_ALPN_PROTOCOL.to_vec()];
+        self.tls_alpn01
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(domain.to_ascii_lowercase(), Arc::new(config));
+        Ok(())
+    }
+
+    pub fn remove_tls_alpn01(&self, domain: &str) {
+        self.tls_alpn01
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&domain.to_ascii_lowercase());
+    }
+
+    /// Router answering `GET /.well-known/acme-challenge/{token}`
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new().route(
+            &format!("{}/{{token}}", HTTP01_PATH),
+            axum::routing::get(move |Path(token): Path<String>| {
+                let challenges = self.clone();
+                async move {
+                    match challenges.http01(&token) {
+                        Some(key_authorization) => key_authorization.into_response(),
+                        None => StatusCode::NOT_FOUND.into_response(),
+                    }
+                }
+            }),
+        )
+    }
+}
+
+impl TlsInterceptor for AcmeChallenges {
+    fn intercept(&self, stream: TcpStream) -> BoxFuture<'static, Option<TcpStream>> {
+        let certificates = self.tls_alpn01.clone();
+        Box::pin(async move {
+            if certificates.read().unwrap_or_else(|e| e.into_inner()).is_empty() {
+                return Some(stream);
+            }
+            let hello = match peek_client_hello(&stream).await {
+                Some(hello) => hello,
+                None => return Some(stream),
+            };
+            if !hello.alpn.iter().any(|p| p == ACME_TLS_ALPN_PROTOCOL) {
+                return Some(stream);
+            }
+            let config = hello
+                .server_name
+                .and_then(|name| certificates.read().unwrap_or_else(|e| e.into_inner()).get(&name).cloned());
+            match config {
+                Some(config) => {
+                    if let Err(e) = answer_tls_alpn01(stream, config).await {
+                        tracing::warn!("TLS-ALPN-01 validation handshake failed: {}", e);
+                    }
+                    None
+                }
+                None => Some(stream),
+            }
+        })
+    }
+}
+
+fn alpn_certificate(
+    domain: &str,
+    key_authorization: &str,
+    key: &openssl::pkey::PKey<openssl::pkey::Private>,
+) -> std::result::Result<X509, openssl::error::ErrorStack> {
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_text("CN", domain)?;
+    let name = name.build();
+
+    let mut builder = X509::builder()?;
+    builder.set_version(2)?;
+    let serial = BigNum::from_u32(1)?.to_asn1_integer()?;
+    builder.set_serial_number(&serial)?;
+    builder.set_subject_name(&name)?;
+    builder.set_issuer_name(&name)?;
+    builder.set_pubkey(key)?;
+    let (not_before, not_after) = (Asn1Time::days_from_now(0)?, Asn1Time::days_from_now(1)?);
+    builder.set_not_before(&not_before)?;
+    builder.set_not_after(&not_after)?;
+    let san = SubjectAlternativeName::new().dns(domain).build(&builder.x509v3_context(None, None))?;
+    builder.append_extension(san)?;
+
+    // extnValue is the DER encoding of an OCTET STRING holding SHA-256(keyAuthorization)
+    let mut value = vec![0x04, 0x20];
+    value.extend_from_slice(&openssl::sha::sha256(key_authorization.as_bytes()));
+    let oid = Asn1Object::from_str(ACME_IDENTIFIER_OID)?;
+    let value = Asn1OctetString::new_from_bytes(&value)?;
+    builder.append_extension(X509Extension::new_from_der(&oid, true, &value)?)?;
+
+    builder.sign(key, MessageDigest::sha256())?;
+    Ok(builder.build())
+}
+
+/// Complete the validation handshake; the validator closes the connection afterwards
+async fn answer_tls_alpn01(stream: TcpStream, config: Arc<ServerConfig>) -> Result<()> {
+    let handshake = TlsAcceptor::from(config).accept(stream);
+    let mut tls = tokio::time::timeout(Duration::from_secs(10), handshake)
+        .await
+        .map_err(|_| RfError::Timeout("TLS-ALPN-01 handshake timed out".to_string()))?
+        .map_err(|e| RfError::Network(format!("TLS-ALPN-01: {}", e)))?;
+    let _ = tls.shutdown().await;
+    Ok(())
+}
+
+/// Fields of a TLS ClientHello needed to route validation handshakes
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ClientHello {
+    pub server_name: Option<String>,
+    pub alpn: Vec<Vec<u8>>,
+}
+
+/// Wait for the first TLS record without consuming it
+async fn peek_client_hello(stream: &TcpStream) -> Option<ClientHello> {
+    let mut buf = vec![0u8; 4096];
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    loop {
+        let n = tokio::time::timeout_at(deadline, stream.peek(&mut buf)).await.ok()?.ok()?;
+        if n == 0 {
+            return None;
+        }
+        if n >= 5 {
+            if buf[0] != 0x16 {
+                return None;
+            }
+            let record_len = 5 + u16::from_be_bytes([buf[3], buf[4]]) as usize;
+            if n >= record_len || n == buf.len() {
+                return parse_client_hello(&buf[5..n.min(record_len)]);
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return None;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+/// Parse a ClientHello handshake message
+pub(crate) fn parse_client_hello(data: &[u8]) -> Option<ClientHello> {
+    struct Reader<'a>(&'a [u8]);
+    impl<'a> Reader<'a> {
+        fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+            if self.0.len() < n {
+                return None;
+            }
+            let (head, tail) = self.0.split_at(n);
+            self.0 = tail;
+            Some(head)
+        }
+        fn u8(&mut self) -> Option<usize> {
+            self.take(1).map(|b| b[0] as usize)
+        }
+        fn u16(&mut self) -> Option<usize> {
+            self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+        }
+        fn vec8(&mut self) -> Option<&'a [u8]> {
+            let n = self.u8()?;
+            self.take(n)
+        }
+        fn vec16(&mut self) -> Option<&'a [u8]> {
+            let n = self.u16()?;
+            self.take(n)
+        }
+    }
+
+    let mut r = Reader(data);
+    if r.u8()? != 0x01 {
+        return None;
+    }
+    r.take(3)?; // handshake length
+    r.take(2 + 32)?; // version, random
+    r.vec8()?; // session id
+    r.vec16()?; // cipher suites
+    r.vec8()?; // compression methods
+
+    let mut hello = ClientHello::default();
+    let mut extensions = Reader(r.vec16().unwrap_or_default());
+    while !extensions.0.is_empty() {
+        let kind = extensions.u16()?;
+        let mut body = Reader(extensions.vec16()?);
+        match kind {
+            0x0000 => {
+                let mut names = Reader(body.vec16()?);
+                while !names.0.is_empty() {
+                    let name_type = names.u8()?;
+                    let name = names.vec16()?;
+                    if name_type == 0 {
+                        hello.server_name = Some(String::from_utf8_lossy(name).to_ascii_lowercase());
+                    }
+                }
+            }
+            0x0010 => {
+                let mut protocols = Reader(body.vec16()?);
+                while !protocols.0.is_empty() {
+                    hello.alpn.push(protocols.vec8()?.to_vec());
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(hello)
+}
@@ -0,0 +1,371 @@
+//! # client
+//!
+//! client 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! ACME protocol client (RFC 8555)
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::ecdsa::EcdsaSig;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use rf_errors::{Result, RfError};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+fn crypto_error(e: openssl::error::ErrorStack) -> RfError {
+    RfError::Internal(format!("Crypto error: {}", e))
+}
+
+pub(crate) fn b64(data: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+/// Generate a P-256 private key
+pub(crate) fn generate_ec_key() -> Result<PKey<Private>> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).map_err(crypto_error)?;
+    let key = EcKey::generate(&group).map_err(crypto_error)?;
+    PKey::from_ec_key(key).map_err(crypto_error)
+}
+
+/// ACME account key (P-256, ES256 signatures)
+pub(crate) struct AccountKey {
+    key: EcKey<Private>,
+}
+
+impl AccountKey {
+    pub fn generate() -> Result<Self> {
+        let key = generate_ec_key()?.ec_key().map_err(crypto_error)?;
+        Ok(Self { key })
+    }
+
+    pub fn from_pem(pem: &[u8]) -> Result<Self> {
+        let key = EcKey::private_key_from_pem(pem)
+            .map_err(|e| RfError::Config(format!("Invalid ACME account key: {}", e)))?;
+        Ok(Self { key })
+    }
+
+    pub fn to_pem(&self) -> Result<Vec<u8>> {
+        self.key.private_key_to_pem().map_err(crypto_error)
+    }
+
+    /// Public key as JWK, members in lexicographic order for the thumbprint
+    pub fn jwk(&self) -> Result<Value> {
+        let mut ctx = BigNumContext::new().map_err(crypto_error)?;
+        let (mut x, mut y) = (BigNum::new().map_err(crypto_error)?, BigNum::new().map_err(crypto_error)?);
+        self.key
+            .public_key()
+            .affine_coordinates(self.key.group(), &mut x, &mut y, &mut ctx)
+            .map_err(crypto_error)?;
+        Ok(json!({
+            "crv": "P-256",
+            "kty": "EC",
+            "x": b64(&x.to_vec_padded(32).map_err(crypto_error)?),
+            "y": b64(&y.to_vec_padded(32).map_err(crypto_error)?),
+        }))
+    }
+
+    /// JWK thumbprint (RFC 7638), used in key authorizations
+    pub fn thumbprint(&self) -> Result<String> {
+        let jwk = self.jwk()?.to_string();
+        Ok(b64(&openssl::sha::sha256(jwk.as_bytes())))
+    }
+
+    /// ES256 signature as raw `r || s`
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let digest = openssl::sha::sha256(data);
+        let signature = EcdsaSig::sign(&digest, &self.key).map_err(crypto_error)?;
+        let mut raw = signature.r().to_vec_padded(32).map_err(crypto_error)?;
+        raw.extend(signature.s().to_vec_padded(32).map_err(crypto_error)?);
+        Ok(raw)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Directory {
+    new_nonce: String,
+    new_account: String,
+    new_order: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Order {
+    pub status: String,
+    #[serde(default)]
+    pub authorizations: Vec<String>,
+    pub finalize: String,
+    #[serde(default)]
+    pub certificate: Option<String>,
+    #[serde(default)]
+    pub error: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Identifier {
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Challenge {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub url: String,
+    #[serde(default)]
+    pub token: String,
+    #[serde(default)]
+    pub error: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Authorization {
+    pub status: String,
+    pub identifier: Identifier,
+    #[serde(default)]
+    pub challenges: Vec<Challenge>,
+}
+
+fn problem_detail(problem: &Option<Value>) -> String {
+    problem
+        .as_ref()
+        .and_then(|p| p.get("detail"))
+        .and_then(|d| d.as_str())
+        .unwrap_or("no details")
+        .to_string()
+}
+
+/// Authenticated ACME session for one account
+pub(crate) struct AcmeClient {
+    http: reqwest::Client,
+    directory: Directory,
+    key: AccountKey,
+    kid: Option<String>,
+    nonce: Mutex<Option<String>>,
+    poll_interval: Duration,
+}
+
+impl AcmeClient {
+    /// Fetch the directory
+    pub async fn connect(
+        http: reqwest::Client,
+        directory_url: &str,
+        key: AccountKey,
+        poll_interval: Duration,
+    ) -> Result<Self> {
+        let directory = http
+            .get(directory_url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| RfError::Network(format!("Failed to fetch ACME directory: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| RfError::Network(format!("Invalid ACME directory: {}", e)))?;
+        Ok(Self {
+            http,
+            directory,
+            key,
+            kid: None,
+            nonce: Mutex::new(None),
+            poll_interval,
+        })
+    }
+
+    pub fn thumbprint(&self) -> Result<String> {
+        self.key.thumbprint()
+    }
+
+    async fn nonce(&self) -> Result<String> {
+        if let Some(nonce) = self.nonce.lock().await.take() {
+            return Ok(nonce);
+        }
+        let response = self
+            .http
+            .head(&self.directory.new_nonce)
+            .send()
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to get ACME nonce: {}", e)))?;
+        replay_nonce(&response).ok_or_else(|| RfError::Network("ACME server returned no nonce".to_string()))
+    }
+
+    fn jws(&self, url: &str, nonce: String, payload: Option<&Value>) -> Result<Value> {
+        let mut protected = json!({ "alg": "ES256", "nonce": nonce, "url": url });
+        match &self.kid {
+            Some(kid) => protected["kid"] = json!(kid),
+            None => protected["jwk"] = self.key.jwk()?,
+        }
+        let protected = b64(protected.to_string().as_bytes());
+        // POST-as-GET uses an empty payload
+        let payload = payload.map(|p| b64(p.to_string().as_bytes())).unwrap_or_default();
+        let signature = self.key.sign(format!("{}.{}", protected, payload).as_bytes())?;
+        Ok(json!({ "protected": protected, "payload": payload, "signature": b64(&signature) }))
+    }
+
+    /// Signed POST; `None` payload sends a POST-as-GET
+    async fn post(&self, url: &str, payload: Option<&Value>) -> Result<reqwest::Response> {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let body = self.jws(url, self.nonce().await?, payload)?;
+            let response = self
+                .http
+                .post(url)
+                .header("content-type", "application/jose+json")
+                .body(body.to_string())
+                .send()
+                .await
+                .map_err(|e| RfError::Network(format!("ACME request to {} failed: {}", url, e)))?;
+            if let Some(nonce) = replay_nonce(&response) {
+                *self.nonce.lock().await = Some(nonce);
+            }
+            if response.status().is_success() {
+                return Ok(response);
+            }
+
+            let status = response.status();
+            let problem: Value = response.json().await.unwrap_or(Value::Null);
+            let kind = problem["type"].as_str().unwrap_or_default();
+            if kind.ends_with(":badNonce") && attempts < 3 {
+                continue;
+            }
+            let detail = problem_detail(&Some(problem.clone()));
+            return Err(match status.as_u16() {
+                401 | 403 => RfError::Unauthorized(format!("ACME {}: {}", kind, detail)),
+                _ => RfError::Network(format!("ACME request to {} failed ({}): {} {}", url, status, kind, detail)),
+            });
+        }
+    }
+
+    async fn post_json<T: DeserializeOwned>(&self, url: &str, payload: Option<&Value>) -> Result<(T, Option<String>)> {
+        let response = self.post(url, payload).await?;
+        let location = response
+            .headers()
+            .get("location")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let body = response
+            .json()
+            .await
+            .map_err(|e| RfError::Network(format!("Invalid ACME response from {}: {}", url, e)))?;
+        Ok((body, location))
+    }
+
+    /// Create or look up the account for this key
+    pub async fn register(&mut self, contact: &[String]) -> Result<()> {
+        let contact: Vec<String> = contact
+            .iter()
+            .map(|c| if c.contains(':') { c.clone() } else { format!("mailto:{}", c) })
+            .collect();
+        let payload = json!({ "termsOfServiceAgreed": true, "contact": contact });
+        let url = self.directory.new_account.clone();
+        let (_, location) = self.post_json::<Value>(&url, Some(&payload)).await?;
+        self.kid = Some(location.ok_or_else(|| RfError::Network("ACME account has no URL".to_string()))?);
+        Ok(())
+    }
+
+    pub async fn new_order(&self, domains: &[String]) -> Result<(String, Order)> {
+        let identifiers: Vec<Value> = domains.iter().map(|d| json!({ "type": "dns", "value": d })).collect();
+        let (order, location) = self
+            .post_json::<Order>(&self.directory.new_order, Some(&json!({ "identifiers": identifiers })))
+            .await?;
+        let url = location.ok_or_else(|| RfError::Network("ACME order has no URL".to_string()))?;
+        Ok((url, order))
+    }
+
+    pub async fn authorization(&self, url: &str) -> Result<Authorization> {
+        Ok(self.post_json(url, None).await?.0)
+    }
+
+    /// Tell the server the challenge response is ready
+    pub async fn respond(&self, challenge_url: &str) -> Result<()> {
+        self.post(challenge_url, Some(&json!({}))).await?;
+        Ok(())
+    }
+
+    /// Wait until the authorization is valid
+    pub async fn wait_authorization(&self, url: &str, timeout: Duration) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let authz = self.authorization(url).await?;
+            match authz.status.as_str() {
+                "valid" => return Ok(()),
+                "pending" | "processing" if tokio::time::Instant::now() < deadline => {
+                    tokio::time::sleep(self.poll_interval).await
+                }
+                "pending" | "processing" => {
+                    return Err(RfError::Timeout(format!(
+                        "ACME validation of {} timed out",
+                        authz.identifier.value
+                    )))
+                }
+                status => {
+                    let detail = authz
+                        .challenges
+                        .iter()
+                        .find(|c| c.error.is_some())
+                        .map(|c| problem_detail(&c.error))
+                        .unwrap_or_default();
+                    return Err(RfError::Network(format!(
+                        "ACME validation of {} failed ({}): {}",
+                        authz.identifier.value, status, detail
+                    )));
+                }
+            }
+        }
+    }
+
+    pub async fn finalize(&self, order: &Order, csr_der: &[u8]) -> Result<()> {
+        self.post(&order.finalize, Some(&json!({ "csr": b64(csr_der) }))).await?;
+        Ok(())
+    }
+
+    /// Wait until the order is valid and return it
+    pub async fn wait_order(&self, url: &str, timeout: Duration) -> Result<Order> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let (order, _) = self.post_json::<Order>(url, None).await?;
+            match order.status.as_str() {
+                "valid" => return Ok(order),
+                "pending" | "ready" | "processing" if tokio::time::Instant::now() < deadline => {
+                    tokio::time::sleep(self.poll_interval).await
+                }
+                "pending" | "ready" | "processing" => {
+                    return Err(RfError::Timeout("ACME order did not complete in time".to_string()))
+                }
+                status => {
+                    return Err(RfError::Network(format!(
+                        "ACME order failed ({}): {}",
+                        status,
+                        problem_detail(&order.error)
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Download the certificate chain as PEM
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self.post(url, None).await?;
+        let pem = response
+            .bytes()
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to download certificate: {}", e)))?;
+        Ok(pem.to_vec())
+    }
+}
+
+fn replay_nonce(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get("replay-nonce")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
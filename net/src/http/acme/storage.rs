@@ -0,0 +1,107 @@
+//! # storage
+//!
+//! storage 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Certificate and account key storage for ACME
+
+use async_trait::async_trait;
+use rf_errors::{Result, RfError};
+use std::path::PathBuf;
+
+/// Key-value storage for ACME account keys and certificates
+///
+/// Keys are short file-name-safe strings such as `example.com.crt`.
+#[async_trait]
+pub trait CertStorage: Send + Sync {
+    /// Load a value, `None` if it does not exist
+    async fn load(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Store a value, replacing any previous one
+    async fn store(&self, key: &str, data: &[u8]) -> Result<()>;
+}
+
+/// Stores each value as a file in a directory
+#[derive(Debug, Clone)]
+pub struct FileCertStorage {
+    dir: PathBuf,
+}
+
+impl FileCertStorage {
+    /// Create a storage in `dir` (created on first write)
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        if key.is_empty() || key.contains(['/', '\\']) || key.starts_with('.') {
+            return Err(RfError::InvalidParameter(format!("Invalid storage key: {}", key)));
+        }
+        Ok(self.dir.join(key))
+    }
+}
+
+#[async_trait]
+impl CertStorage for FileCertStorage {
+    async fn load(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn store(&self, key: &str, data: &[u8]) -> Result<()> {
+        let path = self.path(key)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        // Write then rename so readers (and file watchers) never see a partial file
+        let tmp = self.dir.join(format!(".{}.tmp", key));
+        tokio::fs::write(&tmp, data).await?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600)).await?;
+        }
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+}
+
+/// Stores values in Redis, shared by all instances behind a load balancer
+#[cfg(feature = "acme-redis")]
+pub struct RedisCertStorage {
+    client: std::sync::Arc<rf_database::redis::RedisClient>,
+    prefix: String,
+}
+
+#[cfg(feature = "acme-redis")]
+impl RedisCertStorage {
+    /// Create a storage using keys `<prefix><key>`
+    pub fn new(client: std::sync::Arc<rf_database::redis::RedisClient>, prefix: impl Into<String>) -> Self {
+        Self {
+            client,
+            prefix: prefix.into(),
+        }
+    }
+}
+
+#[cfg(feature = "acme-redis")]
+#[async_trait]
+impl CertStorage for RedisCertStorage {
+    async fn load(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let value: Option<String> = self.client.string().get_json(&format!("{}{}", self.prefix, key)).await?;
+        Ok(value.map(String::into_bytes))
+    }
+
+    async fn store(&self, key: &str, data: &[u8]) -> Result<()> {
+        let value = String::from_utf8(data.to_vec())
+            .map_err(|_| RfError::InvalidParameter("Certificate data must be PEM text".to_string()))?;
+        self.client
+            .string()
+            .set_json(&format!("{}{}", self.prefix, key), &value)
+            .await
+    }
+}
@@ -0,0 +1,481 @@
+//! # admin
+//!
+//! admin 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Admin dashboard plugin
+//!
+//! Serves a small HTML page and a JSON API describing the running server:
+//! routes, middleware, plugins, recent 5xx responses, 1/5/15 minute histogram
+//! rollups from `rf_os::metric`, build information, plus any sections the
+//! application registers (pool stats, cache stats, cron jobs, ...). Every
+//! endpoint requires the configured token, sent as `Authorization: Bearer`,
+//! `X-Admin-Token` or a `token` query parameter.
+//!
+//! The build information is also served as plain JSON at `/debug/version`
+//! for release tooling; `public_version(true)` serves it without the token.
+//!
+//! With `cron(scheduler)` the `cron` section lists the scheduler's jobs, and
+//! runbooks can operate them by name or id:
+//! `POST {path}/api/cron/{job}/trigger`, `.../pause` and `.../resume`, and
+//! `DELETE {path}/api/cron/{job}`.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{AdminPlugin, HttpServer};
+//!
+//! let admin = AdminPlugin::new(std::env::var("ADMIN_TOKEN")?)
+//!     .build_info(rf_os::build_info!())
+//!     .section("cache", move || json!(cache.stats()))
+//!     .async_section("db", move || { let db = db.clone(); async move { json!(db.pool_stats()) } });
+//!
+//! HttpServer::new(addr).with_plugin(admin)?.serve().await?;
+//! // GET /admin, /admin/api, /admin/api/{section}, /debug/version
+//!
+//! let admin = admin.cron(cron.clone());
+//! // POST /admin/api/cron/cleanup/trigger
+//! ```
+
+use super::envelope::ErrorSource;
+use super::plugin::{Plugin, ServerInfo};
+use super::response::Response;
+use axum::extract::{Path, Query, Request};
+use axum::http::header::AUTHORIZATION;
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::{Html, IntoResponse, Response as AxumResponse};
+use axum::routing::{delete, get, post};
+use axum::Router;
+use futures_util::future::BoxFuture;
+use rf_errors::{codes, Result, RfError};
+use rf_os::build::BuildInfo;
+use rf_os::cron::Cron;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Number of recent errors kept by default
+const DEFAULT_ERROR_CAPACITY: usize = 100;
+
+type SectionFn = Arc<dyn Fn() -> BoxFuture<'static, Value> + Send + Sync>;
+
+/// A failed request recorded by the admin plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorRecord {
+    /// Unix time in milliseconds
+    pub time: u64,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub message: Option<String>,
+}
+
+/// Bounded log of recent errors, shared with the application
+#[derive(Debug, Clone)]
+pub struct ErrorLog {
+    records: Arc<Mutex<VecDeque<ErrorRecord>>>,
+    capacity: usize,
+}
+
+impl ErrorLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Record an error, dropping the oldest one when full
+    pub fn record(&self, record: ErrorRecord) {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Recent errors, newest first
+    pub fn recent(&self) -> Vec<ErrorRecord> {
+        let records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.iter().rev().cloned().collect()
+    }
+}
+
+/// Admin dashboard plugin
+pub struct AdminPlugin {
+    path: String,
+    token: String,
+    sections: Vec<(String, SectionFn)>,
+    errors: ErrorLog,
+    server: Arc<RwLock<Option<ServerInfo>>>,
+    started: Instant,
+    build: BuildInfo,
+    version_path: String,
+    public_version: bool,
+    cron: Option<Arc<Cron>>,
+}
+
+impl AdminPlugin {
+    /// Create the plugin, mounted at `/admin`
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            path: "/admin".to_string(),
+            token: token.into(),
+            sections: Vec::new(),
+            errors: ErrorLog::new(DEFAULT_ERROR_CAPACITY),
+            server: Arc::new(RwLock::new(None)),
+            started: Instant::now(),
+            build: rf_os::build::info(),
+            version_path: "/debug/version".to_string(),
+            public_version: false,
+            cron: None,
+        }
+    }
+
+    /// Application build information, usually `rf_os::build_info!()`
+    ///
+    /// Defaults to rf-os's own build information.
+    pub fn build_info(mut self, info: BuildInfo) -> Self {
+        self.build = info;
+        self
+    }
+
+    /// Path of the version endpoint (default `/debug/version`)
+    pub fn version_path(mut self, path: impl Into<String>) -> Self {
+        self.version_path = path.into();
+        self
+    }
+
+    /// Serve the version endpoint without the admin token (default false)
+    pub fn public_version(mut self, public: bool) -> Self {
+        self.public_version = public;
+        self
+    }
+
+    /// Mount point (default `/admin`)
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Number of recent errors to keep (default 100)
+    pub fn error_capacity(mut self, capacity: usize) -> Self {
+        self.errors = ErrorLog::new(capacity.max(1));
+        self
+    }
+
+    /// Add a section computed on each request
+    pub fn section<F>(self, name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn() -> Value + Send + Sync + 'static,
+    {
+        self.async_section(name, move || std::future::ready(f()))
+    }
+
+    /// Add a section computed asynchronously on each request
+    pub fn async_section<F, Fut>(mut self, name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Value> + Send + 'static,
+    {
+        let f: SectionFn = Arc::new(move || Box::pin(f()));
+        self.sections.push((name.into(), f));
+        self
+    }
+
+    /// List the jobs of `cron` in a `cron` section and serve the job operations
+    pub fn cron(mut self, cron: Arc<Cron>) -> Self {
+        self.cron = Some(cron);
+        self
+    }
+
+    /// Error log fed by the plugin, for recording application errors too
+    pub fn errors(&self) -> ErrorLog {
+        self.errors.clone()
+    }
+
+    fn state(&self) -> AdminState {
+        AdminState {
+            token: self.token.clone(),
+            sections: self.sections.clone(),
+            errors: self.errors.clone(),
+            server: self.server.clone(),
+            started: self.started,
+            build: self.build.to_json(),
+            public_version: self.public_version,
+            cron: self.cron.clone(),
+        }
+    }
+}
+
+impl Plugin for AdminPlugin {
+    fn name(&self) -> &str {
+        "admin"
+    }
+
+    fn version(&self) -> &str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    fn init(&mut self) -> Result<()> {
+        if self.token.is_empty() {
+            return Err(RfError::Config("Admin plugin requires a token".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Supported keys: `path`, `token`, `version_path`, `public_version`
+    fn configure(&mut self, config: HashMap<String, String>) -> Result<()> {
+        if let Some(path) = config.get("path") {
+            self.path = path.trim_end_matches('/').to_string();
+        }
+        if let Some(token) = config.get("token") {
+            self.token = token.clone();
+        }
+        if let Some(path) = config.get("version_path") {
+            self.version_path = path.clone();
+        }
+        if let Some(public) = config.get("public_version") {
+            self.public_version = public
+                .parse()
+                .map_err(|_| RfError::Config(format!("Invalid admin public_version: {}", public)))?;
+        }
+        self.init()
+    }
+
+    fn attach(&self, info: &ServerInfo) {
+        *self.server.write().unwrap_or_else(|e| e.into_inner()) = Some(info.clone());
+    }
+
+    fn apply(&self, router: Router) -> Router {
+        let state = Arc::new(self.state());
+        let (page, all, one, version, job, removal) =
+            (state.clone(), state.clone(), state.clone(), state.clone(), state.clone(), state);
+        let admin = Router::new()
+            .route(
+                &self.path,
+                get(move |headers: HeaderMap, query: Query<HashMap<String, String>>| async move {
+                    page.page(&headers, &query)
+                }),
+            )
+            .route(
+                &format!("{}/api", self.path),
+                get(move |headers: HeaderMap, query: Query<HashMap<String, String>>| async move {
+                    all.snapshot(&headers, &query, None).await
+                }),
+            )
+            .route(
+                &format!("{}/api/{{section}}", self.path),
+                get(
+                    move |headers: HeaderMap, query: Query<HashMap<String, String>>, Path(section): Path<String>| async move {
+                        one.snapshot(&headers, &query, Some(&section)).await
+                    },
+                ),
+            )
+            .route(
+                &self.version_path,
+                get(move |headers: HeaderMap, query: Query<HashMap<String, String>>| async move {
+                    version.version(&headers, &query)
+                }),
+            )
+            .route(
+                &format!("{}/api/cron/{{job}}/{{action}}", self.path),
+                post(
+                    move |headers: HeaderMap,
+                          query: Query<HashMap<String, String>>,
+                          Path((name, action)): Path<(String, String)>| async move {
+                        job.cron_job(&headers, &query, &name, &action).await
+                    },
+                ),
+            )
+            .route(
+                &format!("{}/api/cron/{{job}}", self.path),
+                delete(
+                    move |headers: HeaderMap, query: Query<HashMap<String, String>>, Path(name): Path<String>| async move {
+                        removal.cron_job(&headers, &query, &name, "remove").await
+                    },
+                ),
+            );
+        let errors = self.errors.clone();
+        router
+            .merge(admin)
+            .layer(axum::middleware::from_fn(move |request, next| record_errors(errors.clone(), request, next)))
+    }
+}
+
+/// Record responses with a 5xx status
+async fn record_errors(errors: ErrorLog, request: Request, next: Next) -> AxumResponse {
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+    if response.status().is_server_error() {
+        errors.record(ErrorRecord {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            method,
+            path,
+            status: response.status().as_u16(),
+            message: response.extensions().get::<ErrorSource>().map(|e| e.0.to_string()),
+        });
+    }
+    response
+}
+
+struct AdminState {
+    token: String,
+    sections: Vec<(String, SectionFn)>,
+    errors: ErrorLog,
+    server: Arc<RwLock<Option<ServerInfo>>>,
+    started: Instant,
+    build: Value,
+    public_version: bool,
+    cron: Option<Arc<Cron>>,
+}
+
+impl AdminState {
+    fn authorized(&self, headers: &HeaderMap, query: &HashMap<String, String>) -> bool {
+        let token = headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| headers.get("x-admin-token").and_then(|v| v.to_str().ok()))
+            .or_else(|| query.get("token").map(String::as_str));
+        token.is_some_and(|token| constant_time_eq(token.as_bytes(), self.token.as_bytes()))
+    }
+
+    fn page(&self, headers: &HeaderMap, query: &HashMap<String, String>) -> AxumResponse {
+        if !self.authorized(headers, query) {
+            return unauthorized();
+        }
+        Html(PAGE).into_response()
+    }
+
+    /// Bare build information, not wrapped in the response envelope
+    fn version(&self, headers: &HeaderMap, query: &HashMap<String, String>) -> AxumResponse {
+        if !self.public_version && !self.authorized(headers, query) {
+            return unauthorized();
+        }
+        axum::Json(self.build.clone()).into_response()
+    }
+
+    async fn snapshot(&self, headers: &HeaderMap, query: &HashMap<String, String>, only: Option<&str>) -> AxumResponse {
+        if !self.authorized(headers, query) {
+            return unauthorized();
+        }
+        let server = self.server.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut snapshot = Map::new();
+        let mut builtin = |name: &str, value: Value| {
+            if only.is_none_or(|only| only == name) {
+                snapshot.insert(name.to_string(), value);
+            }
+        };
+        builtin(
+            "server",
+            json!({
+                "addr": server.as_ref().map(|s| s.addr.to_string()),
+                "tls": server.as_ref().map(|s| s.tls),
+                "uptime_secs": self.started.elapsed().as_secs(),
+                "version": env!("CARGO_PKG_VERSION"),
+            }),
+        );
+        builtin("routes", json!(server.as_ref().map(|s| &s.routes)));
+        builtin("middleware", json!(server.as_ref().map(|s| &s.middleware)));
+        builtin("plugins", json!(server.as_ref().map(|s| &s.plugins)));
+        builtin("errors", json!(self.errors.recent()));
+        builtin("metrics", json!(rf_os::metric::rollup_snapshot()));
+        builtin("build", self.build.clone());
+        if let Some(cron) = &self.cron {
+            builtin("cron", json!(cron.jobs().await));
+        }
+        for (name, section) in &self.sections {
+            if only.is_none_or(|only| only == name) {
+                snapshot.insert(name.clone(), section().await);
+            }
+        }
+
+        match only {
+            Some(name) => match snapshot.remove(name) {
+                Some(value) => Response::success(value).into_response(),
+                None => Response::fail(codes::NOT_FOUND, format!("Unknown section: {}", name)).into_response(),
+            },
+            None => Response::success(snapshot).into_response(),
+        }
+    }
+
+    /// Trigger, pause, resume or remove a cron job
+    async fn cron_job(&self, headers: &HeaderMap, query: &HashMap<String, String>, job: &str, action: &str) -> AxumResponse {
+        if !self.authorized(headers, query) {
+            return unauthorized();
+        }
+        let Some(cron) = &self.cron else {
+            return Response::fail(codes::NOT_FOUND, "No cron scheduler").into_response();
+        };
+        let find = |jobs: Vec<rf_os::cron::CronJobInfo>| {
+            jobs.into_iter().find(|info| info.name.as_deref() == Some(job) || info.id.to_string() == job)
+        };
+        if find(cron.jobs().await).is_none() {
+            return Response::fail(codes::NOT_FOUND, format!("Unknown cron job: {}", job)).into_response();
+        }
+        let result = match action {
+            "trigger" => cron.trigger(job).map_err(|e| e.to_string()),
+            "pause" => cron.pause(job).await.map_err(|e| e.to_string()),
+            "resume" => cron.resume(job).await.map_err(|e| e.to_string()),
+            "remove" => cron.remove(job).await.map(|_| ()).map_err(|e| e.to_string()),
+            _ => return Response::fail(codes::NOT_FOUND, format!("Unknown cron action: {}", action)).into_response(),
+        };
+        if let Err(e) = result {
+            return Response::fail(codes::INTERNAL_ERROR, e).into_response();
+        }
+        tracing::info!("Admin: cron job {} {}", job, action);
+        let info = find(cron.jobs().await);
+        Response::success(json!({ "job": job, "action": action, "info": info })).into_response()
+    }
+}
+
+fn unauthorized() -> AxumResponse {
+    Response::fail(codes::UNAUTHORIZED, "Invalid admin token").into_response()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>RF Admin</title>
+<style>
+body { font-family: sans-serif; margin: 2em; background: #fafafa; }
+h2 { border-bottom: 1px solid #ddd; padding-bottom: 4px; }
+pre { background: #fff; border: 1px solid #eee; padding: 1em; overflow: auto; }
+</style>
+</head>
+<body>
+<h1>RF Admin</h1>
+<div id="sections">Loading...</div>
+<script>
+const token = new URLSearchParams(location.search).get("token") || "";
+fetch(location.pathname.replace(/\/$/, "") + "/api", { headers: { "X-Admin-Token": token } })
+  .then(r => r.json())
+  .then(body => {
+    const root = document.getElementById("sections");
+    root.textContent = "";
+    for (const [name, value] of Object.entries(body.data || {})) {
+      const h = document.createElement("h2");
+      h.textContent = name;
+      const pre = document.createElement("pre");
+      pre.textContent = JSON.stringify(value, null, 2);
+      root.append(h, pre);
+    }
+  });
+</script>
+</body>
+</html>
+"#;
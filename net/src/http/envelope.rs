@@ -0,0 +1,335 @@
+//! # envelope
+//!
+//! envelope 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Unified response envelope
+//!
+//! Every response takes the shape `{code, message, data}`. Handlers build
+//! envelopes with `Response::success` / `Response::fail` or return
+//! `ApiError` (any `RfError` converts with `?`); `envelope_middleware` maps
+//! envelope codes to HTTP statuses and wraps plain JSON and error responses
+//! so the whole API answers in one shape.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{ApiError, EnvelopeConfig, HttpServer, Response};
+//!
+//! async fn get_user(Path(id): Path<u64>) -> Result<Response, ApiError> {
+//!     let user = find_user(id).await?; // RfError::NotFound -> 404 {"code":404,...}
+//!     Ok(Response::success(user))
+//! }
+//!
+//! let server = HttpServer::new(addr)
+//!     .route(Method::GET, "/users/:id", get_user)?
+//!     .with_envelope(EnvelopeConfig::new().map_code(1000, StatusCode::SERVICE_UNAVAILABLE));
+//! ```
+//!
+//! 5xx errors are answered with `Internal error`, except in an explicitly set
+//! development mode (`rf_util::mode::verbose_errors`), where the message is
+//! kept and `data.backtrace` lists the frames where the `ApiError` was
+//! created with `?` or `into()`.
+
+use crate::buffer::BufferPool;
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Json, Response as AxumResponse};
+use rf_errors::{Code, RfError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Largest response body the middleware reads in order to wrap it
+const MAX_WRAP_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// Response body shape
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope<T = Value> {
+    /// `0` on success, otherwise an RF error code
+    pub code: Code,
+    pub message: String,
+    pub data: Option<T>,
+}
+
+/// Marks a response whose body is already an envelope
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Enveloped(pub Code);
+
+/// The error behind an `ApiError` response, re-rendered by the middleware,
+/// with the frames it was created at when backtraces are captured
+#[derive(Debug, Clone)]
+pub(crate) struct ErrorSource(pub Arc<RfError>, pub Option<Arc<Vec<String>>>);
+
+thread_local! {
+    /// Backtrace of the last `ApiError` converted on this thread, with its message
+    ///
+    /// Handlers convert with `?` and return, and axum renders the error in the
+    /// same poll, so the response picks up the trace of its own error.
+    static LAST_BACKTRACE: RefCell<Option<(String, Arc<Vec<String>>)>> = const { RefCell::new(None) };
+}
+
+/// Frame prefixes left out of error backtraces: runtime, framework and capture frames
+const HIDDEN_FRAMES: &[&str] = &[
+    "std::", "core::", "alloc::", "<std::", "<core::", "<alloc::", "tokio::", "<tokio::", "axum::", "<axum::",
+    "tower::", "<tower::", "hyper::", "<hyper::", "futures_util::", "<futures_util::", "rf_net::http::envelope::",
+    "<rf_net::http::envelope::", "__rust", "_start", "start_thread", "clone",
+];
+
+/// Application frames of `backtrace` as `function (file:line)`
+fn backtrace_frames(backtrace: &Backtrace) -> Vec<String> {
+    let mut frames: Vec<String> = Vec::new();
+    let mut hidden = true;
+    for line in backtrace.to_string().lines() {
+        let line = line.trim();
+        if let Some(location) = line.strip_prefix("at ") {
+            if let (false, Some(frame)) = (hidden, frames.last_mut()) {
+                frame.push_str(&format!(" ({})", location));
+            }
+        } else if let Some((_, function)) = line.split_once(": ") {
+            hidden = HIDDEN_FRAMES.iter().any(|prefix| function.starts_with(prefix));
+            if !hidden {
+                frames.push(function.to_string());
+            }
+        }
+    }
+    frames
+}
+
+/// Keep the backtrace of `err` for its response, in verbose mode
+fn capture_backtrace(err: &RfError) {
+    if !rf_util::mode::verbose_errors() {
+        return;
+    }
+    let frames = Arc::new(backtrace_frames(&Backtrace::force_capture()));
+    LAST_BACKTRACE.with(|last| *last.borrow_mut() = Some((err.to_string(), frames)));
+}
+
+/// The backtrace kept for `err`, if it is the last error converted on this thread
+fn take_backtrace(err: &RfError) -> Option<Arc<Vec<String>>> {
+    LAST_BACKTRACE
+        .with(|last| last.borrow_mut().take())
+        .filter(|(message, _)| *message == err.to_string())
+        .map(|(_, frames)| frames)
+}
+
+/// Envelope settings: code to status mapping and which responses get wrapped
+///
+/// By default code `0` maps to 200, codes 100-599 map to the same HTTP
+/// status and every other code to 500.
+#[derive(Debug, Clone)]
+pub struct EnvelopeConfig {
+    status_map: HashMap<Code, StatusCode>,
+    wrap_responses: bool,
+    expose_internal_errors: bool,
+    backtraces: bool,
+    success_message: String,
+}
+
+impl Default for EnvelopeConfig {
+    fn default() -> Self {
+        Self {
+            status_map: HashMap::new(),
+            wrap_responses: true,
+            expose_internal_errors: rf_util::mode::verbose_errors(),
+            backtraces: rf_util::mode::verbose_errors(),
+            success_message: "OK".to_string(),
+        }
+    }
+}
+
+impl EnvelopeConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer envelopes carrying `code` with `status`
+    pub fn map_code(mut self, code: Code, status: StatusCode) -> Self {
+        self.status_map.insert(code, status);
+        self
+    }
+
+    /// Wrap plain JSON and error responses that are not envelopes (default `true`)
+    pub fn wrap_responses(mut self, wrap: bool) -> Self {
+        self.wrap_responses = wrap;
+        self
+    }
+
+    /// Keep the messages of errors answered with 5xx instead of `Internal error`
+    /// (default: only in an explicitly set development mode)
+    pub fn expose_internal_errors(mut self, expose: bool) -> Self {
+        self.expose_internal_errors = expose;
+        self
+    }
+
+    /// Add `data.backtrace` to 5xx errors whose backtrace was captured
+    /// (default: only in an explicitly set development mode, which also captures them)
+    pub fn backtraces(mut self, backtraces: bool) -> Self {
+        self.backtraces = backtraces;
+        self
+    }
+
+    /// Message of success envelopes (default `OK`)
+    pub fn success_message(mut self, message: impl Into<String>) -> Self {
+        self.success_message = message.into();
+        self
+    }
+
+    /// HTTP status for an envelope code
+    pub fn status_for(&self, code: Code) -> StatusCode {
+        if let Some(status) = self.status_map.get(&code) {
+            return *status;
+        }
+        match code {
+            0 => StatusCode::OK,
+            100..=599 => StatusCode::from_u16(code as u16).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Envelope response for an error
+    ///
+    /// Inside `request_id_middleware` the data is `{"request_id": ...}`, so
+    /// clients can quote the ID of a hidden internal error.
+    pub fn error_response(&self, err: &RfError) -> AxumResponse {
+        self.render_error(err, None)
+    }
+
+    fn render_error(&self, err: &RfError, backtrace: Option<&[String]>) -> AxumResponse {
+        let code = err.code();
+        let status = self.status_for(code);
+        let request_id = rf_core::ctx::request_id();
+        let message = if status.is_server_error() && !self.expose_internal_errors {
+            tracing::error!(request_id = request_id.as_deref(), "Request failed: {}", err);
+            "Internal error".to_string()
+        } else {
+            err.to_string()
+        };
+        let mut data = match request_id {
+            Some(id) => serde_json::json!({ "request_id": &*id }),
+            None => Value::Null,
+        };
+        if let (true, true, Some(frames)) = (self.backtraces, status.is_server_error(), backtrace) {
+            data["backtrace"] = serde_json::json!(frames);
+        }
+        envelope_response(status, code, message, data)
+    }
+}
+
+/// Build an envelope response
+pub(crate) fn envelope_response(status: StatusCode, code: Code, message: String, data: Value) -> AxumResponse {
+    let envelope = Envelope {
+        code,
+        message,
+        data: Some(data),
+    };
+    let mut response = (status, Json(envelope)).into_response();
+    response.extensions_mut().insert(Enveloped(code));
+    response
+}
+
+/// Handler error answered as an envelope
+///
+/// Any error convertible to `RfError` converts with `?`.
+#[derive(Debug)]
+pub struct ApiError(pub RfError);
+
+impl<E: Into<RfError>> From<E> for ApiError {
+    fn from(err: E) -> Self {
+        let err = err.into();
+        capture_backtrace(&err);
+        Self(err)
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> AxumResponse {
+        let backtrace = take_backtrace(&self.0);
+        let mut response = EnvelopeConfig::default().render_error(&self.0, backtrace.as_deref().map(Vec::as_slice));
+        response.extensions_mut().insert(ErrorSource(Arc::new(self.0), backtrace));
+        response
+    }
+}
+
+/// Map envelope codes to statuses and wrap other responses into envelopes
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc::new(config), envelope_middleware)`,
+/// or `HttpServer::with_envelope`.
+pub async fn envelope_middleware(State(config): State<Arc<EnvelopeConfig>>, request: Request, next: Next) -> AxumResponse {
+    let mut response = next.run(request).await;
+    if let Some(source) = response.extensions().get::<ErrorSource>().cloned() {
+        let mut response = config.render_error(&source.0, source.1.as_deref().map(Vec::as_slice));
+        response.extensions_mut().insert(source);
+        return response;
+    }
+    if let Some(Enveloped(code)) = response.extensions().get::<Enveloped>().copied() {
+        *response.status_mut() = config.status_for(code);
+        return response;
+    }
+    if !config.wrap_responses {
+        return response;
+    }
+
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json") || v.contains("+json"));
+    if status.is_success() && !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match BufferPool::global().read_body(body, MAX_WRAP_BODY_SIZE).await {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to read response body: {}", e);
+            return config.error_response(&RfError::Internal(e.to_string()));
+        }
+    };
+    let data = if is_json {
+        serde_json::from_slice(&body).unwrap_or(Value::Null)
+    } else {
+        Value::Null
+    };
+    let (code, message) = if status.is_success() {
+        (0, config.success_message.clone())
+    } else {
+        let text = String::from_utf8_lossy(&body);
+        let message = if is_json || text.trim().is_empty() {
+            status.canonical_reason().unwrap_or("Error").to_string()
+        } else {
+            text.trim().to_string()
+        };
+        (status.as_u16() as Code, message)
+    };
+
+    let envelope = Envelope {
+        code,
+        message,
+        data: Some(data),
+    };
+    let body = match serde_json::to_vec(&envelope) {
+        Ok(body) => body,
+        Err(e) => return config.error_response(&RfError::Serialization(e.to_string())),
+    };
+    parts.headers.remove(CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(CONTENT_TYPE, axum::http::HeaderValue::from_static("application/json"));
+    parts.extensions.insert(Enveloped(code));
+    AxumResponse::from_parts(parts, Body::from(body))
+}
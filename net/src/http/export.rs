@@ -0,0 +1,464 @@
+//! # export
+//!
+//! export 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Data export
+//!
+//! Turns a stream of rows (`Model::stream`, or any stream of serializable
+//! values) into a CSV, XLSX or JSON-lines download. The format is picked with
+//! `?format=csv|xlsx|jsonl` or the `Accept` header through the `DataFormat`
+//! extractor.
+//!
+//! CSV and JSON lines are streamed: rows are encoded in chunks and pulled
+//! from the source only as fast as the client reads. XLSX needs the whole
+//! sheet before the zip container can be written, so it is sent at the end.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{DataFormat, Export};
+//!
+//! async fn export_users(format: DataFormat) -> AxumResponse {
+//!     let rows = db.model("users").fields(&["id", "name", "email"]).stream(256);
+//!     Export::new("users")
+//!         .column("id", "ID")
+//!         .column("name", "Name")
+//!         .column("email", "Email")
+//!         .response(format, rows)
+//! }
+//! ```
+
+use axum::body::{Body, Bytes};
+use axum::extract::FromRequestParts;
+use axum::http::header::{ACCEPT, CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, HeaderValue, StatusCode, Uri};
+use axum::response::{IntoResponse, Response as AxumResponse};
+use futures_util::{Stream, StreamExt};
+use rf_errors::{Result, RfError};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::Write;
+use tokio::sync::mpsc;
+
+/// Bytes collected before a chunk is sent
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// `Content-Disposition` of a download named `filename`
+pub(crate) fn attachment(filename: &str) -> Option<HeaderValue> {
+    let disposition = format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        filename.replace(|c: char| !c.is_ascii() || c == '"' || c.is_ascii_control(), "_"),
+        url::form_urlencoded::byte_serialize(filename.as_bytes()).collect::<String>().replace('+', "%20"),
+    );
+    HeaderValue::from_str(&disposition).ok()
+}
+
+/// Tabular data format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DataFormat {
+    #[default]
+    Csv,
+    Xlsx,
+    JsonLines,
+}
+
+impl DataFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            Self::JsonLines => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Xlsx => "xlsx",
+            Self::JsonLines => "jsonl",
+        }
+    }
+
+    /// Format for a name such as `csv`, `xlsx`, `jsonl` or `ndjson`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "xlsx" | "excel" => Some(Self::Xlsx),
+            "jsonl" | "ndjson" | "jsonlines" => Some(Self::JsonLines),
+            _ => None,
+        }
+    }
+
+    /// Format for a MIME type, ignoring parameters
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "text/csv" | "application/csv" => Some(Self::Csv),
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => Some(Self::Xlsx),
+            "application/x-ndjson" | "application/jsonl" | "application/jsonlines" | "application/x-jsonlines" => {
+                Some(Self::JsonLines)
+            }
+            _ => None,
+        }
+    }
+
+    /// Format for a file name by its extension
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        filename.rsplit_once('.').and_then(|(_, ext)| Self::from_name(ext))
+    }
+
+    /// Pick the format from `?format=` or else the `Accept` header
+    ///
+    /// Returns `None` only for an unknown `format` parameter; an `Accept`
+    /// header without a supported type falls back to CSV.
+    pub fn negotiate(headers: &HeaderMap, uri: &Uri) -> Option<Self> {
+        let requested = uri.query().and_then(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "format")
+                .map(|(_, value)| value.into_owned())
+        });
+        if let Some(name) = requested {
+            return Self::from_name(&name);
+        }
+        let accept = headers.get(ACCEPT).and_then(|v| v.to_str().ok()).unwrap_or("");
+        Some(accept.split(',').find_map(Self::from_content_type).unwrap_or_default())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for DataFormat {
+    type Rejection = AxumResponse;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> std::result::Result<Self, Self::Rejection> {
+        Self::negotiate(&parts.headers, &parts.uri).ok_or_else(|| {
+            (StatusCode::NOT_ACCEPTABLE, "Unsupported export format, use csv, xlsx or jsonl").into_response()
+        })
+    }
+}
+
+/// Export settings
+#[derive(Debug, Clone)]
+pub struct Export {
+    filename: String,
+    columns: Vec<(String, String)>,
+    sheet: String,
+    buffer: usize,
+    bom: bool,
+    escape_formulas: bool,
+}
+
+impl Export {
+    /// Export downloaded as `{filename}.{extension}`
+    pub fn new(filename: impl Into<String>) -> Self {
+        Self {
+            filename: filename.into(),
+            columns: Vec::new(),
+            sheet: "Sheet1".to_string(),
+            buffer: 8,
+            bom: false,
+            escape_formulas: true,
+        }
+    }
+
+    /// Export `field` under the `header` title, in call order
+    ///
+    /// Without columns every field of the first row is exported.
+    pub fn column(mut self, field: impl Into<String>, header: impl Into<String>) -> Self {
+        self.columns.push((field.into(), header.into()));
+        self
+    }
+
+    /// XLSX worksheet name (default `Sheet1`)
+    pub fn sheet(mut self, name: impl Into<String>) -> Self {
+        self.sheet = name.into();
+        self
+    }
+
+    /// Encoded chunks buffered ahead of the client (default 8)
+    pub fn buffer(mut self, chunks: usize) -> Self {
+        self.buffer = chunks.max(1);
+        self
+    }
+
+    /// Start CSV files with a UTF-8 BOM, which Excel needs to detect the encoding
+    pub fn bom(mut self, bom: bool) -> Self {
+        self.bom = bom;
+        self
+    }
+
+    /// Prefix text cells starting with `=`, `+`, `-` or `@` with `'` so
+    /// spreadsheets do not evaluate them (default true)
+    pub fn escape_formulas(mut self, escape: bool) -> Self {
+        self.escape_formulas = escape;
+        self
+    }
+
+    /// Stream `rows` as a download in `format`
+    ///
+    /// An error from the source after the response started aborts the body.
+    pub fn response<S, T>(&self, format: DataFormat, rows: S) -> AxumResponse
+    where
+        S: Stream<Item = Result<T>> + Send + 'static,
+        T: Serialize + Send + 'static,
+    {
+        let (sender, mut receiver) = mpsc::channel::<std::io::Result<Bytes>>(self.buffer);
+        let export = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = export.write(format, rows, &sender).await {
+                tracing::error!("Export {} failed: {}", export.filename, e);
+                let _ = sender.send(Err(std::io::Error::other(e.to_string()))).await;
+            }
+        });
+        let body = Body::from_stream(futures_util::stream::poll_fn(move |cx| receiver.poll_recv(cx)));
+
+        let mut response = AxumResponse::new(body);
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(format.content_type()));
+        if let Some(value) = attachment(&format!("{}.{}", self.filename, format.extension())) {
+            headers.insert(CONTENT_DISPOSITION, value);
+        }
+        response
+    }
+
+    /// Encode every row into `sender`, stopping early when the client is gone
+    async fn write<S, T>(&self, format: DataFormat, rows: S, sender: &mpsc::Sender<std::io::Result<Bytes>>) -> Result<()>
+    where
+        S: Stream<Item = Result<T>> + Send,
+        T: Serialize,
+    {
+        let mut rows = std::pin::pin!(rows);
+        let mut columns = self.columns.clone();
+        let mut buf = Vec::with_capacity(CHUNK_SIZE);
+        let mut sheet = XlsxSheet::default();
+        if format == DataFormat::Csv && self.bom {
+            buf.extend_from_slice("\u{feff}".as_bytes());
+        }
+
+        let mut first = true;
+        while let Some(row) = rows.next().await {
+            let row = match serde_json::to_value(row?) {
+                Ok(Value::Object(row)) => row,
+                Ok(_) => return Err(RfError::Serialization("Exported rows must be objects".to_string())),
+                Err(e) => return Err(RfError::Serialization(format!("Failed to serialize row: {}", e))),
+            };
+            if first {
+                first = false;
+                if columns.is_empty() {
+                    columns = row.keys().map(|key| (key.clone(), key.clone())).collect();
+                }
+                let headers: Vec<Value> = columns.iter().map(|(_, header)| Value::String(header.clone())).collect();
+                match format {
+                    DataFormat::Csv => self.write_csv_row(&mut buf, &headers),
+                    DataFormat::Xlsx => sheet.push_row(&headers, false),
+                    DataFormat::JsonLines => {}
+                }
+            }
+            match format {
+                DataFormat::Csv => {
+                    let cells: Vec<Value> = columns.iter().map(|(field, _)| cell(&row, field)).collect();
+                    self.write_csv_row(&mut buf, &cells);
+                }
+                DataFormat::Xlsx => {
+                    let cells: Vec<Value> = columns.iter().map(|(field, _)| cell(&row, field)).collect();
+                    sheet.push_row(&cells, self.escape_formulas);
+                }
+                DataFormat::JsonLines => {
+                    let line = if self.columns.is_empty() {
+                        Value::Object(row)
+                    } else {
+                        Value::Object(columns.iter().map(|(field, _)| (field.clone(), cell(&row, field))).collect())
+                    };
+                    serde_json::to_writer(&mut buf, &line)
+                        .map_err(|e| RfError::Serialization(format!("Failed to serialize row: {}", e)))?;
+                    buf.push(b'\n');
+                }
+            }
+            if buf.len() >= CHUNK_SIZE && sender.send(Ok(Bytes::from(std::mem::take(&mut buf)))).await.is_err() {
+                return Ok(());
+            }
+        }
+
+        if format == DataFormat::Csv && first {
+            // No rows: still send the header when the columns are known
+            let headers: Vec<Value> = columns.iter().map(|(_, header)| Value::String(header.clone())).collect();
+            if !headers.is_empty() {
+                self.write_csv_row(&mut buf, &headers);
+            }
+        }
+        if format == DataFormat::Xlsx {
+            if first {
+                let headers: Vec<Value> = columns.iter().map(|(_, header)| Value::String(header.clone())).collect();
+                sheet.push_row(&headers, false);
+            }
+            let name = self.sheet.clone();
+            buf = tokio::task::spawn_blocking(move || sheet.finish(&name))
+                .await
+                .map_err(|e| RfError::Internal(format!("XLSX task failed: {}", e)))??;
+        }
+        if !buf.is_empty() {
+            let _ = sender.send(Ok(Bytes::from(buf))).await;
+        }
+        Ok(())
+    }
+
+    fn write_csv_row(&self, buf: &mut Vec<u8>, cells: &[Value]) {
+        write_csv_row(buf, cells, self.escape_formulas);
+    }
+}
+
+/// Append one CSV record, quoting cells that need it
+pub(crate) fn write_csv_row(buf: &mut Vec<u8>, cells: &[Value], escape_formulas: bool) {
+    for (index, value) in cells.iter().enumerate() {
+        if index > 0 {
+            buf.push(b',');
+        }
+        let text = cell_text(value, escape_formulas);
+        if text.contains([',', '"', '\n', '\r']) {
+            buf.push(b'"');
+            buf.extend_from_slice(text.replace('"', "\"\"").as_bytes());
+            buf.push(b'"');
+        } else {
+            buf.extend_from_slice(text.as_bytes());
+        }
+    }
+    buf.extend_from_slice(b"\r\n");
+}
+
+fn cell(row: &Map<String, Value>, field: &str) -> Value {
+    row.get(field).cloned().unwrap_or(Value::Null)
+}
+
+/// Text of a cell; nested values are written as JSON
+fn cell_text(value: &Value, escape_formulas: bool) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) if escape_formulas && s.starts_with(['=', '+', '-', '@']) => format!("'{}", s),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Worksheet XML built row by row
+#[derive(Default)]
+struct XlsxSheet {
+    xml: String,
+    rows: usize,
+}
+
+impl XlsxSheet {
+    fn push_row(&mut self, cells: &[Value], escape_formulas: bool) {
+        self.rows += 1;
+        let row = self.rows;
+        self.xml.push_str(&format!("<row r=\"{}\">", row));
+        for (index, value) in cells.iter().enumerate() {
+            let reference = format!("{}{}", column_name(index), row);
+            match value {
+                Value::Null => {}
+                Value::Number(n) => self.xml.push_str(&format!("<c r=\"{}\"><v>{}</v></c>", reference, n)),
+                Value::Bool(b) => self.xml.push_str(&format!("<c r=\"{}\" t=\"b\"><v>{}</v></c>", reference, *b as u8)),
+                other => self.xml.push_str(&format!(
+                    "<c r=\"{}\" t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+                    reference,
+                    xml_escape(&cell_text(other, escape_formulas))
+                )),
+            }
+        }
+        self.xml.push_str("</row>");
+    }
+
+    /// Package the sheet into an XLSX file
+    fn finish(self, sheet_name: &str) -> Result<Vec<u8>> {
+        const XML: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+        let sheet_name: String = sheet_name
+            .chars()
+            .filter(|c| !matches!(c, '\\' | '/' | '?' | '*' | '[' | ']' | ':'))
+            .take(31)
+            .collect();
+        let parts = [
+            (
+                "[Content_Types].xml",
+                format!(
+                    "{XML}<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+                     <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+                     <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+                     <Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+                     <Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>\
+                     </Types>"
+                ),
+            ),
+            (
+                "_rels/.rels",
+                format!(
+                    "{XML}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+                     <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>\
+                     </Relationships>"
+                ),
+            ),
+            (
+                "xl/workbook.xml",
+                format!(
+                    "{XML}<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+                     xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+                     <sheets><sheet name=\"{}\" sheetId=\"1\" r:id=\"rId1\"/></sheets></workbook>",
+                    xml_escape(if sheet_name.is_empty() { "Sheet1" } else { &sheet_name })
+                ),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                format!(
+                    "{XML}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+                     <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/>\
+                     </Relationships>"
+                ),
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                format!(
+                    "{XML}<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>{}</sheetData></worksheet>",
+                    self.xml
+                ),
+            ),
+        ];
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let zip_error = |e: zip::result::ZipError| RfError::Internal(format!("Failed to write XLSX: {}", e));
+        for (name, content) in parts {
+            zip.start_file(name, options).map_err(zip_error)?;
+            zip.write_all(content.as_bytes()).map_err(RfError::Io)?;
+        }
+        Ok(zip.finish().map_err(zip_error)?.into_inner())
+    }
+}
+
+/// Spreadsheet column letters for a zero-based index (`0` -> `A`, `26` -> `AA`)
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Escape text for XML, dropping characters XML 1.0 does not allow
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,65 @@
+//! # fault
+//!
+//! fault 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Fault injection for server routes
+//!
+//! `fault_middleware` runs every request through an `rf_core::fault::FaultInjector`
+//! keyed by the request path, so tests can make chosen routes slow, fail or
+//! drop the connection and check how clients retry and trip their breakers.
+//! Only built with the `fault-injection` feature.
+//!
+//! - latency delays the request before the handler runs
+//! - an error answers `{"code": status, "message": ...}` with that status
+//!   without calling the handler
+//! - a reset aborts the response, so the client sees the connection close
+//!   before a response arrives
+//!
+//! ```rust,ignore
+//! use rf_core::fault::{FaultInjector, FaultRule};
+//!
+//! let faults = FaultInjector::new()
+//!     .rule(FaultRule::new("/api/payments/*").error_rate(0.5).error_status(503))
+//!     .rule(FaultRule::new("/api/orders").reset_rate(1.0).times(1));
+//! let server = HttpServer::new(addr).with_faults(faults.clone());
+//! ```
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response as AxumResponse;
+use rf_core::fault::{Fault, FaultInjector};
+use serde_json::json;
+
+/// Inject faults into requests, see the module documentation
+///
+/// Use `HttpServer::with_faults`, or add it to a router with
+/// `axum::middleware::from_fn_with_state(injector, fault_middleware)`.
+pub async fn fault_middleware(State(faults): State<FaultInjector>, request: Request, next: Next) -> AxumResponse {
+    match faults.inject(request.uri().path()).await {
+        Ok(()) => next.run(request).await,
+        Err(Fault::Error { status, message }) => {
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+            let body = json!({"code": status.as_u16(), "message": message});
+            let mut response = AxumResponse::new(Body::from(body.to_string()));
+            *response.status_mut() = status;
+            response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            response
+        }
+        Err(Fault::Reset) => {
+            // A failing body makes hyper abort the connection instead of finishing the response
+            let reset = futures_util::stream::once(async {
+                Err::<axum::body::Bytes, _>(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "injected connection reset",
+                ))
+            });
+            AxumResponse::new(Body::from_stream(reset))
+        }
+    }
+}
@@ -0,0 +1,862 @@
+//! # import
+//!
+//! import 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Data import
+//!
+//! Reads CSV, XLSX or JSON-lines files into `T`, validates every row with
+//! `rf_util::valid::Validate` and inserts valid rows in batches through an
+//! `ImportSink` (a `Model` with the `import-db` feature). Invalid rows and
+//! failed batches are collected into an `ImportReport`, which can be
+//! written as a CSV error file for the uploader.
+//!
+//! Cell text is converted like request parameters: `"42"` binds to numeric
+//! fields, `"true"`/`"1"` to booleans and empty cells to `None`.
+//!
+//! `run_stream` and `run_multipart` decode CSV and JSON lines while the file
+//! arrives, inserting batch by batch, so a 1 GB upload is imported in
+//! constant memory. With `error_file` the row errors go to disk as well, and
+//! `ImportReport::error_response` serves them as a download.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{DataFormat, Import};
+//!
+//! #[derive(Deserialize, Serialize, Validate)]
+//! struct User {
+//!     #[valid(rule = "required|length:1,50")]
+//!     name: String,
+//!     #[valid(rule = "required|email")]
+//!     email: String,
+//! }
+//!
+//! let format = DataFormat::from_filename(&filename).unwrap_or_default();
+//! let report = Import::new(format)
+//!     .column("Name", "name")
+//!     .column("Email", "email")
+//!     .run::<User, _>(&file, &db.model("users"))
+//!     .await?;
+//! if !report.is_success() {
+//!     report.write_error_file("uploads/users.errors.csv").await?;
+//! }
+//! ```
+
+use super::export::{attachment, DataFormat};
+use super::parse::{self, ParseError};
+use async_trait::async_trait;
+use axum::body::{Body, Bytes};
+use axum::extract::Multipart;
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::http::HeaderValue;
+use axum::response::Response as AxumResponse;
+use futures_util::{Stream, StreamExt};
+use rf_errors::{Result, RfError};
+use rf_util::valid::Validate;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Destination of imported rows
+#[async_trait]
+pub trait ImportSink<T>: Send + Sync {
+    /// Insert a batch, returning the number of rows written
+    async fn insert_batch(&self, rows: &[T]) -> Result<u64>;
+}
+
+#[cfg(feature = "import-db")]
+#[async_trait]
+impl<T: Serialize + Sync> ImportSink<T> for rf_database::db::Model {
+    async fn insert_batch(&self, rows: &[T]) -> Result<u64> {
+        self.batch_insert(rows).await
+    }
+}
+
+/// Problem with one input row
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RowError {
+    /// 1-based row (or line) number in the file, counting the header
+    pub row: usize,
+    /// Failing field, if known
+    pub field: Option<String>,
+    pub message: String,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    /// Data rows read
+    pub total: usize,
+    /// Rows written by the sink
+    pub imported: u64,
+    /// Rows rejected by decoding, validation or a failed batch
+    pub failed: usize,
+    /// Row errors; only the first `ERROR_SAMPLE` when they go to `error_file`
+    pub errors: Vec<RowError>,
+    /// CSV file holding every row error, see `Import::error_file`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_file: Option<PathBuf>,
+}
+
+/// Row errors kept in `ImportReport::errors` when they are written to a file
+pub const ERROR_SAMPLE: usize = 100;
+
+const ERROR_CSV_HEADER: &str = "row,field,message\r\n";
+
+impl ImportReport {
+    pub fn is_success(&self) -> bool {
+        self.failed == 0
+    }
+
+    /// Errors as CSV with `row,field,message` columns
+    pub fn error_csv(&self) -> String {
+        let mut csv = String::from(ERROR_CSV_HEADER);
+        for error in &self.errors {
+            csv.push_str(&error_line(error));
+        }
+        csv
+    }
+
+    /// Write `error_csv` to `path`
+    pub async fn write_error_file(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        tokio::fs::write(path, self.error_csv()).await.map_err(RfError::Io)
+    }
+
+    /// The error report as a CSV download named `filename`
+    ///
+    /// Streams `error_file` when the import wrote one, `error_csv` otherwise.
+    pub async fn error_response(&self, filename: &str) -> Result<AxumResponse> {
+        let body = match &self.error_file {
+            Some(path) => {
+                let file = tokio::fs::File::open(path).await.map_err(RfError::Io)?;
+                Body::from_stream(futures_util::stream::unfold(file, |mut file| async move {
+                    let mut buf = vec![0; 16 * 1024];
+                    match file.read(&mut buf).await {
+                        Ok(0) => None,
+                        Ok(n) => {
+                            buf.truncate(n);
+                            Some((Ok(Bytes::from(buf)), file))
+                        }
+                        Err(e) => Some((Err(e), file)),
+                    }
+                }))
+            }
+            None => Body::from(self.error_csv()),
+        };
+        let mut response = AxumResponse::new(body);
+        response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static(DataFormat::Csv.content_type()));
+        if let Some(disposition) = attachment(filename) {
+            response.headers_mut().insert(CONTENT_DISPOSITION, disposition);
+        }
+        Ok(response)
+    }
+}
+
+fn error_line(error: &RowError) -> String {
+    let field = error.field.as_deref().unwrap_or("");
+    format!("{},{},{}\r\n", error.row, csv_field(field), csv_field(&error.message))
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Import settings
+#[derive(Debug, Clone)]
+pub struct Import {
+    format: DataFormat,
+    batch_size: usize,
+    columns: HashMap<String, String>,
+    max_errors: Option<usize>,
+    max_row_size: usize,
+    max_xlsx_size: usize,
+    field: Option<String>,
+    error_file: Option<PathBuf>,
+}
+
+impl Import {
+    pub fn new(format: DataFormat) -> Self {
+        Self {
+            format,
+            batch_size: 500,
+            columns: HashMap::new(),
+            max_errors: None,
+            max_row_size: 1024 * 1024,
+            max_xlsx_size: 64 * 1024 * 1024,
+            field: None,
+            error_file: None,
+        }
+    }
+
+    /// Rows per sink call (default 500)
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Read the `header` column (or JSON key) into `field`
+    ///
+    /// Unmapped headers are used as field names unchanged.
+    pub fn column(mut self, header: impl Into<String>, field: impl Into<String>) -> Self {
+        self.columns.insert(header.into(), field.into());
+        self
+    }
+
+    /// Stop after this many row errors; rows read so far stay imported
+    pub fn max_errors(mut self, max: usize) -> Self {
+        self.max_errors = Some(max);
+        self
+    }
+
+    /// Longest CSV row or JSON line in bytes (default 1 MiB)
+    ///
+    /// Only the row being read is buffered, so this bounds the memory of a
+    /// streamed import; a longer row fails the import.
+    pub fn max_row_size(mut self, size: usize) -> Self {
+        self.max_row_size = size.max(1);
+        self
+    }
+
+    /// Largest XLSX file read by `run_stream` (default 64 MiB)
+    ///
+    /// XLSX is a zip archive and is buffered whole before it is read.
+    pub fn max_xlsx_size(mut self, size: usize) -> Self {
+        self.max_xlsx_size = size;
+        self
+    }
+
+    /// Multipart field `run_multipart` imports (default the first file field)
+    pub fn field(mut self, name: impl Into<String>) -> Self {
+        self.field = Some(name.into());
+        self
+    }
+
+    /// Write every row error to a CSV file at `path` as it happens
+    ///
+    /// The report then keeps only the first `ERROR_SAMPLE` errors in memory.
+    pub fn error_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.error_file = Some(path.into());
+        self
+    }
+
+    /// Read, validate and insert `data`
+    ///
+    /// Fails only when the file itself cannot be read; row problems are in the report.
+    pub async fn run<T, S>(&self, data: &[u8], sink: &S) -> Result<ImportReport>
+    where
+        T: DeserializeOwned + Validate + Send + Sync,
+        S: ImportSink<T> + ?Sized,
+    {
+        self.run_format(self.format, futures_util::stream::iter([Ok(data)]), sink).await
+    }
+
+    /// Read, validate and insert a file arriving in chunks
+    ///
+    /// CSV and JSON lines are decoded as the chunks arrive, so memory is
+    /// bounded by `batch_size` and `max_row_size` whatever the file size.
+    /// A read error stops the import; batches inserted before it stay.
+    pub async fn run_stream<T, S, B>(&self, chunks: impl Stream<Item = Result<B>>, sink: &S) -> Result<ImportReport>
+    where
+        T: DeserializeOwned + Validate + Send + Sync,
+        S: ImportSink<T> + ?Sized,
+        B: AsRef<[u8]>,
+    {
+        self.run_format(self.format, chunks, sink).await
+    }
+
+    /// Import the file of a multipart upload while it is received
+    ///
+    /// Reads the `field` file field (the first file field by default); the
+    /// file's extension picks the format when it is a known one. Lift axum's
+    /// default 2 MB body limit on the route for large files.
+    ///
+    /// ```rust,ignore
+    /// async fn import_users(multipart: Multipart) -> Result<Json<ImportReport>> {
+    ///     let report = Import::new(DataFormat::Csv)
+    ///         .error_file(format!("imports/{}.errors.csv", Uuid::new_v4()))
+    ///         .run_multipart::<User, _>(multipart, &db.model("users"))
+    ///         .await?;
+    ///     Ok(Json(report))
+    /// }
+    /// ```
+    pub async fn run_multipart<T, S>(&self, mut multipart: Multipart, sink: &S) -> Result<ImportReport>
+    where
+        T: DeserializeOwned + Validate + Send + Sync,
+        S: ImportSink<T> + ?Sized,
+    {
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to read multipart field: {}", e)))?
+        {
+            let Some(filename) = field.file_name() else { continue };
+            if self.field.as_deref().is_some_and(|name| field.name() != Some(name)) {
+                continue;
+            }
+            let format = DataFormat::from_filename(filename).unwrap_or(self.format);
+            let chunks = futures_util::stream::unfold(field, |mut field| async move {
+                match field.chunk().await {
+                    Ok(Some(chunk)) => Some((Ok(chunk), field)),
+                    Ok(None) => None,
+                    Err(e) => Some((Err(RfError::Network(format!("Failed to read field data: {}", e))), field)),
+                }
+            });
+            return self.run_format(format, chunks, sink).await;
+        }
+        Err(RfError::Validation(match &self.field {
+            Some(name) => format!("Upload has no file field '{}'", name),
+            None => "Upload has no file".to_string(),
+        }))
+    }
+
+    async fn run_format<T, S, B>(&self, format: DataFormat, chunks: impl Stream<Item = Result<B>>, sink: &S) -> Result<ImportReport>
+    where
+        T: DeserializeOwned + Validate + Send + Sync,
+        S: ImportSink<T> + ?Sized,
+        B: AsRef<[u8]>,
+    {
+        let mut chunks = std::pin::pin!(chunks);
+        let mut batcher = Batcher::new(self, sink).await?;
+        if format == DataFormat::Xlsx {
+            let mut data = Vec::new();
+            while let Some(chunk) = chunks.next().await {
+                data.extend_from_slice(chunk?.as_ref());
+                if data.len() > self.max_xlsx_size {
+                    return Err(RfError::InvalidParameter(format!("XLSX file exceeds {} bytes", self.max_xlsx_size)));
+                }
+            }
+            let mut table = TableRows::default();
+            for (index, cells) in read_xlsx(&data)?.into_iter().enumerate() {
+                if let Some(record) = table.row(index + 1, cells) {
+                    if !batcher.add(record).await? {
+                        break;
+                    }
+                }
+            }
+            return batcher.finish().await;
+        }
+
+        let mut decoder = RecordDecoder::new(format, self.max_row_size);
+        let mut records = Vec::new();
+        'read: while let Some(chunk) = chunks.next().await {
+            decoder.push(chunk?.as_ref(), &mut records)?;
+            for record in records.drain(..) {
+                if !batcher.add(record).await? {
+                    break 'read;
+                }
+            }
+        }
+        if !batcher.stopped {
+            decoder.finish(&mut records)?;
+            for record in records.drain(..) {
+                if !batcher.add(record).await? {
+                    break;
+                }
+            }
+        }
+        batcher.finish().await
+    }
+
+    /// Map headers to fields, then bind and validate
+    fn decode<T: DeserializeOwned + Validate>(&self, record: Map<String, Value>) -> RowResult<T> {
+        let record: Map<String, Value> = record
+            .into_iter()
+            .map(|(key, value)| (self.columns.get(&key).cloned().unwrap_or(key), value))
+            .collect();
+        let value: T = parse::from_value(Value::Object(record)).map_err(|e| match e {
+            ParseError::Decode(message) => vec![(None, message)],
+            ParseError::Invalid(errors) => validation_errors(errors),
+        })?;
+        value.validate().map_err(validation_errors)?;
+        Ok(value)
+    }
+}
+
+type RowResult<T> = std::result::Result<T, Vec<(Option<String>, String)>>;
+
+/// Input record with its row number
+type Record = (usize, RowResult<Map<String, Value>>);
+
+fn validation_errors(errors: rf_util::valid::ValidationErrors) -> Vec<(Option<String>, String)> {
+    errors
+        .iter()
+        .flat_map(|(field, errors)| errors.iter().map(move |e| (Some(field.to_string()), e.message.clone())))
+        .collect()
+}
+
+/// Validated rows waiting for the sink, and the report so far
+struct Batcher<'a, T, S: ?Sized> {
+    import: &'a Import,
+    sink: &'a S,
+    report: ImportReport,
+    batch: Vec<T>,
+    rows: Vec<usize>,
+    error_count: usize,
+    error_file: Option<tokio::io::BufWriter<tokio::fs::File>>,
+    /// `max_errors` was reached
+    stopped: bool,
+}
+
+impl<'a, T, S> Batcher<'a, T, S>
+where
+    T: DeserializeOwned + Validate + Send + Sync,
+    S: ImportSink<T> + ?Sized,
+{
+    async fn new(import: &'a Import, sink: &'a S) -> Result<Self> {
+        let error_file = match &import.error_file {
+            Some(path) => {
+                let mut file = tokio::io::BufWriter::new(tokio::fs::File::create(path).await.map_err(RfError::Io)?);
+                file.write_all(ERROR_CSV_HEADER.as_bytes()).await.map_err(RfError::Io)?;
+                Some(file)
+            }
+            None => None,
+        };
+        Ok(Self {
+            import,
+            sink,
+            report: ImportReport { error_file: import.error_file.clone(), ..ImportReport::default() },
+            batch: Vec::with_capacity(import.batch_size),
+            rows: Vec::with_capacity(import.batch_size),
+            error_count: 0,
+            error_file,
+            stopped: false,
+        })
+    }
+
+    /// Decode one record into the batch, inserting full batches; `false` once `max_errors` is reached
+    async fn add(&mut self, (row, record): Record) -> Result<bool> {
+        if self.import.max_errors.is_some_and(|max| self.error_count >= max) {
+            self.stopped = true;
+            return Ok(false);
+        }
+        self.report.total += 1;
+        match record.and_then(|record| self.import.decode::<T>(record)) {
+            Ok(value) => {
+                self.batch.push(value);
+                self.rows.push(row);
+            }
+            Err(errors) => {
+                self.report.failed += 1;
+                let errors: Vec<RowError> = errors.into_iter().map(|(field, message)| RowError { row, field, message }).collect();
+                self.reject(errors).await?;
+            }
+        }
+        if self.batch.len() >= self.import.batch_size {
+            self.flush().await?;
+        }
+        Ok(true)
+    }
+
+    /// Insert the pending batch, marking all of its rows failed on error
+    async fn flush(&mut self) -> Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        match self.sink.insert_batch(&self.batch).await {
+            Ok(count) => self.report.imported += count,
+            Err(e) => {
+                tracing::warn!("Import batch of {} rows failed: {}", self.batch.len(), e);
+                self.report.failed += self.rows.len();
+                let errors: Vec<RowError> = self
+                    .rows
+                    .iter()
+                    .map(|&row| RowError { row, field: None, message: e.to_string() })
+                    .collect();
+                self.reject(errors).await?;
+            }
+        }
+        self.batch.clear();
+        self.rows.clear();
+        Ok(())
+    }
+
+    async fn reject(&mut self, errors: Vec<RowError>) -> Result<()> {
+        self.error_count += errors.len();
+        match &mut self.error_file {
+            Some(file) => {
+                for error in errors {
+                    file.write_all(error_line(&error).as_bytes()).await.map_err(RfError::Io)?;
+                    if self.report.errors.len() < ERROR_SAMPLE {
+                        self.report.errors.push(error);
+                    }
+                }
+            }
+            None => self.report.errors.extend(errors),
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<ImportReport> {
+        self.flush().await?;
+        if let Some(file) = &mut self.error_file {
+            file.flush().await.map_err(RfError::Io)?;
+        }
+        Ok(self.report)
+    }
+}
+
+/// Turns rows of cells into records, the first non-blank row being the header
+///
+/// Blank rows are skipped but still counted, so numbers match the file.
+#[derive(Default)]
+struct TableRows {
+    headers: Option<Vec<String>>,
+}
+
+impl TableRows {
+    /// Record of the 1-based row `number`, `None` for the header and blank rows
+    fn row(&mut self, number: usize, cells: Vec<String>) -> Option<Record> {
+        if cells.iter().all(|cell| cell.trim().is_empty()) {
+            return None;
+        }
+        let Some(headers) = &self.headers else {
+            self.headers = Some(cells.into_iter().map(|h| h.trim().to_string()).collect());
+            return None;
+        };
+        let record = headers
+            .iter()
+            .zip(cells.into_iter().chain(std::iter::repeat(String::new())))
+            .filter(|(header, _)| !header.is_empty())
+            .map(|(header, cell)| (header.clone(), Value::String(cell)))
+            .collect();
+        Some((number, Ok(record)))
+    }
+}
+
+const BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// Incremental CSV (RFC 4180 quoting) and JSON-lines decoder
+///
+/// Chunks may split rows, quotes, line breaks or UTF-8 characters anywhere;
+/// only the row being read is buffered. A leading UTF-8 BOM is skipped.
+struct RecordDecoder {
+    format: DataFormat,
+    max_row_size: usize,
+    /// Leading bytes held back until the BOM is ruled out
+    head: Option<Vec<u8>>,
+    /// Rows (CSV) or lines (JSON lines) completed
+    rows: usize,
+    /// Bytes of the current row or line
+    buf: Vec<u8>,
+    row_size: usize,
+    cells: Vec<String>,
+    quoted: bool,
+    /// Quote inside a quoted cell: the closing quote or the first of `""`
+    quote: bool,
+    /// Unquoted `\r`, dropped before `\n`
+    cr: bool,
+    table: TableRows,
+}
+
+impl RecordDecoder {
+    fn new(format: DataFormat, max_row_size: usize) -> Self {
+        Self {
+            format,
+            max_row_size,
+            head: Some(Vec::new()),
+            rows: 0,
+            buf: Vec::new(),
+            row_size: 0,
+            cells: Vec::new(),
+            quoted: false,
+            quote: false,
+            cr: false,
+            table: TableRows::default(),
+        }
+    }
+
+    /// Decode `chunk`, appending completed records to `out`
+    fn push(&mut self, mut chunk: &[u8], out: &mut Vec<Record>) -> Result<()> {
+        if let Some(head) = &mut self.head {
+            let take = (BOM.len() - head.len()).min(chunk.len());
+            head.extend_from_slice(&chunk[..take]);
+            chunk = &chunk[take..];
+            if head.len() < BOM.len() && BOM.starts_with(head) {
+                return Ok(());
+            }
+            self.start(out)?;
+        }
+        chunk.iter().try_for_each(|&byte| self.byte(byte, out))
+    }
+
+    /// Decode the held-back leading bytes unless they are the BOM
+    fn start(&mut self, out: &mut Vec<Record>) -> Result<()> {
+        match self.head.take() {
+            Some(head) if head != BOM => head.into_iter().try_for_each(|byte| self.byte(byte, out)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Decode the last row, which may lack a line break
+    fn finish(&mut self, out: &mut Vec<Record>) -> Result<()> {
+        self.start(out)?;
+        match self.format {
+            DataFormat::JsonLines => {
+                if !self.buf.is_empty() {
+                    self.end_line(out)?;
+                }
+            }
+            _ => {
+                if std::mem::take(&mut self.quote) {
+                    self.quoted = false;
+                }
+                if self.quoted {
+                    return Err(RfError::InvalidParameter("CSV file has an unterminated quoted field".to_string()));
+                }
+                if std::mem::take(&mut self.cr) {
+                    self.buf.push(b'\r');
+                }
+                if !self.buf.is_empty() || !self.cells.is_empty() {
+                    self.end_row(out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn byte(&mut self, byte: u8, out: &mut Vec<Record>) -> Result<()> {
+        self.row_size += 1;
+        if self.row_size > self.max_row_size {
+            return Err(RfError::InvalidParameter(format!("Row {} exceeds {} bytes", self.rows + 1, self.max_row_size)));
+        }
+        if self.format == DataFormat::JsonLines {
+            return match byte {
+                b'\n' => self.end_line(out),
+                byte => {
+                    self.buf.push(byte);
+                    Ok(())
+                }
+            };
+        }
+
+        if std::mem::take(&mut self.cr) {
+            if byte == b'\n' {
+                return self.end_row(out);
+            }
+            self.buf.push(b'\r');
+        }
+        if std::mem::take(&mut self.quote) {
+            if byte == b'"' {
+                self.buf.push(b'"');
+                return Ok(());
+            }
+            self.quoted = false;
+        }
+        match byte {
+            b'"' if self.quoted => self.quote = true,
+            b'"' if self.buf.is_empty() => self.quoted = true,
+            b',' if !self.quoted => self.end_cell()?,
+            b'\r' if !self.quoted => self.cr = true,
+            b'\n' if !self.quoted => self.end_row(out)?,
+            byte => self.buf.push(byte),
+        }
+        Ok(())
+    }
+
+    fn end_cell(&mut self) -> Result<()> {
+        let cell = String::from_utf8(std::mem::take(&mut self.buf))
+            .map_err(|e| RfError::InvalidParameter(format!("CSV file is not UTF-8 (row {}): {}", self.rows + 1, e)))?;
+        self.cells.push(cell);
+        Ok(())
+    }
+
+    fn end_row(&mut self, out: &mut Vec<Record>) -> Result<()> {
+        self.end_cell()?;
+        self.rows += 1;
+        self.row_size = 0;
+        let cells = std::mem::take(&mut self.cells);
+        out.extend(self.table.row(self.rows, cells));
+        Ok(())
+    }
+
+    /// One JSON object per non-empty line
+    fn end_line(&mut self, out: &mut Vec<Record>) -> Result<()> {
+        self.rows += 1;
+        self.row_size = 0;
+        let line = std::mem::take(&mut self.buf);
+        let line = std::str::from_utf8(&line)
+            .map_err(|e| RfError::InvalidParameter(format!("JSON lines file is not UTF-8 (line {}): {}", self.rows, e)))?;
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        let record = match serde_json::from_str(line) {
+            Ok(Value::Object(record)) => Ok(record),
+            Ok(_) => Err(vec![(None, "Line is not a JSON object".to_string())]),
+            Err(e) => Err(vec![(None, format!("Invalid JSON: {}", e))]),
+        };
+        out.push((self.rows, record));
+        Ok(())
+    }
+}
+
+/// Read the first worksheet of an XLSX file into rows of cell text
+///
+/// Numbers are returned as written in the file; dates are Excel serial numbers.
+fn read_xlsx(data: &[u8]) -> Result<Vec<Vec<String>>> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data))
+        .map_err(|e| RfError::InvalidParameter(format!("Invalid XLSX file: {}", e)))?;
+    let mut read_part = |name: &str| -> Result<Option<String>> {
+        let mut file = match archive.by_name(name) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(RfError::InvalidParameter(format!("Invalid XLSX file: {}", e))),
+        };
+        let mut content = String::new();
+        file.read_to_string(&mut content).map_err(RfError::Io)?;
+        Ok(Some(content))
+    };
+
+    let shared_strings = read_part("xl/sharedStrings.xml")?.map(|xml| parse_shared_strings(&xml)).transpose()?;
+    let sheet_path = match (read_part("xl/workbook.xml")?, read_part("xl/_rels/workbook.xml.rels")?) {
+        (Some(workbook), Some(rels)) => first_sheet_path(&workbook, &rels)?,
+        _ => None,
+    }
+    .unwrap_or_else(|| "xl/worksheets/sheet1.xml".to_string());
+    let sheet = read_part(&sheet_path)?
+        .ok_or_else(|| RfError::InvalidParameter("XLSX file has no worksheet".to_string()))?;
+    parse_sheet(&sheet, &shared_strings.unwrap_or_default())
+}
+
+fn xml_error(e: impl std::fmt::Display) -> RfError {
+    RfError::InvalidParameter(format!("Invalid XLSX file: {}", e))
+}
+
+fn attribute(element: &quick_xml::events::BytesStart<'_>, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == name || attr.key.local_name().as_ref() == name)
+        .and_then(|attr| attr.unescape_value().ok().map(|v| v.into_owned()))
+}
+
+/// Path of the first sheet listed in the workbook
+fn first_sheet_path(workbook: &str, rels: &str) -> Result<Option<String>> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(workbook);
+    let mut id = None;
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                id = attribute(&e, b"r:id");
+                break;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let Some(id) = id else { return Ok(None) };
+
+    let mut reader = quick_xml::Reader::from_str(rels);
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e)
+                if e.local_name().as_ref() == b"Relationship" && attribute(&e, b"Id").as_deref() == Some(&id) =>
+            {
+                return Ok(attribute(&e, b"Target").map(|target| match target.strip_prefix('/') {
+                    Some(absolute) => absolute.to_string(),
+                    None => format!("xl/{}", target),
+                }));
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Shared string table; rich text runs are concatenated
+fn parse_shared_strings(xml: &str) -> Result<Vec<String>> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) if e.local_name().as_ref() == b"si" => current.clear(),
+            Event::End(e) if e.local_name().as_ref() == b"si" => strings.push(std::mem::take(&mut current)),
+            Event::Start(e) if e.local_name().as_ref() == b"t" => in_text = true,
+            Event::End(e) if e.local_name().as_ref() == b"t" => in_text = false,
+            Event::Text(text) if in_text => current.push_str(&text.unescape().map_err(xml_error)?),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(strings)
+}
+
+/// Zero-based column of a cell reference such as `B7`
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: String = reference.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let index = letters
+        .to_ascii_uppercase()
+        .bytes()
+        .fold(0usize, |acc, b| acc * 26 + (b - b'A' + 1) as usize);
+    Some(index - 1)
+}
+
+fn parse_sheet(xml: &str, shared_strings: &[String]) -> Result<Vec<Vec<String>>> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut row_number = 0;
+    let (mut cell_type, mut column) = (String::new(), 0);
+    let mut value = String::new();
+    let mut in_value = false;
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) if e.local_name().as_ref() == b"row" => {
+                // Skipped row numbers are blank rows
+                let number = attribute(&e, b"r").and_then(|r| r.parse().ok()).unwrap_or(row_number + 1);
+                while row_number + 1 < number {
+                    rows.push(Vec::new());
+                    row_number += 1;
+                }
+                row_number = number;
+                row.clear();
+            }
+            Event::End(e) if e.local_name().as_ref() == b"row" => rows.push(std::mem::take(&mut row)),
+            Event::Empty(e) if e.local_name().as_ref() == b"row" => {
+                rows.push(Vec::new());
+                row_number += 1;
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"c" => {
+                cell_type = attribute(&e, b"t").unwrap_or_default();
+                column = attribute(&e, b"r").and_then(|r| column_index(&r)).unwrap_or(row.len());
+                value.clear();
+            }
+            Event::End(e) if e.local_name().as_ref() == b"c" => {
+                let text = match cell_type.as_str() {
+                    "s" => value.trim().parse::<usize>().ok().and_then(|i| shared_strings.get(i).cloned()).unwrap_or_default(),
+                    "b" => (value.trim() == "1").to_string(),
+                    _ => std::mem::take(&mut value),
+                };
+                if row.len() <= column {
+                    row.resize(column + 1, String::new());
+                }
+                row[column] = text;
+            }
+            Event::Start(e) if matches!(e.local_name().as_ref(), b"v" | b"t") => in_value = true,
+            Event::End(e) if matches!(e.local_name().as_ref(), b"v" | b"t") => in_value = false,
+            Event::Text(text) if in_value => value.push_str(&text.unescape().map_err(xml_error)?),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(rows)
+}
@@ -0,0 +1,473 @@
+//! # jsonrpc
+//!
+//! jsonrpc 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! JSON-RPC 2.0 over HTTP and WebSocket
+//!
+//! Methods are registered with typed parameters and results. Parameters
+//! are deserialized from either a JSON object or a positional array, so a
+//! handler taking a struct or a tuple works with both styles. Batch
+//! requests and notifications are supported on both transports, and
+//! `rf_errors::RfError` values are mapped to JSON-RPC error codes.
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct AddParams { a: i64, b: i64 }
+//!
+//! let rpc = JsonRpcServer::new()
+//!     .method("add", |p: AddParams| async move { Ok::<_, RfError>(p.a + p.b) })
+//!     .method("ping", |_: ()| async { Ok::<_, RfError>("pong") });
+//! let rpc = Arc::new(rpc);
+//! let app = Router::new().merge(rpc.clone().router("/rpc"));
+//!
+//! // Push a notification to every connected WebSocket client
+//! rpc.broadcast("stats.updated", json!({ "online": 42 }));
+//! ```
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use futures_util::future::{join_all, BoxFuture};
+use futures_util::{SinkExt, StreamExt};
+use rf_errors::RfError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+
+/// JSON-RPC error object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl JsonRpcError {
+    /// Invalid JSON was received
+    pub const PARSE_ERROR: i64 = -32700;
+    /// The JSON sent is not a valid request object
+    pub const INVALID_REQUEST: i64 = -32600;
+    /// The method does not exist
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    /// Invalid method parameters
+    pub const INVALID_PARAMS: i64 = -32602;
+    /// Internal JSON-RPC error
+    pub const INTERNAL_ERROR: i64 = -32603;
+    /// `RfError::Unauthorized`
+    pub const UNAUTHORIZED: i64 = -32001;
+    /// `RfError::Forbidden`
+    pub const FORBIDDEN: i64 = -32003;
+    /// `RfError::NotFound`
+    pub const NOT_FOUND: i64 = -32004;
+    /// `RfError::Timeout`
+    pub const TIMEOUT: i64 = -32008;
+
+    /// Create an error with a code and message
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Attach additional data
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn parse_error() -> Self {
+        Self::new(Self::PARSE_ERROR, "Parse error")
+    }
+
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_REQUEST, message)
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(Self::METHOD_NOT_FOUND, format!("Method not found: {}", method))
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_PARAMS, message)
+    }
+
+    pub fn internal_error() -> Self {
+        Self::new(Self::INTERNAL_ERROR, "Internal error")
+    }
+}
+
+impl std::fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "JSON-RPC error {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
+/// Client errors keep their message; server errors are reported as
+/// `Internal error` so details do not leak. The RF error code is included
+/// as `data.code`.
+impl From<RfError> for JsonRpcError {
+    fn from(err: RfError) -> Self {
+        let code = match &err {
+            RfError::InvalidParameter(_) | RfError::Validation(_) => Self::INVALID_PARAMS,
+            RfError::Unauthorized(_) => Self::UNAUTHORIZED,
+            RfError::Forbidden(_) => Self::FORBIDDEN,
+            RfError::NotFound(_) => Self::NOT_FOUND,
+            RfError::Timeout(_) => Self::TIMEOUT,
+            _ => Self::INTERNAL_ERROR,
+        };
+        let message = if code == Self::INTERNAL_ERROR {
+            tracing::error!("JSON-RPC method failed: {}", err);
+            "Internal error".to_string()
+        } else {
+            err.to_string()
+        };
+        Self::new(code, message).with_data(serde_json::json!({ "code": err.code() }))
+    }
+}
+
+impl From<JsonRpcError> for RfError {
+    fn from(err: JsonRpcError) -> Self {
+        match err.code {
+            JsonRpcError::PARSE_ERROR | JsonRpcError::INVALID_REQUEST | JsonRpcError::INVALID_PARAMS => RfError::InvalidParameter(err.message),
+            JsonRpcError::METHOD_NOT_FOUND | JsonRpcError::NOT_FOUND => RfError::NotFound(err.message),
+            JsonRpcError::UNAUTHORIZED => RfError::Unauthorized(err.message),
+            JsonRpcError::FORBIDDEN => RfError::Forbidden(err.message),
+            JsonRpcError::TIMEOUT => RfError::Timeout(err.message),
+            _ => RfError::Internal(err.message),
+        }
+    }
+}
+
+/// Distinguish a missing `id` (notification) from `"id": null`
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// JSON-RPC request or notification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+    /// `None` for notifications
+    #[serde(default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+}
+
+impl JsonRpcRequest {
+    /// Create a request expecting a response
+    pub fn new(method: impl Into<String>, params: Option<Value>, id: impl Into<Value>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: method.into(),
+            params,
+            id: Some(id.into()),
+        }
+    }
+
+    /// Create a notification
+    pub fn notification(method: impl Into<String>, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: method.into(),
+            params,
+            id: None,
+        }
+    }
+
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
+    }
+}
+
+/// JSON-RPC response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+    pub id: Value,
+}
+
+impl JsonRpcResponse {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            result: Some(result),
+            error: None,
+            id,
+        }
+    }
+
+    pub fn failure(id: Value, error: JsonRpcError) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            result: None,
+            error: Some(error),
+            id,
+        }
+    }
+
+    /// Convert into a `Result`
+    pub fn into_result(self) -> Result<Value, JsonRpcError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.result.unwrap_or(Value::Null)),
+        }
+    }
+}
+
+type Method = Arc<dyn Fn(Value) -> BoxFuture<'static, Result<Value, JsonRpcError>> + Send + Sync>;
+
+/// JSON-RPC 2.0 method registry and dispatcher
+pub struct JsonRpcServer {
+    methods: HashMap<String, Method>,
+    max_batch_size: usize,
+    notifications: broadcast::Sender<String>,
+}
+
+impl JsonRpcServer {
+    /// Create an empty server
+    pub fn new() -> Self {
+        Self {
+            methods: HashMap::new(),
+            max_batch_size: 100,
+            notifications: broadcast::channel(256).0,
+        }
+    }
+
+    /// Register a method with typed parameters
+    ///
+    /// Use `()` for methods without parameters. A missing `params` member
+    /// is treated as `null`.
+    pub fn method<P, R, E, F, Fut>(mut self, name: impl Into<String>, handler: F) -> Self
+    where
+        P: DeserializeOwned + Send + 'static,
+        R: Serialize + 'static,
+        E: Into<JsonRpcError> + 'static,
+        F: Fn(P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        let method: Method = Arc::new(move |params: Value| {
+            let handler = handler.clone();
+            Box::pin(async move {
+                let params: P = serde_json::from_value(params)
+                    .map_err(|e| JsonRpcError::invalid_params(format!("Invalid params: {}", e)))?;
+                let result = handler(params).await.map_err(Into::into)?;
+                serde_json::to_value(result).map_err(|e| {
+                    tracing::error!("Failed to serialize JSON-RPC result: {}", e);
+                    JsonRpcError::internal_error()
+                })
+            })
+        });
+        self.methods.insert(name.into(), method);
+        self
+    }
+
+    /// Maximum number of calls in a batch (default: 100)
+    pub fn max_batch_size(mut self, size: usize) -> Self {
+        self.max_batch_size = size;
+        self
+    }
+
+    /// Whether a method is registered
+    pub fn has_method(&self, name: &str) -> bool {
+        self.methods.contains_key(name)
+    }
+
+    /// Send a notification to all connected WebSocket clients
+    ///
+    /// Returns the number of clients it was queued for.
+    pub fn broadcast(&self, method: &str, params: impl Serialize) -> usize {
+        let params = match serde_json::to_value(params) {
+            Ok(Value::Null) => None,
+            Ok(params) => Some(params),
+            Err(e) => {
+                tracing::error!("Failed to serialize JSON-RPC notification: {}", e);
+                return 0;
+            }
+        };
+        let notification = JsonRpcRequest::notification(method, params);
+        match serde_json::to_string(&notification) {
+            Ok(text) => self.notifications.send(text).unwrap_or(0),
+            Err(_) => 0,
+        }
+    }
+
+    /// Execute a single request; `None` for notifications
+    pub async fn call(&self, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
+        let id = request.id.clone();
+        let result = if request.jsonrpc != "2.0" {
+            Err(JsonRpcError::invalid_request("jsonrpc must be \"2.0\""))
+        } else {
+            match self.methods.get(&request.method) {
+                Some(method) => method(request.params.unwrap_or(Value::Null)).await,
+                None => Err(JsonRpcError::method_not_found(&request.method)),
+            }
+        };
+        let id = id?;
+        Some(match result {
+            Ok(value) => JsonRpcResponse::success(id, value),
+            Err(error) => JsonRpcResponse::failure(id, error),
+        })
+    }
+
+    /// Handle a parsed message (single request or batch)
+    ///
+    /// Returns `None` when nothing should be sent back, i.e. for
+    /// notifications and batches made only of notifications.
+    pub async fn handle_value(&self, message: Value) -> Option<Value> {
+        match message {
+            Value::Array(calls) => {
+                if calls.is_empty() {
+                    return Some(invalid_request_response("Empty batch"));
+                }
+                if calls.len() > self.max_batch_size {
+                    return Some(invalid_request_response(&format!(
+                        "Batch exceeds {} calls",
+                        self.max_batch_size
+                    )));
+                }
+                let responses: Vec<Value> = join_all(calls.into_iter().map(|call| self.handle_single(call)))
+                    .await
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                if responses.is_empty() {
+                    None
+                } else {
+                    Some(Value::Array(responses))
+                }
+            }
+            call => self.handle_single(call).await,
+        }
+    }
+
+    /// Handle a raw message; `None` when nothing should be sent back
+    pub async fn handle(&self, body: &str) -> Option<String> {
+        let response = match serde_json::from_str::<Value>(body) {
+            Ok(message) => self.handle_value(message).await?,
+            Err(_) => error_response(Value::Null, JsonRpcError::parse_error()),
+        };
+        Some(response.to_string())
+    }
+
+    async fn handle_single(&self, call: Value) -> Option<Value> {
+        let request: JsonRpcRequest = match serde_json::from_value(call) {
+            Ok(request) => request,
+            Err(e) => return Some(invalid_request_response(&e.to_string())),
+        };
+        let response = self.call(request).await?;
+        serde_json::to_value(response).ok()
+    }
+
+    /// Router serving HTTP POST and WebSocket on the same path
+    pub fn router(self: Arc<Self>, path: &str) -> Router {
+        let http = self.clone();
+        Router::new().route(
+            path,
+            axum::routing::post(move |body: String| {
+                let server = http.clone();
+                async move { server.http_response(&body).await }
+            })
+            .get(move |ws: WebSocketUpgrade| {
+                let server = self.clone();
+                async move { ws.on_upgrade(move |socket| server.serve_websocket(socket)) }
+            }),
+        )
+    }
+
+    async fn http_response(&self, body: &str) -> Response {
+        match self.handle(body).await {
+            Some(response) => ([(header::CONTENT_TYPE, "application/json")], response).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        }
+    }
+
+    /// Serve JSON-RPC on an upgraded WebSocket connection
+    ///
+    /// Calls are handled concurrently; responses may arrive out of order
+    /// and are matched by `id`. Broadcast notifications are forwarded to
+    /// the client as well.
+    pub async fn serve_websocket(self: Arc<Self>, socket: WebSocket) {
+        let (mut sink, mut stream) = socket.split();
+        let (tx, mut rx) = mpsc::channel::<String>(64);
+        let mut notifications = self.notifications.subscribe();
+
+        let writer = tokio::spawn(async move {
+            loop {
+                let text = tokio::select! {
+                    text = rx.recv() => match text {
+                        Some(text) => text,
+                        None => break,
+                    },
+                    notification = notifications.recv() => match notification {
+                        Ok(text) => text,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::warn!("WebSocket client lagging, dropped {} notifications", skipped);
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => continue,
+                    },
+                };
+                if sink.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        while let Some(message) = stream.next().await {
+            let text = match message {
+                Ok(Message::Text(text)) => text.to_string(),
+                Ok(Message::Binary(data)) => String::from_utf8_lossy(&data).into_owned(),
+                Ok(Message::Close(_)) => break,
+                Ok(_) => continue,
+                Err(e) => {
+                    tracing::debug!("WebSocket error: {}", e);
+                    break;
+                }
+            };
+            let server = self.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                if let Some(response) = server.handle(&text).await {
+                    let _ = tx.send(response).await;
+                }
+            });
+        }
+
+        drop(tx);
+        writer.abort();
+    }
+}
+
+impl Default for JsonRpcServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn error_response(id: Value, error: JsonRpcError) -> Value {
+    serde_json::to_value(JsonRpcResponse::failure(id, error)).unwrap_or(Value::Null)
+}
+
+fn invalid_request_response(message: &str) -> Value {
+    error_response(Value::Null, JsonRpcError::invalid_request(message))
+}
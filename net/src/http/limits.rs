@@ -0,0 +1,503 @@
+//! # limits
+//!
+//! limits 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Request size limits and slow-client protections
+//!
+//! Connection-level limits (header size and read timeout, write timeout,
+//! connection counts) are applied by the server's accept loop. Body limits
+//! are applied per request, globally or per route pattern, and answer with
+//! `413 Payload Too Large` or `408 Request Timeout` whatever the handler does
+//! with the body read error.
+
+use super::router::RadixRouter;
+use axum::body::{Body, Bytes};
+use axum::extract::{ConnectInfo, Request};
+use axum::http::header::CONTENT_LENGTH;
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::serve::Listener;
+use axum::Router;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use http_body_util::{LengthLimitError, Limited};
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::service::TowerToHyperService;
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::{watch, Semaphore};
+use tokio::time::Sleep;
+use tower::ServiceExt;
+
+/// Smallest header buffer hyper accepts
+const MIN_HEADER_SIZE: usize = 8192;
+
+/// Server-wide limits
+///
+/// Defaults: 64 KiB of headers, at most 100 headers, 30 s to receive
+/// request headers; no body, write or connection limits. Timeouts
+/// deserialize from strings such as `"30s"`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ServerLimits {
+    /// Maximum request body size in bytes (413)
+    pub max_body_size: Option<usize>,
+    /// Time allowed to receive the whole request body, from the start of the request (408)
+    #[serde(with = "rf_os::cfg::serde_duration::option")]
+    pub body_read_timeout: Option<Duration>,
+    /// Maximum size of the request line and headers (431)
+    pub max_header_size: usize,
+    /// Maximum number of request headers (431)
+    pub max_headers: usize,
+    /// Time allowed to receive request headers; closes idle keep-alive and slowloris connections
+    #[serde(with = "rf_os::cfg::serde_duration::option")]
+    pub header_read_timeout: Option<Duration>,
+    /// Time a response write may stall before the connection is closed
+    #[serde(with = "rf_os::cfg::serde_duration::option")]
+    pub write_timeout: Option<Duration>,
+    /// Maximum concurrent connections; further clients wait to be accepted
+    pub max_connections: Option<usize>,
+    /// Maximum concurrent connections from one IP; extra connections are closed
+    pub max_connections_per_ip: Option<usize>,
+}
+
+impl Default for ServerLimits {
+    fn default() -> Self {
+        Self {
+            max_body_size: None,
+            body_read_timeout: None,
+            max_header_size: 64 * 1024,
+            max_headers: 100,
+            header_read_timeout: Some(Duration::from_secs(30)),
+            write_timeout: None,
+            max_connections: None,
+            max_connections_per_ip: None,
+        }
+    }
+}
+
+impl ServerLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = Some(size);
+        self
+    }
+
+    pub fn body_read_timeout(mut self, timeout: Duration) -> Self {
+        self.body_read_timeout = Some(timeout);
+        self
+    }
+
+    /// Values below 8 KiB are raised to 8 KiB
+    pub fn max_header_size(mut self, size: usize) -> Self {
+        self.max_header_size = size;
+        self
+    }
+
+    pub fn max_headers(mut self, count: usize) -> Self {
+        self.max_headers = count;
+        self
+    }
+
+    /// `None` disables the timeout
+    pub fn header_read_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.header_read_timeout = timeout.into();
+        self
+    }
+
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.write_timeout = Some(timeout);
+        self
+    }
+
+    pub fn max_connections(mut self, count: usize) -> Self {
+        self.max_connections = Some(count);
+        self
+    }
+
+    pub fn max_connections_per_ip(mut self, count: usize) -> Self {
+        self.max_connections_per_ip = Some(count);
+        self
+    }
+}
+
+/// Body limits for a route pattern, overriding `ServerLimits`
+///
+/// A route limit may be larger than the server limit, e.g. for uploads.
+#[derive(Debug, Clone, Default)]
+pub struct RouteLimits {
+    pub max_body_size: Option<usize>,
+    pub body_read_timeout: Option<Duration>,
+}
+
+impl RouteLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = Some(size);
+        self
+    }
+
+    pub fn body_read_timeout(mut self, timeout: Duration) -> Self {
+        self.body_read_timeout = Some(timeout);
+        self
+    }
+}
+
+/// Body size limit in effect for a request, available as a request extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimit(pub usize);
+
+/// Per-request limits applied by the server middleware
+pub(crate) struct RequestLimits {
+    pub max_header_size: usize,
+    pub server: RouteLimits,
+    pub routes: RadixRouter<RouteLimits>,
+}
+
+impl RequestLimits {
+    /// Method key under which route limits are stored (they apply to all methods)
+    pub const ANY_METHOD: &'static str = "*";
+
+    fn for_path(&self, path: &str) -> RouteLimits {
+        let route = self.routes.at(Self::ANY_METHOD, path);
+        let route = route.as_ref().map(|m| m.value);
+        RouteLimits {
+            max_body_size: route.and_then(|r| r.max_body_size).or(self.server.max_body_size),
+            body_read_timeout: route.and_then(|r| r.body_read_timeout).or(self.server.body_read_timeout),
+        }
+    }
+}
+
+#[derive(Default)]
+struct GuardState {
+    too_large: AtomicBool,
+    timed_out: AtomicBool,
+}
+
+/// Apply header and body limits, answering 431/413/408 when they are hit
+pub(crate) async fn limits_middleware(limits: Arc<RequestLimits>, request: Request, next: Next) -> Response {
+    let header_size = request.method().as_str().len()
+        + request.uri().to_string().len()
+        + request
+            .headers()
+            .iter()
+            .map(|(name, value)| name.as_str().len() + value.len() + 4)
+            .sum::<usize>();
+    if header_size > limits.max_header_size {
+        return (StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE, "Request header fields too large").into_response();
+    }
+
+    let route = limits.for_path(request.uri().path());
+    if let Some(max) = route.max_body_size {
+        let length = request
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        if length.is_some_and(|length| length > max as u64) {
+            return payload_too_large();
+        }
+    }
+    if route.max_body_size.is_none() && route.body_read_timeout.is_none() {
+        return next.run(request).await;
+    }
+
+    let state = Arc::new(GuardState::default());
+    let (mut parts, body) = request.into_parts();
+    if let Some(max) = route.max_body_size {
+        parts.extensions.insert(BodyLimit(max));
+    }
+    let body = GuardedBody {
+        inner: Limited::new(body, route.max_body_size.unwrap_or(usize::MAX)),
+        deadline: route.body_read_timeout.map(|t| Box::pin(tokio::time::sleep(t))),
+        state: state.clone(),
+    };
+    let response = next.run(Request::from_parts(parts, Body::new(body))).await;
+
+    if state.timed_out.load(Ordering::Acquire) {
+        (StatusCode::REQUEST_TIMEOUT, "Request body read timeout").into_response()
+    } else if state.too_large.load(Ordering::Acquire) {
+        payload_too_large()
+    } else {
+        response
+    }
+}
+
+fn payload_too_large() -> Response {
+    (StatusCode::PAYLOAD_TOO_LARGE, "Payload too large").into_response()
+}
+
+/// Request body enforcing a size limit and a read deadline
+struct GuardedBody {
+    inner: Limited<Body>,
+    deadline: Option<Pin<Box<Sleep>>>,
+    state: Arc<GuardState>,
+}
+
+impl HttpBody for GuardedBody {
+    type Data = Bytes;
+    type Error = axum::BoxError;
+
+    fn poll_frame(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Bytes>, Self::Error>>> {
+        if let Some(deadline) = self.deadline.as_mut() {
+            if deadline.as_mut().poll(cx).is_ready() {
+                self.state.timed_out.store(true, Ordering::Release);
+                let error = std::io::Error::new(std::io::ErrorKind::TimedOut, "request body read timeout");
+                return Poll::Ready(Some(Err(error.into())));
+            }
+        }
+        let frame = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Err(e))) = &frame {
+            if e.is::<LengthLimitError>() {
+                self.state.too_large.store(true, Ordering::Release);
+            }
+        }
+        if let Poll::Ready(None) = &frame {
+            self.deadline = None;
+        }
+        frame
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Connection IO closing the connection when a write stalls
+struct TimeoutIo<I> {
+    inner: I,
+    write_timeout: Option<Duration>,
+    write_deadline: Option<Pin<Box<Sleep>>>,
+}
+
+impl<I> TimeoutIo<I> {
+    fn new(inner: I, write_timeout: Option<Duration>) -> Self {
+        Self {
+            inner,
+            write_timeout,
+            write_deadline: None,
+        }
+    }
+
+    /// Track a pending write, failing once it has stalled for too long
+    fn poll_stalled<T>(&mut self, cx: &mut Context<'_>, poll: Poll<std::io::Result<T>>) -> Poll<std::io::Result<T>> {
+        match (poll, self.write_timeout) {
+            (Poll::Pending, Some(timeout)) => {
+                let deadline = self.write_deadline.get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+                match deadline.as_mut().poll(cx) {
+                    Poll::Ready(()) => Poll::Ready(Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        "response write timeout",
+                    ))),
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+            (poll, _) => {
+                if poll.is_ready() {
+                    self.write_deadline = None;
+                }
+                poll
+            }
+        }
+    }
+}
+
+impl<I: AsyncRead + Unpin> AsyncRead for TimeoutIo<I> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<I: AsyncWrite + Unpin> AsyncWrite for TimeoutIo<I> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        self.poll_stalled(cx, poll)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        self.poll_stalled(cx, poll)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let poll = Pin::new(&mut self.inner).poll_flush(cx);
+        self.poll_stalled(cx, poll)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let poll = Pin::new(&mut self.inner).poll_shutdown(cx);
+        self.poll_stalled(cx, poll)
+    }
+}
+
+/// Releases a per-IP connection slot on drop
+struct IpSlot {
+    counts: Arc<Mutex<HashMap<IpAddr, usize>>>,
+    ip: IpAddr,
+}
+
+impl IpSlot {
+    fn acquire(counts: &Arc<Mutex<HashMap<IpAddr, usize>>>, ip: IpAddr, max: usize) -> Option<Self> {
+        let mut map = counts.lock().unwrap_or_else(|e| e.into_inner());
+        let count = map.entry(ip).or_insert(0);
+        if *count >= max {
+            return None;
+        }
+        *count += 1;
+        Some(Self {
+            counts: counts.clone(),
+            ip,
+        })
+    }
+}
+
+impl Drop for IpSlot {
+    fn drop(&mut self) {
+        let mut map = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = map.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                map.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// Serve `router` on `listener` with connection limits and graceful shutdown
+///
+/// After `shutdown` resolves no new connections are accepted and open ones
+/// are asked to finish; returns an error if they are still open after
+/// `shutdown_timeout`. The peer address is available as `ConnectInfo<SocketAddr>`.
+pub(crate) async fn serve<L, F>(
+    mut listener: L,
+    router: Router,
+    limits: &ServerLimits,
+    shutdown: F,
+    shutdown_timeout: Option<Duration>,
+) -> rf_errors::Result<()>
+where
+    L: Listener<Addr = SocketAddr>,
+    F: Future<Output = ()> + Send + 'static,
+{
+    let mut builder = Builder::new(TokioExecutor::new());
+    builder
+        .http1()
+        .timer(TokioTimer::new())
+        .header_read_timeout(limits.header_read_timeout)
+        .max_buf_size(limits.max_header_size.max(MIN_HEADER_SIZE))
+        .max_headers(limits.max_headers);
+    builder
+        .http2()
+        .timer(TokioTimer::new())
+        .max_header_list_size(u32::try_from(limits.max_header_size).unwrap_or(u32::MAX))
+        // CONNECT protocol needed for HTTP/2 websockets
+        .enable_connect_protocol();
+    let builder = Arc::new(builder);
+
+    let connections = limits.max_connections.map(|max| Arc::new(Semaphore::new(max)));
+    let per_ip = Arc::new(Mutex::new(HashMap::new()));
+
+    let (signal_tx, signal_rx) = watch::channel(());
+    tokio::spawn(async move {
+        shutdown.await;
+        drop(signal_rx);
+    });
+    let (close_tx, close_rx) = watch::channel(());
+
+    loop {
+        let permit = match &connections {
+            Some(semaphore) => tokio::select! {
+                permit = semaphore.clone().acquire_owned() => permit.ok(),
+                _ = signal_tx.closed() => break,
+            },
+            None => None,
+        };
+        let (io, remote_addr) = tokio::select! {
+            conn = listener.accept() => conn,
+            _ = signal_tx.closed() => break,
+        };
+        let ip_slot = match limits.max_connections_per_ip {
+            Some(max) => match IpSlot::acquire(&per_ip, remote_addr.ip(), max) {
+                Some(slot) => Some(slot),
+                None => {
+                    tracing::debug!("Rejected connection from {}: too many connections", remote_addr);
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        let service = router.clone().map_request(move |request: Request<hyper::body::Incoming>| {
+            let mut request = request.map(Body::new);
+            request.extensions_mut().insert(ConnectInfo(remote_addr));
+            request
+        });
+        let service = TowerToHyperService::new(service);
+        let io = TokioIo::new(TimeoutIo::new(io, limits.write_timeout));
+        let builder = builder.clone();
+        let signal_tx = signal_tx.clone();
+        let close_rx = close_rx.clone();
+
+        tokio::spawn(async move {
+            let mut conn = std::pin::pin!(builder.serve_connection_with_upgrades(io, service));
+            let mut signal_closed = std::pin::pin!(signal_tx.closed());
+            let mut shutting_down = false;
+            loop {
+                tokio::select! {
+                    result = conn.as_mut() => {
+                        if let Err(e) = result {
+                            tracing::trace!("Connection from {} closed: {}", remote_addr, e);
+                        }
+                        break;
+                    }
+                    _ = &mut signal_closed, if !shutting_down => {
+                        shutting_down = true;
+                        conn.as_mut().graceful_shutdown();
+                    }
+                }
+            }
+            drop((permit, ip_slot, close_rx));
+        });
+    }
+
+    drop(close_rx);
+    drop(listener);
+    match shutdown_timeout {
+        Some(timeout) => tokio::time::timeout(timeout, close_tx.closed())
+            .await
+            .map_err(|_| rf_errors::RfError::Network("Server shutdown timeout".to_string())),
+        None => {
+            close_tx.closed().await;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! # locale
+//!
+//! locale 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Per-request language and timezone
+//!
+//! `locale_middleware` negotiates the language and timezone of each request
+//! and runs the handler inside `rf_core::ctx::with_locale`, so `rf_os::time::Time`
+//! values in JSON responses and the `datetime` filter of templates render in
+//! the requester's timezone instead of UTC.
+//!
+//! The language comes from the `lang` query parameter, the `lang` cookie or
+//! `Accept-Language`, matched against the supported languages; the timezone
+//! from the `tz` query parameter, the `tz` cookie or the `X-Timezone` header.
+//! Unknown timezones and unsupported languages fall back to the defaults.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{HttpServer, LocaleMiddleware};
+//!
+//! let server = HttpServer::new(addr)
+//!     .with_locale(LocaleMiddleware::new().languages(&["zh-CN", "en-US"]).default_timezone("Asia/Shanghai")?)
+//!     .route(Method::GET, "/orders", list_orders)?;
+//! ```
+
+use axum::extract::{Request, State};
+use axum::http::header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE, COOKIE, VARY};
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response as AxumResponse;
+use rf_core::ctx::Locale;
+use rf_errors::{Result, RfError};
+use std::sync::Arc;
+
+/// Default header carrying the client's IANA timezone
+pub const TIMEZONE_HEADER: &str = "x-timezone";
+
+/// Language and timezone negotiation settings
+#[derive(Debug, Clone)]
+pub struct LocaleMiddleware {
+    languages: Vec<String>,
+    default_language: String,
+    default_timezone: String,
+    timezone_header: HeaderName,
+    language_param: String,
+    timezone_param: String,
+}
+
+impl Default for LocaleMiddleware {
+    fn default() -> Self {
+        Self {
+            languages: Vec::new(),
+            default_language: "en-US".to_string(),
+            default_timezone: "UTC".to_string(),
+            timezone_header: HeaderName::from_static(TIMEZONE_HEADER),
+            language_param: "lang".to_string(),
+            timezone_param: "tz".to_string(),
+        }
+    }
+}
+
+impl LocaleMiddleware {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Languages the application supports; the first is the default
+    ///
+    /// Without a list any requested language is accepted.
+    pub fn languages(mut self, languages: &[&str]) -> Self {
+        self.languages = languages.iter().map(|language| language.to_string()).collect();
+        if let Some(first) = self.languages.first() {
+            self.default_language = first.clone();
+        }
+        self
+    }
+
+    /// Language when the request names none that is supported (default `en-US`)
+    pub fn default_language(mut self, language: &str) -> Self {
+        self.default_language = language.to_string();
+        self
+    }
+
+    /// Timezone when the request names none or an unknown one (default `UTC`)
+    pub fn default_timezone(mut self, timezone: &str) -> Result<Self> {
+        rf_os::time::timezone(timezone)?;
+        self.default_timezone = timezone.to_string();
+        Ok(self)
+    }
+
+    /// Header carrying the client's timezone (default `X-Timezone`)
+    pub fn timezone_header(mut self, name: &str) -> Result<Self> {
+        self.timezone_header = HeaderName::try_from(name)
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid header name {}: {}", name, e)))?;
+        Ok(self)
+    }
+
+    /// Query parameter and cookie names overriding the headers (default `lang` and `tz`)
+    pub fn params(mut self, language: &str, timezone: &str) -> Self {
+        self.language_param = language.to_string();
+        self.timezone_param = timezone.to_string();
+        self
+    }
+
+    /// Negotiate the language and timezone of a request
+    pub fn negotiate(&self, request: &Request) -> Locale {
+        let language = self
+            .override_value(request, &self.language_param)
+            .and_then(|language| self.match_language(&language))
+            .or_else(|| {
+                let header = request.headers().get(ACCEPT_LANGUAGE)?.to_str().ok()?;
+                parse_accept_language(header)
+                    .into_iter()
+                    .filter(|(_, quality)| *quality > 0.0)
+                    .find_map(|(range, _)| match range.as_str() {
+                        "*" => Some(self.default_language.clone()),
+                        range => self.match_language(range),
+                    })
+            })
+            .unwrap_or_else(|| self.default_language.clone());
+        let timezone = self
+            .override_value(request, &self.timezone_param)
+            .or_else(|| {
+                let value = request.headers().get(&self.timezone_header)?.to_str().ok()?;
+                Some(value.trim().to_string())
+            })
+            .filter(|timezone| rf_os::time::timezone(timezone).is_ok())
+            .unwrap_or_else(|| self.default_timezone.clone());
+        Locale::new(language, timezone)
+    }
+
+    /// Value of the query parameter `name`, otherwise of the cookie `name`
+    fn override_value(&self, request: &Request, name: &str) -> Option<String> {
+        let query = request.uri().query().and_then(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        });
+        query.or_else(|| {
+            request
+                .headers()
+                .get_all(COOKIE)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(';'))
+                .filter_map(|pair| pair.trim().split_once('='))
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.trim_matches('"').to_string())
+        })
+    }
+
+    /// The supported language matching `range`: exactly, then by primary subtag
+    fn match_language(&self, range: &str) -> Option<String> {
+        let range = range.trim().replace('_', "-");
+        if range.is_empty() {
+            return None;
+        }
+        if self.languages.is_empty() {
+            return Some(range);
+        }
+        let primary = |tag: &str| tag.split('-').next().unwrap_or_default().to_ascii_lowercase();
+        self.languages
+            .iter()
+            .find(|language| language.eq_ignore_ascii_case(&range))
+            .or_else(|| self.languages.iter().find(|language| primary(language) == primary(&range)))
+            .cloned()
+    }
+}
+
+/// Parse an `Accept-Language` header into `(range, quality)`, highest quality first
+///
+/// Entries keep their header order among equal qualities. Malformed quality
+/// values count as 1.
+pub fn parse_accept_language(header: &str) -> Vec<(String, f32)> {
+    let mut ranges: Vec<(String, f32)> = header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let range = parts.next()?.trim();
+            if range.is_empty() {
+                return None;
+            }
+            let quality = parts
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .and_then(|(_, q)| q.trim().parse::<f32>().ok())
+                .map_or(1.0, |q| q.clamp(0.0, 1.0));
+            Some((range.to_string(), quality))
+        })
+        .collect();
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges
+}
+
+/// Negotiate the locale and run the request inside it
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc::new(config), locale_middleware)`,
+/// or `HttpServer::with_locale`. The locale is also put in the request
+/// extensions for `Extension<rf_core::ctx::Locale>`.
+pub async fn locale_middleware(
+    State(config): State<Arc<LocaleMiddleware>>,
+    mut request: Request,
+    next: Next,
+) -> AxumResponse {
+    let locale = config.negotiate(&request);
+    let language = HeaderValue::from_str(&locale.language).ok();
+    request.extensions_mut().insert(locale.clone());
+    let mut response = rf_core::ctx::with_locale(locale, next.run(request)).await;
+    if let Some(language) = language {
+        response.headers_mut().entry(CONTENT_LANGUAGE).or_insert(language);
+    }
+    response.headers_mut().append(VARY, HeaderValue::from_static("accept-language"));
+    response
+}
@@ -7,6 +7,10 @@
 
 //! HTTP middleware system
 
+pub mod jwt;
+
+pub use jwt::*;
+
 use axum::extract::Request;
 use axum::response::Response;
 use std::future::Future;
@@ -72,41 +76,255 @@ impl Middleware for LoggingMiddleware {
     }
 }
 
+/// Allowed CORS origins
+#[derive(Clone, Debug)]
+enum AllowOrigin {
+    Any,
+    List(Vec<String>),
+    Regex(Vec<regex::Regex>),
+}
+
 /// CORS middleware
-#[derive(Clone)]
+///
+/// Answers preflight requests and adds CORS headers to responses for allowed
+/// origins. Origins can be `*`, an explicit list or regular expressions;
+/// with credentials allowed, the request origin is echoed instead of `*`.
+/// Credentials require an explicit list or regular expressions: any origin
+/// with credentials would let every website make credentialed requests, so
+/// `validate` rejects it and the headers fall back to `*` without credentials.
+/// Register it server-wide with `HttpServer::with_cors_config` and override it
+/// per route group with `RouteGroup::cors`.
+#[derive(Clone, Debug)]
 pub struct CorsMiddleware {
-    allow_origin: String,
+    allow_origin: AllowOrigin,
     allow_methods: String,
     allow_headers: String,
+    expose_headers: Option<String>,
+    allow_credentials: bool,
+    max_age: Option<std::time::Duration>,
 }
 
 impl CorsMiddleware {
     /// Create a new CORS middleware
     pub fn new() -> Self {
         Self {
-            allow_origin: "*".to_string(),
+            allow_origin: AllowOrigin::Any,
             allow_methods: "GET, POST, PUT, DELETE, OPTIONS".to_string(),
             allow_headers: "Content-Type, Authorization".to_string(),
+            expose_headers: None,
+            allow_credentials: false,
+            max_age: None,
         }
     }
 
-    /// Set allowed origin
+    /// Set allowed origin (`*` allows any origin)
     pub fn allow_origin(mut self, origin: &str) -> Self {
-        self.allow_origin = origin.to_string();
+        self.allow_origin = if origin == "*" {
+            AllowOrigin::Any
+        } else {
+            AllowOrigin::List(vec![origin.to_string()])
+        };
+        self
+    }
+
+    /// Set allowed origins
+    pub fn allow_origins<I, S>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow_origin = AllowOrigin::List(origins.into_iter().map(Into::into).collect());
         self
     }
 
-    /// Set allowed methods
+    /// Allow origins matching a regular expression, e.g. `^https://.*\.example\.com$`
+    ///
+    /// Can be called several times; an origin matching any expression is allowed.
+    pub fn allow_origin_regex(mut self, pattern: &str) -> rf_errors::Result<Self> {
+        let regex = regex::Regex::new(pattern)
+            .map_err(|e| rf_errors::RfError::InvalidParameter(format!("Invalid origin pattern {}: {}", pattern, e)))?;
+        match &mut self.allow_origin {
+            AllowOrigin::Regex(patterns) => patterns.push(regex),
+            _ => self.allow_origin = AllowOrigin::Regex(vec![regex]),
+        }
+        Ok(self)
+    }
+
+    /// Set allowed methods (`*` allows any method)
     pub fn allow_methods(mut self, methods: &str) -> Self {
         self.allow_methods = methods.to_string();
         self
     }
 
-    /// Set allowed headers
+    /// Set allowed headers (`*` allows any header)
     pub fn allow_headers(mut self, headers: &str) -> Self {
         self.allow_headers = headers.to_string();
         self
     }
+
+    /// Set response headers readable by the browser
+    pub fn expose_headers(mut self, headers: &str) -> Self {
+        self.expose_headers = Some(headers.to_string());
+        self
+    }
+
+    /// Allow cookies and authorization headers
+    ///
+    /// Needs allowed origins other than `*`, see `validate`.
+    pub fn allow_credentials(mut self, allow: bool) -> Self {
+        self.allow_credentials = allow;
+        self
+    }
+
+    /// Set how long browsers may cache preflight results
+    pub fn max_age(mut self, max_age: std::time::Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Check that the policy is safe to use
+    ///
+    /// # Errors
+    ///
+    /// Returns `RfError::Config` when credentials are allowed for any origin
+    pub fn validate(&self) -> rf_errors::Result<()> {
+        if self.allow_credentials && matches!(self.allow_origin, AllowOrigin::Any) {
+            return Err(rf_errors::RfError::Config(
+                "CORS credentials cannot be allowed for any origin, list the origins or use allow_origin_regex"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether an origin is allowed
+    pub fn is_origin_allowed(&self, origin: &str) -> bool {
+        match &self.allow_origin {
+            AllowOrigin::Any => true,
+            AllowOrigin::List(origins) => origins.iter().any(|o| o.eq_ignore_ascii_case(origin)),
+            AllowOrigin::Regex(patterns) => patterns.iter().any(|p| p.is_match(origin)),
+        }
+    }
+
+    /// Answer a preflight request, or `None` if the request is not a preflight
+    ///
+    /// Disallowed origins, methods or headers get a `403` without CORS headers.
+    pub fn preflight(&self, request: &Request) -> Option<Response> {
+        use axum::http::{header, HeaderValue, StatusCode};
+
+        let headers = request.headers();
+        let origin = headers.get(header::ORIGIN)?.to_str().ok()?;
+        if request.method() != axum::http::Method::OPTIONS {
+            return None;
+        }
+        let method = headers.get(header::ACCESS_CONTROL_REQUEST_METHOD)?.to_str().ok()?;
+        let requested_headers = headers
+            .get(header::ACCESS_CONTROL_REQUEST_HEADERS)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+
+        let allowed = self.is_origin_allowed(origin)
+            && list_allows(&self.allow_methods, method)
+            && requested_headers
+                .split(',')
+                .map(str::trim)
+                .filter(|h| !h.is_empty())
+                .all(|h| list_allows(&self.allow_headers, h));
+        let mut response = Response::new(axum::body::Body::empty());
+        if !allowed {
+            *response.status_mut() = StatusCode::FORBIDDEN;
+            return Some(response);
+        }
+
+        *response.status_mut() = StatusCode::NO_CONTENT;
+        self.set_origin_headers(origin, response.headers_mut());
+        let out = response.headers_mut();
+        let methods = if self.allow_methods.trim() == "*" && self.allow_credentials { method } else { &self.allow_methods };
+        if let Ok(value) = HeaderValue::from_str(methods) {
+            out.insert(header::ACCESS_CONTROL_ALLOW_METHODS, value);
+        }
+        let allow_headers = if self.allow_headers.trim() == "*" && !requested_headers.is_empty() {
+            requested_headers
+        } else {
+            &self.allow_headers
+        };
+        if let Ok(value) = HeaderValue::from_str(allow_headers) {
+            out.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, value);
+        }
+        if let Some(max_age) = self.max_age {
+            out.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age.as_secs()));
+        }
+        out.append(header::VARY, HeaderValue::from_static("Access-Control-Request-Method"));
+        out.append(header::VARY, HeaderValue::from_static("Access-Control-Request-Headers"));
+        Some(response)
+    }
+
+    /// Add CORS headers to the response of an actual (non-preflight) request
+    pub fn apply(&self, origin: Option<&str>, response: &mut Response) {
+        use axum::http::{header, HeaderValue};
+
+        let headers = response.headers_mut();
+        if !matches!(self.allow_origin, AllowOrigin::Any) || self.allow_credentials {
+            headers.append(header::VARY, HeaderValue::from_static("Origin"));
+        }
+        let Some(origin) = origin.filter(|o| self.is_origin_allowed(o)) else {
+            return;
+        };
+        self.set_origin_headers(origin, headers);
+        if let Some(expose) = self.expose_headers.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, expose);
+        }
+    }
+
+    /// Handle a request with this policy
+    pub(crate) async fn handle(&self, request: Request, next: axum::middleware::Next) -> Response {
+        if let Some(response) = self.preflight(&request) {
+            return response;
+        }
+        let origin = request
+            .headers()
+            .get(axum::http::header::ORIGIN)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let mut response = next.run(request).await;
+        self.apply(origin.as_deref(), &mut response);
+        response
+    }
+
+    fn set_origin_headers(&self, origin: &str, headers: &mut axum::http::HeaderMap) {
+        use axum::http::{header, HeaderValue};
+
+        // Any origin never gets credentials, even if `validate` was skipped
+        let any = matches!(self.allow_origin, AllowOrigin::Any);
+        let value = if any {
+            HeaderValue::from_static("*")
+        } else {
+            match HeaderValue::from_str(origin) {
+                Ok(value) => value,
+                Err(_) => return,
+            }
+        };
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+        if self.allow_credentials && !any {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+        }
+    }
+}
+
+/// Apply a CORS policy to a plain axum router
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc::new(cors), cors_middleware)`.
+pub async fn cors_middleware(
+    axum::extract::State(cors): axum::extract::State<std::sync::Arc<CorsMiddleware>>,
+    request: Request,
+    next: axum::middleware::Next,
+) -> Response {
+    cors.handle(request, next).await
+}
+
+/// Whether a comma-separated list (or `*`) contains a value, ignoring case
+fn list_allows(list: &str, value: &str) -> bool {
+    list.trim() == "*" || list.split(',').any(|item| item.trim().eq_ignore_ascii_case(value))
 }
 
 impl Default for CorsMiddleware {
@@ -121,45 +339,43 @@ impl Middleware for CorsMiddleware {
         request: Request,
         next: Next,
     ) -> Pin<Box<dyn Future<Output = Result<Response, axum::Error>> + Send>> {
-        let allow_origin = self.allow_origin.clone();
-        let allow_methods = self.allow_methods.clone();
-        let allow_headers = self.allow_headers.clone();
-        
+        let cors = self.clone();
         Box::pin(async move {
-            // Handle preflight OPTIONS request
-            if request.method() == axum::http::Method::OPTIONS {
-                let mut response = Response::new(axum::body::Body::empty());
-                let headers = response.headers_mut();
-                headers.insert(
-                    axum::http::header::ACCESS_CONTROL_ALLOW_ORIGIN,
-                    allow_origin.parse().unwrap(),
-                );
-                headers.insert(
-                    axum::http::header::ACCESS_CONTROL_ALLOW_METHODS,
-                    allow_methods.parse().unwrap(),
-                );
-                headers.insert(
-                    axum::http::header::ACCESS_CONTROL_ALLOW_HEADERS,
-                    allow_headers.parse().unwrap(),
-                );
+            if let Some(response) = cors.preflight(&request) {
                 return Ok(response);
             }
-
+            let origin = request
+                .headers()
+                .get(axum::http::header::ORIGIN)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
             let mut response = next.call(request).await?;
-            let headers = response.headers_mut();
-            headers.insert(
-                axum::http::header::ACCESS_CONTROL_ALLOW_ORIGIN,
-                allow_origin.parse().unwrap(),
-            );
-            headers.insert(
-                axum::http::header::ACCESS_CONTROL_ALLOW_METHODS,
-                allow_methods.parse().unwrap(),
-            );
-            headers.insert(
-                axum::http::header::ACCESS_CONTROL_ALLOW_HEADERS,
-                allow_headers.parse().unwrap(),
-            );
+            cors.apply(origin.as_deref(), &mut response);
             Ok(response)
         })
     }
 }
+
+/// CORS policies applied by the server: a default plus per-route overrides
+pub(crate) struct CorsRoutes {
+    pub default: Option<std::sync::Arc<CorsMiddleware>>,
+    pub routes: super::router::RadixRouter<std::sync::Arc<CorsMiddleware>>,
+}
+
+impl CorsRoutes {
+    /// Method key under which route policies are stored (they apply to all methods)
+    pub const ANY_METHOD: &'static str = "*";
+}
+
+/// Apply the CORS policy of the matched route, or the server default
+pub(crate) async fn cors_routes_middleware(
+    cors: std::sync::Arc<CorsRoutes>,
+    request: Request,
+    next: axum::middleware::Next,
+) -> Response {
+    let route = cors.routes.at(CorsRoutes::ANY_METHOD, request.uri().path());
+    match route.map(|m| m.value.clone()).or_else(|| cors.default.clone()) {
+        Some(policy) => policy.handle(request, next).await,
+        None => next.run(request).await,
+    }
+}
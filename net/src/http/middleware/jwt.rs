@@ -0,0 +1,536 @@
+//! # jwt
+//!
+//! jwt 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! JWT authentication
+//!
+//! Issues and verifies HS256/RS256 tokens without a separate JWT crate:
+//! HMAC and RSA signatures come from `rf_crypto`. `JwtAuth` holds a key set
+//! for rotation (new tokens are signed with the current key, older keys stay
+//! valid for verification until retired), issues access/refresh token pairs
+//! and verifies tokens in `jwt_middleware`, which puts the claims into the
+//! request extensions for `JwtClaims<T>` and `Request::claims`.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{jwt_middleware, JwtAuth, JwtClaims, JwtKey};
+//!
+//! let auth = JwtAuth::new(JwtKey::hs256("2026-01", secret))
+//!     .issuer("my-app")
+//!     .access_ttl(Duration::from_secs(900));
+//!
+//! let tokens = auth.issue_pair("user-1", json!({ "role": "admin" }))?;
+//! let tokens = auth.refresh(&tokens.refresh_token)?;
+//!
+//! let server = HttpServer::new(addr).group("/api", |api| {
+//!     api.middleware(axum::middleware::from_fn_with_state(auth.clone(), jwt_middleware))
+//!         .route(Method::GET, "/me", |JwtClaims(claims): JwtClaims<Role>| async move { claims.sub })
+//! })?;
+//!
+//! // Rotate keys: tokens signed with "2026-01" stay valid until retired
+//! auth.rotate(JwtKey::hs256("2026-02", new_secret));
+//! auth.retire("2026-01")?;
+//! ```
+
+use crate::http::envelope::ApiError;
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response as AxumResponse};
+use rf_crypto::rsa::{self as rsa, RsaKeyPair, RsaPrivateKey, RsaPublicKey};
+use rf_crypto::sha256;
+use rf_encoding::{base64_url_decode, base64_url_encode};
+use rf_errors::{Result, RfError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default access token lifetime
+const DEFAULT_ACCESS_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Default refresh token lifetime
+const DEFAULT_REFRESH_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Default clock skew tolerated when checking `exp` and `nbf`
+const DEFAULT_LEEWAY: Duration = Duration::from_secs(30);
+
+/// Signature algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Algorithm {
+    /// HMAC with SHA-256
+    HS256,
+    /// RSASSA-PKCS1-v1_5 with SHA-256
+    RS256,
+}
+
+#[derive(Clone)]
+enum KeyMaterial {
+    Hmac(Vec<u8>),
+    Rsa {
+        private_key: Option<Arc<RsaPrivateKey>>,
+        public_key: Arc<RsaPublicKey>,
+    },
+}
+
+/// A signing or verification key, identified by its `kid`
+#[derive(Clone)]
+pub struct JwtKey {
+    kid: String,
+    material: KeyMaterial,
+}
+
+impl JwtKey {
+    /// HS256 key from a shared secret
+    pub fn hs256(kid: impl Into<String>, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            kid: kid.into(),
+            material: KeyMaterial::Hmac(secret.as_ref().to_vec()),
+        }
+    }
+
+    /// RS256 key able to sign and verify
+    pub fn rs256(kid: impl Into<String>, key_pair: &RsaKeyPair) -> Self {
+        Self {
+            kid: kid.into(),
+            material: KeyMaterial::Rsa {
+                private_key: Some(Arc::new(key_pair.private_key().clone())),
+                public_key: Arc::new(key_pair.public_key().clone()),
+            },
+        }
+    }
+
+    /// RS256 key from a PEM private key (PKCS#8 or PKCS#1)
+    pub fn rs256_pem(kid: impl Into<String>, private_key_pem: &str) -> Result<Self> {
+        Ok(Self::rs256(kid, &RsaKeyPair::from_pem(private_key_pem)?))
+    }
+
+    /// RS256 key that only verifies, e.g. the public key of another issuer
+    pub fn rs256_public(kid: impl Into<String>, public_key: RsaPublicKey) -> Self {
+        Self {
+            kid: kid.into(),
+            material: KeyMaterial::Rsa {
+                private_key: None,
+                public_key: Arc::new(public_key),
+            },
+        }
+    }
+
+    /// Verification-only RS256 key from a PEM public key
+    pub fn rs256_public_pem(kid: impl Into<String>, public_key_pem: &str) -> Result<Self> {
+        Ok(Self::rs256_public(kid, rsa::public_key_from_pem(public_key_pem)?))
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        match self.material {
+            KeyMaterial::Hmac(_) => Algorithm::HS256,
+            KeyMaterial::Rsa { .. } => Algorithm::RS256,
+        }
+    }
+
+    /// Whether the key can sign tokens
+    pub fn can_sign(&self) -> bool {
+        !matches!(self.material, KeyMaterial::Rsa { private_key: None, .. })
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match &self.material {
+            KeyMaterial::Hmac(secret) => Ok(sha256::hmac(secret, data)),
+            KeyMaterial::Rsa {
+                private_key: Some(private_key),
+                ..
+            } => rsa::sign(private_key, data),
+            KeyMaterial::Rsa { private_key: None, .. } => Err(RfError::Config(format!(
+                "JWT key '{}' has no private key",
+                self.kid
+            ))),
+        }
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        match &self.material {
+            KeyMaterial::Hmac(secret) => sha256::verify_hmac(secret, data, signature),
+            KeyMaterial::Rsa { public_key, .. } => rsa::verify(public_key, data, signature),
+        }
+    }
+}
+
+impl std::fmt::Debug for JwtKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtKey")
+            .field("kid", &self.kid)
+            .field("algorithm", &self.algorithm())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Kind of token, stored in the `typ` claim
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    Access,
+    Refresh,
+}
+
+/// Registered claims plus application claims flattened into the payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Claims<T = Value> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// Expiration, in Unix seconds
+    pub exp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+    #[serde(default)]
+    pub iat: u64,
+    #[serde(default)]
+    pub jti: String,
+    /// Missing in tokens from other issuers, treated as an access token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<TokenType>,
+    #[serde(flatten)]
+    pub extra: T,
+}
+
+impl<T> Claims<T> {
+    pub fn token_type(&self) -> TokenType {
+        self.typ.unwrap_or(TokenType::Access)
+    }
+}
+
+/// Access and refresh tokens returned by `JwtAuth::issue_pair`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    /// Access token lifetime in seconds
+    pub expires_in: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    alg: Algorithm,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+}
+
+#[derive(Debug)]
+struct KeySet {
+    current: String,
+    keys: Vec<JwtKey>,
+}
+
+/// JWT issuer and verifier
+///
+/// Clones share the key set, so rotating keys on one clone affects all of
+/// them, including the one held by the middleware.
+#[derive(Debug, Clone)]
+pub struct JwtAuth {
+    keys: Arc<RwLock<KeySet>>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    leeway: Duration,
+    access_ttl: Duration,
+    refresh_ttl: Duration,
+}
+
+impl JwtAuth {
+    /// Create an issuer signing with `key`
+    pub fn new(key: JwtKey) -> Self {
+        Self {
+            keys: Arc::new(RwLock::new(KeySet {
+                current: key.kid.clone(),
+                keys: vec![key],
+            })),
+            issuer: None,
+            audience: None,
+            leeway: DEFAULT_LEEWAY,
+            access_ttl: DEFAULT_ACCESS_TTL,
+            refresh_ttl: DEFAULT_REFRESH_TTL,
+        }
+    }
+
+    /// Issuer set on new tokens and required on verified ones
+    pub fn issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Audience set on new tokens and required on verified ones
+    pub fn audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Clock skew tolerated when checking `exp` and `nbf` (default 30s)
+    pub fn leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// Access token lifetime (default 15 minutes)
+    pub fn access_ttl(mut self, ttl: Duration) -> Self {
+        self.access_ttl = ttl;
+        self
+    }
+
+    /// Refresh token lifetime (default 7 days)
+    pub fn refresh_ttl(mut self, ttl: Duration) -> Self {
+        self.refresh_ttl = ttl;
+        self
+    }
+
+    /// Sign new tokens with `key`; previous keys keep verifying until retired
+    ///
+    /// A key with the same `kid` is replaced.
+    pub fn rotate(&self, key: JwtKey) -> Result<()> {
+        if !key.can_sign() {
+            return Err(RfError::Config(format!("JWT key '{}' cannot sign tokens", key.kid)));
+        }
+        let mut keys = self.keys.write().unwrap_or_else(|e| e.into_inner());
+        keys.current = key.kid.clone();
+        keys.keys.retain(|k| k.kid != key.kid);
+        keys.keys.push(key);
+        Ok(())
+    }
+
+    /// Add a key used only for verification
+    pub fn add_key(&self, key: JwtKey) {
+        let mut keys = self.keys.write().unwrap_or_else(|e| e.into_inner());
+        if key.kid == keys.current {
+            return;
+        }
+        keys.keys.retain(|k| k.kid != key.kid);
+        keys.keys.push(key);
+    }
+
+    /// Stop accepting tokens signed with `kid`
+    ///
+    /// # Errors
+    ///
+    /// Fails for the current signing key.
+    pub fn retire(&self, kid: &str) -> Result<()> {
+        let mut keys = self.keys.write().unwrap_or_else(|e| e.into_inner());
+        if keys.current == kid {
+            return Err(RfError::InvalidParameter(format!("JWT key '{}' is the current signing key", kid)));
+        }
+        keys.keys.retain(|k| k.kid != kid);
+        Ok(())
+    }
+
+    /// Identifiers of the known keys, the current one first
+    pub fn key_ids(&self) -> Vec<String> {
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        let mut ids = vec![keys.current.clone()];
+        ids.extend(keys.keys.iter().filter(|k| k.kid != keys.current).map(|k| k.kid.clone()));
+        ids
+    }
+
+    /// Claims for a new token; `exp`, `iat`, `iss`, `aud` and `jti` are filled in
+    pub fn claims<T>(&self, token_type: TokenType, subject: impl Into<String>, extra: T) -> Claims<T> {
+        let now = now();
+        let ttl = match token_type {
+            TokenType::Access => self.access_ttl,
+            TokenType::Refresh => self.refresh_ttl,
+        };
+        Claims {
+            iss: self.issuer.clone(),
+            sub: Some(subject.into()),
+            aud: self.audience.clone(),
+            exp: now + ttl.as_secs(),
+            nbf: None,
+            iat: now,
+            jti: uuid::Uuid::new_v4().to_string(),
+            typ: Some(token_type),
+            extra,
+        }
+    }
+
+    /// Sign claims with the current key
+    pub fn encode<T: Serialize>(&self, claims: &Claims<T>) -> Result<String> {
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        let key = keys
+            .keys
+            .iter()
+            .find(|k| k.kid == keys.current)
+            .ok_or_else(|| RfError::Config("No current JWT key".to_string()))?;
+        let header = Header {
+            alg: key.algorithm(),
+            typ: Some("JWT".to_string()),
+            kid: Some(key.kid.clone()),
+        };
+        let header = serde_json::to_vec(&header).map_err(|e| RfError::Serialization(e.to_string()))?;
+        let payload = serde_json::to_vec(claims).map_err(|e| RfError::Serialization(e.to_string()))?;
+        let signing_input = format!("{}.{}", base64_url_encode(&header), base64_url_encode(&payload));
+        let signature = key.sign(signing_input.as_bytes())?;
+        Ok(format!("{}.{}", signing_input, base64_url_encode(&signature)))
+    }
+
+    /// Issue an access token
+    pub fn issue<T: Serialize>(&self, subject: impl Into<String>, extra: T) -> Result<String> {
+        self.encode(&self.claims(TokenType::Access, subject, extra))
+    }
+
+    /// Issue an access token and a refresh token carrying the same claims
+    pub fn issue_pair<T: Serialize>(&self, subject: impl Into<String>, extra: T) -> Result<TokenPair> {
+        let subject = subject.into();
+        let access = self.claims(TokenType::Access, subject.clone(), &extra);
+        let refresh = self.claims(TokenType::Refresh, subject, &extra);
+        Ok(TokenPair {
+            access_token: self.encode(&access)?,
+            refresh_token: self.encode(&refresh)?,
+            token_type: "Bearer".to_string(),
+            expires_in: self.access_ttl.as_secs(),
+        })
+    }
+
+    /// Verify an access token
+    ///
+    /// # Errors
+    ///
+    /// Returns `RfError::Unauthorized` for malformed, badly signed, expired or
+    /// not yet valid tokens, a wrong issuer or audience, and refresh tokens.
+    pub fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<Claims<T>> {
+        self.verify_type(token, TokenType::Access)
+    }
+
+    /// Exchange a refresh token for a new token pair with the same claims
+    ///
+    /// The new pair is signed with the current key, so refreshing also
+    /// migrates clients to a rotated key.
+    pub fn refresh(&self, refresh_token: &str) -> Result<TokenPair> {
+        let claims: Claims<Value> = self.verify_type(refresh_token, TokenType::Refresh)?;
+        self.issue_pair(claims.sub.unwrap_or_default(), claims.extra)
+    }
+
+    fn verify_type<T: DeserializeOwned>(&self, token: &str, token_type: TokenType) -> Result<Claims<T>> {
+        let claims: Claims<T> = self.decode(token)?;
+        if claims.token_type() != token_type {
+            return Err(unauthorized("Wrong token type"));
+        }
+        let now = now();
+        let leeway = self.leeway.as_secs();
+        if claims.exp.saturating_add(leeway) <= now {
+            return Err(unauthorized("Token expired"));
+        }
+        if claims.nbf.is_some_and(|nbf| nbf > now.saturating_add(leeway)) {
+            return Err(unauthorized("Token not yet valid"));
+        }
+        if self.issuer.is_some() && claims.iss != self.issuer {
+            return Err(unauthorized("Invalid issuer"));
+        }
+        if self.audience.is_some() && claims.aud != self.audience {
+            return Err(unauthorized("Invalid audience"));
+        }
+        Ok(claims)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<Claims<T>> {
+        let (signing_input, signature) = token.rsplit_once('.').ok_or_else(|| unauthorized("Malformed token"))?;
+        let (header, payload) = signing_input
+            .split_once('.')
+            .filter(|(_, payload)| !payload.contains('.'))
+            .ok_or_else(|| unauthorized("Malformed token"))?;
+        let header: Header = base64_url_decode(header)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| unauthorized("Malformed token header"))?;
+        let signature = base64_url_decode(signature).map_err(|_| unauthorized("Malformed token signature"))?;
+
+        // Only keys of the algorithm named in the header are tried, so an RSA
+        // public key is never used as an HMAC secret
+        let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+        let valid = keys
+            .keys
+            .iter()
+            .filter(|k| k.algorithm() == header.alg)
+            .filter(|k| header.kid.as_ref().is_none_or(|kid| *kid == k.kid))
+            .any(|k| k.verify(signing_input.as_bytes(), &signature));
+        drop(keys);
+        if !valid {
+            return Err(unauthorized("Invalid token signature"));
+        }
+
+        let payload = base64_url_decode(payload).map_err(|_| unauthorized("Malformed token payload"))?;
+        serde_json::from_slice(&payload).map_err(|e| unauthorized(&format!("Invalid token claims: {}", e)))
+    }
+}
+
+/// Verify the bearer token and store its claims in the request extensions
+///
+/// Requests without a valid access token get a 401 envelope. Use with
+/// `axum::middleware::from_fn_with_state(auth, jwt_middleware)`, on the whole
+/// server or on a `RouteGroup`.
+pub async fn jwt_middleware(State(auth): State<JwtAuth>, mut request: Request, next: Next) -> AxumResponse {
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer ").or_else(|| v.strip_prefix("bearer ")))
+        .map(str::trim);
+    let Some(token) = token else {
+        return ApiError(unauthorized("Missing bearer token")).into_response();
+    };
+    match auth.verify::<Value>(token) {
+        Ok(claims) => {
+            request.extensions_mut().insert(claims);
+            next.run(request).await
+        }
+        Err(e) => ApiError(e).into_response(),
+    }
+}
+
+/// Extractor for the claims verified by `jwt_middleware`
+///
+/// Rejects with 401 when the middleware did not run and 400 when the
+/// claims do not match `T`.
+#[derive(Debug, Clone)]
+pub struct JwtClaims<T = Value>(pub Claims<T>);
+
+impl<T, S> FromRequestParts<S> for JwtClaims<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> std::result::Result<Self, Self::Rejection> {
+        let claims = parts
+            .extensions
+            .get::<Claims<Value>>()
+            .ok_or_else(|| unauthorized("Missing JWT claims"))?;
+        Ok(Self(typed_claims(claims)?))
+    }
+}
+
+/// Convert the stored claims to application claims
+pub(crate) fn typed_claims<T: DeserializeOwned>(claims: &Claims<Value>) -> Result<Claims<T>> {
+    let value = serde_json::to_value(claims).map_err(|e| RfError::Serialization(e.to_string()))?;
+    serde_json::from_value(value).map_err(|e| RfError::InvalidParameter(format!("Invalid JWT claims: {}", e)))
+}
+
+fn unauthorized(message: &str) -> RfError {
+    RfError::Unauthorized(message.to_string())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
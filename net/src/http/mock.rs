@@ -0,0 +1,453 @@
+//! # mock
+//!
+//! mock 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Response mocking for frontend development
+//!
+//! In mock mode, routes answer with example payloads instead of running
+//! their handlers, so frontends can develop against the API contract before
+//! the backend is done. For each request, in order:
+//!
+//! 1. a body set with `MockConfig::route`
+//! 2. a mock file `<dir>/<METHOD>/<path>.json` (`<path>.<status>.json` for a
+//!    requested status), read on every request so edits apply immediately
+//! 3. the example of the documented response from `api_route`: the
+//!    operation's `response_example`, else one built from the schema
+//!    (`#[api(example = ...)]` values are used where present)
+//!
+//! Routes with none of these run their real handler. Clients pick a
+//! documented response with the `X-Mock-Status` header, e.g. `404` to build
+//! the not-found screen. Latency and error injection apply to every route.
+//!
+//! Mock mode is on when `with_mock` is given an enabled config, or when the
+//! `RF_MOCK` environment variable is set (`1`, `true`, `yes`, `on`):
+//!
+//! | Variable | Meaning |
+//! |----------|---------|
+//! | `RF_MOCK` | Enable mock mode |
+//! | `RF_MOCK_DIR` | Mock file directory (default `mocks`) |
+//! | `RF_MOCK_LATENCY` | Added latency in ms, `300` or `100-800` |
+//! | `RF_MOCK_ERROR_RATE` | Share of requests failed with 500, `0.1` |
+//!
+//! ```rust,ignore
+//! use rf_net::http::{HttpServer, MockConfig, MockRoute};
+//!
+//! let server = HttpServer::new(addr)
+//!     .with_openapi(OpenApiBuilder::new("Shop", "1.0.0"))
+//!     .with_mock(MockConfig::from_env()
+//!         .latency_range(Duration::from_millis(100), Duration::from_millis(600))
+//!         .route(Method::POST, "/orders", MockRoute::new().status(201).body(json!({"id": 1})))?
+//!         .route(Method::GET, "/health", MockRoute::passthrough())?)
+//!     .api_route(Method::GET, "/orders/:id", get_order, ApiOperation::new()
+//!         .response::<Order>(200, "The order")
+//!         .response_example(200, json!({"id": 42, "status": "paid"})))?;
+//! ```
+
+use super::plugin::RouteInfo;
+use super::router::RadixRouter;
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response as AxumResponse;
+use rand::Rng;
+use rf_errors::{Result, RfError};
+use serde_json::{json, Map, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Environment variable enabling mock mode
+pub const MOCK_ENV: &str = "RF_MOCK";
+
+/// Request header selecting the documented response status to mock
+pub const MOCK_STATUS_HEADER: &str = "x-mock-status";
+
+/// Deepest schema nesting expanded when building examples
+const MAX_EXAMPLE_DEPTH: usize = 6;
+
+/// Mock mode settings
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    enabled: bool,
+    dir: PathBuf,
+    latency: Option<(Duration, Duration)>,
+    error_rate: f64,
+    error_status: u16,
+    routes: Vec<(Method, String, MockRoute)>,
+}
+
+impl MockConfig {
+    /// Enabled mock mode with no latency or errors, reading files from `mocks`
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            dir: PathBuf::from("mocks"),
+            latency: None,
+            error_rate: 0.0,
+            error_status: 500,
+            routes: Vec::new(),
+        }
+    }
+
+    /// Settings from `RF_MOCK*` environment variables; disabled unless `RF_MOCK` is set
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let mut config = Self::new();
+        config.enabled = var(MOCK_ENV)
+            .is_some_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"));
+        if let Some(dir) = var("RF_MOCK_DIR") {
+            config.dir = PathBuf::from(dir);
+        }
+        if let Some(latency) = var("RF_MOCK_LATENCY") {
+            match parse_latency(&latency) {
+                Some(range) => config.latency = Some(range),
+                None => tracing::warn!("Ignoring invalid RF_MOCK_LATENCY '{}'", latency),
+            }
+        }
+        if let Some(rate) = var("RF_MOCK_ERROR_RATE") {
+            match rate.trim().parse::<f64>() {
+                Ok(rate) => config.error_rate = rate.clamp(0.0, 1.0),
+                Err(_) => tracing::warn!("Ignoring invalid RF_MOCK_ERROR_RATE '{}'", rate),
+            }
+        }
+        config
+    }
+
+    /// Turn mock mode on or off
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Whether mock mode is on
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Directory of mock files
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    /// Add a fixed latency to every response
+    pub fn latency(self, latency: Duration) -> Self {
+        self.latency_range(latency, latency)
+    }
+
+    /// Add a random latency between `min` and `max` to every response
+    pub fn latency_range(mut self, min: Duration, max: Duration) -> Self {
+        self.latency = Some((min.min(max), min.max(max)));
+        self
+    }
+
+    /// Fail this share of requests (0.0 - 1.0) with `status`
+    pub fn error_rate(mut self, rate: f64, status: u16) -> Self {
+        self.error_rate = rate.clamp(0.0, 1.0);
+        self.error_status = status;
+        self
+    }
+
+    /// Override mocking for one route, `pattern` as registered on the server
+    pub fn route(mut self, method: Method, pattern: &str, route: MockRoute) -> Result<Self> {
+        super::router::to_axum_path(pattern)?;
+        self.routes.push((method, pattern.to_string(), route));
+        Ok(self)
+    }
+
+    /// Build the runtime state for the registered routes and the API document
+    pub(crate) fn build(self, routes: &[RouteInfo], document: &Value) -> Result<MockState> {
+        let mut router = RadixRouter::new();
+        let mut overrides = self.routes;
+        for RouteInfo { method, path: pattern } in routes {
+            let path = crate::oai::openapi_path(pattern)?.0;
+            let position = overrides.iter().position(|(m, p, _)| m.as_str() == method && p == pattern);
+            let entry = MockEntry {
+                responses: documented_responses(document, &path, method),
+                route: position.map(|i| overrides.remove(i).2),
+                path,
+            };
+            router.insert(method, pattern, entry)?;
+        }
+        if let Some((method, pattern, _)) = overrides.first() {
+            return Err(RfError::InvalidParameter(format!("Mocked route {} {} is not registered", method, pattern)));
+        }
+        Ok(MockState {
+            dir: self.dir,
+            latency: self.latency,
+            error_rate: self.error_rate,
+            error_status: self.error_status,
+            routes: router,
+        })
+    }
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// Mock settings for one route
+#[derive(Debug, Clone, Default)]
+pub struct MockRoute {
+    status: Option<u16>,
+    body: Option<Value>,
+    latency: Option<(Duration, Duration)>,
+    error_rate: Option<f64>,
+    passthrough: bool,
+}
+
+impl MockRoute {
+    /// Route settings falling back to the server-wide ones
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Always run the real handler for this route
+    pub fn passthrough() -> Self {
+        Self {
+            passthrough: true,
+            ..Self::default()
+        }
+    }
+
+    /// Status to answer with (and the documented response to use)
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Fixed JSON body, taking precedence over files and examples
+    pub fn body(mut self, body: Value) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    /// Latency for this route instead of the server-wide setting
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = Some((latency, latency));
+        self
+    }
+
+    /// Error rate for this route instead of the server-wide setting
+    pub fn error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = Some(rate.clamp(0.0, 1.0));
+        self
+    }
+}
+
+/// Documented response: status and JSON example, `None` without a body
+type DocumentedResponse = (u16, Option<Value>);
+
+struct MockEntry {
+    /// OpenAPI style path, also used for mock file names
+    path: String,
+    responses: Vec<DocumentedResponse>,
+    route: Option<MockRoute>,
+}
+
+/// Mock mode state used by `mock_middleware`
+pub struct MockState {
+    dir: PathBuf,
+    latency: Option<(Duration, Duration)>,
+    error_rate: f64,
+    error_status: u16,
+    routes: RadixRouter<MockEntry>,
+}
+
+impl MockState {
+    async fn respond(&self, entry: &MockEntry, method: &str, requested: Option<u16>) -> Option<AxumResponse> {
+        let route = entry.route.as_ref();
+        let wanted = requested.or(route.and_then(|r| r.status));
+        if let Some(body) = route.and_then(|r| r.body.as_ref()) {
+            return Some(json_response(wanted.unwrap_or(200), body.to_string().into_bytes()));
+        }
+
+        let base = match entry.path.trim_start_matches('/') {
+            "" => "index".to_string(),
+            path => path.to_string(),
+        };
+        let mut files = Vec::new();
+        if let Some(status) = wanted {
+            files.push((self.dir.join(method).join(format!("{}.{}.json", base, status)), status));
+        }
+        files.push((self.dir.join(method).join(format!("{}.json", base)), wanted.unwrap_or(200)));
+        for (file, status) in files {
+            if let Ok(body) = tokio::fs::read(&file).await {
+                return Some(json_response(status, body));
+            }
+        }
+
+        let documented = match wanted {
+            Some(status) => entry.responses.iter().find(|(s, _)| *s == status),
+            None => entry.responses.iter().find(|(s, _)| (200..300).contains(s)),
+        };
+        match documented {
+            Some((status, Some(example))) => Some(json_response(*status, example.to_string().into_bytes())),
+            Some((status, None)) => Some(empty_response(*status)),
+            // An undocumented status was asked for explicitly
+            None if requested.is_some() => requested.map(empty_response),
+            None => None,
+        }
+    }
+}
+
+/// Serve mock responses, see the module documentation
+///
+/// Use `HttpServer::with_mock` or the `RF_MOCK` environment variable.
+pub async fn mock_middleware(State(mock): State<Arc<MockState>>, request: Request, next: Next) -> AxumResponse {
+    let method = request.method().as_str().to_string();
+    let Some(matched) = mock.routes.at(&method, request.uri().path()) else {
+        return next.run(request).await;
+    };
+    let entry = matched.value;
+    let route = entry.route.as_ref();
+
+    let latency = route.and_then(|r| r.latency).or(mock.latency);
+    if let Some(delay) = latency.map(|(min, max)| random_between(min, max)).filter(|d| !d.is_zero()) {
+        tokio::time::sleep(delay).await;
+    }
+    let error_rate = route.and_then(|r| r.error_rate).unwrap_or(mock.error_rate);
+    if error_rate > 0.0 && rand::thread_rng().gen_bool(error_rate) {
+        let body = json!({"code": mock.error_status, "message": "Injected mock error"});
+        return json_response(mock.error_status, body.to_string().into_bytes());
+    }
+    if route.is_some_and(|r| r.passthrough) {
+        return next.run(request).await;
+    }
+
+    let requested = request
+        .headers()
+        .get(MOCK_STATUS_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u16>().ok());
+    match mock.respond(entry, &method, requested).await {
+        Some(mut response) => {
+            response.headers_mut().insert("x-mock", axum::http::HeaderValue::from_static("1"));
+            response
+        }
+        None => next.run(request).await,
+    }
+}
+
+fn json_response(status: u16, body: Vec<u8>) -> AxumResponse {
+    let mut response = AxumResponse::new(Body::from(body));
+    *response.status_mut() = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, axum::http::HeaderValue::from_static("application/json"));
+    response
+}
+
+fn empty_response(status: u16) -> AxumResponse {
+    let mut response = AxumResponse::new(Body::empty());
+    *response.status_mut() = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+    response
+}
+
+fn random_between(min: Duration, max: Duration) -> Duration {
+    if min >= max {
+        return min;
+    }
+    rand::thread_rng().gen_range(min..=max)
+}
+
+/// `300` or `100-800` milliseconds
+fn parse_latency(value: &str) -> Option<(Duration, Duration)> {
+    let (min, max) = value.split_once('-').unwrap_or((value, value));
+    let min = min.trim().parse::<u64>().ok()?;
+    let max = max.trim().parse::<u64>().ok()?;
+    Some((Duration::from_millis(min.min(max)), Duration::from_millis(min.max(max))))
+}
+
+/// Responses documented for an operation, with JSON examples
+fn documented_responses(document: &Value, path: &str, method: &str) -> Vec<DocumentedResponse> {
+    let operation = &document["paths"][path][method.to_ascii_lowercase()];
+    let mut responses: Vec<DocumentedResponse> = operation["responses"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(status, response)| {
+            let status = status.parse::<u16>().ok()?;
+            let media = &response["content"]["application/json"];
+            if media.is_null() {
+                return Some((status, None));
+            }
+            let example = media
+                .get("example")
+                .cloned()
+                .or_else(|| media["examples"].as_object().and_then(|e| e.values().next()).map(|e| e["value"].clone()))
+                .unwrap_or_else(|| example_from_schema(document, &media["schema"], 0));
+            Some((status, Some(example)))
+        })
+        .collect();
+    responses.sort_by_key(|(status, _)| *status);
+    responses
+}
+
+/// Build an example value from a JSON Schema
+fn example_from_schema(document: &Value, schema: &Value, depth: usize) -> Value {
+    if depth > MAX_EXAMPLE_DEPTH {
+        return Value::Null;
+    }
+    if let Some(example) = schema.get("example").or_else(|| schema["examples"].get(0)) {
+        return example.clone();
+    }
+    if let Some(reference) = schema["$ref"].as_str().and_then(|r| r.strip_prefix('#')) {
+        return match document.pointer(reference) {
+            Some(target) => example_from_schema(document, target, depth + 1),
+            None => Value::Null,
+        };
+    }
+    if let Some(first) = schema["enum"].get(0) {
+        return first.clone();
+    }
+    if let Some(variants) = schema.get("anyOf").or_else(|| schema.get("oneOf")).and_then(Value::as_array) {
+        // Recursive optional fields end in null instead of nesting to the limit
+        return match variants.iter().find(|v| v["type"] != "null") {
+            Some(variant) if depth < 2 => example_from_schema(document, variant, depth + 1),
+            _ => Value::Null,
+        };
+    }
+    let ty = match &schema["type"] {
+        Value::String(ty) => ty.as_str(),
+        Value::Array(types) => types.iter().filter_map(Value::as_str).find(|t| *t != "null").unwrap_or("null"),
+        _ if schema["properties"].is_object() => "object",
+        _ => return Value::Null,
+    };
+    match ty {
+        "object" => {
+            let mut object = Map::new();
+            for (name, property) in schema["properties"].as_object().into_iter().flatten() {
+                object.insert(name.clone(), example_from_schema(document, property, depth + 1));
+            }
+            if object.is_empty() && schema["additionalProperties"].is_object() {
+                object.insert("key".to_string(), example_from_schema(document, &schema["additionalProperties"], depth + 1));
+            }
+            Value::Object(object)
+        }
+        "array" if depth < MAX_EXAMPLE_DEPTH => json!([example_from_schema(document, &schema["items"], depth + 1)]),
+        "array" => json!([]),
+        "string" => json!(match schema["format"].as_str().unwrap_or_default() {
+            "date-time" => "2026-01-01T00:00:00Z",
+            "date" => "2026-01-01",
+            "time" => "12:00:00",
+            "email" => "user@example.com",
+            "uuid" => "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+            "uri" | "url" => "https://example.com",
+            "ip" | "ipv4" => "192.0.2.1",
+            "ipv6" => "2001:db8::1",
+            _ => "string",
+        }),
+        "integer" => json!(schema["minimum"].as_i64().unwrap_or(0)),
+        "number" => json!(schema["minimum"].as_f64().unwrap_or(0.0)),
+        "boolean" => json!(true),
+        _ => Value::Null,
+    }
+}
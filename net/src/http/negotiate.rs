@@ -0,0 +1,492 @@
+//! # negotiate
+//!
+//! negotiate 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Content negotiation
+//!
+//! Handlers return data wrapped in `Negotiated` and the `Accept` header picks
+//! the representation: JSON, XML, MessagePack or CSV out of the box, plus any
+//! `Renderer` registered on the `Negotiator`. Quality values are honoured
+//! (`application/xml;q=0.9, */*;q=0.1`), and a request that accepts none of
+//! the available types gets the default representation, or `406 Not
+//! Acceptable` when the negotiator is strict.
+//!
+//! Each type can limit the representations it offers with `for_type`, for
+//! example when a report only makes sense as JSON or CSV. The first listed
+//! type is then that type's default.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{HttpServer, Negotiated, Negotiator};
+//!
+//! async fn list_users() -> Negotiated<Vec<User>> {
+//!     Negotiated(db.model("users").all_as().await?)
+//! }
+//!
+//! let server = HttpServer::new(addr)
+//!     .with_negotiation(Negotiator::new().for_type::<Vec<User>>(&["application/json", "text/csv"]))
+//!     .route(Method::GET, "/users", list_users)?;
+//! ```
+//!
+//! Without the middleware `Negotiated` answers JSON, so handlers work the
+//! same in servers that do not negotiate.
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, VARY};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response as AxumResponse};
+use rf_errors::{Result, RfError};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Encodes response data into one representation
+pub trait Renderer: Send + Sync {
+    /// Media types answered by this renderer; the first is the canonical one
+    fn media_types(&self) -> &[&'static str];
+
+    /// `Content-Type` header of rendered responses
+    fn content_type(&self) -> &str {
+        self.media_types()[0]
+    }
+
+    /// Encode the data
+    fn render(&self, value: &Value) -> Result<Vec<u8>>;
+}
+
+/// `application/json`
+#[derive(Debug, Clone, Default)]
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn media_types(&self) -> &[&'static str] {
+        &["application/json"]
+    }
+
+    fn render(&self, value: &Value) -> Result<Vec<u8>> {
+        serde_json::to_vec(value).map_err(|e| RfError::Serialization(e.to_string()))
+    }
+}
+
+/// `application/xml`
+///
+/// Objects become child elements named after their keys, array items become
+/// `<item>` elements and `null` an empty element, all under a `<response>`
+/// root by default.
+#[derive(Debug, Clone)]
+pub struct XmlRenderer {
+    root: String,
+}
+
+impl XmlRenderer {
+    /// Renderer with a custom root element
+    pub fn with_root(root: impl Into<String>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Default for XmlRenderer {
+    fn default() -> Self {
+        Self::with_root("response")
+    }
+}
+
+impl Renderer for XmlRenderer {
+    fn media_types(&self) -> &[&'static str] {
+        &["application/xml", "text/xml"]
+    }
+
+    fn content_type(&self) -> &str {
+        "application/xml; charset=utf-8"
+    }
+
+    fn render(&self, value: &Value) -> Result<Vec<u8>> {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        write_xml(&mut xml, &self.root, value);
+        Ok(xml.into_bytes())
+    }
+}
+
+fn write_xml(xml: &mut String, name: &str, value: &Value) {
+    let name = xml_name(name);
+    match value {
+        Value::Null => {
+            xml.push_str(&format!("<{}/>", name));
+            return;
+        }
+        Value::Object(map) => {
+            xml.push_str(&format!("<{}>", name));
+            for (key, child) in map {
+                write_xml(xml, key, child);
+            }
+        }
+        Value::Array(items) => {
+            xml.push_str(&format!("<{}>", name));
+            for item in items {
+                write_xml(xml, "item", item);
+            }
+        }
+        Value::String(s) => {
+            xml.push_str(&format!("<{}>", name));
+            xml.push_str(&rf_encoding::xml::escape(s));
+        }
+        other => {
+            xml.push_str(&format!("<{}>", name));
+            xml.push_str(&other.to_string());
+        }
+    }
+    xml.push_str(&format!("</{}>", name));
+}
+
+/// Element name for a key: characters not allowed in names become `_`
+fn xml_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') { c } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// `application/msgpack`
+#[derive(Debug, Clone, Default)]
+pub struct MsgPackRenderer;
+
+impl Renderer for MsgPackRenderer {
+    fn media_types(&self) -> &[&'static str] {
+        &["application/msgpack", "application/x-msgpack", "application/vnd.msgpack"]
+    }
+
+    fn render(&self, value: &Value) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(value).map_err(|e| RfError::Serialization(e.to_string()))
+    }
+}
+
+/// `text/csv`
+///
+/// A list of objects is one row per object, with a header row from the keys
+/// in order of first appearance. A single object is one row, scalars a single
+/// `value` column. Nested values are written as JSON, and cells starting with
+/// `=`, `+`, `-` or `@` are escaped like in exports.
+#[derive(Debug, Clone, Default)]
+pub struct CsvRenderer;
+
+impl Renderer for CsvRenderer {
+    fn media_types(&self) -> &[&'static str] {
+        &["text/csv", "application/csv"]
+    }
+
+    fn content_type(&self) -> &str {
+        "text/csv; charset=utf-8"
+    }
+
+    fn render(&self, value: &Value) -> Result<Vec<u8>> {
+        let rows: Vec<&Value> = match value {
+            Value::Array(items) => items.iter().collect(),
+            Value::Null => Vec::new(),
+            other => vec![other],
+        };
+        let mut columns: Vec<&str> = Vec::new();
+        for row in &rows {
+            if let Value::Object(map) = row {
+                for key in map.keys() {
+                    if !columns.contains(&key.as_str()) {
+                        columns.push(key);
+                    }
+                }
+            }
+        }
+        if columns.is_empty() {
+            columns.push("value");
+        }
+
+        let mut buf = Vec::new();
+        let header: Vec<Value> = columns.iter().map(|c| Value::String(c.to_string())).collect();
+        super::export::write_csv_row(&mut buf, &header, true);
+        for row in rows {
+            let cells: Vec<Value> = match row {
+                Value::Object(map) => columns.iter().map(|c| map.get(*c).cloned().unwrap_or(Value::Null)).collect(),
+                other => vec![other.clone()],
+            };
+            super::export::write_csv_row(&mut buf, &cells, true);
+        }
+        Ok(buf)
+    }
+}
+
+/// One entry of an `Accept` header
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaRange {
+    /// `type/subtype` in lowercase, possibly with `*` wildcards
+    pub media_type: String,
+    /// Quality value between 0 and 1
+    pub quality: f32,
+}
+
+impl MediaRange {
+    /// How closely the range names a media type: 3 exact, 2 `type/*`, 1 `*/*`, 0 no match
+    pub fn specificity(&self, media_type: &str) -> u8 {
+        if self.media_type == media_type {
+            return 3;
+        }
+        match self.media_type.split_once('/') {
+            Some(("*", "*")) => 1,
+            Some((kind, "*")) if media_type.split_once('/').is_some_and(|(k, _)| k == kind) => 2,
+            _ => 0,
+        }
+    }
+}
+
+/// Parse an `Accept` header, highest quality first
+///
+/// Entries keep their header order among equal qualities. Malformed quality
+/// values count as 1.
+pub fn parse_accept(header: &str) -> Vec<MediaRange> {
+    let mut ranges: Vec<MediaRange> = header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let media_type = parts.next()?.trim().to_ascii_lowercase();
+            if media_type.is_empty() {
+                return None;
+            }
+            let media_type = if media_type == "*" { "*/*".to_string() } else { media_type };
+            let quality = parts
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .and_then(|(_, q)| q.trim().parse::<f32>().ok())
+                .map_or(1.0, |q| q.clamp(0.0, 1.0));
+            Some(MediaRange { media_type, quality })
+        })
+        .collect();
+    ranges.sort_by(|a, b| b.quality.total_cmp(&a.quality));
+    ranges
+}
+
+/// Data a `Negotiated` response carries to the middleware
+#[derive(Clone)]
+struct NegotiatedBody {
+    value: Arc<Value>,
+    type_id: TypeId,
+}
+
+/// Response data rendered in the representation the client asks for
+///
+/// See the module documentation. Answers JSON when the server does not
+/// negotiate.
+#[derive(Debug, Clone)]
+pub struct Negotiated<T>(pub T);
+
+impl<T: Serialize + 'static> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> AxumResponse {
+        let value = match serde_json::to_value(&self.0) {
+            Ok(value) => value,
+            Err(e) => {
+                return super::envelope::EnvelopeConfig::default()
+                    .error_response(&RfError::Serialization(e.to_string()))
+            }
+        };
+        let mut response = rendered(&JsonRenderer, &value);
+        response.extensions_mut().insert(NegotiatedBody {
+            value: Arc::new(value),
+            type_id: TypeId::of::<T>(),
+        });
+        response
+    }
+}
+
+/// Renderers and the rules for choosing between them
+#[derive(Clone)]
+pub struct Negotiator {
+    renderers: Vec<Arc<dyn Renderer>>,
+    default_type: String,
+    types: HashMap<TypeId, Vec<String>>,
+    strict: bool,
+}
+
+impl Negotiator {
+    /// JSON (the default), XML, MessagePack and CSV
+    pub fn new() -> Self {
+        Self {
+            renderers: vec![
+                Arc::new(JsonRenderer),
+                Arc::new(XmlRenderer::default()),
+                Arc::new(MsgPackRenderer),
+                Arc::new(CsvRenderer),
+            ],
+            default_type: "application/json".to_string(),
+            types: HashMap::new(),
+            strict: false,
+        }
+    }
+
+    /// Add a renderer, replacing any renderer with the same canonical media type
+    pub fn renderer(mut self, renderer: impl Renderer + 'static) -> Self {
+        let media_type = renderer.media_types()[0];
+        self.renderers.retain(|r| r.media_types()[0] != media_type);
+        self.renderers.push(Arc::new(renderer));
+        self
+    }
+
+    /// Representation for requests without an `Accept` header or accepting
+    /// nothing available (default `application/json`)
+    pub fn default_type(mut self, media_type: &str) -> Self {
+        self.default_type = media_type.to_ascii_lowercase();
+        self
+    }
+
+    /// Offer `T` only in these media types, the first being its default
+    pub fn for_type<T: 'static>(mut self, media_types: &[&str]) -> Self {
+        let media_types = media_types.iter().map(|m| m.to_ascii_lowercase()).collect();
+        self.types.insert(TypeId::of::<T>(), media_types);
+        self
+    }
+
+    /// Answer `406 Not Acceptable` instead of the default when nothing matches
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    fn find(&self, media_type: &str) -> Option<&Arc<dyn Renderer>> {
+        self.renderers.iter().find(|r| r.media_types().contains(&media_type))
+    }
+
+    /// Renderers offered for a type, most preferred first
+    fn offered(&self, type_id: TypeId) -> Vec<&Arc<dyn Renderer>> {
+        match self.types.get(&type_id) {
+            Some(media_types) => media_types.iter().filter_map(|m| self.find(m)).collect(),
+            None => {
+                let default = self.find(&self.default_type);
+                default
+                    .into_iter()
+                    .chain(self.renderers.iter().filter(|r| default.is_none_or(|d| !Arc::ptr_eq(r, d))))
+                    .collect()
+            }
+        }
+    }
+
+    /// Pick the renderer for an `Accept` header
+    ///
+    /// Returns `None` when the negotiator is strict and nothing offered is
+    /// acceptable.
+    pub fn select(&self, accept: Option<&str>, type_id: TypeId) -> Option<Arc<dyn Renderer>> {
+        let offered = self.offered(type_id);
+        let ranges = accept.map(parse_accept).unwrap_or_default();
+        if ranges.is_empty() {
+            return offered.first().map(|r| Arc::clone(r));
+        }
+
+        let mut best: Option<(&Arc<dyn Renderer>, f32)> = None;
+        for renderer in &offered {
+            // The most specific range naming any of the renderer's types decides its quality
+            let quality = renderer
+                .media_types()
+                .iter()
+                .filter_map(|media_type| {
+                    ranges
+                        .iter()
+                        .filter(|range| range.specificity(media_type) > 0)
+                        .max_by_key(|range| range.specificity(media_type))
+                })
+                .map(|range| range.quality)
+                .fold(0.0f32, f32::max);
+            if quality > 0.0 && best.is_none_or(|(_, q)| quality > q) {
+                best = Some((renderer, quality));
+            }
+        }
+        match best {
+            Some((renderer, _)) => Some(Arc::clone(renderer)),
+            None if self.strict => None,
+            None => offered.first().map(|r| Arc::clone(r)),
+        }
+    }
+
+    /// Render data for a request directly, without the middleware
+    pub fn respond<T: Serialize + 'static>(&self, headers: &HeaderMap, data: &T) -> AxumResponse {
+        match serde_json::to_value(data) {
+            Ok(value) => self.render(accept(headers).as_deref(), TypeId::of::<T>(), &value),
+            Err(e) => super::envelope::EnvelopeConfig::default().error_response(&RfError::Serialization(e.to_string())),
+        }
+    }
+
+    fn render(&self, accept: Option<&str>, type_id: TypeId, value: &Value) -> AxumResponse {
+        let mut response = match self.select(accept, type_id) {
+            Some(renderer) => rendered(renderer.as_ref(), value),
+            None => self.not_acceptable(type_id),
+        };
+        response.headers_mut().append(VARY, HeaderValue::from_static("accept"));
+        response
+    }
+
+    fn not_acceptable(&self, type_id: TypeId) -> AxumResponse {
+        let available: Vec<&str> = self.offered(type_id).iter().map(|r| r.media_types()[0]).collect();
+        let message = format!("Not Acceptable, available types: {}", available.join(", "));
+        let body = json!({"code": StatusCode::NOT_ACCEPTABLE.as_u16(), "message": message});
+        let mut response = AxumResponse::new(Body::from(body.to_string()));
+        *response.status_mut() = StatusCode::NOT_ACCEPTABLE;
+        response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        response
+    }
+}
+
+impl Default for Negotiator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn accept(headers: &HeaderMap) -> Option<String> {
+    let values: Vec<&str> = headers.get_all(ACCEPT).iter().filter_map(|v| v.to_str().ok()).collect();
+    (!values.is_empty()).then(|| values.join(","))
+}
+
+fn rendered(renderer: &dyn Renderer, value: &Value) -> AxumResponse {
+    match renderer.render(value) {
+        Ok(body) => {
+            let mut response = AxumResponse::new(Body::from(body));
+            if let Ok(content_type) = HeaderValue::from_str(renderer.content_type()) {
+                response.headers_mut().insert(CONTENT_TYPE, content_type);
+            }
+            response
+        }
+        Err(e) => super::envelope::EnvelopeConfig::default().error_response(&e),
+    }
+}
+
+/// Render `Negotiated` responses according to the request's `Accept` header
+///
+/// Use `HttpServer::with_negotiation`, or add it to a router with
+/// `axum::middleware::from_fn_with_state(Arc::new(negotiator), negotiate_middleware)`.
+/// Other responses pass through unchanged.
+pub async fn negotiate_middleware(State(negotiator): State<Arc<Negotiator>>, request: Request, next: Next) -> AxumResponse {
+    let accept = accept(request.headers());
+    let response = next.run(request).await;
+    let Some(body) = response.extensions().get::<NegotiatedBody>().cloned() else {
+        return response;
+    };
+    let (mut parts, _) = response.into_parts();
+    parts.extensions.remove::<NegotiatedBody>();
+    let rendered = negotiator.render(accept.as_deref(), body.type_id, &body.value);
+    if !rendered.status().is_success() {
+        return rendered;
+    }
+    // Keep the handler's status and headers, swap in the chosen representation
+    let (rendered, body) = rendered.into_parts();
+    parts.headers.remove(CONTENT_LENGTH);
+    for name in [CONTENT_TYPE, VARY] {
+        parts.headers.remove(&name);
+        for value in rendered.headers.get_all(&name) {
+            parts.headers.append(name.clone(), value.clone());
+        }
+    }
+    AxumResponse::from_parts(parts, body)
+}
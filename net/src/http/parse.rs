@@ -0,0 +1,339 @@
+//! # parse
+//!
+//! parse 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Request parameter binding and validation for `Request::parse`
+
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response as AxumResponse};
+use rf_util::valid::{ValidationError, ValidationErrors};
+use serde::de::{self, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+
+/// Maximum request body read by `Request::parse` when no `BodyLimit` is configured
+pub const MAX_PARSE_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Error returned by `Request::parse`
+///
+/// Converts into a `400 Bad Request` JSON response:
+///
+/// ```json
+/// {"code": 400, "message": "email: Invalid email format",
+///  "errors": [{"field": "email", "rule": "email", "message": "Invalid email format"}]}
+/// ```
+#[derive(Debug)]
+pub enum ParseError {
+    /// The body could not be read or the input does not match the target type
+    Decode(String),
+    /// Field rules failed
+    Invalid(ValidationErrors),
+}
+
+impl ParseError {
+    /// Validation errors, if the input was decoded but failed validation
+    pub fn errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            Self::Invalid(errors) => Some(errors),
+            Self::Decode(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Decode(message) => write!(f, "{}", message),
+            Self::Invalid(errors) => write!(f, "{}", errors),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for rf_errors::RfError {
+    fn from(e: ParseError) -> Self {
+        match e {
+            ParseError::Decode(message) => rf_errors::RfError::InvalidParameter(message),
+            ParseError::Invalid(errors) => errors.into(),
+        }
+    }
+}
+
+impl IntoResponse for ParseError {
+    fn into_response(self) -> AxumResponse {
+        let errors: Vec<&ValidationError> = self
+            .errors()
+            .map(|errors| errors.iter().flat_map(|(_, e)| e).collect())
+            .unwrap_or_default();
+        let code = StatusCode::BAD_REQUEST.as_u16() as rf_errors::Code;
+        let body = serde_json::json!({
+            "code": code,
+            "message": self.to_string(),
+            "data": null,
+            "errors": errors,
+        });
+        let mut response = (StatusCode::BAD_REQUEST, Json(body)).into_response();
+        response.extensions_mut().insert(super::envelope::Enveloped(code));
+        response
+    }
+}
+
+/// Add a string parameter, collecting repeated keys (and `key[]`) into arrays
+pub(crate) fn insert_param(map: &mut Map<String, Value>, key: &str, value: String) {
+    let (key, array) = match key.strip_suffix("[]") {
+        Some(key) => (key, true),
+        None => (key, false),
+    };
+    match map.get_mut(key) {
+        Some(Value::Array(items)) => items.push(Value::String(value)),
+        Some(existing @ Value::String(_)) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, Value::String(value)]);
+        }
+        _ if array => {
+            map.insert(key.to_string(), Value::Array(vec![Value::String(value)]));
+        }
+        _ => {
+            map.insert(key.to_string(), Value::String(value));
+        }
+    }
+}
+
+/// Merge URL-encoded pairs into `map`, replacing keys from earlier sources
+pub(crate) fn merge_urlencoded(map: &mut Map<String, Value>, input: &[u8]) {
+    let mut params = Map::new();
+    for (key, value) in url::form_urlencoded::parse(input) {
+        insert_param(&mut params, &key, value.into_owned());
+    }
+    map.extend(params);
+}
+
+/// Merge a request body into `map` according to its content type
+pub(crate) async fn merge_body(
+    map: &mut Map<String, Value>,
+    headers: &axum::http::HeaderMap,
+    body: axum::body::Body,
+    limit: usize,
+) -> Result<(), ParseError> {
+    let raw_content_type = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or("");
+    let content_type = raw_content_type.to_ascii_lowercase();
+
+    if content_type.starts_with("multipart/form-data") {
+        // The boundary is case-sensitive
+        let boundary = multer::parse_boundary(raw_content_type)
+            .map_err(|e| ParseError::Decode(format!("Invalid multipart body: {}", e)))?;
+        let constraints = multer::Constraints::new()
+            .size_limit(multer::SizeLimit::new().whole_stream(limit as u64));
+        let mut multipart = multer::Multipart::with_constraints(body.into_data_stream(), boundary, constraints);
+        let mut params = Map::new();
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(|e| ParseError::Decode(format!("Invalid multipart body: {}", e)))?
+        {
+            // Only text fields are bound; file parts are skipped
+            if field.file_name().is_some() {
+                continue;
+            }
+            let name = field.name().unwrap_or_default().to_string();
+            let value = field
+                .text()
+                .await
+                .map_err(|e| ParseError::Decode(format!("Invalid multipart body: {}", e)))?;
+            insert_param(&mut params, &name, value);
+        }
+        map.extend(params);
+        return Ok(());
+    }
+
+    let bytes = crate::buffer::BufferPool::global()
+        .read_body(body, limit)
+        .await
+        .map_err(|e| match e {
+            rf_errors::RfError::Network(message) => ParseError::Decode(message),
+            other => ParseError::Decode(other.to_string()),
+        })?;
+    if bytes.is_empty() {
+        return Ok(());
+    }
+    if content_type.starts_with("application/x-www-form-urlencoded") {
+        merge_urlencoded(map, &bytes);
+    } else if content_type.starts_with("application/json") || content_type.contains("+json") || content_type.is_empty()
+    {
+        match serde_json::from_slice(&bytes) {
+            Ok(Value::Object(body)) => map.extend(body),
+            Ok(_) => return Err(ParseError::Decode("JSON body must be an object".to_string())),
+            Err(e) => return Err(ParseError::Decode(format!("Invalid JSON body: {}", e))),
+        }
+    } else {
+        return Err(ParseError::Decode(format!("Unsupported content type: {}", content_type)));
+    }
+    Ok(())
+}
+
+/// Deserialize `value`, converting strings from query/form input to numbers and booleans
+pub(crate) fn from_value<T: serde::de::DeserializeOwned>(value: Value) -> Result<T, ParseError> {
+    T::deserialize(Lenient(value)).map_err(|e| ParseError::Decode(format!("Invalid parameters: {}", e)))
+}
+
+/// `serde_json::Value` deserializer accepting string forms of scalars
+struct Lenient(Value);
+
+macro_rules! lenient_number {
+    ($($method:ident => $visit:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.0 {
+                    Value::String(s) => match s.trim().parse::<$ty>() {
+                        Ok(n) => visitor.$visit(n),
+                        Err(_) => Err(de::Error::invalid_value(de::Unexpected::Str(&s), &visitor)),
+                    },
+                    other => other.$method(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Lenient {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Array(items) => visitor.visit_seq(LenientSeq(items.into_iter())),
+            Value::Object(map) => visitor.visit_map(LenientMap {
+                iter: map.into_iter(),
+                value: None,
+            }),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::String(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" | "on" | "yes" => visitor.visit_bool(true),
+                "false" | "0" | "off" | "no" => visitor.visit_bool(false),
+                _ => Err(de::Error::invalid_value(de::Unexpected::Str(&s), &visitor)),
+            },
+            other => other.deserialize_bool(visitor),
+        }
+    }
+
+    lenient_number! {
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Number(n) => visitor.visit_string(n.to_string()),
+            Value::Bool(b) => visitor.visit_string(b.to_string()),
+            other => other.deserialize_string(visitor),
+        }
+    }
+
+    /// Empty strings (e.g. `?age=`) bind as `None`
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            Value::String(s) if s.is_empty() => visitor.visit_none(),
+            other => visitor.visit_some(Lenient(other)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// A single value binds to a one-element sequence
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Array(items) => visitor.visit_seq(LenientSeq(items.into_iter())),
+            Value::Null => visitor.visit_seq(LenientSeq(Vec::new().into_iter())),
+            other => visitor.visit_seq(LenientSeq(vec![other].into_iter())),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 char bytes byte_buf unit unit_struct map struct identifier ignored_any
+    }
+}
+
+struct LenientSeq(std::vec::IntoIter<Value>);
+
+impl<'de> SeqAccess<'de> for LenientSeq {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        self.0.next().map(|value| seed.deserialize(Lenient(value))).transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct LenientMap {
+    iter: serde_json::map::IntoIter,
+    value: Option<Value>,
+}
+
+impl<'de> MapAccess<'de> for LenientMap {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(Lenient(value)),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+}
@@ -9,8 +9,10 @@
 
 use axum::Router;
 use rf_errors::Result;
+use serde::Serialize;
 use std::sync::Arc;
 use std::collections::HashMap;
+use std::net::SocketAddr;
 
 /// Plugin lifecycle hooks
 pub enum PluginHook {
@@ -22,6 +24,24 @@ pub enum PluginHook {
     OnResponse,
 }
 
+/// Route registered through `HttpServer::route`
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteInfo {
+    pub method: String,
+    pub path: String,
+}
+
+/// Server state handed to plugins when the server starts
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerInfo {
+    pub addr: SocketAddr,
+    pub tls: bool,
+    pub routes: Vec<RouteInfo>,
+    /// Middleware in the order they were added
+    pub middleware: Vec<String>,
+    pub plugins: Vec<String>,
+}
+
 /// Plugin trait
 pub trait Plugin: Send + Sync {
     /// Plugin name
@@ -36,6 +56,11 @@ pub trait Plugin: Send + Sync {
     /// Configure the plugin
     fn configure(&mut self, config: HashMap<String, String>) -> Result<()>;
     
+    /// Receive the server state before `apply` is called
+    fn attach(&self, info: &ServerInfo) {
+        let _ = info;
+    }
+
     /// Apply plugin to router
     fn apply(&self, router: Router) -> Router;
     
@@ -76,7 +101,7 @@ impl PluginManager {
             plugin.configure(config.clone())?;
         }
         
-        self.plugins.insert(name, Arc::new(plugin));
+        self.plugins.insert(name, Arc::from(plugin));
         Ok(())
     }
 
@@ -95,6 +120,13 @@ impl PluginManager {
         self.plugins.get(name)
     }
 
+    /// Hand the server state to all plugins
+    pub fn attach_all(&self, info: &ServerInfo) {
+        for plugin in self.plugins.values() {
+            plugin.attach(info);
+        }
+    }
+
     /// Apply all plugins to a router
     pub fn apply_all(&self, mut router: Router) -> Router {
         for plugin in self.plugins.values() {
@@ -0,0 +1,69 @@
+//! # prometheus
+//!
+//! prometheus 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Prometheus scrape endpoint and HTTP server metrics
+//!
+//! `metrics_handler` serves the default `rf_os::metric::MetricRegistry` in
+//! the Prometheus text format. `http_metrics_middleware` records into it:
+//!
+//! - `http_request_duration_seconds`: histogram labeled `method`, `route`
+//!   (the matched route pattern, `unmatched` for 404s, so paths with IDs do
+//!   not create a series each) and `status`
+//! - `http_requests_in_flight`: requests being handled
+//!
+//! `HttpServer::with_metrics` installs both, plus the process, tokio
+//! runtime and global buffer pool collectors (see `crate::buffer`). Database
+//! pools are registered by the application with
+//! `rf_os::metric::register_pool_metrics`.
+
+use axum::extract::{MatchedPath, Request};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response as AxumResponse};
+use rf_os::metric::{default_registry, MetricRegistry, DEFAULT_BUCKETS, PROMETHEUS_CONTENT_TYPE};
+use std::time::Instant;
+
+/// Metrics of `registry` as a scrape response
+pub fn metrics_response(registry: &MetricRegistry) -> AxumResponse {
+    ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], registry.encode()).into_response()
+}
+
+/// Serve the default registry, e.g. `.route(Method::GET, "/metrics", metrics_handler)`
+pub async fn metrics_handler() -> AxumResponse {
+    metrics_response(default_registry())
+}
+
+/// Record request latency and in-flight requests, see the module documentation
+pub async fn http_metrics_middleware(request: Request, next: Next) -> AxumResponse {
+    let registry = default_registry();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_string(), |path| path.as_str().to_string());
+    let in_flight = registry.gauge("http_requests_in_flight", "HTTP requests being handled").ok();
+    if let Some(gauge) = &in_flight {
+        gauge.inc();
+    }
+    let started = Instant::now();
+    let response = next.run(request).await;
+    if let Some(gauge) = &in_flight {
+        gauge.dec();
+    }
+    match registry.histogram_vec(
+        "http_request_duration_seconds",
+        "HTTP request latency in seconds",
+        &["method", "route", "status"],
+        DEFAULT_BUCKETS,
+    ) {
+        Ok(histogram) => histogram
+            .with(&[&method, &route, response.status().as_str()])
+            .observe_duration(started.elapsed()),
+        Err(e) => tracing::debug!("HTTP metrics not recorded: {}", e),
+    }
+    response
+}
@@ -0,0 +1,522 @@
+//! # proxy
+//!
+//! proxy 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! HTTP reverse proxy
+//!
+//! `ReverseProxy` forwards requests to an upstream server, streaming bodies
+//! both ways. It rewrites the path, drops hop-by-hop headers, adds
+//! `X-Forwarded-*` headers, relays WebSocket connections and retries
+//! idempotent requests on connection errors and 502/503/504 answers with
+//! `crate::retry`, optionally behind a `crate::breaker::CircuitBreaker`.
+//! Only bodies known to fit in 1 MiB are buffered for retries; larger or
+//! chunked bodies are streamed once.
+//!
+//! `X-Forwarded-Host` and `X-Forwarded-Proto` are set from the request the
+//! proxy received, replacing values sent by the client, unless
+//! `trust_forwarded_headers` is enabled. The scheme comes from the listener
+//! (`https` behind the TLS listener of `HttpServer`).
+//!
+//! ```rust,no_run
+//! use axum::Router;
+//! use rf_net::http::ReverseProxy;
+//!
+//! # fn main() -> rf_errors::Result<()> {
+//! let users = ReverseProxy::new("http://users.internal:8080")?
+//!     .strip_prefix("/api/users")
+//!     .retries(2);
+//! let app: Router = Router::new().route("/api/users/{*path}", users.handler());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::buffer::BufferPool;
+use axum::body::Body;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, FromRequestParts, Request};
+use axum::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use axum::http::uri::Scheme;
+use axum::http::{Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{any, MethodRouter};
+use futures_util::{SinkExt, StreamExt};
+use regex::Regex;
+use crate::breaker::CircuitBreaker;
+use crate::retry::RetryConfig;
+use rf_errors::{Result, RfError};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite;
+
+/// Headers that apply to a single connection and are never forwarded
+const HOP_BY_HOP: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Largest request body buffered so that an idempotent request can be retried
+///
+/// Bodies that may be larger, including chunked ones, are sent once without retries.
+const MAX_RETRY_BODY: usize = 1024 * 1024;
+
+/// Forwards requests to an upstream server
+#[derive(Clone)]
+pub struct ReverseProxy {
+    inner: Arc<ProxyConfig>,
+}
+
+#[derive(Clone)]
+struct ProxyConfig {
+    upstream: url::Url,
+    reqwest: reqwest::Client,
+    retry: RetryConfig,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    strip_prefix: Option<String>,
+    rewrites: Vec<(Regex, String)>,
+    remove_request_headers: Vec<HeaderName>,
+    remove_response_headers: Vec<HeaderName>,
+    set_request_headers: Vec<(HeaderName, HeaderValue)>,
+    preserve_host: bool,
+    trust_forwarded_headers: bool,
+    timeout: Option<Duration>,
+    websocket: bool,
+}
+
+impl ReverseProxy {
+    /// Create a proxy for an upstream base URL such as `http://10.0.0.5:8080/v1`
+    ///
+    /// The upstream path is prepended to the (rewritten) request path.
+    pub fn new(upstream: &str) -> Result<Self> {
+        let upstream = url::Url::parse(upstream)
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid upstream URL '{}': {}", upstream, e)))?;
+        if !matches!(upstream.scheme(), "http" | "https") {
+            return Err(RfError::InvalidParameter(format!("Unsupported upstream scheme '{}'", upstream.scheme())));
+        }
+        let reqwest = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| RfError::Network(format!("Failed to build HTTP client: {}", e)))?;
+        let retry = RetryConfig {
+            max_retries: 0,
+            retry_delay: Duration::from_millis(100),
+            retry_on_status: vec![502, 503, 504],
+        };
+        Ok(Self {
+            inner: Arc::new(ProxyConfig {
+                upstream,
+                reqwest,
+                retry,
+                circuit_breaker: None,
+                strip_prefix: None,
+                rewrites: Vec::new(),
+                remove_request_headers: Vec::new(),
+                remove_response_headers: Vec::new(),
+                set_request_headers: Vec::new(),
+                preserve_host: false,
+                trust_forwarded_headers: false,
+                timeout: None,
+                websocket: true,
+            }),
+        })
+    }
+
+    fn config(&mut self) -> &mut ProxyConfig {
+        Arc::make_mut(&mut self.inner)
+    }
+
+    /// Remove a path prefix before forwarding (`/api/users/1` -> `/1`)
+    pub fn strip_prefix(mut self, prefix: &str) -> Self {
+        self.config().strip_prefix = Some(prefix.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Rewrite paths matching `pattern` (anchored) to `replacement`
+    ///
+    /// Applied after `strip_prefix`; `$1`-style groups are expanded.
+    pub fn rewrite(mut self, pattern: &str, replacement: &str) -> Result<Self> {
+        let regex = Regex::new(&format!("^{}$", pattern))
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid rewrite pattern '{}': {}", pattern, e)))?;
+        self.config().rewrites.push((regex, replacement.to_string()));
+        Ok(self)
+    }
+
+    /// Do not forward a request header (e.g. `cookie`)
+    pub fn remove_request_header(mut self, name: &str) -> Self {
+        if let Ok(name) = HeaderName::try_from(name) {
+            self.config().remove_request_headers.push(name);
+        }
+        self
+    }
+
+    /// Do not return an upstream response header (e.g. `server`)
+    pub fn remove_response_header(mut self, name: &str) -> Self {
+        if let Ok(name) = HeaderName::try_from(name) {
+            self.config().remove_response_headers.push(name);
+        }
+        self
+    }
+
+    /// Set a header on every forwarded request
+    pub fn set_request_header(mut self, name: &str, value: &str) -> Result<Self> {
+        let name = HeaderName::try_from(name)
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid header name '{}': {}", name, e)))?;
+        let value = HeaderValue::try_from(value)
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid header value: {}", e)))?;
+        self.config().set_request_headers.push((name, value));
+        Ok(self)
+    }
+
+    /// Forward the client's `Host` header instead of the upstream host
+    pub fn preserve_host(mut self, preserve: bool) -> Self {
+        self.config().preserve_host = preserve;
+        self
+    }
+
+    /// Keep `X-Forwarded-Host` and `X-Forwarded-Proto` sent by the client (default: false)
+    ///
+    /// Only enable this when every client is a trusted proxy that sets these
+    /// headers itself; otherwise clients can spoof the host and scheme seen by
+    /// the upstream.
+    pub fn trust_forwarded_headers(mut self, trust: bool) -> Self {
+        self.config().trust_forwarded_headers = trust;
+        self
+    }
+
+    /// Retry idempotent requests up to `retries` times (default: 0)
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.config().retry.max_retries = retries;
+        self
+    }
+
+    /// Delay between retries (default: 100ms)
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.config().retry.retry_delay = backoff;
+        self
+    }
+
+    /// Replace the retry policy (default: no retries, on 502/503/504)
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.config().retry = retry;
+        self
+    }
+
+    /// Fail fast with 502 while the upstream keeps failing
+    pub fn circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.config().circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Upstream response timeout, including retries; exceeded requests get 504
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config().timeout = Some(timeout);
+        self
+    }
+
+    /// Relay WebSocket upgrades to the upstream (default: enabled)
+    pub fn websocket(mut self, enabled: bool) -> Self {
+        self.config().websocket = enabled;
+        self
+    }
+
+    /// Route handler forwarding every method
+    pub fn handler<S>(self) -> MethodRouter<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        any(move |request: Request| {
+            let proxy = self.clone();
+            async move { proxy.forward(request).await }
+        })
+    }
+
+    /// Upstream URL for a request URI
+    pub fn target(&self, uri: &Uri) -> String {
+        let config = &self.inner;
+        let mut path = uri.path().to_string();
+        if let Some(prefix) = &config.strip_prefix {
+            if let Some(rest) = path.strip_prefix(prefix.as_str()) {
+                if rest.is_empty() || rest.starts_with('/') {
+                    path = rest.to_string();
+                }
+            }
+        }
+        for (pattern, replacement) in &config.rewrites {
+            if pattern.is_match(&path) {
+                path = pattern.replace(&path, replacement.as_str()).into_owned();
+                break;
+            }
+        }
+        let base = config.upstream.as_str().trim_end_matches('/');
+        let path = if path.starts_with('/') { path } else { format!("/{}", path) };
+        match uri.query() {
+            Some(query) => format!("{}{}?{}", base, path, query),
+            None => format!("{}{}", base, path),
+        }
+    }
+
+    /// Forward a request and return the upstream response
+    pub async fn forward(&self, request: Request) -> Response {
+        let (mut parts, body) = request.into_parts();
+        if self.inner.websocket && is_websocket(&parts.headers) {
+            return match WebSocketUpgrade::from_request_parts(&mut parts, &()).await {
+                Ok(upgrade) => self.forward_websocket(upgrade, &parts).await,
+                Err(rejection) => rejection.into_response(),
+            };
+        }
+
+        let url = self.target(&parts.uri);
+        let headers = self.request_headers(&parts);
+        // Retrying needs the whole body in memory: only bodies known to be small are buffered
+        let fits = http_body::Body::size_hint(&body)
+            .upper()
+            .is_some_and(|len| len <= MAX_RETRY_BODY as u64);
+        let retryable = self.inner.retry.max_retries > 0 && is_idempotent(&parts.method) && fits;
+        let result = if retryable {
+            match BufferPool::global().read_body(body, MAX_RETRY_BODY).await {
+                Ok(bytes) => self.send_with_retries(&parts.method, &url, &headers, bytes).await,
+                Err(_) => return error_response(StatusCode::BAD_REQUEST, "Failed to read request body"),
+            }
+        } else {
+            self.send(&parts.method, &url, &headers, streaming_body(body)).await
+        };
+        match result {
+            Ok(response) => self.response(response),
+            Err(RfError::Timeout(_)) => error_response(StatusCode::GATEWAY_TIMEOUT, "Upstream timed out"),
+            Err(e) => {
+                tracing::warn!("Proxy to {} failed: {}", url, e);
+                error_response(StatusCode::BAD_GATEWAY, "Upstream unavailable")
+            }
+        }
+    }
+
+    async fn send(&self, method: &Method, url: &str, headers: &HeaderMap, body: reqwest::Body) -> Result<reqwest::Response> {
+        let request = self.inner.reqwest.request(method.clone(), url).headers(headers.clone()).body(body);
+        let send = || crate::retry::send(request);
+        match self.inner.circuit_breaker {
+            Some(ref breaker) => self.with_timeout(url, breaker.call(send)).await,
+            None => self.with_timeout(url, send()).await,
+        }
+    }
+
+    async fn send_with_retries(&self, method: &Method, url: &str, headers: &HeaderMap, body: axum::body::Bytes)
+        -> Result<reqwest::Response> {
+        let config = &self.inner;
+        let request = crate::retry::send_with_retry(
+            &config.retry,
+            config.circuit_breaker.as_deref(),
+            || Ok(config.reqwest.request(method.clone(), url).headers(headers.clone()).body(body.clone())),
+            crate::retry::send,
+        );
+        self.with_timeout(url, request).await
+    }
+
+    async fn with_timeout<F>(&self, url: &str, request: F) -> Result<reqwest::Response>
+    where
+        F: std::future::Future<Output = Result<reqwest::Response>>,
+    {
+        match self.inner.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, request).await
+                .map_err(|_| RfError::Timeout(format!("No response from {} within {:?}", url, timeout)))?,
+            None => request.await,
+        }
+    }
+
+    fn request_headers(&self, parts: &axum::http::request::Parts) -> HeaderMap {
+        let config = &self.inner;
+        let mut headers = parts.headers.clone();
+        strip_hop_by_hop(&mut headers);
+        for name in &config.remove_request_headers {
+            headers.remove(name);
+        }
+
+        let host = parts.headers.get(header::HOST).cloned();
+        if !config.preserve_host {
+            headers.remove(header::HOST);
+        }
+        if let Some(ConnectInfo(addr)) = parts.extensions.get::<ConnectInfo<SocketAddr>>() {
+            let client = addr.ip().to_string();
+            let forwarded_for = match headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
+                Some(existing) => format!("{}, {}", existing, client),
+                None => client,
+            };
+            if let Ok(value) = HeaderValue::try_from(forwarded_for) {
+                headers.insert("x-forwarded-for", value);
+            }
+        }
+        if !config.trust_forwarded_headers {
+            headers.remove("x-forwarded-host");
+            headers.remove("x-forwarded-proto");
+        }
+        if let Some(host) = host {
+            headers.entry("x-forwarded-host").or_insert(host);
+        }
+        // `HttpServer` records the listener scheme; HTTP/2 requests carry it in the URI
+        let proto = parts
+            .extensions
+            .get::<Scheme>()
+            .or(parts.uri.scheme())
+            .and_then(|scheme| HeaderValue::from_str(scheme.as_str()).ok())
+            .unwrap_or(HeaderValue::from_static("http"));
+        headers.entry("x-forwarded-proto").or_insert(proto);
+
+        for (name, value) in &config.set_request_headers {
+            headers.insert(name, value.clone());
+        }
+        headers
+    }
+
+    fn response(&self, upstream: reqwest::Response) -> Response {
+        let mut response = Response::builder().status(upstream.status());
+        if let Some(headers) = response.headers_mut() {
+            *headers = upstream.headers().clone();
+            strip_hop_by_hop(headers);
+            for name in &self.inner.remove_response_headers {
+                headers.remove(name);
+            }
+        }
+        response
+            .body(Body::from_stream(upstream.bytes_stream()))
+            .unwrap_or_else(|_| error_response(StatusCode::BAD_GATEWAY, "Invalid upstream response"))
+    }
+
+    async fn forward_websocket(&self, upgrade: WebSocketUpgrade, parts: &axum::http::request::Parts) -> Response {
+        let url = self.target(&parts.uri).replacen("http", "ws", 1);
+        let mut request = match tungstenite::client::IntoClientRequest::into_client_request(url.as_str()) {
+            Ok(request) => request,
+            Err(e) => return error_response(StatusCode::BAD_GATEWAY, &format!("Invalid upstream URL: {}", e)),
+        };
+        // Forward end-to-end headers; the handshake headers are generated anew
+        for (name, value) in self.request_headers(parts).iter() {
+            if !name.as_str().starts_with("sec-websocket-") || name == header::SEC_WEBSOCKET_PROTOCOL {
+                request.headers_mut().insert(name, value.clone());
+            }
+        }
+        let (upstream, response) = match tokio_tungstenite::connect_async(request).await {
+            Ok(connected) => connected,
+            Err(e) => {
+                tracing::warn!("WebSocket proxy to {} failed: {}", url, e);
+                return error_response(StatusCode::BAD_GATEWAY, "Upstream unavailable");
+            }
+        };
+        let protocol = response.headers().get(header::SEC_WEBSOCKET_PROTOCOL)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let upgrade = match protocol {
+            Some(protocol) => upgrade.protocols([protocol]),
+            None => upgrade,
+        };
+        upgrade.on_upgrade(move |client| relay(client, upstream))
+    }
+}
+
+/// Copy messages between the client and upstream sockets until either closes
+async fn relay(
+    client: WebSocket,
+    upstream: tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+) {
+    use tungstenite::Message as Upstream;
+    let (mut client_tx, mut client_rx) = client.split();
+    let (mut upstream_tx, mut upstream_rx) = upstream.split();
+    let to_upstream = async {
+        while let Some(Ok(message)) = client_rx.next().await {
+            let message = match message {
+                Message::Text(text) => Upstream::Text(text.to_string()),
+                Message::Binary(data) => Upstream::Binary(data.to_vec()),
+                Message::Ping(data) => Upstream::Ping(data.to_vec()),
+                Message::Pong(data) => Upstream::Pong(data.to_vec()),
+                Message::Close(frame) => {
+                    let frame = frame.map(|f| tungstenite::protocol::CloseFrame {
+                        code: f.code.into(),
+                        reason: f.reason.to_string().into(),
+                    });
+                    let _ = upstream_tx.send(Upstream::Close(frame)).await;
+                    break;
+                }
+            };
+            if upstream_tx.send(message).await.is_err() {
+                break;
+            }
+        }
+    };
+    let to_client = async {
+        while let Some(Ok(message)) = upstream_rx.next().await {
+            let message = match message {
+                Upstream::Text(text) => Message::Text(text.into()),
+                Upstream::Binary(data) => Message::Binary(data.into()),
+                Upstream::Ping(data) => Message::Ping(data.into()),
+                Upstream::Pong(data) => Message::Pong(data.into()),
+                Upstream::Close(frame) => {
+                    let frame = frame.map(|f| axum::extract::ws::CloseFrame {
+                        code: f.code.into(),
+                        reason: f.reason.to_string().into(),
+                    });
+                    let _ = client_tx.send(Message::Close(frame)).await;
+                    break;
+                }
+                Upstream::Frame(_) => continue,
+            };
+            if client_tx.send(message).await.is_err() {
+                break;
+            }
+        }
+    };
+    tokio::select! {
+        _ = to_upstream => {}
+        _ = to_client => {}
+    }
+}
+
+/// Stream a request body to reqwest, which needs a `Sync` stream
+fn streaming_body(body: Body) -> reqwest::Body {
+    let (sender, receiver) = tokio::sync::mpsc::channel::<std::result::Result<axum::body::Bytes, axum::Error>>(8);
+    tokio::spawn(async move {
+        let mut stream = body.into_data_stream();
+        while let Some(chunk) = stream.next().await {
+            if sender.send(chunk).await.is_err() {
+                break;
+            }
+        }
+    });
+    reqwest::Body::wrap_stream(futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    }))
+}
+
+fn strip_hop_by_hop(headers: &mut HeaderMap) {
+    // Headers named in Connection are hop-by-hop as well
+    let named: Vec<HeaderName> = headers.get_all(header::CONNECTION).iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|name| HeaderName::try_from(name.trim()).ok())
+        .collect();
+    for name in named {
+        headers.remove(name);
+    }
+    for name in HOP_BY_HOP {
+        headers.remove(name);
+    }
+}
+
+fn is_websocket(headers: &HeaderMap) -> bool {
+    headers.get(header::UPGRADE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("websocket"))
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE)
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, message.to_string()).into_response()
+}
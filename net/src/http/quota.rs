@@ -0,0 +1,742 @@
+//! # quota
+//!
+//! quota 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Usage quotas
+//!
+//! Unlike rate limiting, quotas count usage over long calendar windows
+//! (API calls per day or month, stored bytes in total) per subject — a
+//! tenant, an API key or a user. Counters live in a `QuotaStore`: memory for
+//! a single instance, Redis (`quota-redis` feature) or a SQL table
+//! (`quota-db` feature) when several instances share them. Windows are UTC
+//! days and months; past windows stay queryable for billing until their
+//! retention expires.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{quota_middleware, QuotaManager, QuotaMiddleware, QuotaPeriod};
+//!
+//! let quotas = Arc::new(
+//!     QuotaManager::memory()
+//!         .limit("api_calls", QuotaPeriod::Monthly, 100_000)
+//!         .limit("storage_bytes", QuotaPeriod::Total, 1 << 30),
+//! );
+//! quotas.set_limit("tenant-42", "api_calls", 1_000_000); // paid plan
+//!
+//! let metered = Arc::new(QuotaMiddleware::new(quotas.clone(), "api_calls").subject_header("x-api-key"));
+//! let server = HttpServer::new(addr).group("/api", |api| {
+//!     api.middleware(axum::middleware::from_fn_with_state(metered, quota_middleware))
+//!         .route(Method::GET, "/items", list_items)
+//! })?;
+//!
+//! // Billing: every subject's usage last month
+//! let report = quotas.report("api_calls", last_month).await?;
+//! ```
+
+use super::response::Response;
+use async_trait::async_trait;
+use axum::extract::{Request, State};
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response as AxumResponse};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use rf_errors::{codes, Code, Result, RfError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+/// How long counters of finished windows are kept by default
+const DEFAULT_RETENTION: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+/// Error code (and status) for exhausted quotas
+const QUOTA_EXCEEDED: Code = 429;
+
+/// Counter window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaPeriod {
+    /// UTC calendar day
+    Daily,
+    /// UTC calendar month
+    Monthly,
+    /// Never resets, for gauges such as stored bytes
+    Total,
+}
+
+impl QuotaPeriod {
+    /// Window label and reset time for the window containing `at`
+    pub fn window(&self, at: DateTime<Utc>) -> (String, Option<DateTime<Utc>>) {
+        let date = at.date_naive();
+        match self {
+            Self::Daily => (date.format("%Y-%m-%d").to_string(), date.succ_opt().map(midnight)),
+            Self::Monthly => {
+                let next = if date.month() == 12 {
+                    NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+                };
+                (date.format("%Y-%m").to_string(), next.map(midnight))
+            }
+            Self::Total => ("total".to_string(), None),
+        }
+    }
+}
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+/// Result of `QuotaStore::consume`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Consumed {
+    /// Whether the amount was added
+    pub allowed: bool,
+    /// Counter value after the call
+    pub used: u64,
+}
+
+/// Counter storage shared by the instances enforcing a quota
+#[async_trait]
+pub trait QuotaStore: Send + Sync {
+    /// Atomically add `amount` unless the counter would exceed `limit`
+    ///
+    /// `expire_at` (Unix seconds) is when the counter can be dropped.
+    async fn consume(&self, key: &str, amount: u64, limit: u64, expire_at: Option<i64>) -> Result<Consumed>;
+
+    /// Subtract `amount`, stopping at zero, and return the new value
+    async fn release(&self, key: &str, amount: u64) -> Result<u64>;
+
+    /// Overwrite the counter
+    async fn set(&self, key: &str, value: u64, expire_at: Option<i64>) -> Result<()>;
+
+    /// Current value, `0` if the counter does not exist
+    async fn get(&self, key: &str) -> Result<u64>;
+
+    /// All counters whose key starts with `prefix`
+    async fn scan(&self, prefix: &str) -> Result<Vec<(String, u64)>>;
+}
+
+/// In-process counters, for a single instance and tests
+#[derive(Debug, Default)]
+pub struct MemoryQuotaStore {
+    counters: Mutex<HashMap<String, (u64, Option<i64>)>>,
+}
+
+impl MemoryQuotaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn counters(&self) -> std::sync::MutexGuard<'_, HashMap<String, (u64, Option<i64>)>> {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Utc::now().timestamp();
+        counters.retain(|_, (_, expire_at)| expire_at.is_none_or(|at| at > now));
+        counters
+    }
+}
+
+#[async_trait]
+impl QuotaStore for MemoryQuotaStore {
+    async fn consume(&self, key: &str, amount: u64, limit: u64, expire_at: Option<i64>) -> Result<Consumed> {
+        let mut counters = self.counters();
+        let counter = counters.entry(key.to_string()).or_insert((0, expire_at));
+        let allowed = counter.0.saturating_add(amount) <= limit;
+        if allowed {
+            counter.0 += amount;
+        }
+        Ok(Consumed {
+            allowed,
+            used: counter.0,
+        })
+    }
+
+    async fn release(&self, key: &str, amount: u64) -> Result<u64> {
+        let mut counters = self.counters();
+        Ok(counters.get_mut(key).map_or(0, |counter| {
+            counter.0 = counter.0.saturating_sub(amount);
+            counter.0
+        }))
+    }
+
+    async fn set(&self, key: &str, value: u64, expire_at: Option<i64>) -> Result<()> {
+        self.counters().insert(key.to_string(), (value, expire_at));
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<u64> {
+        Ok(self.counters().get(key).map_or(0, |counter| counter.0))
+    }
+
+    async fn scan(&self, prefix: &str) -> Result<Vec<(String, u64)>> {
+        Ok(self
+            .counters()
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, counter)| (key.clone(), counter.0))
+            .collect())
+    }
+}
+
+/// Default limit of a metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaRule {
+    pub period: QuotaPeriod,
+    pub limit: u64,
+}
+
+/// Usage of one metric by one subject in one window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub subject: String,
+    pub metric: String,
+    pub period: QuotaPeriod,
+    /// `2026-01-06`, `2026-01` or `total`
+    pub window: String,
+    pub used: u64,
+    pub limit: u64,
+    pub remaining: u64,
+    /// Start of the next window, in Unix seconds
+    pub resets_at: Option<i64>,
+}
+
+/// Result of `QuotaManager::consume`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaCheck {
+    pub allowed: bool,
+    pub usage: QuotaUsage,
+}
+
+/// Quota rules, per-subject limits and counters
+pub struct QuotaManager {
+    store: Arc<dyn QuotaStore>,
+    prefix: String,
+    retention: Duration,
+    rules: HashMap<String, QuotaRule>,
+    overrides: RwLock<HashMap<(String, String), u64>>,
+}
+
+impl QuotaManager {
+    /// Create a manager keeping counters in `store`
+    pub fn new(store: impl QuotaStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+            prefix: "quota:".to_string(),
+            retention: DEFAULT_RETENTION,
+            rules: HashMap::new(),
+            overrides: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Create a manager with in-process counters
+    pub fn memory() -> Self {
+        Self::new(MemoryQuotaStore::new())
+    }
+
+    /// Key prefix in the store (default `quota:`)
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// How long counters are kept after their window ends (default 90 days)
+    pub fn retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Declare a metric with its default limit
+    pub fn limit(mut self, metric: impl Into<String>, period: QuotaPeriod, limit: u64) -> Self {
+        self.rules.insert(metric.into(), QuotaRule { period, limit });
+        self
+    }
+
+    /// Override the limit of a metric for one subject, e.g. for its plan
+    pub fn set_limit(&self, subject: impl Into<String>, metric: impl Into<String>, limit: u64) {
+        let mut overrides = self.overrides.write().unwrap_or_else(|e| e.into_inner());
+        overrides.insert((subject.into(), metric.into()), limit);
+    }
+
+    /// Remove a subject override, returning to the default limit
+    pub fn clear_limit(&self, subject: &str, metric: &str) {
+        let mut overrides = self.overrides.write().unwrap_or_else(|e| e.into_inner());
+        overrides.remove(&(subject.to_string(), metric.to_string()));
+    }
+
+    /// Declared metrics and their default rules
+    pub fn rules(&self) -> &HashMap<String, QuotaRule> {
+        &self.rules
+    }
+
+    /// Limit applying to a subject
+    pub fn limit_for(&self, subject: &str, metric: &str) -> Result<u64> {
+        let rule = self.rule(metric)?;
+        let overrides = self.overrides.read().unwrap_or_else(|e| e.into_inner());
+        Ok(overrides
+            .get(&(subject.to_string(), metric.to_string()))
+            .copied()
+            .unwrap_or(rule.limit))
+    }
+
+    /// Use `amount` of a metric if the subject has enough quota left
+    ///
+    /// Nothing is counted when the quota would be exceeded.
+    pub async fn consume(&self, subject: &str, metric: &str, amount: u64) -> Result<QuotaCheck> {
+        let (key, window, resets_at) = self.key(subject, metric, Utc::now())?;
+        let limit = self.limit_for(subject, metric)?;
+        let consumed = self.store.consume(&key, amount, limit, self.expire_at(resets_at)).await?;
+        Ok(QuotaCheck {
+            allowed: consumed.allowed,
+            usage: self.usage_of(subject, metric, window, consumed.used, limit, resets_at)?,
+        })
+    }
+
+    /// Give back `amount`, e.g. when stored files are deleted
+    pub async fn release(&self, subject: &str, metric: &str, amount: u64) -> Result<QuotaUsage> {
+        let (key, window, resets_at) = self.key(subject, metric, Utc::now())?;
+        let used = self.store.release(&key, amount).await?;
+        self.usage_of(subject, metric, window, used, self.limit_for(subject, metric)?, resets_at)
+    }
+
+    /// Overwrite the current usage, e.g. with a measured storage size
+    pub async fn set_usage(&self, subject: &str, metric: &str, used: u64) -> Result<QuotaUsage> {
+        let (key, window, resets_at) = self.key(subject, metric, Utc::now())?;
+        self.store.set(&key, used, self.expire_at(resets_at)).await?;
+        self.usage_of(subject, metric, window, used, self.limit_for(subject, metric)?, resets_at)
+    }
+
+    /// Usage in the current window
+    pub async fn usage(&self, subject: &str, metric: &str) -> Result<QuotaUsage> {
+        self.usage_at(subject, metric, Utc::now()).await
+    }
+
+    /// Usage in the window containing `at`
+    pub async fn usage_at(&self, subject: &str, metric: &str, at: DateTime<Utc>) -> Result<QuotaUsage> {
+        let (key, window, resets_at) = self.key(subject, metric, at)?;
+        let used = self.store.get(&key).await?;
+        self.usage_of(subject, metric, window, used, self.limit_for(subject, metric)?, resets_at)
+    }
+
+    /// Current usage of every declared metric by a subject
+    pub async fn usage_all(&self, subject: &str) -> Result<Vec<QuotaUsage>> {
+        let mut metrics: Vec<&String> = self.rules.keys().collect();
+        metrics.sort();
+        let mut usage = Vec::with_capacity(metrics.len());
+        for metric in metrics {
+            usage.push(self.usage(subject, metric).await?);
+        }
+        Ok(usage)
+    }
+
+    /// Usage of a metric by every subject in the window containing `at`, for billing
+    pub async fn report(&self, metric: &str, at: DateTime<Utc>) -> Result<Vec<QuotaUsage>> {
+        let rule = self.rule(metric)?;
+        let (window, resets_at) = rule.period.window(at);
+        let prefix = format!("{}{}:{}:", self.prefix, metric, window);
+        let mut report = Vec::new();
+        for (key, used) in self.store.scan(&prefix).await? {
+            let subject = &key[prefix.len()..];
+            let limit = self.limit_for(subject, metric)?;
+            report.push(self.usage_of(subject, metric, window.clone(), used, limit, resets_at)?);
+        }
+        report.sort_by(|a, b| a.subject.cmp(&b.subject));
+        Ok(report)
+    }
+
+    fn rule(&self, metric: &str) -> Result<QuotaRule> {
+        self.rules
+            .get(metric)
+            .copied()
+            .ok_or_else(|| RfError::InvalidParameter(format!("Unknown quota metric: {}", metric)))
+    }
+
+    fn key(&self, subject: &str, metric: &str, at: DateTime<Utc>) -> Result<(String, String, Option<DateTime<Utc>>)> {
+        let (window, resets_at) = self.rule(metric)?.period.window(at);
+        Ok((format!("{}{}:{}:{}", self.prefix, metric, window, subject), window, resets_at))
+    }
+
+    fn expire_at(&self, resets_at: Option<DateTime<Utc>>) -> Option<i64> {
+        resets_at.map(|at| at.timestamp() + self.retention.as_secs() as i64)
+    }
+
+    fn usage_of(
+        &self,
+        subject: &str,
+        metric: &str,
+        window: String,
+        used: u64,
+        limit: u64,
+        resets_at: Option<DateTime<Utc>>,
+    ) -> Result<QuotaUsage> {
+        Ok(QuotaUsage {
+            subject: subject.to_string(),
+            metric: metric.to_string(),
+            period: self.rule(metric)?.period,
+            window,
+            used,
+            limit,
+            remaining: limit.saturating_sub(used),
+            resets_at: resets_at.map(|at| at.timestamp()),
+        })
+    }
+}
+
+type SubjectFn = Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>;
+
+/// Enforces a quota on every request
+///
+/// Each request consumes one unit of the metric for the subject found in
+/// the request. Requests without a subject get 401, exhausted quotas 429.
+/// Responses carry `X-Quota-Limit`, `X-Quota-Remaining` and
+/// `X-Quota-Reset` headers. Store failures are logged and let the request
+/// through.
+pub struct QuotaMiddleware {
+    manager: Arc<QuotaManager>,
+    metric: String,
+    subject: SubjectFn,
+}
+
+impl QuotaMiddleware {
+    /// Meter `metric`, with the subject taken from the `X-API-Key` header
+    pub fn new(manager: Arc<QuotaManager>, metric: impl Into<String>) -> Self {
+        Self {
+            manager,
+            metric: metric.into(),
+            subject: Arc::new(|request: &Request| header_value(request, "x-api-key")),
+        }
+    }
+
+    /// Take the subject from a request header
+    pub fn subject_header(mut self, name: &'static str) -> Self {
+        self.subject = Arc::new(move |request: &Request| header_value(request, name));
+        self
+    }
+
+    /// Take the subject from the request, e.g. a JWT claim
+    pub fn subject_fn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    {
+        self.subject = Arc::new(f);
+        self
+    }
+}
+
+fn header_value(request: &Request, name: &str) -> Option<String> {
+    request
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// Consume one unit of quota per request
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc::new(quota), quota_middleware)`.
+pub async fn quota_middleware(State(quota): State<Arc<QuotaMiddleware>>, request: Request, next: Next) -> AxumResponse {
+    let Some(subject) = (quota.subject)(&request) else {
+        return Response::fail(codes::UNAUTHORIZED, "Missing quota subject").into_response();
+    };
+    let check = match quota.manager.consume(&subject, &quota.metric, 1).await {
+        Ok(check) => check,
+        Err(e) => {
+            tracing::warn!("Quota check for {} failed: {}", quota.metric, e);
+            return next.run(request).await;
+        }
+    };
+
+    let mut response = if check.allowed {
+        next.run(request).await
+    } else {
+        Response::fail(QUOTA_EXCEEDED, format!("Quota exceeded: {}", quota.metric)).into_response()
+    };
+    let headers = response.headers_mut();
+    headers.insert("x-quota-limit", HeaderValue::from(check.usage.limit));
+    headers.insert("x-quota-remaining", HeaderValue::from(check.usage.remaining));
+    if let Some(resets_at) = check.usage.resets_at {
+        headers.insert("x-quota-reset", HeaderValue::from(resets_at));
+    }
+    response
+}
+
+/// Counters in Redis, updated with Lua scripts so checks are atomic
+#[cfg(feature = "quota-redis")]
+pub struct RedisQuotaStore {
+    client: Arc<rf_database::redis::RedisClient>,
+}
+
+#[cfg(feature = "quota-redis")]
+impl RedisQuotaStore {
+    pub fn new(client: Arc<rf_database::redis::RedisClient>) -> Self {
+        Self { client }
+    }
+
+    async fn eval(&self, script: &str, key: &str, args: &[&str]) -> Result<Vec<u64>> {
+        let value = self.client.script().eval(script, &[key], args).await?;
+        redis::from_redis_value(value).map_err(|e| RfError::Database(format!("Unexpected Redis reply: {}", e)))
+    }
+}
+
+#[cfg(feature = "quota-redis")]
+const REDIS_CONSUME: &str = r#"
+local used = tonumber(redis.call('GET', KEYS[1]) or '0')
+local amount = tonumber(ARGV[1])
+if used + amount > tonumber(ARGV[2]) then
+    return {0, used}
+end
+used = redis.call('INCRBY', KEYS[1], amount)
+if ARGV[3] ~= '' then
+    redis.call('EXPIREAT', KEYS[1], ARGV[3])
+end
+return {1, used}
+"#;
+
+#[cfg(feature = "quota-redis")]
+const REDIS_RELEASE: &str = r#"
+local used = tonumber(redis.call('GET', KEYS[1]) or '0')
+if used == 0 then
+    return {0}
+end
+used = math.max(used - tonumber(ARGV[1]), 0)
+redis.call('SET', KEYS[1], used, 'KEEPTTL')
+return {used}
+"#;
+
+#[cfg(feature = "quota-redis")]
+const REDIS_SET: &str = r#"
+redis.call('SET', KEYS[1], ARGV[1])
+if ARGV[2] ~= '' then
+    redis.call('EXPIREAT', KEYS[1], ARGV[2])
+end
+return {tonumber(ARGV[1])}
+"#;
+
+#[cfg(feature = "quota-redis")]
+#[async_trait]
+impl QuotaStore for RedisQuotaStore {
+    async fn consume(&self, key: &str, amount: u64, limit: u64, expire_at: Option<i64>) -> Result<Consumed> {
+        let expire_at = expire_at.map(|at| at.to_string()).unwrap_or_default();
+        let reply = self
+            .eval(REDIS_CONSUME, key, &[&amount.to_string(), &limit.to_string(), &expire_at])
+            .await?;
+        match reply.as_slice() {
+            [allowed, used] => Ok(Consumed {
+                allowed: *allowed == 1,
+                used: *used,
+            }),
+            _ => Err(RfError::Database("Unexpected Redis reply".to_string())),
+        }
+    }
+
+    async fn release(&self, key: &str, amount: u64) -> Result<u64> {
+        let reply = self.eval(REDIS_RELEASE, key, &[&amount.to_string()]).await?;
+        Ok(reply.first().copied().unwrap_or_default())
+    }
+
+    async fn set(&self, key: &str, value: u64, expire_at: Option<i64>) -> Result<()> {
+        let expire_at = expire_at.map(|at| at.to_string()).unwrap_or_default();
+        self.eval(REDIS_SET, key, &[&value.to_string(), &expire_at]).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<u64> {
+        let reply = self.eval("return {tonumber(redis.call('GET', KEYS[1]) or '0')}", key, &[]).await?;
+        Ok(reply.first().copied().unwrap_or_default())
+    }
+
+    /// Uses `KEYS`, meant for occasional billing reports
+    async fn scan(&self, prefix: &str) -> Result<Vec<(String, u64)>> {
+        let pattern = format!("{}*", prefix.replace('*', "\\*").replace('?', "\\?").replace('[', "\\["));
+        let mut counters = Vec::new();
+        for key in self.client.generic().keys(&pattern).await? {
+            let used = self.get(&key).await?;
+            counters.push((key, used));
+        }
+        Ok(counters)
+    }
+}
+
+/// Counters in a SQL table (`quota_key`, `used`, `expire_at`)
+///
+/// Call `ensure_table` once to create the table, and `purge_expired`
+/// periodically to delete counters past their retention.
+#[cfg(feature = "quota-db")]
+pub struct DatabaseQuotaStore {
+    db: Arc<rf_database::Database>,
+    table: String,
+}
+
+#[cfg(feature = "quota-db")]
+impl DatabaseQuotaStore {
+    /// Use the `rf_quota` table
+    pub fn new(db: Arc<rf_database::Database>) -> Self {
+        Self::with_table(db, "rf_quota")
+    }
+
+    /// Use a custom table name
+    pub fn with_table(db: Arc<rf_database::Database>, table: impl Into<String>) -> Self {
+        Self {
+            db,
+            table: table.into(),
+        }
+    }
+
+    /// Create the table if it does not exist
+    pub async fn ensure_table(&self) -> Result<()> {
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {} (quota_key VARCHAR(255) PRIMARY KEY, used BIGINT NOT NULL, expire_at BIGINT)",
+            self.table
+        );
+        self.db.raw_execute(&sql).await?;
+        Ok(())
+    }
+
+    /// Delete expired counters
+    pub async fn purge_expired(&self) -> Result<u64> {
+        let sql = format!("DELETE FROM {} WHERE expire_at IS NOT NULL AND expire_at <= {}", self.table, Utc::now().timestamp());
+        self.db.raw_execute(&sql).await
+    }
+
+    /// Run a statement with `$n` placeholders, rewritten to `?` for MySQL and SQLite
+    async fn execute(&self, sql: &str, args: &[SqlArg<'_>]) -> Result<u64> {
+        let (sql, order) = self.placeholders(sql, args.len());
+        macro_rules! run {
+            ($pool:expr) => {{
+                let mut query = sqlx::query(&sql);
+                for arg in order.iter().filter_map(|&i| args.get(i)) {
+                    query = match arg {
+                        SqlArg::Text(v) => query.bind(*v),
+                        SqlArg::Int(v) => query.bind(*v),
+                        SqlArg::OptInt(v) => query.bind(*v),
+                    };
+                }
+                query.execute($pool).await.map(|r| r.rows_affected())
+            }};
+        }
+        let result = if let Some(pool) = self.db.as_postgres() {
+            run!(pool)
+        } else if let Some(pool) = self.db.as_mysql() {
+            run!(pool)
+        } else if let Some(pool) = self.db.as_sqlite() {
+            run!(pool)
+        } else {
+            return Err(RfError::Database("Unsupported database".to_string()));
+        };
+        result.map_err(|e| RfError::Database(format!("Quota update failed: {}", e)))
+    }
+
+    /// Query `(quota_key, used)` rows with a single `$1` argument
+    async fn rows(&self, sql: &str, arg: &str) -> Result<Vec<(String, i64)>> {
+        let (sql, _) = self.placeholders(sql, 1);
+        let result = if let Some(pool) = self.db.as_postgres() {
+            sqlx::query_as(&sql).bind(arg).fetch_all(pool).await
+        } else if let Some(pool) = self.db.as_mysql() {
+            sqlx::query_as(&sql).bind(arg).fetch_all(pool).await
+        } else if let Some(pool) = self.db.as_sqlite() {
+            sqlx::query_as(&sql).bind(arg).fetch_all(pool).await
+        } else {
+            return Err(RfError::Database("Unsupported database".to_string()));
+        };
+        result.map_err(|e| RfError::Database(format!("Quota query failed: {}", e)))
+    }
+
+    /// SQL for the current database and the argument index of each placeholder
+    ///
+    /// Positional `?` placeholders need one argument per occurrence, so a
+    /// repeated `$1` binds its argument twice.
+    fn placeholders(&self, sql: &str, count: usize) -> (String, Vec<usize>) {
+        let sql = sql.replace("{table}", &self.table);
+        if self.db.as_postgres().is_some() {
+            return (sql, (0..count).collect());
+        }
+        let mut order = Vec::new();
+        let mut rewritten = String::with_capacity(sql.len());
+        let mut chars = sql.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                rewritten.push(c);
+                continue;
+            }
+            let mut index = 0;
+            while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                index = index * 10 + digit as usize;
+                chars.next();
+            }
+            order.push(index.saturating_sub(1));
+            rewritten.push('?');
+        }
+        (rewritten, order)
+    }
+
+    /// Create the counter row if missing
+    async fn ensure_row(&self, key: &str, expire_at: Option<i64>) -> Result<()> {
+        let sql = if self.db.as_mysql().is_some() {
+            "INSERT IGNORE INTO {table} (quota_key, used, expire_at) VALUES ($1, 0, $2)"
+        } else {
+            "INSERT INTO {table} (quota_key, used, expire_at) VALUES ($1, 0, $2) ON CONFLICT (quota_key) DO NOTHING"
+        };
+        self.execute(sql, &[SqlArg::Text(key), SqlArg::OptInt(expire_at)]).await?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "quota-db")]
+enum SqlArg<'a> {
+    Text(&'a str),
+    Int(i64),
+    OptInt(Option<i64>),
+}
+
+#[cfg(feature = "quota-db")]
+#[async_trait]
+impl QuotaStore for DatabaseQuotaStore {
+    async fn consume(&self, key: &str, amount: u64, limit: u64, expire_at: Option<i64>) -> Result<Consumed> {
+        self.ensure_row(key, expire_at).await?;
+        // The condition makes the check and the increment one atomic statement
+        let updated = self
+            .execute(
+                "UPDATE {table} SET used = used + $1 WHERE quota_key = $2 AND used + $1 <= $3",
+                &[SqlArg::Int(amount as i64), SqlArg::Text(key), SqlArg::Int(limit as i64)],
+            )
+            .await?;
+        Ok(Consumed {
+            allowed: updated > 0,
+            used: self.get(key).await?,
+        })
+    }
+
+    async fn release(&self, key: &str, amount: u64) -> Result<u64> {
+        self.execute(
+            "UPDATE {table} SET used = CASE WHEN used > $1 THEN used - $1 ELSE 0 END WHERE quota_key = $2",
+            &[SqlArg::Int(amount as i64), SqlArg::Text(key)],
+        )
+        .await?;
+        self.get(key).await
+    }
+
+    async fn set(&self, key: &str, value: u64, expire_at: Option<i64>) -> Result<()> {
+        self.ensure_row(key, expire_at).await?;
+        self.execute(
+            "UPDATE {table} SET used = $1 WHERE quota_key = $2",
+            &[SqlArg::Int(value as i64), SqlArg::Text(key)],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<u64> {
+        let rows = self.rows("SELECT quota_key, used FROM {table} WHERE quota_key = $1", key).await?;
+        Ok(rows.first().map_or(0, |(_, used)| *used as u64))
+    }
+
+    async fn scan(&self, prefix: &str) -> Result<Vec<(String, u64)>> {
+        let pattern = format!("{}%", prefix.replace('!', "!!").replace('%', "!%").replace('_', "!_"));
+        let rows = self
+            .rows("SELECT quota_key, used FROM {table} WHERE quota_key LIKE $1 ESCAPE '!'", &pattern)
+            .await?;
+        Ok(rows.into_iter().map(|(key, used)| (key, used as u64)).collect())
+    }
+}
@@ -9,15 +9,16 @@
 
 use axum::extract::Request;
 use axum::response::Response;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::Mutex;
 
 /// Rate limiter using token bucket algorithm
 pub struct RateLimiter {
-    capacity: u64,
+    capacity: AtomicU64,
     tokens: Arc<Mutex<u64>>,
-    refill_rate: u64, // tokens per second
+    refill_rate: AtomicU64, // tokens per second
     last_refill: Arc<Mutex<Instant>>,
 }
 
@@ -25,13 +26,31 @@ impl RateLimiter {
     /// Create a new rate limiter
     pub fn new(capacity: u64, refill_rate: u64) -> Self {
         Self {
-            capacity,
+            capacity: AtomicU64::new(capacity),
             tokens: Arc::new(Mutex::new(capacity)),
-            refill_rate,
+            refill_rate: AtomicU64::new(refill_rate),
             last_refill: Arc::new(Mutex::new(Instant::now())),
         }
     }
 
+    /// Change the limits of a running limiter
+    ///
+    /// Tokens above the new capacity are dropped on the next request.
+    pub fn set_limits(&self, capacity: u64, refill_rate: u64) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        self.refill_rate.store(refill_rate, Ordering::Relaxed);
+    }
+
+    /// Bucket capacity
+    pub fn capacity(&self) -> u64 {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Tokens added per second
+    pub fn refill_rate(&self) -> u64 {
+        self.refill_rate.load(Ordering::Relaxed)
+    }
+
     /// Check if a request is allowed
     pub async fn allow(&self) -> bool {
         let mut tokens = self.tokens.lock().await;
@@ -39,12 +58,13 @@ impl RateLimiter {
         
         let now = Instant::now();
         let elapsed = now.duration_since(*last_refill);
-        let tokens_to_add = (elapsed.as_secs_f64() * self.refill_rate as f64) as u64;
+        let tokens_to_add = (elapsed.as_secs_f64() * self.refill_rate() as f64) as u64;
         
         if tokens_to_add > 0 {
-            *tokens = (*tokens + tokens_to_add).min(self.capacity);
+            *tokens += tokens_to_add;
             *last_refill = now;
         }
+        *tokens = (*tokens).min(self.capacity());
         
         if *tokens > 0 {
             *tokens -= 1;
@@ -14,6 +14,9 @@
 //! - 访问 HTTP 方法、URI、头
 //! - 解析查询参数
 //! - 提取单个查询参数
+//! - 提取路径参数（`/users/:id` -> `request.param("id")`）
+//! - 解析 User-Agent（浏览器、操作系统、设备类型、爬虫识别）
+//! - 绑定并验证请求参数（`request.parse::<T>()`）
 //!
 //! # 使用示例
 //!
@@ -39,12 +42,21 @@
 //! }
 //! ```
 
-use axum::extract::Query;
+use super::limits::BodyLimit;
+use super::middleware::jwt::{self, Claims};
+use super::parse::{self, ParseError};
+use super::router::PathParams;
+use super::session::SessionHandle;
+use super::user_agent::UserAgent;
+use axum::extract::{FromRequest, FromRequestParts, Query, RawPathParams};
 use axum::http::HeaderMap;
 use axum::http::Method;
 use axum::http::Uri;
+use futures_util::FutureExt;
 use rf_errors::Result;
+use rf_util::valid::Validate;
 use serde::de::DeserializeOwned;
+use std::convert::Infallible;
 
 /// HTTP 请求封装
 ///
@@ -68,7 +80,19 @@ impl Request {
     ///
     /// 返回一个 Request 封装实例
     pub fn new(request: axum::extract::Request) -> Self {
-        Self { inner: request }
+        let (mut parts, body) = request.into_parts();
+        if parts.extensions.get::<PathParams>().is_none() {
+            // axum 已完成路由匹配，提取结果是同步就绪的
+            if let Some(Ok(raw)) = RawPathParams::from_request_parts(&mut parts, &()).now_or_never() {
+                let params: PathParams = raw.iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect();
+                parts.extensions.insert(params);
+            }
+        }
+        Self {
+            inner: axum::extract::Request::from_parts(parts, body),
+        }
     }
 
     /// 获取 HTTP 方法
@@ -206,6 +230,169 @@ impl Request {
         })
     }
 
+    /// 获取路径参数
+    ///
+    /// # 参数
+    ///
+    /// - `name`: 路由模式中的参数名，如 `/users/:id` 中的 `id`、`/files/*path` 中的 `path`
+    ///
+    /// # 返回值
+    ///
+    /// - `Some(&str)`: 参数存在（已完成百分号解码）
+    /// - `None`: 参数不存在
+    ///
+    /// # 示例
+    ///
+    /// ```ignore
+    /// // 路由: /users/:id，URL: /users/42
+    /// let id = request.param("id").unwrap_or_default();
+    /// ```
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params().and_then(|params| params.get(name))
+    }
+
+    /// 获取全部路径参数
+    ///
+    /// # 返回值
+    ///
+    /// 请求未经过带参数的路由时返回 `None`
+    pub fn params(&self) -> Option<&PathParams> {
+        self.inner.extensions().get::<PathParams>()
+    }
+
+    /// 绑定请求参数到结构体并执行验证规则
+    ///
+    /// 依次合并路径参数、查询参数和请求体（JSON、`x-www-form-urlencoded` 或
+    /// `multipart/form-data` 的文本字段），同名参数后者覆盖前者。查询和表单中的字符串
+    /// 会按字段类型转换为数字或布尔值，空字符串绑定为 `None`，重复参数（或 `key[]`）绑定为数组。
+    /// 反序列化成功后执行字段上 `#[valid(rule = "...")]` 声明的规则。
+    ///
+    /// 请求体只能读取一次，再次调用时只包含路径和查询参数。
+    ///
+    /// # 类型参数
+    ///
+    /// - `T`: 目标类型，需实现 `DeserializeOwned` 和 `Validate`
+    ///
+    /// # 返回值
+    ///
+    /// 返回绑定并通过验证的结构体
+    ///
+    /// # 错误
+    ///
+    /// 返回 `ParseError`，可直接作为 `400 Bad Request` 响应返回，响应体包含每个字段的错误
+    ///
+    /// # 示例
+    ///
+    /// ```ignore
+    /// use rf_util::valid::Validate;
+    ///
+    /// #[derive(Deserialize, Validate)]
+    /// struct CreateUser {
+    ///     #[valid(rule = "required|length:3,20")]
+    ///     name: String,
+    ///     #[valid(rule = "required|email", message = "请输入有效的邮箱")]
+    ///     email: String,
+    ///     #[valid(rule = "between:1,150")]
+    ///     age: Option<u32>,
+    /// }
+    ///
+    /// async fn create(mut request: Request) -> Result<Json<User>, ParseError> {
+    ///     let input: CreateUser = request.parse().await?;
+    ///     // ...
+    /// }
+    /// ```
+    pub async fn parse<T: DeserializeOwned + Validate>(&mut self) -> std::result::Result<T, ParseError> {
+        let mut params = serde_json::Map::new();
+        if let Some(path) = self.params() {
+            for (name, value) in path.iter() {
+                params.insert(name.to_string(), serde_json::Value::String(value.to_string()));
+            }
+        }
+        if let Some(query) = self.uri().query() {
+            parse::merge_urlencoded(&mut params, query.as_bytes());
+        }
+        let limit = self
+            .inner
+            .extensions()
+            .get::<BodyLimit>()
+            .map_or(parse::MAX_PARSE_BODY_SIZE, |limit| limit.0);
+        let body = std::mem::take(self.inner.body_mut());
+        parse::merge_body(&mut params, self.inner.headers(), body, limit).await?;
+
+        let value: T = parse::from_value(serde_json::Value::Object(params))?;
+        value.validate().map_err(ParseError::Invalid)?;
+        Ok(value)
+    }
+
+    /// 获取解析后的 User-Agent
+    ///
+    /// # 返回值
+    ///
+    /// 优先使用 `user_agent_middleware` 已解析的结果，否则使用全局解析器解析 `User-Agent` 头
+    ///
+    /// # 示例
+    ///
+    /// ```ignore
+    /// let ua = request.user_agent();
+    /// if ua.is_bot() {
+    ///     return Ok(Response::text("Hello, crawler"));
+    /// }
+    /// println!("{} on {} ({})", ua.browser_name(), ua.os_name(), ua.device.as_str());
+    /// ```
+    pub fn user_agent(&self) -> UserAgent {
+        if let Some(parsed) = self.inner.extensions().get::<UserAgent>() {
+            return parsed.clone();
+        }
+        let header = self.header("user-agent").and_then(|v| v.to_str().ok()).unwrap_or("");
+        UserAgent::parse(header)
+    }
+
+    /// 获取 `jwt_middleware` 校验通过的 JWT 声明
+    ///
+    /// # 返回值
+    ///
+    /// 未经过 JWT 中间件时返回 `None`
+    ///
+    /// # 错误
+    ///
+    /// 声明无法转换为 `T` 时返回 `RfError::InvalidParameter`
+    ///
+    /// # 示例
+    ///
+    /// ```ignore
+    /// #[derive(Deserialize)]
+    /// struct Role { role: String }
+    ///
+    /// if let Some(claims) = request.claims::<Role>()? {
+    ///     println!("{:?} is {}", claims.sub, claims.extra.role);
+    /// }
+    /// ```
+    pub fn claims<T: DeserializeOwned>(&self) -> Result<Option<Claims<T>>> {
+        self.inner
+            .extensions()
+            .get::<Claims>()
+            .map(jwt::typed_claims)
+            .transpose()
+    }
+
+    /// 获取当前会话
+    ///
+    /// # 返回值
+    ///
+    /// 未启用会话中间件（`HttpServer::with_session`）时返回 `None`
+    ///
+    /// # 示例
+    ///
+    /// ```ignore
+    /// if let Some(session) = request.session() {
+    ///     let visits = session.get::<u32>("visits").unwrap_or(0) + 1;
+    ///     session.insert("visits", visits)?;
+    /// }
+    /// ```
+    pub fn session(&self) -> Option<SessionHandle> {
+        self.inner.extensions().get::<SessionHandle>().cloned()
+    }
+
     /// 获取原始的 axum 请求
     ///
     /// # 返回值
@@ -222,3 +409,12 @@ impl From<axum::extract::Request> for Request {
         Self::new(request)
     }
 }
+
+impl<S: Send + Sync> FromRequest<S> for Request {
+    type Rejection = Infallible;
+
+    /// 作为处理函数参数直接提取 Request
+    async fn from_request(request: axum::extract::Request, _state: &S) -> std::result::Result<Self, Self::Rejection> {
+        Ok(Self::new(request))
+    }
+}
@@ -0,0 +1,259 @@
+//! # request_id
+//!
+//! request_id 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Request IDs and access logging
+//!
+//! `request_id_middleware` gives every request an ID: the inbound
+//! `X-Request-Id` when it looks sane, otherwise a new UUID. The ID is
+//! echoed in the response, recorded on a `request` tracing span, exposed to
+//! handlers as the `RequestId` extractor and through `rf_core::ctx::request_id`
+//! (which error envelopes and `HttpClient` pick up), and written to the
+//! access log through `rf_os::log::access` with the W3C trace id.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{HttpServer, RequestId, RequestIdMiddleware};
+//!
+//! let server = HttpServer::new(addr)
+//!     .with_request_id(RequestIdMiddleware::new().skip_access_log("/health"))
+//!     .route(Method::GET, "/orders", |id: RequestId| async move { id.to_string() })?;
+//! ```
+
+use super::envelope::ApiError;
+use axum::body::{Body, HttpBody};
+use axum::extract::{ConnectInfo, FromRequestParts, Request, State};
+use axum::http::header::USER_AGENT;
+use axum::http::request::Parts;
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response as AxumResponse;
+use futures_util::StreamExt;
+use rf_errors::{Result, RfError};
+use rf_os::log::AccessLog;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::Instrument;
+
+/// Default request ID header
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest inbound request ID that is kept
+const MAX_INBOUND_LEN: usize = 128;
+
+/// ID of the current request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestId {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> std::result::Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<RequestId>()
+            .cloned()
+            .ok_or_else(|| ApiError(RfError::Internal("Request ID middleware is not enabled".to_string())))
+    }
+}
+
+/// Request ID generator
+type IdGenerator = Arc<dyn Fn() -> String + Send + Sync>;
+
+/// Request ID and access log settings
+#[derive(Clone)]
+pub struct RequestIdMiddleware {
+    header: HeaderName,
+    trust_inbound: bool,
+    generator: IdGenerator,
+    access_log: bool,
+    skip_paths: Vec<String>,
+}
+
+impl Default for RequestIdMiddleware {
+    fn default() -> Self {
+        Self {
+            header: HeaderName::from_static(REQUEST_ID_HEADER),
+            trust_inbound: true,
+            generator: Arc::new(|| uuid::Uuid::new_v4().simple().to_string()),
+            access_log: true,
+            skip_paths: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for RequestIdMiddleware {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestIdMiddleware")
+            .field("header", &self.header)
+            .field("trust_inbound", &self.trust_inbound)
+            .field("access_log", &self.access_log)
+            .field("skip_paths", &self.skip_paths)
+            .finish()
+    }
+}
+
+impl RequestIdMiddleware {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Header carrying the ID in both directions (default `X-Request-Id`)
+    pub fn header(mut self, name: &str) -> Result<Self> {
+        self.header = HeaderName::try_from(name)
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid header name {}: {}", name, e)))?;
+        Ok(self)
+    }
+
+    /// Keep IDs sent by clients or upstream proxies (default `true`)
+    ///
+    /// Inbound IDs longer than 128 characters or containing anything but
+    /// visible ASCII are replaced either way.
+    pub fn trust_inbound(mut self, trust: bool) -> Self {
+        self.trust_inbound = trust;
+        self
+    }
+
+    /// Generate IDs with `generator` instead of UUIDv4
+    pub fn generator(mut self, generator: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        self.generator = Arc::new(generator);
+        self
+    }
+
+    /// Write an access log line per request (default `true`)
+    pub fn access_log(mut self, enabled: bool) -> Self {
+        self.access_log = enabled;
+        self
+    }
+
+    /// Leave requests to `path` out of the access log, e.g. health checks
+    pub fn skip_access_log(mut self, path: &str) -> Self {
+        self.skip_paths.push(path.to_string());
+        self
+    }
+
+    /// The inbound ID if it is kept, otherwise a new one
+    fn request_id(&self, request: &Request) -> String {
+        let inbound = request
+            .headers()
+            .get(&self.header)
+            .and_then(|v| v.to_str().ok())
+            .filter(|id| self.trust_inbound && valid_id(id));
+        match inbound {
+            Some(id) => id.to_string(),
+            None => (self.generator)(),
+        }
+    }
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_INBOUND_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Trace id from a W3C `traceparent` header
+fn traceparent_trace_id(request: &Request) -> Option<String> {
+    let value = request.headers().get("traceparent")?.to_str().ok()?;
+    let trace_id = value.split('-').nth(1)?;
+    (trace_id.len() == 32 && trace_id.bytes().all(|b| b.is_ascii_hexdigit()) && trace_id.bytes().any(|b| b != b'0'))
+        .then(|| trace_id.to_ascii_lowercase())
+}
+
+/// Trace id of the current OpenTelemetry context, if any
+fn otel_trace_id() -> Option<String> {
+    use opentelemetry::trace::TraceContextExt;
+
+    let context = opentelemetry::Context::current();
+    let span_context = context.span().span_context().clone();
+    span_context.is_valid().then(|| span_context.trace_id().to_string())
+}
+
+/// Writes the access log line when the response body is finished or dropped
+struct AccessLogGuard {
+    entry: AccessLog,
+    start: Instant,
+}
+
+impl AccessLogGuard {
+    fn add_bytes(&mut self, bytes: usize) {
+        self.entry.bytes += bytes as u64;
+    }
+}
+
+impl Drop for AccessLogGuard {
+    fn drop(&mut self) {
+        self.entry.latency = self.start.elapsed();
+        rf_os::log::access(&self.entry);
+    }
+}
+
+/// Assign request IDs and write access logs
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc::new(config), request_id_middleware)`,
+/// or `HttpServer::with_request_id`.
+pub async fn request_id_middleware(
+    State(config): State<Arc<RequestIdMiddleware>>,
+    mut request: Request,
+    next: Next,
+) -> AxumResponse {
+    let start = Instant::now();
+    let id = config.request_id(&request);
+    let header_value = HeaderValue::from_str(&id).ok();
+    if let Some(value) = &header_value {
+        request.headers_mut().insert(config.header.clone(), value.clone());
+    }
+    request.extensions_mut().insert(RequestId(id.clone()));
+
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let log = config.access_log && !config.skip_paths.iter().any(|p| p == &path);
+    let mut entry = AccessLog {
+        method: method.clone(),
+        path: path.clone(),
+        request_id: Some(id.clone()),
+        trace_id: traceparent_trace_id(&request).or_else(otel_trace_id),
+        client_ip: request.extensions().get::<ConnectInfo<SocketAddr>>().map(|c| c.0.ip().to_string()),
+        user_agent: request.headers().get(USER_AGENT).and_then(|v| v.to_str().ok()).map(str::to_string),
+        ..AccessLog::default()
+    };
+
+    let span = tracing::info_span!("request", request_id = %id, method = %method, path = %path);
+    let mut response = rf_core::ctx::with_request_id(id, next.run(request)).instrument(span).await;
+    if let Some(value) = header_value {
+        response.headers_mut().insert(config.header.clone(), value);
+    }
+    if !log {
+        return response;
+    }
+
+    entry.status = response.status().as_u16();
+    if let Some(bytes) = response.body().size_hint().exact() {
+        entry.bytes = bytes;
+        drop(AccessLogGuard { entry, start });
+        return response;
+    }
+    // Streamed body: count it and log once it is done
+    let (parts, body) = response.into_parts();
+    let mut guard = AccessLogGuard { entry, start };
+    let body = body.into_data_stream().map(move |chunk| {
+        if let Ok(chunk) = &chunk {
+            guard.add_bytes(chunk.len());
+        }
+        chunk
+    });
+    AxumResponse::from_parts(parts, Body::from_stream(body))
+}
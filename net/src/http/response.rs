@@ -10,7 +10,7 @@
 use axum::http::StatusCode;
 use axum::response::{Html, Json as AxumJson, Response as AxumResponse};
 use axum::response::IntoResponse;
-use rf_errors::Result;
+use rf_errors::{Code, Result};
 use serde::Serialize;
 
 /// HTTP response wrapper
@@ -33,6 +33,36 @@ impl Response {
         })
     }
 
+    /// Create a response rendered per the request's `Accept` header
+    ///
+    /// JSON unless the server negotiates, see `HttpServer::with_negotiation`.
+    pub fn negotiated<T: Serialize + 'static>(data: T) -> Self {
+        Self {
+            inner: super::negotiate::Negotiated(data).into_response(),
+        }
+    }
+
+    /// Create a success envelope `{"code": 0, "message": "OK", "data": data}`
+    pub fn success<T: Serialize>(data: T) -> Self {
+        let inner = match serde_json::to_value(data) {
+            Ok(data) => super::envelope::envelope_response(StatusCode::OK, 0, "OK".to_string(), data),
+            Err(e) => super::envelope::EnvelopeConfig::default()
+                .error_response(&rf_errors::RfError::Serialization(e.to_string())),
+        };
+        Self { inner }
+    }
+
+    /// Create a failure envelope `{"code": code, "message": message, "data": null}`
+    ///
+    /// The status follows the code (400-599 as is, anything else 500) and can be
+    /// remapped with `EnvelopeConfig::map_code`.
+    pub fn fail(code: Code, message: impl Into<String>) -> Self {
+        let status = super::envelope::EnvelopeConfig::default().status_for(code);
+        Self {
+            inner: super::envelope::envelope_response(status, code, message.into(), serde_json::Value::Null),
+        }
+    }
+
     /// Create an HTML response
     pub fn html(html: impl Into<String>) -> Self {
         Self {
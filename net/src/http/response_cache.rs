@@ -0,0 +1,569 @@
+//! # response_cache
+//!
+//! response_cache 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Server-side response caching
+//!
+//! `response_cache_middleware` answers `GET` and `HEAD` requests of routes
+//! with a `CachePolicy` from a `ResponseCacheStore`: memory for a single
+//! instance, Redis (`cache-redis` feature) when several instances share the
+//! cache. Entries are keyed by method, path and query plus the request
+//! headers the policy varies on.
+//!
+//! - Fresh entries (younger than `ttl`) are served without calling the handler.
+//! - Within `stale_while_revalidate` after that, the stale entry is served and
+//!   refreshed in the background, once per entry at a time.
+//! - Entries carry tags, e.g. `product:{id}` filled from the path parameters,
+//!   and `ResponseCache::invalidate_tag` drops every entry with a tag when the
+//!   underlying data changes.
+//!
+//! Only successful responses (`200` by default) are stored, never ones
+//! setting cookies or marked `Cache-Control: no-store` / `private`.
+//! Requests with an `Authorization` header bypass the cache unless the
+//! policy varies on it. Responses carry `X-Cache: HIT`, `STALE` or `MISS`.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{response_cache_middleware, CachePolicy, ResponseCache};
+//!
+//! let cache = Arc::new(
+//!     ResponseCache::memory()
+//!         .route("/api/products/{id}", CachePolicy::new(Duration::from_secs(60))
+//!             .stale_while_revalidate(Duration::from_secs(300))
+//!             .vary(&["accept-language"])
+//!             .tag("product:{id}"))
+//!         .route("/api/categories/*", CachePolicy::new(Duration::from_secs(600)).tag("categories")),
+//! );
+//! let router = router.layer(axum::middleware::from_fn_with_state(cache.clone(), response_cache_middleware));
+//!
+//! // After updating product 42
+//! cache.invalidate_tag("product:42").await?;
+//! ```
+
+use async_trait::async_trait;
+use axum::body::{Body, Bytes};
+use axum::extract::{FromRequestParts, MatchedPath, RawPathParams, Request, State};
+use axum::http::header::{AGE, AUTHORIZATION, CACHE_CONTROL, CONNECTION, SET_COOKIE, TRANSFER_ENCODING};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response as AxumResponse;
+use futures_util::StreamExt;
+use http_body_util::BodyExt;
+use rf_crypto::sha256;
+use rf_errors::Result;
+#[cfg(feature = "cache-redis")]
+use rf_errors::RfError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Response header telling whether the cache answered
+pub const X_CACHE: &str = "x-cache";
+
+/// A stored response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// Unix milliseconds when the response was produced
+    pub stored_at: u64,
+    pub tags: Vec<String>,
+}
+
+impl CachedResponse {
+    /// Time since the response was produced
+    pub fn age(&self) -> Duration {
+        Duration::from_millis(now_millis().saturating_sub(self.stored_at))
+    }
+
+    fn into_response(self, cache: &'static str) -> AxumResponse {
+        let age = self.age().as_secs();
+        let mut response = AxumResponse::new(Body::from(self.body));
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        let headers = response.headers_mut();
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+                headers.append(name, value);
+            }
+        }
+        headers.insert(AGE, HeaderValue::from(age));
+        headers.insert(X_CACHE, HeaderValue::from_static(cache));
+        response
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Storage of cached responses
+#[async_trait]
+pub trait ResponseCacheStore: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<CachedResponse>>;
+
+    /// Store `response` for `ttl`, indexed under its tags
+    async fn put(&self, key: &str, response: &CachedResponse, ttl: Duration) -> Result<()>;
+
+    async fn remove(&self, key: &str) -> Result<()>;
+
+    /// Remove every entry stored with `tag`, returning how many there were
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64>;
+}
+
+/// In-process store, for a single instance and tests
+///
+/// Holds at most `capacity` entries; when full, expired entries go first,
+/// then the ones closest to expiry.
+#[derive(Debug)]
+pub struct MemoryResponseCacheStore {
+    capacity: usize,
+    inner: Mutex<MemoryEntries>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryEntries {
+    entries: HashMap<String, (CachedResponse, Instant)>,
+    tags: HashMap<String, HashSet<String>>,
+}
+
+impl MemoryEntries {
+    fn remove(&mut self, key: &str) -> bool {
+        let Some((response, _)) = self.entries.remove(key) else {
+            return false;
+        };
+        for tag in &response.tags {
+            if let Some(keys) = self.tags.get_mut(tag) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.tags.remove(tag);
+                }
+            }
+        }
+        true
+    }
+
+    fn make_room(&mut self, capacity: usize) {
+        let now = Instant::now();
+        let expired: Vec<String> = self.entries.iter().filter(|(_, (_, at))| *at <= now).map(|(key, _)| key.clone()).collect();
+        for key in expired {
+            self.remove(&key);
+        }
+        while self.entries.len() >= capacity {
+            let Some(key) = self.entries.iter().min_by_key(|(_, (_, at))| *at).map(|(key, _)| key.clone()) else {
+                break;
+            };
+            self.remove(&key);
+        }
+    }
+}
+
+impl MemoryResponseCacheStore {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), inner: Mutex::new(MemoryEntries::default()) }
+    }
+
+    /// Entries held, including expired ones not yet evicted
+    pub fn len(&self) -> usize {
+        self.entries().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, MemoryEntries> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for MemoryResponseCacheStore {
+    fn default() -> Self {
+        Self::new(10_000)
+    }
+}
+
+#[async_trait]
+impl ResponseCacheStore for MemoryResponseCacheStore {
+    async fn get(&self, key: &str) -> Result<Option<CachedResponse>> {
+        let mut entries = self.entries();
+        match entries.entries.get(key) {
+            Some((response, at)) if *at > Instant::now() => Ok(Some(response.clone())),
+            Some(_) => {
+                entries.remove(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn put(&self, key: &str, response: &CachedResponse, ttl: Duration) -> Result<()> {
+        let mut entries = self.entries();
+        entries.remove(key);
+        entries.make_room(self.capacity);
+        for tag in &response.tags {
+            entries.tags.entry(tag.clone()).or_default().insert(key.to_string());
+        }
+        entries.entries.insert(key.to_string(), (response.clone(), Instant::now() + ttl));
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.entries().remove(key);
+        Ok(())
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64> {
+        let mut entries = self.entries();
+        let keys = entries.tags.remove(tag).unwrap_or_default();
+        Ok(keys.iter().filter(|key| entries.remove(key)).count() as u64)
+    }
+}
+
+/// How a route is cached
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    ttl: Duration,
+    stale_while_revalidate: Duration,
+    vary: Vec<HeaderName>,
+    tags: Vec<String>,
+    statuses: Vec<u16>,
+}
+
+impl CachePolicy {
+    /// Serve stored responses for `ttl` after they were produced
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            stale_while_revalidate: Duration::ZERO,
+            vary: Vec::new(),
+            tags: Vec::new(),
+            statuses: vec![200],
+        }
+    }
+
+    /// Keep serving an expired response this much longer while it is refreshed in the background
+    pub fn stale_while_revalidate(mut self, window: Duration) -> Self {
+        self.stale_while_revalidate = window;
+        self
+    }
+
+    /// Cache a separate response per value of these request headers
+    pub fn vary(mut self, headers: &[&str]) -> Self {
+        self.vary.extend(headers.iter().filter_map(|name| HeaderName::from_bytes(name.to_ascii_lowercase().as_bytes()).ok()));
+        self
+    }
+
+    /// Tag stored responses; `{name}` is replaced by the path parameter `name`
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Status codes worth storing (default `200`)
+    pub fn statuses(mut self, statuses: &[u16]) -> Self {
+        self.statuses = statuses.to_vec();
+        self
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn tags_for(&self, params: &[(String, String)]) -> Vec<String> {
+        self.tags
+            .iter()
+            .map(|tag| {
+                params
+                    .iter()
+                    .fold(tag.clone(), |tag, (name, value)| tag.replace(&format!("{{{}}}", name), value))
+            })
+            .collect()
+    }
+}
+
+/// Response cache with per-route policies, see the module documentation
+pub struct ResponseCache {
+    store: Arc<dyn ResponseCacheStore>,
+    prefix: String,
+    routes: Vec<(String, CachePolicy)>,
+    max_body_size: usize,
+    refreshing: Arc<Mutex<HashSet<String>>>,
+}
+
+impl ResponseCache {
+    pub fn new(store: impl ResponseCacheStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+            prefix: "rf:response:".to_string(),
+            routes: Vec::new(),
+            max_body_size: 1024 * 1024,
+            refreshing: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Cache in process memory
+    pub fn memory() -> Self {
+        Self::new(MemoryResponseCacheStore::default())
+    }
+
+    /// Prefix of store keys (default `rf:response:`)
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Cache a route
+    ///
+    /// `pattern` is a route template or path; a trailing `*` matches any
+    /// suffix. The first matching route wins.
+    pub fn route(mut self, pattern: impl Into<String>, policy: CachePolicy) -> Self {
+        self.routes.push((pattern.into(), policy));
+        self
+    }
+
+    /// Largest body stored (default 1 MiB); larger responses pass through
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+
+    /// Policy of a route template or path
+    pub fn policy_for(&self, route: &str) -> Option<&CachePolicy> {
+        self.routes
+            .iter()
+            .find(|(pattern, _)| match pattern.strip_suffix('*') {
+                Some(prefix) => route.starts_with(prefix),
+                None => route == pattern,
+            })
+            .map(|(_, policy)| policy)
+    }
+
+    /// Drop every cached response tagged `tag`
+    pub async fn invalidate_tag(&self, tag: &str) -> Result<u64> {
+        self.store.invalidate_tag(&self.tag_key(tag)).await
+    }
+
+    /// Drop the cached `GET` response of `path_and_query` for requests without varied headers
+    pub async fn invalidate_path(&self, path_and_query: &str) -> Result<()> {
+        self.store.remove(&self.key(&Method::GET, path_and_query, &[], &HeaderMap::new())).await
+    }
+
+    fn tag_key(&self, tag: &str) -> String {
+        format!("{}tag:{}", self.prefix, tag)
+    }
+
+    fn key(&self, method: &Method, path_and_query: &str, vary: &[HeaderName], headers: &HeaderMap) -> String {
+        let mut variant = path_and_query.to_string();
+        for name in vary {
+            variant.push('\n');
+            variant.push_str(name.as_str());
+            for value in headers.get_all(name) {
+                variant.push(':');
+                variant.push_str(&String::from_utf8_lossy(value.as_bytes()));
+            }
+        }
+        format!("{}{}:{}", self.prefix, method, sha256::hash(variant.as_bytes()))
+    }
+
+    /// Buffer and store `response` if the policy allows, returning it either way
+    async fn store(&self, key: &str, policy: &CachePolicy, tags: &[String], response: AxumResponse) -> AxumResponse {
+        if !cacheable(policy, &response) {
+            return response;
+        }
+        let (parts, body) = response.into_parts();
+        let (body, rest) = match collect(body, self.max_body_size).await {
+            Ok(collected) => collected,
+            Err(e) => {
+                tracing::warn!("Response body for the cache failed: {}", e);
+                return AxumResponse::from_parts(parts, Body::empty());
+            }
+        };
+        if let Some(rest) = rest {
+            // Too large to cache: send what was read, then the remainder
+            let head = futures_util::stream::once(async move { Ok::<_, axum::Error>(body) });
+            return AxumResponse::from_parts(parts, Body::from_stream(head.chain(rest.into_data_stream())));
+        }
+        let cached = CachedResponse {
+            status: parts.status.as_u16(),
+            headers: parts
+                .headers
+                .iter()
+                .filter(|(name, _)| ![CONNECTION, TRANSFER_ENCODING, AGE].contains(name) && name.as_str() != X_CACHE)
+                .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+                .collect(),
+            body: body.to_vec(),
+            stored_at: now_millis(),
+            tags: tags.iter().map(|tag| self.tag_key(tag)).collect(),
+        };
+        if let Err(e) = self.store.put(key, &cached, policy.ttl + policy.stale_while_revalidate).await {
+            tracing::warn!("Failed to store cached response: {}", e);
+        }
+        AxumResponse::from_parts(parts, Body::from(body))
+    }
+}
+
+fn cacheable(policy: &CachePolicy, response: &AxumResponse) -> bool {
+    let no_store = response
+        .headers()
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| matches!(directive.trim().to_ascii_lowercase().as_str(), "no-store" | "private"));
+    policy.statuses.contains(&response.status().as_u16()) && !no_store && !response.headers().contains_key(SET_COOKIE)
+}
+
+/// Read up to `limit` bytes; past that, also return the unread remainder
+async fn collect(mut body: Body, limit: usize) -> std::result::Result<(Bytes, Option<Body>), axum::Error> {
+    let mut collected = Vec::new();
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame?.into_data() {
+            collected.extend_from_slice(&data);
+            if collected.len() > limit {
+                return Ok((Bytes::from(collected), Some(body)));
+            }
+        }
+    }
+    Ok((Bytes::from(collected), None))
+}
+
+/// Serve and store responses of routes with a cache policy
+///
+/// Use with `axum::middleware::from_fn_with_state(cache, response_cache_middleware)`
+/// as a router layer, so the matched route is known.
+pub async fn response_cache_middleware(State(cache): State<Arc<ResponseCache>>, request: Request, next: Next) -> AxumResponse {
+    if !matches!(*request.method(), Method::GET | Method::HEAD) {
+        return next.run(request).await;
+    }
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| request.uri().path().to_string(), |path| path.as_str().to_string());
+    let Some(policy) = cache.policy_for(&route).cloned() else {
+        return next.run(request).await;
+    };
+    if request.headers().contains_key(AUTHORIZATION) && !policy.vary.contains(&AUTHORIZATION) {
+        return next.run(request).await;
+    }
+
+    let path_and_query = request.uri().path_and_query().map_or("/", |pq| pq.as_str()).to_string();
+    let key = cache.key(request.method(), &path_and_query, &policy.vary, request.headers());
+    let cached = match cache.store.get(&key).await {
+        Ok(cached) => cached,
+        Err(e) => {
+            tracing::warn!("Response cache lookup failed: {}", e);
+            return next.run(request).await;
+        }
+    };
+
+    let (mut parts, body) = request.into_parts();
+    let params: Vec<(String, String)> = RawPathParams::from_request_parts(&mut parts, &())
+        .await
+        .map(|params| params.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect())
+        .unwrap_or_default();
+    let tags = policy.tags_for(&params);
+
+    if let Some(cached) = cached {
+        let age = cached.age();
+        if age < policy.ttl {
+            return cached.into_response("HIT");
+        }
+        if age < policy.ttl + policy.stale_while_revalidate {
+            let first = cache.refreshing.lock().unwrap_or_else(|e| e.into_inner()).insert(key.clone());
+            if first {
+                let request = Request::from_parts(parts, Body::empty());
+                let cache = cache.clone();
+                tokio::spawn(async move {
+                    let response = next.run(request).await;
+                    // Drain the body so the entry is written
+                    let response = cache.store(&key, &policy, &tags, response).await;
+                    let _ = response.into_body().collect().await;
+                    cache.refreshing.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+                });
+            }
+            return cached.into_response("STALE");
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+    let mut response = cache.store(&key, &policy, &tags, response).await;
+    response.headers_mut().insert(X_CACHE, HeaderValue::from_static("MISS"));
+    response
+}
+
+/// Responses in Redis, encoded with MessagePack
+///
+/// Tags are Redis sets of entry keys, expiring with their longest-lived entry.
+#[cfg(feature = "cache-redis")]
+pub struct RedisResponseCacheStore {
+    client: Arc<rf_database::redis::RedisClient>,
+}
+
+#[cfg(feature = "cache-redis")]
+impl RedisResponseCacheStore {
+    pub fn new(client: Arc<rf_database::redis::RedisClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "cache-redis")]
+const REDIS_TAG: &str = r#"
+local ttl = tonumber(ARGV[1])
+for i = 2, #ARGV do
+    redis.call('SADD', ARGV[i], KEYS[1])
+    if redis.call('PTTL', ARGV[i]) < ttl then
+        redis.call('PEXPIRE', ARGV[i], ttl)
+    end
+end
+return 1
+"#;
+
+#[cfg(feature = "cache-redis")]
+const REDIS_INVALIDATE: &str = r#"
+local keys = redis.call('SMEMBERS', KEYS[1])
+local removed = 0
+for _, key in ipairs(keys) do
+    removed = removed + redis.call('DEL', key)
+end
+redis.call('DEL', KEYS[1])
+return removed
+"#;
+
+#[cfg(feature = "cache-redis")]
+#[async_trait]
+impl ResponseCacheStore for RedisResponseCacheStore {
+    async fn get(&self, key: &str) -> Result<Option<CachedResponse>> {
+        match self.client.get_bytes(key).await? {
+            Some(bytes) => rmp_serde::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| RfError::Serialization(format!("Invalid cached response {}: {}", key, e))),
+            None => Ok(None),
+        }
+    }
+
+    async fn put(&self, key: &str, response: &CachedResponse, ttl: Duration) -> Result<()> {
+        let bytes = rmp_serde::to_vec(response).map_err(|e| RfError::Serialization(e.to_string()))?;
+        let ttl = ttl.as_millis().max(1).to_string();
+        self.client.query::<()>(redis::cmd("SET").arg(key).arg(bytes).arg("PX").arg(&ttl)).await?;
+        if !response.tags.is_empty() {
+            let mut args: Vec<&str> = vec![&ttl];
+            args.extend(response.tags.iter().map(String::as_str));
+            self.client.script().eval(REDIS_TAG, &[key], &args).await?;
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.client.generic().del(&[key]).await?;
+        Ok(())
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64> {
+        let removed = self.client.script().eval(REDIS_INVALIDATE, &[tag], &[]).await?;
+        redis::from_redis_value(removed).map_err(|e| RfError::Database(format!("Unexpected Redis reply: {}", e)))
+    }
+}
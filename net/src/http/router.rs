@@ -8,11 +8,289 @@
 //! HTTP router with parameter parsing
 
 use regex::Regex;
+use rf_errors::RfError;
 use std::collections::HashMap;
 use std::sync::Arc;
 use moka::future::Cache;
 use std::time::Duration;
 
+/// One segment of a route pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Static(String),
+    Param(String),
+    Wildcard(String),
+}
+
+/// Parse a route pattern into segments
+///
+/// Accepts both `/users/:id` / `/files/*path` and the axum style
+/// `/users/{id}` / `/files/{*path}`. A bare `*` is named `path`.
+fn parse_pattern(pattern: &str) -> rf_errors::Result<Vec<Segment>> {
+    if !pattern.starts_with('/') {
+        return Err(RfError::InvalidParameter(format!("Route pattern must start with '/': {}", pattern)));
+    }
+    let parts: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let mut segments = Vec::with_capacity(parts.len());
+    let mut names: Vec<String> = Vec::new();
+
+    for (index, part) in parts.iter().enumerate() {
+        let inner = part.strip_prefix('{').and_then(|p| p.strip_suffix('}'));
+        let segment = if let Some(name) = part.strip_prefix('*').or_else(|| inner.and_then(|i| i.strip_prefix('*'))) {
+            if index != parts.len() - 1 {
+                return Err(RfError::InvalidParameter(format!("Wildcard must be the last segment: {}", pattern)));
+            }
+            Segment::Wildcard(if name.is_empty() { "path" } else { name }.to_string())
+        } else if let Some(name) = part.strip_prefix(':').or(inner) {
+            if name.is_empty() {
+                return Err(RfError::InvalidParameter(format!("Empty parameter name: {}", pattern)));
+            }
+            Segment::Param(name.to_string())
+        } else {
+            Segment::Static(part.to_string())
+        };
+
+        if let Segment::Param(name) | Segment::Wildcard(name) = &segment {
+            if names.contains(name) {
+                return Err(RfError::InvalidParameter(format!("Duplicate parameter '{}': {}", name, pattern)));
+            }
+            names.push(name.clone());
+        }
+        segments.push(segment);
+    }
+    Ok(segments)
+}
+
+/// Convert a route pattern to the axum path syntax (`:id` -> `{id}`, `*path` -> `{*path}`)
+pub fn to_axum_path(pattern: &str) -> rf_errors::Result<String> {
+    let segments = parse_pattern(pattern)?;
+    if segments.is_empty() {
+        return Ok("/".to_string());
+    }
+    let mut path = String::new();
+    for segment in segments {
+        path.push('/');
+        match segment {
+            Segment::Static(text) => path.push_str(&text),
+            Segment::Param(name) => path.push_str(&format!("{{{}}}", name)),
+            Segment::Wildcard(name) => path.push_str(&format!("{{*{}}}", name)),
+        }
+    }
+    if pattern.len() > 1 && pattern.ends_with('/') {
+        path.push('/');
+    }
+    Ok(path)
+}
+
+/// Path parameters extracted from a matched route
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathParams {
+    params: Vec<(String, String)>,
+}
+
+impl PathParams {
+    /// Create empty path parameters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a parameter by name
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Iterate over all parameters in pattern order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Number of parameters
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Whether there are no parameters
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Convert into a map
+    pub fn into_map(self) -> HashMap<String, String> {
+        self.params.into_iter().collect()
+    }
+}
+
+impl FromIterator<(String, String)> for PathParams {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self {
+            params: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Route stored in the tree
+#[derive(Debug, Clone)]
+struct Route<T> {
+    pattern: String,
+    value: T,
+}
+
+/// Radix tree node, keyed by path segment
+#[derive(Debug, Clone)]
+struct Node<T> {
+    statics: HashMap<String, Node<T>>,
+    param: Option<(String, Box<Node<T>>)>,
+    wildcard: Option<(String, Route<T>)>,
+    route: Option<Route<T>>,
+}
+
+impl<T> Node<T> {
+    fn new() -> Self {
+        Self {
+            statics: HashMap::new(),
+            param: None,
+            wildcard: None,
+            route: None,
+        }
+    }
+
+    fn find<'a>(&'a self, segments: &[&str], params: &mut Vec<(String, String)>) -> Option<&'a Route<T>> {
+        let (first, rest) = match segments.split_first() {
+            Some(split) => split,
+            None => {
+                return self.route.as_ref().or_else(|| {
+                    self.wildcard.as_ref().map(|(name, route)| {
+                        params.push((name.clone(), String::new()));
+                        route
+                    })
+                });
+            }
+        };
+
+        // Static segments take precedence over parameters, parameters over wildcards
+        if let Some(child) = self.statics.get(*first) {
+            if let Some(route) = child.find(rest, params) {
+                return Some(route);
+            }
+        }
+        if let Some((name, child)) = &self.param {
+            params.push((name.clone(), first.to_string()));
+            if let Some(route) = child.find(rest, params) {
+                return Some(route);
+            }
+            params.pop();
+        }
+        self.wildcard.as_ref().map(|(name, route)| {
+            params.push((name.clone(), segments.join("/")));
+            route
+        })
+    }
+}
+
+/// Matched route
+#[derive(Debug)]
+pub struct RouteMatch<'a, T> {
+    pub value: &'a T,
+    pub pattern: &'a str,
+    pub params: PathParams,
+}
+
+/// Radix tree router
+///
+/// Matches paths against `/users/:id` and `/files/*path` style patterns
+/// per HTTP method. Static segments win over parameters and parameters
+/// over wildcards. Conflicting patterns are rejected at registration:
+/// the same route registered twice, or parameters with different names
+/// at the same position (`/users/:id` vs `/users/:user_id`).
+#[derive(Debug, Clone)]
+pub struct RadixRouter<T> {
+    trees: HashMap<String, Node<T>>,
+}
+
+impl<T> RadixRouter<T> {
+    /// Create an empty router
+    pub fn new() -> Self {
+        Self {
+            trees: HashMap::new(),
+        }
+    }
+
+    /// Register a route for a method
+    pub fn insert(&mut self, method: &str, pattern: &str, value: T) -> rf_errors::Result<()> {
+        let segments = parse_pattern(pattern)?;
+        let conflict = |existing: &str| {
+            RfError::InvalidParameter(format!(
+                "Route conflict: {} {} conflicts with {} {}",
+                method.to_uppercase(), pattern, method.to_uppercase(), existing
+            ))
+        };
+
+        let mut node = self.trees.entry(method.to_uppercase()).or_insert_with(Node::new);
+        for segment in segments {
+            node = match segment {
+                Segment::Static(text) => node.statics.entry(text).or_insert_with(Node::new),
+                Segment::Param(name) => {
+                    if let Some((existing, child)) = &node.param {
+                        if existing != &name {
+                            let sample = child.route.as_ref().map(|r| r.pattern.clone())
+                                .unwrap_or_else(|| format!(":{}", existing));
+                            return Err(conflict(&sample));
+                        }
+                    }
+                    let (_, child) = node.param.get_or_insert_with(|| (name, Box::new(Node::new())));
+                    child
+                }
+                Segment::Wildcard(name) => {
+                    if let Some((_, existing)) = &node.wildcard {
+                        return Err(conflict(&existing.pattern));
+                    }
+                    node.wildcard = Some((name, Route { pattern: pattern.to_string(), value }));
+                    return Ok(());
+                }
+            };
+        }
+
+        if let Some(existing) = &node.route {
+            return Err(conflict(&existing.pattern));
+        }
+        node.route = Some(Route { pattern: pattern.to_string(), value });
+        Ok(())
+    }
+
+    /// Find the route matching a method and path
+    pub fn at(&self, method: &str, path: &str) -> Option<RouteMatch<'_, T>> {
+        let tree = self.trees.get(&method.to_uppercase())?;
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let mut params = Vec::new();
+        tree.find(&segments, &mut params).map(|route| RouteMatch {
+            value: &route.value,
+            pattern: &route.pattern,
+            params: PathParams { params },
+        })
+    }
+
+    /// Whether no routes are registered
+    pub fn is_empty(&self) -> bool {
+        self.trees.is_empty()
+    }
+
+    /// Methods allowed for a path (useful for 405 responses)
+    pub fn allowed_methods(&self, path: &str) -> Vec<String> {
+        let mut methods: Vec<String> = self.trees.keys()
+            .filter(|method| self.at(method, path).is_some())
+            .cloned()
+            .collect();
+        methods.sort();
+        methods
+    }
+}
+
+impl<T> Default for RadixRouter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Route parameter names extracted from route pattern
 #[derive(Debug, Clone)]
 pub struct RouteParams {
@@ -28,61 +306,34 @@ impl RouteParams {
     /// - `/files/*path` -> matches `/files/a/b/c` with param `path=a/b/c`
     pub fn from_pattern(pattern: &str) -> Self {
         let mut names = Vec::new();
-        let mut regex_pattern = String::new();
-        let mut in_param = false;
-        let mut current_param = String::new();
-        
-        for ch in pattern.chars() {
-            match ch {
-                ':' if !in_param => {
-                    in_param = true;
-                    current_param.clear();
-                }
-                '/' if in_param => {
-                    // End of parameter
-                    names.push(current_param.clone());
-                    regex_pattern.push_str(r"([^/]+)");
-                    in_param = false;
-                    regex_pattern.push(ch);
-                }
-                '*' if !in_param => {
-                    // Wildcard parameter
-                    in_param = true;
-                    current_param = "path".to_string();
-                }
-                _ if in_param => {
-                    current_param.push(ch);
-                }
-                _ => {
-                    if in_param && current_param.is_empty() {
-                        // Handle wildcard
-                        names.push("path".to_string());
-                        regex_pattern.push_str(r"(.*)");
-                        in_param = false;
-                    } else if in_param {
-                        names.push(current_param.clone());
-                        regex_pattern.push_str(r"([^/]+)");
-                        in_param = false;
+        let mut regex_pattern = String::from("^");
+        match parse_pattern(pattern) {
+            Ok(segments) => {
+                for segment in &segments {
+                    regex_pattern.push('/');
+                    match segment {
+                        Segment::Static(text) => regex_pattern.push_str(&regex::escape(text)),
+                        Segment::Param(name) => {
+                            names.push(name.clone());
+                            regex_pattern.push_str(r"([^/]+)");
+                        }
+                        Segment::Wildcard(name) => {
+                            names.push(name.clone());
+                            regex_pattern.push_str(r"(.*)");
+                        }
                     }
-                    // Escape special regex characters
-                    if ch == '.' || ch == '+' || ch == '*' || ch == '?' || ch == '^' || ch == '$' || ch == '|' || ch == '(' || ch == ')' || ch == '[' || ch == ']' || ch == '{' || ch == '}' {
-                        regex_pattern.push('\\');
-                    }
-                    regex_pattern.push(ch);
+                }
+                if segments.is_empty() {
+                    regex_pattern.push('/');
                 }
             }
+            Err(_) => regex_pattern.push_str(&regex::escape(pattern)),
         }
-        
-        // Handle trailing parameter
-        if in_param {
-            names.push(current_param);
-            regex_pattern.push_str(r"([^/]+)");
-        }
-        
-        regex_pattern = format!("^{}$", regex_pattern);
+        regex_pattern.push('$');
+
         let pattern = Regex::new(&regex_pattern)
             .unwrap_or_else(|_| Regex::new(&format!("^{}$", regex::escape(pattern))).unwrap());
-        
+
         Self { names, pattern }
     }
     
@@ -107,37 +358,165 @@ impl RouteParams {
     }
 }
 
-/// Type alias for middleware function
-type MiddlewareFn = Arc<dyn Fn(axum::extract::Request, axum::extract::Request) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<axum::response::Response, axum::Error>> + Send>> + Send + Sync>;
+/// Deferred group middleware, applied once all routes are registered
+type GroupLayer = Box<dyn FnOnce(axum::Router) -> axum::Router + Send>;
 
 /// Route group for organizing routes
+///
+/// Routes share a path prefix, middleware and CORS policy. Created through
+/// `HttpServer::group` or nested with `RouteGroup::group`:
+///
+/// ```rust,ignore
+/// let server = HttpServer::new(addr)
+///     .with_cors_config(CorsMiddleware::new().allow_origin("https://app.example.com"))?
+///     .group("/api", |api| {
+///         api.middleware(TimeoutLayer::new(Duration::from_secs(5)))
+///             .route(Method::GET, "/users/:id", get_user)?
+///             .group("/public", |public| {
+///                 public.cors(CorsMiddleware::new())?.route(Method::GET, "/stats", stats)
+///             })
+///     })?;
+/// ```
 pub struct RouteGroup {
     prefix: String,
-    middleware: Vec<MiddlewareFn>,
+    router: axum::Router,
+    routes: RadixRouter<()>,
+    route_list: Vec<(axum::http::Method, String)>,
+    operations: Vec<(axum::http::Method, String, crate::oai::ApiOperation)>,
+    layers: Vec<GroupLayer>,
+    cors: Option<Arc<super::middleware::CorsMiddleware>>,
+    cors_routes: Vec<(String, Arc<super::middleware::CorsMiddleware>)>,
+}
+
+/// Routes collected by a group, ready to merge into the server
+pub(crate) struct GroupRoutes {
+    pub router: axum::Router,
+    pub routes: Vec<(axum::http::Method, String)>,
+    /// OpenAPI descriptions registered with `api_route`
+    pub operations: Vec<(axum::http::Method, String, crate::oai::ApiOperation)>,
+    /// Route patterns with a group CORS policy
+    pub cors: Vec<(String, Arc<super::middleware::CorsMiddleware>)>,
 }
 
 impl RouteGroup {
     /// Create a new route group
     pub fn new(prefix: &str) -> Self {
         Self {
-            prefix: prefix.to_string(),
-            middleware: Vec::new(),
+            prefix: prefix.trim_end_matches('/').to_string(),
+            router: axum::Router::new(),
+            routes: RadixRouter::new(),
+            route_list: Vec::new(),
+            operations: Vec::new(),
+            layers: Vec::new(),
+            cors: None,
+            cors_routes: Vec::new(),
         }
     }
-    
-    /// Add middleware to the group
-    pub fn middleware<F>(mut self, middleware: F) -> Self
+
+    /// Add middleware (a tower layer) to every route of the group, including nested groups
+    ///
+    /// Middleware run in the order they are added, regardless of where routes
+    /// are registered.
+    pub fn middleware<L>(mut self, layer: L) -> Self
     where
-        F: Fn(axum::extract::Request, axum::extract::Request) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<axum::response::Response, axum::Error>> + Send>> + Send + Sync + 'static,
+        L: tower::Layer<axum::routing::Route> + Clone + Send + Sync + 'static,
+        L::Service: tower::Service<axum::extract::Request> + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<axum::extract::Request>>::Response: axum::response::IntoResponse + 'static,
+        <L::Service as tower::Service<axum::extract::Request>>::Error: Into<std::convert::Infallible> + 'static,
+        <L::Service as tower::Service<axum::extract::Request>>::Future: Send + 'static,
     {
-        self.middleware.push(Arc::new(move |req, next| Box::pin(middleware(req, next))));
+        self.layers.push(Box::new(move |router: axum::Router| router.layer(layer)));
         self
     }
-    
+
+    /// Override the server CORS policy for the group and nested groups without their own
+    ///
+    /// Rejects the same policies as `HttpServer::with_cors_config`.
+    pub fn cors(mut self, cors: super::middleware::CorsMiddleware) -> rf_errors::Result<Self> {
+        cors.validate()?;
+        self.cors = Some(Arc::new(cors));
+        Ok(self)
+    }
+
+    /// Register a handler; the pattern is relative to the group prefix
+    pub fn route<H, T>(mut self, method: axum::http::Method, pattern: &str, handler: H) -> rf_errors::Result<Self>
+    where
+        H: axum::handler::Handler<T, ()>,
+        T: 'static,
+    {
+        let filter = axum::routing::MethodFilter::try_from(method.clone())
+            .map_err(|e| RfError::InvalidParameter(format!("Unsupported method {}: {}", method, e)))?;
+        let pattern = self.join(pattern);
+        let path = to_axum_path(&pattern)?;
+        self.routes.insert(method.as_str(), &pattern, ())?;
+        self.router = self.router.route(&path, axum::routing::on(filter, handler));
+        self.route_list.push((method, pattern));
+        Ok(self)
+    }
+
+    /// Register a handler together with its OpenAPI description
+    pub fn api_route<H, T>(self, method: axum::http::Method, pattern: &str, handler: H, operation: crate::oai::ApiOperation) -> rf_errors::Result<Self>
+    where
+        H: axum::handler::Handler<T, ()>,
+        T: 'static,
+    {
+        let full = self.join(pattern);
+        let mut group = self.route(method.clone(), pattern, handler)?;
+        group.operations.push((method, full, operation));
+        Ok(group)
+    }
+
+    /// Add a nested group
+    pub fn group<F>(mut self, prefix: &str, f: F) -> rf_errors::Result<Self>
+    where
+        F: FnOnce(RouteGroup) -> rf_errors::Result<RouteGroup>,
+    {
+        let child = f(RouteGroup::new(&self.join(prefix)))?.into_routes();
+        for (method, pattern) in &child.routes {
+            self.routes.insert(method.as_str(), pattern, ())?;
+        }
+        self.router = self.router.merge(child.router);
+        self.cors_routes.extend(child.cors);
+        self.route_list.extend(child.routes);
+        self.operations.extend(child.operations);
+        Ok(self)
+    }
+
     /// Get the prefix
     pub fn prefix(&self) -> &str {
         &self.prefix
     }
+
+    pub(crate) fn into_routes(self) -> GroupRoutes {
+        let mut router = self.router;
+        for layer in self.layers {
+            router = layer(router);
+        }
+        let mut cors = self.cors_routes;
+        if let Some(policy) = self.cors {
+            // Nested groups with their own policy keep it
+            for (_, pattern) in &self.route_list {
+                if !cors.iter().any(|(p, _)| p == pattern) {
+                    cors.push((pattern.clone(), policy.clone()));
+                }
+            }
+        }
+        GroupRoutes {
+            router,
+            routes: self.route_list,
+            operations: self.operations,
+            cors,
+        }
+    }
+
+    fn join(&self, pattern: &str) -> String {
+        match pattern.trim_end_matches('/') {
+            "" if self.prefix.is_empty() => "/".to_string(),
+            "" => self.prefix.clone(),
+            pattern if pattern.starts_with('/') => format!("{}{}", self.prefix, pattern),
+            pattern => format!("{}/{}", self.prefix, pattern),
+        }
+    }
 }
 
 /// Cached route match result
@@ -338,29 +717,19 @@ pub mod router_helpers {
     }
     
     /// Detect route conflicts between two route patterns
+    ///
+    /// Two routes conflict when they share a method and cannot both be
+    /// registered in a `RadixRouter` (identical routes, or parameters
+    /// with different names at the same position).
     pub fn detect_conflict(pattern1: &str, method1: &str, pattern2: &str, method2: &str) -> bool {
-        if method1 != method2 {
+        if !method1.eq_ignore_ascii_case(method2) {
             return false;
         }
-        
-        // Simple conflict detection: exact match or pattern overlap
         if pattern1 == pattern2 {
             return true;
         }
-        
-        // Check if patterns could match the same paths
-        let params1 = RouteParams::from_pattern(pattern1);
-        let params2 = RouteParams::from_pattern(pattern2);
-        
-        // Test with a few sample paths to detect potential conflicts
-        let test_paths = vec!["/test", "/test/123", "/test/123/456"];
-        for test_path in test_paths {
-            if params1.matches(test_path) && params2.matches(test_path) {
-                return true;
-            }
-        }
-        
-        false
+
+        let mut router = RadixRouter::new();
+        router.insert(method1, pattern1, ()).is_err() || router.insert(method2, pattern2, ()).is_err()
     }
 }
-
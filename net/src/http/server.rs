@@ -7,45 +7,99 @@
 
 //! HTTP server implementation
 
+use super::envelope::{envelope_middleware, EnvelopeConfig};
+use super::session::{session_middleware, SessionMiddleware};
+use super::request_id::{request_id_middleware, RequestIdMiddleware};
+use super::mock::{mock_middleware, MockConfig};
+use super::negotiate::{negotiate_middleware, Negotiator};
+use super::locale::{locale_middleware, LocaleMiddleware};
+use super::middleware::{cors_routes_middleware, CorsMiddleware, CorsRoutes};
+use super::router::RouteGroup;
+use super::plugin::{Plugin, PluginHook, PluginManager, RouteInfo, ServerInfo};
+use super::limits::{self, RequestLimits, RouteLimits, ServerLimits};
+use super::router::{to_axum_path, RadixRouter};
+use super::tls::{TlsAcceptorHandle, TlsConfig, TlsInterceptor, TlsListener};
+use super::websocket::WebSocketHub;
+use axum::extract::DefaultBodyLimit;
+use axum::handler::Handler;
+use axum::serve::ListenerExt;
+use axum::http::uri::Scheme;
+use axum::http::Method;
+use axum::routing::MethodFilter;
 use axum::Router;
-use rf_errors::Result;
+use rf_errors::{Result, RfError};
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
 use tokio::signal;
 use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 use utoipa::openapi::OpenApi;
+use crate::oai::{ApiOperation, OpenApiBuilder};
 use std::sync::Arc;
 
 /// Wrapper trait for service registry to make it object-safe
+#[async_trait::async_trait]
 trait RegistryWrapper: Send + Sync {
-    fn register(&self, instance: &rf_contrib_registry::ServiceInstance) -> rf_errors::Result<()>;
-    fn deregister(&self, service_id: &str) -> rf_errors::Result<()>;
+    async fn start(
+        &self,
+        instance: rf_contrib_registry::ServiceInstance,
+        options: rf_contrib_registry::HeartbeatOptions,
+    ) -> rf_errors::Result<rf_contrib_registry::Registration>;
 }
 
 /// Implementation wrapper for ServiceRegistry
 struct RegistryWrapperImpl<R: rf_contrib_registry::ServiceRegistry>(Arc<R>);
 
-impl<R: rf_contrib_registry::ServiceRegistry> RegistryWrapper for RegistryWrapperImpl<R> {
-    fn register(&self, instance: &rf_contrib_registry::ServiceInstance) -> rf_errors::Result<()> {
-        rf_contrib_registry::ServiceRegistry::register(&*self.0, instance)
-    }
-    
-    fn deregister(&self, service_id: &str) -> rf_errors::Result<()> {
-        rf_contrib_registry::ServiceRegistry::deregister(&*self.0, service_id)
+#[async_trait::async_trait]
+impl<R: rf_contrib_registry::ServiceRegistry + 'static> RegistryWrapper for RegistryWrapperImpl<R> {
+    async fn start(
+        &self,
+        instance: rf_contrib_registry::ServiceInstance,
+        options: rf_contrib_registry::HeartbeatOptions,
+    ) -> rf_errors::Result<rf_contrib_registry::Registration> {
+        rf_contrib_registry::Registration::start(self.0.clone(), instance, options).await
     }
 }
 
 /// HTTP server
 pub struct HttpServer {
     router: Router,
+    routes: RadixRouter<()>,
     addr: SocketAddr,
     shutdown_timeout: Option<std::time::Duration>,
-    max_request_body_size: Option<usize>,
+    // Mutex keeps the server Sync, it is only taken once in `serve`
+    shutdown_signal: Option<std::sync::Mutex<futures_util::future::BoxFuture<'static, ()>>>,
+    limits: ServerLimits,
+    socket: Option<crate::tcp::SocketOptions>,
+    route_limits: RadixRouter<RouteLimits>,
     service_registry: Option<Box<dyn RegistryWrapper>>,
     service_name: Option<String>,
     service_id: Option<String>,
+    heartbeat: rf_contrib_registry::HeartbeatOptions,
     health_check_path: Option<String>,
+    tls: Option<TlsConfig>,
+    envelope: Option<Arc<EnvelopeConfig>>,
+    error_reporting: Option<Arc<rf_os::report::ErrorReporting>>,
+    metrics_path: Option<String>,
+    tracing: Option<Arc<dyn crate::trace::HttpTracing>>,
+    negotiator: Option<Arc<Negotiator>>,
+    session: Option<Arc<SessionMiddleware>>,
+    request_id: Option<Arc<RequestIdMiddleware>>,
+    locale: Option<Arc<LocaleMiddleware>>,
+    mock: Option<MockConfig>,
+    route_table: Vec<RouteInfo>,
+    middleware: Vec<String>,
+    cors: Option<Arc<CorsMiddleware>>,
+    cors_routes: RadixRouter<Arc<CorsMiddleware>>,
+    plugins: PluginManager,
+    websocket_hubs: Vec<WebSocketHub>,
+    openapi: Option<OpenApiBuilder>,
+    api_operations: Vec<(Method, String, ApiOperation)>,
+    banner: Option<rf_os::build::Banner>,
+    #[cfg(feature = "acme")]
+    acme: Option<Arc<super::acme::AcmeManager>>,
+    #[cfg(feature = "fault-injection")]
+    faults: Option<rf_core::fault::FaultInjector>,
 }
 
 impl HttpServer {
@@ -53,17 +107,48 @@ impl HttpServer {
     pub fn new(addr: SocketAddr) -> Self {
         Self {
             router: Router::new(),
+            routes: RadixRouter::new(),
             addr,
             shutdown_timeout: Some(std::time::Duration::from_secs(30)),
-            max_request_body_size: None,
+            shutdown_signal: None,
+            limits: ServerLimits::default(),
+            socket: None,
+            route_limits: RadixRouter::new(),
             service_registry: None,
             service_name: None,
             service_id: None,
+            heartbeat: rf_contrib_registry::HeartbeatOptions::new(),
             health_check_path: Some("/health".to_string()),
+            tls: None,
+            envelope: None,
+            error_reporting: None,
+            metrics_path: None,
+            tracing: None,
+            session: None,
+            request_id: None,
+            locale: None,
+            mock: None,
+            route_table: Vec::new(),
+            middleware: Vec::new(),
+            cors: None,
+            cors_routes: RadixRouter::new(),
+            plugins: PluginManager::new(),
+            websocket_hubs: Vec::new(),
+            openapi: None,
+            api_operations: Vec::new(),
+            banner: None,
+            #[cfg(feature = "acme")]
+            acme: None,
+            #[cfg(feature = "fault-injection")]
+            faults: None,
+            negotiator: None,
         }
     }
 
     /// Set service registry for automatic registration
+    ///
+    /// The instance is registered when the server starts, renewed by
+    /// heartbeats while it runs and deregistered on shutdown.
     pub fn with_registry<R: rf_contrib_registry::ServiceRegistry + Send + Sync + 'static>(mut self, registry: Arc<R>, service_name: String, service_id: String) -> Self {
         self.service_registry = Some(Box::new(RegistryWrapperImpl(registry)));
         self.service_name = Some(service_name);
@@ -71,6 +156,12 @@ impl HttpServer {
         self
     }
 
+    /// Set how the registered instance is renewed, see `HeartbeatOptions`
+    pub fn with_heartbeat(mut self, options: rf_contrib_registry::HeartbeatOptions) -> Self {
+        self.heartbeat = options;
+        self
+    }
+
     /// Set health check path
     pub fn health_check_path(mut self, path: String) -> Self {
         self.health_check_path = Some(path);
@@ -83,8 +174,67 @@ impl HttpServer {
     }
 
     /// Set maximum request body size in bytes
+    ///
+    /// Larger requests are answered with `413 Payload Too Large`.
     pub fn max_request_body_size(mut self, size: usize) -> Self {
-        self.max_request_body_size = Some(size);
+        self.limits.max_body_size = Some(size);
+        self
+    }
+
+    /// Set request size limits, timeouts and connection limits
+    pub fn with_limits(mut self, limits: ServerLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set listener and connection socket options (TCP_NODELAY, keepalive, SO_REUSEPORT, buffers)
+    ///
+    /// With `reuse_port`, several processes can serve the same address.
+    pub fn with_socket_options(mut self, options: crate::tcp::SocketOptions) -> Self {
+        self.socket = Some(options);
+        self
+    }
+
+    /// Override body limits for a route pattern (all methods)
+    ///
+    /// Patterns use the same syntax as `route`, e.g. `/upload/*path`.
+    pub fn route_limits(mut self, pattern: &str, limits: RouteLimits) -> Result<Self> {
+        self.route_limits.insert(RequestLimits::ANY_METHOD, pattern, limits)?;
+        Ok(self)
+    }
+
+    /// Serve HTTPS with a PEM certificate chain and private key
+    ///
+    /// The certificate is reloaded when the files change.
+    pub fn with_tls(self, cert_path: impl Into<std::path::PathBuf>, key_path: impl Into<std::path::PathBuf>) -> Self {
+        self.with_tls_config(TlsConfig::new(cert_path, key_path))
+    }
+
+    /// Serve HTTPS with a custom TLS configuration
+    pub fn with_tls_config(mut self, config: TlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    /// Serve HTTPS with certificates issued and renewed through ACME
+    ///
+    /// HTTP-01 challenges are answered on this server's router as well as
+    /// on `AcmeConfig::http_challenge_addr` if set; TLS-ALPN-01 challenges
+    /// are answered on the TLS listener.
+    #[cfg(feature = "acme")]
+    pub fn with_acme(mut self, manager: Arc<super::acme::AcmeManager>) -> Self {
+        self.acme = Some(manager);
+        self
+    }
+
+    /// Inject latency, errors and connection resets into requests
+    ///
+    /// Rules match the request path; see `rf_core::fault`. Applied when the
+    /// server starts, inside the envelope so injected errors are wrapped like
+    /// real ones.
+    #[cfg(feature = "fault-injection")]
+    pub fn with_faults(mut self, faults: rf_core::fault::FaultInjector) -> Self {
+        self.faults = Some(faults);
         self
     }
 
@@ -93,9 +243,88 @@ impl HttpServer {
         &mut self.router
     }
 
+    /// Register a handler for a method and route pattern
+    ///
+    /// Patterns use `/users/:id` and `/files/*path` syntax (the axum
+    /// `{id}` / `{*path}` syntax is accepted too). Parameters are available
+    /// through `Request::param`. Conflicting routes are rejected here
+    /// instead of panicking when the server starts.
+    pub fn route<H, T>(mut self, method: Method, pattern: &str, handler: H) -> Result<Self>
+    where
+        H: Handler<T, ()>,
+        T: 'static,
+    {
+        let filter = MethodFilter::try_from(method.clone())
+            .map_err(|e| RfError::InvalidParameter(format!("Unsupported method {}: {}", method, e)))?;
+        let path = to_axum_path(pattern)?;
+        self.routes.insert(method.as_str(), pattern, ())?;
+        self.router = self.router.route(&path, axum::routing::on(filter, handler));
+        self.route_table.push(RouteInfo {
+            method: method.to_string(),
+            path: pattern.to_string(),
+        });
+        Ok(self)
+    }
+
+    /// Register a handler together with its OpenAPI description
+    ///
+    /// The operation is added to the document served by `with_openapi`.
+    ///
+    /// ```rust,ignore
+    /// let server = HttpServer::new(addr)
+    ///     .with_openapi(OpenApiBuilder::new("Shop", "1.0.0"))
+    ///     .api_route(Method::GET, "/orders/:id", get_order, ApiOperation::new()
+    ///         .summary("Get an order")
+    ///         .path_param::<u64>("id", "Order ID")
+    ///         .response::<Order>(200, "The order"))?;
+    /// ```
+    pub fn api_route<H, T>(self, method: Method, pattern: &str, handler: H, operation: ApiOperation) -> Result<Self>
+    where
+        H: Handler<T, ()>,
+        T: 'static,
+    {
+        let mut server = self.route(method.clone(), pattern, handler)?;
+        server.api_operations.push((method, pattern.to_string(), operation));
+        Ok(server)
+    }
+
+    /// Register a group of routes sharing a prefix, middleware and CORS policy
+    ///
+    /// ```rust,ignore
+    /// let server = HttpServer::new(addr).group("/api/v1", |api| {
+    ///     api.cors(CorsMiddleware::new().allow_origin_regex(r"^https://.*\.example\.com$")?)?
+    ///         .route(Method::GET, "/users/:id", get_user)?
+    ///         .route(Method::POST, "/users", create_user)
+    /// })?;
+    /// ```
+    pub fn group<F>(mut self, prefix: &str, f: F) -> Result<Self>
+    where
+        F: FnOnce(RouteGroup) -> Result<RouteGroup>,
+    {
+        let group = f(RouteGroup::new(prefix))?.into_routes();
+        for (method, pattern) in &group.routes {
+            self.routes.insert(method.as_str(), pattern, ())?;
+        }
+        for (pattern, cors) in group.cors {
+            // Routes registered for several methods share one policy
+            if self.cors_routes.at(CorsRoutes::ANY_METHOD, &pattern).is_some_and(|m| m.pattern == pattern) {
+                continue;
+            }
+            self.cors_routes.insert(CorsRoutes::ANY_METHOD, &pattern, cors)?;
+        }
+        self.router = self.router.merge(group.router);
+        self.api_operations.extend(group.operations);
+        self.route_table.extend(group.routes.into_iter().map(|(method, path)| RouteInfo {
+            method: method.to_string(),
+            path,
+        }));
+        Ok(self)
+    }
+
     /// Add logging middleware using tower-http
     pub fn with_logging(mut self) -> Self {
         self.router = self.router.layer(TraceLayer::new_for_http());
+        self.middleware.push("logging".to_string());
         self
     }
 
@@ -107,20 +336,156 @@ impl HttpServer {
                 .allow_methods(tower_http::cors::Any)
                 .allow_headers(tower_http::cors::Any),
         );
+        self.middleware.push("cors".to_string());
         self
     }
 
+    /// Add a configurable CORS policy for all routes
+    ///
+    /// Route groups can override it with `RouteGroup::cors`. Applied when the
+    /// server starts, so it covers routes added later too.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RfError::Config` when the policy allows credentials for any
+    /// origin, see `CorsMiddleware::validate`
+    pub fn with_cors_config(mut self, cors: CorsMiddleware) -> Result<Self> {
+        cors.validate()?;
+        self.cors = Some(Arc::new(cors));
+        Ok(self)
+    }
+
     /// Add response compression
     pub fn with_compression(mut self) -> Self {
         use tower_http::compression::CompressionLayer;
         self.router = self.router.layer(CompressionLayer::new());
+        self.middleware.push("compression".to_string());
         self
     }
 
     /// Add request timeout
+    ///
+    /// Handlers also run inside an `rf_core::ctx` deadline, so database
+    /// queries they issue stop when the request budget runs out.
     pub fn with_request_timeout(mut self, timeout: std::time::Duration) -> Self {
         use tower_http::timeout::TimeoutLayer;
-        self.router = self.router.layer(TimeoutLayer::with_status_code(axum::http::StatusCode::REQUEST_TIMEOUT, timeout));
+        self.router = self.router
+            .layer(axum::middleware::from_fn(move |request: axum::extract::Request, next: axum::middleware::Next| {
+                rf_core::ctx::with_timeout(timeout, next.run(request))
+            }))
+            .layer(TimeoutLayer::with_status_code(axum::http::StatusCode::REQUEST_TIMEOUT, timeout));
+        self.middleware.push(format!("timeout({:?})", timeout));
+        self
+    }
+
+    /// Answer every route with the `{code, message, data}` envelope
+    ///
+    /// Applied when the server starts, so it covers routes added later too.
+    pub fn with_envelope(mut self, config: EnvelopeConfig) -> Self {
+        self.envelope = Some(Arc::new(config));
+        self
+    }
+
+    /// Report 5xx responses and panicking handlers to `reporting`
+    ///
+    /// Without this call the global reporting of `rf_os::report` is used, if
+    /// one is installed when the server starts. Applied outside the envelope,
+    /// so reports carry the final status; see `crate::report`.
+    pub fn with_error_reporting(mut self, reporting: rf_os::report::ErrorReporting) -> Self {
+        self.error_reporting = Some(Arc::new(reporting));
+        self
+    }
+
+    /// Serve Prometheus metrics at `path` and record HTTP latency
+    ///
+    /// Registers the process, tokio runtime and global buffer pool collectors
+    /// on the default registry when the server starts. Latency is measured outside every
+    /// other middleware but plugins; see `super::prometheus`.
+    pub fn with_metrics(mut self, path: &str) -> Self {
+        self.metrics_path = Some(path.to_string());
+        self
+    }
+
+    /// Wrap every request in an OpenTelemetry server span
+    ///
+    /// `rf_contrib_trace::TraceMiddleware` implements `HttpTracing`. Without
+    /// this call the server uses `crate::trace::http_tracing` when it starts,
+    /// which an `rf_contrib_trace::init_tracing_*` pipeline sets. The span is
+    /// outermost but for plugins, so request IDs carry its trace ID and
+    /// database and Redis calls of handlers become its children.
+    pub fn with_tracing(mut self, tracing: impl crate::trace::HttpTracing + 'static) -> Self {
+        self.tracing = Some(Arc::new(tracing));
+        self
+    }
+
+    /// Render `Negotiated` responses in the representation the `Accept` header asks for
+    ///
+    /// Applied when the server starts, inside the envelope, so JSON is still
+    /// wrapped while XML, MessagePack and CSV answers are sent as rendered.
+    pub fn with_negotiation(mut self, negotiator: Negotiator) -> Self {
+        self.negotiator = Some(Arc::new(negotiator));
+        self
+    }
+
+    /// Load and save a cookie-based session around every request
+    ///
+    /// Handlers get it with the `SessionHandle` extractor or `Request::session`.
+    pub fn with_session(mut self, sessions: SessionMiddleware) -> Self {
+        self.session = Some(Arc::new(sessions));
+        self
+    }
+
+    /// Assign request IDs and write an access log line per request
+    ///
+    /// Applied when the server starts, outside the envelope and session
+    /// middleware, so error envelopes carry the ID too.
+    pub fn with_request_id(mut self, config: RequestIdMiddleware) -> Self {
+        self.request_id = Some(Arc::new(config));
+        self
+    }
+
+    /// Negotiate the language and timezone of every request
+    ///
+    /// Applied when the server starts, outside the envelope and negotiation
+    /// middleware, so `rf_os::time::Time` values render in the requester's
+    /// timezone in every representation.
+    pub fn with_locale(mut self, config: LocaleMiddleware) -> Self {
+        self.locale = Some(Arc::new(config));
+        self
+    }
+
+    /// Answer routes with example payloads for frontend development
+    ///
+    /// Examples come from fixed route bodies, mock files or the responses
+    /// documented with `api_route`; see `MockConfig`. Without this call mock
+    /// mode still turns on when `RF_MOCK` is set. A disabled config keeps it
+    /// off regardless of the environment.
+    pub fn with_mock(mut self, config: MockConfig) -> Self {
+        self.mock = Some(config);
+        self
+    }
+
+    /// Print a startup banner once the server is listening
+    ///
+    /// The banner template can use `{addr}` and `{scheme}` besides the build
+    /// information placeholders.
+    pub fn with_banner(mut self, banner: rf_os::build::Banner) -> Self {
+        self.banner = Some(banner);
+        self
+    }
+
+    /// Register a plugin, applied when the server starts
+    pub fn with_plugin(mut self, plugin: impl Plugin + 'static) -> Result<Self> {
+        self.plugins.register(Box::new(plugin))?;
+        Ok(self)
+    }
+
+    /// Close the hub's WebSocket connections when the server shuts down
+    ///
+    /// Clients get a 1001 Going Away close frame as soon as the shutdown
+    /// signal arrives instead of holding the graceful drain open.
+    pub fn websocket_hub(mut self, hub: WebSocketHub) -> Self {
+        self.websocket_hubs.push(hub);
         self
     }
 
@@ -131,15 +496,53 @@ impl HttpServer {
         self
     }
 
+    /// Serve an OpenAPI 3.1 document generated from `api_route` registrations
+    ///
+    /// Served at `builder.path()` (`/openapi.json` by default) when the server
+    /// starts, so it covers routes added later too. Operations added to the
+    /// builder directly are kept.
+    pub fn with_openapi(mut self, builder: OpenApiBuilder) -> Self {
+        self.openapi = Some(builder);
+        self
+    }
+
+    /// The OpenAPI document for the routes registered so far
+    ///
+    /// `None` unless `with_openapi` was called.
+    pub fn openapi_document(&self) -> Option<serde_json::Value> {
+        self.openapi_builder().map(|builder| builder.to_json())
+    }
+
+    fn openapi_builder(&self) -> Option<OpenApiBuilder> {
+        Some(self.with_operations(self.openapi.clone()?))
+    }
+
+    fn with_operations(&self, mut builder: OpenApiBuilder) -> OpenApiBuilder {
+        for (method, pattern, operation) in &self.api_operations {
+            builder.add_operation(method.clone(), pattern, operation.clone());
+        }
+        builder
+    }
+
     /// Set shutdown timeout
     pub fn shutdown_timeout(mut self, timeout: std::time::Duration) -> Self {
         self.shutdown_timeout = Some(timeout);
         self
     }
 
+    /// Shut down when `signal` completes instead of on Ctrl+C / SIGTERM
+    ///
+    /// For applications whose signal handling lives elsewhere, such as the
+    /// shutdown coordinator of `rf-frame`.
+    pub fn with_shutdown_signal(mut self, signal: impl std::future::Future<Output = ()> + Send + 'static) -> Self {
+        self.shutdown_signal = Some(std::sync::Mutex::new(Box::pin(signal)));
+        self
+    }
+
     /// Start the server with graceful shutdown
     pub async fn serve(mut self) -> Result<()> {
-        // Register with service registry if configured
+        // Register with service registry if configured, heartbeats keep the instance alive
+        let mut registration = None;
         if let (Some(registry), Some(ref service_name), Some(ref service_id)) = 
             (self.service_registry.as_ref(), self.service_name.as_ref(), self.service_id.as_ref()) {
             let instance = rf_contrib_registry::ServiceInstance {
@@ -149,7 +552,7 @@ impl HttpServer {
                 metadata: std::collections::HashMap::new(),
                 health: rf_contrib_registry::ServiceHealth::Healthy,
             };
-            registry.register(&instance)?;
+            registration = Some(registry.start(instance, self.heartbeat.clone()).await?);
             tracing::info!("Registered service {} ({}) with registry", service_name, service_id);
         }
 
@@ -161,14 +564,64 @@ impl HttpServer {
             }));
         }
 
-        let listener = TcpListener::bind(&self.addr).await
-            .map_err(|e| rf_errors::RfError::Network(format!("Failed to bind: {}", e)))?;
+        if let Some(path) = &self.metrics_path {
+            let registry = rf_os::metric::default_registry();
+            rf_os::metric::register_process_metrics(registry);
+            rf_os::metric::register_runtime_metrics(registry, tokio::runtime::Handle::current());
+            crate::buffer::register_buffer_pool_metrics(registry, "global", crate::buffer::BufferPool::global());
+            self.router = self.router.route(path, axum::routing::get(super::prometheus::metrics_handler));
+        }
+
+        if let Some(builder) = self.openapi_builder() {
+            use super::swagger::create_openapi_router;
+            let document = create_openapi_router(builder.to_json(), builder.path(), builder.swagger_ui_path());
+            self.router = self.router.merge(document);
+        }
+
+        // Load the certificate before binding so configuration errors surface early
+        let tls = match self.tls.take() {
+            Some(config) => {
+                let acceptor = Arc::new(TlsAcceptorHandle::from_pem_files(&config.cert_path, &config.key_path)?);
+                let watcher = if config.watch { Some(acceptor.watch()?) } else { None };
+                Some((acceptor, watcher, config.handshake_timeout, None::<Arc<dyn TlsInterceptor>>))
+            }
+            None => None,
+        };
+
+        #[cfg(feature = "acme")]
+        let (tls, _acme) = match self.acme.take() {
+            Some(manager) => {
+                let challenges = manager.challenges();
+                self.router = self.router.merge(challenges.clone().router());
+                let handle = manager.start().await?;
+                let interceptor: Arc<dyn TlsInterceptor> = challenges;
+                (Some((handle.acceptor(), None, std::time::Duration::from_secs(10), Some(interceptor))), Some(handle))
+            }
+            None => (tls, None),
+        };
+
+        let socket = self.socket.take();
+        let listener = match &socket {
+            Some(options) => options.bind(self.addr)?,
+            None => TcpListener::bind(&self.addr).await
+                .map_err(|e| rf_errors::RfError::Network(format!("Failed to bind: {}", e)))?,
+        };
         
-        tracing::info!("Server listening on {}", self.addr);
+        tracing::info!("Server listening on {}{}", self.addr, if tls.is_some() { " (https)" } else { "" });
+        if let Some(banner) = self.banner.take() {
+            let addr = listener.local_addr().unwrap_or(self.addr);
+            banner
+                .var("addr", addr.to_string())
+                .var("scheme", if tls.is_some() { "https" } else { "http" })
+                .print();
+        }
         
         // Create shutdown signal
-        let registry_opt = self.service_registry.take();
-        let service_id_clone = self.service_id.clone();
+        let websocket_hubs = std::mem::take(&mut self.websocket_hubs);
+        let shutdown_signal = self
+            .shutdown_signal
+            .take()
+            .map(|signal| signal.into_inner().unwrap_or_else(|e| e.into_inner()));
         let shutdown = async move {
             let ctrl_c = async {
                 signal::ctrl_c()
@@ -196,16 +649,23 @@ impl HttpServer {
             #[cfg(not(unix))]
             let terminate = std::future::pending::<()>();
             
-            tokio::select! {
-                _ = ctrl_c => {},
-                _ = terminate => {},
+            match shutdown_signal {
+                Some(signal) => signal.await,
+                None => tokio::select! {
+                    _ = ctrl_c => {},
+                    _ = terminate => {},
+                },
             }
             
             tracing::info!("Shutdown signal received");
+            for hub in &websocket_hubs {
+                hub.shutdown();
+            }
             
-            // Deregister from service registry
-            if let (Some(registry), Some(ref service_id)) = (registry_opt.as_ref(), service_id_clone.as_ref()) {
-                if let Err(e) = registry.deregister(service_id) {
+            // Stop the heartbeats and deregister from service registry
+            if let Some(registration) = registration {
+                let service_id = registration.instance().id.clone();
+                if let Err(e) = registration.deregister().await {
                     tracing::warn!("Failed to deregister service: {}", e);
                 } else {
                     tracing::info!("Deregistered service {} from registry", service_id);
@@ -213,27 +673,132 @@ impl HttpServer {
             }
         };
         
-        // Apply request body size limit if configured
-        let router = if let Some(max_size) = self.max_request_body_size {
-            self.router.layer(tower_http::limit::RequestBodyLimitLayer::new(max_size))
-        } else {
-            self.router
+        let mut router = self.take_limited_router();
+        self.middleware.push("limits".to_string());
+        // Inside the envelope so mocked responses get wrapped like real ones
+        let mock = self.mock.take().unwrap_or_else(MockConfig::from_env);
+        if mock.is_enabled() {
+            let base = self.openapi.clone().unwrap_or_else(|| OpenApiBuilder::new("mock", "0.0.0"));
+            let document = self.with_operations(base).to_json();
+            let state = Arc::new(mock.build(&self.route_table, &document)?);
+            router = router.layer(axum::middleware::from_fn_with_state(state, mock_middleware));
+            self.middleware.push("mock".to_string());
+            tracing::warn!("Mock mode is on: routes answer with example payloads");
+        }
+        #[cfg(feature = "fault-injection")]
+        if let Some(faults) = self.faults.take() {
+            router = router.layer(axum::middleware::from_fn_with_state(faults, super::fault::fault_middleware));
+            self.middleware.push("faults".to_string());
+            tracing::warn!("Fault injection is on");
+        }
+        if let Some(negotiator) = self.negotiator.take() {
+            router = router.layer(axum::middleware::from_fn_with_state(negotiator, negotiate_middleware));
+            self.middleware.push("negotiate".to_string());
+        }
+        if let Some(config) = self.envelope.take() {
+            router = router.layer(axum::middleware::from_fn_with_state(config, envelope_middleware));
+            self.middleware.push("envelope".to_string());
+        }
+        if let Some(reporting) = self.error_reporting.take().or_else(rf_os::report::global) {
+            router = router.layer(axum::middleware::from_fn_with_state(reporting, crate::report::error_report_middleware));
+            self.middleware.push("error_report".to_string());
+        }
+        // Outside the envelope, which rebuilds error responses and would drop the cookie
+        if let Some(sessions) = self.session.take() {
+            router = router.layer(axum::middleware::from_fn_with_state(sessions, session_middleware));
+            self.middleware.push("session".to_string());
+        }
+        if self.cors.is_some() || !self.cors_routes.is_empty() {
+            let cors = Arc::new(CorsRoutes {
+                default: self.cors.take(),
+                routes: std::mem::take(&mut self.cors_routes),
+            });
+            router = router.layer(axum::middleware::from_fn(move |request, next| {
+                cors_routes_middleware(cors.clone(), request, next)
+            }));
+            self.middleware.push("cors".to_string());
+        }
+        if let Some(config) = self.locale.take() {
+            router = router.layer(axum::middleware::from_fn_with_state(config, locale_middleware));
+            self.middleware.push("locale".to_string());
+        }
+        if let Some(config) = self.request_id.take() {
+            router = router.layer(axum::middleware::from_fn_with_state(config, request_id_middleware));
+            self.middleware.push("request_id".to_string());
+        }
+        if self.metrics_path.is_some() {
+            router = router.layer(axum::middleware::from_fn(super::prometheus::http_metrics_middleware));
+            self.middleware.push("metrics".to_string());
+        }
+        if let Some(tracing) = self.tracing.take().or_else(crate::trace::http_tracing) {
+            router = router.layer(axum::middleware::from_fn_with_state(tracing, crate::trace::http_tracing_middleware));
+            self.middleware.push("tracing".to_string());
+        }
+
+        // Plugins go outermost so they see final responses
+        let info = ServerInfo {
+            addr: self.addr,
+            tls: tls.is_some(),
+            routes: std::mem::take(&mut self.route_table),
+            middleware: std::mem::take(&mut self.middleware),
+            plugins: self.plugins.list(),
         };
+        self.plugins.attach_all(&info);
+        router = self.plugins.apply_all(router);
+        self.plugins.call_hook(PluginHook::BeforeStart)?;
+
+        // Listener scheme for handlers such as the reverse proxy's X-Forwarded-Proto
+        let scheme = if tls.is_some() { Scheme::HTTPS } else { Scheme::HTTP };
+        router = router.layer(axum::Extension(scheme));
         
         // Start server with graceful shutdown
-        let server = axum::serve(listener, router)
-            .with_graceful_shutdown(shutdown);
-        
-        if let Some(timeout) = self.shutdown_timeout {
-            tokio::time::timeout(timeout, server)
-                .await
-                .map_err(|_| rf_errors::RfError::Network("Server shutdown timeout".to_string()))?
-                .map_err(|e| rf_errors::RfError::Network(format!("Server error: {}", e)))?;
-        } else {
-            server.await
-                .map_err(|e| rf_errors::RfError::Network(format!("Server error: {}", e)))?;
+        let result = match tls {
+            Some((acceptor, _watcher, handshake_timeout, interceptor)) => {
+                let listener = match interceptor {
+                    Some(interceptor) => TlsListener::with_interceptor(listener, acceptor, handshake_timeout, interceptor)?,
+                    None => TlsListener::new(listener, acceptor, handshake_timeout)?,
+                };
+                limits::serve(listener, router, &self.limits, shutdown, self.shutdown_timeout).await
+            }
+            // TLS connections inherit the options from the listener
+            None => match socket {
+                Some(options) => {
+                    let listener = listener.tap_io(move |stream| {
+                        if let Err(e) = options.apply(stream) {
+                            tracing::debug!("{}", e);
+                        }
+                    });
+                    limits::serve(listener, router, &self.limits, shutdown, self.shutdown_timeout).await
+                }
+                None => limits::serve(listener, router, &self.limits, shutdown, self.shutdown_timeout).await,
+            },
+        };
+        if let Err(e) = self.plugins.call_hook(PluginHook::AfterStop) {
+            tracing::warn!("Plugin stop hook failed: {}", e);
         }
-        
-        Ok(())
+        // Write out lines still queued in async log writers before the process exits
+        rf_os::log::flush();
+        result
+    }
+
+    /// Router with header and body limits applied
+    fn take_limited_router(&mut self) -> Router {
+        let mut router = std::mem::take(&mut self.router);
+        let request_limits = RequestLimits {
+            max_header_size: self.limits.max_header_size,
+            server: RouteLimits {
+                max_body_size: self.limits.max_body_size,
+                body_read_timeout: self.limits.body_read_timeout,
+            },
+            routes: std::mem::take(&mut self.route_limits),
+        };
+        if request_limits.server.max_body_size.is_some() || !request_limits.routes.is_empty() {
+            // The configured limits replace axum's default 2 MiB extractor limit
+            router = router.layer(DefaultBodyLimit::disable());
+        }
+        let request_limits = Arc::new(request_limits);
+        router.layer(axum::middleware::from_fn(move |request, next| {
+            limits::limits_middleware(request_limits.clone(), request, next)
+        }))
     }
 }
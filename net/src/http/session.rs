@@ -0,0 +1,372 @@
+//! # session
+//!
+//! session 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! HTTP sessions
+//!
+//! Binds `rf_os::session::SessionManager` to requests: the session cookie
+//! is read, the session loaded from the configured storage and exposed to
+//! handlers as a `SessionHandle` (extractor or `Request::session`), then
+//! saved after the handler when it changed. New sessions get a cookie only
+//! once something is stored in them, so anonymous requests create nothing.
+//!
+//! ```rust,ignore
+//! use rf_net::http::{HttpServer, HttpSessionConfig, SessionHandle, SessionMiddleware};
+//!
+//! // session: { ttl: 86400, storage: { type: redis, url: "redis://..." }, cookie: { secure: true } }
+//! let config: HttpSessionConfig = cfg.get("session")?;
+//! let server = HttpServer::new(addr)
+//!     .with_session(SessionMiddleware::from_config(&config).await?)
+//!     .route(Method::POST, "/login", |session: SessionHandle| async move {
+//!         session.regenerate();
+//!         session.insert("user_id", 42)?;
+//!         Ok::<_, ApiError>("ok")
+//!     })?;
+//! ```
+
+use super::envelope::ApiError;
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::header::{COOKIE, SET_COOKIE};
+use axum::http::request::Parts;
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response as AxumResponse;
+use rf_errors::{Result, RfError};
+use rf_os::session::{Session, SessionConfig, SessionManager};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// `SameSite` cookie attribute
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SameSite {
+    Strict,
+    #[default]
+    Lax,
+    /// Requires `Secure`, which is then always set
+    None,
+}
+
+impl SameSite {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Strict => "Strict",
+            Self::Lax => "Lax",
+            Self::None => "None",
+        }
+    }
+}
+
+/// Session cookie attributes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionCookie {
+    /// Cookie name (default `RFSESSID`)
+    pub name: String,
+    pub path: String,
+    pub domain: Option<String>,
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: SameSite,
+    /// Send `Max-Age` from the session TTL; otherwise the cookie ends with the browser session
+    pub persistent: bool,
+}
+
+impl Default for SessionCookie {
+    fn default() -> Self {
+        Self {
+            name: "RFSESSID".to_string(),
+            path: "/".to_string(),
+            domain: None,
+            secure: false,
+            http_only: true,
+            same_site: SameSite::Lax,
+            persistent: true,
+        }
+    }
+}
+
+impl SessionCookie {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Only send the cookie over HTTPS
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Hide the cookie from JavaScript (default true)
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    /// Persist the cookie for the session TTL (default true)
+    pub fn persistent(mut self, persistent: bool) -> Self {
+        self.persistent = persistent;
+        self
+    }
+
+    /// `Set-Cookie` value; `max_age` of `Some(0)` removes the cookie
+    fn header(&self, value: &str, max_age: Option<u64>) -> Option<HeaderValue> {
+        let mut cookie = format!("{}={}; Path={}", self.name, value, self.path);
+        if let Some(domain) = &self.domain {
+            cookie.push_str(&format!("; Domain={}", domain));
+        }
+        if let Some(max_age) = max_age {
+            cookie.push_str(&format!("; Max-Age={}", max_age));
+        }
+        if self.http_only {
+            cookie.push_str("; HttpOnly");
+        }
+        if self.secure || self.same_site == SameSite::None {
+            cookie.push_str("; Secure");
+        }
+        cookie.push_str(&format!("; SameSite={}", self.same_site.as_str()));
+        HeaderValue::from_str(&cookie).ok()
+    }
+}
+
+/// Session storage and cookie settings, e.g. the `session` section of the config file
+///
+/// ```yaml
+/// session:
+///   ttl: 86400
+///   sliding: true
+///   storage:
+///     type: file            # memory | redis | file | database
+///     path: /var/lib/app/sessions
+///   cookie:
+///     name: SID
+///     secure: true
+///     same_site: Strict
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpSessionConfig {
+    #[serde(flatten)]
+    pub session: SessionConfig,
+    pub cookie: SessionCookie,
+}
+
+/// Session middleware settings
+pub struct SessionMiddleware {
+    manager: Arc<SessionManager>,
+    cookie: SessionCookie,
+}
+
+impl SessionMiddleware {
+    /// Use sessions from `manager` with the default cookie
+    pub fn new(manager: Arc<SessionManager>) -> Self {
+        Self {
+            manager,
+            cookie: SessionCookie::default(),
+        }
+    }
+
+    /// Build the storage and cookie from configuration
+    pub async fn from_config(config: &HttpSessionConfig) -> Result<Self> {
+        let manager = SessionManager::from_config(&config.session).await?;
+        Ok(Self::new(Arc::new(manager)).cookie(config.cookie.clone()))
+    }
+
+    pub fn cookie(mut self, cookie: SessionCookie) -> Self {
+        self.cookie = cookie;
+        self
+    }
+
+    pub fn manager(&self) -> &Arc<SessionManager> {
+        &self.manager
+    }
+
+    /// Session cookie value sent by the client
+    fn cookie_value<'a>(&self, request: &'a Request) -> Option<&'a str> {
+        request
+            .headers()
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(name, _)| *name == self.cookie.name)
+            .map(|(_, value)| value)
+    }
+
+    async fn load(&self, cookie_value: Option<&str>) -> Option<Session> {
+        let id = Session::id_from_cookie_value(cookie_value?).ok()?;
+        match self.manager.get(&id).await {
+            Ok(session) => session,
+            Err(e) => {
+                tracing::warn!("Failed to load session: {}", e);
+                None
+            }
+        }
+    }
+
+    fn max_age(&self) -> Option<u64> {
+        self.manager.ttl().filter(|_| self.cookie.persistent).map(|ttl| ttl.as_secs())
+    }
+}
+
+/// The session of the current request
+///
+/// Clones share the session; changes are saved after the handler returns.
+#[derive(Debug, Clone)]
+pub struct SessionHandle {
+    session: Arc<Mutex<Session>>,
+}
+
+impl SessionHandle {
+    fn new(session: Session) -> Self {
+        Self {
+            session: Arc::new(Mutex::new(session)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Session> {
+        self.session.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Session ID (not the cookie value)
+    pub fn id(&self) -> String {
+        self.lock().id().to_string()
+    }
+
+    /// Get a value, `None` if missing or not a `T`
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.lock().get(key)
+    }
+
+    /// Store a value
+    pub fn insert<T: Serialize>(&self, key: &str, value: T) -> Result<()> {
+        self.lock()
+            .insert(key, value)
+            .map_err(|e| RfError::Serialization(format!("Failed to serialize session value: {}", e)))
+    }
+
+    pub fn remove(&self, key: &str) {
+        self.lock().remove(key);
+    }
+
+    /// Number of stored values
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Move the data to a new session ID, e.g. after login to prevent session fixation
+    pub fn regenerate(&self) {
+        self.lock().regenerate();
+    }
+
+    /// Delete the session and its cookie, e.g. on logout
+    pub fn destroy(&self) {
+        self.lock().destroy();
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for SessionHandle {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> std::result::Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<SessionHandle>()
+            .cloned()
+            .ok_or_else(|| ApiError(RfError::Internal("Session middleware is not enabled".to_string())))
+    }
+}
+
+/// Take the cookie value of a new or regenerated session
+fn take_cookie_value(session: &mut Session) -> Option<String> {
+    let copy = session.clone();
+    std::mem::replace(session, copy).into_cookie_value()
+}
+
+/// Load the session before the handler and save it afterwards
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc::new(sessions), session_middleware)`,
+/// or `HttpServer::with_session`.
+pub async fn session_middleware(
+    State(sessions): State<Arc<SessionMiddleware>>,
+    mut request: Request,
+    next: Next,
+) -> AxumResponse {
+    let cookie_value = sessions.cookie_value(&request).map(str::to_string);
+    let loaded = sessions.load(cookie_value.as_deref()).await;
+    let loaded_id = loaded.as_ref().map(|s| s.id().to_string());
+    let handle = SessionHandle::new(loaded.unwrap_or_else(|| sessions.manager.create()));
+    request.extensions_mut().insert(handle.clone());
+
+    let mut response = next.run(request).await;
+
+    // Clones drop the cookie value, so take it from the shared session first
+    let (mut session, new_cookie) = {
+        let mut shared = handle.lock();
+        let cookie = take_cookie_value(&mut shared);
+        (shared.clone(), cookie)
+    };
+    let manager = &sessions.manager;
+    let mut set_cookie = None;
+    if session.is_destroyed() {
+        if let Some(id) = &loaded_id {
+            if let Err(e) = manager.delete(id).await {
+                tracing::warn!("Failed to delete session: {}", e);
+            }
+            set_cookie = sessions.cookie.header("", Some(0));
+        }
+    } else {
+        let regenerated = loaded_id.as_deref().is_some_and(|id| id != session.id());
+        if regenerated {
+            if let Some(id) = &loaded_id {
+                if let Err(e) = manager.delete(id).await {
+                    tracing::warn!("Failed to delete session: {}", e);
+                }
+            }
+        }
+        let sliding = loaded_id.is_some() && manager.is_sliding();
+        if session.data_changed() || regenerated || sliding {
+            if let Some(ttl) = manager.ttl() {
+                session.expire_in(ttl);
+            }
+            session.reset_data_changed();
+            if let Err(e) = manager.store(session).await {
+                tracing::error!("Failed to store session: {}", e);
+            } else if let Some(value) = new_cookie.or(if sliding { cookie_value } else { None }) {
+                set_cookie = sessions.cookie.header(&value, sessions.max_age());
+            }
+        }
+    }
+    if let Some(cookie) = set_cookie {
+        response.headers_mut().append(SET_COOKIE, cookie);
+    }
+    response
+}
@@ -10,6 +10,7 @@
 use axum::response::{Html, Json};
 use axum::routing::get;
 use axum::Router;
+use std::sync::Arc;
 use utoipa::openapi::OpenApi;
 
 /// Create Swagger UI router
@@ -35,6 +36,23 @@ pub fn create_swagger_ui_router(openapi: OpenApi, path: &str) -> Router {
         }))
 }
 
+/// Serve a generated OpenAPI document at `json_path`, plus Swagger UI at `ui_path`
+pub fn create_openapi_router(document: serde_json::Value, json_path: &str, ui_path: Option<&str>) -> Router {
+    let document = Arc::new(document);
+    let mut router = Router::new().route(json_path, get(move || {
+        let document = document.clone();
+        async move { Json(document.as_ref().clone()) }
+    }));
+    if let Some(ui_path) = ui_path {
+        let url = json_path.to_string();
+        router = router.route(&format!("{}/", ui_path.trim_end_matches('/')), get(move || {
+            let url = url.clone();
+            async move { create_swagger_ui_html(&url) }
+        }));
+    }
+    router
+}
+
 /// Create a simple Swagger UI HTML page
 pub fn create_swagger_ui_html(openapi_url: &str) -> Html<String> {
     let html = format!(
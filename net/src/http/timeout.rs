@@ -13,12 +13,15 @@ use std::time::Duration;
 use tokio::time::timeout;
 
 /// Timeout middleware
+///
+/// The handler runs inside an `rf_core::ctx` deadline scope, so database
+/// queries issued by the handler are limited to the time left in the budget.
 pub async fn timeout_middleware(
     duration: Duration,
     request: Request,
     next: axum::middleware::Next,
 ) -> Result<Response, axum::Error> {
-    timeout(duration, next.run(request))
+    timeout(duration, rf_core::ctx::with_timeout(duration, next.run(request)))
         .await
         .map_err(|_| {
             axum::Error::new(std::io::Error::new(
@@ -0,0 +1,377 @@
+//! # tls
+//!
+//! tls 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! HTTPS support with certificate hot reload
+//!
+//! TLS is provided by rustls with the ring crypto provider. Certificates are
+//! loaded from PEM files (certificate chain plus private key). The server
+//! config lives in an `ArcSwap`, so it can be replaced at runtime:
+//! `TlsAcceptorHandle::reload` re-reads the files, and `watch` reloads them
+//! through `rf_os::fsnotify` when they change on disk. A failed reload keeps
+//! serving the previous certificate.
+//!
+//! ```ignore
+//! HttpServer::new(addr)
+//!     .with_tls("certs/server.crt", "certs/server.key")
+//!     .serve()
+//!     .await?;
+//! ```
+
+use arc_swap::ArcSwap;
+use futures_util::future::BoxFuture;
+use futures_util::{FutureExt, StreamExt};
+use rf_errors::{Result, RfError};
+use rf_os::fsnotify::FsWatch;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+
+/// TLS configuration for `HttpServer`
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// Reload the certificate when the files change (default: true)
+    pub watch: bool,
+    /// Maximum time for a client to complete the handshake (default: 10s)
+    pub handshake_timeout: Duration,
+}
+
+impl TlsConfig {
+    /// Create a configuration from PEM certificate and key files
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        Self {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+            watch: true,
+            handshake_timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Enable or disable reloading on file changes
+    pub fn watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    /// Set the handshake timeout
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+}
+
+/// Crypto provider used by every TLS config in this crate
+pub(crate) fn crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+/// Certificates of a PEM certificate chain, leaf first
+pub(crate) fn load_certs(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = rustls_pemfile::certs(&mut &pem[..])
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| RfError::Config(format!("Invalid TLS certificate: {}", e)))?;
+    if certs.is_empty() {
+        return Err(RfError::Config("TLS certificate contains no PEM certificate".to_string()));
+    }
+    Ok(certs)
+}
+
+/// First private key of a PEM file (PKCS#8, PKCS#1 or SEC1)
+pub(crate) fn load_private_key(pem: &[u8]) -> Result<PrivateKeyDer<'static>> {
+    rustls_pemfile::private_key(&mut &pem[..])
+        .map_err(|e| RfError::Config(format!("Invalid TLS private key: {}", e)))?
+        .ok_or_else(|| RfError::Config("TLS key contains no PEM private key".to_string()))
+}
+
+/// Root store holding the CAs of a PEM file
+pub(crate) fn load_roots(pem: &[u8]) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(pem)? {
+        roots
+            .add(cert)
+            .map_err(|e| RfError::Config(format!("Invalid CA certificate: {}", e)))?;
+    }
+    Ok(roots)
+}
+
+/// Handshake settings applied whenever the certificate is (re)loaded
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HandshakeOptions {
+    /// ALPN protocols in order of preference
+    pub alpn: Vec<Vec<u8>>,
+    /// PEM CAs that client certificates must chain to (mutual TLS)
+    pub client_ca: Option<Vec<u8>>,
+}
+
+impl HandshakeOptions {
+    fn build(&self, cert_pem: &[u8], key_pem: &[u8]) -> Result<ServerConfig> {
+        let provider = crypto_provider();
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| RfError::Config(format!("Failed to build TLS config: {}", e)))?;
+        let builder = match &self.client_ca {
+            Some(ca) => {
+                let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(load_roots(ca)?), provider)
+                    .build()
+                    .map_err(|e| RfError::Config(format!("Invalid client CA: {}", e)))?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let mut config = builder
+            .with_single_cert(load_certs(cert_pem)?, load_private_key(key_pem)?)
+            .map_err(|e| RfError::Config(format!("Invalid TLS certificate or key: {}", e)))?;
+        config.alpn_protocols = self.alpn.clone();
+        Ok(config)
+    }
+}
+
+/// Hot-swappable TLS acceptor
+///
+/// Shared by HTTPS, the TCP forwarder and `TlsTcpServer`.
+pub struct TlsAcceptorHandle {
+    config: ArcSwap<ServerConfig>,
+    files: Option<(PathBuf, PathBuf)>,
+    options: HandshakeOptions,
+}
+
+impl TlsAcceptorHandle {
+    /// Load the certificate chain and private key from PEM files
+    pub fn from_pem_files(cert_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_pem_files_with(cert_path.as_ref(), key_path.as_ref(), HandshakeOptions::default())
+    }
+
+    /// Create from in-memory PEM data
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self> {
+        Self::from_pem_with(cert_pem, key_pem, HandshakeOptions::default())
+    }
+
+    pub(crate) fn from_pem_files_with(cert_path: &Path, key_path: &Path, options: HandshakeOptions) -> Result<Self> {
+        let config = options.build(&read_pem(cert_path)?, &read_pem(key_path)?)?;
+        Ok(Self {
+            config: ArcSwap::from_pointee(config),
+            files: Some((cert_path.to_path_buf(), key_path.to_path_buf())),
+            options,
+        })
+    }
+
+    pub(crate) fn from_pem_with(cert_pem: &[u8], key_pem: &[u8], options: HandshakeOptions) -> Result<Self> {
+        Ok(Self {
+            config: ArcSwap::from_pointee(options.build(cert_pem, key_pem)?),
+            files: None,
+            options,
+        })
+    }
+
+    /// Replace the certificate; the old one stays active on error
+    pub fn swap(&self, cert_pem: &[u8], key_pem: &[u8]) -> Result<()> {
+        self.config.store(Arc::new(self.options.build(cert_pem, key_pem)?));
+        Ok(())
+    }
+
+    /// Re-read the certificate files
+    pub fn reload(&self) -> Result<()> {
+        let (cert_path, key_path) = self
+            .files
+            .as_ref()
+            .ok_or_else(|| RfError::Config("TLS acceptor was not loaded from files".to_string()))?;
+        self.swap(&read_pem(cert_path)?, &read_pem(key_path)?)?;
+        tracing::info!("Reloaded TLS certificate from {}", cert_path.display());
+        Ok(())
+    }
+
+    /// The current server config
+    pub fn config(&self) -> Arc<ServerConfig> {
+        self.config.load_full()
+    }
+
+    /// An acceptor for the current certificate
+    pub fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(self.config())
+    }
+
+    /// Reload the certificate whenever the files change
+    ///
+    /// The parent directories are watched, so atomic renames and the
+    /// symlink swaps used by Kubernetes secrets are picked up too. Reloads
+    /// are debounced; keep the returned watcher alive to keep watching.
+    pub fn watch(self: &Arc<Self>) -> Result<CertWatcher> {
+        let (cert_path, key_path) = self
+            .files
+            .clone()
+            .ok_or_else(|| RfError::Config("TLS acceptor was not loaded from files".to_string()))?;
+        let names: HashSet<_> = [&cert_path, &key_path]
+            .iter()
+            .filter_map(|p| p.file_name().map(|n| n.to_os_string()))
+            .collect();
+        let (cert_dir, key_dir) = (parent_dir(&cert_path), parent_dir(&key_path));
+        let mut watch = FsWatch::new(&cert_dir);
+        if key_dir != cert_dir {
+            watch = watch.path(&key_dir);
+        }
+        // Certificate and key are usually written separately
+        let mut events = watch.recursive(false).debounce(Duration::from_millis(300)).start()?;
+
+        let handle = Arc::clone(self);
+        let task = tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                let relevant = event
+                    .path()
+                    .file_name()
+                    .is_some_and(|n| names.contains(n) || n.to_string_lossy().starts_with(".."));
+                if !relevant {
+                    continue;
+                }
+                // One reload for the whole burst
+                while let Some(Some(_)) = events.next().now_or_never() {}
+                if let Err(e) = handle.reload() {
+                    tracing::warn!("TLS certificate reload failed, keeping the previous certificate: {}", e);
+                }
+            }
+        });
+
+        Ok(CertWatcher { task })
+    }
+}
+
+fn parent_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+pub(crate) fn read_pem(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|e| RfError::Config(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+/// Keeps certificate file watching alive; stops when dropped
+pub struct CertWatcher {
+    task: JoinHandle<()>,
+}
+
+impl Drop for CertWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Hook run on raw connections before the TLS handshake
+///
+/// Used to answer special handshakes (such as ACME TLS-ALPN-01
+/// validation) with a different certificate.
+pub trait TlsInterceptor: Send + Sync {
+    /// Return the stream to continue with the regular handshake, or
+    /// `None` if the connection was handled
+    fn intercept(&self, stream: TcpStream) -> BoxFuture<'static, Option<TcpStream>>;
+}
+
+/// TLS listener for `axum::serve`
+///
+/// Handshakes run in their own tasks so a slow client cannot block
+/// accepting other connections.
+pub struct TlsListener {
+    incoming: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    local_addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl TlsListener {
+    /// Wrap a bound TCP listener
+    pub fn new(listener: TcpListener, acceptor: Arc<TlsAcceptorHandle>, handshake_timeout: Duration) -> Result<Self> {
+        Self::start(listener, acceptor, handshake_timeout, None)
+    }
+
+    /// Wrap a bound TCP listener, passing connections through an interceptor first
+    pub fn with_interceptor(
+        listener: TcpListener,
+        acceptor: Arc<TlsAcceptorHandle>,
+        handshake_timeout: Duration,
+        interceptor: Arc<dyn TlsInterceptor>,
+    ) -> Result<Self> {
+        Self::start(listener, acceptor, handshake_timeout, Some(interceptor))
+    }
+
+    fn start(
+        listener: TcpListener,
+        acceptor: Arc<TlsAcceptorHandle>,
+        handshake_timeout: Duration,
+        interceptor: Option<Arc<dyn TlsInterceptor>>,
+    ) -> Result<Self> {
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| RfError::Network(format!("Failed to get local address: {}", e)))?;
+        let (tx, incoming) = mpsc::channel(128);
+        let task = tokio::spawn(async move {
+            loop {
+                let (stream, addr) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        tracing::warn!("Failed to accept connection: {}", e);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        continue;
+                    }
+                };
+                let acceptor = acceptor.acceptor();
+                let interceptor = interceptor.clone();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let stream = match interceptor {
+                        Some(interceptor) => match interceptor.intercept(stream).await {
+                            Some(stream) => stream,
+                            None => return,
+                        },
+                        None => stream,
+                    };
+                    match tokio::time::timeout(handshake_timeout, acceptor.accept(stream)).await {
+                        Ok(Ok(tls)) => {
+                            let _ = tx.send((tls, addr)).await;
+                        }
+                        Ok(Err(e)) => tracing::debug!("TLS handshake with {} failed: {}", addr, e),
+                        Err(_) => tracing::debug!("TLS handshake with {} timed out", addr),
+                    }
+                });
+            }
+        });
+        Ok(Self { incoming, local_addr, task })
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.incoming.recv().await {
+            Some(conn) => conn,
+            // The accept task only stops when the listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+impl Drop for TlsListener {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
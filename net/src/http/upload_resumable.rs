@@ -0,0 +1,803 @@
+//! # upload_resumable
+//!
+//! upload_resumable 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Resumable uploads (tus 1.0.0)
+//!
+//! `ResumableUploads` serves the tus protocol so multi-GB files survive
+//! flaky links: the client creates an upload with `POST`, sends data with
+//! `PATCH` and, after a dropped connection, asks for the stored offset with
+//! `HEAD` and continues from there. Supported extensions are `creation`,
+//! `creation-with-upload`, `expiration`, `checksum` and `termination`, so
+//! stock clients such as tus-js-client and Uppy work unchanged.
+//!
+//! Data is staged in a `ResumableStore` (`LocalResumableStore` keeps it in a
+//! directory). Once complete it is moved to an `UploadSink` if one is set,
+//! e.g. `S3Sink`; otherwise it stays in the store.
+//!
+//! ```rust,no_run
+//! use rf_net::http::{LocalResumableStore, ResumableUploads, S3Sink};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # async fn run() {
+//! let uploads = Arc::new(
+//!     ResumableUploads::new(LocalResumableStore::new("./uploads/staging"))
+//!         .sink(S3Sink::new("http://minio:9000", "uploads", "key", "secret"))
+//!         .max_size(10 * 1024 * 1024 * 1024)
+//!         .on_complete(|upload| tracing::info!("{} stored at {:?}", upload.id, upload.location)),
+//! );
+//! let _cleanup = uploads.spawn_cleanup(Duration::from_secs(3600));
+//! let app = axum::Router::new().merge(uploads.router("/files"));
+//! # }
+//! ```
+
+use super::upload_stream::{UploadMeta, UploadSink};
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::extract::{Path as UrlPath, Request};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::options;
+use axum::Router;
+use futures_util::StreamExt;
+use md5::Md5;
+use rf_errors::{Result, RfError};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+/// Protocol version spoken by the server
+pub const TUS_VERSION: &str = "1.0.0";
+
+/// Content type of `PATCH` bodies
+const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+
+/// Data buffered before it is written to the store
+const WRITE_BUFFER: usize = 1024 * 1024;
+
+/// Status answered when the `Upload-Checksum` does not match the data
+const CHECKSUM_MISMATCH: u16 = 460;
+
+/// State of a resumable upload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumableUpload {
+    pub id: String,
+    /// Total size announced by the client
+    pub length: u64,
+    /// Bytes received so far
+    pub offset: u64,
+    /// Decoded `Upload-Metadata`, e.g. `filename` and `filetype`
+    pub metadata: HashMap<String, String>,
+    /// Unix timestamp
+    pub created_at: i64,
+    /// Unix timestamp after which an unfinished upload is removed
+    pub expires_at: i64,
+    /// Where the complete file is stored (sink location or store path)
+    pub location: Option<String>,
+}
+
+impl ResumableUpload {
+    /// All bytes have been received
+    pub fn is_complete(&self) -> bool {
+        self.offset == self.length
+    }
+
+    /// Client filename from the `filename` metadata
+    pub fn filename(&self) -> Option<&str> {
+        self.metadata.get("filename").map(|name| name.as_str())
+    }
+
+    fn is_expired(&self, now: i64) -> bool {
+        !self.is_complete() && self.expires_at <= now
+    }
+}
+
+/// Staging storage for resumable uploads
+#[async_trait]
+pub trait ResumableStore: Send + Sync {
+    /// Load an upload's state
+    async fn get(&self, id: &str) -> Result<Option<ResumableUpload>>;
+
+    /// Persist an upload's state, creating it if new
+    async fn save(&self, upload: &ResumableUpload) -> Result<()>;
+
+    /// Write `chunk` at `offset`, dropping any data stored past it
+    async fn write(&self, id: &str, offset: u64, chunk: &[u8]) -> Result<()>;
+
+    /// Read the received data from the start
+    async fn reader(&self, id: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>>;
+
+    /// Location reported for a complete upload kept in the store
+    fn location(&self, id: &str) -> String;
+
+    /// Delete the data, keeping the state
+    async fn remove_data(&self, id: &str) -> Result<()>;
+
+    /// Delete the state and the data
+    async fn remove(&self, id: &str) -> Result<()>;
+
+    /// State of every upload, for expiry cleanup
+    async fn list(&self) -> Result<Vec<ResumableUpload>>;
+}
+
+/// Stages uploads in a local directory
+///
+/// Each upload is a `<id>.bin` data file next to a `<id>.json` state file;
+/// the location of a complete upload is the data file's path.
+pub struct LocalResumableStore {
+    dir: PathBuf,
+}
+
+impl LocalResumableStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn data_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.bin", id))
+    }
+
+    fn state_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+#[async_trait]
+impl ResumableStore for LocalResumableStore {
+    async fn get(&self, id: &str) -> Result<Option<ResumableUpload>> {
+        match fs::read(self.state_path(id)).await {
+            Ok(data) => serde_json::from_slice(&data)
+                .map(Some)
+                .map_err(|e| RfError::Serialization(format!("Invalid upload state {}: {}", id, e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(RfError::Io(e)),
+        }
+    }
+
+    async fn save(&self, upload: &ResumableUpload) -> Result<()> {
+        fs::create_dir_all(&self.dir).await.map_err(RfError::Io)?;
+        let data = serde_json::to_vec(upload).map_err(|e| RfError::Serialization(e.to_string()))?;
+        // Write then rename so a crash never leaves a truncated state file
+        let path = self.state_path(&upload.id);
+        let partial = path.with_extension("json.part");
+        fs::write(&partial, data).await.map_err(RfError::Io)?;
+        fs::rename(&partial, &path).await.map_err(RfError::Io)
+    }
+
+    async fn write(&self, id: &str, offset: u64, chunk: &[u8]) -> Result<()> {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.data_path(id))
+            .await
+            .map_err(RfError::Io)?;
+        file.set_len(offset).await.map_err(RfError::Io)?;
+        file.seek(SeekFrom::Start(offset)).await.map_err(RfError::Io)?;
+        file.write_all(chunk).await.map_err(RfError::Io)?;
+        file.flush().await.map_err(RfError::Io)
+    }
+
+    async fn reader(&self, id: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        match fs::File::open(self.data_path(id)).await {
+            Ok(file) => Ok(Box::new(file)),
+            // Empty uploads never write data
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Box::new(tokio::io::empty())),
+            Err(e) => Err(RfError::Io(e)),
+        }
+    }
+
+    fn location(&self, id: &str) -> String {
+        self.data_path(id).to_string_lossy().into_owned()
+    }
+
+    async fn remove_data(&self, id: &str) -> Result<()> {
+        remove_file(self.data_path(id)).await
+    }
+
+    async fn remove(&self, id: &str) -> Result<()> {
+        remove_file(self.data_path(id)).await?;
+        remove_file(self.state_path(id)).await
+    }
+
+    async fn list(&self) -> Result<Vec<ResumableUpload>> {
+        let mut entries = match fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(RfError::Io(e)),
+        };
+        let mut uploads = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(RfError::Io)? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if let Some(id) = name.strip_suffix(".json") {
+                if let Some(upload) = self.get(id).await? {
+                    uploads.push(upload);
+                }
+            }
+        }
+        Ok(uploads)
+    }
+}
+
+async fn remove_file(path: PathBuf) -> Result<()> {
+    match fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(RfError::Io(e)),
+        _ => Ok(()),
+    }
+}
+
+/// Hash algorithms accepted in `Upload-Checksum`
+const CHECKSUM_ALGORITHMS: &str = "sha1,sha256,md5";
+
+enum Checksum {
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Md5(Md5),
+}
+
+impl Checksum {
+    fn new(algorithm: &str) -> Option<Self> {
+        match algorithm {
+            "sha1" => Some(Self::Sha1(Sha1::new())),
+            "sha256" => Some(Self::Sha256(Sha256::new())),
+            "md5" => Some(Self::Md5(Md5::new())),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha1(hasher) => hasher.update(data),
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Md5(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Self::Sha1(hasher) => hasher.finalize().to_vec(),
+            Self::Sha256(hasher) => hasher.finalize().to_vec(),
+            Self::Md5(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
+
+type CompleteCallback = Arc<dyn Fn(&ResumableUpload) + Send + Sync>;
+
+/// Serves resumable uploads, see the module documentation
+pub struct ResumableUploads {
+    store: Arc<dyn ResumableStore>,
+    sink: Option<Arc<dyn UploadSink>>,
+    max_size: Option<u64>,
+    expire_after: Duration,
+    on_complete: Option<CompleteCallback>,
+    /// Uploads with a request in progress
+    busy: Mutex<HashSet<String>>,
+}
+
+/// Marks an upload busy until dropped
+struct BusyGuard<'a> {
+    busy: &'a Mutex<HashSet<String>>,
+    id: String,
+}
+
+impl Drop for BusyGuard<'_> {
+    fn drop(&mut self) {
+        self.busy.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.id);
+    }
+}
+
+impl ResumableUploads {
+    /// Serve uploads staged in `store`, unfinished ones expiring after 24 hours
+    pub fn new(store: impl ResumableStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+            sink: None,
+            max_size: None,
+            expire_after: Duration::from_secs(24 * 3600),
+            on_complete: None,
+            busy: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Move complete uploads to `sink`, keeping only their state in the store
+    pub fn sink(mut self, sink: impl UploadSink + 'static) -> Self {
+        self.sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// Set a shared sink for complete uploads
+    pub fn shared_sink(mut self, sink: Arc<dyn UploadSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Maximum upload size in bytes, announced as `Tus-Max-Size`
+    pub fn max_size(mut self, size: u64) -> Self {
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Time an unfinished upload is kept after its last data arrived
+    pub fn expire_after(mut self, duration: Duration) -> Self {
+        self.expire_after = duration;
+        self
+    }
+
+    /// Call `callback` once an upload is complete and stored
+    pub fn on_complete<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ResumableUpload) + Send + Sync + 'static,
+    {
+        self.on_complete = Some(Arc::new(callback));
+        self
+    }
+
+    /// The staging store
+    pub fn store(&self) -> &Arc<dyn ResumableStore> {
+        &self.store
+    }
+
+    /// Router serving the protocol at `path` (creation) and `path/{id}`
+    ///
+    /// `path` is the public path: it is used for the `Location` of new uploads.
+    pub fn router(self: Arc<Self>, path: &str) -> Router {
+        let base = path.trim_end_matches('/').to_string();
+        let create = self.clone();
+        let status = self.clone();
+        let append = self.clone();
+        let terminate = self.clone();
+        let collection = if base.is_empty() { "/".to_string() } else { base.clone() };
+        Router::new()
+            .route(
+                &collection,
+                options(move || {
+                    let uploads = self.clone();
+                    async move { uploads.capabilities() }
+                })
+                .post(move |request: Request| {
+                    let uploads = create.clone();
+                    let base = base.clone();
+                    async move { uploads.create(&base, request).await }
+                }),
+            )
+            .route(
+                &format!("{}/{{id}}", collection.trim_end_matches('/')),
+                axum::routing::head(move |UrlPath(id): UrlPath<String>, headers: HeaderMap| {
+                    let uploads = status.clone();
+                    async move { uploads.status(&id, &headers).await }
+                })
+                .patch(move |UrlPath(id): UrlPath<String>, request: Request| {
+                    let uploads = append.clone();
+                    async move { uploads.append(&id, request).await }
+                })
+                .delete(move |UrlPath(id): UrlPath<String>, headers: HeaderMap| {
+                    let uploads = terminate.clone();
+                    async move { uploads.terminate(&id, &headers).await }
+                }),
+            )
+    }
+
+    /// Remove unfinished uploads past their expiry, returning how many were removed
+    pub async fn cleanup_expired(&self) -> Result<usize> {
+        let now = chrono::Utc::now().timestamp();
+        let mut removed = 0;
+        for upload in self.store.list().await? {
+            if !upload.is_expired(now) {
+                continue;
+            }
+            let Some(_guard) = self.lock(&upload.id) else {
+                continue;
+            };
+            match self.store.remove(&upload.id).await {
+                Ok(()) => removed += 1,
+                Err(e) => tracing::warn!("Failed to remove expired upload {}: {}", upload.id, e),
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Run `cleanup_expired` every `interval` on the Tokio runtime
+    pub fn spawn_cleanup(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let uploads = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(uploads) = uploads.upgrade() else {
+                    return;
+                };
+                match uploads.cleanup_expired().await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Removed {} expired uploads", removed),
+                    Err(e) => tracing::error!("Upload cleanup failed: {}", e),
+                }
+            }
+        })
+    }
+
+    fn lock(&self, id: &str) -> Option<BusyGuard<'_>> {
+        let mut busy = self.busy.lock().unwrap_or_else(|e| e.into_inner());
+        if !busy.insert(id.to_string()) {
+            return None;
+        }
+        Some(BusyGuard { busy: &self.busy, id: id.to_string() })
+    }
+
+    fn capabilities(&self) -> Response {
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        let headers = response.headers_mut();
+        headers.insert("tus-resumable", HeaderValue::from_static(TUS_VERSION));
+        headers.insert("tus-version", HeaderValue::from_static(TUS_VERSION));
+        headers.insert(
+            "tus-extension",
+            HeaderValue::from_static("creation,creation-with-upload,expiration,checksum,termination"),
+        );
+        headers.insert("tus-checksum-algorithm", HeaderValue::from_static(CHECKSUM_ALGORITHMS));
+        if let Some(max) = self.max_size {
+            headers.insert("tus-max-size", HeaderValue::from(max));
+        }
+        response
+    }
+
+    async fn create(&self, base: &str, request: Request) -> Response {
+        if let Some(response) = version_mismatch(request.headers()) {
+            return response;
+        }
+        let headers = request.headers();
+        if headers.contains_key("upload-defer-length") {
+            return reply(StatusCode::BAD_REQUEST, "Upload-Defer-Length is not supported");
+        }
+        let Some(length) = header_u64(headers, "upload-length") else {
+            return reply(StatusCode::BAD_REQUEST, "Missing or invalid Upload-Length");
+        };
+        if let Some(max) = self.max_size.filter(|max| length > *max) {
+            return reply(StatusCode::PAYLOAD_TOO_LARGE, &format!("Upload exceeds maximum size {}", max));
+        }
+        let metadata = match headers.get("upload-metadata").map(|v| v.to_str()) {
+            None => HashMap::new(),
+            Some(Ok(value)) => match parse_metadata(value) {
+                Ok(metadata) => metadata,
+                Err(e) => return reply(StatusCode::BAD_REQUEST, &e.to_string()),
+            },
+            Some(Err(_)) => return reply(StatusCode::BAD_REQUEST, "Invalid Upload-Metadata"),
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let mut upload = ResumableUpload {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            length,
+            offset: 0,
+            metadata,
+            created_at: now,
+            expires_at: now + self.expire_after.as_secs() as i64,
+            location: None,
+        };
+        if let Err(e) = self.store.save(&upload).await {
+            return internal_error(&upload.id, e);
+        }
+        let _guard = self.lock(&upload.id);
+
+        // creation-with-upload: the body carries the first chunk
+        let with_data = content_type(request.headers()) == Some(OFFSET_OCTET_STREAM);
+        let mut response = if with_data || upload.length == 0 {
+            match self.receive(&mut upload, request).await {
+                Ok(()) => StatusCode::CREATED.into_response(),
+                Err(response) => return response,
+            }
+        } else {
+            StatusCode::CREATED.into_response()
+        };
+        let location = format!("{}/{}", base, upload.id);
+        let headers = response.headers_mut();
+        headers.insert("tus-resumable", HeaderValue::from_static(TUS_VERSION));
+        if let Ok(location) = HeaderValue::from_str(&location) {
+            headers.insert(header::LOCATION, location);
+        }
+        if with_data {
+            headers.insert("upload-offset", HeaderValue::from(upload.offset));
+        }
+        insert_expires(headers, &upload);
+        response
+    }
+
+    async fn status(&self, id: &str, headers: &HeaderMap) -> Response {
+        if let Some(response) = version_mismatch(headers) {
+            return response;
+        }
+        let upload = match self.find(id).await {
+            Ok(upload) => upload,
+            Err(response) => return response,
+        };
+        let mut response = StatusCode::OK.into_response();
+        let headers = response.headers_mut();
+        headers.insert("tus-resumable", HeaderValue::from_static(TUS_VERSION));
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        headers.insert("upload-offset", HeaderValue::from(upload.offset));
+        headers.insert("upload-length", HeaderValue::from(upload.length));
+        if !upload.metadata.is_empty() {
+            if let Ok(metadata) = HeaderValue::from_str(&encode_metadata(&upload.metadata)) {
+                headers.insert("upload-metadata", metadata);
+            }
+        }
+        insert_expires(headers, &upload);
+        response
+    }
+
+    async fn append(&self, id: &str, request: Request) -> Response {
+        if let Some(response) = version_mismatch(request.headers()) {
+            return response;
+        }
+        if content_type(request.headers()) != Some(OFFSET_OCTET_STREAM) {
+            return reply(StatusCode::UNSUPPORTED_MEDIA_TYPE, &format!("Content-Type must be {}", OFFSET_OCTET_STREAM));
+        }
+        let Some(offset) = header_u64(request.headers(), "upload-offset") else {
+            return reply(StatusCode::BAD_REQUEST, "Missing or invalid Upload-Offset");
+        };
+        let Some(_guard) = self.lock(id) else {
+            return reply(StatusCode::LOCKED, "Upload is being written by another request");
+        };
+        let mut upload = match self.find(id).await {
+            Ok(upload) => upload,
+            Err(response) => return response,
+        };
+        if offset != upload.offset {
+            return reply(StatusCode::CONFLICT, &format!("Upload-Offset {} does not match {}", offset, upload.offset));
+        }
+        if let Err(response) = self.receive(&mut upload, request).await {
+            return response;
+        }
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        let headers = response.headers_mut();
+        headers.insert("tus-resumable", HeaderValue::from_static(TUS_VERSION));
+        headers.insert("upload-offset", HeaderValue::from(upload.offset));
+        insert_expires(headers, &upload);
+        response
+    }
+
+    async fn terminate(&self, id: &str, headers: &HeaderMap) -> Response {
+        if let Some(response) = version_mismatch(headers) {
+            return response;
+        }
+        // Expired uploads can still be terminated, so `find` is not used
+        if !valid_id(id) {
+            return reply(StatusCode::NOT_FOUND, "Upload not found");
+        }
+        let Some(_guard) = self.lock(id) else {
+            return reply(StatusCode::LOCKED, "Upload is being written by another request");
+        };
+        match self.store.get(id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return reply(StatusCode::NOT_FOUND, "Upload not found"),
+            Err(e) => return internal_error(id, e),
+        }
+        if let Err(e) = self.store.remove(id).await {
+            return internal_error(id, e);
+        }
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        response.headers_mut().insert("tus-resumable", HeaderValue::from_static(TUS_VERSION));
+        response
+    }
+
+    /// Load a live upload; unknown ids are 404, expired ones 410
+    async fn find(&self, id: &str) -> std::result::Result<ResumableUpload, Response> {
+        if !valid_id(id) {
+            return Err(reply(StatusCode::NOT_FOUND, "Upload not found"));
+        }
+        match self.store.get(id).await {
+            Ok(Some(upload)) if upload.is_expired(chrono::Utc::now().timestamp()) => {
+                Err(reply(StatusCode::GONE, "Upload has expired"))
+            }
+            Ok(Some(upload)) => Ok(upload),
+            Ok(None) => Err(reply(StatusCode::NOT_FOUND, "Upload not found")),
+            Err(e) => Err(internal_error(id, e)),
+        }
+    }
+
+    /// Store the request body at the upload's offset, then complete it if all data is in
+    ///
+    /// The new offset is only saved once the body checks out, so data
+    /// failing its checksum is overwritten by the client's retry. Without a
+    /// checksum, whatever arrived before a dropped connection is kept.
+    async fn receive(&self, upload: &mut ResumableUpload, request: Request) -> std::result::Result<(), Response> {
+        let mut checksum = match request.headers().get("upload-checksum").map(|v| v.to_str()) {
+            None => None,
+            Some(Ok(value)) => {
+                let parsed = value.split_once(' ').and_then(|(algorithm, digest)| {
+                    let expected = rf_encoding::base64::decode(digest.trim()).ok()?;
+                    Some((Checksum::new(algorithm)?, expected))
+                });
+                match parsed {
+                    Some(parsed) => Some(parsed),
+                    None => return Err(reply(StatusCode::BAD_REQUEST, "Unsupported or invalid Upload-Checksum")),
+                }
+            }
+            Some(Err(_)) => return Err(reply(StatusCode::BAD_REQUEST, "Invalid Upload-Checksum")),
+        };
+
+        let remaining = upload.length - upload.offset;
+        let mut received = 0u64;
+        let mut buffer = Vec::new();
+        let mut failure = None;
+        let mut stream = request.into_body().into_data_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    failure = Some(reply(StatusCode::BAD_REQUEST, &format!("Failed to read upload data: {}", e)));
+                    break;
+                }
+            };
+            if received + chunk.len() as u64 > remaining {
+                return Err(reply(StatusCode::PAYLOAD_TOO_LARGE, "Data exceeds Upload-Length"));
+            }
+            if let Some((hasher, _)) = checksum.as_mut() {
+                hasher.update(&chunk);
+            }
+            buffer.extend_from_slice(&chunk);
+            received += chunk.len() as u64;
+            if buffer.len() >= WRITE_BUFFER {
+                let at = upload.offset + received - buffer.len() as u64;
+                self.store.write(&upload.id, at, &buffer).await.map_err(|e| internal_error(&upload.id, e))?;
+                buffer.clear();
+            }
+        }
+        if !buffer.is_empty() {
+            let at = upload.offset + received - buffer.len() as u64;
+            self.store.write(&upload.id, at, &buffer).await.map_err(|e| internal_error(&upload.id, e))?;
+        }
+
+        if let Some((hasher, expected)) = checksum.take() {
+            // A partial body cannot be verified
+            if let Some(failure) = failure {
+                return Err(failure);
+            }
+            if hasher.finalize() != expected {
+                let status = StatusCode::from_u16(CHECKSUM_MISMATCH).unwrap_or(StatusCode::BAD_REQUEST);
+                return Err(reply(status, "Checksum mismatch"));
+            }
+        }
+        upload.offset += received;
+        upload.expires_at = chrono::Utc::now().timestamp() + self.expire_after.as_secs() as i64;
+        self.store.save(upload).await.map_err(|e| internal_error(&upload.id, e))?;
+        if let Some(failure) = failure {
+            return Err(failure);
+        }
+
+        // A failed completion is retried by the next PATCH
+        if upload.is_complete() && upload.location.is_none() {
+            self.complete(upload).await.map_err(|e| internal_error(&upload.id, e))?;
+        }
+        Ok(())
+    }
+
+    async fn complete(&self, upload: &mut ResumableUpload) -> Result<()> {
+        let location = match &self.sink {
+            Some(sink) => {
+                let location = self.transfer(sink.as_ref(), upload).await?;
+                if let Err(e) = self.store.remove_data(&upload.id).await {
+                    tracing::warn!("Failed to remove staged upload {}: {}", upload.id, e);
+                }
+                location
+            }
+            None => self.store.location(&upload.id),
+        };
+        upload.location = Some(location);
+        self.store.save(upload).await?;
+        if let Some(callback) = &self.on_complete {
+            callback(upload);
+        }
+        Ok(())
+    }
+
+    /// Stream the staged data into the sink
+    async fn transfer(&self, sink: &dyn UploadSink, upload: &ResumableUpload) -> Result<String> {
+        let meta = UploadMeta {
+            field_name: upload.id.clone(),
+            filename: upload.filename().unwrap_or(&upload.id).to_string(),
+            content_type: upload.metadata.get("filetype").cloned(),
+        };
+        let mut reader = self.store.reader(&upload.id).await?.take(upload.length);
+        let mut writer = sink.open(&meta).await?;
+        let mut buffer = vec![0u8; WRITE_BUFFER];
+        let copied: Result<()> = async {
+            loop {
+                let read = reader.read(&mut buffer).await.map_err(RfError::Io)?;
+                if read == 0 {
+                    return Ok(());
+                }
+                writer.write(&buffer[..read]).await?;
+            }
+        }
+        .await;
+        match copied {
+            Ok(()) => writer.finish().await,
+            Err(e) => {
+                if let Err(abort) = writer.abort().await {
+                    tracing::warn!("Failed to abort transfer of upload {}: {}", upload.id, abort);
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+/// The 412 answer for requests not speaking the supported protocol version
+fn version_mismatch(headers: &HeaderMap) -> Option<Response> {
+    if headers.get("tus-resumable").and_then(|v| v.to_str().ok()) == Some(TUS_VERSION) {
+        return None;
+    }
+    let mut response = reply(StatusCode::PRECONDITION_FAILED, "Unsupported Tus-Resumable version");
+    response.headers_mut().insert("tus-version", HeaderValue::from_static(TUS_VERSION));
+    Some(response)
+}
+
+fn content_type(headers: &HeaderMap) -> Option<&str> {
+    headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(|v| v.split(';').next().unwrap_or(v).trim())
+}
+
+/// Ids are generated alphanumeric; anything else could escape the store's directory
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+fn insert_expires(headers: &mut HeaderMap, upload: &ResumableUpload) {
+    if upload.is_complete() {
+        return;
+    }
+    if let Some(expires) = chrono::DateTime::from_timestamp(upload.expires_at, 0) {
+        let value = expires.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert("upload-expires", value);
+        }
+    }
+}
+
+fn reply(status: StatusCode, message: &str) -> Response {
+    let mut response = (status, Body::from(message.to_string())).into_response();
+    response.headers_mut().insert("tus-resumable", HeaderValue::from_static(TUS_VERSION));
+    response
+}
+
+fn internal_error(id: &str, error: RfError) -> Response {
+    tracing::error!("Resumable upload {} failed: {}", id, error);
+    reply(StatusCode::INTERNAL_SERVER_ERROR, "Upload storage failed")
+}
+
+/// Parse `Upload-Metadata`: comma-separated `key base64(value)` pairs, the value being optional
+pub fn parse_metadata(value: &str) -> Result<HashMap<String, String>> {
+    let mut metadata = HashMap::new();
+    for pair in value.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let (key, encoded) = pair.split_once(' ').unwrap_or((pair, ""));
+        let decoded = rf_encoding::base64::decode(encoded.trim())
+            .map_err(|_| RfError::InvalidParameter(format!("Invalid Upload-Metadata value for '{}'", key)))?;
+        let decoded = String::from_utf8(decoded)
+            .map_err(|_| RfError::InvalidParameter(format!("Upload-Metadata value for '{}' is not UTF-8", key)))?;
+        metadata.insert(key.to_string(), decoded);
+    }
+    Ok(metadata)
+}
+
+fn encode_metadata(metadata: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<String> = metadata
+        .iter()
+        .map(|(key, value)| format!("{} {}", key, rf_encoding::base64::encode(value.as_bytes())))
+        .collect();
+    pairs.sort();
+    pairs.join(",")
+}
@@ -0,0 +1,716 @@
+//! # upload_stream
+//!
+//! upload_stream 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Streaming multipart uploads
+//!
+//! `StreamingUpload` reads multipart file fields chunk by chunk and writes
+//! them straight to an `UploadSink` (a local directory or S3-compatible
+//! object storage) instead of buffering whole files in memory. Size limits,
+//! allowed extensions and magic-byte MIME checks are enforced while the
+//! data streams; a rejected upload removes everything it already stored.
+//!
+//! ```rust,no_run
+//! use axum::extract::{DefaultBodyLimit, Multipart};
+//! use rf_net::http::{LocalDirSink, StreamingUpload};
+//!
+//! async fn upload(multipart: Multipart) -> String {
+//!     let upload = StreamingUpload::new()
+//!         .sink(LocalDirSink::new("./uploads"))
+//!         .max_file_size(100 * 1024 * 1024)
+//!         .allowed_extensions(&["png", "jpg"])
+//!         .allowed_mime_types(&["image/png", "image/jpeg"]);
+//!     match upload.process(multipart).await {
+//!         Ok(result) => result.files[0].location.clone(),
+//!         Err(e) => e.to_string(),
+//!     }
+//! }
+//! // Lift axum's default 2 MB body limit on the upload route:
+//! // .route("/upload", post(upload)).layer(DefaultBodyLimit::disable())
+//! ```
+
+use crate::buffer::BufferPool;
+use async_trait::async_trait;
+use axum::extract::multipart::Field;
+use axum::extract::Multipart;
+use rf_crypto::sha256;
+use rf_errors::{Result, RfError};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+/// Bytes read before the MIME type is sniffed
+const SNIFF_LEN: usize = 512;
+
+/// Detect a MIME type from a file's leading magic bytes
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    let starts = |magic: &[u8]| bytes.starts_with(magic);
+    let at = |offset: usize, magic: &[u8]| bytes.get(offset..offset + magic.len()) == Some(magic);
+    let mime = if starts(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if starts(b"\xff\xd8\xff") {
+        "image/jpeg"
+    } else if starts(b"GIF87a") || starts(b"GIF89a") {
+        "image/gif"
+    } else if starts(b"RIFF") && at(8, b"WEBP") {
+        "image/webp"
+    } else if starts(b"RIFF") && at(8, b"WAVE") {
+        "audio/wav"
+    } else if starts(b"BM") && bytes.len() >= 14 {
+        "image/bmp"
+    } else if starts(b"II*\0") || starts(b"MM\0*") {
+        "image/tiff"
+    } else if starts(b"\0\0\x01\0") {
+        "image/x-icon"
+    } else if starts(b"%PDF-") {
+        "application/pdf"
+    } else if starts(b"PK\x03\x04") || starts(b"PK\x05\x06") {
+        "application/zip"
+    } else if starts(b"\x1f\x8b") {
+        "application/gzip"
+    } else if starts(b"7z\xbc\xaf\x27\x1c") {
+        "application/x-7z-compressed"
+    } else if starts(b"Rar!\x1a\x07") {
+        "application/vnd.rar"
+    } else if at(4, b"ftyp") {
+        "video/mp4"
+    } else if starts(b"\x1a\x45\xdf\xa3") {
+        "video/webm"
+    } else if starts(b"ID3") || starts(b"\xff\xfb") {
+        "audio/mpeg"
+    } else if starts(b"OggS") {
+        "audio/ogg"
+    } else {
+        return None;
+    };
+    Some(mime)
+}
+
+/// Describes the file about to be written to a sink
+#[derive(Debug, Clone)]
+pub struct UploadMeta {
+    pub field_name: String,
+    pub filename: String,
+    /// Sniffed MIME type, falling back to the declared content type
+    pub content_type: Option<String>,
+}
+
+impl UploadMeta {
+    /// Lower-cased extension of the client filename, if it is a plain one
+    pub fn extension(&self) -> Option<String> {
+        Path::new(&self.filename)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|ext| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+            .map(|ext| ext.to_lowercase())
+    }
+
+    /// Storage name: a random id keeping the client extension
+    fn object_name(&self) -> String {
+        match self.extension() {
+            Some(ext) => format!("{}.{}", uuid::Uuid::new_v4().simple(), ext),
+            None => uuid::Uuid::new_v4().simple().to_string(),
+        }
+    }
+}
+
+/// Destination for streamed upload data
+#[async_trait]
+pub trait UploadSink: Send + Sync {
+    /// Start storing one file
+    async fn open(&self, meta: &UploadMeta) -> Result<Box<dyn UploadWriter>>;
+
+    /// Delete a stored file by the location its writer returned
+    async fn remove(&self, location: &str) -> Result<()>;
+}
+
+/// Receives one file's data from `UploadSink::open`
+#[async_trait]
+pub trait UploadWriter: Send {
+    async fn write(&mut self, chunk: &[u8]) -> Result<()>;
+
+    /// Complete the file and return its location
+    async fn finish(self: Box<Self>) -> Result<String>;
+
+    /// Discard everything written so far
+    async fn abort(self: Box<Self>) -> Result<()>;
+}
+
+/// Stores uploads as files in a local directory
+///
+/// Files get random names keeping the client extension; data is written to
+/// a `.part` file that is renamed once complete. The location is the path.
+pub struct LocalDirSink {
+    dir: PathBuf,
+}
+
+impl LocalDirSink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Store uploads in the system temp directory
+    pub fn temp() -> Self {
+        Self::new(std::env::temp_dir().join("rf-uploads"))
+    }
+}
+
+#[async_trait]
+impl UploadSink for LocalDirSink {
+    async fn open(&self, meta: &UploadMeta) -> Result<Box<dyn UploadWriter>> {
+        fs::create_dir_all(&self.dir).await.map_err(RfError::Io)?;
+        let path = self.dir.join(meta.object_name());
+        let partial = path.with_file_name(format!("{}.part", path.file_name().unwrap().to_string_lossy()));
+        let file = fs::File::create(&partial).await.map_err(RfError::Io)?;
+        Ok(Box::new(LocalWriter { file, partial, path }))
+    }
+
+    async fn remove(&self, location: &str) -> Result<()> {
+        match fs::remove_file(location).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(RfError::Io(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+struct LocalWriter {
+    file: fs::File,
+    partial: PathBuf,
+    path: PathBuf,
+}
+
+#[async_trait]
+impl UploadWriter for LocalWriter {
+    async fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        self.file.write_all(chunk).await.map_err(RfError::Io)
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<String> {
+        self.file.flush().await.map_err(RfError::Io)?;
+        self.file.sync_all().await.map_err(RfError::Io)?;
+        fs::rename(&self.partial, &self.path).await.map_err(RfError::Io)?;
+        Ok(self.path.to_string_lossy().into_owned())
+    }
+
+    async fn abort(self: Box<Self>) -> Result<()> {
+        drop(self.file);
+        fs::remove_file(&self.partial).await.map_err(RfError::Io)
+    }
+}
+
+/// Minimum part size accepted by S3 multipart uploads
+const S3_MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// Stores uploads in S3-compatible object storage (AWS S3, MinIO, ...)
+///
+/// Uses path-style URLs and SigV4 signing. Files up to `part_size` are sent
+/// with a single PUT, larger ones as a multipart upload so at most one part
+/// is held in memory. The location is the object key.
+pub struct S3Sink {
+    client: reqwest::Client,
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    prefix: String,
+    part_size: usize,
+}
+
+impl S3Sink {
+    pub fn new(endpoint: &str, bucket: &str, access_key: &str, secret_key: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket: bucket.to_string(),
+            region: "us-east-1".to_string(),
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+            prefix: String::new(),
+            part_size: 8 * 1024 * 1024,
+        }
+    }
+
+    /// Signing region (default: us-east-1)
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = region.to_string();
+        self
+    }
+
+    /// Key prefix for stored objects, e.g. `uploads/`
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Multipart part size (default: 8 MiB, at least 5 MiB)
+    pub fn part_size(mut self, size: usize) -> Self {
+        self.part_size = size.max(S3_MIN_PART_SIZE);
+        self
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        let key: Vec<String> = key.split('/').map(uri_encode).collect();
+        format!("{}/{}/{}", self.endpoint, uri_encode(&self.bucket), key.join("/"))
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> Result<String> {
+        let base = self.object_url(key);
+        let mut query: Vec<(String, String)> = query.iter().map(|(k, v)| (uri_encode(k), uri_encode(v))).collect();
+        query.sort();
+        let query = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+        let url = if query.is_empty() { base } else { format!("{}?{}", base, query) };
+        let parsed = url::Url::parse(&url).map_err(|e| RfError::InvalidParameter(format!("Invalid S3 endpoint: {}", e)))?;
+        let host = match parsed.port() {
+            Some(port) => format!("{}:{}", parsed.host_str().unwrap_or_default(), port),
+            None => parsed.host_str().unwrap_or_default().to_string(),
+        };
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = sha256::hash(&body);
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, parsed.path(), query, host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, sha256::hash(canonical.as_bytes()));
+        let mut key = sha256::hmac(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes());
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            key = sha256::hmac(&key, part.as_bytes());
+        }
+        let signature: String = sha256::hmac(&key, string_to_sign.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect();
+
+        let mut request = self.client.request(method, &url)
+            .header("x-amz-date", &amz_date)
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("authorization", format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.access_key, scope, signed_headers, signature
+            ))
+            .body(body);
+        if let Some(content_type) = content_type {
+            request = request.header("content-type", content_type);
+        }
+        let response = request.send().await
+            .map_err(|e| RfError::Network(format!("S3 request failed: {}", e)))?;
+        let status = response.status();
+        let etag = response.headers().get("etag").and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+        let text = response.text().await
+            .map_err(|e| RfError::Network(format!("S3 request failed: {}", e)))?;
+        if !status.is_success() {
+            return Err(RfError::Network(format!("S3 returned {}: {}", status, text)));
+        }
+        // Part uploads answer with the ETag header, everything else with a body
+        Ok(etag.unwrap_or(text))
+    }
+}
+
+#[async_trait]
+impl UploadSink for S3Sink {
+    async fn open(&self, meta: &UploadMeta) -> Result<Box<dyn UploadWriter>> {
+        Ok(Box::new(S3Writer {
+            sink: S3Sink {
+                client: self.client.clone(),
+                endpoint: self.endpoint.clone(),
+                bucket: self.bucket.clone(),
+                region: self.region.clone(),
+                access_key: self.access_key.clone(),
+                secret_key: self.secret_key.clone(),
+                prefix: self.prefix.clone(),
+                part_size: self.part_size,
+            },
+            key: format!("{}{}", self.prefix, meta.object_name()),
+            content_type: meta.content_type.clone(),
+            buffer: Vec::new(),
+            upload_id: None,
+            parts: Vec::new(),
+        }))
+    }
+
+    async fn remove(&self, location: &str) -> Result<()> {
+        self.send(reqwest::Method::DELETE, location, &[], Vec::new(), None).await.map(|_| ())
+    }
+}
+
+struct S3Writer {
+    sink: S3Sink,
+    key: String,
+    content_type: Option<String>,
+    buffer: Vec<u8>,
+    upload_id: Option<String>,
+    /// ETag per uploaded part, in part order
+    parts: Vec<String>,
+}
+
+impl S3Writer {
+    async fn upload_part(&mut self, data: Vec<u8>) -> Result<()> {
+        let upload_id = match &self.upload_id {
+            Some(id) => id.clone(),
+            None => {
+                let body = self.sink.send(reqwest::Method::POST, &self.key, &[("uploads", "")], Vec::new(),
+                    self.content_type.as_deref()).await?;
+                let id = xml_value(&body, "UploadId")
+                    .ok_or_else(|| RfError::Network("S3 did not return an upload id".to_string()))?;
+                self.upload_id = Some(id.clone());
+                id
+            }
+        };
+        let number = (self.parts.len() + 1).to_string();
+        let etag = self.sink.send(reqwest::Method::PUT, &self.key,
+            &[("partNumber", &number), ("uploadId", &upload_id)], data, None).await?;
+        self.parts.push(etag);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UploadWriter for S3Writer {
+    async fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        self.buffer.extend_from_slice(chunk);
+        while self.buffer.len() >= self.sink.part_size {
+            let rest = self.buffer.split_off(self.sink.part_size);
+            let part = std::mem::replace(&mut self.buffer, rest);
+            self.upload_part(part).await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<String> {
+        let data = std::mem::take(&mut self.buffer);
+        if self.upload_id.is_none() {
+            self.sink.send(reqwest::Method::PUT, &self.key, &[], data, self.content_type.as_deref()).await?;
+            return Ok(self.key);
+        }
+        if !data.is_empty() {
+            self.upload_part(data).await?;
+        }
+        let parts: String = self.parts.iter().enumerate()
+            .map(|(i, etag)| format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", i + 1, etag))
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", parts);
+        let upload_id = self.upload_id.clone().unwrap_or_default();
+        let response = self.sink.send(reqwest::Method::POST, &self.key, &[("uploadId", &upload_id)],
+            body.into_bytes(), Some("application/xml")).await?;
+        // S3 may report a failed completion with a 200 status
+        if response.contains("<Error>") {
+            return Err(RfError::Network(format!("S3 failed to complete upload: {}", response)));
+        }
+        Ok(self.key)
+    }
+
+    async fn abort(self: Box<Self>) -> Result<()> {
+        if let Some(upload_id) = &self.upload_id {
+            self.sink.send(reqwest::Method::DELETE, &self.key, &[("uploadId", upload_id)], Vec::new(), None).await?;
+        }
+        Ok(())
+    }
+}
+
+/// SigV4 URI encoding: everything but unreserved characters
+fn uri_encode(value: &str) -> String {
+    value.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+        _ => format!("%{:02X}", b),
+    }).collect()
+}
+
+fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+    Some(xml[start..end].to_string())
+}
+
+/// Progress of a streaming upload
+#[derive(Debug, Clone)]
+pub struct UploadProgress {
+    pub field_name: String,
+    pub filename: String,
+    /// Bytes received for the current file
+    pub file_bytes: u64,
+    /// Bytes received for all files of the request
+    pub total_bytes: u64,
+}
+
+/// A file stored by `StreamingUpload`
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+    pub field_name: String,
+    /// Client-supplied filename
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub size: u64,
+    /// Where the sink stored the file (path or object key)
+    pub location: String,
+}
+
+/// Result of `StreamingUpload::process`
+#[derive(Debug, Clone, Default)]
+pub struct UploadResult {
+    pub files: Vec<StoredFile>,
+    /// Non-file form fields
+    pub fields: HashMap<String, String>,
+}
+
+impl UploadResult {
+    /// First stored file of a field
+    pub fn file(&self, field_name: &str) -> Option<&StoredFile> {
+        self.files.iter().find(|file| file.field_name == field_name)
+    }
+}
+
+type ProgressCallback = Arc<dyn Fn(&UploadProgress) + Send + Sync>;
+
+/// Streams multipart file fields into an `UploadSink`
+pub struct StreamingUpload {
+    sink: Arc<dyn UploadSink>,
+    max_file_size: Option<u64>,
+    max_total_size: Option<u64>,
+    max_files: Option<usize>,
+    max_field_size: usize,
+    allowed_extensions: Option<Vec<String>>,
+    allowed_mime_types: Option<Vec<String>>,
+    on_progress: Option<ProgressCallback>,
+}
+
+impl StreamingUpload {
+    /// Create an upload storing files in the system temp directory
+    pub fn new() -> Self {
+        Self {
+            sink: Arc::new(LocalDirSink::temp()),
+            max_file_size: None,
+            max_total_size: None,
+            max_files: None,
+            max_field_size: 64 * 1024,
+            allowed_extensions: None,
+            allowed_mime_types: None,
+            on_progress: None,
+        }
+    }
+
+    /// Set the storage sink
+    pub fn sink(mut self, sink: impl UploadSink + 'static) -> Self {
+        self.sink = Arc::new(sink);
+        self
+    }
+
+    /// Set a shared storage sink
+    pub fn shared_sink(mut self, sink: Arc<dyn UploadSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    /// Maximum size of a single file in bytes
+    pub fn max_file_size(mut self, size: u64) -> Self {
+        self.max_file_size = Some(size);
+        self
+    }
+
+    /// Maximum size of all files of a request in bytes
+    pub fn max_total_size(mut self, size: u64) -> Self {
+        self.max_total_size = Some(size);
+        self
+    }
+
+    /// Maximum number of files per request
+    pub fn max_files(mut self, count: usize) -> Self {
+        self.max_files = Some(count);
+        self
+    }
+
+    /// Maximum size of a non-file form field (default: 64 KiB)
+    pub fn max_field_size(mut self, size: usize) -> Self {
+        self.max_field_size = size;
+        self
+    }
+
+    /// Allowed filename extensions (case-insensitive)
+    pub fn allowed_extensions(mut self, extensions: &[&str]) -> Self {
+        self.allowed_extensions = Some(extensions.iter().map(|e| e.trim_start_matches('.').to_lowercase()).collect());
+        self
+    }
+
+    /// Allowed MIME types, checked against the file's magic bytes
+    ///
+    /// Content that matches no known signature is rejected unless
+    /// `application/octet-stream` is allowed.
+    pub fn allowed_mime_types(mut self, mime_types: &[&str]) -> Self {
+        self.allowed_mime_types = Some(mime_types.iter().map(|m| m.to_lowercase()).collect());
+        self
+    }
+
+    /// Call `callback` as file data arrives
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&UploadProgress) + Send + Sync + 'static,
+    {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Stream all fields of a multipart request
+    ///
+    /// On any error, files already stored for this request are removed.
+    pub async fn process(&self, mut multipart: Multipart) -> Result<UploadResult> {
+        let mut result = UploadResult::default();
+        let mut total = 0u64;
+        let outcome: Result<()> = async {
+            while let Some(field) = multipart.next_field().await
+                .map_err(|e| RfError::Network(format!("Failed to read multipart field: {}", e)))? {
+                let field_name = field.name().unwrap_or_default().to_string();
+                let Some(filename) = field.file_name().map(|name| name.to_string()) else {
+                    let value = self.read_field(field).await?;
+                    result.fields.insert(field_name, value);
+                    continue;
+                };
+                if self.max_files.is_some_and(|max| result.files.len() >= max) {
+                    return Err(RfError::Validation(format!("Too many files, at most {} allowed", result.files.len())));
+                }
+                let file = self.store(field, field_name, filename, &mut total).await?;
+                result.files.push(file);
+            }
+            Ok(())
+        }.await;
+
+        if let Err(e) = outcome {
+            for file in &result.files {
+                if let Err(cleanup) = self.sink.remove(&file.location).await {
+                    tracing::warn!("Failed to remove upload {}: {}", file.location, cleanup);
+                }
+            }
+            return Err(e);
+        }
+        Ok(result)
+    }
+
+    async fn read_field(&self, mut field: Field<'_>) -> Result<String> {
+        let mut value = BufferPool::global().get();
+        while let Some(chunk) = field.chunk().await
+            .map_err(|e| RfError::Network(format!("Failed to read field data: {}", e)))? {
+            value.extend_from_slice(&chunk);
+            if value.len() > self.max_field_size {
+                return Err(RfError::Validation(format!("Form field exceeds {} bytes", self.max_field_size)));
+            }
+        }
+        std::str::from_utf8(&value)
+            .map(str::to_string)
+            .map_err(|_| RfError::Validation("Form field is not valid UTF-8".to_string()))
+    }
+
+    async fn store(&self, mut field: Field<'_>, field_name: String, filename: String, total: &mut u64) -> Result<StoredFile> {
+        let mut meta = UploadMeta {
+            field_name,
+            filename,
+            content_type: field.content_type().map(|t| t.to_string()),
+        };
+        if let Some(allowed) = &self.allowed_extensions {
+            match meta.extension() {
+                Some(ext) if allowed.contains(&ext) => {}
+                Some(ext) => return Err(RfError::Validation(format!("File extension '{}' is not allowed", ext))),
+                None => return Err(RfError::Validation("File must have an extension".to_string())),
+            }
+        }
+
+        // Hold back the first bytes until the content type is known
+        let mut head = BufferPool::global().get_with_capacity(SNIFF_LEN);
+        let mut size = 0u64;
+        let mut writer: Option<Box<dyn UploadWriter>> = None;
+        let outcome: Result<()> = async {
+            while let Some(chunk) = field.chunk().await
+                .map_err(|e| RfError::Network(format!("Failed to read field data: {}", e)))? {
+                size += chunk.len() as u64;
+                *total += chunk.len() as u64;
+                if let Some(max) = self.max_file_size.filter(|max| size > *max) {
+                    return Err(RfError::Validation(format!("File '{}' exceeds maximum allowed size {}", meta.filename, max)));
+                }
+                if let Some(max) = self.max_total_size.filter(|max| *total > *max) {
+                    return Err(RfError::Validation(format!("Upload exceeds maximum allowed size {}", max)));
+                }
+                match writer.as_mut() {
+                    Some(writer) => writer.write(&chunk).await?,
+                    None => {
+                        head.extend_from_slice(&chunk);
+                        if head.len() >= SNIFF_LEN {
+                            writer = Some(self.open(&mut meta, &head).await?);
+                            head.clear();
+                        }
+                    }
+                }
+                if let Some(callback) = &self.on_progress {
+                    callback(&UploadProgress {
+                        field_name: meta.field_name.clone(),
+                        filename: meta.filename.clone(),
+                        file_bytes: size,
+                        total_bytes: *total,
+                    });
+                }
+            }
+            if writer.is_none() {
+                writer = Some(self.open(&mut meta, &head).await?);
+            }
+            Ok(())
+        }.await;
+
+        match (outcome, writer) {
+            (Ok(()), Some(writer)) => Ok(StoredFile {
+                location: writer.finish().await?,
+                field_name: meta.field_name,
+                filename: meta.filename,
+                content_type: meta.content_type,
+                size,
+            }),
+            (Err(e), Some(writer)) => {
+                if let Err(cleanup) = writer.abort().await {
+                    tracing::warn!("Failed to abort upload of {}: {}", meta.filename, cleanup);
+                }
+                Err(e)
+            }
+            (Err(e), None) => Err(e),
+            (Ok(()), None) => unreachable!("writer is opened once the field ends"),
+        }
+    }
+
+    /// Validate the sniffed type, then open the sink and write `head`
+    async fn open(&self, meta: &mut UploadMeta, head: &[u8]) -> Result<Box<dyn UploadWriter>> {
+        let sniffed = sniff_mime(head);
+        if let Some(allowed) = &self.allowed_mime_types {
+            let detected = sniffed.unwrap_or("application/octet-stream");
+            if !allowed.iter().any(|mime| mime == detected) {
+                return Err(RfError::Validation(format!(
+                    "File '{}' content ({}) is not an allowed type", meta.filename, detected
+                )));
+            }
+        }
+        if let Some(mime) = sniffed {
+            meta.content_type = Some(mime.to_string());
+        }
+        let mut writer = self.sink.open(meta).await?;
+        if !head.is_empty() {
+            if let Err(e) = writer.write(head).await {
+                let _ = writer.abort().await;
+                return Err(e);
+            }
+        }
+        Ok(writer)
+    }
+}
+
+impl Default for StreamingUpload {
+    fn default() -> Self {
+        Self::new()
+    }
+}
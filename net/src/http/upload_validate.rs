@@ -0,0 +1,367 @@
+//! # upload_validate
+//!
+//! upload_validate 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Content validation of uploaded files
+//!
+//! `UploadValidator` checks what a file actually is rather than what the
+//! client claims: the type is sniffed from the magic bytes, the extension
+//! must agree with it, image dimensions are read from the header (so a tiny
+//! file announcing a 50000×50000 canvas is rejected before anything decodes
+//! it) and an optional `VirusScanner` sees the content last.
+//!
+//! Failures are collected per form field into `ValidationErrors`, so the
+//! handler answers with the same 400 envelope as `Request::parse`:
+//!
+//! ```rust,ignore
+//! use rf_net::http::{extract_files, UploadError, UploadValidator};
+//!
+//! async fn avatar(multipart: Multipart) -> Result<Response, UploadError> {
+//!     let files = extract_files(multipart).await?;
+//!     UploadValidator::new()
+//!         .max_size(5 * 1024 * 1024)
+//!         .allowed_types(&["image/png", "image/jpeg", "image/webp"])
+//!         .max_dimensions(4096, 4096)
+//!         .max_pixels(12_000_000)
+//!         .scanner(clamd)
+//!         .validate(&files)
+//!         .await?;
+//!     // {"code":400,"message":"avatar: ...","data":null,"errors":[{"field":"avatar","rule":"dimensions",...}]}
+//!     ...
+//! }
+//! ```
+
+use super::envelope::ApiError;
+use super::parse::ParseError;
+use super::upload::UploadFile;
+use super::upload_stream::sniff_mime;
+use async_trait::async_trait;
+use axum::response::{IntoResponse, Response as AxumResponse};
+use rf_errors::{Result, RfError};
+use rf_util::valid::{ValidationError, ValidationErrors};
+use std::sync::Arc;
+
+/// MIME type reported for content without a known signature
+const UNKNOWN_MIME: &str = "application/octet-stream";
+
+/// Result of a virus scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    /// Infected, with the name of the detected signature
+    Infected(String),
+}
+
+/// Antivirus hook called with the content of each file that passed the other checks
+///
+/// Closures `Fn(&UploadFile) -> Result<ScanVerdict>` implement it. A scanner
+/// error fails the validation with that error instead of letting the file through.
+#[async_trait]
+pub trait VirusScanner: Send + Sync {
+    async fn scan(&self, file: &UploadFile) -> Result<ScanVerdict>;
+}
+
+#[async_trait]
+impl<F> VirusScanner for F
+where
+    F: Fn(&UploadFile) -> Result<ScanVerdict> + Send + Sync,
+{
+    async fn scan(&self, file: &UploadFile) -> Result<ScanVerdict> {
+        self(file)
+    }
+}
+
+/// Error of `UploadValidator::validate`
+///
+/// Invalid files answer like `ParseError::Invalid` (400 with the per-field
+/// `errors`), other errors like `ApiError`.
+#[derive(Debug)]
+pub enum UploadError {
+    /// Some files failed the checks
+    Invalid(ValidationErrors),
+    /// The upload could not be read or the virus scanner failed
+    Failed(RfError),
+}
+
+impl UploadError {
+    /// Validation errors, if files were rejected
+    pub fn errors(&self) -> Option<&ValidationErrors> {
+        match self {
+            Self::Invalid(errors) => Some(errors),
+            Self::Failed(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for UploadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(errors) => write!(f, "{}", errors),
+            Self::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {}
+
+impl From<RfError> for UploadError {
+    fn from(e: RfError) -> Self {
+        Self::Failed(e)
+    }
+}
+
+impl From<UploadError> for RfError {
+    fn from(e: UploadError) -> Self {
+        match e {
+            UploadError::Invalid(errors) => errors.into(),
+            UploadError::Failed(e) => e,
+        }
+    }
+}
+
+impl IntoResponse for UploadError {
+    fn into_response(self) -> AxumResponse {
+        match self {
+            Self::Invalid(errors) => ParseError::Invalid(errors).into_response(),
+            Self::Failed(e) => ApiError(e).into_response(),
+        }
+    }
+}
+
+/// Validates uploaded files by content, see the module documentation
+#[derive(Clone)]
+pub struct UploadValidator {
+    max_size: Option<u64>,
+    allowed_types: Option<Vec<String>>,
+    check_extension: bool,
+    max_dimensions: Option<(u32, u32)>,
+    min_dimensions: Option<(u32, u32)>,
+    max_pixels: Option<u64>,
+    scanner: Option<Arc<dyn VirusScanner>>,
+}
+
+impl UploadValidator {
+    /// A validator that checks that extensions agree with the content
+    pub fn new() -> Self {
+        Self {
+            max_size: None,
+            allowed_types: None,
+            check_extension: true,
+            max_dimensions: None,
+            min_dimensions: None,
+            max_pixels: None,
+            scanner: None,
+        }
+    }
+
+    /// Maximum file size in bytes
+    pub fn max_size(mut self, size: u64) -> Self {
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Allowed sniffed MIME types
+    ///
+    /// Content without a known signature counts as `application/octet-stream`.
+    pub fn allowed_types(mut self, mime_types: &[&str]) -> Self {
+        self.allowed_types = Some(mime_types.iter().map(|m| m.to_lowercase()).collect());
+        self
+    }
+
+    /// Whether a known extension must match the sniffed type (default `true`)
+    ///
+    /// Stops `payload.exe` renamed to `photo.png`. Unknown extensions are not checked.
+    pub fn check_extension(mut self, check: bool) -> Self {
+        self.check_extension = check;
+        self
+    }
+
+    /// Maximum image width and height in pixels
+    pub fn max_dimensions(mut self, width: u32, height: u32) -> Self {
+        self.max_dimensions = Some((width, height));
+        self
+    }
+
+    /// Minimum image width and height in pixels
+    pub fn min_dimensions(mut self, width: u32, height: u32) -> Self {
+        self.min_dimensions = Some((width, height));
+        self
+    }
+
+    /// Maximum image width × height, the decoded size that matters for memory
+    pub fn max_pixels(mut self, pixels: u64) -> Self {
+        self.max_pixels = Some(pixels);
+        self
+    }
+
+    /// Scan files that passed every other check
+    pub fn scanner(mut self, scanner: impl VirusScanner + 'static) -> Self {
+        self.scanner = Some(Arc::new(scanner));
+        self
+    }
+
+    /// Validate every file, collecting failures by form field
+    ///
+    /// # Errors
+    ///
+    /// `UploadError::Invalid` with one error per failed check (rules
+    /// `max_size`, `file_type`, `extension`, `image`, `dimensions`, `pixels`
+    /// and `virus`), `UploadError::Failed` when the scanner fails
+    pub async fn validate(&self, files: &[UploadFile]) -> std::result::Result<(), UploadError> {
+        let mut errors = ValidationErrors::new();
+        for file in files {
+            for (rule, message) in self.check_file(file).await? {
+                errors.add(ValidationError::new(&file.field_name, rule, &message));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(UploadError::Invalid(errors))
+        }
+    }
+
+    /// Failed rules of one file; the scanner only sees files without failures
+    async fn check_file(&self, file: &UploadFile) -> Result<Vec<(&'static str, String)>> {
+        let mut failures = Vec::new();
+        let name = &file.filename;
+        if let Some(max) = self.max_size.filter(|max| file.size > *max) {
+            failures.push(("max_size", format!("File '{}' exceeds maximum allowed size {}", name, max)));
+        }
+
+        let detected = sniff_mime(&file.data).unwrap_or(UNKNOWN_MIME);
+        if let Some(allowed) = &self.allowed_types {
+            if !allowed.iter().any(|mime| mime == detected) {
+                failures.push(("file_type", format!("File '{}' content ({}) is not an allowed type", name, detected)));
+            }
+        }
+        if self.check_extension {
+            let extension = file.extension().map(str::to_lowercase);
+            if let Some(expected) = extension.as_deref().and_then(extension_mime) {
+                if expected != detected {
+                    failures.push((
+                        "extension",
+                        format!("File '{}' content ({}) does not match its extension", name, detected),
+                    ));
+                }
+            }
+        }
+
+        let limits = self.max_dimensions.is_some() || self.min_dimensions.is_some() || self.max_pixels.is_some();
+        if limits && detected.starts_with("image/") {
+            match image_dimensions(&file.data) {
+                None => failures.push(("image", format!("Image '{}' has an unreadable header", name))),
+                Some((width, height)) => {
+                    let too_large = self.max_dimensions.is_some_and(|(w, h)| width > w || height > h);
+                    let too_small = self.min_dimensions.is_some_and(|(w, h)| width < w || height < h);
+                    if too_large || too_small {
+                        failures.push(("dimensions", format!("Image '{}' is {}x{} pixels", name, width, height)));
+                    }
+                    if let Some(max) = self.max_pixels.filter(|max| u64::from(width) * u64::from(height) > *max) {
+                        failures.push((
+                            "pixels",
+                            format!("Image '{}' has {} pixels, at most {} allowed", name, u64::from(width) * u64::from(height), max),
+                        ));
+                    }
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            if let Some(scanner) = &self.scanner {
+                if let ScanVerdict::Infected(signature) = scanner.scan(file).await? {
+                    tracing::warn!("Rejected infected upload '{}' ({})", name, signature);
+                    failures.push(("virus", format!("File '{}' is infected ({})", name, signature)));
+                }
+            }
+        }
+        Ok(failures)
+    }
+}
+
+impl Default for UploadValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Type sniffed for files with a well-known extension
+fn extension_mime(extension: &str) -> Option<&'static str> {
+    let mime = match extension {
+        "png" => "image/png",
+        "jpg" | "jpeg" | "jpe" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        "zip" | "docx" | "xlsx" | "pptx" | "jar" | "apk" => "application/zip",
+        "gz" | "tgz" => "application/gzip",
+        "7z" => "application/x-7z-compressed",
+        "rar" => "application/vnd.rar",
+        "mp4" | "m4v" | "m4a" | "mov" => "video/mp4",
+        "webm" | "mkv" => "video/webm",
+        "mp3" => "audio/mpeg",
+        "ogg" | "oga" | "ogv" | "opus" => "audio/ogg",
+        "wav" => "audio/wav",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Width and height of a PNG, JPEG, GIF, BMP or WebP image, read from its header
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let u16_be = |at: usize| bytes.get(at..at + 2).map(|b| u32::from(u16::from_be_bytes([b[0], b[1]])));
+    let u16_le = |at: usize| bytes.get(at..at + 2).map(|b| u32::from(u16::from_le_bytes([b[0], b[1]])));
+    let u24_le = |at: usize| bytes.get(at..at + 3).map(|b| u32::from_le_bytes([b[0], b[1], b[2], 0]));
+    let u32_be = |at: usize| bytes.get(at..at + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    let i32_le = |at: usize| bytes.get(at..at + 4).map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+
+    match sniff_mime(bytes)? {
+        "image/png" if bytes.get(12..16) == Some(b"IHDR") => Some((u32_be(16)?, u32_be(20)?)),
+        "image/gif" => Some((u16_le(6)?, u16_le(8)?)),
+        "image/bmp" => {
+            if u24_le(14)? == 12 {
+                // BITMAPCOREHEADER
+                Some((u16_le(18)?, u16_le(20)?))
+            } else {
+                // Negative heights mark top-down bitmaps
+                Some((i32_le(18)?.unsigned_abs(), i32_le(22)?.unsigned_abs()))
+            }
+        }
+        "image/webp" => match bytes.get(12..16)? {
+            b"VP8 " if bytes.get(23..26) == Some(b"\x9d\x01\x2a") => Some((u16_le(26)? & 0x3fff, u16_le(28)? & 0x3fff)),
+            b"VP8L" if bytes.get(20) == Some(&0x2f) => {
+                let bits = u24_le(21)? | (u32::from(*bytes.get(24)?) << 24);
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }
+            b"VP8X" => Some((u24_le(24)? + 1, u24_le(27)? + 1)),
+            _ => None,
+        },
+        "image/jpeg" => {
+            let mut at = 2;
+            loop {
+                if *bytes.get(at)? != 0xff {
+                    return None;
+                }
+                let marker = *bytes.get(at + 1)?;
+                match marker {
+                    // Fill bytes
+                    0xff => at += 1,
+                    // Markers without a length
+                    0x01 | 0xd0..=0xd8 => at += 2,
+                    // Start of frame, except DHT, JPG and DAC
+                    0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                        return Some((u16_be(at + 7)?, u16_be(at + 5)?));
+                    }
+                    _ => at += 2 + u16_be(at + 2)? as usize,
+                }
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,356 @@
+//! # user_agent
+//!
+//! user_agent 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! User agent parsing
+//!
+//! Detects browser, operating system, device type and bots from the
+//! `User-Agent` header. Rules are data-driven: the built-in ruleset lives
+//! in `data/user_agent_rules.yaml` and can be replaced at runtime with
+//! `UaParser::reload` without restarting the server.
+//!
+//! ```ignore
+//! let parser = Arc::new(UaParser::from_file("ua_rules.yaml")?);
+//! let app = Router::new()
+//!     .route("/", get(index))
+//!     .layer(axum::middleware::from_fn(move |req: Request, next: Next| {
+//!         let parser = parser.clone();
+//!         async move { user_agent_middleware(parser, req, next).await.map_err(|e| e.to_string()) }
+//!     }));
+//! ```
+
+use axum::extract::Request;
+use axum::response::Response;
+use regex::{Regex, RegexSet};
+use rf_errors::{Result, RfError};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, LazyLock, RwLock};
+use tracing::Instrument;
+
+const DEFAULT_RULES: &str = include_str!("data/user_agent_rules.yaml");
+
+static GLOBAL_PARSER: LazyLock<UaParser> = LazyLock::new(UaParser::new);
+
+/// Device category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceType {
+    Desktop,
+    Mobile,
+    Tablet,
+    Tv,
+    Console,
+    Bot,
+    Unknown,
+}
+
+impl DeviceType {
+    /// Lowercase name, suitable as a metric label
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeviceType::Desktop => "desktop",
+            DeviceType::Mobile => "mobile",
+            DeviceType::Tablet => "tablet",
+            DeviceType::Tv => "tv",
+            DeviceType::Console => "console",
+            DeviceType::Bot => "bot",
+            DeviceType::Unknown => "unknown",
+        }
+    }
+}
+
+/// Name and version of a browser, OS or bot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UaProduct {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+impl UaProduct {
+    /// Major version number, if any
+    pub fn major_version(&self) -> Option<u32> {
+        self.version.as_deref()?.split('.').next()?.parse().ok()
+    }
+}
+
+/// Parsed user agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserAgent {
+    pub browser: Option<UaProduct>,
+    pub os: Option<UaProduct>,
+    pub device: DeviceType,
+    /// Crawler, script or headless client
+    pub bot: Option<UaProduct>,
+}
+
+impl UserAgent {
+    /// Parse with the global parser
+    pub fn parse(user_agent: &str) -> Self {
+        UaParser::global().parse(user_agent)
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.bot.is_some()
+    }
+
+    pub fn is_mobile(&self) -> bool {
+        self.device == DeviceType::Mobile
+    }
+
+    pub fn is_tablet(&self) -> bool {
+        self.device == DeviceType::Tablet
+    }
+
+    pub fn is_desktop(&self) -> bool {
+        self.device == DeviceType::Desktop
+    }
+
+    /// Browser name or `unknown`
+    pub fn browser_name(&self) -> &str {
+        self.browser.as_ref().map(|b| b.name.as_str()).unwrap_or("unknown")
+    }
+
+    /// OS name or `unknown`
+    pub fn os_name(&self) -> &str {
+        self.os.as_ref().map(|o| o.name.as_str()).unwrap_or("unknown")
+    }
+}
+
+/// A name/version rule in a ruleset file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UaRule {
+    pub regex: String,
+    /// Name template, may reference capture groups as `$1`..`$9`
+    pub name: String,
+    /// Version template
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// A device rule in a ruleset file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UaDeviceRule {
+    pub regex: String,
+    pub device: DeviceType,
+}
+
+/// User agent ruleset, loadable from YAML or JSON
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UaRuleset {
+    #[serde(default)]
+    pub bots: Vec<UaRule>,
+    #[serde(default)]
+    pub browsers: Vec<UaRule>,
+    #[serde(default)]
+    pub os: Vec<UaRule>,
+    #[serde(default)]
+    pub devices: Vec<UaDeviceRule>,
+}
+
+impl UaRuleset {
+    /// The built-in ruleset
+    pub fn builtin() -> Self {
+        Self::from_str(DEFAULT_RULES).expect("built-in user agent rules are valid")
+    }
+
+    /// Parse a ruleset from YAML (JSON is accepted as well)
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(data: &str) -> Result<Self> {
+        serde_yaml::from_str(data)
+            .map_err(|e| RfError::Config(format!("Invalid user agent ruleset: {}", e)))
+    }
+
+    /// Load a ruleset file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Append the rules of another ruleset (checked after these rules)
+    pub fn extend(&mut self, other: UaRuleset) {
+        self.bots.extend(other.bots);
+        self.browsers.extend(other.browsers);
+        self.os.extend(other.os);
+        self.devices.extend(other.devices);
+    }
+}
+
+/// Rules of one section compiled into a single `RegexSet`
+struct CompiledGroup<T> {
+    set: RegexSet,
+    rules: Vec<(Regex, T)>,
+}
+
+impl<T> CompiledGroup<T> {
+    fn compile(rules: Vec<(String, T)>) -> Result<Self> {
+        let set = RegexSet::new(rules.iter().map(|(regex, _)| regex))
+            .map_err(|e| RfError::Config(format!("Invalid user agent rule: {}", e)))?;
+        let rules = rules
+            .into_iter()
+            .map(|(regex, value)| {
+                Regex::new(&regex)
+                    .map(|regex| (regex, value))
+                    .map_err(|e| RfError::Config(format!("Invalid user agent rule: {}", e)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { set, rules })
+    }
+
+    /// First matching rule in declaration order
+    fn first_match(&self, input: &str) -> Option<&(Regex, T)> {
+        self.set.matches(input).iter().next().map(|i| &self.rules[i])
+    }
+}
+
+struct CompiledRules {
+    bots: CompiledGroup<(String, Option<String>)>,
+    browsers: CompiledGroup<(String, Option<String>)>,
+    os: CompiledGroup<(String, Option<String>)>,
+    devices: CompiledGroup<DeviceType>,
+}
+
+impl CompiledRules {
+    fn compile(ruleset: UaRuleset) -> Result<Self> {
+        let products = |rules: Vec<UaRule>| {
+            CompiledGroup::compile(rules.into_iter().map(|r| (r.regex, (r.name, r.version))).collect())
+        };
+        Ok(Self {
+            bots: products(ruleset.bots)?,
+            browsers: products(ruleset.browsers)?,
+            os: products(ruleset.os)?,
+            devices: CompiledGroup::compile(ruleset.devices.into_iter().map(|r| (r.regex, r.device)).collect())?,
+        })
+    }
+
+    fn product(group: &CompiledGroup<(String, Option<String>)>, input: &str) -> Option<UaProduct> {
+        let (regex, (name, version)) = group.first_match(input)?;
+        let captures = regex.captures(input)?;
+        let name = expand(name, &captures);
+        if name.is_empty() {
+            return None;
+        }
+        let version = version.as_deref().map(|v| expand(v, &captures)).filter(|v| !v.is_empty());
+        Some(UaProduct { name, version })
+    }
+}
+
+/// Replace `$1`..`$9` with capture groups; missing groups become empty
+fn expand(template: &str, captures: &regex::Captures<'_>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '$' {
+            if let Some(digit) = chars.peek().and_then(|d| d.to_digit(10)) {
+                chars.next();
+                out.push_str(captures.get(digit as usize).map_or("", |m| m.as_str()));
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out.trim().trim_matches('.').to_string()
+}
+
+/// User agent parser with a hot-reloadable compiled ruleset
+pub struct UaParser {
+    rules: RwLock<Arc<CompiledRules>>,
+}
+
+impl UaParser {
+    /// Create a parser with the built-in ruleset
+    pub fn new() -> Self {
+        Self::with_ruleset(UaRuleset::builtin()).expect("built-in user agent rules compile")
+    }
+
+    /// Create a parser with a custom ruleset
+    pub fn with_ruleset(ruleset: UaRuleset) -> Result<Self> {
+        Ok(Self {
+            rules: RwLock::new(Arc::new(CompiledRules::compile(ruleset)?)),
+        })
+    }
+
+    /// Create a parser from a ruleset file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_ruleset(UaRuleset::from_file(path)?)
+    }
+
+    /// The shared parser used by `Request::user_agent`
+    pub fn global() -> &'static UaParser {
+        &GLOBAL_PARSER
+    }
+
+    /// Replace the ruleset; on error the current rules stay active
+    pub fn update(&self, ruleset: UaRuleset) -> Result<()> {
+        let compiled = Arc::new(CompiledRules::compile(ruleset)?);
+        *self.rules.write().unwrap_or_else(|e| e.into_inner()) = compiled;
+        Ok(())
+    }
+
+    /// Reload the ruleset from a file
+    pub fn reload(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.update(UaRuleset::from_file(path)?)
+    }
+
+    /// Parse a user agent string
+    pub fn parse(&self, user_agent: &str) -> UserAgent {
+        let rules = self.rules.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let user_agent = user_agent.trim();
+
+        let bot = CompiledRules::product(&rules.bots, user_agent);
+        let browser = CompiledRules::product(&rules.browsers, user_agent);
+        let os = CompiledRules::product(&rules.os, user_agent);
+        let device = if bot.is_some() {
+            DeviceType::Bot
+        } else {
+            rules
+                .devices
+                .first_match(user_agent)
+                .map(|(_, device)| *device)
+                .unwrap_or(DeviceType::Unknown)
+        };
+        UserAgent { browser, os, device, bot }
+    }
+}
+
+impl Default for UaParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse the user agent, store it in the request extensions, record a
+/// per-client metric and tag the request span with client fields
+pub async fn user_agent_middleware(
+    parser: Arc<UaParser>,
+    mut request: Request,
+    next: axum::middleware::Next,
+) -> std::result::Result<Response, axum::Error> {
+    let header = request
+        .headers()
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    let user_agent = parser.parse(header);
+
+    metrics::counter!(
+        "http_requests_by_client_total",
+        "device" => user_agent.device.as_str(),
+        "os" => user_agent.os_name().to_string(),
+        "browser" => user_agent.browser_name().to_string(),
+        "bot" => if user_agent.is_bot() { "true" } else { "false" }
+    )
+    .increment(1);
+
+    let span = tracing::info_span!(
+        "client",
+        device = user_agent.device.as_str(),
+        os = user_agent.os_name(),
+        browser = user_agent.browser_name(),
+        bot = user_agent.is_bot()
+    );
+    request.extensions_mut().insert(user_agent);
+    Ok(next.run(request).instrument(span).await)
+}
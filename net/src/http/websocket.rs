@@ -6,11 +6,22 @@
 //! @date 2026-01-06
 
 //! WebSocket support
+//!
+//! Besides the plain upgrade helpers, `WebSocketHub` tracks connections for
+//! server push: named rooms, broadcast, per-connection metadata, ping/pong
+//! heartbeats that evict stale peers, and a close frame to every client on
+//! server shutdown.
 
-use axum::extract::ws::{Message, WebSocket};
+use axum::extract::ws::{CloseFrame, Message, WebSocket};
 use axum::extract::WebSocketUpgrade;
 use axum::response::Response;
-use futures_util::StreamExt;
+use futures_util::{SinkExt, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
 
 /// WebSocket handler function type
 pub type WebSocketHandler = Box<dyn Fn(WebSocket) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> + Send + Sync>;
@@ -42,3 +53,439 @@ pub async fn echo_handler(mut socket: WebSocket) {
         }
     }
 }
+
+/// Close code sent to clients when the server shuts down (Going Away)
+pub const CLOSE_GOING_AWAY: u16 = 1001;
+
+/// Connection identifier, unique within a hub
+pub type ConnectionId = u64;
+
+struct Entry {
+    sender: mpsc::Sender<Message>,
+    closed: watch::Sender<bool>,
+    metadata: HashMap<String, String>,
+    rooms: HashSet<String>,
+}
+
+struct HubInner {
+    connections: RwLock<HashMap<ConnectionId, Entry>>,
+    rooms: RwLock<HashMap<String, HashSet<ConnectionId>>>,
+    next_id: AtomicU64,
+    shutdown: watch::Sender<bool>,
+}
+
+/// Registry of live WebSocket connections with rooms and heartbeats
+///
+/// Cheap to clone; clones share the same connections.
+///
+/// ```rust,no_run
+/// use axum::extract::WebSocketUpgrade;
+/// use axum::routing::get;
+/// use rf_net::http::WebSocketHub;
+///
+/// let hub = WebSocketHub::new();
+/// let chat = hub.clone();
+/// let app: axum::Router = axum::Router::new().route("/chat", get(move |ws: WebSocketUpgrade| {
+///     let hub = chat.clone();
+///     async move {
+///         ws.on_upgrade(move |socket| async move {
+///             let (conn, mut inbound) = hub.accept(socket);
+///             conn.join("lobby");
+///             while let Some(message) = inbound.recv().await {
+///                 hub.broadcast("lobby", message);
+///             }
+///         })
+///     }
+/// }));
+/// ```
+#[derive(Clone)]
+pub struct WebSocketHub {
+    inner: Arc<HubInner>,
+    ping_interval: Duration,
+    stale_after: Duration,
+    buffer: usize,
+}
+
+impl WebSocketHub {
+    /// Create a hub pinging every 30s and evicting peers silent for 75s
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(HubInner {
+                connections: RwLock::new(HashMap::new()),
+                rooms: RwLock::new(HashMap::new()),
+                next_id: AtomicU64::new(1),
+                shutdown: watch::channel(false).0,
+            }),
+            ping_interval: Duration::from_secs(30),
+            stale_after: Duration::from_secs(75),
+            buffer: 64,
+        }
+    }
+
+    /// Interval between pings (default: 30s)
+    pub fn ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Drop connections that send no frame at all, pongs included, for this long (default: 75s)
+    pub fn stale_after(mut self, timeout: Duration) -> Self {
+        self.stale_after = timeout;
+        self
+    }
+
+    /// Per-connection outbound queue size (default: 64)
+    ///
+    /// Broadcasts to a connection whose queue is full are dropped for that
+    /// connection rather than slowing down the others.
+    pub fn buffer(mut self, buffer: usize) -> Self {
+        self.buffer = buffer.max(1);
+        self
+    }
+
+    /// Register an upgraded socket
+    ///
+    /// Returns the connection handle and a receiver for the text and binary
+    /// messages the client sends. Control frames are handled by the hub.
+    /// The receiver ends when the connection closes.
+    pub fn accept(&self, socket: WebSocket) -> (Connection, mpsc::Receiver<Message>) {
+        self.accept_with(socket, HashMap::new())
+    }
+
+    /// Register an upgraded socket with initial metadata
+    pub fn accept_with(&self, socket: WebSocket, metadata: HashMap<String, String>) -> (Connection, mpsc::Receiver<Message>) {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, outbound) = mpsc::channel(self.buffer);
+        let (inbound_tx, inbound) = mpsc::channel(self.buffer);
+        let (closed, _) = watch::channel(false);
+        let connection = Connection {
+            id,
+            hub: self.clone(),
+            sender: sender.clone(),
+            closed: closed.clone(),
+        };
+        self.inner.connections.write().unwrap().insert(id, Entry {
+            sender,
+            closed: closed.clone(),
+            metadata,
+            rooms: HashSet::new(),
+        });
+        let hub = self.clone();
+        tokio::spawn(async move {
+            hub.run(id, socket, outbound, inbound_tx, closed).await;
+            hub.remove(id);
+        });
+        (connection, inbound)
+    }
+
+    /// Register a socket and pass each client message to `handler`
+    ///
+    /// Returns when the connection closes.
+    pub async fn serve<H, Fut>(&self, socket: WebSocket, handler: H)
+    where
+        H: Fn(Connection, Message) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let (connection, mut inbound) = self.accept(socket);
+        while let Some(message) = inbound.recv().await {
+            handler(connection.clone(), message).await;
+        }
+    }
+
+    /// Upgrade handler registering every connection with this hub
+    pub fn upgrade<H, Fut>(&self, ws: WebSocketUpgrade, handler: H) -> Response
+    where
+        H: Fn(Connection, Message) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let hub = self.clone();
+        ws.on_upgrade(move |socket| async move { hub.serve(socket, handler).await })
+    }
+
+    async fn run(
+        &self,
+        id: ConnectionId,
+        socket: WebSocket,
+        mut outbound: mpsc::Receiver<Message>,
+        inbound: mpsc::Sender<Message>,
+        closed: watch::Sender<bool>,
+    ) {
+        let (mut sink, mut stream) = socket.split();
+        let started = Instant::now();
+        let last_seen = Arc::new(AtomicU64::new(0));
+        let mut shutdown = self.inner.shutdown.subscribe();
+        let mut closed_rx = closed.subscribe();
+
+        let reader = {
+            let last_seen = last_seen.clone();
+            let closed = closed.clone();
+            async move {
+                while let Some(message) = stream.next().await {
+                    last_seen.store(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+                    match message {
+                        Ok(message @ (Message::Text(_) | Message::Binary(_))) => {
+                            if inbound.send(message).await.is_err() {
+                                // Nobody reads this connection any more
+                                break;
+                            }
+                        }
+                        Ok(Message::Close(_)) => break,
+                        Ok(_) => {}
+                        Err(e) => {
+                            tracing::debug!("WebSocket {} error: {}", id, e);
+                            break;
+                        }
+                    }
+                }
+                closed.send_replace(true);
+            }
+        };
+
+        let writer = async move {
+            let mut heartbeat = tokio::time::interval(self.ping_interval);
+            heartbeat.tick().await;
+            let close = loop {
+                tokio::select! {
+                    message = outbound.recv() => match message {
+                        Some(message) => {
+                            if sink.send(message).await.is_err() {
+                                break None;
+                            }
+                        }
+                        None => break None,
+                    },
+                    _ = heartbeat.tick() => {
+                        let silent = started.elapsed().saturating_sub(Duration::from_millis(last_seen.load(Ordering::Relaxed)));
+                        if silent > self.stale_after {
+                            tracing::debug!("WebSocket {} silent for {:?}, evicting", id, silent);
+                            break None;
+                        }
+                        if sink.send(Message::Ping(Default::default())).await.is_err() {
+                            break None;
+                        }
+                    }
+                    _ = signaled(&mut shutdown) => {
+                        break Some(CloseFrame { code: CLOSE_GOING_AWAY, reason: "server shutdown".into() });
+                    }
+                    _ = signaled(&mut closed_rx) => {
+                        break Some(CloseFrame { code: axum::extract::ws::close_code::NORMAL, reason: "".into() });
+                    }
+                }
+            };
+            match close {
+                Some(frame) => sink.send(Message::Close(Some(frame))).await.is_ok(),
+                None => false,
+            }
+        };
+
+        // The writer finishing (close, eviction, shutdown) ends the connection;
+        // after a close frame the reader is given a moment to see the reply.
+        tokio::pin!(reader);
+        tokio::select! {
+            _ = &mut reader => {}
+            sent_close = writer => {
+                if sent_close {
+                    let _ = tokio::time::timeout(Duration::from_secs(1), reader).await;
+                }
+            }
+        }
+        closed.send_replace(true);
+    }
+
+    fn remove(&self, id: ConnectionId) {
+        let Some(entry) = self.inner.connections.write().unwrap().remove(&id) else { return };
+        let mut rooms = self.inner.rooms.write().unwrap();
+        for room in entry.rooms {
+            if let Some(members) = rooms.get_mut(&room) {
+                members.remove(&id);
+                if members.is_empty() {
+                    rooms.remove(&room);
+                }
+            }
+        }
+    }
+
+    /// Add a connection to a room
+    pub fn join(&self, id: ConnectionId, room: &str) -> bool {
+        let mut connections = self.inner.connections.write().unwrap();
+        let Some(entry) = connections.get_mut(&id) else { return false };
+        entry.rooms.insert(room.to_string());
+        self.inner.rooms.write().unwrap().entry(room.to_string()).or_default().insert(id);
+        true
+    }
+
+    /// Remove a connection from a room
+    pub fn leave(&self, id: ConnectionId, room: &str) {
+        if let Some(entry) = self.inner.connections.write().unwrap().get_mut(&id) {
+            entry.rooms.remove(room);
+        }
+        let mut rooms = self.inner.rooms.write().unwrap();
+        if let Some(members) = rooms.get_mut(room) {
+            members.remove(&id);
+            if members.is_empty() {
+                rooms.remove(room);
+            }
+        }
+    }
+
+    /// Send a message to every connection in a room
+    ///
+    /// Returns the number of connections it was queued for.
+    pub fn broadcast(&self, room: &str, message: impl Into<Message>) -> usize {
+        let members: Vec<ConnectionId> = match self.inner.rooms.read().unwrap().get(room) {
+            Some(members) => members.iter().copied().collect(),
+            None => return 0,
+        };
+        self.send_many(&members, message.into())
+    }
+
+    /// Send a message to every connection
+    pub fn broadcast_all(&self, message: impl Into<Message>) -> usize {
+        let ids: Vec<ConnectionId> = self.inner.connections.read().unwrap().keys().copied().collect();
+        self.send_many(&ids, message.into())
+    }
+
+    /// Send a message to one connection
+    pub fn send_to(&self, id: ConnectionId, message: impl Into<Message>) -> bool {
+        self.send_many(&[id], message.into()) == 1
+    }
+
+    fn send_many(&self, ids: &[ConnectionId], message: Message) -> usize {
+        let connections = self.inner.connections.read().unwrap();
+        ids.iter()
+            .filter_map(|id| connections.get(id).map(|entry| (id, entry)))
+            .filter(|(id, entry)| match entry.sender.try_send(message.clone()) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    tracing::warn!("WebSocket {} outbound queue full, message dropped", id);
+                    false
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            })
+            .count()
+    }
+
+    /// Handle for a live connection
+    pub fn connection(&self, id: ConnectionId) -> Option<Connection> {
+        self.inner.connections.read().unwrap().get(&id).map(|entry| Connection {
+            id,
+            hub: self.clone(),
+            sender: entry.sender.clone(),
+            closed: entry.closed.clone(),
+        })
+    }
+
+    /// Connections whose metadata `key` equals `value`
+    pub fn find(&self, key: &str, value: &str) -> Vec<ConnectionId> {
+        self.inner.connections.read().unwrap().iter()
+            .filter(|(_, entry)| entry.metadata.get(key).is_some_and(|v| v == value))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Connections in a room
+    pub fn members(&self, room: &str) -> Vec<ConnectionId> {
+        self.inner.rooms.read().unwrap().get(room).map(|members| members.iter().copied().collect()).unwrap_or_default()
+    }
+
+    /// Rooms with at least one connection
+    pub fn rooms(&self) -> Vec<String> {
+        self.inner.rooms.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Number of live connections
+    pub fn len(&self) -> usize {
+        self.inner.connections.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Send a close frame (1001 Going Away) to every connection
+    ///
+    /// Connections accepted afterwards are closed immediately. Called by
+    /// `HttpServer` on shutdown for hubs registered with `websocket_hub`.
+    pub fn shutdown(&self) {
+        self.inner.shutdown.send_replace(true);
+    }
+
+    /// Shut down and wait up to `timeout` for all connections to close
+    pub async fn close_all(&self, timeout: Duration) -> bool {
+        self.shutdown();
+        let deadline = Instant::now() + timeout;
+        while !self.is_empty() {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        true
+    }
+}
+
+impl Default for WebSocketHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn signaled(receiver: &mut watch::Receiver<bool>) {
+    let _ = receiver.wait_for(|set| *set).await.map(|_| ());
+}
+
+/// Handle for a connection registered with a `WebSocketHub`
+#[derive(Clone)]
+pub struct Connection {
+    id: ConnectionId,
+    hub: WebSocketHub,
+    sender: mpsc::Sender<Message>,
+    closed: watch::Sender<bool>,
+}
+
+impl Connection {
+    pub fn id(&self) -> ConnectionId {
+        self.id
+    }
+
+    /// Queue a message, waiting if the outbound queue is full
+    ///
+    /// Returns false once the connection is closed.
+    pub async fn send(&self, message: impl Into<Message>) -> bool {
+        self.sender.send(message.into()).await.is_ok()
+    }
+
+    pub fn join(&self, room: &str) -> bool {
+        self.hub.join(self.id, room)
+    }
+
+    pub fn leave(&self, room: &str) {
+        self.hub.leave(self.id, room)
+    }
+
+    /// Rooms this connection is in
+    pub fn rooms(&self) -> Vec<String> {
+        self.hub.inner.connections.read().unwrap().get(&self.id)
+            .map(|entry| entry.rooms.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn set_metadata(&self, key: &str, value: &str) {
+        if let Some(entry) = self.hub.inner.connections.write().unwrap().get_mut(&self.id) {
+            entry.metadata.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    pub fn metadata(&self, key: &str) -> Option<String> {
+        self.hub.inner.connections.read().unwrap().get(&self.id).and_then(|entry| entry.metadata.get(key).cloned())
+    }
+
+    /// Close the connection with a normal close frame
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        *self.closed.borrow()
+    }
+}
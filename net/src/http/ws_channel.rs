@@ -0,0 +1,538 @@
+//! # ws_channel
+//!
+//! ws_channel 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Typed message channel over WebSocket
+//!
+//! Message types are registered under a type tag with a codec: JSON (serde)
+//! messages travel as text frames, protobuf (prost) messages as binary frames.
+//! Incoming frames are decoded and dispatched to the handler registered for
+//! their type; `request` correlates a reply with its request by id.
+//!
+//! Text frame: `{"type": "chat.send", "id": 7, "reply_to": 3, "data": {...}}`
+//! (`id` and `reply_to` omitted when unset).
+//!
+//! Binary frame: `u8` tag length, tag, `u64` id, `u64` reply_to (big endian,
+//! 0 when unset), then the protobuf payload.
+//!
+//! A failed request handler answers with an `rf.error` text frame carrying
+//! `{"message": ...}`; `request` returns it as `RfError::Custom`.
+//!
+//! ```rust,no_run
+//! use rf_net::http::MessageChannel;
+//! use serde::{Deserialize, Serialize};
+//! use std::sync::Arc;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Join { room: String }
+//! #[derive(Serialize, Deserialize)]
+//! struct Joined { members: usize }
+//!
+//! let channel = Arc::new(MessageChannel::new()
+//!     .json::<Join>("room.join")
+//!     .json::<Joined>("room.joined")
+//!     .on_request(|_sender, join: Join| async move {
+//!         Ok(Joined { members: join.room.len() })
+//!     }));
+//! // In an upgrade handler: `channel.serve(socket).await`
+//! ```
+
+use axum::extract::ws::{Message, WebSocket};
+use futures_util::future::BoxFuture;
+use futures_util::{SinkExt, StreamExt};
+use rf_errors::{Result, RfError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// Type tag of the error reply sent when a request handler fails
+pub const ERROR_TYPE: &str = "rf.error";
+
+type Boxed = Box<dyn Any + Send>;
+type Encoder = Box<dyn Fn(&dyn Any) -> Result<Payload> + Send + Sync>;
+type Decoder = Box<dyn Fn(Payload) -> Result<Boxed> + Send + Sync>;
+type Handler = Arc<dyn Fn(ChannelSender, Boxed, u64) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Encoded message body
+enum Payload {
+    Json(Value),
+    Binary(Vec<u8>),
+}
+
+/// A decoded frame
+struct Envelope {
+    tag: String,
+    id: u64,
+    reply_to: u64,
+    payload: Payload,
+}
+
+impl Envelope {
+    fn into_frame(self) -> Frame {
+        match self.payload {
+            Payload::Json(data) => {
+                let mut object = json!({ "type": self.tag, "data": data });
+                if self.id != 0 {
+                    object["id"] = self.id.into();
+                }
+                if self.reply_to != 0 {
+                    object["reply_to"] = self.reply_to.into();
+                }
+                Frame::Text(object.to_string())
+            }
+            Payload::Binary(body) => {
+                let tag = self.tag.as_bytes();
+                let mut frame = Vec::with_capacity(17 + tag.len() + body.len());
+                frame.push(tag.len() as u8);
+                frame.extend_from_slice(tag);
+                frame.extend_from_slice(&self.id.to_be_bytes());
+                frame.extend_from_slice(&self.reply_to.to_be_bytes());
+                frame.extend_from_slice(&body);
+                Frame::Binary(frame)
+            }
+        }
+    }
+
+    fn from_frame(frame: Frame) -> Result<Self> {
+        match frame {
+            Frame::Text(text) => {
+                let mut object: Value = serde_json::from_str(&text)
+                    .map_err(|e| RfError::Serialization(format!("Invalid message frame: {}", e)))?;
+                let tag = object["type"].as_str()
+                    .ok_or_else(|| RfError::Serialization("Message frame without type".to_string()))?
+                    .to_string();
+                Ok(Self {
+                    tag,
+                    id: object["id"].as_u64().unwrap_or(0),
+                    reply_to: object["reply_to"].as_u64().unwrap_or(0),
+                    payload: Payload::Json(object["data"].take()),
+                })
+            }
+            Frame::Binary(bytes) => {
+                let invalid = || RfError::Serialization("Truncated binary message frame".to_string());
+                let tag_len = *bytes.first().ok_or_else(invalid)? as usize;
+                let header = 1 + tag_len + 16;
+                if bytes.len() < header {
+                    return Err(invalid());
+                }
+                let tag = std::str::from_utf8(&bytes[1..1 + tag_len])
+                    .map_err(|_| RfError::Serialization("Binary message tag is not UTF-8".to_string()))?
+                    .to_string();
+                let number = |at: usize| u64::from_be_bytes(bytes[at..at + 8].try_into().unwrap());
+                Ok(Self {
+                    tag,
+                    id: number(1 + tag_len),
+                    reply_to: number(9 + tag_len),
+                    payload: Payload::Binary(bytes[header..].to_vec()),
+                })
+            }
+        }
+    }
+}
+
+/// Transport-independent WebSocket data frame
+enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+struct Registration {
+    tag: String,
+    encode: Encoder,
+}
+
+#[derive(Default)]
+struct Codecs {
+    by_type: HashMap<TypeId, Registration>,
+    by_tag: HashMap<String, Decoder>,
+}
+
+impl Codecs {
+    fn encode<T: Any>(&self, message: &T) -> Result<(String, Payload)> {
+        let registration = self.by_type.get(&TypeId::of::<T>()).ok_or_else(|| {
+            RfError::InvalidParameter(format!("Message type {} is not registered", std::any::type_name::<T>()))
+        })?;
+        Ok((registration.tag.clone(), (registration.encode)(message)?))
+    }
+
+    fn decode(&self, tag: &str, payload: Payload) -> Result<Boxed> {
+        let decode = self.by_tag.get(tag)
+            .ok_or_else(|| RfError::InvalidParameter(format!("Unknown message type '{}'", tag)))?;
+        decode(payload)
+    }
+}
+
+/// Typed message channel definition: registered types and their handlers
+///
+/// Build once, wrap in `Arc`, then `serve` every upgraded socket or
+/// `connect` to a server as a client.
+pub struct MessageChannel {
+    codecs: Arc<Codecs>,
+    handlers: HashMap<String, Handler>,
+    request_timeout: Duration,
+    buffer: usize,
+}
+
+impl MessageChannel {
+    pub fn new() -> Self {
+        Self {
+            codecs: Arc::new(Codecs::default()),
+            handlers: HashMap::new(),
+            request_timeout: Duration::from_secs(30),
+            buffer: 64,
+        }
+    }
+
+    fn codecs_mut(&mut self) -> &mut Codecs {
+        Arc::get_mut(&mut self.codecs).expect("message types are registered before the channel is used")
+    }
+
+    fn register<T: Any + Send>(mut self, tag: &str, encode: Encoder, decode: Decoder) -> Self {
+        assert!(tag.len() <= u8::MAX as usize, "message type tag longer than 255 bytes");
+        let codecs = self.codecs_mut();
+        codecs.by_type.insert(TypeId::of::<T>(), Registration { tag: tag.to_string(), encode });
+        codecs.by_tag.insert(tag.to_string(), decode);
+        self
+    }
+
+    /// Register a serde message type sent as JSON text frames
+    pub fn json<T>(self, tag: &str) -> Self
+    where
+        T: Serialize + DeserializeOwned + Send + 'static,
+    {
+        self.register::<T>(
+            tag,
+            Box::new(|message| {
+                let message = message.downcast_ref::<T>().expect("encoder registered for this type");
+                serde_json::to_value(message)
+                    .map(Payload::Json)
+                    .map_err(|e| RfError::Serialization(format!("Failed to encode message: {}", e)))
+            }),
+            Box::new(|payload| match payload {
+                Payload::Json(data) => serde_json::from_value::<T>(data)
+                    .map(|message| Box::new(message) as Boxed)
+                    .map_err(|e| RfError::Serialization(format!("Failed to decode message: {}", e))),
+                Payload::Binary(_) => Err(RfError::Serialization("Expected a JSON message".to_string())),
+            }),
+        )
+    }
+
+    /// Register a prost message type sent as binary frames
+    pub fn protobuf<T>(self, tag: &str) -> Self
+    where
+        T: prost::Message + Default + Send + 'static,
+    {
+        self.register::<T>(
+            tag,
+            Box::new(|message| {
+                let message = message.downcast_ref::<T>().expect("encoder registered for this type");
+                Ok(Payload::Binary(message.encode_to_vec()))
+            }),
+            Box::new(|payload| match payload {
+                Payload::Binary(bytes) => T::decode(bytes.as_slice())
+                    .map(|message| Box::new(message) as Boxed)
+                    .map_err(|e| RfError::Serialization(format!("Failed to decode message: {}", e))),
+                Payload::Json(_) => Err(RfError::Serialization("Expected a protobuf message".to_string())),
+            }),
+        )
+    }
+
+    /// Handle every incoming message of type `T`
+    ///
+    /// `T` must be registered. Replaces any earlier handler for the type.
+    pub fn on<T, F, Fut>(mut self, handler: F) -> Self
+    where
+        T: Send + 'static,
+        F: Fn(ChannelSender, T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let tag = self.tag_of::<T>();
+        self.handlers.insert(tag, Arc::new(move |sender, message, _id| {
+            let message = *message.downcast::<T>().expect("decoder registered for this type");
+            Box::pin(handler(sender, message))
+        }));
+        self
+    }
+
+    /// Answer requests of type `Req` with a `Resp`
+    ///
+    /// The response is sent with `reply_to` set to the request id; an error
+    /// is sent back as an `rf.error` frame.
+    pub fn on_request<Req, Resp, F, Fut>(mut self, handler: F) -> Self
+    where
+        Req: Send + 'static,
+        Resp: Send + 'static,
+        F: Fn(ChannelSender, Req) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Resp>> + Send + 'static,
+    {
+        let tag = self.tag_of::<Req>();
+        let handler = Arc::new(handler);
+        self.handlers.insert(tag, Arc::new(move |sender, message, id| {
+            let request = *message.downcast::<Req>().expect("decoder registered for this type");
+            let handler = handler.clone();
+            Box::pin(async move {
+                let result = handler(sender.clone(), request).await;
+                if id == 0 {
+                    // Sent with `send`, nobody waits for the answer
+                    return;
+                }
+                let sent = match result {
+                    Ok(response) => sender.reply(id, response).await,
+                    Err(e) => sender.reply_error(id, &e.to_string()).await,
+                };
+                if let Err(e) = sent {
+                    tracing::warn!("Failed to send reply: {}", e);
+                }
+            })
+        }));
+        self
+    }
+
+    /// Timeout for `ChannelSender::request` (default: 30s)
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Outbound queue size per connection (default: 64)
+    pub fn buffer(mut self, buffer: usize) -> Self {
+        self.buffer = buffer.max(1);
+        self
+    }
+
+    fn tag_of<T: Any>(&self) -> String {
+        match self.codecs.by_type.get(&TypeId::of::<T>()) {
+            Some(registration) => registration.tag.clone(),
+            None => panic!("message type {} must be registered before its handler", std::any::type_name::<T>()),
+        }
+    }
+
+    fn sender(&self) -> (ChannelSender, mpsc::Receiver<Frame>) {
+        let (outbound, receiver) = mpsc::channel(self.buffer);
+        let sender = ChannelSender {
+            inner: Arc::new(SenderInner {
+                codecs: self.codecs.clone(),
+                outbound,
+                pending: Mutex::new(HashMap::new()),
+                next_id: AtomicU64::new(1),
+                request_timeout: self.request_timeout,
+            }),
+        };
+        (sender, receiver)
+    }
+
+    fn dispatch(&self, sender: &ChannelSender, frame: Frame) {
+        let envelope = match Envelope::from_frame(frame) {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                tracing::warn!("Dropping message: {}", e);
+                return;
+            }
+        };
+        if envelope.reply_to != 0 {
+            sender.complete(envelope);
+            return;
+        }
+        let Some(handler) = self.handlers.get(&envelope.tag).cloned() else {
+            tracing::debug!("No handler for message type '{}'", envelope.tag);
+            return;
+        };
+        match self.codecs.decode(&envelope.tag, envelope.payload) {
+            Ok(message) => {
+                tokio::spawn(handler(sender.clone(), message, envelope.id));
+            }
+            Err(e) => tracing::warn!("Dropping message '{}': {}", envelope.tag, e),
+        }
+    }
+
+    /// Run the channel on an upgraded server socket until it closes
+    pub async fn serve(self: Arc<Self>, socket: WebSocket) {
+        self.serve_with(socket, |_| {}).await
+    }
+
+    /// Like `serve`, handing the connection's sender to `on_open` first,
+    /// e.g. to keep it for pushing messages
+    pub async fn serve_with(self: Arc<Self>, socket: WebSocket, on_open: impl FnOnce(ChannelSender)) {
+        let (mut sink, mut stream) = socket.split();
+        let (sender, mut outbound) = self.sender();
+        on_open(sender.clone());
+        let writer = tokio::spawn(async move {
+            while let Some(frame) = outbound.recv().await {
+                let message = match frame {
+                    Frame::Text(text) => Message::Text(text.into()),
+                    Frame::Binary(bytes) => Message::Binary(bytes.into()),
+                };
+                if sink.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+        while let Some(message) = stream.next().await {
+            let frame = match message {
+                Ok(Message::Text(text)) => Frame::Text(text.to_string()),
+                Ok(Message::Binary(bytes)) => Frame::Binary(bytes.to_vec()),
+                Ok(Message::Close(_)) => break,
+                Ok(_) => continue,
+                Err(e) => {
+                    tracing::debug!("WebSocket error: {}", e);
+                    break;
+                }
+            };
+            self.dispatch(&sender, frame);
+        }
+        sender.fail_pending();
+        writer.abort();
+    }
+
+    /// Connect to a channel server as a client
+    ///
+    /// Incoming messages are dispatched to this channel's handlers in the
+    /// background; the returned sender fails once the connection closes.
+    pub async fn connect(self: Arc<Self>, url: &str) -> Result<ChannelSender> {
+        use tokio_tungstenite::tungstenite::Message as ClientMessage;
+        let (socket, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .map_err(|e| RfError::Network(format!("WebSocket connect failed: {}", e)))?;
+        let (mut sink, mut stream) = socket.split();
+        let (sender, mut outbound) = self.sender();
+        let writer = tokio::spawn(async move {
+            while let Some(frame) = outbound.recv().await {
+                let message = match frame {
+                    Frame::Text(text) => ClientMessage::Text(text),
+                    Frame::Binary(bytes) => ClientMessage::Binary(bytes),
+                };
+                if sink.send(message).await.is_err() {
+                    break;
+                }
+            }
+            let _ = sink.close().await;
+        });
+        let client = sender.clone();
+        tokio::spawn(async move {
+            while let Some(message) = stream.next().await {
+                let frame = match message {
+                    Ok(ClientMessage::Text(text)) => Frame::Text(text),
+                    Ok(ClientMessage::Binary(bytes)) => Frame::Binary(bytes),
+                    Ok(ClientMessage::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        tracing::debug!("WebSocket error: {}", e);
+                        break;
+                    }
+                };
+                self.dispatch(&client, frame);
+            }
+            client.fail_pending();
+            writer.abort();
+        });
+        Ok(sender)
+    }
+}
+
+impl Default for MessageChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct SenderInner {
+    codecs: Arc<Codecs>,
+    outbound: mpsc::Sender<Frame>,
+    pending: Mutex<HashMap<u64, oneshot::Sender<Result<Envelope>>>>,
+    next_id: AtomicU64,
+    request_timeout: Duration,
+}
+
+/// Sends typed messages on one connection
+#[derive(Clone)]
+pub struct ChannelSender {
+    inner: Arc<SenderInner>,
+}
+
+impl ChannelSender {
+    async fn push(&self, envelope: Envelope) -> Result<()> {
+        self.inner.outbound.send(envelope.into_frame()).await
+            .map_err(|_| RfError::Network("WebSocket connection closed".to_string()))
+    }
+
+    /// Send a message without waiting for an answer
+    pub async fn send<T: Any>(&self, message: T) -> Result<()> {
+        let (tag, payload) = self.inner.codecs.encode(&message)?;
+        self.push(Envelope { tag, id: 0, reply_to: 0, payload }).await
+    }
+
+    /// Send a request and wait for the `Resp` sent in reply
+    pub async fn request<Req: Any, Resp: Any>(&self, request: Req) -> Result<Resp> {
+        let (tag, payload) = self.inner.codecs.encode(&request)?;
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.inner.pending.lock().unwrap().insert(id, sender);
+        if let Err(e) = self.push(Envelope { tag, id, reply_to: 0, payload }).await {
+            self.inner.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+        let reply = match tokio::time::timeout(self.inner.request_timeout, receiver).await {
+            Ok(Ok(reply)) => reply?,
+            Ok(Err(_)) => return Err(RfError::Network("WebSocket connection closed".to_string())),
+            Err(_) => {
+                self.inner.pending.lock().unwrap().remove(&id);
+                return Err(RfError::Timeout(format!("No reply to '{}' request", std::any::type_name::<Req>())));
+            }
+        };
+        if reply.tag == ERROR_TYPE {
+            let message = match reply.payload {
+                Payload::Json(data) => data["message"].as_str().unwrap_or("remote error").to_string(),
+                Payload::Binary(_) => "remote error".to_string(),
+            };
+            return Err(RfError::Custom(message));
+        }
+        let response = self.inner.codecs.decode(&reply.tag, reply.payload)?;
+        response.downcast::<Resp>().map(|response| *response).map_err(|_| {
+            RfError::Serialization(format!(
+                "Reply '{}' is not a {}",
+                reply.tag,
+                std::any::type_name::<Resp>()
+            ))
+        })
+    }
+
+    async fn reply<T: Any>(&self, id: u64, message: T) -> Result<()> {
+        let (tag, payload) = self.inner.codecs.encode(&message)?;
+        self.push(Envelope { tag, id: 0, reply_to: id, payload }).await
+    }
+
+    async fn reply_error(&self, id: u64, message: &str) -> Result<()> {
+        let payload = Payload::Json(json!({ "message": message }));
+        self.push(Envelope { tag: ERROR_TYPE.to_string(), id: 0, reply_to: id, payload }).await
+    }
+
+    fn complete(&self, envelope: Envelope) {
+        match self.inner.pending.lock().unwrap().remove(&envelope.reply_to) {
+            Some(waiter) => {
+                let _ = waiter.send(Ok(envelope));
+            }
+            None => tracing::debug!("Reply to unknown or expired request {}", envelope.reply_to),
+        }
+    }
+
+    fn fail_pending(&self) {
+        for (_, waiter) in self.inner.pending.lock().unwrap().drain() {
+            let _ = waiter.send(Err(RfError::Network("WebSocket connection closed".to_string())));
+        }
+    }
+
+    /// Whether the connection has closed
+    pub fn is_closed(&self) -> bool {
+        self.inner.outbound.is_closed()
+    }
+}
@@ -30,6 +30,11 @@
 //! - 服务发现：服务注册和发现机制
 //! - 分布式追踪：基于 OpenTelemetry 的链路追踪
 //! - API 文档：自动生成 OpenAPI 规范和 Swagger UI
+//! - Webhook：签名投递、失败重试、死信和接收端校验
+//! - 错误上报：5xx 与 panic 上报到 Sentry 或 Webhook，支持采样和脱敏
+//! - 指标：Prometheus `/metrics` 端点与 HTTP 延迟直方图
+//! - 缓冲池：请求体、上传和 TCP 帧复用的字节缓冲
+//! - 重试与熔断：反向代理和 HTTP 客户端 SDK 共用的重试策略与熔断器
 
 pub mod http {
     pub mod middleware;
@@ -38,12 +43,39 @@ pub mod http {
     pub mod server;
     pub mod hooks;
     pub mod websocket;
+    pub mod ws_channel;
     pub mod static_files;
     pub mod router;
     pub mod rate_limit;
     pub mod timeout;
     pub mod upload;
+    pub mod upload_stream;
+    pub mod upload_resumable;
+    pub mod upload_validate;
     pub mod swagger;
+    pub mod user_agent;
+    pub mod tls;
+    pub mod jsonrpc;
+    pub mod parse;
+    pub mod limits;
+    pub mod envelope;
+    pub mod plugin;
+    pub mod admin;
+    pub mod quota;
+    pub mod session;
+    pub mod export;
+    pub mod import;
+    pub mod proxy;
+    pub mod request_id;
+    pub mod mock;
+    pub mod negotiate;
+    pub mod locale;
+    pub mod prometheus;
+    pub mod response_cache;
+    #[cfg(feature = "acme")]
+    pub mod acme;
+    #[cfg(feature = "fault-injection")]
+    pub mod fault;
     
     pub use middleware::*;
     pub use request::*;
@@ -51,15 +83,43 @@ pub mod http {
     pub use server::*;
     pub use hooks::*;
     pub use websocket::*;
+    pub use ws_channel::*;
     pub use static_files::*;
     pub use router::*;
     pub use rate_limit::*;
     pub use timeout::*;
     pub use upload::*;
+    pub use upload_stream::*;
+    pub use upload_resumable::*;
+    pub use upload_validate::*;
     pub use swagger::*;
+    pub use user_agent::*;
+    pub use tls::*;
+    pub use jsonrpc::*;
+    pub use parse::*;
+    pub use limits::*;
+    pub use envelope::*;
+    pub use plugin::*;
+    pub use admin::*;
+    pub use quota::*;
+    pub use session::*;
+    pub use export::*;
+    pub use import::*;
+    pub use self::proxy::*;
+    pub use request_id::*;
+    pub use mock::*;
+    pub use negotiate::*;
+    pub use locale::*;
+    pub use prometheus::*;
+    pub use response_cache::*;
+    #[cfg(feature = "acme")]
+    pub use acme::*;
+    #[cfg(feature = "fault-injection")]
+    pub use fault::*;
 }
 pub mod client;
 pub mod tcp;
+pub mod proxy;
 pub mod udp;
 pub mod ipv4;
 pub mod ipv6;
@@ -67,10 +127,16 @@ pub mod sel;
 pub mod svc;
 pub mod trace;
 pub mod oai;
+pub mod webhook;
+pub mod report;
+pub mod buffer;
+pub mod retry;
+pub mod breaker;
 
 pub use http::*;
 pub use client::*;
 pub use tcp::*;
+pub use proxy::*;
 pub use udp::*;
 // Re-export with specific names to avoid conflicts
 pub use ipv4::{parse as ipv4_parse, in_network as ipv4_in_network};
@@ -79,3 +145,4 @@ pub use sel::*;
 pub use svc::*;
 pub use trace::*;
 pub use oai::*;
+pub use buffer::*;
@@ -11,9 +11,11 @@
 //!
 //! # 主要功能
 //!
-//! - 创建 OpenAPI 规范文档
-//! - 配置 API 信息（标题、版本、描述）
-//! - 生成标准化的 API 文档
+//! - 创建 OpenAPI 3.1 规范文档
+//! - 配置 API 信息（标题、版本、描述、服务器、安全方案）
+//! - `#[derive(ApiSchema)]` 从请求/响应结构体生成 JSON Schema
+//! - `ApiOperation` 描述路由的参数、请求体和响应，注册路由时自动收集到文档
+//! - `SdkGenerator` 由文档生成类型化的 Rust / TypeScript 客户端
 //!
 //! # 使用示例
 //!
@@ -26,17 +28,562 @@
 //!     .build();
 //! ```
 //!
-//! ## 与 HTTP 服务器集成
+//! ## 从路由生成文档
 //! ```ignore
 //! use rf_net::http::HttpServer;
+//! use rf_net::oai::{ApiOperation, ApiSchema, OpenApiBuilder};
+//!
+//! #[derive(Serialize, Deserialize, ApiSchema)]
+//! struct CreateUser {
+//!     /// 用户名
+//!     name: String,
+//!     #[api(format = "email")]
+//!     email: Option<String>,
+//! }
 //!
 //! let server = HttpServer::new(addr)
-//!     .with_swagger_ui(openapi, "/api-docs");
+//!     .with_openapi(OpenApiBuilder::new("User API", "1.0.0").swagger_ui("/docs"))
+//!     .api_route(Method::POST, "/users", create_user, ApiOperation::new()
+//!         .summary("创建用户")
+//!         .request_body::<CreateUser>()
+//!         .response::<User>(201, "已创建"))?;
+//! // 文档位于 /openapi.json，Swagger UI 位于 /docs/
 //! ```
 //!
-//! # 注意
+//! ## 与 HTTP 服务器集成
+//! ```ignore
+//! use rf_net::http::HttpServer;
 //!
-//! 这是一个简化的实现。对于完整的 OpenAPI 支持，建议使用 utoipa 库的派生宏。
+//! let server = HttpServer::new(addr)
+//!     .with_swagger_ui(openapi, "/api-docs");
+//! ```
+
+use axum::http::Method;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+pub use rf_util_derive::ApiSchema;
+
+pub mod sdk;
+
+pub use sdk::{GeneratedFile, SdkGenerator};
+
+/// JSON Schema 值
+///
+/// OpenAPI 3.1 直接使用 JSON Schema 2020-12，因此模式以 JSON 表示。
+pub type JsonSchema = Value;
+
+/// 可以生成 JSON Schema 的类型
+///
+/// 通常通过 `#[derive(ApiSchema)]` 实现。有名称的类型（`schema_name` 返回 `Some`）
+/// 只在 `components.schemas` 中出现一次，其他位置通过 `$ref` 引用。
+///
+/// # 示例
+///
+/// ```ignore
+/// struct Money(i64);
+///
+/// impl ApiSchema for Money {
+///     fn schema(_: &mut SchemaRegistry) -> JsonSchema {
+///         serde_json::json!({"type": "integer", "description": "金额（分）"})
+///     }
+/// }
+/// ```
+pub trait ApiSchema {
+    /// 组件名称，`None` 表示内联
+    fn schema_name() -> Option<String> {
+        None
+    }
+
+    /// 生成模式，引用的其他类型通过 `registry.schema::<T>()` 获取
+    fn schema(registry: &mut SchemaRegistry) -> JsonSchema;
+
+    /// 作为结构体字段或查询参数时是否必填，`Option` 为 `false`
+    fn is_required() -> bool {
+        true
+    }
+}
+
+/// 收集有名称的模式，输出到 `components.schemas`
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    schemas: BTreeMap<String, JsonSchema>,
+}
+
+impl SchemaRegistry {
+    /// 创建空的模式注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取类型的模式
+    ///
+    /// 有名称的类型在首次使用时注册，返回 `$ref` 引用；递归类型同样适用。
+    pub fn schema<T: ApiSchema + ?Sized>(&mut self) -> JsonSchema {
+        match T::schema_name() {
+            Some(name) => {
+                if !self.schemas.contains_key(&name) {
+                    // 先占位，递归引用时不会重复展开
+                    self.schemas.insert(name.clone(), Value::Null);
+                    let schema = T::schema(self);
+                    self.schemas.insert(name.clone(), schema);
+                }
+                json!({ "$ref": format!("#/components/schemas/{}", name) })
+            }
+            None => T::schema(self),
+        }
+    }
+
+    /// 解析 `$ref`，返回被引用的模式；非引用原样返回
+    pub fn resolve<'a>(&'a self, schema: &'a JsonSchema) -> &'a JsonSchema {
+        schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix("#/components/schemas/"))
+            .and_then(|name| self.schemas.get(name))
+            .unwrap_or(schema)
+    }
+
+    /// 已注册的模式
+    pub fn schemas(&self) -> &BTreeMap<String, JsonSchema> {
+        &self.schemas
+    }
+}
+
+/// 对象模式构建器，`#[derive(ApiSchema)]` 生成的代码使用它
+#[derive(Debug, Clone, Default)]
+pub struct ObjectSchema {
+    properties: Map<String, Value>,
+    required: Vec<String>,
+    description: Option<String>,
+}
+
+impl ObjectSchema {
+    /// 创建空的对象模式
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加属性
+    pub fn property(mut self, name: &str, schema: JsonSchema, required: bool) -> Self {
+        self.properties.insert(name.to_string(), schema);
+        if required {
+            self.required.push(name.to_string());
+        }
+        self
+    }
+
+    /// 设置描述
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// 生成模式
+    pub fn build(self) -> JsonSchema {
+        let mut schema = json!({ "type": "object", "properties": self.properties });
+        if !self.required.is_empty() {
+            schema["required"] = json!(self.required);
+        }
+        if let Some(description) = self.description {
+            schema["description"] = json!(description);
+        }
+        schema
+    }
+}
+
+/// 字符串枚举模式
+pub fn enum_schema(variants: &[&str], description: Option<&str>) -> JsonSchema {
+    annotate(json!({ "type": "string", "enum": variants }), description, None, None)
+}
+
+/// 为模式添加描述、格式和示例
+///
+/// 示例按 JSON 解析，失败时作为字符串。OpenAPI 3.1 允许 `$ref` 旁边出现这些关键字。
+pub fn annotate(mut schema: JsonSchema, description: Option<&str>, format: Option<&str>, example: Option<&str>) -> JsonSchema {
+    if let Some(description) = description {
+        schema["description"] = json!(description);
+    }
+    if let Some(format) = format {
+        schema["format"] = json!(format);
+    }
+    if let Some(example) = example {
+        let value = serde_json::from_str(example).unwrap_or_else(|_| json!(example));
+        schema["examples"] = json!([value]);
+    }
+    schema
+}
+
+macro_rules! primitive_schema {
+    ($($ty:ty => $schema:tt),* $(,)?) => {
+        $(
+            impl ApiSchema for $ty {
+                fn schema(_: &mut SchemaRegistry) -> JsonSchema {
+                    json!($schema)
+                }
+            }
+        )*
+    };
+}
+
+primitive_schema! {
+    bool => { "type": "boolean" },
+    i8 => { "type": "integer", "format": "int32" },
+    i16 => { "type": "integer", "format": "int32" },
+    i32 => { "type": "integer", "format": "int32" },
+    i64 => { "type": "integer", "format": "int64" },
+    isize => { "type": "integer", "format": "int64" },
+    u8 => { "type": "integer", "format": "int32", "minimum": 0 },
+    u16 => { "type": "integer", "format": "int32", "minimum": 0 },
+    u32 => { "type": "integer", "format": "int64", "minimum": 0 },
+    u64 => { "type": "integer", "format": "int64", "minimum": 0 },
+    usize => { "type": "integer", "format": "int64", "minimum": 0 },
+    f32 => { "type": "number", "format": "float" },
+    f64 => { "type": "number", "format": "double" },
+    char => { "type": "string", "minLength": 1, "maxLength": 1 },
+    str => { "type": "string" },
+    String => { "type": "string" },
+    Value => {},
+    chrono::NaiveDate => { "type": "string", "format": "date" },
+    chrono::NaiveDateTime => { "type": "string", "format": "date-time" },
+    std::net::IpAddr => { "type": "string", "format": "ip" },
+}
+
+impl<Tz: chrono::TimeZone> ApiSchema for chrono::DateTime<Tz> {
+    fn schema(_: &mut SchemaRegistry) -> JsonSchema {
+        json!({ "type": "string", "format": "date-time" })
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for Option<T> {
+    fn schema(registry: &mut SchemaRegistry) -> JsonSchema {
+        let inner = registry.schema::<T>();
+        match inner.get("type").and_then(Value::as_str) {
+            Some(ty) if inner.get("enum").is_none() => {
+                let mut schema = inner.clone();
+                schema["type"] = json!([ty, "null"]);
+                schema
+            }
+            _ => json!({ "anyOf": [inner, { "type": "null" }] }),
+        }
+    }
+
+    fn is_required() -> bool {
+        false
+    }
+}
+
+macro_rules! wrapper_schema {
+    ($($ty:ident),*) => {
+        $(
+            impl<T: ApiSchema + ?Sized> ApiSchema for $ty<T> {
+                fn schema(registry: &mut SchemaRegistry) -> JsonSchema {
+                    registry.schema::<T>()
+                }
+
+                fn is_required() -> bool {
+                    T::is_required()
+                }
+            }
+        )*
+    };
+}
+
+wrapper_schema!(Box, Arc);
+
+impl<T: ApiSchema> ApiSchema for Vec<T> {
+    fn schema(registry: &mut SchemaRegistry) -> JsonSchema {
+        json!({ "type": "array", "items": registry.schema::<T>() })
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for [T] {
+    fn schema(registry: &mut SchemaRegistry) -> JsonSchema {
+        json!({ "type": "array", "items": registry.schema::<T>() })
+    }
+}
+
+impl<T: ApiSchema, S> ApiSchema for HashSet<T, S> {
+    fn schema(registry: &mut SchemaRegistry) -> JsonSchema {
+        json!({ "type": "array", "items": registry.schema::<T>(), "uniqueItems": true })
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for BTreeSet<T> {
+    fn schema(registry: &mut SchemaRegistry) -> JsonSchema {
+        json!({ "type": "array", "items": registry.schema::<T>(), "uniqueItems": true })
+    }
+}
+
+impl<T: ApiSchema, S> ApiSchema for HashMap<String, T, S> {
+    fn schema(registry: &mut SchemaRegistry) -> JsonSchema {
+        json!({ "type": "object", "additionalProperties": registry.schema::<T>() })
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for BTreeMap<String, T> {
+    fn schema(registry: &mut SchemaRegistry) -> JsonSchema {
+        json!({ "type": "object", "additionalProperties": registry.schema::<T>() })
+    }
+}
+
+/// 延迟生成的模式，文档构建时调用
+type SchemaFn = fn(&mut SchemaRegistry) -> JsonSchema;
+
+/// 状态码、描述和可选的内容类型与模式
+type ApiResponse = (u16, String, Option<(String, SchemaFn)>);
+
+fn schema_of<T: ApiSchema>(registry: &mut SchemaRegistry) -> JsonSchema {
+    registry.schema::<T>()
+}
+
+/// 单个参数
+#[derive(Debug, Clone)]
+struct ApiParameter {
+    name: String,
+    location: &'static str,
+    schema: SchemaFn,
+    required: bool,
+    description: Option<String>,
+}
+
+/// 路由的 OpenAPI 操作描述
+///
+/// 通过 `HttpServer::api_route` / `RouteGroup::api_route` 注册路由时附带，
+/// 也可以用 `OpenApiBuilder::operation` 手动添加。路径参数自动从路由模式
+/// 中提取（默认字符串类型），可用 `path_param` 指定类型和描述。
+///
+/// # 示例
+///
+/// ```ignore
+/// let op = ApiOperation::new()
+///     .summary("查询订单")
+///     .tag("orders")
+///     .path_param::<u64>("id", "订单 ID")
+///     .query::<OrderFilter>()
+///     .response::<Order>(200, "订单详情")
+///     .response_empty(404, "订单不存在")
+///     .security("bearer");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ApiOperation {
+    summary: Option<String>,
+    description: Option<String>,
+    operation_id: Option<String>,
+    tags: Vec<String>,
+    deprecated: bool,
+    parameters: Vec<ApiParameter>,
+    /// 展开为查询参数的结构体
+    queries: Vec<SchemaFn>,
+    request_body: Option<(String, SchemaFn)>,
+    responses: Vec<ApiResponse>,
+    /// 响应示例，按状态码
+    examples: Vec<(u16, Value)>,
+    security: Vec<String>,
+}
+
+impl ApiOperation {
+    /// 创建空的操作描述
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置摘要
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_string());
+        self
+    }
+
+    /// 设置详细描述
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// 设置操作 ID（生成 SDK 时作为方法名）
+    pub fn operation_id(mut self, id: &str) -> Self {
+        self.operation_id = Some(id.to_string());
+        self
+    }
+
+    /// 添加标签
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// 标记为已废弃
+    pub fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    /// 指定路径参数的类型和描述
+    pub fn path_param<T: ApiSchema>(self, name: &str, description: &str) -> Self {
+        self.parameter::<T>(name, "path", description, true)
+    }
+
+    /// 添加查询参数，`Option` 类型为可选
+    pub fn query_param<T: ApiSchema>(self, name: &str, description: &str) -> Self {
+        self.parameter::<T>(name, "query", description, T::is_required())
+    }
+
+    /// 把结构体的每个字段展开为查询参数，对应 `Query<T>` 提取器
+    pub fn query<T: ApiSchema>(mut self) -> Self {
+        self.queries.push(schema_of::<T>);
+        self
+    }
+
+    /// 添加请求头参数
+    pub fn header<T: ApiSchema>(self, name: &str, description: &str) -> Self {
+        self.parameter::<T>(name, "header", description, T::is_required())
+    }
+
+    /// 设置 JSON 请求体
+    pub fn request_body<T: ApiSchema>(self) -> Self {
+        self.request_body_with::<T>("application/json")
+    }
+
+    /// 设置指定内容类型的请求体
+    pub fn request_body_with<T: ApiSchema>(mut self, content_type: &str) -> Self {
+        self.request_body = Some((content_type.to_string(), schema_of::<T>));
+        self
+    }
+
+    /// 添加 JSON 响应
+    pub fn response<T: ApiSchema>(mut self, status: u16, description: &str) -> Self {
+        self.responses.push((status, description.to_string(), Some(("application/json".to_string(), schema_of::<T>))));
+        self
+    }
+
+    /// 添加没有响应体的响应
+    pub fn response_empty(mut self, status: u16, description: &str) -> Self {
+        self.responses.push((status, description.to_string(), None));
+        self
+    }
+
+    /// 设置 JSON 响应的示例，mock 模式下作为响应体
+    pub fn response_example(mut self, status: u16, example: Value) -> Self {
+        self.examples.retain(|(s, _)| *s != status);
+        self.examples.push((status, example));
+        self
+    }
+
+    /// 要求安全方案，名称对应 `OpenApiBuilder::security_scheme`
+    pub fn security(mut self, scheme: &str) -> Self {
+        self.security.push(scheme.to_string());
+        self
+    }
+
+    fn parameter<T: ApiSchema>(mut self, name: &str, location: &'static str, description: &str, required: bool) -> Self {
+        self.parameters.retain(|p| !(p.name == name && p.location == location));
+        self.parameters.push(ApiParameter {
+            name: name.to_string(),
+            location,
+            schema: schema_of::<T>,
+            required,
+            description: (!description.is_empty()).then(|| description.to_string()),
+        });
+        self
+    }
+
+    /// 生成 Operation 对象
+    fn to_json(&self, path_params: &[String], registry: &mut SchemaRegistry) -> Value {
+        let mut operation = Map::new();
+        if let Some(summary) = &self.summary {
+            operation.insert("summary".into(), json!(summary));
+        }
+        if let Some(description) = &self.description {
+            operation.insert("description".into(), json!(description));
+        }
+        if let Some(id) = &self.operation_id {
+            operation.insert("operationId".into(), json!(id));
+        }
+        if !self.tags.is_empty() {
+            operation.insert("tags".into(), json!(self.tags));
+        }
+        if self.deprecated {
+            operation.insert("deprecated".into(), json!(true));
+        }
+
+        let mut parameters = Vec::new();
+        for name in path_params {
+            if !self.parameters.iter().any(|p| p.location == "path" && &p.name == name) {
+                parameters.push(json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }));
+            }
+        }
+        for param in &self.parameters {
+            let mut value = json!({
+                "name": param.name,
+                "in": param.location,
+                "required": param.required,
+                "schema": (param.schema)(registry),
+            });
+            if let Some(description) = &param.description {
+                value["description"] = json!(description);
+            }
+            parameters.push(value);
+        }
+        for query in &self.queries {
+            let schema = query(registry);
+            let schema = registry.resolve(&schema).clone();
+            let required: Vec<&str> = schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+            for (name, property) in schema["properties"].as_object().into_iter().flatten() {
+                let mut property = property.clone();
+                let description = property.as_object_mut().and_then(|p| p.remove("description"));
+                let mut value = json!({ "name": name, "in": "query", "required": required.contains(&name.as_str()), "schema": property });
+                if let Some(description) = description {
+                    value["description"] = description;
+                }
+                parameters.push(value);
+            }
+        }
+        if !parameters.is_empty() {
+            operation.insert("parameters".into(), json!(parameters));
+        }
+
+        if let Some((content_type, schema)) = &self.request_body {
+            operation.insert("requestBody".into(), json!({
+                "required": true,
+                "content": { content_type.as_str(): { "schema": schema(registry) } },
+            }));
+        }
+
+        let mut responses = Map::new();
+        for (status, description, content) in &self.responses {
+            let mut response = json!({ "description": description });
+            if let Some((content_type, schema)) = content {
+                response["content"] = json!({ content_type.as_str(): { "schema": schema(registry) } });
+                if let Some((_, example)) = self.examples.iter().find(|(s, _)| s == status) {
+                    response["content"][content_type.as_str()]["example"] = example.clone();
+                }
+            }
+            responses.insert(status.to_string(), response);
+        }
+        if responses.is_empty() {
+            responses.insert("200".into(), json!({ "description": "OK" }));
+        }
+        operation.insert("responses".into(), Value::Object(responses));
+
+        if !self.security.is_empty() {
+            let security: Vec<Value> = self.security.iter().map(|s| json!({ s.as_str(): [] })).collect();
+            operation.insert("security".into(), json!(security));
+        }
+        Value::Object(operation)
+    }
+}
+
+/// 路由模式转换为 OpenAPI 路径，返回路径和路径参数名
+pub(crate) fn openapi_path(pattern: &str) -> rf_errors::Result<(String, Vec<String>)> {
+    let path = crate::http::to_axum_path(pattern)?.replace("{*", "{");
+    let params = path
+        .split('/')
+        .filter_map(|s| s.strip_prefix('{').and_then(|s| s.strip_suffix('}')))
+        .map(str::to_string)
+        .collect();
+    Ok((path, params))
+}
 
 /// OpenAPI 规范构建器
 ///
@@ -47,15 +594,19 @@
 /// - `title`: API 标题
 /// - `version`: API 版本号
 /// - `description`: API 描述（可选）
+/// - `operations`: 已注册的路由操作
 ///
 /// # 示例
 ///
 /// ```ignore
 /// let builder = OpenApiBuilder::new("User API", "2.0.0")
-///     .description("用户管理 API");
+///     .description("用户管理 API")
+///     .server("https://api.example.com", "生产环境")
+///     .bearer_auth("bearer");
 ///
 /// let spec = builder.build();
 /// ```
+#[derive(Debug, Clone)]
 pub struct OpenApiBuilder {
     /// API 标题
     title: String,
@@ -63,6 +614,18 @@ pub struct OpenApiBuilder {
     version: String,
     /// API 描述（可选）
     description: Option<String>,
+    /// 服务器地址和描述
+    servers: Vec<(String, String)>,
+    /// 标签和描述
+    tags: Vec<(String, String)>,
+    /// 安全方案
+    security_schemes: Map<String, Value>,
+    /// 方法、路由模式和操作描述
+    operations: Vec<(Method, String, ApiOperation)>,
+    /// 文档 JSON 的路径
+    json_path: String,
+    /// Swagger UI 路径（可选）
+    swagger_ui: Option<String>,
 }
 
 impl OpenApiBuilder {
@@ -87,6 +650,12 @@ impl OpenApiBuilder {
             title: title.to_string(),
             version: version.to_string(),
             description: None,
+            servers: Vec::new(),
+            tags: Vec::new(),
+            security_schemes: Map::new(),
+            operations: Vec::new(),
+            json_path: "/openapi.json".to_string(),
+            swagger_ui: None,
         }
     }
 
@@ -111,22 +680,124 @@ impl OpenApiBuilder {
         self
     }
 
-    /// 构建 OpenAPI 规范
+    /// 添加服务器地址
+    pub fn server(mut self, url: &str, description: &str) -> Self {
+        self.servers.push((url.to_string(), description.to_string()));
+        self
+    }
+
+    /// 添加标签描述
+    pub fn tag(mut self, name: &str, description: &str) -> Self {
+        self.tags.push((name.to_string(), description.to_string()));
+        self
+    }
+
+    /// 添加安全方案（Security Scheme 对象）
     ///
-    /// # 返回值
+    /// # 示例
     ///
-    /// 返回一个完整的 OpenAPI 规范对象
+    /// ```ignore
+    /// let builder = OpenApiBuilder::new("My API", "1.0.0")
+    ///     .security_scheme("api_key", json!({"type": "apiKey", "in": "header", "name": "X-API-Key"}));
+    /// ```
+    pub fn security_scheme(mut self, name: &str, scheme: Value) -> Self {
+        self.security_schemes.insert(name.to_string(), scheme);
+        self
+    }
+
+    /// 添加 JWT Bearer 认证方案
+    pub fn bearer_auth(self, name: &str) -> Self {
+        self.security_scheme(name, json!({ "type": "http", "scheme": "bearer", "bearerFormat": "JWT" }))
+    }
+
+    /// 设置文档 JSON 的路径，默认 `/openapi.json`
+    pub fn json_path(mut self, path: &str) -> Self {
+        self.json_path = path.to_string();
+        self
+    }
+
+    /// 在指定路径提供 Swagger UI，加载本文档
+    pub fn swagger_ui(mut self, path: &str) -> Self {
+        self.swagger_ui = Some(path.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// 添加路由操作
     ///
-    /// # 注意
+    /// 路由模式使用 `/users/:id` 语法，与 `HttpServer::route` 相同。
+    pub fn operation(mut self, method: Method, pattern: &str, operation: ApiOperation) -> Self {
+        self.add_operation(method, pattern, operation);
+        self
+    }
+
+    pub(crate) fn add_operation(&mut self, method: Method, pattern: &str, operation: ApiOperation) {
+        self.operations.push((method, pattern.to_string(), operation));
+    }
+
+    /// 文档 JSON 的路径
+    pub fn path(&self) -> &str {
+        &self.json_path
+    }
+
+    /// Swagger UI 路径
+    pub fn swagger_ui_path(&self) -> Option<&str> {
+        self.swagger_ui.as_deref()
+    }
+
+    /// 生成 OpenAPI 3.1 文档 JSON
     ///
-    /// 这是一个简化的实现，只包含基本信息。
-    /// 对于完整的 OpenAPI 支持，应该使用 utoipa 的派生宏：
+    /// 操作中引用的有名称类型收集到 `components.schemas`。无法解析的
+    /// 路由模式会被跳过并记录警告。
+    pub fn to_json(&self) -> Value {
+        let mut registry = SchemaRegistry::new();
+        let mut paths: BTreeMap<String, Map<String, Value>> = BTreeMap::new();
+        for (method, pattern, operation) in &self.operations {
+            let (path, params) = match openapi_path(pattern) {
+                Ok(path) => path,
+                Err(e) => {
+                    tracing::warn!("Skipping OpenAPI operation {} {}: {}", method, pattern, e);
+                    continue;
+                }
+            };
+            let item = paths.entry(path).or_default();
+            item.insert(method.as_str().to_lowercase(), operation.to_json(&params, &mut registry));
+        }
+
+        let mut info = json!({ "title": self.title, "version": self.version });
+        if let Some(description) = &self.description {
+            info["description"] = json!(description);
+        }
+        let mut document = json!({ "openapi": "3.1.0", "info": info, "paths": paths });
+        if !self.servers.is_empty() {
+            let servers: Vec<Value> = self.servers.iter().map(|(url, description)| {
+                json!({ "url": url, "description": description })
+            }).collect();
+            document["servers"] = json!(servers);
+        }
+        if !self.tags.is_empty() {
+            let tags: Vec<Value> = self.tags.iter().map(|(name, description)| {
+                json!({ "name": name, "description": description })
+            }).collect();
+            document["tags"] = json!(tags);
+        }
+        let mut components = Map::new();
+        if !registry.schemas().is_empty() {
+            components.insert("schemas".into(), json!(registry.schemas()));
+        }
+        if !self.security_schemes.is_empty() {
+            components.insert("securitySchemes".into(), Value::Object(self.security_schemes.clone()));
+        }
+        if !components.is_empty() {
+            document["components"] = Value::Object(components);
+        }
+        document
+    }
+
+    /// 构建 OpenAPI 规范
     ///
-    /// ```ignore
-    /// #[derive(OpenApi)]
-    /// #[openapi(paths(...), components(...))]
-    /// struct ApiDoc;
-    /// ```
+    /// # 返回值
+    ///
+    /// 返回一个完整的 OpenAPI 规范对象，包含已添加的操作和组件
     ///
     /// # 示例
     ///
@@ -135,18 +806,19 @@ impl OpenApiBuilder {
     /// let json = serde_json::to_string(&spec)?;
     /// ```
     pub fn build(self) -> utoipa::openapi::OpenApi {
-        // 创建基本的 OpenAPI 规范
-        let mut spec = utoipa::openapi::OpenApi::default();
-        let info = utoipa::openapi::InfoBuilder::new()
-            .title(&self.title)
-            .version(&self.version);
-        let info = if let Some(desc) = &self.description {
-            info.description(Some(desc))
-        } else {
-            info
-        };
-        spec.info = info.build();
-        spec
+        match serde_json::from_value(self.to_json()) {
+            Ok(spec) => spec,
+            Err(e) => {
+                tracing::warn!("Generated OpenAPI document is not representable, keeping info only: {}", e);
+                let mut spec = utoipa::openapi::OpenApi::default();
+                let info = utoipa::openapi::InfoBuilder::new()
+                    .title(&self.title)
+                    .version(&self.version)
+                    .description(self.description.as_deref());
+                spec.info = info.build();
+                spec
+            }
+        }
     }
 }
 
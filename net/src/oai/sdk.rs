@@ -0,0 +1,1191 @@
+//! # sdk
+//!
+//! sdk 模块 - 从 OpenAPI 文档生成类型化客户端
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! 类型化 HTTP SDK 生成
+//!
+//! 读取 `api_route` 注册的路由和 `ApiSchema` 类型生成的 OpenAPI 文档，输出：
+//! - Rust 客户端 crate：`components.schemas` 中的类型生成结构体/枚举，每个操作生成一个
+//!   异步方法，请求经由 `rf-contrib-sdk-httpclient` 发送（重试、熔断、负载均衡）
+//! - TypeScript 客户端（可选）：接口类型加基于 `fetch` 的客户端类
+//!
+//! 方法名取 `ApiOperation::operation_id`，未设置时由请求方法和路径生成
+//! （`GET /orders/{id}` → `get_orders_by_id`）。
+//!
+//! # 示例
+//!
+//! ```ignore
+//! use rf_net::oai::SdkGenerator;
+//!
+//! // 在服务端代码（如测试或 build 工具）中直接使用路由注册表
+//! SdkGenerator::from_server(&server)?
+//!     .crate_name("shop-client")
+//!     .write_rust("sdk/shop-client")?;
+//!
+//! // 或者从导出的文档生成（`rf gen sdk --spec openapi.json` 同理）
+//! let document = serde_json::from_str(&std::fs::read_to_string("openapi.json")?)?;
+//! SdkGenerator::new(document).write_typescript("web/src/api/shop.ts")?;
+//! ```
+
+use crate::http::HttpServer;
+use rf_errors::{Result, RfError};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 生成的文件，路径相对于输出目录
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// 生成的 crate 如何依赖 RF
+#[derive(Debug, Clone)]
+enum RfSource {
+    Version(String),
+    Path(String),
+    Git(String),
+}
+
+/// SDK 生成器
+#[derive(Debug, Clone)]
+pub struct SdkGenerator {
+    document: Value,
+    crate_name: Option<String>,
+    client_name: Option<String>,
+    rf_source: RfSource,
+}
+
+impl SdkGenerator {
+    /// 从 OpenAPI 3.x 文档创建
+    pub fn new(document: Value) -> Self {
+        Self {
+            document,
+            crate_name: None,
+            client_name: None,
+            rf_source: RfSource::Version(env!("CARGO_PKG_VERSION").to_string()),
+        }
+    }
+
+    /// 从服务器的路由注册表创建，服务器需要通过 `with_openapi` 启用文档
+    pub fn from_server(server: &HttpServer) -> Result<Self> {
+        server
+            .openapi_document()
+            .map(Self::new)
+            .ok_or_else(|| RfError::Config("OpenAPI is not enabled on this server, call with_openapi first".to_string()))
+    }
+
+    /// 生成的 crate 名称（默认：文档标题 + `-client`）
+    pub fn crate_name(mut self, name: &str) -> Self {
+        self.crate_name = Some(name.to_string());
+        self
+    }
+
+    /// 客户端类型名称（默认：文档标题 + `Client`）
+    pub fn client_name(mut self, name: &str) -> Self {
+        self.client_name = Some(name.to_string());
+        self
+    }
+
+    /// 依赖指定版本的 RF crate（默认为当前版本）
+    pub fn rf_version(mut self, version: &str) -> Self {
+        self.rf_source = RfSource::Version(version.to_string());
+        self
+    }
+
+    /// 依赖本地 RF 源码目录，路径原样写入生成的 Cargo.toml
+    pub fn rf_path(mut self, path: &str) -> Self {
+        self.rf_source = RfSource::Path(path.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// 依赖 RF 的 git 仓库
+    pub fn rf_git(mut self, url: &str) -> Self {
+        self.rf_source = RfSource::Git(url.to_string());
+        self
+    }
+
+    /// 生成 Rust crate：`Cargo.toml`、`src/lib.rs`（客户端）和 `src/models.rs`（类型）
+    pub fn generate_rust(&self) -> Result<Vec<GeneratedFile>> {
+        let api = Api::parse(&self.document)?;
+        let client = self.client_type_name(&api);
+        let render = || -> std::result::Result<Vec<GeneratedFile>, fmt::Error> {
+            Ok(vec![
+                GeneratedFile { path: PathBuf::from("Cargo.toml"), contents: self.cargo_toml(&api)? },
+                GeneratedFile { path: PathBuf::from("src/lib.rs"), contents: rust_client(&api, &client)? },
+                GeneratedFile { path: PathBuf::from("src/models.rs"), contents: rust_models(&api)? },
+            ])
+        };
+        render().map_err(|_| RfError::Internal("Failed to render SDK".to_string()))
+    }
+
+    /// 生成 TypeScript 客户端（单个文件）
+    pub fn generate_typescript(&self) -> Result<GeneratedFile> {
+        let api = Api::parse(&self.document)?;
+        let client = self.client_type_name(&api);
+        let contents =
+            typescript_client(&api, &client).map_err(|_| RfError::Internal("Failed to render SDK".to_string()))?;
+        Ok(GeneratedFile { path: PathBuf::from("client.ts"), contents })
+    }
+
+    /// 把 Rust crate 写入目录，已有的生成文件会被覆盖
+    pub fn write_rust(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        for file in self.generate_rust()? {
+            write_file(&dir.join(&file.path), &file.contents)?;
+        }
+        Ok(())
+    }
+
+    /// 把 TypeScript 客户端写入文件
+    pub fn write_typescript(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_file(path.as_ref(), &self.generate_typescript()?.contents)
+    }
+
+    fn client_type_name(&self, api: &Api) -> String {
+        match &self.client_name {
+            Some(name) => name.clone(),
+            None => format!("{}Client", pascal_case(&api.title)),
+        }
+    }
+
+    fn cargo_toml(&self, api: &Api) -> std::result::Result<String, fmt::Error> {
+        let name = match &self.crate_name {
+            Some(name) => name.clone(),
+            None => format!("{}-client", snake_case(&api.title).replace('_', "-")),
+        };
+        let (httpclient, errors) = match &self.rf_source {
+            RfSource::Version(version) => (format!("\"{}\"", version), format!("\"{}\"", version)),
+            RfSource::Path(path) => (
+                format!("{{ path = \"{}/contrib/sdk/httpclient\" }}", path),
+                format!("{{ path = \"{}/errors\" }}", path),
+            ),
+            RfSource::Git(url) => (format!("{{ git = \"{}\" }}", url), format!("{{ git = \"{}\" }}", url)),
+        };
+        let mut out = String::new();
+        writeln!(out, "# Generated by `rf gen sdk` from \"{}\" {}. Do not edit.", api.title, api.version)?;
+        writeln!(out, "[package]")?;
+        writeln!(out, "name = \"{}\"", name)?;
+        writeln!(out, "version = \"{}\"", cargo_version(&api.version))?;
+        writeln!(out, "edition = \"2021\"")?;
+        writeln!(out, "description = {}", toml_string(&format!("Client for {}", api.title)))?;
+        writeln!(out)?;
+        writeln!(out, "[dependencies]")?;
+        writeln!(out, "rf-contrib-sdk-httpclient = {}", httpclient)?;
+        writeln!(out, "rf-errors = {}", errors)?;
+        writeln!(out, "serde = {{ version = \"1.0\", features = [\"derive\"] }}")?;
+        writeln!(out, "serde_json = \"1.0\"")?;
+        Ok(out)
+    }
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// 从文档中提取的 API 描述
+struct Api {
+    title: String,
+    version: String,
+    description: Option<String>,
+    base_url: Option<String>,
+    schemas: Vec<(String, Value)>,
+    operations: Vec<Operation>,
+}
+
+struct Operation {
+    method: String,
+    path: String,
+    /// snake_case 方法名
+    name: String,
+    summary: Option<String>,
+    description: Option<String>,
+    deprecated: bool,
+    path_params: Vec<Param>,
+    query: Vec<Param>,
+    headers: Vec<Param>,
+    body: Option<(String, Value)>,
+    response: ResponseKind,
+}
+
+struct Param {
+    name: String,
+    schema: Value,
+    required: bool,
+    description: Option<String>,
+}
+
+enum ResponseKind {
+    Json(Value),
+    Text,
+    Empty,
+}
+
+impl Api {
+    fn parse(document: &Value) -> Result<Self> {
+        let version = document["openapi"].as_str().unwrap_or_default();
+        if !version.starts_with("3.") {
+            return Err(RfError::InvalidParameter(format!(
+                "Unsupported OpenAPI version '{}', expected 3.x",
+                version
+            )));
+        }
+        let info = &document["info"];
+        let schemas = document["components"]["schemas"]
+            .as_object()
+            .map(|schemas| schemas.iter().map(|(name, schema)| (name.clone(), schema.clone())).collect())
+            .unwrap_or_default();
+
+        let mut operations = Vec::new();
+        let mut names = HashSet::new();
+        for (path, item) in document["paths"].as_object().into_iter().flatten() {
+            // 路径级参数对该路径下所有操作生效
+            let shared = item["parameters"].as_array().cloned().unwrap_or_default();
+            for method in ["get", "put", "post", "delete", "options", "head", "patch", "trace"] {
+                let Some(operation) = item.get(method).filter(|op| op.is_object()) else {
+                    continue;
+                };
+                let mut name = match operation["operationId"].as_str() {
+                    Some(id) => snake_case(id),
+                    None => operation_name(method, path),
+                };
+                if !names.insert(name.clone()) {
+                    let mut index = 2;
+                    while !names.insert(format!("{}_{}", name, index)) {
+                        index += 1;
+                    }
+                    name = format!("{}_{}", name, index);
+                }
+                operations.push(Operation::parse(document, method, path, name, operation, &shared));
+            }
+        }
+
+        Ok(Self {
+            title: info["title"].as_str().unwrap_or("Api").to_string(),
+            version: info["version"].as_str().unwrap_or("0.1.0").to_string(),
+            description: info["description"].as_str().map(str::to_string),
+            base_url: document["servers"][0]["url"].as_str().map(str::to_string),
+            schemas,
+            operations,
+        })
+    }
+}
+
+impl Operation {
+    fn parse(document: &Value, method: &str, path: &str, name: String, operation: &Value, shared: &[Value]) -> Self {
+        let mut path_params = Vec::new();
+        let mut query = Vec::new();
+        let mut headers = Vec::new();
+        let parameters = shared.iter().chain(operation["parameters"].as_array().into_iter().flatten());
+        for param in parameters {
+            let param = resolve(document, param);
+            let Some(param_name) = param["name"].as_str() else {
+                continue;
+            };
+            let location = param["in"].as_str().unwrap_or_default();
+            let list = match location {
+                "path" => &mut path_params,
+                "query" => &mut query,
+                "header" => &mut headers,
+                _ => continue,
+            };
+            // 操作级参数覆盖同名的路径级参数
+            list.retain(|p: &Param| p.name != param_name);
+            list.push(Param {
+                name: param_name.to_string(),
+                schema: param.get("schema").cloned().unwrap_or(Value::Object(Map::new())),
+                required: location == "path" || param["required"].as_bool().unwrap_or(false),
+                description: param["description"].as_str().map(str::to_string),
+            });
+        }
+        // 路径参数按出现顺序排列
+        let order: Vec<&str> = path
+            .split('/')
+            .filter_map(|s| s.strip_prefix('{').and_then(|s| s.strip_suffix('}')))
+            .collect();
+        path_params.sort_by_key(|p| order.iter().position(|name| *name == p.name).unwrap_or(usize::MAX));
+
+        let body = resolve(document, &operation["requestBody"])["content"]
+            .as_object()
+            .and_then(|content| {
+                content
+                    .get("application/json")
+                    .map(|media| ("application/json", media))
+                    .or_else(|| content.iter().next().map(|(ty, media)| (ty.as_str(), media)))
+            })
+            .map(|(content_type, media)| (content_type.to_string(), media["schema"].clone()));
+
+        let mut response = ResponseKind::Empty;
+        let mut statuses: Vec<(&String, &Value)> = operation["responses"].as_object().into_iter().flatten().collect();
+        statuses.sort_by_key(|(status, _)| status.as_str());
+        if let Some((_, success)) = statuses.into_iter().find(|(status, _)| status.starts_with('2')) {
+            let content = resolve(document, success)["content"].clone();
+            if let Some(media) = content.get("application/json") {
+                response = ResponseKind::Json(media["schema"].clone());
+            } else if content.as_object().is_some_and(|c| !c.is_empty()) {
+                response = ResponseKind::Text;
+            }
+        }
+
+        Self {
+            method: method.to_uppercase(),
+            path: path.to_string(),
+            name,
+            summary: operation["summary"].as_str().map(str::to_string),
+            description: operation["description"].as_str().map(str::to_string),
+            deprecated: operation["deprecated"].as_bool().unwrap_or(false),
+            path_params,
+            query,
+            headers,
+            body,
+            response,
+        }
+    }
+
+    /// 查询参数结构体名称
+    fn query_type(&self) -> String {
+        format!("{}Query", pascal_case(&self.name))
+    }
+}
+
+/// 解析 `#/components/...` 引用（参数、请求体、响应可以是引用）
+fn resolve<'a>(document: &'a Value, value: &'a Value) -> &'a Value {
+    match value["$ref"].as_str().and_then(|r| r.strip_prefix("#/")) {
+        Some(pointer) => document.pointer(&format!("/{}", pointer)).unwrap_or(value),
+        None => value,
+    }
+}
+
+/// 由请求方法和路径生成方法名：`GET /orders/{id}/items` → `get_orders_by_id_items`
+fn operation_name(method: &str, path: &str) -> String {
+    let mut parts = vec![method.to_string()];
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(param) => parts.push(format!("by_{}", snake_case(param))),
+            None => parts.push(snake_case(segment)),
+        }
+    }
+    if parts.len() == 1 {
+        parts.push("root".to_string());
+    }
+    parts.retain(|p| !p.is_empty());
+    parts.join("_")
+}
+
+fn ref_name(schema: &Value) -> Option<&str> {
+    schema["$ref"].as_str().and_then(|r| r.rsplit('/').next())
+}
+
+/// 拆分 `anyOf: [T, null]` 和 `type: [T, "null"]`，返回非空部分和是否可为 null
+fn split_nullable(schema: &Value) -> (Value, bool) {
+    if let Some(variants) = schema.get("anyOf").or_else(|| schema.get("oneOf")).and_then(Value::as_array) {
+        let non_null: Vec<&Value> = variants.iter().filter(|v| v["type"] != "null").collect();
+        if non_null.len() == 1 && non_null.len() < variants.len() {
+            return (non_null[0].clone(), true);
+        }
+    }
+    if let Some(types) = schema["type"].as_array() {
+        let non_null: Vec<&Value> = types.iter().filter(|t| *t != "null").collect();
+        if non_null.len() == 1 && non_null.len() < types.len() {
+            let mut inner = schema.clone();
+            inner["type"] = non_null[0].clone();
+            return (inner, true);
+        }
+    }
+    (schema.clone(), false)
+}
+
+// ---------------------------------------------------------------------------
+// Rust
+// ---------------------------------------------------------------------------
+
+/// JSON Schema 对应的 Rust 类型
+fn rust_type(schema: &Value) -> String {
+    if let Some(name) = ref_name(schema) {
+        return type_name(name);
+    }
+    let (schema, nullable) = split_nullable(schema);
+    if nullable {
+        return format!("Option<{}>", rust_type(&schema));
+    }
+    let format = schema["format"].as_str().unwrap_or_default();
+    let unsigned = schema["minimum"].as_f64().is_some_and(|min| min >= 0.0);
+    match schema["type"].as_str() {
+        Some("string") if format == "binary" => "Vec<u8>".to_string(),
+        Some("string") => "String".to_string(),
+        Some("integer") => match (format, unsigned) {
+            ("int32", false) => "i32",
+            ("int32", true) => "u32",
+            (_, true) => "u64",
+            (_, false) => "i64",
+        }
+        .to_string(),
+        Some("number") if format == "float" => "f32".to_string(),
+        Some("number") => "f64".to_string(),
+        Some("boolean") => "bool".to_string(),
+        Some("array") => format!("Vec<{}>", rust_type(&schema["items"])),
+        Some("object") if schema["additionalProperties"].is_object() => {
+            format!("std::collections::HashMap<String, {}>", rust_type(&schema["additionalProperties"]))
+        }
+        _ => "serde_json::Value".to_string(),
+    }
+}
+
+fn rust_models(api: &Api) -> std::result::Result<String, fmt::Error> {
+    let mut out = String::new();
+    writeln!(out, "//! Types of the {} API", api.title)?;
+    writeln!(out, "//!")?;
+    writeln!(out, "//! Generated by `rf gen sdk`. Do not edit.")?;
+    writeln!(out)?;
+    writeln!(out, "use serde::{{Deserialize, Serialize}};")?;
+
+    for (name, schema) in &api.schemas {
+        let type_name = type_name(name);
+        writeln!(out)?;
+        rust_doc(&mut out, "", schema["description"].as_str())?;
+        if let Some(variants) = schema["enum"].as_array().filter(|_| schema["type"] == "string") {
+            writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]")?;
+            writeln!(out, "pub enum {} {{", type_name)?;
+            let mut used = HashSet::new();
+            for value in variants.iter().filter_map(Value::as_str) {
+                let variant = unique(&mut used, variant_name(value));
+                writeln!(out, "    #[serde(rename = {:?})]", value)?;
+                writeln!(out, "    {},", variant)?;
+            }
+            writeln!(out, "}}")?;
+        } else if let Some(properties) = schema["properties"].as_object() {
+            let required: Vec<&str> = schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+            writeln!(out, "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]")?;
+            writeln!(out, "pub struct {} {{", type_name)?;
+            rust_fields(&mut out, properties, &required, Some(name))?;
+            writeln!(out, "}}")?;
+        } else {
+            writeln!(out, "pub type {} = {};", type_name, rust_type(schema))?;
+        }
+    }
+
+    for operation in api.operations.iter().filter(|op| !op.query.is_empty()) {
+        writeln!(out)?;
+        writeln!(out, "/// Query parameters of `{} {}`", operation.method, operation.path)?;
+        let all_optional = operation.query.iter().all(|p| !p.required);
+        let default = if all_optional { "Default, " } else { "" };
+        writeln!(out, "#[derive(Debug, Clone, {}PartialEq, Serialize, Deserialize)]", default)?;
+        writeln!(out, "pub struct {} {{", operation.query_type())?;
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for param in &operation.query {
+            let mut schema = param.schema.clone();
+            if let (Some(description), Some(object)) = (&param.description, schema.as_object_mut()) {
+                object.entry("description").or_insert_with(|| Value::String(description.clone()));
+            }
+            properties.insert(param.name.clone(), schema);
+            if param.required {
+                required.push(param.name.as_str());
+            }
+        }
+        rust_fields(&mut out, &properties, &required, None)?;
+        writeln!(out, "}}")?;
+    }
+    Ok(out)
+}
+
+fn rust_fields(
+    out: &mut String,
+    properties: &Map<String, Value>,
+    required: &[&str],
+    owner: Option<&str>,
+) -> fmt::Result {
+    let mut used = HashSet::new();
+    for (name, schema) in properties {
+        rust_doc(out, "    ", schema["description"].as_str())?;
+        let field = unique(&mut used, rust_ident(&snake_case(name)));
+        if field.trim_start_matches("r#") != name {
+            writeln!(out, "    #[serde(rename = {:?})]", name)?;
+        }
+        let (inner, nullable) = split_nullable(schema);
+        let mut ty = rust_type(&inner);
+        // 直接引用自身的字段需要装箱
+        if owner.is_some() && ref_name(&inner) == owner {
+            ty = format!("Box<{}>", ty);
+        }
+        if nullable || !required.contains(&name.as_str()) {
+            if !required.contains(&name.as_str()) {
+                writeln!(out, "    #[serde(default, skip_serializing_if = \"Option::is_none\")]")?;
+            }
+            ty = format!("Option<{}>", ty);
+        }
+        writeln!(out, "    pub {}: {},", field, ty)?;
+    }
+    Ok(())
+}
+
+fn rust_client(api: &Api, client: &str) -> std::result::Result<String, fmt::Error> {
+    let mut out = String::new();
+    writeln!(out, "//! Client for the {} API ({})", api.title, api.version)?;
+    if let Some(description) = &api.description {
+        writeln!(out, "//!")?;
+        for line in description.lines() {
+            writeln!(out, "//! {}", line)?;
+        }
+    }
+    writeln!(out, "//!")?;
+    writeln!(out, "//! Generated by `rf gen sdk`. Do not edit.")?;
+    writeln!(out)?;
+    writeln!(out, "mod models;")?;
+    writeln!(out)?;
+    writeln!(out, "pub use models::*;")?;
+    writeln!(out, "pub use rf_contrib_sdk_httpclient::{{HttpClient, MultipartForm}};")?;
+    writeln!(out, "pub use rf_errors::{{Result, RfError}};")?;
+    writeln!(out)?;
+    writeln!(out, "use rf_contrib_sdk_httpclient::{{ClientRequest, Method}};")?;
+    writeln!(out)?;
+    writeln!(out, "/// {} client", api.title)?;
+    writeln!(out, "///")?;
+    writeln!(out, "/// Requests go through `HttpClient`, so retries, circuit breaking and load")?;
+    writeln!(out, "/// balancing configured there apply to every call.")?;
+    writeln!(out, "pub struct {} {{", client)?;
+    writeln!(out, "    http: HttpClient,")?;
+    writeln!(out, "    bearer_token: Option<String>,")?;
+    writeln!(out, "    headers: Vec<(String, String)>,")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "impl {} {{", client)?;
+    if let Some(base_url) = &api.base_url {
+        writeln!(out, "    /// First server listed in the API document")?;
+        writeln!(out, "    pub const DEFAULT_BASE_URL: &'static str = {:?};", base_url)?;
+        writeln!(out)?;
+    }
+    writeln!(out, "    /// Create a client for the API at `base_url`")?;
+    writeln!(out, "    pub fn new(base_url: impl Into<String>) -> Self {{")?;
+    writeln!(out, "        let base_url = base_url.into().trim_end_matches('/').to_string();")?;
+    writeln!(out, "        Self::with_http_client(HttpClient::new().with_base_url(base_url))")?;
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(out, "    /// Use a configured client (base URL or load balancer, retry, circuit breaker)")?;
+    writeln!(out, "    pub fn with_http_client(http: HttpClient) -> Self {{")?;
+    writeln!(out, "        Self {{ http, bearer_token: None, headers: Vec::new() }}")?;
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(out, "    /// Send `Authorization: Bearer <token>` with every request")?;
+    writeln!(out, "    pub fn bearer_token(mut self, token: impl Into<String>) -> Self {{")?;
+    writeln!(out, "        self.bearer_token = Some(token.into());")?;
+    writeln!(out, "        self")?;
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(out, "    /// Send a header with every request, e.g. an API key")?;
+    writeln!(out, "    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {{")?;
+    writeln!(out, "        self.headers.push((name.into(), value.into()));")?;
+    writeln!(out, "        self")?;
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(out, "    /// Underlying HTTP client")?;
+    writeln!(out, "    pub fn http_client(&self) -> &HttpClient {{")?;
+    writeln!(out, "        &self.http")?;
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(out, "    fn request(&self, method: Method, path: &str) -> ClientRequest<'_> {{")?;
+    writeln!(out, "        let mut request = self.http.request(method, path);")?;
+    writeln!(out, "        for (name, value) in &self.headers {{")?;
+    writeln!(out, "            request = request.header(name, value);")?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "        if let Some(token) = &self.bearer_token {{")?;
+    writeln!(out, "            request = request.bearer_auth(token);")?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "        request")?;
+    writeln!(out, "    }}")?;
+    for operation in &api.operations {
+        writeln!(out)?;
+        rust_operation(&mut out, operation)?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "/// Percent-encode a path parameter")?;
+    writeln!(out, "#[allow(dead_code)]")?;
+    writeln!(out, "fn encode_path(value: &str) -> String {{")?;
+    writeln!(out, "    let mut encoded = String::with_capacity(value.len());")?;
+    writeln!(out, "    for byte in value.bytes() {{")?;
+    writeln!(out, "        match byte {{")?;
+    writeln!(out, "            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),")?;
+    writeln!(out, "            _ => encoded.push_str(&format!(\"%{{:02X}}\", byte)),")?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "    encoded")?;
+    writeln!(out, "}}")?;
+    Ok(out)
+}
+
+fn rust_operation(out: &mut String, op: &Operation) -> fmt::Result {
+    let mut doc = Vec::new();
+    if let Some(summary) = &op.summary {
+        doc.push(summary.clone());
+    }
+    if let Some(description) = &op.description {
+        if !doc.is_empty() {
+            doc.push(String::new());
+        }
+        doc.extend(description.lines().map(str::to_string));
+    }
+    if !doc.is_empty() {
+        doc.push(String::new());
+    }
+    doc.push(format!("`{} {}`", op.method, op.path));
+    rust_doc(out, "    ", Some(&doc.join("\n")))?;
+    if op.deprecated {
+        writeln!(out, "    #[deprecated]")?;
+    }
+
+    let mut args = Vec::new();
+    let mut used: HashSet<String> = ["self", "request"].iter().map(|s| s.to_string()).collect();
+    let mut path_args = Vec::new();
+    for param in &op.path_params {
+        let arg = unique(&mut used, rust_ident(&snake_case(&param.name)));
+        let ty = match rust_type(&param.schema).as_str() {
+            "String" => "&str".to_string(),
+            other => other.to_string(),
+        };
+        let value = if ty == "&str" { arg.clone() } else { format!("&{}.to_string()", arg) };
+        args.push(format!("{}: {}", arg, ty));
+        path_args.push((param.name.clone(), value));
+    }
+    let body_arg = op.body.as_ref().map(|(content_type, schema)| {
+        let arg = unique(&mut used, "body".to_string());
+        let ty = match content_type.as_str() {
+            "application/json" | "application/x-www-form-urlencoded" => format!("&{}", rust_type(schema)),
+            "multipart/form-data" => "MultipartForm".to_string(),
+            _ => "Vec<u8>".to_string(),
+        };
+        args.push(format!("{}: {}", arg, ty));
+        arg
+    });
+    let query_arg = (!op.query.is_empty()).then(|| {
+        let arg = unique(&mut used, "query".to_string());
+        args.push(format!("{}: &{}", arg, op.query_type()));
+        arg
+    });
+    let mut header_args = Vec::new();
+    for param in &op.headers {
+        let arg = unique(&mut used, rust_ident(&snake_case(&param.name)));
+        let ty = if param.required { "&str" } else { "Option<&str>" };
+        args.push(format!("{}: {}", arg, ty));
+        header_args.push((param.name.clone(), arg, param.required));
+    }
+
+    let output = match &op.response {
+        ResponseKind::Json(schema) => rust_type(schema),
+        ResponseKind::Text => "String".to_string(),
+        ResponseKind::Empty => "()".to_string(),
+    };
+    let mut signature = format!("    pub async fn {}(&self", op.name);
+    for arg in &args {
+        signature.push_str(", ");
+        signature.push_str(arg);
+    }
+    writeln!(out, "{}) -> Result<{}> {{", signature, output)?;
+
+    // 路径
+    let mut template = String::new();
+    let mut values = Vec::new();
+    for segment in op.path.split('/').skip(1) {
+        template.push('/');
+        match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(name) => {
+                template.push_str("{}");
+                let value = path_args.iter().find(|(param, _)| param == name).map(|(_, value)| value.as_str());
+                values.push(match value {
+                    Some(value) => format!("encode_path({})", value),
+                    None => "\"\"".to_string(),
+                });
+            }
+            None => template.push_str(&segment.replace('{', "{{").replace('}', "}}")),
+        }
+    }
+    if template.is_empty() {
+        template.push('/');
+    }
+    let path = if values.is_empty() {
+        format!("{:?}", template.replace("{{", "{").replace("}}", "}"))
+    } else {
+        format!("&format!({:?}, {})", template, values.join(", "))
+    };
+    writeln!(out, "        let request = self.request(Method::{}, {});", op.method, path)?;
+
+    if let (Some(arg), Some((content_type, _))) = (&body_arg, &op.body) {
+        match content_type.as_str() {
+            "application/json" => writeln!(out, "        let request = request.json({});", arg)?,
+            "application/x-www-form-urlencoded" => writeln!(out, "        let request = request.form({});", arg)?,
+            "multipart/form-data" => writeln!(out, "        let request = request.multipart({});", arg)?,
+            _ => writeln!(
+                out,
+                "        let request = request.header(\"content-type\", {:?}).body({});",
+                content_type, arg
+            )?,
+        }
+    }
+    if let Some(arg) = &query_arg {
+        writeln!(out, "        let request = request.query({});", arg)?;
+    }
+    for (name, arg, required) in &header_args {
+        if *required {
+            writeln!(out, "        let request = request.header({:?}, {});", name, arg)?;
+        } else {
+            writeln!(out, "        let request = match {} {{", arg)?;
+            writeln!(out, "            Some(value) => request.header({:?}, value),", name)?;
+            writeln!(out, "            None => request,")?;
+            writeln!(out, "        }};")?;
+        }
+    }
+    match op.response {
+        ResponseKind::Json(_) => writeln!(out, "        request.send_json().await")?,
+        ResponseKind::Text => writeln!(out, "        request.send_text().await")?,
+        ResponseKind::Empty => writeln!(out, "        request.send_text().await.map(|_| ())")?,
+    }
+    writeln!(out, "    }}")
+}
+
+fn rust_doc(out: &mut String, indent: &str, doc: Option<&str>) -> fmt::Result {
+    for line in doc.into_iter().flat_map(str::lines) {
+        if line.is_empty() {
+            writeln!(out, "{}///", indent)?;
+        } else {
+            writeln!(out, "{}/// {}", indent, line)?;
+        }
+    }
+    Ok(())
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct",
+    "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "gen",
+    "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// 转为合法的 Rust 标识符
+fn rust_ident(name: &str) -> String {
+    let name = if name.is_empty() { "value".to_string() } else { name.to_string() };
+    let name = if name.starts_with(|c: char| c.is_ascii_digit()) { format!("_{}", name) } else { name };
+    match name.as_str() {
+        "self" | "super" | "crate" | "Self" => format!("{}_", name),
+        keyword if RUST_KEYWORDS.contains(&keyword) => format!("r#{}", keyword),
+        _ => name,
+    }
+}
+
+fn variant_name(value: &str) -> String {
+    let name = pascal_case(value);
+    if name.is_empty() {
+        "Empty".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("V{}", name)
+    } else {
+        name
+    }
+}
+
+/// 在已用名称后追加数字避免重名
+fn unique(used: &mut HashSet<String>, name: String) -> String {
+    if used.insert(name.clone()) {
+        return name;
+    }
+    let mut index = 2;
+    loop {
+        let candidate = format!("{}{}", name, index);
+        if used.insert(candidate.clone()) {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+/// Cargo 要求语义化版本
+fn cargo_version(version: &str) -> String {
+    let parts: Vec<&str> = version.trim_start_matches('v').split('.').collect();
+    if parts.len() == 3 && parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit())) {
+        parts.join(".")
+    } else {
+        "0.1.0".to_string()
+    }
+}
+
+fn toml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// ---------------------------------------------------------------------------
+// TypeScript
+// ---------------------------------------------------------------------------
+
+fn ts_type(schema: &Value) -> String {
+    if let Some(name) = ref_name(schema) {
+        return type_name(name);
+    }
+    let (schema, nullable) = split_nullable(schema);
+    if nullable {
+        return format!("{} | null", ts_type(&schema));
+    }
+    if let Some(variants) = schema["enum"].as_array() {
+        let literals: Vec<String> = variants.iter().map(Value::to_string).collect();
+        if !literals.is_empty() {
+            return literals.join(" | ");
+        }
+    }
+    match schema["type"].as_str() {
+        Some("string") => "string".to_string(),
+        Some("integer") | Some("number") => "number".to_string(),
+        Some("boolean") => "boolean".to_string(),
+        Some("array") => {
+            let items = ts_type(&schema["items"]);
+            if items.contains(' ') {
+                format!("Array<{}>", items)
+            } else {
+                format!("{}[]", items)
+            }
+        }
+        Some("object") if schema["additionalProperties"].is_object() => {
+            format!("Record<string, {}>", ts_type(&schema["additionalProperties"]))
+        }
+        Some("object") if schema["properties"].is_object() => {
+            let required: Vec<&str> = schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+            let fields: Vec<String> = schema["properties"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(name, field)| {
+                    let optional = if required.contains(&name.as_str()) { "" } else { "?" };
+                    format!("{}{}: {}", ts_key(name), optional, ts_type(field))
+                })
+                .collect();
+            format!("{{ {} }}", fields.join("; "))
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+fn ts_key(name: &str) -> String {
+    let plain = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if plain {
+        name.to_string()
+    } else {
+        format!("{:?}", name)
+    }
+}
+
+fn ts_doc(out: &mut String, indent: &str, doc: Option<&str>) -> fmt::Result {
+    let Some(doc) = doc.filter(|d| !d.is_empty()) else {
+        return Ok(());
+    };
+    let doc = doc.replace("*/", "*\\/");
+    if !doc.contains('\n') {
+        return writeln!(out, "{}/** {} */", indent, doc);
+    }
+    writeln!(out, "{}/**", indent)?;
+    for line in doc.lines() {
+        if line.is_empty() {
+            writeln!(out, "{} *", indent)?;
+        } else {
+            writeln!(out, "{} * {}", indent, line)?;
+        }
+    }
+    writeln!(out, "{} */", indent)
+}
+
+const TS_RUNTIME: &str = r#"
+/** Non-2xx response */
+export class ApiError extends Error {
+  constructor(
+    public readonly status: number,
+    public readonly body: string,
+  ) {
+    super(`HTTP ${status}: ${body.slice(0, 512)}`);
+    this.name = "ApiError";
+  }
+}
+
+export interface ClientOptions {
+  /** Sent as `Authorization: Bearer <token>` */
+  token?: string;
+  /** Sent with every request */
+  headers?: Record<string, string>;
+  /** Custom fetch implementation */
+  fetch?: typeof fetch;
+}
+
+interface RequestOptions {
+  query?: Record<string, unknown>;
+  headers?: Record<string, string | undefined>;
+  body?: unknown;
+  contentType?: string;
+  response: "json" | "text" | "empty";
+}
+"#;
+
+const TS_REQUEST: &str = r#"
+  private async request<T>(method: string, path: string, options: RequestOptions): Promise<T> {
+    let url = this.baseUrl + path;
+    const params = new URLSearchParams();
+    for (const [key, value] of Object.entries(options.query ?? {})) {
+      if (value === undefined || value === null) continue;
+      for (const item of Array.isArray(value) ? value : [value]) params.append(key, String(item));
+    }
+    const query = params.toString();
+    if (query) url += "?" + query;
+
+    const headers: Record<string, string> = { ...this.options.headers };
+    for (const [key, value] of Object.entries(options.headers ?? {})) {
+      if (value !== undefined) headers[key] = value;
+    }
+    if (this.options.token) headers["Authorization"] = `Bearer ${this.options.token}`;
+
+    let body: BodyInit | undefined;
+    if (options.body !== undefined) {
+      if (options.contentType === "application/json") {
+        headers["Content-Type"] = "application/json";
+        body = JSON.stringify(options.body);
+      } else if (options.contentType === "application/x-www-form-urlencoded") {
+        body = new URLSearchParams(options.body as Record<string, string>);
+      } else {
+        if (options.contentType && options.contentType !== "multipart/form-data") {
+          headers["Content-Type"] = options.contentType;
+        }
+        body = options.body as BodyInit;
+      }
+    }
+
+    const response = await (this.options.fetch ?? fetch)(url, { method, headers, body });
+    const text = await response.text();
+    if (!response.ok) throw new ApiError(response.status, text);
+    if (options.response === "json") return (text ? JSON.parse(text) : undefined) as T;
+    if (options.response === "text") return text as T;
+    return undefined as T;
+  }
+"#;
+
+fn typescript_client(api: &Api, client: &str) -> std::result::Result<String, fmt::Error> {
+    let mut out = String::new();
+    writeln!(out, "// Client for the {} API ({})", api.title, api.version)?;
+    writeln!(out, "// Generated by `rf gen sdk`. Do not edit.")?;
+
+    for (name, schema) in &api.schemas {
+        let type_name = type_name(name);
+        writeln!(out)?;
+        ts_doc(&mut out, "", schema["description"].as_str())?;
+        match schema["properties"].as_object() {
+            Some(properties) if schema["enum"].is_null() => {
+                let required: Vec<&str> =
+                    schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+                writeln!(out, "export interface {} {{", type_name)?;
+                for (field, field_schema) in properties {
+                    ts_doc(&mut out, "  ", field_schema["description"].as_str())?;
+                    let optional = if required.contains(&field.as_str()) { "" } else { "?" };
+                    writeln!(out, "  {}{}: {};", ts_key(field), optional, ts_type(field_schema))?;
+                }
+                writeln!(out, "}}")?;
+            }
+            _ => writeln!(out, "export type {} = {};", type_name, ts_type(schema))?,
+        }
+    }
+
+    out.push_str(TS_RUNTIME);
+    writeln!(out)?;
+    writeln!(out, "/** {} client */", api.title)?;
+    writeln!(out, "export class {} {{", client)?;
+    writeln!(out, "  private readonly baseUrl: string;")?;
+    writeln!(out)?;
+    let default_url = api.base_url.as_deref().map(|url| format!(" = {:?}", url)).unwrap_or_default();
+    writeln!(out, "  constructor(baseUrl: string{}, private readonly options: ClientOptions = {{}}) {{", default_url)?;
+    writeln!(out, "    this.baseUrl = baseUrl.replace(/\\/+$/, \"\");")?;
+    writeln!(out, "  }}")?;
+
+    for op in &api.operations {
+        writeln!(out)?;
+        let mut doc = Vec::new();
+        if let Some(summary) = &op.summary {
+            doc.push(summary.clone());
+        }
+        if let Some(description) = &op.description {
+            doc.extend(description.lines().map(str::to_string));
+        }
+        doc.push(format!("`{} {}`", op.method, op.path));
+        if op.deprecated {
+            doc.push("@deprecated".to_string());
+        }
+        ts_doc(&mut out, "  ", Some(&doc.join("\n")))?;
+
+        let mut args = Vec::new();
+        let mut used = HashSet::new();
+        let mut path_args = Vec::new();
+        for param in &op.path_params {
+            let arg = unique(&mut used, camel_case(&param.name));
+            args.push(format!("{}: {}", arg, ts_type(&param.schema)));
+            path_args.push((param.name.clone(), arg));
+        }
+        let body_arg = op.body.as_ref().map(|(content_type, schema)| {
+            let arg = unique(&mut used, "body".to_string());
+            let ty = match content_type.as_str() {
+                "application/json" | "application/x-www-form-urlencoded" => ts_type(schema),
+                "multipart/form-data" => "FormData".to_string(),
+                _ => "Blob | ArrayBuffer | string".to_string(),
+            };
+            args.push(format!("{}: {}", arg, ty));
+            arg
+        });
+        let query_arg = (!op.query.is_empty()).then(|| {
+            let arg = unique(&mut used, "query".to_string());
+            let fields: Vec<String> = op
+                .query
+                .iter()
+                .map(|p| format!("{}{}: {}", ts_key(&p.name), if p.required { "" } else { "?" }, ts_type(&p.schema)))
+                .collect();
+            let optional = if op.query.iter().any(|p| p.required) { "" } else { "?" };
+            args.push(format!("{}{}: {{ {} }}", arg, optional, fields.join("; ")));
+            arg
+        });
+        let mut header_args = Vec::new();
+        for param in &op.headers {
+            let arg = unique(&mut used, camel_case(&param.name));
+            let optional = if param.required { "" } else { "?" };
+            args.push(format!("{}{}: string", arg, optional));
+            header_args.push((param.name.clone(), arg));
+        }
+        // 可选参数只能放在最后
+        let first_optional = args.iter().position(|a| a.split(':').next().is_some_and(|n| n.ends_with('?')));
+        if let Some(index) = first_optional {
+            for arg in args.iter_mut().skip(index) {
+                if let Some((name, ty)) = arg.split_once(": ") {
+                    if !name.ends_with('?') {
+                        *arg = format!("{}: {} | undefined", name, ty);
+                    }
+                }
+            }
+        }
+
+        let (output, kind) = match &op.response {
+            ResponseKind::Json(schema) => (ts_type(schema), "json"),
+            ResponseKind::Text => ("string".to_string(), "text"),
+            ResponseKind::Empty => ("void".to_string(), "empty"),
+        };
+        writeln!(out, "  async {}({}): Promise<{}> {{", camel_case(&op.name), args.join(", "), output)?;
+
+        let mut path = String::new();
+        for segment in op.path.split('/').skip(1) {
+            path.push('/');
+            match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(name) => {
+                    if let Some((_, arg)) = path_args.iter().find(|(param, _)| param == name) {
+                        write!(path, "${{encodeURIComponent(String({}))}}", arg)?;
+                    }
+                }
+                None => path.push_str(&segment.replace('`', "\\`").replace("${", "\\${")),
+            }
+        }
+        if path.is_empty() {
+            path.push('/');
+        }
+        let mut options = Vec::new();
+        if let Some(arg) = &query_arg {
+            options.push(format!("query: {}", arg));
+        }
+        if !header_args.is_empty() {
+            let headers: Vec<String> =
+                header_args.iter().map(|(name, arg)| format!("{:?}: {}", name, arg)).collect();
+            options.push(format!("headers: {{ {} }}", headers.join(", ")));
+        }
+        if let (Some(arg), Some((content_type, _))) = (&body_arg, &op.body) {
+            options.push(format!("body: {}", arg));
+            options.push(format!("contentType: {:?}", content_type));
+        }
+        options.push(format!("response: \"{}\"", kind));
+        writeln!(
+            out,
+            "    return this.request<{}>({:?}, `{}`, {{ {} }});",
+            output,
+            op.method,
+            path,
+            options.join(", ")
+        )?;
+        writeln!(out, "  }}")?;
+    }
+    out.push_str(TS_REQUEST);
+    writeln!(out, "}}")?;
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// 命名
+// ---------------------------------------------------------------------------
+
+/// 按大小写边界和非字母数字字符拆分单词
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = name.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let boundary = c.is_ascii_uppercase()
+            && !current.is_empty()
+            && (chars[i - 1].is_ascii_lowercase()
+                || chars[i - 1].is_ascii_digit()
+                || chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase()));
+        if boundary {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// 类型名称，已经是大写开头的合法标识符时保持不变
+fn type_name(name: &str) -> String {
+    let valid = name.starts_with(|c: char| c.is_ascii_uppercase()) && name.chars().all(|c| c.is_ascii_alphanumeric());
+    if valid {
+        name.to_string()
+    } else {
+        pascal_case(name)
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    words(name).iter().map(|w| w.to_ascii_lowercase()).collect::<Vec<_>>().join("_")
+}
+
+fn pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|w| {
+            let lower = w.to_ascii_lowercase();
+            let mut chars = lower.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
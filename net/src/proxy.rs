@@ -0,0 +1,519 @@
+//! # proxy
+//!
+//! proxy 模块 - 端口转发与 SOCKS5 代理
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! 端口转发、TLS 终结代理和 SOCKS5 客户端
+//!
+//! 用于穿越堡垒机等只能经由跳板访问的部署环境。
+//!
+//! # 主要功能
+//!
+//! - `Socks5Connector`：通过 SOCKS5 代理建立 TCP 连接（支持用户名/密码认证）
+//! - `TcpForwarder`：TCP 端口转发，可选 TLS 终结，可经由 SOCKS5 连接上游
+//! - `Socks5Connector::http_bridge`：本地 HTTP 代理桥，使 `HttpClient` 可以走 SOCKS5
+//!
+//! # 使用示例
+//!
+//! ```ignore
+//! use rf_net::{HttpClient, Socks5Connector, TcpForwarder};
+//!
+//! let socks = Socks5Connector::new("bastion:1080").with_auth("user", "secret");
+//!
+//! // 本地 15432 端口转发到内网数据库
+//! let forwarder = TcpForwarder::bind("127.0.0.1:15432", "db.internal:5432").await?
+//!     .via_socks5(socks.clone());
+//! tokio::spawn(forwarder.run());
+//!
+//! // HTTP 客户端经由 SOCKS5 访问内网服务
+//! let client = HttpClient::with_socks5(socks).await?;
+//! let text = client.get("http://admin.internal/health").text().await?;
+//! ```
+
+use crate::http::TlsAcceptorHandle;
+use rf_errors::{Result, RfError};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+const SOCKS_VERSION: u8 = 0x05;
+const AUTH_NONE: u8 = 0x00;
+const AUTH_PASSWORD: u8 = 0x02;
+const AUTH_UNACCEPTABLE: u8 = 0xFF;
+const CMD_CONNECT: u8 = 0x01;
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+
+fn network_error(context: &str, e: impl std::fmt::Display) -> RfError {
+    RfError::Network(format!("{}: {}", context, e))
+}
+
+/// 拆分 "host:port"，支持 "[::1]:port" 形式的 IPv6 地址
+fn split_host_port(addr: &str) -> Result<(String, u16)> {
+    let (host, port) = addr
+        .rsplit_once(':')
+        .ok_or_else(|| RfError::InvalidParameter(format!("Missing port in address: {}", addr)))?;
+    let port = port
+        .parse()
+        .map_err(|_| RfError::InvalidParameter(format!("Invalid port in address: {}", addr)))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    Ok((host.to_string(), port))
+}
+
+/// SOCKS5 客户端连接器
+///
+/// 目标主机名交由代理解析，因此可以访问只在内网 DNS 中存在的域名。
+#[derive(Debug, Clone)]
+pub struct Socks5Connector {
+    proxy_addr: String,
+    auth: Option<(String, String)>,
+    connect_timeout: Duration,
+}
+
+impl Socks5Connector {
+    /// 创建连接器
+    ///
+    /// # 参数
+    ///
+    /// - `proxy_addr`: SOCKS5 代理地址，格式为 "host:port"
+    pub fn new(proxy_addr: impl Into<String>) -> Self {
+        Self {
+            proxy_addr: proxy_addr.into(),
+            auth: None,
+            connect_timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// 设置用户名/密码认证（RFC 1929）
+    pub fn with_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// 设置连接超时（包含握手），默认 10 秒
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// 代理地址
+    pub fn proxy_addr(&self) -> &str {
+        &self.proxy_addr
+    }
+
+    /// 经由代理连接到目标
+    ///
+    /// # 参数
+    ///
+    /// - `target`: 目标地址，格式为 "host:port"
+    ///
+    /// # 错误
+    ///
+    /// 连接代理失败、认证失败或代理拒绝请求时返回 RfError::Network 错误
+    pub async fn connect(&self, target: &str) -> Result<TcpStream> {
+        let (host, port) = split_host_port(target)?;
+        self.connect_host(&host, port).await
+    }
+
+    /// 经由代理连接到目标主机和端口
+    pub async fn connect_host(&self, host: &str, port: u16) -> Result<TcpStream> {
+        tokio::time::timeout(self.connect_timeout, async {
+            let mut stream = TcpStream::connect(&self.proxy_addr)
+                .await
+                .map_err(|e| network_error("Failed to connect to SOCKS5 proxy", e))?;
+            self.handshake(&mut stream, host, port).await?;
+            Ok(stream)
+        })
+        .await
+        .map_err(|_| RfError::Timeout(format!("SOCKS5 connect to {}:{} timed out", host, port)))?
+    }
+
+    /// 在已建立的连接上执行 SOCKS5 握手
+    pub async fn handshake<S>(&self, stream: &mut S, host: &str, port: u16) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let io = |e| network_error("SOCKS5 handshake failed", e);
+
+        let methods: &[u8] = if self.auth.is_some() { &[AUTH_NONE, AUTH_PASSWORD] } else { &[AUTH_NONE] };
+        let mut greeting = vec![SOCKS_VERSION, methods.len() as u8];
+        greeting.extend_from_slice(methods);
+        stream.write_all(&greeting).await.map_err(io)?;
+
+        let mut reply = [0u8; 2];
+        stream.read_exact(&mut reply).await.map_err(io)?;
+        if reply[0] != SOCKS_VERSION {
+            return Err(RfError::Network("Proxy is not a SOCKS5 server".to_string()));
+        }
+        match reply[1] {
+            AUTH_NONE => {}
+            AUTH_PASSWORD => {
+                let (username, password) = self
+                    .auth
+                    .as_ref()
+                    .ok_or_else(|| RfError::Network("SOCKS5 proxy requires authentication".to_string()))?;
+                if username.len() > 255 || password.len() > 255 {
+                    return Err(RfError::InvalidParameter("SOCKS5 credentials too long".to_string()));
+                }
+                let mut request = vec![0x01, username.len() as u8];
+                request.extend_from_slice(username.as_bytes());
+                request.push(password.len() as u8);
+                request.extend_from_slice(password.as_bytes());
+                stream.write_all(&request).await.map_err(io)?;
+
+                let mut status = [0u8; 2];
+                stream.read_exact(&mut status).await.map_err(io)?;
+                if status[1] != 0 {
+                    return Err(RfError::Unauthorized("SOCKS5 authentication failed".to_string()));
+                }
+            }
+            AUTH_UNACCEPTABLE => {
+                return Err(RfError::Network("SOCKS5 proxy rejected all authentication methods".to_string()))
+            }
+            method => return Err(RfError::Network(format!("Unsupported SOCKS5 auth method: {}", method))),
+        }
+
+        let mut request = vec![SOCKS_VERSION, CMD_CONNECT, 0x00];
+        match host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                request.push(ATYP_IPV4);
+                request.extend_from_slice(&ip.octets());
+            }
+            Ok(IpAddr::V6(ip)) => {
+                request.push(ATYP_IPV6);
+                request.extend_from_slice(&ip.octets());
+            }
+            Err(_) => {
+                if host.len() > 255 {
+                    return Err(RfError::InvalidParameter(format!("Host name too long: {}", host)));
+                }
+                request.push(ATYP_DOMAIN);
+                request.push(host.len() as u8);
+                request.extend_from_slice(host.as_bytes());
+            }
+        }
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request).await.map_err(io)?;
+
+        let mut head = [0u8; 4];
+        stream.read_exact(&mut head).await.map_err(io)?;
+        if head[1] != 0 {
+            return Err(RfError::Network(format!(
+                "SOCKS5 connect to {}:{} failed: {}",
+                host,
+                port,
+                reply_message(head[1])
+            )));
+        }
+        // 跳过绑定地址和端口
+        let addr_len = match head[3] {
+            ATYP_IPV4 => 4,
+            ATYP_IPV6 => 16,
+            ATYP_DOMAIN => {
+                let mut len = [0u8; 1];
+                stream.read_exact(&mut len).await.map_err(io)?;
+                len[0] as usize
+            }
+            atyp => return Err(RfError::Network(format!("Invalid SOCKS5 address type: {}", atyp))),
+        };
+        let mut bound = vec![0u8; addr_len + 2];
+        stream.read_exact(&mut bound).await.map_err(io)?;
+        Ok(())
+    }
+
+    /// 启动本地 HTTP 代理桥
+    ///
+    /// 在 127.0.0.1 的随机端口上接受 HTTP 代理请求（CONNECT 和普通转发），
+    /// 并经由 SOCKS5 连接目标。任何支持 HTTP 代理的客户端都可以借此使用 SOCKS5。
+    /// 返回的句柄被丢弃时桥停止工作。
+    pub async fn http_bridge(&self) -> Result<Socks5HttpBridge> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| network_error("Failed to bind HTTP proxy bridge", e))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| network_error("Failed to get local address", e))?;
+        let connector = self.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::warn!("HTTP proxy bridge accept failed: {}", e);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        continue;
+                    }
+                };
+                let connector = connector.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = bridge_connection(connector, stream).await {
+                        tracing::debug!("HTTP proxy bridge connection failed: {}", e);
+                    }
+                });
+            }
+        });
+        Ok(Socks5HttpBridge { addr, task })
+    }
+}
+
+fn reply_message(code: u8) -> &'static str {
+    match code {
+        0x01 => "general failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+/// 本地 HTTP 代理桥句柄，被丢弃时停止
+pub struct Socks5HttpBridge {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl Socks5HttpBridge {
+    /// 监听地址
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 代理 URL，例如 "http://127.0.0.1:40123"
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for Socks5HttpBridge {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+
+async fn bridge_connection(connector: Socks5Connector, mut client: TcpStream) -> Result<()> {
+    let io = |e| network_error("HTTP proxy bridge I/O failed", e);
+
+    let mut buf = Vec::with_capacity(1024);
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_HEAD_SIZE {
+            return Err(RfError::InvalidParameter("Proxy request head too large".to_string()));
+        }
+        let mut chunk = [0u8; 2048];
+        let n = client.read(&mut chunk).await.map_err(io)?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    let rest = buf[head_end..].to_vec();
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (method, target, version) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(m), Some(t), Some(v)) => (m, t, v),
+        _ => {
+            client.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n").await.map_err(io)?;
+            return Err(RfError::InvalidParameter(format!("Malformed request line: {}", request_line)));
+        }
+    };
+
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let mut upstream = match connector.connect(target).await {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                client.write_all(b"HTTP/1.1 502 Bad Gateway\r\n\r\n").await.map_err(io)?;
+                return Err(e);
+            }
+        };
+        client
+            .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+            .await
+            .map_err(io)?;
+        upstream.write_all(&rest).await.map_err(io)?;
+        tokio::io::copy_bidirectional(&mut client, &mut upstream).await.map_err(io)?;
+        return Ok(());
+    }
+
+    // 普通转发：绝对 URI 改写为源站形式，每个连接只转发一个请求
+    let url = url::Url::parse(target)
+        .map_err(|e| RfError::InvalidParameter(format!("Invalid proxy request target {}: {}", target, e)))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| RfError::InvalidParameter(format!("Missing host in {}", target)))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let mut path = url.path().to_string();
+    if let Some(query) = url.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+
+    let mut rewritten = format!("{} {} {}\r\n", method, path, version);
+    for line in lines.filter(|l| !l.is_empty()) {
+        let name = line.split(':').next().unwrap_or_default().trim().to_ascii_lowercase();
+        if !matches!(name.as_str(), "proxy-connection" | "proxy-authorization" | "connection" | "keep-alive") {
+            rewritten.push_str(line);
+            rewritten.push_str("\r\n");
+        }
+    }
+    rewritten.push_str("Connection: close\r\n\r\n");
+
+    let mut upstream = match connector.connect_host(host.trim_start_matches('[').trim_end_matches(']'), port).await {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            client.write_all(b"HTTP/1.1 502 Bad Gateway\r\n\r\n").await.map_err(io)?;
+            return Err(e);
+        }
+    };
+    upstream.write_all(rewritten.as_bytes()).await.map_err(io)?;
+    upstream.write_all(&rest).await.map_err(io)?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await.map_err(io)?;
+    Ok(())
+}
+
+/// TCP 端口转发器
+///
+/// 将本地端口收到的连接转发到目标地址，可选：
+/// - TLS 终结：对客户端提供 TLS，向上游转发明文
+/// - 经由 SOCKS5 代理连接上游
+pub struct TcpForwarder {
+    listener: TcpListener,
+    target: String,
+    socks5: Option<Socks5Connector>,
+    tls: Option<(Arc<TlsAcceptorHandle>, Duration)>,
+    connect_timeout: Duration,
+}
+
+impl TcpForwarder {
+    /// 绑定本地地址并创建转发器
+    ///
+    /// # 参数
+    ///
+    /// - `listen_addr`: 本地监听地址，格式为 "host:port"
+    /// - `target`: 上游地址，格式为 "host:port"
+    ///
+    /// # 错误
+    ///
+    /// 如果绑定失败，返回 RfError::Network 错误
+    pub async fn bind(listen_addr: &str, target: impl Into<String>) -> Result<Self> {
+        let target = target.into();
+        split_host_port(&target)?;
+        let listener = TcpListener::bind(listen_addr)
+            .await
+            .map_err(|e| network_error("Failed to bind TCP forwarder", e))?;
+        Ok(Self {
+            listener,
+            target,
+            socks5: None,
+            tls: None,
+            connect_timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// 经由 SOCKS5 代理连接上游
+    pub fn via_socks5(mut self, connector: Socks5Connector) -> Self {
+        self.socks5 = Some(connector);
+        self
+    }
+
+    /// 对客户端启用 TLS 终结
+    pub fn tls(mut self, acceptor: Arc<TlsAcceptorHandle>) -> Self {
+        self.tls = Some((acceptor, Duration::from_secs(10)));
+        self
+    }
+
+    /// 设置上游连接超时，默认 10 秒
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// 本地监听地址
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener
+            .local_addr()
+            .map_err(|e| network_error("Failed to get local address", e))
+    }
+
+    /// 运行转发器，直到任务被取消
+    pub async fn run(self) -> Result<()> {
+        let forwarder = Arc::new(ForwardTarget {
+            target: self.target,
+            socks5: self.socks5,
+            connect_timeout: self.connect_timeout,
+        });
+        tracing::info!(
+            "Forwarding {} -> {}",
+            self.listener.local_addr().map(|a| a.to_string()).unwrap_or_default(),
+            forwarder.target
+        );
+        loop {
+            let (stream, peer) = match self.listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::warn!("TCP forwarder accept failed: {}", e);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    continue;
+                }
+            };
+            let forwarder = forwarder.clone();
+            let tls = self.tls.clone();
+            tokio::spawn(async move {
+                let result = match tls {
+                    Some((acceptor, handshake_timeout)) => {
+                        match tokio::time::timeout(handshake_timeout, acceptor.acceptor().accept(stream)).await {
+                            Ok(Ok(stream)) => forwarder.forward(stream).await,
+                            Ok(Err(e)) => Err(network_error("TLS handshake failed", e)),
+                            Err(_) => Err(RfError::Timeout("TLS handshake timed out".to_string())),
+                        }
+                    }
+                    None => forwarder.forward(stream).await,
+                };
+                if let Err(e) = result {
+                    tracing::debug!("Forwarding connection from {} failed: {}", peer, e);
+                }
+            });
+        }
+    }
+}
+
+struct ForwardTarget {
+    target: String,
+    socks5: Option<Socks5Connector>,
+    connect_timeout: Duration,
+}
+
+impl ForwardTarget {
+    async fn forward<S>(&self, mut client: S) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut upstream = match &self.socks5 {
+            Some(connector) => connector.connect(&self.target).await?,
+            None => tokio::time::timeout(self.connect_timeout, TcpStream::connect(&self.target))
+                .await
+                .map_err(|_| RfError::Timeout(format!("Connect to {} timed out", self.target)))?
+                .map_err(|e| network_error("Failed to connect upstream", e))?,
+        };
+        tokio::io::copy_bidirectional(&mut client, &mut upstream)
+            .await
+            .map_err(|e| network_error("Forwarding failed", e))?;
+        Ok(())
+    }
+}
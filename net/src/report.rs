@@ -0,0 +1,334 @@
+//! # report
+//!
+//! report 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Error reporting senders and HTTP middleware
+//!
+//! `SentrySender` posts reports to a Sentry-compatible store endpoint
+//! (Sentry, GlitchTip, self-hosted), and `WebhookReporter` delivers them as
+//! signed webhooks through a `WebhookSender`. Both plug into
+//! `rf_os::report::ErrorReporting`, which samples and scrubs reports first.
+//!
+//! `error_report_middleware` reports 5xx responses and panicking handlers
+//! with the request context. `HttpServer::with_error_reporting` installs it
+//! for every route (the global reporting of `rf_os::report` is used when
+//! none is given); a route group can report to its own with `middleware`,
+//! in which case the server-wide middleware skips its responses:
+//!
+//! ```rust,ignore
+//! use rf_net::report::{error_report_middleware, ErrorReporting, SentrySender, WebhookReporter};
+//!
+//! let reporting = ErrorReporting::new()
+//!     .reporter(SentrySender::new("https://key@sentry.example.com/42")?.environment("production"))
+//!     .sample_rate(0.5);
+//! let payments = Arc::new(ErrorReporting::new().reporter(WebhookReporter::new(webhooks, "oncall")));
+//!
+//! let server = HttpServer::new(addr)
+//!     .with_error_reporting(reporting)
+//!     .group("/payments", |group| {
+//!         group
+//!             .middleware(axum::middleware::from_fn_with_state(payments, error_report_middleware))
+//!             .route(Method::POST, "/", charge)
+//!     })?;
+//! ```
+//!
+//! A panicking handler is answered with a 500 `ApiError`, so the envelope
+//! renders it like any other internal error.
+
+use crate::http::envelope::{ApiError, ErrorSource};
+use crate::http::RequestId;
+use crate::webhook::WebhookSender;
+use async_trait::async_trait;
+use axum::extract::{ConnectInfo, MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response as AxumResponse};
+use futures_util::FutureExt;
+use rf_errors::{Result, RfError};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub use rf_os::report::{
+    clear_global, global, set_global, ErrorReport, ErrorReporter, ErrorReporting, PiiScrubber, ReportContext, ReportKind,
+};
+
+/// Set once the request has been reported, so outer middleware skip it
+///
+/// Kept in the request rather than the response: the envelope rebuilds
+/// error responses and drops their extensions.
+#[derive(Debug, Clone, Default)]
+struct Reported(Arc<AtomicBool>);
+
+/// Sends reports to a Sentry-compatible store endpoint
+///
+/// The DSN has the form `https://<public key>@<host>[/<path>]/<project id>`.
+#[derive(Clone)]
+pub struct SentrySender {
+    client: reqwest::Client,
+    store_url: String,
+    auth: String,
+    environment: Option<String>,
+    release: Option<String>,
+    server_name: Option<String>,
+    timeout: Duration,
+}
+
+impl SentrySender {
+    /// Sender for `dsn`
+    ///
+    /// # Errors
+    ///
+    /// `RfError::Config` when the DSN is malformed
+    pub fn new(dsn: &str) -> Result<Self> {
+        let invalid = |reason: &str| RfError::Config(format!("Invalid Sentry DSN: {}", reason));
+        let url = url::Url::parse(dsn).map_err(|e| invalid(&e.to_string()))?;
+        let key = url.username();
+        if key.is_empty() {
+            return Err(invalid("missing public key"));
+        }
+        let host = url.host_str().ok_or_else(|| invalid("missing host"))?;
+        let path = url.path().trim_matches('/');
+        let (prefix, project) = match path.rsplit_once('/') {
+            Some((prefix, project)) => (format!("/{}", prefix), project),
+            None => (String::new(), path),
+        };
+        if project.is_empty() {
+            return Err(invalid("missing project ID"));
+        }
+        let port = url.port().map(|port| format!(":{}", port)).unwrap_or_default();
+        let mut auth = format!(
+            "Sentry sentry_version=7, sentry_client=rf/{}, sentry_key={}",
+            env!("CARGO_PKG_VERSION"),
+            key
+        );
+        if let Some(secret) = url.password() {
+            auth.push_str(&format!(", sentry_secret={}", secret));
+        }
+        Ok(Self {
+            client: reqwest::Client::new(),
+            store_url: format!("{}://{}{}{}/api/{}/store/", url.scheme(), host, port, prefix, project),
+            auth,
+            environment: None,
+            release: None,
+            server_name: None,
+            timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// Environment shown in Sentry, e.g. `production`
+    pub fn environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Release shown in Sentry, e.g. the application version
+    pub fn release(mut self, release: impl Into<String>) -> Self {
+        self.release = Some(release.into());
+        self
+    }
+
+    /// Server name, e.g. the host name
+    pub fn server_name(mut self, name: impl Into<String>) -> Self {
+        self.server_name = Some(name.into());
+        self
+    }
+
+    /// Request timeout (default 10s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Store endpoint the DSN resolves to
+    pub fn store_url(&self) -> &str {
+        &self.store_url
+    }
+
+    /// Sentry event for `report`
+    pub fn event(&self, report: &ErrorReport) -> Value {
+        let context = &report.context;
+        let mut tags = context.tags.clone();
+        tags.insert("code".to_string(), report.error.code().to_string());
+        if let Some(route) = &context.route {
+            tags.insert("route".to_string(), route.clone());
+        }
+        if let Some(job) = &context.job {
+            tags.insert("job".to_string(), job.clone());
+        }
+        if let Some(request_id) = &context.request_id {
+            tags.insert("request_id".to_string(), request_id.clone());
+        }
+        let mut event = json!({
+            "event_id": report.id,
+            "timestamp": report.timestamp.to_rfc3339(),
+            "platform": "other",
+            "level": match report.kind {
+                ReportKind::Error => "error",
+                ReportKind::Panic => "fatal",
+            },
+            "logger": "rf",
+            "transaction": context.route.as_ref().or(context.job.as_ref()),
+            "exception": {
+                "values": [{
+                    "type": error_type(&report.error),
+                    "value": report.message,
+                    "mechanism": { "type": report.kind.to_string(), "handled": report.kind == ReportKind::Error },
+                }]
+            },
+            "tags": tags,
+            "environment": self.environment,
+            "release": self.release,
+            "server_name": self.server_name,
+        });
+        if let Some(method) = &context.method {
+            event["request"] = json!({
+                "method": method,
+                "url": context.path,
+                "query_string": context.query,
+                "headers": context.headers,
+            });
+            if let Some(status) = context.status {
+                event["contexts"] = json!({ "response": { "status_code": status } });
+            }
+        }
+        if let Some(ip) = &context.client_ip {
+            event["user"] = json!({ "ip_address": ip });
+        }
+        event
+    }
+}
+
+/// Variant name of an error, used as the exception type
+fn error_type(error: &RfError) -> &'static str {
+    match error {
+        RfError::Internal(_) => "Internal",
+        RfError::InvalidParameter(_) => "InvalidParameter",
+        RfError::NotFound(_) => "NotFound",
+        RfError::Unauthorized(_) => "Unauthorized",
+        RfError::Forbidden(_) => "Forbidden",
+        RfError::Timeout(_) => "Timeout",
+        RfError::Database(_) => "Database",
+        RfError::Network(_) => "Network",
+        RfError::Config(_) => "Config",
+        RfError::Io(_) => "Io",
+        RfError::Serialization(_) => "Serialization",
+        RfError::Validation(_) => "Validation",
+        RfError::Custom(_) => "Custom",
+    }
+}
+
+#[async_trait]
+impl ErrorReporter for SentrySender {
+    async fn report(&self, report: &ErrorReport) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.store_url)
+            .timeout(self.timeout)
+            .header("x-sentry-auth", &self.auth)
+            .json(&self.event(report))
+            .send()
+            .await
+            .map_err(|e| RfError::Network(format!("Sentry request failed: {}", e)))?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(RfError::Network(format!("Sentry returned {}", response.status())))
+        }
+    }
+}
+
+/// Delivers reports as `error.error` / `error.panic` webhooks to one endpoint
+///
+/// Retries and dead letters follow the sender's settings.
+#[derive(Clone)]
+pub struct WebhookReporter {
+    sender: WebhookSender,
+    endpoint: String,
+}
+
+impl WebhookReporter {
+    /// Report to `endpoint`, a name registered on `sender`
+    pub fn new(sender: WebhookSender, endpoint: impl Into<String>) -> Self {
+        Self { sender, endpoint: endpoint.into() }
+    }
+}
+
+#[async_trait]
+impl ErrorReporter for WebhookReporter {
+    async fn report(&self, report: &ErrorReport) -> Result<()> {
+        self.sender.deliver(&self.endpoint, format!("error.{}", report.kind), report).await.map(|_| ())
+    }
+}
+
+/// Context of a request, before scrubbing
+fn request_context(request: &Request) -> ReportContext {
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.as_str().to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+        .collect();
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.as_str().to_string())
+        .or_else(|| request.headers().get("x-request-id").and_then(|v| v.to_str().ok()).map(str::to_string));
+    ReportContext {
+        method: Some(request.method().to_string()),
+        path: Some(request.uri().path().to_string()),
+        query: request.uri().query().map(str::to_string),
+        route: request.extensions().get::<MatchedPath>().map(|path| path.as_str().to_string()),
+        request_id,
+        client_ip: request.extensions().get::<ConnectInfo<SocketAddr>>().map(|c| c.0.ip().to_string()),
+        headers,
+        ..Default::default()
+    }
+}
+
+/// Report 5xx responses and panics, see the module documentation
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc::new(reporting), error_report_middleware)`,
+/// or `HttpServer::with_error_reporting`.
+pub async fn error_report_middleware(State(reporting): State<Arc<ErrorReporting>>, mut request: Request, next: Next) -> AxumResponse {
+    let context = request_context(&request);
+    let reported = request.extensions_mut().get_or_insert_default::<Reported>().clone();
+    let response = match AssertUnwindSafe(next.run(request)).catch_unwind().await {
+        Ok(response) => response,
+        Err(panic) => {
+            let message = rf_os::report::panic_message(panic.as_ref());
+            tracing::error!(
+                "Handler for {} {} panicked: {}",
+                context.method.as_deref().unwrap_or("-"),
+                context.path.as_deref().unwrap_or("-"),
+                message
+            );
+            let response = ApiError(RfError::Internal("Internal server error".to_string())).into_response();
+            if !reported.0.swap(true, Ordering::SeqCst) {
+                let context = ReportContext { status: Some(response.status().as_u16()), ..context };
+                reporting.capture(ErrorReport::panic(message).context(context));
+            }
+            return response;
+        }
+    };
+    let status = response.status();
+    if !status.is_server_error() || reported.0.swap(true, Ordering::SeqCst) {
+        return response;
+    }
+    let error = match response.extensions().get::<ErrorSource>() {
+        Some(source) => source.0.clone(),
+        None => Arc::new(RfError::Internal(format!(
+            "{} {} returned {}",
+            context.method.as_deref().unwrap_or("-"),
+            context.path.as_deref().unwrap_or("-"),
+            status
+        ))),
+    };
+    let context = ReportContext { status: Some(status.as_u16()), ..context };
+    reporting.capture(ErrorReport::new(ReportKind::Error, error).context(context));
+    response
+}
@@ -0,0 +1,109 @@
+//! # retry
+//!
+//! retry 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Request retries
+//!
+//! `send_with_retry` sends a request up to `max_retries + 1` times, through
+//! a `CircuitBreaker` if one is given. Connection errors and the statuses in
+//! `retry_on_status` are retried after `retry_delay`. Used by the reverse
+//! proxy and by `rf-contrib-sdk-httpclient`.
+//!
+//! ```rust,ignore
+//! use rf_net::retry::{send, send_with_retry, RetryConfig};
+//!
+//! let client = reqwest::Client::new();
+//! let response = send_with_retry(&RetryConfig::default(), None, || Ok(client.get(url)), send).await?;
+//! ```
+
+use crate::breaker::CircuitBreaker;
+use reqwest::{RequestBuilder, Response};
+use rf_errors::{Result, RfError};
+use std::future::Future;
+use std::time::Duration;
+
+/// Retry configuration
+///
+/// Deserializes from configuration with `retry_delay` such as `"100ms"`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    #[serde(with = "rf_os::cfg::serde_duration")]
+    pub retry_delay: Duration,
+    pub retry_on_status: Vec<u16>, // HTTP status codes to retry on
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            retry_delay: Duration::from_millis(100),
+            retry_on_status: vec![500, 502, 503, 504],
+        }
+    }
+}
+
+/// Send a request with retries and an optional circuit breaker
+///
+/// `prepare` builds each attempt; its errors are returned right away without
+/// touching the breaker. `send` performs the attempt. When every attempt
+/// fails, the last error is returned; a retried status that persists is
+/// returned as the final response.
+pub async fn send_with_retry<P, F, S, Fut>(
+    config: &RetryConfig,
+    breaker: Option<&CircuitBreaker>,
+    mut prepare: F,
+    send: S,
+) -> Result<Response>
+where
+    F: FnMut() -> Result<P>,
+    S: Fn(P) -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    let mut last_error = None;
+    for attempt in 0..=config.max_retries {
+        let request = prepare()?;
+        let result = match breaker {
+            Some(breaker) => breaker.call(|| send(request)).await,
+            None => send(request).await,
+        };
+
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                if config.retry_on_status.contains(&status.as_u16()) && attempt < config.max_retries {
+                    tracing::debug!("Retrying after HTTP {} (attempt {})", status, attempt + 2);
+                    tokio::time::sleep(config.retry_delay).await;
+                    last_error = Some(RfError::Network(format!("HTTP {}", status)));
+                    continue;
+                }
+                return Ok(response);
+            }
+            Err(e) => {
+                last_error = Some(e);
+                if attempt < config.max_retries {
+                    tokio::time::sleep(config.retry_delay).await;
+                }
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| RfError::Network("Request failed after retries".to_string())))
+}
+
+/// Send one request, mapping errors with `send_error`
+pub async fn send(request: RequestBuilder) -> Result<Response> {
+    request.send().await.map_err(send_error)
+}
+
+/// Map a reqwest error, keeping timeouts distinguishable
+pub fn send_error(e: reqwest::Error) -> RfError {
+    if e.is_timeout() {
+        RfError::Timeout(format!("Request timed out: {}", e))
+    } else {
+        RfError::Network(format!("Request failed: {}", e))
+    }
+}
@@ -13,6 +13,31 @@
 //!
 //! - 支持多种负载均衡策略：轮询、随机、加权、最少连接
 //! - 可扩展的 Selector trait，可以实现自定义策略
+//! - `LoadBalancer`：线程安全的后端节点集合，支持会话保持（Cookie 或请求头）
+//!   和连接排空（drain），可由服务注册中心的 watch 事件驱动滚动发布
+//!
+//! # 会话保持与排空
+//!
+//! `LoadBalancer::select` 返回一个 `Lease`，持有期间计入节点的进行中请求数。
+//! 排空中的节点不再接收新请求（包括会话保持到该节点的请求，它们会被重新分配），
+//! 已有请求继续完成；进行中请求归零后，从注册中心消失的节点被移除。
+//!
+//! ```ignore
+//! use rf_net::sel::{Affinity, LoadBalancer, Strategy};
+//!
+//! let balancer = Arc::new(
+//!     LoadBalancer::new(Strategy::LeastConnection).affinity(Affinity::cookie("RFROUTE")),
+//! );
+//! // 注册中心实例变化时自动加入新节点、排空下线节点
+//! balancer.watch(&registry, "orders")?;
+//!
+//! let lease = balancer.select_for(request.headers()).ok_or(no_upstream)?;
+//! let response = forward(lease.address(), request).await;
+//! if let Some(cookie) = lease.set_cookie() {
+//!     response.headers_mut().append(SET_COOKIE, cookie);
+//! }
+//! // lease 在此处释放，排空中的节点据此判断是否可以下线
+//! ```
 //!
 //! # 使用示例
 //!
@@ -29,6 +54,16 @@
 //! }
 //! ```
 
+use axum::http::header::COOKIE;
+use axum::http::{HeaderMap, HeaderValue};
+use rf_contrib_registry::{ServiceHealth, ServiceInstance, ServiceRegistry};
+use rf_errors::Result;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+
 /// 负载均衡策略
 ///
 /// 定义了常见的负载均衡策略类型
@@ -137,3 +172,381 @@ impl Default for RoundRobinSelector {
         Self::new()
     }
 }
+
+/// 会话保持方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Affinity {
+    /// 不保持，每次按策略选择
+    None,
+    /// 按 Cookie 保持：首次选中后通过 `Lease::set_cookie` 下发节点标识
+    Cookie {
+        /// Cookie 名称
+        name: String,
+        /// Cookie 有效期（秒），`None` 表示随浏览器会话结束
+        max_age: Option<u64>,
+    },
+    /// 按请求头（如用户 ID）的值一致性哈希到节点，节点增减时只有少量会话迁移
+    Header(String),
+}
+
+impl Affinity {
+    /// 会话级 Cookie 保持
+    pub fn cookie(name: impl Into<String>) -> Self {
+        Affinity::Cookie { name: name.into(), max_age: None }
+    }
+
+    /// 按请求头保持，名称不区分大小写
+    pub fn header(name: impl Into<String>) -> Self {
+        Affinity::Header(name.into().to_ascii_lowercase())
+    }
+}
+
+/// 负载均衡器中的后端节点
+#[derive(Debug)]
+pub struct Endpoint {
+    id: String,
+    address: SocketAddr,
+    weight: u32,
+    /// Cookie 中的节点标识，不暴露节点 ID
+    token: String,
+    draining: AtomicBool,
+    /// 排空完成后移除（注册中心已下线该节点）
+    remove_when_drained: AtomicBool,
+    in_flight: AtomicUsize,
+    /// 加权轮询的当前权重
+    current_weight: AtomicI64,
+    idle: Notify,
+}
+
+impl Endpoint {
+    fn new(id: &str, address: SocketAddr, weight: u32) -> Self {
+        Self {
+            id: id.to_string(),
+            address,
+            weight: weight.max(1),
+            token: format!("{:016x}", fnv1a(id.as_bytes())),
+            draining: AtomicBool::new(false),
+            remove_when_drained: AtomicBool::new(false),
+            in_flight: AtomicUsize::new(0),
+            current_weight: AtomicI64::new(0),
+            idle: Notify::new(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    /// 是否处于排空状态
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+
+    /// 进行中的请求数
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+}
+
+/// 选中节点的租约
+///
+/// 持有期间计入节点的进行中请求数，请求结束后释放（drop）。
+#[derive(Debug)]
+pub struct Lease {
+    endpoint: Arc<Endpoint>,
+    cookie: Option<String>,
+}
+
+impl Lease {
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    pub fn id(&self) -> &str {
+        &self.endpoint.id
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.endpoint.address
+    }
+
+    /// 需要下发的 `Set-Cookie` 值
+    ///
+    /// 仅在 Cookie 保持且请求未携带有效 Cookie（首次访问或原节点已排空）时返回。
+    pub fn set_cookie(&self) -> Option<HeaderValue> {
+        self.cookie.as_deref().and_then(|cookie| HeaderValue::from_str(cookie).ok())
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        if self.endpoint.in_flight.fetch_sub(1, Ordering::AcqRel) == 1 && self.endpoint.is_draining() {
+            self.endpoint.idle.notify_waiters();
+        }
+    }
+}
+
+/// 线程安全的负载均衡器
+///
+/// 按 `Strategy` 在未排空的节点中选择，支持会话保持和排空，见模块文档。
+pub struct LoadBalancer {
+    strategy: Strategy,
+    affinity: Affinity,
+    endpoints: RwLock<Vec<Arc<Endpoint>>>,
+    next: AtomicUsize,
+}
+
+impl LoadBalancer {
+    /// 创建一个不带会话保持的负载均衡器
+    pub fn new(strategy: Strategy) -> Self {
+        Self {
+            strategy,
+            affinity: Affinity::None,
+            endpoints: RwLock::new(Vec::new()),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// 设置会话保持方式
+    pub fn affinity(mut self, affinity: Affinity) -> Self {
+        self.affinity = affinity;
+        self
+    }
+
+    /// 添加节点（权重为 1）；ID 已存在时更新地址和权重并取消排空
+    pub fn add(&self, id: &str, address: SocketAddr) {
+        self.add_weighted(id, address, 1);
+    }
+
+    /// 添加带权重的节点，权重仅在 `Strategy::Weighted` 下生效
+    pub fn add_weighted(&self, id: &str, address: SocketAddr, weight: u32) {
+        let mut endpoints = self.write();
+        match endpoints.iter().position(|endpoint| endpoint.id == id) {
+            Some(index) if endpoints[index].address == address && endpoints[index].weight == weight.max(1) => {
+                endpoints[index].draining.store(false, Ordering::Release);
+                endpoints[index].remove_when_drained.store(false, Ordering::Release);
+            }
+            // 地址或权重变化：新请求使用新节点，旧租约仍指向原节点
+            Some(index) => endpoints[index] = Arc::new(Endpoint::new(id, address, weight)),
+            None => endpoints.push(Arc::new(Endpoint::new(id, address, weight))),
+        }
+    }
+
+    /// 立即移除节点，不等待进行中的请求
+    pub fn remove(&self, id: &str) -> bool {
+        let mut endpoints = self.write();
+        let before = endpoints.len();
+        endpoints.retain(|endpoint| endpoint.id != id);
+        endpoints.len() != before
+    }
+
+    /// 排空节点：不再分配新请求，进行中的请求继续完成
+    ///
+    /// 返回节点是否存在。用 `add` 重新加入可取消排空。
+    pub fn drain(&self, id: &str) -> bool {
+        match self.get(id) {
+            Some(endpoint) => {
+                endpoint.draining.store(true, Ordering::Release);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 等待节点的进行中请求归零
+    ///
+    /// 节点不存在或已空闲时立即返回 `true`，超时返回 `false`。
+    pub async fn wait_drained(&self, id: &str, timeout: Duration) -> bool {
+        let Some(endpoint) = self.get(id) else {
+            return true;
+        };
+        let wait = async {
+            loop {
+                let idle = endpoint.idle.notified();
+                if endpoint.in_flight() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+
+    /// 按 ID 查找节点
+    pub fn get(&self, id: &str) -> Option<Arc<Endpoint>> {
+        self.read().iter().find(|endpoint| endpoint.id == id).cloned()
+    }
+
+    /// 所有节点，包括排空中的
+    pub fn endpoints(&self) -> Vec<Arc<Endpoint>> {
+        self.read().clone()
+    }
+
+    /// 按注册中心的实例列表更新节点
+    ///
+    /// 新的健康实例加入（权重取元数据 `weight`，默认 1）；消失、不健康或元数据
+    /// `draining` 为 `true` 的实例进入排空，消失的实例在请求归零后移除。
+    pub fn apply(&self, instances: &[ServiceInstance]) {
+        for instance in instances {
+            let draining = instance.health != ServiceHealth::Healthy
+                || instance.metadata.get("draining").is_some_and(|value| value == "true");
+            if draining {
+                self.drain(&instance.id);
+            } else {
+                let weight = instance.metadata.get("weight").and_then(|weight| weight.parse().ok()).unwrap_or(1);
+                self.add_weighted(&instance.id, instance.address, weight);
+            }
+        }
+        for endpoint in self.read().iter() {
+            if !instances.iter().any(|instance| instance.id == endpoint.id) {
+                endpoint.draining.store(true, Ordering::Release);
+                endpoint.remove_when_drained.store(true, Ordering::Release);
+            }
+        }
+        self.sweep();
+    }
+
+    /// 订阅注册中心的实例变化，见 `apply`
+    ///
+    /// 先用当前实例列表初始化，之后每次 watch 回调都会更新节点。
+    pub fn watch<R: ServiceRegistry>(self: &Arc<Self>, registry: &R, service_name: &str) -> Result<()> {
+        self.apply(&registry.discover(service_name)?);
+        let balancer = Arc::downgrade(self);
+        registry.watch(service_name, move |instances| {
+            if let Some(balancer) = balancer.upgrade() {
+                balancer.apply(&instances);
+            }
+            Ok(())
+        })
+    }
+
+    /// 为一次请求选择节点
+    ///
+    /// `key` 为会话保持的键：Cookie 保持时是 Cookie 值，请求头保持时是请求头的值。
+    /// 没有可用节点时返回 `None`。
+    pub fn select(&self, key: Option<&str>) -> Option<Lease> {
+        self.sweep();
+        let endpoints = self.read();
+        let active: Vec<&Arc<Endpoint>> = endpoints.iter().filter(|endpoint| !endpoint.is_draining()).collect();
+        if active.is_empty() {
+            return None;
+        }
+        let (endpoint, cookie) = match (&self.affinity, key) {
+            (Affinity::Cookie { .. }, Some(token)) => match active.iter().find(|endpoint| endpoint.token == token) {
+                Some(endpoint) => (*endpoint, None),
+                None => self.with_cookie(self.pick(&active)),
+            },
+            (Affinity::Cookie { .. }, None) => self.with_cookie(self.pick(&active)),
+            // 最高随机权重（rendezvous）哈希：节点变化时只迁移落在该节点上的键
+            (Affinity::Header(_), Some(key)) => {
+                let endpoint = active
+                    .iter()
+                    .max_by_key(|endpoint| mix(fnv1a(format!("{}\n{}", endpoint.id, key).as_bytes())))
+                    .copied()?;
+                (endpoint, None)
+            }
+            _ => (self.pick(&active), None),
+        };
+        endpoint.in_flight.fetch_add(1, Ordering::AcqRel);
+        Some(Lease { endpoint: endpoint.clone(), cookie })
+    }
+
+    /// 按请求头选择节点，会话保持的键取自配置的 Cookie 或请求头
+    pub fn select_for(&self, headers: &HeaderMap) -> Option<Lease> {
+        let key = match &self.affinity {
+            Affinity::None => None,
+            Affinity::Cookie { name, .. } => headers
+                .get_all(COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(';'))
+                .filter_map(|pair| pair.trim().split_once('='))
+                .find(|(cookie, _)| cookie == name)
+                .map(|(_, value)| value),
+            Affinity::Header(name) => headers.get(name.as_str()).and_then(|value| value.to_str().ok()),
+        };
+        self.select(key)
+    }
+
+    fn with_cookie<'a>(&self, endpoint: &'a Arc<Endpoint>) -> (&'a Arc<Endpoint>, Option<String>) {
+        let Affinity::Cookie { name, max_age } = &self.affinity else {
+            return (endpoint, None);
+        };
+        let mut cookie = format!("{}={}; Path=/; HttpOnly; SameSite=Lax", name, endpoint.token);
+        if let Some(max_age) = max_age {
+            cookie.push_str(&format!("; Max-Age={}", max_age));
+        }
+        (endpoint, Some(cookie))
+    }
+
+    fn pick<'a>(&self, active: &[&'a Arc<Endpoint>]) -> &'a Arc<Endpoint> {
+        match self.strategy {
+            Strategy::RoundRobin => active[self.next.fetch_add(1, Ordering::Relaxed) % active.len()],
+            Strategy::Random => active[rand::random::<usize>() % active.len()],
+            Strategy::LeastConnection => {
+                // 并列时轮转，避免总是压在第一个节点上
+                let offset = self.next.fetch_add(1, Ordering::Relaxed);
+                (0..active.len())
+                    .map(|i| active[(offset + i) % active.len()])
+                    .min_by_key(|endpoint| endpoint.in_flight())
+                    .unwrap_or(active[0])
+            }
+            Strategy::Weighted => {
+                // 平滑加权轮询（nginx 算法）
+                let total: i64 = active.iter().map(|endpoint| i64::from(endpoint.weight)).sum();
+                let mut chosen = active[0];
+                let mut best = i64::MIN;
+                for endpoint in active {
+                    let weight = i64::from(endpoint.weight);
+                    let current = endpoint.current_weight.fetch_add(weight, Ordering::AcqRel) + weight;
+                    if current > best {
+                        best = current;
+                        chosen = endpoint;
+                    }
+                }
+                chosen.current_weight.fetch_sub(total, Ordering::AcqRel);
+                chosen
+            }
+        }
+    }
+
+    /// 移除已下线且排空完成的节点
+    fn sweep(&self) {
+        let idle = |endpoint: &Arc<Endpoint>| {
+            endpoint.remove_when_drained.load(Ordering::Acquire) && endpoint.in_flight() == 0
+        };
+        if self.read().iter().any(idle) {
+            self.write().retain(|endpoint| !idle(endpoint));
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Vec<Arc<Endpoint>>> {
+        self.endpoints.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Vec<Arc<Endpoint>>> {
+        self.endpoints.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// FNV-1a 64 位哈希，跨进程稳定
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3))
+}
+
+/// 64 位哈希终结混合（MurmurHash3 fmix64），让相近输入的哈希值充分分散
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^ (hash >> 33)
+}
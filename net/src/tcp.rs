@@ -13,6 +13,9 @@
 //!
 //! - TCP 服务器：绑定端口并接受连接
 //! - TCP 客户端：连接到远程服务器
+//! - 套接字选项：`SocketOptions` 设置 TCP_NODELAY、keepalive、SO_REUSEPORT 和缓冲区大小
+//! - TLS：基于 rustls 的 `TlsTcpServer` 与 `TlsClientConfig`，支持 ALPN、双向认证和证书热替换
+//! - 分帧：`FrameCodec` 按长度前缀收发消息，可在每帧中携带二进制追踪上下文
 //!
 //! # 使用示例
 //!
@@ -41,6 +44,15 @@
 //! }
 //! ```
 
+mod frame;
+mod socket;
+mod tls;
+
+pub use frame::{Frame, FrameCodec};
+pub use socket::SocketOptions;
+
+pub use tls::*;
+
 use rf_errors::Result;
 use tokio::net::{TcpListener, TcpStream};
 
@@ -51,8 +63,10 @@ use tokio::net::{TcpListener, TcpStream};
 /// # 字段
 ///
 /// - `listener`: 底层的 Tokio TCP 监听器
+/// - `options`: 应用到每个接受的连接上的套接字选项
 pub struct TcpServer {
     listener: TcpListener,
+    options: Option<SocketOptions>,
 }
 
 impl TcpServer {
@@ -78,7 +92,32 @@ impl TcpServer {
     pub async fn bind(addr: &str) -> Result<Self> {
         let listener = TcpListener::bind(addr).await
             .map_err(|e| rf_errors::RfError::Network(format!("Failed to bind TCP server: {}", e)))?;
-        Ok(Self { listener })
+        Ok(Self { listener, options: None })
+    }
+
+    /// 使用套接字选项创建并绑定 TCP 服务器
+    ///
+    /// # 参数
+    ///
+    /// - `addr`: 要绑定的地址
+    /// - `options`: 套接字选项，监听选项（SO_REUSEPORT、backlog）在绑定时设置，
+    ///   连接选项（TCP_NODELAY、keepalive、缓冲区大小）设置到每个接受的连接
+    ///
+    /// # 示例
+    ///
+    /// ```ignore
+    /// let options = SocketOptions::new().nodelay(true).reuse_port(true);
+    /// let server = TcpServer::bind_with("0.0.0.0:9000".parse()?, options)?;
+    /// ```
+    pub fn bind_with(addr: std::net::SocketAddr, options: SocketOptions) -> Result<Self> {
+        let listener = options.bind(addr)?;
+        Ok(Self { listener, options: Some(options) })
+    }
+
+    /// 获取实际绑定的地址（绑定端口 0 时用于获取分配的端口）
+    pub fn local_addr(&self) -> Result<std::net::SocketAddr> {
+        self.listener.local_addr()
+            .map_err(|e| rf_errors::RfError::Network(format!("Failed to get local address: {}", e)))
     }
 
     /// 接受一个客户端连接
@@ -101,8 +140,12 @@ impl TcpServer {
     /// // 使用 stream 进行读写操作
     /// ```
     pub async fn accept(&self) -> Result<(TcpStream, std::net::SocketAddr)> {
-        self.listener.accept().await
-            .map_err(|e| rf_errors::RfError::Network(format!("Failed to accept connection: {}", e)))
+        let (stream, addr) = self.listener.accept().await
+            .map_err(|e| rf_errors::RfError::Network(format!("Failed to accept connection: {}", e)))?;
+        if let Some(options) = &self.options {
+            options.apply(&stream)?;
+        }
+        Ok((stream, addr))
     }
 }
 
@@ -136,5 +179,17 @@ impl TcpClient {
         TcpStream::connect(addr).await
             .map_err(|e| rf_errors::RfError::Network(format!("Failed to connect: {}", e)))
     }
+
+    /// 使用套接字选项连接到指定的 TCP 服务器
+    ///
+    /// # 示例
+    ///
+    /// ```ignore
+    /// let options = SocketOptions::new().nodelay(true).keepalive(Duration::from_secs(30));
+    /// let stream = TcpClient::connect_with("127.0.0.1:8080", &options).await?;
+    /// ```
+    pub async fn connect_with(addr: &str, options: &SocketOptions) -> Result<TcpStream> {
+        options.connect(addr).await
+    }
 }
 
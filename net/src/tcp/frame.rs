@@ -0,0 +1,171 @@
+//! # frame
+//!
+//! frame 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Length-prefixed frames over a stream
+//!
+//! `FrameCodec` splits a byte stream into messages: each frame is a 4-byte
+//! big-endian length followed by that many bytes. With `trace_context`, the
+//! body starts with a one-byte context length and the binary span context
+//! of the sender (see `crate::trace::inject_context_to_binary`), so a trace continues
+//! across TCP services the same way it does over HTTP and gRPC:
+//!
+//! ```text
+//! | length: u32 | context length: u8 | context: 0 or 29 bytes | payload |
+//! ```
+//!
+//! Both ends must agree on the option. Frames written outside a span carry
+//! an empty context.
+//!
+//! Frames are assembled and read in buffers of `BufferPool::global()`, or of
+//! the pool set with `buffer_pool`. A payload shares its buffer's memory,
+//! which goes back to the pool once the payload is dropped.
+//!
+//! ```ignore
+//! let codec = FrameCodec::new().trace_context(true);
+//! codec.write_frame(&mut stream, b"ping").await?;
+//!
+//! while let Some(frame) = codec.read_frame(&mut stream).await? {
+//!     let span = tracing::info_span!("tcp.message");
+//!     span.set_parent(frame.context.clone());
+//!     handle(frame.payload).instrument(span).await;
+//! }
+//! ```
+
+use crate::buffer::BufferPool;
+use bytes::{Buf, Bytes};
+use opentelemetry::Context;
+use crate::trace::{extract_context_from_binary, inject_context_to_binary, BINARY_CONTEXT_LEN};
+use rf_errors::{Result, RfError};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Default largest accepted payload, 8 MiB
+const DEFAULT_MAX_FRAME_SIZE: usize = 8 * 1024 * 1024;
+
+/// A received frame
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Message bytes
+    pub payload: Bytes,
+    /// Trace context of the sender, empty without `trace_context`
+    pub context: Context,
+}
+
+/// Length-prefixed framing, optionally carrying the trace context
+#[derive(Debug, Clone)]
+pub struct FrameCodec {
+    max_frame_size: usize,
+    trace_context: bool,
+    pool: BufferPool,
+}
+
+impl Default for FrameCodec {
+    fn default() -> Self {
+        Self {
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            trace_context: false,
+            pool: BufferPool::global().clone(),
+        }
+    }
+}
+
+impl FrameCodec {
+    /// Codec with an 8 MiB frame limit and no trace context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Largest payload accepted or sent; longer frames fail
+    pub fn max_frame_size(mut self, bytes: usize) -> Self {
+        self.max_frame_size = bytes;
+        self
+    }
+
+    /// Prefix every payload with the binary trace context
+    pub fn trace_context(mut self, enabled: bool) -> Self {
+        self.trace_context = enabled;
+        self
+    }
+
+    /// Take frame buffers from `pool` instead of the global pool
+    pub fn buffer_pool(mut self, pool: BufferPool) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// Write one frame carrying the current trace context
+    pub async fn write_frame<W: AsyncWrite + Unpin>(&self, writer: &mut W, payload: &[u8]) -> Result<()> {
+        // Read before the first await, the context is task-local state
+        let context = Context::current();
+        self.write_frame_with(writer, payload, &context).await
+    }
+
+    /// Write one frame carrying `context`
+    pub async fn write_frame_with<W: AsyncWrite + Unpin>(&self, writer: &mut W, payload: &[u8], context: &Context) -> Result<()> {
+        if payload.len() > self.max_frame_size {
+            return Err(RfError::Network(format!(
+                "Frame of {} bytes exceeds the limit of {} bytes",
+                payload.len(),
+                self.max_frame_size
+            )));
+        }
+        let mut frame = self.pool.get_with_capacity(4 + 1 + BINARY_CONTEXT_LEN + payload.len());
+        frame.extend_from_slice(&[0; 4]);
+        if self.trace_context {
+            match inject_context_to_binary(context) {
+                Some(bytes) => {
+                    frame.extend_from_slice(&[BINARY_CONTEXT_LEN as u8]);
+                    frame.extend_from_slice(&bytes);
+                }
+                None => frame.extend_from_slice(&[0]),
+            }
+        }
+        frame.extend_from_slice(payload);
+        let len = u32::try_from(frame.len() - 4)
+            .map_err(|_| RfError::Network(format!("Frame of {} bytes is too long", payload.len())))?;
+        frame[..4].copy_from_slice(&len.to_be_bytes());
+        writer.write_all(&frame).await.map_err(|e| RfError::Network(format!("Failed to write frame: {}", e)))?;
+        writer.flush().await.map_err(|e| RfError::Network(format!("Failed to write frame: {}", e)))
+    }
+
+    /// Read the next frame, `None` when the stream ends between frames
+    pub async fn read_frame<R: AsyncRead + Unpin>(&self, reader: &mut R) -> Result<Option<Frame>> {
+        let mut header = [0u8; 4];
+        match reader.read_exact(&mut header).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(RfError::Network(format!("Failed to read frame: {}", e))),
+        }
+        let len = u32::from_be_bytes(header) as usize;
+        let overhead = if self.trace_context { 1 + BINARY_CONTEXT_LEN } else { 0 };
+        if len > self.max_frame_size + overhead {
+            return Err(RfError::Network(format!(
+                "Frame of {} bytes exceeds the limit of {} bytes",
+                len, self.max_frame_size
+            )));
+        }
+        let mut body = self.pool.get_with_capacity(len);
+        body.resize(len, 0);
+        reader.read_exact(&mut body).await.map_err(|e| RfError::Network(format!("Failed to read frame: {}", e)))?;
+        if !self.trace_context {
+            return Ok(Some(Frame {
+                payload: body.into_bytes(),
+                context: Context::new(),
+            }));
+        }
+
+        let context_len = *body.first().ok_or_else(|| RfError::Network("Frame without trace context length".to_string()))? as usize;
+        if body.len() < 1 + context_len {
+            return Err(RfError::Network("Frame shorter than its trace context".to_string()));
+        }
+        let context = extract_context_from_binary(&body[1..1 + context_len]);
+        body.advance(1 + context_len);
+        Ok(Some(Frame {
+            payload: body.into_bytes(),
+            context,
+        }))
+    }
+}
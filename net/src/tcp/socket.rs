@@ -0,0 +1,204 @@
+//! # socket
+//!
+//! socket 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Socket options for listeners and connections
+//!
+//! `SocketOptions` sets TCP_NODELAY, SO_KEEPALIVE (with idle time, probe
+//! interval and probe count), SO_REUSEADDR, SO_REUSEPORT and buffer sizes.
+//! Listeners are bound with `bind`; accepted and connected streams get the
+//! per-connection options through `apply`. The options deserialize from the
+//! `socket` table of a server config section:
+//!
+//! ```toml
+//! [server.default.socket]
+//! nodelay = true
+//! keepalive = "60s"          # idle time before the first probe
+//! keepalive_interval = "10s"
+//! keepalive_retries = 5
+//! reuse_port = true          # several processes accept on the same port
+//! recv_buffer_size = 262144
+//! send_buffer_size = 262144
+//! backlog = 4096
+//! ```
+//!
+//! With `reuse_port`, each process binds its own listener and the kernel
+//! spreads incoming connections over them (Linux, BSD).
+
+use rf_errors::{Result, RfError};
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+
+/// TCP socket options
+///
+/// Defaults: SO_REUSEADDR on (so a restarted server can rebind while old
+/// connections are in TIME_WAIT), a backlog of 1024, everything else left to
+/// the operating system.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SocketOptions {
+    /// TCP_NODELAY: send small writes immediately instead of batching them
+    pub nodelay: Option<bool>,
+    /// SO_KEEPALIVE with the idle time before the first probe
+    #[serde(with = "rf_os::cfg::serde_duration::option")]
+    pub keepalive: Option<Duration>,
+    /// Time between keepalive probes
+    #[serde(with = "rf_os::cfg::serde_duration::option")]
+    pub keepalive_interval: Option<Duration>,
+    /// Unanswered probes before the connection is dropped
+    pub keepalive_retries: Option<u32>,
+    /// SO_REUSEADDR on the listener
+    pub reuse_address: bool,
+    /// SO_REUSEPORT on the listener, for several processes on one port (unix only)
+    pub reuse_port: bool,
+    /// SO_RCVBUF in bytes
+    pub recv_buffer_size: Option<usize>,
+    /// SO_SNDBUF in bytes
+    pub send_buffer_size: Option<usize>,
+    /// Length of the queue of connections waiting to be accepted
+    pub backlog: u32,
+}
+
+impl Default for SocketOptions {
+    fn default() -> Self {
+        Self {
+            nodelay: None,
+            keepalive: None,
+            keepalive_interval: None,
+            keepalive_retries: None,
+            reuse_address: true,
+            reuse_port: false,
+            recv_buffer_size: None,
+            send_buffer_size: None,
+            backlog: 1024,
+        }
+    }
+}
+
+impl SocketOptions {
+    /// Options left to the operating system defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set TCP_NODELAY
+    pub fn nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = Some(nodelay);
+        self
+    }
+
+    /// Enable SO_KEEPALIVE, probing after `idle` without traffic
+    pub fn keepalive(mut self, idle: Duration) -> Self {
+        self.keepalive = Some(idle);
+        self
+    }
+
+    /// Time between keepalive probes
+    pub fn keepalive_interval(mut self, interval: Duration) -> Self {
+        self.keepalive_interval = Some(interval);
+        self
+    }
+
+    /// Unanswered keepalive probes before the connection is dropped
+    pub fn keepalive_retries(mut self, retries: u32) -> Self {
+        self.keepalive_retries = Some(retries);
+        self
+    }
+
+    /// Set SO_REUSEADDR on listeners
+    pub fn reuse_address(mut self, reuse: bool) -> Self {
+        self.reuse_address = reuse;
+        self
+    }
+
+    /// Set SO_REUSEPORT on listeners
+    pub fn reuse_port(mut self, reuse: bool) -> Self {
+        self.reuse_port = reuse;
+        self
+    }
+
+    /// Set SO_RCVBUF
+    pub fn recv_buffer_size(mut self, bytes: usize) -> Self {
+        self.recv_buffer_size = Some(bytes);
+        self
+    }
+
+    /// Set SO_SNDBUF
+    pub fn send_buffer_size(mut self, bytes: usize) -> Self {
+        self.send_buffer_size = Some(bytes);
+        self
+    }
+
+    /// Set the accept backlog
+    pub fn backlog(mut self, backlog: u32) -> Self {
+        self.backlog = backlog.max(1);
+        self
+    }
+
+    /// Bind a listener to `addr` with these options
+    pub fn bind(&self, addr: SocketAddr) -> Result<TcpListener> {
+        let error = |what: &str, e: std::io::Error| RfError::Network(format!("Failed to {} {}: {}", what, addr, e));
+        let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))
+            .map_err(|e| error("create socket for", e))?;
+        socket.set_reuse_address(self.reuse_address).map_err(|e| error("set SO_REUSEADDR on", e))?;
+        if self.reuse_port {
+            #[cfg(unix)]
+            socket.set_reuse_port(true).map_err(|e| error("set SO_REUSEPORT on", e))?;
+            #[cfg(not(unix))]
+            return Err(RfError::Network("SO_REUSEPORT is not supported on this platform".to_string()));
+        }
+        // Accepted sockets inherit these from the listener on most platforms;
+        // `apply` sets them again on each connection
+        self.apply_to(&socket).map_err(|e| error("set socket options on", e))?;
+        socket.set_nonblocking(true).map_err(|e| error("configure", e))?;
+        socket.bind(&addr.into()).map_err(|e| error("bind", e))?;
+        socket
+            .listen(i32::try_from(self.backlog).unwrap_or(i32::MAX))
+            .map_err(|e| error("listen on", e))?;
+        TcpListener::from_std(socket.into()).map_err(|e| error("register listener for", e))
+    }
+
+    /// Connect to `addr` with these options
+    pub async fn connect(&self, addr: &str) -> Result<TcpStream> {
+        let stream = TcpStream::connect(addr)
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to connect: {}", e)))?;
+        self.apply(&stream)?;
+        Ok(stream)
+    }
+
+    /// Set the per-connection options (nodelay, keepalive, buffer sizes) on `stream`
+    pub fn apply(&self, stream: &TcpStream) -> Result<()> {
+        self.apply_to(&SockRef::from(stream))
+            .map_err(|e| RfError::Network(format!("Failed to set socket options: {}", e)))
+    }
+
+    fn apply_to(&self, socket: &Socket) -> std::io::Result<()> {
+        if let Some(nodelay) = self.nodelay {
+            socket.set_tcp_nodelay(nodelay)?;
+        }
+        if let Some(idle) = self.keepalive {
+            let mut keepalive = TcpKeepalive::new().with_time(idle);
+            if let Some(interval) = self.keepalive_interval {
+                keepalive = keepalive.with_interval(interval);
+            }
+            #[cfg(not(any(target_os = "windows", target_os = "openbsd")))]
+            if let Some(retries) = self.keepalive_retries {
+                keepalive = keepalive.with_retries(retries);
+            }
+            socket.set_tcp_keepalive(&keepalive)?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,573 @@
+//! # tls
+//!
+//! tls 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! TLS for custom TCP protocols
+//!
+//! Built on rustls, sharing the certificate loader and the hot-swappable
+//! `TlsAcceptorHandle` with HTTPS. `TlsTcpServer` accepts TLS connections
+//! with a PEM certificate chain and key, optional ALPN protocols and optional
+//! client certificate checks. `TlsClientConfig` connects to a TLS server,
+//! verifying its certificate against the system roots and any extra CAs.
+//! Both produce a `TlsTcpStream`, which implements `AsyncRead` and
+//! `AsyncWrite`.
+//!
+//! ```ignore
+//! use rf_net::{TlsClientConfig, TlsServerConfig, TlsTcpServer};
+//!
+//! let config = TlsServerConfig::new("certs/server.crt", "certs/server.key").alpn(&["myproto/1"]);
+//! let server = TlsTcpServer::bind("0.0.0.0:9443", config).await?;
+//! // Pick up renewed certificates without a restart
+//! let _watcher = server.acceptor().watch()?;
+//! loop {
+//!     // Handshake in the connection task so slow clients don't block accepting
+//!     let (handshake, addr) = server.accept_tcp().await?;
+//!     tokio::spawn(async move {
+//!         let stream = handshake.finish().await?;
+//!         assert_eq!(stream.alpn_protocol(), Some(&b"myproto/1"[..]));
+//!         // ...
+//!     });
+//! }
+//!
+//! let client = TlsClientConfig::new().ca_file("certs/ca.crt").alpn(&["myproto/1"]);
+//! let stream = client.connect("server.internal:9443").await?;
+//! ```
+
+use crate::http::tls::{
+    crypto_provider, load_certs, load_private_key, load_roots, read_pem, HandshakeOptions, TlsAcceptorHandle,
+};
+use rf_errors::{Result, RfError};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{CertificateError, ClientConfig, DigitallySignedStruct, ProtocolVersion, RootCertStore, SignatureScheme};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::{TlsAcceptor, TlsConnector, TlsStream};
+
+/// Certificate source: PEM files or in-memory PEM data
+#[derive(Debug, Clone)]
+enum Pem {
+    File(PathBuf),
+    Data(Vec<u8>),
+}
+
+impl Pem {
+    fn load(&self) -> Result<Vec<u8>> {
+        match self {
+            Pem::File(path) => read_pem(path),
+            Pem::Data(data) => Ok(data.clone()),
+        }
+    }
+}
+
+/// Server side TLS settings for `TlsTcpServer`
+#[derive(Debug, Clone)]
+pub struct TlsServerConfig {
+    cert: Pem,
+    key: Pem,
+    alpn: Vec<String>,
+    client_ca: Option<Pem>,
+    /// Maximum time for a client to complete the handshake (default: 10s)
+    pub handshake_timeout: Duration,
+}
+
+impl TlsServerConfig {
+    /// Use the PEM certificate chain and private key files
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        Self::with_pem(Pem::File(cert_path.into()), Pem::File(key_path.into()))
+    }
+
+    /// Use in-memory PEM data
+    pub fn from_pem(cert_pem: impl Into<Vec<u8>>, key_pem: impl Into<Vec<u8>>) -> Self {
+        Self::with_pem(Pem::Data(cert_pem.into()), Pem::Data(key_pem.into()))
+    }
+
+    fn with_pem(cert: Pem, key: Pem) -> Self {
+        Self {
+            cert,
+            key,
+            alpn: Vec::new(),
+            client_ca: None,
+            handshake_timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Negotiate one of `protocols` with ALPN, in order of preference
+    ///
+    /// Clients offering ALPN without a common protocol are rejected with the
+    /// `no_application_protocol` alert; clients without ALPN are accepted.
+    pub fn alpn(mut self, protocols: &[&str]) -> Self {
+        self.alpn = protocols.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// Require client certificates issued by the CAs in this PEM file (mutual TLS)
+    pub fn client_ca(mut self, ca_path: impl Into<PathBuf>) -> Self {
+        self.client_ca = Some(Pem::File(ca_path.into()));
+        self
+    }
+
+    /// Require client certificates issued by these PEM CAs (mutual TLS)
+    pub fn client_ca_pem(mut self, ca_pem: impl Into<Vec<u8>>) -> Self {
+        self.client_ca = Some(Pem::Data(ca_pem.into()));
+        self
+    }
+
+    /// Set the handshake timeout
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    fn build(&self) -> Result<TlsAcceptorHandle> {
+        let options = HandshakeOptions {
+            alpn: alpn_protocols(&self.alpn)?,
+            client_ca: self.client_ca.as_ref().map(Pem::load).transpose()?,
+        };
+        match (&self.cert, &self.key) {
+            // Loaded from files so `reload` and `watch` work
+            (Pem::File(cert), Pem::File(key)) => TlsAcceptorHandle::from_pem_files_with(cert, key, options),
+            (cert, key) => TlsAcceptorHandle::from_pem_with(&cert.load()?, &key.load()?, options),
+        }
+    }
+}
+
+/// TLS server for custom TCP protocols
+pub struct TlsTcpServer {
+    listener: TcpListener,
+    acceptor: Arc<TlsAcceptorHandle>,
+    handshake_timeout: Duration,
+}
+
+impl TlsTcpServer {
+    /// Bind to `addr`, loading the certificate from `config`
+    pub async fn bind(addr: &str, config: TlsServerConfig) -> Result<Self> {
+        let acceptor = Arc::new(config.build()?);
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to bind TCP server: {}", e)))?;
+        Ok(Self {
+            listener,
+            acceptor,
+            handshake_timeout: config.handshake_timeout,
+        })
+    }
+
+    /// Local address, e.g. to find the port after binding to port 0
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener
+            .local_addr()
+            .map_err(|e| RfError::Network(format!("Failed to get local address: {}", e)))
+    }
+
+    /// Certificate holder; `swap`, `reload` and `watch` apply to new connections
+    pub fn acceptor(&self) -> Arc<TlsAcceptorHandle> {
+        self.acceptor.clone()
+    }
+
+    /// Accept a connection and complete the TLS handshake
+    ///
+    /// A slow client holds up the next accept; servers with many clients
+    /// should use `accept_tcp` and finish the handshake in the connection task.
+    pub async fn accept(&self) -> Result<(TlsTcpStream, SocketAddr)> {
+        let (handshake, addr) = self.accept_tcp().await?;
+        Ok((handshake.finish().await?, addr))
+    }
+
+    /// Accept a TCP connection, leaving the TLS handshake to the caller
+    pub async fn accept_tcp(&self) -> Result<(TlsHandshake, SocketAddr)> {
+        let (stream, addr) = self
+            .listener
+            .accept()
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to accept connection: {}", e)))?;
+        Ok((
+            TlsHandshake {
+                stream,
+                acceptor: self.acceptor.acceptor(),
+                timeout: self.handshake_timeout,
+            },
+            addr,
+        ))
+    }
+}
+
+/// Server handshake of an accepted connection, see `TlsTcpServer::accept_tcp`
+pub struct TlsHandshake {
+    stream: TcpStream,
+    acceptor: TlsAcceptor,
+    timeout: Duration,
+}
+
+impl TlsHandshake {
+    /// Complete the handshake within the configured timeout
+    pub async fn finish(self) -> Result<TlsTcpStream> {
+        let stream = tokio::time::timeout(self.timeout, self.acceptor.accept(self.stream))
+            .await
+            .map_err(|_| RfError::Network("TLS handshake timed out".to_string()))?
+            .map_err(handshake_error)?;
+        Ok(TlsTcpStream { inner: TlsStream::Server(stream) })
+    }
+}
+
+/// Client side TLS settings
+///
+/// The server certificate is verified against the system roots plus any CAs
+/// added with `ca_file`/`ca_pem`, and its name must match the host connected
+/// to (or `server_name`).
+#[derive(Debug, Clone)]
+pub struct TlsClientConfig {
+    cas: Vec<Pem>,
+    identity: Option<(Pem, Pem)>,
+    alpn: Vec<String>,
+    server_name: Option<String>,
+    accept_invalid_certs: bool,
+    accept_invalid_hostnames: bool,
+    handshake_timeout: Duration,
+}
+
+impl TlsClientConfig {
+    pub fn new() -> Self {
+        Self {
+            cas: Vec::new(),
+            identity: None,
+            alpn: Vec::new(),
+            server_name: None,
+            accept_invalid_certs: false,
+            accept_invalid_hostnames: false,
+            handshake_timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Trust the CAs in this PEM file in addition to the system roots
+    pub fn ca_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cas.push(Pem::File(path.into()));
+        self
+    }
+
+    /// Trust these PEM CAs in addition to the system roots
+    pub fn ca_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.cas.push(Pem::Data(pem.into()));
+        self
+    }
+
+    /// Present a client certificate (mutual TLS) from PEM files
+    pub fn client_cert(mut self, cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        self.identity = Some((Pem::File(cert_path.into()), Pem::File(key_path.into())));
+        self
+    }
+
+    /// Present a client certificate (mutual TLS) from PEM data
+    pub fn client_cert_pem(mut self, cert_pem: impl Into<Vec<u8>>, key_pem: impl Into<Vec<u8>>) -> Self {
+        self.identity = Some((Pem::Data(cert_pem.into()), Pem::Data(key_pem.into())));
+        self
+    }
+
+    /// Offer these ALPN protocols, in order of preference
+    pub fn alpn(mut self, protocols: &[&str]) -> Self {
+        self.alpn = protocols.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// Name sent with SNI and checked against the certificate (default: the host connected to)
+    pub fn server_name(mut self, name: impl Into<String>) -> Self {
+        self.server_name = Some(name.into());
+        self
+    }
+
+    /// Skip certificate verification entirely; only for tests and development
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Verify the certificate chain but not the host name
+    pub fn danger_accept_invalid_hostnames(mut self, accept: bool) -> Self {
+        self.accept_invalid_hostnames = accept;
+        self
+    }
+
+    /// Maximum time to complete the handshake (default: 10s)
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    /// Connect to `addr` ("host:port") and complete the TLS handshake
+    pub async fn connect(&self, addr: &str) -> Result<TlsTcpStream> {
+        let stream = TcpStream::connect(addr)
+            .await
+            .map_err(|e| RfError::Network(format!("Failed to connect: {}", e)))?;
+        let host = match &self.server_name {
+            Some(name) => name.clone(),
+            None => host_of(addr).to_string(),
+        };
+        self.handshake(stream, &host).await
+    }
+
+    /// Run the TLS handshake over an established connection
+    ///
+    /// IP addresses are checked against the certificate but not sent with SNI.
+    pub async fn handshake(&self, stream: TcpStream, server_name: &str) -> Result<TlsTcpStream> {
+        let name = ServerName::try_from(server_name.to_string())
+            .map_err(|_| RfError::InvalidParameter(format!("Invalid TLS server name: {}", server_name)))?;
+        let connector = TlsConnector::from(Arc::new(self.build()?));
+        let stream = tokio::time::timeout(self.handshake_timeout, connector.connect(name, stream))
+            .await
+            .map_err(|_| RfError::Network("TLS handshake timed out".to_string()))?
+            .map_err(handshake_error)?;
+        Ok(TlsTcpStream { inner: TlsStream::Client(stream) })
+    }
+
+    fn build(&self) -> Result<ClientConfig> {
+        let error = |e: rustls::Error| RfError::Config(format!("Invalid TLS client configuration: {}", e));
+        let provider = crypto_provider();
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(error)?;
+
+        let builder = if self.accept_invalid_certs {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+        } else {
+            let mut roots = RootCertStore::empty();
+            roots.add_parsable_certificates(system_roots().iter().cloned());
+            for ca in &self.cas {
+                roots.roots.extend(load_roots(&ca.load()?)?.roots);
+            }
+            let verifier = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(|e| RfError::Config(format!("Invalid TLS client configuration: {}", e)))?;
+            if self.accept_invalid_hostnames {
+                builder
+                    .dangerous()
+                    .with_custom_certificate_verifier(Arc::new(IgnoreHostname(verifier)))
+            } else {
+                builder.with_webpki_verifier(verifier)
+            }
+        };
+
+        let mut config = match &self.identity {
+            Some((cert, key)) => builder
+                .with_client_auth_cert(load_certs(&cert.load()?)?, load_private_key(&key.load()?)?)
+                .map_err(error)?,
+            None => builder.with_no_client_auth(),
+        };
+        config.alpn_protocols = alpn_protocols(&self.alpn)?;
+        Ok(config)
+    }
+}
+
+impl Default for TlsClientConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Trust anchors of the operating system, loaded once
+fn system_roots() -> &'static [CertificateDer<'static>] {
+    static ROOTS: OnceLock<Vec<CertificateDer<'static>>> = OnceLock::new();
+    ROOTS.get_or_init(|| {
+        let loaded = rustls_native_certs::load_native_certs();
+        for e in &loaded.errors {
+            tracing::debug!("Skipping system CA certificates: {}", e);
+        }
+        loaded.certs
+    })
+}
+
+/// Verifier for `danger_accept_invalid_certs`: checks handshake signatures only
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Verifier for `danger_accept_invalid_hostnames`: full chain check, any name
+#[derive(Debug)]
+struct IgnoreHostname(Arc<WebPkiServerVerifier>);
+
+impl ServerCertVerifier for IgnoreHostname {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        // The name is checked after the chain, so a name error means the chain is valid
+        match self.0.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now) {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::NotValidForName | CertificateError::NotValidForNameContext { .. },
+            )) => Ok(ServerCertVerified::assertion()),
+            result => result,
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.0.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.0.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_verify_schemes()
+    }
+}
+
+/// TLS connection over TCP
+pub struct TlsTcpStream {
+    inner: TlsStream<TcpStream>,
+}
+
+impl TlsTcpStream {
+    /// Protocol selected with ALPN, if any
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.inner.get_ref().1.alpn_protocol()
+    }
+
+    /// DER encoded certificate of the peer, if it sent one
+    pub fn peer_certificate(&self) -> Option<Vec<u8>> {
+        let certs = self.inner.get_ref().1.peer_certificates()?;
+        certs.first().map(|cert| cert.to_vec())
+    }
+
+    /// Negotiated protocol version, e.g. `TLSv1.3`
+    pub fn version(&self) -> &'static str {
+        match self.inner.get_ref().1.protocol_version() {
+            Some(ProtocolVersion::TLSv1_3) => "TLSv1.3",
+            Some(ProtocolVersion::TLSv1_2) => "TLSv1.2",
+            _ => "unknown",
+        }
+    }
+
+    /// Server name the client sent with SNI (server side)
+    pub fn server_name(&self) -> Option<&str> {
+        match &self.inner {
+            TlsStream::Server(stream) => stream.get_ref().1.server_name(),
+            TlsStream::Client(_) => None,
+        }
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.tcp().peer_addr().map_err(|e| RfError::Network(format!("Failed to get peer address: {}", e)))
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.tcp().local_addr().map_err(|e| RfError::Network(format!("Failed to get local address: {}", e)))
+    }
+
+    fn tcp(&self) -> &TcpStream {
+        self.inner.get_ref().0
+    }
+}
+
+impl AsyncRead for TlsTcpStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TlsTcpStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    /// Sends `close_notify`, then shuts down the TCP write half
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+fn handshake_error(e: std::io::Error) -> RfError {
+    let tls = e.get_ref().and_then(|inner| inner.downcast_ref::<rustls::Error>());
+    match tls {
+        Some(rustls::Error::InvalidCertificate(reason)) => {
+            RfError::Network(format!("TLS handshake failed: certificate verification failed: {:?}", reason))
+        }
+        _ => RfError::Network(format!("TLS handshake failed: {}", e)),
+    }
+}
+
+/// ALPN protocol names, checked to be 1-255 bytes long
+fn alpn_protocols(protocols: &[String]) -> Result<Vec<Vec<u8>>> {
+    protocols
+        .iter()
+        .map(|protocol| match protocol.len() {
+            1..=255 => Ok(protocol.as_bytes().to_vec()),
+            _ => Err(RfError::Config(format!("Invalid ALPN protocol: {:?}", protocol))),
+        })
+        .collect()
+}
+
+/// Host part of "host:port" or "[v6]:port"
+fn host_of(addr: &str) -> &str {
+    if let Some(rest) = addr.strip_prefix('[') {
+        return rest.split(']').next().unwrap_or(rest);
+    }
+    match addr.rsplit_once(':') {
+        Some((host, _)) => host,
+        None => addr,
+    }
+}
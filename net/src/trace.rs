@@ -15,6 +15,8 @@
 //! - 支持 OTLP（OpenTelemetry Protocol）通过 gRPC 或 HTTP 导出追踪数据
 //! - 创建和管理 spans
 //! - HTTP 请求的追踪上下文传播
+//! - TCP 帧等二进制传输的追踪上下文编码
+//! - `HttpServer` 的服务端追踪扩展点（`HttpTracing`）
 //!
 //! # 使用示例
 //!
@@ -52,6 +54,8 @@ use rf_errors::Result;
 use opentelemetry::trace::Tracer;
 use opentelemetry::Context;
 use opentelemetry::global;
+use futures_util::future::BoxFuture;
+use std::sync::{Arc, RwLock};
 
 /// 初始化基础的 OpenTelemetry 追踪
 ///
@@ -355,3 +359,149 @@ pub fn inject_context_to_headers(context: &Context, headers: &mut axum::http::He
         propagator.inject_context(context, &mut injector);
     });
 }
+
+/// 二进制追踪上下文的编码长度
+///
+/// 与 `grpc-trace-bin` 相同的格式：一个版本字节，随后是带标签的 trace id、
+/// span id 和 trace flags 字段，共 29 字节。
+pub const BINARY_CONTEXT_LEN: usize = 29;
+
+const BINARY_VERSION: u8 = 0;
+const TRACE_ID_FIELD: u8 = 0;
+const SPAN_ID_FIELD: u8 = 1;
+const TRACE_FLAGS_FIELD: u8 = 2;
+
+/// 将追踪上下文编码为二进制格式
+///
+/// 用于没有文本头的传输（例如 `FrameCodec` 的 TCP 帧）。
+/// 上下文中没有有效的 span 时返回 `None`。
+pub fn inject_context_to_binary(context: &Context) -> Option<[u8; BINARY_CONTEXT_LEN]> {
+    use opentelemetry::trace::TraceContextExt;
+
+    let span = context.span();
+    let span_context = span.span_context();
+    if !span_context.is_valid() {
+        return None;
+    }
+    let mut bytes = [0u8; BINARY_CONTEXT_LEN];
+    bytes[0] = BINARY_VERSION;
+    bytes[1] = TRACE_ID_FIELD;
+    bytes[2..18].copy_from_slice(&span_context.trace_id().to_bytes());
+    bytes[18] = SPAN_ID_FIELD;
+    bytes[19..27].copy_from_slice(&span_context.span_id().to_bytes());
+    bytes[27] = TRACE_FLAGS_FIELD;
+    bytes[28] = span_context.trace_flags().to_u8();
+    Some(bytes)
+}
+
+/// 解码 `inject_context_to_binary` 写入的 span 上下文
+///
+/// 版本未知、字段被截断或 trace id / span id 无效时返回 `None`。
+/// 已知字段之后的未知字段会被忽略，新版本的发送方仍然兼容。
+pub fn span_context_from_binary(bytes: &[u8]) -> Option<opentelemetry::trace::SpanContext> {
+    use opentelemetry::trace::{SpanContext, SpanId, TraceFlags, TraceId, TraceState};
+
+    fn split(bytes: &[u8], len: usize) -> Option<(&[u8], &[u8])> {
+        (bytes.len() >= len).then(|| bytes.split_at(len))
+    }
+
+    let (&version, mut rest) = bytes.split_first()?;
+    if version != BINARY_VERSION {
+        return None;
+    }
+    let mut trace_id = None;
+    let mut span_id = None;
+    let mut flags = TraceFlags::default();
+    while let Some((&field, tail)) = rest.split_first() {
+        rest = match field {
+            TRACE_ID_FIELD => {
+                let (id, tail) = split(tail, 16)?;
+                trace_id = Some(TraceId::from_bytes(id.try_into().ok()?));
+                tail
+            }
+            SPAN_ID_FIELD => {
+                let (id, tail) = split(tail, 8)?;
+                span_id = Some(SpanId::from_bytes(id.try_into().ok()?));
+                tail
+            }
+            TRACE_FLAGS_FIELD => {
+                let (value, tail) = split(tail, 1)?;
+                flags = TraceFlags::new(value[0]);
+                tail
+            }
+            _ => break,
+        };
+    }
+    let span_context = SpanContext::new(trace_id?, span_id?, flags, true, TraceState::default());
+    span_context.is_valid().then_some(span_context)
+}
+
+/// 从二进制格式中提取追踪上下文
+///
+/// 返回以解码出的 span 为远程父 span 的上下文；无法解码时返回空上下文。
+pub fn extract_context_from_binary(bytes: &[u8]) -> Context {
+    use opentelemetry::trace::TraceContextExt;
+
+    match span_context_from_binary(bytes) {
+        Some(span_context) => Context::new().with_remote_span_context(span_context),
+        None => Context::new(),
+    }
+}
+
+/// HTTP 服务端追踪
+///
+/// 由追踪实现提供（例如 `rf-contrib-trace` 的 `TraceMiddleware`），
+/// `HttpServer` 用它为每个请求创建服务端 span。
+pub trait HttpTracing: Send + Sync {
+    /// 在服务端 span 中执行请求
+    fn trace(&self, request: axum::extract::Request, next: axum::middleware::Next) -> BoxFuture<'static, axum::response::Response>;
+}
+
+static HTTP_TRACING: RwLock<Option<Arc<dyn HttpTracing>>> = RwLock::new(None);
+
+/// 设置全局 HTTP 追踪
+///
+/// 追踪管道启动时设置、关闭时清除；没有调用 `with_tracing` 的 `HttpServer`
+/// 在启动时使用这里设置的追踪。
+pub fn set_http_tracing(tracing: Option<Arc<dyn HttpTracing>>) {
+    *HTTP_TRACING.write().unwrap() = tracing;
+}
+
+/// 当前的全局 HTTP 追踪
+pub fn http_tracing() -> Option<Arc<dyn HttpTracing>> {
+    HTTP_TRACING.read().unwrap().clone()
+}
+
+/// 通过 `HttpTracing` 追踪请求的中间件
+pub(crate) async fn http_tracing_middleware(
+    axum::extract::State(tracing): axum::extract::State<Arc<dyn HttpTracing>>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    tracing.trace(request, next).await
+}
+
+/// 追踪管道的关闭函数
+pub type TracingShutdown = fn() -> Result<()>;
+
+static TRACING_SHUTDOWN: RwLock<Option<TracingShutdown>> = RwLock::new(None);
+
+/// 设置追踪管道的关闭函数
+///
+/// 由追踪管道（例如 `rf-contrib-trace` 的 `init_tracing_*`）在启动时设置，
+/// 应用关闭时通过 [`shutdown_tracing`] 调用。
+pub fn set_tracing_shutdown(shutdown: Option<TracingShutdown>) {
+    *TRACING_SHUTDOWN.write().unwrap() = shutdown;
+}
+
+/// 导出尚未发送的 span 并关闭追踪管道
+///
+/// 没有设置关闭函数时直接返回 `Ok(())`。导出可能阻塞，异步代码中应在
+/// `spawn_blocking` 里调用。
+pub fn shutdown_tracing() -> Result<()> {
+    let shutdown = *TRACING_SHUTDOWN.read().unwrap();
+    match shutdown {
+        Some(shutdown) => shutdown(),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,700 @@
+//! # webhook
+//!
+//! webhook 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Webhooks
+//!
+//! Signing follows the Standard Webhooks scheme: every delivery carries
+//! `webhook-id`, `webhook-timestamp` and `webhook-signature` headers, the
+//! signature being `v1,<base64 HMAC-SHA256 of "{id}.{timestamp}.{body}">`.
+//! Several space-separated signatures are sent while secrets rotate.
+//!
+//! `WebhookSender` delivers at least once: `enqueue` saves the message in a
+//! `PendingStore` before delivery starts and removes it once the message is
+//! delivered or dead-lettered, so `resume` can restart deliveries cut short
+//! by a restart or crash. Failed attempts (network errors and non-2xx
+//! responses) are retried with exponential backoff, and messages that
+//! exhaust their attempts go to a `DeadLetterStore` from which they can be
+//! redelivered. Both stores default to memory, which only survives as long
+//! as the process; the Redis stores of the `webhook-redis` feature persist
+//! across restarts. Retries keep the message ID so receivers can deduplicate.
+//! Retry delays are waited out on the sender's `Clock` (see
+//! `WebhookSender::clock`).
+//!
+//! `WebhookVerifier` and `webhook_verify_middleware` check signatures and
+//! timestamps on the receiving side.
+//!
+//! ```rust,ignore
+//! use rf_net::webhook::{WebhookEndpoint, WebhookSender, WebhookVerifier, webhook_verify_middleware};
+//!
+//! let sender = WebhookSender::new()
+//!     .endpoint("billing", WebhookEndpoint::new("https://billing.internal/hooks", secret));
+//! // At startup: restart deliveries left pending by the previous process
+//! sender.resume().await?;
+//! sender.enqueue("billing", "invoice.paid", &invoice).await?;
+//!
+//! // Receiver
+//! let verifier = Arc::new(WebhookVerifier::new(secret));
+//! let server = HttpServer::new(addr).group("/hooks", |hooks| {
+//!     hooks
+//!         .middleware(axum::middleware::from_fn_with_state(verifier, webhook_verify_middleware))
+//!         .route(Method::POST, "/", |Json(message): Json<WebhookMessage>| async move { ... })
+//! })?;
+//! ```
+
+use crate::buffer::BufferPool;
+use crate::http::Response;
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response as AxumResponse};
+use rf_core::clock::Clock;
+use rf_errors::{codes, Result, RfError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+/// Message ID header
+pub const WEBHOOK_ID: &str = "webhook-id";
+/// Unix timestamp (seconds) header
+pub const WEBHOOK_TIMESTAMP: &str = "webhook-timestamp";
+/// Signature header
+pub const WEBHOOK_SIGNATURE: &str = "webhook-signature";
+
+/// Sign a payload, returning a `v1,<base64>` signature
+pub fn sign(secret: &[u8], id: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut content = format!("{}.{}.", id, timestamp).into_bytes();
+    content.extend_from_slice(body);
+    format!("v1,{}", rf_encoding::base64_encode(&rf_crypto::sha256::hmac(secret, &content)))
+}
+
+/// Message sent to an endpoint; this is the request body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookMessage {
+    pub id: String,
+    /// Event type, e.g. `invoice.paid`
+    pub event: String,
+    pub payload: serde_json::Value,
+    /// Unix seconds when the event was created
+    pub created_at: i64,
+}
+
+impl WebhookMessage {
+    pub fn new(event: impl Into<String>, payload: impl Serialize) -> Result<Self> {
+        Ok(Self {
+            id: format!("msg_{}", uuid::Uuid::new_v4().simple()),
+            event: event.into(),
+            payload: serde_json::to_value(payload)
+                .map_err(|e| RfError::Serialization(format!("Failed to serialize webhook payload: {}", e)))?,
+            created_at: chrono::Utc::now().timestamp(),
+        })
+    }
+}
+
+/// Receiving endpoint
+#[derive(Debug, Clone)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    secrets: Vec<Vec<u8>>,
+    headers: Vec<(String, String)>,
+}
+
+impl WebhookEndpoint {
+    pub fn new(url: impl Into<String>, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            url: url.into(),
+            secrets: vec![secret.as_ref().to_vec()],
+            headers: Vec::new(),
+        }
+    }
+
+    /// Also sign with another secret, e.g. while the receiver switches to it
+    pub fn secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.secrets.push(secret.as_ref().to_vec());
+        self
+    }
+
+    /// Extra request header
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    fn signature(&self, id: &str, timestamp: i64, body: &[u8]) -> String {
+        self.secrets
+            .iter()
+            .map(|secret| sign(secret, id, timestamp, body))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Delivery retry schedule
+///
+/// Deserializes from configuration with delays such as `"5s"` or `"1h"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts including the first one (default 8)
+    pub max_attempts: u32,
+    /// Delay after the first failure (default 5s)
+    #[serde(with = "rf_os::cfg::serde_duration")]
+    pub initial_delay: Duration,
+    /// Upper bound for the delay (default 1h)
+    #[serde(with = "rf_os::cfg::serde_duration")]
+    pub max_delay: Duration,
+    /// Delay growth per failure (default 2)
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            initial_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(3600),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the attempt following failed attempt `attempt` (1-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        self.initial_delay.mul_f64(factor).min(self.max_delay)
+    }
+}
+
+/// Message that exhausted its delivery attempts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Registered endpoint name
+    pub endpoint: String,
+    pub message: WebhookMessage,
+    pub attempts: u32,
+    pub last_error: String,
+    /// Unix seconds
+    pub failed_at: i64,
+}
+
+/// Persistence for dead letters, keyed by message ID
+#[async_trait]
+pub trait DeadLetterStore: Send + Sync {
+    async fn push(&self, letter: DeadLetter) -> Result<()>;
+
+    /// All dead letters, oldest first
+    async fn list(&self) -> Result<Vec<DeadLetter>>;
+
+    /// Remove and return a dead letter
+    async fn take(&self, id: &str) -> Result<Option<DeadLetter>>;
+}
+
+/// In-process dead letters
+#[derive(Default)]
+pub struct MemoryDeadLetterStore {
+    letters: Mutex<Vec<DeadLetter>>,
+}
+
+impl MemoryDeadLetterStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl DeadLetterStore for MemoryDeadLetterStore {
+    async fn push(&self, letter: DeadLetter) -> Result<()> {
+        let mut letters = self.letters.lock().unwrap_or_else(|e| e.into_inner());
+        letters.retain(|l| l.message.id != letter.message.id);
+        letters.push(letter);
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<DeadLetter>> {
+        Ok(self.letters.lock().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+
+    async fn take(&self, id: &str) -> Result<Option<DeadLetter>> {
+        let mut letters = self.letters.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(letters
+            .iter()
+            .position(|l| l.message.id == id)
+            .map(|index| letters.remove(index)))
+    }
+}
+
+/// Dead letters in a Redis hash
+#[cfg(feature = "webhook-redis")]
+pub struct RedisDeadLetterStore {
+    client: Arc<rf_database::redis::RedisClient>,
+    key: String,
+}
+
+#[cfg(feature = "webhook-redis")]
+impl RedisDeadLetterStore {
+    /// Use the `webhook:dead` hash
+    pub fn new(client: Arc<rf_database::redis::RedisClient>) -> Self {
+        Self {
+            client,
+            key: "webhook:dead".to_string(),
+        }
+    }
+
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+}
+
+#[cfg(feature = "webhook-redis")]
+#[async_trait]
+impl DeadLetterStore for RedisDeadLetterStore {
+    async fn push(&self, letter: DeadLetter) -> Result<()> {
+        self.client.hash().hset_json(&self.key, &letter.message.id, &letter).await
+    }
+
+    async fn list(&self) -> Result<Vec<DeadLetter>> {
+        let mut letters: Vec<DeadLetter> = self.client.hash().hgetall_json(&self.key).await?.into_values().collect();
+        letters.sort_by_key(|l| l.failed_at);
+        Ok(letters)
+    }
+
+    async fn take(&self, id: &str) -> Result<Option<DeadLetter>> {
+        let hash = self.client.hash();
+        let letter = hash.hget_json(&self.key, id).await?;
+        if letter.is_some() {
+            hash.hdel(&self.key, &[id]).await?;
+        }
+        Ok(letter)
+    }
+}
+
+/// Message queued by `WebhookSender::enqueue` and not delivered yet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingDelivery {
+    /// Registered endpoint name
+    pub endpoint: String,
+    pub message: WebhookMessage,
+    /// Unix seconds
+    pub queued_at: i64,
+}
+
+/// Persistence for queued messages, keyed by message ID
+///
+/// Messages stay in the store until they are delivered or dead-lettered.
+#[async_trait]
+pub trait PendingStore: Send + Sync {
+    async fn save(&self, delivery: PendingDelivery) -> Result<()>;
+
+    /// All pending messages, oldest first
+    async fn list(&self) -> Result<Vec<PendingDelivery>>;
+
+    async fn remove(&self, id: &str) -> Result<()>;
+}
+
+/// In-process pending messages
+#[derive(Default)]
+pub struct MemoryPendingStore {
+    deliveries: Mutex<Vec<PendingDelivery>>,
+}
+
+impl MemoryPendingStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PendingStore for MemoryPendingStore {
+    async fn save(&self, delivery: PendingDelivery) -> Result<()> {
+        let mut deliveries = self.deliveries.lock().unwrap_or_else(|e| e.into_inner());
+        deliveries.retain(|d| d.message.id != delivery.message.id);
+        deliveries.push(delivery);
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<PendingDelivery>> {
+        Ok(self.deliveries.lock().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+
+    async fn remove(&self, id: &str) -> Result<()> {
+        self.deliveries.lock().unwrap_or_else(|e| e.into_inner()).retain(|d| d.message.id != id);
+        Ok(())
+    }
+}
+
+/// Pending messages in a Redis hash
+#[cfg(feature = "webhook-redis")]
+pub struct RedisPendingStore {
+    client: Arc<rf_database::redis::RedisClient>,
+    key: String,
+}
+
+#[cfg(feature = "webhook-redis")]
+impl RedisPendingStore {
+    /// Use the `webhook:pending` hash
+    pub fn new(client: Arc<rf_database::redis::RedisClient>) -> Self {
+        Self {
+            client,
+            key: "webhook:pending".to_string(),
+        }
+    }
+
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+}
+
+#[cfg(feature = "webhook-redis")]
+#[async_trait]
+impl PendingStore for RedisPendingStore {
+    async fn save(&self, delivery: PendingDelivery) -> Result<()> {
+        self.client.hash().hset_json(&self.key, &delivery.message.id, &delivery).await
+    }
+
+    async fn list(&self) -> Result<Vec<PendingDelivery>> {
+        let mut deliveries: Vec<PendingDelivery> =
+            self.client.hash().hgetall_json(&self.key).await?.into_values().collect();
+        deliveries.sort_by_key(|d| d.queued_at);
+        Ok(deliveries)
+    }
+
+    async fn remove(&self, id: &str) -> Result<()> {
+        self.client.hash().hdel(&self.key, &[id]).await.map(|_| ())
+    }
+}
+
+/// Signs and delivers webhooks to named endpoints
+///
+/// Clones share endpoints and the pending and dead-letter stores.
+#[derive(Clone)]
+pub struct WebhookSender {
+    client: reqwest::Client,
+    endpoints: Arc<RwLock<HashMap<String, WebhookEndpoint>>>,
+    retry: RetryPolicy,
+    timeout: Duration,
+    pending: Arc<dyn PendingStore>,
+    dead_letters: Arc<dyn DeadLetterStore>,
+    clock: Clock,
+}
+
+impl WebhookSender {
+    /// Sender with in-memory pending messages and dead letters
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoints: Arc::new(RwLock::new(HashMap::new())),
+            retry: RetryPolicy::default(),
+            timeout: Duration::from_secs(15),
+            pending: Arc::new(MemoryPendingStore::new()),
+            dead_letters: Arc::new(MemoryDeadLetterStore::new()),
+            clock: Clock::system(),
+        }
+    }
+
+    pub fn endpoint(self, name: impl Into<String>, endpoint: WebhookEndpoint) -> Self {
+        self.add_endpoint(name, endpoint);
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Per-attempt request timeout (default 15s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn pending(mut self, store: impl PendingStore + 'static) -> Self {
+        self.pending = Arc::new(store);
+        self
+    }
+
+    pub fn dead_letters(mut self, store: impl DeadLetterStore + 'static) -> Self {
+        self.dead_letters = Arc::new(store);
+        self
+    }
+
+    /// Wait out retry delays on `clock` instead of the system clock
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Register or replace an endpoint at runtime
+    pub fn add_endpoint(&self, name: impl Into<String>, endpoint: WebhookEndpoint) {
+        self.endpoints.write().unwrap_or_else(|e| e.into_inner()).insert(name.into(), endpoint);
+    }
+
+    pub fn remove_endpoint(&self, name: &str) -> Option<WebhookEndpoint> {
+        self.endpoints.write().unwrap_or_else(|e| e.into_inner()).remove(name)
+    }
+
+    fn get_endpoint(&self, name: &str) -> Result<WebhookEndpoint> {
+        self.endpoints
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(name)
+            .cloned()
+            .ok_or_else(|| RfError::NotFound(format!("Webhook endpoint not found: {}", name)))
+    }
+
+    /// Deliver in the background, returning the queued message
+    ///
+    /// The message is saved in the pending store first; an error saving it
+    /// is returned and nothing is sent.
+    pub async fn enqueue(&self, endpoint: &str, event: impl Into<String>, payload: impl Serialize) -> Result<WebhookMessage> {
+        self.get_endpoint(endpoint)?;
+        let message = WebhookMessage::new(event, payload)?;
+        self.spawn(endpoint, message.clone()).await?;
+        Ok(message)
+    }
+
+    /// Save a message as pending, then deliver it in the background
+    async fn spawn(&self, endpoint: &str, message: WebhookMessage) -> Result<()> {
+        self.pending
+            .save(PendingDelivery {
+                endpoint: endpoint.to_string(),
+                message: message.clone(),
+                queued_at: chrono::Utc::now().timestamp(),
+            })
+            .await?;
+        let sender = self.clone();
+        let name = endpoint.to_string();
+        tokio::spawn(async move {
+            let _ = sender.deliver_message(&name, message).await;
+        });
+        Ok(())
+    }
+
+    /// Restart delivery of every pending message, returning how many
+    ///
+    /// Call once at startup, before enqueueing: messages still being
+    /// delivered by this sender would be sent twice.
+    pub async fn resume(&self) -> Result<usize> {
+        let deliveries = self.pending.list().await?;
+        let count = deliveries.len();
+        for delivery in deliveries {
+            tracing::info!("Resuming webhook {} to {}", delivery.message.id, delivery.endpoint);
+            let sender = self.clone();
+            tokio::spawn(async move {
+                let _ = sender.deliver_message(&delivery.endpoint, delivery.message).await;
+            });
+        }
+        Ok(count)
+    }
+
+    /// Messages queued and not yet delivered or dead-lettered
+    pub async fn pending_deliveries(&self) -> Result<Vec<PendingDelivery>> {
+        self.pending.list().await
+    }
+
+    /// Deliver and wait for the outcome, retrying per the policy
+    ///
+    /// The message is not saved as pending: the caller sees the outcome.
+    /// Returns the error of the last attempt once the message is dead-lettered.
+    pub async fn deliver(&self, endpoint: &str, event: impl Into<String>, payload: impl Serialize) -> Result<WebhookMessage> {
+        self.get_endpoint(endpoint)?;
+        let message = WebhookMessage::new(event, payload)?;
+        self.deliver_message(endpoint, message.clone()).await?;
+        Ok(message)
+    }
+
+    /// Deliver or dead-letter a message, then drop it from the pending store
+    ///
+    /// A message whose dead letter cannot be stored stays pending.
+    async fn deliver_message(&self, name: &str, message: WebhookMessage) -> Result<()> {
+        let body = serde_json::to_vec(&message)
+            .map_err(|e| RfError::Serialization(format!("Failed to serialize webhook: {}", e)))?;
+        let mut attempts = 0;
+        let error = loop {
+            attempts += 1;
+            // The endpoint is looked up per attempt so rotated secrets apply to retries
+            let result = match self.get_endpoint(name) {
+                Ok(endpoint) => self.attempt(&endpoint, &message.id, &body).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) => {
+                    self.forget(&message.id).await;
+                    return Ok(());
+                }
+                Err(e) if attempts >= self.retry.max_attempts.max(1) => break e,
+                Err(e) => {
+                    let delay = self.retry.delay(attempts);
+                    tracing::warn!("Webhook {} to {} failed (attempt {}), retrying in {:?}: {}", message.id, name, attempts, delay, e);
+                    self.clock.sleep(delay).await;
+                }
+            }
+        };
+        tracing::error!("Webhook {} to {} dead-lettered after {} attempts: {}", message.id, name, attempts, error);
+        let id = message.id.clone();
+        let letter = DeadLetter {
+            endpoint: name.to_string(),
+            message,
+            attempts,
+            last_error: error.to_string(),
+            failed_at: chrono::Utc::now().timestamp(),
+        };
+        match self.dead_letters.push(letter).await {
+            Ok(()) => self.forget(&id).await,
+            Err(e) => tracing::error!("Failed to store dead letter, keeping webhook {} pending: {}", id, e),
+        }
+        Err(error)
+    }
+
+    async fn forget(&self, id: &str) {
+        if let Err(e) = self.pending.remove(id).await {
+            tracing::error!("Failed to remove pending webhook {}: {}", id, e);
+        }
+    }
+
+    async fn attempt(&self, endpoint: &WebhookEndpoint, id: &str, body: &[u8]) -> Result<()> {
+        let timestamp = chrono::Utc::now().timestamp();
+        let mut request = self
+            .client
+            .post(&endpoint.url)
+            .timeout(self.timeout)
+            .header("content-type", "application/json")
+            .header(WEBHOOK_ID, id)
+            .header(WEBHOOK_TIMESTAMP, timestamp.to_string())
+            .header(WEBHOOK_SIGNATURE, endpoint.signature(id, timestamp, body));
+        for (name, value) in &endpoint.headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .body(body.to_vec())
+            .send()
+            .await
+            .map_err(|e| RfError::Network(format!("Webhook request failed: {}", e)))?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(RfError::Network(format!("Webhook endpoint returned {}", response.status())))
+        }
+    }
+
+    /// Messages that exhausted their attempts
+    pub async fn dead_lettered(&self) -> Result<Vec<DeadLetter>> {
+        self.dead_letters.list().await
+    }
+
+    /// Queue a dead letter for delivery again, with a fresh set of attempts
+    pub async fn redeliver(&self, id: &str) -> Result<()> {
+        let letter = self
+            .dead_letters
+            .take(id)
+            .await?
+            .ok_or_else(|| RfError::NotFound(format!("Dead letter not found: {}", id)))?;
+        self.spawn(&letter.endpoint, letter.message).await
+    }
+}
+
+impl Default for WebhookSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Receiver-side signature check
+pub struct WebhookVerifier {
+    secrets: Vec<Vec<u8>>,
+    tolerance: Duration,
+    max_body_size: usize,
+}
+
+impl WebhookVerifier {
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secrets: vec![secret.as_ref().to_vec()],
+            tolerance: Duration::from_secs(5 * 60),
+            max_body_size: 1024 * 1024,
+        }
+    }
+
+    /// Also accept another secret, e.g. during rotation
+    pub fn secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.secrets.push(secret.as_ref().to_vec());
+        self
+    }
+
+    /// Allowed clock difference to the sender, against replays (default 5 minutes)
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Largest body the middleware buffers (default 1 MiB)
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+
+    /// Check the webhook headers against the raw body
+    pub fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<()> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| RfError::Unauthorized(format!("Missing {} header", name)))
+        };
+        let id = header(WEBHOOK_ID)?;
+        let timestamp: i64 = header(WEBHOOK_TIMESTAMP)?
+            .parse()
+            .map_err(|_| RfError::Unauthorized("Invalid webhook timestamp".to_string()))?;
+        let signatures = header(WEBHOOK_SIGNATURE)?;
+
+        let age = chrono::Utc::now().timestamp().abs_diff(timestamp);
+        if age > self.tolerance.as_secs() {
+            return Err(RfError::Unauthorized("Webhook timestamp outside tolerance".to_string()));
+        }
+
+        let mut content = format!("{}.{}.", id, timestamp).into_bytes();
+        content.extend_from_slice(body);
+        let valid = signatures
+            .split(' ')
+            .filter_map(|s| s.strip_prefix("v1,"))
+            .filter_map(|s| rf_encoding::base64_decode(s).ok())
+            .any(|tag| {
+                self.secrets
+                    .iter()
+                    .any(|secret| rf_crypto::sha256::verify_hmac(secret, &content, &tag))
+            });
+        if valid {
+            Ok(())
+        } else {
+            Err(RfError::Unauthorized("Invalid webhook signature".to_string()))
+        }
+    }
+}
+
+/// Reject requests without a valid webhook signature
+///
+/// Use with `axum::middleware::from_fn_with_state(Arc::new(verifier), webhook_verify_middleware)`.
+pub async fn webhook_verify_middleware(
+    State(verifier): State<Arc<WebhookVerifier>>,
+    request: Request,
+    next: Next,
+) -> AxumResponse {
+    let (parts, body) = request.into_parts();
+    let body = match BufferPool::global().read_body(body, verifier.max_body_size).await {
+        Ok(body) => body,
+        Err(_) => return (axum::http::StatusCode::PAYLOAD_TOO_LARGE, "Payload too large").into_response(),
+    };
+    if let Err(e) = verifier.verify(&parts.headers, &body) {
+        tracing::warn!("Rejected webhook: {}", e);
+        return Response::fail(codes::UNAUTHORIZED, e.to_string()).into_response();
+    }
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
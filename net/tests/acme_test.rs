@@ -0,0 +1,464 @@
+//! ACME tests against an in-process mock ACME server
+#![cfg(feature = "acme")]
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::ecdsa::EcdsaSig;
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::Rsa;
+use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+use openssl::x509::extension::SubjectAlternativeName;
+use openssl::x509::{X509NameBuilder, X509Req, X509};
+use rf_net::http::{
+    AcmeChallengeType, AcmeConfig, AcmeManager, CertStorage, FileCertStorage, TlsInterceptor, TlsListener,
+};
+use rf_net::TlsClientConfig;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+const DOMAIN: &str = "app.example.test";
+
+fn b64d(data: &str) -> Vec<u8> {
+    URL_SAFE_NO_PAD.decode(data).unwrap()
+}
+
+fn ca() -> (X509, PKey<Private>) {
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "Mock ACME CA").unwrap();
+    let name = name.build();
+    let mut cert = X509::builder().unwrap();
+    cert.set_version(2).unwrap();
+    cert.set_subject_name(&name).unwrap();
+    cert.set_issuer_name(&name).unwrap();
+    cert.set_pubkey(&key).unwrap();
+    cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+    cert.set_not_after(&Asn1Time::days_from_now(365).unwrap()).unwrap();
+    cert.sign(&key, MessageDigest::sha256()).unwrap();
+    (cert.build(), key)
+}
+
+struct MockOrder {
+    domains: Vec<String>,
+    authz: Vec<usize>,
+    status: String,
+    certificate: Option<String>,
+}
+
+#[derive(Default)]
+struct MockState {
+    base: String,
+    nonce: u64,
+    bad_nonce_sent: bool,
+    accounts: HashMap<String, Value>,
+    orders: HashMap<usize, MockOrder>,
+    /// authz id -> (domain, token, status)
+    authz: HashMap<usize, (String, String, String)>,
+    validated_with: Vec<String>,
+}
+
+struct MockAcme {
+    state: Mutex<MockState>,
+    /// Where the client answers challenges
+    http01_addr: Mutex<Option<SocketAddr>>,
+    tls_alpn01_addr: Mutex<Option<SocketAddr>>,
+    ca: (X509, PKey<Private>),
+}
+
+impl MockAcme {
+    fn next_nonce(&self) -> String {
+        let mut state = self.state.lock().unwrap();
+        state.nonce += 1;
+        format!("nonce-{}", state.nonce)
+    }
+
+    fn reply(&self, status: StatusCode, location: Option<String>, body: Value) -> Response {
+        let mut headers = HeaderMap::new();
+        headers.insert("replay-nonce", self.next_nonce().parse().unwrap());
+        if let Some(location) = location {
+            headers.insert("location", location.parse().unwrap());
+        }
+        (status, headers, axum::Json(body)).into_response()
+    }
+
+    /// Verify the JWS and return (payload, account jwk)
+    fn verify(&self, url: &str, body: &str) -> Result<(Option<Value>, Value), Box<Response>> {
+        let jws: Value = serde_json::from_str(body).unwrap();
+        let protected: Value = serde_json::from_slice(&b64d(jws["protected"].as_str().unwrap())).unwrap();
+        assert_eq!(protected["alg"], "ES256");
+        assert_eq!(protected["url"], url);
+
+        let mut state = self.state.lock().unwrap();
+        if !state.bad_nonce_sent {
+            state.bad_nonce_sent = true;
+            drop(state);
+            return Err(Box::new(self.reply(
+                StatusCode::BAD_REQUEST,
+                None,
+                json!({ "type": "urn:ietf:params:acme:error:badNonce", "detail": "stale" }),
+            )));
+        }
+        let nonce: u64 = protected["nonce"].as_str().unwrap().trim_start_matches("nonce-").parse().unwrap();
+        assert!(nonce <= state.nonce);
+
+        let jwk = match protected.get("kid") {
+            Some(kid) => state.accounts[kid.as_str().unwrap()].clone(),
+            None => protected["jwk"].clone(),
+        };
+        drop(state);
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let x = BigNum::from_slice(&b64d(jwk["x"].as_str().unwrap())).unwrap();
+        let y = BigNum::from_slice(&b64d(jwk["y"].as_str().unwrap())).unwrap();
+        let key = EcKey::from_public_key_affine_coordinates(&group, &x, &y).unwrap();
+        let signature = b64d(jws["signature"].as_str().unwrap());
+        assert_eq!(signature.len(), 64);
+        let signature = EcdsaSig::from_private_components(
+            BigNum::from_slice(&signature[..32]).unwrap(),
+            BigNum::from_slice(&signature[32..]).unwrap(),
+        )
+        .unwrap();
+        let signing_input = format!("{}.{}", jws["protected"].as_str().unwrap(), jws["payload"].as_str().unwrap());
+        assert!(signature.verify(&openssl::sha::sha256(signing_input.as_bytes()), &key).unwrap());
+
+        let payload = jws["payload"].as_str().unwrap();
+        let payload = (!payload.is_empty()).then(|| serde_json::from_slice(&b64d(payload)).unwrap());
+        Ok((payload, jwk))
+    }
+
+    fn order_json(&self, id: usize) -> Value {
+        let state = self.state.lock().unwrap();
+        let order_state = &state.orders[&id];
+        let mut order = json!({
+            "status": order_state.status,
+            "identifiers": order_state.domains.iter().map(|d| json!({"type": "dns", "value": d})).collect::<Vec<_>>(),
+            "authorizations": order_state.authz.iter().map(|a| format!("{}/authz/{}", state.base, a)).collect::<Vec<_>>(),
+            "finalize": format!("{}/finalize/{}", state.base, id),
+        });
+        if order_state.certificate.is_some() {
+            order["certificate"] = json!(format!("{}/cert/{}", state.base, id));
+        }
+        order
+    }
+
+    fn authz_json(&self, id: usize) -> Value {
+        let state = self.state.lock().unwrap();
+        let (domain, token, status) = &state.authz[&id];
+        json!({
+            "status": status,
+            "identifier": {"type": "dns", "value": domain},
+            "challenges": [
+                {"type": "http-01", "url": format!("{}/chall/{}/http-01", state.base, id), "token": token, "status": "pending"},
+                {"type": "tls-alpn-01", "url": format!("{}/chall/{}/tls-alpn-01", state.base, id), "token": token, "status": "pending"},
+            ]
+        })
+    }
+
+    async fn validate(&self, kind: &str, domain: &str, expected: &str) -> bool {
+        match kind {
+            "http-01" => {
+                let token = expected.split('.').next().unwrap();
+                let addr = self.http01_addr.lock().unwrap().unwrap();
+                let url = format!("http://{}/.well-known/acme-challenge/{}", addr, token);
+                let body = reqwest::get(url).await.unwrap().text().await.unwrap();
+                body == expected
+            }
+            "tls-alpn-01" => {
+                let addr = self.tls_alpn01_addr.lock().unwrap().unwrap();
+                let domain = domain.to_string();
+                let digest = openssl::sha::sha256(expected.as_bytes());
+                tokio::task::spawn_blocking(move || {
+                    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+                    connector.set_verify(SslVerifyMode::NONE);
+                    connector.set_alpn_protos(b"\x0aacme-tls/1").unwrap();
+                    let stream = std::net::TcpStream::connect(addr).unwrap();
+                    let tls = connector.build().connect(&domain, stream).unwrap();
+                    assert_eq!(tls.ssl().selected_alpn_protocol(), Some(&b"acme-tls/1"[..]));
+                    let der = tls.ssl().peer_certificate().unwrap().to_der().unwrap();
+                    // critical id-pe-acmeIdentifier holding OCTET STRING(sha256(key authorization))
+                    let mut extension = vec![0x06, 0x08, 0x2B, 0x06, 0x01, 0x05, 0x05, 0x07, 0x01, 0x1F];
+                    extension.extend_from_slice(&[0x01, 0x01, 0xFF, 0x04, 0x22, 0x04, 0x20]);
+                    extension.extend_from_slice(&digest);
+                    der.windows(extension.len()).any(|w| w == extension.as_slice())
+                })
+                .await
+                .unwrap()
+            }
+            _ => false,
+        }
+    }
+
+    fn issue(&self, csr_der: &[u8]) -> String {
+        let csr = X509Req::from_der(csr_der).unwrap();
+        let public_key = csr.public_key().unwrap();
+        assert!(csr.verify(&public_key).unwrap());
+        let (ca_cert, ca_key) = &self.ca;
+
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", DOMAIN).unwrap();
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_subject_name(&name.build()).unwrap();
+        cert.set_issuer_name(ca_cert.subject_name()).unwrap();
+        cert.set_pubkey(&public_key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(90).unwrap()).unwrap();
+        let san = SubjectAlternativeName::new()
+            .dns(DOMAIN)
+            .build(&cert.x509v3_context(Some(ca_cert), None))
+            .unwrap();
+        cert.append_extension(san).unwrap();
+        cert.sign(ca_key, MessageDigest::sha256()).unwrap();
+
+        let mut chain = String::from_utf8(cert.build().to_pem().unwrap()).unwrap();
+        chain.push_str(&String::from_utf8(ca_cert.to_pem().unwrap()).unwrap());
+        chain
+    }
+}
+
+async fn directory(State(mock): State<Arc<MockAcme>>) -> Response {
+    let base = mock.state.lock().unwrap().base.clone();
+    axum::Json(json!({
+        "newNonce": format!("{}/nonce", base),
+        "newAccount": format!("{}/account", base),
+        "newOrder": format!("{}/order", base),
+    }))
+    .into_response()
+}
+
+async fn nonce(State(mock): State<Arc<MockAcme>>) -> Response {
+    mock.reply(StatusCode::OK, None, Value::Null)
+}
+
+async fn post(State(mock): State<Arc<MockAcme>>, uri: Uri, body: String) -> Response {
+    let base = mock.state.lock().unwrap().base.clone();
+    let url = format!("{}{}", base, uri.path());
+    let (payload, jwk) = match mock.verify(&url, &body) {
+        Ok(verified) => verified,
+        Err(response) => return *response,
+    };
+    let segments: Vec<&str> = uri.path().trim_start_matches('/').split('/').collect();
+
+    match segments.as_slice() {
+        ["account"] => {
+            assert_eq!(payload.unwrap()["termsOfServiceAgreed"], true);
+            let kid = format!("{}/account/1", base);
+            mock.state.lock().unwrap().accounts.insert(kid.clone(), jwk);
+            mock.reply(StatusCode::CREATED, Some(kid), json!({ "status": "valid" }))
+        }
+        ["order"] => {
+            let domains: Vec<String> = payload.unwrap()["identifiers"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|i| i["value"].as_str().unwrap().to_string())
+                .collect();
+            let id = {
+                let mut state = mock.state.lock().unwrap();
+                let id = state.orders.len() + 1;
+                let mut authz_ids = Vec::new();
+                for domain in &domains {
+                    let authz_id = state.authz.len() + 1;
+                    state
+                        .authz
+                        .insert(authz_id, (domain.clone(), format!("token-{}", authz_id), "pending".into()));
+                    authz_ids.push(authz_id);
+                }
+                state.orders.insert(
+                    id,
+                    MockOrder {
+                        domains,
+                        authz: authz_ids,
+                        status: "pending".into(),
+                        certificate: None,
+                    },
+                );
+                id
+            };
+            mock.reply(StatusCode::CREATED, Some(format!("{}/order/{}", base, id)), mock.order_json(id))
+        }
+        ["order", id] => mock.reply(StatusCode::OK, None, mock.order_json(id.parse().unwrap())),
+        ["authz", id] => mock.reply(StatusCode::OK, None, mock.authz_json(id.parse().unwrap())),
+        ["chall", id, kind] => {
+            let id: usize = id.parse().unwrap();
+            let (domain, token, _) = mock.state.lock().unwrap().authz[&id].clone();
+            let thumbprint = {
+                let jwk = format!(
+                    r#"{{"crv":"P-256","kty":"EC","x":"{}","y":"{}"}}"#,
+                    jwk["x"].as_str().unwrap(),
+                    jwk["y"].as_str().unwrap()
+                );
+                URL_SAFE_NO_PAD.encode(openssl::sha::sha256(jwk.as_bytes()))
+            };
+            let valid = mock.validate(kind, &domain, &format!("{}.{}", token, thumbprint)).await;
+            let mut state = mock.state.lock().unwrap();
+            state.authz.get_mut(&id).unwrap().2 = if valid { "valid" } else { "invalid" }.into();
+            state.validated_with.push(kind.to_string());
+            drop(state);
+            mock.reply(StatusCode::OK, None, json!({ "type": kind, "status": "processing" }))
+        }
+        ["finalize", id] => {
+            let id: usize = id.parse().unwrap();
+            let csr = b64d(payload.unwrap()["csr"].as_str().unwrap());
+            let chain = mock.issue(&csr);
+            {
+                let mut state = mock.state.lock().unwrap();
+                let authz_ids = state.orders[&id].authz.clone();
+                assert!(authz_ids.iter().all(|a| state.authz[a].2 == "valid"));
+                let order = state.orders.get_mut(&id).unwrap();
+                order.status = "valid".into();
+                order.certificate = Some(chain);
+            }
+            mock.reply(StatusCode::OK, None, mock.order_json(id))
+        }
+        ["cert", id] => {
+            let chain = mock.state.lock().unwrap().orders[&id.parse::<usize>().unwrap()]
+                .certificate
+                .clone()
+                .unwrap();
+            let mut headers = HeaderMap::new();
+            headers.insert("replay-nonce", mock.next_nonce().parse().unwrap());
+            (StatusCode::OK, headers, chain).into_response()
+        }
+        _ => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn start_mock() -> Arc<MockAcme> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mock = Arc::new(MockAcme {
+        state: Mutex::new(MockState {
+            base: format!("http://{}", listener.local_addr().unwrap()),
+            ..Default::default()
+        }),
+        http01_addr: Mutex::new(None),
+        tls_alpn01_addr: Mutex::new(None),
+        ca: ca(),
+    });
+    let app = Router::new()
+        .route("/directory", get(directory))
+        .route("/nonce", get(nonce))
+        .fallback(axum::routing::post(post))
+        .with_state(mock.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    mock
+}
+
+fn config(mock: &MockAcme) -> AcmeConfig {
+    AcmeConfig::new([DOMAIN])
+        .directory(format!("{}/directory", mock.state.lock().unwrap().base))
+        .contact("admin@example.test")
+        .agree_to_terms(true)
+        .poll_interval(Duration::from_millis(20))
+        .timeout(Duration::from_secs(10))
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("rf-acme-{}-{}", name, std::process::id()))
+}
+
+#[tokio::test]
+async fn test_http01_issuance_and_storage() {
+    let mock = start_mock().await;
+    let dir = temp_dir("http01");
+    let storage: Arc<dyn CertStorage> = Arc::new(FileCertStorage::new(&dir));
+    let manager = AcmeManager::new(config(&mock), storage.clone());
+
+    // Terms must be agreed explicitly
+    let unagreed = AcmeManager::new(config(&mock).agree_to_terms(false), storage.clone());
+    assert!(unagreed.issue().await.is_err());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    *mock.http01_addr.lock().unwrap() = Some(listener.local_addr().unwrap());
+    let router = manager.challenges().router();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    assert!(manager.load_certificate().await.unwrap().is_none());
+    let bundle = manager.issue().await.unwrap();
+    let leaf = X509::from_pem(&bundle.cert_pem).unwrap();
+    assert_eq!(leaf.issuer_name().entries().next().unwrap().data().as_slice(), b"Mock ACME CA");
+    let expires_in = bundle.expires_in().unwrap();
+    assert!(expires_in > Duration::from_secs(89 * 86400) && expires_in <= Duration::from_secs(90 * 86400));
+    assert_eq!(mock.state.lock().unwrap().validated_with, ["http-01"]);
+    // Challenge responses are removed after validation
+    assert!(manager.challenges().http01("token-1").is_none());
+
+    let stored = AcmeManager::new(config(&mock), storage.clone()).load_certificate().await.unwrap().unwrap();
+    assert_eq!(stored.cert_pem, bundle.cert_pem);
+    assert!(dir.join(format!("{}.key", DOMAIN)).exists());
+    assert!(std::fs::read_dir(&dir).unwrap().any(|e| e.unwrap().file_name().to_string_lossy().starts_with("account-")));
+
+    // The account key is reused for later orders
+    manager.issue().await.unwrap();
+    assert_eq!(mock.state.lock().unwrap().accounts.len(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Issuer CN of the certificate served on `addr`
+async fn served_issuer(addr: SocketAddr) -> String {
+    let client = TlsClientConfig::new().danger_accept_invalid_certs(true).server_name(DOMAIN);
+    let tls = client.connect(&addr.to_string()).await.unwrap();
+    let cert = X509::from_der(&tls.peer_certificate().unwrap()).unwrap();
+    let issuer = cert.issuer_name().entries().next().unwrap().data().as_utf8().unwrap().to_string();
+    issuer
+}
+
+#[tokio::test]
+async fn test_tls_alpn01_and_hot_swap() {
+    let mock = start_mock().await;
+    let dir = temp_dir("alpn");
+    let manager = Arc::new(AcmeManager::new(
+        config(&mock).challenge(AcmeChallengeType::TlsAlpn01),
+        Arc::new(FileCertStorage::new(&dir)),
+    ));
+
+    let handle = manager.start().await.unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    *mock.tls_alpn01_addr.lock().unwrap() = Some(addr);
+    let interceptor: Arc<dyn TlsInterceptor> = manager.challenges();
+    let listener =
+        TlsListener::with_interceptor(listener, handle.acceptor(), Duration::from_secs(5), interceptor).unwrap();
+    let app = Router::new().route("/", get(|| async { "ok" }));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    // Temporary self-signed certificate until issuance completes, then the issued one
+    let mut issuer = String::new();
+    for _ in 0..100 {
+        issuer = served_issuer(addr).await;
+        if issuer == "Mock ACME CA" {
+            break;
+        }
+        assert_eq!(issuer, DOMAIN);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(issuer, "Mock ACME CA");
+    assert_eq!(mock.state.lock().unwrap().validated_with, ["tls-alpn-01"]);
+
+    // A restarted manager picks up the stored certificate immediately
+    drop(handle);
+    let restarted = Arc::new(AcmeManager::new(config(&mock), Arc::new(FileCertStorage::new(&dir))));
+    let handle = restarted.start().await.unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let listener = TlsListener::new(listener, handle.acceptor(), Duration::from_secs(5)).unwrap();
+    tokio::spawn(async move { axum::serve(listener, Router::new()).await.unwrap() });
+    assert_eq!(served_issuer(addr).await, "Mock ACME CA");
+    assert_eq!(mock.state.lock().unwrap().orders.len(), 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
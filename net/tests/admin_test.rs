@@ -0,0 +1,158 @@
+//! Admin dashboard plugin tests
+
+use axum::body::Body;
+use axum::http::{Request as HttpRequest, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use rf_errors::RfError;
+use rf_net::http::{AdminPlugin, ApiError, HttpServer, Plugin, RouteInfo, ServerInfo};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn app() -> Router {
+    let admin = AdminPlugin::new("secret").section("cache", || json!({ "entry_count": 3 }));
+    admin.attach(&ServerInfo {
+        addr: "127.0.0.1:8080".parse().unwrap(),
+        tls: false,
+        routes: vec![RouteInfo {
+            method: "GET".to_string(),
+            path: "/fail".to_string(),
+        }],
+        middleware: vec!["logging".to_string(), "limits".to_string()],
+        plugins: vec!["admin".to_string()],
+    });
+    let router = Router::new().route(
+        "/fail",
+        get(|| async { Err::<(), ApiError>(RfError::Database("disk full".into()).into()) }),
+    );
+    admin.apply(router)
+}
+
+async fn send(app: &Router, uri: &str, token: Option<&str>) -> (StatusCode, Value) {
+    let mut request = HttpRequest::get(uri);
+    if let Some(token) = token {
+        request = request.header("authorization", format!("Bearer {}", token));
+    }
+    let response = app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_admin_requires_token() {
+    let app = app();
+    let (status, body) = send(&app, "/admin/api", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], 401);
+    let (status, _) = send(&app, "/admin/api", Some("wrong")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send(&app, "/admin", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Query token, as used when opening the page in a browser
+    let (status, _) = send(&app, "/admin?token=secret", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // An empty token is rejected when the plugin is registered
+    assert!(HttpServer::new("127.0.0.1:0".parse().unwrap()).with_plugin(AdminPlugin::new("")).is_err());
+}
+
+#[tokio::test]
+async fn test_admin_snapshot() {
+    let app = app();
+    let (status, _) = send(&app, "/fail", None).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let (status, body) = send(&app, "/admin/api", Some("secret")).await;
+    assert_eq!(status, StatusCode::OK);
+    let data = &body["data"];
+    assert_eq!(data["server"]["addr"], "127.0.0.1:8080");
+    assert_eq!(data["routes"], json!([{ "method": "GET", "path": "/fail" }]));
+    assert_eq!(data["middleware"], json!(["logging", "limits"]));
+    assert_eq!(data["plugins"], json!(["admin"]));
+    assert_eq!(data["cache"], json!({ "entry_count": 3 }));
+
+    // The failed request is recorded with its error, admin requests are not
+    let errors = data["errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["path"], "/fail");
+    assert_eq!(errors[0]["status"], 500);
+    assert_eq!(errors[0]["message"], "Database error: disk full");
+
+    let (_, body) = send(&app, "/admin/api/cache", Some("secret")).await;
+    assert_eq!(body["data"], json!({ "entry_count": 3 }));
+    let (status, _) = send(&app, "/admin/api/nope", Some("secret")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_admin_version() {
+    let info = rf_os::build_info!();
+    let app = app();
+    let (status, _) = send(&app, "/debug/version", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = send(&app, "/debug/version", Some("secret")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["name"], "rf-os");
+    assert_eq!(body["framework"]["name"], "rf-os");
+
+    let (_, body) = send(&app, "/admin/api/build", Some("secret")).await;
+    assert_eq!(body["data"]["name"], "rf-os");
+
+    let admin = AdminPlugin::new("secret").build_info(info).version_path("/version").public_version(true);
+    let app = admin.apply(Router::new());
+    let (status, body) = send(&app, "/version", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["name"], "rf-net");
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert!(body["features"].is_array());
+}
+
+#[tokio::test]
+async fn test_admin_cron_jobs() {
+    let cron = Arc::new(rf_os::cron::Cron::new().await.unwrap());
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = runs.clone();
+    let options = rf_os::cron::JobOptions::new().name("cleanup");
+    cron.add_with("@daily", options, move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+    })
+    .await
+    .unwrap();
+    let app = AdminPlugin::new("secret").cron(cron.clone()).apply(Router::new());
+    let call = |method: &str, uri: &str, token: &str| {
+        HttpRequest::builder()
+            .method(method)
+            .uri(uri)
+            .header("x-admin-token", token)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(call("POST", "/admin/api/cron/cleanup/trigger", "wrong")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = app.clone().oneshot(call("POST", "/admin/api/cron/cleanup/trigger", "secret")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+    let response = app.clone().oneshot(call("POST", "/admin/api/cron/cleanup/pause", "secret")).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["data"]["info"]["paused"], true);
+
+    let (_, body) = send(&app, "/admin/api/cron", Some("secret")).await;
+    assert_eq!(body["data"][0]["name"], "cleanup");
+    assert_eq!(body["data"][0]["runs"], 1);
+
+    let response = app.clone().oneshot(call("POST", "/admin/api/cron/nope/trigger", "secret")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = app.clone().oneshot(call("POST", "/admin/api/cron/cleanup/explode", "secret")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = app.clone().oneshot(call("DELETE", "/admin/api/cron/cleanup", "secret")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(cron.jobs().await.is_empty());
+}
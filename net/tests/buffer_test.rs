@@ -0,0 +1,81 @@
+//! Buffer pool tests
+
+use axum::body::Body;
+use bytes::Bytes;
+use rf_net::{register_buffer_pool_metrics, BufferPool, BufferPoolStats};
+use rf_os::metric::MetricRegistry;
+
+fn chunked(chunks: &[&'static [u8]]) -> Body {
+    let chunks: Vec<Result<Bytes, std::io::Error>> = chunks.iter().map(|chunk| Ok(Bytes::from_static(chunk))).collect();
+    Body::from_stream(futures_util::stream::iter(chunks))
+}
+
+#[test]
+fn test_buffers_are_reused_within_bounds() {
+    let pool = BufferPool::new().buffer_size(64).max_buffers(1).max_capacity(1024);
+    let mut first = pool.get();
+    assert!(first.capacity() >= 64);
+    first.extend_from_slice(b"hello");
+    let second = pool.get();
+    drop(first);
+    // The pool is full, the second buffer is freed
+    drop(second);
+    assert_eq!(pool.stats(), BufferPoolStats { acquired: 2, reused: 0, discarded: 1, idle: 1 });
+
+    let reused = pool.get();
+    assert!(reused.is_empty());
+    assert_eq!(pool.stats().reused, 1);
+
+    // Split bytes keep their memory, the buffer goes back
+    let mut buffer = reused;
+    buffer.extend_from_slice(b"payload");
+    let bytes = buffer.into_bytes();
+    assert_eq!(&bytes[..], b"payload");
+    assert_eq!(pool.stats().idle, 1);
+    drop(bytes);
+    assert!(pool.get().capacity() >= 64);
+
+    // Buffers grown past the limit are not kept
+    let mut large = pool.get();
+    large.extend_from_slice(&[0; 4096]);
+    assert_eq!(large.into_bytes().len(), 4096);
+    assert_eq!(pool.stats().idle, 0);
+    assert_eq!(pool.stats().discarded, 2);
+    assert_eq!(pool.stats().hit_ratio(), 3.0 / 5.0);
+}
+
+#[tokio::test]
+async fn test_read_body() {
+    let pool = BufferPool::new();
+    let single = pool.read_body(Body::from("whole"), 1024).await.unwrap();
+    assert_eq!(&single[..], b"whole");
+    // A body in one chunk needs no buffer
+    assert_eq!(pool.stats().acquired, 0);
+
+    let gathered = pool.read_body(chunked(&[b"one ", b"two ", b"three"]), 1024).await.unwrap();
+    assert_eq!(&gathered[..], b"one two three");
+    assert_eq!(pool.stats().acquired, 1);
+    drop(gathered);
+
+    let again = pool.read_body(chunked(&[b"a", b"b"]), 1024).await.unwrap();
+    assert_eq!(&again[..], b"ab");
+    assert_eq!(pool.stats().reused, 1);
+
+    assert!(pool.read_body(chunked(&[b"1234", b"5678"]), 6).await.is_err());
+    assert!(pool.read_body(Body::from("too long"), 4).await.is_err());
+    assert!(pool.read_body(Body::empty(), 4).await.unwrap().is_empty());
+}
+
+#[test]
+fn test_pool_metrics() {
+    let registry = MetricRegistry::new();
+    let pool = BufferPool::new();
+    register_buffer_pool_metrics(&registry, "frames", &pool);
+    drop(pool.get());
+    drop(pool.get());
+
+    let text = registry.encode();
+    assert!(text.contains("buffer_pool_acquired_total{pool=\"frames\"} 2\n"), "{}", text);
+    assert!(text.contains("buffer_pool_reused_total{pool=\"frames\"} 1\n"), "{}", text);
+    assert!(text.contains("buffer_pool_idle_buffers{pool=\"frames\"} 1\n"), "{}", text);
+}
@@ -0,0 +1,167 @@
+//! CORS middleware and route group tests
+
+use axum::body::Body;
+use axum::http::{Method, Request as HttpRequest, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use rf_net::http::{cors_middleware, CorsMiddleware, HttpServer};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+async fn start(server: HttpServer, addr: SocketAddr) -> tokio::task::JoinHandle<()> {
+    let task = tokio::spawn(async move {
+        let _ = server.serve().await;
+    });
+    for _ in 0..100 {
+        if tokio::net::TcpStream::connect(addr).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    task
+}
+
+fn header<'a>(response: &'a reqwest::Response, name: &str) -> Option<&'a str> {
+    response.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+#[tokio::test]
+async fn test_cors_policy() {
+    let cors = Arc::new(
+        CorsMiddleware::new()
+            .allow_origin_regex(r"^https://[a-z]+\.example\.com$")
+            .unwrap()
+            .allow_methods("GET, POST")
+            .allow_headers("Content-Type, X-Token")
+            .expose_headers("X-Total")
+            .allow_credentials(true)
+            .max_age(Duration::from_secs(600)),
+    );
+    let app = Router::new()
+        .route("/items", get(|| async { "items" }))
+        .layer(axum::middleware::from_fn_with_state(cors, cors_middleware));
+
+    // Preflight from an allowed origin
+    let request = HttpRequest::builder()
+        .method(Method::OPTIONS)
+        .uri("/items")
+        .header("origin", "https://app.example.com")
+        .header("access-control-request-method", "POST")
+        .header("access-control-request-headers", "x-token")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let headers = response.headers();
+    assert_eq!(headers["access-control-allow-origin"], "https://app.example.com");
+    assert_eq!(headers["access-control-allow-methods"], "GET, POST");
+    assert_eq!(headers["access-control-allow-credentials"], "true");
+    assert_eq!(headers["access-control-max-age"], "600");
+
+    // Disallowed method, header or origin
+    for (origin, method, request_headers) in [
+        ("https://app.example.com", "DELETE", ""),
+        ("https://app.example.com", "GET", "x-other"),
+        ("https://evil.com", "GET", ""),
+    ] {
+        let request = HttpRequest::builder()
+            .method(Method::OPTIONS)
+            .uri("/items")
+            .header("origin", origin)
+            .header("access-control-request-method", method)
+            .header("access-control-request-headers", request_headers)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(response.headers().get("access-control-allow-origin").is_none());
+    }
+
+    // Actual request
+    let request = HttpRequest::get("/items").header("origin", "https://app.example.com").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.headers()["access-control-allow-origin"], "https://app.example.com");
+    assert_eq!(response.headers()["access-control-expose-headers"], "X-Total");
+    assert_eq!(response.headers()["vary"], "Origin");
+    let request = HttpRequest::get("/items").header("origin", "https://evil.com").body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("access-control-allow-origin").is_none());
+}
+
+#[tokio::test]
+async fn test_cors_group_overrides() {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let server = HttpServer::new(addr)
+        .with_cors_config(CorsMiddleware::new().allow_origin("https://app.example.com"))
+        .unwrap()
+        .route(Method::GET, "/private", || async { "private" })
+        .unwrap()
+        .group("/api", |api| {
+            api.route(Method::GET, "/users/:id", || async { "user" })?
+                .group("/public", |public| {
+                    public
+                        .cors(CorsMiddleware::new())?
+                        .route(Method::GET, "/stats", || async { "stats" })?
+                        .route(Method::POST, "/stats", || async { "posted" })
+                })
+        })
+        .unwrap();
+    let task = start(server, addr).await;
+    let client = reqwest::Client::new();
+    let get = |path: &str, origin: &str| client.get(format!("http://{}{}", addr, path)).header("origin", origin.to_string()).send();
+
+    // Server policy
+    let response = get("/private", "https://app.example.com").await.unwrap();
+    assert_eq!(header(&response, "access-control-allow-origin"), Some("https://app.example.com"));
+    let response = get("/api/users/1", "https://other.com").await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "user");
+    let response = get("/api/users/1", "https://other.com").await.unwrap();
+    assert_eq!(header(&response, "access-control-allow-origin"), None);
+
+    // Group override allows any origin, for every method of the route
+    let response = get("/api/public/stats", "https://other.com").await.unwrap();
+    assert_eq!(header(&response, "access-control-allow-origin"), Some("*"));
+    let response = client
+        .request(reqwest::Method::OPTIONS, format!("http://{}/api/public/stats", addr))
+        .header("origin", "https://other.com")
+        .header("access-control-request-method", "POST")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 204);
+
+    // Conflicts across groups are reported instead of panicking
+    let result = HttpServer::new(addr)
+        .route(Method::GET, "/api/users/:id", || async { "" })
+        .unwrap()
+        .group("/api", |api| api.route(Method::GET, "/users/:user_id", || async { "" }));
+    assert!(result.is_err());
+
+    task.abort();
+}
+
+#[tokio::test]
+async fn test_cors_credentials_require_listed_origins() {
+    let any = || CorsMiddleware::new().allow_credentials(true);
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    assert!(any().validate().is_err());
+    assert!(any().allow_origin("*").validate().is_err());
+    let err = HttpServer::new(addr).with_cors_config(any()).err().unwrap();
+    assert!(err.to_string().contains("credentials"), "{}", err);
+    let err = HttpServer::new(addr).group("/api", |api| api.cors(any())).err().unwrap();
+    assert!(err.to_string().contains("credentials"), "{}", err);
+    assert!(any().allow_origin("https://app.example.com").validate().is_ok());
+    assert!(any().allow_origin_regex(r"^https://.*\.example\.com$").unwrap().validate().is_ok());
+
+    // Used directly on an axum router, any origin still never gets credentials
+    let app = Router::new()
+        .route("/items", get(|| async { "items" }))
+        .layer(axum::middleware::from_fn_with_state(Arc::new(any()), cors_middleware));
+    let request = HttpRequest::get("/items").header("origin", "https://evil.com").body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.headers()["access-control-allow-origin"], "*");
+    assert!(response.headers().get("access-control-allow-credentials").is_none());
+}
@@ -0,0 +1,68 @@
+//! Envelope error detail tests by runtime mode
+
+use axum::body::Body;
+use axum::http::{Request as HttpRequest, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use rf_errors::RfError;
+use rf_net::http::{envelope_middleware, ApiError, EnvelopeConfig};
+use rf_util::mode::{self, Mode};
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn load_orders() -> Result<(), RfError> {
+    Err(RfError::Database("connection refused".into()))
+}
+
+async fn orders() -> Result<(), ApiError> {
+    load_orders()?;
+    Ok(())
+}
+
+async fn call(app: Router) -> (StatusCode, Value) {
+    let response = app.oneshot(HttpRequest::get("/orders").body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn app() -> Router {
+    Router::new()
+        .route("/orders", get(orders))
+        .layer(axum::middleware::from_fn_with_state(Arc::new(EnvelopeConfig::new()), envelope_middleware))
+}
+
+/// Switches the process-wide mode, so everything runs in one test
+#[tokio::test]
+async fn test_verbose_errors_by_mode() {
+    mode::set(Mode::Development);
+    let (status, body) = call(app()).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["message"], "Database error: connection refused");
+    let frames: Vec<&str> = body["data"]["backtrace"].as_array().unwrap().iter().filter_map(Value::as_str).collect();
+    assert!(frames.iter().any(|frame| frame.contains("envelope_mode_test::orders")), "{:?}", frames);
+    assert!(!frames.iter().any(|frame| frame.starts_with("tokio::")), "{:?}", frames);
+
+    // Without the middleware the handler's own rendering carries it too
+    let bare = Router::new().route("/orders", get(orders));
+    let (_, body) = call(bare).await;
+    assert!(body["data"]["backtrace"].is_array());
+
+    mode::set(Mode::Production);
+    let (status, body) = call(app()).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["message"], "Internal error");
+    assert!(body["data"].get("backtrace").is_none());
+
+    // Explicit settings win over the mode
+    mode::set(Mode::Development);
+    let quiet = Router::new().route("/orders", get(orders)).layer(axum::middleware::from_fn_with_state(
+        Arc::new(EnvelopeConfig::new().expose_internal_errors(false).backtraces(false)),
+        envelope_middleware,
+    ));
+    let (_, body) = call(quiet).await;
+    assert_eq!(body["message"], "Internal error");
+    assert!(body["data"].get("backtrace").is_none());
+    mode::reset();
+}
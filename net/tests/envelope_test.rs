@@ -0,0 +1,96 @@
+//! Response envelope tests
+
+use axum::body::Body;
+use axum::http::{Request as HttpRequest, StatusCode};
+use axum::routing::get;
+use axum::{Json, Router};
+use rf_errors::RfError;
+use rf_net::http::{envelope_middleware, ApiError, EnvelopeConfig, Response};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+async fn user(axum::extract::Path(id): axum::extract::Path<u64>) -> Result<Response, ApiError> {
+    if id == 0 {
+        return Err(RfError::NotFound(format!("user {}", id)).into());
+    }
+    Ok(Response::success(json!({ "id": id, "name": "alice" })))
+}
+
+fn routes() -> Router {
+    Router::new()
+        .route("/users/{id}", get(user))
+        .route("/quota", get(|| async { Response::fail(4290, "Quota exceeded") }))
+        .route("/db", get(|| async { Err::<Response, ApiError>(RfError::Database("connection refused".into()).into()) }))
+        .route("/plain", get(|| async { Json(json!([1, 2])) }))
+        .route("/text", get(|| async { "hello" }))
+        .route("/teapot", get(|| async { (StatusCode::IM_A_TEAPOT, "short and stout") }))
+}
+
+async fn send(app: Router, uri: &str) -> (StatusCode, Value) {
+    let response = app.oneshot(HttpRequest::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body = serde_json::from_slice(&body).unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into()));
+    (status, body)
+}
+
+fn app(config: EnvelopeConfig) -> Router {
+    routes().layer(axum::middleware::from_fn_with_state(Arc::new(config), envelope_middleware))
+}
+
+#[tokio::test]
+async fn test_envelope_helpers() {
+    // Without the middleware the helpers already produce envelopes
+    let (status, body) = send(routes(), "/users/7").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "code": 0, "message": "OK", "data": { "id": 7, "name": "alice" } }));
+
+    let (status, body) = send(routes(), "/users/0").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body, json!({ "code": 404, "message": "Not found: user 0", "data": null }));
+
+    let (status, body) = send(routes(), "/quota").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["code"], 4290);
+
+    // Server errors do not leak details
+    let (status, body) = send(routes(), "/db").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body, json!({ "code": 1000, "message": "Internal error", "data": null }));
+}
+
+#[tokio::test]
+async fn test_envelope_middleware() {
+    let config = || {
+        EnvelopeConfig::new()
+            .map_code(4290, StatusCode::TOO_MANY_REQUESTS)
+            .map_code(1000, StatusCode::SERVICE_UNAVAILABLE)
+    };
+
+    let (status, body) = send(app(config()), "/quota").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body, json!({ "code": 4290, "message": "Quota exceeded", "data": null }));
+
+    let (status, body) = send(app(config()), "/db").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["message"], "Internal error");
+    let (_, body) = send(app(config().expose_internal_errors(true)), "/db").await;
+    assert_eq!(body["message"], "Database error: connection refused");
+
+    // Plain JSON and error responses are wrapped; other successful bodies are left alone
+    let (status, body) = send(app(config()), "/plain").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "code": 0, "message": "OK", "data": [1, 2] }));
+    let (_, body) = send(app(config()), "/text").await;
+    assert_eq!(body, "hello");
+    let (status, body) = send(app(config()), "/teapot").await;
+    assert_eq!(status, StatusCode::IM_A_TEAPOT);
+    assert_eq!(body, json!({ "code": 418, "message": "short and stout", "data": null }));
+    let (status, body) = send(app(config()), "/missing").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body, json!({ "code": 404, "message": "Not Found", "data": null }));
+
+    let (_, body) = send(app(config().wrap_responses(false)), "/plain").await;
+    assert_eq!(body, json!([1, 2]));
+}
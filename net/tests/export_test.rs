@@ -0,0 +1,284 @@
+//! Data export and import tests
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{header, HeaderMap, Response, Uri};
+use rf_errors::{Result, RfError};
+use rf_net::http::{DataFormat, Export, Import, ImportSink};
+use rf_util::valid::Validate;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Validate)]
+struct User {
+    #[valid(rule = "required|integer")]
+    id: u64,
+    #[valid(rule = "required|length:2,20")]
+    name: String,
+    #[valid(rule = "required|email")]
+    email: String,
+    active: Option<bool>,
+}
+
+/// Collects batches, failing batches that contain `fail@example.com`
+#[derive(Default)]
+struct MemorySink {
+    rows: Mutex<Vec<User>>,
+    batches: Mutex<usize>,
+}
+
+#[async_trait]
+impl ImportSink<User> for MemorySink {
+    async fn insert_batch(&self, rows: &[User]) -> Result<u64> {
+        *self.batches.lock().unwrap() += 1;
+        if rows.iter().any(|u| u.email == "fail@example.com") {
+            return Err(RfError::Database("duplicate key".to_string()));
+        }
+        let mut stored = self.rows.lock().unwrap();
+        stored.extend(rows.iter().map(|u| User { name: u.name.clone(), email: u.email.clone(), ..*u }));
+        Ok(rows.len() as u64)
+    }
+}
+
+fn users() -> Vec<Result<serde_json::Value>> {
+    vec![
+        Ok(json!({"id": 1, "name": "Ann, Jr.", "email": "ann@example.com", "active": true, "secret": "x"})),
+        Ok(json!({"id": 2, "name": "=cmd()", "email": "bob@example.com", "active": null})),
+    ]
+}
+
+fn export() -> Export {
+    Export::new("users").column("id", "ID").column("name", "Name").column("email", "Email").column("active", "Active")
+}
+
+async fn body(response: Response<Body>) -> Vec<u8> {
+    axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()
+}
+
+#[test]
+fn test_format_negotiation() {
+    let uri: Uri = "/export?format=xlsx".parse().unwrap();
+    assert_eq!(DataFormat::negotiate(&HeaderMap::new(), &uri), Some(DataFormat::Xlsx));
+    let uri: Uri = "/export?format=pdf".parse().unwrap();
+    assert_eq!(DataFormat::negotiate(&HeaderMap::new(), &uri), None);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ACCEPT, "text/html, application/x-ndjson;q=0.9".parse().unwrap());
+    assert_eq!(DataFormat::negotiate(&headers, &"/export".parse().unwrap()), Some(DataFormat::JsonLines));
+    headers.insert(header::ACCEPT, "*/*".parse().unwrap());
+    assert_eq!(DataFormat::negotiate(&headers, &"/export".parse().unwrap()), Some(DataFormat::Csv));
+    assert_eq!(DataFormat::from_filename("Users.XLSX"), Some(DataFormat::Xlsx));
+}
+
+#[tokio::test]
+async fn test_csv_and_json_lines_export() {
+    let response = export().response(DataFormat::Csv, futures_util::stream::iter(users()));
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv; charset=utf-8");
+    assert!(response.headers()[header::CONTENT_DISPOSITION].to_str().unwrap().contains("filename=\"users.csv\""));
+    assert_eq!(
+        String::from_utf8(body(response).await).unwrap(),
+        "ID,Name,Email,Active\r\n1,\"Ann, Jr.\",ann@example.com,true\r\n2,'=cmd(),bob@example.com,\r\n"
+    );
+
+    let response = export().response(DataFormat::JsonLines, futures_util::stream::iter(users()));
+    let text = String::from_utf8(body(response).await).unwrap();
+    let lines: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(lines[0], json!({"id": 1, "name": "Ann, Jr.", "email": "ann@example.com", "active": true}));
+    assert_eq!(lines.len(), 2);
+
+    // Columns default to the fields of the first row
+    let rows = futures_util::stream::iter(vec![Ok::<_, RfError>(json!({"a": 1, "b": "x"}))]);
+    let text = String::from_utf8(body(Export::new("t").response(DataFormat::Csv, rows)).await).unwrap();
+    assert_eq!(text, "a,b\r\n1,x\r\n");
+}
+
+#[tokio::test]
+async fn test_xlsx_round_trip() {
+    let rows = vec![
+        Ok(json!({"id": 1, "name": "Ann", "email": "ann@example.com", "active": true})),
+        Ok(json!({"id": 2, "name": "Bob <b>", "email": "bob@example.com", "active": false})),
+    ];
+    let response = export().sheet("Users").response(DataFormat::Xlsx, futures_util::stream::iter(rows));
+    let file = body(response).await;
+    assert!(file.starts_with(b"PK"));
+
+    let sink = MemorySink::default();
+    let report = Import::new(DataFormat::Xlsx)
+        .column("ID", "id")
+        .column("Name", "name")
+        .column("Email", "email")
+        .column("Active", "active")
+        .run::<User, _>(&file, &sink)
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.errors);
+    let stored = sink.rows.lock().unwrap();
+    assert_eq!(stored[1], User { id: 2, name: "Bob <b>".into(), email: "bob@example.com".into(), active: Some(false) });
+}
+
+#[tokio::test]
+async fn test_csv_import_report() {
+    let csv = "\u{feff}id,name,email,active\r\n\
+               1,Ann,ann@example.com,1\r\n\
+               x,Bob,bob@example.com,\r\n\
+               \r\n\
+               3,C,not-an-email,no\r\n\
+               4,\"Dee \"\"D\"\"\",dee@example.com,\r\n\
+               5,Eve,fail@example.com,\r\n\
+               6,Fay,fay@example.com,\r\n";
+    let sink = MemorySink::default();
+    let report = Import::new(DataFormat::Csv).batch_size(2).run::<User, _>(csv.as_bytes(), &sink).await.unwrap();
+
+    assert_eq!((report.total, report.imported, report.failed), (6, 2, 4));
+    assert_eq!(*sink.batches.lock().unwrap(), 2);
+    let names: Vec<String> = sink.rows.lock().unwrap().iter().map(|u| u.name.clone()).collect();
+    assert_eq!(names, vec!["Ann", "Dee \"D\""]);
+    let failed: Vec<(usize, Option<&str>)> = report.errors.iter().map(|e| (e.row, e.field.as_deref())).collect();
+    assert_eq!(failed[0], (3, None));
+    assert!(failed.contains(&(5, Some("name"))) && failed.contains(&(5, Some("email"))));
+    assert!(failed.contains(&(7, None)) && failed.contains(&(8, None)));
+
+    let error_file = report.error_csv();
+    assert!(error_file.starts_with("row,field,message\r\n3,,"));
+    assert!(error_file.contains("\r\n7,,Database error: duplicate key\r\n"));
+}
+
+#[tokio::test]
+async fn test_json_lines_import() {
+    let data = "{\"id\": 1, \"name\": \"Ann\", \"email\": \"ann@example.com\"}\n[1]\n{\"id\": \"2\", \"name\": \"Bob\", \"email\": \"bob@example.com\"}\n";
+    let sink = MemorySink::default();
+    let report = Import::new(DataFormat::JsonLines).max_errors(1).run::<User, _>(data.as_bytes(), &sink).await.unwrap();
+    assert_eq!((report.total, report.imported, report.failed), (2, 1, 1));
+    assert_eq!(report.errors[0].row, 2);
+}
+
+#[tokio::test]
+async fn test_streamed_import_matches_buffered() {
+    let csv = "\u{feff}id,name,email\r\n1,\"Ann\r\nSmith\",ann@example.com\r\n2,\"Bo \"\"B\"\"\",bob@example.com\r\nx,Cy,cy@example.com\r\n3,Dé,dee@example.com";
+    let buffered = Import::new(DataFormat::Csv).run::<User, _>(csv.as_bytes(), &MemorySink::default()).await.unwrap();
+    assert_eq!((buffered.total, buffered.imported, buffered.failed), (4, 3, 1));
+    assert_eq!(buffered.errors[0].row, 4);
+
+    // Chunks split the BOM, quotes, CRLFs and the two-byte 'é'
+    for size in [1, 2, 3, 5, 64] {
+        let sink = MemorySink::default();
+        let chunks = futures_util::stream::iter(csv.as_bytes().chunks(size).map(Ok::<_, RfError>));
+        let report = Import::new(DataFormat::Csv).batch_size(2).run_stream::<User, _, _>(chunks, &sink).await.unwrap();
+        assert_eq!((report.total, report.imported, report.failed), (4, 3, 1), "chunk size {}", size);
+        assert_eq!(report.errors[0].row, 4);
+        let names: Vec<String> = sink.rows.lock().unwrap().iter().map(|u| u.name.clone()).collect();
+        assert_eq!(names, vec!["Ann\r\nSmith", "Bo \"B\"", "Dé"]);
+    }
+
+    let lines = "{\"id\": 1, \"name\": \"Ann\", \"email\": \"ann@example.com\"}\n\n{\"id\": 2}\r\n{\"id\": 3, \"name\": \"Cy\", \"email\": \"cy@example.com\"}";
+    let chunks = futures_util::stream::iter(lines.as_bytes().chunks(7).map(Ok::<_, RfError>));
+    let report = Import::new(DataFormat::JsonLines).run_stream::<User, _, _>(chunks, &MemorySink::default()).await.unwrap();
+    assert_eq!((report.total, report.imported, report.failed), (3, 2, 1));
+    assert_eq!(report.errors[0].row, 3);
+}
+
+#[tokio::test]
+async fn test_streamed_import_limits() {
+    let csv = format!("id,name,email\n1,{},a@example.com\n", "n".repeat(100));
+    let err = Import::new(DataFormat::Csv)
+        .max_row_size(64)
+        .run::<User, _>(csv.as_bytes(), &MemorySink::default())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Row 2 exceeds 64 bytes"), "{}", err);
+
+    let chunks = futures_util::stream::iter(vec![
+        Ok(b"id,name,email\n1,Ann,ann@example.com\n".to_vec()),
+        Err(RfError::Network("connection reset".to_string())),
+    ]);
+    let err = Import::new(DataFormat::Csv).run_stream::<User, _, _>(chunks, &MemorySink::default()).await.unwrap_err();
+    assert!(err.to_string().contains("connection reset"));
+
+    let err = Import::new(DataFormat::Csv)
+        .run::<User, _>(b"id,name\n1,\"Ann\n", &MemorySink::default())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("unterminated"));
+}
+
+#[tokio::test]
+async fn test_multipart_import_with_error_file() {
+    use axum::extract::{FromRequest, Multipart};
+    use rf_net::http::ERROR_SAMPLE;
+
+    // 1000 rows, every fourth with an invalid email
+    let mut csv = String::from("id,name,email\n");
+    for id in 1..=1000 {
+        let email = if id % 4 == 0 { "broken".to_string() } else { format!("u{}@example.com", id) };
+        csv.push_str(&format!("{},User {},{}\n", id, id, email));
+    }
+    let boundary = "rf-import-boundary";
+    let form = format!(
+        "--{b}\r\nContent-Disposition: form-data; name=\"note\"\r\n\r\nweekly\r\n\
+         --{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"users.csv\"\r\nContent-Type: text/csv\r\n\r\n{csv}\r\n\
+         --{b}--\r\n",
+        b = boundary,
+        csv = csv
+    );
+    let request = axum::http::Request::post("/import")
+        .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary))
+        .body(Body::from(form))
+        .unwrap();
+    let multipart = Multipart::from_request(request, &()).await.unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("errors.csv");
+    let sink = MemorySink::default();
+    // The JSON-lines default is overridden by the `.csv` file name
+    let report = Import::new(DataFormat::JsonLines)
+        .field("file")
+        .batch_size(100)
+        .error_file(&path)
+        .run_multipart::<User, _>(multipart, &sink)
+        .await
+        .unwrap();
+    assert_eq!((report.total, report.imported, report.failed), (1000, 750, 250));
+    assert_eq!(*sink.batches.lock().unwrap(), 8);
+    assert_eq!(report.errors.len(), ERROR_SAMPLE);
+    assert_eq!(report.error_file.as_deref(), Some(path.as_path()));
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(written.lines().count(), 251);
+    assert!(written.starts_with("row,field,message\r\n5,email,"));
+
+    let response = report.error_response("users.errors.csv").await.unwrap();
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv; charset=utf-8");
+    assert!(response.headers()[header::CONTENT_DISPOSITION].to_str().unwrap().contains("filename=\"users.errors.csv\""));
+    assert_eq!(String::from_utf8(body(response).await).unwrap(), written);
+
+    // Without an error file the download is built from the report
+    let report = Import::new(DataFormat::Csv)
+        .run::<User, _>(b"id,name,email\n1,A,bad\n", &MemorySink::default())
+        .await
+        .unwrap();
+    let text = String::from_utf8(body(report.error_response("e.csv").await.unwrap()).await).unwrap();
+    assert_eq!(text, report.error_csv());
+}
+
+#[cfg(feature = "import-db")]
+#[tokio::test]
+async fn test_model_export_import() {
+    let dir = std::env::temp_dir().join(format!("rf_export_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let url = format!("sqlite://{}?mode=rwc", dir.join("export.db").display());
+    let db = rf_database::Database::new_sqlite(&url).await.unwrap();
+    db.raw_execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, email TEXT NOT NULL, active BOOLEAN)")
+        .await
+        .unwrap();
+
+    let csv = "id,name,email,active\n1,Ann,ann@example.com,true\n2,Bob,bob@example.com,\n3,Cy,bad,\n";
+    let model = db.model("users").unscoped();
+    let report = Import::new(DataFormat::Csv).run::<User, _>(csv.as_bytes(), &model).await.unwrap();
+    assert_eq!((report.imported, report.failed), (2, 1));
+
+    let rows = db.model("users").unscoped().fields(&["id", "name", "active"]).order_by("id", "ASC").stream(1);
+    let text = String::from_utf8(body(Export::new("users").response(DataFormat::Csv, rows)).await).unwrap();
+    assert_eq!(text, "active,id,name\r\n1,1,Ann\r\n,2,Bob\r\n");
+    let _ = std::fs::remove_dir_all(dir);
+}
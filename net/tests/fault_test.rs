@@ -0,0 +1,127 @@
+//! Fault injection tests
+#![cfg(feature = "fault-injection")]
+
+use axum::http::Method;
+use rf_core::fault::{FaultInjector, FaultRule};
+use rf_errors::{Result, RfError};
+use rf_net::breaker::{CircuitBreaker, CircuitState};
+use rf_net::http::HttpServer;
+use rf_net::retry::{send, send_with_retry, RetryConfig};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+async fn start(faults: FaultInjector) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let server = HttpServer::new(addr)
+        .with_faults(faults)
+        .route(Method::GET, "/orders/:id", || async { "order" })
+        .unwrap()
+        .route(Method::GET, "/payments", || async { "paid" })
+        .unwrap()
+        .route(Method::GET, "/slow", || async { "slow" })
+        .unwrap();
+    let task = tokio::spawn(async move {
+        let _ = server.serve().await;
+    });
+    for _ in 0..100 {
+        if TcpStream::connect(addr).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    (addr, task)
+}
+
+fn retry(max_retries: u32) -> RetryConfig {
+    RetryConfig { max_retries, retry_delay: Duration::from_millis(1), ..RetryConfig::default() }
+}
+
+/// GET `path` with retries, through `breaker` if given
+async fn get(
+    addr: SocketAddr,
+    path: &str,
+    max_retries: u32,
+    breaker: Option<&CircuitBreaker>,
+    timeout: Option<Duration>,
+) -> Result<reqwest::Response> {
+    let client = reqwest::Client::new();
+    let url = format!("http://{}{}", addr, path);
+    let prepare = || {
+        let request = client.get(&url);
+        Ok(match timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        })
+    };
+    send_with_retry(&retry(max_retries), breaker, prepare, send).await
+}
+
+async fn get_text(addr: SocketAddr, path: &str, max_retries: u32, breaker: Option<&CircuitBreaker>) -> String {
+    get(addr, path, max_retries, breaker, None).await.unwrap().text().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_injected_errors_are_retried() {
+    let faults = FaultInjector::new().rule(FaultRule::new("/orders/*").error_rate(1.0).error_status(503).times(2));
+    let (addr, task) = start(faults.clone()).await;
+
+    let response = reqwest::get(format!("http://{}/orders/1", addr)).await.unwrap();
+    assert_eq!(response.status(), 503);
+    assert_eq!(response.json::<Value>().await.unwrap(), json!({"code": 503, "message": "service unavailable"}));
+
+    // One injected failure left, the retry gets through
+    let body = get_text(addr, "/orders/1", 2, None).await;
+    assert_eq!(body, "order");
+    assert_eq!(faults.injected(), 2);
+
+    // Rules can change while the server runs
+    faults.add(FaultRule::new("/orders/*").error_rate(1.0).error_status(500));
+    let response = get(addr, "/orders/2", 1, None, None).await.unwrap();
+    assert_eq!(response.status(), 500);
+    faults.set_enabled(false);
+    assert_eq!(get(addr, "/orders/2", 0, None, None).await.unwrap().status(), 200);
+    task.abort();
+}
+
+#[tokio::test]
+async fn test_resets_trip_the_circuit_breaker() {
+    let faults = FaultInjector::new().rule(FaultRule::new("/payments").reset_rate(1.0));
+    let (addr, task) = start(faults.clone()).await;
+
+    assert!(reqwest::get(format!("http://{}/payments", addr)).await.is_err());
+
+    let breaker = CircuitBreaker::new(2, Duration::from_millis(100)).success_threshold(1);
+    let result = get(addr, "/payments", 1, Some(&breaker), None).await;
+    assert!(matches!(result, Err(RfError::Network(m)) if m.starts_with("Request failed")));
+    assert_eq!(breaker.state(), CircuitState::Open);
+    let result = get(addr, "/payments", 1, Some(&breaker), None).await;
+    assert!(matches!(result, Err(RfError::Network(m)) if m == "Circuit breaker is open"));
+
+    // Once the fault clears, the half-open probe closes the breaker again
+    faults.clear();
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(get_text(addr, "/payments", 1, Some(&breaker)).await, "paid");
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    task.abort();
+}
+
+#[tokio::test]
+async fn test_injected_latency() {
+    let faults = FaultInjector::new().rule(FaultRule::new("/slow").latency(Duration::from_millis(200)));
+    let (addr, task) = start(faults).await;
+
+    let start = Instant::now();
+    assert_eq!(get_text(addr, "/slow", 0, None).await, "slow");
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    // Slower than the client is willing to wait
+    let result = get(addr, "/slow", 0, None, Some(Duration::from_millis(50))).await;
+    assert!(matches!(result, Err(RfError::Timeout(_))));
+
+    let start = Instant::now();
+    get_text(addr, "/payments", 0, None).await;
+    assert!(start.elapsed() < Duration::from_millis(200));
+    task.abort();
+}
@@ -0,0 +1,65 @@
+//! TCP framing tests
+
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+use rf_net::{FrameCodec, TcpClient, TcpServer};
+use tokio::io::AsyncWriteExt;
+
+fn remote() -> Context {
+    let span_context = SpanContext::new(
+        TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    Context::new().with_remote_span_context(span_context)
+}
+
+#[tokio::test]
+async fn test_frames_carry_trace_context() {
+    let server = TcpServer::bind("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().unwrap().to_string();
+    let codec = FrameCodec::new().trace_context(true);
+
+    let (client, accepted) = tokio::join!(TcpClient::connect(&addr), server.accept());
+    let mut client = client.unwrap();
+    let (mut accepted, _) = accepted.unwrap();
+    codec.write_frame_with(&mut client, b"traced", &remote()).await.unwrap();
+    codec.write_frame(&mut client, b"untraced").await.unwrap();
+    codec.write_frame(&mut client, b"").await.unwrap();
+    drop(client);
+
+    let frame = codec.read_frame(&mut accepted).await.unwrap().unwrap();
+    assert_eq!(&frame.payload[..], b"traced");
+    let span = frame.context.span();
+    assert_eq!(span.span_context().trace_id(), TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap());
+    assert!(span.span_context().is_remote());
+
+    let frame = codec.read_frame(&mut accepted).await.unwrap().unwrap();
+    assert_eq!(&frame.payload[..], b"untraced");
+    assert!(!frame.context.has_active_span());
+    assert!(codec.read_frame(&mut accepted).await.unwrap().unwrap().payload.is_empty());
+    assert!(codec.read_frame(&mut accepted).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_frame_limits() {
+    let (mut writer, mut reader) = tokio::io::duplex(1024);
+    let plain = FrameCodec::new();
+    plain.write_frame_with(&mut writer, b"hello", &remote()).await.unwrap();
+    let frame = plain.read_frame(&mut reader).await.unwrap().unwrap();
+    assert_eq!(&frame.payload[..], b"hello");
+    assert!(!frame.context.has_active_span());
+
+    let small = FrameCodec::new().max_frame_size(4).trace_context(true);
+    assert!(small.write_frame(&mut writer, b"hello").await.is_err());
+    FrameCodec::new().trace_context(true).write_frame_with(&mut writer, b"hello", &remote()).await.unwrap();
+    assert!(small.read_frame(&mut reader).await.is_err());
+
+    // A stream cut inside a frame is an error, not the end
+    let (mut writer, mut reader) = tokio::io::duplex(1024);
+    writer.write_all(&[0, 0, 0, 10, 1, 2, 3]).await.unwrap();
+    drop(writer);
+    assert!(plain.read_frame(&mut reader).await.is_err());
+}
@@ -24,5 +24,22 @@ mod tests {
         // Placeholder test for routing
         assert!(true);
     }
+
+    #[tokio::test]
+    async fn test_request_timeout_sets_deadline() {
+        use tower::ServiceExt;
+        let mut server = HttpServer::new("127.0.0.1:0".parse().unwrap())
+            .route(axum::http::Method::GET, "/budget", || async {
+                let remaining = rf_core::ctx::remaining().unwrap();
+                assert!(remaining <= std::time::Duration::from_secs(2));
+                "ok"
+            })
+            .unwrap()
+            .with_request_timeout(std::time::Duration::from_secs(2));
+        let router = std::mem::take(server.router());
+        let request = axum::http::Request::get("/budget").body(axum::body::Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), 200);
+    }
 }
 
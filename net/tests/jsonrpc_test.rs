@@ -0,0 +1,147 @@
+//! JSON-RPC tests
+
+use axum::body::Body;
+use futures_util::{SinkExt, StreamExt};
+use rf_errors::RfError;
+use rf_net::http::{JsonRpcError, JsonRpcServer};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio_tungstenite::tungstenite::Message;
+use tower::ServiceExt;
+
+#[derive(Deserialize)]
+struct AddParams {
+    a: i64,
+    b: i64,
+}
+
+fn server() -> JsonRpcServer {
+    JsonRpcServer::new()
+        .method("add", |p: AddParams| async move { Ok::<_, RfError>(p.a + p.b) })
+        .method("ping", |_: ()| async { Ok::<_, RfError>("pong") })
+        .method("user.get", |(id,): (u64,)| async move {
+            if id == 1 {
+                Ok(json!({ "id": 1, "name": "admin" }))
+            } else {
+                Err(RfError::NotFound(format!("user {}", id)))
+            }
+        })
+        .method("fail", |_: ()| async { Err::<(), _>(RfError::Database("connection reset".into())) })
+}
+
+async fn call(rpc: &JsonRpcServer, body: Value) -> Option<Value> {
+    rpc.handle(&body.to_string()).await.map(|r| serde_json::from_str(&r).unwrap())
+}
+
+#[tokio::test]
+async fn test_single_calls_and_errors() {
+    let rpc = server();
+
+    let r = call(&rpc, json!({"jsonrpc": "2.0", "method": "add", "params": {"a": 2, "b": 3}, "id": 1})).await;
+    assert_eq!(r.unwrap(), json!({"jsonrpc": "2.0", "result": 5, "id": 1}));
+
+    // Positional params and missing params
+    let r = call(&rpc, json!({"jsonrpc": "2.0", "method": "add", "params": [4, 5], "id": "a"})).await;
+    assert_eq!(r.unwrap()["result"], 9);
+    let r = call(&rpc, json!({"jsonrpc": "2.0", "method": "ping", "id": null})).await.unwrap();
+    assert_eq!(r["result"], "pong");
+    assert_eq!(r["id"], Value::Null);
+
+    let r = call(&rpc, json!({"jsonrpc": "2.0", "method": "nope", "id": 2})).await.unwrap();
+    assert_eq!(r["error"]["code"], JsonRpcError::METHOD_NOT_FOUND);
+    let r = call(&rpc, json!({"jsonrpc": "2.0", "method": "add", "params": {"a": 1}, "id": 3})).await.unwrap();
+    assert_eq!(r["error"]["code"], JsonRpcError::INVALID_PARAMS);
+    let r = call(&rpc, json!({"jsonrpc": "1.0", "method": "ping", "id": 4})).await.unwrap();
+    assert_eq!(r["error"]["code"], JsonRpcError::INVALID_REQUEST);
+
+    // RfError mapping
+    let r = call(&rpc, json!({"jsonrpc": "2.0", "method": "user.get", "params": [7], "id": 5})).await.unwrap();
+    assert_eq!(r["error"]["code"], JsonRpcError::NOT_FOUND);
+    assert_eq!(r["error"]["data"]["code"], 404);
+    let r = call(&rpc, json!({"jsonrpc": "2.0", "method": "fail", "id": 6})).await.unwrap();
+    assert_eq!(r["error"]["code"], JsonRpcError::INTERNAL_ERROR);
+    assert_eq!(r["error"]["message"], "Internal error");
+
+    let r: Value = serde_json::from_str(&rpc.handle("{not json").await.unwrap()).unwrap();
+    assert_eq!(r["error"]["code"], JsonRpcError::PARSE_ERROR);
+
+    // Notifications get no response
+    assert!(call(&rpc, json!({"jsonrpc": "2.0", "method": "ping"})).await.is_none());
+}
+
+#[tokio::test]
+async fn test_batch() {
+    let rpc = server();
+    let r = call(
+        &rpc,
+        json!([
+            {"jsonrpc": "2.0", "method": "add", "params": [1, 1], "id": 1},
+            {"jsonrpc": "2.0", "method": "ping"},
+            {"foo": "bar"},
+            {"jsonrpc": "2.0", "method": "user.get", "params": [1], "id": 2}
+        ]),
+    )
+    .await
+    .unwrap();
+    let responses = r.as_array().unwrap();
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0]["result"], 2);
+    assert_eq!(responses[1]["error"]["code"], JsonRpcError::INVALID_REQUEST);
+    assert_eq!(responses[2]["result"]["name"], "admin");
+
+    assert!(call(&rpc, json!([{"jsonrpc": "2.0", "method": "ping"}])).await.is_none());
+    let r = call(&rpc, json!([])).await.unwrap();
+    assert_eq!(r["error"]["code"], JsonRpcError::INVALID_REQUEST);
+
+    let small = server().max_batch_size(1);
+    let r = call(&small, json!([{"jsonrpc": "2.0", "method": "ping", "id": 1}, {"jsonrpc": "2.0", "method": "ping", "id": 2}])).await;
+    assert_eq!(r.unwrap()["error"]["code"], JsonRpcError::INVALID_REQUEST);
+}
+
+#[tokio::test]
+async fn test_http_transport() {
+    let app = Arc::new(server()).router("/rpc");
+    let post = |body: &str| {
+        axum::extract::Request::builder()
+            .method("POST")
+            .uri("/rpc")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    let response = app
+        .clone()
+        .oneshot(post(r#"{"jsonrpc":"2.0","method":"add","params":{"a":1,"b":2},"id":9}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "application/json");
+    let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+    assert_eq!(serde_json::from_slice::<Value>(&body).unwrap()["result"], 3);
+
+    let response = app.oneshot(post(r#"{"jsonrpc":"2.0","method":"ping"}"#)).await.unwrap();
+    assert_eq!(response.status(), 204);
+}
+
+#[tokio::test]
+async fn test_websocket_transport_and_broadcast() {
+    let rpc = Arc::new(server());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = rpc.clone().router("/rpc");
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/rpc", addr)).await.unwrap();
+    ws.send(Message::Text(r#"{"jsonrpc":"2.0","method":"add","params":[20,22],"id":1}"#.into()))
+        .await
+        .unwrap();
+    let reply = ws.next().await.unwrap().unwrap();
+    let reply: Value = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    assert_eq!(reply, json!({"jsonrpc": "2.0", "result": 42, "id": 1}));
+
+    assert_eq!(rpc.broadcast("stats.updated", json!({"online": 3})), 1);
+    let pushed = ws.next().await.unwrap().unwrap();
+    let pushed: Value = serde_json::from_str(pushed.to_text().unwrap()).unwrap();
+    assert_eq!(pushed, json!({"jsonrpc": "2.0", "method": "stats.updated", "params": {"online": 3}}));
+}
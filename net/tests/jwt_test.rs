@@ -0,0 +1,125 @@
+//! JWT authentication tests
+
+use axum::body::Body;
+use axum::http::{Request as HttpRequest, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use rf_crypto::rsa::RsaKeyPair;
+use rf_net::http::{jwt_middleware, Claims, JwtAuth, JwtClaims, JwtKey, Request, TokenType};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tower::ServiceExt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Role {
+    role: String,
+}
+
+fn admin() -> Role {
+    Role {
+        role: "admin".to_string(),
+    }
+}
+
+#[test]
+fn test_jwt_issue_verify_refresh() {
+    let auth = JwtAuth::new(JwtKey::hs256("k1", "secret")).issuer("rf").audience("api");
+    let pair = auth.issue_pair("user-1", admin()).unwrap();
+    assert_eq!(pair.token_type, "Bearer");
+    assert_eq!(pair.expires_in, 900);
+
+    let claims: Claims<Role> = auth.verify(&pair.access_token).unwrap();
+    assert_eq!(claims.sub.as_deref(), Some("user-1"));
+    assert_eq!(claims.iss.as_deref(), Some("rf"));
+    assert_eq!(claims.token_type(), TokenType::Access);
+    assert_eq!(claims.extra, admin());
+
+    // Refresh tokens are not access tokens and vice versa
+    assert!(auth.verify::<Value>(&pair.refresh_token).is_err());
+    assert!(auth.refresh(&pair.access_token).is_err());
+    let refreshed = auth.refresh(&pair.refresh_token).unwrap();
+    let claims: Claims<Role> = auth.verify(&refreshed.access_token).unwrap();
+    assert_eq!(claims.extra, admin());
+
+    // Tampered, wrongly signed, expired and foreign tokens are rejected
+    let mut tampered = pair.access_token.clone();
+    tampered.insert(tampered.find('.').unwrap() + 2, 'x');
+    assert!(auth.verify::<Value>(&tampered).is_err());
+    assert!(auth.verify::<Value>("not.a.token").is_err());
+    let other = JwtAuth::new(JwtKey::hs256("k1", "other")).issuer("rf").audience("api");
+    assert!(auth.verify::<Value>(&other.issue("user-1", ()).unwrap()).is_err());
+    let expired = auth.clone().access_ttl(Duration::ZERO).leeway(Duration::ZERO);
+    assert!(expired.verify::<Value>(&expired.issue("user-1", ()).unwrap()).is_err());
+    let foreign = JwtAuth::new(JwtKey::hs256("k1", "secret")).issuer("other").audience("api");
+    assert!(auth.verify::<Value>(&foreign.issue("user-1", ()).unwrap()).is_err());
+}
+
+#[test]
+fn test_jwt_rs256_and_rotation() {
+    let key_pair = RsaKeyPair::new(2048).unwrap();
+    let auth = JwtAuth::new(JwtKey::rs256("rsa-1", &key_pair));
+    let token = auth.issue("user-1", admin()).unwrap();
+    assert!(auth.verify::<Role>(&token).is_ok());
+
+    // A verifier holding only the public key accepts the token
+    let public_pem = key_pair.public_key_pem().unwrap();
+    let verifier = JwtAuth::new(JwtKey::hs256("unused", "unused"));
+    verifier.add_key(JwtKey::rs256_public_pem("rsa-1", &public_pem).unwrap());
+    assert!(verifier.verify::<Role>(&token).is_ok());
+    assert!(verifier.rotate(JwtKey::rs256_public_pem("rsa-2", &public_pem).unwrap()).is_err());
+
+    // The public key cannot be used as an HMAC secret (alg confusion)
+    let forged = JwtAuth::new(JwtKey::hs256("rsa-1", public_pem.as_bytes()));
+    assert!(verifier.verify::<Value>(&forged.issue("user-1", ()).unwrap()).is_err());
+
+    // Rotation: old tokens stay valid until their key is retired
+    auth.rotate(JwtKey::hs256("hs-2", "new secret")).unwrap();
+    let rotated = auth.issue("user-1", admin()).unwrap();
+    assert!(auth.verify::<Role>(&token).is_ok());
+    assert!(auth.verify::<Role>(&rotated).is_ok());
+    assert_eq!(auth.key_ids(), vec!["hs-2", "rsa-1"]);
+    assert!(auth.retire("hs-2").is_err());
+    auth.retire("rsa-1").unwrap();
+    assert!(auth.verify::<Role>(&token).is_err());
+    assert!(auth.verify::<Role>(&rotated).is_ok());
+}
+
+#[tokio::test]
+async fn test_jwt_middleware() {
+    let auth = JwtAuth::new(JwtKey::hs256("k1", "secret"));
+    let app = Router::new()
+        .route("/me", get(|JwtClaims(claims): JwtClaims<Role>| async move { claims.extra.role }))
+        .route(
+            "/sub",
+            get(|request: Request| async move { request.claims::<Value>().unwrap().unwrap().sub.unwrap() }),
+        )
+        .layer(axum::middleware::from_fn_with_state(auth.clone(), jwt_middleware));
+    let send = |uri: &'static str, token: Option<String>| {
+        let app = app.clone();
+        async move {
+            let mut request = HttpRequest::get(uri);
+            if let Some(token) = token {
+                request = request.header("authorization", format!("Bearer {}", token));
+            }
+            let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    let pair = auth.issue_pair("user-1", admin()).unwrap();
+    assert_eq!(send("/me", Some(pair.access_token.clone())).await, (StatusCode::OK, "admin".to_string()));
+    assert_eq!(send("/sub", Some(pair.access_token)).await, (StatusCode::OK, "user-1".to_string()));
+
+    let (status, body) = send("/me", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["code"], json!(401));
+    let (status, _) = send("/me", Some(pair.refresh_token)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Claims that do not match the extractor type are a bad request
+    let (status, _) = send("/me", Some(auth.issue("user-1", ()).unwrap())).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,182 @@
+//! Request size limit and slow-client protection tests
+
+use axum::body::Bytes;
+use axum::extract::ConnectInfo;
+use axum::http::Method;
+use rf_net::http::{HttpServer, Request, RouteLimits, ServerLimits};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+#[derive(Deserialize, rf_util::valid::Validate)]
+struct Note {
+    text: String,
+}
+
+async fn start(server: impl FnOnce(SocketAddr) -> HttpServer) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let server = server(addr)
+        .route(Method::POST, "/echo", |body: Bytes| async move { body.len().to_string() })
+        .unwrap()
+        .route(Method::POST, "/upload/*path", |body: Bytes| async move { body.len().to_string() })
+        .unwrap()
+        .route(Method::POST, "/note", |mut request: Request| async move {
+            match request.parse::<Note>().await {
+                Ok(note) => note.text.len().to_string(),
+                Err(e) => e.to_string(),
+            }
+        })
+        .unwrap()
+        .route(Method::GET, "/ip", |ConnectInfo(peer): ConnectInfo<SocketAddr>| async move {
+            peer.ip().to_string()
+        })
+        .unwrap();
+    let task = tokio::spawn(async move {
+        let _ = server.serve().await;
+    });
+    for _ in 0..100 {
+        if TcpStream::connect(addr).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    // Let the probe connection close
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    (addr, task)
+}
+
+/// Send a raw request and read the response until the server closes the connection
+async fn raw(addr: SocketAddr, request: &[u8]) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(request).await.unwrap();
+    read_all(&mut stream).await
+}
+
+async fn read_all(stream: &mut TcpStream) -> String {
+    let mut response = Vec::new();
+    let _ = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response)).await;
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+fn post(path: &str, body: &[u8]) -> Vec<u8> {
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\nHost: test\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+        path,
+        body.len()
+    )
+    .into_bytes();
+    request.extend_from_slice(body);
+    request
+}
+
+fn limits() -> ServerLimits {
+    ServerLimits::new()
+        .max_body_size(1024)
+        .body_read_timeout(Duration::from_millis(300))
+        .max_header_size(8192)
+        .header_read_timeout(Duration::from_millis(300))
+}
+
+fn server(addr: SocketAddr) -> HttpServer {
+    HttpServer::new(addr)
+        .with_limits(limits())
+        .route_limits("/upload/*path", RouteLimits::new().max_body_size(4096))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_body_size_limits() {
+    let (addr, task) = start(server).await;
+
+    let response = raw(addr, &post("/echo", &[b'a'; 1000])).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.ends_with("1000"));
+
+    // Declared length over the limit
+    let response = raw(addr, &post("/echo", &[b'a'; 2000])).await;
+    assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+
+    // Chunked body over the limit
+    let mut chunked = b"POST /echo HTTP/1.1\r\nHost: test\r\nConnection: close\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+    for _ in 0..3 {
+        chunked.extend_from_slice(b"200\r\n");
+        chunked.extend_from_slice(&[b'a'; 0x200]);
+        chunked.extend_from_slice(b"\r\n");
+    }
+    chunked.extend_from_slice(b"0\r\n\r\n");
+    let response = raw(addr, &chunked).await;
+    assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+
+    // Route limit larger than the server limit, beyond axum's default extractor limit too
+    let response = raw(addr, &post("/upload/a.bin", &[b'a'; 3000])).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    let response = raw(addr, &post("/upload/a.bin", &[b'a'; 5000])).await;
+    assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+
+    // Request::parse reports the limit as 413 as well
+    let note = format!(r#"{{"text":"{}"}}"#, "a".repeat(2000));
+    let mut request = b"POST /note HTTP/1.1\r\nHost: test\r\nConnection: close\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+    request.extend_from_slice(format!("{:x}\r\n{}\r\n0\r\n\r\n", note.len(), note).as_bytes());
+    let response = raw(addr, &request).await;
+    assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+
+    task.abort();
+}
+
+#[tokio::test]
+async fn test_slow_clients() {
+    let (addr, task) = start(server).await;
+
+    // Body trickling in slower than body_read_timeout
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"POST /echo HTTP/1.1\r\nHost: test\r\nConnection: close\r\nContent-Length: 10\r\n\r\nab")
+        .await
+        .unwrap();
+    let response = read_all(&mut stream).await;
+    assert!(response.starts_with("HTTP/1.1 408"), "{}", response);
+
+    // Headers never completed (slowloris): the connection is closed
+    let started = std::time::Instant::now();
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(b"GET /ip HTTP/1.1\r\nHost: test\r\n").await.unwrap();
+    let response = read_all(&mut stream).await;
+    assert!(started.elapsed() < Duration::from_secs(3));
+    assert!(!response.contains("200 OK"), "{}", response);
+
+    // Oversized headers
+    let header = "a".repeat(10_000);
+    let request = format!("GET /ip HTTP/1.1\r\nHost: test\r\nX-Large: {}\r\nConnection: close\r\n\r\n", header);
+    let response = raw(addr, request.as_bytes()).await;
+    assert!(response.starts_with("HTTP/1.1 431"), "{}", response);
+
+    // Peer address is still available to handlers
+    let response = raw(addr, b"GET /ip HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n").await;
+    assert!(response.ends_with("127.0.0.1"), "{}", response);
+
+    task.abort();
+}
+
+#[tokio::test]
+async fn test_connections_per_ip() {
+    let (addr, task) = start(|addr| HttpServer::new(addr).with_limits(ServerLimits::new().max_connections_per_ip(1))).await;
+
+    let mut first = TcpStream::connect(addr).await.unwrap();
+    first.write_all(b"GET /ip HTTP/1.1\r\nHost: test\r\n\r\n").await.unwrap();
+    let mut buf = [0u8; 256];
+    let n = first.read(&mut buf).await.unwrap();
+    assert!(String::from_utf8_lossy(&buf[..n]).starts_with("HTTP/1.1 200"));
+
+    // The first connection is still open, so a second one is refused
+    let response = raw(addr, b"GET /ip HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n").await;
+    assert!(response.is_empty(), "{}", response);
+
+    drop(first);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let response = raw(addr, b"GET /ip HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+    task.abort();
+}
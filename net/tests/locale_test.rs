@@ -0,0 +1,82 @@
+//! Per-request locale tests
+
+use axum::body::Body;
+use axum::http::header::{CONTENT_LANGUAGE, VARY};
+use axum::http::Request as HttpRequest;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use chrono::{TimeZone, Utc};
+use rf_core::ctx::Locale;
+use rf_net::http::{envelope_middleware, locale_middleware, parse_accept_language, EnvelopeConfig, LocaleMiddleware};
+use rf_os::time::Time;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn app(config: LocaleMiddleware) -> Router {
+    Router::new()
+        .route("/order", get(|Extension(locale): Extension<Locale>| async move {
+            let created_at = Time(Utc.with_ymd_and_hms(2026, 1, 6, 10, 30, 0).unwrap());
+            Json(json!({ "language": locale.language, "created_at": created_at, "display": created_at.localize() }))
+        }))
+        .layer(axum::middleware::from_fn_with_state(Arc::new(EnvelopeConfig::new()), envelope_middleware))
+        .layer(axum::middleware::from_fn_with_state(Arc::new(config), locale_middleware))
+}
+
+async fn send(app: &Router, uri: &str, headers: &[(&str, &str)]) -> (Option<String>, Value) {
+    let mut request = HttpRequest::get(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.headers().get(VARY).unwrap(), "accept-language");
+    let language = response.headers().get(CONTENT_LANGUAGE).map(|v| v.to_str().unwrap().to_string());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (language, serde_json::from_slice::<Value>(&body).unwrap()["data"].clone())
+}
+
+#[test]
+fn test_parse_accept_language() {
+    let ranges = parse_accept_language("fr;q=0.5, zh-CN, en;q=0.8, de;q=bad");
+    let ranges: Vec<(&str, f32)> = ranges.iter().map(|(range, q)| (range.as_str(), *q)).collect();
+    assert_eq!(ranges, vec![("zh-CN", 1.0), ("de", 1.0), ("en", 0.8), ("fr", 0.5)]);
+}
+
+#[tokio::test]
+async fn test_defaults() {
+    let app = app(LocaleMiddleware::new().languages(&["zh-CN", "en-US"]).default_timezone("Asia/Shanghai").unwrap());
+    let (language, data) = send(&app, "/order", &[]).await;
+    assert_eq!(language.as_deref(), Some("zh-CN"));
+    assert_eq!(data["created_at"], "2026-01-06T18:30:00+08:00");
+    assert_eq!(data["display"], "2026年01月06日 18:30:00");
+    assert!(LocaleMiddleware::new().default_timezone("Mars/Olympus").is_err());
+}
+
+#[tokio::test]
+async fn test_negotiation() {
+    let app = app(LocaleMiddleware::new().languages(&["zh-CN", "en-US"]));
+
+    // Accept-Language by quality, matched on the primary subtag
+    let (language, data) = send(&app, "/order", &[
+        ("accept-language", "fr;q=0.9, en-GB;q=0.8, zh;q=0.1"),
+        ("x-timezone", "America/New_York"),
+    ]).await;
+    assert_eq!(language.as_deref(), Some("en-US"));
+    assert_eq!(data["created_at"], "2026-01-06T05:30:00-05:00");
+    assert_eq!(data["display"], "Jan 06, 2026 05:30:00 AM");
+
+    // Query parameters win over cookies, which win over headers
+    let (language, data) = send(&app, "/order?tz=Asia/Tokyo", &[
+        ("accept-language", "en-US"),
+        ("cookie", "sid=1; lang=zh-CN; tz=Europe/Berlin"),
+        ("x-timezone", "America/New_York"),
+    ]).await;
+    assert_eq!(language.as_deref(), Some("zh-CN"));
+    assert_eq!(data["created_at"], "2026-01-06T19:30:00+09:00");
+
+    // Unknown timezones and languages fall back to the defaults
+    let (language, data) = send(&app, "/order", &[("accept-language", "ko, *;q=0"), ("x-timezone", "Nowhere")]).await;
+    assert_eq!(language.as_deref(), Some("zh-CN"));
+    assert_eq!(data["created_at"], "2026-01-06T10:30:00+00:00");
+    assert_eq!(data["language"], "zh-CN");
+}
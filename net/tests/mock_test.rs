@@ -0,0 +1,199 @@
+//! Response mocking tests
+
+use axum::http::Method;
+use axum::Json;
+use rf_net::http::{HttpServer, MockConfig, MockRoute};
+use rf_net::oai::{ApiOperation, ApiSchema, OpenApiBuilder};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+/// An order
+#[derive(Serialize, ApiSchema)]
+#[allow(dead_code)]
+struct Order {
+    id: u64,
+    #[api(example = "paid")]
+    status: String,
+    #[api(format = "email")]
+    contact: Option<String>,
+    items: Vec<Item>,
+}
+
+#[derive(Serialize, ApiSchema)]
+#[allow(dead_code)]
+struct Item {
+    sku: String,
+    quantity: u32,
+}
+
+#[derive(Serialize, ApiSchema)]
+#[allow(dead_code)]
+struct Problem {
+    message: String,
+}
+
+async fn unfinished() -> Json<Value> {
+    Json(json!("real handler"))
+}
+
+async fn start(mock: MockConfig) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let server = HttpServer::new(addr)
+        .with_openapi(OpenApiBuilder::new("Shop", "1.0.0"))
+        .with_mock(mock)
+        .api_route(
+            Method::GET,
+            "/orders/:id",
+            unfinished,
+            ApiOperation::new()
+                .response::<Order>(200, "The order")
+                .response::<Problem>(404, "Not found")
+                .response_empty(409, "Conflict"),
+        )
+        .unwrap()
+        .api_route(
+            Method::GET,
+            "/orders",
+            unfinished,
+            ApiOperation::new()
+                .response::<Vec<Order>>(200, "Orders")
+                .response_example(200, json!([{"id": 1, "status": "pending"}])),
+        )
+        .unwrap()
+        .route(Method::GET, "/health", unfinished)
+        .unwrap()
+        .route(Method::POST, "/orders", unfinished)
+        .unwrap();
+    let task = tokio::spawn(async move {
+        let _ = server.serve().await;
+    });
+    for _ in 0..100 {
+        if TcpStream::connect(addr).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    (addr, task)
+}
+
+async fn send(addr: SocketAddr, method: Method, path: &str, status: Option<u16>) -> (u16, Value) {
+    let mut request = reqwest::Client::new().request(method, format!("http://{}{}", addr, path));
+    if let Some(status) = status {
+        request = request.header("X-Mock-Status", status.to_string());
+    }
+    let response = request.send().await.unwrap();
+    let code = response.status().as_u16();
+    let body = response.text().await.unwrap();
+    (code, serde_json::from_str(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_mock_examples_from_document() {
+    let dir = tempfile::tempdir().unwrap();
+    let (addr, task) = start(MockConfig::new().dir(dir.path())).await;
+
+    // Built from the schema, with field examples and formats
+    let (status, body) = send(addr, Method::GET, "/orders/7", None).await;
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        json!({"id": 0, "status": "paid", "contact": "user@example.com", "items": [{"sku": "string", "quantity": 0}]})
+    );
+
+    // Explicit operation example
+    let (status, body) = send(addr, Method::GET, "/orders", None).await;
+    assert_eq!(status, 200);
+    assert_eq!(body, json!([{"id": 1, "status": "pending"}]));
+
+    // Other documented responses by header
+    let (status, body) = send(addr, Method::GET, "/orders/7", Some(404)).await;
+    assert_eq!(status, 404);
+    assert_eq!(body, json!({"message": "string"}));
+    let (status, body) = send(addr, Method::GET, "/orders/7", Some(409)).await;
+    assert_eq!((status, body), (409, Value::Null));
+
+    // Routes without examples run their handlers
+    let (status, body) = send(addr, Method::GET, "/health", None).await;
+    assert_eq!((status, body), (200, json!("real handler")));
+    task.abort();
+}
+
+#[tokio::test]
+async fn test_mock_files_and_routes() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("GET/orders")).unwrap();
+    std::fs::write(dir.path().join("GET/orders/{id}.json"), r#"{"id": 42}"#).unwrap();
+    std::fs::write(dir.path().join("GET/orders/{id}.404.json"), r#"{"message": "no order 42"}"#).unwrap();
+    std::fs::write(dir.path().join("GET/health.json"), r#"{"status": "up"}"#).unwrap();
+
+    let mock = MockConfig::new()
+        .dir(dir.path())
+        .route(Method::POST, "/orders", MockRoute::new().status(201).body(json!({"id": 100})))
+        .unwrap()
+        .route(Method::GET, "/health", MockRoute::passthrough())
+        .unwrap();
+    let (addr, task) = start(mock).await;
+
+    assert_eq!(send(addr, Method::GET, "/orders/1", None).await, (200, json!({"id": 42})));
+    assert_eq!(send(addr, Method::GET, "/orders/1", Some(404)).await, (404, json!({"message": "no order 42"})));
+    assert_eq!(send(addr, Method::POST, "/orders", None).await, (201, json!({"id": 100})));
+    assert_eq!(send(addr, Method::GET, "/health", None).await, (200, json!("real handler")));
+
+    // Files are read per request
+    std::fs::write(dir.path().join("GET/orders/{id}.json"), r#"{"id": 43}"#).unwrap();
+    assert_eq!(send(addr, Method::GET, "/orders/1", None).await, (200, json!({"id": 43})));
+    task.abort();
+
+    let unknown = MockConfig::new().route(Method::GET, "/missing", MockRoute::new().body(json!(1))).unwrap();
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let server = HttpServer::new(addr).with_mock(unknown).route(Method::GET, "/", unfinished).unwrap();
+    assert!(server.serve().await.is_err());
+}
+
+#[tokio::test]
+async fn test_mock_latency_and_errors() {
+    let mock = MockConfig::new()
+        .latency(Duration::from_millis(150))
+        .route(Method::GET, "/orders", MockRoute::new().error_rate(1.0))
+        .unwrap();
+    let (addr, task) = start(mock).await;
+
+    let started = Instant::now();
+    assert_eq!(send(addr, Method::GET, "/orders/1", None).await.0, 200);
+    assert!(started.elapsed() >= Duration::from_millis(150));
+
+    let (status, body) = send(addr, Method::GET, "/orders", None).await;
+    assert_eq!(status, 500);
+    assert_eq!(body, json!({"code": 500, "message": "Injected mock error"}));
+    task.abort();
+
+    let (addr, task) = start(MockConfig::new().error_rate(1.0, 503)).await;
+    assert_eq!(send(addr, Method::GET, "/health", None).await.0, 503);
+    task.abort();
+
+    // Disabled configs serve the real handlers
+    let (addr, task) = start(MockConfig::new().enabled(false).error_rate(1.0, 503)).await;
+    assert_eq!(send(addr, Method::GET, "/orders/1", None).await, (200, json!("real handler")));
+    task.abort();
+}
+
+#[test]
+fn test_mock_config_from_env() {
+    std::env::set_var("RF_MOCK", "true");
+    std::env::set_var("RF_MOCK_LATENCY", "100-800");
+    std::env::set_var("RF_MOCK_ERROR_RATE", "0.25");
+    let config = MockConfig::from_env();
+    assert!(config.is_enabled());
+    let debug = format!("{:?}", config);
+    assert!(debug.contains("latency: Some((100ms, 800ms))"), "{}", debug);
+    assert!(debug.contains("error_rate: 0.25"), "{}", debug);
+
+    std::env::set_var("RF_MOCK", "0");
+    assert!(!MockConfig::from_env().is_enabled());
+    for name in ["RF_MOCK", "RF_MOCK_LATENCY", "RF_MOCK_ERROR_RATE"] {
+        std::env::remove_var(name);
+    }
+}
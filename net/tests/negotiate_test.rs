@@ -0,0 +1,179 @@
+//! Content negotiation tests
+
+use axum::body::Body;
+use axum::http::header::{ACCEPT, CONTENT_TYPE, VARY};
+use axum::http::{Request as HttpRequest, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use rf_errors::Result;
+use rf_net::http::{
+    envelope_middleware, negotiate_middleware, parse_accept, EnvelopeConfig, Negotiated, Negotiator, Renderer, Response,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+#[derive(Serialize)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+#[derive(Serialize)]
+struct Report {
+    total: u64,
+}
+
+fn users() -> Vec<User> {
+    vec![User { id: 1, name: "alice".into() }, User { id: 2, name: "bob, jr".into() }]
+}
+
+/// `text/plain` as `key=value` lines
+struct PlainRenderer;
+
+impl Renderer for PlainRenderer {
+    fn media_types(&self) -> &[&'static str] {
+        &["text/plain"]
+    }
+
+    fn render(&self, value: &Value) -> Result<Vec<u8>> {
+        let lines: Vec<String> = value
+            .as_object()
+            .map(|map| map.iter().map(|(k, v)| format!("{}={}", k, v)).collect())
+            .unwrap_or_default();
+        Ok(lines.join("\n").into_bytes())
+    }
+}
+
+fn app(negotiator: Negotiator) -> Router {
+    Router::new()
+        .route("/users", get(|| async { Negotiated(users()) }))
+        .route("/report", get(|| async { (StatusCode::CREATED, Negotiated(Report { total: 3 })) }))
+        .route("/user", get(|| async { Response::negotiated(json!({"id": 1, "name": "a<b>"})) }))
+        .route("/text", get(|| async { "hello" }))
+        .layer(axum::middleware::from_fn_with_state(Arc::new(negotiator), negotiate_middleware))
+}
+
+async fn send(app: Router, uri: &str, accept: Option<&str>) -> (StatusCode, String, Vec<u8>) {
+    let mut request = HttpRequest::get(uri);
+    if let Some(accept) = accept {
+        request = request.header(ACCEPT, accept);
+    }
+    let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let content_type = response.headers().get(CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string()).unwrap_or_default();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, content_type, body.to_vec())
+}
+
+#[test]
+fn test_parse_accept() {
+    let ranges = parse_accept("text/*;q=0.5, application/XML, */*;q=0.1, application/json;level=1;q=0.8");
+    let types: Vec<(&str, f32)> = ranges.iter().map(|r| (r.media_type.as_str(), r.quality)).collect();
+    assert_eq!(types, [("application/xml", 1.0), ("application/json", 0.8), ("text/*", 0.5), ("*/*", 0.1)]);
+    assert_eq!(ranges[2].specificity("text/csv"), 2);
+    assert_eq!(ranges[2].specificity("application/csv"), 0);
+    assert_eq!(ranges[3].specificity("application/msgpack"), 1);
+    assert!(parse_accept("").is_empty());
+}
+
+#[tokio::test]
+async fn test_representations() {
+    let (status, content_type, body) = send(app(Negotiator::new()), "/users", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/json");
+    assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), json!([{"id": 1, "name": "alice"}, {"id": 2, "name": "bob, jr"}]));
+
+    let (_, content_type, body) = send(app(Negotiator::new()), "/users", Some("text/csv")).await;
+    assert_eq!(content_type, "text/csv; charset=utf-8");
+    assert_eq!(String::from_utf8(body).unwrap(), "id,name\r\n1,alice\r\n2,\"bob, jr\"\r\n");
+
+    let (_, content_type, body) = send(app(Negotiator::new()), "/user", Some("application/xml")).await;
+    assert_eq!(content_type, "application/xml; charset=utf-8");
+    let xml = String::from_utf8(body).unwrap();
+    assert!(xml.ends_with("<response><id>1</id><name>a&lt;b&gt;</name></response>"), "{}", xml);
+
+    let (_, content_type, body) = send(app(Negotiator::new()), "/users", Some("application/x-msgpack")).await;
+    assert_eq!(content_type, "application/msgpack");
+    let decoded: Value = rmp_serde::from_slice(&body).unwrap();
+    assert_eq!(decoded[1]["name"], "bob, jr");
+
+    // The handler's status survives rendering
+    let (status, content_type, body) = send(app(Negotiator::new()), "/report", Some("text/csv")).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(content_type, "text/csv; charset=utf-8");
+    assert_eq!(body, b"total\r\n3\r\n");
+
+    // Other responses pass through
+    let (_, content_type, body) = send(app(Negotiator::new()), "/text", Some("application/xml")).await;
+    assert!(content_type.starts_with("text/plain"));
+    assert_eq!(body, b"hello");
+}
+
+#[tokio::test]
+async fn test_quality_values() {
+    let negotiator = || Negotiator::new();
+    let (_, content_type, _) = send(app(negotiator()), "/users", Some("application/json;q=0.5, text/csv;q=0.9")).await;
+    assert_eq!(content_type, "text/csv; charset=utf-8");
+
+    // Equal quality goes to the server's preference, the default first
+    let (_, content_type, _) = send(app(negotiator()), "/users", Some("text/csv, application/json")).await;
+    assert_eq!(content_type, "application/json");
+
+    // q=0 rules a type out even under a wildcard
+    let (_, content_type, _) = send(app(negotiator()), "/users", Some("application/json;q=0, */*;q=0.1")).await;
+    assert_eq!(content_type, "application/xml; charset=utf-8");
+
+    // The more specific range decides
+    let (_, content_type, _) = send(app(negotiator()), "/users", Some("text/*;q=0.2, text/csv;q=0.9, application/*;q=0.5")).await;
+    assert_eq!(content_type, "text/csv; charset=utf-8");
+}
+
+#[tokio::test]
+async fn test_defaults_and_strict() {
+    // Unknown types fall back to the default
+    let (status, content_type, _) = send(app(Negotiator::new()), "/users", Some("image/png")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/json");
+
+    let (_, content_type, _) = send(app(Negotiator::new().default_type("application/xml")), "/users", None).await;
+    assert_eq!(content_type, "application/xml; charset=utf-8");
+
+    let strict = || Negotiator::new().strict(true).for_type::<Report>(&["text/csv", "application/json"]);
+    let (status, _, body) = send(app(strict()), "/report", Some("application/xml")).await;
+    assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["message"], "Not Acceptable, available types: text/csv, application/json");
+
+    // Per-type lists pick the type's own default
+    let (_, content_type, _) = send(app(strict()), "/report", Some("*/*")).await;
+    assert_eq!(content_type, "text/csv; charset=utf-8");
+    let (_, content_type, _) = send(app(strict()), "/users", Some("application/xml")).await;
+    assert_eq!(content_type, "application/xml; charset=utf-8");
+}
+
+#[tokio::test]
+async fn test_custom_renderer_and_envelope() {
+    let negotiator = Negotiator::new().renderer(PlainRenderer);
+    let (_, content_type, body) = send(app(negotiator), "/report", Some("text/plain")).await;
+    assert_eq!(content_type, "text/plain");
+    assert_eq!(body, b"total=3");
+
+    // JSON is still wrapped by the envelope, other representations are not
+    let app = || {
+        app(Negotiator::new())
+            .layer(axum::middleware::from_fn_with_state(Arc::new(EnvelopeConfig::new()), envelope_middleware))
+    };
+    let (_, _, body) = send(app(), "/users", Some("application/json")).await;
+    assert_eq!(serde_json::from_slice::<Value>(&body).unwrap()["data"][0]["name"], "alice");
+    let (_, _, body) = send(app(), "/users", Some("text/csv")).await;
+    assert!(body.starts_with(b"id,name\r\n"));
+
+    let response = Negotiator::new().respond(
+        &[(ACCEPT, "text/csv".parse().unwrap())].into_iter().collect(),
+        &users(),
+    );
+    assert_eq!(response.headers()[VARY], "accept");
+    assert_eq!(response.headers()[CONTENT_TYPE], "text/csv; charset=utf-8");
+}
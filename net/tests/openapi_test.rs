@@ -0,0 +1,164 @@
+//! # openapi_test
+//!
+//! openapi_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! OpenAPI generation tests
+
+use axum::extract::Query;
+use axum::http::Method;
+use axum::Json;
+use rf_net::http::HttpServer;
+use rf_net::oai::{ApiOperation, ApiSchema, OpenApiBuilder, SchemaRegistry};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// A shop customer
+#[derive(Serialize, Deserialize, ApiSchema)]
+#[serde(rename_all = "camelCase")]
+struct Customer {
+    /// Customer ID
+    id: u64,
+    display_name: String,
+    #[api(format = "email", example = "ann@example.com")]
+    email: Option<String>,
+    tier: Tier,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Customers who referred this one
+    referrals: Vec<Customer>,
+    #[serde(skip)]
+    #[allow(dead_code)]
+    password_hash: String,
+}
+
+#[derive(Serialize, Deserialize, ApiSchema)]
+#[serde(rename_all = "snake_case")]
+enum Tier {
+    Free,
+    ProPlus,
+}
+
+#[derive(Deserialize, ApiSchema)]
+struct CustomerFilter {
+    /// Name prefix
+    name: Option<String>,
+    limit: u32,
+}
+
+#[derive(Serialize, ApiSchema)]
+struct Page<T> {
+    items: Vec<T>,
+    total: u64,
+}
+
+#[test]
+fn test_derive_api_schema() {
+    let mut registry = SchemaRegistry::new();
+    assert_eq!(registry.schema::<Customer>(), json!({"$ref": "#/components/schemas/Customer"}));
+    assert_eq!(registry.schemas()["Customer"], json!({
+        "type": "object",
+        "description": "A shop customer",
+        "properties": {
+            "id": {"type": "integer", "format": "int64", "minimum": 0, "description": "Customer ID"},
+            "displayName": {"type": "string"},
+            "email": {"type": ["string", "null"], "format": "email", "examples": ["ann@example.com"]},
+            "tier": {"$ref": "#/components/schemas/Tier"},
+            "tags": {"type": "array", "items": {"type": "string"}},
+            "referrals": {
+                "type": "array",
+                "items": {"$ref": "#/components/schemas/Customer"},
+                "description": "Customers who referred this one",
+            },
+        },
+        "required": ["id", "displayName", "tier", "referrals"],
+    }));
+    assert_eq!(registry.schemas()["Tier"], json!({"type": "string", "enum": ["free", "pro_plus"]}));
+
+    // Generic types are inlined
+    let page = registry.schema::<Page<Customer>>();
+    assert_eq!(page["properties"]["items"]["items"], json!({"$ref": "#/components/schemas/Customer"}));
+    assert!(!registry.schemas().contains_key("Page"));
+}
+
+#[tokio::test]
+async fn test_openapi_document_from_routes() {
+    async fn get_customer() -> Json<serde_json::Value> {
+        Json(json!({}))
+    }
+    async fn list_customers(Query(filter): Query<CustomerFilter>) -> String {
+        format!("{:?} {}", filter.name, filter.limit)
+    }
+
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let server = HttpServer::new(addr)
+        .with_openapi(OpenApiBuilder::new("Shop", "1.2.0").bearer_auth("bearer").swagger_ui("/docs"))
+        .api_route(Method::GET, "/customers/:id", get_customer, ApiOperation::new()
+            .summary("Get a customer")
+            .tag("customers")
+            .path_param::<u64>("id", "Customer ID")
+            .response::<Customer>(200, "The customer")
+            .response_empty(404, "Not found")
+            .security("bearer"))
+        .unwrap()
+        .group("/v2", |v2| {
+            v2.api_route(Method::GET, "/customers", list_customers, ApiOperation::new().query::<CustomerFilter>())?
+                .api_route(Method::POST, "/customers/:id/files/*path", get_customer, ApiOperation::new()
+                    .request_body::<Customer>())
+        })
+        .unwrap()
+        .route(Method::GET, "/internal", get_customer)
+        .unwrap();
+
+    let document = server.openapi_document().unwrap();
+    assert_eq!(document["openapi"], "3.1.0");
+    assert_eq!(document["info"], json!({"title": "Shop", "version": "1.2.0"}));
+    assert_eq!(document["paths"].as_object().unwrap().keys().collect::<Vec<_>>(),
+        ["/customers/{id}", "/v2/customers", "/v2/customers/{id}/files/{path}"]);
+
+    let get = &document["paths"]["/customers/{id}"]["get"];
+    assert_eq!(get["parameters"], json!([{
+        "name": "id", "in": "path", "required": true, "description": "Customer ID",
+        "schema": {"type": "integer", "format": "int64", "minimum": 0},
+    }]));
+    assert_eq!(get["responses"]["200"]["content"]["application/json"]["schema"], json!({"$ref": "#/components/schemas/Customer"}));
+    assert_eq!(get["responses"]["404"], json!({"description": "Not found"}));
+    assert_eq!(get["security"], json!([{"bearer": []}]));
+
+    let list = &document["paths"]["/v2/customers"]["get"];
+    assert_eq!(list["parameters"], json!([
+        {"name": "limit", "in": "query", "required": true, "schema": {"type": "integer", "format": "int64", "minimum": 0}},
+        {"name": "name", "in": "query", "required": false, "description": "Name prefix", "schema": {"type": ["string", "null"]}},
+    ]));
+    let upload = &document["paths"]["/v2/customers/{id}/files/{path}"]["post"];
+    assert_eq!(upload["parameters"].as_array().unwrap().len(), 2);
+    assert_eq!(upload["requestBody"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/Customer");
+    assert_eq!(document["components"]["securitySchemes"]["bearer"]["scheme"], "bearer");
+    assert!(document["components"]["schemas"]["CustomerFilter"].is_object());
+
+    // The document converts to the utoipa model used by `with_swagger_ui`
+    let mut builder = OpenApiBuilder::new("Shop", "1.2.0");
+    for path in ["/a/:id", "/b"] {
+        builder = builder.operation(Method::PUT, path, ApiOperation::new().request_body::<Customer>().response::<Page<Customer>>(200, "ok"));
+    }
+    let spec = builder.build();
+    assert_eq!(spec.paths.paths.len(), 2);
+    assert!(spec.components.unwrap().schemas.contains_key("Tier"));
+
+    // Served when the server starts
+    tokio::spawn(server.serve());
+    let client = reqwest::Client::new();
+    let mut served = None;
+    for _ in 0..50 {
+        if let Ok(response) = client.get(format!("http://{}/openapi.json", addr)).send().await {
+            served = Some(response.json::<serde_json::Value>().await.unwrap());
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(served.unwrap(), document);
+    let ui = client.get(format!("http://{}/docs/", addr)).send().await.unwrap().text().await.unwrap();
+    assert!(ui.contains("url: \"/openapi.json\""));
+}
@@ -0,0 +1,113 @@
+//! Request parameter binding tests
+
+use axum::body::Body;
+use axum::http::{Request as HttpRequest, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use rf_net::http::{ParseError, Request};
+use rf_util::valid::Validate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+struct CreateUser {
+    #[valid(rule = "required|integer")]
+    org: u64,
+    #[valid(rule = "required|length:3,20")]
+    name: String,
+    #[valid(rule = "required|email", message = "请输入有效的邮箱")]
+    email: String,
+    #[valid(rule = "between:1,150")]
+    age: Option<u32>,
+    #[serde(default)]
+    admin: bool,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+async fn create(mut request: Request) -> Result<Json<CreateUser>, ParseError> {
+    Ok(Json(request.parse().await?))
+}
+
+fn app() -> Router {
+    Router::new().route("/orgs/{org}/users", post(create))
+}
+
+async fn send(request: HttpRequest<Body>) -> (StatusCode, Value) {
+    let response = app().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn request(uri: &str, content_type: &str, body: impl Into<Body>) -> HttpRequest<Body> {
+    HttpRequest::post(uri).header("content-type", content_type).body(body.into()).unwrap()
+}
+
+#[tokio::test]
+async fn test_parse_json_with_path_and_query() {
+    let body = json!({ "name": "alice", "email": "alice@example.com", "age": 30 }).to_string();
+    let (status, user) = send(request("/orgs/7/users?admin=true&tags=a&tags=b", "application/json", body)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user, json!({
+        "org": 7, "name": "alice", "email": "alice@example.com", "age": 30, "admin": true, "tags": ["a", "b"]
+    }));
+
+    // Body values override query values
+    let body = json!({ "name": "alice", "email": "alice@example.com", "admin": false }).to_string();
+    let (_, user) = send(request("/orgs/7/users?admin=1", "application/json", body)).await;
+    assert_eq!(user["admin"], false);
+}
+
+#[tokio::test]
+async fn test_parse_form_and_multipart() {
+    let form = "name=bob&email=bob%40example.com&age=&admin=on&tags[]=x";
+    let (status, user) = send(request("/orgs/1/users", "application/x-www-form-urlencoded", form)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user["age"], Value::Null);
+    assert_eq!(user["admin"], true);
+    assert_eq!(user["tags"], json!(["x"]));
+
+    let multipart = "--XYZ\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\ncarol\r\n\
+        --XYZ\r\nContent-Disposition: form-data; name=\"email\"\r\n\r\ncarol@example.com\r\n\
+        --XYZ\r\nContent-Disposition: form-data; name=\"avatar\"; filename=\"a.png\"\r\n\r\nPNG\r\n\
+        --XYZ--\r\n";
+    let (status, user) = send(request("/orgs/2/users", "multipart/form-data; boundary=XYZ", multipart)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user["name"], "carol");
+}
+
+#[tokio::test]
+async fn test_parse_validation_errors() {
+    let body = json!({ "name": "al", "email": "nope", "age": 200 }).to_string();
+    let (status, error) = send(request("/orgs/7/users", "application/json", body)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["code"], 400);
+    assert_eq!(error["message"], "name: Length must be between 3 and 20");
+    let errors: Vec<(&str, &str)> = error["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| (e["field"].as_str().unwrap(), e["rule"].as_str().unwrap()))
+        .collect();
+    assert_eq!(errors, [("name", "length"), ("email", "email"), ("age", "between")]);
+    assert_eq!(error["errors"][1]["message"], "请输入有效的邮箱");
+}
+
+#[tokio::test]
+async fn test_parse_decode_errors() {
+    // Wrong type
+    let body = json!({ "name": "alice", "email": "alice@example.com", "age": "old" }).to_string();
+    let (status, error) = send(request("/orgs/7/users", "application/json", body)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(error["message"].as_str().unwrap().starts_with("Invalid parameters"));
+    assert_eq!(error["errors"], json!([]));
+
+    // Malformed JSON and unsupported content types
+    let (status, _) = send(request("/orgs/7/users", "application/json", "{")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, error) = send(request("/orgs/7/users", "text/plain", "hello")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error["message"], "Unsupported content type: text/plain");
+}
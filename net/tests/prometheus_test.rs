@@ -0,0 +1,90 @@
+//! Prometheus endpoint tests
+
+use axum::body::Body;
+use axum::extract::Path;
+use axum::http::{header, Method, Request as HttpRequest, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use rf_net::http::{http_metrics_middleware, metrics_handler, metrics_response, HttpServer};
+use rf_os::metric::{MetricRegistry, PROMETHEUS_CONTENT_TYPE};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tower::ServiceExt;
+
+async fn user(Path(id): Path<u64>) -> String {
+    format!("user {}", id)
+}
+
+async fn body(app: Router, uri: &str) -> (StatusCode, String) {
+    let response = app.oneshot(HttpRequest::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_latency_by_route_pattern() {
+    let app = Router::new()
+        .route("/latency/users/{id}", get(user))
+        .route("/metrics", get(metrics_handler))
+        .layer(axum::middleware::from_fn(http_metrics_middleware));
+    for uri in ["/latency/users/1", "/latency/users/2", "/latency/missing"] {
+        body(app.clone(), uri).await;
+    }
+
+    let (status, text) = body(app, "/metrics").await;
+    assert_eq!(status, StatusCode::OK);
+    // One series per route pattern, not per path
+    assert!(
+        text.contains("http_request_duration_seconds_count{method=\"GET\",route=\"/latency/users/{id}\",status=\"200\"} 2\n"),
+        "{}",
+        text
+    );
+    assert!(text.contains("route=\"unmatched\",status=\"404\""), "{}", text);
+    assert!(!text.contains("/latency/users/1"), "{}", text);
+    assert!(text.contains("# TYPE http_requests_in_flight gauge\n"), "{}", text);
+}
+
+#[tokio::test]
+async fn test_metrics_response() {
+    let registry = MetricRegistry::new();
+    registry.counter("custom_total", "Custom").unwrap().inc();
+    let response = metrics_response(&registry);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], PROMETHEUS_CONTENT_TYPE);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(bytes, "# HELP custom_total Custom\n# TYPE custom_total counter\ncustom_total 1\n");
+}
+
+#[tokio::test]
+async fn test_server_with_metrics() {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let server = HttpServer::new(addr)
+        .with_metrics("/metrics")
+        .route(Method::GET, "/server/users/:id", user)
+        .unwrap();
+    let task = tokio::spawn(async move {
+        let _ = server.serve().await;
+    });
+    for _ in 0..100 {
+        if TcpStream::connect(addr).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let client = reqwest::Client::new();
+    let response = client.get(format!("http://{}/server/users/7", addr)).send().await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "user 7");
+    let response = client.get(format!("http://{}/metrics", addr)).send().await.unwrap();
+    assert_eq!(response.headers()[header::CONTENT_TYPE], PROMETHEUS_CONTENT_TYPE);
+    let text = response.text().await.unwrap();
+    for expected in [
+        "route=\"/server/users/{id}\",status=\"200\"",
+        "# TYPE process_cpu_seconds_total counter\n",
+        "# TYPE process_resident_memory_bytes gauge\n",
+        "# TYPE tokio_runtime_workers gauge\n",
+    ] {
+        assert!(text.contains(expected), "{} missing:\n{}", expected, text);
+    }
+    task.abort();
+}
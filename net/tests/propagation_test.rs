@@ -0,0 +1,52 @@
+//! Binary propagation tests
+
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+use rf_net::trace::{extract_context_from_binary, inject_context_to_binary, span_context_from_binary, BINARY_CONTEXT_LEN};
+
+fn remote(flags: TraceFlags) -> Context {
+    let span_context = SpanContext::new(
+        TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+        flags,
+        true,
+        TraceState::default(),
+    );
+    Context::new().with_remote_span_context(span_context)
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let bytes = inject_context_to_binary(&remote(TraceFlags::SAMPLED)).unwrap();
+    assert_eq!(bytes.len(), BINARY_CONTEXT_LEN);
+    assert_eq!(&bytes[..2], [0, 0]);
+    assert_eq!(bytes[28], 1);
+
+    let context = extract_context_from_binary(&bytes);
+    let span = context.span();
+    let span_context = span.span_context();
+    assert_eq!(span_context.trace_id(), TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap());
+    assert_eq!(span_context.span_id(), SpanId::from_hex("00f067aa0ba902b7").unwrap());
+    assert!(span_context.is_sampled() && span_context.is_remote());
+
+    let unsampled = inject_context_to_binary(&remote(TraceFlags::default())).unwrap();
+    assert!(!span_context_from_binary(&unsampled).unwrap().is_sampled());
+    assert!(inject_context_to_binary(&Context::new()).is_none());
+}
+
+#[test]
+fn test_binary_rejects_bad_input() {
+    let bytes = inject_context_to_binary(&remote(TraceFlags::SAMPLED)).unwrap();
+    // Fields from a newer sender are skipped
+    let mut extended = bytes.to_vec();
+    extended.extend_from_slice(&[9, 1, 2, 3]);
+    assert!(span_context_from_binary(&extended).is_some());
+
+    let mut version = bytes;
+    version[0] = 1;
+    assert!(span_context_from_binary(&version).is_none());
+    assert!(span_context_from_binary(&bytes[..20]).is_none());
+    assert!(span_context_from_binary(&[]).is_none());
+    assert!(span_context_from_binary(&[0; BINARY_CONTEXT_LEN]).is_none());
+    assert!(!extract_context_from_binary(b"garbage").has_active_span());
+}
@@ -0,0 +1,190 @@
+//! Port forwarding and SOCKS5 tests
+
+use axum::routing::get;
+use axum::Router;
+use openssl::asn1::Asn1Time;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::rsa::Rsa;
+use openssl::x509::{X509NameBuilder, X509};
+use rf_net::http::TlsAcceptorHandle;
+use rf_net::{HttpClient, Socks5Connector, TcpForwarder, TlsClientConfig};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+async fn echo_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let (mut reader, mut writer) = stream.split();
+                let _ = tokio::io::copy(&mut reader, &mut writer).await;
+            });
+        }
+    });
+    addr
+}
+
+/// Minimal SOCKS5 server; records the requested targets
+async fn socks5_server(credentials: Option<(&'static str, &'static str)>) -> (SocketAddr, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let targets = Arc::new(Mutex::new(Vec::new()));
+    let seen = targets.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut client, _) = listener.accept().await.unwrap();
+            let seen = seen.clone();
+            tokio::spawn(async move {
+                let mut head = [0u8; 2];
+                client.read_exact(&mut head).await.unwrap();
+                let mut methods = vec![0u8; head[1] as usize];
+                client.read_exact(&mut methods).await.unwrap();
+                match credentials {
+                    Some((user, pass)) => {
+                        client.write_all(&[5, 2]).await.unwrap();
+                        let mut buf = [0u8; 2];
+                        client.read_exact(&mut buf).await.unwrap();
+                        let mut u = vec![0u8; buf[1] as usize];
+                        client.read_exact(&mut u).await.unwrap();
+                        let mut plen = [0u8; 1];
+                        client.read_exact(&mut plen).await.unwrap();
+                        let mut p = vec![0u8; plen[0] as usize];
+                        client.read_exact(&mut p).await.unwrap();
+                        let ok = u == user.as_bytes() && p == pass.as_bytes();
+                        client.write_all(&[1, if ok { 0 } else { 1 }]).await.unwrap();
+                        if !ok {
+                            return;
+                        }
+                    }
+                    None => client.write_all(&[5, 0]).await.unwrap(),
+                }
+
+                let mut req = [0u8; 4];
+                client.read_exact(&mut req).await.unwrap();
+                let host = match req[3] {
+                    1 => {
+                        let mut ip = [0u8; 4];
+                        client.read_exact(&mut ip).await.unwrap();
+                        std::net::Ipv4Addr::from(ip).to_string()
+                    }
+                    3 => {
+                        let mut len = [0u8; 1];
+                        client.read_exact(&mut len).await.unwrap();
+                        let mut name = vec![0u8; len[0] as usize];
+                        client.read_exact(&mut name).await.unwrap();
+                        String::from_utf8(name).unwrap()
+                    }
+                    _ => panic!("unexpected address type"),
+                };
+                let mut port = [0u8; 2];
+                client.read_exact(&mut port).await.unwrap();
+                let target = format!("{}:{}", host, u16::from_be_bytes(port));
+                seen.lock().unwrap().push(target.clone());
+
+                let mut upstream = TcpStream::connect(&target).await.unwrap();
+                client.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).await.unwrap();
+                let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+            });
+        }
+    });
+    (addr, targets)
+}
+
+async fn roundtrip<S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin>(stream: &mut S, data: &[u8]) -> Vec<u8> {
+    stream.write_all(data).await.unwrap();
+    let mut buf = vec![0u8; data.len()];
+    stream.read_exact(&mut buf).await.unwrap();
+    buf
+}
+
+async fn spawn_forwarder(forwarder: TcpForwarder) -> SocketAddr {
+    let addr = forwarder.local_addr().unwrap();
+    tokio::spawn(forwarder.run());
+    addr
+}
+
+#[tokio::test]
+async fn test_forwarder_direct_and_via_socks5() {
+    let echo = echo_server().await;
+
+    let direct = spawn_forwarder(TcpForwarder::bind("127.0.0.1:0", echo.to_string()).await.unwrap()).await;
+    let mut stream = TcpStream::connect(direct).await.unwrap();
+    assert_eq!(roundtrip(&mut stream, b"hello").await, b"hello");
+
+    let (socks, targets) = socks5_server(Some(("admin", "secret"))).await;
+    let connector = Socks5Connector::new(socks.to_string()).with_auth("admin", "secret");
+    let via = spawn_forwarder(
+        TcpForwarder::bind("127.0.0.1:0", echo.to_string())
+            .await
+            .unwrap()
+            .via_socks5(connector.clone()),
+    )
+    .await;
+    let mut stream = TcpStream::connect(via).await.unwrap();
+    assert_eq!(roundtrip(&mut stream, b"through bastion").await, b"through bastion");
+    assert_eq!(targets.lock().unwrap().as_slice(), [echo.to_string()]);
+
+    let mut stream = connector.connect(&format!("localhost:{}", echo.port())).await.unwrap();
+    assert_eq!(roundtrip(&mut stream, b"x").await, b"x");
+    assert_eq!(targets.lock().unwrap()[1], format!("localhost:{}", echo.port()));
+
+    let wrong = Socks5Connector::new(socks.to_string()).with_auth("admin", "nope");
+    assert!(matches!(wrong.connect(&echo.to_string()).await, Err(rf_errors::RfError::Unauthorized(_))));
+    let anonymous = Socks5Connector::new(socks.to_string());
+    assert!(anonymous.connect(&echo.to_string()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_tls_terminating_forwarder() {
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "localhost").unwrap();
+    let name = name.build();
+    let mut cert = X509::builder().unwrap();
+    cert.set_version(2).unwrap();
+    cert.set_subject_name(&name).unwrap();
+    cert.set_issuer_name(&name).unwrap();
+    cert.set_pubkey(&key).unwrap();
+    cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+    cert.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+    cert.sign(&key, MessageDigest::sha256()).unwrap();
+    let acceptor = TlsAcceptorHandle::from_pem(
+        &cert.build().to_pem().unwrap(),
+        &key.private_key_to_pem_pkcs8().unwrap(),
+    )
+    .unwrap();
+
+    let echo = echo_server().await;
+    let addr = spawn_forwarder(
+        TcpForwarder::bind("127.0.0.1:0", echo.to_string())
+            .await
+            .unwrap()
+            .tls(Arc::new(acceptor)),
+    )
+    .await;
+
+    let client = TlsClientConfig::new().danger_accept_invalid_certs(true).server_name("localhost");
+    let mut tls = client.connect(&addr.to_string()).await.unwrap();
+    assert_eq!(roundtrip(&mut tls, b"secure").await, b"secure");
+}
+
+#[tokio::test]
+async fn test_http_client_over_socks5() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new().route("/hello", get(|| async { "hello from internal" }));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let (socks, targets) = socks5_server(None).await;
+    let client = HttpClient::with_socks5(Socks5Connector::new(socks.to_string())).await.unwrap();
+    for _ in 0..2 {
+        let text = client.get(&format!("http://localhost:{}/hello?x=1", port)).text().await.unwrap();
+        assert_eq!(text, "hello from internal");
+    }
+    assert_eq!(targets.lock().unwrap()[0], format!("localhost:{}", port));
+}
@@ -0,0 +1,107 @@
+//! Quota management tests
+
+use axum::body::Body;
+use axum::http::{Request as HttpRequest, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use chrono::{TimeZone, Utc};
+use rf_net::http::{quota_middleware, QuotaManager, QuotaMiddleware, QuotaPeriod};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+#[test]
+fn test_quota_windows() {
+    let at = Utc.with_ymd_and_hms(2026, 12, 31, 23, 59, 59).unwrap();
+    let (window, reset) = QuotaPeriod::Daily.window(at);
+    assert_eq!(window, "2026-12-31");
+    assert_eq!(reset, Some(Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap()));
+    let (window, reset) = QuotaPeriod::Monthly.window(at);
+    assert_eq!(window, "2026-12");
+    assert_eq!(reset, Some(Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap()));
+    assert_eq!(QuotaPeriod::Total.window(at), ("total".to_string(), None));
+}
+
+#[tokio::test]
+async fn test_quota_manager() {
+    let quotas = QuotaManager::memory()
+        .limit("api_calls", QuotaPeriod::Daily, 2)
+        .limit("storage_bytes", QuotaPeriod::Total, 100);
+    quotas.set_limit("pro", "api_calls", 3);
+
+    assert!(quotas.consume("free", "api_calls", 1).await.unwrap().allowed);
+    assert!(quotas.consume("free", "api_calls", 1).await.unwrap().allowed);
+    let check = quotas.consume("free", "api_calls", 1).await.unwrap();
+    assert!(!check.allowed);
+    assert_eq!((check.usage.used, check.usage.remaining), (2, 0));
+    for _ in 0..3 {
+        assert!(quotas.consume("pro", "api_calls", 1).await.unwrap().allowed);
+    }
+    assert!(quotas.consume("nope", "unknown", 1).await.is_err());
+
+    // Gauges can go down and be overwritten
+    assert!(quotas.consume("free", "storage_bytes", 80).await.unwrap().allowed);
+    assert!(!quotas.consume("free", "storage_bytes", 30).await.unwrap().allowed);
+    assert_eq!(quotas.release("free", "storage_bytes", 50).await.unwrap().used, 30);
+    assert_eq!(quotas.set_usage("free", "storage_bytes", 95).await.unwrap().remaining, 5);
+
+    // Usage queries for billing
+    let usage = quotas.usage_all("free").await.unwrap();
+    assert_eq!(usage.iter().map(|u| (u.metric.as_str(), u.used)).collect::<Vec<_>>(), vec![("api_calls", 2), ("storage_bytes", 95)]);
+    let report = quotas.report("api_calls", Utc::now()).await.unwrap();
+    assert_eq!(report.iter().map(|u| (u.subject.as_str(), u.used, u.limit)).collect::<Vec<_>>(), vec![("free", 2, 2), ("pro", 3, 3)]);
+    let yesterday = Utc::now() - chrono::Duration::days(1);
+    assert_eq!(quotas.usage_at("free", "api_calls", yesterday).await.unwrap().used, 0);
+}
+
+#[tokio::test]
+async fn test_quota_middleware() {
+    let quotas = Arc::new(QuotaManager::memory().limit("api_calls", QuotaPeriod::Monthly, 1));
+    let metered = Arc::new(QuotaMiddleware::new(quotas.clone(), "api_calls"));
+    let app = Router::new()
+        .route("/items", get(|| async { "items" }))
+        .layer(axum::middleware::from_fn_with_state(metered, quota_middleware));
+    let send = |key: Option<&'static str>| {
+        let app = app.clone();
+        async move {
+            let mut request = HttpRequest::get("/items");
+            if let Some(key) = key {
+                request = request.header("x-api-key", key);
+            }
+            app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+        }
+    };
+
+    let response = send(Some("key-1")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-quota-limit"], "1");
+    assert_eq!(response.headers()["x-quota-remaining"], "0");
+    assert!(response.headers().contains_key("x-quota-reset"));
+    assert_eq!(send(Some("key-1")).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(send(Some("key-2")).await.status(), StatusCode::OK);
+    assert_eq!(send(None).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(quotas.usage("key-1", "api_calls").await.unwrap().used, 1);
+}
+
+#[cfg(feature = "quota-db")]
+#[tokio::test]
+async fn test_quota_database_store() {
+    use rf_net::http::DatabaseQuotaStore;
+
+    let dir = std::env::temp_dir().join(format!("rf_quota_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("quota.db");
+    let db = rf_database::Database::new_sqlite(&format!("sqlite://{}?mode=rwc", path.display()))
+        .await
+        .unwrap();
+    let store = DatabaseQuotaStore::new(Arc::new(db));
+    store.ensure_table().await.unwrap();
+    let quotas = QuotaManager::new(store).limit("api_calls", QuotaPeriod::Daily, 2);
+
+    assert!(quotas.consume("a_b", "api_calls", 2).await.unwrap().allowed);
+    assert!(!quotas.consume("a_b", "api_calls", 1).await.unwrap().allowed);
+    assert!(quotas.consume("axb", "api_calls", 1).await.unwrap().allowed);
+    assert_eq!(quotas.release("a_b", "api_calls", 5).await.unwrap().used, 0);
+    let report = quotas.report("api_calls", Utc::now()).await.unwrap();
+    assert_eq!(report.iter().map(|u| (u.subject.as_str(), u.used)).collect::<Vec<_>>(), vec![("a_b", 0), ("axb", 1)]);
+    let _ = std::fs::remove_dir_all(dir);
+}
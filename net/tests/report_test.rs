@@ -0,0 +1,163 @@
+//! Error reporting tests
+
+use axum::body::Body;
+use axum::http::{HeaderMap, Request as HttpRequest, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rf_errors::RfError;
+use rf_net::http::{envelope_middleware, ApiError, EnvelopeConfig};
+use rf_net::report::{error_report_middleware, ErrorReport, ErrorReporting, ReportKind, SentrySender};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tower::ServiceExt;
+
+type Reports = Arc<Mutex<Vec<ErrorReport>>>;
+
+fn collecting() -> (Arc<ErrorReporting>, Reports) {
+    let reports = Reports::default();
+    let sink = reports.clone();
+    let reporting = ErrorReporting::new().reporter(move |report: &ErrorReport| {
+        sink.lock().unwrap().push(report.clone());
+        Ok(())
+    });
+    (Arc::new(reporting), reports)
+}
+
+/// Reports are sent in the background; wait until `count` arrived
+async fn wait_for(reports: &Reports, count: usize) -> Vec<ErrorReport> {
+    for _ in 0..50 {
+        if reports.lock().unwrap().len() >= count {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    reports.lock().unwrap().clone()
+}
+
+fn routes() -> Router {
+    Router::new()
+        .route("/ok", get(|| async { "ok" }))
+        .route("/missing", get(|| async { Err::<(), ApiError>(RfError::NotFound("user".into()).into()) }))
+        .route("/db", get(|| async { Err::<(), ApiError>(RfError::Database("connection refused".into()).into()) }))
+        .route(
+            "/panic",
+            get(|| async {
+                if true {
+                    panic!("boom for alice@example.com");
+                }
+            }),
+        )
+}
+
+async fn send(app: Router, request: HttpRequest<Body>) -> (StatusCode, Value) {
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn get_request(uri: &str) -> HttpRequest<Body> {
+    HttpRequest::get(uri).body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_reports_server_errors_and_panics() {
+    let (reporting, reports) = collecting();
+    let app = routes()
+        .layer(axum::middleware::from_fn_with_state(Arc::new(EnvelopeConfig::new()), envelope_middleware))
+        .layer(axum::middleware::from_fn_with_state(reporting, error_report_middleware));
+
+    assert_eq!(send(app.clone(), get_request("/ok")).await.0, StatusCode::OK);
+    assert_eq!(send(app.clone(), get_request("/missing")).await.0, StatusCode::NOT_FOUND);
+
+    let request = HttpRequest::get("/db?token=abc&page=2")
+        .header("authorization", "Bearer secret")
+        .header("x-request-id", "req-1")
+        .header("user-agent", "test")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(send(app.clone(), request).await.0, StatusCode::INTERNAL_SERVER_ERROR);
+    let reported = wait_for(&reports, 1).await;
+    assert_eq!(reported.len(), 1);
+    let report = &reported[0];
+    assert_eq!(report.kind, ReportKind::Error);
+    assert!(matches!(*report.error, RfError::Database(_)));
+    assert_eq!(report.context.status, Some(500));
+    assert_eq!(report.context.route.as_deref(), Some("/db"));
+    assert_eq!(report.context.request_id.as_deref(), Some("req-1"));
+    assert_eq!(report.context.query.as_deref(), Some("token=[Filtered]&page=2"));
+    assert_eq!(report.context.headers["authorization"], "[Filtered]");
+    assert_eq!(report.context.headers["user-agent"], "test");
+
+    // A panic is answered like an internal error and reported with its message, scrubbed
+    let (status, body) = send(app, get_request("/panic")).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["message"], "Internal error");
+    let reported = wait_for(&reports, 2).await;
+    assert_eq!(reported.len(), 2);
+    assert_eq!(reported[1].kind, ReportKind::Panic);
+    assert_eq!(reported[1].message, "Internal error: panic: boom for [email]");
+}
+
+#[tokio::test]
+async fn test_route_reporting_takes_precedence() {
+    let (global, global_reports) = collecting();
+    let (route, route_reports) = collecting();
+    let app = Router::new()
+        .merge(routes().layer(axum::middleware::from_fn_with_state(route, error_report_middleware)))
+        .route("/other", get(|| async { StatusCode::BAD_GATEWAY }))
+        .layer(axum::middleware::from_fn_with_state(Arc::new(EnvelopeConfig::new()), envelope_middleware))
+        .layer(axum::middleware::from_fn_with_state(global, error_report_middleware));
+
+    send(app.clone(), get_request("/db")).await;
+    send(app.clone(), get_request("/panic")).await;
+    send(app, get_request("/other")).await;
+    assert_eq!(wait_for(&route_reports, 2).await.len(), 2);
+    let global = wait_for(&global_reports, 1).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(global_reports.lock().unwrap().len(), 1);
+    assert_eq!(global[0].context.path.as_deref(), Some("/other"));
+    assert_eq!(global[0].message, "Internal error: GET /other returned 502 Bad Gateway");
+}
+
+#[tokio::test]
+async fn test_sentry_sender() {
+    assert!(SentrySender::new("https://sentry.example.com/42").is_err());
+    assert!(SentrySender::new("https://key@sentry.example.com/").is_err());
+    let sender = SentrySender::new("https://key@sentry.example.com/prefix/42").unwrap();
+    assert_eq!(sender.store_url(), "https://sentry.example.com/prefix/api/42/store/");
+
+    let received = Arc::new(Mutex::new(Vec::<(HeaderMap, Value)>::new()));
+    let app = Router::new().route(
+        "/api/7/store/",
+        post({
+            let received = received.clone();
+            move |headers: HeaderMap, Json(event): Json<Value>| async move {
+                received.lock().unwrap().push((headers, event));
+                StatusCode::OK
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let sender = SentrySender::new(&format!("http://public@{}/7", addr)).unwrap().environment("test");
+    let reporting = ErrorReporting::new().reporter(sender).tag("service", "orders");
+    let mut report = ErrorReport::new(ReportKind::Error, RfError::Database("deadlock".into()));
+    report.context.method = Some("POST".into());
+    report.context.path = Some("/orders".into());
+    report.context.status = Some(500);
+    assert!(reporting.report(report).await);
+
+    let received = received.lock().unwrap();
+    let (headers, event) = &received[0];
+    assert!(headers["x-sentry-auth"].to_str().unwrap().contains("sentry_key=public"));
+    assert_eq!(event["level"], "error");
+    assert_eq!(event["environment"], "test");
+    assert_eq!(event["exception"]["values"][0]["type"], "Database");
+    assert_eq!(event["request"]["method"], "POST");
+    assert_eq!(event["tags"]["service"], "orders");
+    assert_eq!(event["contexts"]["response"]["status_code"], 500);
+}
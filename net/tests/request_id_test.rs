@@ -0,0 +1,150 @@
+//! Request ID and access log tests
+
+use axum::body::{Body, Bytes};
+use axum::http::{Request as HttpRequest, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use rf_errors::RfError;
+use rf_net::http::{envelope_middleware, request_id_middleware, ApiError, EnvelopeConfig, RequestId, RequestIdMiddleware};
+use rf_net::HttpClient;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+
+/// Collects formatted log output
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Capture {
+    /// JSON access log records
+    fn access_lines(&self) -> Vec<Value> {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter(|line| line["target"] == "access")
+            .collect()
+    }
+}
+
+fn app(config: RequestIdMiddleware) -> Router {
+    Router::new()
+        .route("/id", get(|id: RequestId| async move {
+            format!("{}|{}", id, rf_core::ctx::request_id().unwrap())
+        }))
+        .route("/fail", get(|| async { Err::<String, ApiError>(RfError::Database("connection refused".into()).into()) }))
+        .route("/stream", get(|| async {
+            let chunks = futures_util::stream::iter([Ok::<_, std::io::Error>(Bytes::from("abc")), Ok(Bytes::from("defg"))]);
+            Body::from_stream(chunks)
+        }))
+        .route("/health", get(|| async { "ok" }))
+        .layer(axum::middleware::from_fn_with_state(Arc::new(EnvelopeConfig::new()), envelope_middleware))
+        .layer(axum::middleware::from_fn_with_state(Arc::new(config), request_id_middleware))
+}
+
+async fn send(app: &Router, uri: &str, headers: &[(&str, &str)]) -> (StatusCode, Option<String>, String) {
+    let mut request = HttpRequest::get(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let id = response.headers().get("x-request-id").map(|v| v.to_str().unwrap().to_string());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, id, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_request_id_and_access_log() {
+    let capture = Capture::default();
+    let writer = capture.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_target(true)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let app = app(RequestIdMiddleware::new().skip_access_log("/health"));
+
+    // Generated ID, visible to handlers and echoed back
+    let (status, id, body) = send(&app, "/id", &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    let id = id.unwrap();
+    assert_eq!(id.len(), 32);
+    assert_eq!(body, format!("{}|{}", id, id));
+
+    // Inbound IDs are honored unless malformed
+    let (_, id, body) = send(&app, "/id", &[("x-request-id", "edge-42"), ("user-agent", "probe/1.0")]).await;
+    assert_eq!((id.as_deref(), body.as_str()), (Some("edge-42"), "edge-42|edge-42"));
+    let (_, id, _) = send(&app, "/id", &[("x-request-id", &"x".repeat(200))]).await;
+    assert_eq!(id.unwrap().len(), 32);
+
+    // Error envelopes carry the ID
+    let (status, _, body) = send(&app, "/fail", &[
+        ("x-request-id", "req-9"),
+        ("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+    ]).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["message"], "Internal error");
+    assert_eq!(body["data"], json!({"request_id": "req-9"}));
+
+    // Streamed bodies are counted once finished
+    let (_, _, body) = send(&app, "/stream", &[("x-request-id", "req-s")]).await;
+    assert_eq!(body, "abcdefg");
+    send(&app, "/health", &[]).await;
+
+    let lines = capture.access_lines();
+    assert_eq!(lines.len(), 5, "{:?}", lines);
+    let probe = &lines[1]["fields"];
+    assert_eq!((probe["method"].as_str(), probe["path"].as_str(), probe["status"].as_u64()), (Some("GET"), Some("/id"), Some(200)));
+    assert_eq!(probe["request_id"], "edge-42");
+    assert_eq!(probe["user_agent"], "probe/1.0");
+    assert_eq!(probe["bytes"], 15);
+    assert!(probe["latency_ms"].as_f64().is_some());
+    let failed = &lines[3];
+    assert_eq!(failed["level"], "WARN");
+    assert_eq!(failed["fields"]["trace_id"], "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(lines[4]["fields"]["request_id"], "req-s");
+    assert_eq!(lines[4]["fields"]["bytes"], 7);
+}
+
+#[tokio::test]
+async fn test_request_id_options_and_propagation() {
+    let app = app(RequestIdMiddleware::new()
+        .trust_inbound(false)
+        .generator(|| "fixed".to_string())
+        .header("x-correlation-id").unwrap());
+    let response = app.oneshot(HttpRequest::get("/id").header("x-correlation-id", "client").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.headers()["x-correlation-id"], "fixed");
+    assert!(response.headers().get("x-request-id").is_none());
+
+    // HttpClient forwards the ID of the current request
+    let echo = Router::new().route("/", get(|headers: axum::http::HeaderMap| async move {
+        headers.get("x-request-id").map(|v| v.to_str().unwrap().to_string()).unwrap_or_default()
+    }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, echo).await.unwrap() });
+
+    let forwarded = rf_core::ctx::with_request_id("req-out", async {
+        HttpClient::new().get(&url).text().await.unwrap()
+    }).await;
+    assert_eq!(forwarded, "req-out");
+    let explicit = rf_core::ctx::with_request_id("req-out", async {
+        HttpClient::new().get(&url).header("x-request-id", "mine").text().await.unwrap()
+    }).await;
+    assert_eq!(explicit, "mine");
+    assert_eq!(HttpClient::new().get(&url).text().await.unwrap(), "");
+}
@@ -0,0 +1,156 @@
+//! Response cache tests
+
+use axum::body::Body;
+use axum::extract::Path;
+use axum::http::{HeaderMap, Request as HttpRequest, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use http_body_util::BodyExt;
+use rf_net::http::{
+    response_cache_middleware, CachePolicy, CachedResponse, MemoryResponseCacheStore, ResponseCache, ResponseCacheStore,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+fn app(cache: Arc<ResponseCache>, calls: Arc<AtomicUsize>) -> Router {
+    let counted = |calls: Arc<AtomicUsize>| move || calls.fetch_add(1, Ordering::SeqCst) + 1;
+    let product = counted(calls.clone());
+    let greeting = counted(calls.clone());
+    let private = counted(calls.clone());
+    let missing = counted(calls);
+    Router::new()
+        .route("/products/{id}", get(move |Path(id): Path<String>| async move { format!("{}:{}", id, product()) }))
+        .route(
+            "/greeting",
+            get(move |headers: HeaderMap| async move {
+                let lang = headers.get("accept-language").and_then(|v| v.to_str().ok()).unwrap_or("en").to_string();
+                format!("{}:{}", lang, greeting())
+            }),
+        )
+        .route(
+            "/private",
+            get(move || async move { ([("cache-control", "private")], private().to_string()).into_response() }),
+        )
+        .route("/missing", get(move || async move { (StatusCode::NOT_FOUND, missing().to_string()) }))
+        .layer(axum::middleware::from_fn_with_state(cache, response_cache_middleware))
+}
+
+async fn call(app: &Router, uri: &str, headers: &[(&str, &str)]) -> (String, String) {
+    let mut request = HttpRequest::builder().uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    let cache = response.headers().get("x-cache").and_then(|v| v.to_str().ok()).unwrap_or("").to_string();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (cache, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_hit_miss_and_vary() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let cache = Arc::new(
+        ResponseCache::memory()
+            .route("/products/{id}", CachePolicy::new(Duration::from_secs(60)))
+            .route("/greeting", CachePolicy::new(Duration::from_secs(60)).vary(&["Accept-Language"])),
+    );
+    let app = app(cache, calls.clone());
+
+    assert_eq!(call(&app, "/products/1", &[]).await, ("MISS".into(), "1:1".into()));
+    assert_eq!(call(&app, "/products/1", &[]).await, ("HIT".into(), "1:1".into()));
+    // The query is part of the key
+    assert_eq!(call(&app, "/products/1?page=2", &[]).await, ("MISS".into(), "1:2".into()));
+
+    assert_eq!(call(&app, "/greeting", &[("accept-language", "de")]).await, ("MISS".into(), "de:3".into()));
+    assert_eq!(call(&app, "/greeting", &[("accept-language", "fr")]).await, ("MISS".into(), "fr:4".into()));
+    assert_eq!(call(&app, "/greeting", &[("accept-language", "de")]).await, ("HIT".into(), "de:3".into()));
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_bypass() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let cache = Arc::new(
+        ResponseCache::memory()
+            .route("/products/*", CachePolicy::new(Duration::from_secs(60)))
+            .route("/private", CachePolicy::new(Duration::from_secs(60)))
+            .route("/missing", CachePolicy::new(Duration::from_secs(60))),
+    );
+    let app = app(cache, calls.clone());
+
+    // Authenticated requests, private responses and errors are never cached
+    for _ in 0..2 {
+        assert_eq!(call(&app, "/products/1", &[("authorization", "Bearer x")]).await.0, "");
+        assert_eq!(call(&app, "/private", &[]).await.0, "MISS");
+        assert_eq!(call(&app, "/missing", &[]).await.0, "MISS");
+    }
+    // Routes without a policy pass through
+    assert_eq!(call(&app, "/greeting", &[]).await.0, "");
+    assert_eq!(calls.load(Ordering::SeqCst), 7);
+}
+
+#[tokio::test]
+async fn test_stale_while_revalidate() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let cache = Arc::new(ResponseCache::memory().route(
+        "/products/{id}",
+        CachePolicy::new(Duration::from_millis(500)).stale_while_revalidate(Duration::from_secs(5)),
+    ));
+    let app = app(cache, calls.clone());
+
+    assert_eq!(call(&app, "/products/1", &[]).await, ("MISS".into(), "1:1".into()));
+    tokio::time::sleep(Duration::from_millis(550)).await;
+    assert_eq!(call(&app, "/products/1", &[]).await, ("STALE".into(), "1:1".into()));
+    // The background refresh replaces the entry
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(call(&app, "/products/1", &[]).await, ("HIT".into(), "1:2".into()));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_tag_invalidation() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let cache = Arc::new(ResponseCache::memory().route(
+        "/products/{id}",
+        CachePolicy::new(Duration::from_secs(60)).tag("product:{id}").tag("products"),
+    ));
+    let app = app(cache.clone(), calls);
+
+    call(&app, "/products/1", &[]).await;
+    call(&app, "/products/2", &[]).await;
+    assert_eq!(cache.invalidate_tag("product:1").await.unwrap(), 1);
+    assert_eq!(call(&app, "/products/1", &[]).await, ("MISS".into(), "1:3".into()));
+    assert_eq!(call(&app, "/products/2", &[]).await.0, "HIT");
+
+    assert_eq!(cache.invalidate_tag("products").await.unwrap(), 2);
+    assert_eq!(call(&app, "/products/2", &[]).await.0, "MISS");
+    cache.invalidate_path("/products/2").await.unwrap();
+    assert_eq!(call(&app, "/products/2", &[]).await.0, "MISS");
+}
+
+#[tokio::test]
+async fn test_memory_store_capacity() {
+    let store = MemoryResponseCacheStore::new(2);
+    let response = |tag: &str| CachedResponse {
+        status: 200,
+        headers: Vec::new(),
+        body: b"ok".to_vec(),
+        stored_at: 0,
+        tags: vec![tag.to_string()],
+    };
+    store.put("a", &response("t"), Duration::from_secs(10)).await.unwrap();
+    store.put("b", &response("t"), Duration::from_secs(60)).await.unwrap();
+    store.put("c", &response("u"), Duration::from_secs(60)).await.unwrap();
+    // The entry closest to expiry made room
+    assert_eq!(store.len(), 2);
+    assert!(store.get("a").await.unwrap().is_none());
+    assert_eq!(store.invalidate_tag("t").await.unwrap(), 1);
+    assert!(store.get("c").await.unwrap().is_some());
+
+    store.put("d", &response("u"), Duration::from_millis(10)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(store.get("d").await.unwrap().is_none());
+}
@@ -0,0 +1,179 @@
+//! Reverse proxy tests
+
+use axum::extract::ws::Message as ServerMessage;
+use axum::extract::{Request, WebSocketUpgrade};
+use axum::http::uri::Scheme;
+use axum::http::StatusCode;
+use axum::routing::{any, get};
+use axum::Router;
+use futures_util::{SinkExt, StreamExt};
+use rf_net::http::ReverseProxy;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap()
+    });
+    format!("127.0.0.1:{}", addr.port())
+}
+
+/// Upstream answering with the request line and selected headers
+async fn upstream(flaky: Arc<AtomicUsize>) -> String {
+    let app = Router::new()
+        .route("/flaky", get(move || {
+            let flaky = flaky.clone();
+            async move {
+                if flaky.fetch_add(1, Ordering::SeqCst) < 2 {
+                    (StatusCode::SERVICE_UNAVAILABLE, "busy".to_string())
+                } else {
+                    (StatusCode::OK, "recovered".to_string())
+                }
+            }
+        }))
+        .route("/ws", get(|ws: WebSocketUpgrade| async move {
+            ws.protocols(["chat"]).on_upgrade(|mut socket| async move {
+                while let Some(Ok(ServerMessage::Text(text))) = socket.recv().await {
+                    let _ = socket.send(ServerMessage::Text(format!("echo {}", text.as_str()).into())).await;
+                }
+            })
+        }))
+        .fallback(any(|request: Request| async move {
+            let (parts, body) = request.into_parts();
+            let header = |name: &str| parts.headers.get(name).map(|v| v.to_str().unwrap().to_string()).unwrap_or_default();
+            let summary = format!(
+                "{} {} xff={} xfh={} xfp={} cookie={} hop={} tag={}",
+                parts.method, parts.uri,
+                header("x-forwarded-for"), header("x-forwarded-host"), header("x-forwarded-proto"),
+                header("cookie"), header("x-hop"), header("x-gateway"),
+            );
+            let body = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+            (
+                [("server", "upstream/1.0"), ("x-upstream", "yes")],
+                format!("{} body={}", summary, String::from_utf8_lossy(&body)),
+            )
+        }));
+    serve(app).await
+}
+
+#[tokio::test]
+async fn test_forward_rewrite_and_headers() {
+    let upstream = upstream(Arc::default()).await;
+    let proxy = ReverseProxy::new(&format!("http://{}/v1", upstream)).unwrap()
+        .strip_prefix("/api")
+        .rewrite("/legacy/(.*)", "/modern/$1").unwrap()
+        .remove_request_header("cookie")
+        .remove_response_header("server")
+        .set_request_header("x-gateway", "rf").unwrap();
+    let gateway = serve(Router::new().route("/api/{*path}", proxy.clone().handler())).await;
+
+    let client = reqwest::Client::new();
+    let response = client.post(format!("http://{}/api/legacy/items?page=2", gateway))
+        .header("cookie", "session=secret")
+        .header("x-forwarded-for", "10.0.0.1")
+        .header("x-forwarded-host", "spoofed.example.com")
+        .header("x-forwarded-proto", "https")
+        .header("connection", "keep-alive, x-hop")
+        .header("x-hop", "dropped")
+        .body("payload")
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("server").is_none());
+    assert_eq!(response.headers()["x-upstream"], "yes");
+    let body = response.text().await.unwrap();
+    assert_eq!(body, format!(
+        "POST /v1/modern/items?page=2 xff=10.0.0.1, 127.0.0.1 xfh={} xfp=http cookie= hop= tag=rf body=payload",
+        gateway
+    ));
+    assert_eq!(proxy.target(&"/api/legacy/a%20b?x=1".parse().unwrap()), format!("http://{}/v1/modern/a%20b?x=1", upstream));
+    assert_eq!(proxy.target(&"/api".parse().unwrap()), format!("http://{}/v1/", upstream));
+}
+
+#[tokio::test]
+async fn test_retries_and_unavailable_upstream() {
+    let flaky = Arc::new(AtomicUsize::new(0));
+    let upstream = upstream(flaky.clone()).await;
+    let retrying = ReverseProxy::new(&format!("http://{}", upstream)).unwrap()
+        .retries(2)
+        .retry_backoff(Duration::from_millis(10));
+    let once = ReverseProxy::new(&format!("http://{}", upstream)).unwrap();
+    let down = ReverseProxy::new("http://127.0.0.1:1").unwrap().retries(1);
+    let gateway = serve(Router::new()
+        .route("/retry/{*path}", retrying.strip_prefix("/retry").handler())
+        .route("/once/{*path}", once.strip_prefix("/once").handler())
+        .route("/down", down.handler())).await;
+
+    let get = |path: &str| reqwest::get(format!("http://{}{}", gateway, path));
+    let response = get("/once/flaky").await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    // Second failure, then success on the third attempt
+    let response = get("/retry/flaky").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await.unwrap(), "recovered");
+    assert_eq!(flaky.load(Ordering::SeqCst), 3);
+    assert_eq!(get("/down").await.unwrap().status(), StatusCode::BAD_GATEWAY);
+}
+
+#[tokio::test]
+async fn test_websocket_passthrough() {
+    let upstream = upstream(Arc::default()).await;
+    let proxy = ReverseProxy::new(&format!("http://{}", upstream)).unwrap().strip_prefix("/live");
+    let gateway = serve(Router::new().route("/live/{*path}", proxy.handler())).await;
+
+    let mut request = tokio_tungstenite::tungstenite::client::IntoClientRequest::into_client_request(
+        format!("ws://{}/live/ws", gateway)
+    ).unwrap();
+    request.headers_mut().insert("sec-websocket-protocol", "chat".parse().unwrap());
+    let (mut socket, response) = tokio_tungstenite::connect_async(request).await.unwrap();
+    assert_eq!(response.headers()["sec-websocket-protocol"], "chat");
+    socket.send(Message::Text("hello".into())).await.unwrap();
+    let reply = tokio::time::timeout(Duration::from_secs(2), socket.next()).await.unwrap().unwrap().unwrap();
+    assert_eq!(reply, Message::Text("echo hello".into()));
+    socket.close(None).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_forwarded_headers() {
+    let upstream = upstream(Arc::default()).await;
+    let proxy = ReverseProxy::new(&format!("http://{}", upstream)).unwrap();
+    let trusting = proxy.clone().trust_forwarded_headers(true);
+    // `HttpServer` adds the listener scheme the same way behind its TLS listener
+    let gateway = serve(Router::new().route("/{*path}", proxy.handler()).layer(axum::Extension(Scheme::HTTPS))).await;
+    let trusted = serve(Router::new().route("/{*path}", trusting.handler())).await;
+
+    let client = reqwest::Client::new();
+    let get = |gateway: &str| client.get(format!("http://{}/items", gateway))
+        .header("x-forwarded-host", "spoofed.example.com")
+        .header("x-forwarded-proto", "ftp")
+        .send();
+    let body = get(&gateway).await.unwrap().text().await.unwrap();
+    assert!(body.contains(&format!("xfh={} xfp=https ", gateway)), "{}", body);
+    let body = get(&trusted).await.unwrap().text().await.unwrap();
+    assert!(body.contains("xfh=spoofed.example.com xfp=ftp "), "{}", body);
+}
+
+#[tokio::test]
+async fn test_large_bodies_are_streamed_without_retries() {
+    let upstream = upstream(Arc::default()).await;
+    let proxy = ReverseProxy::new(&format!("http://{}", upstream)).unwrap().retries(2);
+    let gateway = serve(Router::new().route("/{*path}", proxy.handler())).await;
+    let client = reqwest::Client::new();
+
+    let large = "x".repeat(2 * 1024 * 1024);
+    let response = client.put(format!("http://{}/upload", gateway)).body(large.clone()).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().await.unwrap().ends_with(&format!("body={}", large)));
+
+    // Chunked bodies have no known length either
+    let chunks = futures_util::stream::iter(["chunked ", "upload"].map(Ok::<_, std::io::Error>));
+    let response = client.put(format!("http://{}/upload", gateway))
+        .body(reqwest::Body::wrap_stream(chunks))
+        .send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().await.unwrap().ends_with("body=chunked upload"));
+}
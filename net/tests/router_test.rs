@@ -0,0 +1,82 @@
+//! # router_test
+//!
+//! router_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! HTTP router tests
+
+use axum::body::Body;
+use axum::http::{Method, StatusCode};
+use rf_net::http::{router_helpers, to_axum_path, HttpServer, RadixRouter, Request, RouteParams};
+use tower::ServiceExt;
+
+#[test]
+fn test_radix_router_matching() {
+    let mut router = RadixRouter::new();
+    router.insert("GET", "/users/:id", "user").unwrap();
+    router.insert("GET", "/users/me", "me").unwrap();
+    router.insert("GET", "/users/:id/posts/:post_id", "post").unwrap();
+    router.insert("GET", "/files/*path", "file").unwrap();
+
+    let matched = router.at("GET", "/users/42").unwrap();
+    assert_eq!(*matched.value, "user");
+    assert_eq!(matched.params.get("id"), Some("42"));
+
+    assert_eq!(*router.at("GET", "/users/me").unwrap().value, "me");
+
+    let matched = router.at("GET", "/users/1/posts/2").unwrap();
+    assert_eq!(matched.params.get("post_id"), Some("2"));
+
+    let matched = router.at("GET", "/files/a/b/c.txt").unwrap();
+    assert_eq!(matched.pattern, "/files/*path");
+    assert_eq!(matched.params.get("path"), Some("a/b/c.txt"));
+
+    assert!(router.at("POST", "/users/42").is_none());
+    assert!(router.at("GET", "/users/42/other").is_none());
+}
+
+#[test]
+fn test_radix_router_conflicts() {
+    let mut router = RadixRouter::new();
+    router.insert("GET", "/users/:id", ()).unwrap();
+    assert!(router.insert("GET", "/users/:id", ()).is_err());
+    assert!(router.insert("GET", "/users/:user_id/posts", ()).is_err());
+    assert!(router.insert("POST", "/users/:id", ()).is_ok());
+    assert!(router.insert("GET", "/files/*path/more", ()).is_err());
+    assert!(router_helpers::detect_conflict("/a/:x", "GET", "/a/:y", "get"));
+    assert!(!router_helpers::detect_conflict("/a/:x", "GET", "/a/static", "GET"));
+}
+
+#[test]
+fn test_route_params_and_axum_path() {
+    let params = RouteParams::from_pattern("/files/*path");
+    assert_eq!(params.extract("/files/a/b").unwrap().get("path").map(String::as_str), Some("a/b"));
+    assert_eq!(to_axum_path("/users/:id/files/*path").unwrap(), "/users/{id}/files/{*path}");
+}
+
+#[tokio::test]
+async fn test_request_param() {
+    async fn handler(request: Request) -> String {
+        format!("{}:{}", request.param("id").unwrap_or(""), request.param("path").unwrap_or(""))
+    }
+
+    let mut server = HttpServer::new("127.0.0.1:0".parse().unwrap())
+        .route(Method::GET, "/users/:id/files/*path", handler)
+        .unwrap();
+    let router = server.router().clone();
+
+    let response = router
+        .oneshot(axum::extract::Request::builder().uri("/users/7/files/a/b").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+    assert_eq!(&body[..], b"7:a/b");
+
+    assert!(HttpServer::new("127.0.0.1:0".parse().unwrap())
+        .route(Method::GET, "/users/:id", handler).unwrap()
+        .route(Method::GET, "/users/:name", handler)
+        .is_err());
+}
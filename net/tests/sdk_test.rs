@@ -0,0 +1,182 @@
+//! # sdk_test
+//!
+//! sdk_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! SDK generation tests
+
+use axum::extract::{Path, Query};
+use axum::http::Method;
+use axum::Json;
+use rf_net::http::HttpServer;
+use rf_net::oai::{ApiOperation, ApiSchema, OpenApiBuilder, SdkGenerator};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// An order
+#[derive(Serialize, Deserialize, ApiSchema)]
+#[serde(rename_all = "camelCase")]
+struct Order {
+    id: u64,
+    status: OrderStatus,
+    /// Contact e-mail
+    contact: Option<String>,
+    #[serde(rename = "type")]
+    kind: String,
+    parent: Option<Box<Order>>,
+}
+
+#[derive(Serialize, Deserialize, ApiSchema)]
+#[serde(rename_all = "snake_case")]
+enum OrderStatus {
+    Pending,
+    InProgress,
+}
+
+#[derive(Deserialize, ApiSchema)]
+#[allow(dead_code)]
+struct OrderFilter {
+    status: Option<String>,
+    limit: Option<u32>,
+}
+
+async fn get_order(Path(_id): Path<u64>) -> Json<serde_json::Value> {
+    Json(json!({}))
+}
+
+async fn list_orders(Query(_filter): Query<OrderFilter>) -> Json<Vec<serde_json::Value>> {
+    Json(Vec::new())
+}
+
+async fn create_order(Json(order): Json<Order>) -> Json<Order> {
+    Json(order)
+}
+
+async fn delete_order(Path(_id): Path<u64>) {}
+
+fn server() -> HttpServer {
+    HttpServer::new("127.0.0.1:0".parse().unwrap())
+        .with_openapi(OpenApiBuilder::new("Shop", "1.2.0").server("https://shop.example.com/", "production"))
+        .api_route(
+            Method::GET,
+            "/orders/:id",
+            get_order,
+            ApiOperation::new()
+                .summary("Get an order")
+                .path_param::<u64>("id", "Order ID")
+                .response::<Order>(200, "The order")
+                .response_empty(404, "Not found"),
+        )
+        .unwrap()
+        .api_route(
+            Method::GET,
+            "/orders",
+            list_orders,
+            ApiOperation::new().operation_id("listOrders").query::<OrderFilter>().response::<Vec<Order>>(200, "Orders"),
+        )
+        .unwrap()
+        .api_route(
+            Method::POST,
+            "/orders",
+            create_order,
+            ApiOperation::new()
+                .request_body::<Order>()
+                .header::<String>("Idempotency-Key", "")
+                .response::<Order>(201, "Created"),
+        )
+        .unwrap()
+        .api_route(Method::DELETE, "/orders/:id", delete_order, ApiOperation::new().deprecated())
+        .unwrap()
+}
+
+fn file<'a>(files: &'a [rf_net::oai::GeneratedFile], path: &str) -> &'a str {
+    &files.iter().find(|f| f.path.to_str() == Some(path)).unwrap().contents
+}
+
+#[test]
+fn test_generate_rust() {
+    let files = SdkGenerator::from_server(&server()).unwrap().rf_path("../rf").generate_rust().unwrap();
+
+    let cargo = file(&files, "Cargo.toml");
+    assert!(cargo.contains("name = \"shop-client\""));
+    assert!(cargo.contains("version = \"1.2.0\""));
+    assert!(cargo.contains("rf-contrib-sdk-httpclient = { path = \"../rf/contrib/sdk/httpclient\" }"));
+
+    let models = file(&files, "src/models.rs");
+    assert!(models.contains("/// An order\n#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct Order {"));
+    assert!(models.contains("    pub id: u64,\n"));
+    assert!(models.contains("    pub status: OrderStatus,\n"));
+    assert!(models.contains("    /// Contact e-mail\n    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub contact: Option<String>,\n"));
+    // serde strips the raw identifier prefix
+    assert!(models.contains("    pub status: OrderStatus,\n    pub r#type: String,\n"));
+    assert!(models.contains("pub parent: Option<Box<Order>>,"));
+    assert!(models.contains("    #[serde(rename = \"in_progress\")]\n    InProgress,\n"));
+    assert!(models.contains("#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]\npub struct ListOrdersQuery {"));
+
+    let lib = file(&files, "src/lib.rs");
+    assert!(lib.contains("pub struct ShopClient {"));
+    assert!(lib.contains("pub const DEFAULT_BASE_URL: &'static str = \"https://shop.example.com/\";"));
+    assert!(lib.contains(
+        "    /// Get an order\n    ///\n    /// `GET /orders/{id}`\n    pub async fn get_orders_by_id(&self, id: u64) -> Result<Order> {\n        \
+         let request = self.request(Method::GET, &format!(\"/orders/{}\", encode_path(&id.to_string())));\n        \
+         request.send_json().await\n    }"
+    ));
+    assert!(lib.contains("pub async fn list_orders(&self, query: &ListOrdersQuery) -> Result<Vec<Order>>"));
+    assert!(lib.contains("pub async fn post_orders(&self, body: &Order, idempotency_key: &str) -> Result<Order>"));
+    assert!(lib.contains("let request = request.header(\"Idempotency-Key\", idempotency_key);"));
+    // Undeclared path parameters are strings
+    assert!(lib.contains("    #[deprecated]\n    pub async fn delete_orders_by_id(&self, id: &str) -> Result<()>"));
+    assert!(lib.contains("&format!(\"/orders/{}\", encode_path(id))"));
+    assert!(lib.contains("request.send_text().await.map(|_| ())"));
+}
+
+#[test]
+fn test_generate_typescript() {
+    let ts = SdkGenerator::from_server(&server()).unwrap().generate_typescript().unwrap().contents;
+    assert!(ts.contains("export interface Order {"));
+    assert!(ts.contains("  contact?: string | null;"));
+    assert!(ts.contains("export type OrderStatus = \"pending\" | \"in_progress\";"));
+    assert!(ts.contains("export class ShopClient {"));
+    assert!(ts.contains("constructor(baseUrl: string = \"https://shop.example.com/\""));
+    assert!(ts.contains(
+        "  async getOrdersById(id: number): Promise<Order> {\n    \
+         return this.request<Order>(\"GET\", `/orders/${encodeURIComponent(String(id))}`, { response: \"json\" });"
+    ));
+    assert!(ts.contains("async listOrders(query?: { limit?: number | null; status?: string | null }): Promise<Order[]>"));
+    assert!(ts.contains("async postOrders(body: Order, idempotencyKey: string): Promise<Order>"));
+}
+
+#[test]
+fn test_generate_from_document() {
+    let document = json!({
+        "openapi": "3.0.3",
+        "info": {"title": "user-service", "version": "v2"},
+        "paths": {
+            "/users/{userId}/avatar": {
+                "parameters": [{"name": "userId", "in": "path", "required": true, "schema": {"type": "string"}}],
+                "put": {
+                    "requestBody": {"content": {"image/png": {"schema": {"type": "string", "format": "binary"}}}},
+                    "responses": {"204": {"description": "Stored"}}
+                }
+            }
+        }
+    });
+    let generator = SdkGenerator::new(document).client_name("Users");
+    let files = generator.generate_rust().unwrap();
+    assert!(file(&files, "Cargo.toml").contains("name = \"user-service-client\""));
+    // Not semver
+    assert!(file(&files, "Cargo.toml").contains("version = \"0.1.0\""));
+    let lib = file(&files, "src/lib.rs");
+    assert!(lib.contains("pub struct Users {"));
+    assert!(lib.contains("pub async fn put_users_by_user_id_avatar(&self, user_id: &str, body: Vec<u8>) -> Result<()>"));
+    assert!(lib.contains("request.header(\"content-type\", \"image/png\").body(body)"));
+
+    let dir = tempfile::tempdir().unwrap();
+    generator.write_rust(dir.path()).unwrap();
+    assert!(dir.path().join("src/models.rs").exists());
+
+    assert!(SdkGenerator::new(json!({"swagger": "2.0"})).generate_rust().is_err());
+    assert!(SdkGenerator::from_server(&HttpServer::new("127.0.0.1:0".parse().unwrap())).is_err());
+}
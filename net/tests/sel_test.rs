@@ -0,0 +1,221 @@
+//! Load balancer tests
+
+use axum::http::HeaderMap;
+use rf_contrib_registry::{ServiceHealth, ServiceInstance, ServiceRegistry};
+use rf_errors::Result;
+use rf_net::{Affinity, LoadBalancer, Strategy};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn addr(port: u16) -> SocketAddr {
+    SocketAddr::from(([10, 0, 0, 1], port))
+}
+
+fn instance(id: &str, port: u16, health: ServiceHealth) -> ServiceInstance {
+    ServiceInstance {
+        id: id.to_string(),
+        name: "orders".to_string(),
+        address: addr(port),
+        metadata: HashMap::new(),
+        health,
+    }
+}
+
+type Callback = Box<dyn Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync>;
+
+/// Registry whose watch callback the test fires by hand
+#[derive(Default)]
+struct TestRegistry {
+    instances: Mutex<Vec<ServiceInstance>>,
+    callback: Mutex<Option<Callback>>,
+}
+
+impl TestRegistry {
+    fn publish(&self, instances: Vec<ServiceInstance>) {
+        *self.instances.lock().unwrap() = instances.clone();
+        if let Some(callback) = self.callback.lock().unwrap().as_ref() {
+            callback(instances).unwrap();
+        }
+    }
+}
+
+impl ServiceRegistry for TestRegistry {
+    fn register(&self, instance: &ServiceInstance) -> Result<()> {
+        self.instances.lock().unwrap().push(instance.clone());
+        Ok(())
+    }
+
+    fn deregister(&self, service_id: &str) -> Result<()> {
+        self.instances.lock().unwrap().retain(|instance| instance.id != service_id);
+        Ok(())
+    }
+
+    fn discover(&self, _service_name: &str) -> Result<Vec<ServiceInstance>> {
+        Ok(self.instances.lock().unwrap().clone())
+    }
+
+    fn list_services(&self) -> Result<Vec<String>> {
+        Ok(vec!["orders".to_string()])
+    }
+
+    fn watch<F>(&self, _service_name: &str, callback: F) -> Result<()>
+    where
+        F: Fn(Vec<ServiceInstance>) -> Result<()> + Send + Sync + 'static,
+    {
+        *self.callback.lock().unwrap() = Some(Box::new(callback));
+        Ok(())
+    }
+}
+
+#[test]
+fn test_strategies_and_leases() {
+    let balancer = LoadBalancer::new(Strategy::RoundRobin);
+    assert!(balancer.select(None).is_none());
+    balancer.add("a", addr(1));
+    balancer.add("b", addr(2));
+    let ids: Vec<String> = (0..4).map(|_| balancer.select(None).unwrap().id().to_string()).collect();
+    assert_eq!(ids, ["a", "b", "a", "b"]);
+
+    let least = LoadBalancer::new(Strategy::LeastConnection);
+    least.add("a", addr(1));
+    least.add("b", addr(2));
+    let first = least.select(None).unwrap();
+    let second = least.select(None).unwrap();
+    assert_ne!(first.id(), second.id());
+    assert_eq!(least.get(first.id()).unwrap().in_flight(), 1);
+    let busy = first.id().to_string();
+    drop(second);
+    // The idle endpoint wins until the lease is released
+    for _ in 0..3 {
+        assert_ne!(least.select(None).unwrap().id(), busy);
+    }
+    drop(first);
+    assert_eq!(least.get(&busy).unwrap().in_flight(), 0);
+
+    let weighted = LoadBalancer::new(Strategy::Weighted);
+    weighted.add_weighted("a", addr(1), 3);
+    weighted.add_weighted("b", addr(2), 1);
+    let ids: Vec<String> = (0..8).map(|_| weighted.select(None).unwrap().id().to_string()).collect();
+    assert_eq!(ids.iter().filter(|id| *id == "a").count(), 6);
+    // Smooth: the heavy endpoint does not get three requests in a row at the start of a cycle
+    assert_eq!(&ids[..4], ["a", "a", "b", "a"]);
+}
+
+#[test]
+fn test_cookie_affinity() {
+    let balancer = LoadBalancer::new(Strategy::RoundRobin).affinity(Affinity::Cookie {
+        name: "RFROUTE".to_string(),
+        max_age: Some(3600),
+    });
+    balancer.add("node-1", addr(1));
+    balancer.add("node-2", addr(2));
+
+    let first = balancer.select_for(&HeaderMap::new()).unwrap();
+    let cookie = first.set_cookie().unwrap().to_str().unwrap().to_string();
+    assert!(cookie.starts_with("RFROUTE=") && cookie.ends_with("; Max-Age=3600"), "{}", cookie);
+    assert!(!cookie.contains(first.id()));
+    let pinned = first.id().to_string();
+    drop(first);
+
+    let mut headers = HeaderMap::new();
+    let value = cookie.split(';').next().unwrap();
+    headers.insert("cookie", format!("theme=dark; {}", value).parse().unwrap());
+    for _ in 0..3 {
+        let lease = balancer.select_for(&headers).unwrap();
+        assert_eq!(lease.id(), pinned);
+        assert!(lease.set_cookie().is_none());
+    }
+
+    // A draining endpoint takes no new requests, its sessions move
+    balancer.drain(&pinned);
+    let moved = balancer.select_for(&headers).unwrap();
+    assert_ne!(moved.id(), pinned);
+    assert!(moved.set_cookie().is_some());
+}
+
+#[test]
+fn test_header_affinity() {
+    let balancer = LoadBalancer::new(Strategy::RoundRobin).affinity(Affinity::header("X-User-Id"));
+    for (i, id) in ["a", "b", "c", "d"].iter().enumerate() {
+        balancer.add(id, addr(i as u16));
+    }
+    let route = |user: &str| {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-user-id", user.parse().unwrap());
+        balancer.select_for(&headers).unwrap().id().to_string()
+    };
+    let users: Vec<String> = (0..200).map(|i| format!("user-{}", i)).collect();
+    let before: Vec<String> = users.iter().map(|user| route(user)).collect();
+    assert_eq!(before, users.iter().map(|user| route(user)).collect::<Vec<_>>());
+    assert!(["a", "b", "c", "d"].iter().all(|id| before.iter().any(|chosen| chosen == id)));
+
+    // Only the users of the drained endpoint move
+    balancer.drain("c");
+    for (user, chosen) in users.iter().zip(&before) {
+        let now = route(user);
+        if chosen == "c" {
+            assert_ne!(now, "c");
+        } else {
+            assert_eq!(&now, chosen);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_drain_waits_for_in_flight() {
+    let balancer = Arc::new(LoadBalancer::new(Strategy::RoundRobin));
+    balancer.add("a", addr(1));
+    let lease = balancer.select(None).unwrap();
+    assert!(balancer.drain("a"));
+    assert!(balancer.select(None).is_none());
+    assert!(!balancer.wait_drained("a", Duration::from_millis(50)).await);
+
+    let waiter = {
+        let balancer = balancer.clone();
+        tokio::spawn(async move { balancer.wait_drained("a", Duration::from_secs(5)).await })
+    };
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    drop(lease);
+    assert!(waiter.await.unwrap());
+
+    // Adding it back ends the drain
+    balancer.add("a", addr(1));
+    assert_eq!(balancer.select(None).unwrap().id(), "a");
+}
+
+#[test]
+fn test_registry_watch_rolling_deploy() {
+    let registry = TestRegistry::default();
+    registry.publish(vec![instance("v1-a", 1, ServiceHealth::Healthy), instance("v1-b", 2, ServiceHealth::Healthy)]);
+    let balancer = Arc::new(LoadBalancer::new(Strategy::RoundRobin));
+    balancer.watch(&registry, "orders").unwrap();
+    assert_eq!(balancer.endpoints().len(), 2);
+
+    // v2-a comes up, v1-a goes away while serving a request
+    let in_flight = std::iter::repeat_with(|| balancer.select(None).unwrap()).find(|lease| lease.id() == "v1-a").unwrap();
+    let mut v2 = instance("v2-a", 3, ServiceHealth::Healthy);
+    v2.metadata.insert("weight".to_string(), "2".to_string());
+    registry.publish(vec![v2, instance("v1-b", 2, ServiceHealth::Healthy)]);
+    assert!(balancer.get("v1-a").unwrap().is_draining());
+    assert_eq!(balancer.get("v2-a").unwrap().weight(), 2);
+    for _ in 0..4 {
+        assert_ne!(balancer.select(None).unwrap().id(), "v1-a");
+    }
+
+    // Removed once its last request finishes
+    drop(in_flight);
+    balancer.select(None).unwrap();
+    assert!(balancer.get("v1-a").is_none());
+
+    // Unhealthy and explicitly draining instances are drained but kept
+    let mut draining = instance("v2-a", 3, ServiceHealth::Healthy);
+    draining.metadata.insert("draining".to_string(), "true".to_string());
+    registry.publish(vec![draining, instance("v1-b", 2, ServiceHealth::Unhealthy)]);
+    assert!(balancer.select(None).is_none());
+    assert_eq!(balancer.endpoints().len(), 2);
+    registry.publish(vec![instance("v1-b", 2, ServiceHealth::Healthy)]);
+    assert_eq!(balancer.select(None).unwrap().id(), "v1-b");
+    assert!(balancer.get("v2-a").is_none());
+}
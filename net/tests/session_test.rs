@@ -0,0 +1,121 @@
+//! Session middleware tests
+
+use axum::body::Body;
+use axum::http::{header, Request as HttpRequest, Response, StatusCode};
+use axum::routing::{get, post};
+use axum::Router;
+use rf_net::http::{session_middleware, SameSite, SessionCookie, SessionHandle, SessionMiddleware};
+use rf_os::session::SessionManager;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+fn app(sessions: SessionMiddleware) -> Router {
+    Router::new()
+        .route("/", get(|| async { "anonymous" }))
+        .route(
+            "/visit",
+            get(|session: SessionHandle| async move {
+                let visits = session.get::<u32>("visits").unwrap_or(0) + 1;
+                session.insert("visits", visits).unwrap();
+                visits.to_string()
+            }),
+        )
+        .route(
+            "/login",
+            post(|session: SessionHandle| async move {
+                session.regenerate();
+                session.insert("user", "alice").unwrap();
+            }),
+        )
+        .route("/logout", post(|session: SessionHandle| async move { session.destroy() }))
+        .layer(axum::middleware::from_fn_with_state(Arc::new(sessions), session_middleware))
+}
+
+async fn send(app: &Router, method: &str, uri: &str, cookie: Option<&str>) -> Response<Body> {
+    let mut request = HttpRequest::builder().method(method).uri(uri);
+    if let Some(cookie) = cookie {
+        request = request.header(header::COOKIE, cookie);
+    }
+    app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+fn set_cookie(response: &Response<Body>) -> Option<String> {
+    response.headers().get(header::SET_COOKIE).map(|v| v.to_str().unwrap().to_string())
+}
+
+fn cookie_pair(set_cookie: &str) -> String {
+    set_cookie.split(';').next().unwrap().to_string()
+}
+
+async fn body(response: Response<Body>) -> String {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_session_cookie_lifecycle() {
+    let manager = Arc::new(SessionManager::new().with_ttl(Duration::from_secs(600)));
+    let app = app(SessionMiddleware::new(manager.clone()));
+
+    // Nothing stored, nothing issued
+    assert!(set_cookie(&send(&app, "GET", "/", None).await).is_none());
+
+    let response = send(&app, "GET", "/visit", None).await;
+    let issued = set_cookie(&response).unwrap();
+    assert!(issued.starts_with("RFSESSID="));
+    assert!(issued.contains("Max-Age=600") && issued.contains("HttpOnly") && issued.contains("SameSite=Lax"));
+    assert!(!issued.contains("Secure"));
+    let cookie = cookie_pair(&issued);
+
+    let response = send(&app, "GET", "/visit", Some(&format!("theme=dark; {}", cookie))).await;
+    assert!(set_cookie(&response).is_none());
+    assert_eq!(body(response).await, "2");
+
+    // Login moves the data to a new id and drops the old one
+    let response = send(&app, "POST", "/login", Some(&cookie)).await;
+    let renewed = cookie_pair(&set_cookie(&response).unwrap());
+    assert_ne!(renewed, cookie);
+    assert_eq!(body(send(&app, "GET", "/visit", Some(&cookie)).await).await, "1");
+    assert_eq!(body(send(&app, "GET", "/visit", Some(&renewed)).await).await, "3");
+
+    let response = send(&app, "POST", "/logout", Some(&renewed)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(set_cookie(&response).unwrap().contains("Max-Age=0"));
+    assert_eq!(body(send(&app, "GET", "/visit", Some(&renewed)).await).await, "1");
+}
+
+#[tokio::test]
+async fn test_session_cookie_options() {
+    let manager = Arc::new(SessionManager::new().with_ttl(Duration::from_secs(60)).with_sliding_expiration());
+    let cookie = SessionCookie::new()
+        .name("SID")
+        .domain("example.com")
+        .same_site(SameSite::None)
+        .persistent(false);
+    let app = app(SessionMiddleware::new(manager).cookie(cookie));
+
+    let issued = set_cookie(&send(&app, "GET", "/visit", None).await).unwrap();
+    assert!(issued.starts_with("SID="));
+    assert!(issued.contains("Domain=example.com") && issued.contains("Secure") && issued.contains("SameSite=None"));
+    assert!(!issued.contains("Max-Age"));
+
+    // Sliding sessions refresh the cookie on every request
+    let response = send(&app, "GET", "/", Some(&cookie_pair(&issued))).await;
+    assert_eq!(set_cookie(&response).map(|c| cookie_pair(&c)), Some(cookie_pair(&issued)));
+}
+
+#[test]
+fn test_session_config() {
+    let config: rf_net::http::HttpSessionConfig = serde_json::from_value(serde_json::json!({
+        "ttl": 86400,
+        "storage": { "type": "redis", "url": "redis://127.0.0.1:6379" },
+        "cookie": { "secure": true, "same_site": "Strict" }
+    }))
+    .unwrap();
+    assert_eq!(config.session.ttl.as_secs(), 86400);
+    assert!(matches!(config.session.storage, rf_os::session::SessionStorageConfig::Redis { ref prefix, .. } if prefix == "session:"));
+    assert!(config.cookie.secure && config.cookie.http_only);
+    assert_eq!(config.cookie.same_site, SameSite::Strict);
+    assert_eq!(config.cookie.name, "RFSESSID");
+}
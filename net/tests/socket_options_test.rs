@@ -0,0 +1,73 @@
+//! Socket option tests
+
+use rf_net::{SocketOptions, TcpClient, TcpServer};
+use socket2::SockRef;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_options_applied_to_connections() {
+    let options = SocketOptions::new()
+        .nodelay(true)
+        .keepalive(Duration::from_secs(30))
+        .keepalive_interval(Duration::from_secs(5))
+        .keepalive_retries(3)
+        .recv_buffer_size(64 * 1024);
+    let server = TcpServer::bind_with("127.0.0.1:0".parse().unwrap(), options.clone()).unwrap();
+    let addr = server.local_addr().unwrap().to_string();
+
+    let (client, accepted) = tokio::join!(TcpClient::connect_with(&addr, &options), server.accept());
+    let client = client.unwrap();
+    let (accepted, _) = accepted.unwrap();
+    for stream in [&client, &accepted] {
+        assert!(stream.nodelay().unwrap());
+        let socket = SockRef::from(stream);
+        assert!(socket.keepalive().unwrap());
+        #[cfg(target_os = "linux")]
+        {
+            assert_eq!(socket.tcp_keepalive_time().unwrap(), Duration::from_secs(30));
+            assert_eq!(socket.tcp_keepalive_interval().unwrap(), Duration::from_secs(5));
+            assert_eq!(socket.tcp_keepalive_retries().unwrap(), 3);
+        }
+        // The kernel may round the buffer size up (Linux doubles it)
+        assert!(socket.recv_buffer_size().unwrap() >= 64 * 1024);
+    }
+
+    // Without options the OS defaults apply
+    let plain = TcpClient::connect(&addr).await.unwrap();
+    assert!(!SockRef::from(&plain).keepalive().unwrap());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_reuse_port_allows_several_listeners() {
+    let options = SocketOptions::new().reuse_port(true);
+    let first = TcpServer::bind_with("127.0.0.1:0".parse().unwrap(), options.clone()).unwrap();
+    let addr = first.local_addr().unwrap();
+    let second = TcpServer::bind_with(addr, options).unwrap();
+    assert_eq!(second.local_addr().unwrap(), addr);
+
+    // Without SO_REUSEPORT the port is taken
+    assert!(TcpServer::bind_with(addr, SocketOptions::new()).is_err());
+}
+
+#[test]
+fn test_options_from_config_values() {
+    let options: SocketOptions = serde_json::from_value(serde_json::json!({
+        "nodelay": true,
+        "keepalive": "1m",
+        "keepalive_interval": "10s",
+        "reuse_port": true,
+        "backlog": 4096
+    }))
+    .unwrap();
+    assert_eq!(
+        options,
+        SocketOptions::new()
+            .nodelay(true)
+            .keepalive(Duration::from_secs(60))
+            .keepalive_interval(Duration::from_secs(10))
+            .reuse_port(true)
+            .backlog(4096)
+    );
+    assert!(options.reuse_address);
+}
@@ -0,0 +1,244 @@
+//! TLS TCP server and client tests
+
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::Rsa;
+use openssl::x509::extension::{BasicConstraints, SubjectAlternativeName};
+use openssl::x509::{X509NameBuilder, X509};
+use rf_net::{TlsClientConfig, TlsServerConfig, TlsTcpServer, TlsTcpStream};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+struct Pki {
+    ca: Vec<u8>,
+    cert: Vec<u8>,
+    key: Vec<u8>,
+}
+
+fn certificate(common_name: &str, issuer: Option<(&X509, &PKey<Private>)>) -> (X509, PKey<Private>) {
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    let name = name.build();
+
+    let mut cert = X509::builder().unwrap();
+    cert.set_version(2).unwrap();
+    cert.set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap()).unwrap();
+    cert.set_subject_name(&name).unwrap();
+    cert.set_pubkey(&key).unwrap();
+    cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+    cert.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+    match issuer {
+        Some((ca, ca_key)) => {
+            let san = SubjectAlternativeName::new()
+                .dns("localhost")
+                .ip("127.0.0.1")
+                .build(&cert.x509v3_context(Some(ca), None))
+                .unwrap();
+            cert.append_extension(san).unwrap();
+            cert.set_issuer_name(ca.subject_name()).unwrap();
+            cert.sign(ca_key, MessageDigest::sha256()).unwrap();
+        }
+        None => {
+            cert.append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+                .unwrap();
+            cert.set_issuer_name(&name).unwrap();
+            cert.sign(&key, MessageDigest::sha256()).unwrap();
+        }
+    }
+    (cert.build(), key)
+}
+
+/// CA plus a leaf certificate for localhost/127.0.0.1 signed by it
+fn pki(common_name: &str) -> Pki {
+    let (ca, ca_key) = certificate("test ca", None);
+    let (cert, key) = certificate(common_name, Some((&ca, &ca_key)));
+    Pki {
+        ca: ca.to_pem().unwrap(),
+        cert: cert.to_pem().unwrap(),
+        key: key.private_key_to_pem_pkcs8().unwrap(),
+    }
+}
+
+/// Echo server; returns its address
+async fn echo_server(config: TlsServerConfig) -> SocketAddr {
+    spawn_echo(TlsTcpServer::bind("127.0.0.1:0", config).await.unwrap())
+}
+
+fn spawn_echo(server: TlsTcpServer) -> SocketAddr {
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let Ok((handshake, _)) = server.accept_tcp().await else { break };
+            tokio::spawn(async move {
+                let Ok(mut stream) = handshake.finish().await else { return };
+                let mut buf = [0u8; 1024];
+                loop {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => stream.write_all(&buf[..n]).await.unwrap(),
+                    }
+                }
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+    addr
+}
+
+#[tokio::test]
+async fn test_tls_echo_with_verification() {
+    let server = pki("server");
+    let addr = echo_server(TlsServerConfig::from_pem(server.cert.clone(), server.key.clone())).await;
+
+    let client = TlsClientConfig::new().ca_pem(server.ca.clone());
+    let mut stream = client.connect(&addr.to_string()).await.unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    assert!(stream.peer_certificate().is_some());
+
+    stream.write_all(b"hello tls").await.unwrap();
+    let mut buf = [0u8; 9];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"hello tls");
+
+    // Large payloads span several records
+    let payload = vec![7u8; 200_000];
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let expected = payload.clone();
+    let write = tokio::spawn(async move {
+        writer.write_all(&payload).await.unwrap();
+        writer
+    });
+    let mut echoed = vec![0u8; expected.len()];
+    reader.read_exact(&mut echoed).await.unwrap();
+    assert_eq!(echoed, expected);
+    let mut stream = reader.unsplit(write.await.unwrap());
+    stream.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tls_certificate_verification() {
+    let server = pki("server");
+    let addr = echo_server(TlsServerConfig::from_pem(server.cert.clone(), server.key.clone())).await;
+
+    // Unknown CA
+    let err = TlsClientConfig::new().connect(&addr.to_string()).await.err().unwrap();
+    assert!(err.to_string().contains("certificate verification failed"), "{}", err);
+
+    // Name not in the certificate
+    let wrong_name = TlsClientConfig::new().ca_pem(server.ca.clone()).server_name("example.com");
+    assert!(wrong_name.connect(&addr.to_string()).await.is_err());
+    let relaxed = wrong_name.danger_accept_invalid_hostnames(true);
+    assert!(relaxed.connect(&addr.to_string()).await.is_ok());
+
+    let insecure = TlsClientConfig::new().danger_accept_invalid_certs(true);
+    assert!(insecure.connect(&addr.to_string()).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_tls_alpn() {
+    let server = pki("server");
+    let config = TlsServerConfig::from_pem(server.cert.clone(), server.key.clone()).alpn(&["rf/2", "rf/1"]);
+    let addr = echo_server(config).await;
+    let client = TlsClientConfig::new().ca_pem(server.ca.clone());
+
+    let stream = client.clone().alpn(&["rf/1", "rf/2"]).connect(&addr.to_string()).await.unwrap();
+    assert_eq!(stream.alpn_protocol(), Some(&b"rf/2"[..]));
+
+    let stream = client.clone().alpn(&["rf/1"]).connect(&addr.to_string()).await.unwrap();
+    assert_eq!(stream.alpn_protocol(), Some(&b"rf/1"[..]));
+
+    let stream = client.clone().connect(&addr.to_string()).await.unwrap();
+    assert_eq!(stream.alpn_protocol(), None);
+
+    assert!(client.alpn(&["other"]).connect(&addr.to_string()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_tls_mutual() {
+    let server = pki("server");
+    let client_pki = pki("client");
+    let config = TlsServerConfig::from_pem(server.cert.clone(), server.key.clone()).client_ca_pem(client_pki.ca.clone());
+
+    let tls_server = TlsTcpServer::bind("127.0.0.1:0", config).await.unwrap();
+    let addr = tls_server.local_addr().unwrap().to_string();
+    let accepted = tokio::spawn(async move {
+        let (stream, _) = tls_server.accept().await.unwrap();
+        let der = stream.peer_certificate().unwrap();
+        let cert = X509::from_der(&der).unwrap();
+        let cn = cert.subject_name().entries().next().unwrap().data().as_utf8().unwrap().to_string();
+        // Without a client certificate the handshake fails
+        let rejected = tls_server.accept().await;
+        (cn, rejected.is_err())
+    });
+
+    let client = TlsClientConfig::new()
+        .ca_pem(server.ca.clone())
+        .client_cert_pem(client_pki.cert.clone(), client_pki.key.clone());
+    let mut stream = client.connect(&addr).await.unwrap();
+    stream.write_all(b"ping").await.unwrap();
+
+    let anonymous = TlsClientConfig::new().ca_pem(server.ca.clone());
+    // TLS 1.3 reports the missing certificate after the client handshake completes
+    if let Ok(mut stream) = anonymous.connect(&addr).await {
+        let mut buf = [0u8; 1];
+        assert!(stream.read(&mut buf).await.map(|n| n == 0).unwrap_or(true));
+    }
+
+    let (cn, rejected) = accepted.await.unwrap();
+    assert_eq!(cn, "client");
+    assert!(rejected);
+}
+
+#[tokio::test]
+async fn test_tls_invalid_certificate() {
+    let result = TlsTcpServer::bind("127.0.0.1:0", TlsServerConfig::from_pem("nope", "nope")).await;
+    assert!(result.is_err());
+    let result = TlsTcpServer::bind("127.0.0.1:0", TlsServerConfig::new("/missing.crt", "/missing.key")).await;
+    assert!(result.is_err());
+}
+
+fn common_name(stream: &TlsTcpStream) -> String {
+    let cert = X509::from_der(&stream.peer_certificate().unwrap()).unwrap();
+    cert.subject_name().entries().next().unwrap().data().as_utf8().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_tls_certificate_swap_and_reload() {
+    let dir = std::env::temp_dir().join(format!("rf-tcp-tls-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (cert_path, key_path) = (dir.join("server.crt"), dir.join("server.key"));
+    let first = pki("first");
+    std::fs::write(&cert_path, &first.cert).unwrap();
+    std::fs::write(&key_path, &first.key).unwrap();
+
+    let config = TlsServerConfig::new(&cert_path, &key_path).alpn(&["rf/1"]);
+    let server = TlsTcpServer::bind("127.0.0.1:0", config).await.unwrap();
+    let acceptor = server.acceptor();
+    let addr = spawn_echo(server).to_string();
+    let client = |ca: &[u8]| TlsClientConfig::new().ca_pem(ca.to_vec()).alpn(&["rf/1"]);
+
+    let stream = client(&first.ca).connect(&addr).await.unwrap();
+    assert_eq!(common_name(&stream), "first");
+    assert_eq!(stream.version(), "TLSv1.3");
+
+    // A bad certificate is rejected and the current one keeps serving
+    let second = pki("second");
+    assert!(acceptor.swap(b"nope", &second.key).is_err());
+    assert_eq!(common_name(&client(&first.ca).connect(&addr).await.unwrap()), "first");
+
+    // New connections get the new certificate, with the same ALPN settings
+    acceptor.swap(&second.cert, &second.key).unwrap();
+    let stream = client(&second.ca).connect(&addr).await.unwrap();
+    assert_eq!(common_name(&stream), "second");
+    assert_eq!(stream.alpn_protocol(), Some(&b"rf/1"[..]));
+
+    let third = pki("third");
+    std::fs::write(&cert_path, &third.cert).unwrap();
+    std::fs::write(&key_path, &third.key).unwrap();
+    acceptor.reload().unwrap();
+    assert_eq!(common_name(&client(&third.ca).connect(&addr).await.unwrap()), "third");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,121 @@
+//! TLS listener and certificate reload tests
+
+use axum::routing::get;
+use axum::Router;
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::rsa::Rsa;
+use openssl::x509::{X509NameBuilder, X509};
+use rf_net::http::{TlsAcceptorHandle, TlsListener};
+use rf_net::TlsClientConfig;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Self-signed certificate and PKCS#8 key as PEM
+fn self_signed(common_name: &str) -> (Vec<u8>, Vec<u8>) {
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    let name = name.build();
+
+    let mut cert = X509::builder().unwrap();
+    cert.set_version(2).unwrap();
+    cert.set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap()).unwrap();
+    cert.set_subject_name(&name).unwrap();
+    cert.set_issuer_name(&name).unwrap();
+    cert.set_pubkey(&key).unwrap();
+    cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+    cert.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+    cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+    (cert.build().to_pem().unwrap(), key.private_key_to_pem_pkcs8().unwrap())
+}
+
+/// Send a GET over TLS; returns the peer certificate CN and the response
+async fn get_over_tls(addr: SocketAddr) -> (String, String) {
+    let client = TlsClientConfig::new().danger_accept_invalid_certs(true).server_name("localhost");
+    let mut tls = client.connect(&addr.to_string()).await.unwrap();
+
+    let cert = X509::from_der(&tls.peer_certificate().unwrap()).unwrap();
+    let cn = cert
+        .subject_name()
+        .entries()
+        .next()
+        .unwrap()
+        .data()
+        .as_utf8()
+        .unwrap()
+        .to_string();
+
+    tls.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    tls.read_to_string(&mut response).await.unwrap();
+    (cn, response)
+}
+
+async fn serve(acceptor: Arc<TlsAcceptorHandle>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let listener = TlsListener::new(listener, acceptor, Duration::from_secs(5)).unwrap();
+    let addr = axum::serve::Listener::local_addr(&listener).unwrap();
+    let app = Router::new().route("/", get(|| async { "hello tls" }));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    addr
+}
+
+#[tokio::test]
+async fn test_tls_listener_and_swap() {
+    let (cert, key) = self_signed("first");
+    let acceptor = Arc::new(TlsAcceptorHandle::from_pem(&cert, &key).unwrap());
+    let addr = serve(acceptor.clone()).await;
+
+    let (cn, response) = get_over_tls(addr).await;
+    assert_eq!(cn, "first");
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with("hello tls"));
+
+    // Invalid data is rejected and the current certificate stays active
+    assert!(acceptor.swap(b"not a cert", &key).is_err());
+    assert!(acceptor.reload().is_err());
+    assert_eq!(get_over_tls(addr).await.0, "first");
+
+    let (cert, key) = self_signed("second");
+    acceptor.swap(&cert, &key).unwrap();
+    assert_eq!(get_over_tls(addr).await.0, "second");
+}
+
+#[tokio::test]
+async fn test_reload_on_file_change() {
+    let dir = std::env::temp_dir().join(format!("rf-tls-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (cert_path, key_path) = (dir.join("server.crt"), dir.join("server.key"));
+    let (cert, key) = self_signed("before");
+    std::fs::write(&cert_path, cert).unwrap();
+    std::fs::write(&key_path, key).unwrap();
+
+    let acceptor = Arc::new(TlsAcceptorHandle::from_pem_files(&cert_path, &key_path).unwrap());
+    let _watcher = acceptor.watch().unwrap();
+    let addr = serve(acceptor.clone()).await;
+    assert_eq!(get_over_tls(addr).await.0, "before");
+
+    let (cert, key) = self_signed("after");
+    std::fs::write(&key_path, key).unwrap();
+    std::fs::write(&cert_path, cert).unwrap();
+
+    let mut cn = String::new();
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        cn = get_over_tls(addr).await.0;
+        if cn == "after" {
+            break;
+        }
+    }
+    assert_eq!(cn, "after");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
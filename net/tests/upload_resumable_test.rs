@@ -0,0 +1,226 @@
+//! Resumable upload tests
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use rf_errors::Result;
+use rf_net::http::{
+    parse_metadata, LocalResumableStore, ResumableStore, ResumableUpload, ResumableUploads, UploadMeta, UploadSink,
+    UploadWriter,
+};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+type Files = Arc<Mutex<Vec<(String, Vec<u8>)>>>;
+
+/// Keeps complete files in memory, keyed by filename
+#[derive(Clone, Default)]
+struct MemorySink {
+    files: Files,
+}
+
+struct MemoryWriter {
+    sink: MemorySink,
+    name: String,
+    data: Vec<u8>,
+}
+
+#[async_trait]
+impl UploadSink for MemorySink {
+    async fn open(&self, meta: &UploadMeta) -> Result<Box<dyn UploadWriter>> {
+        Ok(Box::new(MemoryWriter { sink: self.clone(), name: meta.filename.clone(), data: Vec::new() }))
+    }
+
+    async fn remove(&self, _location: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UploadWriter for MemoryWriter {
+    async fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        self.data.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    async fn finish(self: Box<Self>) -> Result<String> {
+        let location = format!("mem://{}", self.name);
+        self.sink.files.lock().unwrap().push((self.name, self.data));
+        Ok(location)
+    }
+
+    async fn abort(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
+
+async fn send(app: &Router, method: &str, uri: &str, headers: &[(&str, &str)], body: &[u8]) -> axum::response::Response {
+    let mut request = Request::builder().method(method).uri(uri).header("tus-resumable", "1.0.0");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    app.clone().oneshot(request.body(Body::from(body.to_vec())).unwrap()).await.unwrap()
+}
+
+fn header<'a>(response: &'a axum::response::Response, name: &str) -> &'a str {
+    response.headers().get(name).map(|v| v.to_str().unwrap()).unwrap_or_default()
+}
+
+async fn patch(app: &Router, location: &str, offset: u64, data: &[u8], extra: &[(&str, &str)]) -> axum::response::Response {
+    let offset = offset.to_string();
+    let mut headers = vec![("content-type", "application/offset+octet-stream"), ("upload-offset", offset.as_str())];
+    headers.extend_from_slice(extra);
+    send(app, "PATCH", location, &headers, data).await
+}
+
+#[tokio::test]
+async fn test_resume_after_interruption() {
+    let dir = TempDir::new().unwrap();
+    let sink = MemorySink::default();
+    let completed = Arc::new(Mutex::new(Vec::<ResumableUpload>::new()));
+    let seen = completed.clone();
+    let uploads = Arc::new(
+        ResumableUploads::new(LocalResumableStore::new(dir.path()))
+            .sink(sink.clone())
+            .on_complete(move |upload| seen.lock().unwrap().push(upload.clone())),
+    );
+    let app = uploads.clone().router("/files");
+
+    let options = send(&app, "OPTIONS", "/files", &[], b"").await;
+    assert_eq!(options.status(), StatusCode::NO_CONTENT);
+    assert!(header(&options, "tus-extension").contains("checksum"));
+
+    // "report.csv" / "text/csv"
+    let created = send(&app, "POST", "/files", &[
+        ("upload-length", "11"),
+        ("upload-metadata", "filename cmVwb3J0LmNzdg==,filetype dGV4dC9jc3Y=,is_private"),
+    ], b"").await;
+    assert_eq!(created.status(), StatusCode::CREATED);
+    let location = header(&created, "location").to_string();
+    assert!(location.starts_with("/files/"));
+    assert!(!header(&created, "upload-expires").is_empty());
+
+    assert_eq!(patch(&app, &location, 0, b"hello", &[]).await.status(), StatusCode::NO_CONTENT);
+
+    // The client lost track of its progress and asks where to continue
+    let head = send(&app, "HEAD", &location, &[], b"").await;
+    assert_eq!(head.status(), StatusCode::OK);
+    assert_eq!(header(&head, "upload-offset"), "5");
+    assert_eq!(header(&head, "upload-length"), "11");
+    assert_eq!(header(&head, "cache-control"), "no-store");
+
+    assert_eq!(patch(&app, &location, 3, b"lo world", &[]).await.status(), StatusCode::CONFLICT);
+    assert_eq!(patch(&app, &location, 5, b" world and more", &[]).await.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let done = patch(&app, &location, 5, b" world", &[]).await;
+    assert_eq!(done.status(), StatusCode::NO_CONTENT);
+    assert_eq!(header(&done, "upload-offset"), "11");
+
+    assert_eq!(*sink.files.lock().unwrap(), [("report.csv".to_string(), b"hello world".to_vec())]);
+    let completed = completed.lock().unwrap().clone();
+    assert_eq!(completed.len(), 1);
+    assert_eq!(completed[0].location.as_deref(), Some("mem://report.csv"));
+    assert_eq!(completed[0].metadata["filetype"], "text/csv");
+    assert_eq!(completed[0].metadata["is_private"], "");
+
+    // The staged data is gone, the state still answers HEAD
+    let id = location.trim_start_matches("/files/");
+    assert!(!dir.path().join(format!("{}.bin", id)).exists());
+    assert_eq!(header(&send(&app, "HEAD", &location, &[], b"").await, "upload-offset"), "11");
+}
+
+#[tokio::test]
+async fn test_checksum_and_creation_with_upload() {
+    let dir = TempDir::new().unwrap();
+    let uploads = Arc::new(ResumableUploads::new(LocalResumableStore::new(dir.path())).max_size(1024));
+    let app = uploads.router("/files/");
+
+    assert_eq!(
+        send(&app, "POST", "/files", &[("upload-length", "2048")], b"").await.status(),
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    let created = send(&app, "POST", "/files", &[
+        ("upload-length", "8"),
+        ("content-type", "application/offset+octet-stream"),
+    ], b"abcd").await;
+    assert_eq!(created.status(), StatusCode::CREATED);
+    assert_eq!(header(&created, "upload-offset"), "4");
+    let location = header(&created, "location").to_string();
+
+    let digest = rf_encoding::base64::encode(&Sha256::digest(b"efgh"));
+    let wrong = format!("sha256 {}", rf_encoding::base64::encode(&Sha256::digest(b"xxxx")));
+    let mismatch = patch(&app, &location, 4, b"efgX", &[("upload-checksum", wrong.as_str())]).await;
+    assert_eq!(mismatch.status().as_u16(), 460);
+    assert_eq!(header(&send(&app, "HEAD", &location, &[], b"").await, "upload-offset"), "4");
+    assert_eq!(
+        patch(&app, &location, 4, b"efgh", &[("upload-checksum", "crc64 AAAA")]).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+
+    let good = format!("sha256 {}", digest);
+    assert_eq!(patch(&app, &location, 4, b"efgh", &[("upload-checksum", good.as_str())]).await.status(), StatusCode::NO_CONTENT);
+
+    // Without a sink the file stays in the store
+    let id = location.rsplit('/').next().unwrap();
+    let stored = LocalResumableStore::new(dir.path()).get(id).await.unwrap().unwrap();
+    let path = stored.location.unwrap();
+    assert_eq!(std::fs::read(path).unwrap(), b"abcdefgh");
+}
+
+#[tokio::test]
+async fn test_protocol_errors_termination_and_expiry() {
+    let dir = TempDir::new().unwrap();
+    let uploads = Arc::new(ResumableUploads::new(LocalResumableStore::new(dir.path())).expire_after(Duration::ZERO));
+    let app = uploads.clone().router("/files");
+
+    let request = Request::post("/files").header("upload-length", "4").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    assert_eq!(header(&response, "tus-version"), "1.0.0");
+    assert_eq!(send(&app, "POST", "/files", &[], b"").await.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(send(&app, "HEAD", "/files/missing", &[], b"").await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(send(&app, "HEAD", "/files/..%2Fetc", &[], b"").await.status(), StatusCode::NOT_FOUND);
+
+    let created = send(&app, "POST", "/files", &[("upload-length", "4")], b"").await;
+    let location = header(&created, "location").to_string();
+    let response = send(&app, "PATCH", &location, &[("upload-offset", "0")], b"data").await;
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    // Expired immediately: gone, then swept
+    assert_eq!(send(&app, "HEAD", &location, &[], b"").await.status(), StatusCode::GONE);
+    send(&app, "POST", "/files", &[("upload-length", "4")], b"").await;
+    assert_eq!(uploads.cleanup_expired().await.unwrap(), 2);
+    assert_eq!(send(&app, "HEAD", &location, &[], b"").await.status(), StatusCode::NOT_FOUND);
+
+    // Termination
+    let uploads = Arc::new(ResumableUploads::new(LocalResumableStore::new(dir.path())));
+    let app = uploads.clone().router("/files");
+    let created = send(&app, "POST", "/files", &[("upload-length", "4")], b"").await;
+    let location = header(&created, "location").to_string();
+    assert_eq!(patch(&app, &location, 0, b"da", &[]).await.status(), StatusCode::NO_CONTENT);
+    assert_eq!(send(&app, "DELETE", &location, &[], b"").await.status(), StatusCode::NO_CONTENT);
+    assert_eq!(send(&app, "HEAD", &location, &[], b"").await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(uploads.cleanup_expired().await.unwrap(), 0);
+    assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none());
+
+    // Ids escaping the store's directory are rejected
+    let outer = Arc::new(ResumableUploads::new(LocalResumableStore::new(dir.path()))).router("/outer");
+    let created = send(&outer, "POST", "/outer", &[("upload-length", "4")], b"").await;
+    let id = header(&created, "location").rsplit('/').next().unwrap().to_string();
+    std::fs::create_dir(dir.path().join("inner")).unwrap();
+    let inner = Arc::new(ResumableUploads::new(LocalResumableStore::new(dir.path().join("inner")))).router("/files");
+    let escaped = format!("/files/..%2F{}", id);
+    assert_eq!(send(&inner, "DELETE", &escaped, &[], b"").await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(send(&outer, "HEAD", &format!("/outer/{}", id), &[], b"").await.status(), StatusCode::OK);
+}
+
+#[test]
+fn test_parse_metadata() {
+    let metadata = parse_metadata("filename d29ybGRfZG9taW5hdGlvbl9wbGFuLnBkZg==, is_confidential").unwrap();
+    assert_eq!(metadata["filename"], "world_domination_plan.pdf");
+    assert_eq!(metadata["is_confidential"], "");
+    assert!(parse_metadata("filename not-base64!").is_err());
+}
@@ -0,0 +1,203 @@
+//! Streaming upload tests
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Multipart, Path, Query, State};
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::routing::{any, post};
+use axum::Router;
+use rf_net::http::{sniff_mime, LocalDirSink, S3Sink, StreamingUpload, UploadSink};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+/// Serve `upload` at /upload, answering with the stored locations or the error
+async fn upload_server(upload: StreamingUpload) -> String {
+    let upload = Arc::new(upload);
+    let app = Router::new()
+        .route("/upload", post(move |multipart: Multipart| {
+            let upload = upload.clone();
+            async move {
+                match upload.process(multipart).await {
+                    Ok(result) => {
+                        let files: Vec<String> = result.files.iter()
+                            .map(|f| format!("{}|{}|{}", f.location, f.size, f.content_type.clone().unwrap_or_default()))
+                            .collect();
+                        (StatusCode::OK, format!("{}\n{}", result.fields.get("title").cloned().unwrap_or_default(), files.join("\n")))
+                    }
+                    Err(e) => (StatusCode::BAD_REQUEST, e.to_string()),
+                }
+            }
+        }))
+        .layer(DefaultBodyLimit::disable());
+    serve(app).await
+}
+
+/// Post a multipart body with the given (field, filename, data) parts
+async fn post_parts(url: &str, parts: &[(&str, Option<&str>, &[u8])]) -> (StatusCode, String) {
+    let mut body = Vec::new();
+    for (name, filename, data) in parts {
+        body.extend_from_slice(b"--BOUNDARY\r\n");
+        match filename {
+            Some(filename) => body.extend_from_slice(format!(
+                "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                name, filename
+            ).as_bytes()),
+            None => body.extend_from_slice(format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name).as_bytes()),
+        }
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(b"--BOUNDARY--\r\n");
+    let response = reqwest::Client::new()
+        .post(format!("{}/upload", url))
+        .header("content-type", "multipart/form-data; boundary=BOUNDARY")
+        .body(body)
+        .send().await.unwrap();
+    (response.status(), response.text().await.unwrap())
+}
+
+fn files_in(dir: &std::path::Path) -> usize {
+    std::fs::read_dir(dir).map(|entries| entries.count()).unwrap_or(0)
+}
+
+#[test]
+fn test_sniff_mime() {
+    assert_eq!(sniff_mime(PNG), Some("image/png"));
+    assert_eq!(sniff_mime(b"\xff\xd8\xff\xe0\0\x10JFIF"), Some("image/jpeg"));
+    assert_eq!(sniff_mime(b"%PDF-1.7"), Some("application/pdf"));
+    assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+    assert_eq!(sniff_mime(b"hello world"), None);
+}
+
+#[tokio::test]
+async fn test_local_sink_limits_and_progress() {
+    let dir = tempfile::tempdir().unwrap();
+    let progress = Arc::new(AtomicU64::new(0));
+    let seen = progress.clone();
+    let url = upload_server(StreamingUpload::new()
+        .sink(LocalDirSink::new(dir.path()))
+        .max_file_size(4096)
+        .allowed_extensions(&["png", "jpg"])
+        .allowed_mime_types(&["image/png", "image/jpeg"])
+        .on_progress(move |p| seen.store(p.total_bytes, Ordering::SeqCst))).await;
+
+    let image = [PNG, &[7u8; 2000]].concat();
+    let (status, body) = post_parts(&url, &[("title", None, b"cat"), ("photo", Some("Cat.PNG"), &image)]).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let mut lines = body.lines();
+    assert_eq!(lines.next(), Some("cat"));
+    let stored: Vec<&str> = lines.next().unwrap().split('|').collect();
+    assert!(stored[0].ends_with(".png"));
+    assert_eq!(std::fs::read(stored[0]).unwrap(), image);
+    assert_eq!(stored[1..], ["2016", "image/png"]);
+    assert_eq!(progress.load(Ordering::SeqCst), 2016);
+    assert_eq!(files_in(dir.path()), 1);
+
+    // Wrong extension, disguised content and oversized files are rejected
+    let (status, body) = post_parts(&url, &[("photo", Some("cat.gif"), PNG)]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("extension 'gif'"), "{}", body);
+    let (status, body) = post_parts(&url, &[("photo", Some("cat.png"), b"MZ\x90\0 not an image")]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("application/octet-stream"), "{}", body);
+
+    // A later failure removes the files already stored by the request
+    let large = [PNG, &[0u8; 5000]].concat();
+    let (status, body) = post_parts(&url, &[("a", Some("a.png"), PNG), ("b", Some("b.png"), &large)]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("exceeds maximum allowed size 4096"), "{}", body);
+    assert_eq!(files_in(dir.path()), 1);
+}
+
+#[derive(Default)]
+struct MockS3 {
+    objects: Mutex<HashMap<String, Vec<u8>>>,
+    parts: Mutex<HashMap<u32, Vec<u8>>>,
+    requests: Mutex<Vec<String>>,
+}
+
+async fn mock_s3(
+    State(s3): State<Arc<MockS3>>,
+    method: Method,
+    Path((bucket, key)): Path<(String, String)>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, HeaderMap, String) {
+    let auth = headers["authorization"].to_str().unwrap();
+    assert!(auth.starts_with("AWS4-HMAC-SHA256 Credential=AKID/"), "{}", auth);
+    assert!(auth.contains("/us-west-2/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="));
+    let key = format!("{}/{}", bucket, key);
+    let mut query_keys: Vec<&str> = query.keys().map(|k| k.as_str()).collect();
+    query_keys.sort();
+    s3.requests.lock().unwrap().push(format!("{} {}", method, query_keys.join(",")));
+    let mut reply = HeaderMap::new();
+    match (method, query.get("partNumber")) {
+        (Method::POST, _) if query.contains_key("uploads") => {
+            return (StatusCode::OK, reply, "<InitiateMultipartUploadResult><UploadId>up-1</UploadId></InitiateMultipartUploadResult>".to_string());
+        }
+        (Method::PUT, Some(number)) => {
+            reply.insert("etag", format!("\"etag-{}\"", number).parse().unwrap());
+            s3.parts.lock().unwrap().insert(number.parse().unwrap(), body.to_vec());
+        }
+        (Method::POST, _) => {
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert!(body.contains("<PartNumber>2</PartNumber><ETag>\"etag-2\"</ETag>"), "{}", body);
+            let mut parts = s3.parts.lock().unwrap();
+            let mut numbers: Vec<u32> = parts.keys().copied().collect();
+            numbers.sort();
+            let data = numbers.iter().flat_map(|n| parts.remove(n).unwrap()).collect();
+            s3.objects.lock().unwrap().insert(key, data);
+            return (StatusCode::OK, reply, "<CompleteMultipartUploadResult/>".to_string());
+        }
+        (Method::PUT, None) => {
+            s3.objects.lock().unwrap().insert(key, body.to_vec());
+        }
+        (Method::DELETE, _) => {
+            s3.objects.lock().unwrap().remove(&key);
+        }
+        _ => return (StatusCode::METHOD_NOT_ALLOWED, reply, String::new()),
+    }
+    (StatusCode::OK, reply, String::new())
+}
+
+#[tokio::test]
+async fn test_s3_sink_single_and_multipart() {
+    let s3 = Arc::new(MockS3::default());
+    let endpoint = serve(Router::new().route("/{bucket}/{*key}", any(mock_s3)).with_state(s3.clone()).layer(DefaultBodyLimit::disable())).await;
+    let sink = || S3Sink::new(&endpoint, "media", "AKID", "secret")
+        .region("us-west-2")
+        .prefix("uploads/")
+        .part_size(5 * 1024 * 1024);
+    let url = upload_server(StreamingUpload::new().sink(sink())).await;
+
+    let (status, body) = post_parts(&url, &[("doc", Some("small.pdf"), b"%PDF-1.7 tiny")]).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let key = body.lines().nth(1).unwrap().split('|').next().unwrap().to_string();
+    assert!(key.starts_with("uploads/") && key.ends_with(".pdf"));
+    assert_eq!(s3.objects.lock().unwrap()[&format!("media/{}", key)], b"%PDF-1.7 tiny");
+
+    let large: Vec<u8> = (0..6 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let (status, body) = post_parts(&url, &[("video", Some("clip.bin"), &large)]).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let key = body.lines().nth(1).unwrap().split('|').next().unwrap().to_string();
+    assert!(s3.objects.lock().unwrap()[&format!("media/{}", key)] == large);
+    assert_eq!(s3.requests.lock().unwrap()[1..], [
+        "POST uploads".to_string(),
+        "PUT partNumber,uploadId".to_string(),
+        "PUT partNumber,uploadId".to_string(),
+        "POST uploadId".to_string(),
+    ]);
+
+    sink().remove(&key).await.unwrap();
+    assert!(!s3.objects.lock().unwrap().contains_key(&format!("media/{}", key)));
+}
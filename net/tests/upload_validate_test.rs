@@ -0,0 +1,121 @@
+//! Upload content validation tests
+
+use axum::response::IntoResponse;
+use axum::http::StatusCode;
+use rf_errors::RfError;
+use rf_net::http::{image_dimensions, ScanVerdict, UploadError, UploadFile, UploadValidator};
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut data = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+    data.extend_from_slice(&width.to_be_bytes());
+    data.extend_from_slice(&height.to_be_bytes());
+    data.extend_from_slice(&[8, 6, 0, 0, 0]);
+    data
+}
+
+fn jpeg(width: u16, height: u16) -> Vec<u8> {
+    let mut data = b"\xff\xd8\xff\xe0\x00\x10JFIF\x00\x01\x01\x00\x00\x01\x00\x01\x00\x00".to_vec();
+    // A DHT segment before the frame header is skipped
+    data.extend_from_slice(b"\xff\xc4\x00\x03\x00");
+    data.extend_from_slice(b"\xff\xc0\x00\x11\x08");
+    data.extend_from_slice(&height.to_be_bytes());
+    data.extend_from_slice(&width.to_be_bytes());
+    data.extend_from_slice(&[3; 12]);
+    data
+}
+
+fn file(field: &str, filename: &str, data: Vec<u8>) -> UploadFile {
+    UploadFile::new(field.to_string(), filename.to_string(), Some("image/png".to_string()), data)
+}
+
+#[test]
+fn test_image_dimensions() {
+    assert_eq!(image_dimensions(&png(640, 480)), Some((640, 480)));
+    assert_eq!(image_dimensions(&jpeg(1920, 1080)), Some((1920, 1080)));
+    assert_eq!(image_dimensions(b"GIF89a\x20\x03\x58\x02\x00"), Some((800, 600)));
+
+    let mut bmp = b"BM".to_vec();
+    bmp.extend_from_slice(&[0; 12]);
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&100i32.to_le_bytes());
+    bmp.extend_from_slice(&(-50i32).to_le_bytes());
+    assert_eq!(image_dimensions(&bmp), Some((100, 50)));
+
+    let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0".to_vec();
+    webp.extend_from_slice(&[0x1f, 0x03, 0x00, 0x57, 0x02, 0x00]);
+    assert_eq!(image_dimensions(&webp), Some((800, 600)));
+
+    assert_eq!(image_dimensions(b"\xff\xd8\xff"), None);
+    assert_eq!(image_dimensions(b"plain text"), None);
+}
+
+#[tokio::test]
+async fn test_sniffed_type_and_extension() {
+    let validator = UploadValidator::new().allowed_types(&["image/png", "image/jpeg"]).max_size(1024);
+    assert!(validator.validate(&[file("avatar", "me.png", png(64, 64))]).await.is_ok());
+    // Unknown extensions are not compared with the content
+    assert!(validator.validate(&[file("avatar", "me.img", jpeg(64, 64))]).await.is_ok());
+
+    let files = [
+        file("avatar", "me.png", jpeg(64, 64)),
+        file("resume", "cv.pdf", b"MZ\x90\0 not a pdf".to_vec()),
+        file("banner", "big.png", [png(1, 1), vec![0; 2048]].concat()),
+    ];
+    let error = validator.validate(&files).await.unwrap_err();
+    let errors = error.errors().unwrap();
+    let rules = |field: &str| errors.get(field).unwrap().iter().map(|e| e.rule.as_str()).collect::<Vec<_>>();
+    assert_eq!(rules("avatar"), ["extension"]);
+    assert_eq!(rules("resume"), ["file_type", "extension"]);
+    assert_eq!(rules("banner"), ["max_size"]);
+    assert!(errors.get("resume").unwrap()[0].message.contains("application/octet-stream"));
+
+    let lenient = UploadValidator::new().check_extension(false);
+    assert!(lenient.validate(&[file("avatar", "me.png", jpeg(64, 64))]).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_dimension_and_pixel_limits() {
+    let validator = UploadValidator::new().max_dimensions(4096, 4096).min_dimensions(16, 16).max_pixels(1_000_000);
+    assert!(validator.validate(&[file("photo", "a.jpg", jpeg(1000, 1000))]).await.is_ok());
+
+    let cases = [
+        (png(50_000, 50_000), vec!["dimensions", "pixels"]),
+        (png(2000, 1000), vec!["pixels"]),
+        (png(8, 8), vec!["dimensions"]),
+        (b"\x89PNG\r\n\x1a\n".to_vec(), vec!["image"]),
+    ];
+    for (data, expected) in cases {
+        let error = validator.validate(&[file("photo", "a.png", data)]).await.unwrap_err();
+        let rules: Vec<_> = error.errors().unwrap().get("photo").unwrap().iter().map(|e| e.rule.clone()).collect();
+        assert_eq!(rules, expected);
+    }
+    // Limits only apply to images
+    assert!(validator.validate(&[file("doc", "a.pdf", b"%PDF-1.7".to_vec())]).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_virus_scanner() {
+    let validator = UploadValidator::new().scanner(|file: &UploadFile| {
+        Ok(if file.data.windows(4).any(|w| w == b"EICA") {
+            ScanVerdict::Infected("Eicar-Test-Signature".to_string())
+        } else {
+            ScanVerdict::Clean
+        })
+    });
+    assert!(validator.validate(&[file("doc", "notes.txt", b"hello".to_vec())]).await.is_ok());
+    let error = validator.validate(&[file("doc", "notes.txt", b"X5O!EICAR".to_vec())]).await.unwrap_err();
+    assert_eq!(error.errors().unwrap().first().unwrap().rule, "virus");
+
+    let response = error.into_response();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["errors"][0]["field"], "doc");
+    assert_eq!(body["errors"][0]["rule"], "virus");
+
+    // Scanner failures are not reported as invalid files
+    let broken = UploadValidator::new().scanner(|_: &UploadFile| Err(RfError::Network("clamd unreachable".to_string())));
+    let error = broken.validate(&[file("doc", "notes.txt", b"hello".to_vec())]).await.unwrap_err();
+    assert!(matches!(error, UploadError::Failed(_)));
+    assert!(error.into_response().status().is_server_error());
+}
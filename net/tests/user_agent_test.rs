@@ -0,0 +1,127 @@
+//! User agent parser tests
+
+use axum::body::Body;
+use axum::routing::get;
+use axum::Router;
+use rf_net::http::{user_agent_middleware, DeviceType, Request, UaParser, UaRuleset, UserAgent};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const CHROME_WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.109 Safari/537.36";
+const SAFARI_IPHONE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.1 Mobile/15E148 Safari/604.1";
+const EDGE_MAC: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.2210.61";
+const ANDROID_TABLET: &str = "Mozilla/5.0 (Linux; Android 13; SM-X700) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/119.0.0.0 Safari/537.36";
+const ANDROID_PHONE: &str = "Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.43 Mobile Safari/537.36";
+const GOOGLEBOT: &str = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+#[test]
+fn test_parse_browsers_and_os() {
+    let ua = UserAgent::parse(CHROME_WINDOWS);
+    assert_eq!(ua.browser_name(), "Chrome");
+    assert_eq!(ua.browser.as_ref().unwrap().major_version(), Some(120));
+    assert_eq!(ua.os_name(), "Windows");
+    assert_eq!(ua.os.as_ref().unwrap().version.as_deref(), Some("10"));
+    assert_eq!(ua.device, DeviceType::Desktop);
+    assert!(!ua.is_bot());
+
+    let ua = UserAgent::parse(SAFARI_IPHONE);
+    assert_eq!(ua.browser_name(), "Safari");
+    assert_eq!(ua.os.as_ref().unwrap().version.as_deref(), Some("17.1"));
+    assert!(ua.is_mobile());
+
+    let ua = UserAgent::parse(EDGE_MAC);
+    assert_eq!(ua.browser_name(), "Edge");
+    assert_eq!(ua.os_name(), "macOS");
+
+    assert!(UserAgent::parse(ANDROID_TABLET).is_tablet());
+    let phone = UserAgent::parse(ANDROID_PHONE);
+    assert!(phone.is_mobile());
+    assert_eq!(phone.os.unwrap().version.as_deref(), Some("14"));
+}
+
+#[test]
+fn test_detect_bots() {
+    let ua = UserAgent::parse(GOOGLEBOT);
+    assert!(ua.is_bot());
+    assert_eq!(ua.device, DeviceType::Bot);
+    assert_eq!(ua.bot.as_ref().unwrap().name, "Googlebot");
+    assert_eq!(ua.bot.unwrap().version.as_deref(), Some("2.1"));
+
+    assert_eq!(UserAgent::parse("curl/8.4.0").bot.unwrap().name, "curl");
+    assert!(UserAgent::parse("SomeNewCrawler/1.0").is_bot());
+
+    let empty = UserAgent::parse("");
+    assert_eq!(empty.device, DeviceType::Unknown);
+    assert!(empty.browser.is_none());
+}
+
+#[test]
+fn test_custom_ruleset_and_reload() {
+    let custom = UaRuleset::from_str(
+        r#"
+browsers:
+  - regex: 'MyApp/(\d+)\.(\d+)'
+    name: MyApp
+    version: '$1.$2'
+devices:
+  - regex: 'MyApp'
+    device: mobile
+"#,
+    )
+    .unwrap();
+
+    let parser = UaParser::new();
+    assert!(parser.parse("MyApp/3.2").browser.is_none());
+
+    let mut rules = UaRuleset::builtin();
+    rules.extend(custom.clone());
+    parser.update(rules).unwrap();
+    let ua = parser.parse("MyApp/3.2");
+    assert_eq!(ua.browser_name(), "MyApp");
+    assert_eq!(ua.browser.as_ref().unwrap().version.as_deref(), Some("3.2"));
+    assert!(ua.is_mobile());
+
+    // Invalid rules are rejected and the active rules are kept
+    let broken = UaRuleset::from_str("browsers:\n  - regex: '('\n    name: x\n").unwrap();
+    assert!(parser.update(broken).is_err());
+    assert_eq!(parser.parse("MyApp/3.2").browser_name(), "MyApp");
+
+    let path = std::env::temp_dir().join(format!("rf-ua-rules-{}.yaml", std::process::id()));
+    std::fs::write(&path, serde_json::to_string(&custom).unwrap()).unwrap();
+    let from_file = UaParser::from_file(&path).unwrap();
+    assert_eq!(from_file.parse("MyApp/1.0").browser_name(), "MyApp");
+    assert_eq!(from_file.parse(CHROME_WINDOWS).browser_name(), "unknown");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_middleware_and_request_helper() {
+    let parser = Arc::new(UaParser::new());
+    let app = Router::new()
+        .route(
+            "/",
+            get(|request: Request| async move {
+                let ua = request.user_agent();
+                format!("{}|{}|{}", ua.browser_name(), ua.os_name(), ua.device.as_str())
+            }),
+        )
+        .layer(axum::middleware::from_fn(
+            move |req: axum::extract::Request, next: axum::middleware::Next| {
+                let parser = parser.clone();
+                async move { user_agent_middleware(parser, req, next).await.unwrap() }
+            },
+        ));
+
+    let response = app
+        .oneshot(
+            axum::extract::Request::builder()
+                .uri("/")
+                .header("user-agent", SAFARI_IPHONE)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+    assert_eq!(&body[..], b"Safari|iOS|mobile");
+}
@@ -0,0 +1,217 @@
+//! Webhook tests
+
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use rf_net::webhook::{
+    sign, webhook_verify_middleware, MemoryPendingStore, PendingDelivery, PendingStore, RetryPolicy, WebhookEndpoint,
+    WebhookMessage, WebhookSender, WebhookVerifier, WEBHOOK_ID, WEBHOOK_SIGNATURE, WEBHOOK_TIMESTAMP,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use rf_test::{DurationExt, MockClock};
+use std::time::Duration;
+
+fn headers(id: &str, timestamp: i64, signature: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(WEBHOOK_ID, id.parse().unwrap());
+    headers.insert(WEBHOOK_TIMESTAMP, timestamp.to_string().parse().unwrap());
+    headers.insert(WEBHOOK_SIGNATURE, signature.parse().unwrap());
+    headers
+}
+
+#[test]
+fn test_webhook_verify() {
+    let verifier = WebhookVerifier::new("new-secret").secret("old-secret");
+    let now = chrono::Utc::now().timestamp();
+    let body = br#"{"a":1}"#;
+
+    let signature = format!("{} {}", sign(b"unknown", "msg_1", now, body), sign(b"old-secret", "msg_1", now, body));
+    assert!(verifier.verify(&headers("msg_1", now, &signature), body).is_ok());
+    assert!(verifier.verify(&headers("msg_1", now, &signature), br#"{"a":2}"#).is_err());
+    assert!(verifier.verify(&headers("msg_2", now, &signature), body).is_err());
+
+    let stale = now - 600;
+    assert!(verifier.verify(&headers("msg_1", stale, &sign(b"new-secret", "msg_1", stale, body)), body).is_err());
+    assert!(verifier.verify(&HeaderMap::new(), body).is_err());
+}
+
+#[tokio::test]
+async fn test_webhook_delivery() {
+    let received = Arc::new(Mutex::new(Vec::<WebhookMessage>::new()));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let verifier = Arc::new(WebhookVerifier::new("secret"));
+    let app = Router::new()
+        .route(
+            "/hooks",
+            post({
+                let (received, calls) = (received.clone(), calls.clone());
+                move |Json(message): Json<WebhookMessage>| async move {
+                    // The first delivery fails and must be retried
+                    if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return StatusCode::SERVICE_UNAVAILABLE;
+                    }
+                    received.lock().unwrap().push(message);
+                    StatusCode::NO_CONTENT
+                }
+            }),
+        )
+        .layer(axum::middleware::from_fn_with_state(verifier, webhook_verify_middleware));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let retry = RetryPolicy {
+        max_attempts: 3,
+        initial_delay: Duration::from_millis(10),
+        ..RetryPolicy::default()
+    };
+    let sender = WebhookSender::new()
+        .retry(retry)
+        .endpoint("ok", WebhookEndpoint::new(format!("http://{}/hooks", addr), "secret"))
+        .endpoint("bad-secret", WebhookEndpoint::new(format!("http://{}/hooks", addr), "other"));
+
+    let message = sender.deliver("ok", "invoice.paid", serde_json::json!({"amount": 10})).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(received.lock().unwrap().as_slice(), &[message]);
+
+    // Rejected signatures exhaust the attempts and are dead-lettered
+    assert!(sender.deliver("bad-secret", "invoice.paid", 1).await.is_err());
+    let dead = sender.dead_lettered().await.unwrap();
+    assert_eq!(dead.len(), 1);
+    assert_eq!((dead[0].endpoint.as_str(), dead[0].attempts), ("bad-secret", 3));
+    assert!(dead[0].last_error.contains("401"));
+
+    // Fixed endpoint, redeliver with the same message ID
+    sender.add_endpoint("bad-secret", WebhookEndpoint::new(format!("http://{}/hooks", addr), "secret"));
+    sender.redeliver(&dead[0].message.id).await.unwrap();
+    for _ in 0..100 {
+        if received.lock().unwrap().len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(received.lock().unwrap()[1].id, dead[0].message.id);
+    assert!(sender.dead_lettered().await.unwrap().is_empty());
+    assert!(sender.deliver("missing", "x", 1).await.is_err());
+}
+
+/// Wait for the sender to have no pending messages
+async fn drained(sender: &WebhookSender) -> bool {
+    for _ in 0..100 {
+        if sender.pending_deliveries().await.unwrap().is_empty() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn test_pending_deliveries() {
+    let received = Arc::new(Mutex::new(Vec::<WebhookMessage>::new()));
+    let release = Arc::new(tokio::sync::Notify::new());
+    let app = Router::new().route(
+        "/hooks",
+        post({
+            let (received, release) = (received.clone(), release.clone());
+            move |Json(message): Json<WebhookMessage>| async move {
+                if message.event == "held" {
+                    release.notified().await;
+                }
+                received.lock().unwrap().push(message);
+                StatusCode::NO_CONTENT
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let endpoint = || WebhookEndpoint::new(format!("http://{}/hooks", addr), "secret");
+    let wait_for = |count: usize| {
+        let received = received.clone();
+        async move {
+            for _ in 0..100 {
+                if received.lock().unwrap().len() == count {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+    };
+
+    // Enqueued messages stay pending until delivered
+    let sender = WebhookSender::new().endpoint("ok", endpoint());
+    let message = sender.enqueue("ok", "held", 1).await.unwrap();
+    let pending = sender.pending_deliveries().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!((pending[0].endpoint.as_str(), &pending[0].message), ("ok", &message));
+    release.notify_one();
+    wait_for(1).await;
+    assert!(drained(&sender).await);
+    assert!(sender.enqueue("missing", "x", 1).await.is_err());
+    assert!(sender.pending_deliveries().await.unwrap().is_empty());
+
+    // A message left pending by a previous process is delivered on resume
+    let store = MemoryPendingStore::new();
+    let leftover = WebhookMessage::new("invoice.paid", 2).unwrap();
+    store
+        .save(PendingDelivery {
+            endpoint: "ok".to_string(),
+            message: leftover.clone(),
+            queued_at: chrono::Utc::now().timestamp(),
+        })
+        .await
+        .unwrap();
+    let restarted = WebhookSender::new().pending(store).endpoint("ok", endpoint());
+    assert_eq!(restarted.resume().await.unwrap(), 1);
+    wait_for(2).await;
+    assert_eq!(received.lock().unwrap()[1], leftover);
+    assert!(drained(&restarted).await);
+}
+
+#[tokio::test]
+async fn test_retry_backoff_on_mock_clock() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = Router::new().route(
+        "/hooks",
+        post({
+            let calls = calls.clone();
+            move || async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mock = MockClock::new();
+    let retry = RetryPolicy {
+        max_attempts: 4,
+        initial_delay: 1.mins(),
+        ..RetryPolicy::default()
+    };
+    let sender = WebhookSender::new()
+        .retry(retry)
+        .clock(mock.clock())
+        .endpoint("down", WebhookEndpoint::new(format!("http://{}/hooks", addr), "secret"));
+    let delivery = tokio::spawn({
+        let sender = sender.clone();
+        async move { sender.deliver("down", "invoice.paid", 1).await }
+    });
+
+    // Delays of 1, 2 and 4 minutes between the attempts
+    for (attempts, delay) in [(1, 1.mins()), (2, 2.mins()), (3, 4.mins())] {
+        mock.wait_for_sleepers(1).await;
+        assert_eq!(calls.load(Ordering::SeqCst), attempts);
+        mock.advance(delay - 1.secs()).await;
+        assert_eq!(mock.sleepers(), 1);
+        mock.advance(1.secs()).await;
+    }
+    assert!(delivery.await.unwrap().is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+    assert_eq!(mock.elapsed(), 7.mins());
+    assert_eq!(sender.dead_lettered().await.unwrap()[0].attempts, 4);
+}
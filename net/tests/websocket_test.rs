@@ -0,0 +1,117 @@
+//! WebSocket hub tests
+
+use axum::extract::ws::Message as ServerMessage;
+use axum::extract::WebSocketUpgrade;
+use axum::routing::get;
+use axum::Router;
+use futures_util::{SinkExt, StreamExt};
+use rf_net::http::{Connection, WebSocketHub, CLOSE_GOING_AWAY};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+async fn serve(hub: WebSocketHub) -> String {
+    let app = Router::new().route("/ws", get(move |ws: WebSocketUpgrade| {
+        let hub = hub.clone();
+        async move {
+            hub.upgrade(ws, |conn: Connection, message: ServerMessage| async move {
+                let text = message.to_text().unwrap_or_default().to_string();
+                if let Some(room) = text.strip_prefix("join ") {
+                    conn.join(room);
+                    conn.set_metadata("user", room);
+                    conn.send(format!("joined {}", room)).await;
+                } else if text == "bye" {
+                    conn.close();
+                }
+            })
+        }
+    }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("ws://{}/ws", addr)
+}
+
+async fn text(ws: &mut (impl StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin)) -> String {
+    loop {
+        match ws.next().await.unwrap().unwrap() {
+            Message::Text(text) => return text.to_string(),
+            Message::Ping(_) | Message::Pong(_) => continue,
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
+
+async fn eventually(check: impl Fn() -> bool) {
+    for _ in 0..100 {
+        if check() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("condition not met");
+}
+
+#[tokio::test]
+async fn test_rooms_and_broadcast() {
+    let hub = WebSocketHub::new();
+    let url = serve(hub.clone()).await;
+    let (mut a, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let (mut b, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+
+    a.send(Message::Text("join red".into())).await.unwrap();
+    assert_eq!(text(&mut a).await, "joined red");
+    b.send(Message::Text("join blue".into())).await.unwrap();
+    assert_eq!(text(&mut b).await, "joined blue");
+    assert_eq!(hub.len(), 2);
+
+    assert_eq!(hub.broadcast("red", "to red"), 1);
+    assert_eq!(text(&mut a).await, "to red");
+    assert_eq!(hub.broadcast("green", "nobody"), 0);
+    assert_eq!(hub.broadcast_all("everyone"), 2);
+    assert_eq!(text(&mut a).await, "everyone");
+    assert_eq!(text(&mut b).await, "everyone");
+
+    let blue = hub.find("user", "blue");
+    assert_eq!(blue, hub.members("blue"));
+    assert!(hub.send_to(blue[0], "direct"));
+    assert_eq!(text(&mut b).await, "direct");
+    assert_eq!(hub.connection(blue[0]).unwrap().rooms(), vec!["blue".to_string()]);
+
+    // A server-side close sends a close frame and unregisters the connection
+    b.send(Message::Text("bye".into())).await.unwrap();
+    assert!(matches!(b.next().await.unwrap().unwrap(), Message::Close(_)));
+    eventually(|| hub.len() == 1).await;
+    assert!(hub.members("blue").is_empty() && !hub.rooms().contains(&"blue".to_string()));
+
+    drop(a);
+    eventually(|| hub.is_empty()).await;
+}
+
+#[tokio::test]
+async fn test_heartbeat_eviction_and_shutdown() {
+    let hub = WebSocketHub::new().ping_interval(Duration::from_millis(50)).stale_after(Duration::from_millis(200));
+    let url = serve(hub.clone()).await;
+
+    // `alive` keeps reading, so the client answers pings; `idle` never does
+    let (alive, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let (_idle, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    tokio::spawn(async move {
+        let mut alive = alive;
+        let mut close = None;
+        while let Some(Ok(message)) = alive.next().await {
+            if let Message::Close(frame) = message {
+                close = frame.map(|frame| u16::from(frame.code));
+                break;
+            }
+        }
+        let _ = sender.send(close);
+    });
+    eventually(|| hub.len() == 2).await;
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(hub.len(), 1);
+
+    hub.shutdown();
+    assert_eq!(receiver.await.unwrap(), Some(CLOSE_GOING_AWAY));
+    assert!(hub.close_all(Duration::from_secs(2)).await);
+}
@@ -0,0 +1,132 @@
+//! Typed WebSocket message channel tests
+
+use axum::extract::WebSocketUpgrade;
+use axum::routing::get;
+use axum::Router;
+use rf_errors::RfError;
+use rf_net::http::{ChannelSender, MessageChannel};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Chat {
+    room: String,
+    text: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Divide {
+    a: i64,
+    b: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Quotient {
+    value: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Sleep {
+    millis: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Position {
+    #[prost(uint32, tag = "1")]
+    player: u32,
+    #[prost(sint32, tag = "2")]
+    x: i32,
+    #[prost(sint32, tag = "3")]
+    y: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Ack {
+    #[prost(uint32, tag = "1")]
+    player: u32,
+}
+
+fn types() -> MessageChannel {
+    MessageChannel::new()
+        .json::<Chat>("chat")
+        .json::<Divide>("math.divide")
+        .json::<Quotient>("math.quotient")
+        .json::<Sleep>("sleep")
+        .protobuf::<Position>("game.position")
+        .protobuf::<Ack>("game.ack")
+}
+
+async fn serve() -> String {
+    let channel = Arc::new(types()
+        // Echo chat back with the text upper-cased
+        .on(|sender: ChannelSender, chat: Chat| async move {
+            let _ = sender.send(Chat { room: chat.room, text: chat.text.to_uppercase() }).await;
+        })
+        .on_request(|_, divide: Divide| async move {
+            if divide.b == 0 {
+                return Err(RfError::InvalidParameter("division by zero".to_string()));
+            }
+            Ok(Quotient { value: divide.a / divide.b })
+        })
+        .on_request(|_, position: Position| async move { Ok(Ack { player: position.player }) })
+        .on_request(|_, sleep: Sleep| async move {
+            tokio::time::sleep(Duration::from_millis(sleep.millis)).await;
+            Ok(Quotient { value: 0 })
+        }));
+    let app = Router::new().route("/ws", get(move |ws: WebSocketUpgrade| {
+        let channel = channel.clone();
+        async move { ws.on_upgrade(move |socket| channel.serve(socket)) }
+    }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("ws://{}/ws", addr)
+}
+
+#[tokio::test]
+async fn test_json_and_protobuf_round_trip() {
+    let url = serve().await;
+    let (tx, mut rx) = mpsc::channel(4);
+    let client = Arc::new(types().on(move |_, chat: Chat| {
+        let tx = tx.clone();
+        async move { tx.send(chat).await.unwrap() }
+    }));
+    let sender = client.connect(&url).await.unwrap();
+
+    sender.send(Chat { room: "lobby".to_string(), text: "hi".to_string() }).await.unwrap();
+    let echoed = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await.unwrap().unwrap();
+    assert_eq!(echoed, Chat { room: "lobby".to_string(), text: "HI".to_string() });
+
+    let ack: Ack = sender.request(Position { player: 7, x: -3, y: 12 }).await.unwrap();
+    assert_eq!(ack.player, 7);
+
+    // Unregistered types are rejected before anything is sent
+    assert!(matches!(sender.send(42u8).await, Err(RfError::InvalidParameter(_))));
+}
+
+#[tokio::test]
+async fn test_request_correlation_errors_and_timeout() {
+    let url = serve().await;
+    let sender = Arc::new(types().request_timeout(Duration::from_millis(200)))
+        .connect(&url).await.unwrap();
+
+    // Concurrent requests are matched to their own replies
+    let slow = sender.clone();
+    let slow = tokio::spawn(async move { slow.request::<_, Quotient>(Divide { a: 100, b: 10 }).await });
+    let results = futures_util::future::join_all((1..=5).map(|b| {
+        let sender = sender.clone();
+        async move { sender.request::<_, Quotient>(Divide { a: 60, b }).await.unwrap().value }
+    })).await;
+    assert_eq!(results, vec![60, 30, 20, 15, 12]);
+    assert_eq!(slow.await.unwrap().unwrap().value, 10);
+
+    match sender.request::<_, Quotient>(Divide { a: 1, b: 0 }).await {
+        Err(RfError::Custom(message)) => assert!(message.contains("division by zero")),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(sender.request::<_, Quotient>(Sleep { millis: 1000 }).await, Err(RfError::Timeout(_))));
+    // A reply of the wrong type is reported rather than panicking
+    assert!(matches!(sender.request::<_, Ack>(Divide { a: 4, b: 2 }).await, Err(RfError::Serialization(_))));
+}
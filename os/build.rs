@@ -0,0 +1,15 @@
+//! # build
+//!
+//! build 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Embeds rf-os's own build information, see `rf_os::build::info`
+
+#[path = "src/build/emit.rs"]
+mod emit;
+
+fn main() {
+    emit::emit_build_env();
+}
@@ -6,18 +6,251 @@
 //! @date 2026-01-06
 
 //! Build information
+//!
+//! `info()` describes rf-os itself; `build_info!()` expands in the calling
+//! crate and describes the application. The git description, rustc version,
+//! profile, target, features and build time are filled in when the crate's
+//! `build.rs` calls `emit_build_env()`:
+//!
+//! ```rust,ignore
+//! // build.rs, with rf-os in [build-dependencies]
+//! fn main() {
+//!     rf_os::build::emit_build_env();
+//! }
+//!
+//! // main.rs
+//! let info = rf_os::build_info!();
+//! Banner::new(info).print();
+//! ```
+
+mod emit;
+
+pub use emit::emit_build_env;
+
+use serde::Serialize;
+use std::collections::BTreeMap;
 
-//! // Get build information
+/// Build information of the calling crate
+///
+/// Fields not emitted by the crate's build script are `None`.
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::build::BuildInfo {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            git_describe: option_env!("RF_BUILD_GIT_DESCRIBE"),
+            git_commit: option_env!("RF_BUILD_GIT_COMMIT"),
+            rustc: option_env!("RF_BUILD_RUSTC"),
+            profile: option_env!("RF_BUILD_PROFILE"),
+            target: option_env!("RF_BUILD_TARGET"),
+            features: option_env!("RF_BUILD_FEATURES"),
+            built_at: option_env!("RF_BUILD_TIME"),
+        }
+        .normalized()
+    };
+}
+
+/// Build information of rf-os
 pub fn info() -> BuildInfo {
-    BuildInfo {
-        version: env!("CARGO_PKG_VERSION"),
-        name: env!("CARGO_PKG_NAME"),
-    }
+    crate::build_info!()
 }
 
 /// Build information structure
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BuildInfo {
-    pub version: &'static str,
     pub name: &'static str,
+    pub version: &'static str,
+    /// `git describe --tags --always --dirty`
+    pub git_describe: Option<&'static str>,
+    pub git_commit: Option<&'static str>,
+    /// `rustc --version`
+    pub rustc: Option<&'static str>,
+    /// Cargo profile (`debug` or `release`)
+    pub profile: Option<&'static str>,
+    pub target: Option<&'static str>,
+    /// Enabled Cargo features, comma separated
+    pub features: Option<&'static str>,
+    /// RFC 3339 UTC build time
+    pub built_at: Option<&'static str>,
 }
 
+impl BuildInfo {
+    /// Treat empty values (e.g. no git checkout) as missing
+    pub fn normalized(mut self) -> Self {
+        for field in [
+            &mut self.git_describe,
+            &mut self.git_commit,
+            &mut self.rustc,
+            &mut self.profile,
+            &mut self.target,
+            &mut self.features,
+            &mut self.built_at,
+        ] {
+            if field.is_some_and(str::is_empty) {
+                *field = None;
+            }
+        }
+        self
+    }
+
+    /// Enabled features
+    pub fn feature_list(&self) -> Vec<&'static str> {
+        self.features.map(|f| f.split(',').filter(|f| !f.is_empty()).collect()).unwrap_or_default()
+    }
+
+    /// Short version string: `1.2.0 (v1.2.0-3-gabc1234, release)`
+    pub fn version_string(&self) -> String {
+        let details: Vec<&str> = [self.git_describe, self.profile].into_iter().flatten().collect();
+        if details.is_empty() {
+            self.version.to_string()
+        } else {
+            format!("{} ({})", self.version, details.join(", "))
+        }
+    }
+
+    /// JSON document served by `/debug/version`, including rf-os's own build
+    pub fn to_json(&self) -> serde_json::Value {
+        let framework = info();
+        serde_json::to_value(BuildInfoJson {
+            name: self.name,
+            version: self.version,
+            git_describe: self.git_describe,
+            git_commit: self.git_commit,
+            rustc: self.rustc,
+            profile: self.profile,
+            target: self.target,
+            features: self.feature_list(),
+            built_at: self.built_at,
+            framework: FrameworkJson {
+                name: framework.name,
+                version: framework.version,
+                git_commit: framework.git_commit,
+            },
+        })
+        .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize)]
+struct BuildInfoJson {
+    name: &'static str,
+    version: &'static str,
+    git_describe: Option<&'static str>,
+    git_commit: Option<&'static str>,
+    rustc: Option<&'static str>,
+    profile: Option<&'static str>,
+    target: Option<&'static str>,
+    features: Vec<&'static str>,
+    built_at: Option<&'static str>,
+    framework: FrameworkJson,
+}
+
+#[derive(Serialize)]
+struct FrameworkJson {
+    name: &'static str,
+    version: &'static str,
+    git_commit: Option<&'static str>,
+}
+
+/// Default banner template
+pub const DEFAULT_BANNER: &str = "{name} {version_string}\nbuilt {built_at} with {rustc}\nfeatures: {features}";
+
+/// Startup banner
+///
+/// The template uses `{placeholder}` names: `name`, `version`,
+/// `version_string`, `git`, `commit`, `rustc`, `profile`, `target`,
+/// `features`, `built_at`, `framework`, plus any added with `var`. Missing
+/// values render as `unknown`.
+#[derive(Debug, Clone)]
+pub struct Banner {
+    info: BuildInfo,
+    art: Option<String>,
+    template: String,
+    vars: BTreeMap<String, String>,
+    enabled: bool,
+}
+
+impl Banner {
+    pub fn new(info: BuildInfo) -> Self {
+        Self {
+            info,
+            art: None,
+            template: DEFAULT_BANNER.to_string(),
+            vars: BTreeMap::new(),
+            enabled: true,
+        }
+    }
+
+    /// ASCII art printed above the text
+    pub fn art(mut self, art: impl Into<String>) -> Self {
+        self.art = Some(art.into());
+        self
+    }
+
+    /// Replace the template
+    pub fn template(mut self, template: impl Into<String>) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    /// Add a placeholder value
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(name.into(), value.into());
+        self
+    }
+
+    /// Turn printing on or off, e.g. from configuration
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn info(&self) -> &BuildInfo {
+        &self.info
+    }
+
+    /// Banner text
+    pub fn render(&self) -> String {
+        let rf = info();
+        let framework = format!("{} {}", rf.name, rf.version);
+        let info = &self.info;
+        let features = info.feature_list().join(", ");
+        let version_string = info.version_string();
+        let builtin: [(&str, Option<&str>); 11] = [
+            ("name", Some(info.name)),
+            ("version", Some(info.version)),
+            ("version_string", Some(&version_string)),
+            ("git", info.git_describe),
+            ("commit", info.git_commit),
+            ("rustc", info.rustc),
+            ("profile", info.profile),
+            ("target", info.target),
+            ("features", Some(features.as_str()).filter(|f| !f.is_empty())),
+            ("built_at", info.built_at),
+            ("framework", Some(&framework)),
+        ];
+        let mut text = self.template.clone();
+        for (name, value) in builtin {
+            text = text.replace(&format!("{{{}}}", name), value.unwrap_or("unknown"));
+        }
+        for (name, value) in &self.vars {
+            text = text.replace(&format!("{{{}}}", name), value);
+        }
+        match &self.art {
+            Some(art) => format!("{}\n{}", art.trim_end_matches('\n'), text),
+            None => text,
+        }
+    }
+
+    /// Print the banner to stdout, unless disabled
+    pub fn print(&self) {
+        if self.enabled {
+            println!("{}", self.render());
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! # emit
+//!
+//! emit 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Build script side of build information
+//!
+//! Only uses `std`, so rf-os's own build script includes this file directly.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Emit the `RF_BUILD_*` variables read by `build_info!`
+///
+/// Call from the application's `build.rs`:
+///
+/// ```rust,ignore
+/// fn main() {
+///     rf_os::build::emit_build_env();
+/// }
+/// ```
+///
+/// Sets the git description and commit, rustc version, profile, target,
+/// enabled features and build time (`SOURCE_DATE_EPOCH` if set, for
+/// reproducible builds). The script re-runs when the git HEAD moves.
+pub fn emit_build_env() {
+    let describe = git(&["describe", "--tags", "--always", "--dirty"]);
+    let commit = git(&["rev-parse", "HEAD"]);
+    set("RF_BUILD_GIT_DESCRIBE", describe.as_deref().unwrap_or_default());
+    set("RF_BUILD_GIT_COMMIT", commit.as_deref().unwrap_or_default());
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command(&rustc, &["--version"]);
+    set("RF_BUILD_RUSTC", rustc_version.as_deref().unwrap_or_default());
+    set("RF_BUILD_PROFILE", &std::env::var("PROFILE").unwrap_or_default());
+    set("RF_BUILD_TARGET", &std::env::var("TARGET").unwrap_or_default());
+
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(|f| f.to_lowercase().replace('_', "-")))
+        .collect();
+    features.sort();
+    set("RF_BUILD_FEATURES", &features.join(","));
+
+    let epoch = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default());
+    set("RF_BUILD_TIME", &format_rfc3339(epoch));
+
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    match git(&["rev-parse", "--git-dir"]) {
+        Some(git_dir) => {
+            let git_dir = Path::new(&git_dir);
+            println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+            println!("cargo:rerun-if-changed={}", git_dir.join("index").display());
+            if let Some(head_ref) = git(&["symbolic-ref", "-q", "HEAD"]) {
+                println!("cargo:rerun-if-changed={}", git_dir.join(head_ref).display());
+            }
+        }
+        // Without a rerun hint Cargo would run the script after every change
+        None => println!("cargo:rerun-if-changed=build.rs"),
+    }
+}
+
+fn set(key: &str, value: &str) {
+    println!("cargo:rustc-env={}={}", key, value.replace('\n', " "));
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    command("git", args)
+}
+
+fn command(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Unix seconds as `YYYY-MM-DDTHH:MM:SSZ`
+fn format_rfc3339(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
@@ -6,41 +6,441 @@
 //! @date 2026-01-06
 
 //! Cache system
+//!
+//! `CacheContainer` is a bounded in-memory cache with a default TTL and idle
+//! time, per-entry TTLs, LRU or LFU eviction and hit/miss statistics.
+//! `get_or_set_with` loads missing values with single-flight de-duplication:
+//! when many tasks miss the same key at once only one loader runs and the
+//! others wait for its value, so an expired hot key does not stampede the
+//! database.
+//!
+//! TTLs follow the cache's `Clock` (see `clock`), so tests can expire
+//! entries by advancing an `rf_test::MockClock` instead of sleeping. Idle
+//! time always runs on the system clock.
+//!
+//! `TieredCache` puts a `CacheContainer` in front of Redis for byte values
+//! shared by all instances. Writes and invalidations are broadcast over Redis
+//! pub/sub so every instance drops its stale local copy. It implements
+//! `rf_database::db::CacheStore`, so models can cache query results in it.
+//!
+//! ```rust,ignore
+//! use rf_os::cache::{CacheConfig, CacheContainer, Eviction};
+//!
+//! let users: CacheContainer<u64, User> = CacheContainer::from_config(&CacheConfig {
+//!     capacity: 10_000,
+//!     eviction: Eviction::Lru,
+//!     ..CacheConfig::default()
+//! })
+//! .metrics("users");
+//!
+//! let user = users
+//!     .try_get_or_set_with(id, Some(Duration::from_secs(60)), || load_user(id))
+//!     .await?;
+//! ```
 
-use moka::future::Cache;
+use crate::cfg::{Config, RfDuration};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use moka::future::{Cache, CacheBuilder};
+use moka::notification::RemovalCause;
+use moka::policy::EvictionPolicy;
+use moka::Expiry;
+use rf_core::clock::Clock;
+use rf_database::db::CacheStore;
+use rf_database::redis::{PubSubGroup, RedisClient, Subscription};
+use rf_errors::{Result, RfError};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::hash::Hash;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Which entry makes room when the cache is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Eviction {
+    /// Least recently used
+    Lru,
+    /// Least frequently used (TinyLFU): a new key may be rejected in favour of popular ones
+    #[default]
+    Lfu,
+}
+
+impl std::str::FromStr for Eviction {
+    type Err = RfError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "lru" => Ok(Self::Lru),
+            "lfu" | "tinylfu" | "tiny_lfu" => Ok(Self::Lfu),
+            other => Err(RfError::Config(format!("Unknown cache eviction policy '{}'", other))),
+        }
+    }
+}
+
+/// Cache configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Maximum number of entries
+    pub capacity: u64,
+    /// Lifetime of an entry after it is inserted, e.g. `"10m"`
+    pub ttl: Option<RfDuration>,
+    /// Lifetime of an entry after it was last read or written
+    pub idle: Option<RfDuration>,
+    /// Eviction policy when `capacity` is reached
+    pub eviction: Eviction,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            ttl: None,
+            idle: None,
+            eviction: Eviction::default(),
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Read the `cache.{name}` section of a configuration
+    ///
+    /// Missing keys keep their defaults.
+    pub fn from_config(config: &Config, name: &str) -> Result<Self> {
+        let key = |field: &str| format!("cache.{}.{}", name, field);
+        let mut cache = Self::default();
+        if let Some(capacity) = config.get(&key("capacity"))? {
+            cache.capacity = capacity.parse().map_err(|_| {
+                RfError::Config(format!("Invalid cache.{}.capacity: {}", name, capacity))
+            })?;
+        }
+        cache.ttl = config.get_duration(&key("ttl"))?.map(RfDuration::from);
+        cache.idle = config.get_duration(&key("idle"))?.map(RfDuration::from);
+        if let Some(eviction) = config.get(&key("eviction"))? {
+            cache.eviction = eviction.parse()?;
+        }
+        Ok(cache)
+    }
+}
+
+/// Cache statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// Number of entries
+    pub entry_count: u64,
+    /// Total weight of the entries (equal to the entry count unless a weigher is used)
+    pub weighted_size: u64,
+    /// Maximum capacity, if bounded
+    pub capacity: Option<u64>,
+    /// Lookups that found a value
+    pub hits: u64,
+    /// Lookups that found nothing, including those that ran a loader
+    pub misses: u64,
+    /// Loader runs of `get_or_set_with` and `try_get_or_set_with`
+    pub loads: u64,
+    /// Loader runs that returned an error
+    pub load_failures: u64,
+    /// Entries removed to stay within the capacity
+    pub evictions: u64,
+    /// Entries removed because their TTL or idle time ran out
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    /// Share of lookups that were hits, 0 before the first lookup
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// A cached value with its own TTL
+#[derive(Clone)]
+struct Entry<V> {
+    value: V,
+    ttl: Option<Duration>,
+    /// Insertion time on the cache's clock
+    stored: Instant,
+}
+
+/// Applies per-entry TTLs on insert and update; the cache-wide TTL and idle time still apply
+struct EntryExpiry;
+
+impl<K, V> Expiry<K, Entry<V>> for EntryExpiry {
+    fn expire_after_create(&self, _key: &K, entry: &Entry<V>, _created_at: Instant) -> Option<Duration> {
+        entry.ttl
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &K,
+        entry: &Entry<V>,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        entry.ttl
+    }
+}
+
+/// Hit, miss and eviction counters, exported as metrics once the cache is named
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    loads: AtomicU64,
+    load_failures: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+    name: OnceLock<String>,
+}
+
+impl Counters {
+    fn add(&self, counter: &AtomicU64, metric: &'static str) {
+        counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(name) = self.name.get() {
+            metrics::counter!(metric, "cache" => name.clone()).increment(1);
+        }
+    }
+
+    fn hit(&self) {
+        self.add(&self.hits, "cache_hits_total");
+    }
+
+    fn miss(&self) {
+        self.add(&self.misses, "cache_misses_total");
+    }
+
+    fn load(&self) {
+        self.add(&self.loads, "cache_loads_total");
+    }
+
+    fn load_failure(&self) {
+        self.add(&self.load_failures, "cache_load_failures_total");
+    }
+
+    fn removed(&self, cause: RemovalCause) {
+        match cause {
+            RemovalCause::Size => self.add(&self.evictions, "cache_evictions_total"),
+            RemovalCause::Expired => self.add(&self.expirations, "cache_expirations_total"),
+            RemovalCause::Explicit | RemovalCause::Replaced => {}
+        }
+    }
+}
 
 /// Generic cache wrapper
+///
+/// Clones share the entries and statistics.
 pub struct CacheContainer<K, V> {
-    cache: Cache<K, V>,
+    cache: Cache<K, Entry<V>>,
+    counters: Arc<Counters>,
+    /// Cache-wide TTL, checked against `clock` when it is not the system clock
+    ttl: Option<Duration>,
+    clock: Clock,
+}
+
+impl<K, V> Clone for CacheContainer<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            counters: self.counters.clone(),
+            ttl: self.ttl,
+            clock: self.clock.clone(),
+        }
+    }
 }
 
 impl<K: Hash + Eq + Send + Sync + 'static, V: Clone + Send + Sync + 'static> CacheContainer<K, V> {
     /// Create a new cache with capacity
     pub fn new(capacity: u64) -> Self {
-        Self {
-            cache: Cache::new(capacity),
-        }
+        Self::build(Cache::builder().max_capacity(capacity), None)
     }
 
     /// Create a new cache with TTL
     pub fn with_ttl(capacity: u64, ttl: Duration) -> Self {
-        let cache = Cache::builder()
-            .max_capacity(capacity)
-            .time_to_live(ttl)
+        Self::build(Cache::builder().max_capacity(capacity).time_to_live(ttl), Some(ttl))
+    }
+
+    /// Create a cache from configuration
+    pub fn from_config(config: &CacheConfig) -> Self {
+        let mut builder = Cache::builder().max_capacity(config.capacity);
+        if let Some(ttl) = config.ttl {
+            builder = builder.time_to_live(ttl.into());
+        }
+        if let Some(idle) = config.idle {
+            builder = builder.time_to_idle(idle.into());
+        }
+        builder = builder.eviction_policy(match config.eviction {
+            Eviction::Lru => EvictionPolicy::lru(),
+            Eviction::Lfu => EvictionPolicy::tiny_lfu(),
+        });
+        Self::build(builder, config.ttl.map(Into::into))
+    }
+
+    fn build(builder: CacheBuilder<K, Entry<V>, Cache<K, Entry<V>>>, ttl: Option<Duration>) -> Self {
+        let counters = Arc::new(Counters::default());
+        let listener = counters.clone();
+        let cache = builder
+            .expire_after(EntryExpiry)
+            .eviction_listener(move |_key, _value, cause| listener.removed(cause))
             .build();
-        Self { cache }
+        Self {
+            cache,
+            counters,
+            ttl,
+            clock: Clock::system(),
+        }
+    }
+
+    /// Expire entries by `clock` instead of the system clock
+    ///
+    /// Entries already cached keep the time they were stored at, so set the
+    /// clock before the first insert.
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Export hits, misses, loads and evictions as `cache_*_total` counters labelled `cache = name`
+    ///
+    /// Only the first name given to a cache (or any of its clones) is used.
+    pub fn metrics(self, name: &str) -> Self {
+        let _ = self.counters.name.set(name.to_string());
+        self
+    }
+
+    fn entry(&self, value: V, ttl: Option<Duration>) -> Entry<V> {
+        Entry {
+            value,
+            ttl,
+            stored: self.clock.now(),
+        }
+    }
+
+    /// Whether `entry` outlived its TTL on a clock other than the system one
+    ///
+    /// moka expires entries by the system clock on its own.
+    fn expired(&self, entry: &Entry<V>) -> bool {
+        if self.clock.is_system() {
+            return false;
+        }
+        let ttl = match (entry.ttl, self.ttl) {
+            (Some(entry), Some(cache)) => Some(entry.min(cache)),
+            (entry, cache) => entry.or(cache),
+        };
+        ttl.is_some_and(|ttl| self.clock.elapsed(entry.stored) >= ttl)
+    }
+
+    /// Drop the entry of `key` if it expired by the cache's clock
+    async fn drop_expired(&self, key: &K) {
+        if self.clock.is_system() {
+            return;
+        }
+        if let Some(entry) = self.cache.get(key).await {
+            if self.expired(&entry) {
+                self.cache.invalidate(key).await;
+                self.counters.add(&self.counters.expirations, "cache_expirations_total");
+            }
+        }
     }
 
     /// Get a value
     pub async fn get(&self, key: &K) -> Option<V> {
-        self.cache.get(key).await
+        self.drop_expired(key).await;
+        let value = self.cache.get(key).await.map(|entry| entry.value);
+        match value {
+            Some(_) => self.counters.hit(),
+            None => self.counters.miss(),
+        }
+        value
     }
 
     /// Insert a value
     pub async fn insert(&self, key: K, value: V) {
-        self.cache.insert(key, value).await;
+        self.cache.insert(key, self.entry(value, None)).await;
+    }
+
+    /// Insert a value that expires after `ttl`, or earlier under the cache's own TTL
+    pub async fn insert_with_ttl(&self, key: K, value: V, ttl: Duration) {
+        self.cache.insert(key, self.entry(value, Some(ttl))).await;
+    }
+
+    /// Get a value, or load, insert and return it when missing
+    ///
+    /// Concurrent calls for the same missing key run `loader` once; the
+    /// others wait and get its value. `ttl` applies to the loaded entry.
+    pub async fn get_or_set_with<F, Fut>(&self, key: K, ttl: Option<Duration>, loader: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        self.drop_expired(&key).await;
+        let counters = &self.counters;
+        let entry = self
+            .cache
+            .entry(key)
+            .or_insert_with(async move {
+                counters.load();
+                self.entry(loader().await, ttl)
+            })
+            .await;
+        self.record_lookup(entry.is_fresh());
+        entry.into_value().value
+    }
+
+    /// Like `get_or_set_with` for a loader that can fail; errors are not cached
+    ///
+    /// Callers that waited on another caller's failed load get its error as
+    /// `RfError::Internal` with the same message.
+    pub async fn try_get_or_set_with<F, Fut>(&self, key: K, ttl: Option<Duration>, loader: F) -> Result<V>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        self.drop_expired(&key).await;
+        let counters = &self.counters;
+        // The caller whose loader ran gets its error as is, moka only shares the message
+        let mut failure = None;
+        let slot = &mut failure;
+        let result = self
+            .cache
+            .entry(key)
+            .or_try_insert_with(async move {
+                counters.load();
+                match loader().await {
+                    Ok(value) => Ok(self.entry(value, ttl)),
+                    Err(e) => {
+                        counters.load_failure();
+                        let message = e.to_string();
+                        *slot = Some(e);
+                        Err(message)
+                    }
+                }
+            })
+            .await;
+        match result {
+            Ok(entry) => {
+                self.record_lookup(entry.is_fresh());
+                Ok(entry.into_value().value)
+            }
+            Err(message) => {
+                self.counters.miss();
+                Err(failure.unwrap_or_else(|| RfError::Internal(message.to_string())))
+            }
+        }
+    }
+
+    fn record_lookup(&self, loaded: bool) {
+        if loaded {
+            self.counters.miss();
+        } else {
+            self.counters.hit();
+        }
     }
 
     /// Remove a value
@@ -52,4 +452,233 @@ impl<K: Hash + Eq + Send + Sync + 'static, V: Clone + Send + Sync + 'static> Cac
     pub async fn clear(&self) {
         self.cache.invalidate_all();
     }
+
+    /// Get cache statistics
+    pub async fn stats(&self) -> CacheStats {
+        // Apply pending inserts and evictions so the counts are current
+        self.cache.run_pending_tasks().await;
+        let counters = &self.counters;
+        CacheStats {
+            entry_count: self.cache.entry_count(),
+            weighted_size: self.cache.weighted_size(),
+            capacity: self.cache.policy().max_capacity(),
+            hits: counters.hits.load(Ordering::Relaxed),
+            misses: counters.misses.load(Ordering::Relaxed),
+            loads: counters.loads.load(Ordering::Relaxed),
+            load_failures: counters.load_failures.load(Ordering::Relaxed),
+            evictions: counters.evictions.load(Ordering::Relaxed),
+            expirations: counters.expirations.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Delay before resubscribing after the invalidation channel dropped
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Two-tier byte cache: memory first, then Redis, with invalidation broadcast
+///
+/// Reads try the local cache, then Redis, and keep what Redis returned
+/// locally. Writes go to Redis and announce the key on the `{prefix}invalidate`
+/// channel; the other instances drop their local copy and read the new value
+/// from Redis on the next lookup. If the channel drops, the local cache is
+/// cleared and the subscription retried, as invalidations may have been
+/// missed meanwhile. Redis errors are logged and the cache falls back to its
+/// local tier, so an outage degrades to per-instance caching.
+///
+/// ```rust,ignore
+/// let cache = Arc::new(TieredCache::new(gins::redis(None).await?, "users", &CacheConfig::default()).await?);
+/// let users = Model::new(db, "users").cache(cache.clone(), Duration::from_secs(60));
+/// ```
+pub struct TieredCache {
+    local: CacheContainer<String, Vec<u8>>,
+    redis: Arc<RedisClient>,
+    prefix: String,
+    channel: String,
+    redis_ttl: Option<Duration>,
+    /// Identifies this instance's broadcasts, which it skips
+    origin: String,
+    listener: Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+impl TieredCache {
+    /// Create a cache named `name` storing Redis keys under `cache:{name}:`
+    ///
+    /// `config` sizes the local tier; its TTL also applies to Redis entries.
+    /// Subscribes to the invalidation channel before returning.
+    pub async fn new(redis: Arc<RedisClient>, name: &str, config: &CacheConfig) -> Result<Self> {
+        let local = CacheContainer::from_config(config);
+        let prefix = format!("cache:{}:", name);
+        let channel = format!("{}invalidate", prefix);
+        let origin = uuid::Uuid::new_v4().simple().to_string();
+        let pubsub = redis.pubsub();
+        let messages = pubsub.subscribe(&[&channel]).await?;
+        let listener = tokio::spawn(listen(pubsub, channel.clone(), origin.clone(), local.clone(), messages));
+        Ok(Self {
+            local,
+            redis,
+            prefix,
+            channel,
+            redis_ttl: config.ttl.map(Into::into),
+            origin,
+            listener: Mutex::new(Some(listener)),
+        })
+    }
+
+    /// Expire Redis entries after `ttl` instead of the local TTL
+    pub fn redis_ttl(mut self, ttl: Duration) -> Self {
+        self.redis_ttl = Some(ttl);
+        self
+    }
+
+    /// Export local hit and miss counters, see `CacheContainer::metrics`
+    pub fn metrics(self, name: &str) -> Self {
+        // Clones share the counters, so naming a clone names the local tier
+        let _ = self.local.clone().metrics(name);
+        self
+    }
+
+    /// Get a value from memory, or from Redis on a local miss
+    pub async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        if let Some(value) = self.local.get(&key.to_string()).await {
+            return Some(value);
+        }
+        match self.redis.get_bytes(&self.redis_key(key)).await {
+            Ok(Some(value)) => {
+                self.local.insert(key.to_string(), value.clone()).await;
+                Some(value)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!("Tiered cache read from Redis failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Store a value in both tiers and tell the other instances to drop theirs
+    pub async fn set(&self, key: &str, value: Vec<u8>) {
+        let redis_key = self.redis_key(key);
+        let written = match self.redis_ttl {
+            Some(ttl) => self.redis.string().set_bytes_ex(&redis_key, &value, ttl.as_secs().max(1)).await,
+            None => self.redis.set_bytes(&redis_key, &value).await,
+        };
+        if let Err(e) = written {
+            tracing::warn!("Tiered cache write to Redis failed: {}", e);
+        }
+        self.local.insert(key.to_string(), value).await;
+        self.broadcast(Some(key)).await;
+    }
+
+    /// Remove a value from both tiers on every instance
+    pub async fn invalidate(&self, key: &str) {
+        self.local.remove(&key.to_string()).await;
+        if let Err(e) = self.redis.generic().del(&[&self.redis_key(key)]).await {
+            tracing::warn!("Tiered cache delete from Redis failed: {}", e);
+        }
+        self.broadcast(Some(key)).await;
+    }
+
+    /// Remove all values from both tiers on every instance
+    pub async fn clear(&self) {
+        self.local.clear().await;
+        let deleted = async {
+            let keys = self.redis.generic().keys(&format!("{}*", self.prefix)).await?;
+            let keys: Vec<&str> = keys.iter().map(String::as_str).filter(|key| *key != self.channel).collect();
+            if !keys.is_empty() {
+                self.redis.generic().del(&keys).await?;
+            }
+            Ok::<_, RfError>(())
+        };
+        if let Err(e) = deleted.await {
+            tracing::warn!("Tiered cache clear in Redis failed: {}", e);
+        }
+        self.broadcast(None).await;
+    }
+
+    /// Local tier statistics
+    pub async fn stats(&self) -> CacheStats {
+        self.local.stats().await
+    }
+
+    fn redis_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    /// Announce a changed key, or `None` for all keys
+    async fn broadcast(&self, key: Option<&str>) {
+        let message = match key {
+            Some(key) => format!("{} {}", self.origin, key),
+            None => self.origin.clone(),
+        };
+        if let Err(e) = self.redis.pubsub().publish(&self.channel, &message).await {
+            tracing::warn!("Tiered cache invalidation broadcast failed: {}", e);
+        }
+    }
+}
+
+impl Drop for TieredCache {
+    fn drop(&mut self) {
+        if let Some(listener) = self.listener.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            listener.abort();
+        }
+    }
+}
+
+/// Apply invalidations broadcast by other instances to the local tier
+async fn listen(
+    pubsub: PubSubGroup,
+    channel: String,
+    origin: String,
+    local: CacheContainer<String, Vec<u8>>,
+    mut messages: Subscription,
+) {
+    loop {
+        while let Some(message) = messages.next().await {
+            let (sender, key) = match message.split_once(' ') {
+                Some((sender, key)) => (sender, Some(key)),
+                None => (message.as_str(), None),
+            };
+            if sender == origin {
+                continue;
+            }
+            match key {
+                Some(key) => local.remove(&key.to_string()).await,
+                None => local.clear().await,
+            }
+        }
+        tracing::warn!("Tiered cache invalidation channel {} dropped, resubscribing", channel);
+        local.clear().await;
+        messages = loop {
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            match pubsub.subscribe(&[&channel]).await {
+                Ok(messages) => break messages,
+                Err(e) => tracing::warn!("Tiered cache resubscribe failed: {}", e),
+            }
+        };
+        // Drop what changed while the channel was down
+        local.clear().await;
+    }
+}
+
+impl CacheStore for TieredCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<Vec<u8>>> {
+        Box::pin(TieredCache::get(self, key))
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: Vec<u8>) -> BoxFuture<'a, ()> {
+        Box::pin(TieredCache::set(self, key, value))
+    }
+
+    fn invalidate<'a>(&'a self, key: &'a str) -> BoxFuture<'a, ()> {
+        Box::pin(TieredCache::invalidate(self, key))
+    }
+
+    fn invalidate_table<'a>(&'a self, _table: &'a str) -> BoxFuture<'a, ()> {
+        // Query keys are not tracked per table, as in `QueryCache`
+        Box::pin(TieredCache::clear(self))
+    }
+
+    fn clear(&self) -> BoxFuture<'_, ()> {
+        Box::pin(TieredCache::clear(self))
+    }
 }
@@ -8,8 +8,42 @@
 //! - **Cfg**: 传统配置管理器（使用 config crate）
 //! - **adapter**: 配置适配器（文件、环境变量、内存等）
 //! - **encryption**: 配置加密/解密
+//! - **secrets**: 密钥引用解析（`ENC(...)` 加密值、Vault 等外部密钥管理）
 //! - **validation**: 配置验证
+//! - **schema**: 配置结构声明与启动诊断
+//! - **duration**: 时长配置类型（`30s`、`5m`、`1h30m`）
 //! - **watcher**: 配置文件监控
+//! - **reload**: 配置热加载与变更通知
+//! - **profile**: 环境配置（`config.{profile}.toml` 覆盖文件）
+//!
+//! ## 分层配置与优先级
+//!
+//! `Config` 的适配器按添加顺序叠加，后添加的覆盖先添加的。`Config::layered` 按以下顺序
+//! 组合常用的配置源（优先级从低到高）：
+//!
+//! 1. 默认值：`Config::with_defaults`，总是位于最底层
+//! 2. 配置文件：`FileConfigAdapter`，TOML、YAML 或 JSON
+//! 3. 环境配置文件：当前环境（`RF_ENV`，未设置时取 `RF_MODE` 对应的 `dev` / `test` / `staging` / `prod`）
+//!    的覆盖文件，如 `config.prod.toml`，不存在时跳过
+//! 4. 环境变量：`EnvConfigAdapter::with_prefix("RF_")`，`__` 表示层级，
+//!    如 `RF_SERVER__PORT` 对应 `server.port`
+//! 5. 命令行参数：`ArgsConfigAdapter`，如 `--server.port=8080`
+//!
+//! 合并以键为单位：高优先级的源只覆盖它设置的键，其余键仍取自低优先级的源。
+//! 读取时可以用 `get_int`、`get_bool`、`get_duration` 取得类型化的值，
+//! 或用 `get_struct` 把某个前缀下的所有键反序列化为结构体。
+//!
+//! ## 热加载
+//!
+//! `Config::watch` 监控配置文件，文件变化后重新加载并把变更的键通知给 `on_change`
+//! 注册的回调。每个文件整体解析成功后才替换旧值，解析失败时保留原有配置。
+//!
+//! ## 密钥
+//!
+//! 数据库密码、API Key 等不以明文写在配置中，而是写成引用：`ENC(...)` 由
+//! `EncryptedSecrets` 用主密钥（`RF_MASTER_KEY`）解密，`vault:<path>#<key>` 由
+//! `rf-contrib-config` 的 `VaultSecrets` 从 HashiCorp Vault 读取。通过
+//! `Config::with_secrets` 添加的解析器在读取时（`get`、`all` 及其上的类型化读取）解析引用。
 //!
 //! @author TimonQWQ
 //! @date 2026-01-06
@@ -20,12 +54,23 @@ pub mod adapter;
 pub mod watcher;
 pub mod validation;
 pub mod encryption;
+pub mod schema;
+pub mod duration;
+pub mod reload;
+pub mod secrets;
+pub mod profile;
+mod de;
 
 // 导出子模块的公共接口
 pub use adapter::*;
 pub use watcher::*;
 pub use validation::*;
 pub use encryption::*;
+pub use duration::{serde_duration, RfDuration};
+pub use reload::{ConfigChange, ConfigChangeHandler, ConfigWatch};
+pub use secrets::{EncryptedSecrets, SecretProvider, MASTER_KEY_ENV};
+pub use profile::{current_profile, profile_path, PROFILE_ENV};
+pub use schema::{ConfigIssue, ConfigLocation, ConfigReport, ConfigSchema, IssueKind, KeySpec};
 
 use rf_errors::Result;
 use std::collections::HashMap;
@@ -39,7 +84,7 @@ use std::sync::Arc;
 ///
 /// # 功能特性
 ///
-/// - 支持多个配置适配器（优先级顺序）
+/// - 支持多个配置适配器（后添加的优先级更高）
 /// - 支持配置验证
 /// - 支持配置加密/解密
 /// - 自动合并多个配置源
@@ -49,6 +94,8 @@ use std::sync::Arc;
 /// - `adapters`: 配置适配器列表（按优先级排序）
 /// - `validator`: 配置验证器（可选）
 /// - `encryption`: 配置加密器（可选）
+/// - `secrets`: 密钥引用解析器，按添加顺序尝试
+/// - `profile`: 加载的环境名（`layered` 创建时设置）
 ///
 /// # 示例
 ///
@@ -66,8 +113,16 @@ pub struct Config {
     adapters: Vec<Arc<dyn ConfigAdapter>>,
     validator: Option<ConfigValidator>,
     encryption: Option<Arc<dyn ConfigEncryption>>,
+    secrets: Vec<Arc<dyn SecretProvider>>,
+    schemas: Vec<Arc<ConfigSchema>>,
+    listeners: reload::Listeners,
+    profile: Option<String>,
+    strict: Option<bool>,
 }
 
+/// 配置中运行模式的键，见 `Config::apply_mode`
+pub const MODE_KEY: &str = "app.mode";
+
 impl Config {
     /// 创建新的配置管理器
     ///
@@ -79,12 +134,17 @@ impl Config {
             adapters: Vec::new(),
             validator: None,
             encryption: None,
+            secrets: Vec::new(),
+            schemas: Vec::new(),
+            listeners: reload::Listeners::default(),
+            profile: None,
+            strict: None,
         }
     }
 
     /// 添加配置适配器
     ///
-    /// 配置适配器按添加顺序进行查询，后面的适配器会覆盖前面的配置。
+    /// 后添加的适配器优先级更高，会覆盖前面适配器中的同名键。
     ///
     /// # 参数
     ///
@@ -98,6 +158,81 @@ impl Config {
         self
     }
 
+    /// 创建分层配置：配置文件 < 环境配置文件 < `RF_` 环境变量 < 命令行参数
+    ///
+    /// 环境由 `current_profile()` 决定（`RF_ENV`，未设置时取 `RF_MODE`），环境配置文件为
+    /// 配置文件旁的 `config.{profile}.toml`，创建时存在才加载。配置文件不存在时该层为空。
+    /// 需要默认值时再调用 `with_defaults`。
+    ///
+    /// # 参数
+    ///
+    /// - `path`: 配置文件路径（按扩展名识别 TOML、YAML 或 JSON）
+    ///
+    /// # 示例
+    ///
+    /// ```rust,ignore
+    /// // RF_ENV=prod 时 config.prod.toml 覆盖 config.toml；
+    /// // RF_SERVER__PORT=9000 或 --server.port=9000 再覆盖文件中的 server.port
+    /// let config = Config::layered("config.toml")?
+    ///     .with_defaults([("server.port", "8080"), ("server.host", "0.0.0.0")]);
+    /// let port = config.get_int("server.port")?;
+    /// ```
+    pub fn layered(path: &str) -> Result<Self> {
+        Self::layered_profile(path, &current_profile())
+    }
+
+    /// 按指定环境创建分层配置
+    ///
+    /// 与 `layered` 相同，但使用 `profile` 而不是当前环境，例如在开发机上查看生产配置。
+    ///
+    /// # 参数
+    ///
+    /// - `path`: 配置文件路径
+    /// - `profile`: 环境名，如 `dev`、`test`、`prod`
+    pub fn layered_profile(path: &str, profile: &str) -> Result<Self> {
+        let mut config = Self::new().adapter(Arc::new(FileConfigAdapter::new(path)?));
+        let overlay = profile_path(path, profile);
+        if std::path::Path::new(&overlay).exists() {
+            config = config.adapter(Arc::new(FileConfigAdapter::new(&overlay)?));
+        }
+        config = config
+            .adapter(Arc::new(EnvConfigAdapter::with_prefix("RF_")))
+            .adapter(Arc::new(ArgsConfigAdapter::from_env()));
+        config.profile = Some(profile.to_string());
+        Ok(config)
+    }
+
+    /// 当前加载的环境
+    ///
+    /// # 返回值
+    ///
+    /// 由 `layered` / `layered_profile` 创建时返回加载的环境名，否则返回 `None`
+    pub fn active_profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// 设置默认值
+    ///
+    /// 默认值位于最低优先级，任何适配器中的同名键都会覆盖它。多次调用时后设置的默认值优先。
+    ///
+    /// # 参数
+    ///
+    /// - `values`: 键值对
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `self`，支持链式调用
+    pub fn with_defaults<I, K, V>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let values = values.into_iter().map(|(key, value)| (key.into(), value.into())).collect();
+        self.adapters.insert(0, Arc::new(MemoryConfigAdapter::from_values(values)));
+        self
+    }
+
     /// 设置配置验证器
     ///
     /// # 参数
@@ -126,10 +261,54 @@ impl Config {
         self
     }
 
+    /// 添加密钥引用解析器
+    ///
+    /// 读取配置时，值中的密钥引用（如 `ENC(...)`、`vault:secret/data/app#password`）
+    /// 交给解析器替换为明文；按添加顺序尝试，第一个识别该值的解析器生效。
+    /// 明文只在读取时产生，不会写回配置源。
+    ///
+    /// # 参数
+    ///
+    /// - `provider`: 密钥解析器，如 `EncryptedSecrets`
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `self`，支持链式调用
+    pub fn with_secrets(mut self, provider: Arc<dyn SecretProvider>) -> Self {
+        self.secrets.push(provider);
+        self
+    }
+
+    /// 解析值中的密钥引用
+    fn resolve_secrets(&self, value: String) -> Result<String> {
+        for provider in &self.secrets {
+            if let Some(resolved) = provider.resolve(&value) {
+                return resolved;
+            }
+        }
+        Ok(value)
+    }
+
+    /// 添加配置结构声明
+    ///
+    /// 除全局注册的结构（`schema::register`）外，`validate` 还会检查此处添加的结构。
+    ///
+    /// # 参数
+    ///
+    /// - `schema`: 配置结构
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `self`，支持链式调用
+    pub fn with_schema(mut self, schema: ConfigSchema) -> Self {
+        self.schemas.push(Arc::new(schema));
+        self
+    }
+
     /// 获取配置值
     ///
-    /// 按适配器顺序查询，找到第一个匹配的配置值。
-    /// 如果启用了加密，会自动解密配置值。
+    /// 从优先级最高（最后添加）的适配器开始查询，返回第一个匹配的配置值。
+    /// 如果启用了加密，会自动解密配置值；值中的密钥引用由 `with_secrets` 添加的解析器替换。
     ///
     /// # 参数
     ///
@@ -139,24 +318,125 @@ impl Config {
     ///
     /// 返回 `Result<Option<String>>`，如果配置存在则返回 Some，否则返回 None
     pub fn get(&self, key: &str) -> Result<Option<String>> {
-        // 按顺序尝试适配器
-        for adapter in &self.adapters {
+        // 从优先级最高的适配器开始
+        for adapter in self.adapters.iter().rev() {
             if let Ok(Some(mut value)) = adapter.get(key) {
                 // 如果启用了加密，解密配置值
                 if let Some(ref encryption) = self.encryption {
                     value = encryption.decrypt(&value)?;
                 }
-                return Ok(Some(value));
+                return self.resolve_secrets(value).map(Some);
             }
         }
         Ok(None)
     }
 
+    /// 获取整数配置值
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `Result<Option<i64>>`，未配置时返回 None，格式错误时返回 `RfError::Config`
+    pub fn get_int(&self, key: &str) -> Result<Option<i64>> {
+        let Some(value) = self.get(key)? else {
+            return Ok(None);
+        };
+        value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| rf_errors::RfError::Config(format!(
+                "Configuration key '{}' must be an integer (got {:?})",
+                key, value
+            )))
+    }
+
+    /// 获取布尔配置值
+    ///
+    /// 接受 `true`/`false`、`1`/`0`、`yes`/`no`、`on`/`off`（不区分大小写）。
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `Result<Option<bool>>`，未配置时返回 None，格式错误时返回 `RfError::Config`
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>> {
+        let Some(value) = self.get(key)? else {
+            return Ok(None);
+        };
+        de::parse_bool(&value)
+            .map(Some)
+            .ok_or_else(|| rf_errors::RfError::Config(format!(
+                "Configuration key '{}' must be a boolean such as true or false (got {:?})",
+                key, value
+            )))
+    }
+
+    /// 把某个前缀下的所有配置反序列化为结构体
+    ///
+    /// 所有配置层合并后再反序列化，因此环境变量或命令行参数可以只覆盖结构体的个别字段。
+    /// 字符串值按目标字段的类型解析：数字、布尔值、时长（配合 `serde_duration` 或
+    /// `RfDuration`）、逗号分隔的列表；空字符串对应 `None`，`servers.0.host` 这样的
+    /// 数字下标对应数组元素。
+    ///
+    /// # 参数
+    ///
+    /// - `prefix`: 键前缀，如 `database.default`；空字符串表示全部配置
+    ///
+    /// # 返回值
+    ///
+    /// 前缀下没有任何配置时按空表反序列化（全部字段使用 `#[serde(default)]` 时得到默认值），
+    /// 类型不匹配时返回 `RfError::Config`
+    ///
+    /// # 示例
+    ///
+    /// ```rust,ignore
+    /// #[derive(Deserialize)]
+    /// struct ServerConfig {
+    ///     host: String,
+    ///     port: u16,
+    ///     #[serde(with = "rf_os::cfg::serde_duration")]
+    ///     request_timeout: Duration,
+    /// }
+    ///
+    /// let server: ServerConfig = config.get_struct("server")?;
+    /// ```
+    pub fn get_struct<T: serde::de::DeserializeOwned>(&self, prefix: &str) -> Result<T> {
+        let values = self.all()?;
+        let node = de::Node::build(&values, prefix).unwrap_or_else(|| de::Node::Table(Default::default()));
+        T::deserialize(node).map_err(|e| {
+            let section = if prefix.is_empty() { "configuration" } else { prefix };
+            rf_errors::RfError::Config(format!("Failed to read '{}': {}", section, e))
+        })
+    }
+
+    /// 获取时长配置值
+    ///
+    /// 支持 `30s`、`5m`、`1h30m`、`250ms` 等写法，纯数字按秒解析。
+    ///
+    /// # 参数
+    ///
+    /// - `key`: 配置键
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `Result<Option<Duration>>`，未配置时返回 None，格式错误时返回 `RfError::Config`
+    pub fn get_duration(&self, key: &str) -> Result<Option<std::time::Duration>> {
+        let Some(value) = self.get(key)? else {
+            return Ok(None);
+        };
+        value
+            .parse::<RfDuration>()
+            .map(|duration| Some(duration.into()))
+            .map_err(|_| rf_errors::RfError::Config(format!(
+                "Configuration key '{}' must be a duration such as 30s or 5m (got {:?})",
+                key, value
+            )))
+    }
+
     /// 设置配置值
     ///
     /// 如果设置了验证器，会先验证配置值。
     /// 如果启用了加密，会自动加密配置值。
-    /// 配置值会写入第一个可写的适配器。
+    /// 配置值会写入优先级最高的可写适配器；环境变量和命令行参数不可写，
+    /// 它们设置的键仍会覆盖写入的值。
     ///
     /// # 参数
     ///
@@ -179,8 +459,8 @@ impl Config {
             value.to_string()
         };
 
-        // 写入第一个可写的适配器
-        for adapter in &self.adapters {
+        // 写入优先级最高的可写适配器
+        for adapter in self.adapters.iter().rev() {
             if adapter.set(key, &final_value).is_ok() {
                 return Ok(());
             }
@@ -212,6 +492,11 @@ impl Config {
         if let Some(ref encryption) = self.encryption {
             result = decrypt_config(&result, encryption.as_ref())?;
         }
+        if !self.secrets.is_empty() {
+            for value in result.values_mut() {
+                *value = self.resolve_secrets(std::mem::take(value))?;
+            }
+        }
 
         // 如果设置了验证器，验证所有配置
         if let Some(ref validator) = self.validator {
@@ -220,6 +505,212 @@ impl Config {
 
         Ok(result)
     }
+
+    /// 注册配置变更回调
+    ///
+    /// `reload` 或 `watch` 重新加载配置后，如果 `prefix` 下（`prefix` 本身或以 `prefix.`
+    /// 开头）有键被新增、修改或删除，以这些变更调用回调。空前缀接收所有变更。
+    /// 回调在重新加载的线程上执行，应尽快返回。
+    ///
+    /// # 参数
+    ///
+    /// - `prefix`: 键前缀，如 `logger` 或 `rate_limit.api`
+    /// - `callback`: 回调函数
+    ///
+    /// # 返回值
+    ///
+    /// 返回回调 ID，可用于 `remove_listener`
+    ///
+    /// # 示例
+    ///
+    /// ```rust,ignore
+    /// config.on_change("logger", |changes| {
+    ///     for change in changes {
+    ///         println!("{}: {:?} -> {:?}", change.key, change.old, change.new);
+    ///     }
+    /// });
+    /// ```
+    pub fn on_change<F>(&self, prefix: &str, callback: F) -> u64
+    where
+        F: Fn(&[ConfigChange]) + Send + Sync + 'static,
+    {
+        self.listeners.subscribe(prefix, Arc::new(callback))
+    }
+
+    /// 移除配置变更回调
+    ///
+    /// # 返回值
+    ///
+    /// 回调存在并已移除时返回 true
+    pub fn remove_listener(&self, id: u64) -> bool {
+        self.listeners.unsubscribe(id)
+    }
+
+    /// 重新加载所有适配器并通知变更
+    ///
+    /// 每个适配器整体替换自己的值：解析失败的文件保留原有配置，其他适配器照常更新。
+    ///
+    /// # 返回值
+    ///
+    /// 返回变更的键（按键排序）；有适配器加载失败时，在通知其他变更后返回第一个错误
+    pub fn reload(&self) -> Result<Vec<ConfigChange>> {
+        let before = self.all().unwrap_or_default();
+        let mut error = None;
+        for adapter in &self.adapters {
+            if let Err(e) = adapter.reload() {
+                tracing::warn!("Failed to reload {}: {}", adapter.source_path().unwrap_or("configuration"), e);
+                error.get_or_insert(e);
+            }
+        }
+        let after = self.all()?;
+        let changes = reload::diff(&before, &after);
+        if !changes.is_empty() {
+            self.listeners.dispatch(&changes);
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(changes),
+        }
+    }
+
+    /// 监控配置文件，变化时自动重新加载
+    ///
+    /// 监控所有带文件来源的适配器（如 `FileConfigAdapter`），短时间内的连续修改合并为一次
+    /// 重新加载，之后通知 `on_change` 回调。返回的 `ConfigWatch` 被丢弃时停止监控；
+    /// 配置被释放后监控也随之结束。
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `Result<ConfigWatch>`，没有可监控的文件时返回 `RfError::Config`
+    ///
+    /// # 示例
+    ///
+    /// ```rust,ignore
+    /// let config = Arc::new(Config::layered("config.toml")?);
+    /// config.on_change("logger.level", |changes| {
+    ///     if let Some(level) = changes[0].new.as_deref() {
+    ///         let _ = rf_os::log::set_level(level);
+    ///     }
+    /// });
+    /// let _watch = config.watch()?;
+    /// ```
+    pub fn watch(self: &Arc<Self>) -> Result<ConfigWatch> {
+        let files: Vec<std::path::PathBuf> = self
+            .adapters
+            .iter()
+            .filter_map(|adapter| adapter.source_path().map(std::path::PathBuf::from))
+            .collect();
+        if files.is_empty() {
+            return Err(rf_errors::RfError::Config("No configuration file to watch".to_string()));
+        }
+        reload::watch(Arc::downgrade(self), files)
+    }
+
+    /// 获取配置值，未配置时返回结构中声明的默认值
+    ///
+    /// # 参数
+    ///
+    /// - `key`: 配置键
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `Result<Option<String>>`，既未配置也没有默认值时返回 None
+    pub fn get_or_default(&self, key: &str) -> Result<Option<String>> {
+        if let Some(value) = self.get(key)? {
+            return Ok(Some(value));
+        }
+        Ok(self.all_schemas()
+            .iter()
+            .find_map(|schema| schema.default_for(key))
+            .map(str::to_string))
+    }
+
+    /// 查询配置键的定义位置（文件与行号）
+    ///
+    /// 按 `all` 的覆盖顺序，返回最终生效值所在的位置；适配器不记录位置时返回 None。
+    pub fn location(&self, key: &str) -> Option<ConfigLocation> {
+        self.adapters
+            .iter()
+            .rev()
+            .find(|adapter| matches!(adapter.get(key), Ok(Some(_))))
+            .and_then(|adapter| adapter.location(key))
+    }
+
+    /// 按配置结构检查所有配置，返回诊断报告
+    ///
+    /// 报告包含未知键（警告）、类型不匹配和缺失的必填键（错误）。
+    pub fn diagnose(&self) -> Result<ConfigReport> {
+        let values = self.all()?;
+        Ok(schema::check(&values, &self.all_schemas(), |key| self.location(key)))
+    }
+
+    /// 设置严格校验：未知键也视为错误
+    ///
+    /// 未设置时由运行模式决定，预发布和生产模式严格（见 `rf_util::mode::strict_config`）。
+    pub fn with_strict_validation(mut self, strict: bool) -> Self {
+        self.strict = Some(strict);
+        self
+    }
+
+    /// 按配置中的 `app.mode` 设置运行模式
+    ///
+    /// 配置了 `app.mode` 时调用 `rf_util::mode::set`，之后各模块的默认行为随之切换；
+    /// 未配置时保持环境变量决定的模式。返回生效的模式，值无效时返回 `RfError::Config`。
+    ///
+    /// 环境配置文件由模式选择，因此该键应写在基础配置文件中。
+    pub fn apply_mode(&self) -> Result<rf_util::mode::Mode> {
+        if let Some(value) = self.get(MODE_KEY)? {
+            let mode: rf_util::mode::Mode = value.parse()?;
+            rf_util::mode::set(mode);
+        }
+        Ok(rf_util::mode::get())
+    }
+
+    /// 启动时校验配置
+    ///
+    /// 记录所有问题的日志；存在错误时返回 `RfError::Config`，
+    /// 错误信息逐行列出问题及其文件位置，避免悄悄回退到默认值。
+    /// 严格校验（见 `with_strict_validation`）时未知键同样导致失败。
+    ///
+    /// # 示例
+    ///
+    /// ```rust,ignore
+    /// let config = Config::new().adapter(Arc::new(FileConfigAdapter::new("config.toml")?));
+    /// config.validate()?;
+    /// ```
+    pub fn validate(&self) -> Result<ConfigReport> {
+        let report = self.diagnose()?;
+        let strict = self.strict.unwrap_or_else(rf_util::mode::strict_config);
+        if !strict {
+            for issue in report.warnings() {
+                tracing::warn!("{}", issue);
+            }
+        }
+        if report.is_ok() && (!strict || report.issues.is_empty()) {
+            return Ok(report);
+        }
+        let errors: Vec<String> = report
+            .issues
+            .iter()
+            .filter(|issue| strict || issue.is_error())
+            .map(|issue| issue.to_string())
+            .collect();
+        for error in &errors {
+            tracing::error!("{}", error);
+        }
+        Err(rf_errors::RfError::Config(format!(
+            "Invalid configuration:\n{}",
+            errors.join("\n")
+        )))
+    }
+
+    fn all_schemas(&self) -> Vec<Arc<ConfigSchema>> {
+        schema::registered()
+            .into_iter()
+            .map(|(_, schema)| schema)
+            .chain(self.schemas.iter().cloned())
+            .collect()
+    }
 }
 
 impl Default for Config {
@@ -7,6 +7,7 @@
 
 //! Configuration adapter system
 
+use super::schema::ConfigLocation;
 use rf_errors::Result;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -22,30 +23,66 @@ pub trait ConfigAdapter: Send + Sync {
     
     /// Get all configuration
     fn all(&self) -> Result<HashMap<String, String>>;
+
+    /// Where a key was defined, if the adapter tracks it
+    fn location(&self, _key: &str) -> Option<ConfigLocation> {
+        None
+    }
+
+    /// File the values come from, watched by `Config::watch`
+    fn source_path(&self) -> Option<&str> {
+        None
+    }
+
+    /// Re-read the source, replacing all values at once
+    ///
+    /// On error the previous values stay in place. Adapters without a
+    /// reloadable source do nothing.
+    fn reload(&self) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// File-based configuration adapter
+///
+/// Loads a TOML, JSON or YAML file (chosen by extension) into flattened
+/// dotted keys: `[database.default] url = "..."` becomes
+/// `database.default.url`. Arrays of scalars are joined with commas. The
+/// line each key was defined on is kept for diagnostics.
 pub struct FileConfigAdapter {
-    _path: String,
+    path: String,
     data: Arc<RwLock<HashMap<String, String>>>,
+    lines: std::sync::RwLock<HashMap<String, usize>>,
 }
 
 impl FileConfigAdapter {
     /// Create a new file adapter
+    ///
+    /// A missing file yields an empty adapter.
     pub fn new(path: &str) -> Result<Self> {
-        let data = if std::path::Path::new(path).exists() {
-            let _content = std::fs::read_to_string(path)
-                .map_err(rf_errors::RfError::Io)?;
-            // Simplified - would parse actual config format
-            Arc::new(RwLock::new(HashMap::new()))
+        let (data, lines) = if std::path::Path::new(path).exists() {
+            Self::load(path)?
         } else {
-            Arc::new(RwLock::new(HashMap::new()))
+            (HashMap::new(), HashMap::new())
         };
         Ok(Self {
-            _path: path.to_string(),
-            data,
+            path: path.to_string(),
+            data: Arc::new(RwLock::new(data)),
+            lines: std::sync::RwLock::new(lines),
         })
     }
+
+    fn load(path: &str) -> Result<(HashMap<String, String>, HashMap<String, usize>)> {
+        let content = std::fs::read_to_string(path)
+            .map_err(rf_errors::RfError::Io)?;
+        let format = FileFormat::from_path(path);
+        Ok((parse_file(&content, format, path)?, locate_keys(&content, format)))
+    }
+
+    /// Path of the loaded file
+    pub fn path(&self) -> &str {
+        &self.path
+    }
 }
 
 impl ConfigAdapter for FileConfigAdapter {
@@ -65,21 +102,261 @@ impl ConfigAdapter for FileConfigAdapter {
         let data = futures::executor::block_on(self.data.read());
         Ok(data.clone())
     }
+
+    fn location(&self, key: &str) -> Option<ConfigLocation> {
+        // Keys inside inline tables and arrays resolve to their parent's line
+        let lines = self.lines.read().unwrap_or_else(|e| e.into_inner());
+        let mut candidate = key;
+        loop {
+            if let Some(line) = lines.get(candidate) {
+                return Some(ConfigLocation { file: self.path.clone(), line: *line });
+            }
+            candidate = &candidate[..candidate.rfind('.')?];
+        }
+    }
+
+    fn source_path(&self) -> Option<&str> {
+        Some(&self.path)
+    }
+
+    fn reload(&self) -> Result<()> {
+        // Parse fully before swapping, so readers never see a half-loaded file
+        let (data, lines) = Self::load(&self.path)?;
+        *futures::executor::block_on(self.data.write()) = data;
+        *self.lines.write().unwrap_or_else(|e| e.into_inner()) = lines;
+        Ok(())
+    }
+}
+
+/// Configuration file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl FileFormat {
+    fn from_path(path: &str) -> Self {
+        match std::path::Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("json") => FileFormat::Json,
+            Some("yaml") | Some("yml") => FileFormat::Yaml,
+            _ => FileFormat::Toml,
+        }
+    }
+}
+
+/// Parse a file into flattened dotted keys
+fn parse_file(content: &str, format: FileFormat, path: &str) -> Result<HashMap<String, String>> {
+    let source_format = match format {
+        FileFormat::Toml => ::config::FileFormat::Toml,
+        FileFormat::Json => ::config::FileFormat::Json,
+        FileFormat::Yaml => ::config::FileFormat::Yaml,
+    };
+    let value: serde_json::Value = ::config::Config::builder()
+        .add_source(::config::File::from_str(content, source_format))
+        .build()
+        .and_then(|config| config.try_deserialize())
+        .map_err(|e| rf_errors::RfError::Config(format!("Failed to parse {}: {}", path, e)))?;
+    let mut data = HashMap::new();
+    flatten("", &value, &mut data);
+    Ok(data)
+}
+
+fn flatten(prefix: &str, value: &serde_json::Value, out: &mut HashMap<String, String>) {
+    use serde_json::Value;
+    let join = |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten(&join(key), value, out);
+            }
+        }
+        Value::Array(items) if items.iter().any(|item| item.is_object() || item.is_array()) => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(&join(&index.to_string()), item, out);
+            }
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(scalar).collect();
+            out.insert(prefix.to_string(), items.join(","));
+        }
+        other => {
+            out.insert(prefix.to_string(), scalar(other));
+        }
+    }
+}
+
+fn scalar(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Line (1-based) on which each key or table is defined
+fn locate_keys(content: &str, format: FileFormat) -> HashMap<String, usize> {
+    match format {
+        FileFormat::Toml => locate_toml(content),
+        FileFormat::Json => locate_json(content),
+        FileFormat::Yaml => locate_yaml(content),
+    }
+}
+
+fn unquote_key(key: &str) -> String {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').trim_matches('\''))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn locate_toml(content: &str) -> HashMap<String, usize> {
+    let mut lines = HashMap::new();
+    let mut table = String::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            let header = line.split('#').next().unwrap_or_default();
+            table = unquote_key(header.trim().trim_start_matches('[').trim_end_matches(']'));
+            lines.entry(table.clone()).or_insert(index + 1);
+        } else if let Some(eq) = line.find('=') {
+            let key = unquote_key(&line[..eq]);
+            let full = if table.is_empty() { key } else { format!("{}.{}", table, key) };
+            lines.entry(full).or_insert(index + 1);
+        }
+    }
+    lines
+}
+
+fn locate_yaml(content: &str) -> HashMap<String, usize> {
+    let mut lines = HashMap::new();
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('-') || trimmed.starts_with("---") {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        let Some(colon) = trimmed.find(':') else {
+            continue;
+        };
+        if !(trimmed[colon + 1..].is_empty() || trimmed[colon + 1..].starts_with(' ')) {
+            continue;
+        }
+        while stack.last().is_some_and(|(level, _)| *level >= indent) {
+            stack.pop();
+        }
+        stack.push((indent, unquote_key(&trimmed[..colon])));
+        let full = stack.iter().map(|(_, key)| key.as_str()).collect::<Vec<_>>().join(".");
+        lines.entry(full).or_insert(index + 1);
+    }
+    lines
+}
+
+fn locate_json(content: &str) -> HashMap<String, usize> {
+    // Open containers; objects carry the key of the member being parsed
+    enum Container {
+        Object(Option<String>),
+        Array,
+    }
+    let mut lines = HashMap::new();
+    let mut stack: Vec<Container> = Vec::new();
+    let mut line = 1;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '{' => stack.push(Container::Object(None)),
+            '[' => stack.push(Container::Array),
+            '}' | ']' => {
+                stack.pop();
+            }
+            '"' => {
+                let mut text = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                text.push(escaped);
+                            }
+                        }
+                        '"' => break,
+                        '\n' => {
+                            line += 1;
+                            text.push(c);
+                        }
+                        _ => text.push(c),
+                    }
+                }
+                while chars.peek().is_some_and(|c| *c == ' ' || *c == '\t') {
+                    chars.next();
+                }
+                if chars.peek() == Some(&':') {
+                    if let Some(Container::Object(key)) = stack.last_mut() {
+                        *key = Some(text);
+                    }
+                    let path: Vec<&str> = stack.iter()
+                        .filter_map(|container| match container {
+                            Container::Object(Some(key)) => Some(key.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    lines.entry(path.join(".")).or_insert(line);
+                }
+            }
+            _ => {}
+        }
+    }
+    lines
 }
 
 /// Environment variable configuration adapter
-pub struct EnvConfigAdapter;
+///
+/// Without a prefix, keys are looked up as variable names verbatim. With a
+/// prefix, variables map to dotted keys: the prefix is stripped, the rest is
+/// lowercased and `__` separates levels, so `RF_DATABASE__DEFAULT__MAX_CONNECTIONS`
+/// is `database.default.max_connections`.
+pub struct EnvConfigAdapter {
+    prefix: Option<String>,
+}
 
 impl EnvConfigAdapter {
     /// Create a new environment adapter
     pub fn new() -> Self {
-        Self
+        Self { prefix: None }
+    }
+
+    /// Read variables starting with `prefix` (such as `RF_`) as dotted keys
+    pub fn with_prefix(prefix: &str) -> Self {
+        Self { prefix: Some(prefix.to_string()) }
+    }
+
+    /// Variable name for a dotted key
+    pub fn var_name(&self, key: &str) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}{}", prefix, key.replace('.', "__").to_uppercase()),
+            None => key.to_string(),
+        }
+    }
+
+    fn key(&self, name: &str) -> Option<String> {
+        match &self.prefix {
+            Some(prefix) => name
+                .strip_prefix(prefix.as_str())
+                .filter(|rest| !rest.is_empty())
+                .map(|rest| rest.to_lowercase().replace("__", ".")),
+            None => Some(name.to_string()),
+        }
     }
 }
 
 impl ConfigAdapter for EnvConfigAdapter {
     fn get(&self, key: &str) -> Result<Option<String>> {
-        Ok(std::env::var(key).ok())
+        Ok(std::env::var(self.var_name(key)).ok())
     }
     
     fn set(&self, _key: &str, _value: &str) -> Result<()> {
@@ -87,7 +364,9 @@ impl ConfigAdapter for EnvConfigAdapter {
     }
     
     fn all(&self) -> Result<HashMap<String, String>> {
-        Ok(std::env::vars().collect())
+        Ok(std::env::vars()
+            .filter_map(|(name, value)| self.key(&name).map(|key| (key, value)))
+            .collect())
     }
 }
 
@@ -97,6 +376,67 @@ impl Default for EnvConfigAdapter {
     }
 }
 
+/// Command-line flag configuration adapter
+///
+/// Reads `--server.port=8080` and `--server.port 8080` as `server.port`; a
+/// flag without a value (`--debug`) is `true`. Other arguments are skipped,
+/// and parsing stops at `--`. Read-only.
+pub struct ArgsConfigAdapter {
+    data: HashMap<String, String>,
+}
+
+impl ArgsConfigAdapter {
+    /// Parse flags from `args` (without the program name)
+    pub fn new<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut data = HashMap::new();
+        let mut args = args.into_iter().map(Into::into).peekable();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                break;
+            }
+            let Some(flag) = arg.strip_prefix("--").filter(|flag| !flag.is_empty()) else {
+                continue;
+            };
+            match flag.split_once('=') {
+                Some((key, value)) => {
+                    data.insert(key.to_string(), value.to_string());
+                }
+                None => {
+                    let value = match args.peek() {
+                        Some(next) if !next.starts_with("--") => args.next().unwrap_or_default(),
+                        _ => "true".to_string(),
+                    };
+                    data.insert(flag.to_string(), value);
+                }
+            }
+        }
+        Self { data }
+    }
+
+    /// Parse the process arguments
+    pub fn from_env() -> Self {
+        Self::new(std::env::args().skip(1))
+    }
+}
+
+impl ConfigAdapter for ArgsConfigAdapter {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.data.get(key).cloned())
+    }
+
+    fn set(&self, _key: &str, _value: &str) -> Result<()> {
+        Err(rf_errors::RfError::Config("Cannot set command-line flags".to_string()))
+    }
+
+    fn all(&self) -> Result<HashMap<String, String>> {
+        Ok(self.data.clone())
+    }
+}
+
 /// Memory-based configuration adapter
 pub struct MemoryConfigAdapter {
     data: Arc<RwLock<HashMap<String, String>>>,
@@ -105,8 +445,13 @@ pub struct MemoryConfigAdapter {
 impl MemoryConfigAdapter {
     /// Create a new memory adapter
     pub fn new() -> Self {
+        Self::from_values(HashMap::new())
+    }
+
+    /// Create a memory adapter holding `values`
+    pub fn from_values(values: HashMap<String, String>) -> Self {
         Self {
-            data: Arc::new(RwLock::new(HashMap::new())),
+            data: Arc::new(RwLock::new(values)),
         }
     }
 }
@@ -0,0 +1,297 @@
+//! # de
+//!
+//! de 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Deserializing flattened configuration into typed structs
+//!
+//! Adapters store every value as a string under a dotted key, so the tree
+//! rebuilt for `Config::get_struct` has string leaves. Leaves are parsed into
+//! whatever type the target asks for: `"8080"` becomes a `u16`, `"true"` a
+//! `bool`, `"a,b"` a `Vec<String>`, and `""` a `None`. Tables whose keys are
+//! all indexes (`servers.0.host`, `servers.1.host`) deserialize as sequences.
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, IntoDeserializer, Unexpected, Visitor};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// A configuration subtree
+#[derive(Debug)]
+pub(crate) enum Node {
+    Leaf { key: String, value: String },
+    Table(BTreeMap<String, Node>),
+}
+
+impl Node {
+    /// Tree of the keys under `prefix` (everything for an empty prefix)
+    ///
+    /// Returns `None` when nothing is configured under the prefix. When a key
+    /// is both a value and a table, the table wins.
+    pub(crate) fn build(values: &HashMap<String, String>, prefix: &str) -> Option<Node> {
+        let mut root: Option<Node> = None;
+        let mut keys: Vec<&String> = values.keys().collect();
+        keys.sort();
+        for key in keys {
+            let rest = if prefix.is_empty() {
+                key.as_str()
+            } else if key == prefix {
+                ""
+            } else {
+                match key.strip_prefix(prefix).and_then(|rest| rest.strip_prefix('.')) {
+                    Some(rest) => rest,
+                    None => continue,
+                }
+            };
+            let leaf = Node::Leaf { key: key.clone(), value: values[key].clone() };
+            if rest.is_empty() {
+                if root.is_none() {
+                    root = Some(leaf);
+                }
+                continue;
+            }
+            let mut node = match root.take() {
+                Some(Node::Table(table)) => table,
+                _ => BTreeMap::new(),
+            };
+            insert(&mut node, rest, leaf);
+            root = Some(Node::Table(node));
+        }
+        root
+    }
+}
+
+fn insert(table: &mut BTreeMap<String, Node>, path: &str, leaf: Node) {
+    match path.split_once('.') {
+        None => {
+            table.entry(path.to_string()).or_insert(leaf);
+        }
+        Some((head, rest)) => {
+            let child = table.entry(head.to_string()).or_insert_with(|| Node::Table(BTreeMap::new()));
+            if let Node::Leaf { .. } = child {
+                *child = Node::Table(BTreeMap::new());
+            }
+            if let Node::Table(child) = child {
+                insert(child, rest, leaf);
+            }
+        }
+    }
+}
+
+/// Deserialization error
+#[derive(Debug)]
+pub(crate) struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Node {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl Node {
+    fn parse<T: std::str::FromStr>(self, expected: &dyn de::Expected) -> Result<T, Error> {
+        match self {
+            Node::Leaf { key, value } => value.trim().parse().map_err(|_| {
+                Error(format!("{}: {}", key, <Error as de::Error>::invalid_value(Unexpected::Str(&value), expected)))
+            }),
+            Node::Table(_) => Err(de::Error::invalid_type(Unexpected::Map, expected)),
+        }
+    }
+
+    fn into_seq(self) -> Vec<Node> {
+        match self {
+            Node::Leaf { value, .. } if value.trim().is_empty() => Vec::new(),
+            Node::Leaf { key, value } => value
+                .split(',')
+                .map(|item| Node::Leaf { key: key.clone(), value: item.trim().to_string() })
+                .collect(),
+            Node::Table(table) => {
+                let mut items: Vec<(String, Node)> = table.into_iter().collect();
+                items.sort_by_key(|(index, _)| index.parse::<usize>().unwrap_or(usize::MAX));
+                items.into_iter().map(|(_, node)| node).collect()
+            }
+        }
+    }
+
+    fn is_seq(&self) -> bool {
+        matches!(self, Node::Table(table) if !table.is_empty() && table.keys().all(|key| key.parse::<usize>().is_ok()))
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                let value = self.parse(&visitor)?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Node {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Node::Leaf { value, .. } => visitor.visit_string(value),
+            node if node.is_seq() => node.deserialize_seq(visitor),
+            node => node.deserialize_map(visitor),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match &self {
+            Node::Leaf { key, value } => match parse_bool(value) {
+                Some(value) => visitor.visit_bool(value),
+                None => Err(Error(format!(
+                    "{}: {}",
+                    key,
+                    <Error as de::Error>::invalid_value(Unexpected::Str(value), &visitor)
+                ))),
+            },
+            Node::Table(_) => Err(de::Error::invalid_type(Unexpected::Map, &visitor)),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Node::Leaf { value, .. } => visitor.visit_string(value),
+            Node::Table(_) => Err(de::Error::invalid_type(Unexpected::Map, &visitor)),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match &self {
+            Node::Leaf { value, .. } if value.is_empty() => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut seq = SeqDeserializer::new(self.into_seq().into_iter());
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let table = match self {
+            Node::Table(table) => table,
+            Node::Leaf { value, .. } if value.is_empty() => BTreeMap::new(),
+            Node::Leaf { key, value } => {
+                return Err(Error(format!(
+                    "{}: {}",
+                    key,
+                    <Error as de::Error>::invalid_type(Unexpected::Str(&value), &visitor)
+                )))
+            }
+        };
+        let mut map = MapDeserializer::new(table.into_iter());
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            Node::Leaf { value, .. } => visitor.visit_enum(value.into_deserializer()),
+            Node::Table(_) => Err(de::Error::invalid_type(Unexpected::Map, &"a unit variant name")),
+        }
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+/// `true`/`false`, `1`/`0`, `yes`/`no` and `on`/`off`, in any case
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,225 @@
+//! # duration
+//!
+//! duration 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Duration configuration type
+//!
+//! `RfDuration` reads durations written with units, such as `30s`, `5m`,
+//! `1h30m` or `250ms`, from configuration files and environment variables.
+//! Plain numbers are seconds, so settings that used to be integer seconds
+//! keep working:
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct JobConfig {
+//!     interval: RfDuration,                  // "5m", "1h30m" or 300
+//!     #[serde(with = "rf_os::cfg::serde_duration")]
+//!     delay: std::time::Duration,            // same formats into a std Duration
+//! }
+//!
+//! let timeout = config.get_duration("server.default.request_timeout")?;
+//! ```
+
+use rf_errors::{Result, RfError};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A duration read from configuration
+///
+/// Parses from and displays as strings like `1h30m`; converts to and from
+/// `std::time::Duration`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RfDuration(Duration);
+
+impl RfDuration {
+    /// Zero duration
+    pub const ZERO: Self = Self(Duration::ZERO);
+
+    /// Duration of `secs` seconds
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+
+    /// Duration of `millis` milliseconds
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(Duration::from_millis(millis))
+    }
+
+    /// The `std::time::Duration` value
+    pub const fn as_duration(&self) -> Duration {
+        self.0
+    }
+
+    /// Whole seconds
+    pub const fn as_secs(&self) -> u64 {
+        self.0.as_secs()
+    }
+
+    /// Whole milliseconds
+    pub const fn as_millis(&self) -> u128 {
+        self.0.as_millis()
+    }
+
+    /// Whether the duration is zero
+    pub const fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl From<Duration> for RfDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<RfDuration> for Duration {
+    fn from(duration: RfDuration) -> Self {
+        duration.0
+    }
+}
+
+impl FromStr for RfDuration {
+    type Err = RfError;
+
+    /// Parse `1h30m`, `1.5s`, `250ms`, `2d` or plain seconds
+    ///
+    /// Units are `d`, `h`, `m`, `s`, `ms`, `us` (or `µs`) and `ns`.
+    fn from_str(value: &str) -> Result<Self> {
+        let invalid = || RfError::Config(format!("Invalid duration '{}', expected e.g. 30s, 5m or 1h30m", value));
+        let text = value.trim();
+        if text.is_empty() {
+            return Err(invalid());
+        }
+        if let Ok(secs) = text.parse::<f64>() {
+            return Duration::try_from_secs_f64(secs).map(Self).map_err(|_| invalid());
+        }
+
+        let mut total = Duration::ZERO;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let number_len = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+            let unit_len = rest[number_len..]
+                .find(|c: char| c.is_ascii_digit() || c == '.')
+                .unwrap_or(rest.len() - number_len);
+            let number: f64 = rest[..number_len].parse().map_err(|_| invalid())?;
+            let unit_secs = match rest[number_len..number_len + unit_len].trim() {
+                "d" => 86_400.0,
+                "h" => 3_600.0,
+                "m" => 60.0,
+                "s" => 1.0,
+                "ms" => 1e-3,
+                "us" | "µs" => 1e-6,
+                "ns" => 1e-9,
+                _ => return Err(invalid()),
+            };
+            let part = Duration::try_from_secs_f64(number * unit_secs).map_err(|_| invalid())?;
+            total = total.checked_add(part).ok_or_else(invalid)?;
+            rest = rest[number_len + unit_len..].trim_start();
+        }
+        Ok(Self(total))
+    }
+}
+
+impl fmt::Display for RfDuration {
+    /// Compact form that parses back to the same value, e.g. `1h30m` or `1s500ms`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_zero() {
+            return f.write_str("0s");
+        }
+        let secs = self.0.as_secs();
+        let nanos = self.0.subsec_nanos();
+        let parts = [
+            (secs / 3600, "h"),
+            (secs / 60 % 60, "m"),
+            (secs % 60, "s"),
+            (u64::from(nanos / 1_000_000), "ms"),
+            (u64::from(nanos / 1_000 % 1_000), "us"),
+            (u64::from(nanos % 1_000), "ns"),
+        ];
+        for (amount, unit) in parts {
+            if amount > 0 {
+                write!(f, "{}{}", amount, unit)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for RfDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RfDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(DurationVisitor)
+    }
+}
+
+struct DurationVisitor;
+
+impl Visitor<'_> for DurationVisitor {
+    type Value = RfDuration;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a duration such as \"30s\", \"5m\" or \"1h30m\", or a number of seconds")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<RfDuration, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, secs: u64) -> std::result::Result<RfDuration, E> {
+        Ok(RfDuration::from_secs(secs))
+    }
+
+    fn visit_i64<E: de::Error>(self, secs: i64) -> std::result::Result<RfDuration, E> {
+        u64::try_from(secs)
+            .map(RfDuration::from_secs)
+            .map_err(|_| E::custom(format!("negative duration {}", secs)))
+    }
+
+    fn visit_f64<E: de::Error>(self, secs: f64) -> std::result::Result<RfDuration, E> {
+        Duration::try_from_secs_f64(secs)
+            .map(RfDuration)
+            .map_err(|_| E::custom(format!("invalid duration {}", secs)))
+    }
+}
+
+/// `#[serde(with = "rf_os::cfg::serde_duration")]` for `std::time::Duration`
+/// fields, in the formats `RfDuration` accepts
+pub mod serde_duration {
+    use super::RfDuration;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        RfDuration::from(*duration).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        RfDuration::deserialize(deserializer).map(Duration::from)
+    }
+
+    /// The same for `Option<Duration>` fields
+    pub mod option {
+        use super::RfDuration;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+        use std::time::Duration;
+
+        pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+            duration.map(RfDuration::from).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+            Option::<RfDuration>::deserialize(deserializer).map(|duration| duration.map(Duration::from))
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! # profile
+//!
+//! profile 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Environment profiles
+//!
+//! A profile (`dev`, `test`, `prod`, or any other name) selects an overlay
+//! file next to the base configuration: with profile `prod`,
+//! `config/config.toml` is overlaid by `config/config.prod.toml`. Keys of the
+//! overlay replace the same keys of the base file; all other keys keep their
+//! base values.
+//!
+//! The profile comes from `RF_ENV` when set, otherwise from the runtime mode
+//! of `rf_util::mode` (`RF_MODE`), which defaults to `dev`.
+
+use std::path::Path;
+
+/// Environment variable naming the active profile
+pub const PROFILE_ENV: &str = "RF_ENV";
+
+/// The profile selected by the environment
+///
+/// `RF_ENV` wins; otherwise the runtime mode maps to `dev`, `test`, `staging` or `prod`.
+pub fn current_profile() -> String {
+    match std::env::var(PROFILE_ENV) {
+        Ok(profile) if !profile.trim().is_empty() => profile.trim().to_string(),
+        _ => rf_util::mode::get().as_str().to_string(),
+    }
+}
+
+/// Path of the overlay file of `profile` for the base file `path`
+///
+/// `config/config.toml` with profile `prod` is `config/config.prod.toml`;
+/// a base file without extension gets the profile as extension.
+pub fn profile_path(path: &str, profile: &str) -> String {
+    let base = Path::new(path);
+    let overlay = match (base.file_stem(), base.extension()) {
+        (Some(stem), Some(ext)) => format!("{}.{}.{}", stem.to_string_lossy(), profile, ext.to_string_lossy()),
+        _ => format!("{}.{}", base.file_name().map(|name| name.to_string_lossy()).unwrap_or_default(), profile),
+    };
+    base.with_file_name(overlay).to_string_lossy().into_owned()
+}
@@ -0,0 +1,180 @@
+//! # reload
+//!
+//! reload 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Configuration hot reload
+//!
+//! `Config::watch` watches the files behind the config's adapters with
+//! `FsNotify`. Bursts of events (editors often write a file in several steps,
+//! or save through a temporary file and a rename) are coalesced, then every
+//! adapter reloads and the keys that changed are sent to the callbacks
+//! registered with `Config::on_change` for a matching prefix.
+
+use super::Config;
+use crate::fsnotify::FsNotify;
+use rf_errors::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, RwLock, Weak};
+use std::time::Duration;
+
+/// Quiet period after the last file event before reloading
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// A key whose value changed in a reload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChange {
+    pub key: String,
+    /// `None` when the key was added
+    pub old: Option<String>,
+    /// `None` when the key was removed
+    pub new: Option<String>,
+}
+
+/// Change callback, called with the changes under its prefix
+pub type ConfigChangeHandler = Arc<dyn Fn(&[ConfigChange]) + Send + Sync>;
+
+/// Registered change callbacks
+#[derive(Default)]
+pub(crate) struct Listeners {
+    next_id: AtomicU64,
+    handlers: RwLock<Vec<(u64, String, ConfigChangeHandler)>>,
+}
+
+impl Listeners {
+    pub(crate) fn subscribe(&self, prefix: &str, handler: ConfigChangeHandler) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.handlers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((id, prefix.trim_end_matches('.').to_string(), handler));
+        id
+    }
+
+    pub(crate) fn unsubscribe(&self, id: u64) -> bool {
+        let mut handlers = self.handlers.write().unwrap_or_else(|e| e.into_inner());
+        let before = handlers.len();
+        handlers.retain(|(handler_id, _, _)| *handler_id != id);
+        handlers.len() != before
+    }
+
+    /// Call each handler with its share of the changes, skipping handlers with none
+    pub(crate) fn dispatch(&self, changes: &[ConfigChange]) {
+        // Handlers may subscribe or read the config, so call them without the lock
+        let handlers = self.handlers.read().unwrap_or_else(|e| e.into_inner()).clone();
+        for (_, prefix, handler) in handlers {
+            let matching: Vec<ConfigChange> = changes
+                .iter()
+                .filter(|change| under_prefix(&change.key, &prefix))
+                .cloned()
+                .collect();
+            if matching.is_empty() {
+                continue;
+            }
+            let call = std::panic::AssertUnwindSafe(|| handler(&matching));
+            if std::panic::catch_unwind(call).is_err() {
+                tracing::error!("Config change handler for '{}' panicked", prefix);
+            }
+        }
+    }
+}
+
+/// `key` is `prefix` or below it; an empty prefix matches every key
+fn under_prefix(key: &str, prefix: &str) -> bool {
+    prefix.is_empty()
+        || key
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Keys added, removed or changed between two snapshots, sorted by key
+pub(crate) fn diff(before: &HashMap<String, String>, after: &HashMap<String, String>) -> Vec<ConfigChange> {
+    let mut changes: Vec<ConfigChange> = before
+        .iter()
+        .filter(|(key, value)| after.get(*key) != Some(*value))
+        .map(|(key, value)| ConfigChange {
+            key: key.clone(),
+            old: Some(value.clone()),
+            new: after.get(key).cloned(),
+        })
+        .chain(after.iter().filter(|(key, _)| !before.contains_key(*key)).map(|(key, value)| ConfigChange {
+            key: key.clone(),
+            old: None,
+            new: Some(value.clone()),
+        }))
+        .collect();
+    changes.sort_by(|a, b| a.key.cmp(&b.key));
+    changes
+}
+
+/// Running file watch of a `Config`, stopped when dropped
+pub struct ConfigWatch {
+    _notify: FsNotify,
+    paths: Vec<PathBuf>,
+}
+
+impl ConfigWatch {
+    /// Files being watched
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+/// Watch `files` and reload `config` when they change
+///
+/// Directories are watched rather than the files themselves, so a file
+/// replaced by a rename keeps being followed.
+pub(crate) fn watch(config: Weak<Config>, files: Vec<PathBuf>) -> Result<ConfigWatch> {
+    let names: HashSet<_> = files.iter().filter_map(|path| path.file_name().map(|n| n.to_os_string())).collect();
+    let (sender, receiver) = mpsc::channel::<()>();
+    let mut notify = FsNotify::with_callback(move |path: &Path| {
+        if path.file_name().is_some_and(|name| names.contains(name)) {
+            let _ = sender.send(());
+        }
+    })?;
+
+    let mut dirs: Vec<PathBuf> = files
+        .iter()
+        .map(|path| match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        })
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    for dir in &dirs {
+        notify.watch_non_recursive(dir)?;
+    }
+
+    // Ends once the watcher, and with it the sender, is dropped
+    std::thread::Builder::new()
+        .name("rf-config-watch".to_string())
+        .spawn(move || {
+            while receiver.recv().is_ok() {
+                loop {
+                    match receiver.recv_timeout(DEBOUNCE) {
+                        Ok(()) => continue,
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                let Some(config) = config.upgrade() else {
+                    return;
+                };
+                match config.reload() {
+                    Ok(changes) if !changes.is_empty() => {
+                        tracing::info!("Configuration reloaded, {} keys changed", changes.len());
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Configuration reload failed: {}", e),
+                }
+            }
+        })
+        .map_err(rf_errors::RfError::Io)?;
+
+    Ok(ConfigWatch { _notify: notify, paths: files })
+}
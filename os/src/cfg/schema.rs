@@ -0,0 +1,416 @@
+//! # schema
+//!
+//! schema 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Configuration schema
+//!
+//! Modules describe the keys they read — type, default, whether required —
+//! and register the description under a name. `Config::validate` checks the
+//! loaded configuration against every registered schema and reports:
+//!
+//! - unknown keys under a namespace owned by a schema (usually typos)
+//! - values that do not parse as the declared type
+//! - required keys that are missing
+//!
+//! with the file and line the value came from when the adapter knows it.
+//!
+//! A `*` segment matches any instance name, so `database.*.url` covers
+//! `database.default.url` and `database.cache.url`. A required wildcard key
+//! is only required for instances that appear in the configuration.
+//!
+//! ```ignore
+//! schema::register("database", ConfigSchema::new()
+//!     .key(KeySpec::new("database.*.url", TypeRule::Url).required())
+//!     .key(KeySpec::new("database.*.max_connections", TypeRule::Integer).default("10")));
+//!
+//! config.validate()?;
+//! ```
+
+use super::validation::{TypeRule, ValidationRule};
+use once_cell::sync::Lazy;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Where a configuration value was defined
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigLocation {
+    pub file: String,
+    /// 1-based line number
+    pub line: usize,
+}
+
+impl fmt::Display for ConfigLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Expected configuration key
+pub struct KeySpec {
+    key: String,
+    ty: TypeRule,
+    rules: Vec<Box<dyn ValidationRule>>,
+    required: bool,
+    default: Option<String>,
+    description: Option<String>,
+}
+
+impl KeySpec {
+    /// Key of type `ty`, `*` segments match any name
+    pub fn new(key: &str, ty: TypeRule) -> Self {
+        Self {
+            key: key.to_string(),
+            ty,
+            rules: Vec::new(),
+            required: false,
+            default: None,
+            description: None,
+        }
+    }
+
+    /// The key must be set
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Value used when the key is not set
+    pub fn default(mut self, value: &str) -> Self {
+        self.default = Some(value.to_string());
+        self
+    }
+
+    /// Human readable description
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Additional rule checked after the type
+    pub fn rule(mut self, rule: Box<dyn ValidationRule>) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Key pattern
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Default value
+    pub fn default_value(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    /// Whether the key is required
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Description
+    pub fn describe(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Check a value against the type and rules
+    fn check(&self, key: &str, value: &str) -> rf_errors::Result<()> {
+        self.ty.validate(key, value)?;
+        for rule in &self.rules {
+            rule.validate(key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Match `key` against the pattern, returning the wildcard captures
+    fn matches<'a>(&self, key: &'a str) -> Option<Vec<&'a str>> {
+        let pattern: Vec<&str> = self.key.split('.').collect();
+        let parts: Vec<&str> = key.split('.').collect();
+        if pattern.len() != parts.len() {
+            return None;
+        }
+        let mut captures = Vec::new();
+        for (pattern, part) in pattern.iter().zip(&parts) {
+            if *pattern == "*" {
+                captures.push(*part);
+            } else if pattern != part {
+                return None;
+            }
+        }
+        Some(captures)
+    }
+
+    /// Whether `key` lies under the pattern's prefix up to the last wildcard,
+    /// returning the captures (e.g. `database.cache.pool.size` for
+    /// `database.*.url` gives `["cache"]`)
+    fn instance_of<'a>(&self, key: &'a str) -> Option<Vec<&'a str>> {
+        let pattern: Vec<&str> = self.key.split('.').collect();
+        let last_wildcard = pattern.iter().rposition(|p| *p == "*")?;
+        let parts: Vec<&str> = key.split('.').collect();
+        if parts.len() <= last_wildcard {
+            return None;
+        }
+        let mut captures = Vec::new();
+        for (pattern, part) in pattern[..=last_wildcard].iter().zip(&parts) {
+            if *pattern == "*" {
+                captures.push(*part);
+            } else if pattern != part {
+                return None;
+            }
+        }
+        Some(captures)
+    }
+
+    /// Pattern with the wildcards replaced by `captures`
+    fn expand(&self, captures: &[&str]) -> String {
+        let mut captures = captures.iter();
+        self.key
+            .split('.')
+            .map(|part| if part == "*" { captures.next().copied().unwrap_or("*") } else { part })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// First key segment
+    fn namespace(&self) -> &str {
+        self.key.split('.').next().unwrap_or_default()
+    }
+}
+
+/// Set of expected keys
+#[derive(Default)]
+pub struct ConfigSchema {
+    keys: Vec<KeySpec>,
+}
+
+impl ConfigSchema {
+    /// Create an empty schema
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a key
+    pub fn key(mut self, spec: KeySpec) -> Self {
+        self.keys.push(spec);
+        self
+    }
+
+    /// Declared keys
+    pub fn keys(&self) -> &[KeySpec] {
+        &self.keys
+    }
+
+    /// Spec matching a concrete key
+    pub fn find(&self, key: &str) -> Option<&KeySpec> {
+        self.keys.iter().find(|spec| spec.matches(key).is_some())
+    }
+
+    /// Default value of a concrete key
+    pub fn default_for(&self, key: &str) -> Option<&str> {
+        self.find(key).and_then(KeySpec::default_value)
+    }
+}
+
+/// Registered schemas by module name
+type Registry = Vec<(String, Arc<ConfigSchema>)>;
+
+static SCHEMAS: Lazy<RwLock<Registry>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Register a module schema, replacing a schema registered under the same name
+pub fn register(module: &str, schema: ConfigSchema) {
+    let mut schemas = SCHEMAS.write().unwrap_or_else(|e| e.into_inner());
+    schemas.retain(|(name, _)| name != module);
+    schemas.push((module.to_string(), Arc::new(schema)));
+}
+
+/// Remove a registered schema
+pub fn unregister(module: &str) {
+    SCHEMAS.write().unwrap_or_else(|e| e.into_inner()).retain(|(name, _)| name != module);
+}
+
+/// Registered schemas
+pub fn registered() -> Vec<(String, Arc<ConfigSchema>)> {
+    SCHEMAS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Kind of configuration problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    /// Key under a schema namespace that no schema declares
+    UnknownKey,
+    /// Value does not match the declared type or rules
+    TypeMismatch,
+    /// Required key is not set
+    MissingRequired,
+}
+
+/// One configuration problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub kind: IssueKind,
+    pub key: String,
+    pub message: String,
+    pub location: Option<ConfigLocation>,
+}
+
+impl ConfigIssue {
+    /// Whether the issue should fail startup; unknown keys are warnings
+    pub fn is_error(&self) -> bool {
+        self.kind != IssueKind::UnknownKey
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{}: ", location)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Result of checking a configuration against the schemas
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigReport {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    /// Whether there are no errors (warnings are allowed)
+    pub fn is_ok(&self) -> bool {
+        !self.issues.iter().any(ConfigIssue::is_error)
+    }
+
+    /// Issues that fail validation
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues.iter().filter(|issue| issue.is_error())
+    }
+
+    /// Issues that are only reported
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues.iter().filter(|issue| !issue.is_error())
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check flattened configuration values against schemas
+///
+/// `locate` maps a key to where it was defined.
+pub fn check(
+    values: &HashMap<String, String>,
+    schemas: &[Arc<ConfigSchema>],
+    locate: impl Fn(&str) -> Option<ConfigLocation>,
+) -> ConfigReport {
+    let specs: Vec<&KeySpec> = schemas.iter().flat_map(|schema| schema.keys.iter()).collect();
+    let namespaces: BTreeSet<&str> = specs.iter().map(|spec| spec.namespace()).collect();
+    let mut keys: Vec<&String> = values.keys().collect();
+    keys.sort();
+
+    let mut issues = Vec::new();
+    for key in &keys {
+        let value = &values[*key];
+        match specs.iter().find(|spec| spec.matches(key).is_some()) {
+            Some(spec) => {
+                if let Err(e) = spec.check(key, value) {
+                    issues.push(ConfigIssue {
+                        kind: IssueKind::TypeMismatch,
+                        key: key.to_string(),
+                        message: format!("{} (got {:?})", error_message(e), value),
+                        location: locate(key),
+                    });
+                }
+            }
+            None => {
+                let namespace = key.split('.').next().unwrap_or_default();
+                if namespaces.contains(namespace) {
+                    let hint = closest(key, &specs)
+                        .map(|suggestion| format!(", did you mean '{}'?", suggestion))
+                        .unwrap_or_default();
+                    issues.push(ConfigIssue {
+                        kind: IssueKind::UnknownKey,
+                        key: key.to_string(),
+                        message: format!("Unknown configuration key '{}'{}", key, hint),
+                        location: locate(key),
+                    });
+                }
+            }
+        }
+    }
+
+    for spec in specs.iter().filter(|spec| spec.required && spec.default.is_none()) {
+        let required: BTreeSet<String> = if spec.key.contains('*') {
+            keys.iter()
+                .filter_map(|key| spec.instance_of(key))
+                .map(|captures| spec.expand(&captures))
+                .collect()
+        } else {
+            BTreeSet::from([spec.key.clone()])
+        };
+        for key in required {
+            if !values.contains_key(&key) {
+                let message = match spec.describe() {
+                    Some(description) => format!("Missing required configuration key '{}' ({})", key, description),
+                    None => format!("Missing required configuration key '{}'", key),
+                };
+                issues.push(ConfigIssue {
+                    kind: IssueKind::MissingRequired,
+                    key,
+                    message,
+                    location: None,
+                });
+            }
+        }
+    }
+    ConfigReport { issues }
+}
+
+fn error_message(error: rf_errors::RfError) -> String {
+    match error {
+        rf_errors::RfError::Config(message) => message,
+        other => other.to_string(),
+    }
+}
+
+/// Declared key closest to an unknown key, for typo hints
+fn closest(key: &str, specs: &[&KeySpec]) -> Option<String> {
+    specs.iter()
+        .filter_map(|spec| {
+            let captures = spec.instance_of(key).unwrap_or_default();
+            let candidate = spec.expand(&captures);
+            if candidate.contains('*') {
+                return None;
+            }
+            let distance = edit_distance(key, &candidate);
+            (distance <= 3).then_some((distance, candidate))
+        })
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,120 @@
+//! # secrets
+//!
+//! secrets 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Secret references in configuration values
+//!
+//! Configuration files hold references instead of plaintext secrets, and
+//! `Config` resolves them through the `SecretProvider`s added with
+//! `Config::with_secrets` when values are read:
+//!
+//! - `ENC(...)`: a value encrypted with AES-256-GCM under a master key,
+//!   resolved by `EncryptedSecrets` (the key comes from `RF_MASTER_KEY`).
+//!   References may be embedded, as in `postgres://app:ENC(...)@db/app`.
+//! - `vault:secret/data/myapp/db#password`: a HashiCorp Vault secret,
+//!   resolved by `VaultSecrets` in `rf-contrib-config`.
+//!
+//! ```rust,no_run
+//! use rf_os::cfg::{Config, EncryptedSecrets};
+//! use std::sync::Arc;
+//!
+//! # fn example() -> rf_errors::Result<()> {
+//! let secrets = EncryptedSecrets::from_env()?;
+//! // Produce a value to paste into config.toml
+//! println!("{}", secrets.encrypt("s3cr3t")?);
+//!
+//! let config = Config::layered("config.toml")?.with_secrets(Arc::new(secrets));
+//! let url = config.get("database.default.url")?; // decrypted
+//! # Ok(())
+//! # }
+//! ```
+
+use base64::{engine::general_purpose, Engine as _};
+use rand::RngCore;
+use rf_errors::{Result, RfError};
+
+/// Environment variable holding the master key of `EncryptedSecrets::from_env`
+pub const MASTER_KEY_ENV: &str = "RF_MASTER_KEY";
+
+const NONCE_LEN: usize = 12;
+
+/// Resolves secret references in configuration values
+pub trait SecretProvider: Send + Sync {
+    /// Resolve the references in `value`
+    ///
+    /// Returns `None` when `value` holds no reference this provider handles,
+    /// leaving it to the next provider.
+    fn resolve(&self, value: &str) -> Option<Result<String>>;
+}
+
+/// Values encrypted as `ENC(base64(nonce || ciphertext))` with AES-256-GCM
+///
+/// The AES key is derived from the master key with HMAC-SHA256, so any
+/// passphrase works as a master key.
+pub struct EncryptedSecrets {
+    key: Vec<u8>,
+}
+
+impl EncryptedSecrets {
+    /// Use `master_key` to encrypt and decrypt values
+    pub fn new(master_key: &str) -> Self {
+        Self { key: rf_crypto::sha256::hmac(master_key.as_bytes(), b"rf-config-secrets") }
+    }
+
+    /// Read the master key from `RF_MASTER_KEY`
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(MASTER_KEY_ENV) {
+            Ok(key) if !key.is_empty() => Ok(Self::new(&key)),
+            _ => Err(RfError::Config(format!("{} is not set", MASTER_KEY_ENV))),
+        }
+    }
+
+    /// Encrypt `plaintext` into an `ENC(...)` reference
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let mut data = nonce.to_vec();
+        data.extend(rf_crypto::aes::encrypt(&self.key, &nonce, plaintext.as_bytes())?);
+        Ok(format!("ENC({})", general_purpose::STANDARD.encode(data)))
+    }
+
+    /// Replace every `ENC(...)` reference in `value` with its plaintext
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        let mut result = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find("ENC(") {
+            let encoded_start = start + "ENC(".len();
+            let end = rest[encoded_start..]
+                .find(')')
+                .ok_or_else(|| RfError::Config("Unterminated ENC( reference".to_string()))?;
+            result.push_str(&rest[..start]);
+            result.push_str(&self.decrypt_one(&rest[encoded_start..encoded_start + end])?);
+            rest = &rest[encoded_start + end + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    fn decrypt_one(&self, encoded: &str) -> Result<String> {
+        let data = general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| RfError::Config(format!("Invalid ENC() value: {}", e)))?;
+        if data.len() <= NONCE_LEN {
+            return Err(RfError::Config("Invalid ENC() value: too short".to_string()));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        // A wrong master key and tampered data look the same to AES-GCM
+        let plaintext = rf_crypto::aes::decrypt(&self.key, nonce, ciphertext)
+            .map_err(|_| RfError::Config("Failed to decrypt ENC() value: wrong master key or corrupted value".to_string()))?;
+        String::from_utf8(plaintext).map_err(|_| RfError::Config("Decrypted ENC() value is not UTF-8".to_string()))
+    }
+}
+
+impl SecretProvider for EncryptedSecrets {
+    fn resolve(&self, value: &str) -> Option<Result<String>> {
+        value.contains("ENC(").then(|| self.decrypt(value))
+    }
+}
@@ -36,6 +36,10 @@ pub enum TypeRule {
     Boolean,
     Url,
     Email,
+    /// Duration such as `30s`, `5m`, `1h`, `250ms` or plain seconds
+    Duration,
+    /// Socket address such as `127.0.0.1:8080`
+    SocketAddr,
 }
 
 impl ValidationRule for TypeRule {
@@ -68,10 +72,25 @@ impl ValidationRule for TypeRule {
                 }
                 Ok(())
             }
+            TypeRule::Duration => {
+                parse_duration(value)
+                    .ok_or_else(|| RfError::Config(format!("Configuration key '{}' must be a duration", key)))?;
+                Ok(())
+            }
+            TypeRule::SocketAddr => {
+                value.parse::<std::net::SocketAddr>()
+                    .map_err(|_| RfError::Config(format!("Configuration key '{}' must be a socket address", key)))?;
+                Ok(())
+            }
         }
     }
 }
 
+/// Parse a duration such as `30s`, `5m`, `1h30m`, `2d`, `250ms` or plain seconds
+pub fn parse_duration(value: &str) -> Option<std::time::Duration> {
+    value.parse::<super::RfDuration>().ok().map(Into::into)
+}
+
 /// Range validation rule
 pub struct RangeRule {
     min: Option<i64>,
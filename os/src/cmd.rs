@@ -6,12 +6,195 @@
 //! @date 2026-01-06
 
 //! Command line parsing
+//!
+//! `Cmd` builds a command line parser for applications on top of clap.
+//! `Flag`s are typed: the value is parsed into the flag's type (and checked
+//! by its validator) while parsing, so errors are reported with the usage
+//! like any other. Commands nest into a tree with `command`; `--help` and
+//! `help <command>` are generated at every level, and `with_completion`
+//! adds a `completion <bash|zsh|fish>` subcommand printing a completion
+//! script:
+//!
+//! ```rust,ignore
+//! use rf_os::cmd::{Cmd, Flag};
+//!
+//! let matches = Cmd::new("app")
+//!     .version("1.0.0")
+//!     .flag(Flag::new("config").short('c').default("config.toml").help("Configuration file"))
+//!     .flag(Flag::count("verbose").short('v').help("More output"))
+//!     .command(
+//!         Cmd::new("serve").about("Start the server").flag(
+//!             Flag::typed::<u16>("port")
+//!                 .default("8080")
+//!                 .env("APP_PORT")
+//!                 .validate(|port: &u16| if *port >= 1024 { Ok(()) } else { Err("must be 1024 or above") }),
+//!         ),
+//!     )
+//!     .command(Cmd::new("db").command(Cmd::new("migrate")).command(Cmd::new("seed")))
+//!     .with_completion()
+//!     .parse();
+//!
+//! match Cmd::subcommand_path(&matches) {
+//!     (path, sub) if path == ["serve"] => serve(Cmd::get::<u16>(sub, "port").unwrap()),
+//!     (path, _) if path == ["db", "migrate"] => migrate(),
+//!     _ => {}
+//! }
+//! ```
+//!
+//! Install the completions with e.g. `app completion bash > /etc/bash_completion.d/app`.
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::io::Write;
+use std::str::FromStr;
+
+/// Name of the subcommand added by `Cmd::with_completion`
+const COMPLETION_COMMAND: &str = "completion";
+
+/// Shells completion scripts are generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    fn generator(self) -> clap_complete::Shell {
+        match self {
+            Shell::Bash => clap_complete::Shell::Bash,
+            Shell::Zsh => clap_complete::Shell::Zsh,
+            Shell::Fish => clap_complete::Shell::Fish,
+        }
+    }
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(format!("Unsupported shell '{}', expected bash, zsh or fish", s)),
+        }
+    }
+}
+
+/// A typed flag or positional argument
+///
+/// Flags are `--<name>` options taking a value unless built with `switch`
+/// or `count`.
+#[derive(Debug, Clone)]
+pub struct Flag {
+    arg: Arg,
+}
+
+impl Flag {
+    /// `--<name> <value>`, a string
+    pub fn new(name: &'static str) -> Self {
+        Self { arg: Arg::new(name).long(name).action(ArgAction::Set) }
+    }
+
+    /// `--<name> <value>` parsed into `T`; read it with `Cmd::get::<T>`
+    pub fn typed<T>(name: &'static str) -> Self
+    where
+        T: FromStr + Clone + Send + Sync + 'static,
+        T::Err: std::fmt::Display,
+    {
+        Self::new(name).validate(|_: &T| Ok::<(), String>(()))
+    }
+
+    /// `--<name>` without a value, `true` when given
+    pub fn switch(name: &'static str) -> Self {
+        Self { arg: Arg::new(name).long(name).action(ArgAction::SetTrue) }
+    }
+
+    /// `--<name>` counted, e.g. `-vvv` is 3; read it with `Cmd::get::<u8>`
+    pub fn count(name: &'static str) -> Self {
+        Self { arg: Arg::new(name).long(name).action(ArgAction::Count) }
+    }
+
+    /// Positional argument, a string unless `validate` gives it a type
+    pub fn positional(name: &'static str) -> Self {
+        Self { arg: Arg::new(name).action(ArgAction::Set) }
+    }
+
+    /// Short name, e.g. `-p`
+    pub fn short(mut self, short: char) -> Self {
+        self.arg = self.arg.short(short);
+        self
+    }
+
+    /// Help line
+    pub fn help(mut self, help: &'static str) -> Self {
+        self.arg = self.arg.help(help);
+        self
+    }
+
+    /// Value used when the flag is not given; it is parsed and validated too
+    pub fn default(mut self, value: &'static str) -> Self {
+        self.arg = self.arg.default_value(value);
+        self
+    }
+
+    /// Read the value from `var` when the flag is not given
+    pub fn env(mut self, var: &'static str) -> Self {
+        self.arg = self.arg.env(var);
+        self
+    }
+
+    /// The flag must be given (or come from its environment variable or default)
+    pub fn required(mut self) -> Self {
+        self.arg = self.arg.required(true);
+        self
+    }
 
-use clap::{Command, Arg, ArgMatches};
+    /// Accept the flag several times, or several positional values
+    pub fn multiple(mut self) -> Self {
+        self.arg = self.arg.action(ArgAction::Append);
+        if self.arg.is_positional() {
+            self.arg = self.arg.num_args(1..);
+        }
+        self
+    }
+
+    /// Restrict a string value to `values`, which are also offered as completions
+    pub fn choices(mut self, values: impl IntoIterator<Item = &'static str>) -> Self {
+        self.arg = self.arg.value_parser(clap::builder::PossibleValuesParser::new(values));
+        self
+    }
+
+    /// Parse the value into `T` and check it with `validator`
+    ///
+    /// The type follows from the validator, e.g. `.validate(|port: &u16| ...)`;
+    /// its error message is shown with the usage.
+    pub fn validate<T, E, F>(mut self, validator: F) -> Self
+    where
+        T: FromStr + Clone + Send + Sync + 'static,
+        T::Err: std::fmt::Display,
+        E: std::fmt::Display,
+        F: Fn(&T) -> Result<(), E> + Clone + Send + Sync + 'static,
+    {
+        self.arg = self.arg.value_parser(move |value: &str| -> Result<T, String> {
+            let parsed = value.parse::<T>().map_err(|e| e.to_string())?;
+            validator(&parsed).map_err(|e| e.to_string())?;
+            Ok(parsed)
+        });
+        self
+    }
+
+    /// The underlying clap argument
+    pub fn into_arg(self) -> Arg {
+        self.arg
+    }
+}
 
 /// Command line parser
+#[derive(Debug, Clone)]
 pub struct Cmd {
     command: Command,
+    completion: bool,
 }
 
 impl Cmd {
@@ -19,6 +202,7 @@ impl Cmd {
     pub fn new(name: &'static str) -> Self {
         Self {
             command: Command::new(name),
+            completion: false,
         }
     }
 
@@ -28,6 +212,41 @@ impl Cmd {
         self
     }
 
+    /// Add a nested command, which can have flags and commands of its own
+    pub fn command(mut self, command: Cmd) -> Self {
+        self.command = self.command.subcommand(command.command);
+        self
+    }
+
+    /// Require a subcommand; help is printed when none is given
+    pub fn subcommand_required(mut self) -> Self {
+        self.command = self.command.subcommand_required(true).arg_required_else_help(true);
+        self
+    }
+
+    /// Add a typed flag
+    pub fn flag(mut self, flag: Flag) -> Self {
+        self.command = self.command.arg(flag.arg);
+        self
+    }
+
+    /// Add a `completion <shell>` subcommand printing a completion script,
+    /// handled by `parse`
+    pub fn with_completion(mut self) -> Self {
+        self.command = self.command.subcommand(
+            Command::new(COMPLETION_COMMAND)
+                .about("Print a shell completion script")
+                .arg(
+                    Arg::new("shell")
+                        .required(true)
+                        .value_parser(["bash", "zsh", "fish"])
+                        .help("Shell to generate the script for"),
+                ),
+        );
+        self.completion = true;
+        self
+    }
+
     /// Add an argument
     pub fn arg(mut self, arg: Arg) -> Self {
         self.command = self.command.arg(arg);
@@ -85,8 +304,15 @@ impl Cmd {
     }
 
     /// Parse command line arguments
-    pub fn parse(self) -> ArgMatches {
-        self.command.get_matches()
+    ///
+    /// Prints help, the version or a completion script and exits when asked to.
+    pub fn parse(mut self) -> ArgMatches {
+        let matches = self.command.clone().get_matches();
+        if let Some(shell) = self.completion_shell(&matches) {
+            self.write_completion(shell, &mut std::io::stdout());
+            std::process::exit(0);
+        }
+        matches
     }
 
     /// Parse command line arguments (try version that doesn't exit on error)
@@ -94,6 +320,74 @@ impl Cmd {
         self.command.try_get_matches()
     }
 
+    /// Parse `args`, the first being the program name, without exiting
+    pub fn try_parse_from<I, T>(&self, args: I) -> Result<ArgMatches, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        self.command.clone().try_get_matches_from(args)
+    }
+
+    /// Shell given to the `completion` subcommand of `with_completion`
+    pub fn completion_shell(&self, matches: &ArgMatches) -> Option<Shell> {
+        if !self.completion {
+            return None;
+        }
+        let shell = matches.subcommand_matches(COMPLETION_COMMAND)?.get_one::<String>("shell")?;
+        shell.parse().ok()
+    }
+
+    /// Write the completion script for `shell`
+    pub fn write_completion(&mut self, shell: Shell, out: &mut dyn Write) {
+        let name = self.command.get_name().to_string();
+        clap_complete::generate(shell.generator(), &mut self.command, name, out);
+    }
+
+    /// Completion script for `shell`
+    pub fn completion_script(&mut self, shell: Shell) -> String {
+        let mut script = Vec::new();
+        self.write_completion(shell, &mut script);
+        String::from_utf8_lossy(&script).into_owned()
+    }
+
+    /// Help text, as printed by `--help`
+    pub fn help_text(&mut self) -> String {
+        self.command.render_help().to_string()
+    }
+
+    /// Typed value of a flag, `None` when absent or of another type
+    pub fn get<T: Clone + Send + Sync + 'static>(matches: &ArgMatches, name: &str) -> Option<T> {
+        matches.try_get_one::<T>(name).ok().flatten().cloned()
+    }
+
+    /// Typed values of a flag given several times
+    pub fn get_all<T: Clone + Send + Sync + 'static>(matches: &ArgMatches, name: &str) -> Vec<T> {
+        matches
+            .try_get_many::<T>(name)
+            .ok()
+            .flatten()
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether a switch was given
+    pub fn get_flag(matches: &ArgMatches, name: &str) -> bool {
+        Self::get::<bool>(matches, name).unwrap_or(false)
+    }
+
+    /// Names of the nested subcommands that were used, outermost first, and
+    /// the matches of the innermost one
+    pub fn subcommand_path(matches: &ArgMatches) -> (Vec<&str>, &ArgMatches) {
+        let mut path = Vec::new();
+        let mut current = matches;
+        while let Some((name, sub)) = current.subcommand() {
+            path.push(name);
+            current = sub;
+        }
+        (path, current)
+    }
+
     /// Get an argument value (requires parsing first)
     pub fn get_arg(matches: &ArgMatches, name: &str) -> Option<String> {
         matches.get_one::<String>(name).cloned()
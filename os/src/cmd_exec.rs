@@ -6,21 +6,64 @@
 //! @date 2026-01-06
 
 //! Command execution utilities
+//!
+//! `CommandBuilder` runs a command with environment overrides, a working
+//! directory, optional stdin input and a timeout after which the process is
+//! killed. `execute` waits for the `CommandResult` (exit code, duration and
+//! captured output); `stream` hands out stdout and stderr line by line while
+//! the command runs:
+//!
+//! ```rust,ignore
+//! use rf_os::cmd_exec::{pipe_commands, CommandBuilder};
+//!
+//! let mut build = CommandBuilder::new("cargo")
+//!     .args(&["build", "--release"])
+//!     .current_dir("/srv/app")
+//!     .env("CARGO_TERM_COLOR", "never")
+//!     .timeout(Duration::from_secs(600))
+//!     .stream()?;
+//! while let Some(line) = build.next().await {
+//!     println!("[{:?}] {}", line.stream, line.line);
+//! }
+//! let result = build.wait().await;
+//! if result.timed_out { ... }
+//!
+//! // ps aux | grep rf | wc -l
+//! let count = pipe_commands(vec![
+//!     CommandBuilder::new("ps").arg("aux"),
+//!     CommandBuilder::new("grep").arg("rf"),
+//!     CommandBuilder::new("wc").arg("-l"),
+//! ]).await?;
+//! ```
 
-use tokio::process::Command;
+use crate::proc::{OutputLine, OutputStream};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::time::Duration;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// How long output readers may run after a timed-out command was killed;
+/// background grandchildren can keep the pipes open
+const READER_GRACE: Duration = Duration::from_millis(100);
+
 /// Command execution result
 #[derive(Debug)]
 pub struct CommandResult {
     pub stdout: String,
     pub stderr: String,
+    /// Exit code, `None` when killed by a signal or on timeout
     pub exit_code: Option<i32>,
     pub success: bool,
+    /// Time from spawn to exit
+    pub duration: Duration,
+    /// The command was killed after its timeout
+    pub timed_out: bool,
 }
 
 /// Command builder with enhanced features
@@ -30,6 +73,7 @@ pub struct CommandBuilder {
     timeout: Option<Duration>,
     working_dir: Option<PathBuf>,
     background: bool,
+    stdin: Option<Vec<u8>>,
 }
 
 impl CommandBuilder {
@@ -44,6 +88,7 @@ impl CommandBuilder {
             timeout: None,
             working_dir: None,
             background: false,
+            stdin: None,
         }
     }
 
@@ -83,21 +128,31 @@ impl CommandBuilder {
         self
     }
 
-    /// Set timeout
+    /// Kill the command if it runs longer than `timeout`
     pub fn timeout(mut self, timeout: Duration) -> Self {
         self.timeout = Some(timeout);
         self
     }
 
+    /// Write `input` to the command's stdin, then close it
+    pub fn stdin(mut self, input: impl Into<Vec<u8>>) -> Self {
+        self.stdin = Some(input.into());
+        self
+    }
+
     /// Run in background
     pub fn background(mut self) -> Self {
         self.background = true;
         self
     }
 
-    /// Execute the command
+    /// Execute the command and capture its output
+    ///
+    /// A command that exceeds its timeout is killed and reported with
+    /// `timed_out` set, along with the output it wrote until then.
     pub async fn execute(mut self) -> Result<CommandResult, Box<dyn std::error::Error>> {
         if self.background {
+            let started = Instant::now();
             let _child = self.command.spawn()?;
             // Don't wait for background process
             return Ok(CommandResult {
@@ -105,24 +160,12 @@ impl CommandBuilder {
                 stderr: String::new(),
                 exit_code: None,
                 success: true,
+                duration: started.elapsed(),
+                timed_out: false,
             });
         }
 
-        let output = if let Some(timeout) = self.timeout {
-            tokio::time::timeout(timeout, self.command.output()).await?
-        } else {
-            self.command.output().await
-        }?;
-
-        let exit_code = output.status.code();
-        let success = output.status.success();
-
-        Ok(CommandResult {
-            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-            exit_code,
-            success,
-        })
+        Ok(self.stream()?.wait().await)
     }
 
     /// Execute and get real-time output
@@ -136,6 +179,142 @@ impl CommandBuilder {
 
         Ok(child)
     }
+
+    /// Spawn the command and stream its output lines
+    ///
+    /// The output is captured as well, for the result of `CommandStream::wait`.
+    pub fn stream(self) -> Result<CommandStream, Box<dyn std::error::Error>> {
+        let timeout = self.timeout;
+        let started = Instant::now();
+        let child = self.spawn_piped(None)?;
+        Ok(CommandStream::run(vec![child], timeout, started))
+    }
+
+    /// Spawn with piped output; stdin comes from `input` or the configured data
+    fn spawn_piped(mut self, input: Option<Stdio>) -> std::io::Result<Child> {
+        let data = self.stdin.take();
+        let stdin = match (input, &data) {
+            (Some(input), _) => input,
+            (None, Some(_)) => Stdio::piped(),
+            (None, None) => Stdio::null(),
+        };
+        let mut child = self.command.stdin(stdin).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+        if let (Some(data), Some(mut stdin)) = (data, child.stdin.take()) {
+            tokio::spawn(async move {
+                // The command may exit without reading all of its input
+                let _ = stdin.write_all(&data).await;
+            });
+        }
+        Ok(child)
+    }
+}
+
+/// Output lines of a running command, see `CommandBuilder::stream`
+pub struct CommandStream {
+    lines: mpsc::UnboundedReceiver<OutputLine>,
+    result: JoinHandle<CommandResult>,
+}
+
+impl CommandStream {
+    /// Supervise `children`, the stages of a pipeline (a single command is a one-stage pipeline)
+    ///
+    /// Lines are read from the stdout of the last stage and the stderr of every stage.
+    fn run(mut children: Vec<Child>, timeout: Option<Duration>, started: Instant) -> Self {
+        let (sender, lines) = mpsc::unbounded_channel();
+        let stdout = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let stderr = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let mut readers = Vec::new();
+        if let Some(output) = children.last_mut().and_then(|child| child.stdout.take()) {
+            readers.push(tokio::spawn(read_lines(output, OutputStream::Stdout, sender.clone(), Arc::clone(&stdout))));
+        }
+        for child in &mut children {
+            if let Some(output) = child.stderr.take() {
+                readers.push(tokio::spawn(read_lines(output, OutputStream::Stderr, sender.clone(), Arc::clone(&stderr))));
+            }
+        }
+        drop(sender);
+
+        let result = tokio::spawn(async move {
+            let wait_all = futures::future::join_all(children.iter_mut().map(|child| child.wait()));
+            let statuses = match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, wait_all).await.ok(),
+                None => Some(wait_all.await),
+            };
+            let timed_out = statuses.is_none();
+            if timed_out {
+                for child in &mut children {
+                    let _ = child.kill().await;
+                }
+            }
+            let duration = started.elapsed();
+
+            for reader in readers {
+                if timed_out {
+                    let abort = reader.abort_handle();
+                    let _ = tokio::time::timeout(READER_GRACE, reader).await;
+                    abort.abort();
+                } else {
+                    let _ = reader.await;
+                }
+            }
+            let (stdout, stderr) = (stdout.lock(), stderr.lock());
+
+            let statuses: Vec<_> = statuses.unwrap_or_default().into_iter().filter_map(|status| status.ok()).collect();
+            let exit_code = if timed_out { None } else { statuses.last().and_then(|status| status.code()) };
+            let success = !timed_out
+                && statuses.len() == children.len()
+                && statuses.iter().all(|status| status.success());
+            CommandResult {
+                stdout: String::from_utf8_lossy(&stdout).to_string(),
+                stderr: String::from_utf8_lossy(&stderr).to_string(),
+                exit_code,
+                success,
+                duration,
+                timed_out,
+            }
+        });
+        Self { lines, result }
+    }
+
+    /// Next output line, `None` once both streams are closed
+    pub async fn next(&mut self) -> Option<OutputLine> {
+        self.lines.recv().await
+    }
+
+    /// Wait for the command to exit; lines not read yet are only kept in the result
+    pub async fn wait(self) -> CommandResult {
+        drop(self.lines);
+        self.result.await.unwrap_or_else(|e| CommandResult {
+            stdout: String::new(),
+            stderr: e.to_string(),
+            exit_code: None,
+            success: false,
+            duration: Duration::ZERO,
+            timed_out: false,
+        })
+    }
+}
+
+/// Send the lines of one stream and append them to `captured`
+async fn read_lines(
+    reader: impl AsyncRead + Unpin,
+    stream: OutputStream,
+    sender: mpsc::UnboundedSender<OutputLine>,
+    captured: Arc<parking_lot::Mutex<Vec<u8>>>,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    while let Ok(read) = reader.read_until(b'\n', &mut line).await {
+        if read == 0 {
+            break;
+        }
+        captured.lock().extend_from_slice(&line);
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches(['\n', '\r']).to_string();
+        // The receiver may be gone; keep capturing
+        let _ = sender.send(OutputLine { stream, line: text });
+        line.clear();
+    }
 }
 
 /// Command history manager
@@ -194,32 +373,28 @@ impl Default for CommandHistory {
     }
 }
 
-/// Execute command with pipe support (simplified)
+/// Run commands as a pipeline, each stdout feeding the next stdin
+///
+/// The result carries the stdout and exit code of the last command and the
+/// stderr of all of them. Like `set -o pipefail`, it is a success only when
+/// every command succeeds. The smallest timeout of the commands applies to
+/// the whole pipeline; stdin input is only used for the first command.
 pub async fn pipe_commands(
     commands: Vec<CommandBuilder>,
 ) -> Result<CommandResult, Box<dyn std::error::Error>> {
-    // This is a simplified pipe implementation
-    // Full implementation would properly chain stdout to stdin
     if commands.is_empty() {
         return Err("No commands to pipe".into());
     }
 
-    // For now, execute commands sequentially
-    let mut last_output = String::new();
-    for (i, cmd_builder) in commands.into_iter().enumerate() {
-        if i > 0 {
-            // In full implementation, pipe last_output to stdin
-            // For now, just execute sequentially
-        }
-        let result = cmd_builder.execute().await?;
-        last_output = result.stdout;
+    let timeout = commands.iter().filter_map(|command| command.timeout).min();
+    let started = Instant::now();
+    let mut children: Vec<Child> = Vec::with_capacity(commands.len());
+    for command in commands {
+        let input = match children.last_mut().and_then(|child| child.stdout.take()) {
+            Some(stdout) => Some(stdout.try_into()?),
+            None => None,
+        };
+        children.push(command.spawn_piped(input)?);
     }
-
-    Ok(CommandResult {
-        stdout: last_output,
-        stderr: String::new(),
-        exit_code: Some(0),
-        success: true,
-    })
+    Ok(CommandStream::run(children, timeout, started).wait().await)
 }
-
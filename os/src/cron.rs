@@ -6,41 +6,802 @@
 //! @date 2026-01-06
 
 //! Cron job scheduler
+//!
+//! A schedule is one of:
+//!
+//! - six fields with seconds, `sec min hour day month weekday` (an optional
+//!   seventh field is the year): `*/10 * * * * *`
+//! - the five standard fields, `min hour day month weekday`, run at second 0
+//!   (weekday `0` and `7` are Sunday): `30 2 * * 1-5`
+//! - `@hourly`, `@daily`, `@weekly`, `@monthly` or `@yearly`
+//! - `@every <duration>`, such as `@every 90s` or `@every 1h30m`
+//!
+//! Expressions are evaluated in UTC unless the job has a timezone, set with
+//! `JobOptions::timezone` or a `CRON_TZ=Asia/Shanghai ` prefix.
+//!
+//! When a run is still going at the next tick, the job's `Overlap` policy
+//! skips the tick, queues it behind the running one or starts it alongside.
+//! Jobs run on the blocking thread pool; a panic fails that run only and is
+//! recorded as the job's last error. A run longer than the job's timeout is
+//! recorded as failed; the thread running it cannot be interrupted, so it
+//! still holds the overlap slot until it returns. Panics and timeouts are
+//! also sent to the scheduler's `ErrorReporting` (see `with_error_reporting`),
+//! or to the global one of `rf_os::report`, with the job name as context.
+//!
+//! With `with_leader_election`, replicas running the same jobs take a lock
+//! per job and tick from a shared `LockBackend` (etcd, Consul or Redis from
+//! `rf-contrib-coordination`) before running it, so each tick runs on one replica.
+//! The lock expires after the job's timeout (60 seconds by default), which
+//! also bounds the clock skew tolerated between replicas. `@every` ticks are
+//! aligned to multiples of the interval so that replicas agree on them.
+//! Jobs are identified by name, or by expression when unnamed.
+//!
+//! ```rust,ignore
+//! use rf_contrib_coordination::RedisBackend;
+//! use rf_os::cron::{Cron, JobOptions, Overlap};
+//!
+//! let cron = Cron::new().await?;
+//! cron.add("0 */5 * * * *", || sync_orders()).await?;
+//! cron.add_with(
+//!     "0 30 9 * * 1-5",
+//!     JobOptions::new().name("report").timezone("Europe/Berlin").overlap(Overlap::Skip),
+//!     || send_report(),
+//! ).await?;
+//! cron.start().await?;
+//!
+//! // Several replicas: each tick runs once across all of them
+//! let locks = Arc::new(RedisBackend::new(rf_frame::gins::redis(None).await?));
+//! let cron = Cron::new().await?.with_leader_election(locks);
+//! cron.add_with("0 0 * * * *", JobOptions::new().name("cleanup").timeout(Duration::from_secs(600)), cleanup).await?;
+//!
+//! for job in cron.jobs().await {
+//!     println!("{} next {:?} last {:?}", job.schedule, job.next_run, job.last_run);
+//! }
+//! ```
+//!
+//! Jobs can be paused, resumed, removed and triggered at any time by name
+//! (or id); a paused job keeps its schedule but skips its ticks, while a
+//! triggered run starts at once, on this replica, whether the job is paused
+//! or not. Jobs whose schedule must survive restarts are built from a
+//! `JobSpec` naming a registered handler, and are saved to the scheduler's
+//! `JobStore` (memory, Redis or a database table, see `store`):
+//!
+//! ```rust,ignore
+//! let cron = Cron::new().await?.with_store(RedisJobStore::new(redis));
+//! cron.handler("cleanup", cleanup);
+//! cron.restore().await?;
+//! cron.schedule(JobSpec::new("nightly-cleanup", "0 0 3 * * *", "cleanup")).await?;
+//! cron.start().await?;
+//!
+//! cron.pause("nightly-cleanup").await?;
+//! cron.trigger("nightly-cleanup")?;
+//! ```
+//!
+//! Pausing is local to the process: under leader election, pause the job on
+//! every replica, or restart them from a shared store.
+//!
+//! Ticks, run durations and timeouts follow the scheduler's `Clock` (see
+//! `with_clock`), so tests can drive a schedule with an `rf_test::MockClock`:
+//!
+//! ```rust,ignore
+//! let mock = MockClock::at(midnight);
+//! let cron = Cron::new().await?.with_clock(mock.clock());
+//! cron.add("0 0 3 * * *", backup).await?;
+//! cron.start().await?;
+//! mock.wait_for_sleepers(1).await;
+//! mock.advance(3.hours()).await;
+//! ```
+
+pub mod store;
+
+pub use store::*;
+
+use crate::cfg::RfDuration;
+use crate::report::{ErrorReport, ErrorReporting, ReportContext, ReportKind};
+use chrono::{DateTime, Utc};
+use rf_core::clock::Clock;
+use crate::lock::LockBackend;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Lock lifetime under leader election for jobs without a timeout
+const DEFAULT_LOCK_TTL: Duration = Duration::from_secs(60);
+
+/// What a tick does while the previous run of the job is still going
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overlap {
+    /// Drop the tick, counted in `CronJobInfo::skipped`
+    Skip,
+    /// Run after the running ones finish, in tick order
+    Queue,
+    /// Run alongside the running ones
+    #[default]
+    Concurrent,
+}
+
+/// Options of one job
+#[derive(Debug, Clone, Default)]
+pub struct JobOptions {
+    /// Name shown by `Cron::jobs`
+    pub name: Option<String>,
+    /// IANA timezone the expression is evaluated in, such as `Asia/Shanghai`
+    pub timezone: Option<String>,
+    pub overlap: Overlap,
+    /// Runs longer than this are recorded as failed; also the lock lifetime under leader election
+    pub timeout: Option<Duration>,
+}
+
+impl JobOptions {
+    /// Default options: UTC, concurrent runs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the job name
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Evaluate the expression in `timezone`
+    pub fn timezone(mut self, timezone: &str) -> Self {
+        self.timezone = Some(timezone.to_string());
+        self
+    }
+
+    /// Set the overlap policy
+    pub fn overlap(mut self, overlap: Overlap) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Record runs longer than `timeout` as failed
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
 
-use tokio_cron_scheduler::{Job, JobScheduler};
+/// Registered cron job
+#[derive(Debug, Clone, Serialize)]
+pub struct CronJobInfo {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub schedule: String,
+    pub timezone: String,
+    pub overlap: Overlap,
+    pub timeout_ms: Option<u64>,
+    /// Next run time, once the scheduler has started
+    pub next_run: Option<DateTime<Utc>>,
+    /// Start of the last finished run
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_duration_ms: Option<u64>,
+    /// Panic or timeout of the last run, `None` when it succeeded
+    pub last_error: Option<String>,
+    /// Finished runs
+    pub runs: u64,
+    /// Runs that panicked or timed out
+    pub failures: u64,
+    /// Ticks dropped by `Overlap::Skip`, or because the lock could not be checked
+    pub skipped: u64,
+    /// Ticks run by another replica under leader election
+    pub claimed_elsewhere: u64,
+    /// Runs in progress
+    pub running: usize,
+    /// Ticks are skipped while paused
+    pub paused: bool,
+    /// Registered handler of a job built from a `JobSpec`
+    pub handler: Option<String>,
+}
+
+/// When a job runs
+enum Schedule {
+    Cron(Box<::cron::Schedule>, Tz),
+    Every(Duration),
+}
+
+impl Schedule {
+    fn parse(expression: &str, timezone: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut expression = expression.trim();
+        let mut tz = None;
+        if let Some(rest) = expression.strip_prefix("CRON_TZ=").or_else(|| expression.strip_prefix("TZ=")) {
+            let (name, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            tz = Some(name);
+            expression = rest.trim();
+        }
+        if let Some(every) = expression.strip_prefix("@every") {
+            let every: RfDuration = every.parse()?;
+            if every.is_zero() {
+                return Err(format!("Invalid cron interval '{}'", expression).into());
+            }
+            return Ok(Schedule::Every(every.into()));
+        }
+        let tz = match timezone.or(tz) {
+            Some(name) => Tz::from_str(name).map_err(|_| format!("Unknown timezone '{}'", name))?,
+            None => Tz::UTC,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let expression = if fields.len() == 5 {
+            format!("0 {} {} {} {} {}", fields[0], fields[1], fields[2], fields[3], standard_weekdays(fields[4])?)
+        } else {
+            expression.to_string()
+        };
+        let schedule = ::cron::Schedule::from_str(&expression)
+            .map_err(|e| format!("Invalid cron expression '{}': {}", expression, e))?;
+        Ok(Schedule::Cron(Box::new(schedule), tz))
+    }
+
+    /// First run strictly after `after`; `aligned` puts `@every` ticks on multiples of the interval
+    fn next_after(&self, after: DateTime<Utc>, aligned: bool) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Cron(schedule, tz) => schedule
+                .after(&after.with_timezone(tz))
+                .next()
+                .map(|next| next.with_timezone(&Utc)),
+            Schedule::Every(every) if aligned => {
+                let every = every.as_millis().max(1) as i64;
+                let next = (after.timestamp_millis().div_euclid(every) + 1) * every;
+                DateTime::from_timestamp_millis(next)
+            }
+            Schedule::Every(every) => chrono::Duration::from_std(*every).ok().map(|every| after + every),
+        }
+    }
+
+    fn timezone(&self) -> String {
+        match self {
+            Schedule::Cron(_, tz) => tz.name().to_string(),
+            Schedule::Every(_) => "UTC".to_string(),
+        }
+    }
+}
+
+/// Translate a standard weekday field (0-7, Sunday = 0 or 7) to the 1-7
+/// numbering (Sunday = 1) of six-field expressions
+fn standard_weekdays(field: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let day = |day: &str| -> Result<String, Box<dyn std::error::Error>> {
+        match day.parse::<u8>() {
+            Ok(n @ 0..=6) => Ok((n + 1).to_string()),
+            Ok(7) => Ok("1".to_string()),
+            Ok(_) => Err(format!("Invalid weekday '{}'", day).into()),
+            // `*`, `?` and names mean the same in both forms
+            Err(_) => Ok(day.to_string()),
+        }
+    };
+    let items = field
+        .split(',')
+        .map(|item| {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, format!("/{}", step)),
+                None => (item, String::new()),
+            };
+            match range.split_once('-') {
+                // `5-7` is Friday to Sunday, which wraps in the 1-7 numbering
+                Some((start, "7")) if step.is_empty() => Ok(format!("{}-7,1", day(start)?)),
+                Some((start, end)) => Ok(format!("{}-{}{}", day(start)?, day(end)?, step)),
+                None => Ok(format!("{}{}", day(range)?, step)),
+            }
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    Ok(items.join(","))
+}
+
+/// Lock deciding which replica runs a tick
+struct Leader {
+    locks: Arc<dyn LockBackend>,
+    prefix: String,
+    /// Lock owner, identifies this scheduler
+    owner: String,
+}
+
+impl Leader {
+    /// Take the lock of `job` for the tick at `tick`, `true` when this replica runs it
+    async fn claim(&self, job: &CronJob, tick: DateTime<Utc>) -> rf_errors::Result<bool> {
+        let key = format!("{}{}:{}", self.prefix, job.lock_key, tick.timestamp_millis());
+        let ttl = job.timeout.unwrap_or(DEFAULT_LOCK_TTL).max(Duration::from_millis(1));
+        self.locks.try_acquire(&key, &self.owner, ttl).await
+    }
+}
+
+/// Outcome of the last finished run
+#[derive(Default)]
+struct LastRun {
+    at: Option<DateTime<Utc>>,
+    duration_ms: Option<u64>,
+    error: Option<String>,
+}
+
+type JobFn = Arc<dyn Fn() + Send + Sync>;
+
+struct CronJob {
+    id: Uuid,
+    name: Option<String>,
+    expression: String,
+    /// Identifies the job across replicas under leader election
+    lock_key: String,
+    schedule: Schedule,
+    overlap: Overlap,
+    timeout: Option<Duration>,
+    run: JobFn,
+    /// Receives panics and timeouts, the global reporting when `None`
+    reporting: Option<Arc<ErrorReporting>>,
+    /// Stored form, for jobs built from a `JobSpec`
+    spec: Mutex<Option<JobSpec>>,
+    paused: AtomicBool,
+    /// Schedule loop, once the scheduler has started
+    task: Mutex<Option<JoinHandle<()>>>,
+    /// Held by a run under `Skip` and `Queue`
+    lock: Arc<tokio::sync::Mutex<()>>,
+    next_run: Mutex<Option<DateTime<Utc>>>,
+    last: Mutex<LastRun>,
+    runs: AtomicU64,
+    failures: AtomicU64,
+    skipped: AtomicU64,
+    claimed_elsewhere: AtomicU64,
+    running: AtomicUsize,
+    clock: Clock,
+}
+
+impl CronJob {
+    fn info(&self) -> CronJobInfo {
+        let last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        CronJobInfo {
+            id: self.id,
+            name: self.name.clone(),
+            schedule: self.expression.clone(),
+            timezone: self.schedule.timezone(),
+            overlap: self.overlap,
+            timeout_ms: self.timeout.map(|timeout| timeout.as_millis() as u64),
+            next_run: *self.next_run.lock().unwrap_or_else(|e| e.into_inner()),
+            last_run: last.at,
+            last_duration_ms: last.duration_ms,
+            last_error: last.error.clone(),
+            runs: self.runs.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            skipped: self.skipped.load(Ordering::Relaxed),
+            claimed_elsewhere: self.claimed_elsewhere.load(Ordering::Relaxed),
+            running: self.running.load(Ordering::Relaxed),
+            paused: self.paused.load(Ordering::Relaxed),
+            handler: self.spec.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(|spec| spec.handler.clone()),
+        }
+    }
+
+    fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.expression)
+    }
+
+    /// Whether `key` is the job's name or id
+    fn is(&self, key: &str) -> bool {
+        self.name.as_deref() == Some(key) || self.id.to_string() == key
+    }
+
+    /// Start a run as the overlap policy allows
+    fn fire(self: &Arc<Self>) {
+        let guard = match self.overlap {
+            Overlap::Skip => match self.lock.clone().try_lock_owned() {
+                Ok(guard) => Some(guard),
+                Err(_) => {
+                    self.skipped.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!("Cron job {} is still running, skipped a run", self.label());
+                    return;
+                }
+            },
+            Overlap::Queue | Overlap::Concurrent => None,
+        };
+        let job = self.clone();
+        tokio::spawn(async move {
+            let _guard = match (guard, job.overlap) {
+                (Some(guard), _) => Some(guard),
+                (None, Overlap::Queue) => Some(job.lock.clone().lock_owned().await),
+                (None, _) => None,
+            };
+            job.execute().await;
+        });
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        self.clock.system_time().into()
+    }
+
+    async fn execute(&self) {
+        let at = self.utc_now();
+        let started = self.clock.now();
+        self.running.fetch_add(1, Ordering::Relaxed);
+        let run = self.run.clone();
+        let mut handle = tokio::task::spawn_blocking(move || run());
+        let (result, timed_out) = match self.timeout {
+            Some(timeout) => match self.clock.timeout(timeout, &mut handle).await {
+                Ok(result) => (result.map_err(panic_message), false),
+                Err(_) => (Err(format!("timed out after {:?}", timeout)), true),
+            },
+            None => ((&mut handle).await.map_err(panic_message), false),
+        };
+        if let Err(error) = &result {
+            self.report(error, timed_out);
+        }
+        self.record(at, started, result.err());
+        if timed_out {
+            // The overlap slot is held until the run really ends
+            let _ = handle.await;
+        }
+        self.running.fetch_sub(1, Ordering::Relaxed);
+    }
 
-/// Cron scheduler wrapper
+    fn report(&self, error: &str, timed_out: bool) {
+        let Some(reporting) = self.reporting.clone().or_else(crate::report::global) else {
+            return;
+        };
+        let report = if timed_out {
+            ErrorReport::new(ReportKind::Error, rf_errors::RfError::Timeout(format!("Cron job {} {}", self.label(), error)))
+        } else {
+            ErrorReport::panic(error)
+        };
+        let context = ReportContext { job: Some(self.label().to_string()), ..Default::default() };
+        reporting.capture(report.context(context).tag("schedule", self.expression.clone()));
+    }
+
+    fn record(&self, at: DateTime<Utc>, started: Instant, error: Option<String>) {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        if let Some(error) = &error {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+            tracing::error!("Cron job {} failed: {}", self.label(), error);
+        }
+        *self.last.lock().unwrap_or_else(|e| e.into_inner()) = LastRun {
+            at: Some(at),
+            duration_ms: Some(self.clock.elapsed(started).as_millis() as u64),
+            error,
+        };
+    }
+
+    /// Fire the job at each tick of its schedule
+    async fn schedule_loop(self: Arc<Self>, leader: Option<Arc<Leader>>) {
+        let mut after = self.utc_now();
+        while let Some(next) = self.schedule.next_after(after, leader.is_some()) {
+            *self.next_run.lock().unwrap_or_else(|e| e.into_inner()) = Some(next);
+            if let Ok(wait) = (next - self.utc_now()).to_std() {
+                self.clock.sleep(wait).await;
+            }
+            // Paused jobs skip their ticks before claiming them
+            if !self.paused.load(Ordering::SeqCst) {
+                match &leader {
+                    None => self.fire(),
+                    Some(leader) => match leader.claim(&self, next).await {
+                        Ok(true) => self.fire(),
+                        Ok(false) => {
+                            self.claimed_elsewhere.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Err(e) => {
+                            // Running without the lock could run the tick on every replica
+                            self.skipped.fetch_add(1, Ordering::Relaxed);
+                            tracing::warn!("Cron job {} skipped a run, lock unavailable: {}", self.label(), e);
+                        }
+                    },
+                }
+            }
+            // Ticks missed while the process was suspended are not caught up
+            let now = self.utc_now();
+            after = if now - next > chrono::Duration::seconds(1) { now } else { next };
+        }
+        *self.next_run.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Message of a job that panicked
+fn panic_message(e: tokio::task::JoinError) -> String {
+    match e.try_into_panic() {
+        Ok(panic) => crate::report::panic_message(panic.as_ref()),
+        Err(e) => e.to_string(),
+    }
+}
+
+/// Cron scheduler
+///
+/// Dropping the scheduler stops it; runs in progress finish.
 pub struct Cron {
-    scheduler: JobScheduler,
+    jobs: Mutex<Vec<Arc<CronJob>>>,
+    started: AtomicBool,
+    leader: Option<Arc<Leader>>,
+    reporting: Option<Arc<ErrorReporting>>,
+    handlers: Mutex<HashMap<String, JobFn>>,
+    store: Option<Arc<dyn JobStore>>,
+    clock: Clock,
 }
 
 impl Cron {
     /// Create a new cron scheduler
     pub async fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let scheduler = JobScheduler::new().await?;
-        Ok(Self { scheduler })
+        Ok(Self {
+            jobs: Mutex::new(Vec::new()),
+            started: AtomicBool::new(false),
+            leader: None,
+            reporting: None,
+            handlers: Mutex::new(HashMap::new()),
+            store: None,
+            clock: Clock::system(),
+        })
+    }
+
+    /// Run each tick on one replica only, coordinating through `locks`
+    ///
+    /// Locks are named `cron:<job>:<tick>`; replicas must share the backend
+    /// and register the same jobs with the same names (or expressions) to
+    /// coordinate.
+    pub fn with_leader_election(mut self, locks: Arc<dyn LockBackend>) -> Self {
+        let host = sysinfo::System::host_name().unwrap_or_else(|| "-".to_string());
+        self.leader = Some(Arc::new(Leader {
+            locks,
+            prefix: "cron:".to_string(),
+            owner: format!("{}:{}:{}", host, std::process::id(), Uuid::new_v4()),
+        }));
+        self
+    }
+
+    /// Prefix of the leader election locks (default `cron:`), to separate applications sharing a backend
+    pub fn with_lock_prefix(mut self, prefix: &str) -> Self {
+        if let Some(leader) = self.leader.as_mut().and_then(Arc::get_mut) {
+            leader.prefix = prefix.to_string();
+        }
+        self
+    }
+
+    /// Report panics and timeouts of jobs added afterwards to `reporting`
+    /// instead of the global reporting
+    pub fn with_error_reporting(mut self, reporting: Arc<ErrorReporting>) -> Self {
+        self.reporting = Some(reporting);
+        self
+    }
+
+    /// Save jobs built from a `JobSpec` to `store`, see `restore`
+    pub fn with_store(mut self, store: impl JobStore + 'static) -> Self {
+        self.store = Some(Arc::new(store));
+        self
+    }
+
+    /// Schedule jobs added afterwards by `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
     }
 
-    /// Add a cron job
-    pub async fn add<F>(&self, schedule: &str, job: F) -> Result<(), Box<dyn std::error::Error>>
+    /// Register a handler that `JobSpec`s can name
+    pub fn handler<F>(&self, name: &str, handler: F)
     where
         F: Fn() + Send + Sync + 'static,
     {
-        let job = std::sync::Arc::new(job);
-        let job_clone = job.clone();
-        let job_async = Job::new_async(schedule, move |_uuid, _l| {
-            let job = job_clone.clone();
-            Box::pin(async move {
-                job();
+        self.handlers.lock().unwrap_or_else(|e| e.into_inner()).insert(name.to_string(), Arc::new(handler));
+    }
+
+    /// Add a cron job with the default options, returns its id
+    ///
+    /// `schedule` is a cron expression or `@every <duration>` such as
+    /// `@every 90s` or `@every 1h30m`, so intervals can come from configuration.
+    pub async fn add<F>(&self, schedule: &str, job: F) -> Result<Uuid, Box<dyn std::error::Error>>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.add_with(schedule, JobOptions::default(), job).await
+    }
+
+    /// Add a cron job with a name, timezone or overlap policy, returns its id
+    pub async fn add_with<F>(&self, schedule: &str, options: JobOptions, job: F) -> Result<Uuid, Box<dyn std::error::Error>>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.insert(schedule, options, Arc::new(job), None)
+    }
+
+    /// Add or replace the job `spec.name`, saving it to the store
+    ///
+    /// The handler must be registered with `handler` first.
+    pub async fn schedule(&self, spec: JobSpec) -> Result<Uuid, Box<dyn std::error::Error>> {
+        let id = self.insert_spec(spec.clone())?;
+        if let Some(store) = &self.store {
+            store.save(&spec).await?;
+        }
+        Ok(id)
+    }
+
+    /// Add the jobs saved in the store, returns how many were added
+    ///
+    /// Call after registering the handlers; specs naming an unknown handler
+    /// are logged and left in the store.
+    pub async fn restore(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let mut restored = 0;
+        for spec in store.load().await? {
+            match self.insert_spec(spec.clone()) {
+                Ok(_) => restored += 1,
+                Err(e) => tracing::warn!("Cron job {} not restored: {}", spec.name, e),
+            }
+        }
+        Ok(restored)
+    }
+
+    fn insert_spec(&self, spec: JobSpec) -> Result<Uuid, Box<dyn std::error::Error>> {
+        let run = self
+            .handlers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&spec.handler)
+            .cloned()
+            .ok_or_else(|| format!("Unknown cron handler '{}'", spec.handler))?;
+        let mut options = JobOptions::new().name(&spec.name).overlap(spec.overlap);
+        options.timezone = spec.timezone.clone();
+        options.timeout = spec.timeout_ms.map(Duration::from_millis);
+        // Parse before removing the job it replaces
+        Schedule::parse(&spec.schedule, spec.timezone.as_deref())?;
+        self.detach(&spec.name);
+        let schedule = spec.schedule.clone();
+        self.insert(&schedule, options, run, Some(spec))
+    }
+
+    fn insert(&self, schedule: &str, options: JobOptions, run: JobFn, spec: Option<JobSpec>) -> Result<Uuid, Box<dyn std::error::Error>> {
+        let schedule_parsed = Schedule::parse(schedule, options.timezone.as_deref())?;
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let label = options.name.clone().unwrap_or_else(|| schedule.trim().to_string());
+        // Jobs sharing a label are told apart by registration order, the same on every replica
+        let same = jobs.iter().filter(|job| job.label() == label).count();
+        let job = Arc::new(CronJob {
+            id: Uuid::new_v4(),
+            name: options.name,
+            expression: schedule.trim().to_string(),
+            lock_key: if same == 0 { label } else { format!("{}#{}", label, same) },
+            schedule: schedule_parsed,
+            overlap: options.overlap,
+            timeout: options.timeout,
+            run,
+            reporting: self.reporting.clone(),
+            paused: AtomicBool::new(spec.as_ref().is_some_and(|spec| spec.paused)),
+            spec: Mutex::new(spec),
+            task: Mutex::new(None),
+            lock: Arc::new(tokio::sync::Mutex::new(())),
+            next_run: Mutex::new(None),
+            last: Mutex::new(LastRun::default()),
+            runs: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            skipped: AtomicU64::new(0),
+            claimed_elsewhere: AtomicU64::new(0),
+            running: AtomicUsize::new(0),
+            clock: self.clock.clone(),
+        });
+        let id = job.id;
+        jobs.push(job.clone());
+        drop(jobs);
+        if self.started.load(Ordering::SeqCst) {
+            self.spawn(job);
+        }
+        Ok(id)
+    }
+
+    /// Add a job running every `interval`
+    pub async fn add_every<F>(&self, interval: Duration, job: F) -> Result<Uuid, Box<dyn std::error::Error>>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.add(&format!("@every {}", RfDuration::from(interval)), job).await
+    }
+
+    fn spawn(&self, job: Arc<CronJob>) {
+        let task = tokio::spawn(job.clone().schedule_loop(self.leader.clone()));
+        if let Some(previous) = job.task.lock().unwrap_or_else(|e| e.into_inner()).replace(task) {
+            previous.abort();
+        }
+    }
+
+    fn find(&self, job: &str) -> Result<Arc<CronJob>, Box<dyn std::error::Error>> {
+        self.jobs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|candidate| candidate.is(job))
+            .cloned()
+            .ok_or_else(|| format!("Unknown cron job '{}'", job).into())
+    }
+
+    /// Unschedule the job `key` without touching the store
+    fn detach(&self, key: &str) -> Option<Arc<CronJob>> {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let index = jobs.iter().position(|job| job.is(key))?;
+        let job = jobs.remove(index);
+        if let Some(task) = job.task.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            task.abort();
+        }
+        Some(job)
+    }
+
+    /// Remove a job by name or id, deleting it from the store; runs in progress finish
+    ///
+    /// Returns `false` when there is no such job.
+    pub async fn remove(&self, job: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let Some(job) = self.detach(job) else {
+            return Ok(false);
+        };
+        let persisted = job.spec.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+        if let (true, Some(store), Some(name)) = (persisted, &self.store, &job.name) {
+            store.delete(name).await?;
+        }
+        Ok(true)
+    }
+
+    /// Skip the ticks of a job until `resume`
+    pub async fn pause(&self, job: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.set_paused(job, true).await
+    }
+
+    /// Run a paused job on its ticks again
+    pub async fn resume(&self, job: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.set_paused(job, false).await
+    }
+
+    async fn set_paused(&self, job: &str, paused: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let job = self.find(job)?;
+        job.paused.store(paused, Ordering::SeqCst);
+        let spec = {
+            let mut spec = job.spec.lock().unwrap_or_else(|e| e.into_inner());
+            spec.as_mut().map(|spec| {
+                spec.paused = paused;
+                spec.clone()
             })
-        })?;
-        self.scheduler.add(job_async).await?;
+        };
+        if let (Some(spec), Some(store)) = (spec, &self.store) {
+            store.save(&spec).await?;
+        }
+        Ok(())
+    }
+
+    /// Run a job now, as its overlap policy allows, even when paused
+    ///
+    /// The run is local: leader election does not apply to it.
+    pub fn trigger(&self, job: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.find(job)?.fire();
         Ok(())
     }
 
     /// Start the scheduler
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
-        self.scheduler.start().await?;
+        if self.started.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        for job in jobs {
+            self.spawn(job);
+        }
         Ok(())
     }
+
+    /// Stop scheduling new runs; runs in progress finish
+    pub fn stop(&self) {
+        self.started.store(false, Ordering::SeqCst);
+        for job in self.jobs.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            if let Some(task) = job.task.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                task.abort();
+            }
+            *job.next_run.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        }
+    }
+
+    /// List registered jobs with their next and last run
+    pub async fn jobs(&self) -> Vec<CronJobInfo> {
+        self.jobs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|job| job.info())
+            .collect()
+    }
+}
+
+impl Drop for Cron {
+    fn drop(&mut self) {
+        self.stop();
+    }
 }
@@ -0,0 +1,254 @@
+//! # store
+//!
+//! store 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Cron job persistence
+//!
+//! A `JobSpec` is the storable form of a job: its schedule and options, and
+//! the name of a handler registered with `Cron::handler` in place of the
+//! closure. `Cron::schedule` saves specs to the scheduler's `JobStore` and
+//! `Cron::restore` recreates them after a restart. Stores keep one spec per
+//! job name.
+
+use super::Overlap;
+use async_trait::async_trait;
+use rf_database::redis::RedisClient;
+use rf_errors::{Result, RfError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A job that can be stored, see the module documentation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobSpec {
+    /// Unique job name
+    pub name: String,
+    /// Cron expression or `@every <duration>`
+    pub schedule: String,
+    /// Name of the handler registered with `Cron::handler`
+    pub handler: String,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub overlap: Overlap,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Paused jobs keep their schedule but do not run on ticks
+    #[serde(default)]
+    pub paused: bool,
+}
+
+impl JobSpec {
+    /// Run `handler` on `schedule` as the job `name`
+    pub fn new(name: &str, schedule: &str, handler: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            schedule: schedule.to_string(),
+            handler: handler.to_string(),
+            timezone: None,
+            overlap: Overlap::default(),
+            timeout_ms: None,
+            paused: false,
+        }
+    }
+
+    /// Evaluate the expression in `timezone`
+    pub fn timezone(mut self, timezone: &str) -> Self {
+        self.timezone = Some(timezone.to_string());
+        self
+    }
+
+    /// Set the overlap policy
+    pub fn overlap(mut self, overlap: Overlap) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Record runs longer than `timeout` as failed
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    /// Start paused
+    pub fn paused(mut self, paused: bool) -> Self {
+        self.paused = paused;
+        self
+    }
+}
+
+/// Where job specs are kept
+#[async_trait]
+pub trait JobStore: Send + Sync {
+    /// All stored specs
+    async fn load(&self) -> Result<Vec<JobSpec>>;
+
+    /// Insert or replace the spec with the same name
+    async fn save(&self, spec: &JobSpec) -> Result<()>;
+
+    /// Delete the spec of job `name`; a missing spec is not an error
+    async fn delete(&self, name: &str) -> Result<()>;
+}
+
+/// In-process store, for tests and single-run tools
+#[derive(Debug, Clone, Default)]
+pub struct MemoryJobStore {
+    specs: Arc<Mutex<BTreeMap<String, JobSpec>>>,
+}
+
+impl MemoryJobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl JobStore for MemoryJobStore {
+    async fn load(&self) -> Result<Vec<JobSpec>> {
+        Ok(self.specs.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect())
+    }
+
+    async fn save(&self, spec: &JobSpec) -> Result<()> {
+        self.specs.lock().unwrap_or_else(|e| e.into_inner()).insert(spec.name.clone(), spec.clone());
+        Ok(())
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        self.specs.lock().unwrap_or_else(|e| e.into_inner()).remove(name);
+        Ok(())
+    }
+}
+
+/// Specs as JSON in one Redis hash (default key `cron:jobs`), field per job
+pub struct RedisJobStore {
+    client: Arc<RedisClient>,
+    key: String,
+}
+
+impl RedisJobStore {
+    pub fn new(client: Arc<RedisClient>) -> Self {
+        Self { client, key: "cron:jobs".to_string() }
+    }
+
+    /// Hash key, to separate applications sharing a Redis
+    pub fn key(mut self, key: &str) -> Self {
+        self.key = key.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl JobStore for RedisJobStore {
+    async fn load(&self) -> Result<Vec<JobSpec>> {
+        let fields = self.client.hash().hgetall(&self.key).await?;
+        let mut specs = fields.into_values().map(|json| decode(&json)).collect::<Result<Vec<_>>>()?;
+        specs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(specs)
+    }
+
+    async fn save(&self, spec: &JobSpec) -> Result<()> {
+        self.client.hash().hset(&self.key, &spec.name, &encode(spec)?).await
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        self.client.hash().hdel(&self.key, &[name]).await?;
+        Ok(())
+    }
+}
+
+/// Specs as JSON in a table with `name` and `spec` columns, created by `ensure_table`
+pub struct DatabaseJobStore {
+    database: Arc<rf_database::db::Database>,
+    table: String,
+}
+
+impl DatabaseJobStore {
+    pub fn new(database: Arc<rf_database::db::Database>, table: &str) -> Self {
+        Self { database, table: table.to_string() }
+    }
+
+    /// Create the table if it does not exist
+    pub async fn ensure_table(&self) -> Result<()> {
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {} (name VARCHAR(191) PRIMARY KEY, spec TEXT NOT NULL)",
+            self.table
+        );
+        self.database.raw_execute(&sql).await?;
+        Ok(())
+    }
+
+    /// SQL with `$n` placeholders rewritten to `?` for MySQL and SQLite
+    fn sql(&self, sql: &str) -> String {
+        let sql = sql.replace("{table}", &self.table);
+        if self.database.as_postgres().is_some() {
+            return sql;
+        }
+        regex::Regex::new(r"\$\d+").map_or(sql.clone(), |re| re.replace_all(&sql, "?").into_owned())
+    }
+
+    async fn execute(&self, sql: &str, values: &[&str]) -> Result<()> {
+        let sql = self.sql(sql);
+        macro_rules! run {
+            ($pool:expr) => {{
+                let mut query = sqlx::query(&sql);
+                for value in values {
+                    query = query.bind(*value);
+                }
+                query.execute($pool).await.map(|_| ())
+            }};
+        }
+        let result = if let Some(pool) = self.database.as_postgres() {
+            run!(pool)
+        } else if let Some(pool) = self.database.as_mysql() {
+            run!(pool)
+        } else if let Some(pool) = self.database.as_sqlite() {
+            run!(pool)
+        } else {
+            return Err(RfError::Database("Unsupported database".to_string()));
+        };
+        result.map_err(|e| RfError::Database(format!("Cron job query failed: {}", e)))
+    }
+}
+
+#[async_trait]
+impl JobStore for DatabaseJobStore {
+    async fn load(&self) -> Result<Vec<JobSpec>> {
+        let sql = self.sql("SELECT spec FROM {table} ORDER BY name");
+        let rows: std::result::Result<Vec<(String,)>, sqlx::Error> = if let Some(pool) = self.database.as_postgres() {
+            sqlx::query_as(&sql).fetch_all(pool).await
+        } else if let Some(pool) = self.database.as_mysql() {
+            sqlx::query_as(&sql).fetch_all(pool).await
+        } else if let Some(pool) = self.database.as_sqlite() {
+            sqlx::query_as(&sql).fetch_all(pool).await
+        } else {
+            return Err(RfError::Database("Unsupported database".to_string()));
+        };
+        let rows = rows.map_err(|e| RfError::Database(format!("Cron job query failed: {}", e)))?;
+        rows.iter().map(|(json,)| decode(json)).collect()
+    }
+
+    async fn save(&self, spec: &JobSpec) -> Result<()> {
+        let sql = if self.database.as_mysql().is_some() {
+            "INSERT INTO {table} (name, spec) VALUES ($1, $2) ON DUPLICATE KEY UPDATE spec = VALUES(spec)"
+        } else {
+            "INSERT INTO {table} (name, spec) VALUES ($1, $2) ON CONFLICT (name) DO UPDATE SET spec = excluded.spec"
+        };
+        self.execute(sql, &[&spec.name, &encode(spec)?]).await
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        self.execute("DELETE FROM {table} WHERE name = $1", &[name]).await
+    }
+}
+
+fn encode(spec: &JobSpec) -> Result<String> {
+    serde_json::to_string(spec).map_err(|e| RfError::Serialization(format!("Failed to serialize cron job: {}", e)))
+}
+
+fn decode(json: &str) -> Result<JobSpec> {
+    serde_json::from_str(json).map_err(|e| RfError::Serialization(format!("Invalid stored cron job: {}", e)))
+}
@@ -6,10 +6,43 @@
 //! @date 2026-01-06
 
 //! File system notification
+//!
+//! `FsNotify` calls a callback with each changed path. `FsWatch` delivers
+//! typed events over an async stream instead, filtered by glob patterns and
+//! debounced: the events of a burst (an editor saving through a temporary
+//! file, a build writing a file in several steps) are coalesced per path and
+//! delivered once the burst is quiet.
+//!
+//! ```rust,ignore
+//! use futures::StreamExt;
+//! use rf_os::fsnotify::{FsEvent, FsWatch};
+//!
+//! let mut events = FsWatch::new("templates")
+//!     .include("**/*.html")
+//!     .exclude("**/.cache/**")
+//!     .start()?;
+//! while let Some(event) = events.next().await {
+//!     if let FsEvent::Modified(path) = event {
+//!         view.reload(&path)?;
+//!     }
+//! }
+//! ```
 
-use notify::{RecommendedWatcher, Watcher};
-use rf_errors::Result;
-use std::path::Path;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rf_errors::{Result, RfError};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::mpsc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Default quiet period before a burst of events is delivered
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(100);
+/// A burst that never goes quiet is delivered after this many quiet periods
+const MAX_DELAY_FACTOR: u32 = 10;
 
 /// File system watcher
 pub struct FsNotify {
@@ -19,17 +52,335 @@ pub struct FsNotify {
 impl FsNotify {
     /// Create a new file system watcher
     pub fn new() -> Result<Self> {
-        let watcher = notify::recommended_watcher(|_| {})
-            .map_err(|e| rf_errors::RfError::Internal(format!("Failed to create watcher: {}", e)))?;
+        Self::with_callback(|_| {})
+    }
+
+    /// Create a watcher that calls `callback` with each changed path
+    ///
+    /// The callback runs on the watcher's own thread, once per path of every
+    /// create, modify or remove event; access events are skipped.
+    pub fn with_callback<F>(callback: F) -> Result<Self>
+    where
+        F: Fn(&Path) + Send + 'static,
+    {
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if matches!(event.kind, notify::EventKind::Access(_)) {
+                return;
+            }
+            for path in &event.paths {
+                callback(path);
+            }
+        })
+        .map_err(|e| rf_errors::RfError::Internal(format!("Failed to create watcher: {}", e)))?;
         Ok(Self { watcher })
     }
 
     /// Watch a path
     pub fn watch<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
         self.watcher
-            .watch(path.as_ref(), notify::RecursiveMode::Recursive)
+            .watch(path.as_ref(), RecursiveMode::Recursive)
             .map_err(|e| rf_errors::RfError::Internal(format!("Failed to watch path: {}", e)))?;
         Ok(())
     }
+
+    /// Watch a file, or a directory without its subdirectories
+    pub fn watch_non_recursive<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.watcher
+            .watch(path.as_ref(), RecursiveMode::NonRecursive)
+            .map_err(|e| rf_errors::RfError::Internal(format!("Failed to watch path: {}", e)))?;
+        Ok(())
+    }
+
+    /// Stop watching a path
+    pub fn unwatch<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.watcher
+            .unwatch(path.as_ref())
+            .map_err(|e| rf_errors::RfError::Internal(format!("Failed to unwatch path: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// A change in a watched tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FsEvent {
+    /// A file or directory was created
+    Created(PathBuf),
+    /// Content or metadata changed
+    Modified(PathBuf),
+    /// A file or directory was removed, or moved out of the watched tree
+    Removed(PathBuf),
+    /// A file or directory was renamed within the watched tree
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+impl FsEvent {
+    /// The path the event is about; the new path for a rename
+    pub fn path(&self) -> &Path {
+        match self {
+            FsEvent::Created(path) | FsEvent::Modified(path) | FsEvent::Removed(path) => path,
+            FsEvent::Renamed { to, .. } => to,
+        }
+    }
+
+    /// Typed events of a raw notify event; access events have none
+    fn from_notify(event: notify::Event) -> Vec<FsEvent> {
+        let each = |make: fn(PathBuf) -> FsEvent, paths: Vec<PathBuf>| paths.into_iter().map(make).collect();
+        match event.kind {
+            EventKind::Access(_) => Vec::new(),
+            EventKind::Create(_) => each(FsEvent::Created, event.paths),
+            EventKind::Remove(_) => each(FsEvent::Removed, event.paths),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                let mut paths = event.paths.into_iter();
+                match (paths.next(), paths.next()) {
+                    (Some(from), Some(to)) => vec![FsEvent::Renamed { from, to }],
+                    _ => Vec::new(),
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => each(FsEvent::Removed, event.paths),
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => each(FsEvent::Created, event.paths),
+            _ => each(FsEvent::Modified, event.paths),
+        }
+    }
 }
 
+/// Include and exclude patterns, matched against paths relative to the watched root
+struct Filter {
+    roots: Vec<PathBuf>,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl Filter {
+    fn matches(&self, path: &Path) -> bool {
+        let relative = self
+            .roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+        self.include.as_ref().is_none_or(|set| set.is_match(relative))
+            && !self.exclude.as_ref().is_some_and(|set| set.is_match(relative))
+    }
+
+    fn accepts(&self, event: &FsEvent) -> bool {
+        match event {
+            FsEvent::Renamed { from, to } => self.matches(from) || self.matches(to),
+            event => self.matches(event.path()),
+        }
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid glob pattern '{}': {}", pattern, e)))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| RfError::InvalidParameter(format!("Invalid glob patterns: {}", e)))
+}
+
+/// Merge `event` into the pending events of a burst, keeping first-seen order
+fn coalesce(pending: &mut Vec<FsEvent>, event: FsEvent) {
+    use FsEvent::*;
+
+    if let Renamed { from, to } = &event {
+        // Drop the halves of the rename reported on their own
+        pending.retain(|e| !matches!(e, Removed(p) if p == from) && !matches!(e, Created(p) if p == to));
+        if let Some(index) = pending.iter().position(|e| matches!(e, Created(p) if p == from)) {
+            // Created and renamed within the burst: it was created under the new name
+            pending[index] = Created(to.clone());
+            return;
+        }
+        pending.push(event);
+        return;
+    }
+
+    let index = pending
+        .iter()
+        .position(|e| !matches!(e, Renamed { .. }) && e.path() == event.path());
+    let Some(index) = index else {
+        pending.push(event);
+        return;
+    };
+    match (&pending[index], event) {
+        // Created and removed within the burst: nothing to report
+        (Created(_), Removed(_)) => {
+            pending.remove(index);
+        }
+        (Created(_), _) => {}
+        (Removed(_), Created(path) | Modified(path)) => pending[index] = Modified(path),
+        (_, event) => pending[index] = event,
+    }
+}
+
+/// Async stream of filesystem events, see `FsWatch`
+///
+/// Watching stops when the stream is dropped.
+pub struct FsEventStream {
+    receiver: UnboundedReceiver<FsEvent>,
+    _watcher: RecommendedWatcher,
+}
+
+impl FsEventStream {
+    /// Next event, `None` once the watcher stopped
+    pub async fn recv(&mut self) -> Option<FsEvent> {
+        self.receiver.recv().await
+    }
+}
+
+impl futures::Stream for FsEventStream {
+    type Item = FsEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<FsEvent>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Typed, filtered and debounced watch of one or more paths
+///
+/// Directories are watched recursively unless `recursive(false)` is set.
+/// Glob patterns are matched against paths relative to the watched path they
+/// are under, and `*` also matches `/`: `*.rs` matches `src/lib.rs`. With
+/// include patterns only matching paths are reported; exclude patterns win
+/// over includes. A rename is reported when either name matches.
+pub struct FsWatch {
+    paths: Vec<PathBuf>,
+    recursive: bool,
+    debounce: Duration,
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl FsWatch {
+    /// Watch `path`
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            paths: vec![path.as_ref().to_path_buf()],
+            recursive: true,
+            debounce: DEFAULT_DEBOUNCE,
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+
+    /// Watch another path with the same settings
+    pub fn path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.paths.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Watch subdirectories too (default `true`)
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Quiet period before a burst of events is delivered (default 100ms)
+    ///
+    /// `Duration::ZERO` delivers events as they happen, without coalescing; a
+    /// rename is then also reported as `Removed` and `Created` before `Renamed`.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Only report paths matching `pattern`, such as `**/*.toml`
+    pub fn include(mut self, pattern: &str) -> Self {
+        self.include.push(pattern.to_string());
+        self
+    }
+
+    /// Never report paths matching `pattern`, such as `**/target/**`
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(pattern.to_string());
+        self
+    }
+
+    /// Start watching
+    ///
+    /// # Errors
+    ///
+    /// Returns `RfError::InvalidParameter` for an invalid glob pattern and
+    /// `RfError::Internal` when a path cannot be watched.
+    pub fn start(self) -> Result<FsEventStream> {
+        let filter = Filter {
+            roots: self.paths.clone(),
+            include: glob_set(&self.include)?,
+            exclude: glob_set(&self.exclude)?,
+        };
+        let (sender, receiver) = unbounded_channel();
+        let mut watcher = if self.debounce.is_zero() {
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                for event in FsEvent::from_notify(event) {
+                    if filter.accepts(&event) {
+                        let _ = sender.send(event);
+                    }
+                }
+            })
+        } else {
+            let (raw, events) = mpsc::channel::<FsEvent>();
+            spawn_debouncer(events, sender, self.debounce)?;
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                for event in FsEvent::from_notify(event) {
+                    if filter.accepts(&event) {
+                        let _ = raw.send(event);
+                    }
+                }
+            })
+        }
+        .map_err(|e| RfError::Internal(format!("Failed to create watcher: {}", e)))?;
+
+        let mode = if self.recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        for path in &self.paths {
+            watcher
+                .watch(path, mode)
+                .map_err(|e| RfError::Internal(format!("Failed to watch path: {}", e)))?;
+        }
+        Ok(FsEventStream { receiver, _watcher: watcher })
+    }
+}
+
+/// Coalesce bursts from `events` and forward them to `sender`
+///
+/// Ends once the watcher, and with it the raw sender, is dropped.
+fn spawn_debouncer(events: mpsc::Receiver<FsEvent>, sender: UnboundedSender<FsEvent>, debounce: Duration) -> Result<()> {
+    let max_delay = debounce * MAX_DELAY_FACTOR;
+    std::thread::Builder::new()
+        .name("rf-fsnotify-debounce".to_string())
+        .spawn(move || {
+            while let Ok(first) = events.recv() {
+                let started = Instant::now();
+                let mut pending = vec![first];
+                loop {
+                    let wait = debounce.min(max_delay.saturating_sub(started.elapsed()));
+                    match events.recv_timeout(wait) {
+                        Ok(event) => coalesce(&mut pending, event),
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                for event in pending {
+                    if sender.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+        })
+        .map_err(RfError::Io)?;
+    Ok(())
+}
@@ -22,12 +22,15 @@
 //! - **file**: 文件操作
 //! - **fpool**: 文件池管理
 //! - **fsnotify**: 文件系统通知
+//! - **lock**: 分布式锁抽象（`LockBackend`）
 //! - **log**: 日志系统
-//! - **metric**: 指标收集
+//! - **metric**: 指标收集（带标签的注册表、Prometheus 文本格式、进程/运行时/连接池指标）
 //! - **metric_otel**: OpenTelemetry 指标
 //! - **mlock**: 内存锁
 //! - **mutex**: 互斥锁封装
+//! - **pipeline**: 异步处理流水线
 //! - **proc**: 进程管理
+//! - **report**: 错误上报（采样、脱敏、告警）
 //! - **res**: 资源管理
 //! - **rpool**: 运行时池（任务池）
 //! - **session**: 会话管理
@@ -53,208 +56,18 @@ pub mod env;
 pub mod file;
 pub mod fpool;
 pub mod fsnotify;
+pub mod lock;
 pub mod log;
 pub mod metric;
 pub mod metric_otel;
 pub mod mlock;
 pub mod mutex;
+pub mod pipeline;
 pub mod proc;
+pub mod report;
 pub mod res;
 pub mod rpool;
-// ========== Session 模块（包含内联定义）==========
-
-/// Session 模块
-///
-/// 提供会话管理功能，包括会话存储、会话管理器等。
-pub mod session {
-    /// Session 存储子模块
-    pub mod storage;
-
-    // 导出 storage 模块的所有公共内容
-    pub use storage::*;
-
-    use axum_sessions::async_session::Session;
-    use rf_errors::Result;
-    use std::collections::HashMap;
-    use std::sync::Arc;
-    use tokio::sync::RwLock;
-
-    /// Session 存储接口
-    ///
-    /// 定义了 Session 存储的基本操作，包括获取、存储和删除会话。
-    ///
-    /// # 方法
-    ///
-    /// - `get`: 根据 ID 获取会话
-    /// - `store`: 存储会话
-    /// - `delete`: 删除会话
-    pub trait SessionStorage: Send + Sync {
-        /// 根据 ID 获取会话
-        ///
-        /// # 参数
-        ///
-        /// - `id`: 会话 ID
-        ///
-        /// # 返回值
-        ///
-        /// 返回 `Result<Option<Session>>`，如果会话存在则返回 Some，否则返回 None
-        fn get(&self, id: &str) -> Result<Option<Session>>;
-
-        /// 存储会话
-        ///
-        /// # 参数
-        ///
-        /// - `session`: 要存储的会话对象
-        ///
-        /// # 返回值
-        ///
-        /// 返回 `Result<()>`，表示操作成功或失败
-        fn store(&self, session: Session) -> Result<()>;
-
-        /// 删除会话
-        ///
-        /// # 参数
-        ///
-        /// - `id`: 要删除的会话 ID
-        ///
-        /// # 返回值
-        ///
-        /// 返回 `Result<()>`，表示操作成功或失败
-        fn delete(&self, id: &str) -> Result<()>;
-    }
-
-    /// 基于内存的 Session 存储
-    ///
-    /// 使用内存中的 HashMap 存储 Session，适用于单机应用和测试环境。
-    ///
-    /// # 字段
-    ///
-    /// - `sessions`: 存储会话的 HashMap
-    pub struct MemorySessionStorage {
-        sessions: Arc<RwLock<HashMap<String, Session>>>,
-    }
-
-    impl MemorySessionStorage {
-        /// 创建新的内存 Session 存储
-        ///
-        /// # 返回值
-        ///
-        /// 返回一个 `MemorySessionStorage` 实例
-        pub fn new() -> Self {
-            Self {
-                sessions: Arc::new(RwLock::new(HashMap::new())),
-            }
-        }
-    }
-
-    impl SessionStorage for MemorySessionStorage {
-        fn get(&self, id: &str) -> Result<Option<Session>> {
-            let sessions = futures::executor::block_on(self.sessions.read());
-            Ok(sessions.get(id).cloned())
-        }
-
-        fn store(&self, session: Session) -> Result<()> {
-            let mut sessions = futures::executor::block_on(self.sessions.write());
-            let session_id = session.id().to_string();
-            sessions.insert(session_id, session);
-            Ok(())
-        }
-
-        fn delete(&self, id: &str) -> Result<()> {
-            let mut sessions = futures::executor::block_on(self.sessions.write());
-            sessions.remove(id);
-            Ok(())
-        }
-    }
-
-    impl Default for MemorySessionStorage {
-        fn default() -> Self {
-            Self::new()
-        }
-    }
-
-    /// Session 管理器
-    ///
-    /// 管理 Session 的生命周期，提供统一的 Session 操作接口。
-    ///
-    /// # 字段
-    ///
-    /// - `storage`: Session 存储后端
-    pub struct SessionManager {
-        storage: Box<dyn SessionStorage>,
-    }
-
-    impl SessionManager {
-        /// 创建使用内存存储的 Session 管理器
-        ///
-        /// # 返回值
-        ///
-        /// 返回一个使用 `MemorySessionStorage` 的 `SessionManager` 实例
-        pub fn new() -> Self {
-            Self {
-                storage: Box::new(MemorySessionStorage::new()),
-            }
-        }
-
-        /// 创建使用自定义存储的 Session 管理器
-        ///
-        /// # 参数
-        ///
-        /// - `storage`: 自定义的 Session 存储实现
-        ///
-        /// # 返回值
-        ///
-        /// 返回一个使用指定存储的 `SessionManager` 实例
-        pub fn with_storage(storage: Box<dyn SessionStorage>) -> Self {
-            Self { storage }
-        }
-
-        /// 根据 ID 获取会话
-        ///
-        /// # 参数
-        ///
-        /// - `id`: 会话 ID
-        ///
-        /// # 返回值
-        ///
-        /// 返回 `Result<Option<Session>>`，如果会话存在则返回 Some，否则返回 None
-        pub fn get(&self, id: &str) -> Result<Option<Session>> {
-            self.storage.get(id)
-        }
-
-        /// 存储会话
-        ///
-        /// # 参数
-        ///
-        /// - `session`: 要存储的会话对象
-        ///
-        /// # 返回值
-        ///
-        /// 返回 `Result<()>`，表示操作成功或失败
-        pub fn store(&self, session: Session) -> Result<()> {
-            self.storage.store(session)
-        }
-
-        /// 删除会话
-        ///
-        /// # 参数
-        ///
-        /// - `id`: 要删除的会话 ID
-        ///
-        /// # 返回值
-        ///
-        /// 返回 `Result<()>`，表示操作成功或失败
-        pub fn delete(&self, id: &str) -> Result<()> {
-            self.storage.delete(id)
-        }
-    }
-
-    impl Default for SessionManager {
-        fn default() -> Self {
-            Self::new()
-        }
-    }
-}
+pub mod session;
 pub mod spath;
 pub mod structs;
 pub mod time;
@@ -275,10 +88,12 @@ pub use env::*;
 pub use file::*;
 pub use fpool::*;
 pub use fsnotify::*;
+pub use lock::*;
 pub use log::*;
 pub use metric::*;
 pub use mlock::*;
 pub use mutex::*;
+pub use pipeline::*;
 pub use proc::*;
 pub use res::*;
 pub use rpool::*;
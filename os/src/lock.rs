@@ -0,0 +1,118 @@
+//! # lock
+//!
+//! lock 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Distributed lock abstraction
+//!
+//! A backend stores one owner per key with an expiry. Owners are opaque
+//! strings chosen by the caller; a backend must only let the current owner
+//! renew or release a key, so a holder whose lock expired cannot delete a
+//! lock taken over by someone else.
+//!
+//! `rf-contrib-coordination` implements `LockBackend` over etcd, Consul and
+//! Redis and builds its mutex and leader election on it; the cron scheduler
+//! takes its per-tick locks from one (see `Cron::with_leader_election`).
+
+use async_trait::async_trait;
+use rf_errors::Result;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Storage for distributed locks
+#[async_trait]
+pub trait LockBackend: Send + Sync {
+    /// Backend name
+    fn name(&self) -> &str;
+
+    /// Take `key` for `owner` if it is free
+    ///
+    /// Returns `true` if `owner` holds the key afterwards, including when it
+    /// already held it.
+    async fn try_acquire(&self, key: &str, owner: &str, ttl: Duration) -> Result<bool>;
+
+    /// Extend the hold of `owner` on `key`
+    ///
+    /// Returns `false` if `owner` no longer holds the key.
+    async fn renew(&self, key: &str, owner: &str, ttl: Duration) -> Result<bool>;
+
+    /// Release `key` if `owner` holds it
+    async fn release(&self, key: &str, owner: &str) -> Result<bool>;
+
+    /// Current owner of `key`
+    async fn holder(&self, key: &str) -> Result<Option<String>>;
+}
+
+/// In-process backend
+///
+/// Only coordinates tasks within one process; useful for tests and
+/// single-instance deployments.
+#[derive(Default)]
+pub struct MemoryBackend {
+    locks: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl MemoryBackend {
+    /// Create an empty backend
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop the lock on `key` as if its TTL had run out
+    pub fn expire(&self, key: &str) {
+        self.locks.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
+    }
+
+    fn with_live<T>(&self, key: &str, f: impl FnOnce(&mut HashMap<String, (String, Instant)>) -> T) -> T {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        if locks.get(key).is_some_and(|(_, expires)| *expires <= Instant::now()) {
+            locks.remove(key);
+        }
+        f(&mut locks)
+    }
+}
+
+#[async_trait]
+impl LockBackend for MemoryBackend {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    async fn try_acquire(&self, key: &str, owner: &str, ttl: Duration) -> Result<bool> {
+        Ok(self.with_live(key, |locks| match locks.get(key) {
+            Some((holder, _)) if holder != owner => false,
+            _ => {
+                locks.insert(key.to_string(), (owner.to_string(), Instant::now() + ttl));
+                true
+            }
+        }))
+    }
+
+    async fn renew(&self, key: &str, owner: &str, ttl: Duration) -> Result<bool> {
+        Ok(self.with_live(key, |locks| match locks.get_mut(key) {
+            Some((holder, expires)) if holder == owner => {
+                *expires = Instant::now() + ttl;
+                true
+            }
+            _ => false,
+        }))
+    }
+
+    async fn release(&self, key: &str, owner: &str) -> Result<bool> {
+        Ok(self.with_live(key, |locks| {
+            if locks.get(key).is_some_and(|(holder, _)| holder == owner) {
+                locks.remove(key);
+                true
+            } else {
+                false
+            }
+        }))
+    }
+
+    async fn holder(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.with_live(key, |locks| locks.get(key).map(|(holder, _)| holder.clone())))
+    }
+}
@@ -6,26 +6,110 @@
 //! @date 2026-01-06
 
 //! Logging system
+//!
+//! Besides the plain helpers, `LogSampler` guards noisy call sites: identical
+//! messages are sampled (the first few, then 1 of N) with a count of what was
+//! dropped, and each key can be rate limited. Samplers are kept per module, so
+//! an error storm from one dependency (e.g. the database being down) can be
+//! throttled without touching the rest:
+//!
+//! ```rust,ignore
+//! use rf_os::log::{self, LogSampler};
+//!
+//! log::set_module_sampler("database", LogSampler::new().every(1000).rate_limit(5, Duration::from_secs(1)));
+//!
+//! // Logged once, then every 1000th occurrence as
+//! // "connection refused (suppressed 999 similar messages)"
+//! log::module_sampler("database").error("connection refused");
+//! ```
+//!
+//! For a single hot call site, `sampled` and `rate_limited` need no setup:
+//!
+//! ```rust,ignore
+//! log::sampled(Level::WARN, 100).log("request without tenant header");
+//! log::rate_limited("payment-retry", 5).warn(&format!("retrying order {}", id));
+//! ```
+//!
+//! The level set by `init` can be changed at runtime with `set_level`, for
+//! example from a `Config::on_change` callback when the config file changes.
+//!
+//! `init_from_config` reads the `[logger]` section and installs its sinks:
+//! stdout, stderr, rotating files and syslog, each in text or JSON format with
+//! its own level and logger selection (see [`sink`]). Named loggers attach
+//! key-value fields that both formats write as structured data:
+//!
+//! ```rust,ignore
+//! log::init_from_config(&config)?;
+//!
+//! let payments = log::logger("payments").field("region", "eu");
+//! payments.with("order_id", "A-1").with("amount", 42).info("order paid");
+//! ```
+
+pub mod format;
+pub mod rotate;
+pub mod sink;
+pub mod writer;
+
+pub use format::{JsonFormat, LogFormat, TextFormat};
+pub use rotate::{parse_size, RollingFile, Rotation};
+pub use sink::{init_with_config, set_sink_level, LoggerConfig, SinkConfig, SinkKind, Syslog};
+pub use writer::{AsyncWriter, Overflow};
 
+use parking_lot::{Mutex, RwLock};
 use tracing::{debug, error, info, trace, warn, Level};
-use tracing_subscriber::EnvFilter;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::time::{Duration, Instant};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Handle for changing the filter installed by `init`
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
 
 /// Initialize the logging system
+///
+/// Writes text lines to stdout at `RUST_LOG`, or info when unset.
 pub fn init() {
-    let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info"));
-
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .init();
+    let _ = init_with_config(&LoggerConfig::default());
 }
 
 /// Initialize with custom level
 pub fn init_with_level(level: Level) {
-    let filter = EnvFilter::new(level.as_str());
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .init();
+    let config = LoggerConfig { level: level.as_str().to_string(), ..LoggerConfig::default() };
+    let _ = init_with_config(&config);
+}
+
+/// Initialize from the `[logger]` section of `config`
+///
+/// Without a `[logger]` section this is `init`. Fails when a sink cannot be
+/// opened or logging is already initialized.
+pub fn init_from_config(config: &crate::cfg::Config) -> rf_errors::Result<()> {
+    init_with_config(&LoggerConfig::from_config(config)?)
+}
+
+/// Flush before shutdown: write pending sampler summaries, then drain every `AsyncWriter`
+///
+/// Returns the number of async writers flushed. Blocks until their queued
+/// lines are written.
+pub fn flush() -> usize {
+    flush_samplers();
+    writer::flush_all()
+}
+
+/// Change the log level at runtime
+///
+/// Takes a level (`debug`) or filter directives (`info,rf_database=debug`).
+/// Fails for invalid directives, or when logging was not set up with `init`
+/// or `init_with_level`.
+pub fn set_level(directives: &str) -> rf_errors::Result<()> {
+    let filter = EnvFilter::try_new(directives.trim())
+        .map_err(|e| rf_errors::RfError::Config(format!("Invalid log level '{}': {}", directives, e)))?;
+    let handle = FILTER
+        .get()
+        .ok_or_else(|| rf_errors::RfError::Internal("Logging is not initialized with rf_os::log::init".to_string()))?;
+    handle
+        .reload(filter)
+        .map_err(|e| rf_errors::RfError::Internal(format!("Failed to set log level: {}", e)))
 }
 
 /// Log at trace level
@@ -52,3 +136,524 @@ pub fn warn(msg: &str) {
 pub fn error(msg: &str) {
     error!("{}", msg);
 }
+
+/// A named logger with key-value fields
+///
+/// Events carry the logger name, which sinks select on with `loggers`, and
+/// the fields, which text lines write as `key=value` and JSON lines as
+/// members of the object. Cloning is cheap enough to derive a logger per
+/// request.
+#[derive(Debug, Clone, Default)]
+pub struct Logger {
+    name: String,
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A logger named `name`, such as `payments` or `audit`
+pub fn logger(name: &str) -> Logger {
+    Logger { name: name.to_string(), fields: serde_json::Map::new() }
+}
+
+impl Logger {
+    /// Add a field to every event of this logger
+    pub fn field(mut self, key: &str, value: impl serde::Serialize) -> Self {
+        self.fields.insert(key.to_string(), serde_json::to_value(value).unwrap_or(serde_json::Value::Null));
+        self
+    }
+
+    /// A copy with one more field, for a single event or a narrower scope
+    pub fn with(&self, key: &str, value: impl serde::Serialize) -> Self {
+        self.clone().field(key, value)
+    }
+
+    /// Logger name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Write `msg` at `level` with the fields
+    pub fn log(&self, level: Level, msg: &str) {
+        let fields = serde_json::Value::Object(self.fields.clone()).to_string();
+        macro_rules! emit {
+            ($level:expr) => {
+                tracing::event!($level, logger = %self.name, rf_fields = %fields, "{}", msg)
+            };
+        }
+        match level {
+            Level::TRACE => emit!(Level::TRACE),
+            Level::DEBUG => emit!(Level::DEBUG),
+            Level::INFO => emit!(Level::INFO),
+            Level::WARN => emit!(Level::WARN),
+            _ => emit!(Level::ERROR),
+        }
+    }
+
+    /// Log at trace level
+    pub fn trace(&self, msg: &str) {
+        self.log(Level::TRACE, msg);
+    }
+
+    /// Log at debug level
+    pub fn debug(&self, msg: &str) {
+        self.log(Level::DEBUG, msg);
+    }
+
+    /// Log at info level
+    pub fn info(&self, msg: &str) {
+        self.log(Level::INFO, msg);
+    }
+
+    /// Log at warn level
+    pub fn warn(&self, msg: &str) {
+        self.log(Level::WARN, msg);
+    }
+
+    /// Log at error level
+    pub fn error(&self, msg: &str) {
+        self.log(Level::ERROR, msg);
+    }
+}
+
+/// One HTTP request, written by `access`
+#[derive(Debug, Clone, Default)]
+pub struct AccessLog {
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub latency: Duration,
+    /// Response body size
+    pub bytes: u64,
+    pub request_id: Option<String>,
+    pub trace_id: Option<String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// Write an access log line
+///
+/// Logged at info level with target `access` and one field per value, so a
+/// JSON subscriber writes a structured record and the access log can be
+/// filtered with `RUST_LOG=access=info`. 5xx responses are logged at warn level.
+pub fn access(entry: &AccessLog) {
+    let latency_ms = entry.latency.as_secs_f64() * 1000.0;
+    macro_rules! emit {
+        ($level:expr) => {
+            tracing::event!(
+                target: "access",
+                $level,
+                method = %entry.method,
+                path = %entry.path,
+                status = entry.status,
+                latency_ms,
+                bytes = entry.bytes,
+                request_id = entry.request_id.as_deref(),
+                trace_id = entry.trace_id.as_deref(),
+                client_ip = entry.client_ip.as_deref(),
+                user_agent = entry.user_agent.as_deref(),
+                "{} {} {} {:.1}ms {}B",
+                entry.method,
+                entry.path,
+                entry.status,
+                latency_ms,
+                entry.bytes
+            )
+        };
+    }
+    if entry.status >= 500 {
+        emit!(Level::WARN);
+    } else {
+        emit!(Level::INFO);
+    }
+}
+
+/// Keys tracked per sampler before idle ones are evicted
+const MAX_SAMPLER_KEYS: usize = 10_000;
+
+static MODULE_SAMPLERS: LazyLock<RwLock<HashMap<String, Arc<LogSampler>>>> = LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Outcome of `LogSampler::check` for a message that should be written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sampled {
+    /// Occurrences of the key in the current window, including this one
+    pub count: u64,
+    /// Messages dropped since the key was last written
+    pub suppressed: u64,
+}
+
+#[derive(Debug)]
+struct SampleState {
+    window_start: Instant,
+    count: u64,
+    suppressed: u64,
+    tokens: f64,
+    refilled: Instant,
+    level: Level,
+    message: String,
+}
+
+/// Samples and rate limits repeated log messages
+///
+/// Within each window the first `first` occurrences of a key are written,
+/// then one of every `every`. With `rate_limit`, a key is additionally held to
+/// `burst` messages per `per`. A written message carries the number of
+/// occurrences dropped before it; `flush` reports counts still pending.
+#[derive(Debug)]
+pub struct LogSampler {
+    module: String,
+    first: u64,
+    every: u64,
+    window: Duration,
+    rate: Option<(u32, Duration)>,
+    state: Mutex<HashMap<String, SampleState>>,
+}
+
+impl Default for LogSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogSampler {
+    /// Sampler writing the first occurrence, then 1 of 100, per 60 second window
+    pub fn new() -> Self {
+        Self {
+            module: String::new(),
+            first: 1,
+            every: 100,
+            window: Duration::from_secs(60),
+            rate: None,
+            state: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Occurrences written in full at the start of each window
+    pub fn first(mut self, first: u64) -> Self {
+        self.first = first;
+        self
+    }
+
+    /// After `first`, write one of every `every` occurrences (1 writes all)
+    pub fn every(mut self, every: u64) -> Self {
+        self.every = every.max(1);
+        self
+    }
+
+    /// Period after which a key's counter starts over
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Allow at most `burst` messages per key in each `per`
+    pub fn rate_limit(mut self, burst: u32, per: Duration) -> Self {
+        self.rate = Some((burst.max(1), per));
+        self
+    }
+
+    /// Module reported in the `module` field of written messages
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    /// Record one occurrence of `key`, returns whether to write it
+    pub fn check(&self, key: &str) -> Option<Sampled> {
+        self.check_at(key, Level::INFO, key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, level: Level, message: &str, now: Instant) -> Option<Sampled> {
+        let mut state = self.state.lock();
+        if state.len() >= MAX_SAMPLER_KEYS && !state.contains_key(key) {
+            let window = self.window;
+            state.retain(|_, entry| entry.suppressed > 0 || now.duration_since(entry.window_start) < window);
+        }
+        let burst = self.rate.map_or(0.0, |(burst, _)| f64::from(burst));
+        let entry = state.entry(key.to_string()).or_insert_with(|| SampleState {
+            window_start: now,
+            count: 0,
+            suppressed: 0,
+            tokens: burst,
+            refilled: now,
+            level,
+            message: String::new(),
+        });
+        if now.duration_since(entry.window_start) >= self.window {
+            entry.window_start = now;
+            entry.count = 0;
+        }
+        entry.count += 1;
+        entry.level = level;
+        entry.message.clear();
+        entry.message.push_str(message);
+
+        let mut keep = entry.count <= self.first || (entry.count - self.first).is_multiple_of(self.every);
+        if let Some((_, per)) = self.rate {
+            let elapsed = now.duration_since(entry.refilled).as_secs_f64();
+            let per = per.as_secs_f64().max(f64::EPSILON);
+            entry.tokens = (entry.tokens + elapsed * burst / per).min(burst);
+            entry.refilled = now;
+            if keep {
+                keep = entry.tokens >= 1.0;
+                if keep {
+                    entry.tokens -= 1.0;
+                }
+            }
+        }
+        if keep {
+            let suppressed = std::mem::take(&mut entry.suppressed);
+            Some(Sampled { count: entry.count, suppressed })
+        } else {
+            entry.suppressed += 1;
+            None
+        }
+    }
+
+    /// Write `msg` at `level` if sampled, keyed by the message itself
+    pub fn log(&self, level: Level, msg: &str) {
+        self.log_keyed(level, msg, msg);
+    }
+
+    /// Write `msg` at `level` if sampled, counting occurrences per `key`
+    ///
+    /// Use a key when messages embed varying details (ids, addresses) but
+    /// should be throttled together.
+    pub fn log_keyed(&self, level: Level, key: &str, msg: &str) {
+        if let Some(sampled) = self.check_at(key, level, msg, Instant::now()) {
+            emit(level, &self.module, msg, sampled.suppressed);
+        }
+    }
+
+    /// Write a summary for every key with dropped messages
+    ///
+    /// Call this periodically or before shutdown so the tail of a storm is
+    /// not lost. Returns the number of summaries written.
+    pub fn flush(&self) -> usize {
+        let pending: Vec<(Level, String, u64)> = {
+            let mut state = self.state.lock();
+            let now = Instant::now();
+            let window = self.window;
+            let pending = state
+                .values_mut()
+                .filter(|entry| entry.suppressed > 0)
+                .map(|entry| (entry.level, entry.message.clone(), std::mem::take(&mut entry.suppressed)))
+                .collect();
+            state.retain(|_, entry| now.duration_since(entry.window_start) < window);
+            pending
+        };
+        for (level, message, suppressed) in &pending {
+            emit(*level, &self.module, message, *suppressed);
+        }
+        pending.len()
+    }
+
+    /// Sampled message at trace level
+    pub fn trace(&self, msg: &str) {
+        self.log(Level::TRACE, msg);
+    }
+
+    /// Sampled message at debug level
+    pub fn debug(&self, msg: &str) {
+        self.log(Level::DEBUG, msg);
+    }
+
+    /// Sampled message at info level
+    pub fn info(&self, msg: &str) {
+        self.log(Level::INFO, msg);
+    }
+
+    /// Sampled message at warn level
+    pub fn warn(&self, msg: &str) {
+        self.log(Level::WARN, msg);
+    }
+
+    /// Sampled message at error level
+    pub fn error(&self, msg: &str) {
+        self.log(Level::ERROR, msg);
+    }
+}
+
+fn emit(level: Level, module: &str, msg: &str, suppressed: u64) {
+    macro_rules! emit {
+        ($level:expr) => {
+            if suppressed > 0 {
+                tracing::event!($level, module, suppressed, "{} (suppressed {} similar messages)", msg, suppressed)
+            } else {
+                tracing::event!($level, module, "{}", msg)
+            }
+        };
+    }
+    match level {
+        Level::TRACE => emit!(Level::TRACE),
+        Level::DEBUG => emit!(Level::DEBUG),
+        Level::INFO => emit!(Level::INFO),
+        Level::WARN => emit!(Level::WARN),
+        _ => emit!(Level::ERROR),
+    }
+}
+
+/// Sampler for `module`, created with the defaults on first use
+pub fn module_sampler(module: &str) -> Arc<LogSampler> {
+    if let Some(sampler) = MODULE_SAMPLERS.read().get(module) {
+        return sampler.clone();
+    }
+    MODULE_SAMPLERS
+        .write()
+        .entry(module.to_string())
+        .or_insert_with(|| {
+            Arc::new(LogSampler {
+                module: module.to_string(),
+                ..LogSampler::new()
+            })
+        })
+        .clone()
+}
+
+/// Configure the sampler for `module`, replacing its counters
+pub fn set_module_sampler(module: &str, mut sampler: LogSampler) -> Arc<LogSampler> {
+    sampler.module = module.to_string();
+    let sampler = Arc::new(sampler);
+    MODULE_SAMPLERS.write().insert(module.to_string(), sampler.clone());
+    sampler
+}
+
+/// Flush pending suppression counts of all module samplers and throttles
+pub fn flush_samplers() -> usize {
+    let samplers: Vec<Arc<LogSampler>> = MODULE_SAMPLERS.read().values().cloned().collect();
+    let throttles: Vec<Arc<Throttle>> = THROTTLES.read().values().cloned().collect();
+    samplers.iter().map(|sampler| sampler.flush()).sum::<usize>()
+        + throttles.iter().map(|throttle| throttle.sampler.flush()).sum::<usize>()
+}
+
+static THROTTLES: LazyLock<RwLock<HashMap<String, Arc<Throttle>>>> = LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Written and dropped message counts of a throttle
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ThrottleStats {
+    /// Call site (`file:line:column`) for `sampled`, the key for `rate_limited`
+    pub key: String,
+    pub written: u64,
+    pub suppressed: u64,
+}
+
+/// A sampled or rate-limited log call site, returned by `sampled` and `rate_limited`
+///
+/// Each message written or dropped also increments the
+/// `log_written_total{key=..}` / `log_suppressed_total{key=..}` counters of
+/// `metric`.
+#[derive(Debug)]
+pub struct Throttle {
+    level: Level,
+    sampler: LogSampler,
+    written: AtomicU64,
+    suppressed: AtomicU64,
+}
+
+impl Throttle {
+    fn get_or_create(key: String, level: Level, sampler: impl FnOnce() -> LogSampler) -> Arc<Throttle> {
+        if let Some(throttle) = THROTTLES.read().get(&key) {
+            return throttle.clone();
+        }
+        THROTTLES
+            .write()
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(Throttle {
+                    level,
+                    sampler: LogSampler { module: key, ..sampler() },
+                    written: AtomicU64::new(0),
+                    suppressed: AtomicU64::new(0),
+                })
+            })
+            .clone()
+    }
+
+    /// Key the messages are counted under
+    pub fn key(&self) -> &str {
+        &self.sampler.module
+    }
+
+    /// Write `msg` at the throttle's level if it passes
+    pub fn log(&self, msg: &str) {
+        self.log_at(self.level, msg);
+    }
+
+    /// Write `msg` at `level` if it passes
+    pub fn log_at(&self, level: Level, msg: &str) {
+        let key = self.key();
+        let (counter, outcome) = match self.sampler.check_at(key, level, msg, Instant::now()) {
+            Some(sampled) => {
+                emit(level, key, msg, sampled.suppressed);
+                (&self.written, "log_written_total")
+            }
+            None => (&self.suppressed, "log_suppressed_total"),
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        crate::metric::counter_inc_with_key(crate::metric::MetricLabels::new().with_label("key", key).to_key(outcome), 1);
+    }
+
+    /// Log at trace level
+    pub fn trace(&self, msg: &str) {
+        self.log_at(Level::TRACE, msg);
+    }
+
+    /// Log at debug level
+    pub fn debug(&self, msg: &str) {
+        self.log_at(Level::DEBUG, msg);
+    }
+
+    /// Log at info level
+    pub fn info(&self, msg: &str) {
+        self.log_at(Level::INFO, msg);
+    }
+
+    /// Log at warn level
+    pub fn warn(&self, msg: &str) {
+        self.log_at(Level::WARN, msg);
+    }
+
+    /// Log at error level
+    pub fn error(&self, msg: &str) {
+        self.log_at(Level::ERROR, msg);
+    }
+
+    /// Messages written and dropped so far
+    pub fn stats(&self) -> ThrottleStats {
+        ThrottleStats {
+            key: self.key().to_string(),
+            written: self.written.load(Ordering::Relaxed),
+            suppressed: self.suppressed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Write the first message from the calling line, then one of every `every_n`
+///
+/// Counting is per call site, so a per-request warning can stay in place
+/// without flooding the log. Counts start over every 60 seconds.
+///
+/// ```rust,ignore
+/// log::sampled(Level::WARN, 100).log(&format!("slow upstream {}", host));
+/// ```
+#[track_caller]
+pub fn sampled(level: Level, every_n: u64) -> Arc<Throttle> {
+    let caller = std::panic::Location::caller();
+    let key = format!("{}:{}:{}", caller.file(), caller.line(), caller.column());
+    Throttle::get_or_create(key, level, || LogSampler::new().every(every_n))
+}
+
+/// Write at most `per_second` messages per second for `key`, at warn level by default
+///
+/// ```rust,ignore
+/// log::rate_limited("payment-retry", 5).warn(&format!("retrying order {}", id));
+/// ```
+pub fn rate_limited(key: &str, per_second: u32) -> Arc<Throttle> {
+    Throttle::get_or_create(key.to_string(), Level::WARN, || {
+        LogSampler::new().every(1).rate_limit(per_second, Duration::from_secs(1))
+    })
+}
+
+/// Counts of every `sampled` and `rate_limited` throttle, sorted by key
+pub fn throttle_stats() -> Vec<ThrottleStats> {
+    let mut stats: Vec<ThrottleStats> = THROTTLES.read().values().map(|throttle| throttle.stats()).collect();
+    stats.sort_by(|a, b| a.key.cmp(&b.key));
+    stats
+}
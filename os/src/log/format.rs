@@ -0,0 +1,172 @@
+//! # format
+//!
+//! format 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Log line formats
+//!
+//! Both formats write one line per event with the timestamp (UTC, RFC 3339),
+//! level, logger, message and key-value fields. Fields attached with
+//! `log::logger(..).field(..)` are flattened next to the fields of `tracing`
+//! macros, and the logger name replaces the target when set:
+//!
+//! ```text
+//! 2026-01-06T10:00:00.000000Z  INFO payments: order paid order_id="A-1" amount=42
+//! {"timestamp":"2026-01-06T10:00:00.000000Z","level":"INFO","logger":"payments","message":"order paid","order_id":"A-1","amount":42}
+//! ```
+
+use serde_json::{Map, Value};
+use std::fmt;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Field carrying the JSON object of `Logger` fields
+pub(crate) const FIELDS_FIELD: &str = "rf_fields";
+/// Field carrying the logger name of `Logger`
+pub(crate) const LOGGER_FIELD: &str = "logger";
+
+/// Output format of a sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// `timestamp LEVEL logger: message key=value`
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Message and fields of one event
+#[derive(Default)]
+pub(crate) struct EventFields {
+    pub message: String,
+    pub logger: Option<String>,
+    pub fields: Vec<(String, Value)>,
+}
+
+impl EventFields {
+    pub fn of(event: &Event<'_>) -> Self {
+        let mut fields = Self::default();
+        event.record(&mut fields);
+        fields
+    }
+
+    fn push(&mut self, field: &Field, value: Value) {
+        match field.name() {
+            "message" => {
+                self.message = match value {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                }
+            }
+            LOGGER_FIELD => self.logger = value.as_str().map(str::to_string),
+            FIELDS_FIELD => {
+                if let Some(Value::Object(map)) = value.as_str().and_then(|s| serde_json::from_str(s).ok()) {
+                    self.fields.extend(map);
+                }
+            }
+            name => self.fields.push((name.to_string(), value)),
+        }
+    }
+}
+
+impl Visit for EventFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, Value::from(format!("{:?}", value)));
+    }
+}
+
+fn timestamp() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+}
+
+/// Names of the spans the event is in, outermost first
+fn span_names<S, N>(ctx: &FmtContext<'_, S, N>) -> Vec<String>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    ctx.event_scope()
+        .map(|scope| scope.from_root().map(|span| span.name().to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Human-readable format
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextFormat;
+
+impl<S, N> FormatEvent<S, N> for TextFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let meta = event.metadata();
+        let fields = EventFields::of(event);
+        write!(writer, "{} {:>5} ", timestamp(), meta.level())?;
+        for span in span_names(ctx) {
+            write!(writer, "{}:", span)?;
+        }
+        write!(writer, "{}: {}", fields.logger.as_deref().unwrap_or(meta.target()), fields.message)?;
+        for (key, value) in &fields.fields {
+            write!(writer, " {}={}", key, value)?;
+        }
+        writeln!(writer)
+    }
+}
+
+/// One JSON object per line, fields flattened into the object
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let meta = event.metadata();
+        let fields = EventFields::of(event);
+        let mut object = Map::new();
+        // Fields first: the standard keys win on a name clash
+        object.extend(fields.fields);
+        object.insert("timestamp".to_string(), Value::from(timestamp()));
+        object.insert("level".to_string(), Value::from(meta.level().as_str()));
+        object.insert(
+            "logger".to_string(),
+            Value::from(fields.logger.unwrap_or_else(|| meta.target().to_string())),
+        );
+        object.insert("message".to_string(), Value::from(fields.message));
+        let spans = span_names(ctx);
+        if !spans.is_empty() {
+            object.insert("spans".to_string(), Value::from(spans));
+        }
+        let line = serde_json::to_string(&Value::Object(object)).map_err(|_| fmt::Error)?;
+        writeln!(writer, "{}", line)
+    }
+}
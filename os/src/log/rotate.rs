@@ -0,0 +1,303 @@
+//! # rotate
+//!
+//! rotate 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Rotating log files
+//!
+//! `RollingFile` appends to one file and rotates it when it reaches a size
+//! limit or when the hour or day changes. The rotated file is renamed with
+//! the rotation time, `logs/app.log` becoming `logs/app.20260106-100000.log`,
+//! and the oldest rotated files are removed beyond `max_files` or `max_age`.
+//!
+//! ```rust,ignore
+//! use rf_os::log::{RollingFile, Rotation};
+//!
+//! let file = RollingFile::new("logs/app.log")?
+//!     .max_size(100 * 1024 * 1024)
+//!     .rotation(Rotation::Daily)
+//!     .max_files(14);
+//! ```
+
+use chrono::{DateTime, Local};
+use parking_lot::{Mutex, MutexGuard};
+use rf_errors::{Result, RfError};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Time-based rotation period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    /// Only rotate on size
+    #[default]
+    Never,
+    /// Rotate when the hour changes
+    Hourly,
+    /// Rotate when the day changes (local time)
+    Daily,
+}
+
+impl Rotation {
+    /// Key of the period `time` falls in; rotation happens when it changes
+    fn period(self, time: DateTime<Local>) -> String {
+        match self {
+            Rotation::Never => String::new(),
+            Rotation::Hourly => time.format("%Y%m%d%H").to_string(),
+            Rotation::Daily => time.format("%Y%m%d").to_string(),
+        }
+    }
+}
+
+struct State {
+    file: File,
+    size: u64,
+    /// Period of the data in the file, set on the first write
+    period: Option<String>,
+}
+
+struct Inner {
+    path: PathBuf,
+    max_size: Option<u64>,
+    rotation: Rotation,
+    max_files: Option<usize>,
+    max_age: Option<Duration>,
+    /// Last modification of the file when it was opened
+    modified: DateTime<Local>,
+    state: Mutex<State>,
+}
+
+/// A log file rotated by size and time, with retention
+///
+/// Clones share the file. Implements `MakeWriter`, so it can be passed to a
+/// `tracing_subscriber::fmt` layer directly.
+#[derive(Clone)]
+pub struct RollingFile {
+    inner: Arc<Inner>,
+}
+
+impl RollingFile {
+    /// Append to `path`, creating it and its directory when missing
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(RfError::Io)?;
+        }
+        let file = open(&path)?;
+        let metadata = file.metadata().map_err(RfError::Io)?;
+        let modified = metadata.modified().map(DateTime::<Local>::from).unwrap_or_else(|_| Local::now());
+        Ok(Self {
+            inner: Arc::new(Inner {
+                path,
+                max_size: None,
+                rotation: Rotation::Never,
+                max_files: None,
+                max_age: None,
+                modified,
+                state: Mutex::new(State { file, size: metadata.len(), period: None }),
+            }),
+        })
+    }
+
+    fn configure(mut self, f: impl FnOnce(&mut Inner)) -> Self {
+        let inner = Arc::get_mut(&mut self.inner).expect("RollingFile is configured before it is cloned");
+        f(inner);
+        self
+    }
+
+    /// Rotate once the file reaches `bytes`
+    pub fn max_size(self, bytes: u64) -> Self {
+        self.configure(|inner| inner.max_size = Some(bytes.max(1)))
+    }
+
+    /// Rotate when the hour or day changes
+    pub fn rotation(self, rotation: Rotation) -> Self {
+        self.configure(|inner| inner.rotation = rotation)
+    }
+
+    /// Keep at most `count` rotated files
+    pub fn max_files(self, count: usize) -> Self {
+        self.configure(|inner| inner.max_files = Some(count))
+    }
+
+    /// Remove rotated files older than `age`
+    pub fn max_age(self, age: Duration) -> Self {
+        self.configure(|inner| inner.max_age = Some(age))
+    }
+
+    /// Path of the active file
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Rotate now, returns the path the active file was renamed to
+    pub fn rotate(&self) -> Result<PathBuf> {
+        let mut state = self.inner.state.lock();
+        self.inner.rotate(&mut state)
+    }
+
+    /// Rotated files, oldest first
+    pub fn rotated_files(&self) -> Vec<PathBuf> {
+        self.inner.rotated_files()
+    }
+}
+
+impl Inner {
+    fn rotate_if_due(&self, state: &mut State) {
+        let period = self.rotation.period(Local::now());
+        // An existing file belongs to the period it was last written in,
+        // so a restart on a new day rotates yesterday's file
+        let current = state.period.get_or_insert_with(|| self.rotation.period(self.modified));
+        let due = self.max_size.is_some_and(|max| state.size >= max) || period != *current;
+        if due && state.size > 0 {
+            if let Err(e) = self.rotate(state) {
+                eprintln!("Failed to rotate log file {}: {}", self.path.display(), e);
+            }
+        }
+        state.period = Some(period);
+    }
+
+    fn rotate(&self, state: &mut State) -> Result<PathBuf> {
+        state.file.flush().map_err(RfError::Io)?;
+        // Rotations within one second get increasing counters; retention may
+        // have removed lower ones, so continue after the highest left
+        let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let last = self
+            .rotated_files()
+            .iter()
+            .filter_map(|path| {
+                let name = path.file_name()?.to_string_lossy().into_owned();
+                let rotated = self.stamp(&name)?;
+                (rotated[..15] == stamp).then(|| rotated[15..].trim_start_matches('-').parse::<usize>().unwrap_or(0))
+            })
+            .max();
+        let target = self.rotated_path(&stamp, last.map_or(0, |n| n + 1));
+        fs::rename(&self.path, &target).map_err(RfError::Io)?;
+        state.file = open(&self.path)?;
+        state.size = 0;
+        self.apply_retention();
+        Ok(target)
+    }
+
+    /// `app.log` -> `app.<stamp>.log`, `app.<stamp>-<n>.log` on a clash
+    fn rotated_path(&self, stamp: &str, n: usize) -> PathBuf {
+        let (stem, ext) = self.name_parts();
+        let stamp = if n == 0 { stamp.to_string() } else { format!("{}-{}", stamp, n) };
+        self.path.with_file_name(format!("{}.{}{}", stem, stamp, ext))
+    }
+
+    fn name_parts(&self) -> (String, String) {
+        let stem = self.path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let ext = self.path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+        (stem, ext)
+    }
+
+    /// Stamp of a rotated file name: `YYYYmmdd-HHMMSS`, optionally followed by `-n`
+    fn stamp<'n>(&self, name: &'n str) -> Option<&'n str> {
+        let (stem, ext) = self.name_parts();
+        let stamp = name.strip_prefix(stem.as_str())?.strip_prefix('.')?.strip_suffix(ext.as_str())?;
+        let valid = stamp.len() >= 15
+            && stamp.as_bytes()[8] == b'-'
+            && stamp.as_bytes()[..15].iter().enumerate().all(|(i, b)| i == 8 || b.is_ascii_digit());
+        valid.then_some(stamp)
+    }
+
+    fn rotated_files(&self) -> Vec<PathBuf> {
+        let dir = match self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            Some(dir) => dir.to_path_buf(),
+            None => PathBuf::from("."),
+        };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<((String, u64), PathBuf)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let stamp = self.stamp(&name)?;
+                // Stamps sort chronologically, clash counters after their stamp
+                let counter = stamp[15..].trim_start_matches('-').parse().unwrap_or(0);
+                Some(((stamp[..15].to_string(), counter), entry.path()))
+            })
+            .collect();
+        files.sort();
+        files.into_iter().map(|(_, path)| path).collect()
+    }
+
+    fn apply_retention(&self) {
+        let mut files = self.rotated_files();
+        if let Some(max_age) = self.max_age {
+            let now = SystemTime::now();
+            files.retain(|path| {
+                let expired = fs::metadata(path)
+                    .and_then(|m| m.modified())
+                    .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() > max_age);
+                if expired {
+                    let _ = fs::remove_file(path);
+                }
+                !expired
+            });
+        }
+        if let Some(max_files) = self.max_files {
+            let excess = files.len().saturating_sub(max_files);
+            for path in &files[..excess] {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    OpenOptions::new().create(true).append(true).open(path).map_err(RfError::Io)
+}
+
+/// Writer for one event, holding the file lock
+pub struct RollingWriter<'a> {
+    state: MutexGuard<'a, State>,
+}
+
+impl Write for RollingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.state.file.write(buf)?;
+        self.state.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.state.file.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RollingFile {
+    type Writer = RollingWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        let mut state = self.inner.state.lock();
+        self.inner.rotate_if_due(&mut state);
+        RollingWriter { state }
+    }
+}
+
+/// Parse a size such as `10MB`, `512k` or `1048576` into bytes
+pub fn parse_size(size: &str) -> Result<u64> {
+    let size = size.trim();
+    let split = size.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| RfError::Config(format!("Invalid size '{}'", size)))?;
+    let multiplier = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1u64,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        _ => return Err(RfError::Config(format!("Invalid size unit in '{}'", size))),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
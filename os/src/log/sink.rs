@@ -0,0 +1,413 @@
+//! # sink
+//!
+//! sink 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Log sinks
+//!
+//! A sink is one destination of log lines (stdout, stderr, a rotating file
+//! or syslog) with its own format, level and logger selection. The `[logger]`
+//! section of the configuration lists the sinks:
+//!
+//! ```toml
+//! [logger]
+//! level = "info"
+//!
+//! [[logger.sinks]]
+//! type = "stdout"
+//!
+//! [[logger.sinks]]
+//! name = "file"
+//! type = "file"
+//! format = "json"
+//! path = "logs/app.log"
+//! max_size = "100MB"
+//! rotation = "daily"
+//! max_files = 14
+//!
+//! [[logger.sinks]]
+//! name = "audit"
+//! type = "syslog"
+//! address = "udp://127.0.0.1:514"
+//! loggers = ["audit", "payments"]
+//! level = "warn"
+//! ```
+//!
+//! `async = true` moves the writes of a stdout, stderr or file sink to a
+//! background thread; `buffer` sets the queue length and `overflow` chooses
+//! between `block` (default) and `drop_oldest` when it is full.
+//!
+//! `logger.level` applies to all sinks and can be changed with
+//! `log::set_level`; a sink's `level` narrows it further and can be changed
+//! with `log::set_sink_level`.
+
+use super::format::{EventFields, JsonFormat, LogFormat, TextFormat};
+use super::rotate::{parse_size, RollingFile, Rotation};
+use super::writer::{AsyncWriter, Overflow};
+use crate::cfg::RfDuration;
+use parking_lot::RwLock;
+use rf_errors::{Result, RfError};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::net::UdpSocket;
+use std::sync::{Arc, LazyLock};
+use tracing::{Level, Metadata};
+use tracing_subscriber::filter::FilterExt;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, Filter, Layered, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+/// Subscriber the sinks are layered on: the registry behind the global filter
+type Base = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+
+/// Per-sink level filters, by sink name
+static SINK_FILTERS: LazyLock<RwLock<HashMap<String, reload::Handle<EnvFilter, Base>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Destination of a sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    #[default]
+    Stdout,
+    Stderr,
+    /// A rotating file, see `RollingFile`
+    File,
+    /// RFC 5424 messages to a syslog daemon
+    Syslog,
+}
+
+/// One sink of the `[logger]` section
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct SinkConfig {
+    /// Name for `set_sink_level`, defaults to the type
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: SinkKind,
+    pub format: LogFormat,
+    /// Level or filter directives (`warn`, `info,sqlx=warn`), on top of `logger.level`
+    pub level: Option<String>,
+    /// Only write events of these loggers (a `log::logger` name or a target prefix)
+    pub loggers: Vec<String>,
+    /// File sink: path of the active file
+    pub path: Option<String>,
+    /// File sink: rotate at this size, such as `100MB`
+    pub max_size: Option<String>,
+    /// File sink: rotate hourly or daily
+    pub rotation: Rotation,
+    /// File sink: rotated files to keep
+    pub max_files: Option<usize>,
+    /// File sink: remove rotated files older than this
+    pub max_age: Option<RfDuration>,
+    /// Syslog sink: `unix:///dev/log` (default) or `udp://host:514`
+    pub address: Option<String>,
+    /// Syslog sink: application name, defaults to the executable name
+    pub app_name: Option<String>,
+    /// Stdout, stderr and file sinks: write from a background thread, see `AsyncWriter`
+    #[serde(rename = "async")]
+    pub non_blocking: bool,
+    /// Lines queued for the background thread (default 8192)
+    pub buffer: Option<usize>,
+    /// What a log call does when the queue is full
+    pub overflow: Overflow,
+}
+
+impl SinkConfig {
+    /// A sink of `kind` with the defaults
+    pub fn new(kind: SinkKind) -> Self {
+        Self { kind, ..Self::default() }
+    }
+
+    fn name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            match self.kind {
+                SinkKind::Stdout => "stdout",
+                SinkKind::Stderr => "stderr",
+                SinkKind::File => "file",
+                SinkKind::Syslog => "syslog",
+            }
+            .to_string()
+        })
+    }
+}
+
+/// The `[logger]` section
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct LoggerConfig {
+    /// Level or filter directives for all sinks; `RUST_LOG` wins when set
+    pub level: String,
+    /// Sinks; stdout in text format when empty
+    pub sinks: Vec<SinkConfig>,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        Self { level: "info".to_string(), sinks: Vec::new() }
+    }
+}
+
+impl LoggerConfig {
+    /// Read the `[logger]` section of `config`
+    pub fn from_config(config: &crate::cfg::Config) -> Result<Self> {
+        if config.all()?.keys().any(|key| key.starts_with("logger.")) {
+            config.get_struct("logger")
+        } else {
+            Ok(Self::default())
+        }
+    }
+}
+
+/// Selects events by logger name or target prefix
+struct LoggerFilter {
+    loggers: Vec<String>,
+}
+
+impl LoggerFilter {
+    fn matches(&self, name: &str) -> bool {
+        self.loggers
+            .iter()
+            .any(|logger| name == logger || name.strip_prefix(logger.as_str()).is_some_and(|rest| rest.starts_with("::")))
+    }
+}
+
+impl<S> Filter<S> for LoggerFilter {
+    fn enabled(&self, _meta: &Metadata<'_>, _cx: &Context<'_, S>) -> bool {
+        // Decided per event, the logger name is a field
+        true
+    }
+
+    fn event_enabled(&self, event: &tracing::Event<'_>, _cx: &Context<'_, S>) -> bool {
+        if self.loggers.is_empty() {
+            return true;
+        }
+        match EventFields::of(event).logger {
+            Some(logger) => self.matches(&logger),
+            None => self.matches(event.metadata().target()),
+        }
+    }
+}
+
+/// Build the layer of one sink
+fn sink_layer(sink: &SinkConfig) -> Result<Box<dyn Layer<Base> + Send + Sync>> {
+    let filter = match &sink.level {
+        Some(level) => EnvFilter::try_new(level.trim())
+            .map_err(|e| RfError::Config(format!("Invalid level '{}' of sink {}: {}", level, sink.name(), e)))?,
+        None => EnvFilter::new("trace"),
+    };
+    let (filter, handle) = reload::Layer::new(filter);
+    SINK_FILTERS.write().insert(sink.name(), handle);
+    let filter = filter.and(LoggerFilter { loggers: sink.loggers.clone() });
+
+    macro_rules! layer {
+        ($writer:expr) => {
+            match sink.format {
+                LogFormat::Text => tracing_subscriber::fmt::layer()
+                    .event_format(TextFormat)
+                    .with_writer($writer)
+                    .with_filter(filter)
+                    .boxed(),
+                LogFormat::Json => tracing_subscriber::fmt::layer()
+                    .event_format(JsonFormat)
+                    .with_writer($writer)
+                    .with_filter(filter)
+                    .boxed(),
+            }
+        };
+    }
+
+    // Writers behind the queue of a non-blocking sink
+    macro_rules! writer {
+        ($writer:expr) => {
+            if sink.non_blocking {
+                let writer = AsyncWriter::with_options($writer, sink.buffer.unwrap_or(8192), sink.overflow);
+                layer!(writer)
+            } else {
+                layer!($writer)
+            }
+        };
+    }
+
+    Ok(match sink.kind {
+        SinkKind::Stdout => writer!(io::stdout),
+        SinkKind::Stderr => writer!(io::stderr),
+        SinkKind::File => {
+            let path = sink
+                .path
+                .as_deref()
+                .ok_or_else(|| RfError::Config(format!("File sink {} has no path", sink.name())))?;
+            let mut file = RollingFile::new(path)?.rotation(sink.rotation);
+            if let Some(size) = &sink.max_size {
+                file = file.max_size(parse_size(size)?);
+            }
+            if let Some(count) = sink.max_files {
+                file = file.max_files(count);
+            }
+            if let Some(age) = sink.max_age {
+                file = file.max_age(age.into());
+            }
+            writer!(file)
+        }
+        SinkKind::Syslog if sink.non_blocking => {
+            // Severity comes from each event's level, which the queue does not keep
+            return Err(RfError::Config(format!("Syslog sink {} cannot be async", sink.name())));
+        }
+        SinkKind::Syslog => {
+            let syslog = Syslog::connect(sink.address.as_deref(), sink.app_name.as_deref())?;
+            layer!(syslog)
+        }
+    })
+}
+
+/// Install the global subscriber with the sinks of `config`
+///
+/// Fails when a sink cannot be opened or a subscriber is already installed.
+pub fn init_with_config(config: &LoggerConfig) -> Result<()> {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(config.level.trim()))
+        .map_err(|e| RfError::Config(format!("Invalid log level '{}': {}", config.level, e)))?;
+    let sinks = if config.sinks.is_empty() {
+        vec![SinkConfig::new(SinkKind::Stdout)]
+    } else {
+        config.sinks.clone()
+    };
+    let layers = sinks.iter().map(sink_layer).collect::<Result<Vec<_>>>()?;
+
+    let (filter, handle) = reload::Layer::new(filter);
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(layers)
+        .try_init()
+        .map_err(|e| RfError::Internal(format!("Failed to install logger: {}", e)))?;
+    let _ = super::FILTER.set(handle);
+    Ok(())
+}
+
+/// Change the level of one sink at runtime
+///
+/// `name` is the sink's `name`, or its type when unnamed. The level still
+/// cannot go below `logger.level`.
+pub fn set_sink_level(name: &str, directives: &str) -> Result<()> {
+    let filter = EnvFilter::try_new(directives.trim())
+        .map_err(|e| RfError::Config(format!("Invalid log level '{}': {}", directives, e)))?;
+    let sinks = SINK_FILTERS.read();
+    let handle = sinks.get(name).ok_or_else(|| RfError::NotFound(format!("Log sink {}", name)))?;
+    handle
+        .reload(filter)
+        .map_err(|e| RfError::Internal(format!("Failed to set level of sink {}: {}", name, e)))
+}
+
+/// Syslog transport
+enum Transport {
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixDatagram),
+    Udp(UdpSocket),
+}
+
+/// Writes each event as one RFC 5424 message
+#[derive(Clone)]
+pub struct Syslog {
+    transport: Arc<Transport>,
+    app_name: String,
+    hostname: String,
+}
+
+impl Syslog {
+    /// Connect to `address`: `unix:///dev/log` (default) or `udp://host:port`
+    pub fn connect(address: Option<&str>, app_name: Option<&str>) -> Result<Self> {
+        let address = address.unwrap_or("unix:///dev/log");
+        let transport = if let Some(addr) = address.strip_prefix("udp://") {
+            let socket = UdpSocket::bind("0.0.0.0:0").map_err(RfError::Io)?;
+            socket.connect(addr).map_err(RfError::Io)?;
+            Transport::Udp(socket)
+        } else {
+            #[cfg(unix)]
+            {
+                let path = address.strip_prefix("unix://").unwrap_or(address);
+                let socket = std::os::unix::net::UnixDatagram::unbound().map_err(RfError::Io)?;
+                socket.connect(path).map_err(RfError::Io)?;
+                Transport::Unix(socket)
+            }
+            #[cfg(not(unix))]
+            return Err(RfError::Config(format!("Unsupported syslog address {}", address)));
+        };
+        let app_name = app_name.map(str::to_string).unwrap_or_else(|| {
+            std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.file_stem().map(|s| s.to_string_lossy().into_owned()))
+                .unwrap_or_else(|| "rf".to_string())
+        });
+        let hostname = sysinfo::System::host_name().unwrap_or_else(|| "-".to_string());
+        Ok(Self { transport: Arc::new(transport), app_name, hostname })
+    }
+
+    fn send(&self, message: &[u8]) -> io::Result<usize> {
+        match self.transport.as_ref() {
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.send(message),
+            Transport::Udp(socket) => socket.send(message),
+        }
+    }
+}
+
+/// Buffers one event, sent when dropped
+pub struct SyslogWriter<'a> {
+    syslog: &'a Syslog,
+    severity: u8,
+    buffer: Vec<u8>,
+}
+
+impl Write for SyslogWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for SyslogWriter<'_> {
+    fn drop(&mut self) {
+        let line = String::from_utf8_lossy(&self.buffer);
+        let line = line.trim_end();
+        if line.is_empty() {
+            return;
+        }
+        // Facility user (1): PRI = 1 * 8 + severity
+        let message = format!(
+            "<{}>1 {} {} {} {} - - {}",
+            8 + self.severity,
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            self.syslog.hostname,
+            self.syslog.app_name,
+            std::process::id(),
+            line
+        );
+        let _ = self.syslog.send(message.as_bytes());
+    }
+}
+
+impl<'a> MakeWriter<'a> for Syslog {
+    type Writer = SyslogWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        SyslogWriter { syslog: self, severity: 6, buffer: Vec::new() }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        let severity = match *meta.level() {
+            Level::ERROR => 3,
+            Level::WARN => 4,
+            Level::INFO => 6,
+            _ => 7,
+        };
+        SyslogWriter { syslog: self, severity, buffer: Vec::new() }
+    }
+}
+
@@ -0,0 +1,260 @@
+//! # writer
+//!
+//! writer 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Non-blocking log writer
+//!
+//! `AsyncWriter` hands formatted lines to a background thread through a
+//! bounded queue, so logging from a request handler costs a copy instead of
+//! a disk write. When the queue is full the overflow policy decides between
+//! dropping the oldest queued line and blocking the caller until there is
+//! room. `log::flush` drains every writer; call it before the process exits
+//! (the HTTP server does after a graceful shutdown) so the tail is not lost.
+//!
+//! ```rust,ignore
+//! use rf_os::log::{AsyncWriter, Overflow, RollingFile};
+//!
+//! let file = AsyncWriter::new(RollingFile::new("logs/app.log")?)
+//!     .capacity(8192)
+//!     .overflow(Overflow::DropOldest);
+//! tracing_subscriber::fmt().with_writer(file.clone()).init();
+//! // ...
+//! rf_os::log::flush();
+//! ```
+
+use parking_lot::{Condvar, Mutex};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Weak};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Writers flushed by `flush_all`
+static WRITERS: LazyLock<Mutex<Vec<Weak<Shared>>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// What a write does when the queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overflow {
+    /// Wait for the background thread to make room; no line is lost
+    #[default]
+    Block,
+    /// Discard the oldest queued line; the caller never waits
+    DropOldest,
+}
+
+struct Queue {
+    lines: VecDeque<Vec<u8>>,
+    /// The background thread is writing lines it took from the queue
+    writing: bool,
+    /// A flush was requested for the inner writer
+    flush: bool,
+    closed: bool,
+    capacity: usize,
+    overflow: Overflow,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    /// Signalled when lines are queued or on close
+    ready: Condvar,
+    /// Signalled when the background thread takes lines or goes idle
+    space: Condvar,
+    dropped: AtomicU64,
+}
+
+impl Shared {
+    fn push(&self, line: Vec<u8>) {
+        let mut queue = self.queue.lock();
+        if queue.closed {
+            return;
+        }
+        while queue.lines.len() >= queue.capacity {
+            match queue.overflow {
+                Overflow::DropOldest => {
+                    queue.lines.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                Overflow::Block => self.space.wait(&mut queue),
+            }
+        }
+        queue.lines.push_back(line);
+        self.ready.notify_one();
+    }
+
+    /// Wait until every queued line is written and the inner writer flushed
+    fn flush(&self) {
+        let mut queue = self.queue.lock();
+        if queue.closed {
+            return;
+        }
+        queue.flush = true;
+        self.ready.notify_one();
+        while queue.flush || queue.writing || !queue.lines.is_empty() {
+            self.space.wait(&mut queue);
+        }
+    }
+
+    fn close(&self) {
+        self.flush();
+        self.queue.lock().closed = true;
+        self.ready.notify_one();
+    }
+}
+
+/// A `MakeWriter` writing on a background thread through a bounded queue
+///
+/// Clones share the queue and the thread. The thread stops when the last
+/// clone is dropped, after writing what is queued.
+#[derive(Clone)]
+pub struct AsyncWriter {
+    shared: Arc<Shared>,
+    _stop: Arc<StopGuard>,
+}
+
+struct StopGuard(Arc<Shared>);
+
+impl Drop for StopGuard {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+impl AsyncWriter {
+    /// Write to `inner` from a background thread, queueing up to 8192 lines and blocking when full
+    pub fn new<W>(inner: W) -> Self
+    where
+        W: for<'a> MakeWriter<'a> + Send + 'static,
+    {
+        Self::with_options(inner, 8192, Overflow::Block)
+    }
+
+    /// Write to `inner` with a queue of `capacity` lines and the given overflow policy
+    pub fn with_options<W>(inner: W, capacity: usize, overflow: Overflow) -> Self
+    where
+        W: for<'a> MakeWriter<'a> + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue {
+                lines: VecDeque::new(),
+                writing: false,
+                flush: false,
+                closed: false,
+                capacity: capacity.max(1),
+                overflow,
+            }),
+            ready: Condvar::new(),
+            space: Condvar::new(),
+            dropped: AtomicU64::new(0),
+        });
+        let worker = shared.clone();
+        std::thread::Builder::new()
+            .name("rf-log-writer".to_string())
+            .spawn(move || run(worker, inner))
+            .expect("failed to spawn the log writer thread");
+        let mut writers = WRITERS.lock();
+        writers.retain(|writer| writer.strong_count() > 0);
+        writers.push(Arc::downgrade(&shared));
+        Self { _stop: Arc::new(StopGuard(shared.clone())), shared }
+    }
+
+    /// Queue at most `capacity` lines
+    pub fn capacity(self, capacity: usize) -> Self {
+        self.shared.queue.lock().capacity = capacity.max(1);
+        self
+    }
+
+    /// Set what a write does when the queue is full
+    pub fn overflow(self, overflow: Overflow) -> Self {
+        self.shared.queue.lock().overflow = overflow;
+        self
+    }
+
+    /// Lines discarded by `Overflow::DropOldest`
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Wait until every queued line is written and the inner writer flushed
+    pub fn flush(&self) {
+        self.shared.flush();
+    }
+}
+
+/// Background thread: write queued lines until closed
+fn run<W>(shared: Arc<Shared>, inner: W)
+where
+    W: for<'a> MakeWriter<'a>,
+{
+    loop {
+        let (lines, flush) = {
+            let mut queue = shared.queue.lock();
+            queue.writing = false;
+            shared.space.notify_all();
+            while queue.lines.is_empty() && !queue.flush && !queue.closed {
+                shared.ready.wait(&mut queue);
+            }
+            if queue.lines.is_empty() && !queue.flush && queue.closed {
+                return;
+            }
+            queue.writing = true;
+            let lines: Vec<Vec<u8>> = queue.lines.drain(..).collect();
+            (lines, std::mem::take(&mut queue.flush))
+        };
+        // Callers blocked on a full queue can continue while this batch is written
+        shared.space.notify_all();
+        for line in &lines {
+            if let Err(e) = inner.make_writer().write_all(line) {
+                eprintln!("Failed to write log line: {}", e);
+            }
+        }
+        if flush {
+            let _ = inner.make_writer().flush();
+        }
+    }
+}
+
+/// Buffer for one event, queued when dropped
+pub struct AsyncLine<'a> {
+    shared: &'a Shared,
+    buf: Vec<u8>,
+}
+
+impl Write for AsyncLine<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for AsyncLine<'_> {
+    fn drop(&mut self) {
+        if !self.buf.is_empty() {
+            self.shared.push(std::mem::take(&mut self.buf));
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for AsyncWriter {
+    type Writer = AsyncLine<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        AsyncLine { shared: &self.shared, buf: Vec::new() }
+    }
+}
+
+/// Flush every live `AsyncWriter`, returns how many were flushed
+pub fn flush_all() -> usize {
+    let writers: Vec<Arc<Shared>> = WRITERS.lock().iter().filter_map(Weak::upgrade).collect();
+    for writer in &writers {
+        writer.flush();
+    }
+    writers.len()
+}
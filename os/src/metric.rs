@@ -6,11 +6,30 @@
 //! @date 2026-01-06
 
 //! Metrics collection
+//!
+//! Besides exporting through the `metrics` facade, histogram values are kept
+//! in in-process rollups: p50/p95/p99 over the last 1, 5 and 15 minutes,
+//! queryable with `rollup_stats` / `rollup_snapshot` without a metrics backend.
+//!
+//! The helpers also record into the default `MetricRegistry`, with their
+//! labels and the name made Prometheus-safe (`http.requests` becomes
+//! `http_requests`), so the values show up on the `/metrics` endpoint.
+//! Helper histograms use `DEFAULT_BUCKETS`; register the histogram with the
+//! same labels first to choose other buckets. `collectors` adds process,
+//! tokio runtime and database pool metrics.
+
+pub mod registry;
+pub mod collectors;
+
+pub use registry::*;
+pub use collectors::*;
 
 use metrics::{Key, KeyName};
-use std::sync::LazyLock;
-use std::collections::HashMap;
-use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::{Arc, LazyLock};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use parking_lot::{Mutex, RwLock};
 
 // Cache for dynamic metric names to avoid allocations
 static METRIC_NAME_CACHE: LazyLock<RwLock<HashMap<String, &'static str>>> = LazyLock::new(|| {
@@ -40,57 +59,101 @@ fn get_static_name(name: &str) -> &'static str {
 /// Increment a counter
 pub fn counter_inc(name: &'static str, value: u64) {
     metrics::counter!(name).increment(value);
+    export_counter(name, &[], value);
 }
 
 /// Set a gauge
 pub fn gauge_set(name: &'static str, value: f64) {
     metrics::gauge!(name).set(value);
+    export_gauge(name, &[], value);
 }
 
 /// Record a histogram value
 pub fn histogram_record(name: &'static str, value: f64) {
     metrics::histogram!(name).record(value);
+    rollup(name).record(value);
+    export_histogram(name, &[], value);
 }
 
 /// Increment a counter with dynamic name
 pub fn counter_inc_dynamic(name: String, value: u64) {
     let static_name = get_static_name(&name);
     metrics::counter!(static_name).increment(value);
+    export_counter(&name, &[], value);
 }
 
 /// Increment a counter with dynamic name and labels
-/// Note: labels are not fully supported by metrics macros, this is a simplified version
-pub fn counter_inc_with_labels(name: String, _labels: Vec<(String, String)>, value: u64) {
-    // For now, ignore labels as metrics macros don't support them directly
-    // Full implementation would require using the recorder API with Key
+/// Note: the `metrics` facade gets the name only; labels are kept in the default registry
+pub fn counter_inc_with_labels(name: String, labels: Vec<(String, String)>, value: u64) {
     let static_name = get_static_name(&name);
     metrics::counter!(static_name).increment(value);
+    export_counter(&name, &labels, value);
 }
 
 /// Set a gauge with dynamic name
 pub fn gauge_set_dynamic(name: String, value: f64) {
     let static_name = get_static_name(&name);
     metrics::gauge!(static_name).set(value);
+    export_gauge(&name, &[], value);
 }
 
 /// Set a gauge with dynamic name and labels
-/// Note: labels are not fully supported by metrics macros, this is a simplified version
-pub fn gauge_set_with_labels(name: String, _labels: Vec<(String, String)>, value: f64) {
+/// Note: the `metrics` facade gets the name only; labels are kept in the default registry
+pub fn gauge_set_with_labels(name: String, labels: Vec<(String, String)>, value: f64) {
     let static_name = get_static_name(&name);
     metrics::gauge!(static_name).set(value);
+    export_gauge(&name, &labels, value);
 }
 
 /// Record a histogram with dynamic name
 pub fn histogram_record_dynamic(name: String, value: f64) {
     let static_name = get_static_name(&name);
     metrics::histogram!(static_name).record(value);
+    rollup(static_name).record(value);
+    export_histogram(&name, &[], value);
 }
 
 /// Record a histogram with dynamic name and labels
-/// Note: labels are not fully supported by metrics macros, this is a simplified version
-pub fn histogram_record_with_labels(name: String, _labels: Vec<(String, String)>, value: f64) {
+/// Note: the `metrics` facade gets the name only; labels are kept in the default registry
+pub fn histogram_record_with_labels(name: String, labels: Vec<(String, String)>, value: f64) {
     let static_name = get_static_name(&name);
     metrics::histogram!(static_name).record(value);
+    rollup(static_name).record(value);
+    export_histogram(&name, &labels, value);
+}
+
+/// Label names and values of a helper call, in Prometheus form
+fn export_labels(labels: &[(String, String)]) -> (Vec<String>, Vec<&str>) {
+    let names = labels.iter().map(|(name, _)| prometheus_name(name)).collect();
+    let values = labels.iter().map(|(_, value)| value.as_str()).collect();
+    (names, values)
+}
+
+fn export_counter(name: &str, labels: &[(String, String)], value: u64) {
+    let (names, values) = export_labels(labels);
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    match default_registry().counter_vec(&prometheus_name(name), name, &names) {
+        Ok(counter) => counter.with(&values).inc_by(value as f64),
+        Err(e) => tracing::debug!("Metric not exported: {}", e),
+    }
+}
+
+fn export_gauge(name: &str, labels: &[(String, String)], value: f64) {
+    let (names, values) = export_labels(labels);
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    match default_registry().gauge_vec(&prometheus_name(name), name, &names) {
+        Ok(gauge) => gauge.with(&values).set(value),
+        Err(e) => tracing::debug!("Metric not exported: {}", e),
+    }
+}
+
+fn export_histogram(name: &str, labels: &[(String, String)], value: f64) {
+    let (names, values) = export_labels(labels);
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    match default_registry().histogram_vec(&prometheus_name(name), name, &names, DEFAULT_BUCKETS) {
+        Ok(histogram) => histogram.with(&values).observe(value),
+        Err(e) => tracing::debug!("Metric not exported: {}", e),
+    }
 }
 
 /// Metric label builder
@@ -162,6 +225,7 @@ pub fn histogram_record_with_key(key: metrics::Key, value: f64) {
     let name_str = key.name().to_string();
     let static_name = get_static_name(&name_str);
     metrics::histogram!(static_name).record(value);
+    rollup(static_name).record(value);
 }
 
 /// Validate metric name
@@ -292,3 +356,271 @@ pub struct HistogramStats {
     pub max: f64,
     pub avg: f64,
 }
+
+/// Windows reported by `rollup_snapshot`
+pub const ROLLUP_WINDOWS: [(&str, Duration); 3] = [
+    ("1m", Duration::from_secs(60)),
+    ("5m", Duration::from_secs(5 * 60)),
+    ("15m", Duration::from_secs(15 * 60)),
+];
+
+/// Relative error of rollup quantiles
+const ROLLUP_ACCURACY: f64 = 0.01;
+
+/// Values closer to zero than this are counted as zero
+const ROLLUP_MIN_VALUE: f64 = 1e-9;
+
+/// Rollup statistics over a time window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WindowStats {
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    /// Values per second over the window
+    pub rate: f64,
+}
+
+/// Values recorded during one slot
+#[derive(Debug, Clone)]
+struct Slot {
+    index: u64,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    zeros: u64,
+    /// Log-scale bins of positive values
+    positive: HashMap<i32, u64>,
+    /// Log-scale bins of negated negative values
+    negative: HashMap<i32, u64>,
+}
+
+impl Slot {
+    fn new(index: u64) -> Self {
+        Self {
+            index,
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            zeros: 0,
+            positive: HashMap::new(),
+            negative: HashMap::new(),
+        }
+    }
+}
+
+/// Sliding-window histogram with bounded memory
+///
+/// Values go into fixed time slots (10 seconds by default) kept for the
+/// retention period (15 minutes). Each slot stores log-scale bins, so
+/// quantiles have a 1% relative error whatever the number of values.
+pub struct RollingHistogram {
+    start: Instant,
+    resolution: Duration,
+    retention: Duration,
+    ln_gamma: f64,
+    slots: Mutex<VecDeque<Slot>>,
+}
+
+impl RollingHistogram {
+    /// Create a histogram with 10 second slots kept for 15 minutes
+    pub fn new() -> Self {
+        Self::with_resolution(Duration::from_secs(10), Duration::from_secs(15 * 60))
+    }
+
+    /// Create a histogram with custom slot size and retention
+    pub fn with_resolution(resolution: Duration, retention: Duration) -> Self {
+        let gamma = (1.0 + ROLLUP_ACCURACY) / (1.0 - ROLLUP_ACCURACY);
+        Self {
+            start: Instant::now(),
+            resolution: resolution.max(Duration::from_millis(1)),
+            retention: retention.max(resolution),
+            ln_gamma: gamma.ln(),
+            slots: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Record a value now
+    pub fn record(&self, value: f64) {
+        self.record_at(value, Instant::now());
+    }
+
+    /// Record a value at `now`; NaN is ignored
+    pub fn record_at(&self, value: f64, now: Instant) {
+        if value.is_nan() {
+            return;
+        }
+        let index = self.slot_index(now);
+        let mut slots = self.slots.lock();
+        if slots.back().is_none_or(|slot| slot.index < index) {
+            slots.push_back(Slot::new(index));
+        }
+        let keep = self.slot_count(self.retention);
+        while slots.front().is_some_and(|slot| slot.index + keep <= index) {
+            slots.pop_front();
+        }
+        // Late values land in their own slot when it is still kept
+        let Some(slot) = slots.iter_mut().rev().find(|slot| slot.index <= index) else {
+            return;
+        };
+        slot.count += 1;
+        slot.sum += value;
+        slot.min = slot.min.min(value);
+        slot.max = slot.max.max(value);
+        if value.abs() < ROLLUP_MIN_VALUE {
+            slot.zeros += 1;
+        } else if value > 0.0 {
+            *slot.positive.entry(self.key(value)).or_insert(0) += 1;
+        } else {
+            *slot.negative.entry(self.key(-value)).or_insert(0) += 1;
+        }
+    }
+
+    /// Statistics over the last `window`, `None` when nothing was recorded
+    pub fn stats(&self, window: Duration) -> Option<WindowStats> {
+        self.stats_at(window, Instant::now())
+    }
+
+    /// Statistics over the `window` ending at `now`
+    ///
+    /// The window is rounded up to whole slots and capped at the retention.
+    pub fn stats_at(&self, window: Duration, now: Instant) -> Option<WindowStats> {
+        let window = window.min(self.retention);
+        let current = self.slot_index(now);
+        let first = (current + 1).saturating_sub(self.slot_count(window));
+        let slots = self.slots.lock();
+        let mut merged = Slot::new(current);
+        for slot in slots.iter().filter(|slot| slot.index >= first && slot.index <= current) {
+            merged.count += slot.count;
+            merged.sum += slot.sum;
+            merged.min = merged.min.min(slot.min);
+            merged.max = merged.max.max(slot.max);
+            merged.zeros += slot.zeros;
+            for (key, count) in &slot.positive {
+                *merged.positive.entry(*key).or_insert(0) += count;
+            }
+            for (key, count) in &slot.negative {
+                *merged.negative.entry(*key).or_insert(0) += count;
+            }
+        }
+        drop(slots);
+        if merged.count == 0 {
+            return None;
+        }
+
+        // Bins in ascending value order
+        let mut bins: Vec<(f64, u64)> = Vec::new();
+        let mut negative: Vec<_> = merged.negative.iter().collect();
+        negative.sort_by(|a, b| b.0.cmp(a.0));
+        bins.extend(negative.into_iter().map(|(key, count)| (-self.value(*key), *count)));
+        if merged.zeros > 0 {
+            bins.push((0.0, merged.zeros));
+        }
+        let mut positive: Vec<_> = merged.positive.iter().collect();
+        positive.sort_by_key(|(key, _)| **key);
+        bins.extend(positive.into_iter().map(|(key, count)| (self.value(*key), *count)));
+
+        let quantile = |q: f64| {
+            let rank = (q * (merged.count - 1) as f64).floor() as u64;
+            let mut seen = 0;
+            for (value, count) in &bins {
+                seen += count;
+                if seen > rank {
+                    return value.clamp(merged.min, merged.max);
+                }
+            }
+            merged.max
+        };
+        Some(WindowStats {
+            count: merged.count,
+            sum: merged.sum,
+            min: merged.min,
+            max: merged.max,
+            avg: merged.sum / merged.count as f64,
+            p50: quantile(0.5),
+            p95: quantile(0.95),
+            p99: quantile(0.99),
+            rate: merged.count as f64 / window.as_secs_f64().max(f64::EPSILON),
+        })
+    }
+
+    fn slot_index(&self, now: Instant) -> u64 {
+        (now.saturating_duration_since(self.start).as_nanos() / self.resolution.as_nanos()) as u64
+    }
+
+    fn slot_count(&self, window: Duration) -> u64 {
+        (window.as_nanos().div_ceil(self.resolution.as_nanos()) as u64).max(1)
+    }
+
+    fn key(&self, value: f64) -> i32 {
+        (value.ln() / self.ln_gamma).ceil() as i32
+    }
+
+    /// Representative value of a bin, within the relative error of every value in it
+    fn value(&self, key: i32) -> f64 {
+        let gamma = self.ln_gamma.exp();
+        2.0 * gamma.powi(key) / (gamma + 1.0)
+    }
+}
+
+impl Default for RollingHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static ROLLUPS: LazyLock<RwLock<HashMap<String, Arc<RollingHistogram>>>> = LazyLock::new(|| {
+    RwLock::new(HashMap::new())
+});
+
+/// Rollup for a histogram name, created on first use
+///
+/// `histogram_record` and friends feed it; keep the handle to record on hot
+/// paths without the name lookup.
+pub fn rollup(name: &str) -> Arc<RollingHistogram> {
+    if let Some(rollup) = ROLLUPS.read().get(name) {
+        return rollup.clone();
+    }
+    ROLLUPS.write().entry(name.to_string()).or_default().clone()
+}
+
+/// Statistics of a histogram over the last `window`
+///
+/// ```rust,ignore
+/// // Shed load when the 1 minute p99 latency passes 500ms
+/// let overloaded = rollup_stats("http_latency_ms", Duration::from_secs(60))
+///     .is_some_and(|stats| stats.p99 > 500.0);
+/// ```
+pub fn rollup_stats(name: &str, window: Duration) -> Option<WindowStats> {
+    ROLLUPS.read().get(name)?.stats(window)
+}
+
+/// Names of all rollups
+pub fn rollup_names() -> Vec<String> {
+    let mut names: Vec<String> = ROLLUPS.read().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// 1m/5m/15m statistics of every rollup with recent values
+pub fn rollup_snapshot() -> BTreeMap<String, BTreeMap<&'static str, WindowStats>> {
+    let rollups: Vec<(String, Arc<RollingHistogram>)> =
+        ROLLUPS.read().iter().map(|(name, rollup)| (name.clone(), rollup.clone())).collect();
+    let now = Instant::now();
+    rollups
+        .into_iter()
+        .filter_map(|(name, rollup)| {
+            let windows: BTreeMap<&'static str, WindowStats> = ROLLUP_WINDOWS
+                .iter()
+                .filter_map(|(label, window)| Some((*label, rollup.stats_at(*window, now)?)))
+                .collect();
+            (!windows.is_empty()).then_some((name, windows))
+        })
+        .collect()
+}
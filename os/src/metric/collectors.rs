@@ -0,0 +1,95 @@
+//! # collectors
+//!
+//! collectors 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Standard collectors: process, tokio runtime and database pool metrics
+//!
+//! Each function adds a collector that samples its source on every scrape,
+//! using the metric names of the official Prometheus clients where one
+//! exists. Registering again replaces the previous collector.
+
+use super::registry::MetricRegistry;
+use parking_lot::Mutex;
+use rf_database::db::Database;
+use std::sync::Arc;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// `process_*` metrics of the current process
+///
+/// CPU time, resident and virtual memory and start time everywhere sysinfo
+/// supports; open and maximum file descriptors where the platform reports them.
+pub fn register_process_metrics(registry: &MetricRegistry) {
+    let Ok(pid) = sysinfo::get_current_pid() else {
+        tracing::warn!("Cannot read the current PID, process metrics are off");
+        return;
+    };
+    let system = Mutex::new(System::new());
+    registry.collector("process", move |registry| collect_process(registry, &system, pid));
+}
+
+fn collect_process(registry: &MetricRegistry, system: &Mutex<System>, pid: Pid) {
+    let mut system = system.lock();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory(),
+    );
+    let Some(process) = system.process(pid) else {
+        return;
+    };
+    let gauges = [
+        ("process_resident_memory_bytes", "Resident memory size in bytes", Some(process.memory() as f64)),
+        ("process_virtual_memory_bytes", "Virtual memory size in bytes", Some(process.virtual_memory() as f64)),
+        ("process_start_time_seconds", "Start time of the process since unix epoch in seconds", Some(process.start_time() as f64)),
+        ("process_open_fds", "Number of open file descriptors", process.open_files().map(|n| n as f64)),
+        ("process_max_fds", "Maximum number of open file descriptors", process.open_files_limit().map(|n| n as f64)),
+    ];
+    for (name, help, value) in gauges {
+        if let (Some(value), Ok(gauge)) = (value, registry.gauge(name, help)) {
+            gauge.set(value);
+        }
+    }
+    if let Ok(cpu) = registry.counter("process_cpu_seconds_total", "Total user and system CPU time spent in seconds") {
+        cpu.set_total(process.accumulated_cpu_time() as f64 / 1000.0);
+    }
+}
+
+/// `tokio_runtime_*` metrics of the runtime behind `handle`
+///
+/// Worker count, alive tasks and the depth of the global queue.
+pub fn register_runtime_metrics(registry: &MetricRegistry, handle: tokio::runtime::Handle) {
+    registry.collector("tokio_runtime", move |registry| {
+        let metrics = handle.metrics();
+        let gauges = [
+            ("tokio_runtime_workers", "Number of worker threads", metrics.num_workers()),
+            ("tokio_runtime_alive_tasks", "Number of alive tasks", metrics.num_alive_tasks()),
+            ("tokio_runtime_global_queue_depth", "Tasks waiting in the global queue", metrics.global_queue_depth()),
+        ];
+        for (name, help, value) in gauges {
+            if let Ok(gauge) = registry.gauge(name, help) {
+                gauge.set(value as f64);
+            }
+        }
+    });
+}
+
+/// `db_pool_*` metrics of `database`'s pool, labeled `pool="<pool>"`
+///
+/// `db_pool_connections` is split by `state` (`idle` / `active`);
+/// `db_pool_max_connections` is the configured limit.
+pub fn register_pool_metrics(registry: &MetricRegistry, pool: &str, database: Arc<Database>) {
+    let name = pool.to_string();
+    registry.collector(&format!("db_pool:{}", pool), move |registry| {
+        let stats = database.pool_stats();
+        if let Ok(connections) = registry.gauge_vec("db_pool_connections", "Connections in the pool", &["pool", "state"]) {
+            connections.with(&[&name, "idle"]).set(stats.idle_connections as f64);
+            connections.with(&[&name, "active"]).set(stats.active_connections as f64);
+        }
+        if let Ok(max) = registry.gauge_vec("db_pool_max_connections", "Maximum connections of the pool", &["pool"]) {
+            max.with(&[&name]).set(stats.max_connections as f64);
+        }
+    });
+}
@@ -0,0 +1,618 @@
+//! # registry
+//!
+//! registry 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Labeled metrics and Prometheus exposition
+//!
+//! A `MetricRegistry` owns metric families: counters, gauges and histograms,
+//! each with a fixed set of label names. `encode` renders every family in the
+//! Prometheus text format (version 0.0.4), after running the registered
+//! collectors, which sample values that live elsewhere (process stats, pool
+//! sizes) at scrape time.
+//!
+//! `gather` returns the same values as plain data for other exporters, and
+//! histogram sinks receive every histogram observation, for backends that
+//! aggregate themselves (see `metric_otel`).
+//!
+//! Registration is idempotent: registering a name again with the same type and
+//! label names returns the existing family, so libraries can register the
+//! metrics they record without coordinating. A different type or label set is
+//! an error.
+//!
+//! ```rust,ignore
+//! let registry = default_registry();
+//! let requests = registry.counter_vec("jobs_total", "Jobs run", &["queue", "outcome"])?;
+//! requests.with(&["emails", "ok"]).inc();
+//!
+//! let latency = registry.histogram("job_duration_seconds", "Job duration", DEFAULT_BUCKETS)?;
+//! latency.observe_duration(started.elapsed());
+//!
+//! let body = registry.encode();
+//! ```
+
+use parking_lot::RwLock;
+use rf_errors::{Result, RfError};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+/// Histogram buckets in seconds, the Prometheus client default
+pub const DEFAULT_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Content type of `MetricRegistry::encode` output
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+fn add_f64(cell: &AtomicU64, value: f64) {
+    let _ = cell.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+        Some((f64::from_bits(bits) + value).to_bits())
+    });
+}
+
+/// Monotonic counter
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    /// Add 1
+    pub fn inc(&self) {
+        self.inc_by(1.0);
+    }
+
+    /// Add `value`; negative and NaN values are ignored
+    pub fn inc_by(&self, value: f64) {
+        if value > 0.0 {
+            add_f64(&self.0, value);
+        }
+    }
+
+    /// Set the total, for collectors that read a running total kept elsewhere
+    pub fn set_total(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Value that goes up and down
+#[derive(Debug, Clone, Default)]
+pub struct Gauge(Arc<AtomicU64>);
+
+impl Gauge {
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn add(&self, value: f64) {
+        add_f64(&self.0, value);
+    }
+
+    pub fn inc(&self) {
+        self.add(1.0);
+    }
+
+    pub fn dec(&self) {
+        self.add(-1.0);
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Receives histogram observations, see `MetricRegistry::histogram_sink`
+pub type HistogramSink = Arc<dyn Fn(&Histogram, f64) + Send + Sync>;
+
+/// Histogram sinks of a registry, shared with its histograms
+#[derive(Default)]
+struct Sinks(RwLock<BTreeMap<String, HistogramSink>>);
+
+impl std::fmt::Debug for Sinks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.0.read().keys()).finish()
+    }
+}
+
+#[derive(Debug)]
+struct HistogramCore {
+    name: String,
+    help: String,
+    labels: Vec<(String, String)>,
+    bounds: Vec<f64>,
+    /// Per-bucket counts, the last one for values above every bound
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    sum: AtomicU64,
+    sinks: Arc<Sinks>,
+}
+
+/// Distribution of observed values in fixed buckets
+#[derive(Debug, Clone)]
+pub struct Histogram(Arc<HistogramCore>);
+
+impl Histogram {
+    fn new(family: &Family, labels: Vec<(String, String)>) -> Self {
+        Self(Arc::new(HistogramCore {
+            name: family.name.clone(),
+            help: family.help.clone(),
+            labels,
+            bounds: family.buckets.clone(),
+            buckets: (0..=family.buckets.len()).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0f64.to_bits()),
+            sinks: family.sinks.clone(),
+        }))
+    }
+
+    /// Family name
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// Family help text
+    pub fn help(&self) -> &str {
+        &self.0.help
+    }
+
+    /// Label names and values of this series
+    pub fn labels(&self) -> &[(String, String)] {
+        &self.0.labels
+    }
+
+    /// Bucket upper bounds
+    pub fn bounds(&self) -> &[f64] {
+        &self.0.bounds
+    }
+
+    /// Record `value`; NaN is ignored
+    pub fn observe(&self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        let index = self.0.bounds.iter().position(|bound| value <= *bound).unwrap_or(self.0.bounds.len());
+        self.0.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.0.count.fetch_add(1, Ordering::Relaxed);
+        add_f64(&self.0.sum, value);
+        for sink in self.0.sinks.0.read().values() {
+            sink(self, value);
+        }
+    }
+
+    /// Record a duration in seconds
+    pub fn observe_duration(&self, duration: Duration) {
+        self.observe(duration.as_secs_f64());
+    }
+
+    pub fn count(&self) -> u64 {
+        self.0.count.load(Ordering::Relaxed)
+    }
+
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.0.sum.load(Ordering::Relaxed))
+    }
+
+    /// Upper bounds with the cumulative count of values at or below each
+    pub fn buckets(&self) -> Vec<(f64, u64)> {
+        let mut total = 0;
+        self.0
+            .bounds
+            .iter()
+            .zip(&self.0.buckets)
+            .map(|(bound, count)| {
+                total += count.load(Ordering::Relaxed);
+                (*bound, total)
+            })
+            .collect()
+    }
+}
+
+/// Type of a metric family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Series {
+    Counter(Counter),
+    Gauge(Gauge),
+    Histogram(Histogram),
+}
+
+#[derive(Debug)]
+struct Family {
+    name: String,
+    help: String,
+    kind: MetricKind,
+    labels: Vec<String>,
+    buckets: Vec<f64>,
+    series: RwLock<BTreeMap<Vec<String>, Series>>,
+    sinks: Arc<Sinks>,
+}
+
+/// Value of one series in a `FamilySnapshot`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleValue {
+    Counter(f64),
+    Gauge(f64),
+    /// Cumulative bucket counts as in `Histogram::buckets`
+    Histogram { buckets: Vec<(f64, u64)>, sum: f64, count: u64 },
+}
+
+/// One series in a `FamilySnapshot`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesSnapshot {
+    /// Label names and values
+    pub labels: Vec<(String, String)>,
+    pub value: SampleValue,
+}
+
+/// Values of a metric family at one point in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct FamilySnapshot {
+    pub name: String,
+    pub help: String,
+    pub kind: MetricKind,
+    pub series: Vec<SeriesSnapshot>,
+}
+
+impl Family {
+    /// Values are matched to label names by position; missing values are
+    /// empty and extra values are dropped.
+    fn key(&self, values: &[&str]) -> Vec<String> {
+        let mut key: Vec<String> = values.iter().take(self.labels.len()).map(|v| v.to_string()).collect();
+        key.resize(self.labels.len(), String::new());
+        key
+    }
+
+    /// Series for `values`, created on first use
+    fn series(&self, values: &[&str]) -> Series {
+        let key = self.key(values);
+        if let Some(series) = self.series.read().get(&key) {
+            return series.clone();
+        }
+        let labels = self.labeled(&key);
+        self.series
+            .write()
+            .entry(key)
+            .or_insert_with(|| match self.kind {
+                MetricKind::Counter => Series::Counter(Counter::default()),
+                MetricKind::Gauge => Series::Gauge(Gauge::default()),
+                MetricKind::Histogram => Series::Histogram(Histogram::new(self, labels)),
+            })
+            .clone()
+    }
+
+    fn remove(&self, values: &[&str]) -> bool {
+        self.series.write().remove(&self.key(values)).is_some()
+    }
+
+    fn labeled(&self, values: &[String]) -> Vec<(String, String)> {
+        self.labels.iter().cloned().zip(values.iter().cloned()).collect()
+    }
+
+    fn snapshot(&self) -> FamilySnapshot {
+        let series = self
+            .series
+            .read()
+            .iter()
+            .map(|(values, series)| SeriesSnapshot {
+                labels: self.labeled(values),
+                value: match series {
+                    Series::Counter(counter) => SampleValue::Counter(counter.get()),
+                    Series::Gauge(gauge) => SampleValue::Gauge(gauge.get()),
+                    Series::Histogram(histogram) => SampleValue::Histogram {
+                        buckets: histogram.buckets(),
+                        sum: histogram.sum(),
+                        count: histogram.count(),
+                    },
+                },
+            })
+            .collect();
+        FamilySnapshot { name: self.name.clone(), help: self.help.clone(), kind: self.kind, series }
+    }
+}
+
+impl FamilySnapshot {
+    /// Append the family in the Prometheus text format; families without series are left out
+    fn encode(&self, out: &mut String) {
+        if self.series.is_empty() {
+            return;
+        }
+        let _ = writeln!(out, "# HELP {} {}", self.name, escape_help(&self.help));
+        let _ = writeln!(out, "# TYPE {} {}", self.name, self.kind.as_str());
+        for series in &self.series {
+            let labels: Vec<(&str, String)> =
+                series.labels.iter().map(|(name, value)| (name.as_str(), value.clone())).collect();
+            match &series.value {
+                SampleValue::Counter(value) | SampleValue::Gauge(value) => sample(out, &self.name, &labels, *value),
+                SampleValue::Histogram { buckets, sum, count } => {
+                    let bucket = format!("{}_bucket", self.name);
+                    for (bound, count) in buckets {
+                        let mut labels = labels.clone();
+                        labels.push(("le", format_value(*bound)));
+                        sample(out, &bucket, &labels, *count as f64);
+                    }
+                    let mut all = labels.clone();
+                    all.push(("le", "+Inf".to_string()));
+                    sample(out, &bucket, &all, *count as f64);
+                    sample(out, &format!("{}_sum", self.name), &labels, *sum);
+                    sample(out, &format!("{}_count", self.name), &labels, *count as f64);
+                }
+            }
+        }
+    }
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, String)], value: f64) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels: Vec<String> =
+            labels.iter().map(|(name, value)| format!("{}=\"{}\"", name, escape_label(value))).collect();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {}", format_value(value));
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn is_valid_name(name: &str, colon: bool) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || (colon && c == ':'))
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || (colon && c == ':'))
+}
+
+/// `name` with the characters Prometheus does not allow replaced by `_`
+///
+/// Lets the dotted names used with the `metrics` facade (`http.requests`)
+/// be exported as `http_requests`.
+pub fn prometheus_name(name: &str) -> String {
+    let mut name: String =
+        name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == ':' { c } else { '_' }).collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+macro_rules! metric_vec {
+    ($(#[$meta:meta])* $vec:ident, $metric:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        pub struct $vec(Arc<Family>);
+
+        impl $vec {
+            /// Series for the label `values`, in label name order
+            pub fn with(&self, values: &[&str]) -> $metric {
+                match self.0.series(values) {
+                    Series::$metric(metric) => metric,
+                    _ => unreachable!("family kind is fixed at registration"),
+                }
+            }
+
+            /// Drop the series for `values`
+            pub fn remove(&self, values: &[&str]) -> bool {
+                self.0.remove(values)
+            }
+
+            /// Drop every series
+            pub fn clear(&self) {
+                self.0.series.write().clear();
+            }
+        }
+    };
+}
+
+metric_vec!(
+    /// Counters with labels
+    CounterVec,
+    Counter
+);
+metric_vec!(
+    /// Gauges with labels
+    GaugeVec,
+    Gauge
+);
+metric_vec!(
+    /// Histograms with labels
+    HistogramVec,
+    Histogram
+);
+
+type Collector = Arc<dyn Fn(&MetricRegistry) + Send + Sync>;
+
+/// Set of metric families, see the module documentation
+#[derive(Default)]
+pub struct MetricRegistry {
+    families: RwLock<BTreeMap<String, Arc<Family>>>,
+    collectors: RwLock<BTreeMap<String, Collector>>,
+    sinks: Arc<Sinks>,
+}
+
+impl MetricRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn register(&self, name: &str, help: &str, kind: MetricKind, labels: &[&str], buckets: &[f64]) -> Result<Arc<Family>> {
+        if !is_valid_name(name, true) {
+            return Err(RfError::InvalidParameter(format!("Invalid metric name: {}", name)));
+        }
+        if let Some(label) = labels
+            .iter()
+            .find(|label| !is_valid_name(label, false) || label.starts_with("__") || (kind == MetricKind::Histogram && **label == "le"))
+        {
+            return Err(RfError::InvalidParameter(format!("Invalid label name for {}: {}", name, label)));
+        }
+        if kind == MetricKind::Histogram && (buckets.is_empty() || buckets.windows(2).any(|pair| pair[0] >= pair[1])) {
+            return Err(RfError::InvalidParameter(format!("Buckets of {} must be non-empty and increasing", name)));
+        }
+        if let Some(family) = self.families.read().get(name) {
+            return Self::matching(family, kind, labels);
+        }
+        let mut families = self.families.write();
+        let family = families.entry(name.to_string()).or_insert_with(|| {
+            Arc::new(Family {
+                name: name.to_string(),
+                help: help.to_string(),
+                kind,
+                labels: labels.iter().map(|label| label.to_string()).collect(),
+                buckets: buckets.to_vec(),
+                series: RwLock::new(BTreeMap::new()),
+                sinks: self.sinks.clone(),
+            })
+        });
+        Self::matching(family, kind, labels)
+    }
+
+    fn matching(family: &Arc<Family>, kind: MetricKind, labels: &[&str]) -> Result<Arc<Family>> {
+        if family.kind != kind || family.labels != labels {
+            return Err(RfError::InvalidParameter(format!(
+                "Metric {} is already registered as a {} with labels [{}]",
+                family.name,
+                family.kind.as_str(),
+                family.labels.join(", ")
+            )));
+        }
+        Ok(family.clone())
+    }
+
+    /// Counter without labels
+    pub fn counter(&self, name: &str, help: &str) -> Result<Counter> {
+        Ok(self.counter_vec(name, help, &[])?.with(&[]))
+    }
+
+    pub fn counter_vec(&self, name: &str, help: &str, labels: &[&str]) -> Result<CounterVec> {
+        self.register(name, help, MetricKind::Counter, labels, &[]).map(CounterVec)
+    }
+
+    /// Gauge without labels
+    pub fn gauge(&self, name: &str, help: &str) -> Result<Gauge> {
+        Ok(self.gauge_vec(name, help, &[])?.with(&[]))
+    }
+
+    pub fn gauge_vec(&self, name: &str, help: &str, labels: &[&str]) -> Result<GaugeVec> {
+        self.register(name, help, MetricKind::Gauge, labels, &[]).map(GaugeVec)
+    }
+
+    /// Histogram without labels, with increasing bucket upper bounds
+    pub fn histogram(&self, name: &str, help: &str, buckets: &[f64]) -> Result<Histogram> {
+        Ok(self.histogram_vec(name, help, &[], buckets)?.with(&[]))
+    }
+
+    /// Buckets only apply when the family is created
+    pub fn histogram_vec(&self, name: &str, help: &str, labels: &[&str], buckets: &[f64]) -> Result<HistogramVec> {
+        self.register(name, help, MetricKind::Histogram, labels, buckets).map(HistogramVec)
+    }
+
+    /// Remove the family `name` with all its series
+    pub fn unregister(&self, name: &str) -> bool {
+        self.families.write().remove(name).is_some()
+    }
+
+    /// Registered family names, sorted
+    pub fn names(&self) -> Vec<String> {
+        self.families.read().keys().cloned().collect()
+    }
+
+    /// Run `collect` before every `encode`, replacing the collector with the same name
+    ///
+    /// Collectors update metrics from values kept elsewhere; registering
+    /// inside them is cheap once the family exists.
+    pub fn collector(&self, name: &str, collect: impl Fn(&MetricRegistry) + Send + Sync + 'static) {
+        self.collectors.write().insert(name.to_string(), Arc::new(collect));
+    }
+
+    /// Remove the collector `name`
+    pub fn remove_collector(&self, name: &str) -> bool {
+        self.collectors.write().remove(name).is_some()
+    }
+
+    /// Pass every histogram observation to `sink`, replacing the sink with the same name
+    ///
+    /// Sinks run on the observing thread, so they should be cheap.
+    pub fn histogram_sink(&self, name: &str, sink: impl Fn(&Histogram, f64) + Send + Sync + 'static) {
+        self.sinks.0.write().insert(name.to_string(), Arc::new(sink));
+    }
+
+    /// Remove the histogram sink `name`
+    pub fn remove_histogram_sink(&self, name: &str) -> bool {
+        self.sinks.0.write().remove(name).is_some()
+    }
+
+    /// Run the collectors
+    pub fn collect(&self) {
+        let collectors: Vec<Collector> = self.collectors.read().values().cloned().collect();
+        for collect in collectors {
+            collect(self);
+        }
+    }
+
+    /// Current values of the family `name`, without running the collectors
+    pub fn snapshot(&self, name: &str) -> Option<FamilySnapshot> {
+        let family = self.families.read().get(name).cloned()?;
+        Some(family.snapshot())
+    }
+
+    /// Current values of every family, after running the collectors
+    pub fn gather(&self) -> Vec<FamilySnapshot> {
+        self.collect();
+        let families: Vec<Arc<Family>> = self.families.read().values().cloned().collect();
+        families.iter().map(|family| family.snapshot()).collect()
+    }
+
+    /// Every family in the Prometheus text format, see `PROMETHEUS_CONTENT_TYPE`
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        for family in self.gather() {
+            family.encode(&mut out);
+        }
+        out
+    }
+}
+
+static DEFAULT_REGISTRY: LazyLock<MetricRegistry> = LazyLock::new(MetricRegistry::new);
+
+/// Registry fed by the `counter_inc` / `gauge_set` / `histogram_record` helpers
+/// and served by the HTTP `/metrics` handler
+pub fn default_registry() -> &'static MetricRegistry {
+    &DEFAULT_REGISTRY
+}
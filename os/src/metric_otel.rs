@@ -6,42 +6,451 @@
 //! @date 2026-01-06
 
 //! OpenTelemetry metrics collection and OTLP export
+//!
+//! `OtlpMetrics::start` pushes metrics to an OTLP collector over gRPC or
+//! HTTP (protobuf) every `interval`, with resource attributes from the
+//! configuration (`metrics.otlp` section, see `OtlpMetricsConfig`). It also
+//! installs the meter provider globally, so `opentelemetry::global::meter`
+//! instruments are exported too.
+//!
+//! With `bridge` on (the default), the default `MetricRegistry` — the one
+//! served on `/metrics` — is exported as well, so one set of metrics feeds
+//! both Prometheus pull and OTLP push:
+//!
+//! - counters and gauges become observable instruments read at each push;
+//!   families registered later are picked up within one interval
+//! - histogram observations are recorded into OTel histograms with the same
+//!   buckets, from the moment the bridge starts
+//!
+//! ```toml
+//! [metrics.otlp]
+//! protocol = "grpc"                 # or "http"
+//! endpoint = "http://otel-collector:4317"
+//! interval = "15s"
+//! service_name = "orders"
+//!
+//! [metrics.otlp.resource]
+//! "deployment.environment" = "prod"
+//!
+//! [metrics.otlp.headers]
+//! authorization = "Bearer ..."
+//! ```
+//!
+//! ```rust,ignore
+//! let otlp = OtlpMetrics::start(&OtlpMetricsConfig::from_config(&config)?)?;
+//! // ...
+//! otlp.shutdown()?; // pushes the last values
+//! ```
+//!
+//! gRPC export runs on the tokio runtime, so start it inside one.
 
-use rf_errors::Result;
+use crate::metric::{default_registry, Histogram, MetricKind, MetricRegistry, SampleValue};
+use opentelemetry::metrics::{Meter, MeterProvider as _, ObservableCounter, ObservableGauge};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{MetricExporter, Protocol, WithExportConfig, WithHttpConfig, WithTonicConfig};
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider, Temporality};
+use opentelemetry_sdk::Resource;
+use parking_lot::{Mutex, RwLock};
+use rf_errors::{Result, RfError};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Transport of the OTLP exporter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtlpProtocol {
+    /// gRPC, default port 4317
+    #[default]
+    Grpc,
+    /// HTTP with protobuf bodies, default port 4318
+    Http,
+}
+
+/// How sums and histograms are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtlpTemporality {
+    /// Totals since start (the Prometheus model)
+    #[default]
+    Cumulative,
+    /// Changes since the last push
+    Delta,
+}
+
+fn default_interval() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn default_bridge() -> bool {
+    true
+}
+
+/// OTLP metrics export settings, usually the `metrics.otlp` configuration section
+#[derive(Debug, Clone, Deserialize)]
+pub struct OtlpMetricsConfig {
+    #[serde(default)]
+    pub protocol: OtlpProtocol,
+    /// Collector endpoint; the exporter's default (or `OTEL_EXPORTER_OTLP_ENDPOINT`) when unset.
+    /// For HTTP the full URL of the metrics path, e.g. `http://collector:4318/v1/metrics`
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Push interval (default 60s)
+    #[serde(default = "default_interval", with = "crate::cfg::serde_duration")]
+    pub interval: Duration,
+    /// Export timeout (default 10s)
+    #[serde(default = "default_timeout", with = "crate::cfg::serde_duration")]
+    pub timeout: Duration,
+    #[serde(default)]
+    pub temporality: OtlpTemporality,
+    /// `service.name` resource attribute
+    #[serde(default)]
+    pub service_name: Option<String>,
+    /// Further resource attributes, e.g. `deployment.environment`
+    #[serde(default)]
+    pub resource: BTreeMap<String, String>,
+    /// Headers (HTTP) or metadata (gRPC) sent with every export, e.g. credentials
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Export the default `MetricRegistry` too (default `true`)
+    #[serde(default = "default_bridge")]
+    pub bridge: bool,
+}
+
+impl Default for OtlpMetricsConfig {
+    fn default() -> Self {
+        Self {
+            protocol: OtlpProtocol::default(),
+            endpoint: None,
+            interval: default_interval(),
+            timeout: default_timeout(),
+            temporality: OtlpTemporality::default(),
+            service_name: None,
+            resource: BTreeMap::new(),
+            headers: BTreeMap::new(),
+            bridge: default_bridge(),
+        }
+    }
+}
+
+impl OtlpMetricsConfig {
+    /// Export to `endpoint` over `protocol`
+    pub fn new(protocol: OtlpProtocol, endpoint: &str) -> Self {
+        Self { protocol, endpoint: Some(endpoint.to_string()), ..Self::default() }
+    }
+
+    /// Read the `metrics.otlp` section; unset keys keep their defaults
+    pub fn from_config(config: &crate::cfg::Config) -> Result<Self> {
+        config.get_struct("metrics.otlp")
+    }
+
+    pub fn service_name(mut self, name: &str) -> Self {
+        self.service_name = Some(name.to_string());
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Add a resource attribute
+    pub fn resource(mut self, key: &str, value: &str) -> Self {
+        self.resource.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Add a header sent with every export
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn bridge(mut self, bridge: bool) -> Self {
+        self.bridge = bridge;
+        self
+    }
+
+    fn build_resource(&self) -> Resource {
+        let mut builder = Resource::builder();
+        if let Some(name) = &self.service_name {
+            builder = builder.with_service_name(name.clone());
+        }
+        builder
+            .with_attributes(self.resource.iter().map(|(key, value)| KeyValue::new(key.clone(), value.clone())))
+            .build()
+    }
+
+    fn build_exporter(&self) -> Result<MetricExporter> {
+        let temporality = match self.temporality {
+            OtlpTemporality::Cumulative => Temporality::Cumulative,
+            OtlpTemporality::Delta => Temporality::Delta,
+        };
+        let exporter = match self.protocol {
+            OtlpProtocol::Grpc => {
+                if tokio::runtime::Handle::try_current().is_err() {
+                    return Err(RfError::Config("OTLP gRPC export must be started inside a tokio runtime".to_string()));
+                }
+                let mut builder = MetricExporter::builder().with_tonic().with_timeout(self.timeout);
+                if let Some(endpoint) = &self.endpoint {
+                    builder = builder.with_endpoint(endpoint.clone());
+                }
+                if !self.headers.is_empty() {
+                    let mut headers = http::HeaderMap::new();
+                    for (name, value) in &self.headers {
+                        let name = http::HeaderName::from_bytes(name.as_bytes())
+                            .map_err(|e| RfError::Config(format!("Invalid OTLP header name '{}': {}", name, e)))?;
+                        let value = http::HeaderValue::from_str(value)
+                            .map_err(|e| RfError::Config(format!("Invalid OTLP header value for '{}': {}", name, e)))?;
+                        headers.insert(name, value);
+                    }
+                    builder = builder.with_metadata(opentelemetry_otlp::tonic_types::metadata::MetadataMap::from_headers(headers));
+                }
+                builder.with_temporality(temporality).build()
+            }
+            OtlpProtocol::Http => {
+                let mut builder = MetricExporter::builder()
+                    .with_http()
+                    .with_protocol(Protocol::HttpBinary)
+                    .with_timeout(self.timeout)
+                    .with_headers(self.headers.clone().into_iter().collect());
+                if let Some(endpoint) = &self.endpoint {
+                    builder = builder.with_endpoint(endpoint.clone());
+                }
+                builder.with_temporality(temporality).build()
+            }
+        };
+        exporter.map_err(|e| RfError::Config(format!("Failed to build OTLP metric exporter: {}", e)))
+    }
+}
+
+/// Running OTLP export, see the module documentation
+pub struct OtlpMetrics {
+    provider: SdkMeterProvider,
+    bridge: Option<Arc<RegistryBridge>>,
+    stop: Option<mpsc::Sender<()>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl OtlpMetrics {
+    /// Start pushing metrics and install the meter provider globally
+    ///
+    /// # Errors
+    ///
+    /// `RfError::Config` when the exporter cannot be built from `config`
+    pub fn start(config: &OtlpMetricsConfig) -> Result<Self> {
+        let exporter = config.build_exporter()?;
+        let reader = PeriodicReader::builder(exporter).with_interval(config.interval).build();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_resource(config.build_resource())
+            .build();
+        opentelemetry::global::set_meter_provider(provider.clone());
+
+        let mut otlp = Self { provider, bridge: None, stop: None, worker: None };
+        if config.bridge {
+            otlp.bridge_registry(default_registry(), config.interval);
+        }
+        tracing::info!(
+            "Pushing OTLP metrics over {:?} to {} every {:?}",
+            config.protocol,
+            config.endpoint.as_deref().unwrap_or("the default endpoint"),
+            config.interval
+        );
+        Ok(otlp)
+    }
+
+    /// Export `registry` through this provider, checking for new families every `interval`
+    fn bridge_registry(&mut self, registry: &'static MetricRegistry, interval: Duration) {
+        let bridge = Arc::new(RegistryBridge {
+            meter: self.provider.meter("rf"),
+            registry,
+            observed: Mutex::new(HashSet::new()),
+            instruments: Mutex::new(Vec::new()),
+            histograms: RwLock::new(HashMap::new()),
+        });
+        let sink = bridge.clone();
+        registry.histogram_sink("otlp", move |histogram, value| sink.record(histogram, value));
+        bridge.sync();
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let worker = bridge.clone();
+        let spawned = std::thread::Builder::new().name("otlp-metrics".to_string()).spawn(move || {
+            while let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                worker.sync();
+            }
+        });
+        match spawned {
+            Ok(handle) => {
+                self.worker = Some(handle);
+                self.stop = Some(stop);
+            }
+            Err(e) => tracing::warn!("Cannot start the OTLP metrics bridge thread, new families are not exported: {}", e),
+        }
+        self.bridge = Some(bridge);
+    }
+
+    /// The meter provider, for instruments outside the registry
+    pub fn provider(&self) -> &SdkMeterProvider {
+        &self.provider
+    }
+
+    /// Push the current values now
+    pub fn flush(&self) -> Result<()> {
+        if let Some(bridge) = &self.bridge {
+            bridge.sync();
+        }
+        self.provider
+            .force_flush()
+            .map_err(|e| RfError::Network(format!("Failed to push OTLP metrics: {}", e)))
+    }
+
+    /// Push the last values and stop
+    pub fn shutdown(mut self) -> Result<()> {
+        self.stop_bridge();
+        self.provider
+            .shutdown()
+            .map_err(|e| RfError::Network(format!("Failed to shut down OTLP metrics: {}", e)))
+    }
+
+    fn stop_bridge(&mut self) {
+        self.stop.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        if let Some(bridge) = self.bridge.take() {
+            bridge.sync();
+            bridge.registry.remove_histogram_sink("otlp");
+        }
+    }
+}
+
+impl Drop for OtlpMetrics {
+    fn drop(&mut self) {
+        self.stop_bridge();
+    }
+}
+
+/// Feeds a `MetricRegistry` into OTel instruments
+struct RegistryBridge {
+    meter: Meter,
+    registry: &'static MetricRegistry,
+    /// Counter and gauge families with an observable instrument
+    observed: Mutex<HashSet<String>>,
+    /// Kept alive with the bridge
+    instruments: Mutex<Vec<Instrument>>,
+    histograms: RwLock<HashMap<String, opentelemetry::metrics::Histogram<f64>>>,
+}
+
+#[allow(dead_code)]
+enum Instrument {
+    Counter(ObservableCounter<f64>),
+    Gauge(ObservableGauge<f64>),
+}
+
+fn attributes(labels: &[(String, String)]) -> Vec<KeyValue> {
+    labels.iter().map(|(name, value)| KeyValue::new(name.clone(), value.clone())).collect()
+}
+
+impl RegistryBridge {
+    /// Run the collectors and add instruments for new counter and gauge families
+    fn sync(&self) {
+        let families = self.registry.gather();
+        let mut observed = self.observed.lock();
+        for family in families {
+            if family.kind == MetricKind::Histogram || observed.contains(&family.name) {
+                continue;
+            }
+            let registry = self.registry;
+            let name = family.name.clone();
+            let instrument = match family.kind {
+                MetricKind::Counter => Instrument::Counter(
+                    self.meter
+                        .f64_observable_counter(family.name.clone())
+                        .with_description(family.help.clone())
+                        .with_callback(move |observer| {
+                            for series in registry.snapshot(&name).map(|family| family.series).unwrap_or_default() {
+                                if let SampleValue::Counter(value) = series.value {
+                                    observer.observe(value, &attributes(&series.labels));
+                                }
+                            }
+                        })
+                        .build(),
+                ),
+                _ => Instrument::Gauge(
+                    self.meter
+                        .f64_observable_gauge(family.name.clone())
+                        .with_description(family.help.clone())
+                        .with_callback(move |observer| {
+                            for series in registry.snapshot(&name).map(|family| family.series).unwrap_or_default() {
+                                if let SampleValue::Gauge(value) = series.value {
+                                    observer.observe(value, &attributes(&series.labels));
+                                }
+                            }
+                        })
+                        .build(),
+                ),
+            };
+            self.instruments.lock().push(instrument);
+            observed.insert(family.name);
+        }
+    }
+
+    /// Mirror one histogram observation
+    fn record(&self, histogram: &Histogram, value: f64) {
+        let attributes = attributes(histogram.labels());
+        if let Some(instrument) = self.histograms.read().get(histogram.name()) {
+            instrument.record(value, &attributes);
+            return;
+        }
+        let mut histograms = self.histograms.write();
+        let instrument = histograms.entry(histogram.name().to_string()).or_insert_with(|| {
+            self.meter
+                .f64_histogram(histogram.name().to_string())
+                .with_description(histogram.help().to_string())
+                .with_boundaries(histogram.bounds().to_vec())
+                .build()
+        });
+        instrument.record(value, &attributes);
+    }
+}
+
+static GLOBAL: Mutex<Option<OtlpMetrics>> = Mutex::new(None);
+
+fn start_global(config: OtlpMetricsConfig) -> Result<()> {
+    let otlp = OtlpMetrics::start(&config)?;
+    if let Some(previous) = GLOBAL.lock().replace(otlp) {
+        let _ = previous.shutdown();
+    }
+    Ok(())
+}
 
 /// Initialize OpenTelemetry metrics with OTLP exporter (gRPC)
-/// Note: This is a simplified implementation. Full implementation would use opentelemetry_sdk::metrics
+///
+/// Keeps the export running until `shutdown_metrics`; use `OtlpMetrics::start`
+/// to hold the handle yourself.
 pub fn init_metrics_otlp_grpc(service_name: &str, endpoint: &str) -> Result<()> {
-    // Create resource with service name
-    // Note: In opentelemetry 0.31, Resource API has changed significantly
-    // This is a placeholder implementation - full implementation would use the new Resource API
-    // For opentelemetry 0.31, use Resource::from_detectors() or Resource::from_attributes()
-    
-    // In a full implementation, this would:
-    // 1. Create OTLP gRPC metric exporter using opentelemetry_otlp
-    // 2. Create a periodic reader
-    // 3. Create SdkMeterProvider with the reader
-    // 4. Set it as the global meter provider
-    
-    tracing::info!("Initialized OTLP gRPC metrics for service: {} at endpoint: {}", service_name, endpoint);
-    Ok(())
+    start_global(OtlpMetricsConfig::new(OtlpProtocol::Grpc, endpoint).service_name(service_name))
 }
 
 /// Initialize OpenTelemetry metrics with OTLP exporter (HTTP)
-/// Note: This is a simplified implementation. Full implementation would use opentelemetry_sdk::metrics
+///
+/// `endpoint` is the full metrics URL, e.g. `http://collector:4318/v1/metrics`.
 pub fn init_metrics_otlp_http(service_name: &str, endpoint: &str) -> Result<()> {
-    // Create resource with service name
-    // Note: In opentelemetry 0.31, Resource API has changed significantly
-    // This is a placeholder implementation - full implementation would use the new Resource API
-    // For opentelemetry 0.31, use Resource::from_detectors() or Resource::from_attributes()
-    
-    // In a full implementation, this would:
-    // 1. Create OTLP HTTP metric exporter using opentelemetry_otlp
-    // 2. Create a periodic reader
-    // 3. Create SdkMeterProvider with the reader
-    // 4. Set it as the global meter provider
-    
-    tracing::info!("Initialized OTLP HTTP metrics for service: {} at endpoint: {}", service_name, endpoint);
-    Ok(())
+    start_global(OtlpMetricsConfig::new(OtlpProtocol::Http, endpoint).service_name(service_name))
 }
 
+/// Push the last values of the export started by `init_metrics_otlp_*` and stop it
+pub fn shutdown_metrics() -> Result<()> {
+    match GLOBAL.lock().take() {
+        Some(otlp) => otlp.shutdown(),
+        None => Ok(()),
+    }
+}
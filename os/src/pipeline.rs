@@ -0,0 +1,592 @@
+//! # pipeline
+//!
+//! pipeline 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Async processing pipeline
+//!
+//! Chains async stages with bounded channels between them. Each stage runs
+//! a fixed number of workers, so slow stages apply backpressure upstream
+//! instead of buffering without limit. Failed items are routed to an error
+//! handler, and every stage reports throughput, failures and latency both
+//! through the `metric` module and as an in-process `PipelineStats`.
+//!
+//! ```ignore
+//! use rf_os::pipeline::Pipeline;
+//! use std::time::Duration;
+//!
+//! let pipeline = Pipeline::<String>::new("ingest")
+//!     .buffer(256)
+//!     .stage("parse", 4, |line| async move { parse(&line) })
+//!     .stage("enrich", 8, |record| async move { enrich(record).await })
+//!     .batch("batch", 500, Duration::from_millis(200))
+//!     .sink("store", 2, |records| async move { store(records).await })
+//!     .on_error(|err| tracing::error!("{}", err));
+//!
+//! let mut handle = pipeline.start();
+//! for line in lines {
+//!     handle.send(line).await?;
+//! }
+//! let stats = handle.join().await?;
+//! ```
+//!
+//! Items are not kept in order once a stage has more than one worker.
+
+use crate::metric::{counter_inc_dynamic, histogram_record_dynamic, normalize_metric_name};
+use futures::FutureExt;
+use parking_lot::Mutex;
+use rf_errors::{Result, RfError};
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+/// Default capacity of the channel in front of each stage
+pub const DEFAULT_BUFFER: usize = 64;
+
+/// A failed item, passed to the pipeline's error handler
+#[derive(Debug)]
+pub struct StageError {
+    /// Pipeline name
+    pub pipeline: String,
+    /// Stage the item failed in
+    pub stage: String,
+    /// Error returned by the stage
+    pub error: RfError,
+}
+
+impl fmt::Display for StageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pipeline '{}' stage '{}' failed: {}", self.pipeline, self.stage, self.error)
+    }
+}
+
+type ErrorHandler = Arc<dyn Fn(&StageError) + Send + Sync>;
+type Build<I, O> = Box<dyn FnOnce(mpsc::Receiver<I>, &Arc<Shared>) -> mpsc::Receiver<O> + Send>;
+
+/// Async pipeline builder
+///
+/// `I` is the type fed into the pipeline and `O` the type coming out of its
+/// last stage. Stages are only spawned by `start` or `run`.
+pub struct Pipeline<I, O = I> {
+    name: String,
+    buffer: usize,
+    input_buffer: usize,
+    stages: Vec<Arc<StageCounters>>,
+    on_error: Option<ErrorHandler>,
+    stop_on_error: bool,
+    build: Build<I, O>,
+}
+
+impl<I: Send + 'static> Pipeline<I> {
+    /// Create an empty pipeline; `name` prefixes its metrics
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            buffer: DEFAULT_BUFFER,
+            input_buffer: DEFAULT_BUFFER,
+            stages: Vec::new(),
+            on_error: None,
+            stop_on_error: false,
+            build: Box::new(|rx, _| rx),
+        }
+    }
+}
+
+impl<I: Send + 'static, O: Send + 'static> Pipeline<I, O> {
+    /// Channel capacity in front of the stages added after this call
+    ///
+    /// Called before any stage, it also sets the capacity of the input channel.
+    pub fn buffer(mut self, capacity: usize) -> Self {
+        self.buffer = capacity.max(1);
+        if self.stages.is_empty() {
+            self.input_buffer = self.buffer;
+        }
+        self
+    }
+
+    /// Call `handler` for every item a stage fails on
+    ///
+    /// Without a handler failures are logged with `tracing::warn!`. The
+    /// handler runs on the worker, so forward to a channel for slow work such
+    /// as writing a dead-letter queue.
+    pub fn on_error<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&StageError) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Arc::new(handler));
+        self
+    }
+
+    /// Stop the whole pipeline on the first failed item
+    ///
+    /// Remaining items are dropped and `join` returns the error.
+    pub fn stop_on_error(mut self, stop: bool) -> Self {
+        self.stop_on_error = stop;
+        self
+    }
+
+    /// Add a stage mapping each item with `workers` concurrent calls of `f`
+    pub fn stage<U, F, Fut>(self, name: impl Into<String>, workers: usize, f: F) -> Pipeline<I, U>
+    where
+        U: Send + 'static,
+        F: Fn(O) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<U>> + Send + 'static,
+    {
+        self.flat_stage(name, workers, move |item| f(item).map(|result| result.map(std::iter::once)))
+    }
+
+    /// Add a stage producing any number of items per input
+    ///
+    /// Return an `Option` to filter items or a `Vec` to fan out.
+    pub fn flat_stage<U, R, F, Fut>(mut self, name: impl Into<String>, workers: usize, f: F) -> Pipeline<I, U>
+    where
+        U: Send + 'static,
+        R: IntoIterator<Item = U> + Send + 'static,
+        R::IntoIter: Send,
+        F: Fn(O) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+    {
+        let counters = Arc::new(StageCounters::new(&self.name, name.into(), workers.max(1)));
+        self.stages.push(counters.clone());
+        let buffer = self.buffer;
+        let f = Arc::new(f);
+        self.chain(move |previous| {
+            Box::new(move |rx, shared| {
+                let rx = previous(rx, shared);
+                spawn_workers(rx, buffer, shared, counters, f)
+            })
+        })
+    }
+
+    /// Add a final stage consuming each item
+    pub fn sink<F, Fut>(self, name: impl Into<String>, workers: usize, f: F) -> Pipeline<I, ()>
+    where
+        F: Fn(O) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.flat_stage(name, workers, move |item| f(item).map(|result| result.map(|()| None)))
+    }
+
+    /// Group items into batches of up to `size`
+    ///
+    /// A partial batch is emitted once its first item has waited `max_wait`,
+    /// and when the input ends.
+    pub fn batch(mut self, name: impl Into<String>, size: usize, max_wait: Duration) -> Pipeline<I, Vec<O>> {
+        let counters = Arc::new(StageCounters::new(&self.name, name.into(), 1));
+        self.stages.push(counters.clone());
+        let buffer = self.buffer;
+        let size = size.max(1);
+        self.chain(move |previous| {
+            Box::new(move |rx, shared| {
+                let rx = previous(rx, shared);
+                spawn_batcher(rx, buffer, shared, counters, size, max_wait)
+            })
+        })
+    }
+
+    fn chain<U>(self, wrap: impl FnOnce(Build<I, O>) -> Build<I, U>) -> Pipeline<I, U> {
+        Pipeline {
+            build: wrap(self.build),
+            name: self.name,
+            buffer: self.buffer,
+            input_buffer: self.input_buffer,
+            stages: self.stages,
+            on_error: self.on_error,
+            stop_on_error: self.stop_on_error,
+        }
+    }
+
+    /// Spawn the stage workers
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn start(self) -> PipelineHandle<I, O> {
+        let (stop, _) = watch::channel(false);
+        let shared = Arc::new(Shared {
+            name: self.name,
+            stages: self.stages,
+            on_error: self.on_error,
+            stop_on_error: self.stop_on_error,
+            stop,
+            first_error: Mutex::new(None),
+            tasks: Mutex::new(Vec::new()),
+        });
+        let (input, rx) = mpsc::channel(self.input_buffer);
+        let output = (self.build)(rx, &shared);
+        PipelineHandle {
+            input: Some(input),
+            output,
+            shared,
+        }
+    }
+
+    /// Feed `items` through the pipeline and collect the output
+    pub async fn run<T>(self, items: T) -> Result<Vec<O>>
+    where
+        T: IntoIterator<Item = I>,
+        T::IntoIter: Send + 'static,
+    {
+        let items = items.into_iter();
+        let mut handle = self.start();
+        let sender = handle.sender();
+        handle.close();
+        let feeder = tokio::spawn(async move {
+            if let Some(sender) = sender {
+                for item in items {
+                    if sender.send(item).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        let mut outputs = Vec::new();
+        while let Some(output) = handle.recv().await {
+            outputs.push(output);
+        }
+        let _ = feeder.await;
+        handle.join().await?;
+        Ok(outputs)
+    }
+}
+
+/// A running pipeline
+pub struct PipelineHandle<I, O> {
+    input: Option<mpsc::Sender<I>>,
+    output: mpsc::Receiver<O>,
+    shared: Arc<Shared>,
+}
+
+impl<I, O> PipelineHandle<I, O> {
+    /// Feed an item, waiting while the first stage is full
+    pub async fn send(&self, item: I) -> Result<()> {
+        let input = self
+            .input
+            .as_ref()
+            .ok_or_else(|| RfError::Internal(format!("Pipeline '{}' input is closed", self.shared.name)))?;
+        input
+            .send(item)
+            .await
+            .map_err(|_| RfError::Internal(format!("Pipeline '{}' has stopped", self.shared.name)))
+    }
+
+    /// Another sender for feeding the pipeline from other tasks
+    ///
+    /// `None` once the input is closed. The input stays open until every
+    /// sender is dropped.
+    pub fn sender(&self) -> Option<mpsc::Sender<I>> {
+        self.input.clone()
+    }
+
+    /// Close the input; stages finish the items already sent
+    pub fn close(&mut self) {
+        self.input = None;
+    }
+
+    /// Next item out of the last stage, `None` once the pipeline has finished
+    pub async fn recv(&mut self) -> Option<O> {
+        self.output.recv().await
+    }
+
+    /// Stop all stages, dropping items not yet processed
+    pub fn stop(&self) {
+        self.shared.stop.send_replace(true);
+    }
+
+    /// Whether the pipeline was stopped by `stop` or `stop_on_error`
+    pub fn is_stopped(&self) -> bool {
+        *self.shared.stop.borrow()
+    }
+
+    /// Current per-stage statistics
+    pub fn stats(&self) -> PipelineStats {
+        self.shared.stats()
+    }
+
+    /// Close the input and wait for every stage to finish
+    ///
+    /// Output not taken with `recv` is discarded. Returns the error that
+    /// stopped the pipeline under `stop_on_error`, or an error if a stage
+    /// panicked.
+    pub async fn join(mut self) -> Result<PipelineStats> {
+        self.close();
+        while self.output.recv().await.is_some() {}
+        let tasks = std::mem::take(&mut *self.shared.tasks.lock());
+        for task in tasks {
+            if let Err(e) = task.await {
+                self.shared.record_error(RfError::Internal(format!(
+                    "Pipeline '{}' worker failed: {}",
+                    self.shared.name, e
+                )));
+            }
+        }
+        match self.shared.first_error.lock().take() {
+            Some(error) => Err(error),
+            None => Ok(self.shared.stats()),
+        }
+    }
+}
+
+/// Statistics of one stage
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageStats {
+    /// Stage name
+    pub name: String,
+    /// Worker count
+    pub workers: usize,
+    /// Items handled successfully (batches for a batch stage)
+    pub processed: u64,
+    /// Items the stage returned an error for
+    pub failed: u64,
+    /// Items being handled right now
+    pub in_flight: u64,
+    /// Mean time spent per item
+    pub avg_latency: Duration,
+}
+
+/// Statistics of a pipeline, in stage order
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineStats {
+    /// Pipeline name
+    pub name: String,
+    /// Per-stage statistics
+    pub stages: Vec<StageStats>,
+}
+
+impl PipelineStats {
+    /// Statistics of the stage called `name`
+    pub fn stage(&self, name: &str) -> Option<&StageStats> {
+        self.stages.iter().find(|stage| stage.name == name)
+    }
+
+    /// Failed items across all stages
+    pub fn failed(&self) -> u64 {
+        self.stages.iter().map(|stage| stage.failed).sum()
+    }
+}
+
+struct StageCounters {
+    name: String,
+    workers: usize,
+    processed: AtomicU64,
+    failed: AtomicU64,
+    in_flight: AtomicU64,
+    latency_nanos: AtomicU64,
+    processed_metric: String,
+    failed_metric: String,
+    latency_metric: String,
+}
+
+impl StageCounters {
+    fn new(pipeline: &str, name: String, workers: usize) -> Self {
+        let prefix = format!(
+            "pipeline.{}.{}",
+            normalize_metric_name(pipeline),
+            normalize_metric_name(&name)
+        );
+        Self {
+            processed_metric: format!("{}.processed", prefix),
+            failed_metric: format!("{}.failed", prefix),
+            latency_metric: format!("{}.latency_ms", prefix),
+            name,
+            workers,
+            processed: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            in_flight: AtomicU64::new(0),
+            latency_nanos: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, ok: bool, elapsed: Duration) {
+        self.latency_nanos.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        histogram_record_dynamic(self.latency_metric.clone(), elapsed.as_secs_f64() * 1000.0);
+        if ok {
+            self.processed.fetch_add(1, Ordering::Relaxed);
+            counter_inc_dynamic(self.processed_metric.clone(), 1);
+        } else {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+            counter_inc_dynamic(self.failed_metric.clone(), 1);
+        }
+    }
+
+    fn stats(&self) -> StageStats {
+        let processed = self.processed.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+        let handled = processed + failed;
+        StageStats {
+            name: self.name.clone(),
+            workers: self.workers,
+            processed,
+            failed,
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            avg_latency: match handled {
+                0 => Duration::ZERO,
+                n => Duration::from_nanos(self.latency_nanos.load(Ordering::Relaxed) / n),
+            },
+        }
+    }
+}
+
+struct Shared {
+    name: String,
+    stages: Vec<Arc<StageCounters>>,
+    on_error: Option<ErrorHandler>,
+    stop_on_error: bool,
+    stop: watch::Sender<bool>,
+    first_error: Mutex<Option<RfError>>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Shared {
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.lock().push(tokio::spawn(future));
+    }
+
+    fn stats(&self) -> PipelineStats {
+        PipelineStats {
+            name: self.name.clone(),
+            stages: self.stages.iter().map(|stage| stage.stats()).collect(),
+        }
+    }
+
+    fn record_error(&self, error: RfError) {
+        let mut first = self.first_error.lock();
+        if first.is_none() {
+            *first = Some(error);
+        }
+    }
+
+    /// Route a failed item; returns whether the pipeline stops
+    fn fail(&self, stage: &str, error: RfError) -> bool {
+        let failure = StageError {
+            pipeline: self.name.clone(),
+            stage: stage.to_string(),
+            error,
+        };
+        match &self.on_error {
+            Some(handler) => handler(&failure),
+            None => tracing::warn!("{}", failure),
+        }
+        if !self.stop_on_error {
+            return false;
+        }
+        self.record_error(RfError::Internal(failure.to_string()));
+        self.stop.send_replace(true);
+        true
+    }
+}
+
+/// Next item for a worker, `None` when the input ended or the pipeline stopped
+async fn next_item<T>(rx: &tokio::sync::Mutex<mpsc::Receiver<T>>, stop: &mut watch::Receiver<bool>) -> Option<T> {
+    let mut rx = rx.lock().await;
+    tokio::select! {
+        biased;
+        _ = stop.wait_for(|stopped| *stopped) => None,
+        item = rx.recv() => item,
+    }
+}
+
+fn spawn_workers<T, U, R, F, Fut>(
+    rx: mpsc::Receiver<T>,
+    buffer: usize,
+    shared: &Arc<Shared>,
+    counters: Arc<StageCounters>,
+    f: Arc<F>,
+) -> mpsc::Receiver<U>
+where
+    T: Send + 'static,
+    U: Send + 'static,
+    R: IntoIterator<Item = U> + Send + 'static,
+    R::IntoIter: Send,
+    F: Fn(T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<R>> + Send + 'static,
+{
+    let rx = Arc::new(tokio::sync::Mutex::new(rx));
+    let (tx, out) = mpsc::channel(buffer);
+    for _ in 0..counters.workers {
+        let (rx, tx, f, counters) = (rx.clone(), tx.clone(), f.clone(), counters.clone());
+        let worker_shared = shared.clone();
+        let mut stop = shared.stop.subscribe();
+        shared.spawn(async move {
+            let shared = worker_shared;
+            while let Some(item) = next_item(&rx, &mut stop).await {
+                counters.in_flight.fetch_add(1, Ordering::Relaxed);
+                let started = Instant::now();
+                let result = AssertUnwindSafe(f(item)).catch_unwind().await;
+                counters.in_flight.fetch_sub(1, Ordering::Relaxed);
+                let result = result.unwrap_or_else(|_| Err(RfError::Internal("Stage panicked".to_string())));
+                counters.record(result.is_ok(), started.elapsed());
+                match result {
+                    Ok(outputs) => {
+                        for output in outputs {
+                            if tx.send(output).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        if shared.fail(&counters.name, error) {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+    }
+    out
+}
+
+fn spawn_batcher<T>(
+    mut rx: mpsc::Receiver<T>,
+    buffer: usize,
+    shared: &Arc<Shared>,
+    counters: Arc<StageCounters>,
+    size: usize,
+    max_wait: Duration,
+) -> mpsc::Receiver<Vec<T>>
+where
+    T: Send + 'static,
+{
+    let (tx, out) = mpsc::channel(buffer);
+    let mut stop = shared.stop.subscribe();
+    shared.spawn(async move {
+        loop {
+            let first = tokio::select! {
+                biased;
+                _ = stop.wait_for(|stopped| *stopped) => return,
+                item = rx.recv() => match item {
+                    Some(item) => item,
+                    None => return,
+                },
+            };
+            let started = Instant::now();
+            let deadline = tokio::time::Instant::now() + max_wait;
+            let mut batch = Vec::with_capacity(size);
+            batch.push(first);
+            let mut closed = false;
+            while batch.len() < size {
+                match tokio::time::timeout_at(deadline, rx.recv()).await {
+                    Ok(Some(item)) => batch.push(item),
+                    Ok(None) => {
+                        closed = true;
+                        break;
+                    }
+                    Err(_) => break,
+                }
+            }
+            counters.record(true, started.elapsed());
+            if tx.send(batch).await.is_err() || closed {
+                return;
+            }
+        }
+    });
+    out
+}
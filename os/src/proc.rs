@@ -6,6 +6,19 @@
 //! @date 2026-01-06
 
 //! Process management
+//!
+//! `supervisor` spawns and supervises child processes with restart
+//! policies, health probes and graceful stops. `daemon` starts detached
+//! background processes and tracks them through PID files.
+
+pub mod daemon;
+pub mod supervisor;
+
+pub use daemon::{pid_alive, stop_pid, Daemon, PidFile, PidStatus};
+pub use supervisor::{
+    HealthCheck, HealthProbe, OutputLine, OutputStream, ProcessSpec, ProcessState, ProcessStatus, RestartPolicy,
+    Supervisor,
+};
 
 use sysinfo::{Pid, System};
 use tokio::process::Command;
@@ -0,0 +1,329 @@
+//! # daemon
+//!
+//! daemon 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Daemons and PID files
+//!
+//! `Daemon` starts a program detached from the calling terminal (a new
+//! session via `setsid` on Unix, a detached process group on Windows) with
+//! its output redirected to a log file, and records its PID in a `PidFile`.
+//! `stop_pid` stops it again:
+//!
+//! ```rust,ignore
+//! use rf_os::proc::{stop_pid, Daemon, PidFile, PidStatus};
+//!
+//! let pid = Daemon::new("target/release/myapp")
+//!     .args(["--config", "config/prod.toml"])
+//!     .log_file("logs/myapp.log")
+//!     .pid_file("run/myapp.pid")
+//!     .spawn()?;
+//!
+//! let pid_file = PidFile::new("run/myapp.pid");
+//! if let PidStatus::Running(pid) = pid_file.status()? {
+//!     stop_pid(pid, Duration::from_secs(10)).await?;
+//!     pid_file.remove()?;
+//! }
+//! ```
+//!
+//! A PID file whose process is gone is stale: `PidFile::status` reports it
+//! and `Daemon::spawn` replaces it.
+
+use rf_errors::{Result, RfError};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Time between liveness checks while stopping
+const STOP_POLL: Duration = Duration::from_millis(50);
+
+/// State of the process recorded in a PID file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PidStatus {
+    /// The process is alive
+    Running(u32),
+    /// The file names a process that is gone
+    Stale(u32),
+    /// There is no PID file
+    Stopped,
+}
+
+/// A file holding the PID of a daemon
+#[derive(Debug, Clone)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// PID file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Recorded PID, `None` when the file does not exist
+    ///
+    /// # Errors
+    ///
+    /// `RfError::InvalidParameter` when the file does not hold a PID
+    pub fn read(&self) -> Result<Option<u32>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(RfError::Io(e)),
+        };
+        content.trim().parse().map(Some).map_err(|_| {
+            RfError::InvalidParameter(format!("PID file {} does not hold a PID", self.path.display()))
+        })
+    }
+
+    /// Record `pid`, creating the parent directories
+    ///
+    /// The file is written next to its final path and renamed, so readers
+    /// never see a partial PID.
+    pub fn write(&self, pid: u32) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = self.path.with_extension("pid.tmp");
+        fs::write(&temp, format!("{}\n", pid))?;
+        fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+
+    /// Delete the file; a missing file is not an error
+    pub fn remove(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(RfError::Io(e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the recorded process is alive
+    pub fn status(&self) -> Result<PidStatus> {
+        Ok(match self.read()? {
+            Some(pid) if pid_alive(pid) => PidStatus::Running(pid),
+            Some(pid) => PidStatus::Stale(pid),
+            None => PidStatus::Stopped,
+        })
+    }
+}
+
+/// A program to start in the background, see the module documentation
+#[derive(Debug, Clone)]
+pub struct Daemon {
+    program: PathBuf,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    cwd: Option<PathBuf>,
+    log_file: Option<PathBuf>,
+    pid_file: Option<PidFile>,
+}
+
+impl Daemon {
+    /// Run `program`; output is discarded unless `log_file` is set
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            cwd: None,
+            log_file: None,
+            pid_file: None,
+        }
+    }
+
+    /// Add arguments
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set an environment variable
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Working directory
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(dir.into());
+        self
+    }
+
+    /// Append stdout and stderr to `path`, creating it and its directories
+    pub fn log_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log_file = Some(path.into());
+        self
+    }
+
+    /// Record the PID in `path`
+    pub fn pid_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.pid_file = Some(PidFile::new(path));
+        self
+    }
+
+    /// Start the daemon and return its PID
+    ///
+    /// # Errors
+    ///
+    /// `RfError::InvalidParameter` when the PID file names a running process,
+    /// `RfError::Io` when the program or the log file cannot be opened
+    pub fn spawn(&self) -> Result<u32> {
+        if let Some(pid_file) = &self.pid_file {
+            match pid_file.status()? {
+                PidStatus::Running(pid) => {
+                    return Err(RfError::InvalidParameter(format!(
+                        "Already running with PID {} ({})",
+                        pid,
+                        pid_file.path().display()
+                    )));
+                }
+                PidStatus::Stale(pid) => {
+                    tracing::warn!("Removing stale PID file {} (PID {})", pid_file.path().display(), pid);
+                    pid_file.remove()?;
+                }
+                PidStatus::Stopped => {}
+            }
+        }
+
+        let (stdout, stderr) = match &self.log_file {
+            Some(path) => {
+                if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                    fs::create_dir_all(parent)?;
+                }
+                let log: File = OpenOptions::new().create(true).append(true).open(path)?;
+                (Stdio::from(log.try_clone()?), Stdio::from(log))
+            }
+            None => (Stdio::null(), Stdio::null()),
+        };
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).envs(&self.env).stdin(Stdio::null()).stdout(stdout).stderr(stderr);
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        detach(&mut command);
+
+        let child = command.spawn()?;
+        let pid = child.id();
+        if let Some(pid_file) = &self.pid_file {
+            pid_file.write(pid)?;
+        }
+        tracing::info!("Started daemon {} (PID {})", self.program.display(), pid);
+        Ok(pid)
+    }
+}
+
+/// Run the child in its own session, away from the terminal and its signals
+#[cfg(unix)]
+fn detach(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: setsid is async-signal-safe and touches no state of the parent
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(windows)]
+fn detach(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn detach(_command: &mut Command) {}
+
+/// Whether a process with `pid` is alive; zombies count as gone
+pub fn pid_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        // SAFETY: signal 0 only checks that the process exists
+        let exists = unsafe { libc::kill(pid as libc::pid_t, 0) } == 0
+            || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+        if !exists {
+            return false;
+        }
+        // `/proc/<pid>/stat` is `pid (comm) state ...`; comm may contain spaces
+        match fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Ok(stat) => stat.rsplit_once(')').and_then(|(_, rest)| rest.split_whitespace().next()) != Some("Z"),
+            Err(_) => true,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let system = sysinfo::System::new_all();
+        system.process(sysinfo::Pid::from(pid as usize)).is_some()
+    }
+}
+
+/// Ask the process to stop (SIGTERM on Unix, `taskkill` on Windows)
+fn signal_stop(pid: u32, force: bool) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+        // SAFETY: plain syscall, the PID was checked to be alive
+        if unsafe { libc::kill(pid as libc::pid_t, signal) } == -1 {
+            let error = std::io::Error::last_os_error();
+            if error.raw_os_error() != Some(libc::ESRCH) {
+                return Err(RfError::Io(error));
+            }
+        }
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        let mut command = Command::new("taskkill");
+        command.arg("/PID").arg(pid.to_string());
+        if force {
+            command.arg("/F");
+        }
+        command.stdout(Stdio::null()).stderr(Stdio::null()).status()?;
+        Ok(())
+    }
+}
+
+/// Stop a process gracefully, killing it if it is still alive after `timeout`
+///
+/// Returns `true` when the process exited by itself before the timeout.
+///
+/// # Errors
+///
+/// `RfError::Io` when the signal cannot be sent, e.g. for lack of permission
+pub async fn stop_pid(pid: u32, timeout: Duration) -> Result<bool> {
+    if !pid_alive(pid) {
+        return Ok(true);
+    }
+    signal_stop(pid, false)?;
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if !pid_alive(pid) {
+            return Ok(true);
+        }
+        tokio::time::sleep(STOP_POLL).await;
+    }
+    tracing::warn!("PID {} did not stop within {:?}, killing it", pid, timeout);
+    signal_stop(pid, true)?;
+    while pid_alive(pid) {
+        tokio::time::sleep(STOP_POLL).await;
+    }
+    Ok(false)
+}
@@ -0,0 +1,636 @@
+//! # supervisor
+//!
+//! supervisor 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Child process supervision
+//!
+//! `Supervisor` spawns child processes from a `ProcessSpec`, writes their
+//! stdout and stderr to the log system, restarts them according to their
+//! `RestartPolicy` and stops them gracefully:
+//!
+//! ```rust,ignore
+//! use rf_os::proc::{HealthCheck, HealthProbe, ProcessSpec, RestartPolicy, Supervisor};
+//!
+//! let supervisor = Supervisor::new();
+//! supervisor.spawn(
+//!     ProcessSpec::new("worker", "./worker")
+//!         .args(["--queue", "orders"])
+//!         .env("RUST_LOG", "info")
+//!         .current_dir("/srv/app")
+//!         .restart(RestartPolicy::OnFailure)
+//!         .backoff(Duration::from_secs(1), Duration::from_secs(30))
+//!         .health(HealthCheck::new(HealthProbe::Http("http://127.0.0.1:9000/health".into()))),
+//! ).await?;
+//!
+//! supervisor.stop("worker").await?;
+//! ```
+//!
+//! Output lines are logged through the logger named after the process, with
+//! a `stream` field (`stdout` at info level, `stderr` at warn level), and the
+//! latest lines are kept for `Supervisor::output`.
+//!
+//! Restarts wait a backoff delay that doubles after each consecutive restart
+//! up to the maximum; a process that stayed up longer than the maximum delay
+//! starts over from the initial delay. A process whose health probe fails
+//! `failures` times in a row is stopped and handled like a failed exit.
+//!
+//! Stopping sends SIGTERM (on Unix), waits for the stop timeout and then
+//! kills the process.
+
+use rf_errors::{Result, RfError};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Output lines kept per process
+const OUTPUT_LINES: usize = 200;
+
+/// When a stopped process is restarted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RestartPolicy {
+    /// Never restart
+    Never,
+    /// Restart after every exit
+    Always,
+    /// Restart after a non-zero exit, a signal or a failed health probe
+    #[default]
+    OnFailure,
+}
+
+/// How the health of a running process is checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthProbe {
+    /// A TCP connection to `host:port` succeeds
+    Tcp(String),
+    /// `GET` of an `http://` URL answers 2xx
+    Http(String),
+    /// The command exits with status 0
+    Exec(String, Vec<String>),
+}
+
+impl HealthProbe {
+    async fn check(&self) -> bool {
+        match self {
+            HealthProbe::Tcp(addr) => tokio::net::TcpStream::connect(addr.as_str()).await.is_ok(),
+            HealthProbe::Http(url) => http_ok(url).await,
+            HealthProbe::Exec(program, args) => Command::new(program)
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .status()
+                .await
+                .is_ok_and(|status| status.success()),
+        }
+    }
+}
+
+/// `GET url` over plain HTTP/1.0 and check for a 2xx status
+async fn http_ok(url: &str) -> bool {
+    let Ok(url) = url::Url::parse(url) else {
+        return false;
+    };
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return false;
+    };
+    let Ok(mut stream) = tokio::net::TcpStream::connect((host, port)).await else {
+        return false;
+    };
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    let request = format!("GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n", path, host);
+    if stream.write_all(request.as_bytes()).await.is_err() {
+        return false;
+    }
+    let mut status_line = String::new();
+    if BufReader::new(stream).read_line(&mut status_line).await.is_err() {
+        return false;
+    }
+    status_line.split_whitespace().nth(1).is_some_and(|code| code.starts_with('2'))
+}
+
+/// Health probe with its schedule
+#[derive(Debug, Clone)]
+pub struct HealthCheck {
+    probe: HealthProbe,
+    interval: Duration,
+    timeout: Duration,
+    failures: u32,
+}
+
+impl HealthCheck {
+    /// Probe every 10 seconds, 5 second timeout, unhealthy after 3 failures in a row
+    pub fn new(probe: HealthProbe) -> Self {
+        Self {
+            probe,
+            interval: Duration::from_secs(10),
+            timeout: Duration::from_secs(5),
+            failures: 3,
+        }
+    }
+
+    /// Time between probes, the first probe runs one interval after the start
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Time after which a probe counts as failed
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Consecutive failed probes that make the process unhealthy (at least 1)
+    pub fn failures(mut self, failures: u32) -> Self {
+        self.failures = failures.max(1);
+        self
+    }
+}
+
+/// What to run and how to supervise it
+#[derive(Debug, Clone)]
+pub struct ProcessSpec {
+    name: String,
+    program: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    env_clear: bool,
+    cwd: Option<PathBuf>,
+    restart: RestartPolicy,
+    backoff_initial: Duration,
+    backoff_max: Duration,
+    max_restarts: Option<u32>,
+    stop_timeout: Duration,
+    health: Option<HealthCheck>,
+}
+
+impl ProcessSpec {
+    /// Run `program` as the process `name`
+    ///
+    /// Defaults: restart on failure, backoff from 1 to 60 seconds, no
+    /// restart limit, 10 second stop timeout, no health check.
+    pub fn new(name: &str, program: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            program: program.to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            env_clear: false,
+            cwd: None,
+            restart: RestartPolicy::default(),
+            backoff_initial: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(60),
+            max_restarts: None,
+            stop_timeout: Duration::from_secs(10),
+            health: None,
+        }
+    }
+
+    /// Process name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add an argument
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Add arguments
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set an environment variable
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Do not inherit the environment of the supervisor
+    pub fn env_clear(mut self) -> Self {
+        self.env_clear = true;
+        self
+    }
+
+    /// Working directory
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(dir.into());
+        self
+    }
+
+    /// Restart policy
+    pub fn restart(mut self, policy: RestartPolicy) -> Self {
+        self.restart = policy;
+        self
+    }
+
+    /// Delay before the first restart and its maximum
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff_initial = initial;
+        self.backoff_max = max.max(initial);
+        self
+    }
+
+    /// Give up after `max` consecutive restarts
+    pub fn max_restarts(mut self, max: u32) -> Self {
+        self.max_restarts = Some(max);
+        self
+    }
+
+    /// Time between SIGTERM and SIGKILL when stopping
+    pub fn stop_timeout(mut self, timeout: Duration) -> Self {
+        self.stop_timeout = timeout;
+        self
+    }
+
+    /// Health check of the running process
+    pub fn health(mut self, check: HealthCheck) -> Self {
+        self.health = Some(check);
+        self
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if self.env_clear {
+            command.env_clear();
+        }
+        command.envs(&self.env);
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        command
+    }
+
+    /// Backoff delay before restart number `attempt` (1-based)
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.backoff_initial.saturating_mul(factor).min(self.backoff_max)
+    }
+}
+
+/// Supervision state of a process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessState {
+    /// The process is running
+    Running,
+    /// Waiting for the backoff delay before a restart
+    Backoff,
+    /// Stopped by `Supervisor::stop`, or exited under `RestartPolicy::Never`
+    /// or `RestartPolicy::OnFailure` with status 0
+    Stopped,
+    /// Gave up: the restart limit was reached or the process could not be spawned
+    Failed(String),
+}
+
+/// Snapshot of a supervised process
+#[derive(Debug, Clone)]
+pub struct ProcessStatus {
+    pub name: String,
+    /// PID of the running process
+    pub pid: Option<u32>,
+    pub state: ProcessState,
+    /// Restarts since `spawn`
+    pub restarts: u32,
+    /// Result of the latest health probe run, `None` without health check
+    /// or before the first probe
+    pub healthy: Option<bool>,
+    /// Exit code of the latest exit, `None` when killed by a signal
+    pub last_exit: Option<i32>,
+    /// Start of the current run
+    pub started_at: Option<Instant>,
+}
+
+/// Stream an output line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// A line written by a supervised process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLine {
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+/// State shared between the supervisor and the supervision task
+struct Shared {
+    status: Mutex<ProcessStatus>,
+    output: Mutex<VecDeque<OutputLine>>,
+}
+
+impl Shared {
+    fn update(&self, f: impl FnOnce(&mut ProcessStatus)) {
+        f(&mut self.status.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    fn status(&self) -> ProcessStatus {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+struct Managed {
+    shared: Arc<Shared>,
+    stop: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+/// Supervisor of child processes, see the module documentation
+#[derive(Default)]
+pub struct Supervisor {
+    processes: tokio::sync::Mutex<HashMap<String, Managed>>,
+}
+
+impl Supervisor {
+    /// Create an empty supervisor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawn and supervise a process, returning its PID
+    ///
+    /// # Errors
+    ///
+    /// `RfError::InvalidParameter` when a process with the same name is still
+    /// supervised, `RfError::Io` when the first spawn fails
+    pub async fn spawn(&self, spec: ProcessSpec) -> Result<u32> {
+        let mut processes = self.processes.lock().await;
+        if let Some(existing) = processes.get(&spec.name) {
+            if !existing.task.is_finished() {
+                return Err(RfError::InvalidParameter(format!("Process '{}' is already supervised", spec.name)));
+            }
+        }
+
+        let shared = Arc::new(Shared {
+            status: Mutex::new(ProcessStatus {
+                name: spec.name.clone(),
+                pid: None,
+                state: ProcessState::Running,
+                restarts: 0,
+                healthy: None,
+                last_exit: None,
+                started_at: None,
+            }),
+            output: Mutex::new(VecDeque::new()),
+        });
+        let child = start(&spec, &shared)?;
+        let pid = child.id().unwrap_or_default();
+        tracing::info!("Started process '{}' (pid {})", spec.name, pid);
+
+        let (stop, stop_rx) = watch::channel(false);
+        let name = spec.name.clone();
+        let task = tokio::spawn(supervise(spec, child, Arc::clone(&shared), stop_rx));
+        processes.insert(name, Managed { shared, stop, task });
+        Ok(pid)
+    }
+
+    /// Stop a process gracefully and stop supervising it
+    ///
+    /// # Errors
+    ///
+    /// `RfError::NotFound` for unknown names
+    pub async fn stop(&self, name: &str) -> Result<ProcessStatus> {
+        let managed = self
+            .processes
+            .lock()
+            .await
+            .remove(name)
+            .ok_or_else(|| RfError::NotFound(format!("Process '{}' is not supervised", name)))?;
+        let _ = managed.stop.send(true);
+        let _ = managed.task.await;
+        Ok(managed.shared.status())
+    }
+
+    /// Stop every process, concurrently
+    pub async fn stop_all(&self) -> Vec<ProcessStatus> {
+        let processes: Vec<Managed> = self.processes.lock().await.drain().map(|(_, managed)| managed).collect();
+        for managed in &processes {
+            let _ = managed.stop.send(true);
+        }
+        let mut statuses = Vec::with_capacity(processes.len());
+        for managed in processes {
+            let _ = managed.task.await;
+            statuses.push(managed.shared.status());
+        }
+        statuses
+    }
+
+    /// Stop a process and spawn it again with the same spec
+    pub async fn restart(&self, spec: ProcessSpec) -> Result<u32> {
+        if let Err(e) = self.stop(&spec.name).await {
+            tracing::debug!("Restarting process '{}' that was not running: {}", spec.name, e);
+        }
+        self.spawn(spec).await
+    }
+
+    /// Status of a process
+    pub async fn status(&self, name: &str) -> Option<ProcessStatus> {
+        self.processes.lock().await.get(name).map(|managed| managed.shared.status())
+    }
+
+    /// Status of every process, sorted by name
+    pub async fn list(&self) -> Vec<ProcessStatus> {
+        let mut statuses: Vec<ProcessStatus> =
+            self.processes.lock().await.values().map(|managed| managed.shared.status()).collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+
+    /// Latest output lines of a process, oldest first
+    pub async fn output(&self, name: &str) -> Vec<OutputLine> {
+        match self.processes.lock().await.get(name) {
+            Some(managed) => managed.shared.output.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        // Children are killed on drop of their handles
+        for managed in self.processes.get_mut().values() {
+            managed.task.abort();
+        }
+    }
+}
+
+/// Spawn the process and its output readers
+fn start(spec: &ProcessSpec, shared: &Arc<Shared>) -> Result<Child> {
+    let mut child = spec.command().spawn().map_err(RfError::Io)?;
+    if let Some(stdout) = child.stdout.take() {
+        tokio::spawn(forward(stdout, OutputStream::Stdout, spec.name.clone(), Arc::clone(shared)));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(forward(stderr, OutputStream::Stderr, spec.name.clone(), Arc::clone(shared)));
+    }
+    shared.update(|status| {
+        status.pid = child.id();
+        status.state = ProcessState::Running;
+        status.healthy = None;
+        status.started_at = Some(Instant::now());
+    });
+    Ok(child)
+}
+
+/// Log the lines of one output stream and keep the latest ones
+async fn forward(reader: impl AsyncRead + Unpin, stream: OutputStream, name: String, shared: Arc<Shared>) {
+    let logger = crate::log::logger(&name);
+    let logger = match stream {
+        OutputStream::Stdout => logger.field("stream", "stdout"),
+        OutputStream::Stderr => logger.field("stream", "stderr"),
+    };
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        match stream {
+            OutputStream::Stdout => logger.info(&line),
+            OutputStream::Stderr => logger.warn(&line),
+        }
+        let mut output = shared.output.lock().unwrap_or_else(|e| e.into_inner());
+        if output.len() == OUTPUT_LINES {
+            output.pop_front();
+        }
+        output.push_back(OutputLine { stream, line });
+    }
+}
+
+/// Returns once the health check failed `failures` times in a row; never without a check
+async fn unhealthy(check: Option<&HealthCheck>, shared: &Shared) {
+    let Some(check) = check else {
+        return std::future::pending().await;
+    };
+    let mut failed = 0;
+    loop {
+        tokio::time::sleep(check.interval).await;
+        let healthy = tokio::time::timeout(check.timeout, check.probe.check()).await.unwrap_or(false);
+        shared.update(|status| status.healthy = Some(healthy));
+        failed = if healthy { 0 } else { failed + 1 };
+        if failed >= check.failures {
+            return;
+        }
+    }
+}
+
+/// SIGTERM, then SIGKILL after `timeout`
+async fn terminate(child: &mut Child, timeout: Duration) -> Option<i32> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: plain syscall on the PID of a child that has not been reaped yet
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+        if let Ok(Ok(status)) = tokio::time::timeout(timeout, child.wait()).await {
+            return status.code();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = timeout;
+    let _ = child.kill().await;
+    child.wait().await.ok().and_then(|status| status.code())
+}
+
+/// Supervision loop of one process
+async fn supervise(spec: ProcessSpec, mut child: Child, shared: Arc<Shared>, mut stop: watch::Receiver<bool>) {
+    let mut attempt = 0u32;
+    loop {
+        let started = Instant::now();
+        let (code, failed) = tokio::select! {
+            status = child.wait() => {
+                let code = status.ok().and_then(|status| status.code());
+                tracing::info!("Process '{}' exited with {:?}", spec.name, code);
+                (code, code != Some(0))
+            }
+            _ = unhealthy(spec.health.as_ref(), &shared) => {
+                tracing::warn!("Process '{}' failed its health check, stopping it", spec.name);
+                (terminate(&mut child, spec.stop_timeout).await, true)
+            }
+            _ = stop.changed() => {
+                tracing::info!("Stopping process '{}'", spec.name);
+                let code = terminate(&mut child, spec.stop_timeout).await;
+                shared.update(|status| {
+                    status.pid = None;
+                    status.last_exit = code;
+                    status.state = ProcessState::Stopped;
+                });
+                return;
+            }
+        };
+        shared.update(|status| {
+            status.pid = None;
+            status.last_exit = code;
+        });
+
+        let restart = match spec.restart {
+            RestartPolicy::Never => false,
+            RestartPolicy::Always => true,
+            RestartPolicy::OnFailure => failed,
+        };
+        if !restart {
+            shared.update(|status| status.state = ProcessState::Stopped);
+            return;
+        }
+
+        if started.elapsed() > spec.backoff_max {
+            attempt = 0;
+        }
+        // Each failed spawn counts as a restart too
+        loop {
+            attempt += 1;
+            if spec.max_restarts.is_some_and(|max| attempt > max) {
+                tracing::error!("Process '{}' reached its restart limit", spec.name);
+                shared.update(|status| {
+                    status.state = ProcessState::Failed(format!("restarted {} times in a row", attempt - 1))
+                });
+                return;
+            }
+            let delay = spec.delay(attempt);
+            shared.update(|status| status.state = ProcessState::Backoff);
+            tracing::info!("Restarting process '{}' in {:?}", spec.name, delay);
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = stop.changed() => {
+                    shared.update(|status| status.state = ProcessState::Stopped);
+                    return;
+                }
+            }
+            shared.update(|status| status.restarts += 1);
+            match start(&spec, &shared) {
+                Ok(restarted) => {
+                    tracing::info!("Restarted process '{}' (pid {:?})", spec.name, restarted.id());
+                    child = restarted;
+                    break;
+                }
+                Err(e) => {
+                    tracing::error!("Failed to restart process '{}': {}", spec.name, e);
+                    shared.update(|status| status.state = ProcessState::Failed(e.to_string()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,416 @@
+//! # report
+//!
+//! report 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Error reporting
+//!
+//! An `ErrorReport` describes a failure worth alerting on: a 5xx response,
+//! a panicking handler or a failed background job. It carries the `RfError`
+//! and the context it happened in (request method, path, headers, request ID
+//! or job name). `ErrorReporting` samples reports, scrubs personal data from
+//! them and hands them to its `ErrorReporter`s, such as the Sentry and
+//! webhook senders of `rf_net::report`, or any closure:
+//!
+//! ```rust,ignore
+//! use rf_os::report::{self, ErrorReporting, PiiScrubber};
+//!
+//! let reporting = ErrorReporting::new()
+//!     .reporter(SentrySender::new(&dsn)?)
+//!     .reporter(|report: &ErrorReport| {
+//!         tracing::error!("{}: {}", report.kind, report.message);
+//!         Ok(())
+//!     })
+//!     .sample_rate(0.25)
+//!     .scrubber(PiiScrubber::new().key("national_id"));
+//! report::set_global(reporting);
+//! ```
+//!
+//! The global reporting is used by `rf_os::cron` for jobs that panic or time
+//! out, and by `HttpServer` unless the server or a route group is given its
+//! own.
+//!
+//! Errors are kept with probability `sample_rate` (1 by default) and panics
+//! with `panic_sample_rate` (also 1). Scrubbing happens before any reporter
+//! sees the report: values of sensitive headers, query parameters and tags
+//! are replaced by `[Filtered]`, e-mail addresses and card numbers are
+//! masked in messages, and the client IP is dropped.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use regex::Regex;
+use rf_errors::{Result, RfError};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::sync::{Arc, LazyLock};
+
+/// Replacement for scrubbed values
+pub const FILTERED: &str = "[Filtered]";
+
+/// Keys whose values are always scrubbed; matched case-insensitively as substrings
+const SENSITIVE_KEYS: &[&str] = &[
+    "authorization",
+    "cookie",
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "api-key",
+    "session",
+    "csrf",
+    "credit_card",
+    "card_number",
+];
+
+/// What went wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    /// An error result, e.g. a 5xx response or a timed out job
+    Error,
+    /// A panic
+    Panic,
+}
+
+impl std::fmt::Display for ReportKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ReportKind::Error => "error",
+            ReportKind::Panic => "panic",
+        })
+    }
+}
+
+/// Where a report comes from; fields that do not apply are `None`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReportContext {
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub query: Option<String>,
+    /// Route pattern that matched, e.g. `/users/{id}`
+    pub route: Option<String>,
+    pub status: Option<u16>,
+    pub request_id: Option<String>,
+    pub client_ip: Option<String>,
+    /// Request headers, lowercase names
+    pub headers: BTreeMap<String, String>,
+    /// Background job name
+    pub job: Option<String>,
+    /// Free-form tags, e.g. the service or environment
+    pub tags: BTreeMap<String, String>,
+}
+
+/// A failure to report, see the module documentation
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    /// 32 hex digits, unique per report
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub kind: ReportKind,
+    #[serde(serialize_with = "serialize_error")]
+    pub error: Arc<RfError>,
+    /// Error message, scrubbed
+    pub message: String,
+    pub context: ReportContext,
+}
+
+fn serialize_error<S: Serializer>(error: &Arc<RfError>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    error.code().serialize(serializer)
+}
+
+impl ErrorReport {
+    /// Report `error` with an empty context
+    pub fn new(kind: ReportKind, error: impl Into<Arc<RfError>>) -> Self {
+        let error = error.into();
+        Self {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            timestamp: Utc::now(),
+            kind,
+            message: error.to_string(),
+            error,
+            context: ReportContext::default(),
+        }
+    }
+
+    /// Report a panic with its payload message
+    pub fn panic(message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self::new(ReportKind::Panic, RfError::Internal(format!("panic: {}", message)))
+    }
+
+    /// Set the context
+    pub fn context(mut self, context: ReportContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Add a tag
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.context.tags.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Message of a panic payload, as given to `catch_unwind` or a `JoinError`
+pub fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panicked".to_string())
+}
+
+/// Receives the reports that pass sampling and scrubbing
+#[async_trait]
+pub trait ErrorReporter: Send + Sync {
+    async fn report(&self, report: &ErrorReport) -> Result<()>;
+}
+
+#[async_trait]
+impl<F> ErrorReporter for F
+where
+    F: Fn(&ErrorReport) -> Result<()> + Send + Sync,
+{
+    async fn report(&self, report: &ErrorReport) -> Result<()> {
+        self(report)
+    }
+}
+
+/// Removes personal data from reports
+#[derive(Debug, Clone)]
+pub struct PiiScrubber {
+    keys: Vec<String>,
+    patterns: Vec<(Regex, String)>,
+    keep_ip: bool,
+}
+
+impl Default for PiiScrubber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PiiScrubber {
+    /// Scrub the built-in sensitive keys, e-mail addresses and card numbers
+    pub fn new() -> Self {
+        Self {
+            keys: SENSITIVE_KEYS.iter().map(|key| key.to_string()).collect(),
+            patterns: vec![
+                (Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap(), "[email]".to_string()),
+                (Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").unwrap(), "[card]".to_string()),
+            ],
+            keep_ip: false,
+        }
+    }
+
+    /// Also scrub values whose key contains `key` (case-insensitive)
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.keys.push(key.into().to_lowercase());
+        self
+    }
+
+    /// Replace matches of `pattern` in messages and values with `replacement`
+    ///
+    /// # Errors
+    ///
+    /// `RfError::InvalidParameter` when the pattern is not a valid regex
+    pub fn pattern(mut self, pattern: &str, replacement: impl Into<String>) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| RfError::InvalidParameter(format!("Invalid scrub pattern '{}': {}", pattern, e)))?;
+        self.patterns.push((regex, replacement.into()));
+        Ok(self)
+    }
+
+    /// Keep the client IP (dropped by default)
+    pub fn keep_ip(mut self, keep: bool) -> Self {
+        self.keep_ip = keep;
+        self
+    }
+
+    /// Whether values under `key` are scrubbed
+    pub fn is_sensitive(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.keys.iter().any(|sensitive| key.contains(sensitive.as_str()))
+    }
+
+    /// `text` with the patterns masked
+    pub fn scrub_text(&self, text: &str) -> String {
+        self.patterns
+            .iter()
+            .fold(text.to_string(), |text, (regex, replacement)| regex.replace_all(&text, replacement.as_str()).into_owned())
+    }
+
+    /// `query` with sensitive parameters filtered and the rest masked
+    pub fn scrub_query(&self, query: &str) -> String {
+        query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, _)) if self.is_sensitive(key) => format!("{}={}", key, FILTERED),
+                Some((key, value)) => format!("{}={}", key, self.scrub_text(value)),
+                None => pair.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    fn scrub_map(&self, map: &mut BTreeMap<String, String>) {
+        for (key, value) in map.iter_mut() {
+            *value = if self.is_sensitive(key) { FILTERED.to_string() } else { self.scrub_text(value) };
+        }
+    }
+
+    /// Scrub a report in place
+    pub fn scrub(&self, report: &mut ErrorReport) {
+        report.message = self.scrub_text(&report.message);
+        let context = &mut report.context;
+        context.path = context.path.as_deref().map(|path| self.scrub_text(path));
+        context.query = context.query.as_deref().map(|query| self.scrub_query(query));
+        self.scrub_map(&mut context.headers);
+        self.scrub_map(&mut context.tags);
+        if !self.keep_ip {
+            context.client_ip = None;
+        }
+    }
+}
+
+/// Samples, scrubs and dispatches reports, see the module documentation
+#[derive(Clone)]
+pub struct ErrorReporting {
+    reporters: Vec<Arc<dyn ErrorReporter>>,
+    sample_rate: f64,
+    panic_sample_rate: f64,
+    scrubber: PiiScrubber,
+    tags: BTreeMap<String, String>,
+}
+
+impl Default for ErrorReporting {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ErrorReporting {
+    /// Reporting without reporters; every report is kept
+    pub fn new() -> Self {
+        Self {
+            reporters: Vec::new(),
+            sample_rate: 1.0,
+            panic_sample_rate: 1.0,
+            scrubber: PiiScrubber::new(),
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Add a reporter
+    pub fn reporter(mut self, reporter: impl ErrorReporter + 'static) -> Self {
+        self.reporters.push(Arc::new(reporter));
+        self
+    }
+
+    /// Fraction of errors reported, clamped to 0..=1
+    pub fn sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Fraction of panics reported, clamped to 0..=1
+    pub fn panic_sample_rate(mut self, rate: f64) -> Self {
+        self.panic_sample_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Replace the default scrubber
+    pub fn scrubber(mut self, scrubber: PiiScrubber) -> Self {
+        self.scrubber = scrubber;
+        self
+    }
+
+    /// Tag every report, e.g. with the environment or release
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    fn sampled(&self, kind: ReportKind) -> bool {
+        let rate = match kind {
+            ReportKind::Error => self.sample_rate,
+            ReportKind::Panic => self.panic_sample_rate,
+        };
+        rate >= 1.0 || (rate > 0.0 && rand::random::<f64>() < rate)
+    }
+
+    /// Sample and scrub `report`, `None` when it is dropped
+    fn prepare(&self, mut report: ErrorReport) -> Option<ErrorReport> {
+        if self.reporters.is_empty() || !self.sampled(report.kind) {
+            return None;
+        }
+        for (key, value) in &self.tags {
+            report.context.tags.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        self.scrubber.scrub(&mut report);
+        Some(report)
+    }
+
+    /// Send `report` to every reporter and wait for them
+    ///
+    /// Returns `false` when the report was sampled out. Reporter failures are
+    /// logged, never returned: reporting must not fail the caller.
+    pub async fn report(&self, report: ErrorReport) -> bool {
+        let Some(report) = self.prepare(report) else {
+            return false;
+        };
+        let sends = self.reporters.iter().map(|reporter| reporter.report(&report));
+        for result in futures::future::join_all(sends).await {
+            if let Err(e) = result {
+                tracing::warn!("Failed to send error report {}: {}", report.id, e);
+            }
+        }
+        true
+    }
+
+    /// Send `report` in the background
+    ///
+    /// Outside a Tokio runtime the report is sent on a short-lived thread.
+    pub fn capture(&self, report: ErrorReport) {
+        let reporting = self.clone();
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    reporting.report(report).await;
+                });
+            }
+            Err(_) => {
+                std::thread::spawn(move || {
+                    match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                        Ok(runtime) => {
+                            runtime.block_on(reporting.report(report));
+                        }
+                        Err(e) => tracing::warn!("Failed to send error report: {}", e),
+                    }
+                });
+            }
+        }
+    }
+}
+
+static GLOBAL: LazyLock<RwLock<Option<Arc<ErrorReporting>>>> = LazyLock::new(|| RwLock::new(None));
+
+/// Install the process-wide reporting
+pub fn set_global(reporting: ErrorReporting) {
+    *GLOBAL.write() = Some(Arc::new(reporting));
+}
+
+/// Remove the process-wide reporting
+pub fn clear_global() {
+    *GLOBAL.write() = None;
+}
+
+/// The process-wide reporting, if one is installed
+pub fn global() -> Option<Arc<ErrorReporting>> {
+    GLOBAL.read().clone()
+}
@@ -11,72 +11,193 @@ pub mod storage;
 
 pub use storage::*;
 
-use axum_sessions::async_session::Session;
-use rf_errors::Result;
+pub use async_session::Session;
+
+use crate::cfg::RfDuration;
+use rf_errors::{Result, RfError};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Session storage backend selected from configuration
+///
+/// ```yaml
+/// session:
+///   ttl: 86400
+///   storage:
+///     type: redis          # memory | redis | file | database
+///     url: redis://127.0.0.1:6379
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SessionStorageConfig {
+    #[default]
+    Memory,
+    Redis {
+        url: String,
+        #[serde(default = "default_prefix")]
+        prefix: String,
+    },
+    File {
+        path: String,
+    },
+    Database {
+        /// `postgres://`, `mysql://` or `sqlite:` URL
+        url: String,
+        #[serde(default = "default_table")]
+        table: String,
+    },
+}
+
+fn default_prefix() -> String {
+    "session:".to_string()
+}
+
+fn default_table() -> String {
+    "rf_session".to_string()
+}
+
+/// Session manager configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    pub storage: SessionStorageConfig,
+    /// Session lifetime, e.g. `"2h"`; plain numbers are seconds
+    pub ttl: RfDuration,
+    /// Extend the lifetime on every access
+    pub sliding: bool,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            storage: SessionStorageConfig::Memory,
+            ttl: RfDuration::from_secs(3600),
+            sliding: false,
+        }
+    }
+}
+
 /// Session manager
 pub struct SessionManager {
-    storage: Box<dyn SessionStorage>,
-    ttl: Option<std::time::Duration>,
+    storage: Arc<dyn SessionStorage>,
+    ttl: Option<Duration>,
+    sliding: bool,
 }
 
 impl SessionManager {
     /// Create a new session manager with memory storage
     pub fn new() -> Self {
         Self {
-            storage: Box::new(MemorySessionStorage::new()),
+            storage: Arc::new(MemorySessionStorage::new()),
             ttl: None,
+            sliding: false,
         }
     }
 
     /// Create a new session manager with custom storage
     pub fn with_storage(storage: Box<dyn SessionStorage>) -> Self {
         Self {
-            storage,
+            storage: Arc::from(storage),
             ttl: None,
+            sliding: false,
         }
     }
 
+    /// Create a session manager from configuration, connecting the storage
+    pub async fn from_config(config: &SessionConfig) -> Result<Self> {
+        let storage: Box<dyn SessionStorage> = match &config.storage {
+            SessionStorageConfig::Memory => Box::new(MemorySessionStorage::new()),
+            SessionStorageConfig::Redis { url, prefix } => {
+                Box::new(RedisSessionStorage::new(url).await?.with_prefix(prefix))
+            }
+            SessionStorageConfig::File { path } => Box::new(FileSessionStorage::new(path)?),
+            SessionStorageConfig::Database { url, table } => {
+                let database = if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+                    rf_database::db::Database::new_postgres(url).await?
+                } else if url.starts_with("mysql://") {
+                    rf_database::db::Database::new_mysql(url).await?
+                } else if url.starts_with("sqlite:") {
+                    rf_database::db::Database::new_sqlite(url).await?
+                } else {
+                    return Err(RfError::Config(format!("Unsupported session database URL: {}", url)));
+                };
+                let storage = DatabaseSessionStorage::new(Arc::new(database), table);
+                storage.ensure_table().await?;
+                Box::new(storage)
+            }
+        };
+        let manager = Self::with_storage(storage).with_ttl(config.ttl.into());
+        Ok(if config.sliding { manager.with_sliding_expiration() } else { manager })
+    }
+
     /// Set TTL for sessions
     pub fn with_ttl(mut self, ttl: std::time::Duration) -> Self {
         self.ttl = Some(ttl);
         self
     }
 
+    /// Extend the session lifetime on every access
+    pub fn with_sliding_expiration(mut self) -> Self {
+        self.sliding = true;
+        self
+    }
+
+    /// Session TTL, if set
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Whether sessions are extended on every access
+    pub fn is_sliding(&self) -> bool {
+        self.sliding
+    }
+
     /// Generate a new session ID
     pub fn generate_session_id(&self) -> String {
         Uuid::new_v4().to_string()
     }
 
-    /// Get a session by ID
-    pub fn get(&self, id: &str) -> Result<Option<Session>> {
-        self.storage.get(id)
+    /// Create an empty session expiring after the TTL
+    pub fn create(&self) -> Session {
+        let mut session = Session::new();
+        if let Some(ttl) = self.ttl {
+            session.expire_in(ttl);
+        }
+        session
+    }
+
+    /// Get a session by ID, `None` if missing or expired
+    pub async fn get(&self, id: &str) -> Result<Option<Session>> {
+        Ok(self.storage.get(id).await?.and_then(Session::validate))
     }
 
     /// Store a session
-    pub fn store(&self, session: Session) -> Result<()> {
-        self.storage.store(session)
+    pub async fn store(&self, session: Session) -> Result<()> {
+        self.storage.store(session).await
     }
 
     /// Delete a session
-    pub fn delete(&self, id: &str) -> Result<()> {
-        self.storage.delete(id)
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        self.storage.delete(id).await
     }
 
     /// Clean up expired sessions (for file/database storage)
-    pub fn cleanup_expired(&self) -> Result<()> {
-        self.storage.cleanup_expired()
+    pub async fn cleanup_expired(&self) -> Result<()> {
+        self.storage.cleanup_expired().await
     }
 
     /// Start automatic expiration cleanup task
     pub fn start_cleanup_task(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
-        let storage = self.storage.as_ref();
+        let storage = self.storage.clone();
         tokio::spawn(async move {
             let mut interval_timer = tokio::time::interval(interval);
             loop {
                 interval_timer.tick().await;
-                let _ = storage.cleanup_expired();
+                if let Err(e) = storage.cleanup_expired().await {
+                    tracing::warn!("Session cleanup failed: {}", e);
+                }
             }
         })
     }
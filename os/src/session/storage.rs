@@ -6,13 +6,16 @@
 //! @date 2026-01-06
 
 //! Session storage adapters
+//!
+//! Sessions are stored with their data and expiry (serialized as JSON for
+//! the file, Redis and database adapters), keyed by the session ID.
 
-use axum_sessions::async_session::Session;
-use rf_errors::Result;
+use async_session::Session;
+use async_trait::async_trait;
+use rf_errors::{Result, RfError};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use serde_json;
 
 /// Type alias for session with expiration timestamp
 type SessionEntry = (Session, chrono::DateTime<chrono::Utc>);
@@ -39,27 +42,40 @@ impl Default for ExpirationPolicy {
 }
 
 /// Session storage trait
+#[async_trait]
 pub trait SessionStorage: Send + Sync {
     /// Get a session by ID
-    fn get(&self, id: &str) -> Result<Option<Session>>;
-    
+    async fn get(&self, id: &str) -> Result<Option<Session>>;
+
     /// Store a session
-    fn store(&self, session: Session) -> Result<()>;
-    
+    async fn store(&self, session: Session) -> Result<()>;
+
     /// Delete a session
-    fn delete(&self, id: &str) -> Result<()>;
-    
+    async fn delete(&self, id: &str) -> Result<()>;
+
     /// Clean up expired sessions
-    fn cleanup_expired(&self) -> Result<()> {
+    async fn cleanup_expired(&self) -> Result<()> {
         Ok(()) // Default implementation does nothing
     }
-    
+
     /// Set expiration policy
     fn set_expiration_policy(&mut self, _policy: ExpirationPolicy) {
         // Default implementation does nothing
     }
 }
 
+fn ttl_duration(ttl: std::time::Duration) -> Result<chrono::Duration> {
+    chrono::Duration::from_std(ttl).map_err(|e| RfError::Internal(format!("Invalid TTL duration: {}", e)))
+}
+
+fn serialize(session: &Session) -> Result<Vec<u8>> {
+    serde_json::to_vec(session).map_err(|e| RfError::Serialization(format!("Failed to serialize session: {}", e)))
+}
+
+fn deserialize(data: &[u8]) -> Result<Session> {
+    serde_json::from_slice(data).map_err(|e| RfError::Serialization(format!("Failed to deserialize session: {}", e)))
+}
+
 /// Memory-based session storage
 pub struct MemorySessionStorage {
     sessions: SessionsMap,
@@ -76,56 +92,58 @@ impl MemorySessionStorage {
     }
 }
 
+#[async_trait]
 impl SessionStorage for MemorySessionStorage {
-    fn get(&self, id: &str) -> Result<Option<Session>> {
-        let mut sessions = futures::executor::block_on(self.sessions.write());
+    async fn get(&self, id: &str) -> Result<Option<Session>> {
+        let mut sessions = self.sessions.write().await;
         let now = chrono::Utc::now();
-        
+
         if let Some((session, expiration)) = sessions.get(id) {
             // Check if expired
             if now > *expiration {
                 sessions.remove(id);
                 return Ok(None);
             }
-            
+
             // Clone session before modifying sessions map
             let session_clone = session.clone();
-            
+
             // If sliding expiration, update expiration time
             if self.expiration_policy.sliding_expiration {
-                let new_expiration = now + chrono::Duration::from_std(self.expiration_policy.default_ttl)
-                    .map_err(|e| rf_errors::RfError::Internal(format!("Invalid TTL duration: {}", e)))?;
+                let new_expiration = now + ttl_duration(self.expiration_policy.default_ttl)?;
                 sessions.insert(id.to_string(), (session_clone.clone(), new_expiration));
             }
-            
+
             Ok(Some(session_clone))
         } else {
             Ok(None)
         }
     }
-    
-    fn store(&self, session: Session) -> Result<()> {
-        let mut sessions = futures::executor::block_on(self.sessions.write());
+
+    async fn store(&self, session: Session) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
         let session_id = session.id().to_string();
-        let expiration = chrono::Utc::now() + chrono::Duration::from_std(self.expiration_policy.default_ttl)
-            .map_err(|e| rf_errors::RfError::Internal(format!("Invalid TTL duration: {}", e)))?;
+        let expiration = match session.expiry() {
+            Some(expiry) => *expiry,
+            None => chrono::Utc::now() + ttl_duration(self.expiration_policy.default_ttl)?,
+        };
         sessions.insert(session_id, (session, expiration));
         Ok(())
     }
-    
-    fn delete(&self, id: &str) -> Result<()> {
-        let mut sessions = futures::executor::block_on(self.sessions.write());
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
         sessions.remove(id);
         Ok(())
     }
-    
-    fn cleanup_expired(&self) -> Result<()> {
-        let mut sessions = futures::executor::block_on(self.sessions.write());
+
+    async fn cleanup_expired(&self) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
         let now = chrono::Utc::now();
         sessions.retain(|_, (_, expiration)| *expiration > now);
         Ok(())
     }
-    
+
     fn set_expiration_policy(&mut self, policy: ExpirationPolicy) {
         self.expiration_policy = policy;
     }
@@ -149,7 +167,7 @@ impl FileSessionStorage {
     /// Create a new file session storage
     pub fn new(base_path: &str) -> Result<Self> {
         std::fs::create_dir_all(base_path)
-            .map_err(rf_errors::RfError::Io)?;
+            .map_err(RfError::Io)?;
         Ok(Self {
             base_path: base_path.to_string(),
             ttl: None,
@@ -170,9 +188,19 @@ impl FileSessionStorage {
         self.crypto_key = Some(key);
         self
     }
-    
+
     fn session_path(&self, id: &str) -> String {
-        format!("{}/{}.session", self.base_path, id)
+        // Session IDs are standard base64, which may contain '/'
+        let name: String = id
+            .chars()
+            .map(|c| match c {
+                '/' => '_',
+                '+' => '-',
+                '=' => '.',
+                c => c,
+            })
+            .collect();
+        format!("{}/{}.session", self.base_path, name)
     }
 
     fn check_expired(&self, path: &str) -> bool {
@@ -189,8 +217,9 @@ impl FileSessionStorage {
     }
 }
 
+#[async_trait]
 impl SessionStorage for FileSessionStorage {
-    fn get(&self, id: &str) -> Result<Option<Session>> {
+    async fn get(&self, id: &str) -> Result<Option<Session>> {
         let path = self.session_path(id);
         if !std::path::Path::new(&path).exists() {
             return Ok(None);
@@ -198,15 +227,15 @@ impl SessionStorage for FileSessionStorage {
 
         // Check if expired
         if self.check_expired(&path) {
-            let _ = std::fs::remove_file(&path);
+            let _ = tokio::fs::remove_file(&path).await;
             return Ok(None);
         }
 
-        let content = std::fs::read(&path)
-            .map_err(rf_errors::RfError::Io)?;
-        
+        let content = tokio::fs::read(&path).await
+            .map_err(RfError::Io)?;
+
         // Decrypt if enabled
-        let _data = if self.crypto_enabled {
+        let data = if self.crypto_enabled {
             if self.crypto_key.is_some() {
                 // Simplified - would use AES decryption
                 content
@@ -217,25 +246,18 @@ impl SessionStorage for FileSessionStorage {
             content
         };
 
-        // Deserialize Session from JSON
-        let _session_data: serde_json::Value = serde_json::from_slice(&_data)
-            .map_err(|e| rf_errors::RfError::Internal(format!("Failed to deserialize session: {}", e)))?;
-        
-        // Create Session from data (simplified)
-        // In full implementation, would reconstruct Session object
-        Ok(None)
+        let session = deserialize(&data)?;
+        if session.is_expired() {
+            let _ = tokio::fs::remove_file(&path).await;
+            return Ok(None);
+        }
+        Ok(Some(session))
     }
-    
-    fn store(&self, session: Session) -> Result<()> {
+
+    async fn store(&self, session: Session) -> Result<()> {
         let path = self.session_path(session.id());
-        
-        // Serialize Session to JSON (simplified)
-        // Note: Session doesn't expose data() directly, would need to use internal methods
-        let data = serde_json::to_vec(&serde_json::json!({
-            "id": session.id().to_string(),
-        }))
-        .map_err(|e| rf_errors::RfError::Internal(format!("Failed to serialize session: {}", e)))?;
-        
+        let data = serialize(&session)?;
+
         // Encrypt if enabled
         let final_data = if self.crypto_enabled {
             if self.crypto_key.is_some() {
@@ -247,17 +269,38 @@ impl SessionStorage for FileSessionStorage {
         } else {
             data
         };
-        
-        std::fs::write(&path, final_data)
-            .map_err(rf_errors::RfError::Io)?;
+
+        tokio::fs::write(&path, final_data).await
+            .map_err(RfError::Io)?;
         Ok(())
     }
-    
-    fn delete(&self, id: &str) -> Result<()> {
+
+    async fn delete(&self, id: &str) -> Result<()> {
         let path = self.session_path(id);
         if std::path::Path::new(&path).exists() {
-            std::fs::remove_file(&path)
-                .map_err(rf_errors::RfError::Io)?;
+            tokio::fs::remove_file(&path).await
+                .map_err(RfError::Io)?;
+        }
+        Ok(())
+    }
+
+    async fn cleanup_expired(&self) -> Result<()> {
+        let mut entries = tokio::fs::read_dir(&self.base_path).await.map_err(RfError::Io)?;
+        while let Some(entry) = entries.next_entry().await.map_err(RfError::Io)? {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "session") {
+                continue;
+            }
+            let path = path.to_string_lossy().into_owned();
+            let expired = self.check_expired(&path)
+                || tokio::fs::read(&path)
+                    .await
+                    .ok()
+                    .and_then(|data| deserialize(&data).ok())
+                    .is_some_and(|session| session.is_expired());
+            if expired {
+                let _ = tokio::fs::remove_file(&path).await;
+            }
         }
         Ok(())
     }
@@ -298,97 +341,46 @@ impl RedisSessionStorage {
     }
 }
 
+#[async_trait]
 impl SessionStorage for RedisSessionStorage {
-    fn get(&self, id: &str) -> Result<Option<Session>> {
-        let key = self.session_key(id);
-        let rt = tokio::runtime::Handle::try_current()
-            .or_else(|_| tokio::runtime::Runtime::new().map(|rt| rt.handle().clone()))?;
-        
-        match rt.block_on(self.client.get(&key)) {
-            Ok(data) => {
-                // Deserialize Session from JSON
-                let session_data: serde_json::Value = serde_json::from_str(&data)
-                    .map_err(|e| rf_errors::RfError::Internal(format!("Failed to deserialize session: {}", e)))?;
-                
-                // Extract session ID from JSON
-                let _session_id = session_data.get("id")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| rf_errors::RfError::Internal("Session data missing 'id' field".to_string()))?;
-                
-                // Create a new Session
-                // Note: This creates a basic session. Full deserialization would require
-                // access to Session internals to restore all session data.
-                // For axum-sessions, we create a new session. The framework will handle ID management.
-                // The application layer should handle restoring session data if needed.
-                // This is a limitation of the current axum-sessions API which doesn't expose
-                // direct serialization/deserialization methods.
-                let session = Session::new();
-                Ok(Some(session))
-            }
-            Err(_) => Ok(None),
-        }
+    async fn get(&self, id: &str) -> Result<Option<Session>> {
+        self.client.get_json(&self.session_key(id)).await
     }
-    
-    fn store(&self, session: Session) -> Result<()> {
+
+    async fn store(&self, session: Session) -> Result<()> {
         let key = self.session_key(session.id());
-        
-        // Serialize Session to JSON
-        // Note: Session doesn't expose data() directly, we serialize the session ID
-        // and attempt to serialize session data if available
-        let session_id = session.id().to_string();
-        
-        // Try to get session data as JSON value
-        // axum_sessions Session may have internal data that can be accessed
-        let data = serde_json::to_string(&serde_json::json!({
-            "id": session_id,
-            // Note: Full session data serialization would require access to Session internals
-            // For now, we store the ID and reconstruct a basic session on deserialization
-            // This is a limitation - full session data cannot be restored without access to Session internals
-        }))
-        .map_err(|e| rf_errors::RfError::Internal(format!("Failed to serialize session: {}", e)))?;
-        
-        let rt = tokio::runtime::Handle::try_current()
-            .or_else(|_| tokio::runtime::Runtime::new().map(|rt| rt.handle().clone()))?;
-        
-        // Store in Redis
-        if let Some(ttl) = self.ttl {
-            rt.block_on(self.client.string().set_ex(&key, &data, ttl))?;
-        } else {
-            rt.block_on(self.client.set(&key, &data))?;
+
+        // The session expiry wins over the storage TTL, Redis drops the key when it passes
+        let ttl = session.expires_in().map(|ttl| ttl.as_secs().max(1)).or(self.ttl);
+        match ttl {
+            Some(ttl) => self.client.string().set_json_ex(&key, &session, ttl).await,
+            None => self.client.set_json(&key, &session).await,
         }
-        
-        Ok(())
     }
-    
-    fn delete(&self, id: &str) -> Result<()> {
+
+    async fn delete(&self, id: &str) -> Result<()> {
         let key = self.session_key(id);
-        let rt = tokio::runtime::Handle::try_current()
-            .or_else(|_| tokio::runtime::Runtime::new().map(|rt| rt.handle().clone()))?;
-        rt.block_on(self.client.generic().del(&[&key]))?;
+        self.client.generic().del(&[&key]).await?;
         Ok(())
     }
 }
 
 /// Database-based session storage
+///
+/// Uses a table with `id`, `data` (JSON) and `expire_at` (Unix seconds)
+/// columns, created by `ensure_table`.
 pub struct DatabaseSessionStorage {
     database: Arc<rf_database::db::Database>,
-    _table: String,
+    table: String,
     ttl: Option<std::time::Duration>,
 }
 
 impl DatabaseSessionStorage {
     /// Create a new database session storage
-    pub fn new(database: &rf_database::db::Database, table: &str) -> Self {
-        // Wrap database reference in Arc for safe sharing
-        let database_arc = Arc::new(unsafe {
-            // Safety: Database is designed to be shared and long-lived.
-            // Creating an Arc from a reference is safe as long as the original
-            // Database outlives this DatabaseSessionStorage.
-            std::ptr::read(database as *const rf_database::db::Database)
-        });
+    pub fn new(database: Arc<rf_database::db::Database>, table: &str) -> Self {
         Self {
-            database: database_arc,
-            _table: table.to_string(),
+            database,
+            table: table.to_string(),
             ttl: None,
         }
     }
@@ -398,28 +390,99 @@ impl DatabaseSessionStorage {
         self.ttl = Some(ttl);
         self
     }
+
+    /// Create the session table if it does not exist
+    pub async fn ensure_table(&self) -> Result<()> {
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {} (id VARCHAR(128) PRIMARY KEY, data TEXT NOT NULL, expire_at BIGINT)",
+            self.table
+        );
+        self.database.raw_execute(&sql).await?;
+        Ok(())
+    }
+
+    /// SQL with `$n` placeholders rewritten to `?` for MySQL and SQLite
+    fn sql(&self, sql: &str) -> String {
+        let sql = sql.replace("{table}", &self.table);
+        if self.database.as_postgres().is_some() {
+            return sql;
+        }
+        regex::Regex::new(r"\$\d+").map_or(sql.clone(), |re| re.replace_all(&sql, "?").into_owned())
+    }
+
+    async fn execute(&self, sql: &str, text: &[&str], ints: &[Option<i64>]) -> Result<u64> {
+        let sql = self.sql(sql);
+        macro_rules! run {
+            ($pool:expr) => {{
+                let mut query = sqlx::query(&sql);
+                for value in text {
+                    query = query.bind(*value);
+                }
+                for value in ints {
+                    query = query.bind(*value);
+                }
+                query.execute($pool).await.map(|r| r.rows_affected())
+            }};
+        }
+        let result = if let Some(pool) = self.database.as_postgres() {
+            run!(pool)
+        } else if let Some(pool) = self.database.as_mysql() {
+            run!(pool)
+        } else if let Some(pool) = self.database.as_sqlite() {
+            run!(pool)
+        } else {
+            return Err(RfError::Database("Unsupported database".to_string()));
+        };
+        result.map_err(|e| RfError::Database(format!("Session query failed: {}", e)))
+    }
 }
 
+#[async_trait]
 impl SessionStorage for DatabaseSessionStorage {
-    fn get(&self, _id: &str) -> Result<Option<Session>> {
-        let _database = &*self.database;
-        // Query session from database
-        // Simplified - would query from table
-        Ok(None)
-    }
-    
-    fn store(&self, _session: Session) -> Result<()> {
-        let _database = &*self.database;
-        // Store session in database
-        // Simplified - would insert/update in table
+    async fn get(&self, id: &str) -> Result<Option<Session>> {
+        let sql = self.sql("SELECT data FROM {table} WHERE id = $1 AND (expire_at IS NULL OR expire_at > $2)");
+        let now = chrono::Utc::now().timestamp();
+        let row: std::result::Result<Option<(String,)>, sqlx::Error> = if let Some(pool) = self.database.as_postgres() {
+            sqlx::query_as(&sql).bind(id).bind(now).fetch_optional(pool).await
+        } else if let Some(pool) = self.database.as_mysql() {
+            sqlx::query_as(&sql).bind(id).bind(now).fetch_optional(pool).await
+        } else if let Some(pool) = self.database.as_sqlite() {
+            sqlx::query_as(&sql).bind(id).bind(now).fetch_optional(pool).await
+        } else {
+            return Err(RfError::Database("Unsupported database".to_string()));
+        };
+        let row = row.map_err(|e| RfError::Database(format!("Session query failed: {}", e)))?;
+        row.map(|(data,)| deserialize(data.as_bytes())).transpose()
+    }
+
+    async fn store(&self, session: Session) -> Result<()> {
+        let data = String::from_utf8(serialize(&session)?)
+            .map_err(|e| RfError::Serialization(format!("Failed to serialize session: {}", e)))?;
+        let expire_at = match (session.expiry(), self.ttl) {
+            (Some(expiry), _) => Some(expiry.timestamp()),
+            (None, Some(ttl)) => Some((chrono::Utc::now() + ttl_duration(ttl)?).timestamp()),
+            (None, None) => None,
+        };
+        let sql = if self.database.as_mysql().is_some() {
+            "INSERT INTO {table} (id, data, expire_at) VALUES ($1, $2, $3) \
+             ON DUPLICATE KEY UPDATE data = VALUES(data), expire_at = VALUES(expire_at)"
+        } else {
+            "INSERT INTO {table} (id, data, expire_at) VALUES ($1, $2, $3) \
+             ON CONFLICT (id) DO UPDATE SET data = excluded.data, expire_at = excluded.expire_at"
+        };
+        self.execute(sql, &[session.id(), &data], &[expire_at]).await?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        self.execute("DELETE FROM {table} WHERE id = $1", &[id], &[]).await?;
         Ok(())
     }
-    
-    fn delete(&self, _id: &str) -> Result<()> {
-        let _database = &*self.database;
-        // Delete session from database
-        // Simplified - would delete from table
+
+    async fn cleanup_expired(&self) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        self.execute("DELETE FROM {table} WHERE expire_at IS NOT NULL AND expire_at <= $1", &[], &[Some(now)])
+            .await?;
         Ok(())
     }
 }
-
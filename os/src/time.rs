@@ -6,9 +6,34 @@
 //! @date 2026-01-06
 
 //! Time handling
+//!
+//! Times are kept in UTC and rendered in the requester's timezone and
+//! language, taken from `rf_core::ctx::locale` (the HTTP locale middleware
+//! negotiates it per request). `Time` serializes that way, `request_tz` does
+//! the same for plain `DateTime<Utc>` fields, and the view engine's
+//! `datetime` filter formats template values with it. Outside a locale
+//! scope everything renders in UTC as before.
+//!
+//! ```rust,ignore
+//! use rf_os::time::{self, Time};
+//!
+//! #[derive(Serialize)]
+//! struct Order {
+//!     created_at: Time,
+//!     #[serde(with = "time::request_tz")]
+//!     paid_at: DateTime<Utc>,
+//! }
+//!
+//! // For a request from Asia/Shanghai: "2026-01-06T18:30:00+08:00"
+//! let json = serde_json::to_string(&order)?;
+//! ```
 
-use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use chrono::{DateTime, Local, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use rf_errors::Result;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
 
 /// Get current time in UTC
 pub fn now() -> DateTime<Utc> {
@@ -42,3 +67,163 @@ pub fn timestamp_millis(dt: &DateTime<Utc>) -> i64 {
     dt.timestamp_millis()
 }
 
+/// Look up an IANA timezone such as `Asia/Shanghai`
+pub fn timezone(name: &str) -> Result<Tz> {
+    Tz::from_str(name).map_err(|_| rf_errors::RfError::InvalidParameter(format!("Unknown timezone '{}'", name)))
+}
+
+/// Timezone of the current request, UTC outside a locale scope or when the name is unknown
+pub fn request_timezone() -> Tz {
+    rf_core::ctx::locale()
+        .and_then(|locale| timezone(&locale.timezone).ok())
+        .unwrap_or(Tz::UTC)
+}
+
+/// Language of the current request, if any
+pub fn request_language() -> Option<String> {
+    rf_core::ctx::locale().map(|locale| locale.language.clone())
+}
+
+/// `dt` in the timezone of the current request
+pub fn in_request_tz(dt: &DateTime<Utc>) -> DateTime<Tz> {
+    dt.with_timezone(&request_timezone())
+}
+
+/// Format `dt` in the timezone of the current request
+pub fn format_in_request_tz(dt: &DateTime<Utc>, format: &str) -> String {
+    in_request_tz(dt).format(format).to_string()
+}
+
+/// Date and time pattern of a language, ISO 8601 style for languages without one
+///
+/// The language is matched on its primary subtag, except for English where
+/// `en-US` (and plain `en`) puts the month first and other regions the day.
+pub fn localized_format(language: &str) -> &'static str {
+    let language = language.to_ascii_lowercase().replace('_', "-");
+    let primary = language.split('-').next().unwrap_or_default();
+    match primary {
+        "zh" | "ja" => "%Y年%m月%d日 %H:%M:%S",
+        "ko" => "%Y년 %m월 %d일 %H:%M:%S",
+        "en" if language == "en" || language.starts_with("en-us") => "%b %d, %Y %I:%M:%S %p",
+        "en" => "%d %b %Y %H:%M:%S",
+        "de" | "ru" | "pl" | "tr" => "%d.%m.%Y %H:%M:%S",
+        "fr" | "es" | "it" | "pt" => "%d/%m/%Y %H:%M:%S",
+        _ => "%Y-%m-%d %H:%M:%S",
+    }
+}
+
+/// Format `dt` in the timezone and with the date pattern of the current request
+pub fn localize(dt: &DateTime<Utc>) -> String {
+    let format = localized_format(request_language().as_deref().unwrap_or_default());
+    format_in_request_tz(dt, format)
+}
+
+/// Parse an RFC 3339 time, or a time without offset (`2026-01-06 18:30:00`)
+/// taken to be in the timezone of the current request
+pub fn parse_in_request_tz(s: &str) -> Result<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .ok_or_else(|| rf_errors::RfError::InvalidParameter(format!("Invalid time '{}'", s)))?;
+    let tz = request_timezone();
+    match tz.from_local_datetime(&naive) {
+        LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => Ok(dt.with_timezone(&Utc)),
+        LocalResult::None => Err(rf_errors::RfError::InvalidParameter(format!(
+            "Time '{}' does not exist in {}",
+            s,
+            tz.name()
+        ))),
+    }
+}
+
+/// A UTC time that renders in the timezone of the current request
+///
+/// Serializes and displays as RFC 3339 with the requester's offset, and
+/// deserializes from RFC 3339 or from a time without offset in the
+/// requester's timezone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time(pub DateTime<Utc>);
+
+impl Time {
+    /// The current time
+    pub fn now() -> Self {
+        Self(Utc::now())
+    }
+
+    /// The time in UTC
+    pub fn utc(&self) -> DateTime<Utc> {
+        self.0
+    }
+
+    /// The time in the timezone of the current request
+    pub fn in_request_tz(&self) -> DateTime<Tz> {
+        in_request_tz(&self.0)
+    }
+
+    /// Format in the timezone of the current request
+    pub fn format(&self, format: &str) -> String {
+        format_in_request_tz(&self.0, format)
+    }
+
+    /// Format in the timezone and with the date pattern of the current request
+    pub fn localize(&self) -> String {
+        localize(&self.0)
+    }
+}
+
+impl From<DateTime<Utc>> for Time {
+    fn from(dt: DateTime<Utc>) -> Self {
+        Self(dt)
+    }
+}
+
+impl From<Time> for DateTime<Utc> {
+    fn from(time: Time) -> Self {
+        time.0
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.in_request_tz().to_rfc3339())
+    }
+}
+
+impl FromStr for Time {
+    type Err = rf_errors::RfError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_in_request_tz(s).map(Self)
+    }
+}
+
+impl Serialize for Time {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        request_tz::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Time {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        request_tz::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Serde functions for `DateTime<Utc>` fields that render like `Time`
+///
+/// Use with `#[serde(with = "rf_os::time::request_tz")]`.
+pub mod request_tz {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(dt: &DateTime<Utc>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(&in_request_tz(dt).to_rfc3339())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<DateTime<Utc>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_in_request_tz(&s).map_err(serde::de::Error::custom)
+    }
+}
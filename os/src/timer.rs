@@ -6,9 +6,15 @@
 //! @date 2026-01-06
 
 //! Timer utilities
+//!
+//! `interval` and `sleep` run on the system clock; `interval_on` and
+//! `sleep_on` take a `Clock`, so timers in code under test can be driven
+//! by an `rf_test::MockClock`.
 
 use tokio::time::{interval as tokio_interval, Duration, Interval};
 
+pub use rf_core::clock::{Clock, Interval as ClockInterval};
+
 /// Create an interval timer
 pub fn interval(period: Duration) -> Interval {
     tokio_interval(period)
@@ -18,3 +24,13 @@ pub fn interval(period: Duration) -> Interval {
 pub async fn sleep(duration: Duration) {
     tokio::time::sleep(duration).await;
 }
+
+/// Create an interval timer on `clock`; the first tick completes at once
+pub fn interval_on(clock: &Clock, period: Duration) -> ClockInterval {
+    clock.interval(period)
+}
+
+/// Sleep for a duration on `clock`
+pub async fn sleep_on(clock: &Clock, duration: Duration) {
+    clock.sleep(duration).await;
+}
@@ -6,35 +6,91 @@
 //! @date 2026-01-06
 
 //! View template engine
+//!
+//! Templates get a `datetime` filter that renders RFC 3339 strings (such as
+//! serialized `Time` values) and Unix timestamps in the requester's timezone
+//! and date pattern, see `rf_os::time`:
+//!
+//! ```text
+//! {{ order.created_at | datetime }}
+//! {{ order.created_at | datetime(format="%Y-%m-%d") }}
+//! {{ order.created_at | datetime(tz="Europe/Berlin") }}
+//! ```
+//!
+//! In development mode (`rf_util::mode::template_reload`) templates are
+//! reloaded from disk before every render, so edits show without a restart;
+//! `auto_reload` overrides the mode.
 
+use crate::time;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
 use rf_errors::Result;
 use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
 use tera::{Tera, Context};
 
 /// Template engine wrapper
 pub struct View {
-    tera: Tera,
+    tera: RwLock<Tera>,
+    auto_reload: bool,
 }
 
 impl View {
     /// Create a new view engine
     pub fn new(template_dir: &str) -> Result<Self> {
-        let tera = Tera::new(template_dir)
+        let mut tera = Tera::new(template_dir)
             .map_err(|e| rf_errors::RfError::Internal(format!("Failed to initialize Tera: {}", e)))?;
-        Ok(Self { tera })
+        tera.register_filter("datetime", datetime_filter);
+        Ok(Self { tera: RwLock::new(tera), auto_reload: rf_util::mode::template_reload() })
+    }
+
+    /// Reload templates before every render (default: in development mode)
+    pub fn auto_reload(mut self, reload: bool) -> Self {
+        self.auto_reload = reload;
+        self
+    }
+
+    /// Reload all templates from disk
+    pub fn reload(&self) -> Result<()> {
+        self.tera.write().full_reload()
+            .map_err(|e| rf_errors::RfError::Internal(format!("Failed to reload templates: {}", e)))
     }
 
     /// Render a template
     pub fn render<T: Serialize>(&self, template: &str, data: &T) -> Result<String> {
         let context = Context::from_serialize(data)
             .map_err(|e| rf_errors::RfError::Internal(format!("Failed to create context: {}", e)))?;
-        self.tera.render(template, &context)
-            .map_err(|e| rf_errors::RfError::Internal(format!("Template render failed: {}", e)))
+        self.render_with_context(template, &context)
     }
 
     /// Render a template with context
     pub fn render_with_context(&self, template: &str, context: &Context) -> Result<String> {
-        self.tera.render(template, context)
+        if self.auto_reload {
+            self.reload()?;
+        }
+        self.tera.read().render(template, context)
             .map_err(|e| rf_errors::RfError::Internal(format!("Template render failed: {}", e)))
     }
 }
+
+/// `datetime` filter: a time in the request's timezone, `format` and `tz` override the locale
+fn datetime_filter(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let dt: DateTime<Utc> = match value {
+        Value::String(s) => time::parse_in_request_tz(s).map_err(|e| tera::Error::msg(e.to_string()))?,
+        Value::Number(n) => n
+            .as_i64()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .ok_or_else(|| tera::Error::msg(format!("Invalid timestamp {}", n)))?,
+        other => return Err(tera::Error::msg(format!("datetime filter expects a time, got {}", other))),
+    };
+    let tz = match args.get("tz").and_then(Value::as_str) {
+        Some(name) => time::timezone(name).map_err(|e| tera::Error::msg(e.to_string()))?,
+        None => time::request_timezone(),
+    };
+    let format = match args.get("format").and_then(Value::as_str) {
+        Some(format) => format.to_string(),
+        None => time::localized_format(time::request_language().as_deref().unwrap_or_default()).to_string(),
+    };
+    Ok(Value::String(dt.with_timezone(&tz).format(&format).to_string()))
+}
@@ -0,0 +1,65 @@
+//! # build_test
+//!
+//! build_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Build information and banner tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::build::{Banner, BuildInfo};
+
+    fn sample() -> BuildInfo {
+        BuildInfo {
+            name: "app",
+            version: "1.2.0",
+            git_describe: Some("v1.2.0-3-gabc1234"),
+            git_commit: Some(""),
+            rustc: Some("rustc 1.80.0"),
+            profile: Some("release"),
+            target: None,
+            features: Some("redis,tls"),
+            built_at: Some("2026-01-06T00:00:00Z"),
+        }
+        .normalized()
+    }
+
+    #[test]
+    fn test_build_info() {
+        let info = rf_os::build_info();
+        assert_eq!(info.name, "rf-os");
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        // Always emitted by rf-os's build script
+        assert!(info.rustc.is_some_and(|r| r.starts_with("rustc")));
+        assert!(info.built_at.is_some_and(|t| t.len() == 20 && t.ends_with('Z')));
+
+        let sample = sample();
+        assert_eq!(sample.git_commit, None);
+        assert_eq!(sample.feature_list(), vec!["redis", "tls"]);
+        assert_eq!(sample.version_string(), "1.2.0 (v1.2.0-3-gabc1234, release)");
+
+        let json = sample.to_json();
+        assert_eq!(json["features"], serde_json::json!(["redis", "tls"]));
+        assert!(json["git_commit"].is_null());
+        assert_eq!(json["framework"]["name"], "rf-os");
+    }
+
+    #[test]
+    fn test_banner() {
+        let banner = Banner::new(sample());
+        assert_eq!(
+            banner.render(),
+            "app 1.2.0 (v1.2.0-3-gabc1234, release)\nbuilt 2026-01-06T00:00:00Z with rustc 1.80.0\nfeatures: redis, tls"
+        );
+
+        let banner = Banner::new(sample())
+            .art("  _ __ / _|\n")
+            .template("{name} on {addr} [{target}]")
+            .var("addr", "0.0.0.0:8080")
+            .enabled(false);
+        assert_eq!(banner.render(), "  _ __ / _|\napp on 0.0.0.0:8080 [unknown]");
+        assert!(!banner.is_enabled());
+    }
+}
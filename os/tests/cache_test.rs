@@ -0,0 +1,194 @@
+//! # cache_test
+//!
+//! cache_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Cache TTL, eviction, single-flight loading and two-tier cache tests
+
+#[cfg(test)]
+mod tests {
+    use rf_database::db::CacheStore;
+    use rf_database::redis::RedisClient;
+    use rf_errors::RfError;
+    use rf_os::cache::{CacheConfig, CacheContainer, Eviction, TieredCache};
+    use rf_test::redis::FakeRedis;
+    use rf_test::{DurationExt, MockClock};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_per_entry_ttl() {
+        let cache = CacheContainer::with_ttl(100, Duration::from_secs(60));
+        cache.insert_with_ttl("short", 1, Duration::from_millis(100)).await;
+        cache.insert("long", 2).await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(cache.get(&"short").await, None);
+        assert_eq!(cache.get(&"long").await, Some(2));
+        let stats = cache.stats().await;
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        assert_eq!(stats.hit_rate(), 0.5);
+
+        // The cache-wide TTL still caps longer entry TTLs
+        let cache = CacheContainer::with_ttl(100, Duration::from_millis(100));
+        cache.insert_with_ttl("key", 1, Duration::from_secs(60)).await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(cache.get(&"key").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_ttl_on_mock_clock() {
+        let mock = MockClock::new();
+        let cache = CacheContainer::with_ttl(100, 1.hours()).clock(mock.clock());
+        cache.insert_with_ttl("session", 1, 5.mins()).await;
+        cache.insert("profile", 2).await;
+
+        mock.advance(4.mins()).await;
+        assert_eq!(cache.get(&"session").await, Some(1));
+        mock.advance(1.mins()).await;
+        assert_eq!(cache.get(&"session").await, None);
+        assert_eq!(cache.get(&"profile").await, Some(2));
+
+        // Expired entries are loaded again
+        let loads = AtomicUsize::new(0);
+        let load = || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            3
+        };
+        assert_eq!(cache.get_or_set_with("profile", None, load).await, 2);
+        mock.advance(1.hours()).await;
+        assert_eq!(cache.get_or_set_with("profile", None, load).await, 3);
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.stats().await.expirations, 2);
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction() {
+        let cache = CacheContainer::from_config(&CacheConfig {
+            capacity: 3,
+            eviction: Eviction::Lru,
+            ..CacheConfig::default()
+        });
+        for key in 0..3 {
+            cache.insert(key, key).await;
+        }
+        cache.stats().await;
+        // Touch 0 so 1 is the least recently used
+        assert_eq!(cache.get(&0).await, Some(0));
+        cache.insert(3, 3).await;
+
+        let stats = cache.stats().await;
+        assert_eq!((stats.entry_count, stats.evictions), (3, 1));
+        assert_eq!(cache.get(&1).await, None);
+        for key in [0, 2, 3] {
+            assert_eq!(cache.get(&key).await, Some(key));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_single_flight() {
+        let cache = CacheContainer::new(100);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut tasks = Vec::new();
+        for _ in 0..20 {
+            let cache = cache.clone();
+            let calls = calls.clone();
+            tasks.push(tokio::spawn(async move {
+                cache
+                    .get_or_set_with("hot", None, || async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        "value".to_string()
+                    })
+                    .await
+            }));
+        }
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), "value");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let stats = cache.stats().await;
+        assert_eq!((stats.loads, stats.misses, stats.hits), (1, 1, 19));
+
+        // Entry TTLs apply to loaded values
+        let value = cache.get_or_set_with("brief", Some(Duration::from_millis(100)), || async { "a".to_string() }).await;
+        assert_eq!(value, "a");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let value = cache.get_or_set_with("brief", None, || async { "b".to_string() }).await;
+        assert_eq!(value, "b");
+    }
+
+    #[tokio::test]
+    async fn test_failed_loads_are_not_cached() {
+        let cache: CacheContainer<&str, u32> = CacheContainer::new(100).metrics("test");
+        let result = cache
+            .try_get_or_set_with("user:1", None, || async { Err(RfError::NotFound("user 1".to_string())) })
+            .await;
+        assert!(matches!(result, Err(RfError::NotFound(_))));
+        assert_eq!(cache.get(&"user:1").await, None);
+
+        let value = cache.try_get_or_set_with("user:1", None, || async { Ok(7) }).await.unwrap();
+        assert_eq!(value, 7);
+        let value = cache.try_get_or_set_with("user:1", None, || async { Ok(8) }).await.unwrap();
+        assert_eq!(value, 7);
+        let stats = cache.stats().await;
+        assert_eq!((stats.loads, stats.load_failures, stats.hits), (2, 1, 1));
+    }
+
+    async fn instance(url: &str) -> TieredCache {
+        let redis = Arc::new(RedisClient::new(url).await.unwrap());
+        TieredCache::new(redis, "users", &CacheConfig::default()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tiered_cache_reads_memory_then_redis() {
+        let redis = FakeRedis::start().await;
+        let a = instance(redis.url()).await;
+        let b = instance(redis.url()).await;
+
+        a.set("1", b"alice".to_vec()).await;
+        assert_eq!(redis.get("cache:users:1").as_deref(), Some(&b"alice"[..]));
+        // A serves from memory, B falls back to Redis once and then serves from memory
+        assert_eq!(a.get("1").await.as_deref(), Some(&b"alice"[..]));
+        assert_eq!(redis.count("GET"), 0);
+        assert_eq!(b.get("1").await.as_deref(), Some(&b"alice"[..]));
+        assert_eq!(b.get("1").await.as_deref(), Some(&b"alice"[..]));
+        assert_eq!(redis.count("GET"), 1);
+        assert_eq!(b.get("missing").await, None);
+        let stats = b.stats().await;
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_tiered_cache_broadcasts_invalidations() {
+        let redis = FakeRedis::start().await;
+        let a = instance(redis.url()).await;
+        let b: Arc<dyn CacheStore> = Arc::new(instance(redis.url()).await);
+
+        a.set("1", b"alice".to_vec()).await;
+        assert_eq!(b.get("1").await.as_deref(), Some(&b"alice"[..]));
+
+        // A write on A drops B's stale copy; B rereads Redis
+        a.set("1", b"alicia".to_vec()).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(b.get("1").await.as_deref(), Some(&b"alicia"[..]));
+        // A skipped its own broadcast and kept its local copy
+        let gets = redis.count("GET");
+        assert_eq!(a.get("1").await.as_deref(), Some(&b"alicia"[..]));
+        assert_eq!(redis.count("GET"), gets);
+
+        b.invalidate("1").await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(a.get("1").await, None);
+
+        a.set("2", b"bob".to_vec()).await;
+        assert_eq!(b.get("2").await.as_deref(), Some(&b"bob"[..]));
+        a.clear().await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(redis.keys().is_empty());
+        assert_eq!(b.get("2").await, None);
+    }
+}
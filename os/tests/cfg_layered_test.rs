@@ -0,0 +1,159 @@
+//! # cfg_layered_test
+//!
+//! cfg_layered_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Layered configuration tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::cfg::*;
+    use serde::Deserialize;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+        Debug,
+        Release,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+        debug: bool,
+        mode: Mode,
+        #[serde(with = "rf_os::cfg::serde_duration")]
+        timeout: Duration,
+        origins: Vec<String>,
+        tls: Option<Tls>,
+        proxy: Option<String>,
+        replicas: Vec<Replica>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Tls {
+        cert: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Replica {
+        host: String,
+        weight: u32,
+    }
+
+    fn layers(dir: &TempDir, prefix: &str, args: &[&str]) -> Config {
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[server]\nhost = \"file\"\nport = 8000\ndebug = false\nmode = \"release\"\ntimeout = \"10s\"\n\
+             origins = \"a.example,b.example\"\nproxy = \"\"\n\
+             [[server.replicas]]\nhost = \"r1\"\nweight = 1\n[[server.replicas]]\nhost = \"r2\"\nweight = 3\n",
+        )
+        .unwrap();
+        Config::new()
+            .adapter(Arc::new(FileConfigAdapter::new(path.to_str().unwrap()).unwrap()))
+            .adapter(Arc::new(EnvConfigAdapter::with_prefix(prefix)))
+            .adapter(Arc::new(ArgsConfigAdapter::new(args.iter().copied())))
+            .with_defaults([("server.host", "default"), ("server.workers", "4"), ("server.mode", "debug")])
+    }
+
+    #[test]
+    fn test_precedence() {
+        let dir = TempDir::new().unwrap();
+        std::env::set_var("RFTEST_PREC_SERVER__PORT", "9000");
+        std::env::set_var("RFTEST_PREC_SERVER__HOST", "env");
+        let config = layers(&dir, "RFTEST_PREC_", &["--server.host=args", "serve"]);
+
+        // defaults < file < env < args
+        assert_eq!(config.get("server.workers").unwrap().as_deref(), Some("4"));
+        assert_eq!(config.get("server.mode").unwrap().as_deref(), Some("release"));
+        assert_eq!(config.get_int("server.port").unwrap(), Some(9000));
+        assert_eq!(config.get("server.host").unwrap().as_deref(), Some("args"));
+        let all = config.all().unwrap();
+        assert_eq!(all["server.host"], "args");
+        assert_eq!(all["server.port"], "9000");
+        assert_eq!(all["server.workers"], "4");
+
+        // Env and flags are read-only, writes land in the file layer
+        config.set("server.workers", "8").unwrap();
+        assert_eq!(config.get_int("server.workers").unwrap(), Some(8));
+        std::env::remove_var("RFTEST_PREC_SERVER__PORT");
+        std::env::remove_var("RFTEST_PREC_SERVER__HOST");
+    }
+
+    #[test]
+    fn test_typed_getters() {
+        let dir = TempDir::new().unwrap();
+        let config = layers(&dir, "RFTEST_TYPED_", &["--server.debug", "--server.verbose", "off", "--server.level=high"]);
+
+        assert_eq!(config.get_bool("server.debug").unwrap(), Some(true));
+        assert_eq!(config.get_bool("server.verbose").unwrap(), Some(false));
+        assert_eq!(config.get_bool("server.missing").unwrap(), None);
+        assert_eq!(config.get_duration("server.timeout").unwrap(), Some(Duration::from_secs(10)));
+        assert_eq!(config.get_int("server.missing").unwrap(), None);
+
+        let err = config.get_int("server.level").unwrap_err().to_string();
+        assert!(err.contains("server.level"), "{}", err);
+        assert!(config.get_bool("server.level").is_err());
+    }
+
+    #[test]
+    fn test_get_struct() {
+        let dir = TempDir::new().unwrap();
+        std::env::set_var("RFTEST_STRUCT_SERVER__TLS__CERT", "/etc/cert.pem");
+        std::env::set_var("RFTEST_STRUCT_SERVER__REPLICAS__1__WEIGHT", "5");
+        let config = layers(&dir, "RFTEST_STRUCT_", &["--server.debug=yes", "--server.timeout", "1m30s"]);
+
+        let server: Server = config.get_struct("server").unwrap();
+        assert_eq!(server.host, "file");
+        assert_eq!(server.port, 8000);
+        assert!(server.debug);
+        assert_eq!(server.mode, Mode::Release);
+        assert_eq!(server.timeout, Duration::from_secs(90));
+        assert_eq!(server.origins, ["a.example", "b.example"]);
+        assert_eq!(server.tls.unwrap().cert, "/etc/cert.pem");
+        assert_eq!(server.proxy, None);
+        assert_eq!(server.replicas.len(), 2);
+        assert_eq!((server.replicas[0].host.as_str(), server.replicas[0].weight), ("r1", 1));
+        assert_eq!((server.replicas[1].host.as_str(), server.replicas[1].weight), ("r2", 5));
+
+        #[derive(Debug, Deserialize, Default)]
+        #[serde(default)]
+        struct Cache {
+            size: usize,
+        }
+        assert_eq!(config.get_struct::<Cache>("cache").unwrap().size, 0);
+
+        std::env::set_var("RFTEST_STRUCT_SERVER__PORT", "http");
+        let err = config.get_struct::<Server>("server").unwrap_err().to_string();
+        assert!(err.contains("server.port"), "{}", err);
+        std::env::remove_var("RFTEST_STRUCT_SERVER__TLS__CERT");
+        std::env::remove_var("RFTEST_STRUCT_SERVER__REPLICAS__1__WEIGHT");
+        std::env::remove_var("RFTEST_STRUCT_SERVER__PORT");
+    }
+
+    #[test]
+    fn test_args_adapter() {
+        let args = ArgsConfigAdapter::new(["run", "--a.b=1", "--c", "two", "--flag", "--d=x=y", "--", "--after=1"]);
+        let all = args.all().unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all["a.b"], "1");
+        assert_eq!(all["c"], "two");
+        assert_eq!(all["flag"], "true");
+        assert_eq!(all["d"], "x=y");
+        assert!(args.set("a.b", "2").is_err());
+    }
+
+    #[test]
+    fn test_env_adapter_names() {
+        let env = EnvConfigAdapter::with_prefix("RF_");
+        assert_eq!(env.var_name("database.default.max_connections"), "RF_DATABASE__DEFAULT__MAX_CONNECTIONS");
+        assert_eq!(EnvConfigAdapter::new().var_name("PATH"), "PATH");
+    }
+}
@@ -0,0 +1,54 @@
+//! # cfg_profile_test
+//!
+//! cfg_profile_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Configuration profile tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::cfg::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_profile_path() {
+        assert_eq!(profile_path("config/config.toml", "prod"), "config/config.prod.toml");
+        assert_eq!(profile_path("app.yaml", "test"), "app.test.yaml");
+        assert_eq!(profile_path("/etc/app/settings", "dev"), "/etc/app/settings.dev");
+    }
+
+    #[test]
+    fn test_overlay_merges_onto_base() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path().join("config.toml");
+        std::fs::write(&base, "[server]\nport = 8080\nhost = \"127.0.0.1\"\n\n[logger]\nlevel = \"debug\"\n").unwrap();
+        std::fs::write(dir.path().join("config.prod.toml"), "[server]\nhost = \"0.0.0.0\"\n\n[logger]\nlevel = \"warn\"\n")
+            .unwrap();
+        let base = base.to_str().unwrap();
+
+        let prod = Config::layered_profile(base, "prod").unwrap();
+        assert_eq!(prod.active_profile(), Some("prod"));
+        assert_eq!(prod.get("server.host").unwrap().as_deref(), Some("0.0.0.0"));
+        assert_eq!(prod.get("server.port").unwrap().as_deref(), Some("8080"));
+        assert_eq!(prod.all().unwrap()["logger.level"], "warn");
+        assert!(prod.location("logger.level").unwrap().file.ends_with("config.prod.toml"));
+
+        // No overlay for this profile: the base file alone
+        let staging = Config::layered_profile(base, "staging").unwrap();
+        assert_eq!(staging.get("server.host").unwrap().as_deref(), Some("127.0.0.1"));
+        assert_eq!(Config::new().active_profile(), None);
+    }
+
+    #[test]
+    fn test_current_profile_from_env() {
+        std::env::set_var(PROFILE_ENV, "staging");
+        assert_eq!(current_profile(), "staging");
+        std::env::remove_var(PROFILE_ENV);
+        std::env::set_var("RF_MODE", "production");
+        assert_eq!(current_profile(), "prod");
+        std::env::remove_var("RF_MODE");
+        assert_eq!(current_profile(), "dev");
+    }
+}
@@ -0,0 +1,119 @@
+//! # cfg_reload_test
+//!
+//! cfg_reload_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Configuration hot reload tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::cfg::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    fn config(dir: &TempDir, content: &str) -> (Arc<Config>, std::path::PathBuf) {
+        let path = dir.path().join("app.toml");
+        std::fs::write(&path, content).unwrap();
+        let file = FileConfigAdapter::new(path.to_str().unwrap()).unwrap();
+        let config = Config::new()
+            .adapter(Arc::new(file))
+            .with_defaults([("logger.level", "info")]);
+        (Arc::new(config), path)
+    }
+
+    fn recorder(config: &Config, prefix: &str) -> Arc<Mutex<Vec<ConfigChange>>> {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        config.on_change(prefix, move |changes| sink.lock().unwrap().extend_from_slice(changes));
+        seen
+    }
+
+    #[test]
+    fn test_reload_dispatches_by_prefix() {
+        let dir = TempDir::new().unwrap();
+        let (config, path) = config(&dir, "[logger]\nlevel = \"warn\"\n[rate_limit.api]\ncapacity = 10\nrefill_rate = 5\n");
+        let logger = recorder(&config, "logger");
+        let api = recorder(&config, "rate_limit.api.");
+        let all = recorder(&config, "");
+        let other = recorder(&config, "rate_limit.ap");
+
+        std::fs::write(&path, "[logger]\nlevel = \"debug\"\n[rate_limit.api]\ncapacity = 20\nburst = 3\n").unwrap();
+        let changes = config.reload().unwrap();
+        let keys: Vec<&str> = changes.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(keys, ["logger.level", "rate_limit.api.burst", "rate_limit.api.capacity", "rate_limit.api.refill_rate"]);
+
+        assert_eq!(
+            *logger.lock().unwrap(),
+            [ConfigChange { key: "logger.level".into(), old: Some("warn".into()), new: Some("debug".into()) }]
+        );
+        let api = api.lock().unwrap();
+        assert_eq!(api.len(), 3);
+        assert_eq!((api[0].old.as_deref(), api[0].new.as_deref()), (None, Some("3")));
+        assert_eq!((api[2].old.as_deref(), api[2].new.as_deref()), (Some("5"), None));
+        assert_eq!(all.lock().unwrap().len(), 4);
+        assert!(other.lock().unwrap().is_empty());
+
+        // Nothing changed, nothing dispatched
+        assert!(config.reload().unwrap().is_empty());
+        assert_eq!(all.lock().unwrap().len(), 4);
+        assert_eq!(config.get_int("rate_limit.api.capacity").unwrap(), Some(20));
+    }
+
+    #[test]
+    fn test_broken_file_keeps_previous_values() {
+        let dir = TempDir::new().unwrap();
+        let (config, path) = config(&dir, "[server]\nport = 8080\n");
+        let seen = recorder(&config, "");
+
+        std::fs::write(&path, "[server\nport = ").unwrap();
+        assert!(config.reload().is_err());
+        assert_eq!(config.get_int("server.port").unwrap(), Some(8080));
+        assert!(config.location("server.port").is_some());
+        assert!(seen.lock().unwrap().is_empty());
+
+        let id = config.on_change("server", |_| panic!("handler failure"));
+        std::fs::write(&path, "[server]\nport = 9090\n").unwrap();
+        assert_eq!(config.reload().unwrap().len(), 1);
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        assert!(config.remove_listener(id));
+        assert!(!config.remove_listener(id));
+    }
+
+    #[test]
+    fn test_watch_reloads_on_write() {
+        let dir = TempDir::new().unwrap();
+        let (config, path) = config(&dir, "[logger]\nlevel = \"warn\"\n");
+        let seen = recorder(&config, "logger.level");
+        let watch = config.watch().unwrap();
+        assert_eq!(watch.paths(), std::slice::from_ref(&path));
+
+        // Save through a temporary file and a rename, like many editors do
+        let temp = dir.path().join("app.toml.tmp");
+        std::fs::write(&temp, "[logger]\nlevel = \"error\"\n").unwrap();
+        std::fs::rename(&temp, &path).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while seen.lock().unwrap().is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(seen.lock().unwrap()[0].new.as_deref(), Some("error"));
+        assert_eq!(config.get("logger.level").unwrap().as_deref(), Some("error"));
+
+        // Stopped once the guard is dropped
+        drop(watch);
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(&path, "[logger]\nlevel = \"trace\"\n").unwrap();
+        std::thread::sleep(Duration::from_millis(400));
+        assert_eq!(config.get("logger.level").unwrap().as_deref(), Some("error"));
+    }
+
+    #[test]
+    fn test_watch_needs_a_file() {
+        let config = Arc::new(Config::new().adapter(Arc::new(MemoryConfigAdapter::new())));
+        assert!(config.watch().is_err());
+        assert!(rf_os::log::set_level("not a level=").is_err());
+    }
+}
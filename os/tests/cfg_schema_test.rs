@@ -0,0 +1,97 @@
+//! # cfg_schema_test
+//!
+//! cfg_schema_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Configuration schema tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::cfg::*;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn schema() -> ConfigSchema {
+        ConfigSchema::new()
+            .key(KeySpec::new("server.*.address", TypeRule::SocketAddr).default("127.0.0.1:8080"))
+            .key(KeySpec::new("server.*.timeout", TypeRule::Duration).default("30s"))
+            .key(KeySpec::new("database.*.url", TypeRule::Url).required())
+            .key(KeySpec::new("database.*.max_connections", TypeRule::Integer)
+                .rule(Box::new(RangeRule::new(Some(1), Some(100)))))
+    }
+
+    fn load(dir: &TempDir, name: &str, content: &str) -> Config {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        let adapter = FileConfigAdapter::new(path.to_str().unwrap()).unwrap();
+        Config::new().adapter(Arc::new(adapter)).with_schema(schema())
+    }
+
+    #[test]
+    fn test_toml_report_with_locations() {
+        let dir = TempDir::new().unwrap();
+        let config = load(&dir, "config.toml", "\
+[server.default]
+address = \"0.0.0.0:8080\"
+timout = \"30s\"
+
+[database.default]
+max_connections = 500
+
+[database.cache]
+url = \"postgresql://localhost/cache\"
+hosts = [\"a\", \"b\"]
+");
+        assert_eq!(config.get("server.default.address").unwrap().as_deref(), Some("0.0.0.0:8080"));
+        assert_eq!(config.get("database.cache.hosts").unwrap().as_deref(), Some("a,b"));
+        assert_eq!(config.get_or_default("server.api.timeout").unwrap().as_deref(), Some("30s"));
+
+        let report = config.diagnose().unwrap();
+        let issues: Vec<(IssueKind, &str, Option<usize>)> = report.issues.iter()
+            .map(|issue| (issue.kind, issue.key.as_str(), issue.location.as_ref().map(|l| l.line)))
+            .collect();
+        assert_eq!(issues, vec![
+            (IssueKind::UnknownKey, "database.cache.hosts", Some(10)),
+            (IssueKind::TypeMismatch, "database.default.max_connections", Some(6)),
+            (IssueKind::UnknownKey, "server.default.timout", Some(3)),
+            (IssueKind::MissingRequired, "database.default.url", None),
+        ]);
+        assert!(report.issues[2].message.contains("did you mean 'server.default.timeout'"));
+        assert!(report.issues[1].to_string().starts_with(&format!("{}:6: ", dir.path().join("config.toml").display())));
+
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("database.default.url") && !error.contains("timout"));
+    }
+
+    #[test]
+    fn test_yaml_and_json_locations() {
+        let dir = TempDir::new().unwrap();
+        let config = load(&dir, "config.yaml", "\
+server:
+  default:
+    address: 0.0.0.0:8080
+database:
+  default:
+    url: not a url
+");
+        let report = config.diagnose().unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].key, "database.default.url");
+        assert_eq!(report.issues[0].location.as_ref().unwrap().line, 6);
+
+        let config = load(&dir, "config.json", r#"{
+  "database": {
+    "default": {
+      "url": "mysql://localhost/app",
+      "max_connections": "many"
+    }
+  }
+}"#);
+        let report = config.diagnose().unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].location.as_ref().unwrap().line, 5);
+        assert!(config.validate().is_err());
+    }
+}
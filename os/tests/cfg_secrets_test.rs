@@ -0,0 +1,85 @@
+//! # cfg_secrets_test
+//!
+//! cfg_secrets_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Configuration secret tests
+
+#[cfg(test)]
+mod tests {
+    use rf_errors::Result;
+    use rf_os::cfg::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let secrets = EncryptedSecrets::new("correct horse battery staple");
+        let encrypted = secrets.encrypt("s3cr3t").unwrap();
+        assert!(encrypted.starts_with("ENC(") && encrypted.ends_with(')'));
+        assert!(!encrypted.contains("s3cr3t"));
+        // Random nonces: the same plaintext never encrypts the same way twice
+        assert_ne!(encrypted, secrets.encrypt("s3cr3t").unwrap());
+        assert_eq!(secrets.decrypt(&encrypted).unwrap(), "s3cr3t");
+
+        let url = format!("postgres://app:{}@db:5432/{}", encrypted, secrets.encrypt("app").unwrap());
+        assert_eq!(secrets.decrypt(&url).unwrap(), "postgres://app:s3cr3t@db:5432/app");
+        assert_eq!(secrets.decrypt("no secrets here").unwrap(), "no secrets here");
+    }
+
+    #[test]
+    fn test_wrong_key_and_malformed_values() {
+        let encrypted = EncryptedSecrets::new("key-a").encrypt("s3cr3t").unwrap();
+        let other = EncryptedSecrets::new("key-b");
+        assert!(other.decrypt(&encrypted).is_err());
+        assert!(other.decrypt("ENC(not base64!)").is_err());
+        assert!(other.decrypt("ENC(AAAA)").is_err());
+        assert!(other.decrypt("ENC(unterminated").is_err());
+        assert!(other.resolve("plain").is_none());
+    }
+
+    struct Upper;
+
+    impl SecretProvider for Upper {
+        fn resolve(&self, value: &str) -> Option<Result<String>> {
+            value.strip_prefix("upper:").map(|rest| Ok(rest.to_uppercase()))
+        }
+    }
+
+    #[test]
+    fn test_config_resolves_secrets() {
+        let secrets = EncryptedSecrets::new("master");
+        let adapter = MemoryConfigAdapter::new();
+        adapter.set("database.default.password", &secrets.encrypt("s3cr3t").unwrap()).unwrap();
+        adapter.set("database.default.user", "upper:app").unwrap();
+        adapter.set("database.default.host", "db").unwrap();
+        let adapter = Arc::new(adapter);
+
+        let config = Config::new()
+            .adapter(adapter.clone())
+            .with_secrets(Arc::new(secrets))
+            .with_secrets(Arc::new(Upper));
+        assert_eq!(config.get("database.default.password").unwrap().as_deref(), Some("s3cr3t"));
+        assert_eq!(config.get("database.default.user").unwrap().as_deref(), Some("APP"));
+        assert_eq!(config.get("database.default.host").unwrap().as_deref(), Some("db"));
+        let all = config.all().unwrap();
+        assert_eq!(all["database.default.password"], "s3cr3t");
+        assert_eq!(all["database.default.user"], "APP");
+
+        // Without providers the references are returned as stored
+        let raw = Config::new().adapter(adapter);
+        assert!(raw.get("database.default.password").unwrap().unwrap().starts_with("ENC("));
+
+        // A reference that cannot be decrypted is an error, not the ciphertext
+        let wrong = Config::new()
+            .adapter(Arc::new({
+                let adapter = MemoryConfigAdapter::new();
+                adapter.set("password", &EncryptedSecrets::new("other").encrypt("x").unwrap()).unwrap();
+                adapter
+            }))
+            .with_secrets(Arc::new(EncryptedSecrets::new("master")));
+        assert!(wrong.get("password").is_err());
+        assert!(wrong.all().is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! # cmd_exec_test
+//!
+//! cmd_exec_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Command execution tests
+
+#[cfg(all(test, unix))]
+mod tests {
+    use rf_os::cmd_exec::{pipe_commands, CommandBuilder};
+    use rf_os::proc::{OutputLine, OutputStream};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_execute_with_env_cwd_and_stdin() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let result = CommandBuilder::new("sh")
+            .args(&["-c", "echo \"$NAME in $(pwd)\"; cat; echo warn >&2; exit 3"])
+            .env("NAME", "rf")
+            .current_dir(dir.path())
+            .stdin("from stdin\n")
+            .execute()
+            .await
+            .unwrap();
+        let cwd = dir.path().canonicalize().unwrap();
+        assert_eq!(result.stdout, format!("rf in {}\nfrom stdin\n", cwd.display()));
+        assert_eq!(result.stderr, "warn\n");
+        assert_eq!((result.exit_code, result.success, result.timed_out), (Some(3), false, false));
+
+        let cleared = CommandBuilder::new("/usr/bin/env").env_clear().env("ONLY", "1").execute().await.unwrap();
+        assert_eq!(cleared.stdout, "ONLY=1\n");
+        assert!(cleared.success);
+    }
+
+    #[tokio::test]
+    async fn test_stream_lines() {
+        let mut stream = CommandBuilder::new("sh")
+            .args(&["-c", "echo one; sleep 0.05; echo two >&2; sleep 0.05; echo three"])
+            .stream()
+            .unwrap();
+        let mut lines = Vec::new();
+        while let Some(line) = stream.next().await {
+            lines.push(line);
+        }
+        let line = |stream, text: &str| OutputLine { stream, line: text.to_string() };
+        assert_eq!(
+            lines,
+            [line(OutputStream::Stdout, "one"), line(OutputStream::Stderr, "two"), line(OutputStream::Stdout, "three")]
+        );
+        let result = stream.wait().await;
+        assert_eq!((result.stdout.as_str(), result.stderr.as_str()), ("one\nthree\n", "two\n"));
+        assert!(result.success && result.duration >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills() {
+        let result = CommandBuilder::new("sh")
+            .args(&["-c", "echo started; sleep 10"])
+            .timeout(Duration::from_millis(200))
+            .execute()
+            .await
+            .unwrap();
+        assert!(result.timed_out && !result.success);
+        assert_eq!((result.exit_code, result.stdout.as_str()), (None, "started\n"));
+        assert!(result.duration < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_pipe_commands() {
+        let result = pipe_commands(vec![
+            CommandBuilder::new("printf").arg("b\\na\\nc\\na\\n"),
+            CommandBuilder::new("sort"),
+            CommandBuilder::new("uniq").arg("-c"),
+            CommandBuilder::new("wc").arg("-l"),
+        ])
+        .await
+        .unwrap();
+        assert_eq!(result.stdout.trim(), "3");
+        assert!(result.success);
+
+        // The input of the first command feeds the pipeline; a failing stage fails it
+        let result = pipe_commands(vec![
+            CommandBuilder::new("cat").stdin("hello\n"),
+            CommandBuilder::new("sh").args(&["-c", "tr a-z A-Z; echo oops >&2; exit 1"]),
+            CommandBuilder::new("cat"),
+        ])
+        .await
+        .unwrap();
+        assert_eq!((result.stdout.as_str(), result.stderr.as_str()), ("HELLO\n", "oops\n"));
+        assert_eq!((result.exit_code, result.success), (Some(0), false));
+
+        let result = pipe_commands(vec![CommandBuilder::new("yes"), CommandBuilder::new("sleep").arg("10").timeout(Duration::from_millis(100))])
+            .await
+            .unwrap();
+        assert!(result.timed_out);
+        assert!(pipe_commands(Vec::new()).await.is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! # cmd_test
+//!
+//! cmd_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Command line parser tests
+
+#[cfg(test)]
+mod tests {
+    use clap::error::ErrorKind;
+    use rf_os::cmd::{Cmd, Flag, Shell};
+
+    fn app() -> Cmd {
+        Cmd::new("app")
+            .version("1.0.0")
+            .flag(Flag::new("config").short('c').default("config.toml").help("Configuration file"))
+            .flag(Flag::count("verbose").short('v'))
+            .flag(Flag::switch("dry-run"))
+            .command(
+                Cmd::new("serve")
+                    .about("Start the server")
+                    .flag(
+                        Flag::typed::<u16>("port")
+                            .short('p')
+                            .default("8080")
+                            .env("RF_CMD_TEST_PORT")
+                            .validate(|port: &u16| if *port >= 1024 { Ok(()) } else { Err("must be 1024 or above") }),
+                    )
+                    .flag(Flag::new("format").choices(["json", "text"]).default("text"))
+                    .flag(Flag::typed::<u32>("worker").multiple()),
+            )
+            .command(
+                Cmd::new("db")
+                    .subcommand_required()
+                    .command(Cmd::new("migrate").flag(Flag::positional("target").validate(|v: &i64| {
+                        if *v >= 0 { Ok(()) } else { Err("negative version") }
+                    })))
+                    .command(Cmd::new("seed").flag(Flag::positional("files").multiple())),
+            )
+            .with_completion()
+    }
+
+    #[test]
+    fn test_typed_flags_and_defaults() {
+        let matches = app().try_parse_from(["app", "-vv", "--dry-run", "serve", "-p", "9000", "--worker", "1", "--worker", "2"]).unwrap();
+        assert_eq!(Cmd::get::<String>(&matches, "config").as_deref(), Some("config.toml"));
+        assert_eq!(Cmd::get::<u8>(&matches, "verbose"), Some(2));
+        assert!(Cmd::get_flag(&matches, "dry-run"));
+
+        let (path, serve) = Cmd::subcommand_path(&matches);
+        assert_eq!(path, ["serve"]);
+        assert_eq!(Cmd::get::<u16>(serve, "port"), Some(9000));
+        // Asking for the wrong type is not a panic
+        assert_eq!(Cmd::get::<String>(serve, "port"), None);
+        assert_eq!(Cmd::get_all::<u32>(serve, "worker"), [1, 2]);
+        assert_eq!(Cmd::get::<String>(serve, "format").as_deref(), Some("text"));
+
+        let matches = app().try_parse_from(["app", "serve"]).unwrap();
+        assert_eq!(Cmd::get::<u16>(Cmd::subcommand_path(&matches).1, "port"), Some(8080));
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let kind = |args: &[&str]| app().try_parse_from(args).unwrap_err().kind();
+        assert_eq!(kind(&["app", "serve", "--port", "80"]), ErrorKind::ValueValidation);
+        assert_eq!(kind(&["app", "serve", "--port", "http"]), ErrorKind::ValueValidation);
+        assert_eq!(kind(&["app", "serve", "--format", "xml"]), ErrorKind::InvalidValue);
+        assert_eq!(kind(&["app", "db", "migrate", "-3"]), ErrorKind::UnknownArgument);
+        assert_eq!(kind(&["app", "--unknown"]), ErrorKind::UnknownArgument);
+        assert_eq!(kind(&["app", "db"]), ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand);
+
+        let error = app().try_parse_from(["app", "serve", "--port", "80"]).unwrap_err().to_string();
+        assert!(error.contains("must be 1024 or above"), "{}", error);
+    }
+
+    #[test]
+    fn test_nested_commands_and_help() {
+        let matches = app().try_parse_from(["app", "db", "seed", "users.sql", "orders.sql"]).unwrap();
+        let (path, seed) = Cmd::subcommand_path(&matches);
+        assert_eq!(path, ["db", "seed"]);
+        assert_eq!(Cmd::get_all::<String>(seed, "files"), ["users.sql", "orders.sql"]);
+
+        let matches = app().try_parse_from(["app", "db", "migrate", "42"]).unwrap();
+        assert_eq!(Cmd::get::<i64>(Cmd::subcommand_path(&matches).1, "target"), Some(42));
+
+        let help = app().help_text();
+        assert!(help.contains("serve") && help.contains("--config"), "{}", help);
+        assert_eq!(app().try_parse_from(["app", "db", "--help"]).unwrap_err().kind(), ErrorKind::DisplayHelp);
+        assert_eq!(app().try_parse_from(["app", "--version"]).unwrap_err().kind(), ErrorKind::DisplayVersion);
+    }
+
+    #[test]
+    fn test_completions() {
+        let app = app();
+        let matches = app.try_parse_from(["app", "completion", "zsh"]).unwrap();
+        assert_eq!(app.completion_shell(&matches), Some(Shell::Zsh));
+        assert_eq!(app.completion_shell(&app.try_parse_from(["app", "serve"]).unwrap()), None);
+        assert!(app.try_parse_from(["app", "completion", "tcsh"]).is_err());
+
+        let mut app = app;
+        assert!(app.completion_script(Shell::Bash).contains("complete -F _app"));
+        let fish = app.completion_script(Shell::Fish);
+        assert!(fish.contains("complete -c app") && fish.contains("migrate"), "{}", fish);
+        assert!(app.completion_script(Shell::Zsh).starts_with("#compdef app"));
+        assert_eq!("FISH".parse::<Shell>(), Ok(Shell::Fish));
+    }
+}
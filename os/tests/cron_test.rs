@@ -0,0 +1,362 @@
+//! # cron_test
+//!
+//! cron_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Cron scheduler tests
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Timelike, Weekday};
+    use rf_errors::{Result, RfError};
+    use rf_os::cron::{Cron, DatabaseJobStore, JobOptions, JobSpec, JobStore, MemoryJobStore, Overlap};
+    use rf_os::lock::{LockBackend, MemoryBackend};
+    use rf_test::{DurationExt, MockClock};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_seconds_schedule() {
+        let cron = Cron::new().await.unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        cron.add("* * * * * *", move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .await
+        .unwrap();
+        cron.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(2200)).await;
+        assert!(count.load(Ordering::SeqCst) >= 2);
+        let job = &cron.jobs().await[0];
+        assert!(job.runs >= 2);
+        assert!(job.last_run.is_some());
+        assert!(job.next_run.unwrap() > job.last_run.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_schedules() {
+        let cron = Cron::new().await.unwrap();
+        assert!(cron.add("not a schedule", || {}).await.is_err());
+        assert!(cron.add("@every 0s", || {}).await.is_err());
+        assert!(cron.add("0 9 * * 8", || {}).await.is_err());
+        let options = JobOptions::new().timezone("Mars/Olympus");
+        assert!(cron.add_with("0 0 9 * * *", options, || {}).await.is_err());
+        assert!(cron.jobs().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_timezones_and_standard_fields() {
+        let cron = Cron::new().await.unwrap();
+        let options = JobOptions::new().name("shanghai").timezone("Asia/Shanghai");
+        cron.add_with("0 0 9 * * *", options, || {}).await.unwrap();
+        cron.add("CRON_TZ=Asia/Shanghai 0 9 * * *", || {}).await.unwrap();
+        // Standard weekday numbering: 0 is Sunday
+        cron.add("30 2 * * 0", || {}).await.unwrap();
+        cron.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let jobs = cron.jobs().await;
+        assert_eq!(jobs[0].name.as_deref(), Some("shanghai"));
+        assert_eq!(jobs[0].timezone, "Asia/Shanghai");
+        for job in &jobs[..2] {
+            let next = job.next_run.unwrap();
+            assert_eq!((next.hour(), next.minute(), next.second()), (1, 0, 0));
+        }
+        let next = jobs[2].next_run.unwrap();
+        assert_eq!(jobs[2].timezone, "UTC");
+        assert_eq!((next.weekday(), next.hour(), next.minute()), (Weekday::Sun, 2, 30));
+    }
+
+    #[tokio::test]
+    async fn test_overlap_policies() {
+        let cron = Cron::new().await.unwrap();
+        let slow = || std::thread::sleep(Duration::from_millis(1500));
+        cron.add_with("* * * * * *", JobOptions::new().overlap(Overlap::Skip), slow).await.unwrap();
+        cron.add_with("* * * * * *", JobOptions::new().overlap(Overlap::Concurrent), slow).await.unwrap();
+        cron.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(2600)).await;
+
+        let jobs = cron.jobs().await;
+        assert!(jobs[0].skipped >= 1, "{:?}", jobs[0]);
+        assert!(jobs[0].running <= 1);
+        assert_eq!(jobs[1].skipped, 0);
+        assert!(jobs[1].running + jobs[1].runs as usize >= 2, "{:?}", jobs[1]);
+    }
+
+    #[tokio::test]
+    async fn test_panic_isolation() {
+        let cron = Cron::new().await.unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        cron.add("@every 100ms", move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            panic!("boom");
+        })
+        .await
+        .unwrap();
+        cron.start().await.unwrap();
+
+        // Failures are recorded after each run finishes on the blocking pool
+        for _ in 0..500 {
+            if cron.jobs().await[0].failures >= 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let job = &cron.jobs().await[0];
+        assert!(job.failures >= 3, "{:?}", job);
+        assert!(count.load(Ordering::SeqCst) >= 3);
+        assert_eq!(job.last_error.as_deref(), Some("boom"));
+
+        cron.stop();
+        // A run started before `stop` may still be finishing
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let runs = count.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(count.load(Ordering::SeqCst), runs);
+        assert!(cron.jobs().await[0].next_run.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_leader_election() {
+        // Stands in for etcd, Consul or Redis shared by the replicas
+        let locks: Arc<dyn LockBackend> = Arc::new(MemoryBackend::new());
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut replicas = Vec::new();
+        for _ in 0..3 {
+            let cron = Cron::new().await.unwrap().with_leader_election(locks.clone()).with_lock_prefix("test:cron:");
+            let counter = count.clone();
+            let options = JobOptions::new().name("tick").timeout(Duration::from_secs(5));
+            cron.add_with("@every 200ms", options, move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .await
+            .unwrap();
+            cron.start().await.unwrap();
+            replicas.push(cron);
+        }
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        for cron in &replicas {
+            cron.stop();
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Each tick ran on exactly one replica
+        let mut runs = 0;
+        let mut ticks = Vec::new();
+        for cron in &replicas {
+            let job = &cron.jobs().await[0];
+            assert_eq!(job.timeout_ms, Some(5000));
+            runs += job.runs;
+            ticks.push(job.runs + job.claimed_elsewhere);
+        }
+        assert!(runs >= 4, "{}", runs);
+        assert_eq!(runs as usize, count.load(Ordering::SeqCst));
+        // A replica starting or stopping a moment later may see one tick fewer
+        assert_eq!(runs, *ticks.iter().max().unwrap(), "{:?}", ticks);
+        assert!(ticks.iter().all(|&t| t + 1 >= runs), "{:?} {}", ticks, runs);
+    }
+
+    /// Backend whose store is unreachable
+    struct Unavailable;
+
+    #[async_trait::async_trait]
+    impl LockBackend for Unavailable {
+        fn name(&self) -> &str {
+            "unavailable"
+        }
+
+        async fn try_acquire(&self, _key: &str, _owner: &str, _ttl: Duration) -> Result<bool> {
+            Err(RfError::Network("connection refused".to_string()))
+        }
+
+        async fn renew(&self, _key: &str, _owner: &str, _ttl: Duration) -> Result<bool> {
+            Ok(false)
+        }
+
+        async fn release(&self, _key: &str, _owner: &str) -> Result<bool> {
+            Ok(false)
+        }
+
+        async fn holder(&self, _key: &str) -> Result<Option<String>> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_leader_election_without_lock_skips_ticks() {
+        let cron = Cron::new().await.unwrap().with_leader_election(Arc::new(Unavailable));
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        cron.add_with("@every 100ms", JobOptions::new().name("tick"), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .await
+        .unwrap();
+        cron.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        cron.stop();
+
+        // Running without the lock could run the tick on every replica
+        let job = &cron.jobs().await[0];
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+        assert_eq!(job.runs, 0);
+        assert!(job.skipped >= 2, "{}", job.skipped);
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let cron = Cron::new().await.unwrap();
+        let options = JobOptions::new().overlap(Overlap::Skip).timeout(Duration::from_millis(100));
+        cron.add_with("@every 150ms", options, || std::thread::sleep(Duration::from_millis(400))).await.unwrap();
+        cron.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(350)).await;
+
+        let job = &cron.jobs().await[0];
+        assert_eq!(job.failures, 1);
+        assert_eq!(job.last_error.as_deref(), Some("timed out after 100ms"));
+        // The timed-out run still holds the slot
+        assert_eq!(job.running, 1);
+        assert!(job.skipped >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_on_mock_clock() {
+        // 2024-01-01 00:00:00 UTC
+        let mock = MockClock::at(std::time::UNIX_EPOCH + 19_723.days());
+        let cron = Cron::new().await.unwrap().with_clock(mock.clock());
+        let (nightly, job) = counter();
+        cron.add("0 0 3 * * *", job).await.unwrap();
+        let (every, job) = counter();
+        cron.add("@every 10m", job).await.unwrap();
+        cron.start().await.unwrap();
+        mock.wait_for_sleepers(2).await;
+        let next = cron.jobs().await[0].next_run.unwrap();
+        assert_eq!((next.hour(), next.minute(), next.day()), (3, 0, 1));
+
+        mock.advance(2.hours() + 59.mins()).await;
+        assert_eq!(nightly.load(Ordering::SeqCst), 0);
+        mock.advance(1.mins()).await;
+        // Runs happen on the blocking pool, in real time
+        for _ in 0..100 {
+            if nightly.load(Ordering::SeqCst) == 1 && every.load(Ordering::SeqCst) == 18 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(nightly.load(Ordering::SeqCst), 1);
+        assert_eq!(every.load(Ordering::SeqCst), 18);
+        let next = cron.jobs().await[0].next_run.unwrap();
+        assert_eq!((next.hour(), next.day()), (3, 2));
+    }
+
+    fn counter() -> (Arc<AtomicUsize>, impl Fn() + Send + Sync + 'static) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        (count, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+    }
+
+    #[tokio::test]
+    async fn test_runtime_control() {
+        let cron = Cron::new().await.unwrap();
+        cron.start().await.unwrap();
+        let (count, job) = counter();
+        let id = cron.add_with("@every 100ms", JobOptions::new().name("tick"), job).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(count.load(Ordering::SeqCst) >= 2);
+
+        cron.pause("tick").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let paused_at = count.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(count.load(Ordering::SeqCst), paused_at);
+        assert!(cron.jobs().await[0].paused);
+
+        // Triggering runs a paused job at once
+        cron.trigger(&id.to_string()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(count.load(Ordering::SeqCst), paused_at + 1);
+
+        cron.resume("tick").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(count.load(Ordering::SeqCst) >= paused_at + 2);
+
+        assert!(cron.remove("tick").await.unwrap());
+        assert!(!cron.remove("tick").await.unwrap());
+        assert!(cron.jobs().await.is_empty());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let removed_at = count.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(count.load(Ordering::SeqCst), removed_at);
+        assert!(cron.trigger("tick").is_err());
+        assert!(cron.pause("tick").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stored_jobs_survive_restart() {
+        let store = MemoryJobStore::new();
+        let (count, job) = counter();
+        {
+            let cron = Cron::new().await.unwrap().with_store(store.clone());
+            cron.handler("count", job);
+            assert!(cron.schedule(JobSpec::new("missing", "@every 1s", "nope")).await.is_err());
+            assert!(cron.schedule(JobSpec::new("bad", "never", "count")).await.is_err());
+            cron.schedule(JobSpec::new("fast", "@every 100ms", "count").overlap(Overlap::Skip)).await.unwrap();
+            cron.schedule(JobSpec::new("slow", "@every 1h", "count")).await.unwrap();
+            // Scheduling a name again replaces the job
+            cron.schedule(JobSpec::new("slow", "@every 2h", "count")).await.unwrap();
+            cron.pause("slow").await.unwrap();
+            cron.remove("fast").await.unwrap();
+            cron.schedule(JobSpec::new("fast", "@every 100ms", "count")).await.unwrap();
+            assert_eq!(cron.jobs().await.len(), 2);
+        }
+
+        let specs = store.load().await.unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[1], JobSpec::new("slow", "@every 2h", "count").paused(true));
+
+        // After a restart the handlers are registered again and the jobs restored
+        let cron = Cron::new().await.unwrap().with_store(store.clone());
+        assert_eq!(cron.restore().await.unwrap(), 0, "no handler registered yet");
+        let (_, job) = counter();
+        cron.handler("count", job);
+        assert_eq!(cron.restore().await.unwrap(), 2);
+        let jobs = cron.jobs().await;
+        let slow = jobs.iter().find(|job| job.name.as_deref() == Some("slow")).unwrap();
+        assert!(slow.paused);
+        assert_eq!(slow.schedule, "@every 2h");
+        assert_eq!(slow.handler.as_deref(), Some("count"));
+        cron.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(cron.jobs().await.iter().any(|job| job.runs >= 2));
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_database_job_store() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("cron.db").display());
+        let database = Arc::new(rf_database::db::Database::new_sqlite(&url).await.unwrap());
+        let store = DatabaseJobStore::new(database, "cron_jobs");
+        store.ensure_table().await.unwrap();
+
+        let spec = JobSpec::new("report", "0 30 9 * * 1-5", "report")
+            .timezone("Europe/Berlin")
+            .timeout(Duration::from_secs(60));
+        store.save(&spec).await.unwrap();
+        store.save(&JobSpec::new("cleanup", "@daily", "cleanup")).await.unwrap();
+        store.save(&spec.clone().paused(true)).await.unwrap();
+        let specs = store.load().await.unwrap();
+        assert_eq!(specs, [JobSpec::new("cleanup", "@daily", "cleanup"), spec.paused(true)]);
+
+        store.delete("cleanup").await.unwrap();
+        store.delete("cleanup").await.unwrap();
+        assert_eq!(store.load().await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,68 @@
+//! # daemon_test
+//!
+//! daemon_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Daemon and PID file tests
+
+#[cfg(all(test, unix))]
+mod tests {
+    use rf_os::proc::{pid_alive, stop_pid, Daemon, PidFile, PidStatus};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pid_file() {
+        let dir = TempDir::new().unwrap();
+        let pid_file = PidFile::new(dir.path().join("run/app.pid"));
+        assert_eq!(pid_file.read().unwrap(), None);
+        assert_eq!(pid_file.status().unwrap(), PidStatus::Stopped);
+        pid_file.remove().unwrap();
+
+        pid_file.write(std::process::id()).unwrap();
+        assert_eq!(pid_file.status().unwrap(), PidStatus::Running(std::process::id()));
+
+        // PIDs above the kernel limit never exist
+        pid_file.write(999_999_999).unwrap();
+        assert_eq!(pid_file.status().unwrap(), PidStatus::Stale(999_999_999));
+
+        std::fs::write(pid_file.path(), "not a pid").unwrap();
+        assert!(pid_file.read().is_err());
+        pid_file.remove().unwrap();
+        assert!(!pid_file.path().exists());
+    }
+
+    #[tokio::test]
+    async fn test_daemon_lifecycle() {
+        let dir = TempDir::new().unwrap();
+        let log = dir.path().join("logs/app.log");
+        let pid_path = dir.path().join("app.pid");
+        let daemon = Daemon::new("sh")
+            .args(["-c", "echo \"started $MODE\"; echo oops >&2; trap 'exit 0' TERM; while true; do sleep 0.05; done"])
+            .env("MODE", "daemon")
+            .log_file(&log)
+            .pid_file(&pid_path);
+
+        let pid = daemon.spawn().unwrap();
+        let pid_file = PidFile::new(&pid_path);
+        assert_eq!(pid_file.status().unwrap(), PidStatus::Running(pid));
+        assert!(daemon.spawn().is_err());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let output = std::fs::read_to_string(&log).unwrap();
+        assert!(output.contains("started daemon") && output.contains("oops"), "{}", output);
+
+        assert!(stop_pid(pid, Duration::from_secs(5)).await.unwrap());
+        assert!(!pid_alive(pid));
+        assert_eq!(pid_file.status().unwrap(), PidStatus::Stale(pid));
+
+        // A stale PID file is replaced
+        let stubborn = Daemon::new("sh").args(["-c", "trap '' TERM; while true; do sleep 0.05; done"]).pid_file(&pid_path);
+        let pid = stubborn.spawn().unwrap();
+        assert_eq!(pid_file.read().unwrap(), Some(pid));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!stop_pid(pid, Duration::from_millis(200)).await.unwrap());
+        assert!(!pid_alive(pid));
+    }
+}
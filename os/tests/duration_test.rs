@@ -0,0 +1,110 @@
+//! # duration_test
+//!
+//! duration_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! RfDuration tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::cache::CacheConfig;
+    use rf_os::cfg::{parse_duration, Config, ConfigAdapter, MemoryConfigAdapter, RfDuration};
+    use rf_os::session::SessionConfig;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_and_display() {
+        let cases = [
+            ("30s", Duration::from_secs(30)),
+            ("5m", Duration::from_secs(300)),
+            ("1h30m", Duration::from_secs(5400)),
+            ("1h 30m", Duration::from_secs(5400)),
+            ("2d", Duration::from_secs(172_800)),
+            ("250ms", Duration::from_millis(250)),
+            ("1.5s", Duration::from_millis(1500)),
+            ("1s500ms", Duration::from_millis(1500)),
+            ("100us", Duration::from_micros(100)),
+            ("45", Duration::from_secs(45)),
+            ("0", Duration::ZERO),
+        ];
+        for (text, expected) in cases {
+            let parsed: RfDuration = text.parse().unwrap();
+            assert_eq!(parsed.as_duration(), expected, "{}", text);
+            // Display parses back to the same value
+            assert_eq!(parsed.to_string().parse::<RfDuration>().unwrap(), parsed);
+        }
+        assert_eq!(RfDuration::from_secs(5400).to_string(), "1h30m");
+        assert_eq!(RfDuration::from_millis(1500).to_string(), "1s500ms");
+        assert_eq!(RfDuration::ZERO.to_string(), "0s");
+
+        for invalid in ["", "abc", "5x", "-5s", "ms", "1h-30m"] {
+            assert!(invalid.parse::<RfDuration>().is_err(), "{}", invalid);
+        }
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("soon"), None);
+    }
+
+    #[test]
+    fn test_serde() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Settings {
+            interval: RfDuration,
+            #[serde(with = "rf_os::cfg::serde_duration")]
+            delay: Duration,
+            #[serde(default, with = "rf_os::cfg::serde_duration::option")]
+            timeout: Option<Duration>,
+        }
+
+        let settings: Settings = serde_json::from_value(serde_json::json!({
+            "interval": "1h30m",
+            "delay": 5,
+            "timeout": "250ms",
+        }))
+        .unwrap();
+        assert_eq!(settings.interval, RfDuration::from_secs(5400));
+        assert_eq!(settings.delay, Duration::from_secs(5));
+        assert_eq!(settings.timeout, Some(Duration::from_millis(250)));
+        assert_eq!(
+            serde_json::to_value(&settings).unwrap(),
+            serde_json::json!({"interval": "1h30m", "delay": "5s", "timeout": "250ms"})
+        );
+
+        let settings: Settings = serde_json::from_str(r#"{"interval": 0.5, "delay": "1m"}"#).unwrap();
+        assert_eq!(settings.interval, RfDuration::from_millis(500));
+        assert_eq!(settings.timeout, None);
+        assert!(serde_json::from_str::<Settings>(r#"{"interval": "soon", "delay": 1}"#).is_err());
+        assert!(serde_json::from_str::<Settings>(r#"{"interval": -1, "delay": 1}"#).is_err());
+
+        // Integer seconds written before the migration keep working
+        let session: SessionConfig = serde_json::from_str(r#"{"ttl": 60}"#).unwrap();
+        assert_eq!(session.ttl, RfDuration::from_secs(60));
+        let session: SessionConfig = serde_json::from_str(r#"{"ttl": "2h"}"#).unwrap();
+        assert_eq!(session.ttl.as_secs(), 7200);
+    }
+
+    #[test]
+    fn test_config_get_duration() {
+        let adapter = Arc::new(MemoryConfigAdapter::new());
+        adapter.set("server.default.request_timeout", "15s").unwrap();
+        adapter.set("server.default.shutdown_timeout", "soon").unwrap();
+        adapter.set("cache.users.capacity", "500").unwrap();
+        adapter.set("cache.users.ttl", "10m").unwrap();
+        let config = Config::new().adapter(adapter);
+
+        assert_eq!(
+            config.get_duration("server.default.request_timeout").unwrap(),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(config.get_duration("server.default.missing").unwrap(), None);
+        let err = config.get_duration("server.default.shutdown_timeout").unwrap_err();
+        assert!(err.to_string().contains("server.default.shutdown_timeout"));
+
+        let cache = CacheConfig::from_config(&config, "users").unwrap();
+        assert_eq!(cache.capacity, 500);
+        assert_eq!(cache.ttl, Some(RfDuration::from_secs(600)));
+        assert_eq!(cache.idle, None);
+    }
+}
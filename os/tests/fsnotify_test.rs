@@ -0,0 +1,94 @@
+//! # fsnotify_test
+//!
+//! fsnotify_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Typed, filtered and debounced file watching tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::fsnotify::{FsEvent, FsEventStream, FsWatch};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Events delivered until the stream has been quiet for `quiet`
+    async fn collect(events: &mut FsEventStream, quiet: Duration) -> Vec<FsEvent> {
+        let mut collected = Vec::new();
+        while let Ok(Some(event)) = tokio::time::timeout(quiet, events.recv()).await {
+            collected.push(event);
+        }
+        collected
+    }
+
+    /// Give the watcher time to register before touching the tree
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    #[tokio::test]
+    async fn test_bursts_are_coalesced() {
+        let dir = TempDir::new().unwrap();
+        let mut events = FsWatch::new(dir.path()).start().unwrap();
+        settle().await;
+
+        let file = dir.path().join("app.toml");
+        std::fs::write(&file, "a = 1").unwrap();
+        std::fs::write(&file, "a = 2").unwrap();
+        std::fs::write(&file, "a = 3").unwrap();
+        let temp = dir.path().join("scratch.tmp");
+        std::fs::write(&temp, "x").unwrap();
+        std::fs::remove_file(&temp).unwrap();
+        assert_eq!(collect(&mut events, Duration::from_millis(500)).await, [FsEvent::Created(file.clone())]);
+
+        std::fs::write(&file, "a = 4").unwrap();
+        assert_eq!(collect(&mut events, Duration::from_millis(500)).await, [FsEvent::Modified(file.clone())]);
+
+        let renamed = dir.path().join("app.old.toml");
+        std::fs::rename(&file, &renamed).unwrap();
+        assert_eq!(
+            collect(&mut events, Duration::from_millis(500)).await,
+            [FsEvent::Renamed { from: file, to: renamed.clone() }]
+        );
+
+        std::fs::remove_file(&renamed).unwrap();
+        assert_eq!(collect(&mut events, Duration::from_millis(500)).await, [FsEvent::Removed(renamed)]);
+    }
+
+    #[tokio::test]
+    async fn test_recursive_watch_with_globs() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        std::fs::create_dir_all(dir.path().join("target")).unwrap();
+        let mut events = FsWatch::new(dir.path())
+            .include("**/*.rs")
+            .exclude("target/**")
+            .debounce(Duration::from_millis(50))
+            .start()
+            .unwrap();
+        settle().await;
+
+        let nested = dir.path().join("src/nested/lib.rs");
+        std::fs::write(&nested, "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("src/notes.txt"), "skip").unwrap();
+        std::fs::write(dir.path().join("target/build.rs"), "skip").unwrap();
+        assert_eq!(collect(&mut events, Duration::from_millis(400)).await, [FsEvent::Created(nested)]);
+
+        // Non-recursive watches ignore subdirectories
+        let mut events = FsWatch::new(dir.path()).recursive(false).debounce(Duration::ZERO).start().unwrap();
+        settle().await;
+        std::fs::write(dir.path().join("src/other.rs"), "").unwrap();
+        let top = dir.path().join("top.rs");
+        std::fs::write(&top, "").unwrap();
+        let collected = collect(&mut events, Duration::from_millis(300)).await;
+        assert!(collected.iter().all(|event| event.path() == top), "{:?}", collected);
+        assert_eq!(collected.first(), Some(&FsEvent::Created(top)));
+    }
+
+    #[test]
+    fn test_invalid_glob() {
+        let dir = TempDir::new().unwrap();
+        assert!(FsWatch::new(dir.path()).include("src/[").start().is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! # log_sink_test
+//!
+//! log_sink_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Structured logging, rotation and sink tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::cfg::{Config, FileConfigAdapter};
+    use rf_os::log::{self, JsonFormat, RollingFile, TextFormat};
+    use std::io::Write;
+    use std::net::UdpSocket;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Capture {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap().lines().map(str::to_string).collect()
+        }
+    }
+
+    fn emit_sample() {
+        let payments = log::logger("payments").field("region", "eu");
+        let span = tracing::info_span!("request");
+        let _entered = span.enter();
+        payments.with("order_id", "A-1").with("amount", 42).info("order paid");
+        tracing::warn!(retries = 3, "slow upstream");
+    }
+
+    #[test]
+    fn test_json_format() {
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt().event_format(JsonFormat).with_writer(move || writer.clone()).finish();
+        tracing::subscriber::with_default(subscriber, emit_sample);
+
+        let lines: Vec<serde_json::Value> = capture.lines().iter().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["logger"], "payments");
+        assert_eq!(lines[0]["message"], "order paid");
+        assert_eq!(lines[0]["order_id"], "A-1");
+        assert_eq!(lines[0]["amount"], 42);
+        assert_eq!(lines[0]["region"], "eu");
+        assert_eq!(lines[0]["spans"], serde_json::json!(["request"]));
+        assert!(lines[0]["timestamp"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(lines[1]["logger"], "log_sink_test::tests");
+        assert_eq!(lines[1]["retries"], 3);
+    }
+
+    #[test]
+    fn test_text_format() {
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt().event_format(TextFormat).with_writer(move || writer.clone()).finish();
+        tracing::subscriber::with_default(subscriber, emit_sample);
+
+        let lines = capture.lines();
+        assert!(lines[0].ends_with(" INFO request:payments: order paid amount=42 order_id=\"A-1\" region=\"eu\""), "{}", lines[0]);
+        assert!(lines[1].ends_with(" WARN request:log_sink_test::tests: slow upstream retries=3"), "{}", lines[1]);
+    }
+
+    #[test]
+    fn test_rolling_file_size_and_retention() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("logs").join("app.log");
+        let file = RollingFile::new(&path).unwrap().max_size(10).max_files(2);
+        for i in 0..5 {
+            let mut writer = tracing_subscriber::fmt::MakeWriter::make_writer(&file);
+            writeln!(writer, "line number {}", i).unwrap();
+        }
+        // Every write after the first one crossed the limit
+        let rotated = file.rotated_files();
+        assert_eq!(rotated.len(), 2);
+        let names: Vec<String> = rotated.iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert!(names.iter().all(|name| name.starts_with("app.") && name.ends_with(".log")), "{:?}", names);
+        assert_eq!(std::fs::read_to_string(&rotated[1]).unwrap(), "line number 3\n");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "line number 4\n");
+
+        assert_eq!(log::parse_size("10MB").unwrap(), 10 * 1024 * 1024);
+        assert_eq!(log::parse_size("512k").unwrap(), 512 * 1024);
+        assert_eq!(log::parse_size("2048").unwrap(), 2048);
+        assert!(log::parse_size("10 parsecs").is_err());
+    }
+
+    #[test]
+    fn test_sinks_from_config() {
+        let dir = TempDir::new().unwrap();
+        let syslog = UdpSocket::bind("127.0.0.1:0").unwrap();
+        syslog.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let app_log = dir.path().join("app.log");
+        let audit_log = dir.path().join("audit.log");
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(&config_path, format!(r#"
+[logger]
+level = "info"
+
+[[logger.sinks]]
+name = "app"
+type = "file"
+format = "json"
+path = "{}"
+max_size = "1MB"
+rotation = "daily"
+max_files = 3
+
+[[logger.sinks]]
+name = "audit"
+type = "file"
+path = "{}"
+loggers = ["audit"]
+
+[[logger.sinks]]
+type = "syslog"
+address = "udp://{}"
+app_name = "shop"
+level = "error"
+"#, app_log.display(), audit_log.display(), syslog.local_addr().unwrap())).unwrap();
+        let config = Config::new().adapter(Arc::new(FileConfigAdapter::new(config_path.to_str().unwrap()).unwrap()));
+        log::init_from_config(&config).unwrap();
+
+        log::logger("audit").field("user", "alice").info("role granted");
+        log::debug("not written: below logger.level");
+        log::error("payment failed");
+
+        let app: Vec<serde_json::Value> = std::fs::read_to_string(&app_log)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(app.len(), 2, "{:?}", app);
+        assert_eq!(app[0]["user"], "alice");
+        assert_eq!(app[1]["message"], "payment failed");
+
+        let audit = std::fs::read_to_string(&audit_log).unwrap();
+        assert_eq!(audit.lines().count(), 1);
+        assert!(audit.contains("audit: role granted user=\"alice\""));
+
+        let mut buf = [0u8; 2048];
+        let n = syslog.recv(&mut buf).unwrap();
+        let message = String::from_utf8_lossy(&buf[..n]);
+        // user facility, error severity
+        assert!(message.starts_with("<11>1 "), "{}", message);
+        assert!(message.contains(" shop "));
+        assert!(message.ends_with("payment failed"));
+
+        // Runtime level changes
+        log::set_sink_level("audit", "warn").unwrap();
+        log::logger("audit").info("dropped by the sink level");
+        assert_eq!(std::fs::read_to_string(&audit_log).unwrap().lines().count(), 1);
+        assert!(log::set_sink_level("missing", "warn").is_err());
+        // ... still written by the app sink
+        assert_eq!(std::fs::read_to_string(&app_log).unwrap().lines().count(), 3);
+        log::set_level("warn").unwrap();
+        log::info("dropped by the global level");
+        assert_eq!(std::fs::read_to_string(&app_log).unwrap().lines().count(), 3);
+
+        assert!(log::init_from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! # log_test
+//!
+//! log_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Log sampling tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::log::{self, LogSampler, Sampled};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn captured(f: impl FnOnce()) -> Vec<String> {
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .without_time()
+            .with_target(false)
+            .finish();
+        tracing::subscriber::with_default(subscriber, f);
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        output.lines().map(|line| line.trim().to_string()).collect()
+    }
+
+    #[test]
+    fn test_sampler_one_of_n() {
+        let sampler = LogSampler::new().first(2).every(3);
+        let kept: Vec<Option<Sampled>> = (0..8).map(|_| sampler.check("db down")).collect();
+        assert_eq!(kept, [
+            Some(Sampled { count: 1, suppressed: 0 }),
+            Some(Sampled { count: 2, suppressed: 0 }),
+            None,
+            None,
+            Some(Sampled { count: 5, suppressed: 2 }),
+            None,
+            None,
+            Some(Sampled { count: 8, suppressed: 2 }),
+        ]);
+        // Keys are counted separately
+        assert_eq!(sampler.check("cache miss"), Some(Sampled { count: 1, suppressed: 0 }));
+    }
+
+    #[test]
+    fn test_sampler_window_and_rate_limit() {
+        let sampler = LogSampler::new().every(1000).window(Duration::from_millis(20));
+        assert!(sampler.check("timeout").is_some());
+        assert!(sampler.check("timeout").is_none());
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(sampler.check("timeout"), Some(Sampled { count: 1, suppressed: 1 }));
+
+        let limited = LogSampler::new().every(1).rate_limit(2, Duration::from_secs(3600));
+        let kept = (0..5).filter(|_| limited.check("retry").is_some()).count();
+        assert_eq!(kept, 2);
+        assert!(limited.check("other").is_some());
+    }
+
+    #[test]
+    fn test_sampler_output_and_flush() {
+        let lines = captured(|| {
+            let sampler = log::set_module_sampler("database", LogSampler::new().every(3));
+            for _ in 0..4 {
+                sampler.error("connection refused");
+            }
+            for port in [1, 2] {
+                sampler.log_keyed(tracing::Level::WARN, "slow query", &format!("slow query on replica {}", port));
+            }
+            assert_eq!(sampler.flush(), 1);
+            assert_eq!(sampler.flush(), 0);
+            assert!(Arc::ptr_eq(&sampler, &log::module_sampler("database")));
+        });
+        assert_eq!(lines, [
+            "ERROR connection refused module=\"database\"",
+            "ERROR connection refused (suppressed 2 similar messages) module=\"database\" suppressed=2",
+            "WARN slow query on replica 1 module=\"database\"",
+            "WARN slow query on replica 2 (suppressed 1 similar messages) module=\"database\" suppressed=1",
+        ]);
+    }
+
+    #[test]
+    fn test_sampled_per_call_site() {
+        let lines = captured(|| {
+            for i in 0..7 {
+                log::sampled(tracing::Level::WARN, 3).log(&format!("slow upstream {}", i));
+            }
+        });
+        assert_eq!(lines.len(), 3, "{:?}", lines);
+        assert!(lines[0].starts_with("WARN slow upstream 0 module=\"os/tests/log_test.rs:"), "{}", lines[0]);
+        assert!(lines[1].starts_with("WARN slow upstream 3 (suppressed 2 similar messages)"), "{}", lines[1]);
+
+        let stats = log::throttle_stats();
+        let site = stats.iter().find(|s| s.key.starts_with("os/tests/log_test.rs:")).unwrap();
+        assert_eq!((site.written, site.suppressed), (3, 4));
+    }
+
+    #[test]
+    fn test_rate_limited_by_key() {
+        let lines = captured(|| {
+            for i in 0..5 {
+                log::rate_limited("payment-retry", 2).warn(&format!("retrying order {}", i));
+            }
+            log::rate_limited("payment-retry", 2).error("still failing");
+        });
+        assert_eq!(lines, [
+            "WARN retrying order 0 module=\"payment-retry\"",
+            "WARN retrying order 1 module=\"payment-retry\"",
+        ]);
+        let throttle = log::rate_limited("payment-retry", 2);
+        assert_eq!(throttle.stats(), log::ThrottleStats { key: "payment-retry".to_string(), written: 2, suppressed: 4 });
+    }
+}
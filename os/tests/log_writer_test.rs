@@ -0,0 +1,143 @@
+//! # log_writer_test
+//!
+//! log_writer_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Non-blocking log writer tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::log::{self, AsyncWriter, Overflow};
+    use std::io::Write;
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::time::{Duration, Instant};
+    use tracing_subscriber::fmt::MakeWriter;
+
+    /// Collects lines, each write waiting until the gate is open
+    #[derive(Clone, Default)]
+    struct Gated {
+        lines: Arc<Mutex<Vec<String>>>,
+        gate: Arc<(Mutex<bool>, Condvar)>,
+        delay: Duration,
+    }
+
+    impl Gated {
+        fn closed() -> Self {
+            Self::default()
+        }
+
+        fn open() -> Self {
+            let gated = Self::default();
+            gated.release();
+            gated
+        }
+
+        fn release(&self) {
+            *self.gate.0.lock().unwrap() = true;
+            self.gate.1.notify_all();
+        }
+
+        fn lines(&self) -> Vec<String> {
+            self.lines.lock().unwrap().clone()
+        }
+    }
+
+    struct GatedLine(Gated);
+
+    impl Write for GatedLine {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let (open, cond) = &*self.0.gate;
+            let _open = cond.wait_while(open.lock().unwrap(), |open| !*open).unwrap();
+            std::thread::sleep(self.0.delay);
+            self.0.lines.lock().unwrap().push(String::from_utf8_lossy(buf).trim().to_string());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Gated {
+        type Writer = GatedLine;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            GatedLine(self.clone())
+        }
+    }
+
+    fn write_line(writer: &AsyncWriter, line: &str) {
+        writer.make_writer().write_all(format!("{}\n", line).as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_writes_do_not_wait_for_io() {
+        let inner = Gated { delay: Duration::from_millis(20), ..Gated::open() };
+        let writer = AsyncWriter::new(inner.clone());
+        let started = Instant::now();
+        for i in 0..5 {
+            write_line(&writer, &format!("line {}", i));
+        }
+        assert!(started.elapsed() < Duration::from_millis(50), "{:?}", started.elapsed());
+
+        writer.flush();
+        assert_eq!(inner.lines(), ["line 0", "line 1", "line 2", "line 3", "line 4"]);
+        assert_eq!(writer.dropped(), 0);
+    }
+
+    #[test]
+    fn test_drop_oldest_when_full() {
+        let inner = Gated::closed();
+        let writer = AsyncWriter::new(inner.clone()).capacity(2).overflow(Overflow::DropOldest);
+        for i in 0..6 {
+            write_line(&writer, &format!("line {}", i));
+        }
+        inner.release();
+        writer.flush();
+
+        // The background thread may have taken line 0 before the gate; the rest
+        // of the queue keeps only the newest lines
+        let lines = inner.lines();
+        assert_eq!(lines.len() as u64 + writer.dropped(), 6);
+        assert_eq!(lines[lines.len() - 2..], ["line 4", "line 5"]);
+        assert!(writer.dropped() >= 3, "{}", writer.dropped());
+    }
+
+    #[test]
+    fn test_block_when_full() {
+        let inner = Gated::closed();
+        let writer = AsyncWriter::with_options(inner.clone(), 1, Overflow::Block);
+        let producer = {
+            let writer = writer.clone();
+            std::thread::spawn(move || {
+                for i in 0..4 {
+                    write_line(&writer, &format!("line {}", i));
+                }
+            })
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!producer.is_finished());
+
+        inner.release();
+        producer.join().unwrap();
+        writer.flush();
+        assert_eq!(inner.lines(), ["line 0", "line 1", "line 2", "line 3"]);
+        assert_eq!(writer.dropped(), 0);
+    }
+
+    #[test]
+    fn test_global_flush_and_drop() {
+        let inner = Gated::open();
+        let writer = AsyncWriter::new(inner.clone());
+        write_line(&writer, "before shutdown");
+        assert!(log::flush() >= 1);
+        assert_eq!(inner.lines(), ["before shutdown"]);
+
+        // Dropping the last clone writes what is queued
+        write_line(&writer, "last words");
+        drop(writer);
+        assert_eq!(inner.lines(), ["before shutdown", "last words"]);
+    }
+}
@@ -0,0 +1,123 @@
+//! # metric_otel_test
+//!
+//! metric_otel_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! OTLP metrics export tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::cfg::{Config, ConfigAdapter, MemoryConfigAdapter};
+    use rf_os::metric::default_registry;
+    use rf_os::metric_otel::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Collector stub answering every OTLP/HTTP export, forwarding head and body
+    fn collector() -> (String, mpsc::Receiver<(String, Vec<u8>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/v1/metrics", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { return };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut head = String::new();
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                        head.push_str(&line.to_ascii_lowercase());
+                        line.clear();
+                    }
+                    if head.is_empty() {
+                        break;
+                    }
+                    let length = head
+                        .lines()
+                        .find_map(|l| l.strip_prefix("content-length:"))
+                        .and_then(|l| l.trim().parse().ok())
+                        .unwrap_or(0);
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    let _ = stream.write_all(
+                        b"HTTP/1.1 200 OK\r\ncontent-type: application/x-protobuf\r\ncontent-length: 0\r\n\r\n",
+                    );
+                    let _ = tx.send((head, body));
+                }
+            }
+        });
+        (endpoint, rx)
+    }
+
+    fn contains(body: &[u8], needle: &str) -> bool {
+        body.windows(needle.len()).any(|w| w == needle.as_bytes())
+    }
+
+    #[test]
+    fn test_config_section() {
+        let adapter = Arc::new(MemoryConfigAdapter::new());
+        adapter.set("metrics.otlp.protocol", "http").unwrap();
+        adapter.set("metrics.otlp.endpoint", "http://collector:4318/v1/metrics").unwrap();
+        adapter.set("metrics.otlp.interval", "15s").unwrap();
+        adapter.set("metrics.otlp.resource.region", "eu").unwrap();
+        let config = OtlpMetricsConfig::from_config(&Config::new().adapter(adapter)).unwrap();
+        assert_eq!(config.protocol, OtlpProtocol::Http);
+        assert_eq!(config.endpoint.as_deref(), Some("http://collector:4318/v1/metrics"));
+        assert_eq!(config.interval, Duration::from_secs(15));
+        assert_eq!(config.timeout, Duration::from_secs(10));
+        assert_eq!(config.resource.get("region").map(String::as_str), Some("eu"));
+        assert!(config.bridge);
+
+        let defaults = OtlpMetricsConfig::default();
+        assert_eq!(defaults.protocol, OtlpProtocol::Grpc);
+        assert_eq!(defaults.interval, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_grpc_needs_runtime() {
+        let err = OtlpMetrics::start(&OtlpMetricsConfig::new(OtlpProtocol::Grpc, "http://127.0.0.1:4317")).err().unwrap();
+        assert!(err.to_string().contains("tokio runtime"), "{}", err);
+    }
+
+    #[test]
+    fn test_http_push_bridges_registry() {
+        let (endpoint, exports) = collector();
+        let registry = default_registry();
+        registry.counter_vec("otel_orders_total", "Orders placed", &["region"]).unwrap().with(&["eu"]).inc_by(3.0);
+        let latency = registry.histogram("otel_latency_seconds", "Latency", &[0.1, 1.0]).unwrap();
+
+        let otlp = OtlpMetrics::start(
+            &OtlpMetricsConfig::new(OtlpProtocol::Http, &endpoint)
+                .service_name("orders")
+                .resource("deployment.environment", "staging")
+                .header("x-api-key", "secret")
+                .interval(Duration::from_secs(3600)),
+        )
+        .unwrap();
+        latency.observe(0.5);
+        // Registered after start, picked up by the next push
+        registry.gauge("otel_queue_depth", "Queue depth").unwrap().set(7.0);
+        otlp.flush().unwrap();
+
+        let (head, body) = exports.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(head.starts_with("post /v1/metrics"), "{}", head);
+        assert!(head.contains("x-api-key: secret"), "{}", head);
+        for expected in [
+            "otel_orders_total",
+            "region",
+            "otel_latency_seconds",
+            "otel_queue_depth",
+            "orders",
+            "deployment.environment",
+            "staging",
+        ] {
+            assert!(contains(&body, expected), "{} missing from export", expected);
+        }
+        otlp.shutdown().unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+//! # metric_test
+//!
+//! metric_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Metric rollup and registry tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::metric::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_rolling_histogram_windows() {
+        let start = Instant::now();
+        let histogram = RollingHistogram::new();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        // 1..=1000 ten minutes ago, 10x slower values in the last minute
+        for value in 1..=1000 {
+            histogram.record_at(value as f64, at(0));
+        }
+        for value in 1..=100 {
+            histogram.record_at(value as f64 * 100.0, at(590));
+        }
+        histogram.record_at(f64::NAN, at(590));
+
+        let minute = histogram.stats_at(Duration::from_secs(60), at(600)).unwrap();
+        assert_eq!(minute.count, 100);
+        assert_eq!((minute.min, minute.max), (100.0, 10_000.0));
+        assert!((minute.p50 - 5_000.0).abs() / 5_000.0 <= 0.02, "{:?}", minute);
+        assert!((minute.p99 - 9_900.0).abs() / 9_900.0 <= 0.02, "{:?}", minute);
+        assert!((minute.rate - 100.0 / 60.0).abs() < 1e-9);
+
+        let quarter = histogram.stats_at(Duration::from_secs(15 * 60), at(600)).unwrap();
+        assert_eq!(quarter.count, 1100);
+        assert!((quarter.p50 - 550.0).abs() / 550.0 <= 0.02, "{:?}", quarter);
+        assert!((quarter.avg - (500_500.0 + 505_000.0) / 1100.0).abs() < 1e-6);
+
+        // Old slots expire
+        assert!(histogram.stats_at(Duration::from_secs(60), at(700)).is_none());
+        histogram.record_at(1.0, at(1000));
+        assert_eq!(histogram.stats_at(Duration::from_secs(15 * 60), at(1000)).unwrap().count, 101);
+    }
+
+    #[test]
+    fn test_rolling_histogram_signed_values() {
+        let start = Instant::now();
+        let histogram = RollingHistogram::with_resolution(Duration::from_secs(1), Duration::from_secs(10));
+        for value in [-50.0, -5.0, 0.0, 0.0, 5.0, 50.0] {
+            histogram.record_at(value, start);
+        }
+        let stats = histogram.stats_at(Duration::from_secs(5), start).unwrap();
+        assert_eq!((stats.count, stats.min, stats.max, stats.sum), (6, -50.0, 50.0, 0.0));
+        assert_eq!(stats.p50, 0.0);
+        assert!((stats.p95 - 5.0).abs() <= 0.05, "{:?}", stats);
+
+        // Negative values sort below zero
+        histogram.record_at(-7.0, start);
+        histogram.record_at(-7.0, start);
+        let stats = histogram.stats_at(Duration::from_secs(5), start).unwrap();
+        assert!((stats.p50 - -5.0).abs() <= 0.05, "{:?}", stats);
+    }
+
+    #[test]
+    fn test_rollup_registry() {
+        for value in [10.0, 20.0, 30.0] {
+            histogram_record("rollup_test_latency_ms", value);
+        }
+        histogram_record_dynamic("rollup_test_dynamic".to_string(), 1.0);
+
+        let stats = rollup_stats("rollup_test_latency_ms", Duration::from_secs(60)).unwrap();
+        assert_eq!((stats.count, stats.min, stats.max), (3, 10.0, 30.0));
+        assert!((stats.p50 - 20.0).abs() <= 0.2);
+        assert!(rollup_stats("rollup_test_missing", Duration::from_secs(60)).is_none());
+        assert!(rollup_names().contains(&"rollup_test_dynamic".to_string()));
+
+        let snapshot = rollup_snapshot();
+        let windows = &snapshot["rollup_test_latency_ms"];
+        assert_eq!(windows.keys().copied().collect::<Vec<_>>(), ["15m", "1m", "5m"]);
+        assert_eq!(serde_json::to_value(&windows["5m"]).unwrap()["count"], 3);
+    }
+
+    #[test]
+    fn test_registry_encode() {
+        let registry = MetricRegistry::new();
+        let jobs = registry.counter_vec("jobs_total", "Jobs run", &["queue", "outcome"]).unwrap();
+        jobs.with(&["emails", "ok"]).inc();
+        jobs.with(&["emails", "ok"]).inc_by(2.0);
+        jobs.with(&["say \"hi\"\n", "failed"]).inc();
+        registry.gauge("queue_depth", "Queued jobs").unwrap().set(-1.5);
+        let latency = registry.histogram("job_duration_seconds", "Job duration", &[0.1, 1.0]).unwrap();
+        latency.observe(0.05);
+        latency.observe(0.5);
+        latency.observe(5.0);
+        latency.observe(f64::NAN);
+        // Families without series are left out
+        registry.counter_vec("unused_total", "Never recorded", &["a"]).unwrap();
+
+        assert_eq!(
+            registry.encode(),
+            "# HELP job_duration_seconds Job duration\n\
+             # TYPE job_duration_seconds histogram\n\
+             job_duration_seconds_bucket{le=\"0.1\"} 1\n\
+             job_duration_seconds_bucket{le=\"1\"} 2\n\
+             job_duration_seconds_bucket{le=\"+Inf\"} 3\n\
+             job_duration_seconds_sum 5.55\n\
+             job_duration_seconds_count 3\n\
+             # HELP jobs_total Jobs run\n\
+             # TYPE jobs_total counter\n\
+             jobs_total{queue=\"emails\",outcome=\"ok\"} 3\n\
+             jobs_total{queue=\"say \\\"hi\\\"\\n\",outcome=\"failed\"} 1\n\
+             # HELP queue_depth Queued jobs\n\
+             # TYPE queue_depth gauge\n\
+             queue_depth -1.5\n"
+        );
+    }
+
+    #[test]
+    fn test_registry_registration() {
+        let registry = MetricRegistry::new();
+        let first = registry.counter_vec("requests_total", "Requests", &["method"]).unwrap();
+        let again = registry.counter_vec("requests_total", "Requests", &["method"]).unwrap();
+        first.with(&["GET"]).inc();
+        assert_eq!(again.with(&["GET"]).get(), 1.0);
+
+        // Other type or labels, invalid names
+        assert!(registry.gauge_vec("requests_total", "Requests", &["method"]).is_err());
+        assert!(registry.counter_vec("requests_total", "Requests", &["path"]).is_err());
+        assert!(registry.counter("1st", "").is_err());
+        assert!(registry.counter_vec("ok_total", "", &["__reserved"]).is_err());
+        assert!(registry.histogram_vec("h", "", &["le"], DEFAULT_BUCKETS).is_err());
+        assert!(registry.histogram("h", "", &[1.0, 0.5]).is_err());
+
+        // Counters only go up; missing label values are empty
+        let counter = first.with(&[]);
+        counter.inc_by(-3.0);
+        assert_eq!(counter.get(), 0.0);
+        assert!(first.remove(&[]));
+        assert!(registry.unregister("requests_total"));
+        assert!(registry.names().is_empty());
+        assert_eq!(prometheus_name("http.requests-total"), "http_requests_total");
+        assert_eq!(prometheus_name("5xx"), "_5xx");
+    }
+
+    #[test]
+    fn test_collectors() {
+        let registry = MetricRegistry::new();
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let seen = calls.clone();
+        registry.collector("calls", move |registry| {
+            let n = seen.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            registry.gauge("collector_calls", "Scrapes").unwrap().set(n as f64);
+        });
+        registry.encode();
+        assert!(registry.encode().contains("collector_calls 2\n"));
+        assert!(registry.remove_collector("calls"));
+
+        register_process_metrics(&registry);
+        let text = registry.encode();
+        for name in ["process_cpu_seconds_total", "process_resident_memory_bytes", "process_start_time_seconds"] {
+            assert!(text.contains(&format!("# TYPE {} ", name)), "{} missing:\n{}", name, text);
+        }
+        let rss = text.lines().find_map(|line| line.strip_prefix("process_resident_memory_bytes ")).unwrap();
+        assert!(rss.parse::<f64>().unwrap() > 0.0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_runtime_and_pool_collectors() {
+        let registry = MetricRegistry::new();
+        register_runtime_metrics(&registry, tokio::runtime::Handle::current());
+        let database = std::sync::Arc::new(rf_database::db::Database::new_sqlite("sqlite::memory:").await.unwrap());
+        register_pool_metrics(&registry, "main", database);
+
+        let text = registry.encode();
+        assert!(text.contains("tokio_runtime_workers 2\n"), "{}", text);
+        assert!(text.contains("db_pool_connections{pool=\"main\",state=\"idle\"}"), "{}", text);
+        assert!(text.contains("db_pool_max_connections{pool=\"main\"}"), "{}", text);
+    }
+
+    #[test]
+    fn test_helpers_export_labels() {
+        counter_inc_with_labels(
+            "export.test.calls".to_string(),
+            vec![("route".to_string(), "/users".to_string())],
+            2,
+        );
+        gauge_set("export_test_gauge", 4.0);
+        let text = default_registry().encode();
+        assert!(text.contains("export_test_calls{route=\"/users\"} 2\n"), "{}", text);
+        assert!(text.contains("export_test_gauge 4\n"), "{}", text);
+    }
+}
@@ -0,0 +1,57 @@
+//! # mode_test
+//!
+//! mode_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Runtime mode behavior tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::cfg::*;
+    use rf_os::view::View;
+    use rf_util::mode::{self, Mode};
+    use std::sync::Arc;
+
+    fn config(values: &[(&str, &str)]) -> Config {
+        let values = values.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        let adapter = MemoryConfigAdapter::from_values(values);
+        Config::new()
+            .adapter(Arc::new(adapter))
+            .with_schema(ConfigSchema::new().key(KeySpec::new("server.port", TypeRule::Integer)))
+    }
+
+    /// Switches the process-wide mode, so everything runs in one test
+    #[test]
+    fn test_mode_switches_defaults() {
+        let typo = [("app.mode", "prod"), ("server.port", "8080"), ("server.prot", "8081")];
+
+        // Unknown keys are warnings unless validation is strict
+        assert!(config(&typo).with_strict_validation(false).validate().is_ok());
+        let error = config(&typo).with_strict_validation(true).validate().unwrap_err().to_string();
+        assert!(error.contains("server.prot"), "{}", error);
+
+        // app.mode switches the mode, and production validates strictly
+        assert_eq!(config(&typo).apply_mode().unwrap(), Mode::Production);
+        assert!(mode::is_prod());
+        assert!(config(&typo).validate().is_err());
+        assert!(config(&[("app.mode", "qa")]).apply_mode().is_err());
+        assert_eq!(current_profile(), "prod");
+
+        // Templates reload on every render in development mode
+        let dir = tempfile::tempdir().unwrap();
+        let page = dir.path().join("page.html");
+        std::fs::write(&page, "v1").unwrap();
+        let glob = format!("{}/*.html", dir.path().display());
+        let cached = View::new(&glob).unwrap();
+        mode::set(Mode::Development);
+        let reloading = View::new(&glob).unwrap();
+        std::fs::write(&page, "v2").unwrap();
+        assert_eq!(cached.render("page.html", &serde_json::json!({})).unwrap(), "v1");
+        assert_eq!(reloading.render("page.html", &serde_json::json!({})).unwrap(), "v2");
+        assert_eq!(cached.auto_reload(true).render("page.html", &serde_json::json!({})).unwrap(), "v2");
+
+        mode::reset();
+    }
+}
@@ -0,0 +1,165 @@
+//! # pipeline_test
+//!
+//! pipeline_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Pipeline tests
+
+#[cfg(test)]
+mod tests {
+    use rf_errors::RfError;
+    use rf_os::pipeline::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_pipeline_stages_and_stats() {
+        let mut output = Pipeline::<u64>::new("test_stages")
+            .stage("double", 4, |n| async move { Ok(n * 2) })
+            .stage("format", 2, |n| async move { Ok(n.to_string()) })
+            .run(1..=100)
+            .await
+            .unwrap();
+        output.sort_by_key(|s| s.parse::<u64>().unwrap());
+        let expected: Vec<String> = (1..=100).map(|n| (n * 2).to_string()).collect();
+        assert_eq!(output, expected);
+
+        let mut handle = Pipeline::<u64>::new("test_stats")
+            .stage("inc", 3, |n| async move { Ok(n + 1) })
+            .start();
+        for n in 0..10 {
+            handle.send(n).await.unwrap();
+        }
+        handle.close();
+        let mut sum = 0;
+        while let Some(n) = handle.recv().await {
+            sum += n;
+        }
+        assert_eq!(sum, 55);
+        let stats = handle.join().await.unwrap();
+        let inc = stats.stage("inc").unwrap();
+        assert_eq!(inc.workers, 3);
+        assert_eq!(inc.processed, 10);
+        assert_eq!(inc.failed, 0);
+        assert_eq!(inc.in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_concurrency_is_bounded() {
+        let current = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (c, p) = (current.clone(), peak.clone());
+        Pipeline::<u32>::new("test_concurrency")
+            .buffer(2)
+            .sink("slow", 4, move |_| {
+                let (c, p) = (c.clone(), p.clone());
+                async move {
+                    let now = c.fetch_add(1, Ordering::SeqCst) + 1;
+                    p.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    c.fetch_sub(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+            .run(0..40)
+            .await
+            .unwrap();
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1 && peak <= 4, "peak concurrency {}", peak);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_error_routing() {
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let routed = failures.clone();
+        let mut handle = Pipeline::<i32>::new("test_errors")
+            .stage("validate", 2, |n| async move {
+                if n % 3 == 0 {
+                    Err(RfError::Validation(format!("{} is divisible by 3", n)))
+                } else {
+                    Ok(n)
+                }
+            })
+            .on_error(move |err| routed.lock().unwrap().push((err.stage.clone(), err.error.to_string())))
+            .start();
+        for n in 1..=9 {
+            handle.send(n).await.unwrap();
+        }
+        handle.close();
+        let mut output = Vec::new();
+        while let Some(n) = handle.recv().await {
+            output.push(n);
+        }
+        output.sort();
+        assert_eq!(output, vec![1, 2, 4, 5, 7, 8]);
+
+        let stats = handle.join().await.unwrap();
+        assert_eq!(stats.failed(), 3);
+        assert_eq!(stats.stage("validate").unwrap().processed, 6);
+        let failures = failures.lock().unwrap();
+        assert_eq!(failures.len(), 3);
+        assert!(failures.iter().all(|(stage, _)| stage == "validate"));
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_stop_on_error() {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+        let handle = Pipeline::<u32>::new("test_stop")
+            .stage("check", 1, |n| async move {
+                if n == 5 {
+                    Err(RfError::Internal("bad record".to_string()))
+                } else {
+                    Ok(n)
+                }
+            })
+            .sink("count", 1, move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { Ok(()) }
+            })
+            .stop_on_error(true)
+            .start();
+        for n in 0..1000 {
+            if handle.send(n).await.is_err() {
+                break;
+            }
+        }
+        assert!(handle.is_stopped());
+        let err = handle.join().await.unwrap_err();
+        assert!(err.to_string().contains("bad record"));
+        assert!(seen.load(Ordering::SeqCst) < 1000);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_fan_out_and_batch() {
+        let batches = Pipeline::<&'static str>::new("test_batch")
+            .flat_stage("split", 2, |line| async move {
+                Ok(line.split(',').map(str::to_string).collect::<Vec<_>>())
+            })
+            .flat_stage("non_empty", 1, |word| async move { Ok((!word.is_empty()).then_some(word)) })
+            .batch("batch", 4, Duration::from_secs(5))
+            .run(vec!["a,b,c", "d,,e", "f,g,h,i"])
+            .await
+            .unwrap();
+        let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![4, 4, 1]);
+        assert_eq!(batches.concat().len(), 9);
+
+        // A partial batch is flushed after max_wait even while the input stays open
+        let mut handle = Pipeline::<u32>::new("test_batch_wait")
+            .batch("batch", 100, Duration::from_millis(20))
+            .start();
+        handle.send(1).await.unwrap();
+        handle.send(2).await.unwrap();
+        let batch = tokio::time::timeout(Duration::from_secs(2), handle.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(batch, vec![1, 2]);
+        let stats = handle.join().await.unwrap();
+        assert_eq!(stats.stage("batch").unwrap().processed, 1);
+    }
+}
@@ -0,0 +1,112 @@
+//! # proc_test
+//!
+//! proc_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Process supervision tests
+
+#[cfg(all(test, unix))]
+mod tests {
+    use rf_os::proc::{
+        HealthCheck, HealthProbe, OutputLine, OutputStream, ProcessSpec, ProcessState, ProcessStatus, RestartPolicy,
+        Supervisor,
+    };
+    use std::time::Duration;
+
+    fn shell(name: &str, script: &str) -> ProcessSpec {
+        ProcessSpec::new(name, "sh").args(["-c", script])
+    }
+
+    /// Poll the status of `name` until `done` holds
+    async fn wait_for(supervisor: &Supervisor, name: &str, done: impl Fn(&ProcessStatus) -> bool) -> ProcessStatus {
+        for _ in 0..200 {
+            let status = supervisor.status(name).await.unwrap();
+            if done(&status) {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("{:?}", supervisor.status(name).await);
+    }
+
+    #[tokio::test]
+    async fn test_output_env_and_cwd() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let supervisor = Supervisor::new();
+        let spec = shell("hello", "echo \"$GREETING from $(pwd)\"; echo oops >&2")
+            .env("GREETING", "hello")
+            .current_dir(dir.path())
+            .restart(RestartPolicy::OnFailure);
+        assert!(supervisor.spawn(spec).await.unwrap() > 0);
+
+        let status = wait_for(&supervisor, "hello", |status| status.state == ProcessState::Stopped).await;
+        assert_eq!((status.last_exit, status.restarts, status.pid), (Some(0), 0, None));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let output = supervisor.output("hello").await;
+        let cwd = dir.path().canonicalize().unwrap();
+        assert!(output.contains(&OutputLine {
+            stream: OutputStream::Stdout,
+            line: format!("hello from {}", cwd.display()),
+        }));
+        assert!(output.contains(&OutputLine { stream: OutputStream::Stderr, line: "oops".to_string() }));
+    }
+
+    #[tokio::test]
+    async fn test_restarts_with_backoff_until_limit() {
+        let supervisor = Supervisor::new();
+        let spec = shell("crashy", "exit 3")
+            .backoff(Duration::from_millis(10), Duration::from_millis(40))
+            .max_restarts(3);
+        supervisor.spawn(spec).await.unwrap();
+
+        let status = wait_for(&supervisor, "crashy", |status| matches!(status.state, ProcessState::Failed(_))).await;
+        assert_eq!((status.restarts, status.last_exit), (3, Some(3)));
+
+        // A clean exit is restarted only under `Always`
+        let spec = shell("always", "exit 0").restart(RestartPolicy::Always).backoff(Duration::from_millis(10), Duration::from_millis(10));
+        supervisor.spawn(spec).await.unwrap();
+        wait_for(&supervisor, "always", |status| status.restarts >= 2).await;
+        assert_eq!(supervisor.stop("always").await.unwrap().state, ProcessState::Stopped);
+        assert!(supervisor.status("always").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stop_terminates_then_kills() {
+        let supervisor = Supervisor::new();
+        supervisor.spawn(shell("polite", "trap 'exit 0' TERM; while true; do sleep 0.05; done")).await.unwrap();
+        let stubborn = shell("stubborn", "trap '' TERM; while true; do sleep 0.05; done").stop_timeout(Duration::from_millis(200));
+        supervisor.spawn(stubborn).await.unwrap();
+        assert!(supervisor.spawn(shell("polite", "true")).await.is_err());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let polite = supervisor.stop("polite").await.unwrap();
+        assert_eq!((polite.state, polite.last_exit), (ProcessState::Stopped, Some(0)));
+        let started = std::time::Instant::now();
+        let stubborn = supervisor.stop("stubborn").await.unwrap();
+        assert_eq!((stubborn.state, stubborn.last_exit), (ProcessState::Stopped, None));
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        assert!(supervisor.stop("stubborn").await.is_err());
+        assert!(supervisor.list().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_health_check_restarts() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        let supervisor = Supervisor::new();
+        let check = HealthCheck::new(HealthProbe::Tcp(closed)).interval(Duration::from_millis(20)).failures(2);
+        let spec = shell("sick", "sleep 10")
+            .health(check)
+            .stop_timeout(Duration::from_millis(100))
+            .backoff(Duration::from_millis(10), Duration::from_millis(10));
+        supervisor.spawn(spec).await.unwrap();
+
+        wait_for(&supervisor, "sick", |status| status.restarts >= 1).await;
+        wait_for(&supervisor, "sick", |status| status.healthy == Some(false)).await;
+        assert_eq!(supervisor.stop_all().await.len(), 1);
+    }
+}
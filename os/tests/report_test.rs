@@ -0,0 +1,105 @@
+//! # report_test
+//!
+//! report_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Error reporting tests
+
+#[cfg(test)]
+mod tests {
+    use rf_errors::RfError;
+    use rf_os::cron::{Cron, JobOptions};
+    use rf_os::report::{ErrorReport, ErrorReporting, PiiScrubber, ReportKind};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn collecting() -> (ErrorReporting, Arc<Mutex<Vec<ErrorReport>>>) {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let reporting = ErrorReporting::new().reporter(move |report: &ErrorReport| {
+            sink.lock().unwrap().push(report.clone());
+            Ok(())
+        });
+        (reporting, reports)
+    }
+
+    #[test]
+    fn test_scrubber() {
+        let scrubber = PiiScrubber::new().key("national_id");
+        let mut report = ErrorReport::new(
+            ReportKind::Error,
+            RfError::Internal("charge for bob@example.com with 4111 1111 1111 1111 failed".into()),
+        )
+        .tag("national_id", "X123")
+        .tag("region", "eu");
+        report.context.query = Some("access_token=abc&q=alice@example.com&flag".into());
+        report.context.client_ip = Some("10.0.0.1".into());
+        report.context.headers.insert("cookie".into(), "sid=1".into());
+        report.context.headers.insert("x-api-key".into(), "k".into());
+        report.context.headers.insert("accept".into(), "*/*".into());
+        scrubber.scrub(&mut report);
+
+        assert_eq!(report.message, "Internal error: charge for [email] with [card] failed");
+        assert_eq!(report.context.query.as_deref(), Some("access_token=[Filtered]&q=[email]&flag"));
+        assert_eq!(report.context.client_ip, None);
+        assert_eq!(report.context.headers["cookie"], "[Filtered]");
+        assert_eq!(report.context.headers["x-api-key"], "[Filtered]");
+        assert_eq!(report.context.headers["accept"], "*/*");
+        assert_eq!(report.context.tags["national_id"], "[Filtered]");
+        assert_eq!(report.context.tags["region"], "eu");
+
+        let mut report = ErrorReport::panic("order 42");
+        report.context.client_ip = Some("10.0.0.1".into());
+        PiiScrubber::new().keep_ip(true).pattern(r"order \d+", "order [id]").unwrap().scrub(&mut report);
+        assert_eq!(report.message, "Internal error: panic: order [id]");
+        assert_eq!(report.context.client_ip.as_deref(), Some("10.0.0.1"));
+        assert!(PiiScrubber::new().pattern("(", "x").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sampling() {
+        let (reporting, reports) = collecting();
+        let reporting = reporting.sample_rate(0.0).tag("env", "test");
+        assert!(!reporting.report(ErrorReport::new(ReportKind::Error, RfError::Internal("dropped".into()))).await);
+        assert!(reporting.report(ErrorReport::panic("kept")).await);
+        {
+            let reports = reports.lock().unwrap();
+            assert_eq!(reports.len(), 1);
+            assert_eq!(reports[0].context.tags["env"], "test");
+        }
+
+        // Nothing is sampled without reporters
+        assert!(!ErrorReporting::new().report(ErrorReport::panic("nobody listens")).await);
+    }
+
+    #[tokio::test]
+    async fn test_cron_jobs_are_reported() {
+        let (reporting, reports) = collecting();
+        let cron = Cron::new().await.unwrap().with_error_reporting(Arc::new(reporting));
+        cron.add_with("@every 100ms", JobOptions::new().name("sync"), || panic!("sync failed")).await.unwrap();
+        cron.add_with("@every 100ms", JobOptions::new().name("slow").timeout(Duration::from_millis(20)), || {
+            std::thread::sleep(Duration::from_millis(100))
+        })
+        .await
+        .unwrap();
+        cron.start().await.unwrap();
+
+        let reported = |job: &str| reports.lock().unwrap().iter().any(|report| report.context.job.as_deref() == Some(job));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while !(reported("sync") && reported("slow")) && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        cron.stop();
+
+        let reports = reports.lock().unwrap();
+        let panic = reports.iter().find(|report| report.context.job.as_deref() == Some("sync")).unwrap();
+        assert_eq!(panic.kind, ReportKind::Panic);
+        assert_eq!(panic.message, "Internal error: panic: sync failed");
+        assert_eq!(panic.context.tags["schedule"], "@every 100ms");
+        let timeout = reports.iter().find(|report| report.context.job.as_deref() == Some("slow")).unwrap();
+        assert_eq!(timeout.kind, ReportKind::Error);
+        assert!(matches!(*timeout.error, RfError::Timeout(_)));
+    }
+}
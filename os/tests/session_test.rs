@@ -0,0 +1,54 @@
+//! # session_test
+//!
+//! session_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Session storage tests
+
+#[cfg(test)]
+mod tests {
+    use rf_os::session::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_memory_session_round_trip() {
+        let manager = SessionManager::new().with_ttl(Duration::from_secs(60));
+        let mut session = manager.create();
+        session.insert("user_id", 42).unwrap();
+        let id = session.id().to_string();
+        manager.store(session).await.unwrap();
+
+        let loaded = manager.get(&id).await.unwrap().unwrap();
+        assert_eq!(loaded.get::<u32>("user_id"), Some(42));
+        manager.delete(&id).await.unwrap();
+        assert!(manager.get(&id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_file_session_expiry() {
+        let temp_dir = TempDir::new().unwrap();
+        let config: SessionConfig = serde_json::from_value(serde_json::json!({
+            "ttl": 60,
+            "storage": { "type": "file", "path": temp_dir.path().to_str().unwrap() }
+        }))
+        .unwrap();
+        let manager = SessionManager::from_config(&config).await.unwrap();
+
+        let mut session = manager.create();
+        session.insert("name", "rf").unwrap();
+        let id = session.id().to_string();
+        manager.store(session).await.unwrap();
+        assert_eq!(manager.get(&id).await.unwrap().unwrap().get::<String>("name").as_deref(), Some("rf"));
+
+        let mut expired = manager.create();
+        expired.expire_in(Duration::ZERO);
+        let expired_id = expired.id().to_string();
+        manager.store(expired).await.unwrap();
+        assert!(manager.get(&expired_id).await.unwrap().is_none());
+        manager.cleanup_expired().await.unwrap();
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+}
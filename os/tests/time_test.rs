@@ -0,0 +1,105 @@
+//! # time_test
+//!
+//! time_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Request timezone rendering tests
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+    use rf_core::ctx::{self, Locale};
+    use rf_os::time::{self, Time};
+    use rf_os::view::View;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Order {
+        created_at: Time,
+        #[serde(with = "time::request_tz")]
+        paid_at: DateTime<Utc>,
+    }
+
+    fn order() -> Order {
+        let dt = Utc.with_ymd_and_hms(2026, 1, 6, 10, 30, 0).unwrap();
+        Order { created_at: Time(dt), paid_at: dt }
+    }
+
+    #[tokio::test]
+    async fn test_serialize_in_request_tz() {
+        let json = serde_json::to_value(order()).unwrap();
+        assert_eq!(json["created_at"], "2026-01-06T10:30:00+00:00");
+
+        let json = ctx::with_locale(Locale::new("zh-CN", "Asia/Shanghai"), async {
+            serde_json::to_value(order()).unwrap()
+        })
+        .await;
+        assert_eq!(json["created_at"], "2026-01-06T18:30:00+08:00");
+        assert_eq!(json["paid_at"], "2026-01-06T18:30:00+08:00");
+
+        // Unknown names render in UTC
+        let json = ctx::with_locale(Locale::new("en", "Mars/Olympus"), async {
+            serde_json::to_value(order()).unwrap()
+        })
+        .await;
+        assert_eq!(json["created_at"], "2026-01-06T10:30:00+00:00");
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_in_request_tz() {
+        let json = r#"{"created_at": "2026-01-06T18:30:00+08:00", "paid_at": "2026-01-06 18:30:00"}"#;
+        let order: Order = ctx::with_locale(Locale::new("zh-CN", "Asia/Shanghai"), async {
+            serde_json::from_str(json).unwrap()
+        })
+        .await;
+        assert_eq!(order.created_at.utc(), order.paid_at);
+        assert_eq!(order.paid_at.to_rfc3339(), "2026-01-06T10:30:00+00:00");
+
+        // 02:30 is skipped when New York moves to daylight saving time
+        let gap = ctx::with_locale(Locale::new("en-US", "America/New_York"), async {
+            "2026-03-08 02:30:00".parse::<Time>()
+        })
+        .await;
+        assert!(gap.is_err());
+        assert!(serde_json::from_str::<Order>(r#"{"created_at": "soon", "paid_at": "now"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_localize() {
+        let dt = Utc.with_ymd_and_hms(2026, 1, 6, 10, 30, 0).unwrap();
+        assert_eq!(time::localize(&dt), "2026-01-06 10:30:00");
+        let cases = [
+            ("zh-CN", "Asia/Shanghai", "2026年01月06日 18:30:00"),
+            ("en-US", "America/New_York", "Jan 06, 2026 05:30:00 AM"),
+            ("en-GB", "Europe/London", "06 Jan 2026 10:30:00"),
+            ("de", "Europe/Berlin", "06.01.2026 11:30:00"),
+        ];
+        for (language, timezone, expected) in cases {
+            let rendered = ctx::with_locale(Locale::new(language, timezone), async { Time(dt).localize() }).await;
+            assert_eq!(rendered, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_view_datetime_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("order.html"),
+            "{{ created_at | datetime }}|{{ created_at | datetime(format=\"%H:%M\") }}|{{ ts | datetime(tz=\"Asia/Tokyo\", format=\"%H:%M %Z\") }}",
+        )
+        .unwrap();
+        let view = View::new(&format!("{}/*.html", dir.path().display())).unwrap();
+        let data = serde_json::json!({ "created_at": Time(Utc.with_ymd_and_hms(2026, 1, 6, 10, 30, 0).unwrap()), "ts": 0 });
+
+        let utc = view.render("order.html", &data).unwrap();
+        assert_eq!(utc, "2026-01-06 10:30:00|10:30|09:00 JST");
+        let shanghai = ctx::with_locale(Locale::new("zh-CN", "Asia/Shanghai"), async {
+            view.render("order.html", &data).unwrap()
+        })
+        .await;
+        assert_eq!(shanghai, "2026年01月06日 18:30:00|18:30|09:00 JST");
+        assert!(view.render("order.html", &serde_json::json!({ "created_at": true, "ts": 0 })).is_err());
+    }
+}
@@ -0,0 +1,308 @@
+//! # clock
+//!
+//! clock 模块 - 时间控制
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! # 时间控制
+//!
+//! [`MockClock`] 是只在测试推进时才走动的时钟。把 [`MockClock::clock`] 交给
+//! 被测组件（例如 `CacheContainer::clock`、`Cron::with_clock`、
+//! `WebhookSender::clock`），再用 [`MockClock::advance`] 推进时间，
+//! TTL 过期、定时任务和重试退避就不必真的等待。
+//!
+//! `advance` 按截止时间顺序逐个唤醒等待中的任务，每唤醒一批就让出执行权，
+//! 让被唤醒的任务运行到下一个等待点，因此一次推进多个周期时，计时器的每次
+//! 触发都会发生。任务在等待真实 I/O 时，先用 [`MockClock::wait_for_sleepers`]
+//! 等它进入时钟等待，再推进时间。
+//!
+//! [`DurationExt`] 让整数直接写成时长，例如 `5.mins()`（`min` 与 `Ord::min`
+//! 同名，因此用复数形式）。
+//!
+//! ## 使用示例
+//!
+//! ```rust
+//! use rf_test::{DurationExt, MockClock};
+//!
+//! # async fn example() {
+//! let mock = MockClock::new();
+//! let clock = mock.clock();
+//! let started = clock.now();
+//!
+//! let sleeper = tokio::spawn({
+//!     let clock = clock.clone();
+//!     async move { clock.sleep(5.mins()).await }
+//! });
+//! mock.wait_for_sleepers(1).await;
+//! mock.advance(5.mins()).await;
+//! sleeper.await.unwrap();
+//! assert_eq!(clock.now() - started, 5.mins());
+//! # }
+//! ```
+
+use rf_core::clock::{Clock, SleepFuture, TimeSource};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::Notify;
+
+/// 每次唤醒后让出执行权的次数
+const SETTLE_YIELDS: usize = 16;
+
+#[derive(Debug)]
+struct State {
+    /// 创建时的单调时间，当前时间为它加上 `elapsed`
+    origin: Instant,
+    elapsed: Duration,
+    /// `elapsed` 为零时的墙上时间
+    system_origin: SystemTime,
+    sleepers: Vec<Sleeper>,
+    next_id: u64,
+}
+
+#[derive(Debug)]
+struct Sleeper {
+    id: u64,
+    deadline: Instant,
+    waker: Waker,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    /// 有任务开始等待时通知
+    registered: Notify,
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 由测试推进的时钟
+///
+/// 克隆共享同一个时间。
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    shared: Arc<Shared>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClock {
+    /// 从当前墙上时间开始的时钟
+    pub fn new() -> Self {
+        Self::at(SystemTime::now())
+    }
+
+    /// 墙上时间从 `time` 开始的时钟，例如让定时任务从某个整点开始运行
+    pub fn at(time: SystemTime) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    origin: Instant::now(),
+                    elapsed: Duration::ZERO,
+                    system_origin: time,
+                    sleepers: Vec::new(),
+                    next_id: 0,
+                }),
+                registered: Notify::new(),
+            }),
+        }
+    }
+
+    /// 交给被测组件使用的时钟
+    pub fn clock(&self) -> Clock {
+        Clock::new(self.clone())
+    }
+
+    /// 创建以来推进的总时间
+    pub fn elapsed(&self) -> Duration {
+        self.shared.lock().elapsed
+    }
+
+    /// 正在等待这个时钟的任务数
+    pub fn sleepers(&self) -> usize {
+        self.shared.lock().sleepers.len()
+    }
+
+    /// 等到至少有 `count` 个任务在等待这个时钟
+    pub async fn wait_for_sleepers(&self, count: usize) {
+        loop {
+            let registered = self.shared.registered.notified();
+            if self.sleepers() >= count {
+                return;
+            }
+            registered.await;
+        }
+    }
+
+    /// 推进 `duration`，按截止时间顺序唤醒到期的任务
+    pub async fn advance(&self, duration: Duration) {
+        let target = self.shared.lock().elapsed + duration;
+        loop {
+            let due = {
+                let mut state = self.shared.lock();
+                let now = state.origin + state.elapsed;
+                let end = state.origin + target;
+                // 下一个尚未到期、且在推进范围内的截止时间
+                let next = state
+                    .sleepers
+                    .iter()
+                    .map(|sleeper| sleeper.deadline)
+                    .filter(|deadline| *deadline > now && *deadline <= end)
+                    .min();
+                let Some(next) = next else {
+                    state.elapsed = target;
+                    break;
+                };
+                state.elapsed = next - state.origin;
+                wake_due(&mut state)
+            };
+            due.into_iter().for_each(Waker::wake);
+            settle().await;
+        }
+        let due = wake_due(&mut self.shared.lock());
+        due.into_iter().for_each(Waker::wake);
+        settle().await;
+    }
+
+    /// 只调整墙上时间，单调时间不变，类似系统校时
+    pub fn set_system_time(&self, time: SystemTime) {
+        let mut state = self.shared.lock();
+        state.system_origin = time.checked_sub(state.elapsed).unwrap_or(time);
+    }
+}
+
+/// 到期任务的 waker；任务在被轮询到时自行移除
+fn wake_due(state: &mut State) -> Vec<Waker> {
+    let now = state.origin + state.elapsed;
+    state
+        .sleepers
+        .iter()
+        .filter(|sleeper| sleeper.deadline <= now)
+        .map(|sleeper| sleeper.waker.clone())
+        .collect()
+}
+
+/// 让被唤醒的任务运行到下一个等待点
+async fn settle() {
+    for _ in 0..SETTLE_YIELDS {
+        tokio::task::yield_now().await;
+    }
+}
+
+impl TimeSource for MockClock {
+    fn now(&self) -> Instant {
+        let state = self.shared.lock();
+        state.origin + state.elapsed
+    }
+
+    fn system_time(&self) -> SystemTime {
+        let state = self.shared.lock();
+        state.system_origin + state.elapsed
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> SleepFuture {
+        Box::pin(MockSleep {
+            shared: self.shared.clone(),
+            deadline,
+            id: None,
+        })
+    }
+}
+
+/// 等待 [`MockClock`] 推进到截止时间
+struct MockSleep {
+    shared: Arc<Shared>,
+    deadline: Instant,
+    /// 已登记的等待
+    id: Option<u64>,
+}
+
+impl Future for MockSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let shared = self.shared.clone();
+        let mut state = shared.lock();
+        if state.origin + state.elapsed >= self.deadline {
+            if let Some(id) = self.id.take() {
+                state.sleepers.retain(|sleeper| sleeper.id != id);
+            }
+            return Poll::Ready(());
+        }
+        match self.id {
+            Some(id) => {
+                if let Some(sleeper) = state.sleepers.iter_mut().find(|sleeper| sleeper.id == id) {
+                    sleeper.waker.clone_from(cx.waker());
+                }
+            }
+            None => {
+                let id = state.next_id;
+                state.next_id += 1;
+                state.sleepers.push(Sleeper {
+                    id,
+                    deadline: self.deadline,
+                    waker: cx.waker().clone(),
+                });
+                self.id = Some(id);
+                drop(state);
+                shared.registered.notify_waiters();
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for MockSleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.shared.lock().sleepers.retain(|sleeper| sleeper.id != id);
+        }
+    }
+}
+
+/// 整数转换为时长
+pub trait DurationExt {
+    /// 毫秒
+    fn ms(self) -> Duration;
+    /// 秒
+    fn secs(self) -> Duration;
+    /// 分钟
+    fn mins(self) -> Duration;
+    /// 小时
+    fn hours(self) -> Duration;
+    /// 天
+    fn days(self) -> Duration;
+}
+
+impl DurationExt for u64 {
+    fn ms(self) -> Duration {
+        Duration::from_millis(self)
+    }
+
+    fn secs(self) -> Duration {
+        Duration::from_secs(self)
+    }
+
+    fn mins(self) -> Duration {
+        Duration::from_secs(self * 60)
+    }
+
+    fn hours(self) -> Duration {
+        Duration::from_secs(self * 3600)
+    }
+
+    fn days(self) -> Duration {
+        Duration::from_secs(self * 86400)
+    }
+}
@@ -16,6 +16,8 @@
 //! ## 子模块
 //!
 //! - [`test`]: 提供断言和测试辅助函数
+//! - [`clock`]：由测试推进的时钟，用于验证过期和调度逻辑
+//! - [`redis`]：内存 Redis 服务，供 `RedisClient` 相关测试使用
 //!
 //! ## 使用示例
 //!
@@ -30,6 +32,9 @@
 //! ```
 
 pub mod test;
+pub mod clock;
+pub mod redis;
 
 pub use test::*;
+pub use clock::{DurationExt, MockClock};
 
@@ -0,0 +1,618 @@
+//! # redis
+//!
+//! redis 模块 - 内存 Redis 服务
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! # 内存 Redis 服务
+//!
+//! [`FakeRedis`] 在本地端口上监听，按 RESP 协议应答，所有连接共享同一份数据，
+//! 测试无需真实的 Redis 即可驱动 `RedisClient`。参数按长度读取，值可以是任意
+//! 字节。
+//!
+//! 支持的命令：
+//!
+//! - 字符串：`GET`、`SET`（含 `NX`、`EX`、`PX`）、`SETEX`、`MGET`、`DEL`、`KEYS`
+//! - 哈希：`HSET`、`HGET`、`HGETALL`
+//! - 列表：`RPUSH`、`LRANGE`、`LPOP`
+//! - 发布订阅：`PUBLISH`、`SUBSCRIBE`
+//! - 流与消费组：`XADD`、`XLEN`、`XRANGE`、`XGROUP CREATE`、`XREADGROUP`、
+//!   `XACK`、`XPENDING`、`XCLAIM`、`XAUTOCLAIM`
+//!
+//! 其余命令一律应答 `OK`。每条命令都会被记录，可用 [`FakeRedis::commands`]
+//! 和 [`FakeRedis::count`] 检查客户端发出了什么。
+//!
+//! ## 使用示例
+//!
+//! ```rust,ignore
+//! use rf_test::redis::FakeRedis;
+//!
+//! let redis = FakeRedis::start().await;
+//! redis.set("session:1", "alice");
+//! let client = RedisClient::new(redis.url()).await?;
+//! assert_eq!(client.get("session:1").await?, "alice");
+//! assert_eq!(redis.count("GET"), 1);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+/// RESP 应答
+enum Reply {
+    Status(&'static str),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Option<Vec<Reply>>),
+}
+
+impl Reply {
+    fn ok() -> Self {
+        Reply::Status("OK")
+    }
+
+    fn bulk(value: impl AsRef<[u8]>) -> Self {
+        Reply::Bulk(Some(value.as_ref().to_vec()))
+    }
+
+    fn array(items: Vec<Reply>) -> Self {
+        Reply::Array(Some(items))
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Reply::Status(status) => out.extend_from_slice(format!("+{}\r\n", status).as_bytes()),
+            Reply::Error(message) => out.extend_from_slice(format!("-{}\r\n", message).as_bytes()),
+            Reply::Integer(value) => out.extend_from_slice(format!(":{}\r\n", value).as_bytes()),
+            Reply::Bulk(None) => out.extend_from_slice(b"$-1\r\n"),
+            Reply::Bulk(Some(value)) => {
+                out.extend_from_slice(format!("${}\r\n", value.len()).as_bytes());
+                out.extend_from_slice(value);
+                out.extend_from_slice(b"\r\n");
+            }
+            Reply::Array(None) => out.extend_from_slice(b"*-1\r\n"),
+            Reply::Array(Some(items)) => {
+                out.extend_from_slice(format!("*{}\r\n", items.len()).as_bytes());
+                for item in items {
+                    item.encode(out);
+                }
+            }
+        }
+    }
+}
+
+/// 流条目 ID，按 `毫秒-序号` 比较
+type EntryId = (u64, u64);
+
+fn parse_id(id: &str) -> Option<EntryId> {
+    match id {
+        "-" | "0" => Some((0, 0)),
+        "+" => Some((u64::MAX, u64::MAX)),
+        _ => {
+            let (ms, seq) = id.split_once('-').unwrap_or((id, "0"));
+            Some((ms.parse().ok()?, seq.parse().ok()?))
+        }
+    }
+}
+
+fn format_id(id: EntryId) -> String {
+    format!("{}-{}", id.0, id.1)
+}
+
+/// 已投递未确认的条目
+struct Pending {
+    consumer: String,
+    delivered_at: Instant,
+    deliveries: usize,
+}
+
+#[derive(Default)]
+struct Group {
+    last_delivered: EntryId,
+    pending: BTreeMap<EntryId, Pending>,
+}
+
+#[derive(Default)]
+struct Stream {
+    entries: BTreeMap<EntryId, Vec<Vec<u8>>>,
+    groups: HashMap<String, Group>,
+}
+
+impl Stream {
+    fn entry(&self, id: EntryId) -> Reply {
+        match self.entries.get(&id) {
+            Some(fields) => Reply::array(vec![
+                Reply::bulk(format_id(id)),
+                Reply::array(fields.iter().map(Reply::bulk).collect()),
+            ]),
+            None => Reply::Bulk(None),
+        }
+    }
+}
+
+/// 哈希的字段与值，保持写入顺序
+type Fields = Vec<(Vec<u8>, Vec<u8>)>;
+
+#[derive(Default)]
+struct State {
+    strings: HashMap<Vec<u8>, Vec<u8>>,
+    expires: HashMap<Vec<u8>, Instant>,
+    hashes: HashMap<Vec<u8>, Fields>,
+    lists: HashMap<Vec<u8>, Vec<Vec<u8>>>,
+    streams: HashMap<Vec<u8>, Stream>,
+    subscribers: Vec<(Vec<u8>, mpsc::UnboundedSender<Vec<u8>>)>,
+    commands: Vec<Vec<Vec<u8>>>,
+    next_ms: u64,
+}
+
+impl State {
+    /// 删除已过期的字符串键
+    fn expire(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<Vec<u8>> = self.expires.iter().filter(|(_, at)| **at <= now).map(|(key, _)| key.clone()).collect();
+        for key in expired {
+            self.expires.remove(&key);
+            self.strings.remove(&key);
+        }
+    }
+
+    fn exists(&self, key: &[u8]) -> bool {
+        self.strings.contains_key(key) || self.hashes.contains_key(key) || self.lists.contains_key(key) || self.streams.contains_key(key)
+    }
+
+    fn execute(&mut self, args: &[Vec<u8>], subscriber: &mpsc::UnboundedSender<Vec<u8>>) -> Reply {
+        self.expire();
+        let name = String::from_utf8_lossy(&args[0]).to_ascii_uppercase();
+        let text = |index: usize| String::from_utf8_lossy(&args[index]).into_owned();
+        let option = |flag: &str| args.iter().position(|arg| arg.eq_ignore_ascii_case(flag.as_bytes()));
+        match name.as_str() {
+            "GET" => Reply::Bulk(self.strings.get(&args[1]).cloned()),
+            "MGET" => Reply::array(args[1..].iter().map(|key| Reply::Bulk(self.strings.get(key).cloned())).collect()),
+            "SET" => {
+                if option("NX").is_some() && self.strings.contains_key(&args[1]) {
+                    return Reply::Bulk(None);
+                }
+                self.strings.insert(args[1].clone(), args[2].clone());
+                let ttl = match (option("PX"), option("EX")) {
+                    (Some(px), _) => Some(Duration::from_millis(text(px + 1).parse().unwrap_or(0))),
+                    (_, Some(ex)) => Some(Duration::from_secs(text(ex + 1).parse().unwrap_or(0))),
+                    _ => None,
+                };
+                match ttl {
+                    Some(ttl) => self.expires.insert(args[1].clone(), Instant::now() + ttl),
+                    None => self.expires.remove(&args[1]),
+                };
+                Reply::ok()
+            }
+            "SETEX" => {
+                self.strings.insert(args[1].clone(), args[3].clone());
+                let ttl = Duration::from_secs(text(2).parse().unwrap_or(0));
+                self.expires.insert(args[1].clone(), Instant::now() + ttl);
+                Reply::ok()
+            }
+            "DEL" => {
+                let removed = args[1..]
+                    .iter()
+                    .filter(|key| {
+                        let existed = self.exists(key);
+                        self.strings.remove(*key);
+                        self.expires.remove(*key);
+                        self.hashes.remove(*key);
+                        self.lists.remove(*key);
+                        self.streams.remove(*key);
+                        existed
+                    })
+                    .count();
+                Reply::Integer(removed as i64)
+            }
+            "KEYS" => {
+                let prefix = text(1).trim_end_matches('*').as_bytes().to_vec();
+                Reply::array(self.strings.keys().filter(|key| key.starts_with(&prefix)).map(Reply::bulk).collect())
+            }
+            "HSET" => {
+                let hash = self.hashes.entry(args[1].clone()).or_default();
+                let mut added = 0;
+                for pair in args[2..].chunks(2) {
+                    let before = hash.len();
+                    hash.retain(|(field, _)| *field != pair[0]);
+                    added += usize::from(hash.len() == before);
+                    hash.push((pair[0].clone(), pair[1].clone()));
+                }
+                Reply::Integer(added as i64)
+            }
+            "HGET" => Reply::Bulk(
+                self.hashes
+                    .get(&args[1])
+                    .and_then(|hash| hash.iter().find(|(field, _)| *field == args[2]))
+                    .map(|(_, value)| value.clone()),
+            ),
+            "HGETALL" => Reply::array(
+                self.hashes
+                    .get(&args[1])
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|(field, value)| [Reply::bulk(field), Reply::bulk(value)])
+                    .collect(),
+            ),
+            "RPUSH" => {
+                let list = self.lists.entry(args[1].clone()).or_default();
+                list.extend(args[2..].iter().cloned());
+                Reply::Integer(list.len() as i64)
+            }
+            "LRANGE" => Reply::array(self.lists.get(&args[1]).into_iter().flatten().map(Reply::bulk).collect()),
+            "LPOP" => {
+                let list = self.lists.entry(args[1].clone()).or_default();
+                Reply::Bulk((!list.is_empty()).then(|| list.remove(0)))
+            }
+            "PUBLISH" => {
+                self.subscribers.retain(|(_, sender)| !sender.is_closed());
+                let mut message = Vec::new();
+                Reply::array(vec![Reply::bulk("message"), Reply::bulk(&args[1]), Reply::bulk(&args[2])]).encode(&mut message);
+                let receivers = self
+                    .subscribers
+                    .iter()
+                    .filter(|(channel, _)| *channel == args[1])
+                    .filter(|(_, sender)| sender.send(message.clone()).is_ok())
+                    .count();
+                Reply::Integer(receivers as i64)
+            }
+            "SUBSCRIBE" => {
+                // 每个频道各有一条确认，除最后一条外直接写入连接
+                for (index, channel) in args[1..].iter().enumerate() {
+                    self.subscribers.push((channel.clone(), subscriber.clone()));
+                    let confirm = Reply::array(vec![Reply::bulk("subscribe"), Reply::bulk(channel), Reply::Integer(index as i64 + 1)]);
+                    if index + 2 < args.len() {
+                        let mut out = Vec::new();
+                        confirm.encode(&mut out);
+                        let _ = subscriber.send(out);
+                    } else {
+                        return confirm;
+                    }
+                }
+                Reply::array(Vec::new())
+            }
+            "XADD" => self.xadd(args),
+            "XLEN" => Reply::Integer(self.streams.get(&args[1]).map_or(0, |stream| stream.entries.len()) as i64),
+            "XRANGE" => {
+                let (start, end) = (parse_id(&text(2)).unwrap_or((0, 0)), parse_id(&text(3)).unwrap_or((u64::MAX, u64::MAX)));
+                let count = option("COUNT").and_then(|i| text(i + 1).parse().ok()).unwrap_or(usize::MAX);
+                let Some(stream) = self.streams.get(&args[1]) else {
+                    return Reply::array(Vec::new());
+                };
+                Reply::array(stream.entries.range(start..=end).take(count).map(|(id, _)| stream.entry(*id)).collect())
+            }
+            "XGROUP" if text(1).eq_ignore_ascii_case("CREATE") => {
+                if !self.streams.contains_key(&args[2]) && option("MKSTREAM").is_none() {
+                    return Reply::Error("ERR The XGROUP subcommand requires the key to exist".to_string());
+                }
+                let stream = self.streams.entry(args[2].clone()).or_default();
+                if stream.groups.contains_key(&text(3)) {
+                    return Reply::Error("BUSYGROUP Consumer Group name already exists".to_string());
+                }
+                let last_delivered = match text(4).as_str() {
+                    "$" => stream.entries.keys().next_back().copied().unwrap_or((0, 0)),
+                    id => parse_id(id).unwrap_or((0, 0)),
+                };
+                stream.groups.insert(text(3), Group { last_delivered, pending: BTreeMap::new() });
+                Reply::ok()
+            }
+            "XREADGROUP" => self.xreadgroup(args),
+            "XACK" => {
+                let Some(group) = self.group(&args[1], &text(2)) else {
+                    return Reply::Integer(0);
+                };
+                let acked = args[3..].iter().filter_map(|id| parse_id(&String::from_utf8_lossy(id))).filter(|id| group.pending.remove(id).is_some()).count();
+                Reply::Integer(acked as i64)
+            }
+            "XPENDING" => self.xpending(args),
+            "XCLAIM" => {
+                let min_idle = Duration::from_millis(text(4).parse().unwrap_or(0));
+                let ids: Vec<EntryId> = args[5..].iter().filter_map(|id| parse_id(&String::from_utf8_lossy(id))).collect();
+                self.claim(&args[1], &text(2), &text(3), min_idle, ids, usize::MAX).map_or_else(
+                    |error| error,
+                    |(claimed, _)| Reply::array(claimed),
+                )
+            }
+            "XAUTOCLAIM" => {
+                let min_idle = Duration::from_millis(text(4).parse().unwrap_or(0));
+                let start = parse_id(&text(5)).unwrap_or((0, 0));
+                let count = option("COUNT").and_then(|i| text(i + 1).parse().ok()).unwrap_or(100);
+                let ids: Vec<EntryId> = match self.group(&args[1], &text(2)) {
+                    Some(group) => group.pending.range(start..).map(|(id, _)| *id).collect(),
+                    None => return Reply::Error("NOGROUP No such key or consumer group".to_string()),
+                };
+                match self.claim(&args[1], &text(2), &text(3), min_idle, ids, count) {
+                    Ok((claimed, next)) => Reply::array(vec![
+                        Reply::bulk(next.map_or_else(|| "0-0".to_string(), format_id)),
+                        Reply::array(claimed),
+                        Reply::array(Vec::new()),
+                    ]),
+                    Err(error) => error,
+                }
+            }
+            _ => Reply::ok(),
+        }
+    }
+
+    fn group(&mut self, key: &[u8], name: &str) -> Option<&mut Group> {
+        self.streams.get_mut(key)?.groups.get_mut(name)
+    }
+
+    fn xadd(&mut self, args: &[Vec<u8>]) -> Reply {
+        // 跳过 `MAXLEN ~ n` 之类的裁剪参数，只认第一个像 ID 的参数
+        let mut index = 2;
+        while index < args.len() && args[index] != b"*" && parse_id(&String::from_utf8_lossy(&args[index])).is_none() {
+            index += 1;
+        }
+        let stream = self.streams.entry(args[1].clone()).or_default();
+        let last = stream.entries.keys().next_back().copied().unwrap_or((0, 0));
+        let id = if args[index] == b"*" {
+            self.next_ms = self.next_ms.max(last.0) + 1;
+            (self.next_ms, 0)
+        } else {
+            let id = parse_id(&String::from_utf8_lossy(&args[index])).unwrap_or((0, 0));
+            if id <= last {
+                return Reply::Error("ERR The ID specified in XADD is equal or smaller than the target stream top item".to_string());
+            }
+            id
+        };
+        stream.entries.insert(id, args[index + 1..].to_vec());
+        Reply::bulk(format_id(id))
+    }
+
+    fn xreadgroup(&mut self, args: &[Vec<u8>]) -> Reply {
+        let text = |index: usize| String::from_utf8_lossy(&args[index]).into_owned();
+        let (group_name, consumer) = (text(2), text(3));
+        let count = args
+            .iter()
+            .position(|arg| arg.eq_ignore_ascii_case(b"COUNT"))
+            .and_then(|i| text(i + 1).parse().ok())
+            .unwrap_or(usize::MAX);
+        let Some(streams_at) = args.iter().position(|arg| arg.eq_ignore_ascii_case(b"STREAMS")) else {
+            return Reply::Error("ERR syntax error".to_string());
+        };
+        let pairs = (args.len() - streams_at - 1) / 2;
+        let mut reads = Vec::new();
+        for index in 0..pairs {
+            let key = &args[streams_at + 1 + index];
+            let id = text(streams_at + 1 + pairs + index);
+            let Some(stream) = self.streams.get_mut(key) else {
+                return Reply::Error("NOGROUP No such key or consumer group".to_string());
+            };
+            let Some(group) = stream.groups.get_mut(&group_name) else {
+                return Reply::Error("NOGROUP No such key or consumer group".to_string());
+            };
+            let ids: Vec<EntryId> = if id == ">" {
+                // 新条目：投递并记入待确认列表
+                let ids: Vec<EntryId> = stream.entries.range((group.last_delivered.0, group.last_delivered.1 + 1)..).take(count).map(|(id, _)| *id).collect();
+                for id in &ids {
+                    group.last_delivered = *id;
+                    group.pending.insert(*id, Pending { consumer: consumer.clone(), delivered_at: Instant::now(), deliveries: 1 });
+                }
+                ids
+            } else {
+                // 历史条目：该消费者尚未确认的部分
+                let start = parse_id(&id).unwrap_or((0, 0));
+                group
+                    .pending
+                    .range(start..)
+                    .filter(|(_, pending)| pending.consumer == consumer)
+                    .take(count)
+                    .map(|(id, _)| *id)
+                    .collect()
+            };
+            if id == ">" && ids.is_empty() {
+                continue;
+            }
+            let entries = ids.into_iter().map(|id| stream.entry(id)).collect();
+            reads.push(Reply::array(vec![Reply::bulk(key), Reply::array(entries)]));
+        }
+        if reads.is_empty() {
+            Reply::Array(None)
+        } else {
+            Reply::array(reads)
+        }
+    }
+
+    fn xpending(&mut self, args: &[Vec<u8>]) -> Reply {
+        let text = |index: usize| String::from_utf8_lossy(&args[index]).into_owned();
+        let Some(group) = self.group(&args[1], &text(2)) else {
+            return Reply::Error("NOGROUP No such key or consumer group".to_string());
+        };
+        if args.len() == 3 {
+            if group.pending.is_empty() {
+                return Reply::array(vec![Reply::Integer(0), Reply::Bulk(None), Reply::Bulk(None), Reply::Array(None)]);
+            }
+            let mut consumers: BTreeMap<&str, usize> = BTreeMap::new();
+            for pending in group.pending.values() {
+                *consumers.entry(&pending.consumer).or_default() += 1;
+            }
+            return Reply::array(vec![
+                Reply::Integer(group.pending.len() as i64),
+                Reply::bulk(format_id(*group.pending.keys().next().unwrap())),
+                Reply::bulk(format_id(*group.pending.keys().next_back().unwrap())),
+                Reply::array(
+                    consumers
+                        .into_iter()
+                        .map(|(name, count)| Reply::array(vec![Reply::bulk(name), Reply::bulk(count.to_string())]))
+                        .collect(),
+                ),
+            ]);
+        }
+        let (start, end) = (parse_id(&text(3)).unwrap_or((0, 0)), parse_id(&text(4)).unwrap_or((u64::MAX, u64::MAX)));
+        let count: usize = text(5).parse().unwrap_or(usize::MAX);
+        let consumer = args.get(6).map(|c| String::from_utf8_lossy(c).into_owned());
+        Reply::array(
+            group
+                .pending
+                .range(start..=end)
+                .filter(|(_, pending)| consumer.as_ref().is_none_or(|c| *c == pending.consumer))
+                .take(count)
+                .map(|(id, pending)| {
+                    Reply::array(vec![
+                        Reply::bulk(format_id(*id)),
+                        Reply::bulk(&pending.consumer),
+                        Reply::Integer(pending.delivered_at.elapsed().as_millis() as i64),
+                        Reply::Integer(pending.deliveries as i64),
+                    ])
+                })
+                .collect(),
+        )
+    }
+
+    /// 把空闲足够久的待确认条目转给 `consumer`，返回条目和下一个游标
+    fn claim(
+        &mut self,
+        key: &[u8],
+        group_name: &str,
+        consumer: &str,
+        min_idle: Duration,
+        ids: Vec<EntryId>,
+        count: usize,
+    ) -> Result<(Vec<Reply>, Option<EntryId>), Reply> {
+        let Some(stream) = self.streams.get_mut(key) else {
+            return Err(Reply::Error("NOGROUP No such key or consumer group".to_string()));
+        };
+        let Some(group) = stream.groups.get_mut(group_name) else {
+            return Err(Reply::Error("NOGROUP No such key or consumer group".to_string()));
+        };
+        let mut claimed = Vec::new();
+        let mut next = None;
+        for id in ids {
+            if claimed.len() == count {
+                next = Some(id);
+                break;
+            }
+            let Some(pending) = group.pending.get_mut(&id) else {
+                continue;
+            };
+            if pending.delivered_at.elapsed() < min_idle {
+                continue;
+            }
+            pending.consumer = consumer.to_string();
+            pending.delivered_at = Instant::now();
+            pending.deliveries += 1;
+            claimed.push(id);
+        }
+        Ok((claimed.into_iter().map(|id| stream.entry(id)).collect(), next))
+    }
+}
+
+/// 监听本地端口的内存 Redis 服务
+#[derive(Clone)]
+pub struct FakeRedis {
+    url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeRedis {
+    /// 在随机端口上启动服务，随测试运行时一起结束
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind fake redis");
+        let url = format!("redis://{}/", listener.local_addr().expect("fake redis address"));
+        let state = Arc::new(Mutex::new(State::default()));
+        let shared = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, shared.clone()));
+            }
+        });
+        Self { url, state }
+    }
+
+    /// 连接地址，形如 `redis://127.0.0.1:port/`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 直接写入字符串键
+    pub fn set(&self, key: &str, value: impl AsRef<[u8]>) {
+        self.state().strings.insert(key.as_bytes().to_vec(), value.as_ref().to_vec());
+    }
+
+    /// 读取字符串键
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let mut state = self.state();
+        state.expire();
+        state.strings.get(key.as_bytes()).cloned()
+    }
+
+    /// 当前所有字符串键
+    pub fn keys(&self) -> Vec<String> {
+        let mut state = self.state();
+        state.expire();
+        state.strings.keys().map(|key| String::from_utf8_lossy(key).into_owned()).collect()
+    }
+
+    /// 收到的全部命令，参数以空格连接
+    pub fn commands(&self) -> Vec<String> {
+        self.state()
+            .commands
+            .iter()
+            .map(|args| args.iter().map(|arg| String::from_utf8_lossy(arg)).collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    /// 名为 `name` 的命令收到的次数，不区分大小写
+    pub fn count(&self, name: &str) -> usize {
+        self.state().commands.iter().filter(|args| args[0].eq_ignore_ascii_case(name.as_bytes())).count()
+    }
+}
+
+async fn serve(stream: tokio::net::TcpStream, state: Arc<Mutex<State>>) {
+    let (read, mut write) = stream.into_split();
+    // 订阅消息与普通应答都经由同一通道按序写出
+    let (sender, mut receiver) = mpsc::unbounded_channel::<Vec<u8>>();
+    tokio::spawn(async move {
+        while let Some(reply) = receiver.recv().await {
+            if write.write_all(&reply).await.is_err() {
+                return;
+            }
+        }
+    });
+    let mut reader = BufReader::new(read);
+    while let Some(args) = read_command(&mut reader).await {
+        if args.is_empty() {
+            continue;
+        }
+        let reply = {
+            let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+            state.commands.push(args.clone());
+            state.execute(&args, &sender)
+        };
+        let mut out = Vec::new();
+        reply.encode(&mut out);
+        if sender.send(out).is_err() {
+            return;
+        }
+    }
+}
+
+/// 读取一条命令的参数，连接关闭或格式错误时返回 `None`
+async fn read_command<R: tokio::io::AsyncBufRead + Unpin>(reader: &mut R) -> Option<Vec<Vec<u8>>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await.ok()? == 0 {
+        return None;
+    }
+    let count: usize = line.trim().strip_prefix('*')?.parse().ok()?;
+    let mut args = Vec::with_capacity(count);
+    for _ in 0..count {
+        line.clear();
+        reader.read_line(&mut line).await.ok()?;
+        let len: usize = line.trim().strip_prefix('$')?.parse().ok()?;
+        let mut arg = vec![0; len + 2];
+        reader.read_exact(&mut arg).await.ok()?;
+        arg.truncate(len);
+        args.push(arg);
+    }
+    Some(args)
+}
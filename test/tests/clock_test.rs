@@ -0,0 +1,104 @@
+//! # clock_test
+//!
+//! clock_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Mock clock tests
+
+#[cfg(test)]
+mod tests {
+    use rf_test::{DurationExt, MockClock};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[tokio::test]
+    async fn test_advance_wakes_sleepers_in_order() {
+        let mock = MockClock::new();
+        let clock = mock.clock();
+        let start = clock.now();
+        let woken = Arc::new(Mutex::new(Vec::new()));
+        for (name, delay) in [("late", 10.mins()), ("early", 1.mins()), ("never", 1.hours())] {
+            let (clock, woken) = (clock.clone(), woken.clone());
+            tokio::spawn(async move {
+                clock.sleep(delay).await;
+                woken.lock().unwrap().push((name, clock.now() - start));
+            });
+        }
+        mock.wait_for_sleepers(3).await;
+
+        mock.advance(59.secs()).await;
+        assert!(woken.lock().unwrap().is_empty());
+        mock.advance(15.mins()).await;
+        // Each sleeper sees the time it asked for, not the end of the advance
+        assert_eq!(*woken.lock().unwrap(), [("early", 1.mins()), ("late", 10.mins())]);
+        assert_eq!(mock.elapsed(), 59.secs() + 15.mins());
+        assert_eq!(mock.sleepers(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_interval_fires_every_period() {
+        let mock = MockClock::new();
+        let clock = mock.clock();
+        let ticks = Arc::new(Mutex::new(Vec::new()));
+        let start = clock.now();
+        tokio::spawn({
+            let (clock, ticks) = (clock.clone(), ticks.clone());
+            async move {
+                let mut interval = clock.interval(5.mins());
+                loop {
+                    let tick = interval.tick().await;
+                    ticks.lock().unwrap().push(tick - start);
+                }
+            }
+        });
+        mock.wait_for_sleepers(1).await;
+        mock.advance(20.mins()).await;
+        assert_eq!(*ticks.lock().unwrap(), [0.mins(), 5.mins(), 10.mins(), 15.mins(), 20.mins()]);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_wall_clock() {
+        let noon = UNIX_EPOCH + 20_000.days() + 12.hours();
+        let mock = MockClock::at(noon);
+        let clock = mock.clock();
+        assert_eq!(clock.system_time(), noon);
+
+        let slow = tokio::spawn({
+            let clock = clock.clone();
+            async move { clock.timeout(30.secs(), std::future::pending::<()>()).await }
+        });
+        let fast = tokio::spawn({
+            let clock = clock.clone();
+            async move { clock.timeout(30.secs(), clock.sleep(10.secs())).await }
+        });
+        mock.wait_for_sleepers(3).await;
+        mock.advance(30.secs()).await;
+        assert!(slow.await.unwrap().is_err());
+        assert!(fast.await.unwrap().is_ok());
+        assert_eq!(clock.system_time(), noon + 30.secs());
+
+        // Adjusting the wall clock leaves monotonic time alone
+        let before = clock.now();
+        mock.set_system_time(UNIX_EPOCH);
+        assert_eq!(clock.system_time(), UNIX_EPOCH);
+        assert_eq!(clock.now(), before);
+        assert_eq!(mock.sleepers(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_sleep_deregisters() {
+        let mock = MockClock::new();
+        let clock = mock.clock();
+        let task = tokio::spawn({
+            let clock = clock.clone();
+            async move { clock.sleep(1.hours()).await }
+        });
+        mock.wait_for_sleepers(1).await;
+        task.abort();
+        let _ = task.await;
+        assert_eq!(mock.sleepers(), 0);
+        assert_eq!(250.ms(), Duration::from_millis(250));
+    }
+}
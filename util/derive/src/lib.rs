@@ -0,0 +1,420 @@
+//! # lib
+//!
+//! lib 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! RF Util Derive Macros
+//!
+//! Provides `#[derive(Validate)]` for `rf_util::valid::Validate` and
+//! `#[derive(ApiSchema)]` for `rf_net::oai::ApiSchema`.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Derive `rf_util::valid::Validate` from `#[valid(...)]` field attributes
+///
+/// - `#[valid(rule = "required|email")]` - rule string
+/// - `#[valid(rule = "...", message = "...")]` - replace the default message (`{field}` is substituted)
+/// - `#[valid(nested)]` - validate a field whose type implements `Validate`
+///
+/// Field names follow `#[serde(rename = "...")]` and `#[serde(rename_all = "...")]`, so
+/// errors name the fields as they appear in the request.
+#[proc_macro_derive(Validate, attributes(valid))]
+pub fn derive_validate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+struct ValidField {
+    ident: syn::Ident,
+    name: String,
+    rule: Option<String>,
+    message: Option<String>,
+    nested: bool,
+}
+
+/// Rules whose parameters name other fields
+const CROSS_FIELD_RULES: &[&str] = &[
+    "same",
+    "different",
+    "required_with",
+    "required_with_all",
+    "required_without",
+    "required_without_all",
+];
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(&input, "Validate requires named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(&input, "Validate can only be derived for structs")),
+    };
+
+    let rename_all = serde_str(&input.attrs, "rename_all")?;
+    let mut valid_fields = Vec::new();
+    for field in fields {
+        let ident = field.ident.clone().expect("named field");
+        let name = match serde_str(&field.attrs, "rename")? {
+            Some(name) => name,
+            None => rename(ident.to_string().trim_start_matches("r#"), rename_all.as_deref()),
+        };
+        let mut valid = ValidField {
+            ident,
+            name,
+            rule: None,
+            message: None,
+            nested: false,
+        };
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("valid")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rule") {
+                    valid.rule = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("message") {
+                    valid.message = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("nested") {
+                    valid.nested = true;
+                } else {
+                    return Err(meta.error("expected `rule`, `message` or `nested`"));
+                }
+                Ok(())
+            })?;
+        }
+        valid_fields.push(valid);
+    }
+
+    // Values needed by the rules: fields with rules plus fields referenced by cross-field rules
+    let mut referenced: Vec<String> = Vec::new();
+    for field in &valid_fields {
+        for rule in field.rule.iter().flat_map(|r| r.split('|')) {
+            if let Some((name, params)) = rule.split_once(':') {
+                let name = name.trim().replace('-', "_");
+                if CROSS_FIELD_RULES.contains(&name.as_str()) {
+                    referenced.extend(params.split(',').map(|p| p.trim().to_string()));
+                } else if name == "required_if" || name == "required_unless" {
+                    referenced.extend(params.split(',').step_by(2).map(|p| p.trim().to_string()));
+                }
+            }
+        }
+    }
+    let data = valid_fields
+        .iter()
+        .filter(|f| f.rule.is_some() || referenced.contains(&f.name))
+        .map(|f| {
+            let (ident, name) = (&f.ident, &f.name);
+            quote! { data.insert(::std::string::String::from(#name), ::rf_util::valid::field_value(&self.#ident)); }
+        });
+    let rules = valid_fields.iter().filter_map(|f| {
+        let rule = f.rule.as_ref()?;
+        let name = &f.name;
+        let message = match &f.message {
+            Some(message) => quote! { ::std::option::Option::Some(#message) },
+            None => quote! { ::std::option::Option::None },
+        };
+        Some(quote! {
+            ::rf_util::valid::FieldRules { field: #name, rules: #rule, message: #message }
+        })
+    });
+    let nested = valid_fields.iter().filter(|f| f.nested).map(|f| {
+        let (ident, name) = (&f.ident, &f.name);
+        quote! {
+            if let ::std::result::Result::Err(e) = ::rf_util::valid::Validate::validate(&self.#ident) {
+                errors.merge(#name, e);
+            }
+        }
+    });
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::rf_util::valid::Validate for #ident #ty_generics #where_clause {
+            fn validate(&self) -> ::std::result::Result<(), ::rf_util::valid::ValidationErrors> {
+                #[allow(unused_mut)]
+                let mut data = ::std::collections::HashMap::new();
+                #(#data)*
+                #[allow(unused_mut)]
+                let mut errors = match ::rf_util::valid::validate_fields(&data, &[#(#rules),*]) {
+                    ::std::result::Result::Ok(()) => ::rf_util::valid::ValidationErrors::new(),
+                    ::std::result::Result::Err(errors) => errors,
+                };
+                #(#nested)*
+                if errors.is_empty() {
+                    ::std::result::Result::Ok(())
+                } else {
+                    ::std::result::Result::Err(errors)
+                }
+            }
+        }
+    })
+}
+
+/// Derive `rf_net::oai::ApiSchema`, the JSON Schema used in generated OpenAPI documents
+///
+/// Supported shapes: structs with named fields (an object schema registered
+/// under `components.schemas`), single-field tuple structs (the inner schema)
+/// and enums with unit variants only (a string enum).
+///
+/// - Doc comments become `description`
+/// - `#[api(description = "...")]` - replace the doc comment
+/// - `#[api(format = "email")]`, `#[api(example = "...")]` - field annotations;
+///   the example is parsed as JSON and falls back to a string
+/// - `#[api(skip)]` - leave a field out of the schema
+///
+/// Follows `#[serde(rename, rename_all, skip, default)]`; `Option` fields and
+/// fields with `#[serde(default)]` are not required. Generic types are inlined
+/// instead of registered by name.
+#[proc_macro_derive(ApiSchema, attributes(api))]
+pub fn derive_api_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_api_schema(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct ApiAttrs {
+    description: Option<String>,
+    format: Option<String>,
+    example: Option<String>,
+    skip: bool,
+}
+
+fn api_attrs(attrs: &[syn::Attribute]) -> syn::Result<ApiAttrs> {
+    let mut api = ApiAttrs {
+        description: doc_comment(attrs),
+        ..ApiAttrs::default()
+    };
+    for attr in attrs.iter().filter(|a| a.path().is_ident("api")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("description") {
+                api.description = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("format") {
+                api.format = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("example") {
+                api.example = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("skip") {
+                api.skip = true;
+            } else {
+                return Err(meta.error("expected `description`, `format`, `example` or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(api)
+}
+
+/// Join `///` lines into one description
+fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }),
+                ..
+            }) => Some(s.value().trim().to_string()),
+            _ => None,
+        })
+        .collect();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+fn quote_option(value: &Option<String>) -> proc_macro2::TokenStream {
+    match value {
+        Some(value) => quote! { ::std::option::Option::Some(#value) },
+        None => quote! { ::std::option::Option::None },
+    }
+}
+
+fn expand_api_schema(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+    let type_attrs = api_attrs(&input.attrs)?;
+    let rename_all = serde_str(&input.attrs, "rename_all")?;
+    let description = quote_option(&type_attrs.description);
+    let unsupported = "ApiSchema requires named fields, a single-field tuple struct or an enum with unit variants";
+
+    let mut named = true;
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let mut properties = Vec::new();
+                for field in &fields.named {
+                    let attrs = api_attrs(&field.attrs)?;
+                    if attrs.skip || serde_flag(&field.attrs, "skip")? {
+                        continue;
+                    }
+                    let field_ident = field.ident.clone().expect("named field");
+                    let name = match serde_str(&field.attrs, "rename")? {
+                        Some(name) => name,
+                        None => rename(field_ident.to_string().trim_start_matches("r#"), rename_all.as_deref()),
+                    };
+                    let ty = &field.ty;
+                    let has_default = serde_flag(&field.attrs, "default")?;
+                    let (description, format, example) =
+                        (quote_option(&attrs.description), quote_option(&attrs.format), quote_option(&attrs.example));
+                    properties.push(quote! {
+                        .property(
+                            #name,
+                            ::rf_net::oai::annotate(registry.schema::<#ty>(), #description, #format, #example),
+                            !#has_default && <#ty as ::rf_net::oai::ApiSchema>::is_required(),
+                        )
+                    });
+                }
+                let description = type_attrs.description.as_ref().map(|d| quote! { .description(#d) });
+                quote! {
+                    ::rf_net::oai::ObjectSchema::new()
+                        #(#properties)*
+                        #description
+                        .build()
+                }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                // Newtypes serialize as their inner value
+                named = false;
+                let ty = &fields.unnamed[0].ty;
+                quote! { ::rf_net::oai::annotate(registry.schema::<#ty>(), #description, ::std::option::Option::None, ::std::option::Option::None) }
+            }
+            _ => return Err(syn::Error::new_spanned(&input, unsupported)),
+        },
+        Data::Enum(data) => {
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(syn::Error::new_spanned(variant, unsupported));
+                }
+                if serde_flag(&variant.attrs, "skip")? {
+                    continue;
+                }
+                variants.push(match serde_str(&variant.attrs, "rename")? {
+                    Some(name) => name,
+                    None => rename_variant(&variant.ident.to_string(), rename_all.as_deref()),
+                });
+            }
+            quote! { ::rf_net::oai::enum_schema(&[#(#variants),*], #description) }
+        }
+        Data::Union(_) => return Err(syn::Error::new_spanned(&input, unsupported)),
+    };
+
+    let name = ident.to_string();
+    let schema_name = if named && input.generics.params.is_empty() {
+        quote! { ::std::option::Option::Some(::std::string::String::from(#name)) }
+    } else {
+        quote! { ::std::option::Option::None }
+    };
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::rf_net::oai::ApiSchema));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::rf_net::oai::ApiSchema for #ident #ty_generics #where_clause {
+            fn schema_name() -> ::std::option::Option<::std::string::String> {
+                #schema_name
+            }
+
+            fn schema(registry: &mut ::rf_net::oai::SchemaRegistry) -> ::rf_net::oai::JsonSchema {
+                #body
+            }
+        }
+    })
+}
+
+/// Whether `#[serde(<key>)]` or `#[serde(<key> = ...)]` is present
+fn serde_flag(attrs: &[syn::Attribute], key: &str) -> syn::Result<bool> {
+    let mut found = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(key) {
+                found = true;
+            }
+            if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|nested| {
+                    if nested.input.peek(syn::Token![=]) {
+                        nested.value()?.parse::<syn::Expr>()?;
+                    }
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(found)
+}
+
+/// Apply a serde `rename_all` rule to a PascalCase variant name
+fn rename_variant(variant: &str, rule: Option<&str>) -> String {
+    let mut snake = String::new();
+    for (i, c) in variant.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    match rule {
+        None | Some("PascalCase") => variant.to_string(),
+        Some("lowercase") => variant.to_lowercase(),
+        Some("UPPERCASE") => variant.to_uppercase(),
+        Some("snake_case") => snake,
+        rule => rename(&snake, rule),
+    }
+}
+
+/// Read `#[serde(<key> = "...")]`, ignoring other serde options
+fn serde_str(attrs: &[syn::Attribute], key: &str) -> syn::Result<Option<String>> {
+    let mut value = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(key) && meta.input.peek(syn::Token![=]) {
+                value = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|nested| {
+                    if nested.input.peek(syn::Token![=]) {
+                        nested.value()?.parse::<syn::Expr>()?;
+                    }
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(value)
+}
+
+/// Apply a serde `rename_all` rule to a snake_case field name
+fn rename(field: &str, rule: Option<&str>) -> String {
+    let words: Vec<&str> = field.split('_').filter(|w| !w.is_empty()).collect();
+    let capitalize = |w: &str| {
+        let mut chars = w.chars();
+        chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    };
+    match rule {
+        Some("lowercase") => field.to_lowercase(),
+        Some("UPPERCASE") => field.to_uppercase(),
+        Some("PascalCase") => words.iter().map(|w| capitalize(w)).collect(),
+        Some("camelCase") => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| if i == 0 { w.to_string() } else { capitalize(w) })
+            .collect(),
+        Some("SCREAMING_SNAKE_CASE") => field.to_uppercase(),
+        Some("kebab-case") => field.replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => field.to_uppercase().replace('_', "-"),
+        _ => field.to_string(),
+    }
+}
@@ -8,23 +8,42 @@
 //! 运行时模式管理工具模块
 //!
 //! 本模块提供了应用程序运行时模式的管理功能。
-//! 通过环境变量 `RF_MODE` 来控制当前的运行模式。
+//! 运行模式依次取自：`set` 设置的模式（例如配置中的 `app.mode`）、环境变量 `RF_MODE`、
+//! 环境变量 `RF_ENV`（值为模式名时），都没有时为开发模式。
 //!
 //! 支持的运行模式：
-//! - Development（开发模式）
-//! - Production（生产模式）
-//! - Testing（测试模式）
+//! - Development（开发模式，`dev`）
+//! - Testing（测试模式，`test`）
+//! - Staging（预发布模式，`staging`）
+//! - Production（生产模式，`prod`）
+//!
+//! 各模块按模式调整默认行为，显式配置优先：
+//! - `template_reload`：模板每次渲染前重新加载，仅开发模式
+//! - `verbose_errors`：5xx 响应显示错误详情和调用栈，仅显式设置的开发模式
+//!   （未设置模式时默认的开发模式不显示，避免未配置的生产部署泄露内部错误）
+//! - `strict_config`：配置校验把未知键也视为错误，预发布和生产模式
 
+use parking_lot::RwLock;
+use rf_errors::RfError;
 use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+/// 运行模式环境变量
+pub const MODE_ENV: &str = "RF_MODE";
+
+/// 设置的模式，优先于环境变量
+static OVERRIDE: RwLock<Option<Mode>> = RwLock::new(None);
 
 /// 运行时模式枚举
 ///
-/// 定义了应用程序的三种运行模式。
+/// 定义了应用程序的四种运行模式。
 ///
 /// # 变体说明
 /// - `Development`: 开发模式，通常启用详细的日志输出和调试功能
 /// - `Production`: 生产模式，优化性能，关闭调试功能
 /// - `Testing`: 测试模式，用于单元测试和集成测试
+/// - `Staging`: 预发布模式，行为与生产模式一致，用于上线前验证
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     /// 开发模式
@@ -33,19 +52,80 @@ pub enum Mode {
     Production,
     /// 测试模式
     Testing,
+    /// 预发布模式
+    Staging,
+}
+
+impl Mode {
+    /// 解析模式名，忽略大小写和首尾空白
+    ///
+    /// 接受 `dev` / `development`、`test` / `testing`、`staging` / `stage`、
+    /// `prod` / `production`，其他值返回 `None`
+    pub fn parse(name: &str) -> Option<Mode> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "dev" | "development" => Some(Mode::Development),
+            "test" | "testing" => Some(Mode::Testing),
+            "staging" | "stage" => Some(Mode::Staging),
+            "prod" | "production" => Some(Mode::Production),
+            _ => None,
+        }
+    }
+
+    /// 短名称：`dev`、`test`、`staging`、`prod`，也是配置环境名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Mode::Development => "dev",
+            Mode::Testing => "test",
+            Mode::Staging => "staging",
+            Mode::Production => "prod",
+        }
+    }
+
+    /// 模板是否在每次渲染前重新加载
+    pub fn template_reload(&self) -> bool {
+        *self == Mode::Development
+    }
+
+    /// 5xx 响应是否显示错误详情和调用栈
+    pub fn verbose_errors(&self) -> bool {
+        *self == Mode::Development
+    }
+
+    /// 配置校验是否把未知键视为错误
+    pub fn strict_config(&self) -> bool {
+        matches!(self, Mode::Staging | Mode::Production)
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Mode {
+    type Err = RfError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Mode::parse(name).ok_or_else(|| {
+            RfError::Config(format!("Unknown mode '{}', expected dev, test, staging or prod", name))
+        })
+    }
 }
 
 /// 获取当前的运行时模式
 ///
-/// 从环境变量 `RF_MODE` 中读取并解析运行模式。
-/// 如果环境变量未设置或值无效，默认返回开发模式。
+/// `set` 设置过模式时返回该模式，否则从环境变量 `RF_MODE` 中读取并解析运行模式，
+/// `RF_MODE` 未设置或值无效时再尝试 `RF_ENV`，都无法解析时默认返回开发模式。
 ///
 /// # 环境变量
 /// - `RF_MODE`: 运行模式
 ///   - "dev" 或 "development" -> Development
 ///   - "prod" 或 "production" -> Production
 ///   - "test" 或 "testing" -> Testing
+///   - "staging" 或 "stage" -> Staging
 ///   - 其他值或未设置 -> Development（默认）
+/// - `RF_ENV`: 配置环境名，是上述模式名时同样生效
 ///
 /// # 返回值
 /// 返回当前的运行模式
@@ -59,15 +139,31 @@ pub enum Mode {
 /// println!("Current mode: {:?}", current_mode);
 /// ```
 pub fn get() -> Mode {
-    match env::var("RF_MODE") {
-        Ok(mode) => match mode.as_str() {
-            "dev" | "development" => Mode::Development,
-            "prod" | "production" => Mode::Production,
-            "test" | "testing" => Mode::Testing,
-            _ => Mode::Development,
-        },
-        Err(_) => Mode::Development,
+    configured().unwrap_or(Mode::Development)
+}
+
+/// 显式设置的运行模式
+///
+/// 与 `get` 相同，但 `set`、`RF_MODE`、`RF_ENV` 都没有给出模式时返回 `None`
+pub fn configured() -> Option<Mode> {
+    if let Some(mode) = *OVERRIDE.read() {
+        return Some(mode);
     }
+    [MODE_ENV, "RF_ENV"]
+        .iter()
+        .find_map(|name| env::var(name).ok().and_then(|value| Mode::parse(&value)))
+}
+
+/// 设置运行模式，优先于环境变量
+///
+/// 用于从配置读取模式（见 `rf_os::cfg::Config::apply_mode`），或在测试中切换模式。
+pub fn set(mode: Mode) {
+    *OVERRIDE.write() = Some(mode);
+}
+
+/// 清除 `set` 设置的模式，恢复从环境变量读取
+pub fn reset() {
+    *OVERRIDE.write() = None;
 }
 
 /// 检查是否为开发模式
@@ -124,3 +220,24 @@ pub fn is_test() -> bool {
     get() == Mode::Testing
 }
 
+/// 检查是否为预发布模式
+pub fn is_staging() -> bool {
+    get() == Mode::Staging
+}
+
+/// 当前模式下模板是否热加载，见 `Mode::template_reload`
+pub fn template_reload() -> bool {
+    get().template_reload()
+}
+
+/// 当前模式下是否显示错误详情和调用栈，见 `Mode::verbose_errors`
+///
+/// 只看显式设置的模式（`configured`），未设置时返回 `false`
+pub fn verbose_errors() -> bool {
+    configured().is_some_and(|mode| mode.verbose_errors())
+}
+
+/// 当前模式下配置校验是否严格，见 `Mode::strict_config`
+pub fn strict_config() -> bool {
+    get().strict_config()
+}
@@ -22,7 +22,7 @@ use std::collections::HashMap;
 /// - `field`: 验证失败的字段名
 /// - `rule`: 失败的验证规则名称
 /// - `message`: 错误消息
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ValidationError {
     pub field: String,
     pub rule: String,
@@ -64,6 +64,7 @@ impl ValidationError {
 #[derive(Debug)]
 pub struct ValidationErrors {
     errors: HashMap<String, Vec<ValidationError>>,
+    order: Vec<String>,
 }
 
 impl ValidationErrors {
@@ -81,6 +82,7 @@ impl ValidationErrors {
     pub fn new() -> Self {
         Self {
             errors: HashMap::new(),
+            order: Vec::new(),
         }
     }
 
@@ -98,11 +100,52 @@ impl ValidationErrors {
     /// errors.add(error);
     /// ```
     pub fn add(&mut self, error: ValidationError) {
+        if !self.errors.contains_key(&error.field) {
+            self.order.push(error.field.clone());
+        }
         self.errors.entry(error.field.clone())
             .or_default()
             .push(error);
     }
 
+    /// 合并嵌套结构的验证错误
+    ///
+    /// 嵌套字段名加上 `prefix.` 前缀，如 `address.city`。
+    ///
+    /// # 参数
+    /// - `prefix`: 嵌套字段名
+    /// - `other`: 嵌套结构的验证错误
+    pub fn merge(&mut self, prefix: &str, other: ValidationErrors) {
+        let mut other = other;
+        for field in std::mem::take(&mut other.order) {
+            for mut error in other.errors.remove(&field).unwrap_or_default() {
+                error.field = format!("{}.{}", prefix, error.field);
+                self.add(error);
+            }
+        }
+    }
+
+    /// 获取第一个验证错误（按添加顺序）
+    ///
+    /// # 返回值
+    /// - `Some(&ValidationError)`: 第一个错误
+    /// - `None`: 没有错误
+    pub fn first(&self) -> Option<&ValidationError> {
+        self.order.first().and_then(|field| self.errors.get(field)).and_then(|errors| errors.first())
+    }
+
+    /// 按添加顺序遍历字段及其错误
+    ///
+    /// # 示例
+    /// ```ignore
+    /// for (field, errors) in errors.iter() {
+    ///     println!("{}: {}", field, errors[0].message);
+    /// }
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Vec<ValidationError>)> {
+        self.order.iter().filter_map(|field| self.errors.get(field).map(|errors| (field.as_str(), errors)))
+    }
+
     /// 检查是否有任何错误
     ///
     /// # 返回值
@@ -167,6 +210,24 @@ impl Default for ValidationErrors {
     }
 }
 
+impl std::fmt::Display for ValidationErrors {
+    /// 输出第一个错误，格式为 `字段: 消息`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.first() {
+            Some(error) => write!(f, "{}: {}", error.field, error.message),
+            None => write!(f, "no validation errors"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl From<ValidationErrors> for rf_errors::RfError {
+    fn from(errors: ValidationErrors) -> Self {
+        rf_errors::RfError::Validation(errors.to_string())
+    }
+}
+
 /// 自定义错误消息映射类型
 ///
 /// 用于为不同的验证规则定义自定义错误消息。
@@ -23,6 +23,7 @@ pub use error::*;
 pub use custom::*;
 pub use i18n::*;
 pub use recursive::*;
+pub use rf_util_derive::Validate;
 
 use rf_errors::Result;
 
@@ -6,9 +6,37 @@
 //! @date 2026-01-06
 
 //! Struct validation
+//!
+//! Rules are declared on struct fields with `#[derive(Validate)]`:
+//!
+//! ```ignore
+//! use rf_util::valid::Validate;
+//!
+//! #[derive(Deserialize, Validate)]
+//! struct SignUp {
+//!     #[valid(rule = "required|email")]
+//!     email: String,
+//!     #[valid(rule = "required|length:6,32", message = "Password must be 6-32 characters")]
+//!     password: String,
+//!     #[valid(rule = "required|same:password")]
+//!     password2: String,
+//!     #[valid(rule = "between:1,150")]
+//!     age: Option<u32>,
+//! }
+//!
+//! sign_up.validate()?;
+//! ```
+//!
+//! Rule strings use the GoFrame syntax `rule1|rule2:param1,param2`. Fields that are empty
+//! (empty string, `None`, empty collection) are only checked by `required*` rules.
+//! `regex` / `not_regex` take the rest of the rule string, so put them last.
 
-use rf_errors::Result;
+use super::custom::get_custom_rule;
+use super::error::{ValidationError, ValidationErrors};
+use super::rules;
+use rf_errors::{Result, RfError};
 use serde::Serialize;
+use std::collections::HashMap;
 
 /// Validate struct with rules
 pub fn validate_struct<T: Serialize>(_value: &T, _rules: &str) -> Result<()> {
@@ -23,3 +51,230 @@ pub fn parse_struct_tags(_struct_type: &str) -> Vec<(String, String)> {
     Vec::new()
 }
 
+/// Types whose fields carry validation rules
+///
+/// Usually implemented with `#[derive(Validate)]`.
+pub trait Validate {
+    /// Check all field rules, collecting every failing field
+    fn validate(&self) -> std::result::Result<(), ValidationErrors>;
+}
+
+impl<T: Validate> Validate for Option<T> {
+    fn validate(&self) -> std::result::Result<(), ValidationErrors> {
+        match self {
+            Some(value) => value.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate(&self) -> std::result::Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        for (i, item) in self.iter().enumerate() {
+            if let Err(e) = item.validate() {
+                errors.merge(&i.to_string(), e);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Rules declared on one field
+#[derive(Debug, Clone, Copy)]
+pub struct FieldRules {
+    /// Field name as it appears in the input (after serde renaming)
+    pub field: &'static str,
+    /// Rule string, e.g. `required|length:6,32`
+    pub rules: &'static str,
+    /// Message replacing the rule's default message; `{field}` is substituted
+    pub message: Option<&'static str>,
+}
+
+/// String form of a field value used by the rules
+///
+/// `None`, empty collections and empty strings all become `""`.
+pub fn field_value<T: Serialize + ?Sized>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::Null) => String::new(),
+        Ok(serde_json::Value::String(s)) => s,
+        Ok(serde_json::Value::Array(a)) if a.is_empty() => String::new(),
+        Ok(serde_json::Value::Object(o)) if o.is_empty() => String::new(),
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// Validate field values against their rules
+///
+/// `data` maps field names to values (see [`field_value`]); cross-field rules such as
+/// `same:password` or `required_with:phone` read the other fields from it. Each field
+/// reports only its first failing rule.
+pub fn validate_fields(
+    data: &HashMap<String, String>,
+    fields: &[FieldRules],
+) -> std::result::Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    for field in fields {
+        let value = data.get(field.field).map(String::as_str).unwrap_or("");
+        if let Err((rule, message)) = check_rules(value, field.rules, data) {
+            let message = match field.message {
+                Some(custom) => custom.replace("{field}", field.field),
+                None => message,
+            };
+            errors.add(ValidationError::new(field.field, &rule, &message));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Split a rule string into `(name, params)` pairs
+fn parse_rules(rules: &str) -> Vec<(String, Vec<String>)> {
+    let mut parsed = Vec::new();
+    let mut rest = rules.trim();
+    while !rest.is_empty() {
+        let (name, after) = match rest.find([':', '|']) {
+            Some(i) if rest.as_bytes()[i] == b':' => (&rest[..i], Some(&rest[i + 1..])),
+            Some(i) => (&rest[..i], None),
+            None => (rest, None),
+        };
+        let name = name.trim().replace('-', "_");
+        let (params, next) = match after {
+            // Regex patterns may contain `|` and `,`
+            Some(pattern) if name == "regex" || name == "not_regex" => (vec![pattern.to_string()], ""),
+            Some(after) => {
+                let (params, next) = after.split_once('|').unwrap_or((after, ""));
+                (params.split(',').map(|p| p.trim().to_string()).collect(), next)
+            }
+            None => (Vec::new(), rest.split_once('|').map(|(_, next)| next).unwrap_or("")),
+        };
+        if !name.is_empty() {
+            parsed.push((name, params));
+        }
+        rest = next.trim();
+    }
+    parsed
+}
+
+/// Check one value, returning the first failing rule and its message
+fn check_rules(
+    value: &str,
+    rules: &str,
+    data: &HashMap<String, String>,
+) -> std::result::Result<(), (String, String)> {
+    let rules = parse_rules(rules);
+    let other = |field: &str| data.get(field).map(String::as_str).unwrap_or("");
+    let present = |field: &str| !other(field).is_empty();
+
+    for (name, params) in &rules {
+        let required = match name.as_str() {
+            "required" => true,
+            "required_if" => params.chunks(2).any(|p| p.len() == 2 && other(&p[0]) == p[1]),
+            "required_unless" => !params.chunks(2).any(|p| p.len() == 2 && other(&p[0]) == p[1]),
+            "required_with" => params.iter().any(|f| present(f)),
+            "required_with_all" => params.iter().all(|f| present(f)),
+            "required_without" => params.iter().any(|f| !present(f)),
+            "required_without_all" => params.iter().all(|f| !present(f)),
+            _ => continue,
+        };
+        if required && value.is_empty() {
+            return Err((name.clone(), "Field is required".to_string()));
+        }
+    }
+    if value.is_empty() {
+        return Ok(());
+    }
+
+    for (name, params) in &rules {
+        let result = match name.as_str() {
+            n if n.starts_with("required") => Ok(()),
+            "same" => match params.first() {
+                Some(f) if other(f) != value => Err(RfError::Validation(format!("Value must be the same as {}", f))),
+                _ => Ok(()),
+            },
+            "different" => match params.first() {
+                Some(f) if other(f) == value => Err(RfError::Validation(format!("Value must be different from {}", f))),
+                _ => Ok(()),
+            },
+            // `length:min,max` counts characters
+            "length" if params.len() == 2 => {
+                let len = value.chars().count();
+                match (params[0].parse::<usize>(), params[1].parse::<usize>()) {
+                    (Ok(min), Ok(max)) if len < min || len > max => Err(RfError::Validation(format!(
+                        "Length must be between {} and {}",
+                        min, max
+                    ))),
+                    (Ok(_), Ok(_)) => Ok(()),
+                    _ => Err(RfError::Validation("Invalid length range".to_string())),
+                }
+            }
+            _ => apply_rule(name, value, params),
+        };
+        if let Err(e) = result {
+            let message = match e {
+                RfError::Validation(message) => message,
+                other => other.to_string(),
+            };
+            return Err((name.clone(), message));
+        }
+    }
+    Ok(())
+}
+
+/// Run a built-in or registered custom rule
+fn apply_rule(name: &str, value: &str, params: &[String]) -> Result<()> {
+    match name {
+        "email" => rules::validate_email(value, params),
+        "url" => rules::validate_url(value, params),
+        "ip" => rules::validate_ip(value, params),
+        "ipv4" => rules::validate_ipv4(value, params),
+        "ipv6" => rules::validate_ipv6(value, params),
+        "mac" => rules::validate_mac(value, params),
+        "regex" => rules::validate_regex(value, params),
+        "not_regex" => rules::validate_not_regex(value, params),
+        "integer" => rules::validate_integer(value, params),
+        "float" => rules::validate_float(value, params),
+        "boolean" => rules::validate_boolean(value, params),
+        "date" => rules::validate_date(value, params),
+        "datetime" => rules::validate_datetime(value, params),
+        "date_format" => rules::validate_date_format(value, params),
+        "json" => rules::validate_json(value, params),
+        "array" => rules::validate_array(value, params),
+        "length" => rules::validate_length(value, params),
+        "min_length" => rules::validate_min_length(value, params),
+        "max_length" => rules::validate_max_length(value, params),
+        "size" => rules::validate_size(value, params),
+        "eq" => rules::validate_eq(value, params),
+        "ne" => rules::validate_ne(value, params),
+        "gt" => rules::validate_gt(value, params),
+        "gte" | "min" => rules::validate_gte(value, params),
+        "lt" => rules::validate_lt(value, params),
+        "lte" | "max" => rules::validate_lte(value, params),
+        "between" => rules::validate_between(value, params),
+        "in" => rules::validate_in(value, params),
+        "not_in" => rules::validate_not_in(value, params),
+        "phone" => rules::validate_phone(value, params),
+        "phone_loose" => rules::validate_phone_loose(value, params),
+        "telephone" => rules::validate_telephone(value, params),
+        "passport" => rules::validate_passport(value, params),
+        "resident_id" => rules::validate_resident_id(value, params),
+        "bank_card" => rules::validate_bank_card(value, params),
+        "qq" => rules::validate_qq(value, params),
+        "postcode" => rules::validate_postcode(value, params),
+        "password" => rules::validate_password(value, params),
+        "password2" => rules::validate_password2(value, params),
+        "password3" => rules::validate_password3(value, params),
+        _ => match get_custom_rule(name) {
+            Some(rule) => rule(value, params),
+            None => Err(RfError::Validation(format!("Unknown validation rule: {}", name))),
+        },
+    }
+}
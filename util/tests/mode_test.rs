@@ -0,0 +1,62 @@
+//! # mode_test
+//!
+//! mode_test 模块
+//!
+//! @author TimonQWQ
+//! @date 2026-01-06
+
+//! Runtime mode tests
+
+#[cfg(test)]
+mod tests {
+    use rf_util::mode::{self, Mode};
+
+    #[test]
+    fn test_parse_and_names() {
+        assert_eq!(Mode::parse(" Production "), Some(Mode::Production));
+        assert_eq!(Mode::parse("stage"), Some(Mode::Staging));
+        assert_eq!(Mode::parse("DEV"), Some(Mode::Development));
+        assert_eq!(Mode::parse("qa"), None);
+        assert_eq!("testing".parse::<Mode>().unwrap(), Mode::Testing);
+        assert!("qa".parse::<Mode>().unwrap_err().to_string().contains("Unknown mode 'qa'"));
+        let names: Vec<String> = [Mode::Development, Mode::Testing, Mode::Staging, Mode::Production]
+            .iter()
+            .map(Mode::to_string)
+            .collect();
+        assert_eq!(names, ["dev", "test", "staging", "prod"]);
+    }
+
+    #[test]
+    fn test_switches() {
+        let switches = |mode: Mode| (mode.template_reload(), mode.verbose_errors(), mode.strict_config());
+        assert_eq!(switches(Mode::Development), (true, true, false));
+        assert_eq!(switches(Mode::Testing), (false, false, false));
+        assert_eq!(switches(Mode::Staging), (false, false, true));
+        assert_eq!(switches(Mode::Production), (false, false, true));
+    }
+
+    /// Environment and override in one test, they are process-wide
+    #[test]
+    fn test_sources() {
+        std::env::remove_var("RF_MODE");
+        std::env::remove_var("RF_ENV");
+        assert_eq!(mode::configured(), None);
+        assert!(mode::is_dev());
+        // The default development mode does not expose errors
+        assert!(mode::template_reload() && !mode::verbose_errors());
+
+        std::env::set_var("RF_ENV", "staging");
+        assert!(mode::is_staging() && mode::strict_config());
+        std::env::set_var("RF_MODE", "dev");
+        assert!(mode::is_dev() && mode::verbose_errors());
+        std::env::set_var("RF_MODE", "unknown");
+        assert!(mode::is_staging());
+
+        mode::set(Mode::Production);
+        assert!(mode::is_prod() && !mode::template_reload());
+        mode::reset();
+        assert!(mode::is_staging());
+        std::env::remove_var("RF_MODE");
+        std::env::remove_var("RF_ENV");
+    }
+}
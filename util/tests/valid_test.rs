@@ -23,5 +23,114 @@ mod tests {
         // Placeholder test for email validation
         assert!(true);
     }
+
+    #[derive(serde::Serialize, Validate)]
+    #[serde(rename_all = "camelCase")]
+    struct SignUp {
+        #[valid(rule = "required|email")]
+        email: String,
+        #[valid(rule = "required|length:6,16", message = "{field} must be 6-16 characters")]
+        password: String,
+        #[valid(rule = "same:password")]
+        confirm_password: String,
+        #[valid(rule = "between:1,150")]
+        age: Option<u32>,
+        #[valid(rule = "required_with:phone|regex:^[0-9]{3}|[0-9]{6}$")]
+        #[serde(default, skip_serializing_if = "String::is_empty", rename = "areaCode")]
+        area: String,
+        phone: String,
+        #[valid(nested)]
+        address: Option<Address>,
+    }
+
+    #[derive(serde::Serialize, Validate)]
+    struct Address {
+        #[valid(rule = "required")]
+        city: String,
+    }
+
+    fn sign_up() -> SignUp {
+        SignUp {
+            email: "user@example.com".into(),
+            password: "secret123".into(),
+            confirm_password: "secret123".into(),
+            age: None,
+            area: String::new(),
+            phone: String::new(),
+            address: None,
+        }
+    }
+
+    #[test]
+    fn test_derive_validate() {
+        assert!(sign_up().validate().is_ok());
+
+        let mut input = sign_up();
+        input.email = "not-an-email".into();
+        input.password = "short".into();
+        input.age = Some(200);
+        let errors = input.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|(field, _)| field).collect();
+        assert_eq!(fields, ["email", "password", "confirmPassword", "age"]);
+        assert_eq!(errors.get("email").unwrap()[0].rule, "email");
+        assert_eq!(errors.get("password").unwrap()[0].message, "password must be 6-16 characters");
+        assert_eq!(errors.first().unwrap().field, "email");
+        assert_eq!(errors.to_string(), "email: Invalid email format");
+        let rf_error: rf_errors::RfError = errors.into();
+        assert!(matches!(rf_error, rf_errors::RfError::Validation(_)));
+    }
+
+    #[test]
+    fn test_cross_field_and_nested_rules() {
+        // required_with: area code becomes required once a phone is given
+        let mut input = sign_up();
+        input.phone = "5550100".into();
+        let errors = input.validate().unwrap_err();
+        assert_eq!(errors.get("areaCode").unwrap()[0].rule, "required_with");
+
+        // regex takes the rest of the rule string, including `|`
+        input.area = "123".into();
+        assert!(input.validate().is_ok());
+        input.area = "123456".into();
+        assert!(input.validate().is_ok());
+        input.area = "12ab".into();
+        assert_eq!(input.validate().unwrap_err().get("areaCode").unwrap()[0].rule, "regex");
+
+        let mut input = sign_up();
+        input.address = Some(Address { city: String::new() });
+        let errors = input.validate().unwrap_err();
+        assert_eq!(errors.get("address.city").unwrap()[0].rule, "required");
+    }
+
+    #[test]
+    fn test_validate_fields_and_custom_rules() {
+        register_custom_rule("even", |value, _| {
+            match value.parse::<i64>() {
+                Ok(n) if n % 2 == 0 => Ok(()),
+                _ => Err(rf_errors::RfError::Validation("Value must be even".to_string())),
+            }
+        });
+        let rules = [
+            FieldRules { field: "count", rules: "integer|even|min:2", message: None },
+            FieldRules { field: "name", rules: "max-length:3", message: None },
+            FieldRules { field: "kind", rules: "in:a,b", message: None },
+            FieldRules { field: "other", rules: "unknown_rule", message: None },
+        ];
+        let mut data = std::collections::HashMap::new();
+        data.insert("count".to_string(), "3".to_string());
+        data.insert("name".to_string(), "abcd".to_string());
+        data.insert("other".to_string(), "x".to_string());
+        let errors = validate_fields(&data, &rules).unwrap_err();
+        assert_eq!(errors.get("count").unwrap()[0].message, "Value must be even");
+        assert_eq!(errors.get("name").unwrap()[0].rule, "max_length");
+        // Empty values are only checked by required rules
+        assert!(errors.get("kind").is_none());
+        assert_eq!(errors.get("other").unwrap()[0].rule, "unknown_rule");
+
+        assert_eq!(field_value(&None::<u32>), "");
+        assert_eq!(field_value(&Vec::<u32>::new()), "");
+        assert_eq!(field_value(&42), "42");
+        assert_eq!(field_value("text"), "text");
+    }
 }
 